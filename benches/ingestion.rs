@@ -0,0 +1,143 @@
+//! Benchmarks of the observation ingest hot path that need no database: request DTOs parsed
+//! and validated, responses serialized, and values interpreted against base lines and derived
+//! rules. Run with `cargo bench --bench ingestion`; compare against a baseline saved on the
+//! previous release with `-- --save-baseline <name>` and `-- --baseline <name>`.
+//!
+//! The end-to-end ingest load scenario, with its throughput target, is `tests/ingest_load.rs`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rme_api_rust::derived;
+use rme_api_rust::dto::observation::{CreateObservationRequest, ObservationResponse, VitalsBundleRequest};
+use rme_api_rust::vitals::VITAL_SIGNS;
+use std::collections::HashMap;
+use validator::Validate;
+
+const CREATE_OBSERVATION: &str = r#"{
+    "value": 36.8,
+    "unit": { "code": "Cel", "display": "°C", "system": "http://unitsofmeasure.org" },
+    "id_pasien": "65a1b2c3d4e5f60718293a4b",
+    "pasien": {
+        "id": "65a1b2c3d4e5f60718293a4b",
+        "nama": { "nama_depan": "Sari", "nama_belakang": "Dewi" },
+        "gender": "female",
+        "nik": "3201010101010001",
+        "lahir": { "tempat": "Bandung", "tanggal": "1990-04-12" },
+        "usia": { "tahun": 35, "bulan": 6, "hari": 3 },
+        "parent": null
+    },
+    "id_petugas": "65a1b2c3d4e5f60718293a4c",
+    "atm_sehat": { "code": "KIT-001", "name": "ATM Sehat Puskesmas", "owner": { "code": "PKM-01", "name": "Puskesmas Cibeunying" } },
+    "time": 1767225600,
+    "coding": { "code": "8310-5", "display": "Body temperature", "system": "http://loinc.org" },
+    "category": { "code": "vital-signs", "display": "Vital Signs", "system": "http://terminology.hl7.org/CodeSystem/observation-category" },
+    "base_line": { "min": 36.1, "max": 37.5 },
+    "interpretation": {
+        "code": "N",
+        "display": "Normal",
+        "system": "http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation",
+        "text": "Body temperature normal"
+    },
+    "log_user_kit_id": null
+}"#;
+
+const VITALS_BUNDLE: &str = r#"{
+    "temperature": 36.8, "systolic": 128, "diastolic": 84, "pulse": 76, "spo2": 98,
+    "weight": 64.5, "height": 158, "time": 1767225600,
+    "atm_sehat": { "code": "KIT-001", "name": "ATM Sehat Puskesmas", "owner": { "code": "PKM-01", "name": "Puskesmas Cibeunying" } }
+}"#;
+
+/// A list page of observations as the API returns them
+fn response_page(size: usize) -> Vec<ObservationResponse> {
+    let mut observation: serde_json::Value = serde_json::from_str(CREATE_OBSERVATION).unwrap();
+    observation["derived"] = false.into();
+    observation["derived_from"] = serde_json::json!([]);
+    observation["created_at"] = "2026-01-01T00:00:00Z".into();
+    observation["updated_at"] = "2026-01-01T00:00:00Z".into();
+    (0..size)
+        .map(|i| {
+            observation["id"] = format!("65a1b2c3d4e5f607{:08x}", i).into();
+            serde_json::from_value(observation.clone()).unwrap()
+        })
+        .collect()
+}
+
+fn dto_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dto");
+
+    group.throughput(Throughput::Bytes(CREATE_OBSERVATION.len() as u64));
+    group.bench_function("create_observation_parse_validate", |b| {
+        b.iter(|| {
+            let request: CreateObservationRequest = serde_json::from_str(black_box(CREATE_OBSERVATION)).unwrap();
+            request.validate().unwrap();
+            request
+        })
+    });
+
+    group.throughput(Throughput::Bytes(VITALS_BUNDLE.len() as u64));
+    group.bench_function("vitals_bundle_parse_validate", |b| {
+        b.iter(|| {
+            let bundle: VitalsBundleRequest = serde_json::from_str(black_box(VITALS_BUNDLE)).unwrap();
+            bundle.validate().unwrap();
+            bundle
+        })
+    });
+
+    let page = response_page(100);
+    group.throughput(Throughput::Elements(page.len() as u64));
+    group.bench_function("observation_page_serialize", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&page)).unwrap())
+    });
+
+    group.finish();
+}
+
+fn interpretation_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interpretation");
+
+    // Below, within and above each sign's base line
+    let readings: Vec<(f64, (f64, f64))> = VITAL_SIGNS.iter()
+        .flat_map(|sign| {
+            let (min, max) = sign.base_line;
+            [min - 1.0, (min + max) / 2.0, max + 1.0].map(|value| (value, sign.base_line))
+        })
+        .collect();
+    group.throughput(Throughput::Elements(readings.len() as u64));
+    group.bench_function("vital_signs_against_base_line", |b| {
+        b.iter(|| {
+            readings.iter()
+                .map(|(value, base_line)| derived::interpret(black_box(*value), *base_line))
+                .filter(|(code, _)| *code != "N")
+                .count()
+        })
+    });
+
+    // Each stored observation looks up the rules it feeds, then computes and interprets them
+    let inputs = [("29463-7", 64.5, "kg"), ("8302-2", 158.0, "cm"), ("8310-5", 36.8, "Cel")];
+    group.throughput(Throughput::Elements(inputs.len() as u64));
+    group.bench_function("derived_rules", |b| {
+        b.iter(|| {
+            let mut results = Vec::new();
+            for (coding_code, _, _) in black_box(&inputs) {
+                for rule in derived::rules_for(coding_code) {
+                    let mut values = HashMap::new();
+                    for (code, value, unit) in &inputs {
+                        if let Some(input) = rule.input_for(code) {
+                            if let Some(normalized) = (input.normalize)(*value, unit) {
+                                values.insert(input.name, normalized);
+                            }
+                        }
+                    }
+                    if let Some(output) = rule.compute(&values) {
+                        results.push((output, rule.interpret(output)));
+                    }
+                }
+            }
+            results
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, dto_benches, interpretation_benches);
+criterion_main!(benches);
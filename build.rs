@@ -0,0 +1,23 @@
+use std::env;
+use std::process::Command;
+
+/// Embed the commit the binary was built from as `GIT_SHA`, for `GET /admin/system-info`.
+/// A `GIT_SHA` set in the build environment (CI without a checkout) takes precedence.
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+
+    let sha = env::var("GIT_SHA").ok().filter(|s| !s.is_empty()).or_else(|| {
+        Command::new("git")
+            .args(["rev-parse", "--short=12", "HEAD"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|sha| sha.trim().to_string())
+    });
+    if let Some(sha) = sha {
+        println!("cargo:rustc-env=GIT_SHA={}", sha);
+    }
+}
@@ -0,0 +1,139 @@
+//! Checking medicines against a patient's recorded allergies.
+//!
+//! An allergy conflicts with a medicine when its substance code equals the medicine's
+//! master catalog entry (`masterMedicineId`), ignoring case. `ALLERGY_CHECK_MODE` decides
+//! what a conflict does: `warn` (default) reports it alongside the result, `block` refuses
+//! with 409 and the conflicts in the error body.
+
+use std::env;
+use serde::Serialize;
+use crate::models::{Allergy, Medicine};
+use crate::status::AllergySeverity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AllergyCheckMode {
+    #[default]
+    Warn,
+    Block,
+}
+
+impl AllergyCheckMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "warn" => Some(Self::Warn),
+            "block" => Some(Self::Block),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        match env::var("ALLERGY_CHECK_MODE") {
+            Ok(raw) => Self::parse(&raw).unwrap_or_else(|| {
+                tracing::warn!(value = %raw, "Ignoring ALLERGY_CHECK_MODE: expected warn or block");
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+/// A medicine matching one of the patient's allergies
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AllergyConflict {
+    pub allergy_id: String,
+    pub substance_code: String,
+    pub substance_display: String,
+    pub severity: AllergySeverity,
+    pub reaction: Option<String>,
+    pub medicine_id: String,
+    pub trade_name: String,
+}
+
+/// Every (allergy, medicine) pair that conflicts, most severe first.
+pub fn find_conflicts(allergies: &[Allergy], medicines: &[Medicine]) -> Vec<AllergyConflict> {
+    let mut conflicts: Vec<AllergyConflict> = medicines
+        .iter()
+        .flat_map(|medicine| {
+            allergies
+                .iter()
+                .filter(|allergy| allergy.substance_code.trim().eq_ignore_ascii_case(medicine.master_medicine_id.trim()))
+                .map(move |allergy| AllergyConflict {
+                    allergy_id: allergy.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    substance_code: allergy.substance_code.clone(),
+                    substance_display: allergy.substance_display.clone(),
+                    severity: allergy.severity.clone(),
+                    reaction: allergy.reaction.clone(),
+                    medicine_id: medicine.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    trade_name: medicine.trade_name.clone(),
+                })
+        })
+        .collect();
+    conflicts.sort_by_key(|conflict| severity_rank(&conflict.severity));
+    conflicts
+}
+
+fn severity_rank(severity: &AllergySeverity) -> u8 {
+    match severity {
+        AllergySeverity::Severe => 0,
+        AllergySeverity::Moderate => 1,
+        AllergySeverity::Mild => 2,
+        AllergySeverity::Other(_) => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{oid::ObjectId, DateTime};
+    use crate::refs::Ref;
+
+    fn allergy(code: &str, severity: AllergySeverity) -> Allergy {
+        Allergy {
+            id: Some(ObjectId::new()),
+            patient_id: Ref::new(ObjectId::new()),
+            substance_code: code.to_string(),
+            substance_display: code.to_string(),
+            severity,
+            reaction: Some("rash".to_string()),
+            recorded_by: "u1".to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        }
+    }
+
+    fn medicine(master: &str) -> Medicine {
+        Medicine {
+            id: Some(ObjectId::new()),
+            master_medicine_id: master.to_string(),
+            batch_number: "B1".to_string(),
+            trade_name: format!("{} 500mg", master),
+            production_date: "2026-01-01".to_string(),
+            expired_date: "2028-01-01".to_string(),
+            purchase_price: 1.0,
+            selling_price: 2.0,
+            qty: 10.0,
+            manufacturer: "Acme".to_string(),
+        }
+    }
+
+    #[test]
+    fn conflicts_match_the_master_catalog_entry() {
+        let allergies = [allergy("KFA-AMOX", AllergySeverity::Mild), allergy("kfa-pcn", AllergySeverity::Severe)];
+        let medicines = [medicine("KFA-PCN"), medicine("KFA-PARA"), medicine(" kfa-amox ")];
+
+        let conflicts = find_conflicts(&allergies, &medicines);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].severity, AllergySeverity::Severe);
+        assert_eq!(conflicts[0].trade_name, "KFA-PCN 500mg");
+        assert_eq!(conflicts[1].substance_code, "KFA-AMOX");
+        assert!(find_conflicts(&[], &medicines).is_empty());
+    }
+
+    #[test]
+    fn mode_defaults_to_warn() {
+        assert_eq!(AllergyCheckMode::parse(" Block "), Some(AllergyCheckMode::Block));
+        assert_eq!(AllergyCheckMode::parse("deny"), None);
+        assert_eq!(AllergyCheckMode::default(), AllergyCheckMode::Warn);
+    }
+}
@@ -0,0 +1,76 @@
+//! Code 128 barcodes for printed labels.
+//!
+//! Only code set B is used: it covers printable ASCII, which is what batch numbers and
+//! specimen IDs are made of. ZPL printers draw the symbol themselves from the data (`^BC`);
+//! `modules` gives the bar pattern for formats that have to draw it, such as PDF.
+
+/// Bar and space widths of each symbol value, starting with a bar
+const PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+    "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+    "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+    "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+    "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+    "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+    "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+    "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+    "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+    "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+const START_B: usize = 104;
+const STOP: usize = 106;
+/// Light modules required on each side of the symbol
+pub const QUIET_ZONE: usize = 10;
+
+/// Symbol values of `data` in code set B, with start, check and stop symbols
+fn symbols(data: &str) -> Result<Vec<usize>, String> {
+    if data.is_empty() {
+        return Err("Barcode data is empty".to_string());
+    }
+    let mut values = vec![START_B];
+    for c in data.chars() {
+        if !(' '..='~').contains(&c) {
+            return Err(format!("'{}' cannot be encoded in a Code 128 barcode", c));
+        }
+        values.push(c as usize - 32);
+    }
+    let check = values.iter().enumerate().map(|(i, v)| v * i.max(1)).sum::<usize>() % 103;
+    values.push(check);
+    values.push(STOP);
+    Ok(values)
+}
+
+/// Dark (`true`) and light modules of the symbol for `data`, without quiet zones
+pub fn modules(data: &str) -> Result<Vec<bool>, String> {
+    let mut modules = Vec::new();
+    for value in symbols(data)? {
+        for (i, width) in PATTERNS[value].bytes().enumerate() {
+            let dark = i % 2 == 0;
+            modules.extend(std::iter::repeat_n(dark, (width - b'0') as usize));
+        }
+    }
+    Ok(modules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_are_eleven_modules_wide() {
+        for (value, pattern) in PATTERNS.iter().enumerate() {
+            let width: u32 = pattern.bytes().map(|w| (w - b'0') as u32).sum();
+            assert_eq!(width, if value == STOP { 13 } else { 11 }, "pattern {}", value);
+        }
+    }
+
+    #[test]
+    fn encodes_with_a_check_symbol() {
+        assert_eq!(symbols("PJJ123C").unwrap(), vec![104, 48, 42, 42, 17, 18, 19, 35, 55, 106]);
+        let bars = modules("PJJ123C").unwrap();
+        assert_eq!(bars.len(), 11 * 9 + 13);
+        assert!(bars[0] && *bars.last().unwrap());
+        assert!(modules("é").is_err());
+    }
+}
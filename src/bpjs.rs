@@ -0,0 +1,463 @@
+//! BPJS Kesehatan VClaim bridging.
+//!
+//! `BPJS_MODE` selects the client: `stub` (default) answers from deterministic test data so
+//! flows can be exercised without credentials, `live` calls VClaim at `BPJS_VCLAIM_URL`
+//! with `BPJS_CONS_ID`, `BPJS_SECRET_KEY` and `BPJS_USER_KEY`. Requests are signed with
+//! `X-signature`, the base64 HMAC-SHA256 of `<cons id>&<timestamp>`. Live mode reads plain
+//! JSON responses; the encrypted and compressed payloads of VClaim 2.0 are not decoded yet.
+//!
+//! Eligibility answers are cached per card and date for `BPJS_CACHE_TTL_SECONDS` (300), in
+//! Redis when replicas share one (see `crate::shared_store`).
+//! Errors keep the BPJS `metaData` code and message, see `BpjsError`.
+
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use hyper::Method;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use crate::http_client::HttpClient;
+use crate::shared_store::SharedStore;
+
+pub const DEFAULT_CACHE_SECONDS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BpjsMode {
+    #[default]
+    Stub,
+    Live,
+}
+
+#[derive(Debug, Clone)]
+pub struct BpjsConfig {
+    pub mode: BpjsMode,
+    pub base_url: Option<String>,
+    pub cons_id: Option<String>,
+    pub secret_key: Option<String>,
+    pub user_key: Option<String>,
+    pub cache_ttl: Duration,
+}
+
+impl Default for BpjsConfig {
+    fn default() -> Self {
+        Self {
+            mode: BpjsMode::Stub,
+            base_url: None,
+            cons_id: None,
+            secret_key: None,
+            user_key: None,
+            cache_ttl: Duration::from_secs(DEFAULT_CACHE_SECONDS),
+        }
+    }
+}
+
+impl BpjsConfig {
+    pub fn from_env() -> Self {
+        let value = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let mode = match value("BPJS_MODE").map(|m| m.to_lowercase()).as_deref() {
+            Some("live") => BpjsMode::Live,
+            Some("stub") | None => BpjsMode::Stub,
+            Some(other) => {
+                tracing::warn!(mode = other, "Unknown BPJS_MODE, using stub");
+                BpjsMode::Stub
+            }
+        };
+        Self {
+            mode,
+            base_url: value("BPJS_VCLAIM_URL").map(|u| u.trim_end_matches('/').to_string()),
+            cons_id: value("BPJS_CONS_ID"),
+            secret_key: value("BPJS_SECRET_KEY"),
+            user_key: value("BPJS_USER_KEY"),
+            cache_ttl: Duration::from_secs(
+                value("BPJS_CACHE_TTL_SECONDS").and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_CACHE_SECONDS),
+            ),
+        }
+    }
+}
+
+/// A failed VClaim call, mapped onto our error responses
+#[derive(Debug, Clone, PartialEq)]
+pub enum BpjsError {
+    /// Live mode without credentials, or the HTTP client could not start
+    NotConfigured(String),
+    /// The request never got an answer
+    Unreachable(String),
+    /// VClaim answered with a non-200 `metaData.code`
+    Rejected { code: String, message: String },
+    InvalidResponse(String),
+    /// The participant exists but cannot be served, e.g. an inactive membership
+    Ineligible(String),
+}
+
+impl BpjsError {
+    /// VClaim reports unknown cards and references as code 201
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::NotConfigured(_) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Rejected { code, .. } if code == "201" => StatusCode::NOT_FOUND,
+            Self::Rejected { .. } | Self::Ineligible(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::Unreachable(_) | Self::InvalidResponse(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotConfigured(_) => "BPJS_NOT_CONFIGURED",
+            Self::Unreachable(_) => "BPJS_UNREACHABLE",
+            Self::Rejected { .. } => "BPJS_REJECTED",
+            Self::InvalidResponse(_) => "BPJS_INVALID_RESPONSE",
+            Self::Ineligible(_) => "BPJS_INELIGIBLE",
+        }
+    }
+
+    /// The BPJS `metaData` of a rejection, for the error body
+    pub fn upstream(&self) -> Option<(&str, &str)> {
+        match self {
+            Self::Rejected { code, message } => Some((code, message)),
+            _ => None,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::NotConfigured(msg) | Self::Unreachable(msg) | Self::InvalidResponse(msg) | Self::Ineligible(msg) => msg.clone(),
+            Self::Rejected { code, message } => format!("BPJS {}: {}", code, message),
+        }
+    }
+}
+
+/// Result of an eligibility check by card number
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Participant {
+    pub card_number: String,
+    pub nik: Option<String>,
+    pub name: String,
+    pub sex: Option<String>,
+    pub birth_date: Option<String>,
+    /// BPJS status text, e.g. `AKTIF`
+    pub status: String,
+    pub active: bool,
+    /// Entitled care class, `1` to `3`
+    pub class: Option<String>,
+    pub participant_type: Option<String>,
+    pub provider_code: Option<String>,
+    pub provider_name: Option<String>,
+}
+
+/// Data for a Surat Eligibilitas Peserta
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SepRequest {
+    pub card_number: String,
+    pub service_date: String,
+    /// `1` inpatient, `2` outpatient, as VClaim codes them
+    pub service_type: String,
+    pub class: String,
+    pub medical_record_number: String,
+    pub diagnosis_code: String,
+    pub poli_code: String,
+    pub referral_number: Option<String>,
+    pub doctor_code: String,
+    pub phone: String,
+    pub user: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sep {
+    pub sep_number: String,
+    pub card_number: String,
+    pub service_date: String,
+    pub service_type: String,
+    pub class: String,
+    pub diagnosis_code: String,
+    pub poli_code: String,
+}
+
+pub trait VClaim: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn participant<'a>(&'a self, card_number: &'a str, date: &'a str) -> BoxFuture<'a, Result<Participant, BpjsError>>;
+    fn create_sep<'a>(&'a self, request: &'a SepRequest) -> BoxFuture<'a, Result<Sep, BpjsError>>;
+}
+
+/// Base64 HMAC-SHA256 of `<cons id>&<timestamp>`, keyed with the secret
+pub fn signature(cons_id: &str, timestamp: i64, secret_key: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}&{}", cons_id, timestamp).as_bytes());
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+/// The `response` of a VClaim envelope, or the error its `metaData` describes
+fn unwrap_envelope(body: &Value) -> Result<&Value, BpjsError> {
+    let meta = body.get("metaData").ok_or_else(|| BpjsError::InvalidResponse("Response has no metaData".to_string()))?;
+    let code = match meta.get("code") {
+        Some(Value::String(code)) => code.clone(),
+        Some(Value::Number(code)) => code.to_string(),
+        _ => return Err(BpjsError::InvalidResponse("metaData has no code".to_string())),
+    };
+    if code != "200" {
+        let message = meta.get("message").and_then(Value::as_str).unwrap_or("").to_string();
+        return Err(BpjsError::Rejected { code, message });
+    }
+    body.get("response").ok_or_else(|| BpjsError::InvalidResponse("Response has no response".to_string()))
+}
+
+fn text(value: &Value, path: &[&str]) -> Option<String> {
+    let mut current = value;
+    for key in path {
+        current = current.get(key)?;
+    }
+    match current {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn participant_from(response: &Value) -> Result<Participant, BpjsError> {
+    let peserta = response.get("peserta").ok_or_else(|| BpjsError::InvalidResponse("Response has no peserta".to_string()))?;
+    let status = text(peserta, &["statusPeserta", "keterangan"]).unwrap_or_default();
+    Ok(Participant {
+        card_number: text(peserta, &["noKartu"]).unwrap_or_default(),
+        nik: text(peserta, &["nik"]),
+        name: text(peserta, &["nama"]).unwrap_or_default(),
+        sex: text(peserta, &["sex"]),
+        birth_date: text(peserta, &["tglLahir"]),
+        active: text(peserta, &["statusPeserta", "kode"]).as_deref() == Some("0") || status.eq_ignore_ascii_case("AKTIF"),
+        status,
+        class: text(peserta, &["hakKelas", "kode"]),
+        participant_type: text(peserta, &["jenisPeserta", "keterangan"]),
+        provider_code: text(peserta, &["provUmum", "kdProvider"]),
+        provider_name: text(peserta, &["provUmum", "nmProvider"]),
+    })
+}
+
+pub struct LiveVClaim {
+    http: HttpClient,
+    base_url: String,
+    cons_id: String,
+    secret_key: String,
+    user_key: String,
+}
+
+impl LiveVClaim {
+    pub fn from_config(config: &BpjsConfig) -> Result<Self, String> {
+        let required = |value: &Option<String>, name: &str| value.clone().ok_or_else(|| format!("{} is not set", name));
+        Ok(Self {
+            http: HttpClient::new()?,
+            base_url: required(&config.base_url, "BPJS_VCLAIM_URL")?,
+            cons_id: required(&config.cons_id, "BPJS_CONS_ID")?,
+            secret_key: required(&config.secret_key, "BPJS_SECRET_KEY")?,
+            user_key: required(&config.user_key, "BPJS_USER_KEY")?,
+        })
+    }
+
+    async fn call(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value, BpjsError> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let timestamp_header = timestamp.to_string();
+        let signature = signature(&self.cons_id, timestamp, &self.secret_key);
+        let headers = [
+            ("x-cons-id", self.cons_id.as_str()),
+            ("x-timestamp", timestamp_header.as_str()),
+            ("x-signature", signature.as_str()),
+            ("user_key", self.user_key.as_str()),
+        ];
+        let url = format!("{}/{}", self.base_url, path);
+        let payload = body.map(|b| b.to_string().into_bytes()).unwrap_or_default();
+        // VClaim expects this content type even for JSON bodies
+        let response = self.http
+            .send(method, &url, &headers, "Application/x-www-form-urlencoded", payload)
+            .await
+            .map_err(BpjsError::Unreachable)?;
+        if !response.is_success() {
+            return Err(BpjsError::Unreachable(format!("VClaim returned HTTP {}: {}", response.status, response.text())));
+        }
+        response.json().map_err(BpjsError::InvalidResponse)
+    }
+}
+
+impl VClaim for LiveVClaim {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    fn participant<'a>(&'a self, card_number: &'a str, date: &'a str) -> BoxFuture<'a, Result<Participant, BpjsError>> {
+        Box::pin(async move {
+            let body = self.call(Method::GET, &format!("Peserta/nokartu/{}/tglSEP/{}", card_number, date), None).await?;
+            participant_from(unwrap_envelope(&body)?)
+        })
+    }
+
+    fn create_sep<'a>(&'a self, request: &'a SepRequest) -> BoxFuture<'a, Result<Sep, BpjsError>> {
+        Box::pin(async move {
+            let body = serde_json::json!({ "request": { "t_sep": {
+                "noKartu": request.card_number,
+                "tglSep": request.service_date,
+                "jnsPelayanan": request.service_type,
+                "klsRawat": { "klsRawatHak": request.class },
+                "noMR": request.medical_record_number,
+                "rujukan": { "noRujukan": request.referral_number.clone().unwrap_or_default() },
+                "diagAwal": request.diagnosis_code,
+                "poli": { "tujuan": request.poli_code },
+                "dpjpLayan": request.doctor_code,
+                "noTelp": request.phone,
+                "user": request.user,
+            }}});
+            let response = self.call(Method::POST, "SEP/2.0/insert", Some(&body)).await?;
+            let response = unwrap_envelope(&response)?;
+            let sep_number = text(response, &["sep", "noSep"])
+                .ok_or_else(|| BpjsError::InvalidResponse("Response has no sep.noSep".to_string()))?;
+            Ok(Sep {
+                sep_number,
+                card_number: request.card_number.clone(),
+                service_date: request.service_date.clone(),
+                service_type: request.service_type.clone(),
+                class: request.class.clone(),
+                diagnosis_code: request.diagnosis_code.clone(),
+                poli_code: request.poli_code.clone(),
+            })
+        })
+    }
+}
+
+/// Test data: every card is an active class 1-3 participant, except cards starting with
+/// `000`, which BPJS does not know.
+pub struct StubVClaim;
+
+impl VClaim for StubVClaim {
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+
+    fn participant<'a>(&'a self, card_number: &'a str, _date: &'a str) -> BoxFuture<'a, Result<Participant, BpjsError>> {
+        Box::pin(async move {
+            if card_number.starts_with("000") {
+                return Err(BpjsError::Rejected { code: "201".to_string(), message: "Peserta tidak ditemukan".to_string() });
+            }
+            let last = card_number.chars().last().and_then(|c| c.to_digit(10)).unwrap_or(0);
+            Ok(Participant {
+                card_number: card_number.to_string(),
+                nik: None,
+                name: format!("PESERTA STUB {}", &card_number[card_number.len().saturating_sub(4)..]),
+                sex: Some(if last.is_multiple_of(2) { "P" } else { "L" }.to_string()),
+                birth_date: None,
+                status: "AKTIF".to_string(),
+                active: true,
+                class: Some((last % 3 + 1).to_string()),
+                participant_type: Some("PEKERJA PENERIMA UPAH".to_string()),
+                provider_code: Some("STUB0001".to_string()),
+                provider_name: Some("FASKES STUB".to_string()),
+            })
+        })
+    }
+
+    fn create_sep<'a>(&'a self, request: &'a SepRequest) -> BoxFuture<'a, Result<Sep, BpjsError>> {
+        Box::pin(async move {
+            let suffix: u32 = rand::thread_rng().gen_range(0..1_000_000);
+            Ok(Sep {
+                sep_number: format!("STUB{}V{:06}", request.service_date.replace('-', ""), suffix),
+                card_number: request.card_number.clone(),
+                service_date: request.service_date.clone(),
+                service_type: request.service_type.clone(),
+                class: request.class.clone(),
+                diagnosis_code: request.diagnosis_code.clone(),
+                poli_code: request.poli_code.clone(),
+            })
+        })
+    }
+}
+
+/// The configured VClaim client with its eligibility cache; shared through `AppState::bpjs`.
+/// The cache lives in `SharedStore`, so replicas sharing Redis ask VClaim once per card and day.
+pub struct BpjsClient {
+    vclaim: Result<Box<dyn VClaim>, String>,
+    ttl: Duration,
+    store: Arc<SharedStore>,
+}
+
+impl BpjsClient {
+    pub fn new(vclaim: Box<dyn VClaim>, ttl: Duration, store: Arc<SharedStore>) -> Self {
+        Self { vclaim: Ok(vclaim), ttl, store }
+    }
+
+    /// Live mode with missing credentials answers every call with `NotConfigured`.
+    pub fn from_config(config: &BpjsConfig, store: Arc<SharedStore>) -> Self {
+        let vclaim = match config.mode {
+            BpjsMode::Stub => Ok(Box::new(StubVClaim) as Box<dyn VClaim>),
+            BpjsMode::Live => LiveVClaim::from_config(config).map(|v| Box::new(v) as Box<dyn VClaim>),
+        };
+        if let Err(e) = &vclaim {
+            tracing::error!(error = %e, "BPJS VClaim unavailable");
+        }
+        Self { vclaim, ttl: config.cache_ttl, store }
+    }
+
+    fn vclaim(&self) -> Result<&dyn VClaim, BpjsError> {
+        self.vclaim.as_deref().map_err(|e| BpjsError::NotConfigured(e.clone()))
+    }
+
+    pub fn mode(&self) -> &'static str {
+        self.vclaim.as_ref().map(|v| v.name()).unwrap_or("unavailable")
+    }
+
+    /// Eligibility on `date`, and whether it came from the cache
+    pub async fn participant(&self, card_number: &str, date: &str) -> Result<(Participant, bool), BpjsError> {
+        let key = format!("bpjs:participant:{}:{}", card_number, date);
+        let cached = self.store.get(&key, Instant::now()).await;
+        if let Some(participant) = cached.and_then(|json| serde_json::from_str::<Participant>(&json).ok()) {
+            return Ok((participant, true));
+        }
+
+        let participant = self.vclaim()?.participant(card_number, date).await?;
+        if !self.ttl.is_zero() {
+            if let Ok(json) = serde_json::to_string(&participant) {
+                self.store.set(&key, &json, self.ttl, Instant::now()).await;
+            }
+        }
+        Ok((participant, false))
+    }
+
+    pub async fn create_sep(&self, request: &SepRequest) -> Result<Sep, BpjsError> {
+        self.vclaim()?.create_sep(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_are_signed_with_hmac_sha256() {
+        assert_eq!(signature("1234", 1_700_000_000, "secret"), "6np+GvYzQ5NMpW0AaYXiFr8TmI4wq6h0Bf+MDiAriiU=");
+    }
+
+    #[test]
+    fn envelopes_map_to_errors() {
+        let not_found = serde_json::json!({ "metaData": { "code": "201", "message": "Peserta tidak ditemukan" }, "response": null });
+        let error = unwrap_envelope(&not_found).unwrap_err();
+        assert_eq!((error.status(), error.code()), (StatusCode::NOT_FOUND, "BPJS_REJECTED"));
+        assert_eq!(error.message(), "BPJS 201: Peserta tidak ditemukan");
+
+        let ok = serde_json::json!({ "metaData": { "code": 200, "message": "OK" }, "response": { "peserta": {
+            "noKartu": "0001234567890", "nama": "BUDI", "statusPeserta": { "kode": "0", "keterangan": "AKTIF" },
+            "hakKelas": { "kode": "2", "keterangan": "KELAS II" }
+        }}});
+        let participant = participant_from(unwrap_envelope(&ok).unwrap()).unwrap();
+        assert!(participant.active);
+        assert_eq!(participant.class.as_deref(), Some("2"));
+    }
+
+    #[tokio::test]
+    async fn eligibility_is_cached_per_card_and_date() {
+        let client = BpjsClient::new(Box::new(StubVClaim), Duration::from_secs(60), Arc::new(SharedStore::in_memory()));
+        let (first, cached) = client.participant("1234567890123", "2026-03-10").await.unwrap();
+        assert!(!cached && first.active);
+        assert!(client.participant("1234567890123", "2026-03-10").await.unwrap().1);
+        assert!(!client.participant("1234567890123", "2026-03-11").await.unwrap().1);
+        assert_eq!(client.participant("0001234567890", "2026-03-10").await.unwrap_err().status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,160 @@
+//! Captcha checks for public endpoints.
+//!
+//! `CAPTCHA_PROVIDER` selects the verifier: `hcaptcha` and `turnstile` (Cloudflare) post the
+//! widget's token to the provider's siteverify endpoint with `CAPTCHA_SECRET`; `off` (default)
+//! accepts any non-empty token, for development without a widget, and is refused with
+//! `APP_ENV=production`. An unknown provider or a missing secret stops startup rather than
+//! accepting every token. Both providers take the same form (`secret`, `response`, `remoteip`)
+//! and answer `{"success": bool, ...}`.
+
+use std::env;
+use futures_util::future::BoxFuture;
+use hyper::Method;
+use serde::Deserialize;
+use crate::http_client::HttpClient;
+
+pub const HCAPTCHA_VERIFY_URL: &str = "https://api.hcaptcha.com/siteverify";
+pub const TURNSTILE_VERIFY_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+
+pub trait CaptchaVerifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Whether the provider accepted `token`; `Err` when it could not be asked
+    fn verify<'a>(&'a self, token: &'a str, remote_ip: Option<&'a str>) -> BoxFuture<'a, Result<bool, String>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptchaConfig {
+    pub provider: String,
+    pub secret: Option<String>,
+    /// `APP_ENV=production`, where `off` is refused
+    pub production: bool,
+}
+
+impl Default for CaptchaConfig {
+    fn default() -> Self {
+        Self { provider: "off".to_string(), secret: None, production: false }
+    }
+}
+
+impl CaptchaConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: env::var("CAPTCHA_PROVIDER").map(|p| p.trim().to_lowercase()).unwrap_or(defaults.provider),
+            secret: env::var("CAPTCHA_SECRET").ok().filter(|v| !v.is_empty()),
+            production: env::var("APP_ENV").is_ok_and(|e| e.trim().eq_ignore_ascii_case("production")),
+        }
+    }
+
+    /// The configured verifier, or why it cannot be used
+    pub fn verifier(&self) -> Result<Box<dyn CaptchaVerifier>, String> {
+        let (name, url) = match self.provider.as_str() {
+            "hcaptcha" => ("hcaptcha", HCAPTCHA_VERIFY_URL),
+            "turnstile" => ("turnstile", TURNSTILE_VERIFY_URL),
+            "off" if self.production => return Err("off accepts any captcha token and is refused with APP_ENV=production".to_string()),
+            "off" => return Ok(Box::new(OffVerifier)),
+            other => return Err(format!("unknown provider '{}'; expected hcaptcha, turnstile or off", other)),
+        };
+        SiteVerifier::new(name, url, self.secret.clone()).map(|verifier| Box::new(verifier) as Box<dyn CaptchaVerifier>)
+    }
+}
+
+/// Stands in for a verifier that could not be built; every check fails rather than passing
+pub struct DisabledVerifier;
+
+impl CaptchaVerifier for DisabledVerifier {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn verify<'a>(&'a self, _token: &'a str, _remote_ip: Option<&'a str>) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async { Err("CAPTCHA_PROVIDER is not usable".to_string()) })
+    }
+}
+
+/// Accepts any non-empty token; for development only.
+pub struct OffVerifier;
+
+impl CaptchaVerifier for OffVerifier {
+    fn name(&self) -> &'static str {
+        "off"
+    }
+
+    fn verify<'a>(&'a self, token: &'a str, _remote_ip: Option<&'a str>) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async move { Ok(!token.trim().is_empty()) })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+/// hCaptcha or Turnstile siteverify
+pub struct SiteVerifier {
+    name: &'static str,
+    http: HttpClient,
+    url: String,
+    secret: String,
+}
+
+impl SiteVerifier {
+    pub fn new(name: &'static str, url: &str, secret: Option<String>) -> Result<Self, String> {
+        Ok(Self {
+            name,
+            http: HttpClient::new()?,
+            url: url.to_string(),
+            secret: secret.ok_or("CAPTCHA_SECRET is not set")?,
+        })
+    }
+}
+
+impl CaptchaVerifier for SiteVerifier {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn verify<'a>(&'a self, token: &'a str, remote_ip: Option<&'a str>) -> BoxFuture<'a, Result<bool, String>> {
+        Box::pin(async move {
+            let body = {
+                let mut form = url::form_urlencoded::Serializer::new(String::new());
+                form.append_pair("secret", &self.secret).append_pair("response", token);
+                if let Some(ip) = remote_ip {
+                    form.append_pair("remoteip", ip);
+                }
+                form.finish()
+            };
+
+            let response = self.http
+                .send(Method::POST, &self.url, &[], "application/x-www-form-urlencoded", body.into_bytes())
+                .await?;
+            if !response.is_success() {
+                return Err(format!("{} returned {}: {}", self.name, response.status, response.text()));
+            }
+            let result: SiteVerifyResponse = response.json()?;
+            // Bad secrets are our problem, not the client's
+            if result.error_codes.iter().any(|c| c.contains("secret")) {
+                return Err(format!("{} rejected the secret: {}", self.name, result.error_codes.join(", ")));
+            }
+            Ok(result.success)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn misconfigured_providers_are_refused() {
+        let name = |config: CaptchaConfig| config.verifier().map(|verifier| verifier.name());
+        assert_eq!(name(CaptchaConfig::default()), Ok("off"));
+        assert!(name(CaptchaConfig { production: true, ..CaptchaConfig::default() }).is_err());
+        assert!(name(CaptchaConfig { provider: "turnstile".to_string(), ..CaptchaConfig::default() }).unwrap_err().contains("CAPTCHA_SECRET"));
+        assert!(name(CaptchaConfig { provider: "recaptcha".to_string(), secret: Some("0x0".to_string()), ..CaptchaConfig::default() }).is_err());
+        let config = CaptchaConfig { provider: "hcaptcha".to_string(), secret: Some("0x0".to_string()), production: true };
+        assert_eq!(name(config), Ok("hcaptcha"));
+    }
+}
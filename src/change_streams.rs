@@ -0,0 +1,299 @@
+//! Domain events for writes made outside the API.
+//!
+//! Scripts and other services write to MongoDB directly, and handlers only publish events
+//! for their own writes. When `CHANGE_STREAM_COLLECTIONS` names collections, `spawn_watcher`
+//! watches them on a change stream (this needs a replica set) and publishes an event with
+//! `EventSource::ChangeStream` for each insert, update, replace or delete the API has not
+//! already published: a change is held for `CHANGE_STREAM_GRACE_MS` (default 2000) and
+//! dropped if the API published an event about the same document meanwhile. Search sync and
+//! denormalization react to these events like to any other; on top of that each external
+//! change is written to the audit log as `external.<kind>`, clears the code or feature flag
+//! cache when it touched them, and queues an outbox message `<collection>.<kind>` when a
+//! webhook is configured.
+//!
+//! The position is kept in `change_stream_state`, so a restart resumes where the watcher
+//! stopped; if the oplog no longer reaches back that far the watcher starts from now and the
+//! gap is logged. Enable it on one instance only, or audit entries and messages are repeated;
+//! the other instances' caches still expire on their own.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, Document},
+    change_stream::event::{ChangeStreamEvent, OperationType, ResumeToken},
+    error::{Error, ErrorKind},
+    options::ChangeStreamOptions,
+    Database,
+};
+use tokio::sync::{broadcast::{self, error::{RecvError, TryRecvError}}, mpsc};
+use crate::db::AppState;
+use crate::events::{DomainEvent, EventKind, EventSource};
+use crate::repository::{AuditLogRepository, ChangeStreamStateRepository, OutboxRepository};
+use crate::services::AuditService;
+
+pub const DEFAULT_GRACE_MS: u64 = 2000;
+/// Audit actor of external changes
+pub const EXTERNAL_ACTOR: &str = "change-stream";
+/// API events older than this no longer match a change
+const RECENT_WINDOW: Duration = Duration::from_secs(60);
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+const QUEUE_CAPACITY: usize = 1024;
+/// `ChangeStreamFatalError` and `ChangeStreamHistoryLost`: the saved position cannot be resumed
+const LOST_POSITION_CODES: [i32; 2] = [280, 286];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeStreamConfig {
+    /// Collections to watch; none disables the watcher
+    pub collections: Vec<String>,
+    /// How long the API has to publish its own event for a change
+    pub grace: Duration,
+}
+
+impl Default for ChangeStreamConfig {
+    fn default() -> Self {
+        Self { collections: Vec::new(), grace: Duration::from_millis(DEFAULT_GRACE_MS) }
+    }
+}
+
+impl ChangeStreamConfig {
+    pub fn from_env() -> Self {
+        let collections = env::var("CHANGE_STREAM_COLLECTIONS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string)
+            .collect();
+        let grace = env::var("CHANGE_STREAM_GRACE_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .map(Duration::from_millis)
+            .unwrap_or(Duration::from_millis(DEFAULT_GRACE_MS));
+        Self { collections, grace }
+    }
+}
+
+/// The event a change of a watched collection stands for; `None` for other operations
+pub fn event_for(change: &ChangeStreamEvent<Document>) -> Option<DomainEvent> {
+    let kind = match change.operation_type {
+        OperationType::Insert => EventKind::Created,
+        OperationType::Update | OperationType::Replace => EventKind::Updated,
+        OperationType::Delete => EventKind::Deleted,
+        _ => return None,
+    };
+    let collection = change.ns.as_ref()?.coll.as_deref()?;
+    let id = match change.document_key.as_ref()?.get("_id")? {
+        Bson::ObjectId(id) => id.to_hex(),
+        Bson::String(id) => id.clone(),
+        other => other.to_string(),
+    };
+
+    let mut event = DomainEvent::new(kind, collection, &id).with_source(EventSource::ChangeStream);
+    if let Some(at) = change.wall_time {
+        event.occurred_at = crate::datetime::to_rfc3339(at);
+    }
+    Some(event)
+}
+
+/// Documents the API published events about lately
+#[derive(Debug, Default)]
+struct RecentWrites {
+    seen: HashMap<(String, String), Vec<Instant>>,
+}
+
+impl RecentWrites {
+    fn record(&mut self, event: &DomainEvent, now: Instant) {
+        if event.source == EventSource::Api {
+            self.seen.entry((event.collection.clone(), event.id.clone())).or_default().push(now);
+        }
+    }
+
+    /// Whether the API published `event` itself; each API event accounts for one change
+    fn take(&mut self, event: &DomainEvent, now: Instant) -> bool {
+        self.seen.retain(|_, times| {
+            times.retain(|at| now.duration_since(*at) < RECENT_WINDOW);
+            !times.is_empty()
+        });
+        let key = (event.collection.clone(), event.id.clone());
+        let Some(times) = self.seen.get_mut(&key) else {
+            return false;
+        };
+        times.remove(0);
+        if times.is_empty() {
+            self.seen.remove(&key);
+        }
+        true
+    }
+}
+
+fn position_lost(error: &Error) -> bool {
+    matches!(error.kind.as_ref(), ErrorKind::Command(e) if LOST_POSITION_CODES.contains(&e.code))
+}
+
+/// A change as it arrived: when, the position after it, and its event
+type Change = (Instant, ResumeToken, Option<DomainEvent>);
+
+/// Reads the change stream into `queue`, reopening it after errors
+async fn watch(db: Database, collections: Vec<String>, positions: Arc<ChangeStreamStateRepository>, queue: mpsc::Sender<Change>) {
+    let mut token = match positions.load_token().await {
+        Ok(token) => token,
+        Err(e) => {
+            tracing::warn!(error = %e, "Loading the change stream position failed, starting from now");
+            None
+        }
+    };
+    let pipeline = [doc! { "$match": {
+        "ns.coll": { "$in": &collections },
+        "operationType": { "$in": ["insert", "update", "replace", "delete"] },
+    } }];
+
+    loop {
+        let options = ChangeStreamOptions::builder().resume_after(token.clone()).build();
+        let mut stream = match db.watch(pipeline.clone(), options).await {
+            Ok(stream) => stream,
+            Err(e) if token.is_some() && position_lost(&e) => {
+                tracing::error!(error = %e, "The change stream can no longer resume, changes since the last position are missed");
+                token = None;
+                if let Err(e) = positions.clear_token().await {
+                    tracing::error!(error = %e, "Clearing the change stream position failed");
+                }
+                continue;
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Opening the change stream failed");
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+
+        loop {
+            match stream.try_next().await {
+                Ok(Some(change)) => {
+                    token = Some(change.id.clone());
+                    if queue.send((Instant::now(), change.id.clone(), event_for(&change))).await.is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!(error = %e, "Change stream failed");
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Publishes the queued changes the API did not publish itself, saving the position as it goes
+async fn publish(
+    state: Arc<AppState>,
+    grace: Duration,
+    positions: Arc<ChangeStreamStateRepository>,
+    mut queue: mpsc::Receiver<Change>,
+    mut api_events: broadcast::Receiver<DomainEvent>,
+) {
+    let mut recent = RecentWrites::default();
+    let mut saved_at: Option<Instant> = None;
+
+    while let Some((received, token, event)) = queue.recv().await {
+        if let Some(event) = event {
+            tokio::time::sleep_until((received + grace).into()).await;
+            loop {
+                match api_events.try_recv() {
+                    Ok(published) => recent.record(&published, Instant::now()),
+                    Err(TryRecvError::Lagged(skipped)) => tracing::warn!(skipped, "Change stream watcher lagged, API events skipped"),
+                    Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                }
+            }
+            if !recent.take(&event, Instant::now()) {
+                state.events.publish(event);
+            }
+        }
+
+        if saved_at.is_none_or(|at| at.elapsed() >= SAVE_INTERVAL) || queue.is_empty() {
+            if let Err(e) = positions.save_token(&token).await {
+                tracing::error!(error = %e, "Saving the change stream position failed");
+            }
+            saved_at = Some(Instant::now());
+        }
+    }
+}
+
+/// What an external change does besides its event: audit, cache invalidation and webhooks
+async fn react(state: &AppState, event: &DomainEvent) {
+    match event.collection.as_str() {
+        "codes" => state.codes.invalidate().await,
+        "feature_flags" => state.feature_flags.invalidate().await,
+        _ => {}
+    }
+
+    let action = format!("external.{}", event.kind.as_str());
+    AuditService::new(AuditLogRepository::new(state.db.clone()))
+        .record(&action, &event.collection, &event.id, EXTERNAL_ACTOR, doc! { "occurred_at": &event.occurred_at })
+        .await;
+
+    if state.config.outbox.webhook_url.is_none() {
+        return;
+    }
+    let Ok(id) = ObjectId::parse_str(&event.id) else { return };
+    let topic = format!("{}.{}", event.collection, event.kind.as_str());
+    let payload = doc! { "source": "change_stream", "occurred_at": &event.occurred_at };
+    if let Err(e) = OutboxRepository::new(state.db.clone()).insert(crate::outbox::entry(&topic, &event.collection, id, payload)).await {
+        tracing::error!(topic, id = %event.id, error = %e, "Queueing an external change failed");
+    }
+}
+
+/// Spawn the change stream watcher and the task reacting to external changes, when
+/// collections are configured
+pub fn spawn_watcher(state: Arc<AppState>) {
+    let config = state.config.change_streams.clone();
+    if config.collections.is_empty() {
+        return;
+    }
+    tracing::info!(collections = %config.collections.join(", "), "Watching for changes made outside the API");
+
+    let positions = Arc::new(ChangeStreamStateRepository::new(state.db.clone()));
+    let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+    // Subscribed before the first change arrives, so no API event is missed
+    let api_events = state.events.subscribe();
+    let mut external = state.events.subscribe();
+
+    tokio::spawn(watch(state.db.clone(), config.collections, positions.clone(), sender));
+    tokio::spawn(publish(state.clone(), config.grace, positions, receiver, api_events));
+    tokio::spawn(async move {
+        loop {
+            match external.recv().await {
+                Ok(event) if event.source == EventSource::ChangeStream => react(&state, &event).await,
+                Ok(_) => {}
+                Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "External change handling lagged, events skipped"),
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn api_events_account_for_one_change_each() {
+        let now = Instant::now();
+        let mut recent = RecentWrites::default();
+        recent.record(&DomainEvent::updated("medicines", "65a1b2c3d4e5f60718293a4b"), now);
+        recent.record(&DomainEvent::updated("codes", "65a1b2c3d4e5f60718293a4c").with_source(EventSource::ChangeStream), now);
+
+        let change = DomainEvent::updated("medicines", "65a1b2c3d4e5f60718293a4b").with_source(EventSource::ChangeStream);
+        assert!(recent.take(&change, now));
+        assert!(!recent.take(&change, now));
+        assert!(!recent.take(&DomainEvent::updated("codes", "65a1b2c3d4e5f60718293a4c"), now));
+
+        recent.record(&DomainEvent::deleted("medicines", "65a1b2c3d4e5f60718293a4b"), now);
+        assert!(!recent.take(&change, now + RECENT_WINDOW));
+    }
+}
@@ -0,0 +1,144 @@
+//! In-memory cache of code lookups.
+//!
+//! `POST /codes/validate` answers from `AppState::codes`: codes are cached by `(system, code)`
+//! for `CODE_CACHE_SECONDS` (default 300), including the pairs that do not exist, and only the
+//! misses go to MongoDB, in one query. Creating, updating or deleting a code through the API
+//! clears the cache on this instance; imports are picked up once entries expire. With Redis
+//! (see `crate::shared_store`) a clear also bumps a shared generation, and every instance
+//! drops its entries on its next lookup after seeing a newer one.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use crate::models::Code;
+use crate::repository::CodeRepository;
+use crate::shared_store::SharedStore;
+
+pub const DEFAULT_CACHE_SECONDS: u64 = 300;
+/// Beyond this many entries expired ones are dropped, and everything if that is not enough
+const MAX_ENTRIES: usize = 100_000;
+const GENERATION_KEY: &str = "codes:generation";
+
+/// `(system, code)`
+pub type CodeKey = (String, String);
+
+pub struct CodeCache {
+    ttl: Duration,
+    entries: RwLock<HashMap<CodeKey, (Instant, Option<Code>)>>,
+    store: Arc<SharedStore>,
+    /// Shared generation the entries belong to
+    generation: AtomicU64,
+}
+
+impl Default for CodeCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_CACHE_SECONDS), Arc::new(SharedStore::in_memory()))
+    }
+}
+
+impl CodeCache {
+    pub fn new(ttl: Duration, store: Arc<SharedStore>) -> Self {
+        Self { ttl, entries: RwLock::new(HashMap::new()), store, generation: AtomicU64::new(0) }
+    }
+
+    pub fn from_env(store: Arc<SharedStore>) -> Self {
+        let seconds = env::var("CODE_CACHE_SECONDS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CACHE_SECONDS);
+        Self::new(Duration::from_secs(seconds), store)
+    }
+
+    /// The code of every key, `None` for unknown pairs
+    pub async fn lookup(&self, repo: &CodeRepository, keys: &[CodeKey]) -> Result<HashMap<CodeKey, Option<Code>>, String> {
+        if self.store.is_shared() {
+            let generation = self.store.counter(GENERATION_KEY).await;
+            if self.generation.swap(generation, Ordering::Relaxed) != generation {
+                self.entries.write().await.clear();
+            }
+        }
+        let (mut found, misses) = self.cached(keys).await;
+        if misses.is_empty() {
+            return Ok(found);
+        }
+
+        let mut loaded: HashMap<CodeKey, Option<Code>> = misses.into_iter().map(|key| (key, None)).collect();
+        for code in repo.find_by_system_code_pairs(&loaded.keys().cloned().collect::<Vec<_>>()).await? {
+            loaded.insert((code.system.clone(), code.code.clone()), Some(code));
+        }
+        self.store(&loaded).await;
+        found.extend(loaded);
+        Ok(found)
+    }
+
+    /// Fresh entries of `keys`, and the distinct keys that need loading
+    async fn cached(&self, keys: &[CodeKey]) -> (HashMap<CodeKey, Option<Code>>, Vec<CodeKey>) {
+        let entries = self.entries.read().await;
+        let mut found = HashMap::new();
+        let mut misses = Vec::new();
+        for key in keys {
+            if found.contains_key(key) || misses.contains(key) {
+                continue;
+            }
+            match entries.get(key) {
+                Some((loaded, code)) if loaded.elapsed() < self.ttl => {
+                    found.insert(key.clone(), code.clone());
+                }
+                _ => misses.push(key.clone()),
+            }
+        }
+        (found, misses)
+    }
+
+    async fn store(&self, loaded: &HashMap<CodeKey, Option<Code>>) {
+        let mut entries = self.entries.write().await;
+        if entries.len() + loaded.len() > MAX_ENTRIES {
+            entries.retain(|_, (at, _)| at.elapsed() < self.ttl);
+            if entries.len() + loaded.len() > MAX_ENTRIES {
+                entries.clear();
+            }
+        }
+        let now = Instant::now();
+        for (key, code) in loaded {
+            entries.insert(key.clone(), (now, code.clone()));
+        }
+    }
+
+    /// Clears the cache here, and on the other instances when they share a store
+    pub async fn invalidate(&self) {
+        self.entries.write().await.clear();
+        if self.store.is_shared() {
+            let generation = self.store.increment(GENERATION_KEY).await;
+            self.generation.store(generation, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: &str) -> CodeKey {
+        ("http://loinc.org".to_string(), code.to_string())
+    }
+
+    #[tokio::test]
+    async fn unknown_pairs_are_cached_until_they_expire() {
+        let cache = CodeCache::default();
+        cache.store(&HashMap::from([(key("8867-4"), None)])).await;
+
+        let (found, misses) = cache.cached(&[key("8867-4"), key("8310-5"), key("8310-5")]).await;
+        assert_eq!(found.get(&key("8867-4")).map(Option::is_none), Some(true));
+        assert_eq!(misses, vec![key("8310-5")]);
+
+        cache.invalidate().await;
+        assert_eq!(cache.cached(&[key("8867-4")]).await.1, vec![key("8867-4")]);
+
+        let expired = CodeCache::new(Duration::ZERO, Arc::new(SharedStore::in_memory()));
+        expired.store(&HashMap::from([(key("8867-4"), None)])).await;
+        assert_eq!(expired.cached(&[key("8867-4")]).await.1, vec![key("8867-4")]);
+    }
+}
@@ -0,0 +1,180 @@
+//! Conditional requests on single resources.
+//!
+//! GETs answer with `Last-Modified` and a matching strong `ETag`, both taken from the
+//! resource's `updated_at` (or, for records never written since, the creation time in its
+//! ObjectId). Writes that send `If-Match` or `If-Unmodified-Since` are refused with 412 when
+//! the resource changed since the client read it, so offline clients notice conflicts
+//! instead of overwriting someone else's edit.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::{appointment::AppointmentResponse, medical_record::MedicalRecordResponse};
+use crate::response::ErrorResponse;
+
+/// HTTP-date (IMF-fixdate), always in GMT
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// When a resource was last written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version(DateTime);
+
+impl Version {
+    /// Version of the resource with this id and RFC 3339 `updated_at`; `None` for a malformed id
+    pub fn of(id: &str, updated_at: Option<&str>) -> Option<Self> {
+        match updated_at.and_then(crate::datetime::parse) {
+            Some(updated_at) => Some(Self(updated_at)),
+            None => ObjectId::parse_str(id).ok().map(|id| Self(id.timestamp())),
+        }
+    }
+
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.0.timestamp_millis())
+    }
+
+    pub fn last_modified(&self) -> String {
+        crate::datetime::to_chrono(self.0).format(HTTP_DATE).to_string()
+    }
+
+    /// `response` with the `ETag` and `Last-Modified` of this version
+    pub fn apply(&self, mut response: Response) -> Response {
+        let headers = response.headers_mut();
+        if let Ok(etag) = HeaderValue::from_str(&self.etag()) {
+            headers.insert(header::ETAG, etag);
+        }
+        if let Ok(modified) = HeaderValue::from_str(&self.last_modified()) {
+            headers.insert(header::LAST_MODIFIED, modified);
+        }
+        response
+    }
+}
+
+/// Responses that carry the version of their resource
+pub trait Versioned {
+    fn version(&self) -> Option<Version>;
+}
+
+impl Versioned for MedicalRecordResponse {
+    fn version(&self) -> Option<Version> {
+        Version::of(&self.id, self.updated_at.as_deref())
+    }
+}
+
+impl Versioned for AppointmentResponse {
+    fn version(&self) -> Option<Version> {
+        Version::of(&self.id, self.updated_at.as_deref())
+    }
+}
+
+/// `response` for `item`, with the validators of its version
+pub fn with_version<T: Versioned>(item: &T, response: Response) -> Response {
+    match item.version() {
+        Some(version) => version.apply(response),
+        None => response,
+    }
+}
+
+/// The `If-Match` and `If-Unmodified-Since` of a write
+#[derive(Debug, Default)]
+pub struct Preconditions {
+    if_match: Option<String>,
+    if_unmodified_since: Option<String>,
+}
+
+impl Preconditions {
+    /// `None` when the request sets neither header
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok()).map(str::to_string);
+        let preconditions = Self {
+            if_match: value(header::IF_MATCH),
+            if_unmodified_since: value(header::IF_UNMODIFIED_SINCE),
+        };
+        (preconditions.if_match.is_some() || preconditions.if_unmodified_since.is_some()).then_some(preconditions)
+    }
+
+    /// Whether the write may go ahead against `current`. `If-Match` takes precedence; an
+    /// unparsable `If-Unmodified-Since` is ignored, as RFC 9110 requires.
+    pub fn allows(&self, current: &Version) -> bool {
+        if let Some(if_match) = &self.if_match {
+            let etag = current.etag();
+            return if_match.split(',').map(str::trim).any(|tag| tag == "*" || tag == etag);
+        }
+        let Some(since) = self.if_unmodified_since.as_deref()
+            .and_then(|since| chrono::DateTime::parse_from_rfc2822(since).ok()) else {
+            return true;
+        };
+        // HTTP dates have whole seconds
+        current.0.timestamp_millis() / 1000 <= since.timestamp()
+    }
+
+    /// 412 unless the write may go ahead
+    pub fn check(&self, current: &Version) -> Result<(), ErrorResponse> {
+        if self.allows(current) {
+            return Ok(());
+        }
+        Err(ErrorResponse::new(
+            StatusCode::PRECONDITION_FAILED,
+            "Resource was modified",
+            "PRECONDITION_FAILED",
+            Some(format!("The resource changed at {}; fetch it again before writing", current.last_modified())),
+        ))
+    }
+
+    /// `check` against the stored resource; a missing one passes, so the write answers 404
+    pub fn check_resource<T: Versioned>(&self, current: Option<&T>) -> Result<(), ErrorResponse> {
+        match current.and_then(Versioned::version) {
+            Some(version) => self.check(&version),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::IntoResponse;
+
+    fn version() -> Version {
+        Version::of("65a1b2c3d4e5f60718293a4b", Some("2026-03-10T08:30:15.250Z")).unwrap()
+    }
+
+    fn preconditions(name: header::HeaderName, value: &str) -> Preconditions {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        Preconditions::from_headers(&headers).unwrap()
+    }
+
+    #[test]
+    fn formats_validators() {
+        let version = version();
+        assert_eq!(version.last_modified(), "Tue, 10 Mar 2026 08:30:15 GMT");
+        assert_eq!(version.etag(), "\"1773131415250\"");
+
+        let id = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        assert_eq!(Version::of(&id.to_hex(), None), Some(Version(id.timestamp())));
+        assert_eq!(Version::of("not-an-id", None), None);
+        assert!(Preconditions::from_headers(&HeaderMap::new()).is_none());
+    }
+
+    #[test]
+    fn if_unmodified_since_compares_whole_seconds() {
+        let version = version();
+        assert!(preconditions(header::IF_UNMODIFIED_SINCE, "Tue, 10 Mar 2026 08:30:15 GMT").allows(&version));
+        assert!(preconditions(header::IF_UNMODIFIED_SINCE, "Wed, 11 Mar 2026 00:00:00 GMT").allows(&version));
+        assert!(!preconditions(header::IF_UNMODIFIED_SINCE, "Tue, 10 Mar 2026 08:30:14 GMT").allows(&version));
+        assert!(preconditions(header::IF_UNMODIFIED_SINCE, "yesterday").allows(&version));
+    }
+
+    #[test]
+    fn if_match_needs_the_current_etag() {
+        let version = version();
+        assert!(preconditions(header::IF_MATCH, "\"1\", \"1773131415250\"").allows(&version));
+        assert!(preconditions(header::IF_MATCH, "*").allows(&version));
+        assert!(!preconditions(header::IF_MATCH, "W/\"1773131415250\"").allows(&version));
+
+        let stale = preconditions(header::IF_MATCH, "\"1\"").check(&version).unwrap_err();
+        assert_eq!(stale.into_response().status(), StatusCode::PRECONDITION_FAILED);
+    }
+}
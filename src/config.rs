@@ -0,0 +1,288 @@
+//! Application configuration read from the environment at startup.
+
+use std::env;
+use std::time::Duration;
+use crate::allergy::AllergyCheckMode;
+use crate::captcha::CaptchaConfig;
+use crate::change_streams::ChangeStreamConfig;
+#[cfg(feature = "billing")]
+use crate::bpjs::BpjsConfig;
+use crate::delete_policy::DeletePolicyConfig;
+use crate::error_reporting::ErrorReportingConfig;
+use crate::links::LinkConfig;
+use crate::load_shed::LoadSheddingConfig;
+use crate::mailer::EmailConfig;
+use crate::otp::OtpConfig;
+use crate::outbox::OutboxConfig;
+#[cfg(feature = "billing")]
+use crate::payment_gateway::PaymentGatewayConfig;
+#[cfg(feature = "s3")]
+use crate::quota::StorageQuotaConfig;
+use crate::repository::slow_query::SlowQueryConfig;
+use crate::request_log::RequestLogConfig;
+use crate::scheduler::SchedulerConfig;
+use crate::self_registration::SelfRegistrationConfig;
+use crate::teleconsult::TeleconsultConfig;
+use crate::timezone::SchedulingConfig;
+
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30_000;
+pub const DEFAULT_HSTS_MAX_AGE: u64 = 31_536_000;
+/// Lets the Swagger UI page load its bundle from unpkg and run its inline bootstrap script
+pub const DEFAULT_DOCS_CSP: &str = "default-src 'self'; script-src 'self' 'unsafe-inline' https://unpkg.com; \
+    style-src 'self' 'unsafe-inline' https://unpkg.com; img-src 'self' data: https:; connect-src 'self'; frame-ancestors 'none'";
+
+#[derive(Debug, Clone, Default)]
+pub struct AppConfig {
+    pub timeouts: TimeoutConfig,
+    pub security_headers: SecurityHeadersConfig,
+    pub teleconsult: TeleconsultConfig,
+    pub otp: OtpConfig,
+    pub email: EmailConfig,
+    pub request_log: RequestLogConfig,
+    pub scheduling: SchedulingConfig,
+    pub delete_policies: DeletePolicyConfig,
+    pub allergy_check: AllergyCheckMode,
+    #[cfg(feature = "billing")]
+    pub payment_gateway: PaymentGatewayConfig,
+    #[cfg(feature = "billing")]
+    pub bpjs: BpjsConfig,
+    pub outbox: OutboxConfig,
+    pub change_streams: ChangeStreamConfig,
+    pub scheduler: SchedulerConfig,
+    pub links: LinkConfig,
+    pub error_reporting: ErrorReportingConfig,
+    pub slow_queries: SlowQueryConfig,
+    pub load_shedding: LoadSheddingConfig,
+    #[cfg(feature = "s3")]
+    pub storage_quotas: StorageQuotaConfig,
+    pub self_registration: SelfRegistrationConfig,
+    pub captcha: CaptchaConfig,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Self {
+        Self {
+            timeouts: TimeoutConfig::from_env(),
+            security_headers: SecurityHeadersConfig::from_env(),
+            teleconsult: TeleconsultConfig::from_env(),
+            otp: OtpConfig::from_env(),
+            email: EmailConfig::from_env(),
+            request_log: RequestLogConfig::from_env(),
+            scheduling: SchedulingConfig::from_env(),
+            delete_policies: DeletePolicyConfig::from_env(),
+            allergy_check: AllergyCheckMode::from_env(),
+            #[cfg(feature = "billing")]
+            payment_gateway: PaymentGatewayConfig::from_env(),
+            #[cfg(feature = "billing")]
+            bpjs: BpjsConfig::from_env(),
+            outbox: OutboxConfig::from_env(),
+            change_streams: ChangeStreamConfig::from_env(),
+            scheduler: SchedulerConfig::from_env(),
+            links: LinkConfig::from_env(),
+            error_reporting: ErrorReportingConfig::from_env(),
+            slow_queries: SlowQueryConfig::from_env(),
+            load_shedding: LoadSheddingConfig::from_env(),
+            #[cfg(feature = "s3")]
+            storage_quotas: StorageQuotaConfig::from_env(),
+            self_registration: SelfRegistrationConfig::from_env(),
+            captcha: CaptchaConfig::from_env(),
+        }
+    }
+}
+
+/// Per-request time budgets.
+///
+/// `REQUEST_TIMEOUT_MS` sets the default; `REQUEST_TIMEOUT_ROUTES` overrides it per path
+/// prefix, e.g. `/search=5000,/admin=120000,/queue/stream=0`. The longest matching prefix
+/// wins and a budget of `0` disables the timeout (for streaming endpoints).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeoutConfig {
+    pub default_ms: u64,
+    pub routes: Vec<(String, u64)>,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            default_ms: DEFAULT_REQUEST_TIMEOUT_MS,
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl TimeoutConfig {
+    pub fn from_env() -> Self {
+        let default_ms = env::var("REQUEST_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_REQUEST_TIMEOUT_MS);
+
+        let routes = match env::var("REQUEST_TIMEOUT_ROUTES") {
+            Ok(raw) => parse_route_budgets(&raw).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Ignoring REQUEST_TIMEOUT_ROUTES");
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        Self { default_ms, routes }
+    }
+
+    /// Budget for a request path; `None` when the timeout is disabled for it.
+    pub fn budget_for(&self, path: &str) -> Option<Duration> {
+        let ms = self.routes
+            .iter()
+            .filter(|(prefix, _)| path_has_prefix(path, prefix))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, ms)| *ms)
+            .unwrap_or(self.default_ms);
+
+        (ms > 0).then(|| Duration::from_millis(ms))
+    }
+}
+
+/// Parse `prefix=ms` pairs separated by commas.
+pub fn parse_route_budgets(raw: &str) -> Result<Vec<(String, u64)>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (prefix, ms) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Expected prefix=ms, got '{}'", entry))?;
+            let prefix = prefix.trim();
+            if !prefix.starts_with('/') {
+                return Err(format!("Route prefix must start with '/': '{}'", prefix));
+            }
+            let ms = ms.trim().parse::<u64>().map_err(|_| format!("Invalid budget for '{}': '{}'", prefix, ms.trim()))?;
+            Ok((prefix.trim_end_matches('/').to_string(), ms))
+        })
+        .collect()
+}
+
+/// Response security headers.
+///
+/// `SECURITY_HEADERS=false` turns the layer off. Strict-Transport-Security is sent when
+/// `SECURITY_HSTS` is true, which defaults to `APP_ENV=production` so local HTTP setups are
+/// not pinned to HTTPS; `SECURITY_HSTS_MAX_AGE` sets its lifetime. `SECURITY_FRAME_OPTIONS`
+/// and `SECURITY_REFERRER_POLICY` override `DENY` and `no-referrer`, and `DOCS_CSP` replaces
+/// the Content-Security-Policy of the `/docs` page (empty to omit it).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityHeadersConfig {
+    pub enabled: bool,
+    pub hsts: bool,
+    pub hsts_max_age: u64,
+    pub frame_options: String,
+    pub referrer_policy: String,
+    pub docs_csp: Option<String>,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hsts: false,
+            hsts_max_age: DEFAULT_HSTS_MAX_AGE,
+            frame_options: "DENY".to_string(),
+            referrer_policy: "no-referrer".to_string(),
+            docs_csp: Some(DEFAULT_DOCS_CSP.to_string()),
+        }
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    env::var(name).ok().and_then(|v| match v.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    })
+}
+
+impl SecurityHeadersConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let production = env::var("APP_ENV").map(|e| e.trim().eq_ignore_ascii_case("production")).unwrap_or(false);
+        let text = |name: &str, default: String| env::var(name).ok().filter(|v| !v.trim().is_empty()).unwrap_or(default);
+
+        Self {
+            enabled: env_flag("SECURITY_HEADERS").unwrap_or(defaults.enabled),
+            hsts: env_flag("SECURITY_HSTS").unwrap_or(production),
+            hsts_max_age: env::var("SECURITY_HSTS_MAX_AGE")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.hsts_max_age),
+            frame_options: text("SECURITY_FRAME_OPTIONS", defaults.frame_options),
+            referrer_policy: text("SECURITY_REFERRER_POLICY", defaults.referrer_policy),
+            docs_csp: match env::var("DOCS_CSP") {
+                Ok(csp) if csp.trim().is_empty() => None,
+                Ok(csp) => Some(csp),
+                Err(_) => defaults.docs_csp,
+            },
+        }
+    }
+
+    /// Headers to add to a response for `path`.
+    pub fn headers_for(&self, path: &str) -> Vec<(&'static str, String)> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        let mut headers = vec![
+            ("x-content-type-options", "nosniff".to_string()),
+            ("x-frame-options", self.frame_options.clone()),
+            ("referrer-policy", self.referrer_policy.clone()),
+        ];
+        if self.hsts {
+            headers.push(("strict-transport-security", format!("max-age={}; includeSubDomains", self.hsts_max_age)));
+        }
+        if let Some(csp) = self.docs_csp.as_ref().filter(|_| path_has_prefix(path, "/docs")) {
+            headers.push(("content-security-policy", csp.clone()));
+        }
+        headers
+    }
+}
+
+/// Whether `path` equals `prefix` or continues it at a segment boundary.
+pub(crate) fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || path == prefix
+        || (path.starts_with(prefix) && path.as_bytes().get(prefix.len()) == Some(&b'/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_route_budgets() {
+        let routes = parse_route_budgets("/search=5000, /admin/=120000,").unwrap();
+        assert_eq!(routes, vec![("/search".to_string(), 5000), ("/admin".to_string(), 120000)]);
+        assert!(parse_route_budgets("search=1").is_err());
+        assert!(parse_route_budgets("/search=fast").is_err());
+    }
+
+    #[test]
+    fn security_headers_add_csp_only_to_docs() {
+        let config = SecurityHeadersConfig::default();
+        let names = |path: &str| config.headers_for(path).into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+
+        assert_eq!(names("/users"), vec!["x-content-type-options", "x-frame-options", "referrer-policy"]);
+        assert!(names("/docs").contains(&"content-security-policy"));
+
+        let production = SecurityHeadersConfig { hsts: true, ..SecurityHeadersConfig::default() };
+        assert!(production.headers_for("/users").contains(&("strict-transport-security", "max-age=31536000; includeSubDomains".to_string())));
+        assert!(SecurityHeadersConfig { enabled: false, ..production }.headers_for("/docs").is_empty());
+    }
+
+    #[test]
+    fn longest_prefix_wins_at_segment_boundaries() {
+        let config = TimeoutConfig {
+            default_ms: 1000,
+            routes: vec![("/codes".to_string(), 2000), ("/codes/import".to_string(), 0)],
+        };
+
+        assert_eq!(config.budget_for("/codes/123"), Some(Duration::from_millis(2000)));
+        assert_eq!(config.budget_for("/codes/import/abc"), None);
+        assert_eq!(config.budget_for("/codesystems"), Some(Duration::from_millis(1000)));
+    }
+}
@@ -0,0 +1,143 @@
+//! Five-field cron expressions: `minute hour day-of-month month day-of-week`.
+//!
+//! Each field takes `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`, or a comma separated
+//! list of these. Days of the week run 0-6 from Sunday, and 7 is Sunday as well. As in
+//! Vixie cron, a day matches when either day field does if both are restricted. Times are
+//! wall-clock times in whatever zone the caller evaluates them in.
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// How far ahead `next_after` looks before giving up, e.g. on `0 0 30 2 *`
+const SEARCH_DAYS: i64 = 366 * 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_field(raw: &str, name: &str, min: u32, max: u32) -> Result<(u64, bool), String> {
+    let invalid = || format!("Invalid {} field '{}'", name, raw);
+    let mut mask = 0u64;
+    for part in raw.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(invalid)?),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (start.parse().map_err(|_| invalid())?, end.parse().map_err(|_| invalid())?)
+        } else {
+            let value: u32 = range.parse().map_err(|_| invalid())?;
+            // `5/15` means from 5 to the end in steps of 15
+            (value, if step > 1 { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("{} values must be between {} and {}, got '{}'", name, min, max, part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok((mask, raw != "*"))
+}
+
+impl CronSchedule {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = raw.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields.as_slice() else {
+            return Err(format!("Cron expression '{}' must have 5 fields: minute hour day month weekday", raw.trim()));
+        };
+        let (minutes, _) = parse_field(minute, "minute", 0, 59)?;
+        let (hours, _) = parse_field(hour, "hour", 0, 23)?;
+        let (days, days_restricted) = parse_field(day, "day-of-month", 1, 31)?;
+        let (months, _) = parse_field(month, "month", 1, 12)?;
+        let (mut weekdays, weekdays_restricted) = parse_field(weekday, "day-of-week", 0, 7)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays |= 1;
+        }
+        Ok(Self { minutes, hours, days, months, weekdays, days_restricted, weekdays_restricted })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        for offset in 0..SEARCH_DAYS {
+            let date = start.date() + Duration::days(offset);
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|h| self.hours & (1 << h) != 0) {
+                for minute in (0..60).filter(|m| self.minutes & (1 << m) != 0) {
+                    let candidate = NaiveDateTime::new(date, NaiveTime::from_hms_opt(hour, minute, 0)?);
+                    if candidate >= start {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(raw: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn finds_the_next_daily_and_weekly_run() {
+        let daily = CronSchedule::parse("0 7 * * *").unwrap();
+        assert_eq!(daily.next_after(at("2026-03-10 06:59")), Some(at("2026-03-10 07:00")));
+        assert_eq!(daily.next_after(at("2026-03-10 07:00")), Some(at("2026-03-11 07:00")));
+
+        // 2026-03-10 is a Tuesday
+        let mondays = CronSchedule::parse("30 8 * * 1").unwrap();
+        assert_eq!(mondays.next_after(at("2026-03-10 09:00")), Some(at("2026-03-16 08:30")));
+        let sundays = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(sundays.next_after(at("2026-03-10 09:00")), Some(at("2026-03-15 00:00")));
+    }
+
+    #[test]
+    fn steps_lists_and_restricted_days() {
+        let quarter = CronSchedule::parse("*/15 9-10 * * *").unwrap();
+        assert_eq!(quarter.next_after(at("2026-03-10 09:20")), Some(at("2026-03-10 09:30")));
+        assert_eq!(quarter.next_after(at("2026-03-10 10:45")), Some(at("2026-03-11 09:00")));
+
+        // The 1st of the month or any Friday
+        let either = CronSchedule::parse("0 6 1 * 5").unwrap();
+        assert_eq!(either.next_after(at("2026-03-10 00:00")), Some(at("2026-03-13 06:00")));
+        assert_eq!(CronSchedule::parse("0 0 30 2 *").unwrap().next_after(at("2026-03-10 00:00")), None);
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert!(CronSchedule::parse("0 7 * *").is_err());
+        assert!(CronSchedule::parse("60 7 * * *").is_err());
+        assert!(CronSchedule::parse("0 7 * * mon").is_err());
+        assert!(CronSchedule::parse("*/0 7 * * *").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Timestamps stored as BSON dates.
+//!
+//! Models keep `created_at`/`updated_at` as `bson::DateTime` with `#[serde(with = "crate::datetime")]`
+//! (or `crate::datetime::optional`): the driver's BSON serializer stores a native date, so MongoDB
+//! sorts and range-filters them, while JSON gets an RFC 3339 string. Reading still accepts the
+//! strings written before `migrations::migrate_timestamps` converted them: RFC 3339, or
+//! `%Y-%m-%d %H:%M:%S` in the server's local time.
+//!
+//! Build documents from models with `bson::to_raw_document_buf`; `bson::to_document` is
+//! human-readable and would store the string form.
+
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use mongodb::bson::{Bson, DateTime};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+pub const LEGACY_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn from_chrono<Tz: TimeZone>(value: chrono::DateTime<Tz>) -> DateTime {
+    DateTime::from_millis(value.timestamp_millis())
+}
+
+pub fn to_chrono(value: DateTime) -> chrono::DateTime<Utc> {
+    chrono::DateTime::from_timestamp_millis(value.timestamp_millis()).unwrap_or_default()
+}
+
+/// RFC 3339 in UTC, the form API responses use
+pub fn to_rfc3339(value: DateTime) -> String {
+    to_chrono(value).to_rfc3339()
+}
+
+pub fn to_rfc3339_opt(value: Option<DateTime>) -> Option<String> {
+    value.map(to_rfc3339)
+}
+
+/// Parse an RFC 3339 timestamp or a legacy local `%Y-%m-%d %H:%M:%S` one.
+pub fn parse(value: &str) -> Option<DateTime> {
+    let value = value.trim();
+    if let Ok(parsed) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(from_chrono(parsed));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, LEGACY_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest().map(from_chrono)
+}
+
+fn from_bson<E: serde::de::Error>(value: Bson) -> Result<Option<DateTime>, E> {
+    match value {
+        Bson::DateTime(value) => Ok(Some(value)),
+        Bson::String(text) if text.is_empty() => Ok(None),
+        Bson::String(text) => parse(&text).map(Some).ok_or_else(|| E::custom(format!("invalid timestamp '{}'", text))),
+        Bson::Null => Ok(None),
+        other => Err(E::custom(format!("expected a timestamp, found {}", other.element_type() as u8))),
+    }
+}
+
+pub fn serialize<S: Serializer>(value: &DateTime, serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&to_rfc3339(*value))
+    } else {
+        value.serialize(serializer)
+    }
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime, D::Error> {
+    from_bson(Bson::deserialize(deserializer)?)?.ok_or_else(|| serde::de::Error::custom("missing timestamp"))
+}
+
+/// The same for `Option<DateTime>` fields; pair it with `default`.
+pub mod optional {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &Option<DateTime>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime>, D::Error> {
+        from_bson(Bson::deserialize(deserializer)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{doc, Bson};
+
+    #[derive(Serialize, Deserialize)]
+    struct Stamped {
+        #[serde(with = "crate::datetime")]
+        created_at: DateTime,
+        #[serde(default, with = "crate::datetime::optional")]
+        updated_at: Option<DateTime>,
+    }
+
+    #[test]
+    fn reads_dates_and_legacy_strings() {
+        let date = DateTime::from_millis(1_773_129_600_000);
+        let native: Stamped = mongodb::bson::from_document(doc! { "created_at": date, "updated_at": date }).unwrap();
+        assert_eq!(native.created_at, date);
+        assert_eq!(native.updated_at, Some(date));
+
+        let legacy: Stamped = mongodb::bson::from_document(doc! { "created_at": "2026-03-10T08:00:00+00:00" }).unwrap();
+        assert_eq!(legacy.created_at, date);
+        assert_eq!(legacy.updated_at, None);
+
+        assert!(mongodb::bson::from_document::<Stamped>(doc! { "created_at": "yesterday" }).is_err());
+    }
+
+    #[test]
+    fn writes_native_dates_and_rfc3339_json() {
+        let date = DateTime::from_millis(1_773_129_600_000);
+        let stamped = Stamped { created_at: date, updated_at: None };
+
+        let stored = mongodb::bson::to_raw_document_buf(&stamped).unwrap().to_document().unwrap();
+        assert_eq!(stored.get("created_at"), Some(&Bson::DateTime(date)));
+        let json = serde_json::to_value(&stamped).unwrap();
+        assert_eq!(json["created_at"], "2026-03-10T08:00:00+00:00");
+    }
+
+    #[test]
+    fn formats_rfc3339_in_utc() {
+        assert_eq!(to_rfc3339(DateTime::from_millis(1_773_129_600_000)), "2026-03-10T08:00:00+00:00");
+        assert!(parse("2026-03-10 08:00:00").is_some());
+    }
+}
@@ -1,23 +1,238 @@
-use mongodb::{Client, options::ClientOptions, Database};
+use mongodb::{
+    Client, Database,
+    event::command::{CommandEventHandler, CommandFailedEvent, CommandStartedEvent, CommandSucceededEvent},
+    options::{ClientOptions, DatabaseOptions, ReadPreference, ReadPreferenceOptions, SelectionCriteria},
+};
 use std::env;
 use std::sync::Arc;
-use aws_sdk_s3::Client as S3Client;
+use crate::config::AppConfig;
+use crate::events::EventBus;
+
+const DATABASE_NAME: &str = "jaga_sehat_indonesia";
+
+/// Which database handle a repository should read through.
+///
+/// Writes always go to `Primary`. Heavy list, stats and export reads use `Replica`, which
+/// prefers secondaries and falls back to the primary when none is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadContext {
+    Primary,
+    Replica,
+}
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Database,
-    pub s3_client: Arc<S3Client>,
+    /// `secondaryPreferred` handle for read-heavy endpoints; see `ReadContext`
+    pub read_db: Database,
+    /// File storage, see `crate::storage`
+    #[cfg(feature = "s3")]
+    pub storage: Arc<crate::storage::Storage>,
+    pub events: EventBus,
+    pub config: Arc<AppConfig>,
+    /// Cached feature flags, see `crate::flags`
+    pub feature_flags: Arc<crate::flags::FlagCache>,
+    /// Code lookups by system and code, see `crate::code_cache`
+    pub codes: Arc<crate::code_cache::CodeCache>,
+    /// Counters shared between replicas, see `crate::shared_store`
+    pub shared: Arc<crate::shared_store::SharedStore>,
+    /// Leader election for scheduled jobs, see `crate::scheduler`
+    pub scheduler: Arc<crate::scheduler::Scheduler>,
+    /// In-flight request pools, see `crate::load_shed`
+    pub load: Arc<crate::load_shed::LoadShedder>,
+    /// Calls to the public registration endpoint per client, see `crate::self_registration`
+    pub registrations: Arc<crate::self_registration::RegistrationLimiter>,
+    /// VClaim client and eligibility cache, see `crate::bpjs`
+    #[cfg(feature = "billing")]
+    pub bpjs: Arc<crate::bpjs::BpjsClient>,
+    #[cfg(feature = "meilisearch")]
+    pub meili: Option<Arc<crate::meilisearch::MeiliClient>>,
+}
+
+impl AppState {
+    pub fn db_for(&self, context: ReadContext) -> Database {
+        match context {
+            ReadContext::Primary => self.db.clone(),
+            ReadContext::Replica => self.read_db.clone(),
+        }
+    }
+}
+
+fn secondary_preferred() -> SelectionCriteria {
+    SelectionCriteria::ReadPreference(ReadPreference::SecondaryPreferred {
+        options: ReadPreferenceOptions::default(),
+    })
+}
+
+/// Passes command events on to each monitor
+struct CommandMonitors(Vec<Arc<dyn CommandEventHandler>>);
+
+impl CommandEventHandler for CommandMonitors {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        self.0.iter().for_each(|monitor| monitor.handle_command_started_event(event.clone()));
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.0.iter().for_each(|monitor| monitor.handle_command_succeeded_event(event.clone()));
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.0.iter().for_each(|monitor| monitor.handle_command_failed_event(event.clone()));
+    }
+}
+
+/// Span every command when traces are exported, and log slow ones
+fn monitor_commands(options: &mut ClientOptions, config: &AppConfig) {
+    let mut monitors: Vec<Arc<dyn CommandEventHandler>> = Vec::new();
+    if crate::telemetry::is_exporting() {
+        monitors.push(Arc::new(crate::telemetry::CommandTracer::default()));
+    }
+    if config.slow_queries.enabled() {
+        monitors.push(Arc::new(crate::repository::slow_query::SlowQueryLog::new(&config.slow_queries)));
+    }
+    options.command_event_handler = match monitors.len() {
+        0 => None,
+        1 => monitors.pop(),
+        _ => Some(Arc::new(CommandMonitors(monitors))),
+    };
+}
+
+/// The application database; mock mode works on a database of its own, see `crate::mock`
+fn database_name() -> String {
+    match crate::mock::enabled() {
+        true => format!("{}{}", DATABASE_NAME, crate::mock::DATABASE_SUFFIX),
+        false => DATABASE_NAME.to_string(),
+    }
+}
+
+/// Read handle for `ReadContext::Replica`: a separate client when `DATABASE_READ_URL` is set,
+/// otherwise the primary client with a `secondaryPreferred` read preference.
+async fn init_read_db(client: &Client, config: &AppConfig) -> Result<Database, Box<dyn std::error::Error>> {
+    let read_options = DatabaseOptions::builder()
+        .selection_criteria(secondary_preferred())
+        .build();
+
+    match env::var("DATABASE_READ_URL") {
+        Ok(uri) if !uri.trim().is_empty() => {
+            let mut options = ClientOptions::parse(uri).await?;
+            monitor_commands(&mut options, config);
+            options.selection_criteria = Some(secondary_preferred());
+            let read_client = Client::with_options(options)?;
+            Ok(read_client.database_with_options(&database_name(), read_options))
+        }
+        _ => Ok(client.database_with_options(&database_name(), read_options)),
+    }
 }
 
 pub async fn init_db() -> Result<Arc<AppState>, Box<dyn std::error::Error>> {
+    let config = Arc::new(AppConfig::from_env());
     let client_uri = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let options = ClientOptions::parse(client_uri).await?;
+    let mut options = ClientOptions::parse(client_uri).await?;
+    monitor_commands(&mut options, &config);
     let client = Client::with_options(options)?;
     
-    let db = client.database("jaga_sehat_indonesia");
+    let db = client.database(&database_name());
+    let read_db = init_read_db(&client, &config).await?;
+    if crate::mock::enabled() {
+        crate::mock::reset(&db).await?;
+    }
+
+    // Index creation is idempotent; indexes it could not create fail the startup check below
+    if let Err(e) = crate::migrations::run(&db).await {
+        tracing::error!(error = %e, "Migration runner failed");
+    }
+    match crate::migrations::migrate_timestamps(&db).await {
+        Ok(0) => {}
+        Ok(converted) => tracing::info!(converted, "Converted string timestamps to dates"),
+        Err(e) => tracing::error!(error = %e, "Timestamp migration failed"),
+    }
+    match crate::migrations::migrate_sync_timestamps(&db).await {
+        Ok(0) => {}
+        Ok(backfilled) => tracing::info!(backfilled, "Backfilled updated_at of reference documents"),
+        Err(e) => tracing::error!(error = %e, "Sync timestamp migration failed"),
+    }
+    match crate::migrations::migrate_phone_numbers(&db).await {
+        Ok(0) => {}
+        Ok(normalized) => tracing::info!(normalized, "Normalized phone numbers to E.164"),
+        Err(e) => tracing::error!(error = %e, "Phone number migration failed"),
+    }
+    match crate::migrations::migrate_practitioners(&db).await {
+        Ok(0) => {}
+        Ok(copied) => tracing::info!(copied, "Copied doctors and nurses into practitioners"),
+        Err(e) => tracing::error!(error = %e, "Practitioner migration failed"),
+    }
+    match crate::migrations::migrate_insurance_versions(&db).await {
+        Ok(0) => {}
+        Ok(versioned) => tracing::info!(versioned, "Backfilled version 1 of insurances"),
+        Err(e) => tracing::error!(error = %e, "Insurance version migration failed"),
+    }
+    match crate::rbac::seed_defaults(&db).await {
+        Ok(true) => tracing::info!("Seeded default role permissions"),
+        Ok(false) => {}
+        Err(e) => tracing::error!(error = %e, "Seeding default role permissions failed"),
+    }
+
+    match crate::migrations::migrate_appointment_instants(&db, &config.scheduling.default_timezone).await {
+        Ok(0) => {}
+        Ok(backfilled) => tracing::info!(backfilled, "Backfilled startsAt of appointments"),
+        Err(e) => tracing::error!(error = %e, "Appointment instant migration failed"),
+    }
+    if crate::mock::enabled() {
+        crate::mock::seed(&db).await?;
+    }
+    // Unlike the checks below these cannot be relaxed: a broken provider would lose or leak
+    // codes, and a broken captcha would let every token through
+    config.otp.provider().map_err(|e| format!("OTP_PROVIDER: {}", e))?;
+    config.captcha.verifier().map_err(|e| format!("CAPTCHA_PROVIDER: {}", e))?;
+    // Missing indexes or broken configuration stop startup unless STARTUP_CHECKS=warn
+    crate::system::startup_check(&db, &config).await?;
+
+    let shared = Arc::new(crate::shared_store::SharedStore::from_env().await);
+    let scheduler = Arc::new(crate::scheduler::Scheduler::new(db.clone(), &config.scheduler));
+    scheduler.start().await;
+    let state = Arc::new(AppState {
+        db,
+        read_db,
+        #[cfg(feature = "s3")]
+        storage: Arc::new(match crate::mock::enabled() {
+            true => crate::storage::Storage::local_from_env(&config.links),
+            false => crate::storage::Storage::from_env(&config.links),
+        }),
+        events: EventBus::new(),
+        #[cfg(feature = "billing")]
+        bpjs: Arc::new(crate::bpjs::BpjsClient::from_config(&config.bpjs, shared.clone())),
+        load: Arc::new(crate::load_shed::LoadShedder::new(&config.load_shedding)),
+        registrations: Arc::new(crate::self_registration::RegistrationLimiter::new(&config.self_registration, shared.clone())),
+        config,
+        feature_flags: Arc::new(crate::flags::FlagCache::from_env()),
+        codes: Arc::new(crate::code_cache::CodeCache::from_env(shared.clone())),
+        shared,
+        scheduler,
+        #[cfg(feature = "meilisearch")]
+        meili: crate::meilisearch::MeiliClient::from_env().map(Arc::new),
+    });
+
+    if let Err(e) = crate::request_log::ensure_collection(&state.db, &state.config.request_log).await {
+        tracing::error!(error = %e, "Creating the request log collection failed");
+    }
+
+    #[cfg(feature = "meilisearch")]
+    crate::meilisearch::spawn_sync_worker(state.clone());
 
-    // Initialize S3 client
-    let s3_client = Arc::new(crate::s3::init_s3_client().await?);
+    #[cfg(feature = "s3")]
+    crate::storage::spawn_warm_up(state.storage.clone());
+    crate::scheduler::spawn_heartbeat(state.scheduler.clone());
+    crate::retention::spawn_scheduler(state.clone());
+    crate::waitlist::spawn_worker(state.clone());
+    crate::role_expiry::spawn_worker(state.clone());
+    crate::no_show::spawn_worker(state.clone());
+    crate::denormalize::spawn_worker(state.clone());
+    crate::outbox::spawn_relay(state.clone());
+    crate::change_streams::spawn_watcher(state.clone());
+    #[cfg(feature = "s3")]
+    crate::reports::spawn_scheduler(state.clone());
+    #[cfg(feature = "s3")]
+    crate::storage_gc::spawn_scheduler(state.clone());
 
-    Ok(Arc::new(AppState { db, s3_client }))
+    Ok(state)
 }
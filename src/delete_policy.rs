@@ -0,0 +1,346 @@
+//! What happens to dependent documents when the document they reference is deleted.
+//!
+//! Each `Relation` names a child collection, the field in it holding the parent's ID and a
+//! `DeletePolicy`:
+//!
+//! - `restrict` refuses the delete with 409 while matching children exist;
+//! - `cascade` soft-deletes the children, setting `deletedAt` (and e.g. a cancelled status);
+//! - `nullify` unsets the reference, keeping the children.
+//!
+//! `DELETE_POLICIES` overrides the defaults of `RELATIONS` by name, e.g.
+//! `doctors.appointments=cascade,medical_records.files=restrict`. A relation only applies
+//! a policy it supports; `nullify` is not offered where the reference is required.
+//!
+//! The checks and child updates run in the transaction the parent is deleted in (see
+//! `DeleteGuard::begin`), so a failed delete leaves the children as they were.
+
+use std::env;
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, Bson, DateTime, Document};
+use mongodb::{ClientSession, Database};
+use crate::db::AppState;
+use crate::status::AppointmentStatus;
+use crate::timezone::ClinicTimezone;
+
+/// Soft-deleted documents carry this field; reads of those collections skip them.
+pub const DELETED_AT: &str = "deletedAt";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeletePolicy {
+    Restrict,
+    Cascade,
+    Nullify,
+}
+
+impl DeletePolicy {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_lowercase().as_str() {
+            "restrict" => Some(Self::Restrict),
+            "cascade" => Some(Self::Cascade),
+            "nullify" => Some(Self::Nullify),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Restrict => "restrict",
+            Self::Cascade => "cascade",
+            Self::Nullify => "nullify",
+        }
+    }
+}
+
+/// Which children of a parent a relation covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    All,
+    /// Appointments from today on that are still open; past visits stay as history
+    Upcoming,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    /// `<parent>.<child>`, as used in `DELETE_POLICIES`
+    pub name: &'static str,
+    pub parent: &'static str,
+    pub child: &'static str,
+    pub field: &'static str,
+    /// What the children are called in a 409
+    pub label: &'static str,
+    pub scope: Scope,
+    pub policy: DeletePolicy,
+    pub allowed: &'static [DeletePolicy],
+    /// Set on cascaded children besides `deletedAt`
+    pub cascade_set: &'static [(&'static str, &'static str)],
+}
+
+const CANCELLED: &[(&str, &str)] = &[("status", "cancelled")];
+const RECORD_DELETED: &[(&str, &str)] = &[("deleteReason", "medical record deleted")];
+
+pub const RELATIONS: &[Relation] = &[
+    Relation {
+        name: "doctors.appointments",
+        parent: "doctors",
+        child: "appointments",
+        field: "doctorId",
+        label: "upcoming appointment(s)",
+        scope: Scope::Upcoming,
+        policy: DeletePolicy::Restrict,
+        allowed: &[DeletePolicy::Restrict, DeletePolicy::Cascade],
+        cascade_set: CANCELLED,
+    },
+    Relation {
+        name: "medical_records.appointments",
+        parent: "medical_records",
+        child: "appointments",
+        field: "patientId",
+        label: "upcoming appointment(s)",
+        scope: Scope::Upcoming,
+        policy: DeletePolicy::Cascade,
+        allowed: &[DeletePolicy::Restrict, DeletePolicy::Cascade],
+        cascade_set: CANCELLED,
+    },
+    Relation {
+        name: "medical_records.observations",
+        parent: "medical_records",
+        child: "observations",
+        field: "id_pasien",
+        label: "observation(s)",
+        scope: Scope::All,
+        policy: DeletePolicy::Restrict,
+        // Observation reads do not skip soft-deleted documents, so these are never cascaded
+        allowed: &[DeletePolicy::Restrict],
+        cascade_set: &[],
+    },
+    Relation {
+        name: "medical_records.files",
+        parent: "medical_records",
+        child: "files",
+        field: "medicalRecordId",
+        label: "file(s)",
+        scope: Scope::All,
+        policy: DeletePolicy::Cascade,
+        allowed: &[DeletePolicy::Restrict, DeletePolicy::Cascade, DeletePolicy::Nullify],
+        cascade_set: &[],
+    },
+    Relation {
+        name: "medical_records.notes",
+        parent: "medical_records",
+        child: "notes",
+        field: "medicalRecordId",
+        label: "note(s)",
+        scope: Scope::All,
+        policy: DeletePolicy::Cascade,
+        // Notes are never removed or detached, only soft-deleted
+        allowed: &[DeletePolicy::Restrict, DeletePolicy::Cascade],
+        cascade_set: RECORD_DELETED,
+    },
+    Relation {
+        name: "wards.beds",
+        parent: "wards",
+        child: "beds",
+        field: "wardId",
+        label: "bed(s)",
+        scope: Scope::All,
+        policy: DeletePolicy::Restrict,
+        // Beds are not soft-deleted; they are removed one by one once free
+        allowed: &[DeletePolicy::Restrict],
+        cascade_set: &[],
+    },
+    Relation {
+        name: "organizations.appointments",
+        parent: "organizations",
+        child: "appointments",
+        field: "organizationId",
+        label: "appointment(s)",
+        scope: Scope::All,
+        policy: DeletePolicy::Nullify,
+        allowed: &[DeletePolicy::Restrict, DeletePolicy::Cascade, DeletePolicy::Nullify],
+        cascade_set: CANCELLED,
+    },
+];
+
+impl Relation {
+    /// Live children of `parent` within the relation's scope. References are stored as hex.
+    pub fn filter(&self, parent: ObjectId, today: &str) -> Document {
+        let mut filter = doc! { self.field: parent.to_hex(), DELETED_AT: Bson::Null };
+        if self.scope == Scope::Upcoming {
+            filter.insert("date", doc! { "$gte": today });
+            filter.insert("status", doc! { "$nin": [AppointmentStatus::Completed, AppointmentStatus::Cancelled, AppointmentStatus::NoShow] });
+        }
+        filter
+    }
+
+    /// The update applied to the children; `None` for `restrict`.
+    pub fn update(&self, now: DateTime) -> Option<Document> {
+        match self.policy {
+            DeletePolicy::Restrict => None,
+            DeletePolicy::Cascade => {
+                let mut set = doc! { DELETED_AT: now };
+                for (key, value) in self.cascade_set {
+                    set.insert(*key, *value);
+                }
+                Some(doc! { "$set": set })
+            }
+            DeletePolicy::Nullify => Some(doc! { "$unset": { self.field: "" } }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletePolicyConfig {
+    pub relations: Vec<Relation>,
+}
+
+impl Default for DeletePolicyConfig {
+    fn default() -> Self {
+        Self { relations: RELATIONS.to_vec() }
+    }
+}
+
+impl DeletePolicyConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(raw) = env::var("DELETE_POLICIES") {
+            if let Err(e) = config.apply_overrides(&raw) {
+                tracing::warn!(error = %e, "Ignoring DELETE_POLICIES");
+                config = Self::default();
+            }
+        }
+        config
+    }
+
+    /// Apply `name=policy` pairs; fails on unknown relations and unsupported policies.
+    pub fn apply_overrides(&mut self, raw: &str) -> Result<(), String> {
+        for entry in raw.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (name, policy) = entry.split_once('=').ok_or_else(|| format!("Expected relation=policy, got '{}'", entry))?;
+            let relation = self.relations.iter_mut()
+                .find(|relation| relation.name == name.trim())
+                .ok_or_else(|| format!("Unknown relation '{}'", name.trim()))?;
+            let policy = DeletePolicy::parse(policy).ok_or_else(|| format!("Unknown policy '{}' for {}", policy.trim(), relation.name))?;
+            if !relation.allowed.contains(&policy) {
+                return Err(format!("{} does not support {}", relation.name, policy.as_str()));
+            }
+            relation.policy = policy;
+        }
+        Ok(())
+    }
+
+    pub fn for_parent<'a>(&'a self, parent: &'a str) -> impl Iterator<Item = &'a Relation> + 'a {
+        self.relations.iter().filter(move |relation| relation.parent == parent)
+    }
+}
+
+/// Enforces the configured policies before a parent is deleted.
+pub struct DeleteGuard {
+    db: Database,
+    config: DeletePolicyConfig,
+    timezone: ClinicTimezone,
+}
+
+impl DeleteGuard {
+    pub fn new(db: Database, config: DeletePolicyConfig, timezone: ClinicTimezone) -> Self {
+        Self { db, config, timezone }
+    }
+
+    pub fn from_state(state: &AppState) -> Self {
+        Self::new(state.db.clone(), state.config.delete_policies.clone(), state.config.scheduling.default_timezone.clone())
+    }
+
+    /// A session with a transaction started, to run `prepare` and the parent's delete in.
+    /// Needs a replica set.
+    pub async fn begin(&self) -> Result<ClientSession, (StatusCode, String)> {
+        let internal = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+        // Any collection reaches the client
+        let mut session = self.db.collection::<Document>("medical_records").client().start_session(None).await.map_err(internal)?;
+        session.start_transaction(None).await.map_err(internal)?;
+        Ok(session)
+    }
+
+    /// Refuse with 409 when a `restrict` relation has children, then cascade or nullify
+    /// the others, within `session`'s transaction. Delete the parent itself in the same one.
+    pub async fn prepare(&self, session: &mut ClientSession, parent: &str, id: ObjectId) -> Result<(), (StatusCode, String)> {
+        let today = self.timezone.today(Utc::now());
+        let internal = |e: mongodb::error::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+
+        let mut blockers = Vec::new();
+        for relation in self.config.for_parent(parent).filter(|r| r.policy == DeletePolicy::Restrict) {
+            let count = self.db.collection::<Document>(relation.child)
+                .count_documents_with_session(relation.filter(id, &today), None, session)
+                .await
+                .map_err(internal)?;
+            if count > 0 {
+                blockers.push(format!("{} {}", count, relation.label));
+            }
+        }
+        if !blockers.is_empty() {
+            return Err((StatusCode::CONFLICT, format!("Cannot delete: still referenced by {}", blockers.join(", "))));
+        }
+
+        let now = DateTime::now();
+        for relation in self.config.for_parent(parent) {
+            if let Some(update) = relation.update(now) {
+                self.db.collection::<Document>(relation.child)
+                    .update_many_with_session(relation.filter(id, &today), update, None, session)
+                    .await
+                    .map_err(internal)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn relation(name: &str) -> Relation {
+        RELATIONS.iter().find(|r| r.name == name).unwrap().clone()
+    }
+
+    #[test]
+    fn restrict_counts_only_upcoming_open_appointments() {
+        let doctors = relation("doctors.appointments");
+        assert_eq!(doctors.policy, DeletePolicy::Restrict);
+        assert_eq!(doctors.update(DateTime::now()), None);
+
+        let id = ObjectId::new();
+        let filter = doctors.filter(id, "2026-03-10");
+        assert_eq!(filter.get_str("doctorId").unwrap(), id.to_hex());
+        assert_eq!(filter.get_document("date").unwrap(), &doc! { "$gte": "2026-03-10" });
+        assert!(filter.get_document("status").is_ok());
+        assert_eq!(filter.get(DELETED_AT), Some(&Bson::Null));
+    }
+
+    #[test]
+    fn cascade_soft_deletes_children() {
+        let now = DateTime::now();
+        let files = relation("medical_records.files");
+        assert_eq!(files.update(now), Some(doc! { "$set": { DELETED_AT: now } }));
+        assert!(!files.filter(ObjectId::new(), "2026-03-10").contains_key("date"));
+
+        let appointments = relation("medical_records.appointments");
+        assert_eq!(appointments.update(now), Some(doc! { "$set": { DELETED_AT: now, "status": "cancelled" } }));
+    }
+
+    #[test]
+    fn nullify_unsets_the_reference() {
+        let organizations = relation("organizations.appointments");
+        assert_eq!(organizations.update(DateTime::now()), Some(doc! { "$unset": { "organizationId": "" } }));
+    }
+
+    #[test]
+    fn overrides_change_supported_policies_only() {
+        let mut config = DeletePolicyConfig::default();
+        config.apply_overrides("doctors.appointments=cascade, medical_records.files=restrict").unwrap();
+        let policy = |name: &str| config.relations.iter().find(|r| r.name == name).unwrap().policy;
+        assert_eq!(policy("doctors.appointments"), DeletePolicy::Cascade);
+        assert_eq!(policy("medical_records.files"), DeletePolicy::Restrict);
+
+        assert!(DeletePolicyConfig::default().apply_overrides("doctors.appointments=nullify").is_err());
+        assert!(DeletePolicyConfig::default().apply_overrides("nurses.appointments=cascade").is_err());
+        assert_eq!(DeletePolicyConfig::default().for_parent("medical_records").count(), 4);
+    }
+}
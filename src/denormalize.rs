@@ -0,0 +1,174 @@
+//! Refreshing the copies of names and contacts embedded in other documents.
+//!
+//! `user_roles.user` copies a user's name, email and phone, and `observations.pasien` copies
+//! the patient's name from their medical record; both go stale when the source is edited.
+//! `spawn_worker` refreshes the copies of one source whenever a `users` or `medical_records`
+//! update event is published, and runs a full pass over every embedded source at
+//! `DENORMALIZE_RUN_HOUR` (local, default 4) to catch writes that published no event.
+//! Only documents whose copy differs are written. `GET /admin/denormalization/report` runs
+//! the full pass as a dry run and reports what it would change.
+
+use std::env;
+use std::sync::Arc;
+use chrono::Local;
+use mongodb::bson::{doc, Document};
+use tokio::sync::broadcast::error::RecvError;
+use crate::db::AppState;
+use crate::events::{DomainEvent, EventKind};
+use crate::models::{MedicalRecord, User};
+use crate::repository::{DenormalizationRepository, MedicalRecordRepository, UserRepository};
+use crate::retention::delay_until_next_run;
+use crate::services::DenormalizationService;
+
+pub const DEFAULT_RUN_HOUR: u32 = 4;
+pub const USER_ROLES: &str = "user_roles";
+pub const OBSERVATIONS: &str = "observations";
+
+/// Embedded copies of one source document and the fields to bring up to date
+#[derive(Debug, Clone, PartialEq)]
+pub struct Refresh {
+    pub collection: &'static str,
+    /// Documents holding a stale copy
+    pub filter: Document,
+    pub set: Document,
+}
+
+/// `(first, rest)` of a full name, as names are split when embedded
+pub fn split_name(name: &str) -> (String, String) {
+    let name = name.trim();
+    let (first, rest) = name.split_once(' ').unwrap_or((name, ""));
+    (first.to_string(), rest.trim().to_string())
+}
+
+/// Filter matching `source` documents in which any of `set` differs
+fn stale(source: Document, set: &Document) -> Document {
+    let differs: Vec<Document> = set.iter().map(|(field, value)| doc! { field: { "$ne": value.clone() } }).collect();
+    let mut filter = source;
+    filter.insert("$or", differs);
+    filter
+}
+
+/// The user's copy in their role assignments
+pub fn user_roles_refresh(user: &User) -> Option<Refresh> {
+    let id = user.id?.to_hex();
+    let (nama_depan, nama_belakang) = split_name(&user.name);
+    let mut set = doc! {
+        "user.nama.nama_depan": nama_depan,
+        "user.nama.nama_belakang": nama_belakang,
+        "user.kontak.email": &user.email,
+    };
+    if let Some(phone) = &user.phone {
+        set.insert("user.kontak.nomor_telepon", phone);
+    }
+    Some(Refresh { collection: USER_ROLES, filter: stale(doc! { "user._id": id }, &set), set })
+}
+
+/// The patient's copy in their observations
+pub fn observations_refresh(record: &MedicalRecord) -> Option<Refresh> {
+    let id = record.id?.to_hex();
+    let (nama_depan, nama_belakang) = split_name(&record.name);
+    let set = doc! { "pasien.nama.nama_depan": nama_depan, "pasien.nama.nama_belakang": nama_belakang };
+    Some(Refresh { collection: OBSERVATIONS, filter: stale(doc! { "id_pasien": id }, &set), set })
+}
+
+fn run_hour() -> u32 {
+    env::var("DENORMALIZE_RUN_HOUR").ok().and_then(|h| h.trim().parse().ok()).filter(|h| *h < 24).unwrap_or(DEFAULT_RUN_HOUR)
+}
+
+fn build_service(state: &AppState) -> DenormalizationService {
+    DenormalizationService::new(
+        DenormalizationRepository::new(state.db.clone()),
+        UserRepository::new(state.db.clone()),
+        MedicalRecordRepository::new(state.db.clone()),
+    )
+}
+
+async fn handle_event(state: &AppState, event: &DomainEvent) -> Result<(), String> {
+    if event.kind != EventKind::Updated {
+        return Ok(());
+    }
+    let refreshed = match event.collection.as_str() {
+        "users" => build_service(state).refresh_user(&event.id).await?,
+        "medical_records" => build_service(state).refresh_patient(&event.id).await?,
+        _ => return Ok(()),
+    };
+    if refreshed > 0 {
+        tracing::info!(refreshed, collection = %event.collection, id = %event.id, "Refreshed embedded copies");
+    }
+    Ok(())
+}
+
+/// Spawn the task refreshing copies on update events and in a nightly full pass.
+pub fn spawn_worker(state: Arc<AppState>) {
+    let hour = run_hour();
+    let mut events = state.events.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => {
+                        if let Err(e) = handle_event(&state, &event).await {
+                            tracing::error!(collection = %event.collection, id = %event.id, error = %e, "Refreshing embedded copies failed");
+                        }
+                    }
+                    // The nightly pass catches up with whatever was skipped
+                    Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "Denormalization worker lagged, events skipped"),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = tokio::time::sleep(delay_until_next_run(Local::now(), hour)) => {
+                    state.scheduler.run("denormalization", async {
+                        let report = build_service(&state).full_pass(false).await?;
+                        tracing::info!(documents = report.targets.iter().map(|t| t.stale_documents).sum::<u64>(), "Nightly denormalization refreshed documents");
+                        Ok(())
+                    }).await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::{oid::ObjectId, DateTime};
+
+    fn user(name: &str, phone: Option<&str>) -> User {
+        User {
+            id: Some(ObjectId::new()),
+            email: "sari@example.com".to_string(),
+            password: String::new(),
+            name: name.to_string(),
+            phone: phone.map(str::to_string),
+            refresh_token: None,
+            reset_token: None,
+            reset_token_expiry: None,
+            email_verified_at: None,
+            created_at: DateTime::now(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn splits_names_at_the_first_space() {
+        assert_eq!(split_name("  Sari Dewi Lestari "), ("Sari".to_string(), "Dewi Lestari".to_string()));
+        assert_eq!(split_name("Budi"), ("Budi".to_string(), String::new()));
+    }
+
+    #[test]
+    fn matches_only_copies_that_differ() {
+        let sari = user("Sari Dewi", Some("+6281234567890"));
+        let refresh = user_roles_refresh(&sari).unwrap();
+        assert_eq!(refresh.collection, USER_ROLES);
+        assert_eq!(refresh.filter.get_str("user._id").unwrap(), sari.id.unwrap().to_hex());
+        assert_eq!(refresh.set.get_str("user.nama.nama_belakang").unwrap(), "Dewi");
+
+        let differs = refresh.filter.get_array("$or").unwrap();
+        assert_eq!(differs.len(), 4);
+        let email = differs[2].as_document().unwrap().get_document("user.kontak.email").unwrap();
+        assert_eq!(email.get_str("$ne").unwrap(), "sari@example.com");
+
+        // Without a phone on the user, the copy's phone is left alone
+        assert!(!user_roles_refresh(&user("Sari", None)).unwrap().set.contains_key("user.kontak.nomor_telepon"));
+    }
+}
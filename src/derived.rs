@@ -0,0 +1,129 @@
+//! Derived-observation rules.
+//!
+//! Each rule maps a set of input codings (e.g. body weight and height) to an output
+//! coding (e.g. BMI) with a formula. When an observation for one input is stored, the
+//! other inputs are looked up for the same patient within the rule's time window and,
+//! if all are present, the output is stored as an observation flagged `derived = true`.
+
+use std::collections::HashMap;
+use crate::growth::GrowthMetric;
+
+pub const INTERPRETATION_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v3-ObservationInterpretation";
+
+pub struct DerivedInput {
+    pub name: &'static str,
+    pub coding_codes: &'static [&'static str],
+    /// Convert a raw value with its unit code into the unit the formula expects
+    pub normalize: fn(f64, &str) -> Option<f64>,
+}
+
+pub struct DerivedRule {
+    pub output_code: &'static str,
+    pub output_display: &'static str,
+    pub output_system: &'static str,
+    pub unit_code: &'static str,
+    pub unit_display: &'static str,
+    pub unit_system: &'static str,
+    pub base_line: (f64, f64),
+    pub inputs: &'static [DerivedInput],
+    /// Maximum distance between input observations, in hours
+    pub window_hours: i64,
+    pub formula: fn(&HashMap<&'static str, f64>) -> Option<f64>,
+}
+
+impl DerivedRule {
+    pub fn input_for(&self, coding_code: &str) -> Option<&DerivedInput> {
+        self.inputs.iter().find(|i| i.coding_codes.contains(&coding_code))
+    }
+
+    /// Compute the output from normalized input values, rounded to two decimals.
+    pub fn compute(&self, values: &HashMap<&'static str, f64>) -> Option<f64> {
+        (self.formula)(values)
+            .filter(|v| v.is_finite())
+            .map(|v| (v * 100.0).round() / 100.0)
+    }
+
+    /// HL7 interpretation code and display for a computed value against the rule's base line.
+    pub fn interpret(&self, value: f64) -> (&'static str, &'static str) {
+        interpret(value, self.base_line)
+    }
+}
+
+/// HL7 interpretation code and display of `value` against a `(min, max)` base line.
+pub fn interpret(value: f64, (min, max): (f64, f64)) -> (&'static str, &'static str) {
+    if value < min {
+        ("L", "Low")
+    } else if value > max {
+        ("H", "High")
+    } else {
+        ("N", "Normal")
+    }
+}
+
+fn weight_kg(value: f64, unit: &str) -> Option<f64> {
+    GrowthMetric::Weight.normalize(value, unit)
+}
+
+fn height_cm(value: f64, unit: &str) -> Option<f64> {
+    GrowthMetric::Height.normalize(value, unit)
+}
+
+fn bmi(values: &HashMap<&'static str, f64>) -> Option<f64> {
+    let weight = *values.get("weight")?;
+    let height_m = *values.get("height")? / 100.0;
+    if height_m <= 0.0 {
+        return None;
+    }
+    Some(weight / (height_m * height_m))
+}
+
+pub static RULES: &[DerivedRule] = &[DerivedRule {
+    output_code: "39156-5",
+    output_display: "Body mass index (BMI) [Ratio]",
+    output_system: "http://loinc.org",
+    unit_code: "kg/m2",
+    unit_display: "kg/m2",
+    unit_system: "http://unitsofmeasure.org",
+    base_line: (18.5, 24.9),
+    inputs: &[
+        DerivedInput { name: "weight", coding_codes: &["29463-7", "3141-9"], normalize: weight_kg },
+        DerivedInput { name: "height", coding_codes: &["8302-2", "8306-3", "3137-7"], normalize: height_cm },
+    ],
+    window_hours: 24,
+    formula: bmi,
+}];
+
+/// Rules that take an observation of `coding_code` as one of their inputs.
+pub fn rules_for(coding_code: &str) -> impl Iterator<Item = &'static DerivedRule> + '_ {
+    RULES.iter().filter(move |rule| rule.input_for(coding_code).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_bmi_from_weight_and_height() {
+        let rule = rules_for("29463-7").next().unwrap();
+        let values = HashMap::from([("weight", 70.0), ("height", 175.0)]);
+
+        assert_eq!(rule.compute(&values), Some(22.86));
+        assert_eq!(rule.interpret(22.86), ("N", "Normal"));
+        assert_eq!(rule.interpret(31.0), ("H", "High"));
+    }
+
+    #[test]
+    fn missing_inputs_yield_nothing() {
+        let rule = &RULES[0];
+        assert_eq!(rule.compute(&HashMap::from([("weight", 70.0)])), None);
+        assert_eq!(rule.compute(&HashMap::from([("weight", 70.0), ("height", 0.0)])), None);
+    }
+
+    #[test]
+    fn inputs_are_normalized() {
+        let rule = &RULES[0];
+        let height = rule.input_for("8302-2").unwrap();
+        assert_eq!((height.normalize)(1.75, "m"), Some(175.0));
+        assert!(rules_for("39156-5").next().is_none());
+    }
+}
@@ -0,0 +1,77 @@
+//! Distributor portal scoping.
+//!
+//! A user holding the `distributor` role in an organization sees only the kits whose
+//! `distributor.code` is the organization's `distributor_code`, and the usage recorded on
+//! them. `KitRepository::scoped` applies the scope to every kit query; admins and other
+//! roles keep seeing all kits.
+
+use mongodb::{bson::{doc, Document}, Database};
+use crate::middleware::AuthUser;
+use crate::rbac::{self, ROLE_DISTRIBUTOR};
+use crate::repository::{OrganizationRepository, UserRoleRepository};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum KitScope {
+    #[default]
+    All,
+    /// Kits of these distributor codes only; none when empty
+    Distributors(Vec<String>),
+}
+
+impl KitScope {
+    /// Narrows a kit filter to the scope; a distributor the filter already names must be in it too
+    pub fn apply(&self, mut filter: Document) -> Document {
+        if let KitScope::Distributors(codes) = self {
+            let condition = doc! { "distributor.code": { "$in": codes } };
+            if filter.contains_key("distributor.code") {
+                filter.insert("$and", vec![condition]);
+            } else {
+                filter.extend(condition);
+            }
+        }
+        filter
+    }
+
+    pub fn allows(&self, distributor_code: &str) -> bool {
+        match self {
+            KitScope::All => true,
+            KitScope::Distributors(codes) => codes.iter().any(|code| code == distributor_code),
+        }
+    }
+}
+
+/// The caller's scope: restricted when they hold the distributor role and are not an admin
+pub async fn kit_scope(db: &Database, user: &AuthUser) -> Result<KitScope, String> {
+    let (roles, permissions) = rbac::load_permissions(db, &user.id).await?;
+    let (resource, action) = rbac::ADMIN_ACCESS;
+    if !roles.iter().any(|role| role == ROLE_DISTRIBUTOR) || permissions.allows(resource, action) {
+        return Ok(KitScope::All);
+    }
+
+    let organizations = UserRoleRepository::new(db.clone())
+        .find_active_organization_ids_for_role(&user.id, ROLE_DISTRIBUTOR)
+        .await
+        .map_err(|e| e.to_string())?;
+    let codes = OrganizationRepository::new(db.clone()).find_distributor_codes(&organizations).await?;
+    Ok(KitScope::Distributors(codes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distributor_scopes_narrow_kit_filters() {
+        let scope = KitScope::Distributors(vec!["D01".to_string()]);
+        assert_eq!(scope.apply(doc! { "code": "K1" }), doc! { "code": "K1", "distributor.code": { "$in": ["D01"] } });
+        assert!(scope.allows("D01"));
+        assert!(!scope.allows("D02"));
+        assert_eq!(
+            scope.apply(doc! { "distributor.code": "D02" }),
+            doc! { "distributor.code": "D02", "$and": [{ "distributor.code": { "$in": ["D01"] } }] },
+        );
+
+        assert_eq!(KitScope::All.apply(doc! {}), doc! {});
+        assert!(!KitScope::Distributors(Vec::new()).allows("D01"));
+    }
+}
@@ -1,7 +1,12 @@
-use axum::response::{Html, IntoResponse, Json};
+use axum::response::{IntoResponse, Json};
 use axum::http::StatusCode;
-use serde_json::json;
+use serde_json::{json, Map, Value};
+use crate::resource_router::OperationKind;
+use crate::status::{DoctorStatus, Gender};
 
+const ERROR_RESPONSE: &str = "#/components/schemas/ErrorResponse";
+
+#[cfg(feature = "docs-ui")]
 pub async fn docs_html() -> impl IntoResponse {
     // Simple Swagger UI HTML pointing to /openapi.json
     let html = r#"
@@ -25,14 +30,289 @@ pub async fn docs_html() -> impl IntoResponse {
 </html>
 "#;
 
-    Html(html)
+    axum::response::Html(html)
 }
 
 pub async fn openapi_json() -> impl IntoResponse {
-    let spec = json!({
+    (StatusCode::OK, Json(spec()))
+}
+
+/// The OpenAPI document of this build
+pub fn spec() -> Value {
+    let disabled = disabled_prefixes();
+    let mut paths = Map::new();
+    for group in path_groups() {
+        if let Value::Object(entries) = group {
+            paths.extend(entries.into_iter().filter(|(path, _)| !disabled.iter().any(|prefix| path.starts_with(prefix))));
+        }
+    }
+
+    // Operations registered as resources are grouped under their resource's tag
+    let mut tags: Vec<&str> = Vec::new();
+    for resource in crate::routes::resources() {
+        for operation in resource.operations() {
+            let entry = paths.entry(operation.path.clone()).or_insert_with(|| json!({}));
+            if let Value::Object(methods) = entry {
+                let method = methods.entry(operation.method).or_insert_with(|| json!({}));
+                if let Value::Object(method) = method {
+                    method.insert("tags".to_string(), json!([operation.tag]));
+                    let collection = operation.path.strip_suffix("/{id}").unwrap_or(&operation.path);
+                    describe_resource_operation(method, operation.kind, request_schema(collection));
+                }
+            }
+            if !tags.contains(&operation.tag) {
+                tags.push(operation.tag);
+            }
+        }
+    }
+
+    // The token endpoint answers errors as RFC 6749 describes, unless the request is malformed
+    if let Some(Value::Object(token)) = paths.get_mut("/auth/token").and_then(|methods| methods.get_mut("post")) {
+        let schema = json!({ "oneOf": [{ "$ref": "#/components/schemas/OAuthError" }, { "$ref": ERROR_RESPONSE }] });
+        token.insert("responses".to_string(), json!({ "default": { "description": "Error", "content": { "application/json": { "schema": schema } } } }));
+    }
+    for (path, methods) in paths.iter_mut() {
+        if let Value::Object(methods) = methods {
+            for method in methods.values_mut().filter_map(Value::as_object_mut) {
+                describe_operation(path, method);
+            }
+        }
+    }
+
+    json!({
         "openapi": "3.0.0",
-        "info": { "title": "RME API", "version": "0.1.0" },
-        "paths": {
+        "info": {
+            "title": "RME API",
+            "version": "0.1.0",
+            "description": "JSON keys are snake_case. Send `API-Version: 1` to get the camelCase keys of older responses until the next release. Codes and interpretations answer in the language of `?lang=` or `Accept-Language` (`id`, `en`) when a `display_i18n` translation exists."
+        },
+        "tags": tags.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        "paths": paths,
+        "components": { "schemas": envelope_schemas() }
+    })
+}
+
+fn json_content(schema: &str) -> Value {
+    json!({ "application/json": { "schema": { "$ref": schema } } })
+}
+
+/// A string of at least `min` and at most `max` characters
+fn text(min: usize, max: Option<usize>) -> Value {
+    let mut schema = json!({ "type": "string", "minLength": min });
+    if let Some(max) = max {
+        schema["maxLength"] = json!(max);
+    }
+    schema
+}
+
+fn nullable(mut schema: Value) -> Value {
+    schema["nullable"] = json!(true);
+    schema
+}
+
+/// Body of a create at the collection `path`, from the validation of its request DTO.
+/// Updates take the same fields, none of them required; resources not listed here document
+/// a bare object.
+fn request_schema(path: &str) -> Option<Value> {
+    let (required, properties): (&[&str], Value) = match path {
+        "/doctors" => (&["name", "nip", "sip", "specialization", "status"], json!({
+            "name": text(1, None),
+            "nip": text(1, None),
+            "sip": text(1, None),
+            "specialization": text(1, None),
+            "status": { "type": "string", "enum": DoctorStatus::KNOWN },
+        })),
+        "/nurses" => (&["name", "nip", "status"], json!({
+            "name": text(1, None),
+            "nip": text(1, None),
+            "status": text(1, None),
+        })),
+        "/services" => (&["name", "category", "sub_category"], json!({
+            "name": text(1, None),
+            "category": text(1, None),
+            "sub_category": text(1, None),
+        })),
+        "/medical-records" => (&["nik", "name", "dob", "gender", "hp", "email"], json!({
+            "nik": { "type": "string", "minLength": 16, "maxLength": 16, "pattern": "^[0-9]{16}$", "example": "3201010101900001" },
+            "name": text(1, None),
+            "dob": { "type": "string", "format": "date", "example": "1990-04-12" },
+            "gender": { "type": "string", "enum": Gender::KNOWN },
+            "hp": { "type": "string", "description": "Normalized to E.164; local numbers are taken as Indonesian", "example": "+6281311110001" },
+            "email": { "type": "string", "format": "email", "example": "patient@example.com" },
+        })),
+        "/wards" => (&["code", "name"], json!({
+            "code": text(1, Some(50)),
+            "name": text(1, Some(200)),
+            "class": nullable(json!({ "type": "string" })),
+            "organization_id": nullable(json!({ "type": "string", "pattern": "^[0-9a-f]{24}$" })),
+        })),
+        "/admin/permissions" => (&["resource", "action"], json!({
+            "resource": text(1, Some(100)),
+            "action": text(1, Some(50)),
+            "description": nullable(text(0, Some(500))),
+        })),
+        "/admin/feature-flags" => (&["key"], json!({
+            "key": { "type": "string", "minLength": 1, "maxLength": 100, "pattern": "^[a-z0-9_.-]+$", "example": "contract_test" },
+            "description": nullable(text(0, Some(500))),
+            "enabled": { "type": "boolean" },
+            "organizations": { "type": "array", "items": { "type": "string" } },
+            "rollout_percentage": nullable(json!({ "type": "integer", "minimum": 0, "maximum": 100 })),
+        })),
+        "/admin/organizations" => (&["name"], json!({
+            "name": text(1, Some(200)),
+            "timezone": nullable(json!({ "type": "string", "example": "Asia/Jakarta" })),
+            "latitude": nullable(json!({ "type": "number", "minimum": -90, "maximum": 90 })),
+            "longitude": nullable(json!({ "type": "number", "minimum": -180, "maximum": 180 })),
+            "distributor_code": nullable(text(1, Some(50))),
+        })),
+        _ => return None,
+    };
+    Some(json!({ "type": "object", "required": required, "properties": properties }))
+}
+
+/// Success responses and request bodies of a resource route, known from what it does
+fn describe_resource_operation(method: &mut Map<String, Value>, kind: OperationKind, request: Option<Value>) {
+    let success = match kind {
+        OperationKind::List => json!({ "2XX": { "description": "The items", "content": json_content("#/components/schemas/ListResponse") } }),
+        OperationKind::Create | OperationKind::Get | OperationKind::Update => {
+            json!({ "2XX": { "description": "The item", "content": json_content("#/components/schemas/ApiResponse") } })
+        }
+        OperationKind::Delete => json!({
+            "204": { "description": "Deleted" },
+            "2XX": { "description": "Deleted", "content": json_content("#/components/schemas/ApiResponse") }
+        }),
+        OperationKind::Other => return,
+    };
+    method.insert("responses".to_string(), success);
+    if matches!(kind, OperationKind::Create | OperationKind::Update) {
+        let mut schema = request.unwrap_or_else(|| json!({ "type": "object" }));
+        if let (OperationKind::Update, Value::Object(schema)) = (kind, &mut schema) {
+            schema.remove("required");
+        }
+        let body = json!({ "required": true, "content": { "application/json": { "schema": schema } } });
+        method.insert("requestBody".to_string(), body);
+    }
+}
+
+/// Schema of a path parameter: ids are ObjectIds, versions numbers, the rest free text
+fn parameter_schema(name: &str) -> Value {
+    if name == "id" || name.ends_with("_id") {
+        json!({ "type": "string", "pattern": "^[0-9a-f]{24}$" })
+    } else if name == "version" {
+        json!({ "type": "integer", "minimum": 1 })
+    } else {
+        json!({ "type": "string" })
+    }
+}
+
+/// Path parameters and the error envelope, which every operation shares
+fn describe_operation(path: &str, method: &mut Map<String, Value>) {
+    let parameters: Vec<Value> = path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": parameter_schema(name) }))
+        .collect();
+    if !parameters.is_empty() {
+        method.entry("parameters").or_insert_with(|| Value::Array(parameters));
+    }
+
+    let responses = method.entry("responses").or_insert_with(|| json!({}));
+    if let Value::Object(responses) = responses {
+        responses.entry("default").or_insert_with(|| json!({ "description": "Error", "content": json_content(ERROR_RESPONSE) }));
+    }
+}
+
+/// The envelopes of `crate::response`
+fn envelope_schemas() -> Value {
+    json!({
+        "ApiResponse": {
+            "type": "object",
+            "required": ["success", "status", "message", "timestamp"],
+            "properties": {
+                "success": { "type": "boolean" },
+                "status": { "type": "integer" },
+                "message": { "type": "string" },
+                "data": { "nullable": true },
+                "timestamp": { "type": "string" }
+            }
+        },
+        "ListResponse": {
+            "type": "object",
+            "required": ["success", "status", "message", "data", "timestamp"],
+            "properties": {
+                "success": { "type": "boolean" },
+                "status": { "type": "integer" },
+                "message": { "type": "string" },
+                "data": { "type": "array" },
+                "pagination": { "$ref": "#/components/schemas/Pagination" },
+                "timestamp": { "type": "string" }
+            }
+        },
+        "Pagination": {
+            "type": "object",
+            "required": ["current_page", "per_page", "total", "total_pages", "has_next", "has_prev"],
+            "properties": {
+                "current_page": { "type": "integer" },
+                "per_page": { "type": "integer" },
+                "total": { "type": "integer" },
+                "total_pages": { "type": "integer" },
+                "has_next": { "type": "boolean" },
+                "has_prev": { "type": "boolean" }
+            }
+        },
+        "OAuthError": {
+            "type": "object",
+            "required": ["error"],
+            "properties": {
+                "error": { "type": "string" },
+                "error_description": { "type": "string" }
+            }
+        },
+        "ErrorResponse": {
+            "type": "object",
+            "required": ["success", "status", "message", "error", "timestamp"],
+            "properties": {
+                "success": { "type": "boolean", "enum": [false] },
+                "status": { "type": "integer" },
+                "message": { "type": "string" },
+                "error": {
+                    "type": "object",
+                    "required": ["code"],
+                    "properties": {
+                        "code": { "type": "string" },
+                        "details": { "type": "string", "nullable": true }
+                    }
+                },
+                "data": { "nullable": true },
+                "timestamp": { "type": "string" }
+            }
+        }
+    })
+}
+
+/// Paths of the cargo features this build was compiled without
+fn disabled_prefixes() -> Vec<&'static str> {
+    let mut prefixes = Vec::new();
+    if !cfg!(feature = "s3") {
+        prefixes.extend(["/files", "/share", "/admin/report-schedules", "/admin/storage-usage", "/admin/storage"]);
+    }
+    if !cfg!(feature = "kits") {
+        prefixes.extend(["/kits", "/operators", "/distributors", "/admin/firmware"]);
+    }
+    if !cfg!(feature = "billing") {
+        prefixes.extend(["/price-lists", "/invoices", "/corporate-clients", "/cashier-shifts", "/payments", "/integrations/bpjs", "/stats/revenue", "/stats/services"]);
+    }
+    if !cfg!(feature = "fhir") {
+        prefixes.extend(["/codes/import", "/observations/export.ndjson"]);
+    }
+    prefixes
+}
+
+/// Path summaries by area. Each area is its own `json!` call so no single invocation hits the
+/// macro recursion limit.
+fn path_groups() -> Vec<Value> {
+    vec![
+        // Authentication
+        json!({
             "/auth/register": {
                 "post": { "summary": "Register user" }
             },
@@ -48,26 +328,528 @@ pub async fn openapi_json() -> impl IntoResponse {
             "/auth/reset-password": {
                 "post": { "summary": "Reset password using token" }
             },
+            "/auth/patient/otp": {
+                "post": { "summary": "Send a patient portal login code to the phone on the patient's medical record (nik)" }
+            },
+            "/auth/verify-email": {
+                "get": { "summary": "Verify an account email from the signed link sent on registration (token)" }
+            },
+            "/auth/resend-verification": {
+                "post": { "summary": "Send a new email verification link to an unverified account" }
+            },
+            "/auth/otp/request": {
+                "post": { "summary": "Send a one-time code by SMS/WhatsApp (purpose=patient_login with nik, or phone_verification with phone); rate limited per subject" }
+            },
+            "/auth/otp/verify": {
+                "post": { "summary": "Verify a one-time code; patient_login returns a patient token, phone_verification marks records with that phone verified" }
+            },
+            "/auth/patient/login": {
+                "post": { "summary": "Exchange NIK and login code for a read-only token scoped to the patient's records, appointments and observations" }
+            },
             "/auth/me": {
                 "get": { "summary": "Get current user (requires Bearer access token)" }
             },
+            "/auth/token": {
+                "post": { "summary": "OAuth2 client-credentials token for a service account (form: grant_type, client_id, client_secret, scope; or HTTP Basic)" }
+            },
+            "/auth/kiosk/pair": {
+                "post": { "summary": "Exchange a kiosk pairing code (single use, 15 minutes) for a token valid KIOSK_TOKEN_EXPIRATION_DAYS (180) that only reaches check-in, queue boards and /auth/me" }
+            },
+            "/auth/me/flags": {
+                "get": { "summary": "Feature flags evaluated for the current user (key -> enabled)" }
+            },
+            "/auth/me/permissions": {
+                "get": { "summary": "Resolved permission matrix (resource -> actions) of the current user's roles" }
+            },
+            "/share/{token}": {
+                "get": { "summary": "Download a shared file (no token; the link is the credential); 410 once the share expired or was revoked. Every attempt is audited" }
+            },
+            "/metrics": {
+                "get": { "summary": "Prometheus metrics: in-flight requests, limits and shed requests per load-shedding pool, slow MongoDB commands" }
+            }
+        }),
+        // Patients, records, terminology and observations
+        json!({
             "/medical-records": {
-                "get": { "summary": "List medical records" },
-                "post": { "summary": "Create medical record" }
+                "get": { "summary": "List medical records (expand=guardians embeds the related patients who act for each)" },
+                "post": { "summary": "Create medical record with a generated NRME (RM-YYYY-000123); an already registered NIK returns the existing record with 200" }
+            },
+            "/medical-records/by-nik/{nik}": {
+                "get": { "summary": "Get the medical record of a NIK (expand=guardians)" }
             },
             "/medical-records/{id}": {
-                "get": { "summary": "Get medical record" },
-                "put": { "summary": "Update medical record" },
-                "delete": { "summary": "Delete medical record" }
+                "get": { "summary": "Get medical record (expand=guardians embeds the related patients who act for the patient)" },
+                "put": { "summary": "Update medical record; the changed fields are stored with the author" },
+                "delete": { "summary": "Delete medical record; cancels its upcoming appointments and soft-deletes its files and notes, 409 while observations reference it (DELETE_POLICIES)" }
+            },
+            "/files": {
+                "post": { "summary": "Upload a file (multipart file, uploader, medical_record_id, organization_id); content must match the extension and is typed by its sniffed format, charged to the caller and organization, 413 QUOTA_EXCEEDED with the remaining quota when over STORAGE_QUOTA_USER_MB or STORAGE_QUOTA_ORGANIZATION_MB" }
+            },
+            "/files/{id}/content": {
+                "get": { "summary": "Download the current content of a file" },
+                "put": { "summary": "Replace a file's content with a new version under a new storage key (multipart file, optional uploader and version), checked like uploads; earlier versions stay listed" }
+            },
+            "/files/{id}/versions": {
+                "get": { "summary": "Versions of a file, oldest first, with who uploaded each and its download link" }
+            },
+            "/files/{id}/versions/{version}/content": {
+                "get": { "summary": "Download one version of a file" }
+            },
+            "/files/{id}/versions/{version}/restore": {
+                "post": { "summary": "Make an earlier version current again; recorded as a new version" }
+            },
+            "/files/{id}/share": {
+                "post": { "summary": "Create a public download link for a file (expires_in_minutes, default 1440, at most 10080); returns the signed token and url" }
+            },
+            "/files/{id}/share/{share_id}": {
+                "delete": { "summary": "Revoke a file's share link; the link answers 410 from then on" }
+            },
+            "/medical-records/{id}/changes": {
+                "get": { "summary": "Field-level changes of a medical record (old and new values), newest first, with the user who made each (page, limit)" }
+            },
+            "/medical-records/{id}/vitals": {
+                "post": { "summary": "Record a vitals bundle (temperature, systolic/diastolic, pulse, spo2, weight, height, time) as one LOINC-coded, interpreted observation per sign, in one transaction" }
+            },
+            "/notes": {
+                "get": { "summary": "List clinical notes (medical_record_id, page, limit)" },
+                "post": { "summary": "Create a note on a medical record (text and/or SOAP sections); stored as version 1" }
+            },
+            "/notes/{id}": {
+                "get": { "summary": "Get the current version of a note" },
+                "put": { "summary": "Edit a note, creating a new immutable version (version guards against concurrent edits)" },
+                "delete": { "summary": "Soft-delete a note; body { reason } is required and audited" }
+            },
+            "/notes/{id}/versions": {
+                "get": { "summary": "Every version of a note, oldest first" }
+            },
+            "/wards": {
+                "get": { "summary": "List wards (organization_id)" },
+                "post": { "summary": "Create a ward" }
+            },
+            "/wards/occupancy": {
+                "get": { "summary": "Live bed counts by status and occupancy rate per ward and in total (organization_id)" }
+            },
+            "/wards/{id}": {
+                "get": { "summary": "Get ward by ID" },
+                "put": { "summary": "Update ward" },
+                "delete": { "summary": "Delete a ward without beds" }
+            },
+            "/wards/{id}/beds": {
+                "get": { "summary": "List the beds of a ward" },
+                "post": { "summary": "Add a bed to a ward" }
+            },
+            "/beds/{id}": {
+                "put": { "summary": "Rename a bed or set it available, cleaning or maintenance; refused while occupied" },
+                "delete": { "summary": "Delete a bed that is not occupied" }
+            },
+            "/admissions": {
+                "get": { "summary": "List admissions, newest first (status, ward_id)" },
+                "post": { "summary": "Admit a patient (medical record) to an available bed" }
+            },
+            "/admissions/{id}": {
+                "get": { "summary": "Get admission by ID, with its bed transfers" }
+            },
+            "/admissions/{id}/transfer": {
+                "post": { "summary": "Move an admitted patient to another available bed" }
+            },
+            "/admissions/{id}/discharge": {
+                "post": { "summary": "Discharge the patient and free the bed" }
+            },
+            "/search": {
+                "get": { "summary": "Search patients, doctors, medicines and appointments (q, limit, types)" }
+            },
+            "/lookup/barcode/{value}": {
+                "get": { "summary": "Resolve a scanned value to medicine batches, a kit (by code) or a patient (card QR RME:<nrme>), as {type, item} matches; 404 when none" }
+            },
+            "/patients/by-phone/{phone}": {
+                "get": { "summary": "Patients whose phone matches once normalized to E.164 (08xx is read as +628xx)" }
+            },
+            "/patients/duplicates": {
+                "get": { "summary": "List duplicate patient candidates (same NIK, or same DOB with similar name)" }
+            },
+            "/patients/{id}/merge": {
+                "post": { "summary": "Merge duplicate patients into this one, repointing appointments and observations" }
             },
-            "/doctors": { "get": { "summary": "List doctors" }, "post": {"summary": "Create doctor"} },
-            "/nurses": { "get": { "summary": "List nurses" } },
+            "/codes/validate": {
+                "post": { "summary": "Check up to 500 {system, code} pairs in one call: whether each exists and is active, with its display; served from a cache refreshed every CODE_CACHE_SECONDS (300)" }
+            },
+            "/codes/{id}/children/bulk": {
+                "post": { "summary": "Link up to 500 child codes (child_code_id, norut) under a parent; all are checked first and the valid ones inserted in one transaction, or none with all_or_nothing" }
+            },
+            "/codes/import": {
+                "post": { "summary": "Import a LOINC or SNOMED CT subset (CSV/JSON) as a background job" }
+            },
+            "/codes/import/{job_id}": {
+                "get": { "summary": "Get code import job status and progress" }
+            },
+            "/observations": {
+                "get": { "summary": "List observations (id_pasien; view=summary leaves out the embedded patient and kit)" }
+            },
+            "/observations/export.ndjson": {
+                "get": { "summary": "Stream observations as newline-delimited JSON (from, to, coding_code, id_pasien); requires observations:export and is audited" }
+            },
+            "/observations/pasien/{id}/trends/{coding_code}": {
+                "get": { "summary": "Rolling mean/min/max, regression slope and base-line breach flags for a patient's vital sign (window, rolling, from, to)" }
+            }
+        }),
+        // Offline sync
+        json!({
+            "/sync/reference": {
+                "get": { "summary": "Codes, interpretations and regions created, updated and deleted since a cursor or RFC 3339 timestamp (since, collections, limit); page with the returned cursor while has_more" }
+            },
+            "/sync/clinical/push": {
+                "post": { "summary": "Apply up to 200 note mutations made offline (client_id UUID, entity, operation, entity_id, base_version, changed_at, data) in order; stale edits follow strategy last_writer_wins or manual, and replayed UUIDs return their first outcome" }
+            },
+            "/sync/clinical/pull": {
+                "get": { "summary": "Notes of the given medical records (medical_record_ids) created, updated and deleted since a cursor or RFC 3339 timestamp (since, limit)" }
+            },
+            "/sync/conflicts": {
+                "get": { "summary": "List the caller's sync conflicts, or everyone's for admins (status, page, limit)" }
+            },
+            "/sync/conflicts/{id}/resolve": {
+                "post": { "summary": "Resolve an open sync conflict with keep_server, or use_client to apply the offline edit over the current version" }
+            }
+        }),
+        // Service accounts and kiosks
+        json!({
+            "/admin/service-accounts": {
+                "get": { "summary": "List service accounts (admin)" },
+                "post": { "summary": "Create a service account with resource:action scopes; returns the client secret once (admin)" }
+            },
+            "/admin/service-accounts/{id}": {
+                "get": { "summary": "Get a service account (admin)" },
+                "put": { "summary": "Update name, scopes or active flag (admin)" },
+                "delete": { "summary": "Delete a service account (admin)" }
+            },
+            "/admin/service-accounts/{id}/rotate-secret": {
+                "post": { "summary": "Issue a new client secret and revoke tokens issued before it (admin)" }
+            },
+            "/admin/kiosks": {
+                "get": { "summary": "List check-in kiosks with status pending|paired|revoked and last activity (admin)" },
+                "post": { "summary": "Register a kiosk (name, organization_id); returns its pairing code once (admin)" }
+            },
+            "/admin/kiosks/{id}": {
+                "get": { "summary": "Get a kiosk device (admin)" }
+            },
+            "/admin/kiosks/{id}/pairing-code": {
+                "post": { "summary": "Issue a new pairing code; pairing with it revokes the device's earlier tokens (admin)" }
+            },
+            "/admin/kiosks/{id}/revoke": {
+                "post": { "summary": "Revoke a kiosk's tokens and pending pairing code (admin)" }
+            }
+        }),
+        // Patients
+        json!({
+            "/public/patients/register": {
+                "post": { "summary": "Kiosk self-registration (nik, name, phone): sends a code to the phone, then with otp creates a pending record whose dob and gender come from the NIK; 409 if the NIK is registered, 429 past SELF_REGISTRATION_MAX_PER_HOUR per client (public)" }
+            },
+            "/patients/registrations": {
+                "get": { "summary": "Self-registered patients by status (pending by default, or confirmed), oldest first" }
+            },
+            "/patients/{id}/registration/confirm": {
+                "post": { "summary": "Confirm a pending self-registration after checking the patient's identity; 409 when it is not pending" }
+            },
+            "/patients/{id}/allergies": {
+                "get": { "summary": "A patient's allergies and adverse reactions" },
+                "post": { "summary": "Record an allergy (substance_code from the medicine master catalog, substance_display, severity mild|moderate|severe, reaction)" }
+            },
+            "/patients/{id}/allergies/{allergy_id}": {
+                "put": { "summary": "Update an allergy" },
+                "delete": { "summary": "Delete an allergy" }
+            },
+            "/patients/{id}/allergies/check": {
+                "post": { "summary": "Check medicine_ids against the patient's allergies; warnings, or 409 with the conflicts in data when ALLERGY_CHECK_MODE=block" }
+            },
+            "/patients/{id}/relationships": {
+                "get": { "summary": "A patient's links to other patients in both directions, each seen from this patient (relationship, guardian, guardian_of_related; inverse when recorded from the other side)" },
+                "post": { "summary": "Link a related patient (related_patient_id, relationship parent|child|spouse|sibling|caregiver|dependent, guardian defaulting to true for parents and caregivers, notes); 409 when the two are already linked" }
+            },
+            "/patients/{id}/relationships/{relationship_id}": {
+                "put": { "summary": "Update a link recorded for this patient" },
+                "delete": { "summary": "Delete a link recorded for this patient" }
+            },
+            "/patients/{id}/growth": {
+                "get": { "summary": "WHO growth z-scores and percentiles for weight or height observations (metric=weight|height)" }
+            },
+            "/patients/{id}/timeline": {
+                "get": { "summary": "Admissions, observations, files and appointments newest first as typed entries (types=encounter,observation,file,appointment, limit 1-100, cursor from next_cursor)" }
+            }
+        }),
+        // Public booking
+        json!({
+            "/public/doctors": {
+                "get": { "summary": "Active doctors with id, name and specialization only, paginated (public)" }
+            },
+            "/public/doctors/{id}/slots": {
+                "get": { "summary": "A doctor's slots on a day (date, organization_id) with availability; 404 for inactive doctors (public)" }
+            },
+            "/public/appointments": {
+                "post": { "summary": "Book without an account (nik, phone, doctor_id, date, time, organization_id): with captcha_token sends a code to the phone, then with otp books the slot as pending_confirmation for a patient whose record has that NIK and phone; 403 on a failed captcha, 409 when the slot is taken (public)" }
+            }
+        }),
+        // Administration, kits and operators
+        json!({
+            "/admin/retention/status": {
+                "get": { "summary": "Retention policies, last run and documents archived/purged (admin)" }
+            },
+            "/admin/system-info": {
+                "get": { "summary": "Version, git SHA, compiled features, active flags, storage, Mongo topology, index health, config issues and slow query counts (admin)" }
+            },
+            "/admin/storage-usage": {
+                "get": { "summary": "Bytes and files stored per uploader and organization, largest first, with quota and remaining bytes (scope=user|organization) (admin)" }
+            },
+            "/admin/storage/reconciliation": {
+                "get": { "summary": "Garbage collector settings and the latest reconciliation of stored objects against file records: orphans found, pending and deleted, and referenced keys missing from storage (admin)" },
+                "post": { "summary": "Reconcile storage now; deletes orphans past the grace period unless STORAGE_GC_MODE is report or off (admin)" }
+            },
+            "/admin/request-logs": {
+                "get": { "summary": "Redacted request/response captures for routes in REQUEST_LOG_ROUTES (path, status, page, limit) (admin)" }
+            },
+            "/admin/jobs": {
+                "get": { "summary": "Background jobs (status=pending|running|completed|failed, job_type, page, limit); failed ones form the dead-letter queue (admin)" }
+            },
+            "/admin/jobs/retry": { "post": { "summary": "Retry the failed jobs of a job_type, oldest first (limit, default 100) (admin)" } },
+            "/admin/jobs/{id}": { "get": { "summary": "Get a job with the error of every failed attempt (admin)" } },
+            "/admin/jobs/{id}/retry": { "post": { "summary": "Requeue and run a failed job (admin)" } },
+            "/admin/outbox": {
+                "get": { "summary": "Outbox entries for external delivery (status, topic, stuck=true, page, limit) (admin)" }
+            },
+            "/admin/outbox/{id}": { "get": { "summary": "Get an outbox entry with its payload and last error (admin)" } },
+            "/admin/outbox/{id}/retry": { "post": { "summary": "Make a failed or backed-off outbox entry due again (admin)" } },
+            "/admin/report-schedules": {
+                "get": { "summary": "Scheduled reports (report_type, active, page, limit) (admin)" },
+                "post": { "summary": "Schedule a report (appointments_per_doctor, revenue, stock) by cron in the clinic timezone, emailed as csv or pdf (admin)" }
+            },
+            "/admin/report-schedules/{id}": {
+                "get": { "summary": "Get a report schedule with its next and last run (admin)" },
+                "put": { "summary": "Update a report schedule; a new cron recomputes the next run (admin)" },
+                "delete": { "summary": "Delete a report schedule (admin)" }
+            },
+            "/admin/report-schedules/{id}/run": { "post": { "summary": "Render, store and email the report now (admin)" } },
+            "/admin/report-templates": {
+                "get": { "summary": "Custom report templates (page, limit) (admin)" },
+                "post": { "summary": "Store an aggregation over a report collection with typed {{name}} parameters; $out, $merge and JavaScript are refused (admin)" }
+            },
+            "/admin/report-templates/{id}": {
+                "get": { "summary": "Get a report template (admin)" },
+                "put": { "summary": "Update a report template; the result is validated again (admin)" },
+                "delete": { "summary": "Delete a report template (admin)" }
+            },
+            "/reports/run": { "post": { "summary": "Run a report template with parameters; returns columns and one page of rows (page, limit) (admin)" } },
+            "/admin/reviews": { "get": { "summary": "List reviews for moderation (status, doctor_id, page, limit) (admin)" } },
+            "/admin/reviews/{id}": {
+                "put": { "summary": "Publish or hide a review (admin)" },
+                "delete": { "summary": "Delete a review (admin)" }
+            },
+            "/admin/permissions": {
+                "get": { "summary": "List permissions (admin)" },
+                "post": { "summary": "Create a permission (resource, action) (admin)" }
+            },
+            "/admin/permissions/{id}": {
+                "get": { "summary": "Get a permission (admin)" },
+                "put": { "summary": "Update a permission; renaming moves its role grants (admin)" },
+                "delete": { "summary": "Delete a permission and revoke it from all roles (admin)" }
+            },
+            "/admin/role-permissions": {
+                "get": { "summary": "List role grants (role_code) (admin)" },
+                "post": { "summary": "Grant a permission to a role; '*' matches any role, resource or action (admin)" }
+            },
+            "/admin/role-permissions/{id}": { "delete": { "summary": "Revoke a role grant (admin)" } },
+            "/admin/feature-flags": {
+                "get": { "summary": "List feature flags (admin)" },
+                "post": { "summary": "Create a feature flag with organizations and rollout percentage (admin)" }
+            },
+            "/admin/feature-flags/{id}": {
+                "get": { "summary": "Get a feature flag (admin)" },
+                "put": { "summary": "Update a feature flag (admin)" },
+                "delete": { "summary": "Delete a feature flag (admin)" }
+            },
+            "/admin/organizations": {
+                "get": { "summary": "List organizations (admin)" },
+                "post": { "summary": "Create an organization with its clinic time zone, e.g. Asia/Makassar or +08:00, location and the kit distributor_code it represents (admin)" }
+            },
+            "/admin/organizations/{id}": {
+                "get": { "summary": "Get an organization (admin)" },
+                "put": { "summary": "Update an organization; a new time zone applies to appointments booked afterwards (admin)" },
+                "delete": { "summary": "Delete an organization; its appointments keep their time zone and lose organizationId (admin)" }
+            },
+            "/admin/firmware": {
+                "get": { "summary": "List firmware releases (admin)" },
+                "post": { "summary": "Create a firmware release (admin)" }
+            },
+            "/admin/firmware/{id}": {
+                "get": { "summary": "Get firmware release (admin)" },
+                "put": { "summary": "Update firmware release (admin)" },
+                "delete": { "summary": "Delete firmware release (admin)" }
+            },
+            "/kits": {
+                "get": { "summary": "List kits (view=summary returns code, name, status and firmware only)" }
+            },
+            "/kits/nearby": {
+                "get": { "summary": "Kits within radius meters (default 10000) of lat, lng, nearest first, with their distance (limit, distributor)" }
+            },
+            "/kits/map-clusters": {
+                "get": { "summary": "Kits with a location grouped into a grid that gets finer with zoom (0-20), with counts and mean position (min_lat, min_lng, max_lat, max_lng, distributor)" }
+            },
+            "/kits/{code}/heartbeat": {
+                "post": { "summary": "Record a kit heartbeat and the firmware version it runs" }
+            },
+            "/kits/{code}/firmware/latest": {
+                "get": { "summary": "Newest active firmware for the kit's model and whether an update is available" }
+            },
+            "/kits/{id}/usage": {
+                "get": { "summary": "Observations, patients served and active hours per day/week for a kit (period, from, to, tz)" }
+            },
+            "/distributors/{code}/summary": {
+                "get": { "summary": "Kits and active kits of a distributor, observations per month and the top 10 operators (months, default 12; tz). Holders of the distributor role see only the distributor of their organization, here and on every /kits and usage route" }
+            },
+            "/operators/{nik}/activity": {
+                "get": { "summary": "Observations, patients served and active hours per day/week for an operator (period, from, to, tz)" }
+            }
+        }),
+        // Users, roles and organizations
+        json!({
+            "/admin/denormalization/report": {
+                "get": { "summary": "Dry run of the nightly refresh of embedded copies (user names and contacts in user_roles, patient names in observations): stale documents per target, changing nothing (admin)" }
+            },
+            "/admin/scheduler": {
+                "get": { "summary": "Scheduler lease holder, whether this instance leads, and the last run of each scheduled job (admin)" }
+            },
+            "/admin/indexes": {
+                "get": { "summary": "Indexes from the migration definitions against those in the database per collection: present, missing, differs (other keys or uniqueness) or unmanaged (admin)" }
+            },
+            "/admin/indexes/rebuild": {
+                "post": { "summary": "Create the missing indexes in a background index_rebuild job; poll /admin/jobs/{id} for progress. Differing indexes must be dropped first (admin)" }
+            },
+            "/user-roles/bulk": {
+                "post": { "summary": "Assign up to 200 roles at once (assignments); each is validated and created on its own, with a per-item status of created, invalid, duplicate (same user, role and organization) or failed" }
+            },
+            "/organizations/{id}/members": {
+                "get": { "summary": "Users holding roles in an organization, each with their roles there (include_inactive, page, limit); for admins and members of the organization" }
+            }
+        }),
+        // Appointments and queues
+        json!({
+            "/appointments/availability": {
+                "get": { "summary": "Bookable slots of a doctor's day in the clinic's time zone (doctor_id, date, organization_id)" }
+            },
+            "/appointments/{id}/check-in": {
+                "post": { "summary": "Check in a patient for today's appointment and assign the next per-doctor queue number" }
+            },
+            "/appointments/{id}/no-show": {
+                "post": { "summary": "Mark a started, scheduled appointment without check-in as no_show; a sweep does this NO_SHOW_GRACE_MINUTES (120) after the start" }
+            },
+            "/stats/doctors/{id}/appointments": {
+                "get": { "summary": "Completed, cancelled and no-show counts, no-show rate and average consult minutes (called to completed) of a doctor (from, to; default the last 30 days)" }
+            },
+            "/appointments/{id}/teleconsult": {
+                "get": { "summary": "Meeting link and token for a virtual appointment, created on first access" }
+            },
+            "/appointments/{id}/teleconsult/start": { "post": { "summary": "Start the teleconsult; the appointment becomes in_progress" } },
+            "/appointments/{id}/teleconsult/end": { "post": { "summary": "End the teleconsult; the appointment becomes completed" } },
+            "/appointments/{id}/review": { "post": { "summary": "Rate (1-5) and comment on a completed appointment; patient only, once" } },
+            "/appointments/series": {
+                "post": { "summary": "Create a recurring appointment series (RRULE subset) and generate its appointments up to a horizon; 409 if any slot is taken, 422 if the patient or doctor does not exist" }
+            },
+            "/appointments/series/{id}": {
+                "get": { "summary": "Get a series with its occurrences" },
+                "put": { "summary": "Change doctor, time or rrule for this/future/all occurrences" }
+            },
+            "/appointments/series/{id}/cancel": { "post": { "summary": "Cancel this/future/all occurrences of a series" } },
+            "/appointments/waitlist": {
+                "get": { "summary": "List waitlist entries (doctor_id, date, status)" },
+                "post": { "summary": "Join a doctor's waitlist for a date after booking failed with 409" }
+            },
+            "/appointments/waitlist/{id}": { "delete": { "summary": "Leave the waitlist, releasing any held slot" } },
+            "/appointments/waitlist/{id}/confirm": { "post": { "summary": "Confirm a slot held for a waitlisted patient before the hold expires" } },
+            "/queues/{doctor_id}/next": { "post": { "summary": "Call the waiting patient with the lowest queue number" } },
+            "/queues/{doctor_id}/today": { "get": { "summary": "Waiting-room board: current patient, waiting list and served count" } },
+            "/queues/{doctor_id}/stream": { "get": { "summary": "Server-sent events with the board after every check-in or call" } }
+        }),
+        // Basic resources
+        json!({
+            "/practitioners": { "get": { "summary": "List doctors and nurses (type: doctor, nurse; status)" }, "post": { "summary": "Create a practitioner with qualifications; doctors need a specialization and an SIP qualification" } },
+            "/practitioners/{id}": { "get": { "summary": "Get practitioner" }, "put": { "summary": "Update practitioner; the type cannot change and qualifications replace the list" }, "delete": { "summary": "Delete practitioner; doctors follow the /doctors delete policies" } },
+            "/doctors": { "get": { "summary": "List doctors, the practitioners of type doctor (status: active, inactive, on_leave)" }, "post": {"summary": "Create doctor"} },
+            "/doctors/{id}": { "delete": { "summary": "Delete doctor; 409 while the doctor has upcoming appointments (DELETE_POLICIES)" } },
+            "/doctors/{id}/reviews": { "get": { "summary": "Published reviews of a doctor" } },
+            "/nurses": { "get": { "summary": "List nurses, the practitioners of type nurse" } },
             "/medicines": { "get": { "summary": "List medicines" } },
-            "/appointments": { "get": { "summary": "List appointments" }, "post": {"summary": "Create appointment"} },
+            "/medicines/{id}/label": { "get": { "summary": "Printable batch label with a Code 128 barcode of the batch number (format: zpl (default), pdf; copies 1-100)" } },
+            "/stock-opnames": { "get": { "summary": "List stock opnames, newest first (status: counting, approved, cancelled)" }, "post": { "summary": "Start a stock opname, snapshotting the qty of every batch (or of master_medicine_ids); 409 while another is counting" } },
+            "/stock-opnames/{id}/counts": { "post": { "summary": "Record counted quantities per batch; counts replace earlier ones" } },
+            "/stock-opnames/{id}/approve": { "post": { "summary": "Approve a fully counted opname; each variance is added to the batch qty and recorded as an adjustment stock movement, in one transaction" } },
+            "/stock-opnames/{id}/cancel": { "post": { "summary": "Cancel an opname that is still counting" } },
+            "/stock-opnames/{id}/report": { "get": { "summary": "Counted and uncounted batches, shortage and surplus quantities and the variance valued at purchase prices" } },
+            "/appointments": { "get": { "summary": "List appointments (patient_id, status; expand=doctor,patient embeds name summaries)" }, "post": {"summary": "Create appointment; 422 naming patient_id or doctor_id when the referenced record does not exist"} },
             "/services": { "get": { "summary": "List services" } },
-            "/insurances": { "get": { "summary": "List insurances" } }
+            "/price-lists": { "get": { "summary": "List price list versions, newest first (organization_id, status)" }, "post": { "summary": "Create the organization's next version as a draft (items, or based_on to copy a version)" } },
+            "/price-lists/active": { "get": { "summary": "The published price list in effect for an organization (organization_id, date; default today)" } },
+            "/price-lists/quote": { "post": { "summary": "Price services and medicines from the list in effect on the day of service" } },
+            "/price-lists/{id}": { "put": { "summary": "Update a draft" }, "delete": { "summary": "Delete a draft" } },
+            "/price-lists/{id}/publish": { "post": { "summary": "Publish a draft; it applies from effective_from on and cannot change afterwards" } },
+            "/invoices": { "get": { "summary": "List invoices (medical_record_id, status)" }, "post": { "summary": "Create an invoice priced from the price list in effect on service_date; numbered INV-YYYY-000123 per organization and service year; insurance_id claims it under that insurance's terms on service_date; a patient on a corporate client's roster then is billed to the employer for its contract share of the rest" } },
+            "/invoices/{id}/payments": { "get": { "summary": "Payments of an invoice" }, "post": { "summary": "Record a cash, transfer or QRIS payment in the caller's open shift" } },
+            "/invoices/{id}/payment-links": { "get": { "summary": "Payment gateway links of an invoice" }, "post": { "summary": "Create a Midtrans or Xendit link for the outstanding balance (PAYMENT_GATEWAY)" } },
+            "/payments/callback": { "post": { "summary": "Gateway notification (no token; signature or x-callback-token verified); a paid link records the payment" } },
+            "/integrations/bpjs/participants/{card_number}": { "get": { "summary": "BPJS participant eligibility by card number (?date=, clinic today by default); cached for BPJS_CACHE_TTL_SECONDS" } },
+            "/integrations/bpjs/sep": { "post": { "summary": "Create a SEP for an active BPJS participant (stub unless BPJS_MODE=live)" } },
+            "/cashier-shifts": { "get": { "summary": "List cashier shifts (cashier_id, status)" }, "post": { "summary": "Open a shift for the caller with an opening cash float" } },
+            "/cashier-shifts/{id}/close": { "post": { "summary": "Close a shift with the counted cash; returns expected cash and the difference" } },
+            "/payments/settlement": { "get": { "summary": "Daily settlement: payments by method and by cashier (date, organization_id)" } },
+            "/stats/revenue": { "get": { "summary": "Payments received by day, week or month (group_by) and by method, and billed lines by service category (from, to; default the last 30 days; organization_id, else the caller's organizations)" } },
+            "/stats/services/utilization": { "get": { "summary": "Invoices, quantity and amount billed per service, most used first (from, to, organization_id)" } },
+            "/insurances": { "get": { "summary": "List insurances (status: active, inactive, suspended)" } },
+            "/insurances/{id}": { "put": { "summary": "Update an insurance as a new version effective from effective_from (default today); earlier claims keep their version" } },
+            "/insurances/{id}/versions": { "get": { "summary": "Versions of an insurance with their effective periods, newest first" } }
+        }),
+        // Corporate clients
+        json!({
+            "/corporate-clients": { "get": { "summary": "List corporate clients (organization_id, status: active, inactive)" }, "post": { "summary": "Create a corporate client with its contract: period, coverage terms and payment term" } },
+            "/corporate-clients/{id}": { "delete": { "summary": "Delete a client no invoice was billed to, with its roster" } },
+            "/corporate-clients/{id}/employees": { "get": { "summary": "Employee roster of a corporate client" }, "post": { "summary": "Put a patient on the roster from start_date (default today)" } },
+            "/corporate-clients/{id}/employees/{employee_id}": { "put": { "summary": "Change the employee number or set end_date when the employee leaves" }, "delete": { "summary": "Take a patient off the roster" } },
+            "/corporate-clients/{id}/statements": { "get": { "summary": "Monthly statements, newest first" }, "post": { "summary": "Generate the statement of a month (YYYY-MM) from the invoices billed to the client; regenerating keeps its STM-YYYY-000123 number" } },
+            "/corporate-clients/{id}/statements/{month}": { "get": { "summary": "The statement of a month (YYYY-MM)" } }
+        }),
+        // Chronic disease programs
+        json!({
+            "/programs": { "get": { "summary": "List programs (status: active, inactive)" }, "post": { "summary": "Create a program with its eligibility rules (diagnosis_codes as ICD-10 prefixes, observation thresholds) and control targets (observation code with min and/or max)" } },
+            "/programs/{id}": { "delete": { "summary": "Delete a program nobody was enrolled in" } },
+            "/programs/{id}/eligibility": { "post": { "summary": "Check whether a patient (medical_record_id, diagnosis_codes) meets an eligibility rule by diagnosis or latest observation, and which" } },
+            "/programs/{id}/enrollments": { "get": { "summary": "Enrollments of a program, newest first (status: active, withdrawn)" }, "post": { "summary": "Enroll an eligible patient (medical_record_id, diagnosis_codes) in an active program; 422 when ineligible, 409 when already enrolled" } },
+            "/programs/{id}/enrollments/{enrollment_id}/withdraw": { "post": { "summary": "Withdraw an active enrollment with an optional reason" } },
+            "/programs/{id}/cohort": { "get": { "summary": "Active enrollments as controlled, uncontrolled or no_data by the latest observation of each control target, with counts and the controlled percentage of patients with data" } }
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_operation_declares_its_parameters_and_error_envelope() {
+        let spec = spec();
+        let paths = spec["paths"].as_object().unwrap();
+        for (path, methods) in paths {
+            let names: Vec<&str> = path.split('/').filter_map(|s| s.strip_prefix('{')?.strip_suffix('}')).collect();
+            for (method, operation) in methods.as_object().unwrap() {
+                let declared: Vec<&str> = operation["parameters"].as_array().into_iter().flatten()
+                    .filter_map(|p| p["name"].as_str())
+                    .collect();
+                assert_eq!(declared, names, "parameters of {} {}", method, path);
+                assert!(operation["responses"]["default"].is_object(), "error response of {} {}", method, path);
+            }
         }
-    });
 
-    (StatusCode::OK, Json(spec))
+        assert_eq!(paths["/doctors"]["get"]["responses"]["2XX"]["content"]["application/json"]["schema"]["$ref"], "#/components/schemas/ListResponse");
+        assert_eq!(paths["/doctors/{id}"]["get"]["parameters"][0]["schema"]["pattern"], "^[0-9a-f]{24}$");
+        let create = &paths["/doctors"]["post"]["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(create["required"].as_array().map(Vec::len), Some(5));
+        assert_eq!(create["properties"]["status"]["enum"], json!(DoctorStatus::KNOWN));
+        let update = &paths["/doctors/{id}"]["put"]["requestBody"]["content"]["application/json"]["schema"];
+        assert!(update["required"].is_null() && update["properties"]["status"].is_object());
+        assert!(spec["components"]["schemas"]["ErrorResponse"].is_object());
+    }
 }
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::status::AdmissionStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct AdmitRequest {
+    #[validate(length(min = 1, message = "Medical record ID is required"))]
+    pub medical_record_id: String,
+    #[validate(length(min = 1, message = "Bed ID is required"))]
+    pub bed_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct TransferRequest {
+    #[validate(length(min = 1, message = "Bed ID is required"))]
+    pub bed_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Validate)]
+pub struct DischargeRequest {
+    #[validate(length(max = 2000, message = "Note cannot exceed 2000 characters"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct AdmissionQuery {
+    #[serde(default)]
+    #[validate(custom = "AdmissionStatus::validate")]
+    pub status: Option<AdmissionStatus>,
+    pub ward_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BedTransferResponse {
+    pub from_bed_id: String,
+    pub to_bed_id: String,
+    pub transferred_by: String,
+    pub transferred_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdmissionResponse {
+    pub id: String,
+    pub medical_record_id: String,
+    pub bed_id: String,
+    pub ward_id: String,
+    pub status: AdmissionStatus,
+    pub admitted_by: String,
+    pub admitted_at: String,
+    pub transfers: Vec<BedTransferResponse>,
+    pub discharged_by: Option<String>,
+    pub discharged_at: Option<String>,
+    pub discharge_note: Option<String>,
+}
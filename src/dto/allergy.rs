@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::allergy::{AllergyCheckMode, AllergyConflict};
+use crate::status::AllergySeverity;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateAllergyRequest {
+    /// Master catalog code of the substance, as in `Medicine.master_medicine_id`
+    #[validate(length(min = 1, message = "Substance code is required"))]
+    pub substance_code: String,
+    #[validate(length(min = 1, message = "Substance display is required"))]
+    pub substance_display: String,
+    #[validate(custom = "AllergySeverity::validate")]
+    pub severity: AllergySeverity,
+    #[validate(length(max = 1000, message = "Reaction cannot exceed 1000 characters"))]
+    pub reaction: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateAllergyRequest {
+    #[validate(length(min = 1, message = "Substance code cannot be empty"))]
+    pub substance_code: Option<String>,
+    #[validate(length(min = 1, message = "Substance display cannot be empty"))]
+    pub substance_display: Option<String>,
+    #[validate(custom = "AllergySeverity::validate")]
+    pub severity: Option<AllergySeverity>,
+    #[validate(length(max = 1000, message = "Reaction cannot exceed 1000 characters"))]
+    pub reaction: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AllergyResponse {
+    pub id: String,
+    pub patient_id: String,
+    pub substance_code: String,
+    pub substance_display: String,
+    pub severity: AllergySeverity,
+    pub reaction: Option<String>,
+    pub recorded_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// Medicines about to be prescribed to the patient
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct AllergyCheckRequest {
+    #[validate(length(min = 1, message = "At least one medicine ID is required"))]
+    pub medicine_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AllergyCheckResponse {
+    pub mode: AllergyCheckMode,
+    pub conflicts: Vec<AllergyConflict>,
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
+use crate::dto::common::{validate_date, Links};
+use crate::repository::appointment::Expansion;
+use crate::status::AppointmentStatus;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateAppointmentRequest {
@@ -11,8 +14,15 @@ pub struct CreateAppointmentRequest {
     pub date: String,
     #[validate(length(min = 1, message = "Time is required"))]
     pub time: String,
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: String,
+    #[validate(custom = "AppointmentStatus::validate")]
+    pub status: AppointmentStatus,
+    /// `in_person` (default) or `virtual`; virtual appointments get a teleconsult session
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Clinic the visit is at; `date` and `time` are in its time zone
+    #[serde(default)]
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -30,8 +40,13 @@ pub struct UpdateAppointmentRequest {
     #[validate(length(min = 1, message = "Time is required"))]
     pub time: Option<String>,
     #[serde(default)]
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: Option<String>,
+    #[validate(custom = "AppointmentStatus::validate")]
+    pub status: Option<AppointmentStatus>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,5 +56,295 @@ pub struct AppointmentResponse {
     pub doctor_id: String,
     pub date: String,
     pub time: String,
+    pub status: AppointmentStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_number: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_in_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub called_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// RFC 3339 with the clinic's offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reminder_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Present with `expand=doctor`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doctor: Option<DoctorSummary>,
+    /// Present with `expand=patient`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patient: Option<PatientSummary>,
+    /// Present with `?links=true`
+    #[serde(rename = "_links", default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DoctorSummary {
+    pub id: String,
+    pub name: String,
+    pub specialization: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PatientSummary {
+    pub id: String,
+    pub name: String,
+    pub nrme: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AvailabilityQuery {
+    #[validate(length(min = 24, max = 24, message = "Doctor IDs must be 24 characters"))]
+    pub doctor_id: String,
+    /// `YYYY-MM-DD` in the clinic's zone
+    pub date: String,
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailabilitySlot {
+    /// `HH:MM` clinic time, as appointments store it
+    pub time: String,
+    pub starts_at: String,
+    pub available: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AvailabilityResponse {
+    pub doctor_id: String,
+    pub date: String,
+    pub timezone: String,
+    pub utc_offset: String,
+    pub slots: Vec<AvailabilitySlot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeleconsultSessionResponse {
+    pub id: String,
+    pub appointment_id: String,
+    pub provider: String,
+    pub room: String,
+    pub meeting_url: String,
+    pub token: Option<String>,
+    pub token_expires_at: Option<String>,
     pub status: String,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueEntry {
+    pub appointment_id: String,
+    pub patient_id: String,
+    pub queue_number: i64,
+    pub status: AppointmentStatus,
+    pub time: String,
+    pub checked_in_at: Option<String>,
+    pub called_at: Option<String>,
+}
+
+/// Waiting-room display data for one doctor's day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueBoard {
+    pub doctor_id: String,
+    pub date: String,
+    /// Most recently called patient
+    pub current: Option<QueueEntry>,
+    pub waiting: Vec<QueueEntry>,
+    /// Checked-in patients that have already been called
+    pub served: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateAppointmentSeriesRequest {
+    #[validate(length(min = 24, max = 24, message = "Patient IDs must be 24 characters"))]
+    pub patient_id: String,
+    #[validate(length(min = 24, max = 24, message = "Doctor IDs must be 24 characters"))]
+    pub doctor_id: String,
+    /// e.g. `FREQ=WEEKLY;BYDAY=MO,TH;COUNT=12`
+    #[validate(length(min = 1, message = "Recurrence rule is required"))]
+    pub rrule: String,
+    /// First possible occurrence, `YYYY-MM-DD`
+    #[validate(length(equal = 10, message = "Start date must be YYYY-MM-DD"))]
+    pub start_date: String,
+    #[validate(length(min = 1, message = "Time is required"))]
+    pub time: String,
+    /// Last date to generate appointments for; defaults to 90 days after the start
+    #[validate(length(equal = 10, message = "Horizon date must be YYYY-MM-DD"))]
+    pub horizon_date: Option<String>,
+}
+
+/// Which occurrences a series-level change applies to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesScope {
+    /// Only the occurrence on `occurrence_date`
+    This,
+    /// The occurrence on `occurrence_date` and every later one
+    Future,
+    All,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateAppointmentSeriesRequest {
+    pub scope: SeriesScope,
+    #[validate(length(equal = 10, message = "Occurrence date must be YYYY-MM-DD"))]
+    pub occurrence_date: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 24, max = 24, message = "Doctor IDs must be 24 characters"))]
+    pub doctor_id: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Time is required"))]
+    pub time: Option<String>,
+    /// New recurrence for `future` or `all`; the affected occurrences are regenerated
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Recurrence rule is required"))]
+    pub rrule: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CancelAppointmentSeriesRequest {
+    pub scope: SeriesScope,
+    #[validate(length(equal = 10, message = "Occurrence date must be YYYY-MM-DD"))]
+    pub occurrence_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppointmentSeriesResponse {
+    pub id: String,
+    pub patient_id: String,
+    pub doctor_id: String,
+    pub rrule: String,
+    pub start_date: String,
+    pub time: String,
+    pub horizon_date: String,
+    pub status: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub appointments: Vec<AppointmentResponse>,
+}
+
+/// Query of `GET /public/doctors/:id/slots`
+#[derive(Debug, Deserialize, Validate)]
+pub struct PublicSlotsQuery {
+    /// `YYYY-MM-DD` in the clinic's zone
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub date: String,
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
+}
+
+/// Body of `POST /public/appointments`. Without `otp` the captcha is checked and a code is
+/// sent to `phone`; the same body with `otp` books the slot.
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct PublicBookingRequest {
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: String,
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: String,
+    #[validate(length(min = 24, max = 24, message = "Doctor IDs must be 24 characters"))]
+    pub doctor_id: String,
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub date: String,
+    #[validate(length(min = 1, message = "Time is required"))]
+    pub time: String,
+    #[serde(default)]
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
+    /// hCaptcha or Turnstile widget token; required when requesting a code
+    #[serde(default)]
+    pub captcha_token: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 1, message = "OTP cannot be empty"))]
+    pub otp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PublicBookingResponse {
+    pub appointment_id: String,
+    pub doctor: DoctorSummary,
+    pub date: String,
+    pub time: String,
+    pub starts_at: Option<String>,
+    pub status: AppointmentStatus,
+}
+
+/// Filters of `GET /appointments`, next to the pagination parameters
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct AppointmentListQuery {
+    pub patient_id: Option<String>,
+    #[validate(custom = "AppointmentStatus::validate")]
+    pub status: Option<AppointmentStatus>,
+    /// Comma-separated references to embed: `doctor`, `patient`
+    #[validate(custom = "validate_expand")]
+    pub expand: Option<String>,
+}
+
+impl AppointmentListQuery {
+    pub fn expansion(&self) -> Expansion {
+        self.expand.as_deref().and_then(|raw| parse_expand(raw).ok()).unwrap_or_default()
+    }
+}
+
+fn parse_expand(raw: &str) -> Result<Expansion, String> {
+    let mut expansion = Expansion::default();
+    for name in raw.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match name {
+            "doctor" => expansion.doctor = true,
+            "patient" => expansion.patient = true,
+            other => return Err(format!("Cannot expand '{}'; use doctor, patient", other)),
+        }
+    }
+    Ok(expansion)
+}
+
+fn validate_expand(raw: &str) -> Result<(), ValidationError> {
+    parse_expand(raw).map(|_| ()).map_err(|message| {
+        let mut error = ValidationError::new("unknown_expansion");
+        error.message = Some(message.into());
+        error
+    })
+}
+
+/// Period of `GET /stats/doctors/:id/appointments`; the 30 days up to today by default
+#[derive(Debug, Deserialize, Validate)]
+pub struct DoctorStatsQuery {
+    /// Inclusive start date, `YYYY-MM-DD`
+    #[validate(custom = "validate_date")]
+    pub from: Option<String>,
+    /// Inclusive end date, `YYYY-MM-DD`
+    #[validate(custom = "validate_date")]
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DoctorAppointmentStats {
+    pub doctor_id: String,
+    pub from: String,
+    pub to: String,
+    pub total: i64,
+    pub completed: i64,
+    pub cancelled: i64,
+    pub no_show: i64,
+    /// No-shows over appointments that reached an outcome (completed or no-show)
+    pub no_show_rate: Option<f64>,
+    /// Minutes from being called in to completion, over completed appointments with both times
+    pub average_consult_minutes: Option<f64>,
+    pub timed_consults: i64,
 }
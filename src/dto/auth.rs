@@ -9,6 +9,10 @@ pub struct RegisterRequest {
     pub password: String,
     #[validate(length(min = 1, message = "Name is required"))]
     pub name: String,
+    /// Stored in E.164 form, see `crate::phone`
+    #[serde(default)]
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -82,3 +86,95 @@ pub struct ResetPasswordResponse {
     pub success: bool,
     pub message: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct PatientOtpRequest {
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct PatientLoginRequest {
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: String,
+    #[validate(length(min = 1, message = "OTP is required"))]
+    pub otp: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PatientLoginResponse {
+    pub patient_id: String,
+    pub name: String,
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OtpRequestResponse {
+    pub success: bool,
+    pub message: String,
+    pub expires_in: i64,
+}
+
+/// Body of POST /auth/otp/request: `nik` for `patient_login`, `phone` for `phone_verification`
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct OtpRequest {
+    #[validate(length(min = 1, message = "Purpose is required"))]
+    pub purpose: String,
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: Option<String>,
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct OtpVerifyRequest {
+    #[validate(length(min = 1, message = "Purpose is required"))]
+    pub purpose: String,
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: Option<String>,
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: Option<String>,
+    #[validate(length(min = 1, message = "Code is required"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PhoneVerificationResponse {
+    pub phone: String,
+    pub verified_at: String,
+    /// Medical records using this phone number, now marked verified
+    pub records_updated: u64,
+}
+
+/// Outcome of POST /auth/otp/verify, depending on the code's purpose
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum OtpVerification {
+    PatientLogin(PatientLoginResponse),
+    Phone(PhoneVerificationResponse),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyEmailQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct ResendVerificationRequest {
+    #[validate(email(message = "Invalid email format"))]
+    pub email: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailVerificationResponse {
+    pub email: String,
+    pub email_verified_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResendVerificationResponse {
+    pub success: bool,
+    pub message: String,
+}
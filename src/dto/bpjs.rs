@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::bpjs::{Participant, Sep};
+
+/// BPJS card numbers are 13 digits
+pub(crate) fn validate_card_number(value: &str) -> Result<(), ValidationError> {
+    if value.len() == 13 && value.bytes().all(|b| b.is_ascii_digit()) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("card_number");
+    error.message = Some("Card number must be 13 digits".into());
+    Err(error)
+}
+
+fn validate_care_type(value: &str) -> Result<(), ValidationError> {
+    if matches!(value, "inpatient" | "outpatient") {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("care_type");
+    error.message = Some("Care type must be inpatient or outpatient".into());
+    Err(error)
+}
+
+/// Eligibility on `date`, the clinic's today by default
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ParticipantQuery {
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateSepRequest {
+    #[validate(custom = "validate_card_number")]
+    pub card_number: String,
+    /// The clinic's today by default
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub service_date: Option<String>,
+    #[validate(custom = "validate_care_type")]
+    pub care_type: String,
+    /// Care class `1` to `3`; the participant's entitled class by default
+    #[validate(length(min = 1, max = 1, message = "Class must be 1, 2 or 3"))]
+    pub class: Option<String>,
+    #[validate(length(min = 1, max = 20, message = "Medical record number must be between 1 and 20 characters"))]
+    pub medical_record_number: String,
+    #[validate(length(min = 3, max = 10, message = "Diagnosis code must be an ICD-10 code"))]
+    pub diagnosis_code: String,
+    #[validate(length(min = 1, max = 10, message = "Poli code must be between 1 and 10 characters"))]
+    pub poli_code: String,
+    #[validate(length(min = 1, max = 30, message = "Referral number must be between 1 and 30 characters"))]
+    pub referral_number: Option<String>,
+    #[validate(length(min = 1, max = 20, message = "Doctor code must be between 1 and 20 characters"))]
+    pub doctor_code: String,
+    #[validate(length(min = 6, max = 15, message = "Phone must be between 6 and 15 characters"))]
+    pub phone: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EligibilityResponse {
+    pub participant: Participant,
+    pub date: String,
+    /// Whether the answer came from the cache rather than BPJS
+    pub cached: bool,
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SepResponse {
+    pub sep: Sep,
+    pub participant: Participant,
+    pub source: String,
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::models::ChildCode;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateChildCodeRequest {
@@ -24,6 +25,45 @@ pub struct UpdateChildCodeRequest {
     pub norut: Option<i32>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct BulkChildCodeItem {
+    #[validate(length(min = 24, max = 24, message = "Child Code ID must be 24 characters"))]
+    pub child_code_id: String,
+    #[validate(range(min = 1, message = "Sequence number (norut) must be positive"))]
+    pub norut: i32,
+}
+
+/// Links many codes under the parent of the path
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct BulkLinkChildCodesRequest {
+    /// Each is checked on its own before anything is inserted
+    #[validate(length(min = 1, max = 500, message = "Between 1 and 500 children per request"))]
+    pub children: Vec<BulkChildCodeItem>,
+    /// Link nothing when any child fails its checks
+    #[serde(default)]
+    pub all_or_nothing: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BulkChildCodeResult {
+    /// Position in `children`
+    pub index: usize,
+    /// `created`, `invalid`, `not_found`, `duplicate` or, when `all_or_nothing` stopped the
+    /// request, `skipped`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub child_code: Option<ChildCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BulkChildCodeResponse {
+    pub created: usize,
+    pub failed: usize,
+    pub results: Vec<BulkChildCodeResult>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ParentCodeEmbedDto {
     pub code_id: String,
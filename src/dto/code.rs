@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::i18n::{validate_translations, Translations};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Validate)]
 pub struct CodeCategoryEmbedDto {
@@ -17,10 +18,13 @@ pub struct CreateCodeDto {
     pub code: String,
     #[validate(length(min = 1, message = "Display cannot be empty"))]
     pub display: String,
+    /// Translations of `display` by language (`id`, `en`)
+    #[validate(custom = "validate_translations")]
+    pub display_i18n: Option<Translations>,
     #[validate(length(min = 1, message = "System cannot be empty"))]
     pub system: String,
     #[validate(length(min = 24, max = 24, message = "Category ID must be a valid ObjectId (24 chars)"))]
-    #[serde(rename = "categoryId")]
+    #[serde(alias = "categoryId")]
     pub category_id: String,
 }
 
@@ -28,7 +32,53 @@ pub struct CreateCodeDto {
 pub struct UpdateCodeDto {
     pub code: Option<String>,
     pub display: Option<String>,
+    /// Replaces all translations of `display`
+    #[validate(custom = "validate_translations")]
+    pub display_i18n: Option<Translations>,
     pub system: Option<String>,
     #[validate]
     pub category: Option<CodeCategoryEmbedDto>,
+    pub active: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct ImportCodesDto {
+    /// `loinc` or `snomed`
+    #[validate(length(min = 1, message = "Terminology cannot be empty"))]
+    pub terminology: String,
+    /// `csv` (comma or tab separated) or `json`
+    #[validate(length(min = 1, message = "Format cannot be empty"))]
+    pub format: String,
+    /// Raw export contents
+    #[validate(length(min = 1, message = "Content cannot be empty"))]
+    pub content: String,
+    #[validate(length(min = 24, max = 24, message = "Category ID must be a valid ObjectId (24 chars)"))]
+    #[serde(alias = "categoryId")]
+    pub category_id: String,
+    /// Overwrite display and category of codes that already exist instead of skipping them
+    #[serde(alias = "updateExisting", default)]
+    pub update_existing: bool,
+}
+
+/// One entry of `POST /codes/validate`
+#[derive(Serialize, Deserialize, Debug, Clone, Validate)]
+pub struct CodeRefDto {
+    #[validate(length(min = 1, message = "System cannot be empty"))]
+    pub system: String,
+    #[validate(length(min = 1, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+/// Whether a `{system, code}` pair is known, in request order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CodeValidation {
+    pub system: String,
+    pub code: String,
+    pub exists: bool,
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// In the request's language, see `crate::i18n`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
 }
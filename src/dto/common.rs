@@ -1,4 +1,37 @@
-use serde::Serialize;
+use std::collections::BTreeMap;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::ValidationError;
+
+/// `?view=` of list endpoints: `summary` returns a projection of the listed documents
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum View {
+    Summary,
+    #[default]
+    Full,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ViewQuery {
+    #[serde(default)]
+    pub view: View,
+}
+
+/// `?links=true` adds `_links` to the resources of a response
+#[derive(Debug, Deserialize, Default)]
+pub struct LinksQuery {
+    #[serde(default)]
+    pub links: bool,
+}
+
+/// HAL-style `_links` of a resource, keyed by relation (`self`, `appointments`, ...)
+pub type Links = BTreeMap<String, Link>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Link {
+    pub href: String,
+}
 
 #[derive(Debug, Serialize)]
 pub struct DeleteResponse {
@@ -19,3 +52,13 @@ pub struct BulkDeleteResponse {
     pub deleted_ids: Vec<String>,
     pub failed_ids: Vec<String>,
 }
+
+/// `#[validate(custom = ..)]` check for `YYYY-MM-DD` fields
+pub(crate) fn validate_date(value: &str) -> Result<(), ValidationError> {
+    if NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").is_ok() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("date");
+    error.message = Some("Date must be YYYY-MM-DD".into());
+    Err(error)
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use chrono::NaiveDate;
+use crate::dto::common::validate_date;
+use crate::dto::insurance::InsuranceTermsDto;
+use crate::status::CorporateClientStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[validate(schema(function = "validate_contract_period"))]
+pub struct CorporateContractDto {
+    #[validate(length(min = 1, max = 100, message = "Contract number must be between 1 and 100 characters"))]
+    pub number: String,
+    #[validate(custom = "validate_date")]
+    pub start_date: String,
+    /// Inclusive; open-ended when absent
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+    /// Applied to what is left of an invoice after any insurance claim
+    #[validate]
+    pub terms: InsuranceTermsDto,
+    #[serde(default = "default_payment_term_days")]
+    #[validate(range(max = 365, message = "Payment term cannot exceed 365 days"))]
+    pub payment_term_days: u32,
+}
+
+fn default_payment_term_days() -> u32 {
+    30
+}
+
+fn validate_contract_period(contract: &CorporateContractDto) -> Result<(), ValidationError> {
+    match &contract.end_date {
+        Some(end) if end.trim() < contract.start_date.trim() => {
+            let mut error = ValidationError::new("contract_period");
+            error.message = Some("Contract end date cannot be before its start date".into());
+            Err(error)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateCorporateClientRequest {
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: String,
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: String,
+    #[validate(email(message = "Invalid email format"))]
+    pub billing_email: Option<String>,
+    #[validate]
+    pub contract: CorporateContractDto,
+    #[serde(default)]
+    #[validate(custom = "CorporateClientStatus::validate")]
+    pub status: Option<CorporateClientStatus>,
+}
+
+/// A new contract replaces the current one for invoices created from then on
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateCorporateClientRequest {
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: Option<String>,
+    #[validate(email(message = "Invalid email format"))]
+    pub billing_email: Option<String>,
+    #[validate]
+    pub contract: Option<CorporateContractDto>,
+    #[serde(default)]
+    #[validate(custom = "CorporateClientStatus::validate")]
+    pub status: Option<CorporateClientStatus>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct CorporateClientQuery {
+    pub organization_id: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "CorporateClientStatus::validate")]
+    pub status: Option<CorporateClientStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateContractResponse {
+    pub number: String,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub terms: InsuranceTermsDto,
+    pub payment_term_days: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateClientResponse {
+    pub id: String,
+    pub organization_id: String,
+    pub code: String,
+    pub name: String,
+    pub billing_email: Option<String>,
+    pub contract: CorporateContractResponse,
+    pub status: CorporateClientStatus,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// Puts a patient on the roster; their invoices served from `start_date` on are billed to the client
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct AddCorporateEmployeeRequest {
+    #[validate(length(min = 24, max = 24, message = "Medical record IDs must be 24 characters"))]
+    pub medical_record_id: String,
+    #[validate(length(min = 1, max = 50, message = "Employee number must be between 1 and 50 characters"))]
+    pub employee_number: String,
+    /// Defaults to today in the clinic timezone
+    #[validate(custom = "validate_date")]
+    pub start_date: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+}
+
+/// Set `end_date` when an employee leaves; the entry stays for the statements that list it
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateCorporateEmployeeRequest {
+    #[validate(length(min = 1, max = 50, message = "Employee number must be between 1 and 50 characters"))]
+    pub employee_number: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateEmployeeResponse {
+    pub id: String,
+    pub client_id: String,
+    pub medical_record_id: String,
+    pub employee_number: String,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// `#[validate(custom = ..)]` check for `YYYY-MM` fields
+fn validate_month(value: &str) -> Result<(), ValidationError> {
+    if value.trim().len() == 7 && NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d").is_ok() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("month");
+    error.message = Some("Month must be YYYY-MM".into());
+    Err(error)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct GenerateStatementRequest {
+    /// `YYYY-MM`
+    #[validate(custom = "validate_month")]
+    pub month: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorporateStatementLineResponse {
+    pub invoice_id: String,
+    pub invoice_number: Option<String>,
+    pub medical_record_id: String,
+    pub employee_number: String,
+    pub service_date: String,
+    pub total: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateStatementResponse {
+    pub id: String,
+    pub number: String,
+    pub client_id: String,
+    pub organization_id: String,
+    pub month: String,
+    pub lines: Vec<CorporateStatementLineResponse>,
+    pub total: f64,
+    pub due_date: String,
+    pub generated_by: String,
+    pub generated_at: String,
+}
@@ -0,0 +1,32 @@
+use serde::Serialize;
+
+/// A source whose copies are stale
+#[derive(Debug, Serialize, Clone)]
+pub struct StaleSource {
+    pub source_id: String,
+    pub documents: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DenormalizationTarget {
+    /// Collection holding the copies, e.g. `user_roles`
+    pub collection: String,
+    /// Collection the copies come from, e.g. `users`
+    pub source: String,
+    pub sources_checked: u64,
+    /// Sources that no longer exist; their copies are left as they are
+    pub sources_missing: u64,
+    pub sources_stale: u64,
+    /// Stale documents found, or refreshed outside a dry run
+    pub stale_documents: u64,
+    /// First stale sources
+    pub samples: Vec<StaleSource>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DenormalizationReport {
+    pub dry_run: bool,
+    pub started_at: String,
+    pub finished_at: String,
+    pub targets: Vec<DenormalizationTarget>,
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::status::DoctorStatus;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateDoctorRequest {
@@ -11,8 +12,8 @@ pub struct CreateDoctorRequest {
     pub sip: String,
     #[validate(length(min = 1, message = "Specialization is required"))]
     pub specialization: String,
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: String,
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: DoctorStatus,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -30,8 +31,8 @@ pub struct UpdateDoctorRequest {
     #[validate(length(min = 1, message = "Specialization is required"))]
     pub specialization: Option<String>,
     #[serde(default)]
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: Option<String>,
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: Option<DoctorStatus>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,5 +42,14 @@ pub struct DoctorResponse {
     pub nip: String,
     pub sip: String,
     pub specialization: String,
-    pub status: String,
+    pub status: DoctorStatus,
+    pub rating_average: Option<f64>,
+    pub rating_count: i64,
+}
+
+/// Filters of `GET /doctors`, next to the pagination parameters
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct DoctorListQuery {
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: Option<DoctorStatus>,
 }
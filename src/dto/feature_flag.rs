@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateFeatureFlagRequest {
+    /// Lowercase letters, digits, `_`, `-` and `.`, e.g. `new_billing`
+    #[validate(length(min = 1, max = 100, message = "Key must be between 1 and 100 characters"))]
+    pub key: String,
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub organizations: Vec<String>,
+    /// Defaults to 100, i.e. everyone once enabled
+    #[validate(range(min = 0, max = 100, message = "Rollout percentage must be between 0 and 100"))]
+    pub rollout_percentage: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateFeatureFlagRequest {
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    pub organizations: Option<Vec<String>>,
+    #[validate(range(min = 0, max = 100, message = "Rollout percentage must be between 0 and 100"))]
+    pub rollout_percentage: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureFlagResponse {
+    pub id: String,
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub organizations: Vec<String>,
+    pub rollout_percentage: i32,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
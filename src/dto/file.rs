@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::models::StorageReconciliation;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileResponse {
@@ -11,6 +13,91 @@ pub struct FileResponse {
     pub path: String,
     pub url: String,
     pub uploader: String,
-    #[serde(rename = "createdAt")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medical_record_id: Option<String>,
+    pub version: i32,
     pub created_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileVersionResponse {
+    pub version: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub extension: String,
+    pub size: u64,
+    pub uploader: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<i32>,
+    /// Whether this is the file's current content
+    pub current: bool,
+    /// Downloads this version's content
+    pub content_url: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Validate)]
+pub struct CreateFileShareRequest {
+    /// Defaults to one day; at most seven days
+    #[validate(range(min = 1, max = 10080, message = "Expiry must be between 1 minute and 7 days"))]
+    pub expires_in_minutes: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileShareResponse {
+    pub id: String,
+    pub file_id: String,
+    /// Only returned when the share is created
+    pub token: String,
+    /// Public link that downloads the file without a login
+    pub url: String,
+    pub expires_at: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// Data of a 413 for an upload over quota
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuotaExceeded {
+    /// `user` or `organization`
+    pub scope: String,
+    pub owner_id: String,
+    pub limit_bytes: u64,
+    pub used_bytes: u64,
+    pub remaining_bytes: u64,
+    pub requested_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageUsageResponse {
+    pub scope: String,
+    pub owner_id: String,
+    pub bytes: u64,
+    pub files: u64,
+    /// Absent when the scope has no quota
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_bytes: Option<u64>,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StorageReconciliationStatus {
+    /// `delete`, `report` or `off`
+    pub mode: String,
+    /// Local hour at which the nightly run starts
+    pub run_hour: u32,
+    pub grace_hours: i64,
+    pub prefixes: Vec<String>,
+    pub last_run: Option<StorageReconciliation>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StorageUsageQuery {
+    /// `user` or `organization`; both when absent
+    pub scope: Option<String>,
 }
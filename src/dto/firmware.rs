@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateFirmwareRequest {
+    #[validate(length(min = 1, message = "Version is required"))]
+    pub version: String,
+    #[validate(length(equal = 64, message = "Checksum must be a hex-encoded SHA-256 (64 chars)"))]
+    pub checksum: String,
+    #[validate(length(min = 1, message = "Artifact key is required"))]
+    pub artifact_key: String,
+    #[validate(length(min = 1, message = "Artifact URL is required"))]
+    pub artifact_url: String,
+    #[serde(default)]
+    pub kit_models: Vec<String>,
+    pub release_notes: Option<String>,
+    #[serde(default)]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateFirmwareRequest {
+    pub version: Option<String>,
+    #[validate(length(equal = 64, message = "Checksum must be a hex-encoded SHA-256 (64 chars)"))]
+    pub checksum: Option<String>,
+    pub artifact_key: Option<String>,
+    pub artifact_url: Option<String>,
+    pub kit_models: Option<Vec<String>>,
+    pub release_notes: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FirmwareResponse {
+    pub id: String,
+    pub version: String,
+    pub checksum: String,
+    pub artifact_key: String,
+    pub artifact_url: String,
+    pub kit_models: Vec<String>,
+    pub release_notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// Answer to a kit asking whether it should update.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LatestFirmwareResponse {
+    pub kit_code: String,
+    pub kit_model: Option<String>,
+    pub current_version: Option<String>,
+    pub latest: Option<FirmwareResponse>,
+    pub update_available: bool,
+}
@@ -1,5 +1,32 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::dto::common::validate_date;
+use crate::models::InsuranceTerms;
+use crate::status::InsuranceStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Validate)]
+pub struct InsuranceTermsDto {
+    /// Share of the invoice total the insurer pays
+    #[validate(range(min = 0.0, max = 100.0, message = "Coverage must be between 0 and 100 percent"))]
+    pub coverage_percent: f64,
+    #[serde(default)]
+    #[validate(range(min = 0.0, message = "Copay cannot be negative"))]
+    pub copay: f64,
+    #[validate(range(min = 0.0, message = "Maximum claim cannot be negative"))]
+    pub max_claim: Option<f64>,
+}
+
+impl From<InsuranceTermsDto> for InsuranceTerms {
+    fn from(dto: InsuranceTermsDto) -> Self {
+        Self { coverage_percent: dto.coverage_percent, copay: dto.copay, max_claim: dto.max_claim }
+    }
+}
+
+impl From<InsuranceTerms> for InsuranceTermsDto {
+    fn from(terms: InsuranceTerms) -> Self {
+        Self { coverage_percent: terms.coverage_percent, copay: terms.copay, max_claim: terms.max_claim }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateInsuranceRequest {
@@ -10,10 +37,19 @@ pub struct CreateInsuranceRequest {
     pub insurance_type: String,
     #[validate(length(min = 1, message = "Code is required"))]
     pub code: String,
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: String,
+    #[validate(custom = "InsuranceStatus::validate")]
+    pub status: InsuranceStatus,
+    /// Without terms the insurance covers nothing
+    #[serde(default)]
+    #[validate]
+    pub terms: Option<InsuranceTermsDto>,
+    /// Defaults to today in the clinic timezone
+    #[validate(custom = "validate_date")]
+    pub effective_from: Option<String>,
 }
 
+/// Every change is a new version in effect from `effective_from` (default today), which may
+/// not be before the current version's
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct UpdateInsuranceRequest {
     #[serde(default)]
@@ -26,8 +62,13 @@ pub struct UpdateInsuranceRequest {
     #[validate(length(min = 1, message = "Code is required"))]
     pub code: Option<String>,
     #[serde(default)]
-    #[validate(length(min = 1, message = "Status is required"))]
-    pub status: Option<String>,
+    #[validate(custom = "InsuranceStatus::validate")]
+    pub status: Option<InsuranceStatus>,
+    #[serde(default)]
+    #[validate]
+    pub terms: Option<InsuranceTermsDto>,
+    #[validate(custom = "validate_date")]
+    pub effective_from: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -37,5 +78,32 @@ pub struct InsuranceResponse {
     #[serde(rename = "type")]
     pub insurance_type: String,
     pub code: String,
-    pub status: String,
+    pub status: InsuranceStatus,
+    pub terms: InsuranceTermsDto,
+    pub version: i32,
+    pub effective_from: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsuranceVersionResponse {
+    pub insurance_id: String,
+    pub version: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub insurance_type: String,
+    pub code: String,
+    pub status: InsuranceStatus,
+    pub terms: InsuranceTermsDto,
+    pub effective_from: String,
+    /// Exclusive; `None` on the latest version
+    pub effective_until: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: String,
+}
+
+/// Filters of `GET /insurances`, next to the pagination parameters
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct InsuranceListQuery {
+    #[validate(custom = "InsuranceStatus::validate")]
+    pub status: Option<InsuranceStatus>,
 }
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::i18n::{validate_translations, Translations};
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct InterpretationCodingDto {
@@ -19,6 +20,9 @@ pub struct CreateInterpretationRequest {
     pub max: f64,
     pub coding: InterpretationCodingDto,
     pub text: Option<String>,
+    /// Translations of `text` by language (`id`, `en`)
+    #[validate(custom = "validate_translations")]
+    pub display_i18n: Option<Translations>,
     pub created_at: Option<String>,
 }
 
@@ -29,6 +33,9 @@ pub struct UpdateInterpretationRequest {
     pub max: Option<f64>,
     pub coding: Option<InterpretationCodingDto>,
     pub text: Option<String>,
+    /// Replaces all translations of `text`
+    #[validate(custom = "validate_translations")]
+    pub display_i18n: Option<Translations>,
     pub created_at: Option<String>,
 }
 
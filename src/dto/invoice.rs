@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::dto::price_list::QuoteItemDto;
+use crate::status::{InvoiceStatus, PriceItemType};
+
+/// Lines are priced from the organization's price list in effect on `service_date`
+/// (default today), claimed from `insurance_id` under its terms on that day, and billed to
+/// the patient's employer when a corporate client has them on its roster then.
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateInvoiceRequest {
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: String,
+    #[validate(length(min = 24, max = 24, message = "Medical record IDs must be 24 characters"))]
+    pub medical_record_id: String,
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub service_date: Option<String>,
+    #[validate(length(min = 1, message = "At least one item is required"))]
+    #[validate]
+    pub items: Vec<QuoteItemDto>,
+    /// Claimed under the version of the insurance in effect on the service date
+    #[validate(length(min = 24, max = 24, message = "Insurance IDs must be 24 characters"))]
+    pub insurance_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct InvoiceQuery {
+    pub medical_record_id: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "InvoiceStatus::validate")]
+    pub status: Option<InvoiceStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceLineResponse {
+    pub item_type: PriceItemType,
+    pub item_id: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceClaimResponse {
+    pub insurance_id: String,
+    pub insurance_version: i32,
+    pub covered_amount: f64,
+    pub patient_amount: f64,
+}
+
+/// Billed to the patient's employer, found on its corporate client's roster
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceCorporateResponse {
+    pub client_id: String,
+    pub employee_number: String,
+    pub covered_amount: f64,
+    pub patient_amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvoiceResponse {
+    pub id: String,
+    pub number: Option<String>,
+    pub organization_id: String,
+    pub medical_record_id: String,
+    pub service_date: String,
+    pub price_list_id: String,
+    pub price_list_version: i32,
+    pub lines: Vec<InvoiceLineResponse>,
+    pub total: f64,
+    pub paid_amount: f64,
+    pub outstanding: f64,
+    pub claim: Option<InvoiceClaimResponse>,
+    pub corporate: Option<InvoiceCorporateResponse>,
+    pub status: InvoiceStatus,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::services::job_service::JOB_STATUSES;
+
+fn validate_job_status(value: &str) -> Result<(), ValidationError> {
+    if JOB_STATUSES.contains(&value) {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("status");
+    error.message = Some(format!("Status must be one of {}", JOB_STATUSES.join(", ")).into());
+    Err(error)
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct JobQuery {
+    #[validate(custom = "validate_job_status")]
+    pub status: Option<String>,
+    pub job_type: Option<String>,
+}
+
+/// Retry the failed jobs of one type
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct RetryJobsRequest {
+    #[validate(length(min = 1, max = 100, message = "Job type must be between 1 and 100 characters"))]
+    pub job_type: String,
+    /// Oldest failed jobs first; 100 by default
+    #[validate(range(min = 1, max = 500, message = "Limit must be between 1 and 500"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetryJobsResponse {
+    pub job_type: String,
+    pub retried: usize,
+    pub job_ids: Vec<String>,
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::status::KioskStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateKioskDeviceRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+    /// Clinic the kiosk stands in
+    #[serde(default)]
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: Option<String>,
+}
+
+/// Body of `POST /auth/kiosk/pair`, sent by the kiosk itself
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct KioskPairRequest {
+    #[validate(length(min = 1, max = 32, message = "Pairing code must be between 1 and 32 characters"))]
+    pub pairing_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KioskDeviceResponse {
+    pub id: String,
+    pub name: String,
+    pub organization_id: Option<String>,
+    pub status: KioskStatus,
+    pub pairing_expires_at: Option<String>,
+    pub paired_at: Option<String>,
+    pub revoked_at: Option<String>,
+    pub last_seen_at: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+/// Returned when a pairing code is issued, the only time it is visible
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KioskPairingCodeResponse {
+    #[serde(flatten)]
+    pub device: KioskDeviceResponse,
+    pub pairing_code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KioskTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub device: KioskDeviceResponse,
+}
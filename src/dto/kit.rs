@@ -50,6 +50,10 @@ pub struct CreateKitRequest {
     pub order_id: String,
     #[validate]
     pub pasien: KitPasienDto,
+    pub model: Option<String>,
+    /// Where the kit is deployed; given together with `longitude`
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -63,6 +67,9 @@ pub struct UpdateKitRequest {
     pub log_user_kit_id: Option<String>,
     pub order_id: Option<String>,
     pub pasien: Option<KitPasienDto>,
+    pub model: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -77,6 +84,145 @@ pub struct KitResponse {
     pub log_user_kit_id: String,
     pub order_id: String,
     pub pasien: KitPasienDto,
+    pub model: Option<String>,
+    pub firmware_version: Option<String>,
+    pub last_heartbeat_at: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
     pub created_at: String,
     pub updated_at: Option<String>,
 }
+
+/// List item of `GET /kits?view=summary`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KitSummary {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    pub is_active: bool,
+    pub model: Option<String>,
+    pub firmware_version: Option<String>,
+    pub last_heartbeat_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct KitHeartbeatRequest {
+    #[validate(length(min = 1, message = "Firmware version is required"))]
+    pub firmware_version: String,
+    pub model: Option<String>,
+}
+
+/// `GET /kits/nearby`, nearest first
+#[derive(Debug, Deserialize, Validate)]
+pub struct NearbyKitsQuery {
+    #[validate(range(min = -90.0, max = 90.0, message = "lat must be between -90 and 90"))]
+    pub lat: f64,
+    #[validate(range(min = -180.0, max = 180.0, message = "lng must be between -180 and 180"))]
+    pub lng: f64,
+    /// Meters; 10 km by default
+    #[validate(range(min = 1.0, max = 500000.0, message = "radius must be between 1 and 500000 meters"))]
+    pub radius: Option<f64>,
+    #[validate(range(min = 1, max = 500, message = "limit must be between 1 and 500"))]
+    pub limit: Option<i64>,
+    /// Distributor code
+    pub distributor: Option<String>,
+}
+
+/// `GET /kits/map-clusters`; the whole map unless all four bounds are given
+#[derive(Debug, Deserialize, Validate)]
+pub struct MapClustersQuery {
+    /// Map zoom level, 0 to 20; 5 by default
+    #[validate(range(max = 20, message = "zoom must be between 0 and 20"))]
+    pub zoom: Option<u8>,
+    #[validate(range(min = -90.0, max = 90.0, message = "min_lat must be between -90 and 90"))]
+    pub min_lat: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0, message = "min_lng must be between -180 and 180"))]
+    pub min_lng: Option<f64>,
+    #[validate(range(min = -90.0, max = 90.0, message = "max_lat must be between -90 and 90"))]
+    pub max_lat: Option<f64>,
+    #[validate(range(min = -180.0, max = 180.0, message = "max_lng must be between -180 and 180"))]
+    pub max_lng: Option<f64>,
+    pub distributor: Option<String>,
+}
+
+impl MapClustersQuery {
+    /// `[min_lng, min_lat, max_lng, max_lat]`, if the query names a box
+    pub fn bounds(&self) -> Result<Option<[f64; 4]>, String> {
+        match (self.min_lng, self.min_lat, self.max_lng, self.max_lat) {
+            (None, None, None, None) => Ok(None),
+            (Some(min_lng), Some(min_lat), Some(max_lng), Some(max_lat)) => {
+                if min_lat >= max_lat || min_lng >= max_lng {
+                    return Err("min_lat and min_lng must be below max_lat and max_lng".to_string());
+                }
+                Ok(Some([min_lng, min_lat, max_lng, max_lat]))
+            }
+            _ => Err("min_lat, min_lng, max_lat and max_lng must be given together".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NearbyKit {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    pub is_active: bool,
+    pub distributor: KitDistributorDto,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters from the requested point
+    pub distance: f64,
+}
+
+/// Kits of one grid cell; `kit_id` is set when the cell holds a single kit
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MapCluster {
+    /// Mean position of the cell's kits
+    pub latitude: f64,
+    pub longitude: f64,
+    pub count: i64,
+    pub active: i64,
+    pub kit_id: Option<String>,
+}
+
+/// `GET /distributors/:code/summary`
+#[derive(Debug, Deserialize, Validate)]
+pub struct DistributorSummaryQuery {
+    /// Calendar months up to and including the current one; 12 by default
+    #[validate(range(min = 1, max = 24, message = "months must be between 1 and 24"))]
+    pub months: Option<u32>,
+    /// Olson timezone or UTC offset used to cut months (default `UTC`)
+    pub tz: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MonthlyObservations {
+    /// `YYYY-MM`
+    pub month: String,
+    pub observations: i64,
+    pub patients_served: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TopOperator {
+    /// `id_petugas` of the observations
+    pub operator_id: String,
+    /// NIK recorded for the operator on the distributor's kits, if any
+    pub nik: Option<String>,
+    pub observations: i64,
+    pub patients_served: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DistributorSummary {
+    pub code: String,
+    pub name: String,
+    pub kits: i64,
+    pub active_kits: i64,
+    pub timezone: String,
+    /// First day counted, `YYYY-MM-DD`
+    pub from: String,
+    pub observations: i64,
+    pub observations_per_month: Vec<MonthlyObservations>,
+    pub top_operators: Vec<TopOperator>,
+}
@@ -1,19 +1,19 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
+use crate::dto::common::Links;
+use crate::status::{Gender, RegistrationStatus, RelationshipType};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateMedicalRecordRequest {
     #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
     pub nik: String,
-    #[validate(length(min = 1, message = "NRME is required"))]
-    pub nrme: String,
     #[validate(length(min = 1, message = "Name is required"))]
     pub name: String,
     #[validate(length(min = 1, message = "DOB is required"))]
     pub dob: String,
-    #[validate(length(min = 1, message = "Gender is required"))]
-    pub gender: String,
-    #[validate(length(min = 1, message = "HP is required"))]
+    #[validate(custom = "Gender::validate")]
+    pub gender: Gender,
+    #[validate(custom = "crate::phone::validate")]
     pub hp: String,
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -31,10 +31,10 @@ pub struct UpdateMedicalRecordRequest {
     #[validate(length(min = 1, message = "DOB is required"))]
     pub dob: Option<String>,
     #[serde(default)]
-    #[validate(length(min = 1, message = "Gender is required"))]
-    pub gender: Option<String>,
+    #[validate(custom = "Gender::validate")]
+    pub gender: Option<Gender>,
     #[serde(default)]
-    #[validate(length(min = 1, message = "HP is required"))]
+    #[validate(custom = "crate::phone::validate")]
     pub hp: Option<String>,
     #[serde(default)]
     #[validate(email(message = "Invalid email format"))]
@@ -48,8 +48,83 @@ pub struct MedicalRecordResponse {
     pub nrme: String,
     pub name: String,
     pub dob: String,
-    pub gender: String,
+    pub gender: Gender,
     pub hp: String,
     pub email: String,
     pub last_visit_date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_verified_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// Only on records patients created themselves
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration_status: Option<RegistrationStatus>,
+    /// Present with `expand=guardians`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardians: Option<Vec<GuardianResponse>>,
+    /// Present with `?links=true`
+    #[serde(rename = "_links", default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<Links>,
+}
+
+/// A related patient who acts for the patient, from their `PatientRelationship`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GuardianResponse {
+    pub relationship_id: String,
+    pub patient_id: String,
+    pub name: String,
+    pub hp: String,
+    pub email: String,
+    pub relationship: RelationshipType,
+}
+
+/// `?expand=` of the medical record reads
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct MedicalRecordExpandQuery {
+    /// Comma-separated: `guardians`
+    #[validate(custom = "validate_expand")]
+    pub expand: Option<String>,
+}
+
+impl MedicalRecordExpandQuery {
+    pub fn guardians(&self) -> bool {
+        parse_expand(self.expand.as_deref().unwrap_or_default()).unwrap_or_default()
+    }
+}
+
+/// Whether `guardians` is asked for
+fn parse_expand(raw: &str) -> Result<bool, String> {
+    let mut guardians = false;
+    for name in raw.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match name {
+            "guardians" => guardians = true,
+            other => return Err(format!("Cannot expand '{}'; use guardians", other)),
+        }
+    }
+    Ok(guardians)
+}
+
+fn validate_expand(raw: &str) -> Result<(), ValidationError> {
+    parse_expand(raw).map(|_| ()).map_err(|message| {
+        let mut error = ValidationError::new("unknown_expansion");
+        error.message = Some(message.into());
+        error
+    })
+}
+
+/// One field of a change; `old` or `new` is null when the field was unset
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FieldChangeResponse {
+    pub field: String,
+    pub old: Option<serde_json::Value>,
+    pub new: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MedicalRecordChangeResponse {
+    pub id: String,
+    pub changed_by: String,
+    pub changed_by_name: String,
+    pub fields: Vec<FieldChangeResponse>,
+    pub created_at: String,
 }
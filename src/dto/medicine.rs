@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
+use crate::status::LabelFormat;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
 pub struct CreateMedicineRequest {
@@ -61,3 +62,13 @@ pub struct MedicineResponse {
     pub qty: f64,
     pub manufacturer: String,
 }
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct LabelQuery {
+    /// `zpl` by default
+    #[serde(default)]
+    #[validate(custom = "LabelFormat::validate")]
+    pub format: Option<LabelFormat>,
+    #[validate(range(min = 1, max = 100, message = "Copies must be between 1 and 100"))]
+    pub copies: Option<u32>,
+}
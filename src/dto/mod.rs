@@ -5,6 +5,7 @@ pub mod medicine;
 pub mod appointment;
 pub mod service;
 pub mod insurance;
+#[cfg(feature = "s3")]
 pub mod file;
 pub mod auth;
 pub mod user;
@@ -15,5 +16,47 @@ pub mod child_code;
 pub mod code;
 pub mod region;
 pub mod interpretation;
+#[cfg(feature = "kits")]
 pub mod kit;
 pub mod observation;
+pub mod patient;
+pub mod search;
+pub mod retention;
+pub mod denormalization;
+#[cfg(feature = "kits")]
+pub mod firmware;
+#[cfg(feature = "kits")]
+pub mod usage;
+pub mod waitlist;
+pub mod review;
+pub mod permission;
+pub mod service_account;
+pub mod kiosk;
+pub mod request_log;
+pub mod feature_flag;
+pub mod system;
+pub mod organization;
+pub mod note;
+pub mod allergy;
+pub mod ward;
+pub mod admission;
+pub mod program;
+#[cfg(feature = "billing")]
+pub mod price_list;
+#[cfg(feature = "billing")]
+pub mod invoice;
+#[cfg(feature = "billing")]
+pub mod corporate_client;
+#[cfg(feature = "billing")]
+pub mod payment;
+#[cfg(feature = "billing")]
+pub mod bpjs;
+pub mod outbox;
+pub mod job;
+#[cfg(feature = "s3")]
+pub mod report;
+pub mod report_template;
+pub mod practitioner;
+pub mod stock_opname;
+pub mod sync;
+pub mod scheduler;
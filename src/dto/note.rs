@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::models::SoapSections;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Validate)]
+pub struct SoapSectionsDto {
+    #[validate(length(max = 10000, message = "Subjective cannot exceed 10000 characters"))]
+    pub subjective: Option<String>,
+    #[validate(length(max = 10000, message = "Objective cannot exceed 10000 characters"))]
+    pub objective: Option<String>,
+    #[validate(length(max = 10000, message = "Assessment cannot exceed 10000 characters"))]
+    pub assessment: Option<String>,
+    #[validate(length(max = 10000, message = "Plan cannot exceed 10000 characters"))]
+    pub plan: Option<String>,
+}
+
+impl SoapSectionsDto {
+    /// Trimmed sections, `None` when all are blank
+    pub fn into_sections(self) -> Option<SoapSections> {
+        let clean = |section: Option<String>| section.map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        let sections = SoapSections {
+            subjective: clean(self.subjective),
+            objective: clean(self.objective),
+            assessment: clean(self.assessment),
+            plan: clean(self.plan),
+        };
+        (sections != SoapSections::default()).then_some(sections)
+    }
+}
+
+impl From<SoapSections> for SoapSectionsDto {
+    fn from(sections: SoapSections) -> Self {
+        Self {
+            subjective: sections.subjective,
+            objective: sections.objective,
+            assessment: sections.assessment,
+            plan: sections.plan,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateNoteRequest {
+    #[validate(length(min = 1, message = "Medical record ID is required"))]
+    pub medical_record_id: String,
+    #[validate(length(max = 20000, message = "Text cannot exceed 20000 characters"))]
+    pub text: Option<String>,
+    #[validate]
+    pub soap: Option<SoapSectionsDto>,
+}
+
+/// Replaces the note's content with a new version
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateNoteRequest {
+    #[validate(length(max = 20000, message = "Text cannot exceed 20000 characters"))]
+    pub text: Option<String>,
+    #[validate]
+    pub soap: Option<SoapSectionsDto>,
+    /// Version the edit is based on; a mismatch is refused with 409
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct DeleteNoteRequest {
+    #[validate(custom = "validate_reason")]
+    pub reason: String,
+}
+
+fn validate_reason(reason: &str) -> Result<(), ValidationError> {
+    if reason.trim().is_empty() {
+        let mut error = ValidationError::new("required");
+        error.message = Some("A reason is required to delete a note".into());
+        return Err(error);
+    }
+    if reason.chars().count() > 500 {
+        let mut error = ValidationError::new("length");
+        error.message = Some("Reason cannot exceed 500 characters".into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct NoteQuery {
+    pub medical_record_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteResponse {
+    pub id: String,
+    pub medical_record_id: String,
+    pub author_id: String,
+    pub text: Option<String>,
+    pub soap: Option<SoapSectionsDto>,
+    pub version: i32,
+    pub updated_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteVersionResponse {
+    pub version: i32,
+    pub edited_by: String,
+    pub text: Option<String>,
+    pub soap: Option<SoapSectionsDto>,
+    pub created_at: String,
+}
+
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use crate::models::Observation;
+use crate::repository::observation::ObservationSummaryRow;
+use crate::status::Gender;
 
 #[derive(Debug, Serialize, Deserialize, Validate, Clone)]
 pub struct ObservationUnitDto {
@@ -40,8 +42,8 @@ pub struct ObservationPasienDto {
     #[validate(length(min = 1))]
     pub id: String,
     pub nama: ObservationPasienNamaDto,
-    #[validate(length(min = 1))]
-    pub gender: String,
+    #[validate(custom = "Gender::validate")]
+    pub gender: Gender,
     #[validate(length(min = 1))]
     pub nik: String,
     pub lahir: ObservationPasienLahirDto,
@@ -153,10 +155,43 @@ pub struct ObservationResponse {
     pub base_line: ObservationBaseLineDto,
     pub interpretation: ObservationInterpretationDto,
     pub log_user_kit_id: Option<String>,
+    pub derived: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub derived_from: Vec<String>,
     pub updated_at: Option<String>,
     pub created_at: Option<String>,
 }
 
+/// List item of `GET /observations?view=summary`, without the embedded patient and kit
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ObservationSummary {
+    pub id: String,
+    pub id_pasien: String,
+    pub value: f64,
+    pub unit: String,
+    pub coding_code: String,
+    pub coding_display: String,
+    pub interpretation: String,
+    pub time: i64,
+    pub derived: bool,
+}
+
+impl From<ObservationSummaryRow> for ObservationSummary {
+    fn from(row: ObservationSummaryRow) -> Self {
+        Self {
+            id: row.id.to_hex(),
+            id_pasien: row.id_pasien.to_hex(),
+            value: row.value,
+            unit: row.unit.code,
+            coding_code: row.coding.code,
+            coding_display: row.coding.display,
+            interpretation: row.interpretation.code,
+            time: row.time,
+            derived: row.derived,
+        }
+    }
+}
+
 impl From<Observation> for ObservationResponse {
     fn from(obs: Observation) -> Self {
         Self {
@@ -167,7 +202,7 @@ impl From<Observation> for ObservationResponse {
                 display: obs.unit.display,
                 system: obs.unit.system,
             },
-            id_pasien: obs.id_pasien,
+            id_pasien: obs.id_pasien.to_hex(),
             pasien: ObservationPasienDto {
                 id: obs.pasien.id,
                 nama: ObservationPasienNamaDto {
@@ -218,8 +253,136 @@ impl From<Observation> for ObservationResponse {
                 text: obs.interpretation.text,
             },
             log_user_kit_id: obs.log_user_kit_id,
-            updated_at: obs.updated_at,
-            created_at: obs.created_at,
+            derived: obs.derived,
+            derived_from: obs.derived_from,
+            updated_at: crate::datetime::to_rfc3339_opt(obs.updated_at),
+            created_at: crate::datetime::to_rfc3339_opt(obs.created_at),
         }
     }
 }
+
+#[derive(Debug, Deserialize)]
+pub struct TrendQuery {
+    /// Number of most recent observations to analyse (default 20, max 500)
+    pub window: Option<i64>,
+    /// Size of the rolling mean/min/max window in observations (default 5)
+    pub rolling: Option<i64>,
+    /// Only include observations with `time >= from`
+    pub from: Option<i64>,
+    /// Only include observations with `time <= to`
+    pub to: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendPoint {
+    pub time: i64,
+    pub value: f64,
+    pub rolling_mean: f64,
+    pub rolling_min: f64,
+    pub rolling_max: f64,
+    /// `above_max` or `below_min` when the value is outside its base line
+    pub breach: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendFlags {
+    /// Breach of the latest value against its base line, if any
+    pub latest_breach: Option<String>,
+    /// Number of most recent consecutive values outside their base line
+    pub consecutive_breaches: usize,
+    /// Latest value breaches and the trend is moving further away from the base line
+    pub worsening: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendResponse {
+    pub id_pasien: String,
+    pub coding: ObservationCodingDto,
+    pub unit: ObservationUnitDto,
+    pub base_line: ObservationBaseLineDto,
+    pub count: usize,
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Linear-regression slope in value units per day; `None` with fewer than two distinct times
+    pub slope_per_day: Option<f64>,
+    pub flags: TrendFlags,
+    pub points: Vec<TrendPoint>,
+}
+
+/// Vital signs measured together, see `crate::vitals`. Each present value becomes one observation.
+#[derive(Debug, Serialize, Deserialize, Validate, Clone)]
+#[validate(schema(function = "validate_vitals_bundle"))]
+pub struct VitalsBundleRequest {
+    /// Body temperature in °C
+    #[validate(range(min = 25.0, max = 45.0, message = "Temperature must be between 25 and 45 °C"))]
+    pub temperature: Option<f64>,
+    /// Blood pressure in mmHg
+    #[validate(range(min = 40.0, max = 300.0, message = "Systolic pressure must be between 40 and 300 mmHg"))]
+    pub systolic: Option<f64>,
+    #[validate(range(min = 20.0, max = 200.0, message = "Diastolic pressure must be between 20 and 200 mmHg"))]
+    pub diastolic: Option<f64>,
+    /// Beats per minute
+    #[validate(range(min = 20.0, max = 300.0, message = "Pulse must be between 20 and 300 /min"))]
+    pub pulse: Option<f64>,
+    /// Percent
+    #[validate(range(min = 0.0, max = 100.0, message = "SpO2 must be between 0 and 100 %"))]
+    pub spo2: Option<f64>,
+    /// Kilograms
+    #[validate(range(min = 0.3, max = 500.0, message = "Weight must be between 0.3 and 500 kg"))]
+    pub weight: Option<f64>,
+    /// Centimetres
+    #[validate(range(min = 20.0, max = 300.0, message = "Height must be between 20 and 300 cm"))]
+    pub height: Option<f64>,
+    /// Epoch seconds of the measurement; defaults to now
+    pub time: Option<i64>,
+    /// Kit the measurements were taken with, if any
+    #[validate]
+    pub atm_sehat: Option<ObservationAtmSehatDto>,
+}
+
+fn validate_vitals_bundle(bundle: &VitalsBundleRequest) -> Result<(), ValidationError> {
+    let fail = |code: &'static str, message: &'static str| {
+        let mut error = ValidationError::new(code);
+        error.message = Some(message.into());
+        Err(error)
+    };
+    let values = [bundle.temperature, bundle.systolic, bundle.diastolic, bundle.pulse, bundle.spo2, bundle.weight, bundle.height];
+    if values.iter().all(Option::is_none) {
+        return fail("empty_bundle", "At least one vital sign is required");
+    }
+    match (bundle.systolic, bundle.diastolic) {
+        (Some(systolic), Some(diastolic)) if systolic <= diastolic => fail("blood_pressure", "Systolic pressure must be above diastolic"),
+        (Some(_), None) | (None, Some(_)) => fail("blood_pressure", "Systolic and diastolic pressure are recorded together"),
+        _ => Ok(()),
+    }
+}
+
+/// Filters of `GET /observations`, next to the pagination parameters
+#[derive(Debug, Deserialize, Default)]
+pub struct ObservationListQuery {
+    pub id_pasien: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ObservationExportQuery {
+    /// Only export observations with `time >= from`
+    pub from: Option<i64>,
+    /// Only export observations with `time <= to`
+    pub to: Option<i64>,
+    /// Comma-separated coding codes
+    pub coding_code: Option<String>,
+    pub id_pasien: Option<String>,
+}
+
+impl ObservationExportQuery {
+    pub fn coding_codes(&self) -> Vec<&str> {
+        self.coding_code
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|code| !code.is_empty())
+            .collect()
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateOrganizationRequest {
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: String,
+    /// `Asia/Jakarta`, `Asia/Makassar`, `Asia/Jayapura`, `UTC` or an offset like `+07:00`;
+    /// defaults to `CLINIC_TIMEZONE`
+    pub timezone: Option<String>,
+    /// Given together with `longitude`
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Kit distributor code the organization represents
+    #[validate(length(min = 1, max = 50, message = "Distributor code must be between 1 and 50 characters"))]
+    pub distributor_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateOrganizationRequest {
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: Option<String>,
+    pub timezone: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    #[validate(length(min = 1, max = 50, message = "Distributor code must be between 1 and 50 characters"))]
+    pub distributor_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrganizationResponse {
+    pub id: String,
+    pub name: String,
+    pub timezone: String,
+    /// Current offset of `timezone`, e.g. `+07:00`
+    pub utc_offset: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub distributor_code: Option<String>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::models::OutboxEntry;
+use crate::status::OutboxStatus;
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct OutboxQuery {
+    #[serde(default)]
+    #[validate(custom = "OutboxStatus::validate")]
+    pub status: Option<OutboxStatus>,
+    pub topic: Option<String>,
+    /// Only entries needing attention: failed, or pending after a failed attempt
+    #[serde(default)]
+    pub stuck: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutboxEntryResponse {
+    pub id: String,
+    pub topic: String,
+    pub aggregate_type: String,
+    pub aggregate_id: String,
+    pub payload: serde_json::Value,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub next_attempt_at: String,
+    pub created_at: String,
+    pub published_at: Option<String>,
+}
+
+impl From<OutboxEntry> for OutboxEntryResponse {
+    fn from(entry: OutboxEntry) -> Self {
+        Self {
+            id: entry.id.map(|oid| oid.to_hex()).unwrap_or_default(),
+            topic: entry.topic,
+            aggregate_type: entry.aggregate_type,
+            aggregate_id: entry.aggregate_id,
+            payload: serde_json::to_value(&entry.payload).unwrap_or_default(),
+            status: entry.status,
+            attempts: entry.attempts,
+            last_error: entry.last_error,
+            next_attempt_at: crate::datetime::to_rfc3339(entry.next_attempt_at),
+            created_at: crate::datetime::to_rfc3339(entry.created_at),
+            published_at: crate::datetime::to_rfc3339_opt(entry.published_at),
+        }
+    }
+}
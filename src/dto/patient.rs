@@ -0,0 +1,220 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::dto::medical_record::MedicalRecordResponse;
+use crate::status::{AdmissionStatus, AppointmentStatus, RegistrationStatus, RelationshipType};
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateQuery {
+    /// Minimum normalized-name similarity (0.0 - 1.0) for name+dob matches
+    pub threshold: Option<f64>,
+    /// Maximum number of candidate groups returned
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroupResponse {
+    /// "nik" for identical NIK, "name_dob" for fuzzy name match on same date of birth
+    pub reason: String,
+    pub score: f64,
+    pub records: Vec<MedicalRecordResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct MergePatientRequest {
+    #[validate(length(min = 1, message = "At least one duplicate ID is required"))]
+    pub duplicate_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MergePatientResponse {
+    pub patient: MedicalRecordResponse,
+    pub merged_ids: Vec<String>,
+    pub appointments_repointed: u64,
+    pub observations_repointed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrowthQuery {
+    /// `weight` or `height`
+    pub metric: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrowthPoint {
+    pub time: i64,
+    pub age_months: f64,
+    /// Measurement in the reference unit (kg or cm)
+    pub value: f64,
+    /// `None` when the age is outside the 0–60 month reference range
+    pub z_score: Option<f64>,
+    pub percentile: Option<f64>,
+}
+
+/// Reference curve values at one age, for plotting the standard SD lines.
+#[derive(Debug, Serialize)]
+pub struct GrowthReferencePoint {
+    pub age_months: f64,
+    pub sd_neg3: f64,
+    pub sd_neg2: f64,
+    pub median: f64,
+    pub sd2: f64,
+    pub sd3: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrowthResponse {
+    pub patient_id: String,
+    pub metric: String,
+    pub sex: String,
+    pub unit: String,
+    pub series: Vec<GrowthPoint>,
+    pub reference: Vec<GrowthReferencePoint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreatePatientRelationshipRequest {
+    #[validate(length(min = 1, message = "Related patient ID is required"))]
+    pub related_patient_id: String,
+    /// What the related patient is to this one
+    #[validate(custom = "RelationshipType::validate")]
+    pub relationship: RelationshipType,
+    /// Whether the related patient acts for this one; defaults to true for parents and caregivers
+    pub guardian: Option<bool>,
+    #[validate(length(max = 1000, message = "Notes cannot exceed 1000 characters"))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdatePatientRelationshipRequest {
+    #[validate(custom = "RelationshipType::validate")]
+    pub relationship: Option<RelationshipType>,
+    pub guardian: Option<bool>,
+    #[validate(length(max = 1000, message = "Notes cannot exceed 1000 characters"))]
+    pub notes: Option<String>,
+}
+
+/// A link as seen from `patient_id`, whichever side it was recorded from
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatientRelationshipResponse {
+    pub id: String,
+    pub patient_id: String,
+    pub related_patient_id: String,
+    /// Empty when the related record no longer exists
+    pub related_patient_name: String,
+    /// What the related patient is to this one
+    pub relationship: RelationshipType,
+    /// The related patient acts for this one
+    pub guardian: bool,
+    /// This patient acts for the related one
+    pub guardian_of_related: bool,
+    /// Recorded from the related patient, and changed or removed there
+    pub inverse: bool,
+    pub notes: Option<String>,
+    pub recorded_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// Body of `POST /public/patients/register`; without `otp` a code is sent to `phone`
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct SelfRegistrationRequest {
+    #[validate(length(min = 16, max = 16, message = "NIK must be 16 characters"))]
+    pub nik: String,
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: String,
+    #[validate(length(min = 1, message = "OTP cannot be empty"))]
+    pub otp: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfRegistrationResponse {
+    pub patient_id: String,
+    pub nrme: String,
+    pub name: String,
+    pub registration_status: RegistrationStatus,
+}
+
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct RegistrationListQuery {
+    /// `pending` (default) or `confirmed`
+    #[validate(custom = "RegistrationStatus::validate")]
+    pub status: Option<RegistrationStatus>,
+}
+
+/// A page of `GET /patients/:id/timeline`
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct TimelineQuery {
+    /// `next_cursor` of the previous page; the newest entries when absent
+    pub cursor: Option<String>,
+    /// Entries per page, 20 by default
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: Option<usize>,
+    /// Comma-separated entry types to include, e.g. `observation,appointment`; all by default
+    pub types: Option<String>,
+}
+
+/// An admission, the patient's stay in a ward
+#[derive(Debug, Serialize)]
+pub struct EncounterEntry {
+    pub id: String,
+    pub occurred_at: String,
+    pub status: AdmissionStatus,
+    pub ward_id: String,
+    pub bed_id: String,
+    pub discharged_at: Option<String>,
+    pub discharge_note: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ObservationEntry {
+    pub id: String,
+    pub occurred_at: String,
+    pub code: String,
+    pub display: String,
+    pub value: f64,
+    pub unit: String,
+    /// Interpretation code against the base line, e.g. `N` or `H`
+    pub interpretation: String,
+    pub derived: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    pub id: String,
+    pub occurred_at: String,
+    pub name: String,
+    pub file_type: String,
+    pub extension: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AppointmentEntry {
+    pub id: String,
+    pub occurred_at: String,
+    pub doctor_id: String,
+    pub date: String,
+    pub time: String,
+    pub status: AppointmentStatus,
+    pub mode: Option<String>,
+}
+
+/// One entry of a patient's timeline; `type` says which fields it has
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEntry {
+    Encounter(EncounterEntry),
+    Observation(ObservationEntry),
+    File(FileEntry),
+    Appointment(AppointmentEntry),
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    /// Newest first
+    pub entries: Vec<TimelineEntry>,
+    /// Pass as `cursor` for the next page; absent on the last one
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::status::{GatewayStatus, PaymentMethod, PriceItemType, RevenuePeriod, ShiftStatus};
+
+/// Recorded in the caller's open cashier shift
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct RecordPaymentRequest {
+    #[validate(custom = "PaymentMethod::validate")]
+    pub method: PaymentMethod,
+    #[validate(range(min = 0.01, message = "Amount must be positive"))]
+    pub amount: f64,
+    #[validate(length(min = 1, max = 100, message = "Reference must be between 1 and 100 characters"))]
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaymentResponse {
+    pub id: String,
+    pub invoice_id: String,
+    pub organization_id: String,
+    pub method: PaymentMethod,
+    pub amount: f64,
+    pub reference: Option<String>,
+    pub cashier_id: String,
+    pub shift_id: Option<String>,
+    pub paid_at: String,
+    pub settlement_date: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct OpenShiftRequest {
+    #[validate(range(min = 0.0, message = "Opening cash cannot be negative"))]
+    pub opening_cash: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CloseShiftRequest {
+    #[validate(range(min = 0.0, message = "Counted cash cannot be negative"))]
+    pub counted_cash: f64,
+    #[validate(length(max = 1000, message = "Note cannot exceed 1000 characters"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ShiftQuery {
+    pub cashier_id: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "ShiftStatus::validate")]
+    pub status: Option<ShiftStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MethodTotal {
+    pub method: PaymentMethod,
+    pub count: i64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShiftResponse {
+    pub id: String,
+    pub cashier_id: String,
+    pub status: ShiftStatus,
+    pub opening_cash: f64,
+    pub opened_at: String,
+    pub closed_at: Option<String>,
+    /// Opening cash plus cash payments; running total while the shift is open
+    pub expected_cash: f64,
+    pub counted_cash: Option<f64>,
+    pub difference: Option<f64>,
+    pub note: Option<String>,
+    pub payments: Vec<MethodTotal>,
+}
+
+/// `date` defaults to today in the clinic timezone
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct SettlementQuery {
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub date: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CashierSettlement {
+    pub cashier_id: String,
+    pub count: i64,
+    pub amount: f64,
+    pub by_method: Vec<MethodTotal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SettlementReport {
+    pub date: String,
+    pub organization_id: Option<String>,
+    pub count: i64,
+    pub total: f64,
+    pub by_method: Vec<MethodTotal>,
+    pub by_cashier: Vec<CashierSettlement>,
+}
+
+/// `GET /stats/revenue`; the 30 days up to today by day unless given. Callers outside the
+/// admin role only see the organizations they hold a role in.
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct RevenueStatsQuery {
+    #[validate(custom = "RevenuePeriod::validate")]
+    pub group_by: Option<RevenuePeriod>,
+    /// Inclusive start date, `YYYY-MM-DD`
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub from: Option<String>,
+    /// Inclusive end date, `YYYY-MM-DD`
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub to: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+/// `GET /stats/services/utilization`, scoped like `RevenueStatsQuery`
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct UtilizationStatsQuery {
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub from: Option<String>,
+    #[validate(custom = "crate::dto::common::validate_date")]
+    pub to: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PeriodRevenue {
+    /// The day, the Monday starting the week, or `YYYY-MM`
+    pub period: String,
+    pub count: i64,
+    pub amount: f64,
+    pub by_method: Vec<MethodTotal>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CategoryRevenue {
+    pub item_type: PriceItemType,
+    /// Service category; `null` for medicines
+    pub category: Option<String>,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+/// Payments received by settlement date, and the lines billed by service date
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RevenueStats {
+    pub from: String,
+    pub to: String,
+    pub group_by: RevenuePeriod,
+    pub organization_id: Option<String>,
+    pub count: i64,
+    pub total: f64,
+    pub by_period: Vec<PeriodRevenue>,
+    pub by_method: Vec<MethodTotal>,
+    /// Billed, not received: payments are not split over invoice lines
+    pub by_category: Vec<CategoryRevenue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ServiceUtilization {
+    pub service_id: String,
+    pub name: Option<String>,
+    pub category: Option<String>,
+    pub invoices: i64,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceUtilizationStats {
+    pub from: String,
+    pub to: String,
+    pub organization_id: Option<String>,
+    pub services: Vec<ServiceUtilization>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaymentLinkResponse {
+    /// Order id reported back by the gateway
+    pub id: String,
+    pub invoice_id: String,
+    pub provider: String,
+    pub amount: f64,
+    pub url: String,
+    pub status: GatewayStatus,
+    pub expires_at: Option<String>,
+    pub payment_id: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CallbackResponse {
+    pub order_id: String,
+    pub status: GatewayStatus,
+    /// Whether this callback changed anything; repeats are acknowledged without effect
+    pub applied: bool,
+    pub payment_id: Option<String>,
+}
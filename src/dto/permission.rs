@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreatePermissionRequest {
+    #[validate(length(min = 1, max = 100, message = "Resource must be between 1 and 100 characters"))]
+    pub resource: String,
+    #[validate(length(min = 1, max = 50, message = "Action must be between 1 and 50 characters"))]
+    pub action: String,
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdatePermissionRequest {
+    #[validate(length(min = 1, max = 100, message = "Resource must be between 1 and 100 characters"))]
+    pub resource: Option<String>,
+    #[validate(length(min = 1, max = 50, message = "Action must be between 1 and 50 characters"))]
+    pub action: Option<String>,
+    #[validate(length(max = 500, message = "Description cannot exceed 500 characters"))]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PermissionResponse {
+    pub id: String,
+    pub resource: String,
+    pub action: String,
+    pub description: Option<String>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct GrantPermissionRequest {
+    /// Role code, or `*` for every authenticated user
+    #[validate(length(min = 1, max = 50, message = "Role code must be between 1 and 50 characters"))]
+    pub role_code: String,
+    /// Resource of an existing permission, or `*`
+    #[validate(length(min = 1, message = "Resource is required"))]
+    pub resource: String,
+    /// Action of an existing permission, or `*`
+    #[validate(length(min = 1, message = "Action is required"))]
+    pub action: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RolePermissionQuery {
+    pub role_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RolePermissionResponse {
+    pub id: String,
+    pub role_code: String,
+    pub resource: String,
+    pub action: String,
+    pub created_at: String,
+}
+
+/// Permissions resolved for the caller's active roles
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PermissionMatrixResponse {
+    pub roles: Vec<String>,
+    /// Allowed actions per resource; `*` stands for any resource or action
+    pub permissions: BTreeMap<String, Vec<String>>,
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::dto::common::validate_date;
+use crate::status::{DoctorStatus, PractitionerType};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct QualificationDto {
+    #[validate(length(min = 1, max = 20, message = "Qualification code must be between 1 and 20 characters"))]
+    pub code: String,
+    #[validate(length(min = 1, message = "Qualification number is required"))]
+    pub number: String,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    #[validate(custom = "validate_date")]
+    pub valid_until: Option<String>,
+}
+
+/// Doctors need a specialization and an `SIP` qualification, which `/doctors` shows
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreatePractitionerRequest {
+    #[serde(rename = "type")]
+    #[validate(custom = "PractitionerType::validate")]
+    pub practitioner_type: PractitionerType,
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "NIP is required"))]
+    pub nip: String,
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: DoctorStatus,
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Specialization is required"))]
+    pub specialization: Option<String>,
+    #[serde(default)]
+    #[validate]
+    pub qualifications: Vec<QualificationDto>,
+}
+
+/// The type of a practitioner cannot change; `qualifications` replaces the whole list
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdatePractitionerRequest {
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Name is required"))]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[validate(length(min = 1, message = "NIP is required"))]
+    pub nip: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: Option<DoctorStatus>,
+    #[serde(default)]
+    #[validate(length(min = 1, message = "Specialization is required"))]
+    pub specialization: Option<String>,
+    #[serde(default)]
+    #[validate]
+    pub qualifications: Option<Vec<QualificationDto>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PractitionerResponse {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub practitioner_type: PractitionerType,
+    pub name: String,
+    pub nip: String,
+    pub status: DoctorStatus,
+    pub specialization: Option<String>,
+    pub qualifications: Vec<QualificationDto>,
+    pub rating_average: Option<f64>,
+    pub rating_count: i64,
+}
+
+/// Filters of `GET /practitioners`, next to the pagination parameters
+#[derive(Debug, Deserialize, Default, Validate)]
+pub struct PractitionerListQuery {
+    #[serde(rename = "type")]
+    #[validate(custom = "PractitionerType::validate")]
+    pub practitioner_type: Option<PractitionerType>,
+    #[validate(custom = "DoctorStatus::validate")]
+    pub status: Option<DoctorStatus>,
+}
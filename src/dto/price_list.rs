@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::dto::common::validate_date;
+use crate::status::{PriceItemType, PriceListStatus};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct PriceListItemDto {
+    #[validate(custom = "PriceItemType::validate")]
+    pub item_type: PriceItemType,
+    #[validate(length(min = 24, max = 24, message = "Item IDs must be 24 characters"))]
+    pub item_id: String,
+    #[validate(range(min = 0.0, message = "Price cannot be negative"))]
+    pub price: f64,
+}
+
+/// Creates a draft. Without `items`, the items of `based_on` are copied, e.g. to start
+/// the next version from the current one.
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreatePriceListRequest {
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: String,
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: String,
+    #[validate(custom = "validate_date")]
+    pub effective_from: String,
+    #[validate(custom = "validate_items")]
+    #[validate]
+    pub items: Option<Vec<PriceListItemDto>>,
+    pub based_on: Option<String>,
+}
+
+/// Only drafts can be changed
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdatePriceListRequest {
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: Option<String>,
+    #[validate(custom = "validate_date")]
+    pub effective_from: Option<String>,
+    #[validate(custom = "validate_items")]
+    #[validate]
+    pub items: Option<Vec<PriceListItemDto>>,
+}
+
+/// One price per item
+fn validate_items(items: &[PriceListItemDto]) -> Result<(), ValidationError> {
+    for (i, item) in items.iter().enumerate() {
+        if items[..i].iter().any(|other| other.item_type == item.item_type && other.item_id == item.item_id) {
+            let mut error = ValidationError::new("duplicate_item");
+            error.message = Some(format!("{} {} is priced more than once", item.item_type, item.item_id).into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct PriceListQuery {
+    pub organization_id: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "PriceListStatus::validate")]
+    pub status: Option<PriceListStatus>,
+}
+
+/// `date` defaults to today in the clinic timezone
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct ActivePriceListQuery {
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: String,
+    #[validate(custom = "validate_date")]
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct QuoteItemDto {
+    #[validate(custom = "PriceItemType::validate")]
+    pub item_type: PriceItemType,
+    #[validate(length(min = 24, max = 24, message = "Item IDs must be 24 characters"))]
+    pub item_id: String,
+    #[validate(range(min = 0.001, message = "Quantity must be positive"))]
+    pub quantity: f64,
+}
+
+/// Prices billable items as of `date`, the day of service (default today)
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct QuoteRequest {
+    #[validate(length(min = 24, max = 24, message = "Organization IDs must be 24 characters"))]
+    pub organization_id: String,
+    #[validate(custom = "validate_date")]
+    pub date: Option<String>,
+    #[validate(length(min = 1, message = "At least one item is required"))]
+    #[validate]
+    pub items: Vec<QuoteItemDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriceListItemResponse {
+    pub item_type: PriceItemType,
+    pub item_id: String,
+    pub price: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceListResponse {
+    pub id: String,
+    pub organization_id: String,
+    pub version: i32,
+    pub name: String,
+    pub effective_from: String,
+    pub status: PriceListStatus,
+    pub items: Vec<PriceListItemResponse>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub published_by: Option<String>,
+    pub published_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct QuoteLine {
+    pub item_type: PriceItemType,
+    pub item_id: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QuoteResponse {
+    pub price_list_id: String,
+    pub price_list_version: i32,
+    pub date: String,
+    pub lines: Vec<QuoteLine>,
+    pub total: f64,
+}
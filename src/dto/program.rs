@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::models::{ObservationThreshold, ProgramEligibility};
+use crate::programs::ControlStatus;
+use crate::status::{ProgramEnrollmentStatus, ProgramStatus};
+
+/// Inclusive bounds on the latest observation of `code`; at least one is required
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+#[validate(schema(function = "validate_bounds"))]
+pub struct ObservationThresholdDto {
+    #[validate(length(min = 1, max = 50, message = "Observation code must be between 1 and 50 characters"))]
+    pub code: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+fn validate_bounds(threshold: &ObservationThresholdDto) -> Result<(), ValidationError> {
+    let message = match (threshold.min, threshold.max) {
+        (None, None) => "A threshold needs a min, a max or both",
+        (Some(min), Some(max)) if min > max => "Threshold min cannot be above its max",
+        _ => return Ok(()),
+    };
+    let mut error = ValidationError::new("threshold_bounds");
+    error.message = Some(message.into());
+    Err(error)
+}
+
+impl From<ObservationThresholdDto> for ObservationThreshold {
+    fn from(dto: ObservationThresholdDto) -> Self {
+        Self { code: dto.code.trim().to_string(), min: dto.min, max: dto.max }
+    }
+}
+
+impl From<ObservationThreshold> for ObservationThresholdDto {
+    fn from(threshold: ObservationThreshold) -> Self {
+        Self { code: threshold.code, min: threshold.min, max: threshold.max }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Validate)]
+pub struct ProgramEligibilityDto {
+    /// ICD-10 codes, each also matching its subcodes
+    #[serde(default)]
+    pub diagnosis_codes: Vec<String>,
+    #[serde(default)]
+    #[validate]
+    pub observations: Vec<ObservationThresholdDto>,
+}
+
+impl From<ProgramEligibilityDto> for ProgramEligibility {
+    fn from(dto: ProgramEligibilityDto) -> Self {
+        Self {
+            diagnosis_codes: dto.diagnosis_codes.iter().map(|code| code.trim().to_uppercase()).filter(|code| !code.is_empty()).collect(),
+            observations: dto.observations.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<ProgramEligibility> for ProgramEligibilityDto {
+    fn from(eligibility: ProgramEligibility) -> Self {
+        Self {
+            diagnosis_codes: eligibility.diagnosis_codes,
+            observations: eligibility.observations.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateProgramRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: String,
+    #[validate(length(max = 2000, message = "Description cannot exceed 2000 characters"))]
+    pub description: Option<String>,
+    /// Anyone may enroll when absent
+    #[serde(default)]
+    #[validate]
+    pub eligibility: ProgramEligibilityDto,
+    #[validate(length(min = 1, message = "At least one control target is required"))]
+    #[validate]
+    pub control_targets: Vec<ObservationThresholdDto>,
+    #[serde(default)]
+    #[validate(custom = "ProgramStatus::validate")]
+    pub status: Option<ProgramStatus>,
+}
+
+/// New rules apply to enrollments and cohort reports from then on
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateProgramRequest {
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: Option<String>,
+    #[validate(length(max = 2000, message = "Description cannot exceed 2000 characters"))]
+    pub description: Option<String>,
+    #[validate]
+    pub eligibility: Option<ProgramEligibilityDto>,
+    #[validate(length(min = 1, message = "At least one control target is required"))]
+    #[validate]
+    pub control_targets: Option<Vec<ObservationThresholdDto>>,
+    #[serde(default)]
+    #[validate(custom = "ProgramStatus::validate")]
+    pub status: Option<ProgramStatus>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ProgramQuery {
+    #[serde(default)]
+    #[validate(custom = "ProgramStatus::validate")]
+    pub status: Option<ProgramStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProgramResponse {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub eligibility: ProgramEligibilityDto,
+    pub control_targets: Vec<ObservationThresholdDto>,
+    pub status: ProgramStatus,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+/// A patient to enroll, or to check eligibility for
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct EnrollPatientRequest {
+    #[validate(length(min = 24, max = 24, message = "Medical record ID must be 24 characters"))]
+    pub medical_record_id: String,
+    /// ICD-10 codes of the patient's current diagnoses
+    #[serde(default)]
+    pub diagnosis_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, Validate)]
+pub struct WithdrawEnrollmentRequest {
+    #[validate(length(max = 1000, message = "Reason cannot exceed 1000 characters"))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct EnrollmentQuery {
+    #[serde(default)]
+    #[validate(custom = "ProgramEnrollmentStatus::validate")]
+    pub status: Option<ProgramEnrollmentStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EnrollmentResponse {
+    pub id: String,
+    pub program_id: String,
+    pub medical_record_id: String,
+    pub diagnosis_codes: Vec<String>,
+    pub eligible_by: Vec<String>,
+    pub status: ProgramEnrollmentStatus,
+    pub enrolled_by: String,
+    pub enrolled_at: String,
+    pub withdrawn_by: Option<String>,
+    pub withdrawn_at: Option<String>,
+    pub withdrawal_reason: Option<String>,
+}
+
+/// The latest observation of one control target
+#[derive(Debug, Serialize, Clone)]
+pub struct CohortReading {
+    pub code: String,
+    pub value: f64,
+    pub unit: String,
+    pub observed_at: String,
+    pub within_target: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CohortPatient {
+    pub enrollment_id: String,
+    pub medical_record_id: String,
+    pub control: ControlStatus,
+    /// Targets never observed are left out
+    pub readings: Vec<CohortReading>,
+}
+
+/// The program's active enrollments by control status, judged on the latest observations
+#[derive(Debug, Serialize, Clone)]
+pub struct CohortReportResponse {
+    pub program_id: String,
+    pub code: String,
+    pub name: String,
+    pub enrolled: usize,
+    pub controlled: usize,
+    pub uncontrolled: usize,
+    pub no_data: usize,
+    /// Controlled share of the enrolled patients with data, in percent; absent without any
+    pub controlled_percent: Option<f64>,
+    pub patients: Vec<CohortPatient>,
+}
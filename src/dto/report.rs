@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::dto::file::FileResponse;
+use crate::status::{ReportFormat, ReportType};
+
+fn validate_cron(value: &str) -> Result<(), ValidationError> {
+    crate::cron::CronSchedule::parse(value).map(|_| ()).map_err(|message| {
+        let mut error = ValidationError::new("cron");
+        error.message = Some(message.into());
+        error
+    })
+}
+
+fn validate_recipients(recipients: &[String]) -> Result<(), ValidationError> {
+    if let Some(invalid) = recipients.iter().find(|r| !validator::validate_email(r.trim())) {
+        let mut error = ValidationError::new("email");
+        error.message = Some(format!("'{}' is not a valid email address", invalid).into());
+        return Err(error);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateReportScheduleRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(custom = "ReportType::validate")]
+    pub report_type: ReportType,
+    /// `minute hour day month weekday` in the clinic timezone, e.g. `0 7 * * 1`
+    #[validate(custom = "validate_cron")]
+    pub cron: String,
+    #[validate(length(min = 1, max = 20, message = "Between 1 and 20 recipients are required"), custom = "validate_recipients")]
+    pub recipients: Vec<String>,
+    /// `csv` by default
+    #[serde(default)]
+    #[validate(custom = "ReportFormat::validate")]
+    pub format: Option<ReportFormat>,
+    /// Days before the run date the report covers; 1 by default
+    #[validate(range(min = 1, max = 366, message = "Period must be between 1 and 366 days"))]
+    pub period_days: Option<i32>,
+    pub organization_id: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateReportScheduleRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+    #[validate(custom = "validate_cron")]
+    pub cron: Option<String>,
+    #[validate(length(min = 1, max = 20, message = "Between 1 and 20 recipients are required"), custom = "validate_recipients")]
+    pub recipients: Option<Vec<String>>,
+    #[serde(default)]
+    #[validate(custom = "ReportFormat::validate")]
+    pub format: Option<ReportFormat>,
+    #[validate(range(min = 1, max = 366, message = "Period must be between 1 and 366 days"))]
+    pub period_days: Option<i32>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ReportScheduleQuery {
+    #[serde(default)]
+    #[validate(custom = "ReportType::validate")]
+    pub report_type: Option<ReportType>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportScheduleResponse {
+    pub id: String,
+    pub name: String,
+    pub report_type: ReportType,
+    pub cron: String,
+    pub recipients: Vec<String>,
+    pub format: ReportFormat,
+    pub period_days: i32,
+    pub organization_id: Option<String>,
+    pub active: bool,
+    pub next_run_at: Option<String>,
+    pub last_run_at: Option<String>,
+    pub last_file_id: Option<String>,
+    pub last_error: Option<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportRunResponse {
+    pub schedule_id: String,
+    pub report_type: ReportType,
+    /// Period covered; one day for stock snapshots
+    pub from: String,
+    pub to: String,
+    pub rows: usize,
+    pub file: FileResponse,
+    pub delivered_to: Vec<String>,
+    /// Recipients the mailer could not send to
+    pub failed_recipients: Vec<String>,
+}
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+use crate::pagination::PaginationMeta;
+use crate::status::ReportParameterType;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportParameterRequest {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: ReportParameterType,
+    #[serde(default)]
+    pub required: bool,
+    pub default: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateReportTemplateRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+    pub collection: String,
+    /// Aggregation stages; string values `"{{name}}"` are parameter placeholders
+    #[validate(length(min = 1, max = 20, message = "A pipeline needs between 1 and 20 stages"))]
+    pub pipeline: Vec<Value>,
+    #[serde(default)]
+    pub parameters: Vec<ReportParameterRequest>,
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateReportTemplateRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+    #[validate(length(max = 500, message = "Description must be at most 500 characters"))]
+    pub description: Option<String>,
+    pub collection: Option<String>,
+    #[validate(length(min = 1, max = 20, message = "A pipeline needs between 1 and 20 stages"))]
+    pub pipeline: Option<Vec<Value>>,
+    pub parameters: Option<Vec<ReportParameterRequest>>,
+    pub columns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportTemplateResponse {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub collection: String,
+    pub pipeline: Vec<Value>,
+    pub parameters: Vec<ReportParameterRequest>,
+    pub columns: Vec<String>,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct RunReportRequest {
+    #[validate(length(equal = 24, message = "template_id must be a valid report template ID"))]
+    pub template_id: String,
+    /// Values by parameter name
+    #[serde(default)]
+    pub parameters: Map<String, Value>,
+}
+
+/// One page of a template's result as a table
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportResultResponse {
+    pub template_id: String,
+    pub name: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+    pub pagination: PaginationMeta,
+}
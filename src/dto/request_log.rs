@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use crate::models::RequestLog;
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RequestLogQuery {
+    /// Path prefix, e.g. `/observations`
+    pub path: Option<String>,
+    pub status: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestLogResponse {
+    pub id: String,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: i32,
+    pub duration_ms: i64,
+    pub user_id: Option<String>,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+    pub created_at: String,
+}
+
+impl From<RequestLog> for RequestLogResponse {
+    fn from(log: RequestLog) -> Self {
+        Self {
+            id: log.id.map(|oid| oid.to_hex()).unwrap_or_default(),
+            method: log.method,
+            path: log.path,
+            query: log.query,
+            status: log.status,
+            duration_ms: log.duration_ms,
+            user_id: log.user_id,
+            request_body: log.request_body,
+            response_body: log.response_body,
+            created_at: crate::datetime::to_rfc3339(log.created_at),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use serde::Serialize;
+use crate::models::RetentionRun;
+use crate::retention::RetentionPolicy;
+
+#[derive(Debug, Serialize)]
+pub struct RetentionTotal {
+    pub collection: String,
+    pub action: String,
+    pub documents: i64,
+    pub runs: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetentionStatusResponse {
+    pub enabled: bool,
+    /// Local hour at which the nightly run starts
+    pub run_hour: u32,
+    pub policies: Vec<RetentionPolicy>,
+    pub last_run: Option<RetentionRun>,
+    pub totals: Vec<RetentionTotal>,
+}
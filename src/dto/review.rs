@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateReviewRequest {
+    #[validate(range(min = 1, max = 5, message = "Rating must be between 1 and 5"))]
+    pub rating: i32,
+    #[validate(length(max = 2000, message = "Comment cannot exceed 2000 characters"))]
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct ModerateReviewRequest {
+    /// `published` or `hidden`
+    #[validate(length(min = 1, message = "Status is required"))]
+    pub status: String,
+    #[validate(length(max = 500, message = "Note cannot exceed 500 characters"))]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ReviewQuery {
+    pub status: Option<String>,
+    pub doctor_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewResponse {
+    pub id: String,
+    pub patient_id: String,
+    pub doctor_id: String,
+    pub appointment_id: String,
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderation_note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moderated_at: Option<String>,
+    pub created_at: String,
+}
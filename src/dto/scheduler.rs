@@ -0,0 +1,15 @@
+use serde::Serialize;
+use crate::models::{ScheduledJobRun, SchedulerLease};
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerStatusResponse {
+    /// The instance answering this request
+    pub instance_id: String,
+    /// Whether that instance runs the scheduled jobs
+    pub is_leader: bool,
+    pub lease_seconds: u64,
+    /// `None` when no instance holds the lease
+    pub lease: Option<SchedulerLease>,
+    /// Last run of each job, on whichever instance led at the time
+    pub jobs: Vec<ScheduledJobRun>,
+}
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use crate::dto::{
+    appointment::{AppointmentResponse, PatientSummary}, doctor::DoctorResponse,
+    medical_record::MedicalRecordResponse, medicine::MedicineResponse,
+};
+#[cfg(feature = "kits")]
+use crate::dto::kit::KitSummary;
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    /// Maximum hits per resource type (default 5, max 50)
+    pub limit: Option<i64>,
+    /// Comma-separated subset of: patients, doctors, medicines, appointments
+    pub types: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit<T> {
+    pub score: f64,
+    pub item: T,
+}
+
+/// Hits grouped by resource type. A group is omitted when it was not requested
+/// or the caller is not allowed to read that resource.
+#[derive(Debug, Serialize)]
+pub struct GlobalSearchResponse {
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patients: Option<Vec<SearchHit<MedicalRecordResponse>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doctors: Option<Vec<SearchHit<DoctorResponse>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medicines: Option<Vec<SearchHit<MedicineResponse>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub appointments: Option<Vec<SearchHit<AppointmentResponse>>>,
+}
+
+/// An entity a scanned value resolved to, tagged with its type
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", content = "item", rename_all = "snake_case")]
+pub enum LookupMatch {
+    Medicine(MedicineResponse),
+    #[cfg(feature = "kits")]
+    Kit(KitSummary),
+    Patient(PatientSummary),
+}
+
+#[derive(Debug, Serialize)]
+pub struct LookupResponse {
+    pub value: String,
+    /// Usually one; batch numbers can repeat across products
+    pub matches: Vec<LookupMatch>,
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateServiceAccountRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: String,
+    /// `resource:action` scopes, e.g. `observations:write`; action is `read`, `write` or `*`
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateServiceAccountRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be between 1 and 100 characters"))]
+    pub name: Option<String>,
+    #[validate(length(min = 1, message = "At least one scope is required"))]
+    pub scopes: Option<Vec<String>>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountResponse {
+    pub id: String,
+    pub name: String,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub active: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+    pub rotated_at: Option<String>,
+    pub last_used_at: Option<String>,
+}
+
+/// Returned on creation and rotation, the only times the client secret is visible
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccountCredentialsResponse {
+    #[serde(flatten)]
+    pub account: ServiceAccountResponse,
+    pub client_secret: String,
+}
+
+/// OAuth2 token request (RFC 6749 section 4.4), form encoded. Credentials may instead be
+/// sent with HTTP Basic authentication.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Space separated subset of the account's scopes; all of them when omitted
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+/// OAuth2 error body (RFC 6749 section 5.2)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TokenErrorResponse {
+    pub error: String,
+    pub error_description: String,
+}
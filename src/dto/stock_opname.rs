@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::status::StockOpnameStatus;
+
+/// Starts counting every batch, or only the batches of `master_medicine_ids`
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateStockOpnameRequest {
+    #[validate(length(max = 500, message = "Notes must be at most 500 characters"))]
+    pub notes: Option<String>,
+    #[validate(length(min = 1, message = "At least one master medicine ID is required"))]
+    pub master_medicine_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct StockCountDto {
+    #[validate(length(min = 24, max = 24, message = "Medicine IDs must be 24 characters"))]
+    pub medicine_id: String,
+    #[validate(range(min = 0.0, message = "Counted quantity cannot be negative"))]
+    pub counted_qty: f64,
+    #[validate(length(max = 200, message = "Notes must be at most 200 characters"))]
+    pub note: Option<String>,
+}
+
+/// Counts replace earlier counts of the same batches
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct SubmitStockCountsRequest {
+    #[validate(length(min = 1, max = 500, message = "Between 1 and 500 counts are required"), custom = "validate_counts")]
+    #[validate]
+    pub items: Vec<StockCountDto>,
+}
+
+/// One count per batch
+fn validate_counts(items: &[StockCountDto]) -> Result<(), ValidationError> {
+    for (i, item) in items.iter().enumerate() {
+        if items[..i].iter().any(|other| other.medicine_id == item.medicine_id) {
+            let mut error = ValidationError::new("duplicate_item");
+            error.message = Some(format!("Medicine {} is counted more than once", item.medicine_id).into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct StockOpnameQuery {
+    #[serde(default)]
+    #[validate(custom = "StockOpnameStatus::validate")]
+    pub status: Option<StockOpnameStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StockOpnameItemResponse {
+    pub medicine_id: String,
+    pub master_medicine_id: String,
+    pub batch_number: String,
+    pub trade_name: String,
+    pub expired_date: String,
+    pub expected_qty: f64,
+    pub counted_qty: Option<f64>,
+    pub variance: Option<f64>,
+    pub note: Option<String>,
+    pub counted_by: Option<String>,
+    pub counted_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StockOpnameResponse {
+    pub id: String,
+    pub notes: Option<String>,
+    pub status: StockOpnameStatus,
+    pub items: Vec<StockOpnameItemResponse>,
+    pub started_by: String,
+    pub started_at: String,
+    pub closed_by: Option<String>,
+    pub closed_at: Option<String>,
+}
+
+/// A batch whose count differs from the snapshot, valued at its purchase price
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StockVarianceLine {
+    pub medicine_id: String,
+    pub batch_number: String,
+    pub trade_name: String,
+    pub expected_qty: f64,
+    pub counted_qty: f64,
+    pub variance: f64,
+    pub variance_value: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StockOpnameReport {
+    pub id: String,
+    pub status: StockOpnameStatus,
+    pub started_at: String,
+    pub closed_at: Option<String>,
+    pub items: usize,
+    pub counted: usize,
+    /// Batches still to count; approval needs none
+    pub uncounted: usize,
+    /// Sum of the negative variances, as a positive quantity
+    pub shortage_qty: f64,
+    pub surplus_qty: f64,
+    /// Net variance at purchase prices
+    pub variance_value: f64,
+    pub lines: Vec<StockVarianceLine>,
+}
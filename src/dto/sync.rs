@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use mongodb::bson::Uuid;
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+use crate::status::{ConflictResolution, SyncConflictStatus, SyncEntity, SyncOperation, SyncOutcome, SyncStrategy};
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ReferenceSyncQuery {
+    /// `cursor` of the previous response, or an RFC 3339 timestamp; everything when absent
+    pub since: Option<String>,
+    /// Comma separated subset of `codes`, `interpretations` and `regions`; all by default
+    pub collections: Option<String>,
+    /// Most documents per collection, and deletions, in one response (default 500)
+    #[validate(range(min = 1, max = 5000, message = "Limit must be between 1 and 5000"))]
+    pub limit: Option<i64>,
+}
+
+/// Changes of one collection since the cursor. A document can be both updated and deleted
+/// within one response; apply `deleted` last.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CollectionChanges {
+    pub created: Vec<serde_json::Value>,
+    pub updated: Vec<serde_json::Value>,
+    /// Ids of deleted documents
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SyncChanges {
+    /// Pass as `since` on the next call
+    pub cursor: String,
+    /// More changes are waiting; call again with `cursor` right away
+    pub has_more: bool,
+    pub collections: BTreeMap<String, CollectionChanges>,
+}
+
+fn validate_uuid(value: &str) -> Result<(), ValidationError> {
+    if Uuid::parse_str(value.trim()).is_ok() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("uuid");
+    error.message = Some(format!("'{}' is not a UUID", value).into());
+    Err(error)
+}
+
+fn validate_timestamp(value: &str) -> Result<(), ValidationError> {
+    if chrono::DateTime::parse_from_rfc3339(value.trim()).is_ok() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("timestamp");
+    error.message = Some("changed_at must be an RFC 3339 timestamp".into());
+    Err(error)
+}
+
+/// A change made on a device while offline
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct ClientMutation {
+    /// Generated by the device; pushing the same UUID again returns the first outcome
+    #[validate(custom = "validate_uuid")]
+    pub client_id: String,
+    #[validate(custom = "SyncEntity::validate")]
+    pub entity: SyncEntity,
+    #[validate(custom = "SyncOperation::validate")]
+    pub operation: SyncOperation,
+    /// The document changed: its id, or the `client_id` of the create that made it offline.
+    /// Absent for creates.
+    pub entity_id: Option<String>,
+    /// Version the change was made on; required for updates and deletes
+    pub base_version: Option<i32>,
+    /// When the device made the change, RFC 3339
+    #[validate(custom = "validate_timestamp")]
+    pub changed_at: String,
+    /// Body of the matching API call, e.g. `POST /notes` for a create of a note
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct PushRequest {
+    #[validate(length(min = 1, max = 100, message = "Device ID must be between 1 and 100 characters"))]
+    pub device_id: String,
+    /// `last_writer_wins` by default
+    #[validate(custom = "SyncStrategy::validate")]
+    pub strategy: Option<SyncStrategy>,
+    /// Applied in order, so a create can be followed by edits of the same note
+    #[validate(length(min = 1, max = 200, message = "Push between 1 and 200 mutations at a time"))]
+    pub mutations: Vec<ClientMutation>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MutationResult {
+    pub client_id: String,
+    pub status: SyncOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    /// Version of the document after the mutation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    /// For `conflict`, the queued conflict
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The mutation had been pushed before; this is its first outcome
+    pub replayed: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PushResponse {
+    pub applied: usize,
+    pub conflicts: usize,
+    /// Superseded, rejected and failed mutations
+    pub not_applied: usize,
+    pub results: Vec<MutationResult>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct ClinicalPullQuery {
+    /// `cursor` of the previous response, or an RFC 3339 timestamp; everything when absent
+    pub since: Option<String>,
+    /// Comma separated medical record ids whose notes the device keeps
+    #[validate(length(min = 1, message = "medical_record_ids is required"))]
+    pub medical_record_ids: String,
+    /// Most changed and deleted notes in one response (default 500)
+    #[validate(range(min = 1, max = 5000, message = "Limit must be between 1 and 5000"))]
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+pub struct SyncConflictQuery {
+    #[validate(custom = "SyncConflictStatus::validate")]
+    pub status: Option<SyncConflictStatus>,
+}
+
+#[derive(Debug, Deserialize, Clone, Validate)]
+pub struct ResolveConflictRequest {
+    #[validate(custom = "ConflictResolution::validate")]
+    pub resolution: ConflictResolution,
+}
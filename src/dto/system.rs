@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    pub key: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageInfo {
+    pub backend: String,
+    pub bucket: Option<String>,
+    pub region: Option<String>,
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MongoTopology {
+    /// `standalone`, `replica_set` or `sharded`
+    pub kind: String,
+    pub set_name: Option<String>,
+    pub hosts: Vec<String>,
+    pub writable_primary: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexHealth {
+    pub expected: usize,
+    /// `collection.index` names the migration runner manages but the database lacks
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexState {
+    Present,
+    Missing,
+    /// Same name, other keys or uniqueness; `POST /admin/indexes/rebuild` leaves it alone
+    Differs,
+    /// In the database but not managed by the migration runner
+    Unmanaged,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IndexSpec {
+    pub keys: mongodb::bson::Document,
+    pub unique: bool,
+    /// `partialFilterExpression`, when only some documents are indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial: Option<mongodb::bson::Document>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IndexStatus {
+    pub name: String,
+    pub state: IndexState,
+    /// From `migrations::index_definitions`
+    pub expected: Option<IndexSpec>,
+    /// As listed by the database
+    pub actual: Option<IndexSpec>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionIndexes {
+    pub collection: String,
+    pub indexes: Vec<IndexStatus>,
+}
+
+/// Expected against actual indexes of every collection the migration runner manages
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexReport {
+    pub expected: usize,
+    pub missing: usize,
+    pub differing: usize,
+    pub unmanaged: usize,
+    pub collections: Vec<CollectionIndexes>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SlowQueryCount {
+    pub collection: String,
+    pub operation: String,
+    pub count: u64,
+    /// Slowest of them
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SystemInfoResponse {
+    pub version: String,
+    pub git_sha: String,
+    /// Cargo features compiled in
+    pub features: Vec<String>,
+    /// Keys of feature flags that are switched on
+    pub active_flags: Vec<String>,
+    pub storage: StorageInfo,
+    pub mongo: MongoTopology,
+    pub indexes: IndexHealth,
+    pub config_issues: Vec<ConfigIssue>,
+    /// Commands slower than `SLOW_QUERY_MS` since startup
+    pub slow_queries: Vec<SlowQueryCount>,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// `day` (default) or `week`
+    pub period: Option<String>,
+    /// Inclusive start date, `YYYY-MM-DD`
+    pub from: Option<String>,
+    /// Inclusive end date, `YYYY-MM-DD`
+    pub to: Option<String>,
+    /// Olson timezone or UTC offset used to cut days and weeks (default `UTC`)
+    pub tz: Option<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct UsageTotals {
+    pub observations: i64,
+    pub patients_served: i64,
+    pub active_hours: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageBucket {
+    pub period_start: String,
+    pub observations: i64,
+    pub patients_served: i64,
+    /// Distinct clock hours with at least one observation
+    pub active_hours: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageReport {
+    /// Kit code or operator NIK the report covers
+    pub subject: String,
+    pub period: String,
+    pub timezone: String,
+    pub totals: UsageTotals,
+    pub series: Vec<UsageBucket>,
+}
@@ -7,6 +7,8 @@ pub struct UserResponse {
     pub id: String,
     pub email: String,
     pub name: String,
+    pub phone: Option<String>,
+    pub email_verified_at: Option<String>,
     pub created_at: String,
     pub updated_at: Option<String>,
 }
@@ -22,4 +24,7 @@ pub struct UpdateUserRequest {
     #[serde(default)]
     #[validate(length(min = 1, message = "Name cannot be empty"))]
     pub name: Option<String>,
+    #[serde(default)]
+    #[validate(custom = "crate::phone::validate")]
+    pub phone: Option<String>,
 }
@@ -27,7 +27,7 @@ pub struct UserContactDto {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
     #[serde(rename = "nomor_telepon")]
-    #[validate(length(min = 10, message = "Phone number too short"))]
+    #[validate(custom = "crate::phone::validate")]
     pub nomor_telepon: String,
 }
 
@@ -71,6 +71,10 @@ pub struct CreateUserRoleRequest {
     pub organisasi: OrganizationEmbedDto,
     #[serde(rename = "is_active")]
     pub is_active: bool,
+    /// RFC 3339; the role grants nothing before it
+    pub valid_from: Option<String>,
+    /// RFC 3339; the role expires at this instant
+    pub valid_until: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Validate)]
@@ -83,6 +87,10 @@ pub struct UpdateUserRoleRequest {
     pub organisasi: Option<OrganizationEmbedDto>,
     #[serde(rename = "is_active")]
     pub is_active: Option<bool>,
+    /// RFC 3339; an empty string removes it
+    pub valid_from: Option<String>,
+    /// RFC 3339; an empty string removes it
+    pub valid_until: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -93,8 +101,62 @@ pub struct UserRoleResponse {
     pub organisasi: OrganizationEmbedDto,
     #[serde(rename = "is_active")]
     pub is_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
     #[serde(rename = "updated_at")]
     pub updated_at: String,
     #[serde(rename = "created_at")]
     pub created_at: String,
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct BulkCreateUserRoleRequest {
+    /// Each is validated and created on its own
+    #[validate(length(min = 1, max = 200, message = "Between 1 and 200 assignments per request"))]
+    pub assignments: Vec<CreateUserRoleRequest>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BulkUserRoleResult {
+    /// Position in `assignments`
+    pub index: usize,
+    /// `created`, `invalid`, `duplicate` or `failed`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_role: Option<UserRoleResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BulkUserRoleResponse {
+    pub created: usize,
+    pub failed: usize,
+    pub results: Vec<BulkUserRoleResult>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OrganizationMembersQuery {
+    /// Also list inactive and expired assignments
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MemberRoleResponse {
+    pub user_role_id: String,
+    pub role: RoleEmbedDto,
+    pub is_active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OrganizationMemberResponse {
+    pub user: UserEmbedDto,
+    pub roles: Vec<MemberRoleResponse>,
+}
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct JoinWaitlistRequest {
+    #[validate(length(min = 24, max = 24, message = "Patient IDs must be 24 characters"))]
+    pub patient_id: String,
+    #[validate(length(min = 24, max = 24, message = "Doctor IDs must be 24 characters"))]
+    pub doctor_id: String,
+    #[validate(length(equal = 10, message = "Date must be YYYY-MM-DD"))]
+    pub date: String,
+    /// Preferred time; leave empty to accept any slot of the day
+    #[validate(length(min = 1, message = "Time cannot be empty"))]
+    pub time: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct WaitlistQuery {
+    pub doctor_id: Option<String>,
+    pub date: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistEntryResponse {
+    pub id: String,
+    pub patient_id: String,
+    pub doctor_id: String,
+    pub date: String,
+    pub time: Option<String>,
+    pub status: String,
+    pub hold_appointment_id: Option<String>,
+    pub hold_expires_at: Option<String>,
+    pub notified_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+use crate::status::BedStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateWardRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: String,
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: String,
+    pub class: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateWardRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: Option<String>,
+    #[validate(length(min = 1, max = 200, message = "Name must be between 1 and 200 characters"))]
+    pub name: Option<String>,
+    pub class: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct WardQuery {
+    pub organization_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WardResponse {
+    pub id: String,
+    pub code: String,
+    pub name: String,
+    pub class: Option<String>,
+    pub organization_id: Option<String>,
+    pub created_at: String,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct CreateBedRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: String,
+}
+
+/// Occupancy changes through admissions; this sets the other statuses
+#[derive(Debug, Serialize, Deserialize, Clone, Validate)]
+pub struct UpdateBedRequest {
+    #[validate(length(min = 1, max = 50, message = "Code must be between 1 and 50 characters"))]
+    pub code: Option<String>,
+    #[validate(custom = "BedStatus::validate")]
+    pub status: Option<BedStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BedResponse {
+    pub id: String,
+    pub ward_id: String,
+    pub code: String,
+    pub status: BedStatus,
+    pub patient_id: Option<String>,
+    pub admission_id: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct OccupancyCounts {
+    pub total: i64,
+    pub available: i64,
+    pub occupied: i64,
+    pub cleaning: i64,
+    pub maintenance: i64,
+    /// `occupied / total`, 0 for a ward without beds
+    pub occupancy_rate: f64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WardOccupancy {
+    pub ward_id: String,
+    pub code: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub counts: OccupancyCounts,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OccupancyResponse {
+    pub wards: Vec<WardOccupancy>,
+    pub totals: OccupancyCounts,
+    pub generated_at: String,
+}
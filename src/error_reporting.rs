@@ -0,0 +1,316 @@
+//! Optional Sentry reporting of panics and 5xx responses.
+//!
+//! Built with the `sentry` feature and with `SENTRY_DSN` set, every 5xx `ErrorResponse` is
+//! sent with its route, user id and request id (echoed to the client in `X-Request-Id`), and
+//! so is any panic inside a handler. `SENTRY_ENVIRONMENT` names the deployment,
+//! `SENTRY_SAMPLE_RATE` (default 1.0) and `SENTRY_TRACES_SAMPLE_RATE` (default 0) sample
+//! events and transactions.
+//!
+//! Nothing identifying a patient may leave: fields matching the rules in
+//! `SENTRY_SCRUB_FIELDS` (added to the defaults, matched like `REQUEST_LOG_REDACT_FIELDS`)
+//! are replaced, and long digit runs (NIKs, phone and card numbers) and e-mail addresses are
+//! masked in every message before an event is sent.
+
+use std::env;
+use serde_json::Value;
+use crate::request_log::{matches_rules, REDACTED};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const DEFAULT_SCRUB_RULES: &[&str] = &[
+    "password", "secret", "token", "nik", "otp", "authorization", "apikey", "cookie",
+    "phone", "email", "address", "birth", "=name", "patientname", "diagnosis", "note",
+];
+/// Digit runs at least this long are masked; shorter ones are counts, codes or years
+const MIN_MASKED_DIGITS: usize = 8;
+/// Longest client-supplied request id kept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReportingConfig {
+    pub dsn: Option<String>,
+    pub environment: Option<String>,
+    pub sample_rate: f32,
+    pub traces_sample_rate: f32,
+    pub scrub_rules: Vec<String>,
+}
+
+impl Default for ErrorReportingConfig {
+    fn default() -> Self {
+        Self {
+            dsn: None,
+            environment: None,
+            sample_rate: 1.0,
+            traces_sample_rate: 0.0,
+            scrub_rules: DEFAULT_SCRUB_RULES.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+}
+
+fn rate(name: &str, default: f32) -> f32 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<f32>().ok())
+        .filter(|rate| (0.0..=1.0).contains(rate))
+        .unwrap_or(default)
+}
+
+impl ErrorReportingConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let mut scrub_rules = defaults.scrub_rules;
+        if let Ok(extra) = env::var("SENTRY_SCRUB_FIELDS") {
+            scrub_rules.extend(extra.split(',').map(str::trim).filter(|r| !r.is_empty()).map(str::to_string));
+        }
+        let non_empty = |name| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+        Self {
+            dsn: non_empty("SENTRY_DSN"),
+            environment: non_empty("SENTRY_ENVIRONMENT"),
+            sample_rate: rate("SENTRY_SAMPLE_RATE", defaults.sample_rate),
+            traces_sample_rate: rate("SENTRY_TRACES_SAMPLE_RATE", defaults.traces_sample_rate),
+            scrub_rules,
+        }
+    }
+
+    /// `text` with long digit runs and e-mail addresses masked
+    pub fn scrub_text(&self, text: &str) -> String {
+        text.split_inclusive(char::is_whitespace)
+            .map(|word| {
+                let trimmed = word.trim_end();
+                if trimmed.contains('@') && trimmed.contains('.') {
+                    return format!("{}{}", REDACTED, &word[trimmed.len()..]);
+                }
+                mask_digit_runs(word)
+            })
+            .collect()
+    }
+
+    /// Scrub a JSON value in place: sensitive fields are replaced, strings are masked
+    pub fn scrub_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if matches_rules(key, &self.scrub_rules) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.scrub_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub_value(item)),
+            Value::String(text) => *text = self.scrub_text(text),
+            _ => {}
+        }
+    }
+}
+
+/// Mask digit runs that stand alone; those inside hex ids and codes are kept
+fn mask_digit_runs(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut masked = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            masked.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let end = (i..chars.len()).find(|j| !chars[*j].is_ascii_digit()).unwrap_or(chars.len());
+        let glued = (i > 0 && chars[i - 1].is_alphabetic()) || chars.get(end).is_some_and(|c| c.is_alphabetic());
+        if end - i >= MIN_MASKED_DIGITS && !glued {
+            masked.push_str(REDACTED);
+        } else {
+            masked.extend(&chars[i..end]);
+        }
+        i = end;
+    }
+    masked
+}
+
+/// A client-supplied request id when it is short and printable, otherwise a new one
+pub fn request_id(supplied: Option<&str>) -> String {
+    supplied
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| mongodb::bson::oid::ObjectId::new().to_hex())
+}
+
+/// What a 5xx `ErrorResponse` said, kept on the response for the reporting middleware
+#[derive(Debug, Clone)]
+pub struct ReportedError {
+    pub code: String,
+    pub message: String,
+    pub details: Option<String>,
+}
+
+/// The request an event belongs to
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub method: String,
+    /// The matched route, e.g. `/medical-records/:id`, never the raw path
+    pub route: String,
+    pub user_id: Option<String>,
+}
+
+/// Keeps the client alive; dropping it flushes queued events
+pub struct ReportingGuard {
+    #[cfg(feature = "sentry")]
+    _guard: Option<sentry::ClientInitGuard>,
+}
+
+/// Start reporting when a DSN is configured
+#[cfg_attr(not(feature = "sentry"), allow(unused_variables))]
+pub fn init(config: &ErrorReportingConfig) -> ReportingGuard {
+    #[cfg(feature = "sentry")]
+    return ReportingGuard { _guard: reporter::init(config) };
+    #[cfg(not(feature = "sentry"))]
+    ReportingGuard {}
+}
+
+pub fn is_enabled() -> bool {
+    #[cfg(feature = "sentry")]
+    return sentry::Hub::current().client().is_some_and(|client| client.is_enabled());
+    #[cfg(not(feature = "sentry"))]
+    false
+}
+
+/// Run a request with `context` attached to whatever is reported while it runs
+#[cfg_attr(not(feature = "sentry"), allow(unused_variables))]
+pub async fn with_context<F: std::future::Future>(context: &RequestContext, future: F) -> F::Output {
+    #[cfg(feature = "sentry")]
+    return reporter::with_context(context, future).await;
+    #[cfg(not(feature = "sentry"))]
+    future.await
+}
+
+/// Report a 5xx response of the request; call it inside `with_context`
+#[cfg_attr(not(feature = "sentry"), allow(unused_variables))]
+pub fn report(context: &RequestContext, status: u16, error: Option<&ReportedError>) {
+    #[cfg(feature = "sentry")]
+    reporter::report(context, status, error);
+}
+
+#[cfg(feature = "sentry")]
+mod reporter {
+    use std::future::Future;
+    use std::sync::Arc;
+    use sentry::{protocol::{Event, Level, User}, Hub, SentryFutureExt};
+    use super::{ErrorReportingConfig, ReportedError, RequestContext};
+
+    pub fn init(config: &ErrorReportingConfig) -> Option<sentry::ClientInitGuard> {
+        let dsn = config.dsn.as_deref()?.parse().map_err(|e| tracing::error!(error = %e, "Invalid SENTRY_DSN")).ok()?;
+        let scrubber = config.clone();
+        let options = sentry::ClientOptions {
+            dsn: Some(dsn),
+            environment: config.environment.clone().map(Into::into),
+            release: sentry::release_name!(),
+            sample_rate: config.sample_rate,
+            traces_sample_rate: config.traces_sample_rate,
+            send_default_pii: false,
+            before_send: Some(Arc::new(move |event| Some(scrub(&scrubber, event)))),
+            ..Default::default()
+        };
+        Some(sentry::init(options))
+    }
+
+    /// Mask everything an event could carry about a patient
+    fn scrub(config: &ErrorReportingConfig, mut event: Event<'static>) -> Event<'static> {
+        event.message = event.message.map(|m| config.scrub_text(&m));
+        for exception in event.exception.values.iter_mut() {
+            exception.value = exception.value.as_deref().map(|v| config.scrub_text(v));
+        }
+        for breadcrumb in event.breadcrumbs.values.iter_mut() {
+            breadcrumb.message = breadcrumb.message.as_deref().map(|m| config.scrub_text(m));
+            breadcrumb.data.clear();
+        }
+        for (_, value) in event.extra.iter_mut() {
+            config.scrub_value(value);
+        }
+        event.extra.retain(|key, _| !crate::request_log::matches_rules(key, &config.scrub_rules));
+        for (_, value) in event.tags.iter_mut() {
+            *value = config.scrub_text(value);
+        }
+        // Only the id is sent; request bodies, headers and addresses never are
+        event.request = None;
+        if let Some(user) = event.user.as_mut() {
+            *user = User { id: user.id.take(), ..Default::default() };
+        }
+        event
+    }
+
+    pub async fn with_context<F: Future>(context: &RequestContext, future: F) -> F::Output {
+        let hub = Arc::new(Hub::new_from_top(Hub::current()));
+        hub.configure_scope(|scope| {
+            scope.set_tag("request_id", &context.request_id);
+            scope.set_tag("http.method", &context.method);
+            scope.set_tag("route", &context.route);
+            scope.set_transaction(Some(&format!("{} {}", context.method, context.route)));
+            if let Some(user_id) = &context.user_id {
+                scope.set_user(Some(User { id: Some(user_id.clone()), ..Default::default() }));
+            }
+        });
+        future.bind_hub(hub).await
+    }
+
+    pub fn report(context: &RequestContext, status: u16, error: Option<&ReportedError>) {
+        let mut event = Event {
+            level: Level::Error,
+            message: Some(match error {
+                Some(error) => format!("{} {}: {}", context.method, context.route, error.message),
+                None => format!("{} {} answered {}", context.method, context.route, status),
+            }),
+            ..Default::default()
+        };
+        event.tags.insert("http.status_code".to_string(), status.to_string());
+        if let Some(error) = error {
+            event.tags.insert("error.code".to_string(), error.code.clone());
+            if let Some(details) = &error.details {
+                event.extra.insert("details".to_string(), details.clone().into());
+            }
+        }
+        // Sent through the request's hub, which carries its tags and user
+        Hub::current().capture_event(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_identifiers_in_text() {
+        let config = ErrorReportingConfig::default();
+        assert_eq!(
+            config.scrub_text("Duplicate key nik 3201010101010001 for budi@example.com, 3 retries in 2024"),
+            "Duplicate key nik [REDACTED] for [REDACTED] 3 retries in 2024",
+        );
+        assert_eq!(config.scrub_text("phone +6281234567890."), "phone +[REDACTED].");
+    }
+
+    #[test]
+    fn scrubs_sensitive_fields_and_nested_strings() {
+        let config = ErrorReportingConfig::default();
+        let mut value = serde_json::json!({
+            "nik": "3201010101010001",
+            "name": "Budi",
+            "doctor_id": "65a1b2c3d4e5f60718293a4b",
+            "errors": [{ "message": "0812345678901 is already registered" }],
+        });
+        config.scrub_value(&mut value);
+        assert_eq!(value, serde_json::json!({
+            "nik": REDACTED,
+            "name": REDACTED,
+            "doctor_id": "65a1b2c3d4e5f60718293a4b",
+            "errors": [{ "message": "[REDACTED] is already registered" }],
+        }));
+    }
+
+    #[test]
+    fn keeps_only_reasonable_request_ids() {
+        assert_eq!(request_id(Some("abc-123")), "abc-123");
+        assert_eq!(request_id(Some("bad id")).len(), 24);
+        assert_eq!(request_id(None).len(), 24);
+    }
+}
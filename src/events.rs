@@ -0,0 +1,110 @@
+//! In-process domain event bus.
+//!
+//! Handlers publish an event after a successful write; background workers
+//! (search sync, notifications, ...) subscribe and react asynchronously. Writes made outside
+//! the API are published by the change stream watcher, see `crate::change_streams`.
+
+use tokio::sync::broadcast;
+
+const DEFAULT_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::Created => "created",
+            EventKind::Updated => "updated",
+            EventKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// Where the write behind an event was seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EventSource {
+    #[default]
+    Api,
+    /// A write outside the API, seen on a MongoDB change stream
+    ChangeStream,
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainEvent {
+    pub kind: EventKind,
+    /// MongoDB collection of the affected document
+    pub collection: String,
+    /// Hex ObjectId of the affected document
+    pub id: String,
+    pub occurred_at: String,
+    /// Snapshot of the document, for subscribers that cannot load it any more (deletions)
+    pub data: Option<serde_json::Value>,
+    pub source: EventSource,
+}
+
+impl DomainEvent {
+    pub fn new(kind: EventKind, collection: &str, id: &str) -> Self {
+        Self {
+            kind,
+            collection: collection.to_string(),
+            id: id.to_string(),
+            occurred_at: chrono::Utc::now().to_rfc3339(),
+            data: None,
+            source: EventSource::Api,
+        }
+    }
+
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn with_source(mut self, source: EventSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn created(collection: &str, id: &str) -> Self {
+        Self::new(EventKind::Created, collection, id)
+    }
+
+    pub fn updated(collection: &str, id: &str) -> Self {
+        Self::new(EventKind::Updated, collection, id)
+    }
+
+    pub fn deleted(collection: &str, id: &str) -> Self {
+        Self::new(EventKind::Deleted, collection, id)
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(DEFAULT_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publish an event. Having no subscribers is not an error.
+    pub fn publish(&self, event: DomainEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,195 @@
+//! Feature flags.
+//!
+//! Flags live in the `feature_flags` collection and are cached in `AppState::feature_flags`
+//! for `FEATURE_FLAG_CACHE_SECONDS` (default 30); admin changes invalidate the cache at once
+//! on this instance. An enabled flag is on for callers in one of its `organizations` and for
+//! `rollout_percentage` percent of everyone else, bucketed by a stable hash of the flag key
+//! and user id so a caller keeps the same answer across requests.
+//!
+//! Handlers take the `Flags` extractor and ask `flags.is_enabled("new_billing")`.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use mongodb::Database;
+use tokio::sync::RwLock;
+use crate::db::AppState;
+use crate::middleware::AuthUser;
+use crate::models::FeatureFlag;
+use crate::repository::{FeatureFlagRepository, UserRoleRepository};
+
+pub const DEFAULT_CACHE_SECONDS: u64 = 30;
+
+/// Who a flag is evaluated for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagContext {
+    pub user_id: Option<String>,
+    pub organizations: Vec<String>,
+}
+
+/// Stable 0-99 bucket of a subject for one flag (FNV-1a), so rollouts of different flags
+/// do not select the same users.
+pub fn bucket(key: &str, subject: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in key.bytes().chain([b':']).chain(subject.bytes()) {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash % 100
+}
+
+pub fn evaluate(flag: &FeatureFlag, context: &FlagContext) -> bool {
+    if !flag.enabled {
+        return false;
+    }
+    if context.organizations.iter().any(|org| flag.organizations.contains(org)) {
+        return true;
+    }
+    match flag.rollout_percentage {
+        p if p >= 100 => true,
+        p if p <= 0 => false,
+        p => context.user_id.as_deref().is_some_and(|id| bucket(&flag.key, id) < p as u32),
+    }
+}
+
+type FlagMap = Arc<HashMap<String, FeatureFlag>>;
+
+/// Flags by key, reloaded from MongoDB once the cached copy is older than its TTL.
+pub struct FlagCache {
+    ttl: Duration,
+    entry: RwLock<Option<(Instant, FlagMap)>>,
+}
+
+impl Default for FlagCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_CACHE_SECONDS))
+    }
+}
+
+impl FlagCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entry: RwLock::new(None) }
+    }
+
+    pub fn from_env() -> Self {
+        let seconds = env::var("FEATURE_FLAG_CACHE_SECONDS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CACHE_SECONDS);
+        Self::new(Duration::from_secs(seconds))
+    }
+
+    pub async fn get(&self, db: &Database) -> Result<FlagMap, String> {
+        if let Some((loaded, flags)) = self.entry.read().await.as_ref() {
+            if loaded.elapsed() < self.ttl {
+                return Ok(flags.clone());
+            }
+        }
+
+        let flags: FlagMap = Arc::new(
+            FeatureFlagRepository::new(db.clone())
+                .find_all()
+                .await?
+                .into_iter()
+                .map(|flag| (flag.key.clone(), flag))
+                .collect(),
+        );
+        *self.entry.write().await = Some((Instant::now(), flags.clone()));
+        Ok(flags)
+    }
+
+    pub async fn invalidate(&self) {
+        *self.entry.write().await = None;
+    }
+}
+
+/// Flags evaluated for the current caller. Unknown keys are off.
+#[derive(Debug, Clone, Default)]
+pub struct Flags {
+    flags: FlagMap,
+    context: FlagContext,
+}
+
+impl Flags {
+    pub fn new(flags: FlagMap, context: FlagContext) -> Self {
+        Self { flags, context }
+    }
+
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.flags.get(key).is_some_and(|flag| evaluate(flag, &self.context))
+    }
+
+    /// Every known flag and whether it is on for this caller
+    pub fn evaluated(&self) -> HashMap<String, bool> {
+        self.flags.iter().map(|(key, flag)| (key.clone(), evaluate(flag, &self.context))).collect()
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for Flags {
+    type Rejection = std::convert::Infallible;
+
+    /// Never rejects: when flags or organizations cannot be loaded every flag reads as off.
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let flags = match state.feature_flags.get(&state.db).await {
+            Ok(flags) => flags,
+            Err(e) => {
+                tracing::error!(error = %e, "Loading feature flags failed");
+                return Ok(Self::default());
+            }
+        };
+
+        let user = parts.extensions.get::<AuthUser>();
+        let mut context = FlagContext { user_id: user.map(|u| u.id.clone()), organizations: Vec::new() };
+        if let Some(user) = user.filter(|u| u.patient_id.is_none() && u.service.is_none() && u.kiosk.is_none()) {
+            match UserRoleRepository::new(state.db.clone()).find_active_organization_ids(&user.id).await {
+                Ok(organizations) => context.organizations = organizations,
+                Err(e) => tracing::error!(error = %e, "Loading organizations for feature flags failed"),
+            }
+        }
+
+        Ok(Self::new(flags, context))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(enabled: bool, organizations: &[&str], rollout_percentage: i32) -> FeatureFlag {
+        FeatureFlag {
+            id: None,
+            key: "new_billing".to_string(),
+            description: None,
+            enabled,
+            organizations: organizations.iter().map(|o| o.to_string()).collect(),
+            rollout_percentage,
+            created_at: mongodb::bson::DateTime::now(),
+            updated_at: None,
+        }
+    }
+
+    fn user(id: &str, organizations: &[&str]) -> FlagContext {
+        FlagContext { user_id: Some(id.to_string()), organizations: organizations.iter().map(|o| o.to_string()).collect() }
+    }
+
+    #[test]
+    fn organizations_and_switch_take_precedence() {
+        assert!(evaluate(&flag(true, &["rs-1"], 0), &user("u1", &["rs-1"])));
+        assert!(!evaluate(&flag(true, &["rs-1"], 0), &user("u1", &["rs-2"])));
+        assert!(!evaluate(&flag(false, &["rs-1"], 100), &user("u1", &["rs-1"])));
+        assert!(evaluate(&flag(true, &[], 100), &FlagContext::default()));
+    }
+
+    #[test]
+    fn percentage_rollout_is_stable_and_proportional() {
+        let half = flag(true, &[], 50);
+        let on = (0..1000).filter(|i| evaluate(&half, &user(&format!("user-{}", i), &[]))).count();
+        assert!((400..600).contains(&on), "{} of 1000 enabled", on);
+
+        assert_eq!(bucket("new_billing", "u1"), bucket("new_billing", "u1"));
+        assert!(!evaluate(&half, &FlagContext::default()));
+    }
+}
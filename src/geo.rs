@@ -0,0 +1,87 @@
+//! Locations of kits and organizations.
+//!
+//! Stored as GeoJSON points (`{"type": "Point", "coordinates": [lng, lat]}`) under `location`,
+//! which the `2dsphere` indexes from `crate::migrations` cover; the API speaks `latitude` and
+//! `longitude`. Map clusters group points into a grid whose cells halve with every zoom level,
+//! like web map tiles.
+
+use mongodb::bson::{doc, Bson};
+use serde::{Deserialize, Serialize};
+
+/// Degrees of longitude per cluster cell at zoom 0; a quarter of a world tile
+const CELL_DEGREES_AT_ZOOM_0: f64 = 90.0;
+pub const MAX_ZOOM: u8 = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GeoPoint {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// `[longitude, latitude]`, in GeoJSON order
+    pub coordinates: [f64; 2],
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self { kind: "Point".to_string(), coordinates: [longitude, latitude] }
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.coordinates[1]
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.coordinates[0]
+    }
+}
+
+impl From<&GeoPoint> for Bson {
+    fn from(point: &GeoPoint) -> Self {
+        Bson::Document(doc! { "type": &point.kind, "coordinates": point.coordinates.to_vec() })
+    }
+}
+
+/// A point from an optional `latitude` and `longitude` pair; `Ok(None)` when both are absent,
+/// an error when only one is given or either is out of range
+pub fn point(latitude: Option<f64>, longitude: Option<f64>) -> Result<Option<GeoPoint>, String> {
+    match (latitude, longitude) {
+        (None, None) => Ok(None),
+        (Some(latitude), Some(longitude)) => {
+            if !(-90.0..=90.0).contains(&latitude) {
+                return Err("latitude must be between -90 and 90".to_string());
+            }
+            if !(-180.0..=180.0).contains(&longitude) {
+                return Err("longitude must be between -180 and 180".to_string());
+            }
+            Ok(Some(GeoPoint::new(latitude, longitude)))
+        }
+        _ => Err("latitude and longitude must be given together".to_string()),
+    }
+}
+
+/// Width in degrees of a cluster cell at `zoom`
+pub fn cell_degrees(zoom: u8) -> f64 {
+    CELL_DEGREES_AT_ZOOM_0 / f64::from(1u32 << zoom.min(MAX_ZOOM))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_need_both_coordinates_in_range() {
+        let jakarta = point(Some(-6.2), Some(106.8)).unwrap().unwrap();
+        assert_eq!(jakarta.coordinates, [106.8, -6.2]);
+        assert_eq!(jakarta.latitude(), -6.2);
+        assert_eq!(point(None, None), Ok(None));
+        assert!(point(Some(-6.2), None).is_err());
+        assert!(point(Some(91.0), Some(106.8)).is_err());
+        assert!(point(Some(-6.2), Some(181.0)).is_err());
+    }
+
+    #[test]
+    fn cells_halve_with_each_zoom_level() {
+        assert_eq!(cell_degrees(0), 90.0);
+        assert_eq!(cell_degrees(3), 11.25);
+        assert_eq!(cell_degrees(30), cell_degrees(MAX_ZOOM));
+    }
+}
@@ -0,0 +1,294 @@
+//! WHO Child Growth Standards (0–60 months) z-score and percentile calculation.
+//!
+//! Reference LMS parameters are embedded for selected ages and linearly interpolated in
+//! between. Z-scores use the LMS method; weight-for-age beyond ±3 SD uses the WHO
+//! restricted extrapolation so extreme values are not over-stated.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthMetric {
+    Weight,
+    Height,
+}
+
+impl GrowthMetric {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "weight" | "wfa" => Some(Self::Weight),
+            "height" | "length" | "lhfa" => Some(Self::Height),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Weight => "weight",
+            Self::Height => "height",
+        }
+    }
+
+    /// LOINC codes of observations carrying this measurement.
+    pub fn coding_codes(&self) -> &'static [&'static str] {
+        match self {
+            // Body weight, body weight measured
+            Self::Weight => &["29463-7", "3141-9"],
+            // Body height, body height lying (length), body height measured
+            Self::Height => &["8302-2", "8306-3", "3137-7"],
+        }
+    }
+
+    /// Unit the reference tables are expressed in.
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Weight => "kg",
+            Self::Height => "cm",
+        }
+    }
+
+    /// Convert a measurement into the reference unit; `None` for unknown units.
+    pub fn normalize(&self, value: f64, unit: &str) -> Option<f64> {
+        match (self, unit.trim().to_lowercase().as_str()) {
+            (Self::Weight, "kg" | "kilogram" | "[kg]") => Some(value),
+            (Self::Weight, "g" | "gram") => Some(value / 1000.0),
+            (Self::Height, "cm" | "centimeter") => Some(value),
+            (Self::Height, "m" | "meter") => Some(value * 100.0),
+            (Self::Height, "mm" | "millimeter") => Some(value / 10.0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl Sex {
+    /// Accepts Indonesian (`L`/`P`, `laki-laki`/`perempuan`) and English forms.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "l" | "laki-laki" | "laki laki" | "m" | "male" | "pria" => Some(Self::Male),
+            "p" | "perempuan" | "f" | "female" | "wanita" => Some(Self::Female),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Male => "male",
+            Self::Female => "female",
+        }
+    }
+}
+
+/// LMS parameters at an age in months.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lms {
+    pub month: f64,
+    pub l: f64,
+    pub m: f64,
+    pub s: f64,
+}
+
+const fn lms(month: f64, l: f64, m: f64, s: f64) -> Lms {
+    Lms { month, l, m, s }
+}
+
+pub const MAX_AGE_MONTHS: f64 = 60.0;
+
+const WEIGHT_FOR_AGE_BOYS: &[Lms] = &[
+    lms(0.0, 0.3487, 3.3464, 0.14602),
+    lms(1.0, 0.2297, 4.4709, 0.13395),
+    lms(2.0, 0.1970, 5.5675, 0.12385),
+    lms(3.0, 0.1738, 6.3762, 0.11727),
+    lms(4.0, 0.1553, 7.0023, 0.11316),
+    lms(5.0, 0.1395, 7.5105, 0.11080),
+    lms(6.0, 0.1257, 7.9340, 0.10958),
+    lms(9.0, 0.0917, 8.9014, 0.10881),
+    lms(12.0, 0.0644, 9.6479, 0.10925),
+    lms(18.0, 0.0211, 10.9385, 0.11046),
+    lms(24.0, -0.0137, 12.1515, 0.11426),
+    lms(36.0, -0.0613, 14.3429, 0.11939),
+    lms(48.0, -0.1103, 16.3489, 0.12530),
+    lms(60.0, -0.1506, 18.3366, 0.13061),
+];
+
+const WEIGHT_FOR_AGE_GIRLS: &[Lms] = &[
+    lms(0.0, 0.3809, 3.2322, 0.14171),
+    lms(1.0, 0.1714, 4.1873, 0.13724),
+    lms(2.0, 0.0962, 5.1282, 0.13000),
+    lms(3.0, 0.0402, 5.8458, 0.12619),
+    lms(4.0, -0.0050, 6.4237, 0.12402),
+    lms(5.0, -0.0430, 6.8985, 0.12274),
+    lms(6.0, -0.0756, 7.2970, 0.12204),
+    lms(9.0, -0.1519, 8.2254, 0.12166),
+    lms(12.0, -0.2024, 8.9481, 0.12268),
+    lms(18.0, -0.2637, 10.2315, 0.12565),
+    lms(24.0, -0.2941, 11.4775, 0.12904),
+    lms(36.0, -0.3367, 13.8503, 0.13550),
+    lms(48.0, -0.3730, 16.0697, 0.14087),
+    lms(60.0, -0.4005, 18.2193, 0.14491),
+];
+
+// Length (recumbent) up to 24 months, standing height from 24 months.
+const HEIGHT_FOR_AGE_BOYS: &[Lms] = &[
+    lms(0.0, 1.0, 49.8842, 0.03795),
+    lms(1.0, 1.0, 54.7244, 0.03557),
+    lms(2.0, 1.0, 58.4249, 0.03424),
+    lms(3.0, 1.0, 61.4292, 0.03328),
+    lms(4.0, 1.0, 63.8860, 0.03257),
+    lms(5.0, 1.0, 65.9026, 0.03204),
+    lms(6.0, 1.0, 67.6236, 0.03165),
+    lms(9.0, 1.0, 72.0036, 0.03103),
+    lms(12.0, 1.0, 75.7488, 0.03137),
+    lms(18.0, 1.0, 82.2587, 0.03279),
+    lms(24.0, 1.0, 87.1161, 0.03507),
+    lms(36.0, 1.0, 96.0835, 0.03776),
+    lms(48.0, 1.0, 103.3273, 0.04059),
+    lms(60.0, 1.0, 110.2647, 0.04217),
+];
+
+const HEIGHT_FOR_AGE_GIRLS: &[Lms] = &[
+    lms(0.0, 1.0, 49.1477, 0.03790),
+    lms(1.0, 1.0, 53.6872, 0.03640),
+    lms(2.0, 1.0, 57.0673, 0.03568),
+    lms(3.0, 1.0, 59.8029, 0.03520),
+    lms(4.0, 1.0, 62.0899, 0.03486),
+    lms(5.0, 1.0, 64.0301, 0.03463),
+    lms(6.0, 1.0, 65.7311, 0.03448),
+    lms(9.0, 1.0, 70.1435, 0.03433),
+    lms(12.0, 1.0, 74.0150, 0.03479),
+    lms(18.0, 1.0, 80.7079, 0.03637),
+    lms(24.0, 1.0, 85.7153, 0.03764),
+    lms(36.0, 1.0, 95.0515, 0.03980),
+    lms(48.0, 1.0, 102.7312, 0.04170),
+    lms(60.0, 1.0, 109.4233, 0.04348),
+];
+
+pub fn reference_table(metric: GrowthMetric, sex: Sex) -> &'static [Lms] {
+    match (metric, sex) {
+        (GrowthMetric::Weight, Sex::Male) => WEIGHT_FOR_AGE_BOYS,
+        (GrowthMetric::Weight, Sex::Female) => WEIGHT_FOR_AGE_GIRLS,
+        (GrowthMetric::Height, Sex::Male) => HEIGHT_FOR_AGE_BOYS,
+        (GrowthMetric::Height, Sex::Female) => HEIGHT_FOR_AGE_GIRLS,
+    }
+}
+
+/// Age in months from the `usia` (years, months, days) recorded on an observation.
+pub fn age_in_months(years: i32, months: i32, days: i32) -> f64 {
+    years as f64 * 12.0 + months as f64 + days as f64 / 30.4375
+}
+
+/// LMS parameters at `age_months`, interpolated; `None` outside 0–60 months.
+pub fn lms_at(metric: GrowthMetric, sex: Sex, age_months: f64) -> Option<Lms> {
+    if !(0.0..=MAX_AGE_MONTHS).contains(&age_months) {
+        return None;
+    }
+    let table = reference_table(metric, sex);
+    let upper = table.iter().position(|row| row.month >= age_months)?;
+    if upper == 0 || table[upper].month == age_months {
+        return Some(table[upper]);
+    }
+
+    let (a, b) = (table[upper - 1], table[upper]);
+    let t = (age_months - a.month) / (b.month - a.month);
+    let lerp = |x: f64, y: f64| x + (y - x) * t;
+    Some(Lms { month: age_months, l: lerp(a.l, b.l), m: lerp(a.m, b.m), s: lerp(a.s, b.s) })
+}
+
+/// Measurement at a given z-score.
+pub fn value_at(lms: &Lms, z: f64) -> f64 {
+    if lms.l == 0.0 {
+        lms.m * (lms.s * z).exp()
+    } else {
+        lms.m * (1.0 + lms.l * lms.s * z).powf(1.0 / lms.l)
+    }
+}
+
+pub fn z_score(metric: GrowthMetric, lms: &Lms, value: f64) -> f64 {
+    let z = if lms.l == 0.0 {
+        (value / lms.m).ln() / lms.s
+    } else {
+        ((value / lms.m).powf(lms.l) - 1.0) / (lms.l * lms.s)
+    };
+
+    if metric != GrowthMetric::Weight || z.abs() <= 3.0 {
+        return z;
+    }
+
+    // WHO restricted extrapolation: beyond ±3 SD, distances are measured in units of the 2–3 SD gap
+    if z > 3.0 {
+        let sd3 = value_at(lms, 3.0);
+        3.0 + (value - sd3) / (sd3 - value_at(lms, 2.0))
+    } else {
+        let sd3 = value_at(lms, -3.0);
+        -3.0 + (value - sd3) / (value_at(lms, -2.0) - sd3)
+    }
+}
+
+/// Percentile (0–100) of a z-score under the standard normal distribution.
+pub fn percentile(z: f64) -> f64 {
+    normal_cdf(z) * 100.0
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz & Stegun 7.1.26 approximation (max error 1.5e-7).
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_has_zero_z_score() {
+        let lms = lms_at(GrowthMetric::Weight, Sex::Male, 12.0).unwrap();
+        assert!(z_score(GrowthMetric::Weight, &lms, 9.6479).abs() < 1e-9);
+        assert!((percentile(0.0) - 50.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn value_at_round_trips_z_score() {
+        let lms = lms_at(GrowthMetric::Height, Sex::Female, 30.0).unwrap();
+        let value = value_at(&lms, -2.0);
+        assert!((z_score(GrowthMetric::Height, &lms, value) + 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn interpolates_between_reference_ages() {
+        let lms = lms_at(GrowthMetric::Height, Sex::Male, 7.5).unwrap();
+        assert!((lms.m - (67.6236 + 72.0036) / 2.0).abs() < 1e-9);
+        assert_eq!(lms_at(GrowthMetric::Height, Sex::Male, 61.0), None);
+    }
+
+    #[test]
+    fn restricts_weight_extrapolation_beyond_three_sd() {
+        let lms = lms_at(GrowthMetric::Weight, Sex::Female, 24.0).unwrap();
+        let sd3 = value_at(&lms, 3.0);
+        let gap = sd3 - value_at(&lms, 2.0);
+        assert!((z_score(GrowthMetric::Weight, &lms, sd3 + gap) - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percentiles_follow_normal_distribution() {
+        assert!((percentile(-2.0) - 2.275).abs() < 0.01);
+        assert!((percentile(1.0) - 84.134).abs() < 0.01);
+    }
+
+    #[test]
+    fn parses_sex_and_units() {
+        assert_eq!(Sex::parse("Perempuan"), Some(Sex::Female));
+        assert_eq!(Sex::parse("L"), Some(Sex::Male));
+        assert_eq!(GrowthMetric::Weight.normalize(3500.0, "g"), Some(3.5));
+        assert_eq!(GrowthMetric::Height.normalize(1.1, "lb"), None);
+        assert_eq!(age_in_months(1, 6, 0), 18.0);
+    }
+}
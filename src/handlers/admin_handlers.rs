@@ -0,0 +1,137 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Extension,
+};
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    middleware::AuthUser,
+    dto::request_log::{RequestLogQuery, RequestLogResponse},
+    dto::system::{MongoTopology, SystemInfoResponse},
+    pagination::{PaginationMeta, PaginationParams},
+    repository::{DenormalizationRepository, JobRepository, MedicalRecordRepository, RequestLogRepository, RetentionRepository, UserRepository},
+    retention::RetentionConfig,
+    services::{DenormalizationService, JobService, RetentionService},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+/// Dry run of the nightly denormalization pass: embedded copies that are stale, changing nothing
+pub async fn get_denormalization_report(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let db = state.db_for(ReadContext::Replica);
+    let service = DenormalizationService::new(
+        DenormalizationRepository::new(db.clone()),
+        UserRepository::new(db.clone()),
+        MedicalRecordRepository::new(db),
+    );
+
+    match service.full_pass(true).await {
+        Ok(report) => ApiResponse::ok("Denormalization report generated successfully", report).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to generate denormalization report", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_retention_status(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let service = RetentionService::new(
+        RetentionRepository::new(state.db_for(ReadContext::Replica)),
+        RetentionConfig::from_env(),
+    );
+
+    match service.status().await {
+        Ok(status) => ApiResponse::ok("Retention status retrieved successfully", status).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve retention status", Some(e)).into_response(),
+    }
+}
+
+/// The scheduler lease, whether the answering instance holds it, and the last run of each job
+pub async fn get_scheduler_status(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match state.scheduler.status().await {
+        Ok(status) => ApiResponse::ok("Scheduler status retrieved successfully", status).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve scheduler status", Some(e)).into_response(),
+    }
+}
+
+/// Indexes the migration runner defines against those in the database, per collection
+pub async fn get_indexes(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match crate::system::index_report(&state.db).await {
+        Ok(report) => ApiResponse::ok("Index report generated successfully", report).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to compare indexes", Some(e)).into_response(),
+    }
+}
+
+/// Create the missing indexes in a background job; poll `/admin/jobs/:id` for progress
+pub async fn rebuild_indexes(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+) -> impl IntoResponse {
+    let jobs = JobService::new(JobRepository::new(state.db.clone()));
+    match crate::system::enqueue_index_rebuild(&state.db, &jobs, &user.id).await {
+        Ok(job) => {
+            crate::jobs::spawn(state.db.clone(), job.clone());
+            ApiResponse::success(StatusCode::ACCEPTED, "Index rebuild started", job).into_response()
+        }
+        Err(e) => ErrorResponse::internal_error("Failed to start index rebuild", Some(e)).into_response(),
+    }
+}
+
+/// Captured request/response pairs, newest first. Empty unless `REQUEST_LOG_ROUTES` is set.
+pub async fn get_request_logs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RequestLogQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let repo = RequestLogRepository::new(state.db_for(ReadContext::Replica));
+
+    match repo.find_paginated(query.path.as_deref(), query.status, &params).await {
+        Ok((logs, total)) => {
+            let logs: Vec<RequestLogResponse> = logs.into_iter().map(RequestLogResponse::from).collect();
+            let meta = PaginationMeta::new(params.page, params.limit, total);
+            PaginatedResponse::ok("Request logs retrieved successfully", logs, meta).into_response()
+        }
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve request logs", Some(e)).into_response(),
+    }
+}
+
+/// Build, configuration and database health of this instance
+pub async fn get_system_info(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let mongo = crate::system::mongo_topology(&state.db).await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Reading Mongo topology failed");
+        MongoTopology { kind: "unknown".to_string(), set_name: None, hosts: Vec::new(), writable_primary: false }
+    });
+    let indexes = match crate::system::index_health(&state.db).await {
+        Ok(indexes) => indexes,
+        Err(e) => return ErrorResponse::internal_error("Failed to check indexes", Some(e)).into_response(),
+    };
+    let active_flags = match state.feature_flags.get(&state.db).await {
+        Ok(flags) => {
+            let mut keys: Vec<String> = flags.values().filter(|f| f.enabled).map(|f| f.key.clone()).collect();
+            keys.sort();
+            keys
+        }
+        Err(e) => return ErrorResponse::internal_error("Failed to load feature flags", Some(e)).into_response(),
+    };
+
+    let info = SystemInfoResponse {
+        version: crate::system::VERSION.to_string(),
+        git_sha: crate::system::git_sha().to_string(),
+        features: crate::system::compiled_features(),
+        active_flags,
+        storage: crate::system::storage_info(),
+        mongo,
+        indexes,
+        config_issues: crate::system::check_config(&state.config, |name| std::env::var(name).ok()),
+        slow_queries: crate::repository::slow_query::counts(),
+    };
+    ApiResponse::ok("System info retrieved successfully", info).into_response()
+}
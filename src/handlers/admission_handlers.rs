@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::AdmissionService,
+    repository::{AdmissionRepository, BedRepository},
+    refs::ReferenceChecker,
+    dto::admission::{AdmissionQuery, AdmitRequest, DischargeRequest, TransferRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> AdmissionService {
+    let db = state.db_for(ctx);
+    AdmissionService::new(AdmissionRepository::new(db.clone()), BedRepository::new(db.clone()), ReferenceChecker::new(db))
+}
+
+pub async fn admit_patient(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<AdmitRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).admit(payload, &user.id).await {
+        Ok(admission) => ApiResponse::created("Patient admitted successfully", admission).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to admit patient", "ADMIT_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_admissions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AdmissionQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((admissions, meta)) => PaginatedResponse::ok("Admissions retrieved successfully", admissions, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve admissions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_admission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(Some(admission)) => ApiResponse::ok("Admission retrieved successfully", admission).into_response(),
+        Ok(None) => ErrorResponse::not_found("Admission not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve admission", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn transfer_patient(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<TransferRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).transfer(oid, payload, &user.id).await {
+        Ok(admission) => ApiResponse::ok("Patient transferred successfully", admission).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to transfer patient", "TRANSFER_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn discharge_patient(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<DischargeRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).discharge(oid, payload, &user.id).await {
+        Ok(admission) => ApiResponse::ok("Patient discharged successfully", admission).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to discharge patient", "DISCHARGE_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    allergy::AllergyCheckMode,
+    db::{AppState, ReadContext},
+    services::AllergyService,
+    repository::{AllergyRepository, MedicineRepository},
+    refs::ReferenceChecker,
+    dto::allergy::{AllergyCheckRequest, CreateAllergyRequest, UpdateAllergyRequest},
+    middleware::AuthUser,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> AllergyService {
+    let db = state.db_for(ctx);
+    AllergyService::new(
+        AllergyRepository::new(db.clone()),
+        MedicineRepository::new(db.clone()),
+        ReferenceChecker::new(db),
+        state.config.allergy_check,
+    )
+}
+
+pub async fn get_allergies(
+    State(state): State<Arc<AppState>>,
+    Path(patient_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(patient) = ObjectId::parse_str(&patient_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).list(patient).await {
+        Ok(allergies) => ApiResponse::ok("Allergies retrieved successfully", allergies).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve allergies", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_allergy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(patient_id): Path<String>,
+    Json(payload): Json<CreateAllergyRequest>,
+) -> impl IntoResponse {
+    let Ok(patient) = ObjectId::parse_str(&patient_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(patient, &user, payload).await {
+        Ok(allergy) => ApiResponse::created("Allergy recorded successfully", allergy).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to record allergy", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_allergy(
+    State(state): State<Arc<AppState>>,
+    Path((patient_id, id)): Path<(String, String)>,
+    Json(payload): Json<UpdateAllergyRequest>,
+) -> impl IntoResponse {
+    let (Ok(patient), Ok(oid)) = (ObjectId::parse_str(&patient_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(patient, oid, payload).await {
+        Ok(allergy) => ApiResponse::ok("Allergy updated successfully", allergy).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update allergy", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_allergy(
+    State(state): State<Arc<AppState>>,
+    Path((patient_id, id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (Ok(patient), Ok(oid)) = (ObjectId::parse_str(&patient_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(patient, oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Allergy not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete allergy", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Check medicines against the patient's allergies. With `ALLERGY_CHECK_MODE=block` a
+/// conflict is a 409 carrying the conflicts in `data`; otherwise they come back as warnings.
+pub async fn check_allergies(
+    State(state): State<Arc<AppState>>,
+    Path(patient_id): Path<String>,
+    Json(payload): Json<AllergyCheckRequest>,
+) -> impl IntoResponse {
+    let Ok(patient) = ObjectId::parse_str(&patient_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).check(patient, &payload.medicine_ids).await {
+        Ok(result) if result.mode == AllergyCheckMode::Block && !result.conflicts.is_empty() => {
+            let details = format!("{} allergy conflict(s)", result.conflicts.len());
+            ErrorResponse::conflict("Medicines conflict with recorded allergies", Some(details)).with_data(result).into_response()
+        }
+        Ok(result) if result.conflicts.is_empty() => ApiResponse::ok("No allergy conflicts found", result).into_response(),
+        Ok(result) => ApiResponse::ok("Medicines conflict with recorded allergies", result).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to check allergies", "CHECK_FAILED", Some(msg)).into_response(),
+    }
+}
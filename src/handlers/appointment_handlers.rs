@@ -1,28 +1,83 @@
 use axum::{
     extract::{Path, State, Query},
+    http::HeaderMap,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
-    services::AppointmentService,
-    repository::AppointmentRepository,
-    dto::appointment::{CreateAppointmentRequest, UpdateAppointmentRequest},
+    db::{AppState, ReadContext},
+    middleware::AuthUser,
+    services::{AppointmentService, AppointmentSeriesService, OrganizationService, appointment_service::MODE_VIRTUAL},
+    handlers::teleconsult_handlers,
+    repository::{AppointmentRepository, AppointmentSeriesRepository, OrganizationRepository},
+    dto::common::LinksQuery,
+    dto::appointment::{
+        AppointmentListQuery, AppointmentResponse, AvailabilityQuery, DoctorStatsQuery, CreateAppointmentRequest, UpdateAppointmentRequest, CreateAppointmentSeriesRequest,
+        UpdateAppointmentSeriesRequest, CancelAppointmentSeriesRequest,
+    },
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
+    events::DomainEvent,
+    refs::ReferenceChecker,
+    delete_policy::DeleteGuard,
+    conditional::{with_version, Preconditions},
 };
 
+/// Create the meeting of a virtual appointment up front so its link is ready before the visit.
+fn prepare_teleconsult(state: &Arc<AppState>, appointment: &AppointmentResponse) {
+    if appointment.mode.as_deref() != Some(MODE_VIRTUAL) {
+        return;
+    }
+    let Ok(oid) = ObjectId::parse_str(&appointment.id) else { return };
+    let state = state.clone();
+    tokio::spawn(async move {
+        if let Err((_, e)) = teleconsult_handlers::build_service(&state).ensure_session(oid).await {
+            tracing::error!(appointment_id = %oid.to_hex(), error = %e, "Failed to create teleconsult session");
+        }
+    });
+}
+
+/// Fill `_links` when the client asked for them
+fn link(state: &AppState, query: &LinksQuery, mut appointment: AppointmentResponse) -> AppointmentResponse {
+    if query.links {
+        appointment.links = Some(state.config.links.appointment(&appointment));
+    }
+    appointment
+}
+
+pub(crate) fn build_service(state: &AppState, ctx: ReadContext) -> AppointmentService {
+    let db = state.db_for(ctx);
+    AppointmentService::new(
+        AppointmentRepository::new(db.clone()),
+        OrganizationService::new(
+            OrganizationRepository::new(db.clone()),
+            DeleteGuard::from_state(state),
+            state.config.scheduling.default_timezone.clone(),
+        ),
+        ReferenceChecker::new(db),
+        state.config.scheduling.clone(),
+    )
+}
+
 pub async fn get_appointments(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
+    Query(filter): Query<AppointmentListQuery>,
+    Query(links): Query<LinksQuery>,
 ) -> impl IntoResponse {
-    let repo = AppointmentRepository::new(state.db.clone());
-    let service = AppointmentService::new(repo);
-    
-    match service.get_all_paginated(params.clone()).await {
-        Ok((appointments, meta)) => PaginatedResponse::ok("Appointments retrieved successfully", appointments, meta).into_response(),
+    if let Err(e) = crate::validation::validate_payload(&filter) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.get_all_paginated(params.clone(), filter.patient_id.as_deref(), filter.status.as_ref(), filter.expansion()).await {
+        Ok((appointments, meta)) => {
+            let appointments = appointments.into_iter().map(|appointment| link(&state, &links, appointment)).collect();
+            PaginatedResponse::ok("Appointments retrieved successfully", appointments, meta).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve appointments", "FETCH_FAILED", Some(msg)).into_response(),
     }
 }
@@ -35,11 +90,13 @@ pub async fn create_appointment(
         return e.into_response();
     }
 
-    let repo = AppointmentRepository::new(state.db.clone());
-    let service = AppointmentService::new(repo);
-    
+    let service = build_service(&state, ReadContext::Primary);
     match service.create(payload).await {
-        Ok((status, appointment)) => ApiResponse::success(status, "Appointment created successfully", appointment).into_response(),
+        Ok((status, appointment)) => {
+            state.events.publish(DomainEvent::created("appointments", &appointment.id));
+            prepare_teleconsult(&state, &appointment);
+            ApiResponse::success(status, "Appointment created successfully", appointment).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to create appointment", "CREATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -47,16 +104,19 @@ pub async fn create_appointment(
 pub async fn get_appointment(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    Query(links): Query<LinksQuery>,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = AppointmentRepository::new(state.db.clone());
-    let service = AppointmentService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
 
     match service.get_by_id(oid).await {
-        Ok(Some(appointment)) => ApiResponse::ok("Appointment retrieved successfully", appointment).into_response(),
+        Ok(Some(appointment)) => {
+            let appointment = link(&state, &links, appointment);
+            with_version(&appointment, ApiResponse::ok("Appointment retrieved successfully", appointment.clone()).into_response())
+        }
         Ok(None) => ErrorResponse::not_found("Appointment not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve appointment", "FETCH_FAILED", Some(msg)).into_response(),
     }
@@ -65,6 +125,7 @@ pub async fn get_appointment(
 pub async fn update_appointment(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateAppointmentRequest>,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
@@ -75,12 +136,23 @@ pub async fn update_appointment(
         return e.into_response();
     }
 
+    let service = build_service(&state, ReadContext::Primary);
+    if let Some(preconditions) = Preconditions::from_headers(&headers) {
+        let current = match service.get_by_id(oid).await {
+            Ok(current) => current,
+            Err((status, msg)) => return ErrorResponse::new(status, "Failed to update appointment", "UPDATE_FAILED", Some(msg)).into_response(),
+        };
+        if let Err(response) = preconditions.check_resource(current.as_ref()) {
+            return response.into_response();
+        }
+    }
 
-    let repo = AppointmentRepository::new(state.db.clone());
-    let service = AppointmentService::new(repo);
-    
     match service.update(oid, payload).await {
-        Ok(appointment) => ApiResponse::ok("Appointment updated successfully", appointment).into_response(),
+        Ok(appointment) => {
+            state.events.publish(DomainEvent::updated("appointments", &appointment.id));
+            prepare_teleconsult(&state, &appointment);
+            with_version(&appointment, ApiResponse::ok("Appointment updated successfully", appointment.clone()).into_response())
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update appointment", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -88,17 +160,201 @@ pub async fn update_appointment(
 pub async fn delete_appointment(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = AppointmentRepository::new(state.db.clone());
-    let service = AppointmentService::new(repo);
-    
+    let service = build_service(&state, ReadContext::Primary);
+
+    // Snapshot for subscribers such as the waitlist, which need the freed slot
+    let current = match service.get_by_id(oid).await {
+        Ok(appointment) => appointment,
+        Err((status, msg)) => return ErrorResponse::new(status, "Failed to delete appointment", "DELETE_FAILED", Some(msg)).into_response(),
+    };
+    if let Some(preconditions) = Preconditions::from_headers(&headers) {
+        if let Err(response) = preconditions.check_resource(current.as_ref()) {
+            return response.into_response();
+        }
+    }
+    let snapshot = current.and_then(|a| serde_json::to_value(a).ok());
+
     match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
+        Ok(true) => {
+            let event = DomainEvent::deleted("appointments", &id);
+            state.events.publish(match snapshot {
+                Some(data) => event.with_data(data),
+                None => event,
+            });
+            no_content().into_response()
+        }
         Ok(false) => ErrorResponse::not_found("Appointment not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete appointment", "DELETE_FAILED", Some(msg)).into_response(),
     }
 }
+
+pub async fn check_in_appointment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.check_in(oid, user.kiosk.as_ref()).await {
+        Ok(appointment) => {
+            state.events.publish(DomainEvent::updated("appointments", &appointment.id));
+            prepare_teleconsult(&state, &appointment);
+            state.events.publish(DomainEvent::updated(crate::handlers::queue_handlers::QUEUE_EVENTS, &appointment.doctor_id));
+            ApiResponse::ok("Appointment checked in successfully", appointment).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to check in appointment", "CHECK_IN_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Mark a patient who did not come; the sweep in `crate::no_show` does this after a grace period
+pub async fn mark_no_show(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).mark_no_show(oid).await {
+        Ok(appointment) => {
+            state.events.publish(DomainEvent::updated("appointments", &appointment.id));
+            ApiResponse::ok("Appointment marked as no-show", appointment).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to mark appointment as no-show", "NO_SHOW_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Appointment outcomes of a doctor
+///
+/// GET /stats/doctors/:id/appointments?from=2026-03-01&to=2026-03-31
+pub async fn get_doctor_appointment_stats(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<DoctorStatsQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).doctor_stats(oid, query).await {
+        Ok(stats) => ApiResponse::ok("Appointment statistics retrieved successfully", stats).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve appointment statistics", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_availability(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AvailabilityQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    // Availability must not offer a slot booked a moment ago, so it reads from the primary
+    match build_service(&state, ReadContext::Primary).availability(query).await {
+        Ok(availability) => ApiResponse::ok("Availability retrieved successfully", availability).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve availability", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+fn build_series_service(state: &AppState) -> AppointmentSeriesService {
+    AppointmentSeriesService::new(
+        AppointmentSeriesRepository::new(state.db.clone()),
+        AppointmentRepository::new(state.db.clone()),
+        ReferenceChecker::new(state.db.clone()),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+pub async fn create_appointment_series(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateAppointmentSeriesRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_series_service(&state).create(payload).await {
+        Ok(series) => {
+            state.events.publish(DomainEvent::created("appointment_series", &series.id));
+            ApiResponse::created("Appointment series created successfully", series).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create appointment series", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_appointment_series(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_series_service(&state).get_by_id(oid).await {
+        Ok(Some(series)) => ApiResponse::ok("Appointment series retrieved successfully", series).into_response(),
+        Ok(None) => ErrorResponse::not_found("Appointment series not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve appointment series", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_appointment_series(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateAppointmentSeriesRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_series_service(&state).update(oid, payload).await {
+        Ok(series) => {
+            state.events.publish(DomainEvent::updated("appointment_series", &series.id));
+            ApiResponse::ok("Appointment series updated successfully", series).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update appointment series", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn cancel_appointment_series(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<CancelAppointmentSeriesRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_series_service(&state).cancel(oid, payload).await {
+        Ok((series, cancelled)) => {
+            for appointment in cancelled.iter().filter_map(|a| a.id) {
+                state.events.publish(DomainEvent::updated("appointments", &appointment.to_hex()));
+            }
+            state.events.publish(DomainEvent::updated("appointment_series", &series.id));
+            ApiResponse::ok("Appointment series cancelled successfully", series).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to cancel appointment series", "CANCEL_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -1,5 +1,5 @@
 use axum::{
-    extract::State,
+    extract::{Query, State},
     response::IntoResponse,
     Json,
 };
@@ -9,13 +9,31 @@ use crate::{
     db::AppState,
     dto::auth::{
         RegisterRequest, LoginRequest, ForgotPasswordRequest, ResetPasswordRequest,
-        RefreshTokenRequest,
+        RefreshTokenRequest, PatientOtpRequest, PatientLoginRequest, OtpRequest, OtpVerifyRequest,
+        VerifyEmailQuery, ResendVerificationRequest,
     },
     response::{ApiResponse, ErrorResponse},
-    repository::UserRepository,
-    services::AuthService,
+    repository::{MedicalRecordRepository, OtpRepository, UserRepository},
+    services::{AuthService, EmailVerificationService, OtpService, PatientAuthService},
 };
 
+fn patient_auth_service(state: &AppState) -> PatientAuthService {
+    PatientAuthService::new(
+        MedicalRecordRepository::new(state.db.clone()),
+        OtpService::new(OtpRepository::new(state.db.clone()), &state.config.otp),
+    )
+}
+
+fn otp_error_code(status: axum::http::StatusCode) -> &'static str {
+    match status.as_u16() {
+        400 => "VALIDATION_ERROR",
+        401 => "INVALID_OTP",
+        429 => "OTP_RATE_LIMITED",
+        502 => "OTP_DELIVERY_FAILED",
+        _ => "OTP_FAILED",
+    }
+}
+
 /// Register a new user
 /// 
 /// POST /auth/register
@@ -40,7 +58,17 @@ pub async fn register(
     let service = AuthService::new(repo);
     
     match service.register(payload).await {
-        Ok((status, response)) => ApiResponse::success(status, "User registered successfully", response).into_response(),
+        Ok((status, response)) => {
+            // Registration does not wait for the mail provider
+            let (state, user_id, email) = (state.clone(), response.id.clone(), response.email.clone());
+            tokio::spawn(async move {
+                let verification = EmailVerificationService::new(UserRepository::new(state.db.clone()), &state.config.email);
+                if let Err(e) = verification.send_link(&user_id, &email).await {
+                    tracing::error!(user_id = %user_id, error = %e, "Sending the verification email failed");
+                }
+            });
+            ApiResponse::success(status, "User registered successfully", response).into_response()
+        }
         Err((status, msg)) => {
             let error_code = match status.as_u16() {
                 409 => "EMAIL_EXISTS",
@@ -182,6 +210,160 @@ pub async fn reset_password(
     }
 }
 
+/// Request a patient portal login code
+///
+/// POST /auth/patient/otp
+///
+/// Request body:
+/// ```json
+/// {
+///     "nik": "3201010101010001"
+/// }
+/// ```
+pub async fn request_patient_otp(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PatientOtpRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match patient_auth_service(&state).request_login_code(payload).await {
+        Ok(response) => ApiResponse::ok("Login code requested", response).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to send login code", otp_error_code(status), Some(msg)).into_response(),
+    }
+}
+
+/// Patient portal login with NIK and the code from /auth/patient/otp
+///
+/// POST /auth/patient/login
+///
+/// Request body:
+/// ```json
+/// {
+///     "nik": "3201010101010001",
+///     "otp": "123456"
+/// }
+/// ```
+///
+/// The returned token is scoped to the patient's own records.
+pub async fn patient_login(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<PatientLoginRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match patient_auth_service(&state).login(payload).await {
+        Ok(response) => ApiResponse::ok("Login successful", response).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Login failed", otp_error_code(status), Some(msg)).into_response(),
+    }
+}
+
+/// Request a one-time code
+///
+/// POST /auth/otp/request
+///
+/// Request body:
+/// ```json
+/// {
+///     "purpose": "phone_verification",
+///     "phone": "+6281234567890"
+/// }
+/// ```
+///
+/// `patient_login` codes take a `nik` instead and go to the phone on that patient's record.
+pub async fn request_otp(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<OtpRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match patient_auth_service(&state).request_code(payload).await {
+        Ok(response) => ApiResponse::ok("Code requested", response).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to send code", otp_error_code(status), Some(msg)).into_response(),
+    }
+}
+
+/// Verify a one-time code
+///
+/// POST /auth/otp/verify
+///
+/// Request body:
+/// ```json
+/// {
+///     "purpose": "phone_verification",
+///     "phone": "+6281234567890",
+///     "code": "123456"
+/// }
+/// ```
+///
+/// `patient_login` codes return a patient token, like /auth/patient/login.
+pub async fn verify_otp(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<OtpVerifyRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match patient_auth_service(&state).verify_code(payload).await {
+        Ok(response) => ApiResponse::ok("Code verified", response).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Code verification failed", otp_error_code(status), Some(msg)).into_response(),
+    }
+}
+
+/// Verify an email address from the link sent on registration
+///
+/// GET /auth/verify-email?token=<verification_token>
+pub async fn verify_email(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<VerifyEmailQuery>,
+) -> impl IntoResponse {
+    let service = EmailVerificationService::new(UserRepository::new(state.db.clone()), &state.config.email);
+
+    match service.verify(&query.token).await {
+        Ok(response) => ApiResponse::ok("Email verified successfully", response).into_response(),
+        Err((status, msg)) => {
+            let error_code = match status.as_u16() {
+                400 => "INVALID_TOKEN",
+                404 => "USER_NOT_FOUND",
+                _ => "EMAIL_VERIFICATION_FAILED",
+            };
+            ErrorResponse::new(status, "Failed to verify email", error_code, Some(msg)).into_response()
+        }
+    }
+}
+
+/// Send a new verification link
+///
+/// POST /auth/resend-verification
+///
+/// Request body:
+/// ```json
+/// {
+///     "email": "user@example.com"
+/// }
+/// ```
+pub async fn resend_verification(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<ResendVerificationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = EmailVerificationService::new(UserRepository::new(state.db.clone()), &state.config.email);
+
+    match service.resend(payload).await {
+        Ok(response) => ApiResponse::ok("Verification email requested", response).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to send verification email", "RESEND_VERIFICATION_FAILED", Some(msg)).into_response(),
+    }
+}
+
 /// Get current user info (protected route example)
 /// 
 /// GET /auth/me
@@ -193,6 +375,7 @@ pub async fn get_me(
     ApiResponse::ok("User info retrieved", serde_json::json!({
         "id": user.id,
         "email": user.email,
-        "name": user.name
+        "name": user.name,
+        "patient_id": user.patient_id
     })).into_response()
 }
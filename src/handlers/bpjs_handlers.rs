@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::json;
+use std::sync::Arc;
+use crate::{
+    bpjs::BpjsError,
+    db::AppState,
+    dto::bpjs::{CreateSepRequest, ParticipantQuery},
+    middleware::AuthUser,
+    services::BpjsService,
+    response::{ApiResponse, ErrorResponse},
+};
+
+fn build_service(state: &AppState) -> BpjsService<'_> {
+    BpjsService::new(&state.bpjs, state.config.scheduling.default_timezone.clone())
+}
+
+/// Our error format, with the BPJS `metaData` of rejections under `data`
+fn bpjs_error(message: &str, error: BpjsError) -> ErrorResponse {
+    let response = ErrorResponse::new(error.status(), message, error.code(), Some(error.message()));
+    match error.upstream() {
+        Some((code, upstream)) => response.with_data(json!({ "bpjs_code": code, "bpjs_message": upstream })),
+        None => response,
+    }
+}
+
+/// Participant eligibility by card number; answers are cached briefly.
+pub async fn get_participant(
+    State(state): State<Arc<AppState>>,
+    Path(card_number): Path<String>,
+    Query(query): Query<ParticipantQuery>,
+) -> impl IntoResponse {
+    if crate::dto::bpjs::validate_card_number(&card_number).is_err() {
+        return ErrorResponse::bad_request("Invalid card number", Some("Card number must be 13 digits".to_string())).into_response();
+    }
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state).eligibility(&card_number, query.date).await {
+        Ok(eligibility) => ApiResponse::ok("Participant retrieved successfully", eligibility).into_response(),
+        Err(e) => bpjs_error("Failed to check participant", e).into_response(),
+    }
+}
+
+pub async fn create_sep(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateSepRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).create_sep(payload, &user.id).await {
+        Ok(sep) => ApiResponse::created("SEP created successfully", sep).into_response(),
+        Err(e) => bpjs_error("Failed to create SEP", e).into_response(),
+    }
+}
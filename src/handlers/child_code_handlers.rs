@@ -6,18 +6,18 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::{ChildCodeService},
     repository::{ChildCodeRepository, CodeRepository},
-    dto::child_code::{CreateChildCodeRequest, UpdateChildCodeRequest},
+    dto::child_code::{BulkLinkChildCodesRequest, CreateChildCodeRequest, UpdateChildCodeRequest},
     response::{ApiResponse, ErrorResponse, no_content},
 };
 
 pub async fn get_child_codes(
     State(state): State<Arc<AppState>>,
 ) -> impl IntoResponse {
-    let repo = Arc::new(ChildCodeRepository::new(state.db.clone()));
-    let code_repo = Arc::new(CodeRepository::new(state.db.clone()));
+    let repo = Arc::new(ChildCodeRepository::new(state.db_for(ReadContext::Replica)));
+    let code_repo = Arc::new(CodeRepository::new(state.db_for(ReadContext::Replica)));
     let service = ChildCodeService::new(repo, code_repo);
     
     match service.get_all().await {
@@ -44,6 +44,30 @@ pub async fn create_child_code(
     }
 }
 
+/// `POST /codes/:id/children/bulk`: link many codes under the parent code at once
+pub async fn bulk_link_child_codes(
+    State(state): State<Arc<AppState>>,
+    Path(parent_id): Path<String>,
+    Json(payload): Json<BulkLinkChildCodesRequest>,
+) -> impl IntoResponse {
+    let Ok(parent_oid) = ObjectId::parse_str(&parent_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let repo = Arc::new(ChildCodeRepository::new(state.db.clone()));
+    let code_repo = Arc::new(CodeRepository::new(state.db.clone()));
+    let service = ChildCodeService::new(repo, code_repo);
+    let total = payload.children.len();
+
+    match service.bulk_link(parent_oid, payload).await {
+        Ok(result) => ApiResponse::ok(format!("{} of {} child codes linked", result.created, total), result).into_response(),
+        Err((status, e)) => ErrorResponse::new(status, "Failed to link child codes", "CREATE_FAILED", Some(e)).into_response(),
+    }
+}
+
 pub async fn get_child_code(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -7,34 +7,55 @@ use std::sync::Arc;
 use axum::http::StatusCode;
 
 use crate::{
-    db::AppState,
-    dto::code::{CreateCodeDto, UpdateCodeDto},
+    db::{AppState, ReadContext},
+    i18n::Locale,
+    dto::code::{CodeRefDto, CreateCodeDto, UpdateCodeDto},
     response::{ApiResponse, ErrorResponse, no_content},
     repository::CodeRepository,
     services::CodeService,
 };
+#[cfg(feature = "fhir")]
+use axum::Extension;
+#[cfg(feature = "fhir")]
+use mongodb::bson::oid::ObjectId;
+#[cfg(feature = "fhir")]
+use crate::{
+    dto::code::ImportCodesDto,
+    middleware::AuthUser,
+    repository::JobRepository,
+    services::{CodeImportService, JobService},
+    services::code_import_service::CODE_IMPORT_JOB,
+};
 
 pub async fn get_codes(
     State(state): State<Arc<AppState>>,
+    locale: Locale,
 ) -> impl IntoResponse {
-    let repo = Arc::new(CodeRepository::new(state.db.clone()));
+    let repo = Arc::new(CodeRepository::new(state.db_for(ReadContext::Replica)));
     let service = CodeService::new(repo);
     
     match service.get_all_codes().await {
-        Ok(codes) => ApiResponse::ok("Codes retrieved successfully", codes).into_response(),
+        Ok(mut codes) => {
+            codes.iter_mut().for_each(|code| code.localize(locale));
+            ApiResponse::ok("Codes retrieved successfully", codes).into_response()
+        }
         Err(msg) => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retrieve codes", "FETCH_FAILED", Some(msg)).into_response(),
     }
 }
 
 pub async fn get_code(
     State(state): State<Arc<AppState>>,
+    locale: Locale,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     let repo = Arc::new(CodeRepository::new(state.db.clone()));
     let service = CodeService::new(repo);
     
     match service.get_code_by_id(&id).await {
-        Ok(Some(code)) => ApiResponse::ok("Code retrieved successfully", code).into_response(),
+        Ok(Some(mut code)) => {
+            code.localize(locale);
+            ApiResponse::ok("Code retrieved successfully", code).into_response()
+        }
         Ok(None) => ErrorResponse::not_found("Code not found").into_response(),
         Err(msg) => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retrieve code", "FETCH_FAILED", Some(msg)).into_response(),
     }
@@ -52,7 +73,10 @@ pub async fn create_code(
     let service = CodeService::new(repo);
     
     match service.create_code(payload).await {
-        Ok(code) => ApiResponse::success(StatusCode::CREATED, "Code created successfully", code).into_response(),
+        Ok(code) => {
+            state.codes.invalidate().await;
+            ApiResponse::success(StatusCode::CREATED, "Code created successfully", code).into_response()
+        }
         Err(msg) => {
             if msg.contains("exists") {
                 ErrorResponse::new(StatusCode::CONFLICT, "Code already exists", "DUPLICATE_CODE", Some(msg)).into_response()
@@ -76,7 +100,10 @@ pub async fn update_code(
     let service = CodeService::new(repo);
     
     match service.update_code(&id, payload).await {
-        Ok(code) => ApiResponse::ok("Code updated successfully", code).into_response(),
+        Ok(code) => {
+            state.codes.invalidate().await;
+            ApiResponse::ok("Code updated successfully", code).into_response()
+        }
         Err(msg) => {
             if msg.contains("not found") {
                 ErrorResponse::not_found("Code not found").into_response()
@@ -97,8 +124,80 @@ pub async fn delete_code(
     let service = CodeService::new(repo);
     
     match service.delete_code(&id).await {
-        Ok(true) => no_content().into_response(),
+        Ok(true) => {
+            state.codes.invalidate().await;
+            no_content().into_response()
+        }
         Ok(false) => ErrorResponse::not_found("Code not found").into_response(),
         Err(msg) => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to delete code", "DELETE_FAILED", Some(msg)).into_response(),
     }
 }
+
+/// Most pairs one `POST /codes/validate` may check
+const MAX_VALIDATE_CODES: usize = 500;
+
+pub async fn validate_codes(
+    State(state): State<Arc<AppState>>,
+    locale: Locale,
+    Json(payload): Json<Vec<CodeRefDto>>,
+) -> impl IntoResponse {
+    if payload.is_empty() || payload.len() > MAX_VALIDATE_CODES {
+        return ErrorResponse::bad_request(
+            "Invalid codes",
+            Some(format!("Send between 1 and {} system/code pairs", MAX_VALIDATE_CODES)),
+        ).into_response();
+    }
+    for code in &payload {
+        if let Err(e) = crate::validation::validate_payload(code) {
+            return e.into_response();
+        }
+    }
+
+    let service = CodeService::new(Arc::new(CodeRepository::new(state.db_for(ReadContext::Replica))));
+    match service.validate_codes(&state.codes, payload, locale).await {
+        Ok(results) => ApiResponse::ok("Codes validated successfully", results).into_response(),
+        Err(msg) => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to validate codes", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+#[cfg(feature = "fhir")]
+pub async fn import_codes(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<ImportCodesDto>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = CodeImportService::new(
+        CodeRepository::new(state.db.clone()),
+        JobService::new(JobRepository::new(state.db.clone())),
+    );
+
+    match service.enqueue(payload, &user.id).await {
+        Ok(job) => {
+            crate::jobs::spawn(state.db.clone(), job.clone());
+            ApiResponse::success(StatusCode::ACCEPTED, "Code import started", job).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to start code import", "IMPORT_FAILED", Some(msg)).into_response(),
+    }
+}
+
+#[cfg(feature = "fhir")]
+pub async fn get_import_job(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&job_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = JobService::new(JobRepository::new(state.db.clone()));
+
+    match service.get(oid).await {
+        Ok(Some(job)) if job.job_type == CODE_IMPORT_JOB => ApiResponse::ok("Import job retrieved successfully", job).into_response(),
+        Ok(_) => ErrorResponse::not_found("Import job not found").into_response(),
+        Err(msg) => ErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to retrieve import job", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
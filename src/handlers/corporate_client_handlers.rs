@@ -0,0 +1,229 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::CorporateClientService,
+    repository::{CorporateClientRepository, InvoiceRepository},
+    refs::ReferenceChecker,
+    sequences::SequenceGenerator,
+    dto::corporate_client::{
+        AddCorporateEmployeeRequest, CorporateClientQuery, CreateCorporateClientRequest, GenerateStatementRequest,
+        UpdateCorporateClientRequest, UpdateCorporateEmployeeRequest,
+    },
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse, no_content},
+};
+
+pub(crate) fn build_service(state: &AppState, ctx: ReadContext) -> CorporateClientService {
+    let db = state.db_for(ctx);
+    CorporateClientService::new(
+        CorporateClientRepository::new(db.clone()),
+        InvoiceRepository::new(db.clone()),
+        ReferenceChecker::new(db.clone()),
+        SequenceGenerator::new(db),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+pub async fn get_corporate_clients(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CorporateClientQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((clients, meta)) => PaginatedResponse::ok("Corporate clients retrieved successfully", clients, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve corporate clients", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_corporate_client(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateCorporateClientRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload, &user.id).await {
+        Ok(client) => ApiResponse::created("Corporate client created successfully", client).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create corporate client", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_corporate_client(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).get(oid).await {
+        Ok(Some(client)) => ApiResponse::ok("Corporate client retrieved successfully", client).into_response(),
+        Ok(None) => ErrorResponse::not_found("Corporate client not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve corporate client", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_corporate_client(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateCorporateClientRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, payload).await {
+        Ok(client) => ApiResponse::ok("Corporate client updated successfully", client).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update corporate client", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_corporate_client(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Corporate client not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete corporate client", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_corporate_employees(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).employees(oid).await {
+        Ok(employees) => ApiResponse::ok("Employees retrieved successfully", employees).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve employees", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn add_corporate_employee(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<AddCorporateEmployeeRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).add_employee(oid, payload, &user.id).await {
+        Ok(employee) => ApiResponse::created("Employee added successfully", employee).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to add employee", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_corporate_employee(
+    State(state): State<Arc<AppState>>,
+    Path((client_id, id)): Path<(String, String)>,
+    Json(payload): Json<UpdateCorporateEmployeeRequest>,
+) -> impl IntoResponse {
+    let (Ok(client), Ok(oid)) = (ObjectId::parse_str(&client_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update_employee(client, oid, payload).await {
+        Ok(Some(employee)) => ApiResponse::ok("Employee updated successfully", employee).into_response(),
+        Ok(None) => ErrorResponse::not_found("Employee not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update employee", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn remove_corporate_employee(
+    State(state): State<Arc<AppState>>,
+    Path((client_id, id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (Ok(client), Ok(oid)) = (ObjectId::parse_str(&client_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).remove_employee(client, oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Employee not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to remove employee", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_corporate_statements(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).statements(oid).await {
+        Ok(statements) => ApiResponse::ok("Statements retrieved successfully", statements).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve statements", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn generate_corporate_statement(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<GenerateStatementRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).generate_statement(oid, &payload.month, &user.id).await {
+        Ok(statement) => ApiResponse::ok("Statement generated successfully", statement).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to generate statement", "GENERATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_corporate_statement(
+    State(state): State<Arc<AppState>>,
+    Path((id, month)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).statement(oid, &month).await {
+        Ok(Some(statement)) => ApiResponse::ok("Statement retrieved successfully", statement).into_response(),
+        Ok(None) => ErrorResponse::not_found("Statement not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve statement", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,150 @@
+//! Standard CRUD handlers shared by simple resources.
+//!
+//! A service implements `CrudService` (and `PagedCrudService` for a paginated list), and
+//! `CrudController<Service>` provides the handlers: `CrudController::<RegionService>::get`
+//! parses the id, builds the service and answers with the usual responses and messages.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use serde::{de::DeserializeOwned, Serialize};
+use validator::Validate;
+use crate::{
+    db::{AppState, ReadContext},
+    i18n::Locale,
+    pagination::{PaginationMeta, PaginationParams},
+    response::{no_content, ApiResponse, ErrorResponse, PaginatedResponse},
+    validation::validate_payload,
+};
+
+pub trait CrudService: Sized + Send + Sync + 'static {
+    type Item: Serialize + Send;
+    type Create: DeserializeOwned + Validate + Send + 'static;
+    type Update: DeserializeOwned + Validate + Send + 'static;
+
+    /// Singular name used in messages, e.g. `Region`
+    const NAME: &'static str;
+    /// Plural name used in messages, e.g. `Regions`
+    const PLURAL: &'static str;
+
+    fn build(state: &AppState, context: ReadContext) -> Self;
+
+    fn create(&self, dto: Self::Create) -> impl Future<Output = Result<Self::Item, String>> + Send;
+
+    fn get_by_id(&self, id: ObjectId) -> impl Future<Output = Result<Option<Self::Item>, String>> + Send;
+
+    fn update(&self, id: ObjectId, dto: Self::Update) -> impl Future<Output = Result<Self::Item, String>> + Send;
+
+    fn delete(&self, id: ObjectId) -> impl Future<Output = Result<bool, String>> + Send;
+
+    /// Puts translated values of a read item in the caller's language, see `crate::i18n`
+    fn localize(_item: &mut Self::Item, _locale: Locale) {}
+}
+
+/// Services whose collection is listed page by page
+pub trait PagedCrudService: CrudService {
+    fn get_all_paginated(&self, params: PaginationParams) -> impl Future<Output = Result<(Vec<Self::Item>, PaginationMeta), String>> + Send;
+}
+
+pub struct CrudController<S>(PhantomData<S>);
+
+fn invalid_id() -> Response {
+    ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response()
+}
+
+impl<S: CrudService> CrudController<S> {
+    pub async fn create(
+        State(state): State<Arc<AppState>>,
+        Json(payload): Json<S::Create>,
+    ) -> Response {
+        if let Err(e) = validate_payload(&payload) {
+            return e.into_response();
+        }
+
+        let service = S::build(&state, ReadContext::Primary);
+        match service.create(payload).await {
+            Ok(item) => ApiResponse::success(StatusCode::CREATED, format!("{} created successfully", S::NAME), item).into_response(),
+            Err(e) => ErrorResponse::bad_request(format!("Failed to create {}", S::NAME.to_lowercase()), Some(e)).into_response(),
+        }
+    }
+
+    pub async fn get(
+        State(state): State<Arc<AppState>>,
+        locale: Locale,
+        Path(id): Path<String>,
+    ) -> Response {
+        let Ok(oid) = ObjectId::parse_str(&id) else {
+            return invalid_id();
+        };
+
+        let service = S::build(&state, ReadContext::Primary);
+        match service.get_by_id(oid).await {
+            Ok(Some(mut item)) => {
+                S::localize(&mut item, locale);
+                ApiResponse::ok(format!("{} retrieved successfully", S::NAME), item).into_response()
+            }
+            Ok(None) => ErrorResponse::not_found(format!("{} not found", S::NAME)).into_response(),
+            Err(e) => ErrorResponse::internal_error(format!("Failed to retrieve {}", S::NAME.to_lowercase()), Some(e)).into_response(),
+        }
+    }
+
+    pub async fn update(
+        State(state): State<Arc<AppState>>,
+        Path(id): Path<String>,
+        Json(payload): Json<S::Update>,
+    ) -> Response {
+        let Ok(oid) = ObjectId::parse_str(&id) else {
+            return invalid_id();
+        };
+
+        if let Err(e) = validate_payload(&payload) {
+            return e.into_response();
+        }
+
+        let service = S::build(&state, ReadContext::Primary);
+        match service.update(oid, payload).await {
+            Ok(item) => ApiResponse::ok(format!("{} updated successfully", S::NAME), item).into_response(),
+            Err(e) => ErrorResponse::bad_request(format!("Failed to update {}", S::NAME.to_lowercase()), Some(e)).into_response(),
+        }
+    }
+
+    pub async fn delete(
+        State(state): State<Arc<AppState>>,
+        Path(id): Path<String>,
+    ) -> Response {
+        let Ok(oid) = ObjectId::parse_str(&id) else {
+            return invalid_id();
+        };
+
+        let service = S::build(&state, ReadContext::Primary);
+        match service.delete(oid).await {
+            Ok(true) => no_content().into_response(),
+            Ok(false) => ErrorResponse::not_found(format!("{} not found", S::NAME)).into_response(),
+            Err(e) => ErrorResponse::internal_error(format!("Failed to delete {}", S::NAME.to_lowercase()), Some(e)).into_response(),
+        }
+    }
+}
+
+impl<S: PagedCrudService> CrudController<S> {
+    pub async fn list(
+        State(state): State<Arc<AppState>>,
+        locale: Locale,
+        Query(params): Query<PaginationParams>,
+    ) -> Response {
+        let service = S::build(&state, ReadContext::Replica);
+        match service.get_all_paginated(params).await {
+            Ok((mut items, meta)) => {
+                items.iter_mut().for_each(|item| S::localize(item, locale));
+                PaginatedResponse::ok(format!("{} retrieved successfully", S::PLURAL), items, meta).into_response()
+            }
+            Err(e) => ErrorResponse::internal_error(format!("Failed to retrieve {}", S::PLURAL.to_lowercase()), Some(e)).into_response(),
+        }
+    }
+}
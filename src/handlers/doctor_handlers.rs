@@ -6,22 +6,32 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
+    events::DomainEvent,
     services::DoctorService,
     repository::DoctorRepository,
-    dto::doctor::{CreateDoctorRequest, UpdateDoctorRequest},
+    dto::doctor::{CreateDoctorRequest, DoctorListQuery, UpdateDoctorRequest},
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
+    delete_policy::DeleteGuard,
 };
 
+fn build_service(state: &AppState, ctx: ReadContext) -> DoctorService {
+    DoctorService::new(DoctorRepository::new(state.db_for(ctx)), DeleteGuard::from_state(state))
+}
+
 pub async fn get_doctors(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
+    Query(filter): Query<DoctorListQuery>,
 ) -> impl IntoResponse {
-    let repo = DoctorRepository::new(state.db.clone());
-    let service = DoctorService::new(repo);
+    if let Err(e) = crate::validation::validate_payload(&filter) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
     
-    match service.get_all_paginated(params.clone()).await {
+    match service.get_all_paginated(params.clone(), filter.status.as_ref()).await {
         Ok((doctors, meta)) => PaginatedResponse::ok("Doctors retrieved successfully", doctors, meta).into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve doctors", "FETCH_FAILED", Some(msg)).into_response(),
     }
@@ -35,11 +45,13 @@ pub async fn create_doctor(
         return e.into_response();
     }
 
-    let repo = DoctorRepository::new(state.db.clone());
-    let service = DoctorService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.create(payload).await {
-        Ok((status, doctor)) => ApiResponse::success(status, "Doctor created successfully", doctor).into_response(),
+        Ok((status, doctor)) => {
+            state.events.publish(DomainEvent::created("doctors", &doctor.id));
+            ApiResponse::success(status, "Doctor created successfully", doctor).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to create doctor", "CREATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -52,8 +64,7 @@ pub async fn get_doctor(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = DoctorRepository::new(state.db.clone());
-    let service = DoctorService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
 
     match service.get_by_id(oid).await {
         Ok(Some(doctor)) => ApiResponse::ok("Doctor retrieved successfully", doctor).into_response(),
@@ -76,11 +87,13 @@ pub async fn update_doctor(
     }
 
 
-    let repo = DoctorRepository::new(state.db.clone());
-    let service = DoctorService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.update(oid, payload).await {
-        Ok(doctor) => ApiResponse::ok("Doctor updated successfully", doctor).into_response(),
+        Ok(doctor) => {
+            state.events.publish(DomainEvent::updated("doctors", &id));
+            ApiResponse::ok("Doctor updated successfully", doctor).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update doctor", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -93,11 +106,13 @@ pub async fn delete_doctor(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = DoctorRepository::new(state.db.clone());
-    let service = DoctorService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
+        Ok(true) => {
+            state.events.publish(DomainEvent::deleted("doctors", &id));
+            no_content().into_response()
+        }
         Ok(false) => ErrorResponse::not_found("Doctor not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete doctor", "DELETE_FAILED", Some(msg)).into_response(),
     }
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::FeatureFlagService,
+    repository::FeatureFlagRepository,
+    dto::feature_flag::{CreateFeatureFlagRequest, UpdateFeatureFlagRequest},
+    flags::Flags,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState) -> FeatureFlagService {
+    FeatureFlagService::new(FeatureFlagRepository::new(state.db.clone()))
+}
+
+/// Flags as evaluated for the caller, for clients that gate UI on them
+pub async fn get_my_flags(flags: Flags) -> impl IntoResponse {
+    ApiResponse::ok("Feature flags evaluated successfully", flags.evaluated()).into_response()
+}
+
+pub async fn get_feature_flags(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_service(&state).list().await {
+        Ok(flags) => ApiResponse::ok("Feature flags retrieved successfully", flags).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve feature flags", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateFeatureFlagRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).create(payload).await {
+        Ok(flag) => {
+            state.feature_flags.invalidate().await;
+            ApiResponse::created("Feature flag created successfully", flag).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create feature flag", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).get(oid).await {
+        Ok(Some(flag)) => ApiResponse::ok("Feature flag retrieved successfully", flag).into_response(),
+        Ok(None) => ErrorResponse::not_found("Feature flag not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve feature flag", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateFeatureFlagRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).update(oid, payload).await {
+        Ok(Some(flag)) => {
+            state.feature_flags.invalidate().await;
+            ApiResponse::ok("Feature flag updated successfully", flag).into_response()
+        }
+        Ok(None) => ErrorResponse::not_found("Feature flag not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update feature flag", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).delete(oid).await {
+        Ok(true) => {
+            state.feature_flags.invalidate().await;
+            no_content().into_response()
+        }
+        Ok(false) => ErrorResponse::not_found("Feature flag not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete feature flag", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
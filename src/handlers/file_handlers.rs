@@ -1,23 +1,63 @@
 use axum::{
     extract::{Path, State, Query, Multipart},
+    http::{header, HeaderMap},
     response::IntoResponse,
+    Extension, Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
-    services::FileService,
-    repository::FileRepository,
+    db::{AppState, ReadContext},
+    dto::file::{CreateFileShareRequest, QuotaExceeded, StorageUsageQuery},
+    quota::UploadOwner,
+    middleware::AuthUser,
+    services::{AuditService, FileService, FileShareService, StorageReconciliationService},
+    repository::{AuditLogRepository, FileRepository, FileShareRepository, FileVersionRepository, StorageReconciliationRepository, StorageUsageRepository, UserRoleRepository},
+    storage_gc::StorageGcConfig,
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
+    refs::ReferenceChecker,
 };
 
+fn build_service(state: &AppState, ctx: ReadContext) -> FileService {
+    let db = state.db_for(ctx);
+    FileService::new(
+        FileRepository::new(db.clone()),
+        FileVersionRepository::new(db.clone()),
+        StorageUsageRepository::new(state.db.clone()),
+        state.storage.clone(),
+        ReferenceChecker::new(db),
+        &state.config.storage_quotas,
+    )
+}
+
+fn build_share_service(state: &AppState) -> FileShareService {
+    FileShareService::new(
+        FileShareRepository::new(state.db.clone()),
+        FileRepository::new(state.db.clone()),
+        state.storage.clone(),
+        AuditService::new(AuditLogRepository::new(state.db.clone())),
+        &state.config.links,
+    )
+}
+
+/// 503 for writes while storage is not configured; metadata stays readable
+fn storage_unavailable(state: &AppState) -> Option<axum::response::Response> {
+    (!state.storage.is_configured()).then(|| {
+        ErrorResponse::new(
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "File storage is not configured",
+            "STORAGE_NOT_CONFIGURED",
+            Some(crate::storage::NOT_CONFIGURED.to_string()),
+        ).into_response()
+    })
+}
+
 pub async fn get_files(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = FileRepository::new(state.db.clone());
-    let service = FileService::new(repo, state.s3_client.clone());
+    let service = build_service(&state, ReadContext::Replica);
     
     match service.get_all_paginated(params.clone()).await {
         Ok((files, meta)) => PaginatedResponse::ok("Files retrieved successfully", files, meta).into_response(),
@@ -33,8 +73,7 @@ pub async fn get_file(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = FileRepository::new(state.db.clone());
-    let service = FileService::new(repo, state.s3_client.clone());
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.get_by_id(oid).await {
         Ok(Some(file)) => ApiResponse::ok("File retrieved successfully", file).into_response(),
@@ -43,52 +82,212 @@ pub async fn get_file(
     }
 }
 
-pub async fn create_file(
-    State(state): State<Arc<AppState>>,
-    mut multipart: Multipart,
-) -> impl IntoResponse {
-    let mut file_name = String::new();
-    let mut file_bytes: Vec<u8> = Vec::new();
-    let mut uploader = String::from("unknown");
+/// Fields of an upload form
+#[derive(Default)]
+struct Upload {
+    file_name: String,
+    file_bytes: Vec<u8>,
+    uploader: Option<String>,
+    medical_record_id: Option<String>,
+    organization_id: Option<String>,
+    version: Option<String>,
+}
+
+async fn read_upload(mut multipart: Multipart) -> Result<Upload, ErrorResponse> {
+    let mut upload = Upload::default();
 
-    // Extract file and uploader from multipart form data
+    // Extract the file and the optional fields from multipart form data
     while let Ok(Some(field)) = multipart.next_field().await {
         let field_name: String = field.name().unwrap_or_default().to_string();
         
         match field_name.as_str() {
             "file" => {
-                file_name = field.file_name().unwrap_or("unnamed").to_string();
+                upload.file_name = field.file_name().unwrap_or("unnamed").to_string();
                 match field.bytes().await {
                     Ok(bytes) => {
-                        file_bytes = bytes.to_vec();
+                        upload.file_bytes = bytes.to_vec();
                     },
-                    Err(_) => return ErrorResponse::bad_request("Failed to read file content", None).into_response(),
-                }
-            },
-            "uploader" => {
-                match field.text().await {
-                    Ok(text) => uploader = text,
-                    Err(_) => {}
+                    Err(_) => return Err(ErrorResponse::bad_request("Failed to read file content", None)),
                 }
             },
+            "uploader" => upload.uploader = field.text().await.ok(),
+            "medical_record_id" => upload.medical_record_id = field.text().await.ok(),
+            "organization_id" => upload.organization_id = field.text().await.ok(),
+            "version" => upload.version = field.text().await.ok(),
             _ => {}
         }
     }
 
     // Validate file was provided
-    if file_name.is_empty() || file_bytes.is_empty() {
-        return ErrorResponse::bad_request("No file provided", Some("Please upload a valid file".to_string())).into_response();
+    if upload.file_name.is_empty() || upload.file_bytes.is_empty() {
+        return Err(ErrorResponse::bad_request("No file provided", Some("Please upload a valid file".to_string())));
+    }
+    Ok(upload)
+}
+
+/// The caller and the organization to charge: `requested` when they belong to it, else the
+/// first of their active roles
+async fn upload_owner(state: &AppState, user: &AuthUser, requested: Option<String>) -> Result<UploadOwner, ErrorResponse> {
+    let organizations = match UserRoleRepository::new(state.db.clone()).find_active_organization_ids(&user.id).await {
+        Ok(organizations) => organizations,
+        Err(e) => return Err(ErrorResponse::internal_error("Failed to load organizations", Some(e.to_string()))),
+    };
+    let organization_id = match requested.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+        Some(id) if organizations.contains(&id) => Some(id),
+        Some(_) => return Err(ErrorResponse::forbidden("You do not belong to that organization")),
+        None => organizations.into_iter().next(),
+    };
+    Ok(UploadOwner { user_id: Some(user.id.clone()), organization_id })
+}
+
+fn quota_exceeded(exceeded: QuotaExceeded) -> ErrorResponse {
+    let details = format!(
+        "The {} quota has {} of {} bytes left; this upload needs {}",
+        exceeded.scope, exceeded.remaining_bytes, exceeded.limit_bytes, exceeded.requested_bytes,
+    );
+    ErrorResponse::new(axum::http::StatusCode::PAYLOAD_TOO_LARGE, "Storage quota exceeded", "QUOTA_EXCEEDED", Some(details)).with_data(exceeded)
+}
+
+fn parse_version_path(id: &str, version: &str) -> Result<(ObjectId, i32), ErrorResponse> {
+    let Ok(oid) = ObjectId::parse_str(id) else {
+        return Err(ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())));
+    };
+    match version.parse::<i32>() {
+        Ok(version) => Ok((oid, version)),
+        Err(_) => Err(ErrorResponse::bad_request("Invalid version", Some("version must be a number".to_string()))),
     }
+}
+
+fn content_response(name: &str, file_type: String, content: Vec<u8>) -> axum::response::Response {
+    (
+        [
+            (header::CONTENT_TYPE, file_type),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"{}\"", name.replace(['"', '\\'], "_"))),
+        ],
+        content,
+    ).into_response()
+}
 
-    let repo = FileRepository::new(state.db.clone());
-    let service = FileService::new(repo, state.s3_client.clone());
+pub async fn create_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    multipart: Multipart,
+) -> impl IntoResponse {
+    if let Some(unavailable) = storage_unavailable(&state) {
+        return unavailable;
+    }
+    let upload = match read_upload(multipart).await {
+        Ok(upload) => upload,
+        Err(e) => return e.into_response(),
+    };
+    let owner = match upload_owner(&state, &user, upload.organization_id).await {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+    let uploader = upload.uploader.unwrap_or_else(|| "unknown".to_string());
+
+    let service = build_service(&state, ReadContext::Primary);
+    match service.quota_exceeded(&owner, upload.file_bytes.len() as u64).await {
+        Ok(None) => {}
+        Ok(Some(exceeded)) => return quota_exceeded(exceeded).into_response(),
+        Err((status, msg)) => return ErrorResponse::new(status, "Failed to upload file", "UPLOAD_FAILED", Some(msg)).into_response(),
+    }
     
-    match service.create(file_name, file_bytes, uploader).await {
+    match service.create(upload.file_name, upload.file_bytes, uploader, upload.medical_record_id, &owner).await {
         Ok((status, file)) => ApiResponse::success(status, "File uploaded successfully", file).into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to upload file", "UPLOAD_FAILED", Some(msg)).into_response(),
     }
 }
 
+/// Replace a file's content with a new version (multipart `file`, optional `uploader` and
+/// `version`, which must be the current one)
+pub async fn replace_file_content(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    multipart: Multipart,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    if let Some(unavailable) = storage_unavailable(&state) {
+        return unavailable;
+    }
+    let upload = match read_upload(multipart).await {
+        Ok(upload) => upload,
+        Err(e) => return e.into_response(),
+    };
+    let expected_version = match upload.version.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::parse::<i32>) {
+        None => None,
+        Some(Ok(version)) => Some(version),
+        Some(Err(_)) => return ErrorResponse::bad_request("Invalid version", Some("version must be a number".to_string())).into_response(),
+    };
+    let uploader = upload.uploader.unwrap_or(user.id);
+
+    // New versions are charged to whoever the file is charged to
+    let service = build_service(&state, ReadContext::Primary);
+    let quota = match service.owner_of(oid).await {
+        Ok(owner) => service.quota_exceeded(&owner, upload.file_bytes.len() as u64).await,
+        Err(e) => Err(e),
+    };
+    match quota {
+        Ok(None) => {}
+        Ok(Some(exceeded)) => return quota_exceeded(exceeded).into_response(),
+        Err((status, msg)) => return ErrorResponse::new(status, "Failed to replace file content", "UPLOAD_FAILED", Some(msg)).into_response(),
+    }
+
+    match service.replace(oid, upload.file_name, upload.file_bytes, uploader, expected_version).await {
+        Ok(file) => ApiResponse::ok("File content replaced successfully", file).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to replace file content", "UPLOAD_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_file_versions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).versions(oid).await {
+        Ok(Some(versions)) => ApiResponse::ok("File versions retrieved successfully", versions).into_response(),
+        Ok(None) => ErrorResponse::not_found("File not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve file versions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn restore_file_version(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, version)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (oid, version) = match parse_version_path(&id, &version) {
+        Ok(ids) => ids,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_service(&state, ReadContext::Primary).restore(oid, version, &user.id).await {
+        Ok(file) => ApiResponse::ok("File version restored successfully", file).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to restore file version", "RESTORE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_file_version_content(
+    State(state): State<Arc<AppState>>,
+    Path((id, version)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (oid, version) = match parse_version_path(&id, &version) {
+        Ok(ids) => ids,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_service(&state, ReadContext::Primary).version_content(oid, version).await {
+        Ok(file) => content_response(&file.name, file.file_type, file.content),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to read file version", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
 pub async fn delete_file(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -96,9 +295,11 @@ pub async fn delete_file(
     let Ok(oid) = ObjectId::parse_str(&id) else {
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
+    if let Some(unavailable) = storage_unavailable(&state) {
+        return unavailable;
+    }
 
-    let repo = FileRepository::new(state.db.clone());
-    let service = FileService::new(repo, state.s3_client.clone());
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.delete(oid).await {
         Ok(true) => no_content().into_response(),
@@ -106,3 +307,139 @@ pub async fn delete_file(
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete file", "DELETE_FAILED", Some(msg)).into_response(),
     }
 }
+
+/// Content of a file read through the API; it is the `url` of files kept by the local
+/// backend, while files in S3 are usually downloaded from theirs
+pub async fn get_file_content(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    let backend = match state.storage.backend() {
+        Ok(backend) => backend,
+        Err((status, msg)) => return ErrorResponse::new(status, "File storage is not configured", "STORAGE_NOT_CONFIGURED", Some(msg)).into_response(),
+    };
+
+    let file = match FileRepository::new(state.db.clone()).find_by_id(oid).await {
+        Ok(Some(file)) => file,
+        Ok(None) => return ErrorResponse::not_found("File not found").into_response(),
+        Err(e) => return ErrorResponse::internal_error("Failed to retrieve file", Some(e)).into_response(),
+    };
+    let Some(read) = backend.read(state.storage.bucket(), &file.path) else {
+        return ErrorResponse::not_found("File content is not served by this API; download it from the file's url").into_response();
+    };
+    let content = read.await;
+    match content {
+        Ok(bytes) => content_response(&file.name, file.file_type, bytes),
+        Err(e) => ErrorResponse::internal_error("Failed to read file content", Some(e)).into_response(),
+    }
+}
+
+pub async fn create_file_share(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    payload: Option<Json<CreateFileShareRequest>>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_share_service(&state).create(oid, &user, payload).await {
+        Ok(share) => ApiResponse::created("Share link created successfully", share).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to share file", "SHARE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn revoke_file_share(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, share_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (Ok(oid), Ok(share_oid)) = (ObjectId::parse_str(&id), ObjectId::parse_str(&share_id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("IDs must be valid MongoDB ObjectIds".to_string())).into_response();
+    };
+
+    match build_share_service(&state).revoke(oid, share_oid, &user).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Active share link not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to revoke share link", "REVOKE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Download through a share link; public, the token is the credential
+pub async fn get_shared_file(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let mut client = mongodb::bson::Document::new();
+    if let Some(agent) = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok()) {
+        client.insert("user_agent", agent);
+    }
+
+    match build_share_service(&state).open(&token, client).await {
+        Ok(file) => (
+            [
+                (header::CONTENT_TYPE, file.file_type),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", file.name.replace(['"', '\\'], "_"))),
+                (header::CACHE_CONTROL, "private, no-store".to_string()),
+            ],
+            file.content,
+        ).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Shared file is not available", "SHARE_UNAVAILABLE", Some(msg)).into_response(),
+    }
+}
+
+/// Storage consumption per uploader and organization against their quotas
+pub async fn get_storage_usage(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StorageUsageQuery>,
+) -> impl IntoResponse {
+    let scope = query.scope.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    if scope.is_some_and(|s| s != crate::quota::SCOPE_USER && s != crate::quota::SCOPE_ORGANIZATION) {
+        return ErrorResponse::bad_request("Invalid scope", Some("scope must be user or organization".to_string())).into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).usage(scope).await {
+        Ok(usage) => ApiResponse::ok("Storage usage retrieved successfully", usage).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve storage usage", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+fn build_reconciliation_service(state: &AppState) -> StorageReconciliationService {
+    StorageReconciliationService::new(
+        StorageReconciliationRepository::new(state.db.clone()),
+        state.storage.clone(),
+        StorageGcConfig::from_env(),
+    )
+}
+
+/// Garbage collector settings and its latest findings
+pub async fn get_storage_reconciliation(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_reconciliation_service(&state).status().await {
+        Ok(status) => ApiResponse::ok("Storage reconciliation retrieved successfully", status).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve storage reconciliation", Some(e)).into_response(),
+    }
+}
+
+pub async fn run_storage_reconciliation(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if let Some(response) = storage_unavailable(&state) {
+        return response;
+    }
+
+    match build_reconciliation_service(&state).run("manual").await {
+        Ok(run) => ApiResponse::ok("Storage reconciled successfully", run).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to reconcile storage", "RECONCILIATION_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::FirmwareService,
+    repository::{FirmwareRepository, KitRepository},
+    dto::firmware::{CreateFirmwareRequest, UpdateFirmwareRequest},
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState, context: ReadContext) -> FirmwareService {
+    let db = state.db_for(context);
+    FirmwareService::new(
+        Arc::new(FirmwareRepository::new(db.clone())),
+        Arc::new(KitRepository::new(db)),
+    )
+}
+
+pub async fn get_firmware_releases(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.get_all().await {
+        Ok(releases) => ApiResponse::ok("Firmware releases retrieved successfully", releases).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve firmware releases", Some(e)).into_response(),
+    }
+}
+
+pub async fn create_firmware(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateFirmwareRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.create(payload).await {
+        Ok(firmware) => ApiResponse::success(StatusCode::CREATED, "Firmware created successfully", firmware).into_response(),
+        Err(e) if e.contains("already exists") => ErrorResponse::conflict("Firmware version already exists", Some(e)).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to create firmware", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_firmware(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.get_by_id(oid).await {
+        Ok(Some(firmware)) => ApiResponse::ok("Firmware retrieved successfully", firmware).into_response(),
+        Ok(None) => ErrorResponse::not_found("Firmware not found").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve firmware", Some(e)).into_response(),
+    }
+}
+
+pub async fn update_firmware(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateFirmwareRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.update(oid, payload).await {
+        Ok(firmware) => ApiResponse::ok("Firmware updated successfully", firmware).into_response(),
+        Err(e) if e.contains("not found") => ErrorResponse::not_found("Firmware not found").into_response(),
+        Err(e) if e.contains("already exists") => ErrorResponse::conflict("Firmware version already exists", Some(e)).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to update firmware", Some(e)).into_response(),
+    }
+}
+
+pub async fn delete_firmware(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Firmware not found").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to delete firmware", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_latest_firmware_for_kit(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+) -> impl IntoResponse {
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.latest_for_kit(&code).await {
+        Ok(Some(latest)) => ApiResponse::ok("Latest firmware retrieved successfully", latest).into_response(),
+        Ok(None) => ErrorResponse::not_found("Kit not found").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve latest firmware", Some(e)).into_response(),
+    }
+}
@@ -1,27 +1,36 @@
 use axum::{
     extract::{Path, State, Query},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::InsuranceService,
     repository::InsuranceRepository,
-    dto::insurance::{CreateInsuranceRequest, UpdateInsuranceRequest},
+    dto::insurance::{CreateInsuranceRequest, InsuranceListQuery, UpdateInsuranceRequest},
+    middleware::AuthUser,
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
 };
 
+fn build_service(state: &AppState, ctx: ReadContext) -> InsuranceService {
+    InsuranceService::new(InsuranceRepository::new(state.db_for(ctx)), state.config.scheduling.default_timezone.clone())
+}
+
 pub async fn get_insurances(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
+    Query(filter): Query<InsuranceListQuery>,
 ) -> impl IntoResponse {
-    let repo = InsuranceRepository::new(state.db.clone());
-    let service = InsuranceService::new(repo);
-    
-    match service.get_all_paginated(params.clone()).await {
+    if let Err(e) = crate::validation::validate_payload(&filter) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.get_all_paginated(params.clone(), filter.status.as_ref()).await {
         Ok((insurances, meta)) => PaginatedResponse::ok("Insurances retrieved successfully", insurances, meta).into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve insurances", "FETCH_FAILED", Some(msg)).into_response(),
     }
@@ -29,16 +38,15 @@ pub async fn get_insurances(
 
 pub async fn create_insurance(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(payload): Json<CreateInsuranceRequest>,
 ) -> impl IntoResponse {
     if let Err(e) = crate::validation::validate_payload(&payload) {
         return e.into_response();
     }
 
-    let repo = InsuranceRepository::new(state.db.clone());
-    let service = InsuranceService::new(repo);
-    
-    match service.create(payload).await {
+    let service = build_service(&state, ReadContext::Primary);
+    match service.create(payload, &user.id).await {
         Ok((status, insurance)) => ApiResponse::success(status, "Insurance created successfully", insurance).into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to create insurance", "CREATE_FAILED", Some(msg)).into_response(),
     }
@@ -52,9 +60,7 @@ pub async fn get_insurance(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = InsuranceRepository::new(state.db.clone());
-    let service = InsuranceService::new(repo);
-
+    let service = build_service(&state, ReadContext::Primary);
     match service.get_by_id(oid).await {
         Ok(Some(insurance)) => ApiResponse::ok("Insurance retrieved successfully", insurance).into_response(),
         Ok(None) => ErrorResponse::not_found("Insurance not found").into_response(),
@@ -64,6 +70,7 @@ pub async fn get_insurance(
 
 pub async fn update_insurance(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateInsuranceRequest>,
 ) -> impl IntoResponse {
@@ -76,10 +83,8 @@ pub async fn update_insurance(
     }
 
 
-    let repo = InsuranceRepository::new(state.db.clone());
-    let service = InsuranceService::new(repo);
-    
-    match service.update(oid, payload).await {
+    let service = build_service(&state, ReadContext::Primary);
+    match service.update(oid, payload, &user.id).await {
         Ok(insurance) => ApiResponse::ok("Insurance updated successfully", insurance).into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update insurance", "UPDATE_FAILED", Some(msg)).into_response(),
     }
@@ -93,12 +98,26 @@ pub async fn delete_insurance(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = InsuranceRepository::new(state.db.clone());
-    let service = InsuranceService::new(repo);
-    
+    let service = build_service(&state, ReadContext::Primary);
     match service.delete(oid).await {
         Ok(true) => no_content().into_response(),
         Ok(false) => ErrorResponse::not_found("Insurance not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete insurance", "DELETE_FAILED", Some(msg)).into_response(),
     }
 }
+
+/// Every version of an insurance with its effective period, newest first
+pub async fn get_insurance_versions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).versions(oid).await {
+        Ok(Some(versions)) => ApiResponse::ok("Insurance versions retrieved successfully", versions).into_response(),
+        Ok(None) => ErrorResponse::not_found("Insurance not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve insurance versions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
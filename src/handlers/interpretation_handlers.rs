@@ -1,34 +1,66 @@
 use axum::{
     extract::{Path, State, Query},
     response::IntoResponse,
-    Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
+    i18n::Locale,
     services::InterpretationService,
     repository::InterpretationRepository,
+    models::Interpretation,
     dto::interpretation::{CreateInterpretationRequest, UpdateInterpretationRequest},
-    response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
-    pagination::PaginationParams,
+    handlers::crud::{CrudController, CrudService, PagedCrudService},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    pagination::{PaginationMeta, PaginationParams},
 };
 
-pub async fn get_interpretations(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<PaginationParams>,
-) -> impl IntoResponse {
-    let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
-    let service = InterpretationService::new(repo);
-    
-    match service.get_all_paginated(params).await {
-        Ok((interpretations, meta)) => PaginatedResponse::ok("Interpretations retrieved successfully", interpretations, meta).into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to retrieve interpretations", Some(e)).into_response(),
+/// `list`, `create`, `get`, `update` and `delete` for `/interpretations`
+pub type Interpretations = CrudController<InterpretationService>;
+
+impl CrudService for InterpretationService {
+    type Item = Interpretation;
+    type Create = CreateInterpretationRequest;
+    type Update = UpdateInterpretationRequest;
+
+    const NAME: &'static str = "Interpretation";
+    const PLURAL: &'static str = "Interpretations";
+
+    fn build(state: &AppState, context: ReadContext) -> Self {
+        InterpretationService::new(Arc::new(InterpretationRepository::new(state.db_for(context))))
+    }
+
+    async fn create(&self, dto: CreateInterpretationRequest) -> Result<Interpretation, String> {
+        InterpretationService::create(self, dto).await
+    }
+
+    async fn get_by_id(&self, id: ObjectId) -> Result<Option<Interpretation>, String> {
+        InterpretationService::get_by_id(self, id).await
+    }
+
+    async fn update(&self, id: ObjectId, dto: UpdateInterpretationRequest) -> Result<Interpretation, String> {
+        InterpretationService::update(self, id, dto).await
+    }
+
+    async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        InterpretationService::delete(self, id).await
+    }
+
+    fn localize(item: &mut Interpretation, locale: Locale) {
+        item.localize(locale);
+    }
+}
+
+impl PagedCrudService for InterpretationService {
+    async fn get_all_paginated(&self, params: PaginationParams) -> Result<(Vec<Interpretation>, PaginationMeta), String> {
+        InterpretationService::get_all_paginated(self, params).await
     }
 }
 
 pub async fn get_interpretation_by_code(
     State(state): State<Arc<AppState>>,
+    locale: Locale,
     Path(code): Path<String>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
@@ -36,13 +68,17 @@ pub async fn get_interpretation_by_code(
     let service = InterpretationService::new(repo);
     
     match service.get_by_code_paginated(&code, params).await {
-        Ok((interpretations, meta)) => PaginatedResponse::ok("Interpretations retrieved successfully", interpretations, meta).into_response(),
+        Ok((mut interpretations, meta)) => {
+            interpretations.iter_mut().for_each(|i| i.localize(locale));
+            PaginatedResponse::ok("Interpretations retrieved successfully", interpretations, meta).into_response()
+        }
         Err(e) => ErrorResponse::internal_error("Failed to retrieve interpretations", Some(e)).into_response(),
     }
 }
 
 pub async fn get_interpretations_by_coding_code(
     State(state): State<Arc<AppState>>,
+    locale: Locale,
     Path(coding_code): Path<String>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
@@ -50,96 +86,29 @@ pub async fn get_interpretations_by_coding_code(
     let service = InterpretationService::new(repo);
     
     match service.get_by_coding_code_paginated(&coding_code, params).await {
-        Ok((interpretations, meta)) => PaginatedResponse::ok("Interpretations retrieved successfully", interpretations, meta).into_response(),
+        Ok((mut interpretations, meta)) => {
+            interpretations.iter_mut().for_each(|i| i.localize(locale));
+            PaginatedResponse::ok("Interpretations retrieved successfully", interpretations, meta).into_response()
+        }
         Err(e) => ErrorResponse::internal_error("Failed to retrieve interpretations", Some(e)).into_response(),
     }
 }
 
 pub async fn get_interpretation_by_code_and_coding_code(
     State(state): State<Arc<AppState>>,
+    locale: Locale,
     Path((code, coding_code)): Path<(String, String)>,
 ) -> impl IntoResponse {
     let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
     let service = InterpretationService::new(repo);
     
     match service.get_by_code_and_coding_code(&code, &coding_code).await {
-        Ok(Some(interpretation)) => ApiResponse::ok("Interpretation retrieved successfully", interpretation).into_response(),
+        Ok(Some(mut interpretation)) => {
+            interpretation.localize(locale);
+            ApiResponse::ok("Interpretation retrieved successfully", interpretation).into_response()
+        }
         Ok(None) => ErrorResponse::not_found("Interpretation not found").into_response(),
         Err(e) => ErrorResponse::internal_error("Failed to retrieve interpretation", Some(e)).into_response(),
     }
 }
 
-pub async fn create_interpretation(
-    State(state): State<Arc<AppState>>,
-    Json(payload): Json<CreateInterpretationRequest>,
-) -> impl IntoResponse {
-    if let Err(e) = crate::validation::validate_payload(&payload) {
-        return e.into_response();
-    }
-
-    let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
-    let service = InterpretationService::new(repo);
-    
-    match service.create(payload).await {
-        Ok(interpretation) => ApiResponse::success(axum::http::StatusCode::CREATED, "Interpretation created successfully", interpretation).into_response(),
-        Err(e) => ErrorResponse::bad_request("Failed to create interpretation", Some(e)).into_response(),
-    }
-}
-
-pub async fn get_interpretation(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
-
-    let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
-    let service = InterpretationService::new(repo);
-
-    match service.get_by_id(oid).await {
-        Ok(Some(interpretation)) => ApiResponse::ok("Interpretation retrieved successfully", interpretation).into_response(),
-        Ok(None) => ErrorResponse::not_found("Interpretation not found").into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to retrieve interpretation", Some(e)).into_response(),
-    }
-}
-
-pub async fn update_interpretation(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(payload): Json<UpdateInterpretationRequest>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
-
-    if let Err(e) = crate::validation::validate_payload(&payload) {
-        return e.into_response();
-    }
-
-    let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
-    let service = InterpretationService::new(repo);
-    
-    match service.update(oid, payload).await {
-        Ok(interpretation) => ApiResponse::ok("Interpretation updated successfully", interpretation).into_response(),
-        Err(e) => ErrorResponse::bad_request("Failed to update interpretation", Some(e)).into_response(),
-    }
-}
-
-pub async fn delete_interpretation(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
-
-    let repo = Arc::new(InterpretationRepository::new(state.db.clone()));
-    let service = InterpretationService::new(repo);
-    
-    match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
-        Ok(false) => ErrorResponse::not_found("Interpretation not found").into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to delete interpretation", Some(e)).into_response(),
-    }
-}
@@ -0,0 +1,75 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::{InsuranceService, InvoiceService, PriceListService},
+    repository::{InsuranceRepository, InvoiceRepository, PriceListRepository},
+    refs::ReferenceChecker,
+    sequences::SequenceGenerator,
+    dto::invoice::{CreateInvoiceRequest, InvoiceQuery},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> InvoiceService {
+    let db = state.db_for(ctx);
+    let price_lists = PriceListService::new(
+        PriceListRepository::new(db.clone()),
+        ReferenceChecker::new(db.clone()),
+        state.config.scheduling.default_timezone.clone(),
+    );
+    let insurances = InsuranceService::new(InsuranceRepository::new(db.clone()), state.config.scheduling.default_timezone.clone());
+    let corporate = crate::handlers::corporate_client_handlers::build_service(state, ctx);
+    InvoiceService::new(InvoiceRepository::new(db.clone()), price_lists, insurances, corporate, ReferenceChecker::new(db.clone()), SequenceGenerator::new(db))
+}
+
+pub async fn create_invoice(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateInvoiceRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload, &user.id).await {
+        Ok(invoice) => ApiResponse::created("Invoice created successfully", invoice).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create invoice", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_invoices(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<InvoiceQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((invoices, meta)) => PaginatedResponse::ok("Invoices retrieved successfully", invoices, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve invoices", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_invoice(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(Some(invoice)) => ApiResponse::ok("Invoice retrieved successfully", invoice).into_response(),
+        Ok(None) => ErrorResponse::not_found("Invoice not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve invoice", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
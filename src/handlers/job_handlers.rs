@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::JobService,
+    repository::JobRepository,
+    dto::job::{JobQuery, RetryJobsRequest, RetryJobsResponse},
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+const DEFAULT_RETRY_LIMIT: i64 = 100;
+
+fn build_service(state: &AppState) -> JobService {
+    JobService::new(JobRepository::new(state.db.clone()))
+}
+
+/// Background jobs, newest first; `?status=failed` is the dead-letter queue.
+pub async fn get_jobs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<JobQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state).list(query, params).await {
+        Ok((jobs, meta)) => PaginatedResponse::ok("Jobs retrieved successfully", jobs, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve jobs", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// A job with the errors of all its failed attempts
+pub async fn get_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).get(oid).await {
+        Ok(Some(job)) => ApiResponse::ok("Job retrieved successfully", job).into_response(),
+        Ok(None) => ErrorResponse::not_found("Job not found").into_response(),
+        Err(msg) => ErrorResponse::internal_error("Failed to retrieve job", Some(msg)).into_response(),
+    }
+}
+
+pub async fn retry_job(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).retry(oid).await {
+        Ok(job) => {
+            crate::jobs::spawn(state.db.clone(), job.clone());
+            ApiResponse::ok("Job queued for retry", job).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retry job", "RETRY_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Retry the failed jobs of one type
+pub async fn retry_jobs(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<RetryJobsRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let limit = payload.limit.unwrap_or(DEFAULT_RETRY_LIMIT);
+    match build_service(&state).retry_failed(&payload.job_type, limit).await {
+        Ok(jobs) => {
+            let job_ids = jobs.iter().filter_map(|job| job.id.map(|id| id.to_hex())).collect();
+            let retried = jobs.len();
+            for job in jobs {
+                crate::jobs::spawn(state.db.clone(), job);
+            }
+            let response = RetryJobsResponse { job_type: payload.job_type, retried, job_ids };
+            ApiResponse::ok("Failed jobs queued for retry", response).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retry jobs", "RETRY_FAILED", Some(msg)).into_response(),
+    }
+}
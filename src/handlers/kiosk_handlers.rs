@@ -0,0 +1,112 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::KioskService,
+    repository::KioskDeviceRepository,
+    dto::kiosk::{CreateKioskDeviceRequest, KioskPairRequest},
+    middleware::AuthUser,
+    response::{ApiResponse, ErrorResponse},
+};
+
+fn build_service(state: &AppState) -> KioskService {
+    KioskService::new(KioskDeviceRepository::new(state.db.clone()))
+}
+
+/// Pair a kiosk with the code shown to the admin who registered it
+///
+/// POST /auth/kiosk/pair
+///
+/// Request body:
+/// ```json
+/// {
+///     "pairing_code": "K7PX-3QRM"
+/// }
+/// ```
+pub async fn pair_kiosk(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<KioskPairRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).pair(&payload.pairing_code).await {
+        Ok(token) => ApiResponse::ok("Kiosk paired successfully", token).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Pairing failed", "PAIRING_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_kiosks(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_service(&state).list().await {
+        Ok(devices) => ApiResponse::ok("Kiosk devices retrieved successfully", devices).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve kiosk devices", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_kiosk(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateKioskDeviceRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).create(payload, &user.id).await {
+        Ok(device) => ApiResponse::created("Kiosk device registered; enter the pairing code on the kiosk", device).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to register kiosk device", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_kiosk(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).get(oid).await {
+        Ok(Some(device)) => ApiResponse::ok("Kiosk device retrieved successfully", device).into_response(),
+        Ok(None) => ErrorResponse::not_found("Kiosk device not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve kiosk device", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn renew_kiosk_pairing_code(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).renew_pairing_code(oid).await {
+        Ok(Some(device)) => ApiResponse::ok("Pairing code issued; pairing with it revokes the device's current token", device).into_response(),
+        Ok(None) => ErrorResponse::not_found("Kiosk device not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to issue pairing code", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn revoke_kiosk(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).revoke(oid).await {
+        Ok(Some(device)) => ApiResponse::ok("Kiosk device revoked", device).into_response(),
+        Ok(None) => ErrorResponse::not_found("Kiosk device not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to revoke kiosk device", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
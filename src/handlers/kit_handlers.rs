@@ -1,59 +1,92 @@
 use axum::{
-    extract::{Path, State},
-    response::IntoResponse,
-    Json,
+    extract::{Path, State, Query},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
+    distributor::{self, KitScope},
     services::KitService,
     repository::KitRepository,
-    dto::kit::{CreateKitRequest, UpdateKitRequest},
-    response::{ApiResponse, ErrorResponse, no_content},
+    dto::common::{View, ViewQuery},
+    dto::kit::{CreateKitRequest, UpdateKitRequest, KitHeartbeatRequest, MapClustersQuery, NearbyKitsQuery},
+    middleware::AuthUser,
+    response::{no_content, ApiResponse, ErrorResponse},
 };
 
+fn build_service(state: &AppState, context: ReadContext, scope: KitScope) -> KitService {
+    KitService::new(Arc::new(KitRepository::new(state.db_for(context)).scoped(scope)))
+}
+
+/// The kits the caller may see, see `crate::distributor`
+pub(crate) async fn caller_scope(state: &AppState, user: &AuthUser) -> Result<KitScope, Response> {
+    distributor::kit_scope(&state.db, user).await
+        .map_err(|e| ErrorResponse::internal_error("Failed to resolve kit scope", Some(e)).into_response())
+}
+
+fn invalid_id() -> Response {
+    ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response()
+}
+
 pub async fn get_kits(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(view): Query<ViewQuery>,
 ) -> impl IntoResponse {
-    let repo = Arc::new(KitRepository::new(state.db.clone()));
-    let service = KitService::new(repo);
-    
-    match service.get_all().await {
-        Ok(kits) => ApiResponse::ok("Kits retrieved successfully", kits).into_response(),
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+    let service = build_service(&state, ReadContext::Replica, scope);
+
+    let result = match view.view {
+        View::Summary => service.get_summaries().await
+            .map(|kits| ApiResponse::ok("Kits retrieved successfully", kits).into_response()),
+        View::Full => service.get_all().await
+            .map(|kits| ApiResponse::ok("Kits retrieved successfully", kits).into_response()),
+    };
+    match result {
+        Ok(response) => response,
         Err(e) => ErrorResponse::internal_error("Failed to retrieve kits", Some(e)).into_response(),
     }
 }
 
 pub async fn create_kit(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(payload): Json<CreateKitRequest>,
-) -> impl IntoResponse {
+) -> Response {
     if let Err(e) = crate::validation::validate_payload(&payload) {
         return e.into_response();
     }
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
 
-    let repo = Arc::new(KitRepository::new(state.db.clone()));
-    let service = KitService::new(repo);
-    
-    match service.create(payload).await {
-        Ok(kit) => ApiResponse::success(axum::http::StatusCode::CREATED, "Kit created successfully", kit).into_response(),
+    match build_service(&state, ReadContext::Primary, scope).create(payload).await {
+        Ok(kit) => ApiResponse::success(StatusCode::CREATED, "Kit created successfully", KitService::map_to_response(kit)).into_response(),
         Err(e) => ErrorResponse::bad_request("Failed to create kit", Some(e)).into_response(),
     }
 }
 
 pub async fn get_kit(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Path(id): Path<String>,
-) -> impl IntoResponse {
+) -> Response {
     let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+        return invalid_id();
+    };
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
     };
 
-    let repo = Arc::new(KitRepository::new(state.db.clone()));
-    let service = KitService::new(repo);
-
-    match service.get_by_id(oid).await {
+    match build_service(&state, ReadContext::Primary, scope).get_by_id(oid).await {
         Ok(Some(kit)) => ApiResponse::ok("Kit retrieved successfully", kit).into_response(),
         Ok(None) => ErrorResponse::not_found("Kit not found").into_response(),
         Err(e) => ErrorResponse::internal_error("Failed to retrieve kit", Some(e)).into_response(),
@@ -62,21 +95,22 @@ pub async fn get_kit(
 
 pub async fn update_kit(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Path(id): Path<String>,
     Json(payload): Json<UpdateKitRequest>,
-) -> impl IntoResponse {
+) -> Response {
     let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+        return invalid_id();
     };
-
     if let Err(e) = crate::validation::validate_payload(&payload) {
         return e.into_response();
     }
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
 
-    let repo = Arc::new(KitRepository::new(state.db.clone()));
-    let service = KitService::new(repo);
-    
-    match service.update(oid, payload).await {
+    match build_service(&state, ReadContext::Primary, scope).update(oid, payload).await {
         Ok(kit) => ApiResponse::ok("Kit updated successfully", kit).into_response(),
         Err(e) => ErrorResponse::bad_request("Failed to update kit", Some(e)).into_response(),
     }
@@ -84,18 +118,81 @@ pub async fn update_kit(
 
 pub async fn delete_kit(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Path(id): Path<String>,
-) -> impl IntoResponse {
+) -> Response {
     let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+        return invalid_id();
+    };
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
     };
 
-    let repo = Arc::new(KitRepository::new(state.db.clone()));
-    let service = KitService::new(repo);
-    
-    match service.delete(oid).await {
+    match build_service(&state, ReadContext::Primary, scope).delete(oid).await {
         Ok(true) => no_content().into_response(),
         Ok(false) => ErrorResponse::not_found("Kit not found").into_response(),
         Err(e) => ErrorResponse::internal_error("Failed to delete kit", Some(e)).into_response(),
     }
 }
+
+pub async fn get_nearby_kits(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<NearbyKitsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    match build_service(&state, ReadContext::Replica, scope).nearby(query).await {
+        Ok(kits) => ApiResponse::ok("Kits retrieved successfully", kits).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve kits", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_kit_map_clusters(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<MapClustersQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let bounds = match query.bounds() {
+        Ok(bounds) => bounds,
+        Err(e) => return ErrorResponse::bad_request("Invalid map bounds", Some(e)).into_response(),
+    };
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    match build_service(&state, ReadContext::Replica, scope).map_clusters(query, bounds).await {
+        Ok(clusters) => ApiResponse::ok("Kit clusters retrieved successfully", clusters).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve kit clusters", Some(e)).into_response(),
+    }
+}
+
+/// Called by the kit itself, so not scoped to a distributor
+pub async fn kit_heartbeat(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    Json(payload): Json<KitHeartbeatRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary, KitScope::All);
+
+    match service.heartbeat(&code, payload).await {
+        Ok(Some(kit)) => ApiResponse::ok("Heartbeat recorded", kit).into_response(),
+        Ok(None) => ErrorResponse::not_found("Kit not found").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to record heartbeat", Some(e)).into_response(),
+    }
+}
@@ -1,29 +1,76 @@
 use axum::{
     extract::{Path, State, Query},
-    response::IntoResponse,
-    Json,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Extension, Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
+    events::DomainEvent,
     services::MedicalRecordService,
-    repository::MedicalRecordRepository,
-    dto::medical_record::{CreateMedicalRecordRequest, UpdateMedicalRecordRequest},
+    repository::{MedicalRecordChangeRepository, MedicalRecordRepository},
+    dto::common::LinksQuery,
+    dto::medical_record::{CreateMedicalRecordRequest, MedicalRecordExpandQuery, MedicalRecordResponse, UpdateMedicalRecordRequest},
+    middleware::AuthUser,
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
+    delete_policy::DeleteGuard,
+    sequences::SequenceGenerator,
+    conditional::{with_version, Preconditions},
 };
 use axum::http::StatusCode;
 
+/// Fill `_links` when the client asked for them
+fn link(state: &AppState, query: &LinksQuery, mut record: MedicalRecordResponse) -> MedicalRecordResponse {
+    if query.links {
+        record.links = Some(state.config.links.medical_record(&record.id));
+    }
+    record
+}
+
+/// Embed what `?expand=` asks for
+async fn expand(state: &AppState, query: &MedicalRecordExpandQuery, records: &mut [MedicalRecordResponse]) -> Result<(), Response> {
+    if !query.guardians() {
+        return Ok(());
+    }
+    super::patient_relationship_handlers::build_service(state, ReadContext::Replica)
+        .expand_guardians(records)
+        .await
+        .map_err(|(status, msg)| ErrorResponse::new(status, "Failed to expand guardians", "FETCH_FAILED", Some(msg)).into_response())
+}
+
+fn build_service(state: &AppState, ctx: ReadContext) -> MedicalRecordService {
+    let db = state.db_for(ctx);
+    MedicalRecordService::new(
+        MedicalRecordRepository::new(db.clone()),
+        MedicalRecordChangeRepository::new(db.clone()),
+        SequenceGenerator::new(db),
+        DeleteGuard::from_state(state),
+    )
+}
+
 pub async fn get_medical_records(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
+    Query(links): Query<LinksQuery>,
+    Query(expansion): Query<MedicalRecordExpandQuery>,
 ) -> impl IntoResponse {
-    let repo = MedicalRecordRepository::new(state.db.clone());
-    let service = MedicalRecordService::new(repo);
+    if let Err(e) = crate::validation::validate_payload(&expansion) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
     
     match service.get_all_paginated(params.clone()).await {
-        Ok((records, meta)) => PaginatedResponse::ok("Medical records retrieved successfully", records, meta).into_response(),
+        Ok((mut records, meta)) => {
+            if let Err(response) = expand(&state, &expansion, &mut records).await {
+                return response;
+            }
+            let records = records.into_iter().map(|record| link(&state, &links, record)).collect();
+            PaginatedResponse::ok("Medical records retrieved successfully", records, meta).into_response()
+        }
         Err(e) => ErrorResponse::internal_error("Failed to retrieve medical records", Some(e)).into_response(),
     }
 }
@@ -31,21 +78,54 @@ pub async fn get_medical_records(
 pub async fn get_medical_record(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    Query(links): Query<LinksQuery>,
+    Query(expansion): Query<MedicalRecordExpandQuery>,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = MedicalRecordRepository::new(state.db.clone());
-    let service = MedicalRecordService::new(repo);
+    if let Err(e) = crate::validation::validate_payload(&expansion) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.get_by_id(oid).await {
-        Ok(Some(record)) => ApiResponse::ok("Medical record retrieved successfully", record).into_response(),
+        Ok(Some(mut record)) => {
+            if let Err(response) = expand(&state, &expansion, std::slice::from_mut(&mut record)).await {
+                return response;
+            }
+            let record = link(&state, &links, record);
+            with_version(&record, ApiResponse::ok("Medical record retrieved successfully", record.clone()).into_response())
+        }
         Ok(None) => ErrorResponse::not_found("Medical record not found").into_response(),
         Err(e) => ErrorResponse::internal_error("Failed to retrieve medical record", Some(e)).into_response(),
     }
 }
 
+pub async fn get_medical_record_by_nik(
+    State(state): State<Arc<AppState>>,
+    Path(nik): Path<String>,
+    Query(links): Query<LinksQuery>,
+    Query(expansion): Query<MedicalRecordExpandQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&expansion) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).get_by_nik(&nik).await {
+        Ok(Some(mut record)) => {
+            if let Err(response) = expand(&state, &expansion, std::slice::from_mut(&mut record)).await {
+                return response;
+            }
+            ApiResponse::ok("Medical record retrieved successfully", link(&state, &links, record)).into_response()
+        }
+        Ok(None) => ErrorResponse::not_found("Medical record not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve medical record", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
 pub async fn create_medical_record(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<CreateMedicalRecordRequest>,
@@ -54,14 +134,16 @@ pub async fn create_medical_record(
         return e.into_response();
     }
 
-    let repo = MedicalRecordRepository::new(state.db.clone());
-    let service = MedicalRecordService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.create(payload).await {
-        Ok((status, record)) => ApiResponse::success(status, "Medical record created successfully", record).into_response(),
+        Ok((StatusCode::OK, record)) => ApiResponse::ok("A medical record with this NIK already exists; returning it instead of creating another", record).into_response(),
+        Ok((status, record)) => {
+            state.events.publish(DomainEvent::created("medical_records", &record.id));
+            ApiResponse::success(status, "Medical record created successfully", record).into_response()
+        }
         Err((status, msg)) => {
             let error_code = match status {
-                StatusCode::CONFLICT => "DUPLICATE_NIK",
                 StatusCode::UNPROCESSABLE_ENTITY => "VALIDATION_ERROR",
                 _ => "VALIDATION_FAILED",
             };
@@ -72,7 +154,9 @@ pub async fn create_medical_record(
 
 pub async fn update_medical_record(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     Json(payload): Json<UpdateMedicalRecordRequest>,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
@@ -83,12 +167,22 @@ pub async fn update_medical_record(
         return e.into_response();
     }
 
+    let service = build_service(&state, ReadContext::Primary);
+    if let Some(preconditions) = Preconditions::from_headers(&headers) {
+        let current = match service.get_by_id(oid).await {
+            Ok(current) => current,
+            Err(e) => return ErrorResponse::internal_error("Failed to update medical record", Some(e)).into_response(),
+        };
+        if let Err(response) = preconditions.check_resource(current.as_ref()) {
+            return response.into_response();
+        }
+    }
 
-    let repo = MedicalRecordRepository::new(state.db.clone());
-    let service = MedicalRecordService::new(repo);
-    
-    match service.update(oid, payload).await {
-        Ok(record) => ApiResponse::ok("Medical record updated successfully", record).into_response(),
+    match service.update(oid, &user, payload).await {
+        Ok(record) => {
+            state.events.publish(DomainEvent::updated("medical_records", &id));
+            with_version(&record, ApiResponse::ok("Medical record updated successfully", record.clone()).into_response())
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update medical record", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -96,17 +190,46 @@ pub async fn update_medical_record(
 pub async fn delete_medical_record(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let Ok(oid) = ObjectId::parse_str(&id) else {
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = MedicalRecordRepository::new(state.db.clone());
-    let service = MedicalRecordService::new(repo);
-    
+    let service = build_service(&state, ReadContext::Primary);
+    if let Some(preconditions) = Preconditions::from_headers(&headers) {
+        let current = match service.get_by_id(oid).await {
+            Ok(current) => current,
+            Err(e) => return ErrorResponse::internal_error("Failed to delete medical record", Some(e)).into_response(),
+        };
+        if let Err(response) = preconditions.check_resource(current.as_ref()) {
+            return response.into_response();
+        }
+    }
+
     match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
+        Ok(true) => {
+            state.events.publish(DomainEvent::deleted("medical_records", &id));
+            no_content().into_response()
+        }
         Ok(false) => ErrorResponse::not_found("Medical record not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete medical record", "DELETE_FAILED", Some(msg)).into_response(),
     }
 }
+
+/// Field-level changes of a record, newest first, with the user who made each.
+pub async fn get_medical_record_changes(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).changes(oid, params).await {
+        Ok(Some((changes, meta))) => PaginatedResponse::ok("Medical record changes retrieved successfully", changes, meta).into_response(),
+        Ok(None) => ErrorResponse::not_found("Medical record not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve medical record changes", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
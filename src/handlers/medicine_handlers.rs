@@ -1,15 +1,19 @@
 use axum::{
     extract::{Path, State, Query},
+    http::{header, StatusCode},
     response::IntoResponse,
     Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
+    events::DomainEvent,
     services::MedicineService,
     repository::MedicineRepository,
-    dto::medicine::{CreateMedicineRequest, UpdateMedicineRequest},
+    dto::medicine::{CreateMedicineRequest, LabelQuery, UpdateMedicineRequest},
+    labels::{self, Label},
+    status::LabelFormat,
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
 };
@@ -18,7 +22,7 @@ pub async fn get_medicines(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = MedicineRepository::new(state.db.clone());
+    let repo = MedicineRepository::new(state.db_for(ReadContext::Replica));
     let service = MedicineService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
@@ -39,7 +43,10 @@ pub async fn create_medicine(
     let service = MedicineService::new(repo);
     
     match service.create(payload).await {
-        Ok((status, medicine)) => ApiResponse::success(status, "Medicine created successfully", medicine).into_response(),
+        Ok((status, medicine)) => {
+            state.events.publish(DomainEvent::created("medicines", &medicine.id));
+            ApiResponse::success(status, "Medicine created successfully", medicine).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to create medicine", "CREATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -62,6 +69,64 @@ pub async fn get_medicine(
     }
 }
 
+/// Pharmacy label of a medicine batch
+///
+/// GET /medicines/:id/label?format=zpl&copies=2
+///
+/// ZPL (default) goes straight to a Zebra-compatible printer; `pdf` has one page per copy.
+/// The barcode holds the batch number.
+pub async fn get_medicine_label(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<LabelQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    let service = MedicineService::new(MedicineRepository::new(state.db.clone()));
+    let medicine = match service.get_by_id(oid).await {
+        Ok(Some(medicine)) => medicine,
+        Ok(None) => return ErrorResponse::not_found("Medicine not found").into_response(),
+        Err((status, msg)) => return ErrorResponse::new(status, "Failed to retrieve medicine", "FETCH_FAILED", Some(msg)).into_response(),
+    };
+
+    let label = Label {
+        title: medicine.trade_name,
+        lines: vec![
+            format!("Batch: {}", medicine.batch_number),
+            format!("Exp: {}", medicine.expired_date),
+            medicine.manufacturer,
+        ],
+        barcode: medicine.batch_number,
+    };
+    let copies = query.copies.unwrap_or(1);
+    let (content_type, extension, body) = match query.format.unwrap_or(LabelFormat::Zpl) {
+        LabelFormat::Pdf => match labels::pdf(&[label], copies) {
+            Ok(pdf) => ("application/pdf", "pdf", pdf),
+            Err(e) => return ErrorResponse::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "The batch number cannot be printed as a barcode",
+                "LABEL_FAILED",
+                Some(e),
+            ).into_response(),
+        },
+        _ => ("text/plain; charset=utf-8", "zpl", labels::zpl(&[label], copies).into_bytes()),
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"medicine-{}.{}\"", id, extension)),
+        ],
+        body,
+    ).into_response()
+}
+
 pub async fn update_medicine(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -80,7 +145,10 @@ pub async fn update_medicine(
     let service = MedicineService::new(repo);
     
     match service.update(oid, payload).await {
-        Ok(medicine) => ApiResponse::ok("Medicine updated successfully", medicine).into_response(),
+        Ok(medicine) => {
+            state.events.publish(DomainEvent::updated("medicines", &id));
+            ApiResponse::ok("Medicine updated successfully", medicine).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update medicine", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -97,7 +165,10 @@ pub async fn delete_medicine(
     let service = MedicineService::new(repo);
     
     match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
+        Ok(true) => {
+            state.events.publish(DomainEvent::deleted("medicines", &id));
+            no_content().into_response()
+        }
         Ok(false) => ErrorResponse::not_found("Medicine not found").into_response(),
         Err((status, msg)) => ErrorResponse::new(status, "Failed to delete medicine", "DELETE_FAILED", Some(msg)).into_response(),
     }
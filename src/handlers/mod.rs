@@ -7,11 +7,13 @@ pub mod medicine_handlers;
 pub mod appointment_handlers;
 pub mod service_handlers;
 pub mod insurance_handlers;
+#[cfg(feature = "s3")]
 pub mod file_handlers;
 
 pub mod role_handlers;
 pub mod user_role_handlers;
 pub mod child_code_handlers;
+pub mod crud;
 pub mod region_handlers;
 pub mod interpretation_handlers;
 
@@ -26,6 +28,7 @@ pub use medicine_handlers::*;
 pub use appointment_handlers::*;
 pub use service_handlers::*;
 pub use insurance_handlers::*;
+#[cfg(feature = "s3")]
 pub use file_handlers::*;
 pub use role_handlers::*;
 pub use user_role_handlers::*;
@@ -33,7 +36,51 @@ pub use code_handlers::*;
 pub use interpretation_handlers::*;
 
 pub mod code_handlers;
+#[cfg(feature = "kits")]
 pub mod kit_handlers;
+#[cfg(feature = "kits")]
 pub use kit_handlers::*;
 pub mod observation_handlers;
 pub use observation_handlers::*;
+pub mod patient_handlers;
+pub mod search_handlers;
+pub mod admin_handlers;
+#[cfg(feature = "kits")]
+pub mod firmware_handlers;
+#[cfg(feature = "kits")]
+pub mod usage_handlers;
+pub mod queue_handlers;
+pub mod waitlist_handlers;
+pub mod teleconsult_handlers;
+pub mod review_handlers;
+pub mod permission_handlers;
+pub mod service_account_handlers;
+pub mod feature_flag_handlers;
+pub mod organization_handlers;
+pub mod note_handlers;
+pub mod allergy_handlers;
+pub mod ward_handlers;
+pub mod admission_handlers;
+pub mod program_handlers;
+#[cfg(feature = "billing")]
+pub mod price_list_handlers;
+#[cfg(feature = "billing")]
+pub mod invoice_handlers;
+#[cfg(feature = "billing")]
+pub mod corporate_client_handlers;
+#[cfg(feature = "billing")]
+pub mod payment_handlers;
+#[cfg(feature = "billing")]
+pub mod bpjs_handlers;
+pub mod outbox_handlers;
+pub mod job_handlers;
+#[cfg(feature = "s3")]
+pub mod report_handlers;
+pub mod report_template_handlers;
+pub mod practitioner_handlers;
+pub mod patient_relationship_handlers;
+pub mod self_registration_handlers;
+pub mod public_booking_handlers;
+pub mod kiosk_handlers;
+pub mod stock_opname_handlers;
+pub mod sync_handlers;
@@ -0,0 +1,125 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::{AuditService, NoteService},
+    repository::{AuditLogRepository, NoteRepository, NoteVersionRepository},
+    refs::ReferenceChecker,
+    dto::note::{CreateNoteRequest, DeleteNoteRequest, NoteQuery, UpdateNoteRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse, no_content},
+};
+
+pub(crate) fn build_service(state: &AppState, ctx: ReadContext) -> NoteService {
+    let db = state.db_for(ctx);
+    NoteService::new(
+        NoteRepository::new(db.clone()),
+        NoteVersionRepository::new(db.clone()),
+        ReferenceChecker::new(db),
+        AuditService::new(AuditLogRepository::new(state.db.clone())),
+    )
+}
+
+pub async fn create_note(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateNoteRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(&user, payload).await {
+        Ok(note) => ApiResponse::created("Note created successfully", note).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create note", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_notes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<NoteQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((notes, meta)) => PaginatedResponse::ok("Notes retrieved successfully", notes, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve notes", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_note(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(Some(note)) => ApiResponse::ok("Note retrieved successfully", note).into_response(),
+        Ok(None) => ErrorResponse::not_found("Note not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve note", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_note(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateNoteRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, &user, payload).await {
+        Ok(note) => ApiResponse::ok("Note updated successfully", note).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update note", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_note_versions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).versions(oid).await {
+        Ok(Some(versions)) => ApiResponse::ok("Note versions retrieved successfully", versions).into_response(),
+        Ok(None) => ErrorResponse::not_found("Note not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve note versions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Soft-delete; the body carries the required `reason`.
+pub async fn delete_note(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<DeleteNoteRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).delete(oid, &user, payload).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Note not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete note", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
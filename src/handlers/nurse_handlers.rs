@@ -6,7 +6,7 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::NurseService,
     repository::NurseRepository,
     dto::nurse::{CreateNurseRequest, UpdateNurseRequest},
@@ -18,7 +18,7 @@ pub async fn get_nurses(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = NurseRepository::new(state.db.clone());
+    let repo = NurseRepository::new(state.db_for(ReadContext::Replica));
     let service = NurseService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
@@ -1,35 +1,49 @@
 use axum::{
+    body::{Body, Bytes},
     extract::{Path, State, Query},
+    http::header,
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
-use mongodb::bson::oid::ObjectId;
+use futures_util::stream::StreamExt;
+use mongodb::bson::{doc, oid::ObjectId};
 use std::sync::Arc;
 use crate::{
-    db::AppState,
-    services::ObservationService,
-    repository::ObservationRepository,
-    dto::observation::{CreateObservationRequest, UpdateObservationRequest},
+    db::{AppState, ReadContext},
+    middleware::AuthUser,
+    rbac::{self, PermissionSet},
+    services::{AuditService, ObservationService},
+    repository::{AuditLogRepository, MedicalRecordRepository, ObservationRepository},
+    refs::ReferenceChecker,
+    dto::common::{View, ViewQuery},
+    dto::observation::{CreateObservationRequest, UpdateObservationRequest, TrendQuery, ObservationListQuery, ObservationExportQuery, VitalsBundleRequest},
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
 };
 
+fn build_service(state: &AppState, ctx: ReadContext) -> ObservationService {
+    let db = state.db_for(ctx);
+    ObservationService::new(ObservationRepository::new(db.clone()), MedicalRecordRepository::new(db.clone()), ReferenceChecker::new(db))
+}
+
 pub async fn get_observations(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
+    Query(filter): Query<ObservationListQuery>,
+    Query(view): Query<ViewQuery>,
 ) -> impl IntoResponse {
-    let repo = ObservationRepository::new(state.db.clone());
-    let service = ObservationService::new(repo);
-    
-    match service.get_observations(params.clone()).await {
-        Ok((observations, total)) => {
-            let meta = crate::pagination::PaginationMeta::new(
-                params.page,
-                params.limit,
-                total
-            );
-            PaginatedResponse::ok("Observations retrieved successfully", observations, meta).into_response()
-        },
+    let service = build_service(&state, ReadContext::Replica);
+    let id_pasien = filter.id_pasien.as_deref();
+    let meta = |total| crate::pagination::PaginationMeta::new(params.page, params.limit, total);
+
+    let result = match view.view {
+        View::Summary => service.get_summaries(params.clone(), id_pasien).await
+            .map(|(observations, total)| PaginatedResponse::ok("Observations retrieved successfully", observations, meta(total)).into_response()),
+        View::Full => service.get_observations(params.clone(), id_pasien).await
+            .map(|(observations, total)| PaginatedResponse::ok("Observations retrieved successfully", observations, meta(total)).into_response()),
+    };
+    match result {
+        Ok(response) => response,
         Err(e) => ErrorResponse::internal_error("Failed to retrieve observations", Some(e)).into_response(),
     }
 }
@@ -42,12 +56,11 @@ pub async fn create_observation(
         return e.into_response();
     }
 
-    let repo = ObservationRepository::new(state.db.clone());
-    let service = ObservationService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.create_observation(payload).await {
         Ok(observation) => ApiResponse::success(axum::http::StatusCode::CREATED, "Observation created successfully", observation).into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to create observation", Some(e)).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create observation", "CREATE_FAILED", Some(msg)).into_response(),
     }
 }
 
@@ -59,8 +72,7 @@ pub async fn get_observation(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = ObservationRepository::new(state.db.clone());
-    let service = ObservationService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
 
     match service.get_observation_by_id(&id).await {
         Ok(Some(observation)) => ApiResponse::ok("Observation retrieved successfully", observation).into_response(),
@@ -82,12 +94,11 @@ pub async fn update_observation(
         return e.into_response();
     }
 
-    let repo = ObservationRepository::new(state.db.clone());
-    let service = ObservationService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.update_observation(&id, payload).await {
         Ok(observation) => ApiResponse::ok("Observation updated successfully", observation).into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to update observation", Some(e)).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update observation", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
 
@@ -99,8 +110,7 @@ pub async fn delete_observation(
         return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
     };
 
-    let repo = ObservationRepository::new(state.db.clone());
-    let service = ObservationService::new(repo);
+    let service = build_service(&state, ReadContext::Primary);
     
     match service.delete_observation(&id).await {
         Ok(true) => no_content().into_response(),
@@ -108,3 +118,89 @@ pub async fn delete_observation(
         Err(e) => ErrorResponse::internal_error("Failed to delete observation", Some(e)).into_response(),
     }
 }
+
+pub async fn get_observation_trend(
+    State(state): State<Arc<AppState>>,
+    Path((patient_id, coding_code)): Path<(String, String)>,
+    Query(query): Query<TrendQuery>,
+) -> impl IntoResponse {
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.get_trend(&patient_id, &coding_code, query).await {
+        Ok(Some(trend)) => ApiResponse::ok("Observation trend retrieved successfully", trend).into_response(),
+        Ok(None) => ErrorResponse::not_found("No observations found for this patient and coding").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to compute observation trend", Some(e)).into_response(),
+    }
+}
+
+/// Stream matching observations as newline-delimited JSON, one `ObservationResponse` per line.
+/// Requires `observations:export`; each export is written to the audit log.
+pub async fn export_observations(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<ObservationExportQuery>,
+) -> impl IntoResponse {
+    let (resource, action) = rbac::OBSERVATIONS_EXPORT;
+    let permissions = match &user.service {
+        Some(service) => PermissionSet::from_scopes(&service.scopes),
+        None => match rbac::load_permissions(&state.db, &user.id).await {
+            Ok((_, permissions)) => permissions,
+            Err(e) => return ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
+        },
+    };
+    if !permissions.allows(resource, action) {
+        return ErrorResponse::forbidden(format!("Permission {}:{} required", resource, action)).into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
+    let observations = match service.export(&query).await {
+        Ok(observations) => observations,
+        Err(e) => return ErrorResponse::internal_error("Failed to export observations", Some(e)).into_response(),
+    };
+
+    AuditService::new(AuditLogRepository::new(state.db.clone()))
+        .record("export", "observations", "", &user.id, doc! {
+            "from": query.from,
+            "to": query.to,
+            "codingCodes": query.coding_codes(),
+            "idPasien": query.id_pasien.as_deref(),
+        })
+        .await;
+
+    let lines = observations.map(|observation| {
+        observation.map(|observation| {
+            let mut line = serde_json::to_vec(&observation).unwrap_or_default();
+            line.push(b'\n');
+            Bytes::from(line)
+        })
+    });
+
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"observations.ndjson\""),
+        ],
+        Body::from_stream(lines),
+    ).into_response()
+}
+
+/// Record a bundle of vital signs for a medical record as individual observations
+pub async fn record_vitals(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<VitalsBundleRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).record_vitals(oid, &user.id, payload).await {
+        Ok(observations) => ApiResponse::created("Vital signs recorded successfully", observations).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to record vital signs", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
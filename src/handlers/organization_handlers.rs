@@ -0,0 +1,152 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::{OrganizationService, UserRoleService},
+    repository::{OrganizationRepository, UserRoleRepository},
+    dto::organization::{CreateOrganizationRequest, UpdateOrganizationRequest},
+    dto::user_role::OrganizationMembersQuery,
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::PaginatedResponse,
+    events::DomainEvent,
+    delete_policy::DeleteGuard,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState) -> OrganizationService {
+    OrganizationService::new(
+        OrganizationRepository::new(state.db.clone()),
+        DeleteGuard::from_state(state),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+pub async fn get_organizations(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_service(&state).list().await {
+        Ok(organizations) => ApiResponse::ok("Organizations retrieved successfully", organizations).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve organizations", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_organization(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateOrganizationRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).create(payload).await {
+        Ok(organization) => {
+            state.events.publish(DomainEvent::created("organizations", &organization.id));
+            ApiResponse::created("Organization created successfully", organization).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create organization", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_organization(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).get(oid).await {
+        Ok(Some(organization)) => ApiResponse::ok("Organization retrieved successfully", organization).into_response(),
+        Ok(None) => ErrorResponse::not_found("Organization not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve organization", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_organization(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateOrganizationRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).update(oid, payload).await {
+        Ok(Some(organization)) => {
+            state.events.publish(DomainEvent::updated("organizations", &organization.id));
+            ApiResponse::ok("Organization updated successfully", organization).into_response()
+        }
+        Ok(None) => ErrorResponse::not_found("Organization not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update organization", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_organization(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).delete(oid).await {
+        Ok(true) => {
+            state.events.publish(DomainEvent::deleted("organizations", &id));
+            no_content().into_response()
+        }
+        Ok(false) => ErrorResponse::not_found("Organization not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete organization", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Users holding roles in the organization with their roles there; for admins and the
+/// organization's own members
+pub async fn get_organization_members(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Query(params): Query<PaginationParams>,
+    Query(query): Query<OrganizationMembersQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let (resource, action) = crate::rbac::ADMIN_ACCESS;
+    let allowed = match crate::rbac::load_permissions(&state.db, &user.id).await {
+        Ok((_, permissions)) if permissions.allows(resource, action) => Ok(true),
+        Ok(_) => UserRoleRepository::new(state.db.clone())
+            .find_active_organization_ids(&user.id)
+            .await
+            .map(|organizations| organizations.contains(&id))
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
+    match allowed {
+        Ok(true) => {}
+        Ok(false) => return ErrorResponse::forbidden("Only administrators and members of the organization can list its members").into_response(),
+        Err(e) => return ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
+    }
+
+    match OrganizationRepository::new(state.db.clone()).find_by_id(oid).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return ErrorResponse::not_found("Organization not found").into_response(),
+        Err(e) => return ErrorResponse::internal_error("Failed to retrieve organization", Some(e)).into_response(),
+    }
+
+    let service = UserRoleService::new(UserRoleRepository::new(state.db.clone()));
+    match service.members(&id, query.include_inactive, PaginationParams::new(params.page, params.limit)).await {
+        Ok((members, meta)) => PaginatedResponse::ok("Organization members retrieved successfully", members, meta).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to retrieve organization members", Some(e)).into_response(),
+    }
+}
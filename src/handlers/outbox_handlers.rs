@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::OutboxService,
+    repository::OutboxRepository,
+    dto::outbox::OutboxQuery,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> OutboxService {
+    OutboxService::new(OutboxRepository::new(state.db_for(ctx)))
+}
+
+/// Outbox entries, newest first; `?stuck=true` lists those needing attention.
+pub async fn get_outbox_entries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<OutboxQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).list(query, params).await {
+        Ok((entries, meta)) => PaginatedResponse::ok("Outbox entries retrieved successfully", entries, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve outbox entries", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_outbox_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(entry) => ApiResponse::ok("Outbox entry retrieved successfully", entry).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve outbox entry", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Make a failed or backed-off entry due again
+pub async fn retry_outbox_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).retry(oid).await {
+        Ok(entry) => ApiResponse::ok("Outbox entry queued for delivery", entry).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retry outbox entry", "RETRY_FAILED", Some(msg)).into_response(),
+    }
+}
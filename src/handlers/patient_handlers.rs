@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, State, Query},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    events::DomainEvent,
+    middleware::AuthUser,
+    delete_policy::DeleteGuard,
+    services::{PatientService, PatientReferences, AuditService, TimelineService},
+    repository::{MedicalRecordRepository, AppointmentRepository, ObservationRepository, AuditLogRepository, AdmissionRepository, FileRepository},
+    dto::patient::{DuplicateQuery, MergePatientRequest, GrowthQuery, TimelineQuery},
+    growth::GrowthMetric,
+    response::{ApiResponse, ErrorResponse},
+};
+
+fn build_service(state: &AppState, context: ReadContext) -> PatientService {
+    let db = state.db_for(context);
+    PatientService::new(
+        MedicalRecordRepository::new(db.clone()),
+        ObservationRepository::new(db.clone()),
+        PatientReferences::new(db),
+        DeleteGuard::from_state(state),
+        AuditService::new(AuditLogRepository::new(state.db.clone())),
+    )
+}
+
+pub async fn get_duplicate_patients(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DuplicateQuery>,
+) -> impl IntoResponse {
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.find_duplicates(query.threshold, query.limit).await {
+        Ok(groups) => ApiResponse::ok("Duplicate candidates retrieved successfully", groups).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to find duplicate patients", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_patients_by_phone(
+    State(state): State<Arc<AppState>>,
+    Path(phone): Path<String>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).find_by_phone(&phone).await {
+        Ok(records) => ApiResponse::ok("Patients retrieved successfully", records).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve patients", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn merge_patients(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<MergePatientRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.merge(oid, payload.duplicate_ids, &user.id).await {
+        Ok(result) => {
+            for merged_id in &result.merged_ids {
+                state.events.publish(DomainEvent::deleted("medical_records", merged_id));
+            }
+            state.events.publish(DomainEvent::updated("medical_records", &id));
+            ApiResponse::ok("Patients merged successfully", result).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to merge patients", "MERGE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_patient_growth(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<GrowthQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    let Some(metric) = GrowthMetric::parse(&query.metric) else {
+        return ErrorResponse::bad_request("Invalid metric", Some("metric must be 'weight' or 'height'".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.growth(oid, metric).await {
+        Ok(growth) => ApiResponse::ok("Growth chart retrieved successfully", growth).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to compute growth chart", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_patient_timeline(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<TimelineQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    let db = state.db_for(ReadContext::Replica);
+    let service = TimelineService::new(
+        MedicalRecordRepository::new(db.clone()),
+        AdmissionRepository::new(db.clone()),
+        ObservationRepository::new(db.clone()),
+        FileRepository::new(db.clone()),
+        AppointmentRepository::new(db.clone()),
+    );
+
+    match service.timeline(oid, query).await {
+        Ok(timeline) => ApiResponse::ok("Timeline retrieved successfully", timeline).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to fetch timeline", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::PatientRelationshipService,
+    repository::{MedicalRecordRepository, PatientRelationshipRepository},
+    refs::ReferenceChecker,
+    dto::patient::{CreatePatientRelationshipRequest, UpdatePatientRelationshipRequest},
+    middleware::AuthUser,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+pub(crate) fn build_service(state: &AppState, ctx: ReadContext) -> PatientRelationshipService {
+    let db = state.db_for(ctx);
+    PatientRelationshipService::new(
+        PatientRelationshipRepository::new(db.clone()),
+        MedicalRecordRepository::new(db.clone()),
+        ReferenceChecker::new(db),
+    )
+}
+
+pub async fn get_relationships(
+    State(state): State<Arc<AppState>>,
+    Path(patient_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(patient) = ObjectId::parse_str(&patient_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).list(patient).await {
+        Ok(relationships) => ApiResponse::ok("Relationships retrieved successfully", relationships).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve relationships", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_relationship(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(patient_id): Path<String>,
+    Json(payload): Json<CreatePatientRelationshipRequest>,
+) -> impl IntoResponse {
+    let Ok(patient) = ObjectId::parse_str(&patient_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(patient, &user, payload).await {
+        Ok(relationship) => ApiResponse::created("Relationship recorded successfully", relationship).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to record relationship", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_relationship(
+    State(state): State<Arc<AppState>>,
+    Path((patient_id, id)): Path<(String, String)>,
+    Json(payload): Json<UpdatePatientRelationshipRequest>,
+) -> impl IntoResponse {
+    let (Ok(patient), Ok(oid)) = (ObjectId::parse_str(&patient_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(patient, oid, payload).await {
+        Ok(relationship) => ApiResponse::ok("Relationship updated successfully", relationship).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update relationship", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_relationship(
+    State(state): State<Arc<AppState>>,
+    Path((patient_id, id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let (Ok(patient), Ok(oid)) = (ObjectId::parse_str(&patient_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(patient, oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Relationship not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete relationship", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,249 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::{GatewayService, PaymentService},
+    repository::{CashierShiftRepository, GatewayTransactionRepository, InvoiceRepository, OutboxRepository, PaymentRepository, UserRoleRepository},
+    dto::payment::{CloseShiftRequest, OpenShiftRequest, RecordPaymentRequest, RevenueStatsQuery, SettlementQuery, ShiftQuery, UtilizationStatsQuery},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> PaymentService {
+    let db = state.db_for(ctx);
+    PaymentService::new(
+        InvoiceRepository::new(db.clone()),
+        PaymentRepository::new(db.clone()),
+        CashierShiftRepository::new(db),
+        OutboxRepository::new(state.db.clone()),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+fn build_gateway_service(state: &AppState) -> GatewayService {
+    GatewayService::new(
+        state.config.payment_gateway.gateway(),
+        GatewayTransactionRepository::new(state.db.clone()),
+        build_service(state, ReadContext::Primary),
+    )
+}
+
+/// Record a payment; the caller is the cashier and needs an open shift.
+pub async fn record_payment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(invoice_id): Path<String>,
+    Json(payload): Json<RecordPaymentRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&invoice_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).record(oid, payload, &user.id).await {
+        Ok(payment) => ApiResponse::created("Payment recorded successfully", payment).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to record payment", "PAYMENT_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_invoice_payments(
+    State(state): State<Arc<AppState>>,
+    Path(invoice_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&invoice_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).list_for_invoice(oid).await {
+        Ok(payments) => ApiResponse::ok("Payments retrieved successfully", payments).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve payments", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn open_shift(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<OpenShiftRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).open_shift(&user.id, payload).await {
+        Ok(shift) => ApiResponse::created("Shift opened successfully", shift).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to open shift", "SHIFT_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn close_shift(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<CloseShiftRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).close_shift(oid, payload).await {
+        Ok(shift) => ApiResponse::ok("Shift closed successfully", shift).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to close shift", "SHIFT_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_shifts(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ShiftQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).list_shifts(query, params).await {
+        Ok((shifts, meta)) => PaginatedResponse::ok("Shifts retrieved successfully", shifts, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve shifts", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_shift(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get_shift(oid).await {
+        Ok(Some(shift)) => ApiResponse::ok("Shift retrieved successfully", shift).into_response(),
+        Ok(None) => ErrorResponse::not_found("Shift not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve shift", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_settlement(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SettlementQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).settlement(query).await {
+        Ok(report) => ApiResponse::ok("Daily settlement retrieved successfully", report).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve daily settlement", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Organizations whose statistics the caller may see: `requested` when they hold a role in it,
+/// else all of theirs. Admins see any organization, and all of them when none is requested.
+async fn stats_scope(state: &AppState, user: &AuthUser, requested: Option<&str>) -> Result<Option<Vec<String>>, ErrorResponse> {
+    let requested = requested.map(str::trim).filter(|id| !id.is_empty());
+    let (resource, action) = crate::rbac::ADMIN_ACCESS;
+    let permissions = crate::rbac::load_permissions(&state.db, &user.id).await
+        .map_err(|e| ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)))?
+        .1;
+    if permissions.allows(resource, action) {
+        return Ok(requested.map(|id| vec![id.to_string()]));
+    }
+
+    let organizations = UserRoleRepository::new(state.db.clone()).find_active_organization_ids(&user.id).await
+        .map_err(|e| ErrorResponse::internal_error("Failed to load organizations", Some(e.to_string())))?;
+    match requested {
+        Some(id) if organizations.iter().any(|o| o == id) => Ok(Some(vec![id.to_string()])),
+        Some(_) => Err(ErrorResponse::forbidden("You do not belong to that organization")),
+        None => Ok(Some(organizations)),
+    }
+}
+
+pub async fn get_revenue_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<RevenueStatsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let organizations = match stats_scope(&state, &user, query.organization_id.as_deref()).await {
+        Ok(organizations) => organizations,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_service(&state, ReadContext::Replica).revenue_stats(query, organizations).await {
+        Ok(stats) => ApiResponse::ok("Revenue statistics retrieved successfully", stats).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve revenue statistics", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_service_utilization(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<UtilizationStatsQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let organizations = match stats_scope(&state, &user, query.organization_id.as_deref()).await {
+        Ok(organizations) => organizations,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_service(&state, ReadContext::Replica).service_utilization(query, organizations).await {
+        Ok(stats) => ApiResponse::ok("Service utilization retrieved successfully", stats).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve service utilization", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_payment_link(
+    State(state): State<Arc<AppState>>,
+    Path(invoice_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&invoice_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_gateway_service(&state).create_link(oid).await {
+        Ok(link) => ApiResponse::created("Payment link created successfully", link).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create payment link", "PAYMENT_LINK_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_payment_links(
+    State(state): State<Arc<AppState>>,
+    Path(invoice_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&invoice_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_gateway_service(&state).links(oid).await {
+        Ok(links) => ApiResponse::ok("Payment links retrieved successfully", links).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve payment links", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Unauthenticated: the configured gateway's signature or callback token is checked instead.
+pub async fn payment_callback(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> impl IntoResponse {
+    match build_gateway_service(&state).handle_callback(&headers, &body).await {
+        Ok(result) => ApiResponse::ok("Callback processed", result).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to process payment callback", "CALLBACK_FAILED", Some(msg)).into_response(),
+    }
+}
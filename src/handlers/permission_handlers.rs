@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::PermissionService,
+    repository::{PermissionRepository, RolePermissionRepository},
+    dto::permission::{
+        CreatePermissionRequest, GrantPermissionRequest, PermissionMatrixResponse, RolePermissionQuery, UpdatePermissionRequest,
+    },
+    middleware::AuthUser,
+    rbac,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> PermissionService {
+    let db = state.db_for(ctx);
+    PermissionService::new(PermissionRepository::new(db.clone()), RolePermissionRepository::new(db))
+}
+
+/// Permission matrix of the caller's active roles
+pub async fn get_my_permissions(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+) -> impl IntoResponse {
+    match rbac::load_permissions(&state.db, &user.id).await {
+        Ok((roles, permissions)) => {
+            let matrix = PermissionMatrixResponse { roles, permissions: permissions.matrix() };
+            ApiResponse::ok("Permissions retrieved successfully", matrix).into_response()
+        }
+        Err(e) => ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_permissions(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list().await {
+        Ok(permissions) => ApiResponse::ok("Permissions retrieved successfully", permissions).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve permissions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_permission(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreatePermissionRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload).await {
+        Ok(permission) => ApiResponse::created("Permission created successfully", permission).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create permission", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_permission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(Some(permission)) => ApiResponse::ok("Permission retrieved successfully", permission).into_response(),
+        Ok(None) => ErrorResponse::not_found("Permission not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve permission", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_permission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePermissionRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, payload).await {
+        Ok(Some(permission)) => ApiResponse::ok("Permission updated successfully", permission).into_response(),
+        Ok(None) => ErrorResponse::not_found("Permission not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update permission", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_permission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Permission not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete permission", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_role_permissions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RolePermissionQuery>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list_grants(query.role_code.as_deref()).await {
+        Ok(grants) => ApiResponse::ok("Role permissions retrieved successfully", grants).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve role permissions", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn grant_permission(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<GrantPermissionRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).grant(payload).await {
+        Ok(grant) => ApiResponse::created("Permission granted successfully", grant).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to grant permission", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn revoke_permission(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).revoke(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Role permission not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to revoke permission", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
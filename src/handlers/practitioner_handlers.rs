@@ -0,0 +1,128 @@
+use axum::{
+    extract::{Path, State, Query},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    events::DomainEvent,
+    services::PractitionerService,
+    repository::PractitionerRepository,
+    dto::practitioner::{CreatePractitionerRequest, PractitionerListQuery, UpdatePractitionerRequest},
+    response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
+    pagination::PaginationParams,
+    delete_policy::DeleteGuard,
+    status::PractitionerType,
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> PractitionerService {
+    PractitionerService::new(PractitionerRepository::new(state.db_for(ctx)), DeleteGuard::from_state(state))
+}
+
+/// Events keep the collection names of the `/doctors` and `/nurses` views, which search
+/// indexing and webhooks subscribe to
+fn event_collection(practitioner_type: &PractitionerType) -> &'static str {
+    match practitioner_type {
+        PractitionerType::Doctor => "doctors",
+        _ => "nurses",
+    }
+}
+
+pub async fn get_practitioners(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+    Query(filter): Query<PractitionerListQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&filter) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Replica);
+
+    match service.get_all_paginated(params, filter.practitioner_type.as_ref(), filter.status.as_ref()).await {
+        Ok((practitioners, meta)) => PaginatedResponse::ok("Practitioners retrieved successfully", practitioners, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve practitioners", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_practitioner(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreatePractitionerRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.create(payload).await {
+        Ok(practitioner) => {
+            state.events.publish(DomainEvent::created(event_collection(&practitioner.practitioner_type), &practitioner.id));
+            ApiResponse::created("Practitioner created successfully", practitioner).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create practitioner", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_practitioner(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.get_by_id(oid).await {
+        Ok(Some(practitioner)) => ApiResponse::ok("Practitioner retrieved successfully", practitioner).into_response(),
+        Ok(None) => ErrorResponse::not_found("Practitioner not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve practitioner", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_practitioner(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePractitionerRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.update(oid, payload).await {
+        Ok(practitioner) => {
+            state.events.publish(DomainEvent::updated(event_collection(&practitioner.practitioner_type), &id));
+            ApiResponse::ok("Practitioner updated successfully", practitioner).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update practitioner", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_practitioner(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    let service = build_service(&state, ReadContext::Primary);
+
+    match service.delete(oid).await {
+        Ok(Some(practitioner_type)) => {
+            state.events.publish(DomainEvent::deleted(event_collection(&practitioner_type), &id));
+            no_content().into_response()
+        }
+        Ok(None) => ErrorResponse::not_found("Practitioner not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete practitioner", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
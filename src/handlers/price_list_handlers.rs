@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::PriceListService,
+    repository::PriceListRepository,
+    refs::ReferenceChecker,
+    dto::price_list::{ActivePriceListQuery, CreatePriceListRequest, PriceListQuery, QuoteRequest, UpdatePriceListRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> PriceListService {
+    let db = state.db_for(ctx);
+    PriceListService::new(
+        PriceListRepository::new(db.clone()),
+        ReferenceChecker::new(db),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+pub async fn get_price_lists(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PriceListQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((lists, meta)) => PaginatedResponse::ok("Price lists retrieved successfully", lists, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve price lists", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_price_list(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreatePriceListRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload, &user.id).await {
+        Ok(list) => ApiResponse::created("Price list draft created successfully", list).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create price list", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_price_list(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).get(oid).await {
+        Ok(Some(list)) => ApiResponse::ok("Price list retrieved successfully", list).into_response(),
+        Ok(None) => ErrorResponse::not_found("Price list not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve price list", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_price_list(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdatePriceListRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, payload).await {
+        Ok(list) => ApiResponse::ok("Price list updated successfully", list).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update price list", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_price_list(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Price list not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete price list", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn publish_price_list(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).publish(oid, &user.id).await {
+        Ok(list) => ApiResponse::ok("Price list published successfully", list).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to publish price list", "PUBLISH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_active_price_list(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ActivePriceListQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).active_response(&query.organization_id, query.date.as_deref()).await {
+        Ok(list) => ApiResponse::ok("Active price list retrieved successfully", list).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve active price list", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn quote_prices(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<QuoteRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).quote(payload).await {
+        Ok(quote) => ApiResponse::ok("Prices resolved successfully", quote).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to resolve prices", "PRICING_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,199 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::ProgramService,
+    repository::{ObservationRepository, ProgramRepository},
+    refs::ReferenceChecker,
+    dto::program::{CreateProgramRequest, EnrollPatientRequest, EnrollmentQuery, ProgramQuery, UpdateProgramRequest, WithdrawEnrollmentRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> ProgramService {
+    let db = state.db_for(ctx);
+    ProgramService::new(
+        ProgramRepository::new(db.clone()),
+        ObservationRepository::new(db.clone()),
+        ReferenceChecker::new(db),
+    )
+}
+
+pub async fn get_programs(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ProgramQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((programs, meta)) => PaginatedResponse::ok("Programs retrieved successfully", programs, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve programs", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_program(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateProgramRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload, &user.id).await {
+        Ok(program) => ApiResponse::created("Program created successfully", program).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create program", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_program(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).get(oid).await {
+        Ok(Some(program)) => ApiResponse::ok("Program retrieved successfully", program).into_response(),
+        Ok(None) => ErrorResponse::not_found("Program not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve program", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_program(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateProgramRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, payload).await {
+        Ok(program) => ApiResponse::ok("Program updated successfully", program).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update program", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_program(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Program not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete program", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn check_program_eligibility(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<EnrollPatientRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).eligibility(oid, &payload).await {
+        Ok(result) if result.eligible => ApiResponse::ok("The patient is eligible", result).into_response(),
+        Ok(result) => ApiResponse::ok("The patient is not eligible", result).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to check eligibility", "CHECK_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_program_enrollments(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<EnrollmentQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).enrollments(oid, query).await {
+        Ok(enrollments) => ApiResponse::ok("Enrollments retrieved successfully", enrollments).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve enrollments", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Enroll a patient meeting one of the program's eligibility rules; 422 otherwise
+pub async fn enroll_patient(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<EnrollPatientRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).enroll(oid, payload, &user.id).await {
+        Ok(enrollment) => ApiResponse::created("Patient enrolled successfully", enrollment).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to enroll patient", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn withdraw_enrollment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((program_id, id)): Path<(String, String)>,
+    Json(payload): Json<WithdrawEnrollmentRequest>,
+) -> impl IntoResponse {
+    let (Ok(program), Ok(oid)) = (ObjectId::parse_str(&program_id), ObjectId::parse_str(&id)) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).withdraw(program, oid, payload, &user.id).await {
+        Ok(enrollment) => ApiResponse::ok("Enrollment withdrawn successfully", enrollment).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to withdraw enrollment", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_program_cohort(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).cohort(oid).await {
+        Ok(report) => ApiResponse::ok("Cohort report generated successfully", report).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to generate cohort report", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
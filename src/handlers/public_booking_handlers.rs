@@ -0,0 +1,116 @@
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use crate::{
+    captcha::DisabledVerifier,
+    db::{AppState, ReadContext},
+    dto::appointment::{PublicBookingRequest, PublicSlotsQuery},
+    handlers::appointment_handlers,
+    pagination::PaginationParams,
+    repository::{DoctorRepository, MedicalRecordRepository, OtpRepository},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    services::{OtpService, PublicBookingService},
+    services::public_booking_service::PublicBookingOutcome,
+    events::DomainEvent,
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> PublicBookingService {
+    let db = state.db_for(ctx);
+    PublicBookingService::new(
+        DoctorRepository::new(db.clone()),
+        MedicalRecordRepository::new(db),
+        appointment_handlers::build_service(state, ctx),
+        OtpService::new(OtpRepository::new(state.db.clone()), &state.config.otp),
+        // init_db refuses to start with a verifier that cannot be built
+        state.config.captcha.verifier().unwrap_or_else(|_| Box::new(DisabledVerifier)),
+    )
+}
+
+fn error_code(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        400 | 422 => "VALIDATION_ERROR",
+        401 => "INVALID_OTP",
+        403 => "CAPTCHA_FAILED",
+        404 => "NOT_FOUND",
+        409 => "SLOT_UNAVAILABLE",
+        429 => "OTP_RATE_LIMITED",
+        502 => "VERIFICATION_UNAVAILABLE",
+        _ => "BOOKING_FAILED",
+    }
+}
+
+/// Active doctors, with name and specialization only
+pub async fn get_public_doctors(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).doctors(params).await {
+        Ok((doctors, meta)) => PaginatedResponse::ok("Doctors retrieved successfully", doctors, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve doctors", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_public_slots(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<PublicSlotsQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).slots(oid, query).await {
+        Ok(slots) => ApiResponse::ok("Slots retrieved successfully", slots).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve slots", error_code(status), Some(msg)).into_response(),
+    }
+}
+
+/// Book an appointment without an account
+///
+/// POST /public/appointments
+///
+/// Request body:
+/// ```json
+/// {
+///     "nik": "3201011505900001",
+///     "phone": "081234567890",
+///     "doctor_id": "65f0c1e2a4b5c6d7e8f90123",
+///     "date": "2026-03-12",
+///     "time": "09:30",
+///     "captcha_token": "10000000-aaaa-bbbb-cccc-000000000001"
+/// }
+/// ```
+///
+/// Sends a code to the phone; the same body with `"otp"` then books the slot as `pending_confirmation`.
+pub async fn create_public_appointment(
+    State(state): State<Arc<AppState>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<PublicBookingRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    // Passed on to the captcha provider; behind a proxy it follows SELF_REGISTRATION_TRUST_FORWARDED_FOR
+    let client = state.config.self_registration.client_key(peer.map(|ConnectInfo(addr)| addr), &headers);
+    let remote_ip = (client != "unknown").then_some(client.as_str());
+    match build_service(&state, ReadContext::Primary).book(payload, remote_ip).await {
+        Ok(PublicBookingOutcome::CodeSent(response)) => ApiResponse::ok("Booking code requested", response).into_response(),
+        Ok(PublicBookingOutcome::Booked(booking)) => {
+            state.events.publish(DomainEvent::created("appointments", &booking.appointment_id));
+            ApiResponse::created("Appointment requested; the clinic will confirm it", booking).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Booking failed", error_code(status), Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,75 @@
+use axum::{
+    extract::{Path, State},
+    response::{sse::{Event, KeepAlive, Sse}, IntoResponse},
+};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use crate::{
+    db::{AppState, ReadContext},
+    services::AppointmentService,
+    events::DomainEvent,
+    response::{ApiResponse, ErrorResponse},
+};
+
+/// Event collection for queue changes; the event id is the doctor id.
+pub const QUEUE_EVENTS: &str = "queues";
+
+fn build_service(state: &AppState) -> AppointmentService {
+    // Queue state is read from the primary so the board reflects a call the moment it is made
+    crate::handlers::appointment_handlers::build_service(state, ReadContext::Primary)
+}
+
+pub async fn call_next_patient(
+    State(state): State<Arc<AppState>>,
+    Path(doctor_id): Path<String>,
+) -> impl IntoResponse {
+    match build_service(&state).call_next(&doctor_id).await {
+        Ok(entry) => {
+            state.events.publish(DomainEvent::updated("appointments", &entry.appointment_id));
+            state.events.publish(DomainEvent::updated(QUEUE_EVENTS, &doctor_id));
+            ApiResponse::ok("Next patient called successfully", entry).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to call next patient", "QUEUE_CALL_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_today_queue(
+    State(state): State<Arc<AppState>>,
+    Path(doctor_id): Path<String>,
+) -> impl IntoResponse {
+    match build_service(&state).queue_board(&doctor_id).await {
+        Ok(board) => ApiResponse::ok("Queue retrieved successfully", board).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve queue", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Server-sent events for waiting-room screens: the current board on connect, then a fresh
+/// `queue` event whenever a patient of this doctor checks in or is called.
+pub async fn stream_queue(
+    State(state): State<Arc<AppState>>,
+    Path(doctor_id): Path<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.events.subscribe();
+
+    let events = stream::unfold((state, doctor_id, receiver, true), |(state, doctor_id, mut receiver, first)| async move {
+        if !first {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) if event.collection == QUEUE_EVENTS && event.id == doctor_id => break,
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+
+        let event = match build_service(&state).queue_board(&doctor_id).await {
+            Ok(board) => Event::default().event("queue").json_data(board).unwrap_or_default(),
+            Err((_, msg)) => Event::default().event("error").data(msg),
+        };
+        Some((Ok(event), (state, doctor_id, receiver, false)))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
@@ -1,104 +1,55 @@
 use axum::{
     extract::{Path, State, Query},
     response::IntoResponse,
-    Json,
 };
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::RegionService,
     repository::RegionRepository,
+    models::Region,
     dto::region::{CreateRegionRequest, UpdateRegionRequest},
-    response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
-    pagination::PaginationParams,
+    handlers::crud::{CrudController, CrudService, PagedCrudService},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    pagination::{PaginationMeta, PaginationParams},
 };
 
-pub async fn get_regions(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<PaginationParams>,
-) -> impl IntoResponse {
-    let repo = Arc::new(RegionRepository::new(state.db.clone()));
-    let service = RegionService::new(repo);
-    
-    match service.get_all_paginated(params).await {
-        Ok((regions, meta)) => PaginatedResponse::ok("Regions retrieved successfully", regions, meta).into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to retrieve regions", Some(e)).into_response(),
-    }
-}
+/// `list`, `create`, `get`, `update` and `delete` for `/regions`
+pub type Regions = CrudController<RegionService>;
 
-pub async fn create_region(
-    State(state): State<Arc<AppState>>,
-    Json(payload): Json<CreateRegionRequest>,
-) -> impl IntoResponse {
-    if let Err(e) = crate::validation::validate_payload(&payload) {
-        return e.into_response();
-    }
-
-    let repo = Arc::new(RegionRepository::new(state.db.clone()));
-    let service = RegionService::new(repo);
-    
-    match service.create(payload).await {
-        Ok(region) => ApiResponse::success(axum::http::StatusCode::CREATED, "Region created successfully", region).into_response(),
-        Err(e) => ErrorResponse::bad_request("Failed to create region", Some(e)).into_response(),
-    }
-}
+impl CrudService for RegionService {
+    type Item = Region;
+    type Create = CreateRegionRequest;
+    type Update = UpdateRegionRequest;
 
-pub async fn get_region(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
+    const NAME: &'static str = "Region";
+    const PLURAL: &'static str = "Regions";
 
-    let repo = Arc::new(RegionRepository::new(state.db.clone()));
-    let service = RegionService::new(repo);
+    fn build(state: &AppState, context: ReadContext) -> Self {
+        RegionService::new(Arc::new(RegionRepository::new(state.db_for(context))))
+    }
 
-    match service.get_by_id(oid).await {
-        Ok(Some(region)) => ApiResponse::ok("Region retrieved successfully", region).into_response(),
-        Ok(None) => ErrorResponse::not_found("Region not found").into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to retrieve region", Some(e)).into_response(),
+    async fn create(&self, dto: CreateRegionRequest) -> Result<Region, String> {
+        RegionService::create(self, dto).await
     }
-}
 
-pub async fn update_region(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-    Json(payload): Json<UpdateRegionRequest>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
+    async fn get_by_id(&self, id: ObjectId) -> Result<Option<Region>, String> {
+        RegionService::get_by_id(self, id).await
+    }
 
-    if let Err(e) = crate::validation::validate_payload(&payload) {
-        return e.into_response();
+    async fn update(&self, id: ObjectId, dto: UpdateRegionRequest) -> Result<Region, String> {
+        RegionService::update(self, id, dto).await
     }
 
-    let repo = Arc::new(RegionRepository::new(state.db.clone()));
-    let service = RegionService::new(repo);
-    
-    match service.update(oid, payload).await {
-        Ok(region) => ApiResponse::ok("Region updated successfully", region).into_response(),
-        Err(e) => ErrorResponse::bad_request("Failed to update region", Some(e)).into_response(),
+    async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        RegionService::delete(self, id).await
     }
 }
 
-pub async fn delete_region(
-    State(state): State<Arc<AppState>>,
-    Path(id): Path<String>,
-) -> impl IntoResponse {
-    let Ok(oid) = ObjectId::parse_str(&id) else {
-        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
-    };
-
-    let repo = Arc::new(RegionRepository::new(state.db.clone()));
-    let service = RegionService::new(repo);
-    
-    match service.delete(oid).await {
-        Ok(true) => no_content().into_response(),
-        Ok(false) => ErrorResponse::not_found("Region not found").into_response(),
-        Err(e) => ErrorResponse::internal_error("Failed to delete region", Some(e)).into_response(),
+impl PagedCrudService for RegionService {
+    async fn get_all_paginated(&self, params: PaginationParams) -> Result<(Vec<Region>, PaginationMeta), String> {
+        RegionService::get_all_paginated(self, params).await
     }
 }
 
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    dto::report::{CreateReportScheduleRequest, ReportScheduleQuery, UpdateReportScheduleRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
+};
+
+fn invalid_id() -> axum::response::Response {
+    ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response()
+}
+
+pub async fn create_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateReportScheduleRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match crate::reports::build_service(&state).create(payload, &user.id).await {
+        Ok(schedule) => ApiResponse::created("Report schedule created successfully", schedule).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create report schedule", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_report_schedules(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReportScheduleQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match crate::reports::build_service(&state).list(query, params).await {
+        Ok((schedules, meta)) => PaginatedResponse::ok("Report schedules retrieved successfully", schedules, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve report schedules", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+
+    match crate::reports::build_service(&state).get(oid).await {
+        Ok(schedule) => ApiResponse::ok("Report schedule retrieved successfully", schedule).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve report schedule", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateReportScheduleRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match crate::reports::build_service(&state).update(oid, payload).await {
+        Ok(schedule) => ApiResponse::ok("Report schedule updated successfully", schedule).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update report schedule", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+
+    match crate::reports::build_service(&state).delete(oid).await {
+        Ok(()) => no_content().into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete report schedule", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Send the report now, outside its schedule; `nextRunAt` is left as is.
+pub async fn run_report_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+
+    match crate::reports::build_service(&state).run_now(oid).await {
+        Ok(run) => ApiResponse::ok("Report sent", run).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to run report", "RUN_FAILED", Some(msg)).into_response(),
+    }
+}
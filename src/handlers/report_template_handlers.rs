@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::ReportTemplateService,
+    repository::ReportTemplateRepository,
+    dto::report_template::{CreateReportTemplateRequest, RunReportRequest, UpdateReportTemplateRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> ReportTemplateService {
+    ReportTemplateService::new(ReportTemplateRepository::new(state.db_for(ctx)))
+}
+
+fn invalid_id() -> axum::response::Response {
+    ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response()
+}
+
+pub async fn create_report_template(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateReportTemplateRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(payload, &user.id).await {
+        Ok(template) => ApiResponse::created("Report template created successfully", template).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create report template", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_report_templates(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list(params).await {
+        Ok((templates, meta)) => PaginatedResponse::ok("Report templates retrieved successfully", templates, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve report templates", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+
+    match build_service(&state, ReadContext::Replica).get(oid).await {
+        Ok(template) => ApiResponse::ok("Report template retrieved successfully", template).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve report template", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateReportTemplateRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update(oid, payload).await {
+        Ok(template) => ApiResponse::ok("Report template updated successfully", template).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update report template", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_report_template(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return invalid_id();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(()) => no_content().into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete report template", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Run a template with parameters; `page` and `limit` select the rows returned.
+pub async fn run_report(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+    Json(payload): Json<RunReportRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).run(payload, params).await {
+        Ok(result) => ApiResponse::ok("Report generated successfully", result).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to run report", "REPORT_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,106 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::{ReviewService, review_service::REVIEW_PUBLISHED},
+    repository::{AppointmentRepository, DoctorRepository, MedicalRecordRepository, ReviewRepository},
+    dto::review::{CreateReviewRequest, ModerateReviewRequest, ReviewQuery},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    response::{ApiResponse, ErrorResponse, PaginatedResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> ReviewService {
+    let db = state.db_for(ctx);
+    ReviewService::new(
+        ReviewRepository::new(db.clone()),
+        AppointmentRepository::new(db.clone()),
+        MedicalRecordRepository::new(db.clone()),
+        DoctorRepository::new(db),
+    )
+}
+
+pub async fn create_review(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateReviewRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create(oid, &user, payload).await {
+        Ok(review) => ApiResponse::created("Review submitted successfully", review).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to submit review", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Published reviews of one doctor
+pub async fn get_doctor_reviews(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    let query = ReviewQuery { doctor_id: Some(id), status: Some(REVIEW_PUBLISHED.to_string()) };
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((reviews, meta)) => PaginatedResponse::ok("Reviews retrieved successfully", reviews, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve reviews", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_reviews_for_moderation(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReviewQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((reviews, meta)) => PaginatedResponse::ok("Reviews retrieved successfully", reviews, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve reviews", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn moderate_review(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<ModerateReviewRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).moderate(oid, &user, payload).await {
+        Ok(review) => ApiResponse::ok("Review moderated successfully", review).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to moderate review", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_review(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Review not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete review", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
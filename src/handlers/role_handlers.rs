@@ -6,7 +6,7 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::RoleService,
     repository::RoleRepository,
     dto::role::{CreateRoleRequest, UpdateRoleRequest},
@@ -18,7 +18,7 @@ pub async fn get_roles(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = RoleRepository::new(state.db.clone());
+    let repo = RoleRepository::new(state.db_for(ReadContext::Replica));
     let service = RoleService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
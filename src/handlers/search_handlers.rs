@@ -0,0 +1,88 @@
+use axum::{
+    extract::{Path, State, Query},
+    response::IntoResponse,
+    Extension,
+};
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    middleware::AuthUser,
+    rbac,
+    services::{LookupService, SearchService},
+    repository::{MedicalRecordRepository, MedicineRepository, SearchRepository},
+    dto::search::SearchQuery,
+    response::{ApiResponse, ErrorResponse},
+};
+#[cfg(feature = "kits")]
+use crate::repository::KitRepository;
+
+pub async fn global_search(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let q = query.q.trim();
+    if q.is_empty() {
+        return ErrorResponse::bad_request("Search query is required", Some("Provide a non-empty 'q' parameter".to_string())).into_response();
+    }
+
+    let types = match SearchService::parse_types(query.types.as_deref()) {
+        Ok(types) => types,
+        Err(e) => return ErrorResponse::bad_request("Invalid search types", Some(e)).into_response(),
+    };
+
+    let permissions = match rbac::load_permissions(&state.db, &user.id).await {
+        Ok((_, permissions)) => permissions,
+        Err(e) => return ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
+    };
+
+    let service = SearchService::new(SearchRepository::new(state.db_for(ReadContext::Replica)));
+    #[cfg(feature = "meilisearch")]
+    let service = service.with_meilisearch(state.meili.clone());
+
+    match service.search(q, &types, &permissions, query.limit).await {
+        Ok(results) => ApiResponse::ok("Search completed successfully", results).into_response(),
+        Err(e) => ErrorResponse::internal_error("Search failed", Some(e)).into_response(),
+    }
+}
+
+/// Resolve a scanned barcode or QR code
+///
+/// GET /lookup/barcode/:value
+///
+/// `RME:<nrme>` (patient cards) matches a patient; other values match medicine batches,
+/// kits by code and patients by medical record number. 404 when nothing matches.
+pub async fn lookup_barcode(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(value): Path<String>,
+) -> impl IntoResponse {
+    let value = value.trim();
+    if value.is_empty() || value.len() > 200 {
+        return ErrorResponse::bad_request("Invalid barcode", Some("The value must be between 1 and 200 characters".to_string())).into_response();
+    }
+
+    let permissions = match rbac::load_permissions(&state.db, &user.id).await {
+        Ok((_, permissions)) => permissions,
+        Err(e) => return ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
+    };
+
+    #[cfg(feature = "kits")]
+    let scope = match crate::handlers::kit_handlers::caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    let db = state.db_for(ReadContext::Replica);
+    let service = LookupService::new(
+        MedicineRepository::new(db.clone()),
+        MedicalRecordRepository::new(db.clone()),
+        #[cfg(feature = "kits")]
+        KitRepository::new(db).scoped(scope),
+    );
+    match service.lookup(value, &permissions).await {
+        Ok(result) if result.matches.is_empty() => ErrorResponse::not_found("No medicine, kit or patient matches this barcode").into_response(),
+        Ok(result) => ApiResponse::ok("Barcode resolved successfully", result).into_response(),
+        Err(e) => ErrorResponse::internal_error("Lookup failed", Some(e)).into_response(),
+    }
+}
@@ -0,0 +1,122 @@
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+use crate::{
+    db::AppState,
+    dto::patient::{RegistrationListQuery, SelfRegistrationRequest},
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    repository::{MedicalRecordRepository, OtpRepository},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    sequences::SequenceGenerator,
+    services::{OtpService, SelfRegistrationService},
+    services::self_registration_service::SelfRegistrationOutcome,
+    events::DomainEvent,
+    status::RegistrationStatus,
+};
+
+fn build_service(state: &AppState) -> SelfRegistrationService {
+    SelfRegistrationService::new(
+        MedicalRecordRepository::new(state.db.clone()),
+        SequenceGenerator::new(state.db.clone()),
+        OtpService::new(OtpRepository::new(state.db.clone()), &state.config.otp),
+    )
+}
+
+fn error_code(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        400 | 422 => "VALIDATION_ERROR",
+        401 => "INVALID_OTP",
+        409 => "ALREADY_REGISTERED",
+        429 => "OTP_RATE_LIMITED",
+        502 => "OTP_DELIVERY_FAILED",
+        _ => "REGISTRATION_FAILED",
+    }
+}
+
+/// Register a walk-in patient from a kiosk
+///
+/// POST /public/patients/register
+///
+/// Request body:
+/// ```json
+/// {
+///     "nik": "3201011505900001",
+///     "name": "Budi Santoso",
+///     "phone": "081234567890"
+/// }
+/// ```
+///
+/// Sends a code to the phone; the same body with `"otp"` then creates a pending record.
+pub async fn register_patient(
+    State(state): State<Arc<AppState>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+    Json(payload): Json<SelfRegistrationRequest>,
+) -> impl IntoResponse {
+    let client = state.config.self_registration.client_key(peer.map(|ConnectInfo(addr)| addr), &headers);
+    if let Err(retry_after) = state.registrations.check(&client, Instant::now()).await {
+        let mut response = ErrorResponse::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many registration attempts",
+            "RATE_LIMITED",
+            Some(format!("Try again in {} seconds", retry_after)),
+        ).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    }
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).register(payload).await {
+        Ok(SelfRegistrationOutcome::CodeSent(response)) => ApiResponse::ok("Registration code requested", response).into_response(),
+        Ok(SelfRegistrationOutcome::Registered(registration)) => {
+            state.events.publish(DomainEvent::created("medical_records", &registration.patient_id));
+            ApiResponse::created("Registration received; staff will confirm it at the front desk", registration).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Registration failed", error_code(status), Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_registrations(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<PaginationParams>,
+    Query(query): Query<RegistrationListQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    let status = query.status.unwrap_or(RegistrationStatus::Pending);
+    match build_service(&state).list(&status, params).await {
+        Ok((records, meta)) => PaginatedResponse::ok("Registrations retrieved successfully", records, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve registrations", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn confirm_registration(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).confirm(oid, &user).await {
+        Ok(record) => {
+            state.events.publish(DomainEvent::updated("medical_records", &id));
+            ApiResponse::ok("Registration confirmed successfully", record).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to confirm registration", "CONFIRM_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Form, Json,
+};
+use base64::Engine;
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::ServiceAccountService,
+    repository::ServiceAccountRepository,
+    dto::service_account::{CreateServiceAccountRequest, TokenErrorResponse, TokenRequest, UpdateServiceAccountRequest},
+    middleware::AuthUser,
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState) -> ServiceAccountService {
+    ServiceAccountService::new(ServiceAccountRepository::new(state.db.clone()))
+}
+
+fn token_error(status: StatusCode, error: &str, description: impl Into<String>) -> axum::response::Response {
+    let body = TokenErrorResponse { error: error.to_string(), error_description: description.into() };
+    (status, Json(body)).into_response()
+}
+
+/// Client id and secret from an HTTP Basic Authorization header
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers.get(header::AUTHORIZATION)?.to_str().ok()?.strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let (id, secret) = String::from_utf8(decoded).ok()?.split_once(':').map(|(i, s)| (i.to_string(), s.to_string()))?;
+    Some((id, secret))
+}
+
+/// OAuth2 client-credentials token endpoint. Responds with the plain RFC 6749 bodies rather
+/// than the API envelope so standard OAuth clients can use it.
+pub async fn issue_token(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(request): Form<TokenRequest>,
+) -> impl IntoResponse {
+    if request.grant_type != "client_credentials" {
+        return token_error(StatusCode::BAD_REQUEST, "unsupported_grant_type", "Only client_credentials is supported");
+    }
+
+    let credentials = basic_credentials(&headers)
+        .or_else(|| request.client_id.clone().zip(request.client_secret.clone()));
+    let Some((client_id, client_secret)) = credentials else {
+        return token_error(StatusCode::UNAUTHORIZED, "invalid_client", "Client credentials are required");
+    };
+
+    match build_service(&state).issue_token(&client_id, &client_secret, request.scope.as_deref()).await {
+        Ok(token) => Json(token).into_response(),
+        Err((StatusCode::UNAUTHORIZED, msg)) => token_error(StatusCode::UNAUTHORIZED, "invalid_client", msg),
+        Err((StatusCode::BAD_REQUEST, msg)) => token_error(StatusCode::BAD_REQUEST, "invalid_scope", msg),
+        Err((status, msg)) => token_error(status, "server_error", msg),
+    }
+}
+
+pub async fn get_service_accounts(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    match build_service(&state).list().await {
+        Ok(accounts) => ApiResponse::ok("Service accounts retrieved successfully", accounts).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve service accounts", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_service_account(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateServiceAccountRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).create(payload, &user.id).await {
+        Ok(credentials) => ApiResponse::created("Service account created successfully; store the client secret now", credentials).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create service account", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_service_account(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).get(oid).await {
+        Ok(Some(account)) => ApiResponse::ok("Service account retrieved successfully", account).into_response(),
+        Ok(None) => ErrorResponse::not_found("Service account not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve service account", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_service_account(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateServiceAccountRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state).update(oid, payload).await {
+        Ok(Some(account)) => ApiResponse::ok("Service account updated successfully", account).into_response(),
+        Ok(None) => ErrorResponse::not_found("Service account not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update service account", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn rotate_service_account_secret(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).rotate_secret(oid).await {
+        Ok(Some(credentials)) => ApiResponse::ok("Client secret rotated; tokens issued before now are revoked", credentials).into_response(),
+        Ok(None) => ErrorResponse::not_found("Service account not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to rotate client secret", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_service_account(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).delete(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Service account not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete service account", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
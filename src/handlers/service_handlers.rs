@@ -6,7 +6,7 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::ServiceService,
     repository::ServiceRepository,
     dto::service::{CreateServiceRequest, UpdateServiceRequest},
@@ -18,7 +18,7 @@ pub async fn get_services(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = ServiceRepository::new(state.db.clone());
+    let repo = ServiceRepository::new(state.db_for(ReadContext::Replica));
     let service = ServiceService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
@@ -0,0 +1,157 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    dto::stock_opname::{CreateStockOpnameRequest, StockOpnameQuery, SubmitStockCountsRequest},
+    events::DomainEvent,
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    repository::{MedicineRepository, StockOpnameRepository},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    services::StockOpnameService,
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> StockOpnameService {
+    let db = state.db_for(ctx);
+    StockOpnameService::new(StockOpnameRepository::new(db.clone()), MedicineRepository::new(db))
+}
+
+pub async fn get_stock_opnames(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StockOpnameQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Replica).list(query, params).await {
+        Ok((opnames, meta)) => PaginatedResponse::ok("Stock opnames retrieved successfully", opnames, meta).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve stock opnames", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Start a stock opname
+///
+/// POST /stock-opnames
+///
+/// Request body (both fields optional):
+/// ```json
+/// {
+///     "notes": "Year-end count",
+///     "master_medicine_ids": ["PCT500"]
+/// }
+/// ```
+pub async fn create_stock_opname(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<CreateStockOpnameRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).start(payload, &user.id).await {
+        Ok(opname) => ApiResponse::created("Stock opname started successfully", opname).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to start stock opname", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_stock_opname(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).get(oid).await {
+        Ok(opname) => ApiResponse::ok("Stock opname retrieved successfully", opname).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve stock opname", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Record counted quantities
+///
+/// POST /stock-opnames/:id/counts
+///
+/// Request body:
+/// ```json
+/// {
+///     "items": [{ "medicine_id": "65f0c1e2a4b5c6d7e8f90123", "counted_qty": 97, "note": "3 strips damaged" }]
+/// }
+/// ```
+pub async fn submit_stock_counts(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<SubmitStockCountsRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).submit_counts(oid, payload, &user.id).await {
+        Ok(opname) => ApiResponse::ok("Counts recorded successfully", opname).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to record counts", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn approve_stock_opname(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).approve(oid, &user.id).await {
+        Ok(opname) => {
+            state.events.publish(DomainEvent::updated("stock_opnames", &id));
+            for item in opname.items.iter().filter(|item| item.variance.is_some_and(|variance| variance != 0.0)) {
+                state.events.publish(DomainEvent::updated("medicines", &item.medicine_id));
+            }
+            ApiResponse::ok("Stock opname approved and stock adjusted", opname).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to approve stock opname", "APPROVE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn cancel_stock_opname(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).cancel(oid, &user.id).await {
+        Ok(opname) => ApiResponse::ok("Stock opname cancelled", opname).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to cancel stock opname", "CANCEL_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_stock_opname_report(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).report(oid).await {
+        Ok(report) => ApiResponse::ok("Stock opname report retrieved successfully", report).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve stock opname report", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension, Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    dto::sync::{ClinicalPullQuery, PushRequest, ReferenceSyncQuery, ResolveConflictRequest, SyncConflictQuery},
+    handlers::note_handlers,
+    middleware::AuthUser,
+    pagination::PaginationParams,
+    repository::{NoteRepository, SyncConflictRepository, SyncMutationRepository, SyncRepository, TombstoneRepository},
+    response::{ApiResponse, ErrorResponse, PaginatedResponse},
+    services::{ClinicalSyncService, SyncService},
+};
+
+/// Codes, interpretations and regions changed since `since`, for offline clients; see `crate::sync`
+pub async fn get_reference_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ReferenceSyncQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    // The primary: a lagging secondary could hand out a cursor past writes it has not seen yet
+    let service = SyncService::new(SyncRepository::new(state.db.clone()), TombstoneRepository::new(state.db.clone()));
+    match service.reference(query).await {
+        Ok(changes) => ApiResponse::ok("Reference data changes retrieved successfully", changes).into_response(),
+        Err((status, e)) => ErrorResponse::new(status, "Failed to retrieve reference data changes", "SYNC_FAILED", Some(e)).into_response(),
+    }
+}
+
+fn build_clinical_service(state: &AppState) -> ClinicalSyncService {
+    ClinicalSyncService::new(
+        note_handlers::build_service(state, ReadContext::Primary),
+        NoteRepository::new(state.db.clone()),
+        SyncMutationRepository::new(state.db.clone()),
+        SyncConflictRepository::new(state.db.clone()),
+    )
+}
+
+/// The caller's id, or `None` for admins, who see and resolve everyone's conflicts
+async fn conflict_owner(state: &AppState, user: &AuthUser) -> Result<Option<String>, ErrorResponse> {
+    let (resource, action) = crate::rbac::ADMIN_ACCESS;
+    let permissions = crate::rbac::load_permissions(&state.db, &user.id).await
+        .map_err(|e| ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)))?
+        .1;
+    Ok((!permissions.allows(resource, action)).then(|| user.id.clone()))
+}
+
+/// Applies notes created, edited and deleted offline; every mutation gets its own result
+pub async fn push_clinical_changes(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(payload): Json<PushRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let total = payload.mutations.len();
+    let response = build_clinical_service(&state).push(&user, payload).await;
+    let message = format!("{} of {} mutations applied", response.applied, total);
+    ApiResponse::ok(message, response).into_response()
+}
+
+/// Notes of the given medical records changed since `since`
+pub async fn pull_clinical_changes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ClinicalPullQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+
+    match build_clinical_service(&state).pull(query).await {
+        Ok(changes) => ApiResponse::ok("Clinical changes retrieved successfully", changes).into_response(),
+        Err((status, e)) => ErrorResponse::new(status, "Failed to retrieve clinical changes", "SYNC_FAILED", Some(e)).into_response(),
+    }
+}
+
+pub async fn get_sync_conflicts(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<SyncConflictQuery>,
+    Query(params): Query<PaginationParams>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let owner = match conflict_owner(&state, &user).await {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_clinical_service(&state).conflicts(owner.as_deref(), query, params).await {
+        Ok((conflicts, meta)) => PaginatedResponse::ok("Sync conflicts retrieved successfully", conflicts, meta).into_response(),
+        Err((status, e)) => ErrorResponse::new(status, "Failed to retrieve sync conflicts", "FETCH_FAILED", Some(e)).into_response(),
+    }
+}
+
+pub async fn resolve_sync_conflict(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<ResolveConflictRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+    let owner = match conflict_owner(&state, &user).await {
+        Ok(owner) => owner,
+        Err(e) => return e.into_response(),
+    };
+
+    match build_clinical_service(&state).resolve(oid, &user, owner.as_deref(), payload).await {
+        Ok(conflict) => ApiResponse::ok("Sync conflict resolved successfully", conflict).into_response(),
+        Err((status, e)) => ErrorResponse::new(status, "Failed to resolve sync conflict", "RESOLVE_FAILED", Some(e)).into_response(),
+    }
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::AppState,
+    services::TeleconsultService,
+    repository::{AppointmentRepository, TeleconsultRepository},
+    events::DomainEvent,
+    response::{ApiResponse, ErrorResponse},
+};
+
+pub(crate) fn build_service(state: &AppState) -> TeleconsultService {
+    TeleconsultService::new(
+        TeleconsultRepository::new(state.db.clone()),
+        AppointmentRepository::new(state.db.clone()),
+        state.config.teleconsult.provider(),
+    )
+}
+
+pub async fn get_session(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).ensure_session(oid).await {
+        Ok(session) => ApiResponse::ok("Teleconsult session retrieved successfully", session).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve teleconsult session", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn start_session(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).start(oid).await {
+        Ok(session) => {
+            state.events.publish(DomainEvent::updated("appointments", &id));
+            ApiResponse::ok("Teleconsult session started successfully", session).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to start teleconsult session", "START_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn end_session(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state).end(oid).await {
+        Ok(session) => {
+            state.events.publish(DomainEvent::updated("appointments", &id));
+            ApiResponse::ok("Teleconsult session ended successfully", session).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to end teleconsult session", "END_FAILED", Some(msg)).into_response(),
+    }
+}
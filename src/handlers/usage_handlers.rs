@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Extension,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    distributor::KitScope,
+    handlers::kit_handlers::caller_scope,
+    middleware::AuthUser,
+    services::{DistributorService, UsageService},
+    repository::{KitRepository, ObservationRepository},
+    dto::kit::DistributorSummaryQuery,
+    dto::usage::UsageQuery,
+    response::{ApiResponse, ErrorResponse},
+};
+
+fn build_service(state: &AppState, scope: KitScope) -> UsageService {
+    let db = state.db_for(ReadContext::Replica);
+    UsageService::new(ObservationRepository::new(db.clone()), KitRepository::new(db).scoped(scope))
+}
+
+pub async fn get_kit_usage(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    match build_service(&state, scope).kit_usage(oid, query).await {
+        Ok(report) => ApiResponse::ok("Kit usage retrieved successfully", report).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve kit usage", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_operator_activity(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(nik): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> impl IntoResponse {
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    match build_service(&state, scope).operator_activity(&nik, query).await {
+        Ok(report) => ApiResponse::ok("Operator activity retrieved successfully", report).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve operator activity", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Kits, monthly observations and top operators of a distributor; distributors only see their own
+pub async fn get_distributor_summary(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(code): Path<String>,
+    Query(query): Query<DistributorSummaryQuery>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&query) {
+        return e.into_response();
+    }
+    let scope = match caller_scope(&state, &user).await {
+        Ok(scope) => scope,
+        Err(response) => return response,
+    };
+
+    let db = state.db_for(ReadContext::Replica);
+    let service = DistributorService::new(KitRepository::new(db.clone()).scoped(scope), ObservationRepository::new(db));
+    match service.summary(code.trim(), query).await {
+        Ok(summary) => ApiResponse::ok("Distributor summary retrieved successfully", summary).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve distributor summary", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -7,20 +7,21 @@ use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     dto::auth::RegisterRequest,
     dto::user::UpdateUserRequest,
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     repository::UserRepository,
     services::UserService,
     pagination::PaginationParams,
+    events::DomainEvent,
 };
 
 pub async fn get_users(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = UserRepository::new(state.db.clone());
+    let repo = UserRepository::new(state.db_for(ReadContext::Replica));
     let service = UserService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
@@ -29,6 +30,18 @@ pub async fn get_users(
     }
 }
 
+pub async fn get_users_by_phone(
+    State(state): State<Arc<AppState>>,
+    Path(phone): Path<String>,
+) -> impl IntoResponse {
+    let service = UserService::new(UserRepository::new(state.db_for(ReadContext::Replica)));
+
+    match service.find_by_phone(&phone).await {
+        Ok(users) => ApiResponse::ok("Users retrieved successfully", users).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve users", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
 pub async fn get_user(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
@@ -81,7 +94,10 @@ pub async fn update_user(
     let service = UserService::new(repo);
     
     match service.update(oid, payload).await {
-        Ok(user) => ApiResponse::ok("User updated successfully", user).into_response(),
+        Ok(user) => {
+            state.events.publish(DomainEvent::updated("users", &id));
+            ApiResponse::ok("User updated successfully", user).into_response()
+        }
         Err((status, msg)) => ErrorResponse::new(status, "Failed to update user", "UPDATE_FAILED", Some(msg)).into_response(),
     }
 }
@@ -6,10 +6,10 @@ use axum::{
 use mongodb::bson::oid::ObjectId;
 use std::sync::Arc;
 use crate::{
-    db::AppState,
+    db::{AppState, ReadContext},
     services::UserRoleService,
     repository::UserRoleRepository,
-    dto::user_role::{CreateUserRoleRequest, UpdateUserRoleRequest},
+    dto::user_role::{BulkCreateUserRoleRequest, CreateUserRoleRequest, UpdateUserRoleRequest},
     response::{ApiResponse, ErrorResponse, no_content, PaginatedResponse},
     pagination::PaginationParams,
 };
@@ -18,7 +18,7 @@ pub async fn get_user_roles(
     State(state): State<Arc<AppState>>,
     Query(params): Query<PaginationParams>,
 ) -> impl IntoResponse {
-    let repo = UserRoleRepository::new(state.db.clone());
+    let repo = UserRoleRepository::new(state.db_for(ReadContext::Replica));
     let service = UserRoleService::new(repo);
     
     match service.get_all_paginated(params.clone()).await {
@@ -44,6 +44,21 @@ pub async fn create_user_role(
     }
 }
 
+/// Assign many roles at once; each item is validated and created on its own
+pub async fn bulk_create_user_roles(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<BulkCreateUserRoleRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    let service = UserRoleService::new(UserRoleRepository::new(state.db.clone()));
+    let total = payload.assignments.len();
+    let result = service.bulk_create(payload.assignments).await;
+    ApiResponse::ok(format!("{} of {} user roles created", result.created, total), result).into_response()
+}
+
 pub async fn get_user_role(
     State(state): State<Arc<AppState>>,
     Path(id): Path<String>,
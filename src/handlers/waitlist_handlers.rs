@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    services::WaitlistService,
+    repository::{AppointmentRepository, NotificationRepository, WaitlistRepository},
+    dto::waitlist::{JoinWaitlistRequest, WaitlistQuery},
+    events::DomainEvent,
+    response::{ApiResponse, ErrorResponse},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> WaitlistService {
+    let db = state.db_for(ctx);
+    WaitlistService::new(
+        WaitlistRepository::new(db.clone()),
+        AppointmentRepository::new(db.clone()),
+        NotificationRepository::new(db),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+pub async fn join_waitlist(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<JoinWaitlistRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).join(payload).await {
+        Ok(entry) => {
+            state.events.publish(DomainEvent::created("waitlist", &entry.id));
+            ApiResponse::created("Added to waitlist successfully", entry).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to join waitlist", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_waitlist(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WaitlistQuery>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list(query).await {
+        Ok(entries) => ApiResponse::ok("Waitlist retrieved successfully", entries).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve waitlist", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn confirm_waitlist_hold(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).confirm(oid).await {
+        Ok(entry) => {
+            state.events.publish(DomainEvent::updated("waitlist", &entry.id));
+            if let Some(appointment_id) = &entry.hold_appointment_id {
+                state.events.publish(DomainEvent::updated("appointments", appointment_id));
+            }
+            ApiResponse::ok("Waitlist slot confirmed successfully", entry).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to confirm waitlist slot", "CONFIRM_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn cancel_waitlist_entry(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).cancel(oid).await {
+        Ok((entry, released)) => {
+            state.events.publish(DomainEvent::updated("waitlist", &entry.id));
+            // A released hold frees the slot for the next patient in line
+            if let Some(appointment_id) = released {
+                state.events.publish(DomainEvent::updated("appointments", &appointment_id));
+            }
+            ApiResponse::ok("Waitlist entry cancelled successfully", entry).into_response()
+        }
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to cancel waitlist entry", "CANCEL_FAILED", Some(msg)).into_response(),
+    }
+}
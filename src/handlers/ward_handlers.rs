@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::IntoResponse,
+    Json,
+};
+use mongodb::bson::oid::ObjectId;
+use std::sync::Arc;
+use crate::{
+    db::{AppState, ReadContext},
+    delete_policy::DeleteGuard,
+    services::WardService,
+    repository::{BedRepository, WardRepository},
+    dto::ward::{CreateBedRequest, CreateWardRequest, UpdateBedRequest, UpdateWardRequest, WardQuery},
+    response::{ApiResponse, ErrorResponse, no_content},
+};
+
+fn build_service(state: &AppState, ctx: ReadContext) -> WardService {
+    let db = state.db_for(ctx);
+    WardService::new(WardRepository::new(db.clone()), BedRepository::new(db), DeleteGuard::from_state(state))
+}
+
+pub async fn get_wards(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WardQuery>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Replica).list_wards(query.organization_id.as_deref()).await {
+        Ok(wards) => ApiResponse::ok("Wards retrieved successfully", wards).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve wards", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_ward(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateWardRequest>,
+) -> impl IntoResponse {
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create_ward(payload).await {
+        Ok(ward) => ApiResponse::created("Ward created successfully", ward).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create ward", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_ward(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Replica).get_ward(oid).await {
+        Ok(Some(ward)) => ApiResponse::ok("Ward retrieved successfully", ward).into_response(),
+        Ok(None) => ErrorResponse::not_found("Ward not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve ward", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_ward(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateWardRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update_ward(oid, payload).await {
+        Ok(ward) => ApiResponse::ok("Ward updated successfully", ward).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update ward", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_ward(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete_ward(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Ward not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete ward", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn get_beds(
+    State(state): State<Arc<AppState>>,
+    Path(ward_id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&ward_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).list_beds(oid).await {
+        Ok(beds) => ApiResponse::ok("Beds retrieved successfully", beds).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve beds", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn create_bed(
+    State(state): State<Arc<AppState>>,
+    Path(ward_id): Path<String>,
+    Json(payload): Json<CreateBedRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&ward_id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).create_bed(oid, payload).await {
+        Ok(bed) => ApiResponse::created("Bed created successfully", bed).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to create bed", "CREATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn update_bed(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(payload): Json<UpdateBedRequest>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    if let Err(e) = crate::validation::validate_payload(&payload) {
+        return e.into_response();
+    }
+
+    match build_service(&state, ReadContext::Primary).update_bed(oid, payload).await {
+        Ok(bed) => ApiResponse::ok("Bed updated successfully", bed).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to update bed", "UPDATE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+pub async fn delete_bed(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::bad_request("Invalid ID format", Some("ID must be a valid MongoDB ObjectId".to_string())).into_response();
+    };
+
+    match build_service(&state, ReadContext::Primary).delete_bed(oid).await {
+        Ok(true) => no_content().into_response(),
+        Ok(false) => ErrorResponse::not_found("Bed not found").into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to delete bed", "DELETE_FAILED", Some(msg)).into_response(),
+    }
+}
+
+/// Bed counts per ward and in total, read from the primary so the dashboard sees the
+/// latest admissions.
+pub async fn get_occupancy(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<WardQuery>,
+) -> impl IntoResponse {
+    match build_service(&state, ReadContext::Primary).occupancy(query.organization_id.as_deref()).await {
+        Ok(occupancy) => ApiResponse::ok("Ward occupancy retrieved successfully", occupancy).into_response(),
+        Err((status, msg)) => ErrorResponse::new(status, "Failed to retrieve ward occupancy", "FETCH_FAILED", Some(msg)).into_response(),
+    }
+}
@@ -0,0 +1,131 @@
+//! Minimal JSON-over-HTTP(S) client shared by outbound integrations.
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::{Method, Request};
+use hyper_rustls::HttpsConnector;
+use hyper_util::client::legacy::{connect::HttpConnector, Client};
+use hyper_util::rt::TokioExecutor;
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::Instrument;
+
+/// Response status and raw body of an outbound call.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, String> {
+        serde_json::from_slice(&self.body).map_err(|e| format!("Invalid JSON response: {}", e))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: Client<HttpsConnector<HttpConnector>, Full<Bytes>>,
+}
+
+impl HttpClient {
+    pub fn new() -> Result<Self, String> {
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_provider_and_native_roots(rustls::crypto::ring::default_provider())
+            .map_err(|e| format!("Failed to load TLS roots: {}", e))?
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Ok(Self {
+            inner: Client::builder(TokioExecutor::new()).build(connector),
+        })
+    }
+
+    /// Send a request with an optional JSON body.
+    pub async fn send_json<B: Serialize>(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Option<&B>,
+    ) -> Result<HttpResponse, String> {
+        let payload = match body {
+            Some(b) => serde_json::to_vec(b).map_err(|e| format!("Failed to encode request: {}", e))?,
+            None => Vec::new(),
+        };
+        self.send(method, url, headers, "application/json", payload).await
+    }
+
+    /// Send a request with a raw body of the given content type, in a client span whose
+    /// context goes along as `traceparent`.
+    pub async fn send(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, String> {
+        let host = url.parse::<hyper::Uri>().ok().and_then(|uri| uri.host().map(str::to_string)).unwrap_or_default();
+        // Named without the path, which may carry identifiers
+        let span = tracing::info_span!(
+            "http.client",
+            otel.name = %method,
+            otel.kind = "client",
+            http.request.method = %method,
+            server.address = %host,
+            http.response.status_code = tracing::field::Empty,
+        );
+        self.send_in(method, url, headers, content_type, body).instrument(span).await
+    }
+
+    async fn send_in(
+        &self,
+        method: Method,
+        url: &str,
+        headers: &[(&str, &str)],
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, String> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(url)
+            .header("content-type", content_type)
+            .header("accept", "application/json");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        for (name, value) in crate::telemetry::trace_headers() {
+            builder = builder.header(name, value);
+        }
+
+        let request = builder
+            .body(Full::new(Bytes::from(body)))
+            .map_err(|e| format!("Invalid request: {}", e))?;
+
+        let response = self.inner
+            .request(request)
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", url, e))?;
+
+        let status = response.status().as_u16();
+        tracing::Span::current().record("http.response.status_code", status);
+        let body = response
+            .into_body()
+            .collect()
+            .await
+            .map_err(|e| format!("Failed to read response from {}: {}", url, e))?
+            .to_bytes()
+            .to_vec();
+
+        Ok(HttpResponse { status, body })
+    }
+}
@@ -0,0 +1,149 @@
+//! Translated display values.
+//!
+//! `Code.display` and `Interpretation.text` keep their single stored value; `display_i18n`
+//! adds translations keyed by language (`id`, `en`). Clients pick the language with
+//! `?lang=` or the `Accept-Language` header, and handlers take the `Locale` extractor and
+//! replace the display value with the translation, falling back to the stored value and then
+//! to any other translation. Without a supported language responses are left as stored.
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use axum::{async_trait, extract::FromRequestParts, http::{header, request::Parts}};
+use mongodb::bson::{Bson, Document};
+use validator::ValidationError;
+
+/// Supported languages, in fallback order
+pub const LANGUAGES: [&str; 2] = ["id", "en"];
+const MAX_TRANSLATION_LENGTH: usize = 500;
+
+/// Language -> display value
+pub type Translations = BTreeMap<String, String>;
+
+/// The supported language of `tag`, e.g. `en` for `en-US`
+fn language(tag: &str) -> Option<&'static str> {
+    let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+    LANGUAGES.iter().copied().find(|lang| *lang == primary)
+}
+
+/// The requested language, if any is supported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Locale(Option<&'static str>);
+
+impl Locale {
+    pub fn new(lang: &str) -> Self {
+        Self(language(lang))
+    }
+
+    /// `lang` wins over `Accept-Language`, whose entries are tried by descending quality
+    pub fn from_request(lang: Option<&str>, accept_language: Option<&str>) -> Self {
+        if let Some(lang) = lang.and_then(language) {
+            return Self(Some(lang));
+        }
+        let Some(header) = accept_language else {
+            return Self(None);
+        };
+
+        let mut ranges: Vec<(&str, f32)> = header.split(',').filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+            (quality > 0.0).then_some((tag, quality))
+        }).collect();
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Self(ranges.into_iter().find_map(|(tag, _)| language(tag)))
+    }
+
+    pub fn language(&self) -> Option<&'static str> {
+        self.0
+    }
+
+    /// The value to show: the requested translation, else `base`, else another translation
+    pub fn resolve(&self, base: &str, translations: &Translations) -> String {
+        let Some(lang) = self.0 else {
+            return base.to_string();
+        };
+        if let Some(value) = translations.get(lang) {
+            return value.clone();
+        }
+        if !base.is_empty() {
+            return base.to_string();
+        }
+        LANGUAGES.iter()
+            .find_map(|other| translations.get(*other))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Replaces `value` with its translation
+    pub fn apply(&self, value: &mut String, translations: &Translations) {
+        if self.0.is_some() {
+            *value = self.resolve(value, translations);
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Locale {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let lang = parts.uri.query().and_then(|query| {
+            url::form_urlencoded::parse(query.as_bytes())
+                .find(|(key, _)| key == "lang")
+                .map(|(_, value)| value.into_owned())
+        });
+        let accept_language = parts.headers.get(header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok());
+        Ok(Self::from_request(lang.as_deref(), accept_language))
+    }
+}
+
+pub fn to_document(translations: &Translations) -> Document {
+    translations.iter().map(|(lang, value)| (lang.clone(), Bson::String(value.clone()))).collect()
+}
+
+/// Keys must be supported languages and values non-empty
+pub fn validate_translations(translations: &Translations) -> Result<(), ValidationError> {
+    for (lang, value) in translations {
+        if !LANGUAGES.contains(&lang.as_str()) {
+            let mut error = ValidationError::new("unsupported_language");
+            error.message = Some(format!("Unsupported language '{}', expected one of {}", lang, LANGUAGES.join(", ")).into());
+            return Err(error);
+        }
+        if value.trim().is_empty() || value.chars().count() > MAX_TRANSLATION_LENGTH {
+            let mut error = ValidationError::new("invalid_translation");
+            error.message = Some(format!("The '{}' translation must be between 1 and {} characters", lang, MAX_TRANSLATION_LENGTH).into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_comes_from_the_query_or_the_best_accepted_language() {
+        assert_eq!(Locale::from_request(Some("en"), Some("id")).language(), Some("en"));
+        assert_eq!(Locale::from_request(Some("fr"), Some("en-US,en;q=0.9")).language(), Some("en"));
+        assert_eq!(Locale::from_request(None, Some("fr-FR, id;q=0.5, en;q=0.8")).language(), Some("en"));
+        assert_eq!(Locale::from_request(None, Some("en;q=0, id-ID")).language(), Some("id"));
+        assert_eq!(Locale::from_request(None, Some("fr, *")).language(), None);
+        assert_eq!(Locale::from_request(None, None).language(), None);
+    }
+
+    #[test]
+    fn translations_fall_back_to_the_stored_value() {
+        let translations = Translations::from([("en".to_string(), "High".to_string())]);
+        assert_eq!(Locale::new("en").resolve("Tinggi", &translations), "High");
+        assert_eq!(Locale::new("id").resolve("Tinggi", &translations), "Tinggi");
+        assert_eq!(Locale::new("id").resolve("", &translations), "High");
+        assert_eq!(Locale::default().resolve("Tinggi", &translations), "Tinggi");
+
+        assert!(validate_translations(&translations).is_ok());
+        assert!(validate_translations(&Translations::from([("fr".to_string(), "Haut".to_string())])).is_err());
+        assert!(validate_translations(&Translations::from([("id".to_string(), " ".to_string())])).is_err());
+    }
+}
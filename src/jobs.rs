@@ -0,0 +1,51 @@
+//! Background job runner.
+//!
+//! Jobs are persisted in the `jobs` collection by `JobService::enqueue` and executed on a
+//! Tokio task; clients poll the job document for status and progress. Failed jobs keep the
+//! error of each attempt and can be retried from `/admin/jobs`.
+
+use mongodb::Database;
+use crate::models::Job;
+use crate::repository::JobRepository;
+use crate::services::JobService;
+#[cfg(feature = "fhir")]
+use crate::repository::CodeRepository;
+#[cfg(feature = "fhir")]
+use crate::services::{code_import_service::CODE_IMPORT_JOB, CodeImportService};
+
+/// Run a persisted job in the background.
+pub fn spawn(db: Database, job: Job) {
+    tokio::spawn(run(db, job));
+}
+
+async fn run(db: Database, job: Job) {
+    let Some(id) = job.id else { return };
+    let jobs = JobService::new(JobRepository::new(db.clone()));
+
+    if let Err(e) = jobs.start(id).await {
+        tracing::error!(job_id = %id, error = %e, "Failed to start job");
+        return;
+    }
+
+    let outcome = match job.job_type.as_str() {
+        #[cfg(feature = "fhir")]
+        CODE_IMPORT_JOB => {
+            CodeImportService::new(CodeRepository::new(db.clone()), JobService::new(JobRepository::new(db.clone())))
+                .execute(&job)
+                .await
+        }
+        crate::system::INDEX_REBUILD_JOB => crate::system::rebuild_indexes(&db, &jobs, &job).await,
+        other => Err(format!("Unknown job type '{}'", other)),
+    };
+
+    let finished = match outcome {
+        Ok(result) => jobs.complete(id, result).await,
+        Err(e) => {
+            tracing::error!(job_id = %id, job_type = %job.job_type, error = %e, "Job failed");
+            jobs.fail(id, job.attempts + 1, &e).await
+        }
+    };
+    if let Err(e) = finished {
+        tracing::error!(job_id = %id, error = %e, "Failed to record job outcome");
+    }
+}
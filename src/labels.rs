@@ -0,0 +1,159 @@
+//! Printable labels for pharmacy and lab printers.
+//!
+//! A `Label` is a few lines of text over a Code 128 barcode (see `crate::barcode`), sized
+//! 60 x 40 mm. `zpl` renders labels for Zebra-compatible thermal printers at 203 dpi, which
+//! draw the barcode themselves; `pdf` renders one page per label for office printers, with
+//! the bars drawn from `barcode::modules`.
+
+use crate::barcode::{self, QUIET_ZONE};
+
+/// Label width and height in 203 dpi printer dots
+const ZPL_WIDTH: u32 = 480;
+const ZPL_HEIGHT: u32 = 320;
+/// Label width and height in PDF points
+const PDF_WIDTH: f64 = 170.0;
+const PDF_HEIGHT: f64 = 113.0;
+const PDF_MARGIN: f64 = 8.0;
+const PDF_BAR_HEIGHT: f64 = 36.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    /// First line, printed larger
+    pub title: String,
+    pub lines: Vec<String>,
+    /// Printed as a barcode and in plain text below it
+    pub barcode: String,
+}
+
+/// Field data with ZPL's control characters hex-escaped, for use after `^FH`
+fn zpl_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '^' | '~' | '_' => format!("_{:02X}", c as u32),
+            c if c.is_control() => " ".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// ZPL for `copies` of each label
+pub fn zpl(labels: &[Label], copies: u32) -> String {
+    let mut out = String::new();
+    for label in labels {
+        out.push_str(&format!("^XA^CI28^PW{}^LL{}\n", ZPL_WIDTH, ZPL_HEIGHT));
+        out.push_str(&format!("^FO20,20^A0N,30,30^FH^FD{}^FS\n", zpl_text(&label.title)));
+        for (i, line) in label.lines.iter().enumerate() {
+            out.push_str(&format!("^FO20,{}^A0N,24,24^FH^FD{}^FS\n", 60 + i * 30, zpl_text(line)));
+        }
+        out.push_str(&format!("^FO20,{}^BY2^BCN,80,Y,N,N^FH^FD{}^FS\n", 70 + label.lines.len() * 30, zpl_text(&label.barcode)));
+        out.push_str(&format!("^PQ{}\n^XZ\n", copies.max(1)));
+    }
+    out
+}
+
+fn pdf_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\\' | '(' | ')' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// Drawing operators of one label page
+fn pdf_page(label: &Label) -> Result<String, String> {
+    let mut content = format!("BT /F1 11 Tf {} {} Td ({}) Tj ET\n", PDF_MARGIN, PDF_HEIGHT - PDF_MARGIN - 11.0, pdf_text(&label.title));
+    for (i, line) in label.lines.iter().enumerate() {
+        let y = PDF_HEIGHT - PDF_MARGIN - 24.0 - i as f64 * 10.0;
+        content.push_str(&format!("BT /F1 8 Tf {} {:.1} Td ({}) Tj ET\n", PDF_MARGIN, y, pdf_text(line)));
+    }
+
+    let bars = barcode::modules(&label.barcode)?;
+    let module = (PDF_WIDTH - 2.0 * PDF_MARGIN) / (bars.len() + 2 * QUIET_ZONE) as f64;
+    let bottom = PDF_MARGIN + 10.0;
+    // Runs of dark modules become one rectangle each
+    let mut start = None;
+    for (i, dark) in bars.iter().chain(std::iter::once(&false)).enumerate() {
+        match (dark, start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                let x = PDF_MARGIN + (QUIET_ZONE + from) as f64 * module;
+                content.push_str(&format!("{:.3} {:.1} {:.3} {:.1} re\n", x, bottom, (i - from) as f64 * module, PDF_BAR_HEIGHT));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    content.push_str("f\n");
+    content.push_str(&format!("BT /F1 7 Tf {} {} Td ({}) Tj ET", PDF_MARGIN + QUIET_ZONE as f64 * module, PDF_MARGIN, pdf_text(&label.barcode)));
+    Ok(content)
+}
+
+/// A PDF with one page per label copy
+pub fn pdf(labels: &[Label], copies: u32) -> Result<Vec<u8>, String> {
+    let mut contents = Vec::new();
+    for label in labels {
+        let page = pdf_page(label)?;
+        contents.extend(std::iter::repeat_n(page, copies.max(1) as usize));
+    }
+    // Objects 1-3 are the catalog, the page tree and the font; each page adds a page and its content
+    let page_ids: Vec<usize> = (0..contents.len()).map(|i| 4 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "), contents.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+    ];
+    for (content, id) in contents.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PDF_WIDTH, PDF_HEIGHT, id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref));
+    Ok(pdf.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label() -> Label {
+        Label {
+            title: "Amoxicillin 500mg".to_string(),
+            lines: vec!["Batch: BN^2024".to_string(), "Exp: 2027-01-31".to_string()],
+            barcode: "BN^2024".to_string(),
+        }
+    }
+
+    #[test]
+    fn zpl_escapes_field_data() {
+        let zpl = zpl(&[label()], 3);
+        assert!(zpl.starts_with("^XA"));
+        assert!(zpl.contains("^FDBatch: BN_5E2024^FS"));
+        assert!(zpl.contains("^BCN,80,Y,N,N^FH^FDBN_5E2024^FS"));
+        assert!(zpl.contains("^PQ3\n^XZ"));
+    }
+
+    #[test]
+    fn pdf_has_a_page_per_copy() {
+        let pdf = String::from_utf8(pdf(&[label()], 2).unwrap()).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(Amoxicillin 500mg) Tj"));
+        assert!(pdf.contains(" re\n"));
+    }
+}
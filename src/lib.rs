@@ -1,16 +1,90 @@
+pub mod config;
 pub mod db;
 pub mod models;
 pub mod handlers;
 pub mod routes;
+pub mod resource_router;
 pub mod docs;
 pub mod validation;
+#[cfg(feature = "s3")]
 pub mod s3;
+#[cfg(feature = "s3")]
+pub mod storage;
+#[cfg(feature = "s3")]
+pub mod quota;
+#[cfg(feature = "s3")]
+pub mod storage_gc;
 pub mod repository;
 pub mod services;
 pub mod response;
 pub mod pagination;
 pub mod dto;
 pub mod middleware;
+pub mod matching;
+pub mod migrations;
+pub mod rbac;
+pub mod events;
+pub mod http_client;
+pub mod telemetry;
+pub mod error_reporting;
+pub mod load_shed;
+pub mod metrics;
+#[cfg(feature = "fhir")]
+pub mod terminology;
+pub mod stats;
+pub mod no_show;
+pub mod growth;
+pub mod derived;
+pub mod recurrence;
+pub mod retention;
+pub mod waitlist;
+pub mod role_expiry;
+pub mod denormalize;
+pub mod self_registration;
+pub mod captcha;
+pub mod barcode;
+pub mod labels;
+pub mod geo;
+pub mod distributor;
+pub mod teleconsult;
+pub mod otp;
+pub mod mailer;
+pub mod request_log;
+pub mod flags;
+pub mod code_cache;
+pub mod shared_store;
+pub mod sync;
+pub mod timeline;
+pub mod system;
+pub mod datetime;
+pub mod conditional;
+pub mod links;
+pub mod timezone;
+pub mod naming;
+pub mod i18n;
+pub mod status;
+pub mod refs;
+pub mod delete_policy;
+pub mod allergy;
+pub mod programs;
+pub mod vitals;
+#[cfg(feature = "billing")]
+pub mod payment_gateway;
+#[cfg(feature = "billing")]
+pub mod bpjs;
+pub mod outbox;
+pub mod change_streams;
+pub mod scheduler;
+pub mod mock;
+pub mod cron;
+#[cfg(feature = "s3")]
+pub mod reports;
+pub mod report_templates;
+pub mod sequences;
+pub mod phone;
+pub mod jobs;
+#[cfg(feature = "meilisearch")]
+pub mod meilisearch;
 
 // Re-export AppState for tests and external usage
 pub use db::AppState;
@@ -0,0 +1,89 @@
+//! `_links` of resources, for clients that follow URLs instead of building them.
+//!
+//! Every href starts with `API_BASE_PATH`, the prefix the API is published under (e.g.
+//! `/api/v1` behind the gateway); it is empty when clients reach this server directly.
+
+use std::env;
+use crate::dto::appointment::AppointmentResponse;
+use crate::dto::common::{Link, Links};
+use crate::services::appointment_service::MODE_VIRTUAL;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LinkConfig {
+    /// Without a trailing slash; empty for the root
+    pub base_path: String,
+}
+
+impl LinkConfig {
+    pub fn from_env() -> Self {
+        Self::new(&env::var("API_BASE_PATH").unwrap_or_default())
+    }
+
+    pub fn new(base_path: &str) -> Self {
+        let base_path = base_path.trim().trim_end_matches('/');
+        let base_path = match base_path {
+            "" => String::new(),
+            path if path.starts_with('/') => path.to_string(),
+            path => format!("/{}", path),
+        };
+        Self { base_path }
+    }
+
+    /// `path` under the base path, e.g. `/api/v1/doctors`
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_path, path)
+    }
+
+    fn link(&self, path: &str) -> Link {
+        Link { href: self.url(path) }
+    }
+
+    /// A patient's record and the collections that belong to it
+    pub fn medical_record(&self, id: &str) -> Links {
+        Links::from([
+            ("self".to_string(), self.link(&format!("/medical-records/{}", id))),
+            ("changes".to_string(), self.link(&format!("/medical-records/{}/changes", id))),
+            ("appointments".to_string(), self.link(&format!("/appointments?patient_id={}", id))),
+            ("observations".to_string(), self.link(&format!("/observations?id_pasien={}", id))),
+            ("allergies".to_string(), self.link(&format!("/patients/{}/allergies", id))),
+            ("relationships".to_string(), self.link(&format!("/patients/{}/relationships", id))),
+        ])
+    }
+
+    /// An appointment, its patient and doctor, and its teleconsult when it is virtual
+    pub fn appointment(&self, appointment: &AppointmentResponse) -> Links {
+        let id = &appointment.id;
+        let mut links = Links::from([
+            ("self".to_string(), self.link(&format!("/appointments/{}", id))),
+            ("patient".to_string(), self.link(&format!("/medical-records/{}", appointment.patient_id))),
+            ("doctor".to_string(), self.link(&format!("/doctors/{}", appointment.doctor_id))),
+        ]);
+        if let Some(series_id) = &appointment.series_id {
+            links.insert("series".to_string(), self.link(&format!("/appointments/series/{}", series_id)));
+        }
+        if appointment.mode.as_deref() == Some(MODE_VIRTUAL) {
+            links.insert("teleconsult".to_string(), self.link(&format!("/appointments/{}/teleconsult", id)));
+        }
+        links
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_the_base_path() {
+        assert_eq!(LinkConfig::new("").url("/doctors"), "/doctors");
+        assert_eq!(LinkConfig::new("/api/v1/").url("/doctors"), "/api/v1/doctors");
+        assert_eq!(LinkConfig::new("api/v1").url("/doctors"), "/api/v1/doctors");
+    }
+
+    #[test]
+    fn links_a_patient_to_its_collections() {
+        let links = LinkConfig::new("/api/v1").medical_record("65a1b2c3d4e5f60718293a4b");
+        assert_eq!(links["self"].href, "/api/v1/medical-records/65a1b2c3d4e5f60718293a4b");
+        assert_eq!(links["appointments"].href, "/api/v1/appointments?patient_id=65a1b2c3d4e5f60718293a4b");
+        assert_eq!(links["observations"].href, "/api/v1/observations?id_pasien=65a1b2c3d4e5f60718293a4b");
+    }
+}
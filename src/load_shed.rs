@@ -0,0 +1,178 @@
+//! Load shedding by in-flight request count.
+//!
+//! Requests are served from two pools: `expensive` for the path prefixes in
+//! `LOAD_SHED_EXPENSIVE_ROUTES` (exports, imports, uploads and report runs by default) and
+//! `standard` for everything else. When a pool already serves `LOAD_SHED_MAX_INFLIGHT`
+//! (default 256) or `LOAD_SHED_MAX_EXPENSIVE` (default 8) requests, further ones are refused
+//! with 503 and `Retry-After: LOAD_SHED_RETRY_AFTER_SECS` (default 5) instead of queueing
+//! until they time out; a limit of `0` never refuses. `/metrics` is never shed and reports
+//! each pool's utilization.
+
+use std::env;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use crate::config::path_has_prefix;
+use crate::mailer::parse_route_prefixes;
+
+pub const DEFAULT_MAX_INFLIGHT: usize = 256;
+pub const DEFAULT_MAX_EXPENSIVE: usize = 8;
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 5;
+pub const DEFAULT_EXPENSIVE_ROUTES: &[&str] = &["/observations/export.ndjson", "/codes/import", "/files", "/reports/run"];
+/// Served even when saturated, so monitoring sees the overload
+pub const EXEMPT_ROUTES: &[&str] = &["/metrics"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadSheddingConfig {
+    pub max_inflight: usize,
+    pub max_expensive: usize,
+    pub expensive_routes: Vec<String>,
+    pub retry_after_secs: u64,
+}
+
+impl Default for LoadSheddingConfig {
+    fn default() -> Self {
+        Self {
+            max_inflight: DEFAULT_MAX_INFLIGHT,
+            max_expensive: DEFAULT_MAX_EXPENSIVE,
+            expensive_routes: DEFAULT_EXPENSIVE_ROUTES.iter().map(|r| r.to_string()).collect(),
+            retry_after_secs: DEFAULT_RETRY_AFTER_SECS,
+        }
+    }
+}
+
+impl LoadSheddingConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let number = |name: &str| env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok());
+
+        Self {
+            max_inflight: number("LOAD_SHED_MAX_INFLIGHT").map_or(defaults.max_inflight, |n| n as usize),
+            max_expensive: number("LOAD_SHED_MAX_EXPENSIVE").map_or(defaults.max_expensive, |n| n as usize),
+            expensive_routes: env::var("LOAD_SHED_EXPENSIVE_ROUTES")
+                .map(|raw| parse_route_prefixes(&raw))
+                .unwrap_or(defaults.expensive_routes),
+            retry_after_secs: number("LOAD_SHED_RETRY_AFTER_SECS").filter(|s| *s > 0).unwrap_or(defaults.retry_after_secs),
+        }
+    }
+
+    pub fn is_expensive(&self, path: &str) -> bool {
+        self.expensive_routes.iter().any(|prefix| path_has_prefix(path, prefix))
+    }
+}
+
+/// A bounded count of requests in flight
+#[derive(Debug)]
+pub struct Pool {
+    pub name: &'static str,
+    /// `0` for no limit
+    limit: usize,
+    in_flight: AtomicUsize,
+    shed: AtomicU64,
+}
+
+impl Pool {
+    pub fn new(name: &'static str, limit: usize) -> Self {
+        Self { name, limit, in_flight: AtomicUsize::new(0), shed: AtomicU64::new(0) }
+    }
+
+    /// A slot for one request, released when the permit is dropped; `None` when full
+    pub fn try_acquire(self: &Arc<Self>) -> Option<Permit> {
+        let acquired = self.in_flight.fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+            (self.limit == 0 || current < self.limit).then_some(current + 1)
+        });
+        match acquired {
+            Ok(_) => Some(Permit(self.clone())),
+            Err(_) => {
+                self.shed.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Requests refused since startup
+    pub fn shed(&self) -> u64 {
+        self.shed.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug)]
+pub struct Permit(Arc<Pool>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// The pools requests are admitted through
+#[derive(Debug)]
+pub struct LoadShedder {
+    pub config: LoadSheddingConfig,
+    pub standard: Arc<Pool>,
+    pub expensive: Arc<Pool>,
+}
+
+impl LoadShedder {
+    pub fn new(config: &LoadSheddingConfig) -> Self {
+        Self {
+            standard: Arc::new(Pool::new("standard", config.max_inflight)),
+            expensive: Arc::new(Pool::new("expensive", config.max_expensive)),
+            config: config.clone(),
+        }
+    }
+
+    /// Pool a request to `path` is admitted through; `None` for exempt routes
+    pub fn pool_for(&self, path: &str) -> Option<&Arc<Pool>> {
+        if EXEMPT_ROUTES.iter().any(|prefix| path_has_prefix(path, prefix)) {
+            None
+        } else if self.config.is_expensive(path) {
+            Some(&self.expensive)
+        } else {
+            Some(&self.standard)
+        }
+    }
+
+    pub fn pools(&self) -> [&Arc<Pool>; 2] {
+        [&self.standard, &self.expensive]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_requests_over_the_limit_until_one_finishes() {
+        let pool = Arc::new(Pool::new("expensive", 2));
+        let first = pool.try_acquire().unwrap();
+        let _second = pool.try_acquire().unwrap();
+        assert!(pool.try_acquire().is_none());
+        assert_eq!((pool.in_flight(), pool.shed()), (2, 1));
+
+        drop(first);
+        assert!(pool.try_acquire().is_some());
+        assert_eq!(pool.in_flight(), 1);
+
+        let unlimited = Arc::new(Pool::new("standard", 0));
+        let permits: Vec<_> = (0..1000).map(|_| unlimited.try_acquire().unwrap()).collect();
+        assert_eq!(unlimited.in_flight(), permits.len());
+    }
+
+    #[test]
+    fn routes_requests_to_pools() {
+        let shedder = LoadShedder::new(&LoadSheddingConfig::default());
+        assert_eq!(shedder.pool_for("/observations/export.ndjson").unwrap().name, "expensive");
+        assert_eq!(shedder.pool_for("/files/65a1b2c3d4e5f60718293a4b").unwrap().name, "expensive");
+        assert_eq!(shedder.pool_for("/doctors").unwrap().name, "standard");
+        assert_eq!(shedder.pool_for("/filesystem").unwrap().name, "standard");
+        assert!(shedder.pool_for("/metrics").is_none());
+    }
+}
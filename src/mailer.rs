@@ -0,0 +1,181 @@
+//! Outbound email.
+//!
+//! `MAIL_PROVIDER` selects delivery: `log` (default) prints messages to the server log and
+//! `sendgrid` sends them through the SendGrid v3 API with `SENDGRID_API_KEY`. Messages are
+//! sent from `MAIL_FROM`; links in them point at `APP_BASE_URL`.
+//!
+//! `EMAIL_VERIFICATION_TTL_HOURS` (24) bounds verification links, and
+//! `EMAIL_VERIFIED_ROUTES` lists path prefixes (comma separated, e.g. `/admin,/users`) that
+//! staff may only use once their email is verified. It is empty, so nothing is gated, by default.
+
+use std::env;
+use futures_util::future::BoxFuture;
+use hyper::Method;
+use crate::http_client::HttpClient;
+
+pub const SENDGRID_API_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+pub trait Mailer: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), String>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct EmailConfig {
+    pub provider: String,
+    pub from: String,
+    pub sendgrid_api_key: Option<String>,
+    pub app_base_url: String,
+    pub verification_ttl_hours: i64,
+    pub verified_routes: Vec<String>,
+}
+
+impl Default for EmailConfig {
+    fn default() -> Self {
+        Self {
+            provider: "log".to_string(),
+            from: "no-reply@localhost".to_string(),
+            sendgrid_api_key: None,
+            app_base_url: "http://localhost:8000".to_string(),
+            verification_ttl_hours: 24,
+            verified_routes: Vec::new(),
+        }
+    }
+}
+
+impl EmailConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: env::var("MAIL_PROVIDER").map(|p| p.trim().to_lowercase()).unwrap_or(defaults.provider),
+            from: env::var("MAIL_FROM").ok().filter(|v| !v.is_empty()).unwrap_or(defaults.from),
+            sendgrid_api_key: env::var("SENDGRID_API_KEY").ok().filter(|v| !v.is_empty()),
+            app_base_url: env::var("APP_BASE_URL")
+                .map(|u| u.trim_end_matches('/').to_string())
+                .unwrap_or(defaults.app_base_url),
+            verification_ttl_hours: env::var("EMAIL_VERIFICATION_TTL_HOURS")
+                .ok()
+                .and_then(|h| h.parse().ok())
+                .filter(|h| *h > 0)
+                .unwrap_or(defaults.verification_ttl_hours),
+            verified_routes: env::var("EMAIL_VERIFIED_ROUTES")
+                .map(|raw| parse_route_prefixes(&raw))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The configured mailer; unknown names and a missing SendGrid key fall back to logging.
+    pub fn mailer(&self) -> Box<dyn Mailer> {
+        match self.provider.as_str() {
+            "sendgrid" => match SendGridMailer::from_config(self) {
+                Ok(mailer) => Box::new(mailer),
+                Err(e) => {
+                    tracing::warn!(error = %e, "SendGrid mailer unavailable, logging emails instead");
+                    Box::new(LogMailer)
+                }
+            },
+            other => {
+                if other != "log" {
+                    tracing::warn!(provider = other, "Unknown MAIL_PROVIDER, logging emails instead");
+                }
+                Box::new(LogMailer)
+            }
+        }
+    }
+
+    /// Link a new account opens to verify its email.
+    pub fn verification_link(&self, token: &str) -> String {
+        format!("{}/auth/verify-email?token={}", self.app_base_url, token)
+    }
+}
+
+/// Comma separated path prefixes, each starting with `/`.
+pub fn parse_route_prefixes(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|p| p.trim().trim_end_matches('/'))
+        .filter(|p| p.starts_with('/'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Prints emails to the server log; for development without a mail provider.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            tracing::info!(to = %email.to, subject = %email.subject, body = %email.body, "Email");
+            Ok(())
+        })
+    }
+}
+
+pub struct SendGridMailer {
+    http: HttpClient,
+    api_key: String,
+    from: String,
+}
+
+impl SendGridMailer {
+    pub fn from_config(config: &EmailConfig) -> Result<Self, String> {
+        Ok(Self {
+            http: HttpClient::new()?,
+            api_key: config.sendgrid_api_key.clone().ok_or_else(|| "SENDGRID_API_KEY is not set".to_string())?,
+            from: config.from.clone(),
+        })
+    }
+}
+
+impl Mailer for SendGridMailer {
+    fn name(&self) -> &'static str {
+        "sendgrid"
+    }
+
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "personalizations": [{ "to": [{ "email": email.to }] }],
+                "from": { "email": self.from },
+                "subject": email.subject,
+                "content": [{ "type": "text/plain", "value": email.body }],
+            });
+            let auth = format!("Bearer {}", self.api_key);
+            let response = self.http
+                .send_json(Method::POST, SENDGRID_API_URL, &[("authorization", auth.as_str())], Some(&body))
+                .await?;
+            if response.is_success() {
+                Ok(())
+            } else {
+                Err(format!("SendGrid returned {}: {}", response.status, response.text()))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_verified_route_prefixes() {
+        assert_eq!(parse_route_prefixes(" /admin/, /users ,,reports"), vec!["/admin".to_string(), "/users".to_string()]);
+        assert!(parse_route_prefixes("").is_empty());
+    }
+
+    #[test]
+    fn verification_links_point_at_the_api() {
+        let config = EmailConfig { app_base_url: "https://rme.example.org".to_string(), ..EmailConfig::default() };
+        assert_eq!(config.verification_link("abc"), "https://rme.example.org/auth/verify-email?token=abc");
+    }
+}
@@ -1,46 +1,37 @@
-mod db;
-mod models;
-mod handlers;
-mod docs;
-mod routes;
-mod validation;
-mod s3;
-mod repository;
-mod services;
-mod response;
-mod pagination;
-mod dto;
-mod middleware;
-
 use dotenvy::dotenv;
+use rme_api_rust::{db, error_reporting, routes, telemetry};
 use std::env;
-use tower_http::trace::TraceLayer;
+use std::net::SocketAddr;
 
 #[tokio::main]
 async fn main() {
     dotenv().ok();
     
-    // Initialize tracing
-    tracing_subscriber::fmt::init();
+    // Initialize tracing; exports spans when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _telemetry = telemetry::init();
+
+    let port = env::var("PORT").unwrap_or_else(|_| "8000".to_string());
+    let addr = format!("0.0.0.0:{}", port);
 
     // Connect to database
     let state = match db::init_db().await {
         Ok(s) => s,
         Err(e) => {
-            eprintln!("Failed to connect to database: {}", e);
+            tracing::error!(error = %e, "Failed to connect to database");
             return;
         }
     };
 
+    // Report panics and server errors when SENTRY_DSN is set
+    let _reporting = error_reporting::init(&state.config.error_reporting);
+
     // Build router
-    let app = routes::create_router(state)
-        .layer(TraceLayer::new_for_http());
+    let app = routes::create_router(state);
 
-    let port = env::var("PORT").unwrap_or_else(|_| "8000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
 
-    println!("Server running on http://{}", addr);
+    tracing::info!(%addr, "Server running");
 
-    axum::serve(listener, app).await.unwrap();
+    // Peer addresses let the public registration endpoint be limited per client
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await.unwrap();
 }
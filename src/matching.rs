@@ -0,0 +1,87 @@
+//! Fuzzy matching helpers used for patient duplicate detection.
+
+/// Honorifics and academic title fragments commonly attached to Indonesian names.
+/// Punctuated titles such as "S.Pd" or "M.Kes" are split into fragments before matching.
+const NAME_TITLES: &[&str] = &[
+    "dr", "drg", "ir", "h", "hj", "prof", "tn", "ny", "nn", "sdr", "an",
+    "s", "m", "a", "pd", "kom", "ked", "kes", "kep", "md", "skm", "st", "se", "mm",
+];
+
+/// Normalize a person name for comparison: lowercase, strip punctuation,
+/// drop titles and collapse whitespace.
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .filter(|part| !NAME_TITLES.contains(part))
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// Levenshtein edit distance between two strings (by chars).
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.is_empty() {
+        return b.len();
+    }
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+/// Similarity score between two names in the range 0.0..=1.0, computed on
+/// normalized names so "Hj. Siti Aminah" and "siti aminah" score 1.0.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_name(a);
+    let b = normalize_name(b);
+    let max_len = a.chars().count().max(b.chars().count());
+
+    if max_len == 0 {
+        return 0.0;
+    }
+
+    1.0 - (levenshtein(&a, &b) as f64 / max_len as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_strips_titles_and_punctuation() {
+        assert_eq!(normalize_name("Hj. Siti  Aminah, S.Pd"), "siti aminah");
+        assert_eq!(normalize_name("dr. Budi Santoso"), "budi santoso");
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+
+    #[test]
+    fn similarity_tolerates_typos() {
+        assert_eq!(name_similarity("Hj. Siti Aminah", "siti aminah"), 1.0);
+        assert!(name_similarity("Muhammad Rizki", "Muhamad Rizky") > 0.8);
+        assert!(name_similarity("Budi Santoso", "Agus Salim") < 0.5);
+    }
+}
@@ -0,0 +1,154 @@
+//! Optional Meilisearch integration (cargo feature `meilisearch`).
+//!
+//! Patient, doctor and medicine documents are pushed to Meilisearch whenever a
+//! domain event for their collection is published, and `/search` queries the
+//! index instead of MongoDB when `MEILISEARCH_URL` is configured.
+
+use std::env;
+use std::sync::Arc;
+use hyper::Method;
+use mongodb::bson::oid::ObjectId;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use crate::db::AppState;
+use crate::events::{DomainEvent, EventKind};
+use crate::http_client::HttpClient;
+use crate::repository::{DoctorRepository, MedicalRecordRepository, MedicineRepository};
+use crate::services::{DoctorService, MedicalRecordService, MedicineService};
+
+/// Meilisearch index backing a MongoDB collection, if that collection is indexed.
+pub fn index_for(collection: &str) -> Option<&'static str> {
+    match collection {
+        "medical_records" => Some("patients"),
+        "doctors" => Some("doctors"),
+        "medicines" => Some("medicines"),
+        _ => None,
+    }
+}
+
+pub struct MeiliClient {
+    http: HttpClient,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl MeiliClient {
+    /// Build a client from `MEILISEARCH_URL` / `MEILISEARCH_API_KEY`; `None` when not configured.
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("MEILISEARCH_URL").ok().filter(|u| !u.trim().is_empty())?;
+        let http = match HttpClient::new() {
+            Ok(http) => http,
+            Err(e) => {
+                tracing::warn!(error = %e, "Meilisearch disabled");
+                return None;
+            }
+        };
+
+        Some(Self {
+            http,
+            url: url.trim_end_matches('/').to_string(),
+            api_key: env::var("MEILISEARCH_API_KEY").ok(),
+        })
+    }
+
+    async fn call(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value, String> {
+        let auth = self.api_key.as_ref().map(|k| format!("Bearer {}", k));
+        let headers: Vec<(&str, &str)> = auth.iter().map(|a| ("authorization", a.as_str())).collect();
+        let url = format!("{}{}", self.url, path);
+
+        let response = self.http.send_json(method, &url, &headers, body).await?;
+        if !response.is_success() {
+            return Err(format!("Meilisearch returned {}: {}", response.status, response.text()));
+        }
+        response.json()
+    }
+
+    pub async fn upsert_document(&self, index: &str, document: Value) -> Result<(), String> {
+        let path = format!("/indexes/{}/documents?primaryKey=id", index);
+        self.call(Method::POST, &path, Some(&json!([document]))).await.map(|_| ())
+    }
+
+    pub async fn delete_document(&self, index: &str, id: &str) -> Result<(), String> {
+        let path = format!("/indexes/{}/documents/{}", index, id);
+        self.call(Method::DELETE, &path, None).await.map(|_| ())
+    }
+
+    /// Search an index, returning hits with their ranking score.
+    pub async fn search<T: DeserializeOwned>(&self, index: &str, query: &str, limit: i64) -> Result<Vec<(f64, T)>, String> {
+        let path = format!("/indexes/{}/search", index);
+        let body = json!({ "q": query, "limit": limit, "showRankingScore": true });
+        let result = self.call(Method::POST, &path, Some(&body)).await?;
+
+        result
+            .get("hits")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|mut hit| {
+                let score = hit.get("_rankingScore").and_then(Value::as_f64).unwrap_or(0.0);
+                if let Some(obj) = hit.as_object_mut() {
+                    obj.remove("_rankingScore");
+                }
+                serde_json::from_value::<T>(hit)
+                    .map(|item| (score, item))
+                    .map_err(|e| format!("Failed to decode Meilisearch hit: {}", e))
+            })
+            .collect()
+    }
+}
+
+/// Load the current response DTO for an indexed document, or `None` if it no longer exists.
+async fn load_document(state: &AppState, collection: &str, id: ObjectId) -> Result<Option<Value>, String> {
+    let document = match collection {
+        "medical_records" => MedicalRecordRepository::new(state.db.clone()).find_by_id(id).await?
+            .map(MedicalRecordService::map_to_response)
+            .map(serde_json::to_value),
+        "doctors" => DoctorRepository::new(state.db.clone()).find_by_id(id).await?
+            .map(DoctorService::map_to_response)
+            .map(serde_json::to_value),
+        "medicines" => MedicineRepository::new(state.db.clone()).find_by_id(id).await?
+            .map(MedicineService::map_to_response)
+            .map(serde_json::to_value),
+        _ => None,
+    };
+
+    document.transpose().map_err(|e| e.to_string())
+}
+
+async fn apply_event(state: &AppState, client: &MeiliClient, event: &DomainEvent) -> Result<(), String> {
+    let Some(index) = index_for(&event.collection) else { return Ok(()) };
+
+    if event.kind == EventKind::Deleted {
+        return client.delete_document(index, &event.id).await;
+    }
+
+    let oid = ObjectId::parse_str(&event.id).map_err(|e| e.to_string())?;
+    match load_document(state, &event.collection, oid).await? {
+        Some(document) => client.upsert_document(index, document).await,
+        None => client.delete_document(index, &event.id).await,
+    }
+}
+
+/// Spawn the background task keeping Meilisearch in sync with the event bus.
+pub fn spawn_sync_worker(state: Arc<AppState>) {
+    let Some(client) = state.meili.clone() else { return };
+    let mut events = state.events.subscribe();
+
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = apply_event(&state, &client, &event).await {
+                        tracing::error!(collection = %event.collection, id = %event.id, error = %e, "Meilisearch sync failed");
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Meilisearch sync lagged, events skipped");
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
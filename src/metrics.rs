@@ -0,0 +1,71 @@
+//! Prometheus text exposition for `GET /metrics`.
+//!
+//! Reports the utilization of the load-shedding pools (see `crate::load_shed`) and the slow
+//! query counts (see `crate::repository::slow_query`). The endpoint is public so scrapers
+//! need no token; it carries no identifiers.
+
+use std::fmt::Write;
+use std::sync::Arc;
+use axum::{extract::State, http::header, response::IntoResponse};
+use crate::db::AppState;
+use crate::load_shed::LoadShedder;
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Escape a label value as the text format requires
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+pub fn render(load: &LoadShedder) -> String {
+    let mut out = String::new();
+
+    family(&mut out, "http_requests_in_flight", "gauge", "Requests being served, by load-shedding pool.");
+    for pool in load.pools() {
+        let _ = writeln!(out, "http_requests_in_flight{{pool=\"{}\"}} {}", pool.name, pool.in_flight());
+    }
+    family(&mut out, "http_requests_in_flight_limit", "gauge", "Most requests a pool serves at once; 0 is unlimited.");
+    for pool in load.pools() {
+        let _ = writeln!(out, "http_requests_in_flight_limit{{pool=\"{}\"}} {}", pool.name, pool.limit());
+    }
+    family(&mut out, "http_requests_shed_total", "counter", "Requests refused with 503 because their pool was full.");
+    for pool in load.pools() {
+        let _ = writeln!(out, "http_requests_shed_total{{pool=\"{}\"}} {}", pool.name, pool.shed());
+    }
+
+    family(&mut out, "mongodb_slow_queries_total", "counter", "MongoDB commands slower than SLOW_QUERY_MS.");
+    for slow in crate::repository::slow_query::counts() {
+        let _ = writeln!(
+            out,
+            "mongodb_slow_queries_total{{collection=\"{}\",operation=\"{}\"}} {}",
+            label(&slow.collection), label(&slow.operation), slow.count,
+        );
+    }
+    out
+}
+
+pub async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    ([(header::CONTENT_TYPE, CONTENT_TYPE)], render(&state.load))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_shed::LoadSheddingConfig;
+
+    #[test]
+    fn renders_pool_utilization() {
+        let load = LoadShedder::new(&LoadSheddingConfig::default());
+        let _permit = load.standard.try_acquire().unwrap();
+        let text = render(&load);
+        assert!(text.contains("# TYPE http_requests_in_flight gauge\n"));
+        assert!(text.contains("http_requests_in_flight{pool=\"standard\"} 1\n"));
+        assert!(text.contains("http_requests_in_flight_limit{pool=\"expensive\"} 8\n"));
+        assert!(text.contains("http_requests_shed_total{pool=\"standard\"} 0\n"));
+    }
+}
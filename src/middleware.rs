@@ -1,12 +1,18 @@
 use axum::{
-    extract::{Request, State},
-    http::header,
+    extract::{MatchedPath, OriginalUri, Request, State},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode, Uri},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use mongodb::bson::{oid::ObjectId, DateTime};
 use std::sync::Arc;
+use std::time::Instant;
+use futures_util::StreamExt;
 
 use crate::db::AppState;
+use crate::models::RequestLog;
+use crate::naming::{ApiVersion, API_VERSION_HEADER};
+use crate::repository::{AppointmentRepository, KioskDeviceRepository, ObservationRepository, RequestLogRepository, ServiceAccountRepository, UserRepository};
 use crate::response::ErrorResponse;
 use crate::services::AuthService;
 
@@ -16,6 +22,29 @@ pub struct AuthUser {
     pub id: String,
     pub email: String,
     pub name: String,
+    /// Set for patient portal tokens, which only reach this patient's data
+    pub patient_id: Option<String>,
+    /// Set for service account tokens; `id` is then the service account id
+    pub service: Option<ServiceIdentity>,
+    /// Set for kiosk tokens; `id` is then the kiosk device id
+    pub kiosk: Option<KioskIdentity>,
+}
+
+/// Client-credentials caller, see `service_scope`.
+#[derive(Clone, Debug)]
+pub struct ServiceIdentity {
+    pub scopes: Vec<String>,
+    /// Unix time the token was issued
+    pub issued_at: i64,
+}
+
+/// Paired check-in kiosk, see `kiosk_scope`.
+#[derive(Clone, Debug)]
+pub struct KioskIdentity {
+    /// Unix time the token was issued
+    pub issued_at: i64,
+    /// Organization the device is paired to; filled in by `kiosk_scope` from the device
+    pub organization_id: Option<String>,
 }
 
 /// JWT Authentication Middleware
@@ -44,10 +73,15 @@ pub async fn auth_middleware(
     match AuthService::validate_token(token) {
         Ok(claims) => {
             // Add user info to request extensions
+            let service = claims.scopes.map(|scopes| ServiceIdentity { scopes, issued_at: claims.iat as i64 });
+            let kiosk = (claims.token_type == "kiosk").then_some(KioskIdentity { issued_at: claims.iat as i64, organization_id: None });
             let auth_user = AuthUser {
                 id: claims.sub,
                 email: claims.email,
                 name: claims.name,
+                patient_id: claims.patient_id,
+                service,
+                kiosk,
             };
             request.extensions_mut().insert(auth_user);
             
@@ -55,8 +89,565 @@ pub async fn auth_middleware(
             next.run(request).await
         }
         Err(e) => {
-            ErrorResponse::unauthorized(&format!("Invalid token: {}", e)).into_response()
+            ErrorResponse::unauthorized(format!("Invalid token: {}", e)).into_response()
+        }
+    }
+}
+
+/// Where a patient-scoped token may go, decided from the method and path.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PatientAccess {
+    Allowed,
+    /// A list endpoint; the named query parameter is forced to the patient's id
+    ScopedList(&'static str),
+    Appointment(ObjectId),
+    Observation(ObjectId),
+    Denied,
+}
+
+/// Patient tokens are read-only and limited to the patient's own record, appointments and
+/// observations. Single appointments and observations still need an ownership check.
+pub fn classify_patient_request(method: &Method, path: &str, patient_id: &str) -> PatientAccess {
+    if method != Method::GET && method != Method::HEAD {
+        return PatientAccess::Denied;
+    }
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let own = |id: &str| if id == patient_id { PatientAccess::Allowed } else { PatientAccess::Denied };
+    let by_id = |id: &str, access: fn(ObjectId) -> PatientAccess| {
+        ObjectId::parse_str(id).map_or(PatientAccess::Denied, access)
+    };
+
+    match segments.as_slice() {
+        ["auth", "me"] => PatientAccess::Allowed,
+        ["patients", id, ..] | ["medical-records", id] => own(id),
+        ["appointments"] => PatientAccess::ScopedList("patient_id"),
+        ["appointments", id] | ["appointments", id, "teleconsult"] => by_id(id, PatientAccess::Appointment),
+        ["observations"] => PatientAccess::ScopedList("id_pasien"),
+        ["observations", "pasien", id, "trends", _] => own(id),
+        ["observations", id] => by_id(id, PatientAccess::Observation),
+        _ => PatientAccess::Denied,
+    }
+}
+
+/// `uri` with query parameter `name` replaced by `value`
+fn force_query_param(uri: &Uri, name: &str, value: &str) -> Option<Uri> {
+    let mut params: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty() && p.split('=').next() != Some(name))
+        .collect();
+    let forced = format!("{}={}", name, value);
+    params.push(&forced);
+    format!("{}?{}", uri.path(), params.join("&")).parse().ok()
+}
+
+/// Patient Scope Middleware
+///
+/// Must run inside `auth_middleware`. Staff tokens pass untouched; patient tokens are held to
+/// `classify_patient_request`, with list queries narrowed to the patient and single
+/// appointments or observations of other patients rejected.
+pub async fn patient_scope(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(patient_id) = request.extensions().get::<AuthUser>().and_then(|u| u.patient_id.clone()) else {
+        return next.run(request).await;
+    };
+
+    // Nested routers see a stripped path; classify the path the client asked for
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let owner = match classify_patient_request(request.method(), &path, &patient_id) {
+        PatientAccess::Allowed => return next.run(request).await,
+        PatientAccess::ScopedList(param) => {
+            let Some(uri) = force_query_param(request.uri(), param, &patient_id) else {
+                return ErrorResponse::bad_request("Invalid query string", None).into_response();
+            };
+            *request.uri_mut() = uri;
+            return next.run(request).await;
+        }
+        PatientAccess::Appointment(id) => AppointmentRepository::new(state.db.clone())
+            .find_by_id(id)
+            .await
+            .map(|a| a.map(|a| a.patient_id)),
+        PatientAccess::Observation(id) => ObservationRepository::new(state.db.clone())
+            .find_by_id(id)
+            .await
+            .map(|o| o.map(|o| o.id_pasien)),
+        PatientAccess::Denied => {
+            return ErrorResponse::forbidden("This resource is outside the patient's scope").into_response();
+        }
+    };
+
+    match owner {
+        // Missing resources fall through to the handler's 404
+        Ok(None) => next.run(request).await,
+        Ok(Some(owner)) if owner == patient_id => next.run(request).await,
+        Ok(Some(_)) => ErrorResponse::forbidden("This resource is outside the patient's scope").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to check patient scope", Some(e)).into_response(),
+    }
+}
+
+/// Kiosk tokens check patients in and show queues, and do nothing else
+pub fn kiosk_allows(method: &Method, path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match segments.as_slice() {
+        ["auth", "me"] | ["queues", _, "today"] | ["queues", _, "stream"] => method == Method::GET || method == Method::HEAD,
+        ["appointments", _, "check-in"] => method == Method::POST,
+        _ => false,
+    }
+}
+
+/// Kiosk Scope Middleware
+///
+/// Must run inside `auth_middleware`. Other callers pass through. Kiosk tokens are held to
+/// `kiosk_allows`, and the device must still be paired: revoking it, or pairing it again,
+/// cuts off tokens already handed out. The device's organization is copied onto the
+/// caller's `KioskIdentity` so check-in can keep to it.
+pub async fn kiosk_scope(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some((id, kiosk)) = request.extensions().get::<AuthUser>().and_then(|u| Some((u.id.clone(), u.kiosk.clone()?))) else {
+        return next.run(request).await;
+    };
+
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    if !kiosk_allows(request.method(), &path) {
+        return ErrorResponse::forbidden("Kiosk tokens only reach check-in and queue endpoints").into_response();
+    }
+
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::unauthorized("Invalid token subject").into_response();
+    };
+    let devices = KioskDeviceRepository::new(state.db.clone());
+    let device = match devices.find_by_id(oid).await {
+        Ok(Some(device)) => device,
+        Ok(None) => return ErrorResponse::unauthorized("Kiosk device no longer exists").into_response(),
+        Err(e) => return ErrorResponse::internal_error("Failed to resolve kiosk device", Some(e)).into_response(),
+    };
+    let current = device.revoked_at.is_none()
+        && device.paired_at.is_some_and(|at| kiosk.issued_at >= at.timestamp_millis() / 1000);
+    if !current {
+        return ErrorResponse::unauthorized("Kiosk device was revoked or paired again").into_response();
+    }
+
+    // Recorded at most once a minute so polling queue boards do not write on every request
+    let now = DateTime::now();
+    if device.last_seen_at.is_none_or(|seen| now.timestamp_millis() - seen.timestamp_millis() > 60_000) {
+        if let Err(e) = devices.touch(oid, now).await {
+            tracing::warn!(kiosk_id = %id, error = %e, "Failed to record kiosk activity");
+        }
+    }
+    if let Some(kiosk) = request.extensions_mut().get_mut::<AuthUser>().and_then(|u| u.kiosk.as_mut()) {
+        kiosk.organization_id = device.organization_id;
+    }
+    next.run(request).await
+}
+
+/// Restrict service account tokens to their scopes.
+///
+/// Must run inside `auth_middleware`. Other callers pass through. The account must still be
+/// active and the token issued after its last secret rotation, so disabling or rotating an
+/// account cuts off tokens already handed out.
+pub async fn service_scope(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some((id, service)) = request.extensions().get::<AuthUser>().and_then(|u| Some((u.id.clone(), u.service.clone()?))) else {
+        return next.run(request).await;
+    };
+
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let (resource, action) = crate::rbac::required_scope(request.method(), &path);
+    if !crate::rbac::PermissionSet::from_scopes(&service.scopes).allows(&resource, action) {
+        return ErrorResponse::forbidden(format!("Scope {}:{} required", resource, action)).into_response();
+    }
+
+    let Ok(oid) = ObjectId::parse_str(&id) else {
+        return ErrorResponse::unauthorized("Invalid token subject").into_response();
+    };
+    match ServiceAccountRepository::new(state.db.clone()).find_by_id(oid).await {
+        Ok(Some(account)) if account.active => {
+            let rotated = account.rotated_at.as_deref()
+                .and_then(|at| chrono::DateTime::parse_from_rfc3339(at).ok())
+                .is_some_and(|at| service.issued_at < at.timestamp());
+            if rotated {
+                return ErrorResponse::unauthorized("Token was issued before the client secret was rotated").into_response();
+            }
+            next.run(request).await
+        }
+        Ok(_) => ErrorResponse::unauthorized("Service account is disabled or no longer exists").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to resolve service account", Some(e)).into_response(),
+    }
+}
+
+/// Verified Email Middleware
+///
+/// Must run inside `auth_middleware`. Staff requests under a prefix of
+/// `AppConfig::email.verified_routes` need an account whose email has been verified; patient
+/// tokens are not user accounts and are left to `patient_scope`.
+pub async fn require_verified_email(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let routes = &state.config.email.verified_routes;
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    if !routes.iter().any(|prefix| crate::config::path_has_prefix(&path, prefix)) {
+        return next.run(request).await;
+    }
+
+    let Some(user) = request.extensions().get::<AuthUser>().filter(|u| u.patient_id.is_none() && u.service.is_none() && u.kiosk.is_none()) else {
+        return next.run(request).await;
+    };
+    let Ok(user_id) = ObjectId::parse_str(&user.id) else {
+        return ErrorResponse::unauthorized("Invalid token subject").into_response();
+    };
+
+    match UserRepository::new(state.db.clone()).find_by_id(user_id).await {
+        Ok(Some(account)) if account.email_verified_at.is_some() => next.run(request).await,
+        Ok(Some(_)) => ErrorResponse::new(
+            StatusCode::FORBIDDEN,
+            "Email not verified",
+            "EMAIL_NOT_VERIFIED",
+            Some("Verify your email with the link sent on registration, or request a new one with POST /auth/resend-verification".to_string()),
+        ).into_response(),
+        Ok(None) => ErrorResponse::unauthorized("User no longer exists").into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to load user", Some(e)).into_response(),
+    }
+}
+
+/// User id of a valid bearer token, for logs and reports outside the auth layer
+fn bearer_user_id(headers: &axum::http::HeaderMap) -> Option<String> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| AuthService::validate_token(token).ok())
+        .map(|claims| claims.sub)
+}
+
+/// Error Reporting Middleware
+///
+/// When reporting is enabled, tags the request with its route, user and request id (taken
+/// from `X-Request-Id` or generated, and echoed back), and reports 5xx responses and panics.
+pub async fn report_errors(request: Request, next: Next) -> Response {
+    if !crate::error_reporting::is_enabled() {
+        return next.run(request).await;
+    }
+
+    let supplied = request.headers().get(crate::error_reporting::REQUEST_ID_HEADER).and_then(|v| v.to_str().ok());
+    let context = crate::error_reporting::RequestContext {
+        request_id: crate::error_reporting::request_id(supplied),
+        method: request.method().to_string(),
+        route: request.extensions().get::<MatchedPath>().map_or("unmatched", MatchedPath::as_str).to_string(),
+        user_id: bearer_user_id(request.headers()),
+    };
+
+    let mut response = crate::error_reporting::with_context(&context, async {
+        let response = next.run(request).await;
+        if response.status().is_server_error() {
+            let reported = response.extensions().get::<crate::error_reporting::ReportedError>();
+            crate::error_reporting::report(&context, response.status().as_u16(), reported);
         }
+        response
+    }).await;
+
+    if let Ok(value) = HeaderValue::from_str(&context.request_id) {
+        response.headers_mut().insert(crate::error_reporting::REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Load Shedding Middleware
+///
+/// Admits the request through its pool in `AppState::load`, or refuses it with 503 and
+/// `Retry-After` when the pool is full. Expensive routes keep their slot until a streamed
+/// body has been sent.
+pub async fn shed_load(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(pool) = state.load.pool_for(request.uri().path()).cloned() else {
+        return next.run(request).await;
+    };
+    let Some(permit) = pool.try_acquire() else {
+        let retry_after = state.load.config.retry_after_secs;
+        let mut response = ErrorResponse::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Server is busy",
+            "OVERLOADED",
+            Some(format!("Too many {} requests in progress; retry in {} seconds", pool.name, retry_after)),
+        ).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        return response;
+    };
+
+    let response = next.run(request).await;
+    if !Arc::ptr_eq(&pool, &state.load.expensive) {
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let body = body.into_data_stream().map(move |chunk| {
+        let _held = &permit;
+        chunk
+    });
+    Response::from_parts(parts, axum::body::Body::from_stream(body))
+}
+
+/// Security Headers Middleware
+///
+/// Adds the headers from `AppConfig::security_headers` to every response, leaving any a
+/// handler already set.
+pub async fn security_headers(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    for (name, value) in state.config.security_headers.headers_for(&path) {
+        let name = HeaderName::from_static(name);
+        if headers.contains_key(&name) {
+            continue;
+        }
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+    response
+}
+
+/// API Naming Middleware
+///
+/// Answers `API-Version: 1` requests with the legacy response keys from
+/// `crate::naming::LEGACY_RESPONSE_KEYS` and a `Deprecation` header; every response echoes
+/// the version it was served in.
+pub async fn api_naming(request: Request, next: Next) -> Response {
+    let version = ApiVersion::from_headers(request.headers());
+    let renames = match version {
+        ApiVersion::V1 => crate::naming::legacy_keys(request.uri().path()),
+        ApiVersion::V2 => Vec::new(),
+    };
+    let mut response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !renames.is_empty() && is_json {
+        let (mut parts, body) = response.into_parts();
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => return ErrorResponse::internal_error("Failed to read response body", Some(e.to_string())).into_response(),
+        };
+        let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+            Ok(mut value) => {
+                crate::naming::rename_keys(&mut value, &renames);
+                parts.headers.remove(header::CONTENT_LENGTH);
+                axum::body::Body::from(value.to_string())
+            }
+            Err(_) => axum::body::Body::from(bytes),
+        };
+        response = Response::from_parts(parts, body);
+    }
+
+    let headers = response.headers_mut();
+    headers.insert(HeaderName::from_static(API_VERSION_HEADER), HeaderValue::from_static(version.as_str()));
+    if version == ApiVersion::V1 {
+        headers.insert(HeaderName::from_static("deprecation"), HeaderValue::from_static("true"));
+    }
+    response
+}
+
+/// Error code for an error response that carries none, as the helpers of `ErrorResponse` name them
+fn error_code(status: StatusCode) -> String {
+    match status {
+        StatusCode::UNPROCESSABLE_ENTITY => "VALIDATION_ERROR".to_string(),
+        _ if status.is_server_error() => "INTERNAL_ERROR".to_string(),
+        _ => status.canonical_reason().unwrap_or("ERROR").to_uppercase().replace([' ', '-'], "_"),
+    }
+}
+
+/// Error Envelope Middleware
+///
+/// Errors answered outside the handlers, such as body, path and query extractor rejections or
+/// unknown routes and methods, come as plain text or empty; this wraps them in the
+/// `ErrorResponse` envelope the API documents, keeping their text as the details.
+pub async fn json_errors(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if is_json || !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let text = match axum::body::to_bytes(body, 64 * 1024).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).trim().to_string(),
+        Err(_) => String::new(),
+    };
+    let message = status.canonical_reason().unwrap_or("Error");
+    let details = (!text.is_empty()).then_some(text);
+    let mut wrapped = ErrorResponse::new(status, message, error_code(status), details).into_response();
+    for (name, value) in parts.headers.iter().filter(|(name, _)| *name != header::CONTENT_TYPE && *name != header::CONTENT_LENGTH) {
+        wrapped.headers_mut().insert(name.clone(), value.clone());
+    }
+    wrapped
+}
+
+/// Debug Capture Middleware
+///
+/// For routes listed in `AppConfig::request_log`, stores the redacted request and response
+/// bodies in `request_logs` without delaying the response. Only JSON and form bodies are
+/// buffered, so uploads and event streams pass through untouched.
+pub async fn request_capture(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let config = &state.config.request_log;
+    let path = request.uri().path().to_string();
+    if !config.captures(&path) {
+        return next.run(request).await;
+    }
+
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let query = request.uri().query().map(|q| config.redact_form(q));
+    let user_id = bearer_user_id(request.headers());
+
+    let content_type = |headers: &axum::http::HeaderMap| {
+        headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string)
+    };
+
+    let request_type = content_type(request.headers());
+    let (request, request_body) = if crate::request_log::is_capturable(request_type.as_deref()) {
+        let (parts, body) = request.into_parts();
+        let Ok(bytes) = axum::body::to_bytes(body, crate::request_log::CAPTURE_BUFFER_LIMIT).await else {
+            return ErrorResponse::new(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large", "PAYLOAD_TOO_LARGE", None).into_response();
+        };
+        let sanitized = config.sanitize_body(request_type.as_deref(), &bytes);
+        (Request::from_parts(parts, axum::body::Body::from(bytes)), sanitized)
+    } else {
+        (request, None)
+    };
+
+    let response = next.run(request).await;
+
+    let response_type = content_type(response.headers());
+    let (response, response_body) = if crate::request_log::is_capturable(response_type.as_deref()) {
+        let (parts, body) = response.into_parts();
+        match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => {
+                let sanitized = config.sanitize_body(response_type.as_deref(), &bytes);
+                (Response::from_parts(parts, axum::body::Body::from(bytes)), sanitized)
+            }
+            Err(e) => return ErrorResponse::internal_error("Failed to read response body", Some(e.to_string())).into_response(),
+        }
+    } else {
+        (response, None)
+    };
+
+    let log = RequestLog {
+        id: None,
+        method,
+        path,
+        query,
+        status: response.status().as_u16() as i32,
+        duration_ms: started.elapsed().as_millis() as i64,
+        user_id,
+        request_body,
+        response_body,
+        created_at: DateTime::now(),
+    };
+    let repo = RequestLogRepository::new(state.db.clone());
+    tokio::spawn(async move {
+        if let Err(e) = repo.create(log).await {
+            tracing::error!(error = %e, "Failed to store request log");
+        }
+    });
+
+    response
+}
+
+/// Request Timeout Middleware
+///
+/// Races the handler against the route's budget from `AppConfig::timeouts`. When the
+/// budget runs out the handler future is dropped, which cancels any in-flight repository
+/// call, and a 504 is returned with the elapsed time.
+pub async fn timeout_middleware(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(budget) = state.config.timeouts.budget_for(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let started = Instant::now();
+    tokio::select! {
+        response = next.run(request) => response,
+        _ = tokio::time::sleep(budget) => ErrorResponse::new(
+            StatusCode::GATEWAY_TIMEOUT,
+            "Request timed out",
+            "REQUEST_TIMEOUT",
+            Some(format!(
+                "Request exceeded its {} ms budget after {} ms",
+                budget.as_millis(),
+                started.elapsed().as_millis()
+            )),
+        ).into_response(),
+    }
+}
+
+/// Admin Authorization Middleware
+///
+/// Must run inside `auth_middleware`. Rejects callers whose roles are not granted the
+/// `admin:access` permission.
+pub async fn require_admin(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(user) = request.extensions().get::<AuthUser>() else {
+        return ErrorResponse::unauthorized("Authentication required").into_response();
+    };
+
+    let (resource, action) = crate::rbac::ADMIN_ACCESS;
+    match crate::rbac::load_permissions(&state.db, &user.id).await {
+        Ok((_, permissions)) if permissions.allows(resource, action) => next.run(request).await,
+        Ok(_) => ErrorResponse::forbidden(format!("Permission {}:{} required", resource, action)).into_response(),
+        Err(e) => ErrorResponse::internal_error("Failed to resolve user permissions", Some(e)).into_response(),
     }
 }
 
@@ -85,3 +676,52 @@ pub async fn require_auth(
     
     next.run(request).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATIENT: &str = "65f0c0ffee0000000000abcd";
+
+    #[test]
+    fn patient_tokens_reach_only_their_own_records() {
+        assert_eq!(classify_patient_request(&Method::GET, "/patients/65f0c0ffee0000000000abcd/growth", PATIENT), PatientAccess::Allowed);
+        assert_eq!(classify_patient_request(&Method::GET, "/patients/65f0c0ffee0000000000ffff/growth", PATIENT), PatientAccess::Denied);
+        assert_eq!(classify_patient_request(&Method::GET, "/observations/pasien/65f0c0ffee0000000000abcd/trends/8867-4", PATIENT), PatientAccess::Allowed);
+        assert_eq!(classify_patient_request(&Method::POST, "/patients/65f0c0ffee0000000000abcd/merge", PATIENT), PatientAccess::Denied);
+        assert_eq!(classify_patient_request(&Method::GET, "/users", PATIENT), PatientAccess::Denied);
+    }
+
+    #[test]
+    fn lists_are_scoped_and_items_need_an_ownership_check() {
+        assert_eq!(classify_patient_request(&Method::GET, "/appointments", PATIENT), PatientAccess::ScopedList("patient_id"));
+        assert_eq!(classify_patient_request(&Method::GET, "/observations/", PATIENT), PatientAccess::ScopedList("id_pasien"));
+        let id = ObjectId::parse_str(PATIENT).unwrap();
+        assert_eq!(classify_patient_request(&Method::GET, "/appointments/65f0c0ffee0000000000abcd/teleconsult", PATIENT), PatientAccess::Appointment(id));
+        assert_eq!(classify_patient_request(&Method::GET, "/appointments/waitlist", PATIENT), PatientAccess::Denied);
+    }
+
+    #[test]
+    fn kiosk_tokens_reach_only_check_in_and_queues() {
+        assert!(kiosk_allows(&Method::POST, "/appointments/65f0c0ffee0000000000abcd/check-in"));
+        assert!(kiosk_allows(&Method::GET, "/queues/65f0c0ffee0000000000abcd/today"));
+        assert!(!kiosk_allows(&Method::POST, "/queues/65f0c0ffee0000000000abcd/next"));
+        assert!(!kiosk_allows(&Method::GET, "/appointments/65f0c0ffee0000000000abcd"));
+        assert!(!kiosk_allows(&Method::GET, "/patients/65f0c0ffee0000000000abcd/growth"));
+    }
+
+    #[test]
+    fn bare_errors_get_the_codes_of_the_error_helpers() {
+        assert_eq!(error_code(StatusCode::BAD_REQUEST), "BAD_REQUEST");
+        assert_eq!(error_code(StatusCode::UNSUPPORTED_MEDIA_TYPE), "UNSUPPORTED_MEDIA_TYPE");
+        assert_eq!(error_code(StatusCode::UNPROCESSABLE_ENTITY), "VALIDATION_ERROR");
+        assert_eq!(error_code(StatusCode::BAD_GATEWAY), "INTERNAL_ERROR");
+    }
+
+    #[test]
+    fn forced_query_params_replace_client_values() {
+        let uri: Uri = "/appointments?page=2&patient_id=other".parse().unwrap();
+        let forced = force_query_param(&uri, "patient_id", PATIENT).unwrap();
+        assert_eq!(forced.to_string(), format!("/appointments?page=2&patient_id={}", PATIENT));
+    }
+}
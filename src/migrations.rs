@@ -0,0 +1,704 @@
+use futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOptions, IndexOptions},
+    Database, IndexModel,
+};
+
+/// An index the application expects to exist on a collection.
+pub struct IndexDefinition {
+    pub collection: &'static str,
+    pub name: &'static str,
+    pub keys: Document,
+    pub unique: bool,
+    /// Only documents matching this filter are indexed, e.g. to keep one active entry unique
+    pub partial: Option<Document>,
+}
+
+/// All indexes managed by the migration runner.
+pub fn index_definitions() -> Vec<IndexDefinition> {
+    vec![
+        // Full-text indexes backing GET /search
+        IndexDefinition {
+            collection: "medical_records",
+            name: "medical_records_text",
+            keys: doc! { "name": "text", "nik": "text", "nrme": "text" },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "practitioners",
+            name: "practitioners_text",
+            keys: doc! { "name": "text", "specialization": "text", "nip": "text" },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "medicines",
+            name: "medicines_text",
+            keys: doc! { "tradeName": "text", "batchNumber": "text", "manufacturer": "text" },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "appointments",
+            name: "appointments_text",
+            keys: doc! { "date": "text", "time": "text", "status": "text" },
+            unique: false,
+            partial: None,
+        },
+        // Queue lookups for check-in, call-next and the display board
+        IndexDefinition {
+            collection: "appointments",
+            name: "appointments_queue",
+            keys: doc! { "doctorId": 1, "date": 1, "queueNumber": 1 },
+            unique: false,
+            partial: None,
+        },
+        // The no-show sweep looks for scheduled appointments that have started
+        IndexDefinition {
+            collection: "appointments",
+            name: "appointments_no_show",
+            keys: doc! { "status": 1, "startsAt": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Exact matches for GET /lookup/barcode/:value
+        IndexDefinition {
+            collection: "medicines",
+            name: "medicines_batch_number",
+            keys: doc! { "batchNumber": 1 },
+            unique: false,
+            partial: None,
+        },
+        // GET /sync/reference, see `crate::sync`
+        IndexDefinition {
+            collection: "codes",
+            name: "codes_updated_at",
+            keys: doc! { "updated_at": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "interpretations",
+            name: "interpretations_updated_at",
+            keys: doc! { "updated_at": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "regions",
+            name: "regions_updated_at",
+            keys: doc! { "updated_at": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "tombstones",
+            name: "tombstones_deleted_at",
+            keys: doc! { "deleted_at": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Clinical push and pull, see `crate::sync`
+        IndexDefinition {
+            collection: "notes",
+            name: "notes_updated_at",
+            keys: doc! { "updatedAt": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "notes",
+            name: "notes_deleted_at",
+            keys: doc! { "deletedAt": 1, "_id": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "sync_mutations",
+            name: "sync_mutations_client_id",
+            keys: doc! { "client_id": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "sync_conflicts",
+            name: "sync_conflicts_user_status",
+            keys: doc! { "user_id": 1, "status": 1, "created_at": -1 },
+            unique: false,
+            partial: None,
+        },
+        // POST /codes/validate, see `crate::code_cache`
+        IndexDefinition {
+            collection: "codes",
+            name: "codes_system_code",
+            keys: doc! { "system": 1, "code": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "kits",
+            name: "kits_code",
+            keys: doc! { "code": 1 },
+            unique: false,
+            partial: None,
+        },
+        // GET /kits/nearby and /kits/map-clusters
+        IndexDefinition {
+            collection: "kits",
+            name: "kits_location",
+            keys: doc! { "location": "2dsphere" },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "organizations",
+            name: "organizations_location",
+            keys: doc! { "location": "2dsphere" },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "medical_records",
+            name: "medical_records_nrme",
+            keys: doc! { "nrme": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Starting an opname looks for one still counting
+        IndexDefinition {
+            collection: "stock_opnames",
+            name: "stock_opnames_status",
+            keys: doc! { "status": 1, "startedAt": -1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "stock_movements",
+            name: "stock_movements_medicine",
+            keys: doc! { "medicineId": 1, "createdAt": -1 },
+            unique: false,
+            partial: None,
+        },
+        // One permission per resource and action, granted once per role
+        IndexDefinition {
+            collection: "permissions",
+            name: "permissions_key",
+            keys: doc! { "resource": 1, "action": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "role_permissions",
+            name: "role_permissions_key",
+            keys: doc! { "role_code": 1, "resource": 1, "action": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "service_accounts",
+            name: "service_accounts_client_id",
+            keys: doc! { "client_id": 1 },
+            unique: true,
+            partial: None,
+        },
+        // Pairing looks devices up by the hash of the code typed on the kiosk
+        IndexDefinition {
+            collection: "kiosk_devices",
+            name: "kiosk_devices_pairing_code",
+            keys: doc! { "pairingCodeHash": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "feature_flags",
+            name: "feature_flags_key",
+            keys: doc! { "key": 1 },
+            unique: true,
+            partial: None,
+        },
+        // One snapshot per note version
+        IndexDefinition {
+            collection: "note_versions",
+            name: "note_versions_key",
+            keys: doc! { "noteId": 1, "version": 1 },
+            unique: true,
+            partial: None,
+        },
+        // One snapshot per file version
+        IndexDefinition {
+            collection: "file_versions",
+            name: "file_versions_key",
+            keys: doc! { "fileId": 1, "version": 1 },
+            unique: true,
+            partial: None,
+        },
+        // A patient's observations, also refreshed by `crate::denormalize`
+        IndexDefinition {
+            collection: "observations",
+            name: "observations_patient",
+            keys: doc! { "id_pasien": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Duplicate checks and a user's role lookups
+        IndexDefinition {
+            collection: "user_roles",
+            name: "user_roles_assignment",
+            keys: doc! { "user._id": 1, "role.code": 1, "organisasi._id": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Organization member listings
+        IndexDefinition {
+            collection: "user_roles",
+            name: "user_roles_organization",
+            keys: doc! { "organisasi._id": 1, "user.nama.nama_depan": 1 },
+            unique: false,
+            partial: None,
+        },
+        // One running total per uploader and organization
+        IndexDefinition {
+            collection: "storage_usage",
+            name: "storage_usage_owner",
+            keys: doc! { "scope": 1, "ownerId": 1 },
+            unique: true,
+            partial: None,
+        },
+        // A record's change history, newest first
+        IndexDefinition {
+            collection: "medical_record_changes",
+            name: "medical_record_changes_record",
+            keys: doc! { "recordId": 1, "createdAt": -1 },
+            unique: false,
+            partial: None,
+        },
+        // Staff review of self-registered patients
+        IndexDefinition {
+            collection: "medical_records",
+            name: "medical_records_self_registration",
+            keys: doc! { "selfRegistration.status": 1, "selfRegistration.registeredAt": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Lookup by normalized phone number
+        IndexDefinition {
+            collection: "medical_records",
+            name: "medical_records_hp",
+            keys: doc! { "hp": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "users",
+            name: "users_phone",
+            keys: doc! { "phone": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Versions are numbered per organization
+        IndexDefinition {
+            collection: "price_lists",
+            name: "price_lists_version",
+            keys: doc! { "organizationId": 1, "version": 1 },
+            unique: true,
+            partial: None,
+        },
+        // Versions are numbered per insurance
+        IndexDefinition {
+            collection: "insurance_versions",
+            name: "insurance_versions_version",
+            keys: doc! { "insuranceId": 1, "version": 1 },
+            unique: true,
+            partial: None,
+        },
+        // Corporate clients: codes per organization, one roster entry per patient and client,
+        // coverage lookups by patient, and one statement per client and month
+        IndexDefinition {
+            collection: "corporate_clients",
+            name: "corporate_clients_code",
+            keys: doc! { "organizationId": 1, "code": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "corporate_employees",
+            name: "corporate_employees_patient",
+            keys: doc! { "clientId": 1, "patientId": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "corporate_employees",
+            name: "corporate_employees_coverage",
+            keys: doc! { "patientId": 1, "startDate": -1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "corporate_statements",
+            name: "corporate_statements_month",
+            keys: doc! { "clientId": 1, "month": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "invoices",
+            name: "invoices_corporate_client",
+            keys: doc! { "corporate.clientId": 1, "serviceDate": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Daily settlement and shift reconciliation
+        IndexDefinition {
+            collection: "payments",
+            name: "payments_settlement",
+            keys: doc! { "settlementDate": 1, "organizationId": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Service revenue and utilization statistics
+        IndexDefinition {
+            collection: "invoices",
+            name: "invoices_service_date",
+            keys: doc! { "serviceDate": 1, "organizationId": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "payments",
+            name: "payments_shift",
+            keys: doc! { "shiftId": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "payments",
+            name: "payments_invoice",
+            keys: doc! { "invoiceId": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "gateway_transactions",
+            name: "gateway_transactions_invoice",
+            keys: doc! { "invoiceId": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Due pending entries, claimed by the outbox relay
+        IndexDefinition {
+            collection: "outbox",
+            name: "outbox_status_next_attempt",
+            keys: doc! { "status": 1, "nextAttemptAt": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Due report schedules, swept by the report scheduler
+        IndexDefinition {
+            collection: "report_schedules",
+            name: "report_schedules_due",
+            keys: doc! { "active": 1, "nextRunAt": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Beds of a ward, grouped for occupancy
+        IndexDefinition {
+            collection: "beds",
+            name: "beds_ward_status",
+            keys: doc! { "wardId": 1, "status": 1 },
+            unique: false,
+            partial: None,
+        },
+        // A patient's active admission
+        IndexDefinition {
+            collection: "admissions",
+            name: "admissions_patient_status",
+            keys: doc! { "patientId": 1, "status": 1 },
+            unique: false,
+            partial: None,
+        },
+        // Program codes, one active enrollment per patient and program, and a program's cohort
+        IndexDefinition {
+            collection: "programs",
+            name: "programs_code",
+            keys: doc! { "code": 1 },
+            unique: true,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "program_enrollments",
+            name: "program_enrollments_active",
+            keys: doc! { "programId": 1, "patientId": 1 },
+            unique: true,
+            partial: Some(doc! { "status": "active" }),
+        },
+        IndexDefinition {
+            collection: "program_enrollments",
+            name: "program_enrollments_program",
+            keys: doc! { "programId": 1, "status": 1, "enrolledAt": -1 },
+            unique: false,
+            partial: None,
+        },
+        // Latest code and hourly rate limit per OTP subject
+        IndexDefinition {
+            collection: "otp_codes",
+            name: "otp_codes_subject",
+            keys: doc! { "purpose": 1, "subject": 1, "created_at": -1 },
+            unique: false,
+            partial: None,
+        },
+        // A patient's links from either side, and their guardians
+        IndexDefinition {
+            collection: "patient_relationships",
+            name: "patient_relationships_patient",
+            keys: doc! { "patientId": 1, "guardian": 1 },
+            unique: false,
+            partial: None,
+        },
+        IndexDefinition {
+            collection: "patient_relationships",
+            name: "patient_relationships_related",
+            keys: doc! { "relatedPatientId": 1 },
+            unique: false,
+            partial: None,
+        },
+        // /doctors and /nurses views filtered by status
+        IndexDefinition {
+            collection: "practitioners",
+            name: "practitioners_type_status",
+            keys: doc! { "type": 1, "status": 1 },
+            unique: false,
+            partial: None,
+        },
+    ]
+}
+
+/// Create every index from `index_definitions`. Creating an existing index is a no-op in MongoDB,
+/// so this is safe to run on every startup.
+pub async fn run(db: &Database) -> Result<(), String> {
+    for definition in index_definitions() {
+        create_index(db, &definition).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn create_index(db: &Database, definition: &IndexDefinition) -> Result<(), String> {
+    let options = IndexOptions::builder()
+        .name(definition.name.to_string())
+        .unique(definition.unique)
+        .partial_filter_expression(definition.partial.clone())
+        .build();
+    let model = IndexModel::builder()
+        .keys(definition.keys.clone())
+        .options(options)
+        .build();
+
+    db.collection::<Document>(definition.collection)
+        .create_index(model, None)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to create index {} on {}: {}", definition.name, definition.collection, e))
+}
+
+/// Timestamp fields stored as BSON dates, per collection. `request_logs` is left out: it is
+/// capped, so its documents cannot change size, and it ages out on its own.
+pub const TIMESTAMP_FIELDS: &[(&str, &[&str])] = &[
+    ("appointment_series", &["created_at", "updated_at"]),
+    ("audit_logs", &["created_at"]),
+    ("child_codes", &["created_at", "updated_at"]),
+    ("codes", &["created_at", "updated_at"]),
+    ("feature_flags", &["created_at", "updated_at"]),
+    ("files", &["createdAt"]),
+    ("firmware", &["created_at", "updated_at"]),
+    ("interpretations", &["created_at", "updated_at"]),
+    ("jobs", &["created_at", "updated_at"]),
+    ("kits", &["created_at", "updated_at"]),
+    ("notifications", &["created_at"]),
+    ("observations", &["created_at", "updated_at"]),
+    ("organizations", &["created_at", "updated_at"]),
+    ("otp_codes", &["created_at"]),
+    ("permissions", &["created_at", "updated_at"]),
+    ("reviews", &["created_at"]),
+    ("role_permissions", &["created_at"]),
+    ("service_accounts", &["created_at", "updated_at"]),
+    ("teleconsult_sessions", &["created_at"]),
+    ("user_roles", &["created_at", "updated_at"]),
+    ("users", &["createdAt", "updatedAt"]),
+    ("waitlist", &["created_at", "updated_at"]),
+];
+
+/// `$set` stage parsing a string field into a date, leaving values it cannot parse untouched.
+fn date_from_string_stage(field: &str, format: Option<(&str, &str)>) -> Document {
+    let mut parse = doc! { "dateString": format!("${}", field), "onError": format!("${}", field) };
+    if let Some((format, timezone)) = format {
+        parse.insert("format", format);
+        parse.insert("timezone", timezone);
+    }
+    doc! { "$set": { field: { "$dateFromString": parse } } }
+}
+
+/// Convert string timestamps in `TIMESTAMP_FIELDS` to BSON dates. Legacy `%Y-%m-%d %H:%M:%S`
+/// values were written in the server's local time and are read in its current offset; RFC 3339
+/// values carry their own. Only string values are touched, so reruns convert nothing.
+pub async fn migrate_timestamps(db: &Database) -> Result<u64, String> {
+    let offset = chrono::Local::now().format("%:z").to_string();
+    let mut converted = 0;
+
+    for (collection, fields) in TIMESTAMP_FIELDS {
+        let collection = db.collection::<Document>(collection);
+        for field in *fields {
+            for format in [Some(("%Y-%m-%d %H:%M:%S", offset.as_str())), None] {
+                let result = collection
+                    .update_many(doc! { *field: { "$type": "string" } }, vec![date_from_string_stage(field, format)], None)
+                    .await
+                    .map_err(|e| format!("Failed to convert {}.{}: {}", collection.name(), field, e))?;
+                converted += result.modified_count;
+            }
+        }
+    }
+
+    Ok(converted)
+}
+
+/// Give reference documents stored without `updated_at` the current time, so the sync feeds of
+/// `crate::sync` can page through them. Reruns find nothing to set.
+pub async fn migrate_sync_timestamps(db: &Database) -> Result<u64, String> {
+    let mut backfilled = 0;
+    for collection in crate::sync::REFERENCE_COLLECTIONS {
+        let result = db.collection::<Document>(collection)
+            .update_many(doc! { "updated_at": null }, vec![doc! { "$set": { "updated_at": "$$NOW" } }], None)
+            .await
+            .map_err(|e| format!("Failed to backfill {}.updated_at: {}", collection, e))?;
+        backfilled += result.modified_count;
+    }
+    Ok(backfilled)
+}
+
+/// Backfill `startsAt` and `timezone` of appointments booked before they were stored, reading
+/// `date` and `time` in the default clinic zone. Unparseable slots get a null `startsAt`, so
+/// reruns skip them too.
+pub async fn migrate_appointment_instants(db: &Database, timezone: &crate::timezone::ClinicTimezone) -> Result<u64, String> {
+    let parse = doc! {
+        "$dateFromString": {
+            "dateString": { "$concat": ["$date", " ", { "$substrCP": ["$time", 0, 5] }] },
+            "format": "%Y-%m-%d %H:%M",
+            "timezone": timezone.utc_offset(),
+            "onError": null,
+            "onNull": null,
+        }
+    };
+    let pipeline = vec![doc! {
+        "$set": {
+            "startsAt": parse,
+            "timezone": { "$ifNull": ["$timezone", timezone.name()] },
+        }
+    }];
+
+    db.collection::<Document>("appointments")
+        .update_many(doc! { "startsAt": { "$exists": false } }, pipeline, None)
+        .await
+        .map(|result| result.modified_count)
+        .map_err(|e| format!("Failed to backfill appointments.startsAt: {}", e))
+}
+
+/// Rewrite stored phone numbers in E.164 form, see `crate::phone`. Numbers that do not
+/// parse are left as they are; normalized ones no longer match the filter on reruns.
+pub async fn migrate_phone_numbers(db: &Database) -> Result<u64, String> {
+    let mut normalized = 0;
+
+    for (collection, field) in [("medical_records", "hp"), ("users", "phone")] {
+        let collection = db.collection::<Document>(collection);
+        let options = FindOptions::builder().projection(doc! { field: 1 }).build();
+        let mut cursor = collection
+            .find(doc! { field: { "$type": "string", "$not": { "$regex": "^\\+" } } }, options)
+            .await
+            .map_err(|e| format!("Failed to read {}.{}: {}", collection.name(), field, e))?;
+
+        while let Some(document) = cursor.try_next().await.map_err(|e| e.to_string())? {
+            let Some(phone) = document.get_str(field).ok().and_then(|raw| crate::phone::normalize(raw).ok()) else {
+                continue;
+            };
+            collection
+                .update_one(doc! { "_id": document.get("_id") }, doc! { "$set": { field: phone } }, None)
+                .await
+                .map_err(|e| format!("Failed to normalize {}.{}: {}", collection.name(), field, e))?;
+            normalized += 1;
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Copy `doctors` and `nurses` into `practitioners`, keeping their `_id`s so references stay
+/// valid; a doctor's `sip` becomes its `SIP` qualification. Practitioners that already exist
+/// are kept, so reruns copy nothing. The old collections are left in place.
+pub async fn migrate_practitioners(db: &Database) -> Result<u64, String> {
+    let practitioners = db.collection::<Document>("practitioners");
+    let count = || async {
+        practitioners
+            .count_documents(None, None)
+            .await
+            .map_err(|e| format!("Failed to count practitioners: {}", e))
+    };
+    let before = count().await?;
+
+    let doctor = doc! {
+        "$set": {
+            "type": "doctor",
+            "qualifications": [{ "code": crate::models::SIP, "number": "$sip" }],
+        }
+    };
+    let nurse = doc! { "$set": { "type": "nurse", "qualifications": [] } };
+    for (collection, stage) in [("doctors", doctor), ("nurses", nurse)] {
+        let pipeline = vec![
+            stage,
+            doc! { "$unset": "sip" },
+            doc! { "$merge": { "into": "practitioners", "on": "_id", "whenMatched": "keepExisting", "whenNotMatched": "insert" } },
+        ];
+        db.collection::<Document>(collection)
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| format!("Failed to copy {} into practitioners: {}", collection, e))?;
+    }
+
+    Ok(count().await?.saturating_sub(before))
+}
+
+/// Make insurances created before versioning their own version 1, in effect since 1970-01-01
+/// with no coverage until their terms are set, so every earlier invoice date resolves to it.
+/// Versioned insurances no longer match the filter on reruns.
+pub async fn migrate_insurance_versions(db: &Database) -> Result<u64, String> {
+    let unversioned = doc! { "version": { "$exists": false } };
+    let pipeline = vec![
+        doc! { "$match": unversioned.clone() },
+        doc! {
+            "$project": {
+                "_id": 0,
+                "insuranceId": { "$toString": "$_id" },
+                "version": { "$literal": 1 },
+                "name": 1,
+                "type": 1,
+                "code": 1,
+                "status": 1,
+                "terms": { "$ifNull": ["$terms", { "coveragePercent": 0.0, "copay": 0.0 }] },
+                "effectiveFrom": { "$literal": "1970-01-01" },
+                "createdAt": "$$NOW",
+            }
+        },
+        doc! { "$merge": { "into": "insurance_versions", "on": ["insuranceId", "version"], "whenMatched": "keepExisting", "whenNotMatched": "insert" } },
+    ];
+    let insurances = db.collection::<Document>("insurances");
+    insurances
+        .aggregate(pipeline, None)
+        .await
+        .map_err(|e| format!("Failed to copy insurances into insurance_versions: {}", e))?;
+
+    insurances
+        .update_many(unversioned, doc! { "$set": { "version": 1, "effectiveFrom": "1970-01-01" } }, None)
+        .await
+        .map(|result| result.modified_count)
+        .map_err(|e| format!("Failed to backfill insurances.version: {}", e))
+}
@@ -0,0 +1,222 @@
+//! Mock mode for frontend development.
+//!
+//! `rme-api-rust --mock` (or `MOCK_MODE=true`) serves the whole API through
+//! `routes::create_router`, against a database of its own, `jaga_sehat_indonesia_mock`, that
+//! is dropped and seeded from `static/mock_fixtures.json` on every start, so each run begins
+//! with the same data. Fixture items are create requests with a fixed `id`, built by the
+//! services' own `build` as their create endpoints would build them. Fixture users sign in
+//! with the password `mock` and hold the `admin` role in the fixture organization. Files are
+//! kept on local disk under `STORAGE_LOCAL_DIR` instead of S3, see `crate::storage`.
+//!
+//! Mock mode still needs a MongoDB at `DATABASE_URL`, e.g. a local container. Repositories
+//! wrap MongoDB collections directly; serving the API without one would take an in-memory
+//! implementation of every repository, which is out of scope here.
+
+use chrono::Datelike;
+use mongodb::bson::{oid::ObjectId, DateTime, Document};
+use mongodb::Database;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use validator::Validate;
+use crate::dto::doctor::CreateDoctorRequest;
+use crate::dto::medical_record::CreateMedicalRecordRequest;
+use crate::dto::medicine::CreateMedicineRequest;
+use crate::models::{Organization, OrganizationEmbed, RoleCategory, RoleEmbed, User, UserBirth, UserContact, UserEmbed, UserName, UserRole};
+use crate::sequences::{SequenceGenerator, MEDICAL_RECORD};
+use crate::services::{DoctorService, MedicalRecordService, MedicineService};
+
+const FIXTURES: &str = include_str!("../static/mock_fixtures.json");
+/// Password of every fixture user
+pub const MOCK_PASSWORD: &str = "mock";
+/// Appended to the database name in mock mode
+pub const DATABASE_SUFFIX: &str = "_mock";
+
+/// Whether the server was started with `--mock` or `MOCK_MODE=true`
+pub fn enabled() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--mock")
+        || std::env::var("MOCK_MODE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+            .unwrap_or(false)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MockUser {
+    id: ObjectId,
+    email: String,
+    name: String,
+    phone: Option<String>,
+    email_verified_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MockOrganization {
+    id: ObjectId,
+    name: String,
+    timezone: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Fixtures {
+    #[serde(rename = "/admin/organizations")]
+    organizations: Vec<MockOrganization>,
+    #[serde(rename = "/users")]
+    users: Vec<MockUser>,
+    #[serde(rename = "/medical-records")]
+    medical_records: Vec<Map<String, Value>>,
+    #[serde(rename = "/doctors")]
+    doctors: Vec<Map<String, Value>>,
+    #[serde(rename = "/medicines")]
+    medicines: Vec<Map<String, Value>>,
+}
+
+/// The fixture's id and its create request, checked as the create endpoint checks it
+fn request<R: DeserializeOwned + Validate>(resource: &str, mut fixture: Map<String, Value>) -> Result<(ObjectId, R), String> {
+    let id = fixture.remove("id")
+        .and_then(|id| id.as_str().and_then(|id| ObjectId::parse_str(id).ok()))
+        .ok_or_else(|| format!("{} fixture without an id", resource))?;
+    let request: R = serde_json::from_value(Value::Object(fixture)).map_err(|e| format!("{} fixture {}: {}", resource, id, e))?;
+    request.validate().map_err(|e| format!("{} fixture {}: {}", resource, id, e))?;
+    Ok((id, request))
+}
+
+/// `item` in its stored form; see `crate::datetime` on why not `bson::to_document`
+fn stored<T: Serialize>(item: &T) -> Result<Document, String> {
+    mongodb::bson::to_raw_document_buf(item)
+        .map_err(|e| e.to_string())?
+        .to_document()
+        .map_err(|e| e.to_string())
+}
+
+/// `item` stored under `id`; models write their id as `id`, which the stored form keeps in `_id`
+fn with_id<T: Serialize>(id: ObjectId, item: &T) -> Result<Document, String> {
+    let mut document = stored(item)?;
+    document.remove("id");
+    document.insert("_id", id);
+    Ok(document)
+}
+
+fn user(user: &MockUser, password_hash: &str) -> User {
+    User {
+        id: None,
+        email: user.email.clone(),
+        password: password_hash.to_string(),
+        name: user.name.clone(),
+        phone: user.phone.clone(),
+        refresh_token: None,
+        reset_token: None,
+        reset_token_expiry: None,
+        email_verified_at: user.email_verified_at.clone(),
+        created_at: DateTime::now(),
+        updated_at: None,
+    }
+}
+
+/// `admin` in `organization`, so fixture users reach every endpoint
+fn admin_role(user: &MockUser, organization: &MockOrganization) -> UserRole {
+    let now = DateTime::now();
+    let (first, last) = user.name.split_once(' ').unwrap_or((&user.name, ""));
+    UserRole {
+        id: None,
+        role: RoleEmbed {
+            code: crate::rbac::ROLE_ADMIN.to_string(),
+            system: "mock".to_string(),
+            display: "Administrator".to_string(),
+            category: RoleCategory { code: "staff".to_string(), system: "mock".to_string(), display: "Staff".to_string(), id: organization.id.to_hex() },
+        },
+        user: UserEmbed {
+            nama: UserName { nama_depan: first.to_string(), nama_belakang: last.to_string() },
+            nik: String::new(),
+            kontak: UserContact { email: user.email.clone(), nomor_telepon: user.phone.clone().unwrap_or_default() },
+            lahir: UserBirth { tempat: String::new(), tanggal: String::new() },
+            id: user.id.to_hex(),
+        },
+        organisasi: OrganizationEmbed { name: organization.name.clone(), id: organization.id.to_hex() },
+        is_active: true,
+        valid_from: None,
+        valid_until: None,
+        updated_at: now,
+        created_at: now,
+    }
+}
+
+/// Fixture documents by collection, except medical records, whose NRMEs are taken from the
+/// sequence while seeding
+fn documents(fixtures: &Fixtures, password_hash: &str) -> Result<Vec<(&'static str, Vec<Document>)>, String> {
+    let organization = fixtures.organizations.first().ok_or("Fixtures have no organization")?;
+    let organizations = fixtures.organizations.iter()
+        .map(|o| with_id(o.id, &Organization {
+            id: None,
+            name: o.name.clone(),
+            timezone: o.timezone.clone(),
+            location: None,
+            distributor_code: None,
+            created_at: DateTime::now(),
+            updated_at: None,
+        }))
+        .collect::<Result<_, _>>()?;
+    let users = fixtures.users.iter().map(|u| with_id(u.id, &user(u, password_hash))).collect::<Result<_, _>>()?;
+    let roles = fixtures.users.iter().map(|u| stored(&admin_role(u, organization))).collect::<Result<_, _>>()?;
+    let doctors = fixtures.doctors.iter()
+        .map(|f| request::<CreateDoctorRequest>("Doctor", f.clone()).and_then(|(id, r)| with_id(id, &DoctorService::build(r))))
+        .collect::<Result<_, _>>()?;
+    let medicines = fixtures.medicines.iter()
+        .map(|f| request::<CreateMedicineRequest>("Medicine", f.clone()).and_then(|(id, r)| with_id(id, &MedicineService::build(r))))
+        .collect::<Result<_, _>>()?;
+    Ok(vec![("organizations", organizations), ("users", users), ("user_roles", roles), ("doctors", doctors), ("medicines", medicines)])
+}
+
+/// Empty the mock database, before migrations create its indexes again
+pub async fn reset(db: &Database) -> Result<(), String> {
+    db.drop(None).await.map_err(|e| format!("Dropping the mock database failed: {}", e))
+}
+
+/// Insert the fixtures into the freshly reset mock database
+pub async fn seed(db: &Database) -> Result<(), String> {
+    let fixtures: Fixtures = serde_json::from_str(FIXTURES).map_err(|e| format!("static/mock_fixtures.json: {}", e))?;
+    let password_hash = bcrypt::hash(MOCK_PASSWORD, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())?;
+    for (collection, documents) in documents(&fixtures, &password_hash)? {
+        if !documents.is_empty() {
+            db.collection::<Document>(collection).insert_many(documents, None).await
+                .map_err(|e| format!("Seeding {} failed: {}", collection, e))?;
+        }
+    }
+
+    // NRMEs come from the real sequence, so records created later continue the numbering
+    let sequences = SequenceGenerator::new(db.clone());
+    let year = chrono::Local::now().year();
+    for fixture in &fixtures.medical_records {
+        let (id, request) = request::<CreateMedicalRecordRequest>("Medical record", fixture.clone())?;
+        let nrme = sequences.next(&MEDICAL_RECORD, None, year).await?;
+        let record = MedicalRecordService::build(request, nrme).map_err(|(_, e)| e)?;
+        db.collection::<Document>("medical_records").insert_one(with_id(id, &record)?, None).await
+            .map_err(|e| format!("Seeding medical_records failed: {}", e))?;
+    }
+
+    tracing::info!(password = MOCK_PASSWORD, users = fixtures.users.len(), "Mock mode: seeded fixtures; fixture users sign in with this password");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixtures_build_into_stored_documents() {
+        let fixtures: Fixtures = serde_json::from_str(FIXTURES).unwrap();
+        let documents = documents(&fixtures, "hash").unwrap();
+
+        let doctors = &documents.iter().find(|(collection, _)| *collection == "doctors").unwrap().1;
+        assert_eq!(doctors[0].get_object_id("_id").unwrap().to_hex(), "6d6f636b0000000000000201");
+        assert!(!doctors[0].contains_key("id"));
+
+        let roles = &documents.iter().find(|(collection, _)| *collection == "user_roles").unwrap().1;
+        assert_eq!(roles.len(), fixtures.users.len());
+        assert_eq!(roles[0].get_document("role").unwrap().get_str("code").unwrap(), crate::rbac::ROLE_ADMIN);
+        assert_eq!(roles[0].get_document("user").unwrap().get_str("_id").unwrap(), "6d6f636b0000000000000001");
+
+        for fixture in &fixtures.medical_records {
+            let (_, request) = request::<CreateMedicalRecordRequest>("Medical record", fixture.clone()).unwrap();
+            assert!(MedicalRecordService::build(request, MEDICAL_RECORD.format(2026, 1)).is_ok());
+        }
+    }
+}
@@ -1,5 +1,15 @@
 use serde::{Deserialize, Serialize};
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use std::collections::BTreeMap;
+use crate::geo::GeoPoint;
+use crate::i18n::{Locale, Translations};
+use crate::refs::Ref;
+use crate::status::{
+    AdmissionStatus, AllergySeverity, AppointmentStatus, BedStatus, CorporateClientStatus, DoctorStatus, Gender, InsuranceStatus, InvoiceStatus, PaymentMethod,
+    GatewayStatus, OutboxStatus, PractitionerType, PriceItemType, PriceListStatus, RegistrationStatus, RelationshipType, ReportFormat, ReportParameterType, ReportType,
+    ShiftStatus, StockMovementType, StockOpnameStatus, SyncConflictStatus, SyncEntity, SyncOperation, SyncOutcome, ConflictResolution, ScheduledRunStatus,
+    ProgramStatus, ProgramEnrollmentStatus,
+};
 
 // Helper to serialize Option<ObjectId> as Option<String> (hex)
 fn serialize_oid_as_id<S>(oid: &Option<ObjectId>, serializer: S) -> Result<S::Ok, S::Error>
@@ -46,13 +56,196 @@ pub struct MedicalRecord {
     pub nik: String,
     pub name: String,
     pub dob: String,
-    pub gender: String,
+    pub gender: Gender,
     pub hp: String,
     pub email: String,
     #[serde(rename = "lastVisitDate")]
     pub last_visit_date: String,
+    /// When `hp` was last confirmed with a one-time code; cleared when `hp` changes
+    #[serde(rename = "phoneVerifiedAt", default, skip_serializing_if = "Option::is_none")]
+    pub phone_verified_at: Option<String>,
+    /// Stamped on every write; the `Last-Modified` of the record, see `crate::conditional`
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    /// Set on records patients created themselves; absent on those staff registered
+    #[serde(rename = "selfRegistration", default, skip_serializing_if = "Option::is_none")]
+    pub self_registration: Option<SelfRegistration>,
 }
 
+/// How a self-registered patient stands with staff, see `crate::self_registration`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SelfRegistration {
+    pub status: RegistrationStatus,
+    #[serde(rename = "registeredAt", with = "crate::datetime")]
+    pub registered_at: DateTime,
+    #[serde(rename = "confirmedBy", default, skip_serializing_if = "Option::is_none")]
+    pub confirmed_by: Option<String>,
+    #[serde(rename = "confirmedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub confirmed_at: Option<DateTime>,
+}
+
+/// The fields one update changed on a medical record; collection `medical_record_changes`.
+/// Only ever inserted, next to the update it describes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MedicalRecordChange {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "recordId")]
+    pub record_id: String,
+    /// User who made the update
+    #[serde(rename = "changedBy")]
+    pub changed_by: String,
+    #[serde(rename = "changedByName", default)]
+    pub changed_by_name: String,
+    pub fields: Vec<FieldChange>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Stored field name, e.g. `hp` or `phoneVerifiedAt`
+    pub field: String,
+    /// Absent when the field was not set before
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old: Option<mongodb::bson::Bson>,
+    /// Absent when the update cleared the field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new: Option<mongodb::bson::Bson>,
+}
+
+/// A doctor or nurse; collection `practitioners`. `Doctor` and `Nurse` are the views of one
+/// `type` served by `/doctors` and `/nurses`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Practitioner {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "type")]
+    pub practitioner_type: PractitionerType,
+    pub name: String,
+    pub nip: String,
+    pub status: DoctorStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub specialization: Option<String>,
+    /// Licenses and certificates; a doctor's practice license has code `SIP`
+    #[serde(default)]
+    pub qualifications: Vec<Qualification>,
+    /// Mean of published review ratings, kept up to date by the review service
+    #[serde(rename = "ratingAverage", default, skip_serializing_if = "Option::is_none")]
+    pub rating_average: Option<f64>,
+    #[serde(rename = "ratingCount", default)]
+    pub rating_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Qualification {
+    /// Kind of license, e.g. `SIP` (practice license) or `STR` (registration)
+    pub code: String,
+    pub number: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "validUntil", default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+}
+
+/// Qualification code of a doctor's practice license, shown as `sip` on `/doctors`
+pub const SIP: &str = "SIP";
+
+impl Practitioner {
+    /// Number of the qualification with `code`
+    pub fn qualification(&self, code: &str) -> Option<&str> {
+        self.qualifications.iter().find(|q| q.code == code).map(|q| q.number.as_str())
+    }
+
+    /// Replace the number of the qualification with `code`, adding it when missing
+    pub fn set_qualification(&mut self, code: &str, number: String) {
+        match self.qualifications.iter_mut().find(|q| q.code == code) {
+            Some(qualification) => qualification.number = number,
+            None => self.qualifications.push(Qualification { code: code.to_string(), number, issuer: None, valid_until: None }),
+        }
+    }
+
+    /// Apply the fields of the `/doctors` view, keeping the other qualifications
+    pub fn apply_doctor(&mut self, doctor: Doctor) {
+        self.name = doctor.name;
+        self.nip = doctor.nip;
+        self.status = doctor.status;
+        self.specialization = Some(doctor.specialization);
+        self.set_qualification(SIP, doctor.sip);
+    }
+
+    /// Apply the fields of the `/nurses` view
+    pub fn apply_nurse(&mut self, nurse: Nurse) {
+        self.name = nurse.name;
+        self.nip = nurse.nip;
+        self.status = DoctorStatus::from(nurse.status);
+    }
+}
+
+impl From<Doctor> for Practitioner {
+    fn from(doctor: Doctor) -> Self {
+        let mut practitioner = Practitioner {
+            id: doctor.id,
+            practitioner_type: PractitionerType::Doctor,
+            name: String::new(),
+            nip: String::new(),
+            status: DoctorStatus::Active,
+            specialization: None,
+            qualifications: Vec::new(),
+            rating_average: doctor.rating_average,
+            rating_count: doctor.rating_count,
+        };
+        practitioner.apply_doctor(doctor);
+        practitioner
+    }
+}
+
+impl From<Nurse> for Practitioner {
+    fn from(nurse: Nurse) -> Self {
+        let mut practitioner = Practitioner {
+            id: nurse.id,
+            practitioner_type: PractitionerType::Nurse,
+            name: String::new(),
+            nip: String::new(),
+            status: DoctorStatus::Active,
+            specialization: None,
+            qualifications: Vec::new(),
+            rating_average: None,
+            rating_count: 0,
+        };
+        practitioner.apply_nurse(nurse);
+        practitioner
+    }
+}
+
+impl From<Practitioner> for Doctor {
+    fn from(practitioner: Practitioner) -> Self {
+        Doctor {
+            id: practitioner.id,
+            sip: practitioner.qualification(SIP).unwrap_or_default().to_string(),
+            name: practitioner.name,
+            nip: practitioner.nip,
+            specialization: practitioner.specialization.unwrap_or_default(),
+            status: practitioner.status,
+            rating_average: practitioner.rating_average,
+            rating_count: practitioner.rating_count,
+        }
+    }
+}
+
+impl From<Practitioner> for Nurse {
+    fn from(practitioner: Practitioner) -> Self {
+        Nurse {
+            id: practitioner.id,
+            name: practitioner.name,
+            nip: practitioner.nip,
+            status: practitioner.status.as_str().to_string(),
+        }
+    }
+}
+
+/// `/doctors` view of a `Practitioner` of type `doctor`
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Doctor {
     #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
@@ -61,9 +254,103 @@ pub struct Doctor {
     pub nip: String,
     pub sip: String,
     pub specialization: String,
+    pub status: DoctorStatus,
+    /// Mean of published review ratings, kept up to date by the review service
+    #[serde(rename = "ratingAverage", default, skip_serializing_if = "Option::is_none")]
+    pub rating_average: Option<f64>,
+    #[serde(rename = "ratingCount", default)]
+    pub rating_count: i64,
+}
+
+/// Patient feedback on a completed appointment; collection `reviews`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Review {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: String,
+    #[serde(rename = "doctorId")]
+    pub doctor_id: String,
+    #[serde(rename = "appointmentId")]
+    pub appointment_id: String,
+    /// 1 to 5
+    pub rating: i32,
+    pub comment: Option<String>,
+    /// `published` or `hidden`; only published reviews count towards the doctor's rating
     pub status: String,
+    pub moderation_note: Option<String>,
+    pub moderated_by: Option<String>,
+    pub moderated_at: Option<String>,
+    pub created_by: String,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Subjective, objective, assessment and plan sections of a clinical note
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SoapSections {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subjective: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assessment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
 }
 
+/// Clinical note on a medical record; collection `notes`. Holds the current version, every
+/// version including the first is kept in `note_versions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Note {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "medicalRecordId")]
+    pub medical_record_id: Ref<MedicalRecord>,
+    /// User who created the note
+    #[serde(rename = "authorId")]
+    pub author_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soap: Option<SoapSections>,
+    /// Number of the current version, starting at 1
+    pub version: i32,
+    #[serde(rename = "updatedBy")]
+    pub updated_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", with = "crate::datetime")]
+    pub updated_at: DateTime,
+    /// Notes are only ever soft-deleted, see `crate::delete_policy`
+    #[serde(rename = "deletedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub deleted_at: Option<DateTime>,
+    #[serde(rename = "deletedBy", default, skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<String>,
+    #[serde(rename = "deleteReason", default, skip_serializing_if = "Option::is_none")]
+    pub delete_reason: Option<String>,
+}
+
+/// Immutable snapshot of a note as written by one edit; collection `note_versions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NoteVersion {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "noteId")]
+    pub note_id: String,
+    pub version: i32,
+    /// User who wrote this version
+    #[serde(rename = "editedBy")]
+    pub edited_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub soap: Option<SoapSections>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// `/nurses` view of a `Practitioner` of type `nurse`
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Nurse {
     #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
@@ -95,17 +382,215 @@ pub struct Medicine {
     pub manufacturer: String,
 }
 
+/// Allergy or adverse reaction of a patient; collection `allergies`. `substanceCode` is
+/// compared with `Medicine.masterMedicineId` when medicines are checked for a patient.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Allergy {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    #[serde(rename = "substanceCode")]
+    pub substance_code: String,
+    #[serde(rename = "substanceDisplay")]
+    pub substance_display: String,
+    pub severity: AllergySeverity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reaction: Option<String>,
+    #[serde(rename = "recordedBy")]
+    pub recorded_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A link between two patients; collection `patient_relationships`. `relationship` is what
+/// the related patient is to the patient, and `guardian` marks a related patient who acts
+/// for the patient, e.g. the parent of a child. One document covers both directions.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatientRelationship {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    #[serde(rename = "relatedPatientId")]
+    pub related_patient_id: Ref<MedicalRecord>,
+    pub relationship: RelationshipType,
+    #[serde(default)]
+    pub guardian: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(rename = "recordedBy")]
+    pub recorded_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Appointment {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    #[serde(rename = "doctorId")]
+    pub doctor_id: Ref<Doctor>,
+    pub date: String,
+    pub time: String,
+    pub status: AppointmentStatus,
+    /// Per-doctor per-day queue number, assigned at check-in
+    #[serde(rename = "queueNumber", default, skip_serializing_if = "Option::is_none")]
+    pub queue_number: Option<i64>,
+    #[serde(rename = "checkedInAt", default, skip_serializing_if = "Option::is_none")]
+    pub checked_in_at: Option<String>,
+    #[serde(rename = "calledAt", default, skip_serializing_if = "Option::is_none")]
+    pub called_at: Option<String>,
+    /// Stamped when the status becomes `completed`; the consult lasted from `calledAt` to here
+    #[serde(rename = "completedAt", default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Series this appointment was generated from
+    #[serde(rename = "seriesId", default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<String>,
+    /// `in_person` (default) or `virtual`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(rename = "organizationId", default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    /// Zone `date` and `time` are in, see `crate::timezone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// `date` and `time` as an instant
+    #[serde(rename = "startsAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub starts_at: Option<DateTime>,
+    /// Set when a delete policy cascaded to this appointment, see `crate::delete_policy`
+    #[serde(rename = "deletedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub deleted_at: Option<DateTime>,
+    /// Stamped on every write; the `Last-Modified` of the appointment, see `crate::conditional`
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Video consultation for a `virtual` appointment; collection `teleconsult_sessions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TeleconsultSession {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "appointmentId")]
+    pub appointment_id: String,
+    pub provider: String,
+    pub room: String,
+    pub meeting_url: String,
+    pub token: Option<String>,
+    pub token_expires_at: Option<String>,
+    /// `scheduled`, `active` or `ended`
+    pub status: String,
+    pub started_at: Option<String>,
+    pub ended_at: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Repeating visits expanded into concrete appointments up to `horizonDate`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AppointmentSeries {
     #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
     pub id: Option<ObjectId>,
     #[serde(rename = "patientId")]
     pub patient_id: String,
     #[serde(rename = "doctorId")]
     pub doctor_id: String,
-    pub date: String,
+    /// RRULE subset, see `crate::recurrence`
+    pub rrule: String,
+    #[serde(rename = "startDate")]
+    pub start_date: String,
     pub time: String,
+    #[serde(rename = "horizonDate")]
+    pub horizon_date: String,
+    pub status: String,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Atomic sequence backing appointment queue numbers; `_id` is `doctorId:date`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueueCounter {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub seq: i64,
+}
+
+/// Atomic counter behind `crate::sequences`; `_id` is the sequence scope, e.g. `invoice:<org>:2026`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Counter {
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub seq: i64,
+}
+
+/// A patient waiting for a slot of a doctor's day to free up; collection `waitlist`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistEntry {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: String,
+    #[serde(rename = "doctorId")]
+    pub doctor_id: String,
+    pub date: String,
+    /// Preferred time; any released slot of the day is offered when absent
+    pub time: Option<String>,
+    /// `waiting`, `held`, `booked`, `expired` or `cancelled`
     pub status: String,
+    /// Appointment holding the offered slot while `held`
+    #[serde(rename = "holdAppointmentId")]
+    pub hold_appointment_id: Option<String>,
+    #[serde(rename = "holdExpiresAt")]
+    pub hold_expires_at: Option<String>,
+    pub notified_at: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A message for a user or patient, delivered in-app; collection `notifications`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Notification {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// `patient` or `user`
+    pub recipient_type: String,
+    pub recipient_id: String,
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    pub data: mongodb::bson::Document,
+    pub read_at: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// A one-time code sent for `purpose` (`patient_login` or `phone_verification`); collection `otp_codes`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OtpCode {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub purpose: String,
+    /// What the code is bound to, such as a patient's NIK
+    pub subject: String,
+    /// bcrypt hash of the code; the code itself is never stored
+    pub code_hash: String,
+    pub attempts: i32,
+    pub expires_at: String,
+    /// Set once the code has been used; a code is accepted only once
+    #[serde(default)]
+    pub consumed_at: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -118,6 +603,295 @@ pub struct Service {
     pub sub_category: String,
 }
 
+/// Prices of an organization from `effectiveFrom` on; collection `price_lists`. Billing
+/// reads prices from the newest published list in effect on the day of service, never from
+/// the service or medicine documents.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceList {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Ref<Organization>,
+    /// Numbered per organization, starting at 1
+    pub version: i32,
+    pub name: String,
+    /// `YYYY-MM-DD`, in the clinic timezone
+    #[serde(rename = "effectiveFrom")]
+    pub effective_from: String,
+    pub status: PriceListStatus,
+    pub items: Vec<PriceListItem>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "publishedBy", default, skip_serializing_if = "Option::is_none")]
+    pub published_by: Option<String>,
+    #[serde(rename = "publishedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub published_at: Option<DateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriceListItem {
+    #[serde(rename = "itemType")]
+    pub item_type: PriceItemType,
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    pub price: f64,
+}
+
+impl PriceList {
+    pub fn price_of(&self, item_type: &PriceItemType, item_id: &str) -> Option<f64> {
+        self.items.iter().find(|item| item.item_type == *item_type && item.item_id == item_id).map(|item| item.price)
+    }
+}
+
+/// Bill of a patient's services and medicines; collection `invoices`. Lines are priced from
+/// the price list in effect on `serviceDate` when the invoice is created.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Invoice {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "organizationId")]
+    pub organization_id: Ref<Organization>,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    /// `INV-YYYY-000123`, numbered per organization and service year; absent on older invoices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "serviceDate")]
+    pub service_date: String,
+    #[serde(rename = "priceListId")]
+    pub price_list_id: String,
+    #[serde(rename = "priceListVersion")]
+    pub price_list_version: i32,
+    pub lines: Vec<InvoiceLine>,
+    pub total: f64,
+    #[serde(rename = "paidAmount")]
+    pub paid_amount: f64,
+    /// Billed to the patient's insurance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claim: Option<InsuranceClaim>,
+    /// Billed to the patient's employer, after any insurance claim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corporate: Option<CorporateBilling>,
+    pub status: InvoiceStatus,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// The insurer's share of an invoice under the insurance version in effect on the day of service
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InsuranceClaim {
+    #[serde(rename = "insuranceId")]
+    pub insurance_id: String,
+    #[serde(rename = "insuranceVersion")]
+    pub insurance_version: i32,
+    #[serde(rename = "coveredAmount")]
+    pub covered_amount: f64,
+    #[serde(rename = "patientAmount")]
+    pub patient_amount: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvoiceLine {
+    #[serde(rename = "itemType")]
+    pub item_type: PriceItemType,
+    #[serde(rename = "itemId")]
+    pub item_id: String,
+    pub quantity: f64,
+    #[serde(rename = "unitPrice")]
+    pub unit_price: f64,
+    pub amount: f64,
+}
+
+/// Money received against an invoice; collection `payments`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Payment {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "invoiceId")]
+    pub invoice_id: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: String,
+    pub method: PaymentMethod,
+    pub amount: f64,
+    /// Transfer or QRIS transaction reference
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    #[serde(rename = "cashierId")]
+    pub cashier_id: String,
+    /// Open shift the cashier recorded it in; gateway payments have none
+    #[serde(rename = "shiftId", default, skip_serializing_if = "Option::is_none")]
+    pub shift_id: Option<String>,
+    #[serde(rename = "paidAt", with = "crate::datetime")]
+    pub paid_at: DateTime,
+    /// Day the payment settles on, `YYYY-MM-DD` in the clinic timezone
+    #[serde(rename = "settlementDate")]
+    pub settlement_date: String,
+}
+
+/// A payment link created at the payment gateway for an invoice; collection
+/// `gateway_transactions`. Its id is the order id the gateway reports back in callbacks.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GatewayTransaction {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "invoiceId")]
+    pub invoice_id: String,
+    /// `midtrans` or `xendit`
+    pub provider: String,
+    pub amount: f64,
+    pub url: String,
+    #[serde(rename = "providerReference", default, skip_serializing_if = "Option::is_none")]
+    pub provider_reference: Option<String>,
+    pub status: GatewayStatus,
+    #[serde(rename = "expiresAt", default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<String>,
+    /// Set once the paid callback recorded a payment
+    #[serde(rename = "paymentId", default, skip_serializing_if = "Option::is_none")]
+    pub payment_id: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A message for external systems about a change, written in the transaction of the change;
+/// collection `outbox`. The relay in `crate::outbox` delivers it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OutboxEntry {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// e.g. `payment.recorded`
+    pub topic: String,
+    /// Collection of the changed document
+    #[serde(rename = "aggregateType")]
+    pub aggregate_type: String,
+    #[serde(rename = "aggregateId")]
+    pub aggregate_id: String,
+    pub payload: mongodb::bson::Document,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    #[serde(rename = "lastError", default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Not delivered before; also pushed forward while a relay holds the entry
+    #[serde(rename = "nextAttemptAt", with = "crate::datetime")]
+    pub next_attempt_at: DateTime,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "publishedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub published_at: Option<DateTime>,
+}
+
+/// A report rendered on a cron schedule and emailed to its recipients; collection
+/// `report_schedules`. Each run covers the `periodDays` days before the run date.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportSchedule {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(rename = "reportType")]
+    pub report_type: ReportType,
+    /// Five-field cron expression in the clinic timezone, see `crate::cron`
+    pub cron: String,
+    pub recipients: Vec<String>,
+    pub format: ReportFormat,
+    #[serde(rename = "periodDays")]
+    pub period_days: i32,
+    /// Limits appointment and revenue reports to one organization
+    #[serde(rename = "organizationId", default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    pub active: bool,
+    #[serde(rename = "nextRunAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub next_run_at: Option<DateTime>,
+    #[serde(rename = "lastRunAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub last_run_at: Option<DateTime>,
+    /// File of the last successful run
+    #[serde(rename = "lastFileId", default, skip_serializing_if = "Option::is_none")]
+    pub last_file_id: Option<String>,
+    #[serde(rename = "lastError", default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A stored aggregation over one collection, run with parameters through `POST /reports/run`;
+/// collection `report_templates`. See `crate::report_templates` for what a pipeline may use.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportTemplate {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub collection: String,
+    /// Stages with `"{{name}}"` placeholders for the parameters
+    pub pipeline: Vec<mongodb::bson::Document>,
+    #[serde(default)]
+    pub parameters: Vec<ReportParameter>,
+    /// Column order of the result; the keys of the rows when empty
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReportParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: ReportParameterType,
+    #[serde(default)]
+    pub required: bool,
+    /// Used when the run gives no value, already of `param_type`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<mongodb::bson::Bson>,
+}
+
+/// A cashier's working session; collection `cashier_shifts`. Closing it compares the
+/// counted cash drawer with the opening float plus the cash taken.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CashierShift {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "cashierId")]
+    pub cashier_id: String,
+    pub status: ShiftStatus,
+    #[serde(rename = "openingCash")]
+    pub opening_cash: f64,
+    #[serde(rename = "openedAt", with = "crate::datetime")]
+    pub opened_at: DateTime,
+    #[serde(rename = "closedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub closed_at: Option<DateTime>,
+    #[serde(rename = "expectedCash", default, skip_serializing_if = "Option::is_none")]
+    pub expected_cash: Option<f64>,
+    #[serde(rename = "countedCash", default, skip_serializing_if = "Option::is_none")]
+    pub counted_cash: Option<f64>,
+    /// `countedCash - expectedCash`; negative when the drawer is short
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difference: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// An insurance product as of its latest version; collection `insurances`. Every change is a
+/// new version in `insurance_versions`, so invoices are claimed under the terms in effect on
+/// the day of service.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Insurance {
     #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
@@ -126,7 +900,210 @@ pub struct Insurance {
     #[serde(rename = "type")]
     pub insurance_type: String,
     pub code: String,
-    pub status: String,
+    pub status: InsuranceStatus,
+    #[serde(default)]
+    pub terms: InsuranceTerms,
+    /// Numbered per insurance, starting at 1
+    #[serde(default)]
+    pub version: i32,
+    /// `YYYY-MM-DD` the latest version takes effect, in the clinic timezone
+    #[serde(rename = "effectiveFrom", default)]
+    pub effective_from: String,
+}
+
+/// What an insurance, or an employer under its contract, pays of an invoice
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct InsuranceTerms {
+    /// Share of the invoice total, 0 to 100
+    #[serde(rename = "coveragePercent", default)]
+    pub coverage_percent: f64,
+    /// Paid by the patient on every invoice
+    #[serde(default)]
+    pub copay: f64,
+    /// Most paid on one invoice
+    #[serde(rename = "maxClaim", default, skip_serializing_if = "Option::is_none")]
+    pub max_claim: Option<f64>,
+}
+
+impl InsuranceTerms {
+    /// The insurer's share of `total`, leaving the copay to the patient, up to the maximum claim
+    pub fn covered_amount(&self, total: f64) -> f64 {
+        let covered = (total * self.coverage_percent / 100.0).min(total - self.copay).max(0.0);
+        self.max_claim.map_or(covered, |max| covered.min(max))
+    }
+}
+
+/// One version of an insurance; collection `insurance_versions`. It is in effect from
+/// `effectiveFrom` until the `effectiveFrom` of the next version.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InsuranceVersion {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "insuranceId")]
+    pub insurance_id: String,
+    pub version: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub insurance_type: String,
+    pub code: String,
+    pub status: InsuranceStatus,
+    pub terms: InsuranceTerms,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "effectiveFrom")]
+    pub effective_from: String,
+    /// `YYYY-MM-DD`, exclusive; unset on the latest version
+    #[serde(rename = "effectiveUntil", default, skip_serializing_if = "Option::is_none")]
+    pub effective_until: Option<String>,
+    /// Unset on versions backfilled from insurances created before versioning
+    #[serde(rename = "createdBy", default, skip_serializing_if = "Option::is_none")]
+    pub created_by: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+impl InsuranceVersion {
+    pub fn of(insurance: &Insurance, insurance_id: ObjectId, created_by: &str) -> Self {
+        Self {
+            id: None,
+            insurance_id: insurance_id.to_hex(),
+            version: insurance.version,
+            name: insurance.name.clone(),
+            insurance_type: insurance.insurance_type.clone(),
+            code: insurance.code.clone(),
+            status: insurance.status.clone(),
+            terms: insurance.terms.clone(),
+            effective_from: insurance.effective_from.clone(),
+            effective_until: None,
+            created_by: Some(created_by.to_string()),
+            created_at: DateTime::now(),
+        }
+    }
+}
+
+/// The employer's share of an invoice under the contract of its corporate client
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorporateBilling {
+    #[serde(rename = "clientId")]
+    pub client_id: String,
+    #[serde(rename = "employeeNumber")]
+    pub employee_number: String,
+    #[serde(rename = "coveredAmount")]
+    pub covered_amount: f64,
+    #[serde(rename = "patientAmount")]
+    pub patient_amount: f64,
+}
+
+/// Terms a company agreed with the clinic for its employees
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorporateContract {
+    pub number: String,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "startDate")]
+    pub start_date: String,
+    /// `YYYY-MM-DD`, inclusive; unset for open-ended contracts
+    #[serde(rename = "endDate", default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    pub terms: InsuranceTerms,
+    /// Days after a statement is generated that it is due
+    #[serde(rename = "paymentTermDays")]
+    pub payment_term_days: u32,
+}
+
+impl CorporateContract {
+    pub fn covers(&self, date: &str) -> bool {
+        self.start_date.as_str() <= date && self.end_date.as_deref().is_none_or(|end| date <= end)
+    }
+}
+
+/// A company whose employees' care is billed to it; collection `corporate_clients`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateClient {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// The clinic organization the contract is with
+    #[serde(rename = "organizationId")]
+    pub organization_id: Ref<Organization>,
+    pub code: String,
+    pub name: String,
+    #[serde(rename = "billingEmail", default, skip_serializing_if = "Option::is_none")]
+    pub billing_email: Option<String>,
+    pub contract: CorporateContract,
+    pub status: CorporateClientStatus,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A patient on a corporate client's employee roster; collection `corporate_employees`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateEmployee {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "clientId")]
+    pub client_id: String,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    #[serde(rename = "employeeNumber")]
+    pub employee_number: String,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "startDate")]
+    pub start_date: String,
+    /// `YYYY-MM-DD`, inclusive; set when the employee leaves
+    #[serde(rename = "endDate", default, skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<String>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// One invoice billed to a corporate client in a statement
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CorporateStatementLine {
+    #[serde(rename = "invoiceId")]
+    pub invoice_id: String,
+    #[serde(rename = "invoiceNumber", default, skip_serializing_if = "Option::is_none")]
+    pub invoice_number: Option<String>,
+    #[serde(rename = "patientId")]
+    pub patient_id: String,
+    #[serde(rename = "employeeNumber")]
+    pub employee_number: String,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "serviceDate")]
+    pub service_date: String,
+    pub total: f64,
+    /// Billed to the client
+    pub amount: f64,
+}
+
+/// What a corporate client owes for the invoices served in one month; collection
+/// `corporate_statements`, one per client and month. Regenerating replaces it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CorporateStatement {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// `STM-YYYY-000123`, numbered per organization and statement year
+    pub number: String,
+    #[serde(rename = "clientId")]
+    pub client_id: String,
+    #[serde(rename = "organizationId")]
+    pub organization_id: String,
+    /// `YYYY-MM`
+    pub month: String,
+    pub lines: Vec<CorporateStatementLine>,
+    pub total: f64,
+    /// `YYYY-MM-DD`
+    #[serde(rename = "dueDate")]
+    pub due_date: String,
+    #[serde(rename = "generatedBy")]
+    pub generated_by: String,
+    #[serde(rename = "generatedAt", with = "crate::datetime")]
+    pub generated_at: DateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -141,8 +1118,90 @@ pub struct File {
     pub path: String,
     pub url: String,
     pub uploader: String,
-    #[serde(rename = "createdAt")]
-    pub created_at: String,
+    /// Medical record the file is attached to
+    #[serde(rename = "medicalRecordId", default, skip_serializing_if = "Option::is_none")]
+    pub medical_record_id: Option<Ref<MedicalRecord>>,
+    /// User the storage is charged to, see `crate::quota`
+    #[serde(rename = "ownerId", default, skip_serializing_if = "Option::is_none")]
+    pub owner_id: Option<String>,
+    /// Organization the storage is charged to
+    #[serde(rename = "organizationId", default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    /// Current version; every version is kept in `file_versions`
+    #[serde(default = "first_version")]
+    pub version: i32,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    /// When the content was last replaced or restored
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    /// Set when a delete policy cascaded to this file, see `crate::delete_policy`
+    #[serde(rename = "deletedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub deleted_at: Option<DateTime>,
+}
+
+/// Files stored before versioning are at version 1
+fn first_version() -> i32 {
+    1
+}
+
+/// Stored content of one version of a file; collection `file_versions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileVersion {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub version: i32,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub extension: String,
+    pub size: u64,
+    pub path: String,
+    pub url: String,
+    /// Who uploaded or restored this version
+    pub uploader: String,
+    /// Version whose content a restore copied
+    #[serde(rename = "restoredFrom", default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<i32>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Bytes and files stored by one user or organization; collection `storage_usage`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageUsage {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// `user` or `organization`
+    pub scope: String,
+    #[serde(rename = "ownerId")]
+    pub owner_id: String,
+    pub bytes: i64,
+    pub files: i64,
+    #[serde(with = "crate::datetime")]
+    pub updated_at: DateTime,
+}
+
+/// Public link to a file, see `crate::services::FileShareService`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileShare {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "fileId")]
+    pub file_id: String,
+    pub created_by: String,
+    #[serde(with = "crate::datetime")]
+    pub expires_at: DateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub revoked_at: Option<DateTime>,
+    #[serde(default)]
+    pub access_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub last_accessed_at: Option<DateTime>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -152,16 +1211,21 @@ pub struct User {
     pub email: String,
     pub password: String,
     pub name: String,
+    /// E.164, see `crate::phone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
     #[serde(rename = "refreshToken", skip_serializing_if = "Option::is_none")]
     pub refresh_token: Option<String>,
     #[serde(rename = "resetToken", skip_serializing_if = "Option::is_none")]
     pub reset_token: Option<String>,
     #[serde(rename = "resetTokenExpiry", skip_serializing_if = "Option::is_none")]
     pub reset_token_expiry: Option<String>,
-    #[serde(rename = "createdAt")]
-    pub created_at: String,
-    #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
+    #[serde(rename = "emailVerifiedAt", default, skip_serializing_if = "Option::is_none")]
+    pub email_verified_at: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -183,6 +1247,377 @@ pub struct Role {
     pub category: RoleCategory,
 }
 
+/// An action on a resource that can be granted to roles, e.g. `patients:read`; collection `permissions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Permission {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub resource: String,
+    pub action: String,
+    pub description: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Grant of a permission to a role code; collection `role_permissions`. Role code `*` grants to
+/// every authenticated user and `*` as resource or action matches any.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RolePermission {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub role_code: String,
+    pub resource: String,
+    pub action: String,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Check-in kiosk, paired with a one-time code; collection `kiosk_devices`. Its tokens only
+/// reach check-in and queue endpoints, see `crate::middleware::kiosk_scope`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KioskDevice {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    #[serde(rename = "organizationId", default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    /// SHA-256 of the outstanding pairing code; cleared once the device pairs
+    #[serde(rename = "pairingCodeHash", default, skip_serializing_if = "Option::is_none")]
+    pub pairing_code_hash: Option<String>,
+    #[serde(rename = "pairingExpiresAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub pairing_expires_at: Option<DateTime>,
+    /// Tokens issued before the latest pairing are rejected
+    #[serde(rename = "pairedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub paired_at: Option<DateTime>,
+    #[serde(rename = "revokedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub revoked_at: Option<DateTime>,
+    #[serde(rename = "lastSeenAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub last_seen_at: Option<DateTime>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Physical count of medicine stock; collection `stock_opnames`. Starting one snapshots the
+/// `qty` of each batch as `expectedQty`; approval adjusts the batches by their variance.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StockOpname {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    pub status: StockOpnameStatus,
+    pub items: Vec<StockOpnameItem>,
+    #[serde(rename = "startedBy")]
+    pub started_by: String,
+    #[serde(rename = "startedAt", with = "crate::datetime")]
+    pub started_at: DateTime,
+    /// Who approved or cancelled the opname
+    #[serde(rename = "closedBy", default, skip_serializing_if = "Option::is_none")]
+    pub closed_by: Option<String>,
+    #[serde(rename = "closedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub closed_at: Option<DateTime>,
+}
+
+/// One medicine batch of a stock opname
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StockOpnameItem {
+    #[serde(rename = "medicineId")]
+    pub medicine_id: String,
+    #[serde(rename = "masterMedicineId")]
+    pub master_medicine_id: String,
+    #[serde(rename = "batchNumber")]
+    pub batch_number: String,
+    #[serde(rename = "tradeName")]
+    pub trade_name: String,
+    #[serde(rename = "expiredDate")]
+    pub expired_date: String,
+    /// Purchase price at the start, to value the variance
+    #[serde(rename = "purchasePrice")]
+    pub purchase_price: f64,
+    #[serde(rename = "expectedQty")]
+    pub expected_qty: f64,
+    #[serde(rename = "countedQty", default, skip_serializing_if = "Option::is_none")]
+    pub counted_qty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(rename = "countedBy", default, skip_serializing_if = "Option::is_none")]
+    pub counted_by: Option<String>,
+    #[serde(rename = "countedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub counted_at: Option<DateTime>,
+}
+
+impl StockOpnameItem {
+    /// Counted minus expected; negative for a shortage
+    pub fn variance(&self) -> Option<f64> {
+        self.counted_qty.map(|counted| counted - self.expected_qty)
+    }
+}
+
+/// Change of a medicine batch's `qty`; collection `stock_movements`. `qty` is signed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StockMovement {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "medicineId")]
+    pub medicine_id: String,
+    #[serde(rename = "batchNumber")]
+    pub batch_number: String,
+    #[serde(rename = "type")]
+    pub movement_type: StockMovementType,
+    pub qty: f64,
+    /// The stock opname an adjustment came from
+    #[serde(rename = "stockOpnameId", default, skip_serializing_if = "Option::is_none")]
+    pub stock_opname_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Client-credentials identity for an external system; collection `service_accounts`.
+/// Tokens issued to it carry `scopes` (`resource:action`) instead of user roles.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServiceAccount {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    pub client_id: String,
+    /// bcrypt hash of the client secret, which is only shown on creation and rotation
+    pub client_secret_hash: String,
+    pub scopes: Vec<String>,
+    pub active: bool,
+    pub created_by: String,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    /// Tokens issued before the last rotation are rejected
+    #[serde(default)]
+    pub rotated_at: Option<String>,
+    #[serde(default)]
+    pub last_used_at: Option<String>,
+}
+
+/// Captured request and response, see `crate::request_log`; capped collection `request_logs`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequestLog {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub method: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: i32,
+    pub duration_ms: i64,
+    /// Subject of a valid bearer token, if any
+    pub user_id: Option<String>,
+    pub request_body: Option<String>,
+    pub response_body: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// Runtime switch for gating behavior; collection `feature_flags`. See `crate::flags`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FeatureFlag {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,
+    /// Organization ids (`UserRole.organisasi._id`) that get the flag whenever it is enabled
+    #[serde(default)]
+    pub organizations: Vec<String>,
+    /// Share of all other callers, 0-100, picked by a stable hash of the key and user id
+    pub rollout_percentage: i32,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Clinic or other organization; collection `organizations`. `UserRole.organisasi._id` and
+/// `Appointment.organizationId` refer to it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Organization {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    /// Zone appointments are booked in, see `crate::timezone`
+    pub timezone: String,
+    /// See `crate::geo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    /// `Kit.distributor.code` this organization represents; its `distributor` role holders see
+    /// only those kits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distributor_code: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Inpatient ward; collection `wards`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Ward {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub code: String,
+    pub name: String,
+    /// Care class, e.g. `VIP` or `Kelas 1`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    #[serde(rename = "organizationId", default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// Bed in a ward; collection `beds`. An occupied bed names its patient and admission.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bed {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "wardId")]
+    pub ward_id: String,
+    pub code: String,
+    pub status: BedStatus,
+    #[serde(rename = "patientId", default, skip_serializing_if = "Option::is_none")]
+    pub patient_id: Option<String>,
+    #[serde(rename = "admissionId", default, skip_serializing_if = "Option::is_none")]
+    pub admission_id: Option<String>,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// One move of an admitted patient between beds
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BedTransfer {
+    #[serde(rename = "fromBedId")]
+    pub from_bed_id: String,
+    #[serde(rename = "toBedId")]
+    pub to_bed_id: String,
+    #[serde(rename = "transferredBy")]
+    pub transferred_by: String,
+    #[serde(rename = "transferredAt", with = "crate::datetime")]
+    pub transferred_at: DateTime,
+}
+
+/// Inpatient stay of a patient; collection `admissions`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Admission {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    /// Current bed, or the last one once discharged
+    #[serde(rename = "bedId")]
+    pub bed_id: String,
+    #[serde(rename = "wardId")]
+    pub ward_id: String,
+    pub status: AdmissionStatus,
+    #[serde(rename = "admittedBy")]
+    pub admitted_by: String,
+    #[serde(rename = "admittedAt", with = "crate::datetime")]
+    pub admitted_at: DateTime,
+    #[serde(default)]
+    pub transfers: Vec<BedTransfer>,
+    #[serde(rename = "dischargedBy", default, skip_serializing_if = "Option::is_none")]
+    pub discharged_by: Option<String>,
+    #[serde(rename = "dischargedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub discharged_at: Option<DateTime>,
+    #[serde(rename = "dischargeNote", default, skip_serializing_if = "Option::is_none")]
+    pub discharge_note: Option<String>,
+}
+
+/// A bound on the latest observation of one coding, inclusive at both ends
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ObservationThreshold {
+    /// `coding.code` of the observation, e.g. LOINC `8480-6` for systolic pressure
+    pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+impl ObservationThreshold {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+/// Who may enroll: a patient with one of `diagnosisCodes` or whose latest observation meets
+/// one of `observations`. Without either rule anyone may.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ProgramEligibility {
+    /// ICD-10 codes, each also matching its subcodes: `E11` matches `E11.9`
+    #[serde(rename = "diagnosisCodes", default)]
+    pub diagnosis_codes: Vec<String>,
+    #[serde(default)]
+    pub observations: Vec<ObservationThreshold>,
+}
+
+/// Chronic disease management program, e.g. Prolanis for hypertension or diabetes;
+/// collection `programs`. `controlTargets` decide whether an enrolled patient is controlled.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Program {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub code: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub eligibility: ProgramEligibility,
+    #[serde(rename = "controlTargets", default)]
+    pub control_targets: Vec<ObservationThreshold>,
+    pub status: ProgramStatus,
+    #[serde(rename = "createdBy")]
+    pub created_by: String,
+    #[serde(rename = "createdAt", with = "crate::datetime")]
+    pub created_at: DateTime,
+    #[serde(rename = "updatedAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+}
+
+/// A patient in a program; collection `program_enrollments`. A patient has at most one
+/// active enrollment per program, and a withdrawn one stays for the record.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProgramEnrollment {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    #[serde(rename = "programId")]
+    pub program_id: String,
+    #[serde(rename = "patientId")]
+    pub patient_id: Ref<MedicalRecord>,
+    /// Diagnoses given at enrollment
+    #[serde(rename = "diagnosisCodes", default)]
+    pub diagnosis_codes: Vec<String>,
+    /// The eligibility rules the patient met, e.g. `diagnosis I10`
+    #[serde(rename = "eligibleBy", default)]
+    pub eligible_by: Vec<String>,
+    pub status: ProgramEnrollmentStatus,
+    #[serde(rename = "enrolledBy")]
+    pub enrolled_by: String,
+    #[serde(rename = "enrolledAt", with = "crate::datetime")]
+    pub enrolled_at: DateTime,
+    #[serde(rename = "withdrawnBy", default, skip_serializing_if = "Option::is_none")]
+    pub withdrawn_by: Option<String>,
+    #[serde(rename = "withdrawnAt", default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub withdrawn_at: Option<DateTime>,
+    #[serde(rename = "withdrawalReason", default, skip_serializing_if = "Option::is_none")]
+    pub withdrawal_reason: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RoleEmbed {
     pub code: String,
@@ -238,10 +1673,16 @@ pub struct UserRole {
     pub organisasi: OrganizationEmbed,
     #[serde(rename = "is_active")]
     pub is_active: bool,
-    #[serde(rename = "updated_at")]
-    pub updated_at: String,
-    #[serde(rename = "created_at")]
-    pub created_at: String,
+    /// Grants nothing before this; see `crate::role_expiry`
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub valid_from: Option<DateTime>,
+    /// Grants nothing from this on, and is deactivated by the expiry sweep
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub valid_until: Option<DateTime>,
+    #[serde(rename = "updated_at", with = "crate::datetime")]
+    pub updated_at: DateTime,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CodeCategoryEmbed {
@@ -256,12 +1697,29 @@ pub struct Code {
     pub id: Option<ObjectId>,
     pub code: String,
     pub display: String,
+    /// Translations of `display`, see `crate::i18n`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub display_i18n: Translations,
     pub system: String,
     pub category: CodeCategoryEmbed,
-    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "created_at")]
-    pub created_at: String,
+    /// Inactive codes stay readable but should not be picked for new records
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+impl Code {
+    /// `display` in the requested language
+    pub fn localize(&mut self, locale: Locale) {
+        locale.apply(&mut self.display, &self.display_i18n);
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -284,10 +1742,10 @@ pub struct ChildCode {
     pub system: String,
     pub display: String,
     pub norut: i32,
-    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "created_at")]
-    pub created_at: String,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -302,6 +1760,10 @@ pub struct Region {
     pub kecamatan: String,
     pub kelurahan: String,
     pub len: String,
+    #[serde(skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub created_at: Option<DateTime>,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct InterpretationCoding {
@@ -319,10 +1781,20 @@ pub struct Interpretation {
     pub max: f64,
     pub coding: InterpretationCoding,
     pub text: String,
-    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "created_at", skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<String>,
+    /// Translations of `text`, see `crate::i18n`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub display_i18n: Translations,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub created_at: Option<DateTime>,
+}
+
+impl Interpretation {
+    /// `text` in the requested language
+    pub fn localize(&mut self, locale: Locale) {
+        locale.apply(&mut self.text, &self.display_i18n);
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -363,10 +1835,21 @@ pub struct Kit {
     pub log_user_kit_id: String,
     pub order_id: String,
     pub pasien: KitPasien,
-    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "created_at")]
-    pub created_at: String,
+    /// Hardware model, used to target firmware releases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Firmware version last reported by the kit's heartbeat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub firmware_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_heartbeat_at: Option<String>,
+    /// Where the kit is deployed, see `crate::geo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoPoint>,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -399,7 +1882,7 @@ pub struct ObservationPasienUsia {
 pub struct ObservationPasien {
     pub id: String,
     pub nama: ObservationPasienNama,
-    pub gender: String,
+    pub gender: Gender,
     pub nik: String,
     pub lahir: ObservationPasienLahir,
     pub usia: ObservationPasienUsia,
@@ -453,7 +1936,7 @@ pub struct Observation {
     pub id: Option<ObjectId>,
     pub value: f64,
     pub unit: ObservationUnit,
-    pub id_pasien: String,
+    pub id_pasien: Ref<MedicalRecord>,
     pub pasien: ObservationPasien,
     pub id_petugas: String,
     pub atm_sehat: ObservationAtmSehat,
@@ -463,8 +1946,267 @@ pub struct Observation {
     pub base_line: ObservationBaseLine,
     pub interpretation: ObservationInterpretation,
     pub log_user_kit_id: Option<String>,
-    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none")]
-    pub updated_at: Option<String>,
-    #[serde(rename = "created_at", skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<String>,
+    /// Computed from other observations by a rule in `crate::derived`
+    #[serde(default)]
+    pub derived: bool,
+    /// IDs of the input observations of a derived observation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub derived_from: Vec<String>,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub created_at: Option<DateTime>,
+}
+
+/// A deleted reference document, kept so offline clients learn about the deletion; collection
+/// `tombstones`. See `crate::sync`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tombstone {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// Collection the document was deleted from
+    pub collection: String,
+    /// Hex ObjectId of the deleted document
+    pub entity_id: String,
+    #[serde(with = "crate::datetime")]
+    pub deleted_at: DateTime,
+}
+
+/// The outcome of a mutation pushed by an offline client, keyed by the client's UUID so pushing
+/// it again returns the same outcome; collection `sync_mutations`. See `crate::sync`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncMutation {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub client_id: String,
+    pub user_id: String,
+    pub device_id: String,
+    pub entity: SyncEntity,
+    pub operation: SyncOperation,
+    /// Hex ObjectId of the document; for a create, the one it was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity_id: Option<String>,
+    pub status: SyncOutcome,
+    /// Version of the document after the mutation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// An offline edit based on an outdated version, waiting for someone to resolve it; collection
+/// `sync_conflicts`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SyncConflict {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// UUID of the mutation
+    pub client_id: String,
+    /// User who pushed the mutation
+    pub user_id: String,
+    pub device_id: String,
+    pub entity: SyncEntity,
+    pub operation: SyncOperation,
+    pub entity_id: String,
+    /// Version the device edited
+    pub base_version: i32,
+    /// Version on the server when the mutation arrived
+    pub server_version: i32,
+    /// The mutation's data, as pushed
+    pub data: serde_json::Value,
+    /// When the device made the change
+    #[serde(with = "crate::datetime")]
+    pub changed_at: DateTime,
+    pub status: SyncConflictStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<ConflictResolution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "crate::datetime::optional")]
+    pub resolved_at: Option<DateTime>,
+    #[serde(with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AuditLog {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub action: String,
+    pub entity: String,
+    pub entity_id: String,
+    pub actor: String,
+    pub details: mongodb::bson::Document,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct JobProgress {
+    pub total: u64,
+    pub processed: u64,
+    pub succeeded: u64,
+    pub skipped: u64,
+    pub failed: u64,
+}
+
+/// One failed run of a job, kept in `Job::errors` across retries
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct JobFailure {
+    pub attempt: i32,
+    pub error: String,
+    pub failed_at: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Job {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    pub job_type: String,
+    pub status: String,
+    pub payload: mongodb::bson::Document,
+    pub progress: JobProgress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<mongodb::bson::Document>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Every failed attempt, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<JobFailure>,
+    pub attempts: i32,
+    pub created_by: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetentionResult {
+    pub collection: String,
+    pub action: String,
+    pub cutoff: String,
+    /// Documents archived or purged
+    pub documents: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub archive_keys: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetentionRun {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// `scheduled` or `manual`
+    pub trigger: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub results: Vec<RetentionResult>,
+}
+
+/// Object found by a storage reconciliation that no file references
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrphanedObject {
+    pub key: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Within the grace period, so kept for now
+    pub pending: bool,
+    pub deleted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One run of `crate::storage_gc`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageReconciliation {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// `scheduled` or `manual`
+    pub trigger: String,
+    /// `delete` or `report`
+    pub mode: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub bucket: String,
+    pub prefixes: Vec<String>,
+    pub grace_hours: i64,
+    pub objects_scanned: u64,
+    pub orphans_found: u64,
+    pub orphan_bytes: u64,
+    pub orphans_pending: u64,
+    pub orphans_deleted: u64,
+    pub missing_found: u64,
+    /// First orphans by key; the counts above cover all of them
+    pub orphans: Vec<OrphanedObject>,
+    /// First referenced keys storage does not have
+    pub missing: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Firmware {
+    #[serde(rename(serialize = "id", deserialize = "_id"), skip_serializing_if = "Option::is_none", serialize_with = "serialize_oid_as_id")]
+    pub id: Option<ObjectId>,
+    /// Semantic version, e.g. `1.4.2`
+    pub version: String,
+    /// SHA-256 of the artifact, hex encoded
+    pub checksum: String,
+    pub artifact_key: String,
+    pub artifact_url: String,
+    /// Kit models this release applies to; empty means every model
+    pub kit_models: Vec<String>,
+    pub release_notes: Option<String>,
+    /// Only active releases are offered to kits
+    pub is_active: bool,
+    #[serde(rename = "updated_at", skip_serializing_if = "Option::is_none", default, with = "crate::datetime::optional")]
+    pub updated_at: Option<DateTime>,
+    #[serde(rename = "created_at", with = "crate::datetime")]
+    pub created_at: DateTime,
+}
+
+/// The lease deciding which instance runs scheduled jobs; collection `scheduler_leases`, one
+/// document per lease. See `crate::scheduler`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SchedulerLease {
+    #[serde(rename(serialize = "name", deserialize = "_id"))]
+    pub name: String,
+    /// Instance id of the leader
+    pub holder: String,
+    /// When the holder took the lease over
+    #[serde(with = "crate::datetime")]
+    pub acquired_at: DateTime,
+    /// Last heartbeat of the holder
+    #[serde(with = "crate::datetime")]
+    pub renewed_at: DateTime,
+    /// Without another heartbeat, the lease lapses and the document is removed by its TTL index
+    #[serde(with = "crate::datetime")]
+    pub expires_at: DateTime,
+}
+
+/// The last run of a scheduled job, whichever instance ran it; collection `scheduled_job_runs`,
+/// keyed by job name
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScheduledJobRun {
+    #[serde(rename(serialize = "job", deserialize = "_id"))]
+    pub job: String,
+    pub instance: String,
+    #[serde(with = "crate::datetime")]
+    pub started_at: DateTime,
+    #[serde(with = "crate::datetime")]
+    pub finished_at: DateTime,
+    pub status: ScheduledRunStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
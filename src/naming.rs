@@ -0,0 +1,216 @@
+//! JSON naming policy of the API.
+//!
+//! Request and response bodies use snake_case keys. Models keep the names their collections
+//! were written with (`patientId`, `createdAt`, ...), which is why handlers answer with DTOs
+//! rather than models.
+//!
+//! Clients pick the response naming with the `API-Version` header: `2` (the default) is
+//! snake_case throughout; `1` restores the camelCase keys older responses had, listed in
+//! `LEGACY_RESPONSE_KEYS`, and is answered with `Deprecation: true`. Input accepts both
+//! spellings through serde aliases on the renamed fields. Version 1 and the aliases are
+//! removed in the next release.
+
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+pub const API_VERSION_HEADER: &str = "api-version";
+
+/// `(path prefix, key, legacy key)` of response fields renamed in version 2
+pub const LEGACY_RESPONSE_KEYS: &[(&str, &str, &str)] = &[
+    ("/files", "created_at", "createdAt"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const CURRENT: ApiVersion = ApiVersion::V2;
+
+    /// The requested version; missing or unknown values get the current one.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        match headers.get(API_VERSION_HEADER).and_then(|v| v.to_str().ok()).map(str::trim) {
+            Some("1") => ApiVersion::V1,
+            _ => Self::CURRENT,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "1",
+            ApiVersion::V2 => "2",
+        }
+    }
+}
+
+/// Lowercase ASCII words joined by underscores; `_id` counts.
+pub fn is_snake_case(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Version 1 renames for a request path, as `(key, legacy key)`
+pub fn legacy_keys(path: &str) -> Vec<(&'static str, &'static str)> {
+    LEGACY_RESPONSE_KEYS
+        .iter()
+        .filter(|(prefix, _, _)| path == *prefix || path.starts_with(&format!("{}/", prefix)))
+        .map(|(_, key, legacy)| (*key, *legacy))
+        .collect()
+}
+
+/// Rename object keys at any depth.
+pub fn rename_keys(value: &mut Value, renames: &[(&str, &str)]) {
+    match value {
+        Value::Object(map) => {
+            for (from, to) in renames {
+                if let Some(field) = map.remove(*from) {
+                    map.insert(to.to_string(), field);
+                }
+            }
+            map.values_mut().for_each(|v| rename_keys(v, renames));
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| rename_keys(v, renames)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::appointment::{AppointmentResponse, DoctorSummary, PatientSummary};
+    use crate::dto::code::{CreateCodeDto, ImportCodesDto};
+    use crate::pagination::PaginationMeta;
+    use serde_json::json;
+
+    fn non_snake_keys(value: &Value, path: &str, found: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map {
+                    let here = format!("{}.{}", path, key);
+                    if !is_snake_case(key) {
+                        found.push(here.clone());
+                    }
+                    non_snake_keys(field, &here, found);
+                }
+            }
+            Value::Array(items) => items.iter().for_each(|v| non_snake_keys(v, path, found)),
+            _ => {}
+        }
+    }
+
+    /// Quoted values following `attribute = ` in a source file
+    fn attribute_values<'a>(source: &'a str, attribute: &str) -> Vec<&'a str> {
+        let needle = format!("{} = \"", attribute);
+        source
+            .match_indices(&needle)
+            .filter_map(|(at, _)| {
+                let rest = &source[at + needle.len()..];
+                rest.find('"').map(|end| &rest[..end])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dto_field_renames_follow_the_policy() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/dto");
+        let mut violations = Vec::new();
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            let file = path.file_name().unwrap().to_string_lossy().to_string();
+
+            for name in attribute_values(&source, "rename").into_iter().filter(|n| !is_snake_case(n)) {
+                violations.push(format!("{}: rename = \"{}\"", file, name));
+            }
+            for policy in attribute_values(&source, "rename_all") {
+                if !matches!(policy, "snake_case" | "lowercase") {
+                    violations.push(format!("{}: rename_all = \"{}\"", file, policy));
+                }
+            }
+        }
+        assert!(violations.is_empty(), "DTOs must serialize snake_case keys: {:?}", violations);
+    }
+
+    #[test]
+    fn responses_serialize_snake_case_keys() {
+        let appointment = AppointmentResponse {
+            id: "a".into(),
+            patient_id: "p".into(),
+            doctor_id: "d".into(),
+            date: "2026-03-10".into(),
+            time: "09:00".into(),
+            status: "scheduled".into(),
+            queue_number: Some(1),
+            checked_in_at: Some("2026-03-10T08:55:00+07:00".into()),
+            called_at: Some("2026-03-10T09:00:00+07:00".into()),
+            completed_at: Some("2026-03-10T09:12:00+07:00".into()),
+            series_id: Some("s".into()),
+            mode: Some("virtual".into()),
+            organization_id: Some("o".into()),
+            timezone: Some("Asia/Jakarta".into()),
+            starts_at: Some("2026-03-10T09:00:00+07:00".into()),
+            reminder_at: Some("2026-03-09T09:00:00+07:00".into()),
+            updated_at: None,
+            doctor: Some(DoctorSummary { id: "d".into(), name: "Dr. A".into(), specialization: "GP".into() }),
+            patient: Some(PatientSummary { id: "p".into(), name: "B".into(), nrme: "0001".into() }),
+            links: None,
+        };
+        let appointment = AppointmentResponse {
+            links: Some(crate::links::LinkConfig::new("/api/v1").appointment(&appointment)),
+            ..appointment
+        };
+        let samples = [
+            serde_json::to_value(appointment).unwrap(),
+            serde_json::to_value(PaginationMeta::new(1, 10, 25)).unwrap(),
+            #[cfg(feature = "s3")]
+            serde_json::to_value(crate::dto::file::FileResponse {
+                id: "f".into(),
+                name: "scan.png".into(),
+                file_type: "image/png".into(),
+                extension: "png".into(),
+                size: 1,
+                path: "uploads/scan.png".into(),
+                url: "https://example.org/scan.png".into(),
+                uploader: "u".into(),
+                medical_record_id: Some("r".into()),
+                version: 2,
+                created_at: "2026-03-10T08:00:00+00:00".into(),
+                updated_at: Some("2026-03-11T08:00:00+00:00".into()),
+            }).unwrap(),
+        ];
+
+        let mut found = Vec::new();
+        samples.iter().for_each(|sample| non_snake_keys(sample, "", &mut found));
+        assert!(found.is_empty(), "camelCase keys in responses: {:?}", found);
+    }
+
+    #[test]
+    fn legacy_input_names_are_still_accepted() {
+        let current: CreateCodeDto = serde_json::from_value(json!({
+            "code": "1", "display": "One", "system": "s", "category_id": "c"
+        })).unwrap();
+        let legacy: CreateCodeDto = serde_json::from_value(json!({
+            "code": "1", "display": "One", "system": "s", "categoryId": "c"
+        })).unwrap();
+        assert_eq!(current.category_id, legacy.category_id);
+
+        let import: ImportCodesDto = serde_json::from_value(json!({
+            "terminology": "loinc", "format": "csv", "content": "x", "categoryId": "c", "updateExisting": true
+        })).unwrap();
+        assert!(import.update_existing);
+    }
+
+    #[test]
+    fn version_one_restores_legacy_keys() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(ApiVersion::from_headers(&headers), ApiVersion::V2);
+        headers.insert(API_VERSION_HEADER, "1".parse().unwrap());
+        assert_eq!(ApiVersion::from_headers(&headers), ApiVersion::V1);
+
+        assert!(legacy_keys("/filesystem").is_empty());
+        let mut body = json!({ "data": [{ "id": "f", "created_at": "t" }], "timestamp": "t" });
+        rename_keys(&mut body, &legacy_keys("/files/abc"));
+        assert_eq!(body, json!({ "data": [{ "id": "f", "createdAt": "t" }], "timestamp": "t" }));
+    }
+}
@@ -0,0 +1,34 @@
+//! No-show tracking.
+//!
+//! A scheduled appointment nobody checked in for becomes `no_show` once
+//! `NO_SHOW_GRACE_MINUTES` (default 120) have passed since it started, checked every
+//! `SWEEP_INTERVAL`. Front desk staff can also mark one earlier with
+//! `POST /appointments/:id/no-show`. Each marked appointment is published as updated.
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::Utc;
+use crate::db::AppState;
+use crate::events::DomainEvent;
+use crate::handlers::appointment_handlers;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Spawn the sweep marking missed appointments as no-shows.
+pub fn spawn_worker(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            sweep.tick().await;
+            state.scheduler.run("no_shows", async {
+                let service = appointment_handlers::build_service(&state, crate::db::ReadContext::Primary);
+                let ids = service.mark_no_shows(Utc::now()).await?;
+                if !ids.is_empty() {
+                    tracing::info!(count = ids.len(), "Marked appointments as no-show");
+                }
+                ids.iter().for_each(|id| state.events.publish(DomainEvent::updated("appointments", id)));
+                Ok(())
+            }).await;
+        }
+    });
+}
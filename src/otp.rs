@@ -0,0 +1,272 @@
+//! One-time code delivery over SMS and WhatsApp.
+//!
+//! `OTP_PROVIDER` selects how codes reach the phone: `twilio` sends an SMS through the Twilio
+//! Messages API (`TWILIO_ACCOUNT_SID`, `TWILIO_AUTH_TOKEN`, `TWILIO_FROM`). For development,
+//! `log` writes each message, code included, to the server log so codes can be read there, and
+//! `whatsapp` is a stub logging the WhatsApp Business API message it would send with the code
+//! redacted; both are refused with `APP_ENV=production`. Unset, no code is sent and requesting one fails. A provider that is
+//! named but cannot be built, unknown or missing credentials, stops startup.
+//!
+//! Limits: codes live `OTP_TTL_SECONDS` (300) and allow `OTP_MAX_ATTEMPTS` (5) guesses; a new
+//! code for the same subject needs `OTP_RESEND_COOLDOWN_SECONDS` (60) to pass and at most
+//! `OTP_MAX_PER_HOUR` (5) are issued per hour.
+
+use std::env;
+use base64::Engine;
+use futures_util::future::BoxFuture;
+use hyper::Method;
+use rand::Rng;
+use crate::http_client::HttpClient;
+
+pub const PURPOSE_PATIENT_LOGIN: &str = "patient_login";
+pub const PURPOSE_PHONE_VERIFICATION: &str = "phone_verification";
+pub const PURPOSE_PATIENT_REGISTRATION: &str = "patient_registration";
+pub const PURPOSE_APPOINTMENT_BOOKING: &str = "appointment_booking";
+pub const TWILIO_API_URL: &str = "https://api.twilio.com";
+
+pub trait OtpProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn send<'a>(&'a self, to: &'a str, message: &'a str) -> BoxFuture<'a, Result<(), String>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct OtpConfig {
+    /// Empty when codes are not sent
+    pub provider: String,
+    /// `APP_ENV=production`, where the development providers are refused
+    pub production: bool,
+    pub ttl_seconds: i64,
+    pub max_attempts: i32,
+    pub resend_cooldown_seconds: i64,
+    pub max_per_hour: u64,
+    pub twilio_account_sid: Option<String>,
+    pub twilio_auth_token: Option<String>,
+    pub twilio_from: Option<String>,
+}
+
+impl Default for OtpConfig {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            production: false,
+            ttl_seconds: 300,
+            max_attempts: 5,
+            resend_cooldown_seconds: 60,
+            max_per_hour: 5,
+            twilio_account_sid: None,
+            twilio_auth_token: None,
+            twilio_from: None,
+        }
+    }
+}
+
+fn env_positive<T: std::str::FromStr + PartialOrd + Default>(name: &str, default: T) -> T {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|v| *v > T::default())
+        .unwrap_or(default)
+}
+
+impl OtpConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let secret = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            provider: env::var("OTP_PROVIDER").map(|p| p.trim().to_lowercase()).unwrap_or(defaults.provider),
+            production: env::var("APP_ENV").is_ok_and(|e| e.trim().eq_ignore_ascii_case("production")),
+            ttl_seconds: env_positive("OTP_TTL_SECONDS", defaults.ttl_seconds),
+            max_attempts: env_positive("OTP_MAX_ATTEMPTS", defaults.max_attempts),
+            resend_cooldown_seconds: env_positive("OTP_RESEND_COOLDOWN_SECONDS", defaults.resend_cooldown_seconds),
+            max_per_hour: env_positive("OTP_MAX_PER_HOUR", defaults.max_per_hour),
+            twilio_account_sid: secret("TWILIO_ACCOUNT_SID"),
+            twilio_auth_token: secret("TWILIO_AUTH_TOKEN"),
+            twilio_from: secret("TWILIO_FROM"),
+        }
+    }
+
+    /// The configured provider, or why it cannot be used
+    pub fn provider(&self) -> Result<Box<dyn OtpProvider>, String> {
+        let development = |provider: Box<dyn OtpProvider>| match self.production {
+            true => Err(format!("{} is for development only and is refused with APP_ENV=production", provider.name())),
+            false => Ok(provider),
+        };
+        match self.provider.as_str() {
+            "" => Ok(Box::new(DisabledProvider)),
+            "twilio" => TwilioProvider::from_config(self).map(|provider| Box::new(provider) as Box<dyn OtpProvider>),
+            "log" => development(Box::new(LogProvider)),
+            "whatsapp" => development(Box::new(WhatsAppStubProvider)),
+            other => Err(format!("unknown provider '{}'; expected twilio, log or whatsapp", other)),
+        }
+    }
+}
+
+pub fn generate_code() -> String {
+    format!("{:06}", rand::thread_rng().gen_range(0..1_000_000))
+}
+
+/// Last four digits of a phone number, for logs and responses
+pub fn mask_phone(phone: &str) -> String {
+    let digits: Vec<char> = phone.chars().filter(|c| c.is_ascii_digit()).collect();
+    let visible: String = digits.iter().skip(digits.len().saturating_sub(4)).collect();
+    format!("****{}", visible)
+}
+
+/// `message` with every run of four or more digits masked, so no code reaches a log
+pub fn redact_codes(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut digits = String::new();
+    for c in message.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        match digits.len() {
+            0..=3 => redacted.push_str(&digits),
+            n => redacted.push_str(&"*".repeat(n)),
+        }
+        digits.clear();
+        redacted.push(c);
+    }
+    redacted.pop();
+    redacted
+}
+
+/// No provider configured; every code request fails rather than reaching nobody
+pub struct DisabledProvider;
+
+impl OtpProvider for DisabledProvider {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn send<'a>(&'a self, _to: &'a str, _message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async { Err("OTP_PROVIDER is not set".to_string()) })
+    }
+}
+
+/// Logs each message with its code, to read codes in development without an SMS gateway; never
+/// built with `APP_ENV=production`.
+pub struct LogProvider;
+
+impl OtpProvider for LogProvider {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            tracing::info!(to = %mask_phone(to), message, "OTP");
+            Ok(())
+        })
+    }
+}
+
+pub struct TwilioProvider {
+    http: HttpClient,
+    base_url: String,
+    account_sid: String,
+    auth_token: String,
+    from: String,
+}
+
+impl TwilioProvider {
+    pub fn from_config(config: &OtpConfig) -> Result<Self, String> {
+        let missing = |name: &str| format!("{} is not set", name);
+        Ok(Self {
+            http: HttpClient::new()?,
+            base_url: TWILIO_API_URL.to_string(),
+            account_sid: config.twilio_account_sid.clone().ok_or_else(|| missing("TWILIO_ACCOUNT_SID"))?,
+            auth_token: config.twilio_auth_token.clone().ok_or_else(|| missing("TWILIO_AUTH_TOKEN"))?,
+            from: config.twilio_from.clone().ok_or_else(|| missing("TWILIO_FROM"))?,
+        })
+    }
+}
+
+impl OtpProvider for TwilioProvider {
+    fn name(&self) -> &'static str {
+        "twilio"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let url = format!("{}/2010-04-01/Accounts/{}/Messages.json", self.base_url, self.account_sid);
+            let credentials = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", self.account_sid, self.auth_token));
+            let auth = format!("Basic {}", credentials);
+            let body = url::form_urlencoded::Serializer::new(String::new())
+                .append_pair("To", to)
+                .append_pair("From", &self.from)
+                .append_pair("Body", message)
+                .finish();
+
+            let response = self.http
+                .send(Method::POST, &url, &[("authorization", auth.as_str())], "application/x-www-form-urlencoded", body.into_bytes())
+                .await?;
+            if response.is_success() {
+                Ok(())
+            } else {
+                Err(format!("Twilio returned {}: {}", response.status, response.text()))
+            }
+        })
+    }
+}
+
+/// Logs the WhatsApp Business API template message a real integration would send.
+pub struct WhatsAppStubProvider;
+
+impl OtpProvider for WhatsAppStubProvider {
+    fn name(&self) -> &'static str {
+        "whatsapp"
+    }
+
+    fn send<'a>(&'a self, to: &'a str, message: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let payload = serde_json::json!({
+                "messaging_product": "whatsapp",
+                "to": mask_phone(to),
+                "type": "text",
+                "text": { "body": redact_codes(message) },
+            });
+            tracing::info!(to = %mask_phone(to), %payload, "WhatsApp OTP stub");
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_phone_numbers() {
+        assert_eq!(mask_phone("+62 812-3456-7890"), "****7890");
+        assert_eq!(mask_phone("12"), "****12");
+    }
+
+    #[test]
+    fn codes_are_six_digits() {
+        let code = generate_code();
+        assert_eq!(code.len(), 6);
+        assert!(code.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn broken_or_development_providers_are_refused() {
+        let name = |config: OtpConfig| config.provider().map(|provider| provider.name());
+        assert_eq!(name(OtpConfig::default()), Ok("none"));
+        assert!(name(OtpConfig { provider: "twilio".to_string(), ..OtpConfig::default() }).unwrap_err().contains("TWILIO_ACCOUNT_SID"));
+        assert!(name(OtpConfig { provider: "twillio".to_string(), ..OtpConfig::default() }).is_err());
+        assert_eq!(name(OtpConfig { provider: "whatsapp".to_string(), ..OtpConfig::default() }), Ok("whatsapp"));
+        assert!(name(OtpConfig { provider: "log".to_string(), production: true, ..OtpConfig::default() }).is_err());
+    }
+
+    #[test]
+    fn logged_messages_never_carry_the_code() {
+        assert_eq!(
+            redact_codes("Your login code is 042917. It expires in 5 minutes."),
+            "Your login code is ******. It expires in 5 minutes.",
+        );
+        assert_eq!(redact_codes("1234"), "****");
+    }
+}
@@ -0,0 +1,229 @@
+//! Transactional outbox for messages to external systems.
+//!
+//! A change that others must hear about writes an `OutboxEntry` in the same transaction
+//! (`OutboxRepository::insert_with`), so the message survives a crash right after the write.
+//! The relay started by `spawn_relay` POSTs due entries to `OUTBOX_WEBHOOK_URL` as JSON,
+//! signed with `x-outbox-signature` (hex HMAC-SHA256 of the body under
+//! `OUTBOX_WEBHOOK_SECRET`) when a secret is set. Delivery is at least once: receivers
+//! deduplicate by `x-outbox-id`. Failures back off exponentially from 30 seconds up to an
+//! hour; after `OUTBOX_MAX_ATTEMPTS` (10) the entry is `failed` until an admin retries it.
+//! Without a webhook URL nothing is delivered and entries stay pending.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use hmac::{Hmac, Mac};
+use hyper::Method;
+use mongodb::bson::{oid::ObjectId, DateTime, Document};
+use sha2::Sha256;
+use crate::db::AppState;
+use crate::http_client::HttpClient;
+use crate::models::OutboxEntry;
+use crate::repository::OutboxRepository;
+use crate::status::OutboxStatus;
+
+pub const PAYMENT_RECORDED: &str = "payment.recorded";
+
+pub const DEFAULT_MAX_ATTEMPTS: i32 = 10;
+const DEFAULT_POLL_SECONDS: u64 = 5;
+/// How long a claimed entry is hidden from other relays
+const LEASE: Duration = Duration::from_secs(60);
+const FIRST_BACKOFF_SECONDS: u64 = 30;
+const MAX_BACKOFF_SECONDS: u64 = 3600;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutboxConfig {
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub poll_interval: Duration,
+    pub max_attempts: i32,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            webhook_url: None,
+            webhook_secret: None,
+            poll_interval: Duration::from_secs(DEFAULT_POLL_SECONDS),
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+        }
+    }
+}
+
+impl OutboxConfig {
+    pub fn from_env() -> Self {
+        let value = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let defaults = Self::default();
+        Self {
+            webhook_url: value("OUTBOX_WEBHOOK_URL"),
+            webhook_secret: value("OUTBOX_WEBHOOK_SECRET"),
+            poll_interval: value("OUTBOX_POLL_SECONDS")
+                .and_then(|v| v.parse().ok())
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.poll_interval),
+            max_attempts: value("OUTBOX_MAX_ATTEMPTS")
+                .and_then(|v| v.parse().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(defaults.max_attempts),
+        }
+    }
+}
+
+/// A pending entry about `aggregate_id` in `aggregate_type`, due now
+pub fn entry(topic: &str, aggregate_type: &str, aggregate_id: ObjectId, payload: Document) -> OutboxEntry {
+    let now = DateTime::now();
+    OutboxEntry {
+        id: None,
+        topic: topic.to_string(),
+        aggregate_type: aggregate_type.to_string(),
+        aggregate_id: aggregate_id.to_hex(),
+        payload,
+        status: OutboxStatus::Pending,
+        attempts: 0,
+        last_error: None,
+        next_attempt_at: now,
+        created_at: now,
+        published_at: None,
+    }
+}
+
+/// Wait before the attempt after `attempts` failed ones; `None` once they are used up.
+pub fn backoff(attempts: i32, max_attempts: i32) -> Option<Duration> {
+    if attempts >= max_attempts {
+        return None;
+    }
+    let exponent = attempts.saturating_sub(1).clamp(0, 16) as u32;
+    Some(Duration::from_secs((FIRST_BACKOFF_SECONDS << exponent).min(MAX_BACKOFF_SECONDS)))
+}
+
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// The JSON body a webhook receives
+pub fn message(entry: &OutboxEntry) -> serde_json::Value {
+    serde_json::json!({
+        "id": entry.id.map(|id| id.to_hex()),
+        "topic": entry.topic,
+        "aggregate_type": entry.aggregate_type,
+        "aggregate_id": entry.aggregate_id,
+        "payload": entry.payload,
+        "created_at": crate::datetime::to_rfc3339(entry.created_at),
+    })
+}
+
+struct Relay {
+    outbox: OutboxRepository,
+    http: HttpClient,
+    url: String,
+    secret: Option<String>,
+    max_attempts: i32,
+}
+
+impl Relay {
+    async fn deliver(&self, entry: &OutboxEntry) -> Result<(), String> {
+        let id = entry.id.map(|id| id.to_hex()).unwrap_or_default();
+        let body = serde_json::to_vec(&message(entry)).map_err(|e| e.to_string())?;
+        let signature = self.secret.as_deref().map(|secret| signature(secret, &body));
+        let mut headers = vec![("x-outbox-id", id.as_str()), ("x-outbox-topic", entry.topic.as_str())];
+        if let Some(signature) = &signature {
+            headers.push(("x-outbox-signature", signature.as_str()));
+        }
+
+        let response = self.http.send(Method::POST, &self.url, &headers, "application/json", body).await?;
+        if response.is_success() {
+            Ok(())
+        } else {
+            Err(format!("Webhook returned HTTP {}: {}", response.status, response.text()))
+        }
+    }
+
+    /// Deliver every due entry; returns how many were published.
+    async fn drain(&self) -> Result<usize, String> {
+        let mut published = 0;
+        while let Some(entry) = self.outbox.claim_due(LEASE).await? {
+            let Some(id) = entry.id else { continue };
+            let attempts = entry.attempts + 1;
+            match self.deliver(&entry).await {
+                Ok(()) => {
+                    self.outbox.mark_published(id, attempts).await?;
+                    published += 1;
+                }
+                Err(e) => {
+                    let retry_in = backoff(attempts, self.max_attempts);
+                    if retry_in.is_none() {
+                        tracing::error!(entry_id = %id, topic = %entry.topic, attempts, error = %e, "Outbox entry failed");
+                    }
+                    self.outbox.mark_attempt_failed(id, attempts, &e, retry_in).await?;
+                }
+            }
+        }
+        Ok(published)
+    }
+}
+
+/// Spawn the relay delivering outbox entries, when a webhook is configured.
+pub fn spawn_relay(state: Arc<AppState>) {
+    let config = state.config.outbox.clone();
+    let Some(url) = config.webhook_url else {
+        tracing::warn!("OUTBOX_WEBHOOK_URL not set; outbox entries are kept but not delivered");
+        return;
+    };
+    let http = match HttpClient::new() {
+        Ok(http) => http,
+        Err(e) => {
+            tracing::error!(error = %e, "Outbox relay not started");
+            return;
+        }
+    };
+    let relay = Relay {
+        outbox: OutboxRepository::new(state.db.clone()),
+        http,
+        url,
+        secret: config.webhook_secret,
+        max_attempts: config.max_attempts,
+    };
+
+    tokio::spawn(async move {
+        let mut poll = tokio::time::interval(config.poll_interval);
+        loop {
+            poll.tick().await;
+            if let Err(e) = relay.drain().await {
+                tracing::error!(error = %e, "Outbox relay failed");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour_then_gives_up() {
+        assert_eq!(backoff(1, 10), Some(Duration::from_secs(30)));
+        assert_eq!(backoff(2, 10), Some(Duration::from_secs(60)));
+        assert_eq!(backoff(9, 10), Some(Duration::from_secs(3600)));
+        assert_eq!(backoff(10, 10), None);
+    }
+
+    #[test]
+    fn messages_are_signed_over_the_body() {
+        let id = ObjectId::new();
+        let mut pending = entry(PAYMENT_RECORDED, "payments", id, doc! { "amount": 150000.0 });
+        pending.id = Some(ObjectId::new());
+        let body = message(&pending);
+        assert_eq!(body["aggregate_id"], serde_json::json!(id.to_hex()));
+        assert_eq!(body["payload"]["amount"], serde_json::json!(150000.0));
+
+        // RFC 4231 test case 2
+        assert_eq!(
+            signature("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Online payment links for invoices.
+//!
+//! `PAYMENT_GATEWAY` selects the provider: `midtrans` creates Snap transactions with
+//! `MIDTRANS_SERVER_KEY` (sandbox unless `MIDTRANS_PRODUCTION=true`) and `xendit` creates
+//! invoices with `XENDIT_SECRET_KEY`. Unset, online payment is disabled.
+//!
+//! Providers call `POST /payments/callback` when a payment settles. Midtrans notifications
+//! are verified by their `signature_key`, a SHA-512 of the order, status code, amount and
+//! server key; Xendit callbacks by the `x-callback-token` header, `XENDIT_CALLBACK_TOKEN`.
+
+use std::env;
+use axum::http::HeaderMap;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::future::BoxFuture;
+use hyper::Method;
+use serde::Deserialize;
+use sha2::{Digest, Sha512};
+use crate::http_client::HttpClient;
+use crate::status::{GatewayStatus, PaymentMethod};
+
+pub const MIDTRANS_SANDBOX_URL: &str = "https://app.sandbox.midtrans.com/snap/v1/transactions";
+pub const MIDTRANS_PRODUCTION_URL: &str = "https://app.midtrans.com/snap/v1/transactions";
+pub const XENDIT_INVOICE_URL: &str = "https://api.xendit.co/v2/invoices";
+
+/// What to charge; `order_id` comes back in the callback.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkRequest {
+    pub order_id: String,
+    pub amount: f64,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaymentLink {
+    pub url: String,
+    pub provider_reference: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+/// A verified callback
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatewayEvent {
+    pub order_id: String,
+    pub status: GatewayStatus,
+    pub amount: f64,
+    /// How the patient paid, as named by the provider
+    pub payment_type: Option<String>,
+    pub provider_reference: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallbackError {
+    /// Signature or token does not match
+    Unauthorized(String),
+    Invalid(String),
+}
+
+pub trait PaymentGateway: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn create_link<'a>(&'a self, request: &'a LinkRequest) -> BoxFuture<'a, Result<PaymentLink, String>>;
+    fn verify_callback(&self, headers: &HeaderMap, body: &[u8]) -> Result<GatewayEvent, CallbackError>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PaymentGatewayConfig {
+    pub provider: Option<String>,
+    pub midtrans_server_key: Option<String>,
+    pub midtrans_production: bool,
+    pub xendit_secret_key: Option<String>,
+    pub xendit_callback_token: Option<String>,
+}
+
+impl PaymentGatewayConfig {
+    pub fn from_env() -> Self {
+        let secret = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+        Self {
+            provider: env::var("PAYMENT_GATEWAY").ok().map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()),
+            midtrans_server_key: secret("MIDTRANS_SERVER_KEY"),
+            midtrans_production: env::var("MIDTRANS_PRODUCTION").is_ok_and(|v| v.trim().eq_ignore_ascii_case("true")),
+            xendit_secret_key: secret("XENDIT_SECRET_KEY"),
+            xendit_callback_token: secret("XENDIT_CALLBACK_TOKEN"),
+        }
+    }
+
+    /// The configured gateway; `None` when disabled or its keys are missing.
+    pub fn gateway(&self) -> Option<Box<dyn PaymentGateway>> {
+        let provider = self.provider.as_deref()?;
+        let gateway: Result<Box<dyn PaymentGateway>, String> = match provider {
+            "midtrans" => MidtransGateway::from_config(self).map(|g| Box::new(g) as Box<dyn PaymentGateway>),
+            "xendit" => XenditGateway::from_config(self).map(|g| Box::new(g) as Box<dyn PaymentGateway>),
+            other => Err(format!("unknown provider '{}'", other)),
+        };
+        gateway.map_err(|e| tracing::error!(provider, error = %e, "Payment gateway unavailable")).ok()
+    }
+}
+
+/// How a provider's payment type is recorded
+pub fn payment_method_for(payment_type: Option<&str>) -> PaymentMethod {
+    match payment_type.map(|t| t.to_lowercase()).as_deref() {
+        Some("qris") | Some("gopay") | Some("shopeepay") => PaymentMethod::Qris,
+        Some("bank_transfer") | Some("echannel") | Some("permata") | Some("direct_debit") => PaymentMethod::Transfer,
+        _ => PaymentMethod::Gateway,
+    }
+}
+
+fn basic_auth(key: &str) -> String {
+    format!("Basic {}", BASE64.encode(format!("{}:", key)))
+}
+
+/// Compare secrets without bailing out at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+pub struct MidtransGateway {
+    http: HttpClient,
+    server_key: String,
+    url: &'static str,
+}
+
+#[derive(Deserialize)]
+struct MidtransSnap {
+    token: String,
+    redirect_url: String,
+}
+
+#[derive(Deserialize)]
+struct MidtransNotification {
+    order_id: String,
+    status_code: String,
+    gross_amount: String,
+    signature_key: String,
+    transaction_status: String,
+    #[serde(default)]
+    fraud_status: Option<String>,
+    #[serde(default)]
+    payment_type: Option<String>,
+    #[serde(default)]
+    transaction_id: Option<String>,
+}
+
+impl MidtransGateway {
+    pub fn from_config(config: &PaymentGatewayConfig) -> Result<Self, String> {
+        Ok(Self {
+            http: HttpClient::new()?,
+            server_key: config.midtrans_server_key.clone().ok_or_else(|| "MIDTRANS_SERVER_KEY is not set".to_string())?,
+            url: if config.midtrans_production { MIDTRANS_PRODUCTION_URL } else { MIDTRANS_SANDBOX_URL },
+        })
+    }
+}
+
+/// Hex SHA-512 of `order_id + status_code + gross_amount + server_key`
+pub fn midtrans_signature(order_id: &str, status_code: &str, gross_amount: &str, server_key: &str) -> String {
+    let digest = Sha512::digest(format!("{}{}{}{}", order_id, status_code, gross_amount, server_key));
+    hex::encode(digest)
+}
+
+fn midtrans_event(notification: MidtransNotification, server_key: &str) -> Result<GatewayEvent, CallbackError> {
+    let expected = midtrans_signature(&notification.order_id, &notification.status_code, &notification.gross_amount, server_key);
+    if !constant_time_eq(expected.as_bytes(), notification.signature_key.to_lowercase().as_bytes()) {
+        return Err(CallbackError::Unauthorized("signature_key does not match".to_string()));
+    }
+
+    let status = match notification.transaction_status.as_str() {
+        "settlement" => GatewayStatus::Paid,
+        "capture" if notification.fraud_status.as_deref().is_none_or(|f| f == "accept") => GatewayStatus::Paid,
+        "capture" | "pending" | "authorize" => GatewayStatus::Pending,
+        "expire" => GatewayStatus::Expired,
+        _ => GatewayStatus::Failed,
+    };
+    let amount = notification.gross_amount.parse()
+        .map_err(|_| CallbackError::Invalid(format!("gross_amount '{}' is not a number", notification.gross_amount)))?;
+
+    Ok(GatewayEvent {
+        order_id: notification.order_id,
+        status,
+        amount,
+        payment_type: notification.payment_type,
+        provider_reference: notification.transaction_id,
+    })
+}
+
+impl PaymentGateway for MidtransGateway {
+    fn name(&self) -> &'static str {
+        "midtrans"
+    }
+
+    fn create_link<'a>(&'a self, request: &'a LinkRequest) -> BoxFuture<'a, Result<PaymentLink, String>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "transaction_details": { "order_id": request.order_id, "gross_amount": request.amount.ceil() as i64 },
+                "item_details": [{ "id": request.order_id, "name": request.description, "price": request.amount.ceil() as i64, "quantity": 1 }],
+            });
+            let auth = basic_auth(&self.server_key);
+            let response = self.http.send_json(Method::POST, self.url, &[("authorization", auth.as_str())], Some(&body)).await?;
+            if !response.is_success() {
+                return Err(format!("Midtrans returned {}: {}", response.status, response.text()));
+            }
+            let snap: MidtransSnap = response.json()?;
+            Ok(PaymentLink { url: snap.redirect_url, provider_reference: Some(snap.token), expires_at: None })
+        })
+    }
+
+    fn verify_callback(&self, _headers: &HeaderMap, body: &[u8]) -> Result<GatewayEvent, CallbackError> {
+        let notification: MidtransNotification = serde_json::from_slice(body)
+            .map_err(|e| CallbackError::Invalid(format!("Not a Midtrans notification: {}", e)))?;
+        midtrans_event(notification, &self.server_key)
+    }
+}
+
+pub struct XenditGateway {
+    http: HttpClient,
+    secret_key: String,
+    callback_token: String,
+}
+
+#[derive(Deserialize)]
+struct XenditInvoice {
+    id: String,
+    invoice_url: String,
+    #[serde(default)]
+    expiry_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct XenditCallback {
+    id: String,
+    external_id: String,
+    status: String,
+    #[serde(default)]
+    amount: f64,
+    #[serde(default)]
+    paid_amount: Option<f64>,
+    #[serde(default)]
+    payment_method: Option<String>,
+}
+
+impl XenditGateway {
+    pub fn from_config(config: &PaymentGatewayConfig) -> Result<Self, String> {
+        Ok(Self {
+            http: HttpClient::new()?,
+            secret_key: config.xendit_secret_key.clone().ok_or_else(|| "XENDIT_SECRET_KEY is not set".to_string())?,
+            callback_token: config.xendit_callback_token.clone().ok_or_else(|| "XENDIT_CALLBACK_TOKEN is not set".to_string())?,
+        })
+    }
+}
+
+fn xendit_event(headers: &HeaderMap, body: &[u8], callback_token: &str) -> Result<GatewayEvent, CallbackError> {
+    let token = headers.get("x-callback-token").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if !constant_time_eq(token.as_bytes(), callback_token.as_bytes()) {
+        return Err(CallbackError::Unauthorized("x-callback-token does not match".to_string()));
+    }
+    let callback: XenditCallback = serde_json::from_slice(body)
+        .map_err(|e| CallbackError::Invalid(format!("Not a Xendit invoice callback: {}", e)))?;
+
+    let status = match callback.status.to_uppercase().as_str() {
+        "PAID" | "SETTLED" => GatewayStatus::Paid,
+        "PENDING" => GatewayStatus::Pending,
+        "EXPIRED" => GatewayStatus::Expired,
+        _ => GatewayStatus::Failed,
+    };
+    Ok(GatewayEvent {
+        order_id: callback.external_id,
+        status,
+        amount: callback.paid_amount.unwrap_or(callback.amount),
+        payment_type: callback.payment_method,
+        provider_reference: Some(callback.id),
+    })
+}
+
+impl PaymentGateway for XenditGateway {
+    fn name(&self) -> &'static str {
+        "xendit"
+    }
+
+    fn create_link<'a>(&'a self, request: &'a LinkRequest) -> BoxFuture<'a, Result<PaymentLink, String>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "external_id": request.order_id,
+                "amount": request.amount,
+                "description": request.description,
+            });
+            let auth = basic_auth(&self.secret_key);
+            let response = self.http.send_json(Method::POST, XENDIT_INVOICE_URL, &[("authorization", auth.as_str())], Some(&body)).await?;
+            if !response.is_success() {
+                return Err(format!("Xendit returned {}: {}", response.status, response.text()));
+            }
+            let invoice: XenditInvoice = response.json()?;
+            Ok(PaymentLink { url: invoice.invoice_url, provider_reference: Some(invoice.id), expires_at: invoice.expiry_date })
+        })
+    }
+
+    fn verify_callback(&self, headers: &HeaderMap, body: &[u8]) -> Result<GatewayEvent, CallbackError> {
+        xendit_event(headers, body, &self.callback_token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(status: &str, signature: String) -> MidtransNotification {
+        MidtransNotification {
+            order_id: "ord-1".to_string(),
+            status_code: "200".to_string(),
+            gross_amount: "150000.00".to_string(),
+            signature_key: signature,
+            transaction_status: status.to_string(),
+            fraud_status: Some("accept".to_string()),
+            payment_type: Some("qris".to_string()),
+            transaction_id: Some("tx-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn midtrans_notifications_need_a_matching_signature() {
+        let signature = midtrans_signature("ord-1", "200", "150000.00", "server-key");
+        let event = midtrans_event(notification("settlement", signature.clone()), "server-key").unwrap();
+        assert_eq!(event.status, GatewayStatus::Paid);
+        assert_eq!(event.amount, 150_000.0);
+        assert_eq!(payment_method_for(event.payment_type.as_deref()), PaymentMethod::Qris);
+
+        assert_eq!(midtrans_event(notification("expire", signature), "server-key").unwrap().status, GatewayStatus::Expired);
+        assert!(matches!(
+            midtrans_event(notification("settlement", "00".repeat(64)), "server-key"),
+            Err(CallbackError::Unauthorized(_))
+        ));
+    }
+
+    #[test]
+    fn xendit_callbacks_need_the_callback_token() {
+        let body = br#"{"id":"inv-1","external_id":"ord-1","status":"PAID","amount":50000,"paid_amount":50000,"payment_method":"BANK_TRANSFER"}"#;
+        let mut headers = HeaderMap::new();
+        assert!(matches!(xendit_event(&headers, body, "token"), Err(CallbackError::Unauthorized(_))));
+
+        headers.insert("x-callback-token", "token".parse().unwrap());
+        let event = xendit_event(&headers, body, "token").unwrap();
+        assert_eq!((event.order_id.as_str(), event.status), ("ord-1", GatewayStatus::Paid));
+        assert_eq!(payment_method_for(event.payment_type.as_deref()), PaymentMethod::Transfer);
+    }
+}
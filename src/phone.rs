@@ -0,0 +1,107 @@
+//! Phone numbers in E.164 form, e.g. `+6281234567890`.
+//!
+//! Numbers are stored normalized so that lookups and OTP delivery see one spelling per
+//! number. Without a country code a number is read as Indonesian: `0812-3456-7890`,
+//! `62 812 3456 7890` and `812 3456 7890` all become `+6281234567890`; `00` is taken as
+//! the international prefix. Spaces, dashes, dots and parentheses are ignored.
+//!
+//! Indonesian numbers are checked against the national plan: mobile numbers (`8…`) have 9
+//! to 12 national digits, landlines (area codes `2`–`7`, `9`) 8 to 11. Other countries are
+//! only held to E.164's 8 to 15 digits.
+
+use validator::ValidationError;
+
+/// Country code assumed for numbers written without one
+pub const DEFAULT_COUNTRY_CODE: &str = "62";
+
+const SEPARATORS: &[char] = &[' ', '-', '.', '(', ')'];
+
+/// `raw` as an E.164 number, or why it is not a phone number.
+pub fn normalize(raw: &str) -> Result<String, String> {
+    let invalid = |reason: &str| format!("'{}' is not a valid phone number: {}", raw.trim(), reason);
+
+    let trimmed = raw.trim();
+    let (plus, rest) = match trimmed.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed),
+    };
+    if rest.chars().any(|c| !c.is_ascii_digit() && !SEPARATORS.contains(&c)) {
+        return Err(invalid("only digits, spaces, dashes, dots and parentheses are allowed"));
+    }
+    let digits: String = rest.chars().filter(char::is_ascii_digit).collect();
+    if digits.is_empty() {
+        return Err(invalid("no digits"));
+    }
+
+    let international = if plus {
+        digits
+    } else if let Some(international) = digits.strip_prefix("00") {
+        international.to_string()
+    } else if let Some(national) = digits.strip_prefix('0') {
+        format!("{}{}", DEFAULT_COUNTRY_CODE, national)
+    } else if digits.starts_with(DEFAULT_COUNTRY_CODE) {
+        digits
+    } else if digits.starts_with('8') {
+        format!("{}{}", DEFAULT_COUNTRY_CODE, digits)
+    } else {
+        return Err(invalid("add the country code, e.g. +62"));
+    };
+
+    if international.starts_with('0') {
+        return Err(invalid("country codes do not start with 0"));
+    }
+    if !(8..=15).contains(&international.len()) {
+        return Err(invalid("an international number has 8 to 15 digits"));
+    }
+    if let Some(national) = international.strip_prefix(DEFAULT_COUNTRY_CODE) {
+        check_indonesian(national).map_err(invalid)?;
+    }
+
+    Ok(format!("+{}", international))
+}
+
+fn check_indonesian(national: &str) -> Result<(), &'static str> {
+    match national.chars().next() {
+        Some('8') if (9..=12).contains(&national.len()) => Ok(()),
+        Some('8') => Err("Indonesian mobile numbers have 10 to 13 digits including the leading 0"),
+        Some('2'..='7' | '9') if (8..=11).contains(&national.len()) => Ok(()),
+        Some('2'..='7' | '9') => Err("Indonesian landline numbers have 9 to 12 digits including the leading 0"),
+        _ => Err("Indonesian numbers start with 08 or an area code"),
+    }
+}
+
+/// `#[validate(custom = ..)]` check for phone fields
+pub fn validate(value: &str) -> Result<(), ValidationError> {
+    normalize(value).map(|_| ()).map_err(|message| {
+        let mut error = ValidationError::new("phone");
+        error.message = Some(message.into());
+        error
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indonesian_spellings_normalize_to_one_number() {
+        for raw in ["081234567890", "0812-3456-7890", " +62 812 3456 7890 ", "6281234567890", "81234567890", "(0812) 3456.7890", "006281234567890"] {
+            assert_eq!(normalize(raw).unwrap(), "+6281234567890", "{}", raw);
+        }
+        assert_eq!(normalize("021-5551234").unwrap(), "+62215551234");
+        assert_eq!(normalize("+1 (415) 555-2671").unwrap(), "+14155552671");
+    }
+
+    #[test]
+    fn rejects_numbers_outside_the_plan() {
+        assert!(normalize("").is_err());
+        assert!(normalize("0812-ABCD-7890").is_err());
+        assert!(normalize("0812345").is_err());
+        assert!(normalize("08123456789012").is_err());
+        assert!(normalize("0112345678").is_err());
+        assert!(normalize("5551234").is_err());
+        assert!(normalize("+1234567890123456").is_err());
+        assert!(validate("12").is_err());
+        assert!(validate("0812 3456 7890").is_ok());
+    }
+}
@@ -0,0 +1,92 @@
+//! Chronic disease programs: who may enroll, and whether an enrolled patient is controlled.
+//!
+//! Both read the patient's latest observation of each coding a program names. A patient is
+//! eligible with a diagnosis under one of the program's codes or with a latest observation
+//! within one of its eligibility thresholds. In a cohort a patient is `uncontrolled` when the
+//! latest observation of any control target is outside it, `no_data` when a target was never
+//! observed and none is outside, and `controlled` otherwise.
+
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::models::{ObservationThreshold, ProgramEligibility};
+
+/// Whether a patient may enroll, and the rules that allow it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Eligibility {
+    pub eligible: bool,
+    /// e.g. `diagnosis E11.9` or `observation 8480-6 = 152`
+    pub met: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlStatus {
+    Controlled,
+    Uncontrolled,
+    NoData,
+}
+
+/// `code` is `program_code` or one of its subcodes, ignoring case
+pub fn diagnosis_matches(program_code: &str, code: &str) -> bool {
+    let program_code = program_code.trim();
+    !program_code.is_empty() && code.trim().to_uppercase().starts_with(&program_code.to_uppercase())
+}
+
+/// Eligibility of a patient with `diagnosis_codes` and the latest observed value of each coding
+pub fn eligibility(rules: &ProgramEligibility, diagnosis_codes: &[String], latest: &HashMap<&str, f64>) -> Eligibility {
+    if rules.diagnosis_codes.is_empty() && rules.observations.is_empty() {
+        return Eligibility { eligible: true, met: Vec::new() };
+    }
+
+    let diagnoses = diagnosis_codes.iter()
+        .filter(|code| rules.diagnosis_codes.iter().any(|program_code| diagnosis_matches(program_code, code)))
+        .map(|code| format!("diagnosis {}", code.trim().to_uppercase()));
+    let observations = rules.observations.iter()
+        .filter_map(|threshold| latest.get(threshold.code.as_str()).filter(|value| threshold.contains(**value)).map(|value| (threshold, value)))
+        .map(|(threshold, value)| format!("observation {} = {}", threshold.code, value));
+
+    let met: Vec<String> = diagnoses.chain(observations).collect();
+    Eligibility { eligible: !met.is_empty(), met }
+}
+
+pub fn control(targets: &[ObservationThreshold], latest: &HashMap<&str, f64>) -> ControlStatus {
+    let values: Vec<Option<&f64>> = targets.iter().map(|target| latest.get(target.code.as_str())).collect();
+    if targets.iter().zip(&values).any(|(target, value)| value.is_some_and(|value| !target.contains(*value))) {
+        ControlStatus::Uncontrolled
+    } else if values.iter().any(Option::is_none) {
+        ControlStatus::NoData
+    } else {
+        ControlStatus::Controlled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(code: &str, min: Option<f64>, max: Option<f64>) -> ObservationThreshold {
+        ObservationThreshold { code: code.to_string(), min, max }
+    }
+
+    #[test]
+    fn eligibility_and_control_follow_the_latest_observations() {
+        let rules = ProgramEligibility {
+            diagnosis_codes: vec!["I10".to_string(), "E11".to_string()],
+            observations: vec![threshold("8480-6", Some(140.0), None)],
+        };
+        let none = HashMap::new();
+
+        let by_diagnosis = eligibility(&rules, &["e11.9".to_string(), "J06".to_string()], &none);
+        assert_eq!(by_diagnosis, Eligibility { eligible: true, met: vec!["diagnosis E11.9".to_string()] });
+        let by_observation = eligibility(&rules, &[], &HashMap::from([("8480-6", 152.0)]));
+        assert_eq!(by_observation.met, vec!["observation 8480-6 = 152".to_string()]);
+        assert!(!eligibility(&rules, &["I1".to_string()], &HashMap::from([("8480-6", 128.0)])).eligible);
+        assert!(eligibility(&ProgramEligibility::default(), &[], &none).eligible);
+
+        let targets = [threshold("8480-6", None, Some(139.0)), threshold("8462-4", None, Some(89.0))];
+        assert_eq!(control(&targets, &HashMap::from([("8480-6", 132.0), ("8462-4", 85.0)])), ControlStatus::Controlled);
+        assert_eq!(control(&targets, &HashMap::from([("8480-6", 132.0), ("8462-4", 92.0)])), ControlStatus::Uncontrolled);
+        assert_eq!(control(&targets, &HashMap::from([("8480-6", 150.0)])), ControlStatus::Uncontrolled);
+        assert_eq!(control(&targets, &HashMap::from([("8480-6", 132.0)])), ControlStatus::NoData);
+    }
+}
@@ -0,0 +1,103 @@
+//! Storage quotas per uploader and organization.
+//!
+//! Every upload is charged to the user who made it and to their organization (the one named
+//! in the form's `organization_id`, else their first active role's); deleting a file refunds
+//! the bytes of all its versions. Running totals live in the `storage_usage` collection.
+//! `STORAGE_QUOTA_USER_MB` and `STORAGE_QUOTA_ORGANIZATION_MB` cap them; `0` (the default)
+//! only tracks. An upload that would go over a cap is refused with 413 and the remaining
+//! quota. Files uploaded before tracking started, and report deliveries, are not counted.
+
+use std::env;
+use crate::dto::file::QuotaExceeded;
+
+pub const SCOPE_USER: &str = "user";
+pub const SCOPE_ORGANIZATION: &str = "organization";
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorageQuotaConfig {
+    /// Bytes per uploader; `0` for no limit
+    pub user_bytes: u64,
+    /// Bytes per organization; `0` for no limit
+    pub organization_bytes: u64,
+}
+
+impl StorageQuotaConfig {
+    pub fn from_env() -> Self {
+        let megabytes = |name: &str| env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok()).unwrap_or(0);
+        Self {
+            user_bytes: megabytes("STORAGE_QUOTA_USER_MB").saturating_mul(MB),
+            organization_bytes: megabytes("STORAGE_QUOTA_ORGANIZATION_MB").saturating_mul(MB),
+        }
+    }
+
+    /// Cap of `scope`, `None` when unlimited
+    pub fn limit(&self, scope: &str) -> Option<u64> {
+        let limit = match scope {
+            SCOPE_USER => self.user_bytes,
+            SCOPE_ORGANIZATION => self.organization_bytes,
+            _ => 0,
+        };
+        (limit > 0).then_some(limit)
+    }
+
+    /// The quota `requested` more bytes would exceed, given `used` bytes so far
+    pub fn exceeded(&self, scope: &str, owner_id: &str, used: u64, requested: u64) -> Option<QuotaExceeded> {
+        let limit = self.limit(scope)?;
+        (used.saturating_add(requested) > limit).then(|| QuotaExceeded {
+            scope: scope.to_string(),
+            owner_id: owner_id.to_string(),
+            limit_bytes: limit,
+            used_bytes: used,
+            remaining_bytes: limit.saturating_sub(used),
+            requested_bytes: requested,
+        })
+    }
+}
+
+/// Who an upload is charged to
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UploadOwner {
+    pub user_id: Option<String>,
+    pub organization_id: Option<String>,
+}
+
+impl UploadOwner {
+    /// `(scope, owner id)` pairs the upload counts against
+    pub fn scopes(&self) -> Vec<(&'static str, &str)> {
+        let mut scopes = Vec::new();
+        if let Some(user_id) = &self.user_id {
+            scopes.push((SCOPE_USER, user_id.as_str()));
+        }
+        if let Some(organization_id) = &self.organization_id {
+            scopes.push((SCOPE_ORGANIZATION, organization_id.as_str()));
+        }
+        scopes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_uploads_over_the_cap_with_the_remaining_quota() {
+        let config = StorageQuotaConfig { user_bytes: 10 * MB, organization_bytes: 0 };
+        assert!(config.exceeded(SCOPE_USER, "u1", 4 * MB, 6 * MB).is_none());
+
+        let exceeded = config.exceeded(SCOPE_USER, "u1", 8 * MB, 3 * MB).unwrap();
+        assert_eq!((exceeded.limit_bytes, exceeded.remaining_bytes, exceeded.requested_bytes), (10 * MB, 2 * MB, 3 * MB));
+
+        // Unlimited scopes are only tracked
+        assert!(config.exceeded(SCOPE_ORGANIZATION, "o1", u64::MAX / 2, u64::MAX / 2).is_none());
+        // Over the cap already, e.g. after it was lowered
+        assert_eq!(config.exceeded(SCOPE_USER, "u1", 12 * MB, 1).unwrap().remaining_bytes, 0);
+    }
+
+    #[test]
+    fn charges_the_user_and_the_organization() {
+        let owner = UploadOwner { user_id: Some("u1".to_string()), organization_id: Some("o1".to_string()) };
+        assert_eq!(owner.scopes(), vec![(SCOPE_USER, "u1"), (SCOPE_ORGANIZATION, "o1")]);
+        assert!(UploadOwner::default().scopes().is_empty());
+    }
+}
@@ -0,0 +1,248 @@
+//! Permission-based access control.
+//!
+//! Roles (`UserRole.role.code`) hold permissions, an action on a resource such as
+//! `patients:read`, through the `role_permissions` collection. The role code `*` grants a
+//! permission to every authenticated user, and `*` as resource or action matches any, so the
+//! default `admin` grant of `*:*` covers permissions added later. When no grants exist yet,
+//! `seed_defaults` installs the rules that used to be hardcoded here.
+//!
+//! Service accounts have no roles; their tokens carry scopes in the same `resource:action`
+//! form, checked per request against `required_scope`.
+
+use std::collections::BTreeMap;
+use axum::http::Method;
+use mongodb::{bson::DateTime, Database};
+use crate::models::{Permission, RolePermission};
+use crate::repository::{PermissionRepository, RolePermissionRepository, UserRoleRepository};
+
+pub const ROLE_ADMIN: &str = "admin";
+pub const ROLE_DOCTOR: &str = "doctor";
+pub const ROLE_NURSE: &str = "nurse";
+pub const ROLE_RECEPTIONIST: &str = "receptionist";
+pub const ROLE_PHARMACIST: &str = "pharmacist";
+/// Sees only the kits of the distributors its organizations represent, see `crate::distributor`
+pub const ROLE_DISTRIBUTOR: &str = "distributor";
+
+/// Matches every role, resource or action
+pub const ANY: &str = "*";
+pub const ACTION_READ: &str = "read";
+pub const ACTION_WRITE: &str = "write";
+/// Resource and action guarding the `/admin` routes
+pub const ADMIN_ACCESS: (&str, &str) = ("admin", "access");
+/// Needed for `GET /observations/export.ndjson`, on top of `observations:read` for service accounts
+pub const OBSERVATIONS_EXPORT: (&str, &str) = ("observations", "export");
+
+/// Resources whose visibility depends on the caller's permissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Patients,
+    Doctors,
+    Medicines,
+    Appointments,
+}
+
+impl Resource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Resource::Patients => "patients",
+            Resource::Doctors => "doctors",
+            Resource::Medicines => "medicines",
+            Resource::Appointments => "appointments",
+        }
+    }
+}
+
+/// Grants installed on first start, as `(role, resource, action)`.
+pub fn default_grants() -> Vec<(&'static str, &'static str, &'static str)> {
+    let mut grants = vec![(ROLE_ADMIN, ANY, ANY), (ANY, "doctors", ACTION_READ)];
+    for role in [ROLE_DOCTOR, ROLE_NURSE, ROLE_RECEPTIONIST] {
+        grants.push((role, "patients", ACTION_READ));
+        grants.push((role, "appointments", ACTION_READ));
+    }
+    for role in [ROLE_DOCTOR, ROLE_NURSE, ROLE_PHARMACIST] {
+        grants.push((role, "medicines", ACTION_READ));
+    }
+    grants
+}
+
+/// Permissions resolved for one caller.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionSet {
+    grants: Vec<(String, String)>,
+}
+
+impl PermissionSet {
+    /// Permissions of `roles` among `(role, resource, action)` grants.
+    pub fn resolve<'a>(grants: impl IntoIterator<Item = (&'a str, &'a str, &'a str)>, roles: &[String]) -> Self {
+        let mut resolved: Vec<(String, String)> = grants
+            .into_iter()
+            .filter(|(role, _, _)| *role == ANY || roles.iter().any(|r| r == role))
+            .map(|(_, resource, action)| (resource.to_string(), action.to_string()))
+            .collect();
+        resolved.sort();
+        resolved.dedup();
+        Self { grants: resolved }
+    }
+
+    /// Permissions carried by service account scopes; malformed scopes grant nothing.
+    pub fn from_scopes(scopes: &[String]) -> Self {
+        let parsed: Vec<(String, String)> = scopes.iter().filter_map(|s| parse_scope(s)).collect();
+        Self::resolve(parsed.iter().map(|(r, a)| (ANY, r.as_str(), a.as_str())), &[])
+    }
+
+    pub fn allows(&self, resource: &str, action: &str) -> bool {
+        self.grants
+            .iter()
+            .any(|(r, a)| (r == ANY || r == resource) && (a == ANY || a == action))
+    }
+
+    pub fn can_read(&self, resource: Resource) -> bool {
+        self.allows(resource.name(), ACTION_READ)
+    }
+
+    /// Actions allowed per resource, as returned by `GET /auth/me/permissions`.
+    pub fn matrix(&self) -> BTreeMap<String, Vec<String>> {
+        let mut matrix: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (resource, action) in &self.grants {
+            matrix.entry(resource.clone()).or_default().push(action.clone());
+        }
+        matrix
+    }
+}
+
+/// Split a `resource:action` scope, normalized to lowercase.
+pub fn parse_scope(scope: &str) -> Option<(String, String)> {
+    let (resource, action) = scope.trim().split_once(':')?;
+    let (resource, action) = (resource.trim().to_lowercase(), action.trim().to_lowercase());
+    if resource.is_empty() || action.is_empty() {
+        return None;
+    }
+    Some((resource, action))
+}
+
+/// Scope a request needs: the first path segment as resource, `read` for safe methods and
+/// `write` for anything else.
+pub fn required_scope(method: &Method, path: &str) -> (String, &'static str) {
+    let resource = path.trim_start_matches('/').split('/').next().unwrap_or_default().to_string();
+    let action = if method == Method::GET || method == Method::HEAD { ACTION_READ } else { ACTION_WRITE };
+    (resource, action)
+}
+
+/// Load the active role codes assigned to a user.
+pub async fn load_role_codes(db: &Database, user_id: &str) -> Result<Vec<String>, String> {
+    UserRoleRepository::new(db.clone())
+        .find_active_role_codes(user_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Load a user's role codes and the permissions they grant.
+pub async fn load_permissions(db: &Database, user_id: &str) -> Result<(Vec<String>, PermissionSet), String> {
+    let roles = load_role_codes(db, user_id).await?;
+    let mut codes = roles.clone();
+    codes.push(ANY.to_string());
+
+    let grants = RolePermissionRepository::new(db.clone()).find_for_roles(Some(&codes)).await?;
+    let permissions = PermissionSet::resolve(
+        grants.iter().map(|g| (g.role_code.as_str(), g.resource.as_str(), g.action.as_str())),
+        &roles,
+    );
+    Ok((roles, permissions))
+}
+
+/// Install `default_grants` and their permissions when no grants exist yet, so a fresh
+/// database keeps the built-in access rules. Returns whether anything was seeded.
+pub async fn seed_defaults(db: &Database) -> Result<bool, String> {
+    let role_permissions = RolePermissionRepository::new(db.clone());
+    if role_permissions.count().await? > 0 {
+        return Ok(false);
+    }
+
+    let now = DateTime::now();
+    let permissions = PermissionRepository::new(db.clone());
+    let mut keys: Vec<(&str, &str)> = default_grants()
+        .into_iter()
+        .map(|(_, resource, action)| (resource, action))
+        .filter(|(resource, action)| *resource != ANY && *action != ANY)
+        .collect();
+    keys.push(ADMIN_ACCESS);
+    keys.sort();
+    keys.dedup();
+    for (resource, action) in keys {
+        if permissions.find_by_key(resource, action).await?.is_none() {
+            permissions.create(Permission {
+                id: None,
+                resource: resource.to_string(),
+                action: action.to_string(),
+                description: None,
+                created_at: now,
+                updated_at: None,
+            }).await?;
+        }
+    }
+
+    let grants = default_grants()
+        .into_iter()
+        .map(|(role, resource, action)| RolePermission {
+            id: None,
+            role_code: role.to_string(),
+            resource: resource.to_string(),
+            action: action.to_string(),
+            created_at: now,
+        })
+        .collect();
+    role_permissions.insert_many(grants).await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn defaults_for(roles: &[&str]) -> PermissionSet {
+        let roles: Vec<String> = roles.iter().map(|r| r.to_string()).collect();
+        PermissionSet::resolve(default_grants(), &roles)
+    }
+
+    #[test]
+    fn open_resources_allow_everyone() {
+        assert!(defaults_for(&[]).can_read(Resource::Doctors));
+    }
+
+    #[test]
+    fn restricted_resources_require_a_granted_role() {
+        let pharmacist = defaults_for(&[ROLE_PHARMACIST]);
+        assert!(pharmacist.can_read(Resource::Medicines));
+        assert!(!pharmacist.can_read(Resource::Patients));
+        assert!(!defaults_for(&[]).can_read(Resource::Appointments));
+    }
+
+    #[test]
+    fn wildcards_cover_later_permissions() {
+        let admin = defaults_for(&[ROLE_ADMIN]);
+        assert!(admin.allows(ADMIN_ACCESS.0, ADMIN_ACCESS.1));
+        assert!(admin.allows("invoices", "write"));
+        assert!(!defaults_for(&[ROLE_DOCTOR]).allows(ADMIN_ACCESS.0, ADMIN_ACCESS.1));
+    }
+
+    #[test]
+    fn scopes_cover_requests_by_resource_and_method() {
+        let scopes = PermissionSet::from_scopes(&["Observations:write".to_string(), "patients:read".to_string(), "bogus".to_string()]);
+        let (resource, action) = required_scope(&Method::POST, "/observations/bulk");
+        assert_eq!((resource.as_str(), action), ("observations", ACTION_WRITE));
+        assert!(scopes.allows(&resource, action));
+
+        let (resource, action) = required_scope(&Method::DELETE, "/patients/abc");
+        assert!(!scopes.allows(&resource, action));
+        assert!(scopes.allows("patients", ACTION_READ));
+        assert_eq!(parse_scope("patients:"), None);
+    }
+
+    #[test]
+    fn matrix_groups_actions_by_resource() {
+        let grants = [("nurse", "patients", "read"), ("nurse", "patients", "write"), ("doctor", "medicines", "read")];
+        let matrix = PermissionSet::resolve(grants, &["nurse".to_string()]).matrix();
+        assert_eq!(matrix.len(), 1);
+        assert_eq!(matrix["patients"], vec!["read".to_string(), "write".to_string()]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Recurrence rules for appointment series.
+//!
+//! A subset of RFC 5545 RRULE is supported: `FREQ=DAILY|WEEKLY|MONTHLY`, `INTERVAL`,
+//! `BYDAY` (weekly rules only), `COUNT` and `UNTIL`, e.g. `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=12`.
+//! Monthly rules repeat on the start date's day of the month and skip months without it.
+
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+
+/// Upper bound on occurrences generated from one rule, whatever the horizon.
+pub const MAX_OCCURRENCES: usize = 366;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    pub freq: Frequency,
+    pub interval: u32,
+    pub by_day: Vec<Weekday>,
+    pub count: Option<usize>,
+    pub until: Option<NaiveDate>,
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        other => Err(format!("Unknown BYDAY value '{}'", other)),
+    }
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    let date = value.split('T').next().unwrap_or(value);
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .map_err(|_| format!("Invalid UNTIL date '{}'", value))
+}
+
+impl RecurrenceRule {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let raw = raw.strip_prefix("RRULE:").unwrap_or(raw);
+
+        let mut freq = None;
+        let mut interval = 1;
+        let mut by_day = Vec::new();
+        let mut count = None;
+        let mut until = None;
+
+        for part in raw.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected KEY=VALUE, got '{}'", part))?;
+            let value = value.trim().to_uppercase();
+
+            match key.trim().to_uppercase().as_str() {
+                "FREQ" => {
+                    freq = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        other => return Err(format!("Unsupported FREQ '{}'", other)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value.parse().ok().filter(|i| *i > 0).ok_or_else(|| format!("Invalid INTERVAL '{}'", value))?
+                }
+                "BYDAY" => {
+                    by_day = value.split(',').map(|d| parse_weekday(d.trim())).collect::<Result<_, _>>()?
+                }
+                "COUNT" => {
+                    count = Some(value.parse().ok().filter(|c| *c > 0).ok_or_else(|| format!("Invalid COUNT '{}'", value))?)
+                }
+                "UNTIL" => until = Some(parse_date(&value)?),
+                other => return Err(format!("Unsupported rule part '{}'", other)),
+            }
+        }
+
+        let freq = freq.ok_or_else(|| "FREQ is required".to_string())?;
+        if !by_day.is_empty() && freq != Frequency::Weekly {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+        if count.is_some() && until.is_some() {
+            return Err("COUNT and UNTIL cannot be combined".to_string());
+        }
+
+        Ok(Self { freq, interval, by_day, count, until })
+    }
+
+    /// Occurrence dates from `start` (the first occurrence when it matches the rule) up to and
+    /// including `horizon`, limited by `COUNT`, `UNTIL` and `MAX_OCCURRENCES`.
+    pub fn expand(&self, start: NaiveDate, horizon: NaiveDate) -> Vec<NaiveDate> {
+        let end = self.until.map_or(horizon, |until| until.min(horizon));
+        let limit = self.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+        let mut dates = Vec::new();
+
+        let mut period = 0u32;
+        while dates.len() < limit {
+            let Some((period_start, candidates)) = self.period_dates(start, period) else {
+                break;
+            };
+            if period_start > end {
+                break;
+            }
+
+            for date in candidates {
+                if date >= start && date <= end && dates.len() < limit {
+                    dates.push(date);
+                }
+            }
+            period += 1;
+        }
+
+        dates
+    }
+
+    /// First day of the `period`-th repetition of the rule and its candidate dates, in order.
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Option<(NaiveDate, Vec<NaiveDate>)> {
+        let step = period.checked_mul(self.interval)?;
+        match self.freq {
+            Frequency::Daily => {
+                let date = start.checked_add_days(Days::new(step as u64))?;
+                Some((date, vec![date]))
+            }
+            Frequency::Weekly if self.by_day.is_empty() => {
+                let date = start.checked_add_days(Days::new(step as u64 * 7))?;
+                Some((date, vec![date]))
+            }
+            Frequency::Weekly => {
+                let monday = start - Days::new(start.weekday().num_days_from_monday() as u64);
+                let week_start = monday.checked_add_days(Days::new(step as u64 * 7))?;
+                let mut dates: Vec<NaiveDate> = self.by_day
+                    .iter()
+                    .map(|day| week_start + Days::new(day.num_days_from_monday() as u64))
+                    .collect();
+                dates.sort();
+                dates.dedup();
+                Some((week_start, dates))
+            }
+            Frequency::Monthly => {
+                let month_start = start.with_day(1)?.checked_add_months(Months::new(step))?;
+                Some((month_start, month_start.with_day(start.day()).into_iter().collect()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rule = RecurrenceRule::parse("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,th;COUNT=4").unwrap();
+        assert_eq!(rule.freq, Frequency::Weekly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(rule.by_day, vec![Weekday::Mon, Weekday::Thu]);
+        assert_eq!(rule.count, Some(4));
+
+        assert!(RecurrenceRule::parse("INTERVAL=2").is_err());
+        assert!(RecurrenceRule::parse("FREQ=YEARLY").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;BYDAY=MO").is_err());
+        assert!(RecurrenceRule::parse("FREQ=DAILY;COUNT=2;UNTIL=20260101").is_err());
+    }
+
+    #[test]
+    fn expands_weekly_by_day_with_count() {
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,TH;COUNT=4").unwrap();
+        // 2026-03-04 is a Wednesday, so the first Monday of its week is skipped
+        let dates = rule.expand(date(2026, 3, 4), date(2027, 1, 1));
+        assert_eq!(dates, vec![date(2026, 3, 5), date(2026, 3, 16), date(2026, 3, 19), date(2026, 3, 30)]);
+    }
+
+    #[test]
+    fn stops_at_until_or_horizon() {
+        let rule = RecurrenceRule::parse("FREQ=DAILY;INTERVAL=3;UNTIL=2026-01-10").unwrap();
+        assert_eq!(rule.expand(date(2026, 1, 1), date(2026, 12, 31)), vec![date(2026, 1, 1), date(2026, 1, 4), date(2026, 1, 7), date(2026, 1, 10)]);
+        assert_eq!(rule.expand(date(2026, 1, 1), date(2026, 1, 5)).len(), 2);
+    }
+
+    #[test]
+    fn monthly_skips_short_months() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY").unwrap();
+        let dates = rule.expand(date(2026, 1, 31), date(2026, 5, 31));
+        assert_eq!(dates, vec![date(2026, 1, 31), date(2026, 3, 31), date(2026, 5, 31)]);
+    }
+}
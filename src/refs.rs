@@ -0,0 +1,251 @@
+//! Typed references between documents and the checks that they resolve.
+//!
+//! A `Ref<T>` is an `ObjectId` of a `T` document. It is stored as the hex string the
+//! reference fields have always held, so existing documents and string filters keep
+//! working, and reads accept a native ObjectId as well. `ReferenceChecker` verifies on
+//! create and update that referenced documents exist, with one query per collection.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use axum::http::StatusCode;
+use futures_util::stream::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::options::FindOptions;
+use mongodb::Database;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::models::{Doctor, MedicalRecord, Medicine, Organization, Service};
+
+/// A document type other documents refer to.
+pub trait Referenced {
+    const COLLECTION: &'static str;
+    /// Used in error messages
+    const NAME: &'static str;
+    /// Required `type` of the document, for collections holding several kinds
+    const TYPE: Option<&'static str> = None;
+}
+
+impl Referenced for MedicalRecord {
+    const COLLECTION: &'static str = "medical_records";
+    const NAME: &'static str = "Patient";
+}
+
+impl Referenced for Doctor {
+    const COLLECTION: &'static str = "practitioners";
+    const NAME: &'static str = "Doctor";
+    const TYPE: Option<&'static str> = Some("doctor");
+}
+
+impl Referenced for Organization {
+    const COLLECTION: &'static str = "organizations";
+    const NAME: &'static str = "Organization";
+}
+
+impl Referenced for Service {
+    const COLLECTION: &'static str = "services";
+    const NAME: &'static str = "Service";
+}
+
+impl Referenced for Medicine {
+    const COLLECTION: &'static str = "medicines";
+    const NAME: &'static str = "Medicine";
+}
+
+pub struct Ref<T> {
+    id: ObjectId,
+    target: PhantomData<fn() -> T>,
+}
+
+impl<T> Ref<T> {
+    pub fn new(id: ObjectId) -> Self {
+        Self { id, target: PhantomData }
+    }
+
+    pub fn id(&self) -> ObjectId {
+        self.id
+    }
+
+    pub fn to_hex(&self) -> String {
+        self.id.to_hex()
+    }
+}
+
+impl<T: Referenced> Ref<T> {
+    /// Parse a request field; the error names the field, for a 422.
+    pub fn parse_field(field: &str, value: &str) -> Result<Self, (StatusCode, String)> {
+        ObjectId::parse_str(value.trim())
+            .map(Self::new)
+            .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, format!("{}: '{}' is not a valid {} ID", field, value, T::NAME)))
+    }
+
+    /// This reference as an existence check of `field`
+    pub fn check(&self, field: &'static str) -> RefCheck {
+        RefCheck { field, collection: T::COLLECTION, kind: T::TYPE, name: T::NAME, id: self.id }
+    }
+}
+
+impl<T> Clone for Ref<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Ref<T> {}
+
+impl<T> PartialEq for Ref<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Ref<T> {}
+
+impl<T> Hash for Ref<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl<T> PartialEq<str> for Ref<T> {
+    fn eq(&self, other: &str) -> bool {
+        self.id.to_hex() == other
+    }
+}
+
+impl<T> PartialEq<String> for Ref<T> {
+    fn eq(&self, other: &String) -> bool {
+        self.id.to_hex() == *other
+    }
+}
+
+impl<T> fmt::Debug for Ref<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ref({})", self.id.to_hex())
+    }
+}
+
+impl<T> fmt::Display for Ref<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.id.to_hex())
+    }
+}
+
+impl<T> From<Ref<T>> for Bson {
+    fn from(value: Ref<T>) -> Self {
+        Bson::String(value.to_hex())
+    }
+}
+
+impl<T> Serialize for Ref<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id.to_hex())
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Ref<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Native(ObjectId),
+            Hex(String),
+        }
+
+        match Stored::deserialize(deserializer)? {
+            Stored::Native(id) => Ok(Self::new(id)),
+            Stored::Hex(hex) => ObjectId::parse_str(&hex)
+                .map(Self::new)
+                .map_err(|_| serde::de::Error::custom(format!("'{}' is not a valid ObjectId", hex))),
+        }
+    }
+}
+
+/// One reference to verify: `id` must exist in `collection`, with `type` equal to `kind` when set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefCheck {
+    pub field: &'static str,
+    pub collection: &'static str,
+    pub kind: Option<&'static str>,
+    pub name: &'static str,
+    pub id: ObjectId,
+}
+
+pub struct ReferenceChecker {
+    db: Database,
+}
+
+impl ReferenceChecker {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// 422 naming the first field whose document does not exist.
+    pub async fn ensure_exist(&self, checks: &[RefCheck]) -> Result<(), (StatusCode, String)> {
+        let mut by_collection: BTreeMap<(&str, Option<&str>), Vec<ObjectId>> = BTreeMap::new();
+        for check in checks {
+            by_collection.entry((check.collection, check.kind)).or_default().push(check.id);
+        }
+
+        let mut found = Vec::new();
+        for ((collection, kind), ids) in by_collection {
+            let mut filter = doc! { "_id": { "$in": ids } };
+            if let Some(kind) = kind {
+                filter.insert("type", kind);
+            }
+            let options = FindOptions::builder().projection(doc! { "_id": 1 }).build();
+            let existing: Vec<Document> = self.db
+                .collection::<Document>(collection)
+                .find(filter, options)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .try_collect()
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            found.extend(existing.iter().filter_map(|d| d.get_object_id("_id").ok()).map(|id| (collection, id)));
+        }
+
+        match missing(checks, &found) {
+            Some(check) => Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("{}: {} {} does not exist", check.field, check.name, check.id.to_hex()),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The first check without a matching `(collection, id)` in `found`
+fn missing<'a>(checks: &'a [RefCheck], found: &[(&str, ObjectId)]) -> Option<&'a RefCheck> {
+    checks.iter().find(|check| !found.iter().any(|(collection, id)| *collection == check.collection && *id == check.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refs_store_as_hex_and_read_native_ids() {
+        let id = ObjectId::new();
+        let patient: Ref<MedicalRecord> = Ref::new(id);
+        assert_eq!(serde_json::to_value(patient).unwrap(), serde_json::json!(id.to_hex()));
+        assert_eq!(Bson::from(patient), Bson::String(id.to_hex()));
+
+        let native: Ref<MedicalRecord> = mongodb::bson::from_bson(Bson::ObjectId(id)).unwrap();
+        assert_eq!(native, patient);
+        assert!(serde_json::from_str::<Ref<Doctor>>("\"not-an-id\"").is_err());
+    }
+
+    #[test]
+    fn invalid_fields_and_missing_documents_are_named() {
+        let (status, message) = Ref::<Doctor>::parse_field("doctor_id", "123").unwrap_err();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(message.starts_with("doctor_id:"));
+
+        let patient = Ref::<MedicalRecord>::new(ObjectId::new()).check("patient_id");
+        let doctor = Ref::<Doctor>::new(ObjectId::new()).check("doctor_id");
+        let checks = [patient.clone(), doctor.clone()];
+        assert_eq!(missing(&checks, &[("medical_records", patient.id)]), Some(&doctor));
+        assert_eq!(missing(&checks, &[("medical_records", patient.id), ("practitioners", doctor.id)]), None);
+    }
+}
@@ -0,0 +1,283 @@
+//! Custom reports over stored aggregation templates.
+//!
+//! A `ReportTemplate` is an aggregation pipeline over one of `COLLECTIONS`, checked when
+//! it is saved: stages must be in `STAGES`, `$lookup` may only join the same collections
+//! and operators that write (`$out`, `$merge`) or run JavaScript are refused at any
+//! depth. String values of the form `"{{name}}"` are placeholders for declared
+//! parameters; a run binds them to typed values, appends a `$facet` for the requested
+//! page and flattens the documents into columns and rows.
+
+use std::time::Duration;
+use chrono::NaiveDate;
+use mongodb::bson::{doc, Bson, Document};
+use serde_json::{Map, Value};
+use crate::models::ReportParameter;
+use crate::pagination::PaginationParams;
+use crate::status::ReportParameterType;
+
+/// Collections a template may read. Users, sessions and credentials are left out.
+pub const COLLECTIONS: &[&str] = &[
+    "appointments", "admissions", "beds", "invoices", "medicines", "organizations", "payments", "practitioners", "services", "wards",
+];
+
+/// Read-only stages a template may use
+pub const STAGES: &[&str] = &[
+    "$match", "$project", "$addFields", "$set", "$unset", "$group", "$sort", "$limit", "$skip", "$unwind", "$count",
+    "$lookup", "$bucket", "$sortByCount", "$replaceRoot",
+];
+
+/// Refused anywhere in a pipeline, including inside `$lookup` and expressions
+const FORBIDDEN: &[&str] = &["$out", "$merge", "$where", "$function", "$accumulator"];
+
+pub const MAX_STAGES: usize = 20;
+/// Server-side limit of one run
+pub const MAX_TIME: Duration = Duration::from_secs(30);
+
+/// Placeholder name of `"{{name}}"`
+fn placeholder(value: &str) -> Option<&str> {
+    value.strip_prefix("{{")?.strip_suffix("}}").map(str::trim)
+}
+
+fn valid_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Check a template before it is stored; the error is shown to the author.
+pub fn validate_template(collection: &str, pipeline: &[Document], parameters: &[ReportParameter]) -> Result<(), String> {
+    if !COLLECTIONS.contains(&collection) {
+        return Err(format!("Collection '{}' is not available for reports; use one of {}", collection, COLLECTIONS.join(", ")));
+    }
+    if pipeline.is_empty() || pipeline.len() > MAX_STAGES {
+        return Err(format!("A pipeline needs between 1 and {} stages", MAX_STAGES));
+    }
+    validate_stages(pipeline)?;
+
+    let mut names = Vec::new();
+    for parameter in parameters {
+        if !valid_name(&parameter.name) {
+            return Err(format!("Parameter name '{}' must be lowercase letters, digits and underscores", parameter.name));
+        }
+        if names.contains(&parameter.name.as_str()) {
+            return Err(format!("Parameter '{}' is declared twice", parameter.name));
+        }
+        if !parameter.param_type.is_known() {
+            return Err(format!("Parameter '{}': type must be one of {}", parameter.name, ReportParameterType::KNOWN.join(", ")));
+        }
+        names.push(&parameter.name);
+    }
+
+    let mut used = Vec::new();
+    for stage in pipeline {
+        collect_placeholders(&Bson::Document(stage.clone()), &mut used);
+    }
+    match used.iter().find(|name| !names.contains(&name.as_str())) {
+        Some(name) => Err(format!("Placeholder '{{{{{}}}}}' has no declared parameter", name)),
+        None => Ok(()),
+    }
+}
+
+fn validate_stages(pipeline: &[Document]) -> Result<(), String> {
+    for (i, stage) in pipeline.iter().enumerate() {
+        let mut keys = stage.keys();
+        let (Some(name), None) = (keys.next(), keys.next()) else {
+            return Err(format!("Stage {} must have exactly one operator", i + 1));
+        };
+        if FORBIDDEN.contains(&name.as_str()) || !STAGES.contains(&name.as_str()) {
+            return Err(format!("Stage {}: {} is not allowed", i + 1, name));
+        }
+        forbid_operators(stage.get(name).unwrap())?;
+        if name == "$lookup" {
+            let lookup = stage.get_document(name).map_err(|_| format!("Stage {}: $lookup must be a document", i + 1))?;
+            let from = lookup.get_str("from").unwrap_or_default();
+            if !COLLECTIONS.contains(&from) {
+                return Err(format!("Stage {}: $lookup from '{}' is not available for reports", i + 1, from));
+            }
+            if let Ok(inner) = lookup.get_array("pipeline") {
+                let inner: Vec<Document> = inner.iter().filter_map(|s| s.as_document().cloned()).collect();
+                validate_stages(&inner)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn forbid_operators(value: &Bson) -> Result<(), String> {
+    match value {
+        Bson::Document(document) => {
+            for (key, value) in document {
+                if FORBIDDEN.contains(&key.as_str()) {
+                    return Err(format!("{} is not allowed in reports", key));
+                }
+                forbid_operators(value)?;
+            }
+            Ok(())
+        }
+        Bson::Array(values) => values.iter().try_for_each(forbid_operators),
+        _ => Ok(()),
+    }
+}
+
+fn collect_placeholders(value: &Bson, names: &mut Vec<String>) {
+    match value {
+        Bson::String(s) => names.extend(placeholder(s).map(str::to_string)),
+        Bson::Document(document) => document.values().for_each(|v| collect_placeholders(v, names)),
+        Bson::Array(values) => values.iter().for_each(|v| collect_placeholders(v, names)),
+        _ => {}
+    }
+}
+
+/// A request value as the parameter's type
+pub fn coerce(parameter: &ReportParameter, value: &Value) -> Result<Bson, String> {
+    let invalid = || format!("Parameter '{}' must be a {}", parameter.name, parameter.param_type);
+    match parameter.param_type {
+        ReportParameterType::String => value.as_str().map(Bson::from).ok_or_else(invalid),
+        ReportParameterType::Number => match value.as_i64() {
+            Some(n) => Ok(Bson::Int64(n)),
+            None => value.as_f64().map(Bson::Double).ok_or_else(invalid),
+        },
+        ReportParameterType::Boolean => value.as_bool().map(Bson::Boolean).ok_or_else(invalid),
+        ReportParameterType::Date => value.as_str()
+            .filter(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok())
+            .map(Bson::from)
+            .ok_or_else(|| format!("Parameter '{}' must be a YYYY-MM-DD date", parameter.name)),
+        ReportParameterType::Other(_) => Err(invalid()),
+    }
+}
+
+/// The pipeline with each placeholder replaced by its value or default.
+pub fn bind(pipeline: &[Document], parameters: &[ReportParameter], values: &Map<String, Value>) -> Result<Vec<Document>, String> {
+    if let Some(unknown) = values.keys().find(|key| !parameters.iter().any(|p| &p.name == *key)) {
+        return Err(format!("Unknown parameter '{}'", unknown));
+    }
+    let mut bound = Vec::new();
+    for parameter in parameters {
+        let value = match values.get(&parameter.name).filter(|v| !v.is_null()) {
+            Some(value) => Some(coerce(parameter, value)?),
+            None => parameter.default.clone(),
+        };
+        match value {
+            Some(value) => bound.push((parameter.name.as_str(), value)),
+            None if parameter.required => return Err(format!("Parameter '{}' is required", parameter.name)),
+            None => bound.push((parameter.name.as_str(), Bson::Null)),
+        }
+    }
+
+    Ok(pipeline.iter().map(|stage| match substitute(Bson::Document(stage.clone()), &bound) {
+        Bson::Document(stage) => stage,
+        _ => unreachable!(),
+    }).collect())
+}
+
+fn substitute(value: Bson, bound: &[(&str, Bson)]) -> Bson {
+    match value {
+        Bson::String(s) => match placeholder(&s).and_then(|name| bound.iter().find(|(n, _)| *n == name)) {
+            Some((_, value)) => value.clone(),
+            None => Bson::String(s),
+        },
+        Bson::Document(document) => Bson::Document(document.into_iter().map(|(k, v)| (k, substitute(v, bound))).collect()),
+        Bson::Array(values) => Bson::Array(values.into_iter().map(|v| substitute(v, bound)).collect()),
+        other => other,
+    }
+}
+
+/// The bound pipeline ending in a `$facet` with the page of rows and the total count
+pub fn paginated(mut pipeline: Vec<Document>, pagination: &PaginationParams) -> Vec<Document> {
+    pipeline.push(doc! {
+        "$facet": {
+            "rows": [{ "$skip": pagination.skip() as i64 }, { "$limit": pagination.limit as i64 }],
+            "total": [{ "$count": "count" }],
+        }
+    });
+    pipeline
+}
+
+/// Columns and rows of result documents. Without declared columns, the keys in the
+/// order they first appear. Missing cells are null.
+pub fn tabulate(documents: Vec<Document>, columns: &[String]) -> (Vec<String>, Vec<Vec<Value>>) {
+    let columns = if columns.is_empty() {
+        let mut keys: Vec<String> = Vec::new();
+        for key in documents.iter().flat_map(|d| d.keys()) {
+            if !keys.contains(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    } else {
+        columns.to_vec()
+    };
+
+    let rows = documents.into_iter().map(|mut document| {
+        columns.iter()
+            .map(|column| document.remove(column).map(|v| v.into_relaxed_extjson()).unwrap_or(Value::Null))
+            .collect()
+    }).collect();
+    (columns, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(name: &str, param_type: &str, required: bool) -> ReportParameter {
+        ReportParameter { name: name.to_string(), param_type: param_type.into(), required, default: None }
+    }
+
+    #[test]
+    fn refuses_writes_and_unlisted_collections() {
+        let group = doc! { "$group": { "_id": "$doctorId", "total": { "$sum": 1 } } };
+        assert!(validate_template("appointments", std::slice::from_ref(&group), &[]).is_ok());
+        assert!(validate_template("users", std::slice::from_ref(&group), &[]).is_err());
+
+        let out = validate_template("appointments", &[group.clone(), doc! { "$out": "copy" }], &[]).unwrap_err();
+        assert!(out.contains("$out"));
+        assert!(validate_template("appointments", &[doc! { "$merge": { "into": "copy" } }], &[]).is_err());
+
+        let nested = doc! { "$lookup": { "from": "practitioners", "as": "d", "pipeline": [{ "$merge": "x" }] } };
+        assert!(validate_template("appointments", &[nested], &[]).is_err());
+        let js = doc! { "$match": { "$expr": { "$function": { "body": "return true", "args": [], "lang": "js" } } } };
+        assert!(validate_template("appointments", &[js], &[]).is_err());
+        let users = doc! { "$lookup": { "from": "users", "localField": "a", "foreignField": "b", "as": "u" } };
+        assert!(validate_template("appointments", &[users], &[]).is_err());
+        assert!(validate_template("appointments", &[doc! { "$match": {}, "$limit": 1 }], &[]).is_err());
+    }
+
+    #[test]
+    fn placeholders_need_declared_parameters() {
+        let pipeline = [doc! { "$match": { "date": { "$gte": "{{from}}" } } }];
+        assert!(validate_template("appointments", &pipeline, &[]).unwrap_err().contains("{{from}}"));
+        assert!(validate_template("appointments", &pipeline, &[parameter("from", "date", true)]).is_ok());
+        assert!(validate_template("appointments", &pipeline, &[parameter("from", "date", true), parameter("from", "date", false)]).is_err());
+        assert!(validate_template("appointments", &pipeline, &[parameter("from", "datetime", true)]).is_err());
+    }
+
+    #[test]
+    fn binds_typed_values_and_defaults() {
+        let pipeline = [doc! { "$match": { "date": { "$gte": "{{from}}" }, "amount": { "$gte": "{{min}}" }, "note": "{{ note }}" } }];
+        let mut min = parameter("min", "number", false);
+        min.default = Some(Bson::Int64(0));
+        let parameters = [parameter("from", "date", true), min, parameter("note", "string", false)];
+
+        let values: Map<String, Value> = serde_json::from_str(r#"{ "from": "2026-03-01" }"#).unwrap();
+        let bound = bind(&pipeline, &parameters, &values).unwrap();
+        assert_eq!(bound[0], doc! { "$match": { "date": { "$gte": "2026-03-01" }, "amount": { "$gte": 0_i64 }, "note": Bson::Null } });
+
+        assert!(bind(&pipeline, &parameters, &Map::new()).unwrap_err().contains("required"));
+        let bad: Map<String, Value> = serde_json::from_str(r#"{ "from": "01/03/2026" }"#).unwrap();
+        assert!(bind(&pipeline, &parameters, &bad).is_err());
+        let unknown: Map<String, Value> = serde_json::from_str(r#"{ "from": "2026-03-01", "x": 1 }"#).unwrap();
+        assert!(bind(&pipeline, &parameters, &unknown).unwrap_err().contains("'x'"));
+    }
+
+    #[test]
+    fn pages_and_tabulates_results() {
+        let pipeline = paginated(vec![doc! { "$match": {} }], &PaginationParams::new(2, 10));
+        assert_eq!(pipeline[1].get_document("$facet").unwrap().get_array("rows").unwrap()[0], Bson::Document(doc! { "$skip": 10_i64 }));
+
+        let (columns, rows) = tabulate(vec![doc! { "_id": "d1", "total": 3 }, doc! { "_id": "d2", "name": "x" }], &[]);
+        assert_eq!(columns, ["_id", "total", "name"]);
+        assert_eq!(rows[1], vec![Value::from("d2"), Value::Null, Value::from("x")]);
+        let (columns, rows) = tabulate(vec![doc! { "_id": "d1", "total": 3 }], &["total".to_string()]);
+        assert_eq!((columns.len(), rows[0].clone()), (1, vec![Value::from(3)]));
+    }
+}
@@ -0,0 +1,267 @@
+//! Scheduled reports.
+//!
+//! A `ReportSchedule` names a report (`appointments_per_doctor`, `revenue` or `stock`), a
+//! cron expression evaluated in the clinic timezone, recipients and a format. The scheduler
+//! started by `spawn_scheduler` checks every minute for due schedules. A run renders the
+//! report for the `periodDays` days before the run date (stock is a snapshot), stores it as
+//! a CSV or PDF file with `FileService` and emails each recipient a summary with a link to
+//! the file through the configured mailer.
+
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{NaiveDate, Utc};
+use crate::cron::CronSchedule;
+use crate::db::AppState;
+use crate::refs::ReferenceChecker;
+use crate::repository::appointment::DoctorAppointmentRow;
+use crate::repository::medicine::StockRow;
+use crate::repository::payment::RevenueRow;
+use crate::repository::{AppointmentRepository, FileRepository, FileVersionRepository, MedicineRepository, PaymentRepository, ReportScheduleRepository, StorageUsageRepository};
+use crate::services::{FileService, ReportService};
+use crate::timezone::ClinicTimezone;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+const PDF_LINES_PER_PAGE: usize = 60;
+/// Rows quoted in the body of a report email
+pub const EMAIL_PREVIEW_ROWS: usize = 20;
+
+/// A rendered report: a title and rows of cells under named columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTable {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl ReportTable {
+    fn new(title: String, columns: &[&str]) -> Self {
+        Self { title, columns: columns.iter().map(|c| c.to_string()).collect(), rows: Vec::new() }
+    }
+
+    /// RFC 4180 CSV with a header row
+    pub fn to_csv(&self) -> String {
+        let line = |cells: &[String]| {
+            cells.iter()
+                .map(|cell| if cell.contains([',', '"', '\n']) { format!("\"{}\"", cell.replace('"', "\"\"")) } else { cell.clone() })
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let mut csv = line(&self.columns);
+        for row in &self.rows {
+            csv.push_str("\r\n");
+            csv.push_str(&line(row));
+        }
+        csv.push_str("\r\n");
+        csv
+    }
+
+    /// The title, header and at most `limit` rows as aligned plain text
+    pub fn text_lines(&self, limit: usize) -> Vec<String> {
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|i| self.rows.iter().filter_map(|row| row.get(i)).chain([&self.columns[i]]).map(|c| c.chars().count()).max().unwrap_or(0))
+            .collect();
+        let line = |cells: &[String]| {
+            cells.iter().zip(&widths).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect::<Vec<_>>().join("  ").trim_end().to_string()
+        };
+        let mut lines = vec![self.title.clone(), String::new(), line(&self.columns)];
+        lines.push(widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>().join("  "));
+        lines.extend(self.rows.iter().take(limit).map(|row| line(row)));
+        if self.rows.len() > limit {
+            lines.push(format!("... {} more rows", self.rows.len() - limit));
+        }
+        lines
+    }
+}
+
+/// `periodDays` days up to yesterday, as `YYYY-MM-DD` bounds
+pub fn report_period(today: NaiveDate, period_days: i32) -> (String, String) {
+    let from = today - chrono::Duration::days(period_days.max(1) as i64);
+    let to = today - chrono::Duration::days(1);
+    (from.format("%Y-%m-%d").to_string(), to.format("%Y-%m-%d").to_string())
+}
+
+/// The first run of `cron` after `now`, in the clinic's wall-clock time
+pub fn next_run(cron: &CronSchedule, timezone: &ClinicTimezone, now: chrono::DateTime<Utc>) -> Option<chrono::DateTime<Utc>> {
+    let next = cron.next_after(timezone.local(now).naive_local())?;
+    timezone.to_utc(&next.format("%Y-%m-%d").to_string(), &next.format("%H:%M").to_string()).ok()
+}
+
+pub fn appointments_table(from: &str, to: &str, rows: &[DoctorAppointmentRow]) -> ReportTable {
+    let mut table = ReportTable::new(format!("Appointments per doctor, {} to {}", from, to), &["doctor", "total", "completed", "cancelled", "no_show"]);
+    for row in rows {
+        let doctor = row.doctor_name.clone().unwrap_or_else(|| row.doctor_id.clone());
+        table.rows.push(vec![doctor, row.total.to_string(), row.completed.to_string(), row.cancelled.to_string(), row.no_show.to_string()]);
+    }
+    let sum = |field: fn(&DoctorAppointmentRow) -> i64| rows.iter().map(field).sum::<i64>().to_string();
+    table.rows.push(vec!["TOTAL".to_string(), sum(|r| r.total), sum(|r| r.completed), sum(|r| r.cancelled), sum(|r| r.no_show)]);
+    table
+}
+
+pub fn revenue_table(from: &str, to: &str, rows: &[RevenueRow]) -> ReportTable {
+    let mut table = ReportTable::new(format!("Revenue, {} to {}", from, to), &["date", "method", "payments", "amount"]);
+    for row in rows {
+        table.rows.push(vec![row.date.clone(), row.method.to_string(), row.count.to_string(), format!("{:.2}", row.amount)]);
+    }
+    let count: i64 = rows.iter().map(|r| r.count).sum();
+    let amount: f64 = rows.iter().map(|r| r.amount).sum();
+    table.rows.push(vec!["TOTAL".to_string(), String::new(), count.to_string(), format!("{:.2}", amount)]);
+    table
+}
+
+/// Stock on `today`; batches expiring within 90 days are flagged
+pub fn stock_table(today: NaiveDate, rows: &[StockRow]) -> ReportTable {
+    let mut table = ReportTable::new(format!("Medicine stock on {}", today), &["medicine", "master_id", "batches", "qty", "earliest_expiry", "note"]);
+    let soon = (today + chrono::Duration::days(90)).format("%Y-%m-%d").to_string();
+    let today = today.format("%Y-%m-%d").to_string();
+    for row in rows {
+        let note = if row.earliest_expiry.as_str() < today.as_str() {
+            "expired batch"
+        } else if row.earliest_expiry <= soon {
+            "expires within 90 days"
+        } else {
+            ""
+        };
+        table.rows.push(vec![
+            row.trade_name.clone(),
+            row.master_medicine_id.clone(),
+            row.batches.to_string(),
+            format!("{}", row.qty),
+            row.earliest_expiry.clone(),
+            note.to_string(),
+        ]);
+    }
+    table
+}
+
+fn pdf_text(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '\\' | '(' | ')' => format!("\\{}", c),
+            c if c.is_ascii() && !c.is_ascii_control() => c.to_string(),
+            _ => "?".to_string(),
+        })
+        .collect()
+}
+
+/// A plain PDF of `lines` in 9 pt Courier on A4 pages
+pub fn text_pdf(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() { vec![&[][..]] } else { lines.chunks(PDF_LINES_PER_PAGE).collect() };
+    // Objects 1-3 are the catalog, the page tree and the font; each page adds a page and its content
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "), pages.len()),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        let mut content = String::from("BT /F1 9 Tf 12 TL 40 802 Td\n");
+        for line in page.iter() {
+            content.push_str(&format!("({}) Tj T*\n", pdf_text(line)));
+        }
+        content.push_str("ET");
+        objects.push(format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 595 842] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>", id + 1));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", i + 1, object));
+    }
+    let xref = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!("trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref));
+    pdf.into_bytes()
+}
+
+pub fn build_service(state: &AppState) -> ReportService {
+    ReportService::new(
+        ReportScheduleRepository::new(state.db.clone()),
+        AppointmentRepository::new(state.db.clone()),
+        PaymentRepository::new(state.db.clone()),
+        MedicineRepository::new(state.db.clone()),
+        FileService::new(
+            FileRepository::new(state.db.clone()),
+            FileVersionRepository::new(state.db.clone()),
+            StorageUsageRepository::new(state.db.clone()),
+            state.storage.clone(),
+            ReferenceChecker::new(state.db.clone()),
+            &state.config.storage_quotas,
+        ),
+        ReferenceChecker::new(state.db.clone()),
+        state.config.email.mailer(),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+/// Spawn the task running due report schedules.
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            sweep.tick().await;
+            state.scheduler.run("scheduled_reports", async {
+                let runs = build_service(&state).run_due(Utc::now()).await?;
+                if runs > 0 {
+                    tracing::info!(runs, "Ran scheduled reports");
+                }
+                Ok(())
+            }).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::PaymentMethod;
+    use chrono::TimeZone;
+
+    fn date(raw: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(raw, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn periods_end_yesterday_and_runs_follow_clinic_time() {
+        assert_eq!(report_period(date("2026-03-10"), 7), ("2026-03-03".to_string(), "2026-03-09".to_string()));
+        assert_eq!(report_period(date("2026-03-10"), 1), ("2026-03-09".to_string(), "2026-03-09".to_string()));
+
+        // 07:00 in Jakarta is 00:00 UTC
+        let cron = CronSchedule::parse("0 7 * * *").unwrap();
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 1, 0, 0).unwrap();
+        assert_eq!(next_run(&cron, &ClinicTimezone::default(), now), Some(Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn tables_total_their_rows() {
+        let rows = [
+            RevenueRow { date: "2026-03-09".into(), method: PaymentMethod::Cash, count: 2, amount: 100_000.0 },
+            RevenueRow { date: "2026-03-09".into(), method: PaymentMethod::Qris, count: 1, amount: 25_000.5 },
+        ];
+        let table = revenue_table("2026-03-09", "2026-03-09", &rows);
+        assert_eq!(table.rows.last().unwrap(), &vec!["TOTAL".to_string(), String::new(), "3".to_string(), "125000.50".to_string()]);
+        assert!(table.to_csv().starts_with("date,method,payments,amount\r\n2026-03-09,cash,2,100000.00\r\n"));
+
+        let stock = stock_table(date("2026-03-10"), &[StockRow {
+            master_medicine_id: "m1".into(), trade_name: "Paracetamol, 500 mg".into(), batches: 2, qty: 40.0, earliest_expiry: "2026-05-01".into(),
+        }]);
+        assert_eq!(stock.rows[0][5], "expires within 90 days");
+        assert!(stock.to_csv().contains("\"Paracetamol, 500 mg\",m1"));
+    }
+
+    #[test]
+    fn pdfs_paginate_and_escape_text() {
+        let lines: Vec<String> = (0..61).map(|i| format!("line (#{})", i)).collect();
+        let pdf = String::from_utf8(text_pdf(&lines)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4"));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(line \\(#60\\)) Tj"));
+
+        let xref: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert!(pdf[xref..].starts_with("xref\n0 8\n"));
+    }
+}
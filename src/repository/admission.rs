@@ -0,0 +1,109 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Admission;
+use crate::pagination::PaginationParams;
+use crate::status::{AdmissionStatus, TimelineEntryType};
+use crate::timeline::{self, Position, TimeField};
+use futures_util::stream::TryStreamExt;
+
+pub struct AdmissionRepository {
+    collection: Collection<Admission>,
+}
+
+impl AdmissionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Admission>("admissions");
+        Self { collection }
+    }
+
+    /// The patient's admissions after `after` in their timeline, newest first
+    pub async fn find_timeline(&self, patient_id: &str, after: Option<&Position>, limit: i64) -> Result<Vec<Admission>, String> {
+        let field = TimeField::Date("admittedAt");
+        let filter = timeline::source_filter(doc! { "patientId": patient_id }, &TimelineEntryType::Encounter, field, after);
+        let options = FindOptions::builder().sort(timeline::newest_first(field)).limit(limit).build();
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn insert(&self, admission: Admission) -> Result<Admission, String> {
+        self.collection
+            .insert_one(admission.clone(), None)
+            .await
+            .map(|_| admission)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Admission>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_active_by_patient(&self, patient_id: &str) -> Result<Option<Admission>, String> {
+        self.collection
+            .find_one(doc! { "patientId": patient_id, "status": AdmissionStatus::Admitted }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Most recent first, optionally narrowed to a status and a ward
+    pub async fn find_paginated(&self, status: Option<&AdmissionStatus>, ward_id: Option<&str>, pagination: &PaginationParams) -> Result<(Vec<Admission>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+        if let Some(ward_id) = ward_id {
+            filter.insert("wardId", ward_id);
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "admittedAt": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+        let admissions = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((admissions, total))
+    }
+
+    /// Apply `update` to an admission that is still active and in `bed_id`
+    pub async fn update_active(&self, id: ObjectId, bed_id: &str, update: Document) -> Result<Option<Admission>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "bedId": bed_id, "status": AdmissionStatus::Admitted }, update, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Move a merged duplicate's admissions to the patient it was merged into, within
+    /// `session`'s transaction
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
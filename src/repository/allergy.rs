@@ -0,0 +1,77 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Allergy;
+use futures_util::stream::TryStreamExt;
+
+pub struct AllergyRepository {
+    collection: Collection<Allergy>,
+}
+
+impl AllergyRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Allergy>("allergies");
+        Self { collection }
+    }
+
+    pub async fn create(&self, allergy: Allergy) -> Result<Allergy, String> {
+        let result = self
+            .collection
+            .insert_one(allergy.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_allergy = allergy;
+        created_allergy.id = result.inserted_id.as_object_id();
+
+        Ok(created_allergy)
+    }
+
+    /// A patient's allergies, newest first
+    pub async fn find_by_patient(&self, patient_id: &str) -> Result<Vec<Allergy>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "createdAt": -1 })
+            .build();
+
+        self.collection
+            .find(doc! { "patientId": patient_id }, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, patient_id: &str, id: ObjectId, set: Document) -> Result<Option<Allergy>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "patientId": patient_id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, patient_id: &str, id: ObjectId) -> Result<bool, String> {
+        let result = self
+            .collection
+            .delete_one(doc! { "_id": id, "patientId": patient_id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Move a merged duplicate's allergies to the patient it was merged into, within
+    /// `session`'s transaction
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to, "updatedAt": DateTime::now() } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,7 +1,170 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Database,
+};
 use futures_util::stream::TryStreamExt;
-use crate::models::Appointment;
+use serde::Deserialize;
+use crate::delete_policy::DELETED_AT;
+use crate::models::{Appointment, QueueCounter};
 use crate::pagination::PaginationParams;
+use crate::status::{AppointmentStatus, TimelineEntryType};
+use crate::timeline::{self, Position, TimeField};
+
+/// Referenced documents to embed in list results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Expansion {
+    pub doctor: bool,
+    pub patient: bool,
+}
+
+impl Expansion {
+    pub fn is_empty(&self) -> bool {
+        !self.doctor && !self.patient
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DoctorSummaryRow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub name: String,
+    #[serde(default)]
+    pub specialization: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PatientSummaryRow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub name: String,
+    #[serde(default)]
+    pub nrme: String,
+}
+
+/// An appointment with the documents of `Expansion` it references; `None` when not
+/// requested or when the reference dangles.
+#[derive(Debug)]
+pub struct ExpandedAppointment {
+    pub appointment: Appointment,
+    pub doctor: Option<DoctorSummaryRow>,
+    pub patient: Option<PatientSummaryRow>,
+}
+
+fn list_filter(patient_id: Option<&str>, status: Option<&AppointmentStatus>) -> Document {
+    let mut filter = doc! { DELETED_AT: Bson::Null };
+    if let Some(patient_id) = patient_id {
+        filter.insert("patientId", patient_id);
+    }
+    if let Some(status) = status {
+        filter.insert("status", status.as_str());
+    }
+    filter
+}
+
+/// `$lookup` of the document `local_field` refers to, kept as a single embedded `as` document.
+/// References are stored as hex strings, so they are converted before matching `_id`.
+pub(crate) fn lookup_one(from: &str, local_field: &str, as_field: &str, fields: &[&str]) -> [Document; 2] {
+    let mut project = Document::new();
+    for field in fields {
+        project.insert(*field, 1);
+    }
+    [
+        doc! { "$lookup": {
+            "from": from,
+            "let": { "ref": { "$convert": { "input": format!("${}", local_field), "to": "objectId", "onError": Bson::Null, "onNull": Bson::Null } } },
+            "pipeline": [
+                { "$match": { "$expr": { "$eq": ["$_id", "$$ref"] } } },
+                { "$project": project },
+            ],
+            "as": as_field,
+        }},
+        doc! { "$set": { as_field: { "$arrayElemAt": [format!("${}", as_field), 0] } } },
+    ]
+}
+
+/// The aggregation of one page of the list with the requested lookups.
+fn expanded_pipeline(filter: Document, skip: u64, limit: i64, expansion: Expansion) -> Vec<Document> {
+    let mut pipeline = vec![
+        doc! { "$match": filter },
+        doc! { "$skip": skip as i64 },
+        doc! { "$limit": limit },
+    ];
+    if expansion.doctor {
+        pipeline.extend(lookup_one("practitioners", "doctorId", "doctor", &["name", "specialization"]));
+    }
+    if expansion.patient {
+        pipeline.extend(lookup_one("medical_records", "patientId", "patient", &["name", "nrme"]));
+    }
+    pipeline
+}
+
+fn take_embedded<T: serde::de::DeserializeOwned>(document: &mut Document, key: &str) -> Result<Option<T>, String> {
+    match document.remove(key) {
+        Some(Bson::Document(embedded)) => mongodb::bson::from_document(embedded).map(Some).map_err(|e| e.to_string()),
+        _ => Ok(None),
+    }
+}
+
+/// Appointments of one doctor in a date range by outcome, see
+/// `AppointmentRepository::count_per_doctor`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct DoctorAppointmentRow {
+    pub doctor_id: String,
+    #[serde(default)]
+    pub doctor_name: Option<String>,
+    pub total: i64,
+    pub completed: i64,
+    pub cancelled: i64,
+    pub no_show: i64,
+}
+
+fn per_doctor_pipeline(from: &str, to: &str, organization_id: Option<&str>) -> Vec<Document> {
+    let mut filter = doc! { "date": { "$gte": from, "$lte": to }, DELETED_AT: Bson::Null };
+    if let Some(organization_id) = organization_id {
+        filter.insert("organizationId", organization_id);
+    }
+    let count_status = |status: AppointmentStatus| doc! { "$sum": { "$cond": [{ "$eq": ["$status", status] }, 1, 0] } };
+    let mut pipeline = vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": "$doctorId",
+            "total": { "$sum": 1 },
+            "completed": count_status(AppointmentStatus::Completed),
+            "cancelled": count_status(AppointmentStatus::Cancelled),
+            "no_show": count_status(AppointmentStatus::NoShow),
+        }},
+    ];
+    pipeline.extend(lookup_one("practitioners", "_id", "doctor", &["name"]));
+    pipeline.push(doc! { "$project": {
+        "_id": 0,
+        "doctor_id": "$_id",
+        "doctor_name": "$doctor.name",
+        "total": 1, "completed": 1, "cancelled": 1, "no_show": 1,
+    }});
+    pipeline.push(doc! { "$sort": { "total": -1, "doctor_id": 1 } });
+    pipeline
+}
+
+/// Outcome and consult times of one appointment, see `AppointmentRepository::find_outcomes`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct AppointmentOutcomeRow {
+    pub status: AppointmentStatus,
+    #[serde(rename = "calledAt", default)]
+    pub called_at: Option<String>,
+    #[serde(rename = "completedAt", default)]
+    pub completed_at: Option<String>,
+}
+
+/// Scheduled appointments that started before `cutoff` without a check-in
+fn no_show_filter(cutoff: DateTime) -> Document {
+    doc! {
+        "status": AppointmentStatus::Scheduled,
+        "queueNumber": Bson::Null,
+        "startsAt": { "$lt": cutoff },
+        DELETED_AT: Bson::Null,
+    }
+}
 
 pub struct AppointmentRepository {
     db: Database,
@@ -12,6 +175,35 @@ impl AppointmentRepository {
         Self { db }
     }
 
+    /// The patient's appointments after `after` in their timeline, newest first; ones booked
+    /// before `startsAt` was stored and never backfilled have no place in it
+    pub async fn find_timeline(&self, patient_id: &str, after: Option<&Position>, limit: i64) -> Result<Vec<Appointment>, String> {
+        let field = TimeField::Date("startsAt");
+        let base = doc! { "patientId": patient_id, "startsAt": { "$type": "date" }, DELETED_AT: Bson::Null };
+        let filter = timeline::source_filter(base, &TimelineEntryType::Appointment, field, after);
+        let options = FindOptions::builder().sort(timeline::newest_first(field)).limit(limit).build();
+        self.db.collection::<Appointment>("appointments")
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Appointments dated `from` to `to` (inclusive) per doctor, busiest first
+    pub async fn count_per_doctor(&self, from: &str, to: &str, organization_id: Option<&str>) -> Result<Vec<DoctorAppointmentRow>, String> {
+        let cursor = self.db.collection::<Document>("appointments")
+            .aggregate(per_doctor_pipeline(from, to, organization_id), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
     pub async fn find_all(&self) -> Result<Vec<Appointment>, String> {
         let collection = self.db.collection::<Appointment>("appointments");
         match collection.find(doc! {}, None).await {
@@ -25,11 +217,12 @@ impl AppointmentRepository {
         }
     }
 
-    pub async fn find_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<Appointment>, u64), String> {
+    pub async fn find_all_paginated(&self, pagination: PaginationParams, patient_id: Option<&str>, status: Option<&AppointmentStatus>) -> Result<(Vec<Appointment>, u64), String> {
         let collection = self.db.collection::<Appointment>("appointments");
+        let filter = list_filter(patient_id, status);
         
         let total = collection
-            .count_documents(doc! {}, None)
+            .count_documents(filter.clone(), None)
             .await
             .map_err(|e| format!("Failed to count documents: {}", e))?;
 
@@ -38,7 +231,7 @@ impl AppointmentRepository {
             .limit(pagination.limit() as i64)
             .build();
 
-        match collection.find(doc! {}, options).await {
+        match collection.find(filter, options).await {
             Ok(cursor) => {
                 let records = cursor
                     .try_collect::<Vec<Appointment>>()
@@ -50,12 +243,46 @@ impl AppointmentRepository {
         }
     }
 
+    /// `find_all_paginated` with the referenced doctor and patient joined in one aggregation
+    pub async fn find_all_expanded(
+        &self,
+        pagination: PaginationParams,
+        patient_id: Option<&str>,
+        status: Option<&AppointmentStatus>,
+        expansion: Expansion,
+    ) -> Result<(Vec<ExpandedAppointment>, u64), String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let filter = list_filter(patient_id, status);
+        let total = collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| format!("Failed to count documents: {}", e))?;
+
+        let pipeline = expanded_pipeline(filter, pagination.skip(), pagination.limit() as i64, expansion);
+        let docs: Vec<Document> = collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))?;
+
+        let rows = docs.into_iter().map(|mut d| {
+            let doctor = take_embedded(&mut d, "doctor")?;
+            let patient = take_embedded(&mut d, "patient")?;
+            let appointment = mongodb::bson::from_document(d).map_err(|e| e.to_string())?;
+            Ok(ExpandedAppointment { appointment, doctor, patient })
+        }).collect::<Result<Vec<_>, String>>()?;
+        Ok((rows, total))
+    }
+
     pub async fn insert(&self, mut appointment: Appointment) -> Result<Appointment, String> {
         let collection = self.db.collection::<Appointment>("appointments");
         
         if appointment.id.is_none() {
             appointment.id = Some(mongodb::bson::oid::ObjectId::new());
         }
+        appointment.updated_at = Some(DateTime::now());
 
         collection
             .insert_one(appointment.clone(), None)
@@ -68,19 +295,31 @@ impl AppointmentRepository {
     pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<Appointment>, String> {
         let collection = self.db.collection::<Appointment>("appointments");
         collection
-            .find_one(doc! { "_id": id }, None)
+            .find_one(doc! { "_id": id, DELETED_AT: Bson::Null }, None)
             .await
             .map_err(|e| format!("Database error: {}", e))
     }
 
-    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, appointment: Appointment) -> Result<Appointment, String> {
+    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, mut appointment: Appointment) -> Result<Appointment, String> {
         let collection = self.db.collection::<Appointment>("appointments");
+        appointment.updated_at = Some(DateTime::now());
         match collection.replace_one(doc! { "_id": id }, appointment.clone(), None).await {
             Ok(_) => Ok(appointment),
             Err(e) => Err(format!("Failed to update appointment: {}", e)),
         }
     }
 
+    /// Repoint every appointment of one patient to another within `session`'s transaction,
+    /// returning the number of updated documents
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to, "updatedAt": DateTime::now() } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| format!("Failed to reassign appointments: {}", e))
+    }
+
     pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
         let collection = self.db.collection::<Appointment>("appointments");
         match collection.delete_one(doc! { "_id": id }, None).await {
@@ -88,4 +327,265 @@ impl AppointmentRepository {
             Err(e) => Err(format!("Failed to delete appointment: {}", e)),
         }
     }
+
+    /// Atomically take the next queue number for a doctor on a day, starting at 1
+    pub async fn next_queue_number(&self, doctor_id: &str, date: &str) -> Result<i64, String> {
+        let collection = self.db.collection::<QueueCounter>("queue_counters");
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        collection
+            .find_one_and_update(
+                doc! { "_id": format!("{}:{}", doctor_id, date) },
+                doc! { "$inc": { "seq": 1_i64 } },
+                options,
+            )
+            .await
+            .map_err(|e| format!("Failed to allocate queue number: {}", e))?
+            .map(|counter| counter.seq)
+            .ok_or_else(|| "Queue counter was not returned".to_string())
+    }
+
+    /// Record a check-in unless the appointment already holds a queue number
+    pub async fn check_in(
+        &self,
+        id: mongodb::bson::oid::ObjectId,
+        queue_number: i64,
+        checked_in_at: &str,
+    ) -> Result<Option<Appointment>, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        collection
+            .find_one_and_update(
+                doc! { "_id": id, "queueNumber": null },
+                doc! { "$set": { "queueNumber": queue_number, "checkedInAt": checked_in_at, "status": AppointmentStatus::CheckedIn, "updatedAt": DateTime::now() } },
+                options,
+            )
+            .await
+            .map_err(|e| format!("Failed to check in appointment: {}", e))
+    }
+
+    /// Mark the lowest waiting queue number of a doctor's day as called
+    pub async fn call_next(&self, doctor_id: &str, date: &str, called_at: &str) -> Result<Option<Appointment>, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! { "queueNumber": 1 })
+            .return_document(ReturnDocument::After)
+            .build();
+
+        collection
+            .find_one_and_update(
+                doc! { "doctorId": doctor_id, "date": date, "status": AppointmentStatus::CheckedIn },
+                doc! { "$set": { "status": AppointmentStatus::Called, "calledAt": called_at, "updatedAt": DateTime::now() } },
+                options,
+            )
+            .await
+            .map_err(|e| format!("Failed to call next patient: {}", e))
+    }
+
+    /// Every checked-in appointment of a doctor's day, in queue order
+    pub async fn find_queue(&self, doctor_id: &str, date: &str) -> Result<Vec<Appointment>, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let options = FindOptions::builder().sort(doc! { "queueNumber": 1 }).build();
+
+        collection
+            .find(doc! { "doctorId": doctor_id, "date": date, "queueNumber": { "$ne": null } }, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    pub async fn insert_many(&self, mut appointments: Vec<Appointment>) -> Result<(), String> {
+        if appointments.is_empty() {
+            return Ok(());
+        }
+        let now = DateTime::now();
+        for appointment in &mut appointments {
+            appointment.updated_at = Some(now);
+        }
+        let collection = self.db.collection::<Appointment>("appointments");
+        collection
+            .insert_many(appointments, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Insert failed: {}", e))
+    }
+
+    /// Non-cancelled appointments of a doctor occupying any of the `(date, time)` slots,
+    /// ignoring the appointments in `exclude`
+    pub async fn find_conflicts(&self, doctor_id: &str, slots: &[(String, String)], exclude: &[ObjectId]) -> Result<Vec<Appointment>, String> {
+        if slots.is_empty() {
+            return Ok(Vec::new());
+        }
+        let collection = self.db.collection::<Appointment>("appointments");
+        let slot_filters: Vec<Document> = slots.iter().map(|(date, time)| doc! { "date": date, "time": time }).collect();
+        let filter = doc! {
+            "doctorId": doctor_id,
+            "status": { "$ne": AppointmentStatus::Cancelled },
+            "_id": { "$nin": exclude },
+            "$or": slot_filters,
+        };
+
+        collection
+            .find(filter, FindOptions::builder().sort(doc! { "date": 1, "time": 1 }).build())
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// Occurrences of a series in date order. `from` keeps dates on or after it, `on` a single
+    /// date; `open_only` drops completed and cancelled visits.
+    pub async fn find_by_series(&self, series_id: &str, from: Option<&str>, on: Option<&str>, open_only: bool) -> Result<Vec<Appointment>, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let mut filter = doc! { "seriesId": series_id };
+        if let Some(from) = from {
+            filter.insert("date", doc! { "$gte": from });
+        }
+        if let Some(on) = on {
+            filter.insert("date", on);
+        }
+        if open_only {
+            filter.insert("status", doc! { "$nin": [AppointmentStatus::Completed, AppointmentStatus::Cancelled] });
+        }
+
+        collection
+            .find(filter, FindOptions::builder().sort(doc! { "date": 1, "time": 1 }).build())
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// Appointments of a doctor dated `from` to `to` (inclusive), outcome fields only
+    pub async fn find_outcomes(&self, doctor_id: &str, from: &str, to: &str) -> Result<Vec<AppointmentOutcomeRow>, String> {
+        let collection = self.db.collection::<AppointmentOutcomeRow>("appointments");
+        let filter = doc! { "doctorId": doctor_id, "date": { "$gte": from, "$lte": to }, DELETED_AT: Bson::Null };
+        let options = FindOptions::builder().projection(doc! { "_id": 0, "status": 1, "calledAt": 1, "completedAt": 1 }).build();
+        collection
+            .find(filter, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// Mark one appointment `no_show` if it is still scheduled and not checked in
+    pub async fn mark_no_show(&self, id: ObjectId) -> Result<Option<Appointment>, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": AppointmentStatus::Scheduled, "queueNumber": Bson::Null },
+                doc! { "$set": { "status": AppointmentStatus::NoShow, "updatedAt": DateTime::now() } },
+                options,
+            )
+            .await
+            .map_err(|e| format!("Failed to mark appointment as no-show: {}", e))
+    }
+
+    /// Mark every scheduled appointment that started before `cutoff` and never checked in as
+    /// `no_show`, returning their ids
+    pub async fn mark_no_shows(&self, cutoff: DateTime) -> Result<Vec<ObjectId>, String> {
+        let collection = self.db.collection::<Document>("appointments");
+        let options = FindOptions::builder().projection(doc! { "_id": 1 }).build();
+        let ids: Vec<ObjectId> = collection
+            .find(no_show_filter(cutoff), options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect::<Vec<Document>>()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))?
+            .iter()
+            .filter_map(|document| document.get_object_id("_id").ok())
+            .collect();
+        if ids.is_empty() {
+            return Ok(ids);
+        }
+
+        // Re-applying the filter skips appointments checked in since they were read
+        let mut filter = no_show_filter(cutoff);
+        filter.insert("_id", doc! { "$in": &ids });
+        collection
+            .update_many(filter, doc! { "$set": { "status": AppointmentStatus::NoShow, "updatedAt": DateTime::now() } }, None)
+            .await
+            .map_err(|e| format!("Failed to mark no-shows: {}", e))?;
+        Ok(ids)
+    }
+
+    pub async fn update_many_by_ids(&self, ids: &[ObjectId], mut set: Document) -> Result<u64, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        set.insert("updatedAt", DateTime::now());
+        collection
+            .update_many(doc! { "_id": { "$in": ids } }, doc! { "$set": set }, None)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| format!("Failed to update appointments: {}", e))
+    }
+
+    pub async fn delete_by_ids(&self, ids: &[ObjectId]) -> Result<u64, String> {
+        let collection = self.db.collection::<Appointment>("appointments");
+        collection
+            .delete_many(doc! { "_id": { "$in": ids } }, None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| format!("Failed to delete appointments: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expanded_pipeline_pages_before_joining() {
+        let expansion = Expansion { doctor: true, patient: false };
+        let pipeline = expanded_pipeline(list_filter(None, Some(&AppointmentStatus::Scheduled)), 20, 10, expansion);
+        let stages: Vec<&str> = pipeline.iter().map(|stage| stage.keys().next().unwrap().as_str()).collect();
+        assert_eq!(stages, ["$match", "$skip", "$limit", "$lookup", "$set"]);
+
+        let lookup = pipeline[3].get_document("$lookup").unwrap();
+        assert_eq!(lookup.get_str("from").unwrap(), "practitioners");
+        assert_eq!(lookup.get_str("as").unwrap(), "doctor");
+        assert!(Expansion::default().is_empty());
+    }
+
+    #[test]
+    fn per_doctor_counts_group_before_joining_names() {
+        let pipeline = per_doctor_pipeline("2026-03-01", "2026-03-07", Some("org1"));
+        let stages: Vec<&str> = pipeline.iter().map(|stage| stage.keys().next().unwrap().as_str()).collect();
+        assert_eq!(stages, ["$match", "$group", "$lookup", "$set", "$project", "$sort"]);
+        let filter = pipeline[0].get_document("$match").unwrap();
+        assert_eq!(filter.get_document("date").unwrap(), &doc! { "$gte": "2026-03-01", "$lte": "2026-03-07" });
+        assert_eq!(filter.get_str("organizationId").unwrap(), "org1");
+
+        let row: DoctorAppointmentRow = mongodb::bson::from_document(doc! {
+            "doctor_id": "d1", "total": 5, "completed": 3, "cancelled": 1, "no_show": 1,
+        }).unwrap();
+        assert_eq!((row.doctor_name, row.total), (None, 5));
+    }
+
+    #[test]
+    fn dangling_references_embed_nothing() {
+        let mut document = doc! { "doctor": Bson::Null };
+        assert!(take_embedded::<DoctorSummaryRow>(&mut document, "doctor").unwrap().is_none());
+
+        let id = ObjectId::new();
+        let mut document = doc! { "patient": { "_id": id, "name": "B", "nrme": "0001" } };
+        let patient = take_embedded::<PatientSummaryRow>(&mut document, "patient").unwrap().unwrap();
+        assert_eq!((patient.id, patient.nrme.as_str()), (id, "0001"));
+        assert!(document.is_empty());
+    }
 }
@@ -0,0 +1,55 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    ClientSession, Collection, Database,
+};
+use crate::models::AppointmentSeries;
+
+pub struct AppointmentSeriesRepository {
+    collection: Collection<AppointmentSeries>,
+}
+
+impl AppointmentSeriesRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<AppointmentSeries>("appointment_series");
+        Self { collection }
+    }
+
+    pub async fn create(&self, series: AppointmentSeries) -> Result<AppointmentSeries, String> {
+        let result = self
+            .collection
+            .insert_one(series.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_series = series;
+        created_series.id = result.inserted_id.as_object_id();
+
+        Ok(created_series)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<AppointmentSeries>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update(&self, id: ObjectId, series: AppointmentSeries) -> Result<AppointmentSeries, String> {
+        self.collection
+            .replace_one(doc! { "_id": id }, series.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(series)
+    }
+
+    /// Move a merged duplicate's series to the patient it was merged into, within `session`'s
+    /// transaction; their appointments are moved by `AppointmentRepository::reassign_patient`
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
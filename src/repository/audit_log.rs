@@ -0,0 +1,56 @@
+use mongodb::{
+    bson::doc,
+    options::FindOptions,
+    Collection, Database,
+};
+use futures_util::stream::TryStreamExt;
+use crate::models::AuditLog;
+use crate::pagination::PaginationParams;
+
+pub struct AuditLogRepository {
+    collection: Collection<AuditLog>,
+}
+
+impl AuditLogRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<AuditLog>("audit_logs");
+        Self { collection }
+    }
+
+    pub async fn insert(&self, log: AuditLog) -> Result<AuditLog, String> {
+        let result = self
+            .collection
+            .insert_one(log.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_log = log;
+        created_log.id = result.inserted_id.as_object_id();
+
+        Ok(created_log)
+    }
+
+    pub async fn find_by_entity_paginated(&self, entity: &str, entity_id: &str, pagination: PaginationParams) -> Result<(Vec<AuditLog>, u64), String> {
+        let filter = doc! { "entity": entity, "entity_id": entity_id };
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .sort(doc! { "created_at": -1 })
+            .build();
+
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let logs: Vec<AuditLog> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((logs, total))
+    }
+}
@@ -0,0 +1,168 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use serde::Deserialize;
+use crate::models::Bed;
+use crate::status::BedStatus;
+use futures_util::stream::TryStreamExt;
+
+/// Bed counts of one ward by status, see `BedRepository::occupancy`
+#[derive(Debug, Deserialize, Default, PartialEq)]
+pub struct BedOccupancyRow {
+    #[serde(rename = "_id")]
+    pub ward_id: String,
+    pub total: i64,
+    pub available: i64,
+    pub occupied: i64,
+    pub cleaning: i64,
+    pub maintenance: i64,
+}
+
+fn occupancy_pipeline(ward_ids: Option<&[String]>) -> Vec<Document> {
+    let count = |status: BedStatus| doc! { "$sum": { "$cond": [{ "$eq": ["$status", status] }, 1, 0] } };
+    let mut pipeline = Vec::new();
+    if let Some(ward_ids) = ward_ids {
+        pipeline.push(doc! { "$match": { "wardId": { "$in": ward_ids } } });
+    }
+    pipeline.push(doc! { "$group": {
+        "_id": "$wardId",
+        "total": { "$sum": 1 },
+        "available": count(BedStatus::Available),
+        "occupied": count(BedStatus::Occupied),
+        "cleaning": count(BedStatus::Cleaning),
+        "maintenance": count(BedStatus::Maintenance),
+    }});
+    pipeline.push(doc! { "$sort": { "_id": 1 } });
+    pipeline
+}
+
+pub struct BedRepository {
+    collection: Collection<Bed>,
+}
+
+impl BedRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Bed>("beds");
+        Self { collection }
+    }
+
+    pub async fn create(&self, bed: Bed) -> Result<Bed, String> {
+        let result = self
+            .collection
+            .insert_one(bed.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = bed;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_ward(&self, ward_id: &str) -> Result<Vec<Bed>, String> {
+        let options = FindOptions::builder().sort(doc! { "code": 1 }).build();
+        let cursor = self.collection
+            .find(doc! { "wardId": ward_id }, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Bed>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Update a bed that is not occupied; occupancy only changes through admissions.
+    pub async fn update_unoccupied(&self, id: ObjectId, set: Document) -> Result<Option<Bed>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": { "$ne": BedStatus::Occupied } }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Occupy an available bed; `None` when it is not available (any more).
+    pub async fn claim(&self, id: ObjectId, patient_id: &str, admission_id: &str) -> Result<Option<Bed>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let set = doc! {
+            "status": BedStatus::Occupied,
+            "patientId": patient_id,
+            "admissionId": admission_id,
+            "updatedAt": DateTime::now(),
+        };
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": BedStatus::Available }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Free a bed held by `admission_id`
+    pub async fn release(&self, id: ObjectId, admission_id: &str) -> Result<bool, String> {
+        let update = doc! {
+            "$set": { "status": BedStatus::Available, "updatedAt": DateTime::now() },
+            "$unset": { "patientId": "", "admissionId": "" },
+        };
+        self.collection
+            .update_one(doc! { "_id": id, "admissionId": admission_id }, update, None)
+            .await
+            .map(|result| result.modified_count > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Bed counts by status per ward, of `ward_ids` or all wards
+    pub async fn occupancy(&self, ward_ids: Option<&[String]>) -> Result<Vec<BedOccupancyRow>, String> {
+        let cursor = self.collection
+            .aggregate(occupancy_pipeline(ward_ids), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id, "status": { "$ne": BedStatus::Occupied } }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Keep the bed a merged duplicate occupies under the patient it was merged into, within
+    /// `session`'s transaction
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to, "updatedAt": DateTime::now() } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occupancy_counts_every_status() {
+        let pipeline = occupancy_pipeline(Some(&["w1".to_string()]));
+        assert_eq!(pipeline[0], doc! { "$match": { "wardId": { "$in": ["w1"] } } });
+        let group = pipeline[1].get_document("$group").unwrap();
+        for status in BedStatus::KNOWN {
+            assert!(group.contains_key(*status), "missing {}", status);
+        }
+        assert_eq!(occupancy_pipeline(None).len(), 2);
+    }
+}
@@ -0,0 +1,89 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::CashierShift;
+use crate::pagination::PaginationParams;
+use crate::status::ShiftStatus;
+use futures_util::stream::TryStreamExt;
+
+pub struct CashierShiftRepository {
+    collection: Collection<CashierShift>,
+}
+
+impl CashierShiftRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<CashierShift>("cashier_shifts");
+        Self { collection }
+    }
+
+    pub async fn create(&self, shift: CashierShift) -> Result<CashierShift, String> {
+        let result = self
+            .collection
+            .insert_one(shift.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = shift;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<CashierShift>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_open(&self, cashier_id: &str) -> Result<Option<CashierShift>, String> {
+        self.collection
+            .find_one(doc! { "cashierId": cashier_id, "status": ShiftStatus::Open }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Most recently opened first
+    pub async fn find_paginated(&self, cashier_id: Option<&str>, status: Option<&ShiftStatus>, pagination: &PaginationParams) -> Result<(Vec<CashierShift>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(cashier_id) = cashier_id {
+            filter.insert("cashierId", cashier_id);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "openedAt": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let shifts: Vec<CashierShift> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((shifts, total))
+    }
+
+    /// Close an open shift; `None` when it is missing or already closed.
+    pub async fn close(&self, id: ObjectId, set: Document) -> Result<Option<CashierShift>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let mut set = set;
+        set.insert("status", ShiftStatus::Closed);
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": ShiftStatus::Open }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,54 @@
+use mongodb::{
+    bson::{self, doc, DateTime, Document},
+    change_stream::event::ResumeToken,
+    options::UpdateOptions,
+    Collection, Database,
+};
+
+const WATCHER_ID: &str = "watcher";
+
+/// Where the change stream watcher left off, see `crate::change_streams`
+pub struct ChangeStreamStateRepository {
+    collection: Collection<Document>,
+}
+
+impl ChangeStreamStateRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Document>("change_stream_state");
+        Self { collection }
+    }
+
+    pub async fn load_token(&self) -> Result<Option<ResumeToken>, String> {
+        let state = self.collection
+            .find_one(doc! { "_id": WATCHER_ID }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        match state.and_then(|state| state.get("token").cloned()) {
+            Some(token) => bson::from_bson(token).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn save_token(&self, token: &ResumeToken) -> Result<(), String> {
+        let token = bson::to_bson(token).map_err(|e| e.to_string())?;
+        let options = UpdateOptions::builder().upsert(true).build();
+        self.collection
+            .update_one(
+                doc! { "_id": WATCHER_ID },
+                doc! { "$set": { "token": token, "updated_at": DateTime::now() } },
+                options,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Forget the position, after the oplog no longer reaches back to it
+    pub async fn clear_token(&self) -> Result<(), String> {
+        self.collection
+            .delete_one(doc! { "_id": WATCHER_ID }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
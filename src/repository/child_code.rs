@@ -40,6 +40,43 @@ impl ChildCodeRepository {
         Ok(child_codes)
     }
 
+    /// Ids of the codes already linked under the parent
+    pub async fn find_linked_code_ids(&self, parent_code_id: &str) -> Result<Vec<String>, String> {
+        let ids = self.collection
+            .distinct("code_id", doc! { "parent.code_id": parent_code_id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(ids.into_iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+    }
+
+    /// Insert `child_codes` in one transaction: all of them are stored or none.
+    /// Needs a replica set, as every MongoDB transaction does.
+    pub async fn create_all(&self, child_codes: Vec<ChildCode>) -> Result<Vec<ChildCode>, String> {
+        if child_codes.is_empty() {
+            return Ok(child_codes);
+        }
+        let mut session = self.collection.client().start_session(None).await.map_err(|e| e.to_string())?;
+        session.start_transaction(None).await.map_err(|e| e.to_string())?;
+
+        let inserted = match self.collection.insert_many_with_session(child_codes.clone(), None, &mut session).await {
+            Ok(inserted) => inserted,
+            Err(e) => {
+                let _ = session.abort_transaction().await;
+                return Err(e.to_string());
+            }
+        };
+        session.commit_transaction().await.map_err(|e| e.to_string())?;
+
+        Ok(child_codes
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut child_code)| {
+                child_code.id = inserted.inserted_ids.get(&index).and_then(|id| id.as_object_id());
+                child_code
+            })
+            .collect())
+    }
+
     pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<ChildCode>, String> {
         self.collection
             .find_one(doc! { "_id": id }, None)
@@ -62,7 +99,7 @@ impl ChildCodeRepository {
                 "system": child_code.system.clone(),
                 "display": child_code.display.clone(),
                 "norut": child_code.norut,
-                "updated_at": child_code.updated_at.clone(),
+                "updated_at": child_code.updated_at,
             }
         };
 
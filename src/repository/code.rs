@@ -1,6 +1,21 @@
-use mongodb::{bson::doc, Database};
+use std::collections::BTreeMap;
+use mongodb::{bson::{doc, oid::ObjectId, DateTime, Document}, Database};
 use futures_util::stream::TryStreamExt;
-use crate::models::Code;
+use crate::models::{Code, CodeCategoryEmbed};
+use crate::repository::TombstoneRepository;
+
+/// One `$in` per system, so the `codes_system_code` index serves each branch
+fn system_code_filter(pairs: &[(String, String)]) -> Document {
+    let mut by_system: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (system, code) in pairs {
+        by_system.entry(system.as_str()).or_default().push(code.as_str());
+    }
+    let branches: Vec<Document> = by_system
+        .into_iter()
+        .map(|(system, codes)| doc! { "system": system, "code": { "$in": codes } })
+        .collect();
+    doc! { "$or": branches }
+}
 
 pub struct CodeRepository {
     db: Database,
@@ -47,6 +62,82 @@ impl CodeRepository {
     pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
         let collection = self.db.collection::<Code>("codes");
         let result = collection.delete_one(doc! { "_id": id }, None).await.map_err(|e| e.to_string())?;
+        if result.deleted_count > 0 {
+            TombstoneRepository::new(self.db.clone()).record("codes", id).await;
+        }
         Ok(result.deleted_count > 0)
     }
+
+    /// Existing codes of `system` whose code is one of `codes`.
+    pub async fn find_by_system_and_codes(&self, system: &str, codes: &[String]) -> Result<Vec<Code>, String> {
+        let collection = self.db.collection::<Code>("codes");
+        let cursor = collection
+            .find(doc! { "system": system, "code": { "$in": codes } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_ids(&self, ids: &[ObjectId]) -> Result<Vec<Code>, String> {
+        let collection = self.db.collection::<Code>("codes");
+        let cursor = collection
+            .find(doc! { "_id": { "$in": ids } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Codes matching any of the `(system, code)` pairs
+    pub async fn find_by_system_code_pairs(&self, pairs: &[(String, String)]) -> Result<Vec<Code>, String> {
+        if pairs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let collection = self.db.collection::<Code>("codes");
+        let cursor = collection
+            .find(system_code_filter(pairs), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn insert_many(&self, codes: Vec<Code>) -> Result<usize, String> {
+        if codes.is_empty() {
+            return Ok(0);
+        }
+        let collection = self.db.collection::<Code>("codes");
+        let result = collection.insert_many(codes, None).await.map_err(|e| e.to_string())?;
+        Ok(result.inserted_ids.len())
+    }
+
+    pub async fn update_display_and_category(&self, id: ObjectId, display: &str, category: &CodeCategoryEmbed, updated_at: DateTime) -> Result<(), String> {
+        let collection = self.db.collection::<Code>("codes");
+        let category = mongodb::bson::to_document(category).map_err(|e| e.to_string())?;
+        collection
+            .update_one(
+                doc! { "_id": id },
+                doc! { "$set": { "display": display, "category": category, "updated_at": updated_at } },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_are_grouped_by_system() {
+        let pairs = [
+            ("http://loinc.org".to_string(), "8867-4".to_string()),
+            ("http://snomed.info/sct".to_string(), "271649006".to_string()),
+            ("http://loinc.org".to_string(), "8310-5".to_string()),
+        ];
+        assert_eq!(system_code_filter(&pairs), doc! { "$or": [
+            { "system": "http://loinc.org", "code": { "$in": ["8867-4", "8310-5"] } },
+            { "system": "http://snomed.info/sct", "code": { "$in": ["271649006"] } },
+        ] });
+    }
 }
@@ -0,0 +1,218 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReplaceOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::{CorporateClient, CorporateEmployee, CorporateStatement};
+use crate::pagination::PaginationParams;
+use crate::status::CorporateClientStatus;
+use futures_util::stream::TryStreamExt;
+
+/// Corporate clients with their employee rosters and monthly statements
+pub struct CorporateClientRepository {
+    clients: Collection<CorporateClient>,
+    employees: Collection<CorporateEmployee>,
+    statements: Collection<CorporateStatement>,
+}
+
+impl CorporateClientRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            clients: db.collection::<CorporateClient>("corporate_clients"),
+            employees: db.collection::<CorporateEmployee>("corporate_employees"),
+            statements: db.collection::<CorporateStatement>("corporate_statements"),
+        }
+    }
+
+    pub async fn create(&self, client: CorporateClient) -> Result<CorporateClient, String> {
+        let result = self.clients
+            .insert_one(client.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = client;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<CorporateClient>, String> {
+        self.clients
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// By name
+    pub async fn find_paginated(&self, organization_id: Option<&str>, status: Option<&CorporateClientStatus>, pagination: &PaginationParams) -> Result<(Vec<CorporateClient>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(organization_id) = organization_id {
+            filter.insert("organizationId", organization_id);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.clients
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "name": 1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.clients
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let clients: Vec<CorporateClient> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((clients, total))
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<CorporateClient>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.clients
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The client and its roster
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        let result = self.clients
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        if result.deleted_count > 0 {
+            self.employees
+                .delete_many(doc! { "clientId": id.to_hex() }, None)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Fails on a patient already on the client's roster
+    pub async fn create_employee(&self, employee: CorporateEmployee) -> Result<CorporateEmployee, String> {
+        let result = self.employees
+            .insert_one(employee.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = employee;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    /// The client's roster by employee number
+    pub async fn find_employees(&self, client_id: &str) -> Result<Vec<CorporateEmployee>, String> {
+        let options = FindOptions::builder().sort(doc! { "employeeNumber": 1 }).build();
+        self.employees
+            .find(doc! { "clientId": client_id }, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_employee(&self, client_id: &str, id: ObjectId) -> Result<Option<CorporateEmployee>, String> {
+        self.employees
+            .find_one(doc! { "_id": id, "clientId": client_id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_employee(&self, client_id: &str, id: ObjectId, set: Document) -> Result<Option<CorporateEmployee>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.employees
+            .find_one_and_update(doc! { "_id": id, "clientId": client_id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_employee(&self, client_id: &str, id: ObjectId) -> Result<bool, String> {
+        self.employees
+            .delete_one(doc! { "_id": id, "clientId": client_id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Roster entries of the patient in effect on `date` (`YYYY-MM-DD`), latest start first
+    pub async fn find_employments(&self, patient_id: &str, date: &str) -> Result<Vec<CorporateEmployee>, String> {
+        let filter = doc! {
+            "patientId": patient_id,
+            "startDate": { "$lte": date },
+            "$or": [{ "endDate": null }, { "endDate": { "$gte": date } }],
+        };
+        let options = FindOptions::builder().sort(doc! { "startDate": -1 }).build();
+        self.employees
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_statement(&self, client_id: &str, month: &str) -> Result<Option<CorporateStatement>, String> {
+        self.statements
+            .find_one(doc! { "clientId": client_id, "month": month }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Newest month first
+    pub async fn find_statements(&self, client_id: &str) -> Result<Vec<CorporateStatement>, String> {
+        let options = FindOptions::builder().sort(doc! { "month": -1 }).build();
+        self.statements
+            .find(doc! { "clientId": client_id }, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Insert the client's statement for its month, or replace the one generated before
+    pub async fn save_statement(&self, statement: CorporateStatement) -> Result<CorporateStatement, String> {
+        let filter = doc! { "clientId": &statement.client_id, "month": &statement.month };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        self.statements
+            .replace_one(filter, statement.clone(), options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        self.find_statement(&statement.client_id, &statement.month).await?
+            .ok_or_else(|| "Saved statement not found".to_string())
+    }
+
+    /// Move a merged duplicate's roster entries to the patient it was merged into, within
+    /// `session`'s transaction. On a client whose roster already has the patient, the
+    /// duplicate's entry is dropped: a patient is on a roster once. Past statements keep
+    /// the ID they were billed under.
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        let clients = self.employees
+            .distinct_with_session("clientId", doc! { "patientId": to }, None, session)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.employees
+            .delete_many_with_session(doc! { "patientId": from, "clientId": { "$in": clients } }, None, session)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.employees
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to, "updatedAt": DateTime::now() } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,33 @@
+use mongodb::{
+    bson::doc,
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::Counter;
+
+/// Counters of `crate::sequences`, one document per sequence scope.
+pub struct CounterRepository {
+    collection: Collection<Counter>,
+}
+
+impl CounterRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Counter>("counters");
+        Self { collection }
+    }
+
+    /// Atomically take the next value of a counter, starting at 1
+    pub async fn next(&self, key: &str) -> Result<i64, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": key }, doc! { "$inc": { "seq": 1_i64 } }, options)
+            .await
+            .map_err(|e| format!("Failed to allocate number: {}", e))?
+            .map(|counter| counter.seq)
+            .ok_or_else(|| "Counter was not returned".to_string())
+    }
+}
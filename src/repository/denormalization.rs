@@ -0,0 +1,45 @@
+use mongodb::{
+    bson::{doc, Bson, Document},
+    Database,
+};
+use crate::denormalize::Refresh;
+
+/// Writes to embedded copies, across the collections holding them
+pub struct DenormalizationRepository {
+    db: Database,
+}
+
+impl DenormalizationRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Documents whose copy is stale
+    pub async fn count_stale(&self, refresh: &Refresh) -> Result<u64, String> {
+        self.db.collection::<Document>(refresh.collection)
+            .count_documents(refresh.filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Bring stale copies up to date, returning how many documents changed
+    pub async fn apply(&self, refresh: &Refresh) -> Result<u64, String> {
+        self.db.collection::<Document>(refresh.collection)
+            .update_many(refresh.filter.clone(), doc! { "$set": refresh.set.clone() }, None)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Distinct string values of `field` in `collection`, e.g. the sources copied into it
+    pub async fn distinct_ids(&self, collection: &str, field: &str) -> Result<Vec<String>, String> {
+        let values = self.db.collection::<Document>(collection)
+            .distinct(field, None, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(values.into_iter().filter_map(|value| match value {
+            Bson::String(id) => Some(id),
+            _ => None,
+        }).collect())
+    }
+}
@@ -1,91 +1,56 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
-use futures_util::stream::TryStreamExt;
-use crate::models::Doctor;
+use mongodb::{bson::{doc, oid::ObjectId}, ClientSession, Database};
+use crate::models::{Doctor, Practitioner};
+use crate::repository::PractitionerRepository;
+use crate::status::{DoctorStatus, PractitionerType};
 use crate::pagination::PaginationParams;
 
+/// The `/doctors` view of `practitioners`: practitioners of type `doctor`, read and written
+/// as `Doctor`. Writes keep the qualifications the view does not show.
 pub struct DoctorRepository {
-    db: Database,
+    practitioners: PractitionerRepository,
 }
 
 impl DoctorRepository {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self { practitioners: PractitionerRepository::new(db) }
     }
 
     pub async fn find_all(&self) -> Result<Vec<Doctor>, String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        match collection.find(doc! {}, None).await {
-            Ok(cursor) => {
-                cursor
-                    .try_collect::<Vec<Doctor>>()
-                    .await
-                    .map_err(|e| format!("Failed to collect results: {}", e))
-            }
-            Err(e) => Err(format!("Database error: {}", e)),
-        }
+        let doctors = self.practitioners.find(doc! { "type": PractitionerType::Doctor.as_str() }).await?;
+        Ok(doctors.into_iter().map(Doctor::from).collect())
     }
 
-    pub async fn find_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<Doctor>, u64), String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        
-        let total = collection
-            .count_documents(doc! {}, None)
-            .await
-            .map_err(|e| format!("Failed to count documents: {}", e))?;
-
-        let options = FindOptions::builder()
-            .skip(pagination.skip())
-            .limit(pagination.limit() as i64)
-            .build();
-
-        match collection.find(doc! {}, options).await {
-            Ok(cursor) => {
-                let records = cursor
-                    .try_collect::<Vec<Doctor>>()
-                    .await
-                    .map_err(|e| format!("Failed to collect results: {}", e))?;
-                Ok((records, total))
-            }
-            Err(e) => Err(format!("Database error: {}", e)),
+    pub async fn find_all_paginated(&self, pagination: PaginationParams, status: Option<&DoctorStatus>) -> Result<(Vec<Doctor>, u64), String> {
+        let mut filter = doc! { "type": PractitionerType::Doctor.as_str() };
+        if let Some(status) = status {
+            filter.insert("status", status.as_str());
         }
-    }
 
-    pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<Doctor>, String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        collection
-            .find_one(doc! { "_id": id }, None)
-            .await
-            .map_err(|e| format!("Database error: {}", e))
+        let (doctors, total) = self.practitioners.find_paginated(filter, &pagination).await?;
+        Ok((doctors.into_iter().map(Doctor::from).collect(), total))
     }
 
-    pub async fn insert(&self, mut doctor: Doctor) -> Result<Doctor, String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        
-        if doctor.id.is_none() {
-            doctor.id = Some(mongodb::bson::oid::ObjectId::new());
-        }
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Doctor>, String> {
+        let doctor = self.practitioners.find_by_id(id, Some(&PractitionerType::Doctor)).await?;
+        Ok(doctor.map(Doctor::from))
+    }
 
-        collection
-            .insert_one(doctor.clone(), None)
-            .await
-            .map_err(|e| format!("Insert failed: {}", e))?;
+    pub async fn insert(&self, doctor: Doctor) -> Result<Doctor, String> {
+        self.practitioners.insert(Practitioner::from(doctor)).await.map(Doctor::from)
+    }
 
-        Ok(doctor)
+    pub async fn update(&self, id: ObjectId, doctor: Doctor) -> Result<Doctor, String> {
+        let mut practitioner = self.practitioners.find_by_id(id, Some(&PractitionerType::Doctor)).await?
+            .ok_or_else(|| "Failed to update doctor: not found".to_string())?;
+        practitioner.apply_doctor(doctor);
+        self.practitioners.replace(id, practitioner).await.map(Doctor::from)
     }
 
-    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, doctor: Doctor) -> Result<Doctor, String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        match collection.replace_one(doc! { "_id": id }, doctor.clone(), None).await {
-            Ok(_) => Ok(doctor),
-            Err(e) => Err(format!("Failed to update doctor: {}", e)),
-        }
+    pub async fn delete(&self, session: &mut ClientSession, id: ObjectId) -> Result<bool, String> {
+        self.practitioners.delete_with_session(session, id, Some(&PractitionerType::Doctor)).await
     }
 
-    pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
-        let collection = self.db.collection::<Doctor>("doctors");
-        match collection.delete_one(doc! { "_id": id }, None).await {
-            Ok(result) => Ok(result.deleted_count > 0),
-            Err(e) => Err(format!("Failed to delete doctor: {}", e)),
-        }
+    pub async fn set_rating(&self, id: ObjectId, average: Option<f64>, count: i64) -> Result<(), String> {
+        self.practitioners.set_rating(id, average, count).await
     }
 }
@@ -0,0 +1,73 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::FeatureFlag;
+use futures_util::stream::TryStreamExt;
+
+pub struct FeatureFlagRepository {
+    collection: Collection<FeatureFlag>,
+}
+
+impl FeatureFlagRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<FeatureFlag>("feature_flags");
+        Self { collection }
+    }
+
+    pub async fn create(&self, flag: FeatureFlag) -> Result<FeatureFlag, String> {
+        let result = self
+            .collection
+            .insert_one(flag.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_flag = flag;
+        created_flag.id = result.inserted_id.as_object_id();
+
+        Ok(created_flag)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<FeatureFlag>, String> {
+        let options = FindOptions::builder().sort(doc! { "key": 1 }).build();
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<FeatureFlag>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_key(&self, key: &str) -> Result<Option<FeatureFlag>, String> {
+        self.collection
+            .find_one(doc! { "key": key }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<FeatureFlag>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,7 +1,10 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{bson::{doc, Bson, Document}, ClientSession, Database, options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument}};
 use futures_util::stream::TryStreamExt;
+use crate::delete_policy::DELETED_AT;
 use crate::models::File;
 use crate::pagination::PaginationParams;
+use crate::status::TimelineEntryType;
+use crate::timeline::{self, Position, TimeField};
 
 pub struct FileRepository {
     db: Database,
@@ -12,9 +15,24 @@ impl FileRepository {
         Self { db }
     }
 
+    /// Files attached to the patient's record after `after` in their timeline, newest first
+    pub async fn find_timeline(&self, patient_id: &str, after: Option<&Position>, limit: i64) -> Result<Vec<File>, String> {
+        let field = TimeField::Date("createdAt");
+        let base = doc! { "medicalRecordId": patient_id, DELETED_AT: Bson::Null };
+        let filter = timeline::source_filter(base, &TimelineEntryType::File, field, after);
+        let options = FindOptions::builder().sort(timeline::newest_first(field)).limit(limit).build();
+        self.db.collection::<File>("files")
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn find_all(&self) -> Result<Vec<File>, String> {
         let collection = self.db.collection::<File>("files");
-        match collection.find(doc! {}, None).await {
+        match collection.find(doc! { DELETED_AT: Bson::Null }, None).await {
             Ok(cursor) => {
                 cursor
                     .try_collect::<Vec<File>>()
@@ -29,7 +47,7 @@ impl FileRepository {
         let collection = self.db.collection::<File>("files");
         
         let total = collection
-            .count_documents(doc! {}, None)
+            .count_documents(doc! { DELETED_AT: Bson::Null }, None)
             .await
             .map_err(|e| format!("Failed to count documents: {}", e))?;
 
@@ -38,7 +56,7 @@ impl FileRepository {
             .limit(pagination.limit() as i64)
             .build();
 
-        match collection.find(doc! {}, options).await {
+        match collection.find(doc! { DELETED_AT: Bson::Null }, options).await {
             Ok(cursor) => {
                 let records = cursor
                     .try_collect::<Vec<File>>()
@@ -53,7 +71,7 @@ impl FileRepository {
     pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<File>, String> {
         let collection = self.db.collection::<File>("files");
         collection
-            .find_one(doc! { "_id": id }, None)
+            .find_one(doc! { "_id": id, DELETED_AT: Bson::Null }, None)
             .await
             .map_err(|e| format!("Database error: {}", e))
     }
@@ -73,6 +91,21 @@ impl FileRepository {
         Ok(file)
     }
 
+    /// Apply `set` to a live file still at `version`; `None` when it changed or was deleted.
+    pub async fn update_at_version(&self, id: mongodb::bson::oid::ObjectId, version: i32, set: Document) -> Result<Option<File>, String> {
+        let collection = self.db.collection::<File>("files");
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        // Files stored before versioning have no `version` and are at version 1
+        let at_version = if version == 1 { doc! { "$in": [1, Bson::Null] } } else { doc! { "$eq": version } };
+
+        collection
+            .find_one_and_update(doc! { "_id": id, "version": at_version, DELETED_AT: Bson::Null }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))
+    }
+
     pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
         let collection = self.db.collection::<File>("files");
         
@@ -83,4 +116,14 @@ impl FileRepository {
 
         Ok(result.deleted_count > 0)
     }
+
+    /// Attach a merged duplicate's files to the record it was merged into, within `session`'s
+    /// transaction, so they stay on the patient's timeline
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.db.collection::<File>("files")
+            .update_many_with_session(doc! { "medicalRecordId": from }, doc! { "$set": { "medicalRecordId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
 }
@@ -0,0 +1,61 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime},
+    options::{FindOneAndUpdateOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::FileShare;
+
+pub struct FileShareRepository {
+    collection: Collection<FileShare>,
+}
+
+impl FileShareRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<FileShare>("file_shares");
+        Self { collection }
+    }
+
+    pub async fn create(&self, share: FileShare) -> Result<FileShare, String> {
+        let result = self
+            .collection
+            .insert_one(share.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = share;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<FileShare>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Revoke a share of `file_id` that is not revoked yet
+    pub async fn revoke(&self, id: ObjectId, file_id: &str, at: DateTime) -> Result<Option<FileShare>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "fileId": file_id, "revoked_at": Bson::Null },
+                doc! { "$set": { "revoked_at": at } },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn record_access(&self, id: ObjectId, at: DateTime) -> Result<(), String> {
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$inc": { "access_count": 1 }, "$set": { "last_accessed_at": at } }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
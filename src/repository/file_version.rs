@@ -0,0 +1,62 @@
+use mongodb::{
+    bson::doc,
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::FileVersion;
+use futures_util::stream::TryStreamExt;
+
+/// Versions are only inserted, and removed with their file.
+pub struct FileVersionRepository {
+    collection: Collection<FileVersion>,
+}
+
+impl FileVersionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<FileVersion>("file_versions");
+        Self { collection }
+    }
+
+    pub async fn insert(&self, version: FileVersion) -> Result<FileVersion, String> {
+        let result = self
+            .collection
+            .insert_one(version.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_version = version;
+        created_version.id = result.inserted_id.as_object_id();
+
+        Ok(created_version)
+    }
+
+    /// Every version of a file, oldest first
+    pub async fn find_by_file(&self, file_id: &str) -> Result<Vec<FileVersion>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "version": 1 })
+            .build();
+
+        self.collection
+            .find(doc! { "fileId": file_id }, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_version(&self, file_id: &str, version: i32) -> Result<Option<FileVersion>, String> {
+        self.collection
+            .find_one(doc! { "fileId": file_id, "version": version }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_by_file(&self, file_id: &str) -> Result<u64, String> {
+        self.collection
+            .delete_many(doc! { "fileId": file_id }, None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| e.to_string())
+    }
+}
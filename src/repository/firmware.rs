@@ -0,0 +1,105 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::Firmware;
+use futures_util::stream::TryStreamExt;
+
+pub struct FirmwareRepository {
+    collection: Collection<Firmware>,
+}
+
+impl FirmwareRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Firmware>("firmware");
+        Self { collection }
+    }
+
+    pub async fn create(&self, firmware: Firmware) -> Result<Firmware, String> {
+        let result = self
+            .collection
+            .insert_one(firmware.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_firmware = firmware;
+        created_firmware.id = result.inserted_id.as_object_id();
+
+        Ok(created_firmware)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<Firmware>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Firmware>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_version(&self, version: &str) -> Result<Option<Firmware>, String> {
+        self.collection
+            .find_one(doc! { "version": version }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Active releases applicable to `model`: those targeting it, plus those targeting every model.
+    pub async fn find_active_for_model(&self, model: Option<&str>) -> Result<Vec<Firmware>, String> {
+        let mut targets = vec![doc! { "kit_models": { "$size": 0 } }];
+        if let Some(model) = model {
+            targets.push(doc! { "kit_models": model });
+        }
+
+        let cursor = self.collection
+            .find(doc! { "is_active": true, "$or": targets }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn update(&self, id: ObjectId, firmware: Firmware) -> Result<Firmware, String> {
+        let update = doc! {
+            "$set": {
+                "version": firmware.version.clone(),
+                "checksum": firmware.checksum.clone(),
+                "artifact_key": firmware.artifact_key.clone(),
+                "artifact_url": firmware.artifact_url.clone(),
+                "kit_models": firmware.kit_models.clone(),
+                "release_notes": firmware.release_notes.clone(),
+                "is_active": firmware.is_active,
+                "updated_at": firmware.updated_at,
+            }
+        };
+
+        self.collection
+            .update_one(doc! { "_id": id }, update, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(firmware)
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        let result = self
+            .collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.deleted_count > 0)
+    }
+}
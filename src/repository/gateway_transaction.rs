@@ -0,0 +1,74 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::GatewayTransaction;
+use crate::status::GatewayStatus;
+use futures_util::stream::TryStreamExt;
+
+pub struct GatewayTransactionRepository {
+    collection: Collection<GatewayTransaction>,
+}
+
+impl GatewayTransactionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<GatewayTransaction>("gateway_transactions");
+        Self { collection }
+    }
+
+    /// Keeps the id the link was created with, which is the order id the provider calls back with
+    pub async fn insert(&self, transaction: GatewayTransaction) -> Result<GatewayTransaction, String> {
+        let id = transaction.id.ok_or("Transaction without an ID".to_string())?;
+        // The model serializes its id as an `id` string for API responses
+        let mut document = mongodb::bson::to_document(&transaction).map_err(|e| e.to_string())?;
+        document.remove("id");
+        document.insert("_id", id);
+        self.collection.clone_with_type::<Document>()
+            .insert_one(document, None)
+            .await
+            .map(|_| transaction)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<GatewayTransaction>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_invoice(&self, invoice_id: &str) -> Result<Vec<GatewayTransaction>, String> {
+        let options = FindOptions::builder().sort(doc! { "createdAt": -1 }).build();
+        let cursor = self.collection
+            .find(doc! { "invoiceId": invoice_id }, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Move a pending transaction to `status`; `None` when it is no longer pending, so a
+    /// repeated callback is only applied once. Within `session`'s transaction, so a payment
+    /// that fails to record leaves it pending for the provider's retry.
+    pub async fn settle(&self, session: &mut ClientSession, id: ObjectId, status: GatewayStatus, set: Document) -> Result<Option<GatewayTransaction>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let mut set = set;
+        set.insert("status", status);
+        set.insert("updatedAt", DateTime::now());
+        self.collection
+            .find_one_and_update_with_session(doc! { "_id": id, "status": GatewayStatus::Pending }, doc! { "$set": set }, options, session)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn set_payment(&self, session: &mut ClientSession, id: ObjectId, payment_id: &str) -> Result<(), String> {
+        self.collection
+            .update_one_with_session(doc! { "_id": id }, doc! { "$set": { "paymentId": payment_id } }, None, session)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
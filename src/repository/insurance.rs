@@ -1,6 +1,7 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{bson::{doc, oid::ObjectId}, Database, options::{FindOneOptions, FindOptions}};
 use futures_util::stream::TryStreamExt;
-use crate::models::Insurance;
+use crate::models::{Insurance, InsuranceVersion};
+use crate::status::InsuranceStatus;
 use crate::pagination::PaginationParams;
 
 pub struct InsuranceRepository {
@@ -25,11 +26,15 @@ impl InsuranceRepository {
         }
     }
 
-    pub async fn find_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<Insurance>, u64), String> {
+    pub async fn find_all_paginated(&self, pagination: PaginationParams, status: Option<&InsuranceStatus>) -> Result<(Vec<Insurance>, u64), String> {
         let collection = self.db.collection::<Insurance>("insurances");
-        
+        let filter = match status {
+            Some(status) => doc! { "status": status.as_str() },
+            None => doc! {},
+        };
+
         let total = collection
-            .count_documents(doc! {}, None)
+            .count_documents(filter.clone(), None)
             .await
             .map_err(|e| format!("Failed to count documents: {}", e))?;
 
@@ -38,7 +43,7 @@ impl InsuranceRepository {
             .limit(pagination.limit() as i64)
             .build();
 
-        match collection.find(doc! {}, options).await {
+        match collection.find(filter, options).await {
             Ok(cursor) => {
                 let records = cursor
                     .try_collect::<Vec<Insurance>>()
@@ -53,11 +58,59 @@ impl InsuranceRepository {
     pub async fn insert(&self, insurance: Insurance) -> Result<Insurance, String> {
         let collection = self.db.collection::<Insurance>("insurances");
         match collection.insert_one(insurance.clone(), None).await {
-            Ok(_) => Ok(insurance),
+            Ok(result) => Ok(Insurance { id: result.inserted_id.as_object_id(), ..insurance }),
             Err(e) => Err(format!("Failed to insert insurance: {}", e)),
         }
     }
 
+    /// Fails on a duplicate `(insuranceId, version)`, when another change took the number first
+    pub async fn insert_version(&self, version: &InsuranceVersion) -> Result<(), String> {
+        let collection = self.db.collection::<InsuranceVersion>("insurance_versions");
+        collection
+            .insert_one(version, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to insert insurance version: {}", e))
+    }
+
+    /// End `version` where the next one takes effect
+    pub async fn close_version(&self, insurance_id: ObjectId, version: i32, until: &str) -> Result<(), String> {
+        let collection = self.db.collection::<InsuranceVersion>("insurance_versions");
+        collection
+            .update_one(
+                doc! { "insuranceId": insurance_id.to_hex(), "version": version },
+                doc! { "$set": { "effectiveUntil": until } },
+                None,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to close insurance version: {}", e))
+    }
+
+    /// Every version of the insurance, newest first
+    pub async fn find_versions(&self, insurance_id: ObjectId) -> Result<Vec<InsuranceVersion>, String> {
+        let collection = self.db.collection::<InsuranceVersion>("insurance_versions");
+        let options = FindOptions::builder().sort(doc! { "version": -1 }).build();
+        collection
+            .find(doc! { "insuranceId": insurance_id.to_hex() }, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// The version in effect on `date` (`YYYY-MM-DD`): the latest `effectiveFrom` not after
+    /// it, and of those the highest version
+    pub async fn find_version_on(&self, insurance_id: ObjectId, date: &str) -> Result<Option<InsuranceVersion>, String> {
+        let collection = self.db.collection::<InsuranceVersion>("insurance_versions");
+        let options = FindOneOptions::builder().sort(doc! { "effectiveFrom": -1, "version": -1 }).build();
+        collection
+            .find_one(doc! { "insuranceId": insurance_id.to_hex(), "effectiveFrom": { "$lte": date } }, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
+    }
+
     pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<Insurance>, String> {
         let collection = self.db.collection::<Insurance>("insurances");
         collection
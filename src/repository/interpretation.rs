@@ -3,16 +3,18 @@ use mongodb::{
     Collection, Database,
 };
 use crate::models::Interpretation;
+use crate::repository::TombstoneRepository;
 use futures_util::stream::TryStreamExt;
 
 pub struct InterpretationRepository {
     collection: Collection<Interpretation>,
+    tombstones: TombstoneRepository,
 }
 
 impl InterpretationRepository {
     pub fn new(db: Database) -> Self {
         let collection = db.collection::<Interpretation>("interpretations");
-        Self { collection }
+        Self { collection, tombstones: TombstoneRepository::new(db) }
     }
 
     pub async fn create(&self, interpretation: Interpretation) -> Result<Interpretation, String> {
@@ -101,8 +103,9 @@ impl InterpretationRepository {
                     "display": interpretation.coding.display.clone(),
                 },
                 "text": interpretation.text.clone(),
-                "created_at": interpretation.created_at.clone(),
-                "updated_at": interpretation.updated_at.clone(),
+                "display_i18n": crate::i18n::to_document(&interpretation.display_i18n),
+                "created_at": interpretation.created_at,
+                "updated_at": interpretation.updated_at,
             }
         };
 
@@ -121,6 +124,9 @@ impl InterpretationRepository {
             .await
             .map_err(|e| e.to_string())?;
 
+        if result.deleted_count > 0 {
+            self.tombstones.record("interpretations", id).await;
+        }
         Ok(result.deleted_count > 0)
     }
 }
@@ -0,0 +1,265 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Invoice;
+use crate::pagination::PaginationParams;
+use crate::repository::appointment::lookup_one;
+use crate::repository::payment::scope_to_organizations;
+use crate::status::{InvoiceStatus, PriceItemType};
+use futures_util::stream::TryStreamExt;
+use serde::Deserialize;
+
+/// Billed lines of one item type and service category, see `InvoiceRepository::revenue_by_category`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct CategoryRevenueRow {
+    pub item_type: PriceItemType,
+    /// The service's category; absent for medicines and services that no longer exist
+    #[serde(default)]
+    pub category: Option<String>,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+/// Billed lines of one service, see `InvoiceRepository::service_utilization`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ServiceUtilizationRow {
+    pub service_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Invoices billing the service at least once
+    pub invoices: i64,
+    pub quantity: f64,
+    pub amount: f64,
+}
+
+/// Lines of the invoices served `from` to `to` inclusive that were not cancelled
+fn billed_lines(from: &str, to: &str, organizations: Option<&[String]>) -> Vec<Document> {
+    let mut filter = doc! {
+        "serviceDate": { "$gte": from, "$lte": to },
+        "status": { "$ne": InvoiceStatus::Cancelled.as_str() },
+    };
+    scope_to_organizations(&mut filter, organizations);
+    vec![
+        doc! { "$match": filter },
+        doc! { "$unwind": "$lines" },
+    ]
+}
+
+fn revenue_by_category_pipeline(from: &str, to: &str, organizations: Option<&[String]>) -> Vec<Document> {
+    let mut pipeline = billed_lines(from, to, organizations);
+    pipeline.extend(lookup_one("services", "lines.itemId", "service", &["category"]));
+    pipeline.extend([
+        doc! { "$group": {
+            "_id": {
+                "itemType": "$lines.itemType",
+                "category": { "$cond": [{ "$eq": ["$lines.itemType", PriceItemType::Service.as_str()] }, "$service.category", null] },
+            },
+            "quantity": { "$sum": "$lines.quantity" },
+            "amount": { "$sum": "$lines.amount" },
+        }},
+        doc! { "$project": { "_id": 0, "item_type": "$_id.itemType", "category": "$_id.category", "quantity": 1, "amount": 1 } },
+        doc! { "$sort": { "amount": -1, "item_type": 1, "category": 1 } },
+    ]);
+    pipeline
+}
+
+fn service_utilization_pipeline(from: &str, to: &str, organizations: Option<&[String]>) -> Vec<Document> {
+    let mut pipeline = billed_lines(from, to, organizations);
+    pipeline.extend([
+        doc! { "$match": { "lines.itemType": PriceItemType::Service.as_str() } },
+        // Once per invoice and service first, so `invoices` counts each invoice once
+        doc! { "$group": {
+            "_id": { "invoice": "$_id", "service": "$lines.itemId" },
+            "quantity": { "$sum": "$lines.quantity" },
+            "amount": { "$sum": "$lines.amount" },
+        }},
+        doc! { "$group": {
+            "_id": "$_id.service",
+            "invoices": { "$sum": 1 },
+            "quantity": { "$sum": "$quantity" },
+            "amount": { "$sum": "$amount" },
+        }},
+    ]);
+    pipeline.extend(lookup_one("services", "_id", "service", &["name", "category"]));
+    pipeline.extend([
+        doc! { "$project": {
+            "_id": 0,
+            "service_id": "$_id",
+            "name": "$service.name",
+            "category": "$service.category",
+            "invoices": 1,
+            "quantity": 1,
+            "amount": 1,
+        }},
+        doc! { "$sort": { "quantity": -1, "service_id": 1 } },
+    ]);
+    pipeline
+}
+
+pub struct InvoiceRepository {
+    collection: Collection<Invoice>,
+}
+
+impl InvoiceRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Invoice>("invoices");
+        Self { collection }
+    }
+
+    pub async fn create(&self, invoice: Invoice) -> Result<Invoice, String> {
+        let result = self
+            .collection
+            .insert_one(invoice.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = invoice;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Invoice>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Most recent first
+    pub async fn find_paginated(&self, patient_id: Option<&str>, status: Option<&InvoiceStatus>, pagination: &PaginationParams) -> Result<(Vec<Invoice>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(patient_id) = patient_id {
+            filter.insert("patientId", patient_id);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "createdAt": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let invoices: Vec<Invoice> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((invoices, total))
+    }
+
+    /// Set the paid amount and status if the invoice still shows `expected_paid`, so two
+    /// payments recorded at once cannot both count against the same balance. Runs in
+    /// `session`'s transaction, so the payment recording it commits with it.
+    pub async fn apply_payment(&self, session: &mut ClientSession, id: ObjectId, expected_paid: f64, paid_amount: f64, status: InvoiceStatus) -> Result<Option<Invoice>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let filter = doc! {
+            "_id": id,
+            "paidAmount": expected_paid,
+            "status": { "$in": [InvoiceStatus::Unpaid, InvoiceStatus::PartiallyPaid] },
+        };
+        let update = doc! { "$set": { "paidAmount": paid_amount, "status": status, "updatedAt": DateTime::now() } };
+        self.collection
+            .find_one_and_update_with_session(filter, update, options, session)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Invoices billed to a corporate client served `from` to `to` inclusive that were not
+    /// cancelled, by service date
+    pub async fn find_billed_to_client(&self, client_id: &str, from: &str, to: &str) -> Result<Vec<Invoice>, String> {
+        let filter = doc! {
+            "corporate.clientId": client_id,
+            "serviceDate": { "$gte": from, "$lte": to },
+            "status": { "$ne": InvoiceStatus::Cancelled.as_str() },
+        };
+        let options = FindOptions::builder().sort(doc! { "serviceDate": 1, "createdAt": 1 }).build();
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn any_billed_to_client(&self, client_id: &str) -> Result<bool, String> {
+        self.collection
+            .find_one(doc! { "corporate.clientId": client_id }, None)
+            .await
+            .map(|invoice| invoice.is_some())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Billed quantity and amount per item type and service category, by service date
+    pub async fn revenue_by_category(&self, from: &str, to: &str, organizations: Option<&[String]>) -> Result<Vec<CategoryRevenueRow>, String> {
+        self.aggregate(revenue_by_category_pipeline(from, to, organizations)).await
+    }
+
+    /// Invoices, quantity and amount billed per service, most used first
+    pub async fn service_utilization(&self, from: &str, to: &str, organizations: Option<&[String]>) -> Result<Vec<ServiceUtilizationRow>, String> {
+        self.aggregate(service_utilization_pipeline(from, to, organizations)).await
+    }
+
+    async fn aggregate<T: serde::de::DeserializeOwned>(&self, pipeline: Vec<Document>) -> Result<Vec<T>, String> {
+        let cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Bill a merged duplicate's invoices to the patient it was merged into, within `session`'s
+    /// transaction; amounts and payments are untouched
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utilization_counts_each_invoice_once_per_service() {
+        let organizations = vec!["org1".to_string()];
+        let pipeline = service_utilization_pipeline("2026-03-01", "2026-03-31", Some(&organizations));
+        assert_eq!(pipeline[0], doc! { "$match": {
+            "serviceDate": { "$gte": "2026-03-01", "$lte": "2026-03-31" },
+            "status": { "$ne": "cancelled" },
+            "organizationId": { "$in": ["org1"] },
+        }});
+        assert_eq!(pipeline[2], doc! { "$match": { "lines.itemType": "service" } });
+        let first = pipeline[3].get_document("$group").unwrap();
+        assert_eq!(first.get_document("_id").unwrap(), &doc! { "invoice": "$_id", "service": "$lines.itemId" });
+        let second = pipeline[4].get_document("$group").unwrap();
+        assert_eq!(second.get_str("_id").unwrap(), "$_id.service");
+        assert!(pipeline[5].contains_key("$lookup"));
+
+        let row: ServiceUtilizationRow = mongodb::bson::from_document(doc! {
+            "service_id": "s1", "invoices": 2_i64, "quantity": 3.0, "amount": 150_000.0,
+        }).unwrap();
+        assert_eq!(row.name, None);
+    }
+}
@@ -0,0 +1,145 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::{Job, JobFailure, JobProgress};
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+pub struct JobRepository {
+    collection: Collection<Job>,
+}
+
+impl JobRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Job>("jobs");
+        Self { collection }
+    }
+
+    pub async fn create(&self, job: Job) -> Result<Job, String> {
+        let result = self
+            .collection
+            .insert_one(job.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_job = job;
+        created_job.id = result.inserted_id.as_object_id();
+
+        Ok(created_job)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Job>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Apply a `$set` to a job, stamping `updated_at`.
+    pub async fn set_fields(&self, id: ObjectId, mut fields: Document, now: DateTime) -> Result<(), String> {
+        fields.insert("updated_at", now);
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$set": fields }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Mark a job running and count the attempt.
+    pub async fn mark_running(&self, id: ObjectId, now: DateTime) -> Result<(), String> {
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! {
+                    "$set": { "status": "running", "started_at": crate::datetime::to_rfc3339(now), "updated_at": now },
+                    "$unset": { "error": "", "finished_at": "" },
+                    "$inc": { "attempts": 1 },
+                },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn update_progress(&self, id: ObjectId, progress: &JobProgress, now: DateTime) -> Result<(), String> {
+        let progress = mongodb::bson::to_document(progress).map_err(|e| e.to_string())?;
+        self.set_fields(id, doc! { "progress": progress }, now).await
+    }
+
+    /// Mark a job failed and append the failure to its history.
+    pub async fn record_failure(&self, id: ObjectId, failure: &JobFailure, now: DateTime) -> Result<(), String> {
+        let entry = mongodb::bson::to_document(failure).map_err(|e| e.to_string())?;
+        self.collection
+            .update_one(
+                doc! { "_id": id },
+                doc! {
+                    "$set": { "status": "failed", "error": &failure.error, "finished_at": &failure.failed_at, "updated_at": now },
+                    "$push": { "errors": entry },
+                },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<Job>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .sort(doc! { "created_at": -1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+
+    /// Oldest first, at most `limit`
+    pub async fn find_ids(&self, filter: Document, limit: i64) -> Result<Vec<ObjectId>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": 1 })
+            .limit(limit)
+            .projection(doc! { "_id": 1 })
+            .build();
+        let cursor = self.collection.clone_with_type::<Document>()
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let ids: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+        Ok(ids.iter().filter_map(|d| d.get_object_id("_id").ok()).collect())
+    }
+
+    /// Put a failed job back to pending with its progress cleared; `None` when it is not
+    /// failed (any more), so a job is requeued once.
+    pub async fn requeue_failed(&self, id: ObjectId, now: DateTime) -> Result<Option<Job>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": "failed" },
+                doc! {
+                    "$set": {
+                        "status": "pending",
+                        "progress.processed": 0, "progress.succeeded": 0, "progress.skipped": 0, "progress.failed": 0,
+                        "updated_at": now,
+                    },
+                    "$unset": { "result": "", "finished_at": "" },
+                },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
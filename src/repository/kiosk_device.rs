@@ -0,0 +1,84 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::KioskDevice;
+use futures_util::stream::TryStreamExt;
+
+pub struct KioskDeviceRepository {
+    collection: Collection<KioskDevice>,
+}
+
+impl KioskDeviceRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<KioskDevice>("kiosk_devices");
+        Self { collection }
+    }
+
+    pub async fn create(&self, device: KioskDevice) -> Result<KioskDevice, String> {
+        let result = self
+            .collection
+            .insert_one(device.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = device;
+        created.id = result.inserted_id.as_object_id();
+        Ok(created)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<KioskDevice>, String> {
+        let options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<KioskDevice>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Use the unexpired pairing code with this hash; `None` when there is none. The code is
+    /// cleared in the same update, so it pairs one device once.
+    pub async fn pair(&self, code_hash: &str, now: DateTime) -> Result<Option<KioskDevice>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let filter = doc! { "pairingCodeHash": code_hash, "pairingExpiresAt": { "$gt": now } };
+        let update = doc! {
+            "$set": { "pairedAt": now },
+            "$unset": { "pairingCodeHash": "", "pairingExpiresAt": "", "revokedAt": "" },
+        };
+
+        self.collection
+            .find_one_and_update(filter, update, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<KioskDevice>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Record a request from the device; best effort for the admin listing
+    pub async fn touch(&self, id: ObjectId, now: DateTime) -> Result<(), String> {
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$set": { "lastSeenAt": now } }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
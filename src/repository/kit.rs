@@ -1,18 +1,127 @@
 use mongodb::{
-    bson::{doc, oid::ObjectId},
-    Collection, Database,
+    bson::{doc, oid::ObjectId, Bson, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
 };
-use crate::models::Kit;
+use serde::Deserialize;
+use crate::distributor::KitScope;
+use crate::geo::GeoPoint;
+use crate::models::{Kit, KitDistributor};
 use futures_util::stream::TryStreamExt;
 
+/// The fields of a kit a list view needs; owner, operator and patient are left out.
+#[derive(Debug, Deserialize)]
+pub struct KitSummaryRow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub code: String,
+    pub name: String,
+    pub is_active: bool,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub firmware_version: Option<String>,
+    #[serde(default)]
+    pub last_heartbeat_at: Option<String>,
+}
+
+/// A kit found by `KitRepository::find_nearby`
+#[derive(Debug, Deserialize)]
+pub struct NearbyKitRow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub code: String,
+    pub name: String,
+    pub is_active: bool,
+    pub distributor: KitDistributor,
+    pub location: GeoPoint,
+    /// Meters
+    pub distance: f64,
+}
+
+/// Kits of one map grid cell, see `KitRepository::map_clusters`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct MapClusterRow {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub count: i64,
+    pub active: i64,
+    pub first_id: ObjectId,
+}
+
+fn nearby_pipeline(center: &GeoPoint, radius: f64, limit: i64, distributor: Option<&str>, scope: &KitScope) -> Vec<Document> {
+    let mut query = Document::new();
+    if let Some(distributor) = distributor {
+        query.insert("distributor.code", distributor);
+    }
+    let query = scope.apply(query);
+    vec![
+        doc! { "$geoNear": {
+            "near": center,
+            "key": "location",
+            "distanceField": "distance",
+            "maxDistance": radius,
+            "spherical": true,
+            "query": query,
+        }},
+        doc! { "$limit": limit },
+        doc! { "$project": { "code": 1, "name": 1, "is_active": 1, "distributor": 1, "location": 1, "distance": 1 } },
+    ]
+}
+
+/// Kits with a location grouped into cells `cell` degrees wide, largest clusters first.
+/// `bounds` is `[min_lng, min_lat, max_lng, max_lat]`.
+fn clusters_pipeline(cell: f64, bounds: Option<[f64; 4]>, distributor: Option<&str>, scope: &KitScope) -> Vec<Document> {
+    let mut filter = doc! { "location": { "$exists": true } };
+    if let Some([min_lng, min_lat, max_lng, max_lat]) = bounds {
+        let ring = vec![
+            vec![min_lng, min_lat], vec![max_lng, min_lat], vec![max_lng, max_lat], vec![min_lng, max_lat], vec![min_lng, min_lat],
+        ];
+        filter.insert("location", doc! { "$geoWithin": { "$geometry": { "type": "Polygon", "coordinates": [ring] } } });
+    }
+    if let Some(distributor) = distributor {
+        filter.insert("distributor.code", distributor);
+    }
+    let filter = scope.apply(filter);
+    let longitude = doc! { "$arrayElemAt": ["$location.coordinates", 0] };
+    let latitude = doc! { "$arrayElemAt": ["$location.coordinates", 1] };
+    vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": {
+                "x": { "$floor": { "$divide": [longitude.clone(), cell] } },
+                "y": { "$floor": { "$divide": [latitude.clone(), cell] } },
+            },
+            "count": { "$sum": 1 },
+            "active": { "$sum": { "$cond": ["$is_active", 1, 0] } },
+            "latitude": { "$avg": latitude },
+            "longitude": { "$avg": longitude },
+            "first_id": { "$first": "$_id" },
+        }},
+        doc! { "$project": { "_id": 0, "latitude": 1, "longitude": 1, "count": 1, "active": 1, "first_id": 1 } },
+        doc! { "$sort": { "count": -1, "latitude": 1, "longitude": 1 } },
+    ]
+}
+
 pub struct KitRepository {
     collection: Collection<Kit>,
+    scope: KitScope,
 }
 
 impl KitRepository {
     pub fn new(db: Database) -> Self {
         let collection = db.collection::<Kit>("kits");
-        Self { collection }
+        Self { collection, scope: KitScope::All }
+    }
+
+    /// Limits every query but `code_exists` and `record_heartbeat` to `scope`
+    pub fn scoped(mut self, scope: KitScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn scope(&self) -> &KitScope {
+        &self.scope
     }
 
     pub async fn create(&self, kit: Kit) -> Result<Kit, String> {
@@ -31,7 +140,7 @@ impl KitRepository {
     pub async fn find_all(&self) -> Result<Vec<Kit>, String> {
         let cursor = self
             .collection
-            .find(None, None)
+            .find(self.scope.apply(doc! {}), None)
             .await
             .map_err(|e| e.to_string())?;
 
@@ -40,22 +149,65 @@ impl KitRepository {
         Ok(kits)
     }
 
+    pub async fn find_all_summaries(&self) -> Result<Vec<KitSummaryRow>, String> {
+        let projection = doc! { "code": 1, "name": 1, "is_active": 1, "model": 1, "firmware_version": 1, "last_heartbeat_at": 1 };
+        let cursor = self
+            .collection
+            .clone_with_type::<KitSummaryRow>()
+            .find(self.scope.apply(doc! {}), FindOptions::builder().projection(projection).build())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
     pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Kit>, String> {
         self.collection
-            .find_one(doc! { "_id": id }, None)
+            .find_one(self.scope.apply(doc! { "_id": id }), None)
             .await
             .map_err(|e| e.to_string())
     }
 
     pub async fn find_by_code(&self, code: &str) -> Result<Option<Kit>, String> {
         self.collection
-            .find_one(doc! { "code": code }, None)
+            .find_one(self.scope.apply(doc! { "code": code }), None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Whether any kit, in scope or not, has `code`
+    pub async fn code_exists(&self, code: &str) -> Result<bool, String> {
+        self.collection
+            .count_documents(doc! { "code": code }, None)
             .await
+            .map(|count| count > 0)
             .map_err(|e| e.to_string())
     }
 
+    pub async fn find_by_distributor(&self, code: &str) -> Result<Vec<Kit>, String> {
+        let cursor = self.collection
+            .find(self.scope.apply(doc! { "distributor.code": code }), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Codes of the kits in scope; `None` when the scope is unrestricted
+    pub async fn find_scoped_codes(&self) -> Result<Option<Vec<String>>, String> {
+        if self.scope == KitScope::All {
+            return Ok(None);
+        }
+        let codes = self.collection
+            .distinct("code", self.scope.apply(doc! {}), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(Some(codes.into_iter().filter_map(|code| code.as_str().map(str::to_string)).collect()))
+    }
+
     pub async fn update(&self, id: ObjectId, kit: Kit) -> Result<Kit, String> {
-        let filter = doc! { "_id": id };
+        let filter = self.scope.apply(doc! { "_id": id });
         let update = doc! {
             "$set": {
                 "code": kit.code.clone(),
@@ -80,7 +232,9 @@ impl KitRepository {
                     "id_pasien": kit.pasien.id_pasien.clone(),
                     "time": kit.pasien.time,
                 },
-                "updated_at": kit.updated_at.clone(),
+                "model": kit.model.clone(),
+                "location": kit.location.as_ref().map(Bson::from),
+                "updated_at": kit.updated_at,
             }
         };
 
@@ -92,13 +246,103 @@ impl KitRepository {
         Ok(kit)
     }
 
+    /// Operator IDs recorded on kits for an operator NIK.
+    pub async fn find_operator_ids_by_nik(&self, nik: &str) -> Result<Vec<String>, String> {
+        let ids = self.collection
+            .distinct("operator.id", self.scope.apply(doc! { "operator.nik": nik }), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(ids.into_iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+    }
+
+    /// Store the firmware a kit reports, returning the updated kit or `None` for an unknown code.
+    /// Kits report for themselves, so the scope does not apply.
+    pub async fn record_heartbeat(&self, code: &str, firmware_version: &str, model: Option<&str>, at: &str) -> Result<Option<Kit>, String> {
+        let mut set = doc! { "firmware_version": firmware_version, "last_heartbeat_at": at };
+        if let Some(model) = model.filter(|m| !m.is_empty()) {
+            set.insert("model", model);
+        }
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "code": code }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Kits within `radius` meters of `center`, nearest first
+    pub async fn find_nearby(&self, center: &GeoPoint, radius: f64, limit: i64, distributor: Option<&str>) -> Result<Vec<NearbyKitRow>, String> {
+        self.aggregate(nearby_pipeline(center, radius, limit, distributor, &self.scope)).await
+    }
+
+    pub async fn map_clusters(&self, cell: f64, bounds: Option<[f64; 4]>, distributor: Option<&str>) -> Result<Vec<MapClusterRow>, String> {
+        self.aggregate(clusters_pipeline(cell, bounds, distributor, &self.scope)).await
+    }
+
+    async fn aggregate<T: serde::de::DeserializeOwned>(&self, pipeline: Vec<Document>) -> Result<Vec<T>, String> {
+        let cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
     pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
         let result = self
             .collection
-            .delete_one(doc! { "_id": id }, None)
+            .delete_one(self.scope.apply(doc! { "_id": id }), None)
             .await
             .map_err(|e| e.to_string())?;
 
         Ok(result.deleted_count > 0)
     }
+
+    /// Point kits measuring a merged duplicate at the patient it was merged into, within
+    /// `session`'s transaction. Unscoped: a merge covers every distributor's kits.
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "pasien.id_pasien": from }, doc! { "$set": { "pasien.id_pasien": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearby_starts_with_geo_near_on_the_location() {
+        let pipeline = nearby_pipeline(&GeoPoint::new(-6.2, 106.8), 5_000.0, 50, Some("D01"), &KitScope::All);
+        let near = pipeline[0].get_document("$geoNear").unwrap();
+        assert_eq!(near.get_document("near").unwrap(), &doc! { "type": "Point", "coordinates": [106.8, -6.2] });
+        assert_eq!(near.get_f64("maxDistance").unwrap(), 5_000.0);
+        assert_eq!(near.get_document("query").unwrap(), &doc! { "distributor.code": "D01" });
+        assert_eq!(pipeline[1], doc! { "$limit": 50_i64 });
+    }
+
+    #[test]
+    fn clusters_are_limited_to_the_box() {
+        let pipeline = clusters_pipeline(0.5, Some([106.0, -7.0, 107.0, -6.0]), None, &KitScope::Distributors(vec!["D01".to_string()]));
+        let within = pipeline[0].get_document("$match").unwrap().get_document("location").unwrap();
+        let ring = within.get_document("$geoWithin").unwrap().get_document("$geometry").unwrap()
+            .get_array("coordinates").unwrap()[0].as_array().unwrap().clone();
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring[0], ring[4]);
+        assert_eq!(pipeline[0].get_document("$match").unwrap().get_document("distributor.code").unwrap(), &doc! { "$in": ["D01"] });
+
+        let row: MapClusterRow = mongodb::bson::from_document(doc! {
+            "latitude": -6.5, "longitude": 106.5, "count": 3_i64, "active": 2_i64, "first_id": ObjectId::new(),
+        }).unwrap();
+        assert_eq!(row.count, 3);
+    }
 }
@@ -1,7 +1,8 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{bson::{doc, oid::ObjectId, DateTime}, ClientSession, Database, options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument}};
 use futures_util::stream::TryStreamExt;
 use crate::models::MedicalRecord;
 use crate::pagination::PaginationParams;
+use crate::status::RegistrationStatus;
 
 pub struct MedicalRecordRepository {
     db: Database,
@@ -68,12 +69,77 @@ impl MedicalRecordRepository {
             .map_err(|e| format!("Database error: {}", e))
     }
 
+    pub async fn find_by_nrme(&self, nrme: &str) -> Result<Option<MedicalRecord>, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        collection
+            .find_one(doc! { "nrme": nrme }, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
+    }
+
+    pub async fn find_by_phone(&self, hp: &str) -> Result<Vec<MedicalRecord>, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        match collection.find(doc! { "hp": hp }, None).await {
+            Ok(cursor) => {
+                cursor
+                    .try_collect::<Vec<MedicalRecord>>()
+                    .await
+                    .map_err(|e| format!("Failed to collect results: {}", e))
+            }
+            Err(e) => Err(format!("Database error: {}", e)),
+        }
+    }
+
+    pub async fn find_by_ids(&self, ids: &[mongodb::bson::oid::ObjectId]) -> Result<Vec<MedicalRecord>, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        match collection.find(doc! { "_id": { "$in": ids } }, None).await {
+            Ok(cursor) => {
+                cursor
+                    .try_collect::<Vec<MedicalRecord>>()
+                    .await
+                    .map_err(|e| format!("Failed to collect results: {}", e))
+            }
+            Err(e) => Err(format!("Database error: {}", e)),
+        }
+    }
+
+    /// Group records sharing the value of `field`, returning only groups with more than one record.
+    pub async fn find_groups_by_field(&self, field: &str) -> Result<Vec<Vec<MedicalRecord>>, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        let pipeline = vec![
+            doc! { "$match": { field: { "$nin": [null, ""] } } },
+            doc! { "$group": { "_id": format!("${}", field), "records": { "$push": "$$ROOT" }, "count": { "$sum": 1 } } },
+            doc! { "$match": { "count": { "$gt": 1 } } },
+        ];
+
+        let mut cursor = collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let mut groups = Vec::new();
+        while let Some(group) = cursor.try_next().await.map_err(|e| format!("Failed to collect results: {}", e))? {
+            let records = group
+                .get_array("records")
+                .map_err(|e| format!("Malformed aggregation result: {}", e))?
+                .iter()
+                .filter_map(|r| r.as_document())
+                .map(|r| mongodb::bson::from_document::<MedicalRecord>(r.clone()))
+                .collect::<Result<Vec<MedicalRecord>, _>>()
+                .map_err(|e| format!("Failed to decode record: {}", e))?;
+            groups.push(records);
+        }
+
+        Ok(groups)
+    }
+
     pub async fn insert(&self, mut record: MedicalRecord) -> Result<MedicalRecord, String> {
         let collection = self.db.collection::<MedicalRecord>("medical_records");
         
         if record.id.is_none() {
             record.id = Some(mongodb::bson::oid::ObjectId::new());
         }
+        record.updated_at = Some(DateTime::now());
 
         collection
             .insert_one(record.clone(), None)
@@ -83,9 +149,10 @@ impl MedicalRecordRepository {
         Ok(record)
     }
 
-    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, record: MedicalRecord) -> Result<MedicalRecord, String> {
+    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, mut record: MedicalRecord) -> Result<MedicalRecord, String> {
         let collection = self.db.collection::<MedicalRecord>("medical_records");
-        
+        record.updated_at = Some(DateTime::now());
+
         collection
             .replace_one(doc! { "_id": id }, record.clone(), None)
             .await
@@ -94,14 +161,86 @@ impl MedicalRecordRepository {
         Ok(record)
     }
 
-    pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
+    /// Self-registered records in `status`, oldest registration first
+    pub async fn find_registrations(&self, status: &RegistrationStatus, pagination: PaginationParams) -> Result<(Vec<MedicalRecord>, u64), String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        let filter = doc! { "selfRegistration.status": status.as_str() };
+
+        let total = collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| format!("Failed to count documents: {}", e))?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "selfRegistration.registeredAt": 1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+
+        let records = collection
+            .find(filter, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))?;
+        Ok((records, total))
+    }
+
+    /// Confirm a pending self-registration; `None` when the record is not pending
+    pub async fn confirm_registration(&self, id: mongodb::bson::oid::ObjectId, confirmed_by: &str) -> Result<Option<MedicalRecord>, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        let now = DateTime::now();
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        collection
+            .find_one_and_update(
+                doc! { "_id": id, "selfRegistration.status": RegistrationStatus::Pending.as_str() },
+                doc! { "$set": {
+                    "selfRegistration.status": RegistrationStatus::Confirmed.as_str(),
+                    "selfRegistration.confirmedBy": confirmed_by,
+                    "selfRegistration.confirmedAt": now,
+                    "updatedAt": now,
+                } },
+                options,
+            )
+            .await
+            .map_err(|e| format!("Update failed: {}", e))
+    }
+
+    /// Stamp every record using this phone number as verified; returns how many changed
+    pub async fn mark_phone_verified(&self, hp: &str, verified_at: &str) -> Result<u64, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        collection
+            .update_many(doc! { "hp": hp }, doc! { "$set": { "phoneVerifiedAt": verified_at, "updatedAt": DateTime::now() } }, None)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| format!("Update failed: {}", e))
+    }
+
+    pub async fn delete(&self, session: &mut ClientSession, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
         let collection = self.db.collection::<MedicalRecord>("medical_records");
         
         let result = collection
-            .delete_one(doc! { "_id": id }, None)
+            .delete_one_with_session(doc! { "_id": id }, None, session)
             .await
             .map_err(|e| format!("Delete failed: {}", e))?;
 
         Ok(result.deleted_count > 0)
     }
+
+    /// Replace the record `id` with the merged `record` within `session`'s transaction
+    pub async fn merge_into(&self, session: &mut ClientSession, id: ObjectId, mut record: MedicalRecord) -> Result<MedicalRecord, String> {
+        let collection = self.db.collection::<MedicalRecord>("medical_records");
+        record.updated_at = Some(DateTime::now());
+
+        collection
+            .replace_one_with_session(doc! { "_id": id }, record.clone(), None, session)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))?;
+
+        Ok(record)
+    }
 }
@@ -0,0 +1,54 @@
+use mongodb::{
+    bson::doc,
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::MedicalRecordChange;
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+/// Changes are only ever inserted, never updated or deleted.
+pub struct MedicalRecordChangeRepository {
+    collection: Collection<MedicalRecordChange>,
+}
+
+impl MedicalRecordChangeRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<MedicalRecordChange>("medical_record_changes");
+        Self { collection }
+    }
+
+    pub async fn insert(&self, change: MedicalRecordChange) -> Result<MedicalRecordChange, String> {
+        let result = self
+            .collection
+            .insert_one(change.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = change;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    /// Changes to a record, newest first
+    pub async fn find_by_record(&self, record_id: &str, pagination: &PaginationParams) -> Result<(Vec<MedicalRecordChange>, u64), String> {
+        let filter = doc! { "recordId": record_id };
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .sort(doc! { "createdAt": -1, "_id": -1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+}
@@ -1,8 +1,20 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{bson::{doc, Document}, Database, options::FindOptions};
 use futures_util::stream::TryStreamExt;
+use serde::Deserialize;
 use crate::models::Medicine;
 use crate::pagination::PaginationParams;
 
+/// Stock of one medicine over its batches, see `MedicineRepository::stock_summary`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct StockRow {
+    pub master_medicine_id: String,
+    pub trade_name: String,
+    pub batches: i64,
+    pub qty: f64,
+    /// `YYYY-MM-DD` of the batch expiring first
+    pub earliest_expiry: String,
+}
+
 pub struct MedicineRepository {
     db: Database,
 }
@@ -66,6 +78,41 @@ impl MedicineRepository {
             .map_err(|e| format!("Database error: {}", e))
     }
 
+    pub async fn find_by_ids(&self, ids: &[mongodb::bson::oid::ObjectId]) -> Result<Vec<Medicine>, String> {
+        let collection = self.db.collection::<Medicine>("medicines");
+        collection
+            .find(doc! { "_id": { "$in": ids } }, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// Every batch of the given master medicines
+    pub async fn find_by_master_ids(&self, master_medicine_ids: &[String]) -> Result<Vec<Medicine>, String> {
+        let collection = self.db.collection::<Medicine>("medicines");
+        collection
+            .find(doc! { "masterMedicineId": { "$in": master_medicine_ids } }, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    /// Batches labelled with `batch_number`; different products may share one
+    pub async fn find_by_batch_number(&self, batch_number: &str, limit: i64) -> Result<Vec<Medicine>, String> {
+        let collection = self.db.collection::<Medicine>("medicines");
+        collection
+            .find(doc! { "batchNumber": batch_number }, FindOptions::builder().limit(limit).build())
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
     pub async fn update(&self, id: mongodb::bson::oid::ObjectId, medicine: Medicine) -> Result<Medicine, String> {
         let collection = self.db.collection::<Medicine>("medicines");
         match collection.replace_one(doc! { "_id": id }, medicine.clone(), None).await {
@@ -81,4 +128,28 @@ impl MedicineRepository {
             Err(e) => Err(format!("Failed to delete medicine: {}", e)),
         }
     }
+
+    /// Quantity per medicine over all batches, by trade name
+    pub async fn stock_summary(&self) -> Result<Vec<StockRow>, String> {
+        let pipeline = vec![
+            doc! { "$group": {
+                "_id": "$masterMedicineId",
+                "trade_name": { "$first": "$tradeName" },
+                "batches": { "$sum": 1 },
+                "qty": { "$sum": "$qty" },
+                "earliest_expiry": { "$min": "$expiredDate" },
+            }},
+            doc! { "$project": { "_id": 0, "master_medicine_id": "$_id", "trade_name": 1, "batches": 1, "qty": 1, "earliest_expiry": 1 } },
+            doc! { "$sort": { "trade_name": 1 } },
+        ];
+        let cursor = self.db.collection::<Document>("medicines")
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
 }
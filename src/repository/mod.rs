@@ -1,5 +1,11 @@
 pub mod medical_record;
 pub mod file;
+#[cfg(feature = "s3")]
+pub mod file_share;
+pub mod file_version;
+pub mod storage_usage;
+#[cfg(feature = "s3")]
+pub mod storage_reconciliation;
 pub mod doctor;
 pub mod nurse;
 pub mod medicine;
@@ -10,6 +16,12 @@ pub mod user;
 
 pub use medical_record::MedicalRecordRepository;
 pub use file::FileRepository;
+#[cfg(feature = "s3")]
+pub use file_share::FileShareRepository;
+pub use file_version::FileVersionRepository;
+pub use storage_usage::StorageUsageRepository;
+#[cfg(feature = "s3")]
+pub use storage_reconciliation::StorageReconciliationRepository;
 pub use doctor::DoctorRepository;
 pub use nurse::NurseRepository;
 pub use medicine::MedicineRepository;
@@ -35,3 +47,96 @@ pub mod kit;
 pub use kit::KitRepository;
 pub mod observation;
 pub use observation::ObservationRepository;
+pub mod audit_log;
+pub use audit_log::AuditLogRepository;
+pub mod search;
+pub use search::SearchRepository;
+pub mod job;
+pub use job::JobRepository;
+pub mod retention;
+pub mod denormalization;
+pub use retention::RetentionRepository;
+pub use denormalization::DenormalizationRepository;
+pub mod firmware;
+pub use firmware::FirmwareRepository;
+pub mod appointment_series;
+pub use appointment_series::AppointmentSeriesRepository;
+pub mod waitlist;
+pub use waitlist::WaitlistRepository;
+pub mod notification;
+pub use notification::NotificationRepository;
+pub mod teleconsult;
+pub use teleconsult::TeleconsultRepository;
+pub mod review;
+pub use review::ReviewRepository;
+pub mod otp;
+pub use otp::OtpRepository;
+pub mod permission;
+pub use permission::PermissionRepository;
+pub mod role_permission;
+pub use role_permission::RolePermissionRepository;
+pub mod service_account;
+pub use service_account::ServiceAccountRepository;
+pub mod kiosk_device;
+pub use kiosk_device::KioskDeviceRepository;
+pub mod request_log;
+pub use request_log::RequestLogRepository;
+pub mod feature_flag;
+pub use feature_flag::FeatureFlagRepository;
+pub mod organization;
+pub use organization::OrganizationRepository;
+pub mod note;
+pub use note::NoteRepository;
+pub mod note_version;
+pub use note_version::NoteVersionRepository;
+pub mod allergy;
+pub use allergy::AllergyRepository;
+pub mod ward;
+pub use ward::WardRepository;
+pub mod bed;
+pub use bed::BedRepository;
+pub mod admission;
+pub use admission::AdmissionRepository;
+pub mod program;
+pub use program::ProgramRepository;
+pub mod price_list;
+pub use price_list::PriceListRepository;
+pub mod invoice;
+pub use invoice::InvoiceRepository;
+pub mod corporate_client;
+pub use corporate_client::CorporateClientRepository;
+pub mod payment;
+pub use payment::PaymentRepository;
+pub mod cashier_shift;
+pub use cashier_shift::CashierShiftRepository;
+pub mod gateway_transaction;
+pub use gateway_transaction::GatewayTransactionRepository;
+pub mod outbox;
+pub use outbox::OutboxRepository;
+pub mod report_schedule;
+pub use report_schedule::ReportScheduleRepository;
+pub mod report_template;
+pub use report_template::ReportTemplateRepository;
+pub mod medical_record_change;
+pub use medical_record_change::MedicalRecordChangeRepository;
+pub mod counter;
+pub use counter::CounterRepository;
+pub mod practitioner;
+pub use practitioner::PractitionerRepository;
+pub mod slow_query;
+pub mod patient_relationship;
+pub use patient_relationship::PatientRelationshipRepository;
+pub mod stock_opname;
+pub use stock_opname::StockOpnameRepository;
+pub mod tombstone;
+pub use tombstone::TombstoneRepository;
+pub mod sync;
+pub use sync::SyncRepository;
+pub mod sync_mutation;
+pub use sync_mutation::SyncMutationRepository;
+pub mod sync_conflict;
+pub use sync_conflict::SyncConflictRepository;
+pub mod change_stream;
+pub use change_stream::ChangeStreamStateRepository;
+pub mod scheduler;
+pub use scheduler::SchedulerRepository;
@@ -0,0 +1,131 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::delete_policy::DELETED_AT;
+use crate::models::Note;
+use crate::pagination::PaginationParams;
+use crate::sync::Position;
+use futures_util::stream::TryStreamExt;
+
+pub struct NoteRepository {
+    collection: Collection<Note>,
+}
+
+impl NoteRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Note>("notes");
+        Self { collection }
+    }
+
+    pub async fn create(&self, note: Note) -> Result<Note, String> {
+        let result = self
+            .collection
+            .insert_one(note.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_note = note;
+        created_note.id = result.inserted_id.as_object_id();
+
+        Ok(created_note)
+    }
+
+    /// A note that has not been deleted
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Note>, String> {
+        self.collection
+            .find_one(doc! { "_id": id, DELETED_AT: Bson::Null }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Live notes, most recently edited first, optionally of one medical record
+    pub async fn find_paginated(&self, medical_record_id: Option<&str>, pagination: &PaginationParams) -> Result<(Vec<Note>, u64), String> {
+        let mut filter = doc! { DELETED_AT: Bson::Null };
+        if let Some(medical_record_id) = medical_record_id {
+            filter.insert("medicalRecordId", medical_record_id);
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "updatedAt": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+        let notes = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((notes, total))
+    }
+
+    /// Apply `set` to a live note still at `version`; `None` when it is gone or was edited meanwhile.
+    pub async fn update_at_version(&self, id: ObjectId, version: i32, set: Document) -> Result<Option<Note>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "version": version, DELETED_AT: Bson::Null }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Mark a live note deleted with `set`; `None` when it does not exist or is already deleted.
+    pub async fn soft_delete(&self, id: ObjectId, set: Document) -> Result<Option<Note>, String> {
+        self.collection
+            .find_one_and_update(doc! { "_id": id, DELETED_AT: Bson::Null }, doc! { "$set": set }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Live notes of the medical records written after `position`, by `updatedAt` then `_id`
+    pub async fn find_changed(&self, medical_record_ids: &[String], position: Option<Position>, limit: i64) -> Result<Vec<Note>, String> {
+        let mut filter = position.map(|p| p.filter("updatedAt")).unwrap_or_default();
+        filter.insert(DELETED_AT, Bson::Null);
+        self.find_feed(filter, medical_record_ids, "updatedAt", limit).await
+    }
+
+    /// Notes of the medical records deleted after `position`, by `deletedAt` then `_id`
+    pub async fn find_deleted(&self, medical_record_ids: &[String], position: Option<Position>, limit: i64) -> Result<Vec<Note>, String> {
+        let filter = position
+            .map(|p| p.filter(DELETED_AT))
+            .unwrap_or_else(|| doc! { DELETED_AT: { "$ne": Bson::Null } });
+        self.find_feed(filter, medical_record_ids, DELETED_AT, limit).await
+    }
+
+    async fn find_feed(&self, mut filter: Document, medical_record_ids: &[String], field: &str, limit: i64) -> Result<Vec<Note>, String> {
+        filter.insert("medicalRecordId", doc! { "$in": medical_record_ids });
+        let options = FindOptions::builder()
+            .sort(doc! { field: 1, "_id": 1 })
+            .limit(limit)
+            .build();
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Move a merged duplicate's notes, deleted ones included, to the record it was merged
+    /// into, within `session`'s transaction. `updatedAt` moves on so sync clients fetch them
+    /// under their new record; the note's version does not change.
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "medicalRecordId": from }, doc! { "$set": { "medicalRecordId": to, "updatedAt": DateTime::now() } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
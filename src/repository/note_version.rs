@@ -0,0 +1,47 @@
+use mongodb::{
+    bson::doc,
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::NoteVersion;
+use futures_util::stream::TryStreamExt;
+
+/// Versions are only ever inserted, never updated or deleted.
+pub struct NoteVersionRepository {
+    collection: Collection<NoteVersion>,
+}
+
+impl NoteVersionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<NoteVersion>("note_versions");
+        Self { collection }
+    }
+
+    pub async fn insert(&self, version: NoteVersion) -> Result<NoteVersion, String> {
+        let result = self
+            .collection
+            .insert_one(version.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_version = version;
+        created_version.id = result.inserted_id.as_object_id();
+
+        Ok(created_version)
+    }
+
+    /// Every version of a note, oldest first
+    pub async fn find_by_note(&self, note_id: &str) -> Result<Vec<NoteVersion>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "version": 1 })
+            .build();
+
+        self.collection
+            .find(doc! { "noteId": note_id }, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,26 @@
+use mongodb::{Collection, Database};
+use crate::models::Notification;
+
+pub struct NotificationRepository {
+    collection: Collection<Notification>,
+}
+
+impl NotificationRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Notification>("notifications");
+        Self { collection }
+    }
+
+    pub async fn create(&self, notification: Notification) -> Result<Notification, String> {
+        let result = self
+            .collection
+            .insert_one(notification.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_notification = notification;
+        created_notification.id = result.inserted_id.as_object_id();
+
+        Ok(created_notification)
+    }
+}
@@ -1,84 +1,49 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
-use futures_util::stream::TryStreamExt;
-use crate::models::Nurse;
+use mongodb::{bson::{doc, oid::ObjectId}, Database};
+use crate::models::{Nurse, Practitioner};
+use crate::repository::PractitionerRepository;
+use crate::status::PractitionerType;
 use crate::pagination::PaginationParams;
 
+/// The `/nurses` view of `practitioners`: practitioners of type `nurse`, read and written
+/// as `Nurse`. Writes keep the fields the view does not show.
 pub struct NurseRepository {
-    db: Database,
+    practitioners: PractitionerRepository,
 }
 
 impl NurseRepository {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self { practitioners: PractitionerRepository::new(db) }
     }
 
     pub async fn find_all(&self) -> Result<Vec<Nurse>, String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        match collection.find(doc! {}, None).await {
-            Ok(cursor) => {
-                cursor
-                    .try_collect::<Vec<Nurse>>()
-                    .await
-                    .map_err(|e| format!("Failed to collect results: {}", e))
-            }
-            Err(e) => Err(format!("Database error: {}", e)),
-        }
+        let nurses = self.practitioners.find(doc! { "type": PractitionerType::Nurse.as_str() }).await?;
+        Ok(nurses.into_iter().map(Nurse::from).collect())
     }
 
     pub async fn find_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<Nurse>, u64), String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        
-        let total = collection
-            .count_documents(doc! {}, None)
-            .await
-            .map_err(|e| format!("Failed to count documents: {}", e))?;
-
-        let options = FindOptions::builder()
-            .skip(pagination.skip())
-            .limit(pagination.limit() as i64)
-            .build();
-
-        match collection.find(doc! {}, options).await {
-            Ok(cursor) => {
-                let records = cursor
-                    .try_collect::<Vec<Nurse>>()
-                    .await
-                    .map_err(|e| format!("Failed to collect results: {}", e))?;
-                Ok((records, total))
-            }
-            Err(e) => Err(format!("Database error: {}", e)),
-        }
+        let (nurses, total) = self.practitioners
+            .find_paginated(doc! { "type": PractitionerType::Nurse.as_str() }, &pagination)
+            .await?;
+        Ok((nurses.into_iter().map(Nurse::from).collect(), total))
     }
 
     pub async fn insert(&self, nurse: Nurse) -> Result<Nurse, String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        match collection.insert_one(nurse.clone(), None).await {
-            Ok(_) => Ok(nurse),
-            Err(e) => Err(format!("Failed to insert nurse: {}", e)),
-        }
+        self.practitioners.insert(Practitioner::from(nurse)).await.map(Nurse::from)
     }
 
-    pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<Nurse>, String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        collection
-            .find_one(doc! { "_id": id }, None)
-            .await
-            .map_err(|e| format!("Database error: {}", e))
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Nurse>, String> {
+        let nurse = self.practitioners.find_by_id(id, Some(&PractitionerType::Nurse)).await?;
+        Ok(nurse.map(Nurse::from))
     }
 
-    pub async fn update(&self, id: mongodb::bson::oid::ObjectId, nurse: Nurse) -> Result<Nurse, String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        match collection.replace_one(doc! { "_id": id }, nurse.clone(), None).await {
-            Ok(_) => Ok(nurse),
-            Err(e) => Err(format!("Failed to update nurse: {}", e)),
-        }
+    pub async fn update(&self, id: ObjectId, nurse: Nurse) -> Result<Nurse, String> {
+        let mut practitioner = self.practitioners.find_by_id(id, Some(&PractitionerType::Nurse)).await?
+            .ok_or_else(|| "Failed to update nurse: not found".to_string())?;
+        practitioner.apply_nurse(nurse);
+        self.practitioners.replace(id, practitioner).await.map(Nurse::from)
     }
 
-    pub async fn delete(&self, id: mongodb::bson::oid::ObjectId) -> Result<bool, String> {
-        let collection = self.db.collection::<Nurse>("nurses");
-        match collection.delete_one(doc! { "_id": id }, None).await {
-            Ok(result) => Ok(result.deleted_count > 0),
-            Err(e) => Err(format!("Failed to delete nurse: {}", e)),
-        }
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.practitioners.delete(id, Some(&PractitionerType::Nurse)).await
     }
 }
@@ -1,11 +1,133 @@
 use mongodb::{
-    bson::{doc, oid::ObjectId},
-    Collection, Database,
+    bson::{doc, oid::ObjectId, Document},
+    ClientSession, Collection, Cursor, Database,
 };
-use crate::models::Observation;
+use serde::Deserialize;
+use crate::models::{MedicalRecord, Observation, ObservationBaseLine, ObservationCoding, ObservationUnit};
+use crate::refs::Ref;
+use crate::status::TimelineEntryType;
+use crate::timeline::{self, Position, TimeField};
 use futures_util::stream::TryStreamExt;
 use crate::pagination::PaginationParams;
 
+/// One observation in a trend series, with rolling statistics computed by the pipeline.
+#[derive(Debug, Deserialize)]
+pub struct ObservationTrendRow {
+    pub time: i64,
+    pub value: f64,
+    pub rolling_mean: f64,
+    pub rolling_min: f64,
+    pub rolling_max: f64,
+    pub base_line: ObservationBaseLine,
+    pub coding: ObservationCoding,
+    pub unit: ObservationUnit,
+}
+
+/// Observations recorded in one month, see `ObservationRepository::kit_activity`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct MonthlyActivityRow {
+    /// `YYYY-MM`
+    pub month: String,
+    pub observations: i64,
+    pub patients_served: i64,
+}
+
+/// Observations recorded by one operator (`id_petugas`), see `ObservationRepository::kit_activity`
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct OperatorActivityRow {
+    pub operator_id: String,
+    pub observations: i64,
+    pub patients_served: i64,
+}
+
+/// `time` as a date; it may be epoch seconds or milliseconds
+fn observed_at() -> Document {
+    doc! { "$toDate": {
+        "$cond": [{ "$gt": ["$time", 100_000_000_000_i64] }, "$time", { "$multiply": ["$time", 1000] }]
+    }}
+}
+
+/// Observations of the kits with `kit_codes` since `from`, per month in `timezone` and per
+/// operator, the `top_operators` busiest first
+fn kit_activity_pipeline(kit_codes: &[String], from: mongodb::bson::DateTime, timezone: &str, top_operators: i64) -> Vec<Document> {
+    vec![
+        doc! { "$match": { "atm_sehat.code": { "$in": kit_codes } } },
+        doc! { "$addFields": { "ts": observed_at() } },
+        doc! { "$match": { "ts": { "$gte": from } } },
+        doc! { "$facet": {
+            "months": [
+                { "$group": {
+                    "_id": { "$dateToString": { "format": "%Y-%m", "date": "$ts", "timezone": timezone } },
+                    "observations": { "$sum": 1 },
+                    "patients": { "$addToSet": "$id_pasien" },
+                }},
+                { "$project": { "_id": 0, "month": "$_id", "observations": 1, "patients_served": { "$size": "$patients" } } },
+                { "$sort": { "month": 1 } },
+            ],
+            "operators": [
+                { "$group": {
+                    "_id": "$id_petugas",
+                    "observations": { "$sum": 1 },
+                    "patients": { "$addToSet": "$id_pasien" },
+                }},
+                { "$sort": { "observations": -1, "_id": 1 } },
+                { "$limit": top_operators },
+                { "$project": { "_id": 0, "operator_id": "$_id", "observations": 1, "patients_served": { "$size": "$patients" } } },
+            ],
+        }},
+    ]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SummaryCode {
+    pub code: String,
+    #[serde(default)]
+    pub display: String,
+}
+
+/// The fields of an observation a list view needs, see `SUMMARY_PROJECTION`.
+#[derive(Debug, Deserialize)]
+pub struct ObservationSummaryRow {
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub id_pasien: Ref<MedicalRecord>,
+    pub value: f64,
+    pub time: i64,
+    pub unit: SummaryCode,
+    pub coding: SummaryCode,
+    pub interpretation: SummaryCode,
+    #[serde(default)]
+    pub derived: bool,
+}
+
+/// Leaves out the embedded `pasien` and `atm_sehat` documents
+fn summary_projection() -> Document {
+    doc! {
+        "id_pasien": 1, "value": 1, "time": 1, "derived": 1,
+        "unit.code": 1, "unit.display": 1,
+        "coding.code": 1, "coding.display": 1,
+        "interpretation.code": 1, "interpretation.display": 1,
+    }
+}
+
+/// Filter of an export: `from <= time <= to`, any of `coding_codes`, one patient
+fn export_filter(from: Option<i64>, to: Option<i64>, coding_codes: &[&str], id_pasien: Option<&str>) -> Document {
+    let mut filter = Document::new();
+    let mut time_range = Document::new();
+    if let Some(from) = from { time_range.insert("$gte", from); }
+    if let Some(to) = to { time_range.insert("$lte", to); }
+    if !time_range.is_empty() {
+        filter.insert("time", time_range);
+    }
+    if !coding_codes.is_empty() {
+        filter.insert("coding.code", doc! { "$in": coding_codes });
+    }
+    if let Some(id_pasien) = id_pasien {
+        filter.insert("id_pasien", id_pasien);
+    }
+    filter
+}
+
 pub struct ObservationRepository {
     collection: Collection<Observation>,
 }
@@ -16,6 +138,20 @@ impl ObservationRepository {
         Self { collection }
     }
 
+    /// The patient's observations after `after` in their timeline, newest first
+    pub async fn find_timeline(&self, patient_id: &str, after: Option<&Position>, limit: i64) -> Result<Vec<Observation>, String> {
+        let field = TimeField::UnixSeconds("time");
+        let filter = timeline::source_filter(doc! { "id_pasien": patient_id }, &TimelineEntryType::Observation, field, after);
+        let options = mongodb::options::FindOptions::builder().sort(timeline::newest_first(field)).limit(limit).build();
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn create(&self, observation: Observation) -> Result<Observation, String> {
         let result = self
             .collection
@@ -29,9 +165,35 @@ impl ObservationRepository {
         Ok(created_observation)
     }
 
-    pub async fn find_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<Observation>, u64), String> {
+    /// Insert `observations` in one transaction: all of them are stored or none.
+    /// Needs a replica set, as every MongoDB transaction does.
+    pub async fn create_all(&self, observations: Vec<Observation>) -> Result<Vec<Observation>, String> {
+        let mut session = self.collection.client().start_session(None).await.map_err(|e| e.to_string())?;
+        session.start_transaction(None).await.map_err(|e| e.to_string())?;
+
+        let inserted = match self.collection.insert_many_with_session(observations.clone(), None, &mut session).await {
+            Ok(inserted) => inserted,
+            Err(e) => {
+                let _ = session.abort_transaction().await;
+                return Err(e.to_string());
+            }
+        };
+        session.commit_transaction().await.map_err(|e| e.to_string())?;
+
+        Ok(observations
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut observation)| {
+                observation.id = inserted.inserted_ids.get(&index).and_then(|id| id.as_object_id());
+                observation
+            })
+            .collect())
+    }
+
+    pub async fn find_all_paginated(&self, pagination: PaginationParams, id_pasien: Option<&str>) -> Result<(Vec<Observation>, u64), String> {
+        let filter = id_pasien.map(|id_pasien| doc! { "id_pasien": id_pasien });
         let total = self.collection
-            .count_documents(None, None)
+            .count_documents(filter.clone(), None)
             .await
             .map_err(|e| e.to_string())?;
 
@@ -42,7 +204,7 @@ impl ObservationRepository {
             .build();
 
         let cursor = self.collection
-            .find(None, options)
+            .find(filter, options)
             .await
             .map_err(|e| e.to_string())?;
 
@@ -51,6 +213,32 @@ impl ObservationRepository {
         Ok((observations, total))
     }
 
+    /// `find_all_paginated` projected to `ObservationSummaryRow`
+    pub async fn find_summaries_paginated(&self, pagination: PaginationParams, id_pasien: Option<&str>) -> Result<(Vec<ObservationSummaryRow>, u64), String> {
+        let filter = id_pasien.map(|id_pasien| doc! { "id_pasien": id_pasien });
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = mongodb::options::FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .sort(doc! { "created_at": -1 })
+            .projection(summary_projection())
+            .build();
+
+        let cursor = self.collection
+            .clone_with_type::<ObservationSummaryRow>()
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let rows: Vec<ObservationSummaryRow> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((rows, total))
+    }
+
     pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Observation>, String> {
         self.collection
             .find_one(doc! { "_id": id }, None)
@@ -62,7 +250,10 @@ impl ObservationRepository {
         let filter = doc! { "_id": id };
         
         // Convert observation to BSON document for update
-        let mut obs_doc = mongodb::bson::to_document(&observation).map_err(|e| e.to_string())?;
+        let mut obs_doc = mongodb::bson::to_raw_document_buf(&observation)
+            .map_err(|e| e.to_string())?
+            .to_document()
+            .map_err(|e| e.to_string())?;
         obs_doc.remove("_id"); // Don't update the ID
 
         let update = doc! {
@@ -77,6 +268,262 @@ impl ObservationRepository {
         Ok(observation)
     }
 
+    /// Repoint every observation of one patient to another within `session`'s transaction,
+    /// returning the number of updated documents
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(
+                doc! { "id_pasien": from },
+                doc! { "$set": { "id_pasien": to, "pasien.id": to } },
+                None,
+                session,
+            )
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+
+    /// The latest `window` observations of one coding for a patient, oldest first, with
+    /// rolling mean/min/max over the preceding `rolling` documents.
+    pub async fn find_trend(
+        &self,
+        patient_id: &str,
+        coding_code: &str,
+        from: Option<i64>,
+        to: Option<i64>,
+        window: i64,
+        rolling: i64,
+    ) -> Result<Vec<ObservationTrendRow>, String> {
+        let mut filter = doc! { "id_pasien": patient_id, "coding.code": coding_code };
+        let mut time_range = Document::new();
+        if let Some(from) = from { time_range.insert("$gte", from); }
+        if let Some(to) = to { time_range.insert("$lte", to); }
+        if !time_range.is_empty() {
+            filter.insert("time", time_range);
+        }
+
+        let rolling_window = doc! { "documents": [-(rolling - 1), 0] };
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$sort": { "time": -1 } },
+            doc! { "$limit": window },
+            doc! { "$setWindowFields": {
+                "sortBy": { "time": 1 },
+                "output": {
+                    "rolling_mean": { "$avg": "$value", "window": rolling_window.clone() },
+                    "rolling_min": { "$min": "$value", "window": rolling_window.clone() },
+                    "rolling_max": { "$max": "$value", "window": rolling_window },
+                },
+            }},
+            doc! { "$project": {
+                "_id": 0, "time": 1, "value": 1, "rolling_mean": 1, "rolling_min": 1, "rolling_max": 1,
+                "base_line": 1, "coding": 1, "unit": 1,
+            }},
+        ];
+
+        let cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// All observations of a patient having one of `coding_codes`, oldest first.
+    pub async fn find_by_patient_and_codings(&self, patient_id: &str, coding_codes: &[&str]) -> Result<Vec<Observation>, String> {
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "time": 1 })
+            .build();
+
+        let cursor = self.collection
+            .find(doc! { "id_pasien": patient_id, "coding.code": { "$in": coding_codes } }, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Latest measured (non-derived) observation of a patient with one of `coding_codes` and `from <= time <= to`.
+    pub async fn find_latest_measured(&self, patient_id: &str, coding_codes: &[&str], from: i64, to: i64) -> Result<Option<Observation>, String> {
+        let options = mongodb::options::FindOneOptions::builder()
+            .sort(doc! { "time": -1 })
+            .build();
+
+        self.collection
+            .find_one(
+                doc! {
+                    "id_pasien": patient_id,
+                    "coding.code": { "$in": coding_codes },
+                    "time": { "$gte": from, "$lte": to },
+                    "derived": { "$ne": true },
+                },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The latest observation of each of `coding_codes` for each of `patient_ids`
+    pub async fn find_latest_per_coding(&self, patient_ids: &[String], coding_codes: &[&str]) -> Result<Vec<Observation>, String> {
+        if patient_ids.is_empty() || coding_codes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pipeline = vec![
+            doc! { "$match": { "id_pasien": { "$in": patient_ids }, "coding.code": { "$in": coding_codes } } },
+            doc! { "$sort": { "time": -1, "_id": -1 } },
+            doc! { "$group": { "_id": { "patient": "$id_pasien", "code": "$coding.code" }, "latest": { "$first": "$$ROOT" } } },
+            doc! { "$replaceRoot": { "newRoot": "$latest" } },
+        ];
+
+        let cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Derived observation of a patient for one output coding at an exact time.
+    pub async fn find_derived(&self, patient_id: &str, coding_code: &str, time: i64) -> Result<Option<Observation>, String> {
+        self.collection
+            .find_one(doc! { "id_pasien": patient_id, "coding.code": coding_code, "time": time, "derived": true }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Observation counts, distinct patients and distinct active hours per `unit` ("day" or
+    /// "week") for observations matching `filter`, plus overall totals.
+    /// `time` may be epoch seconds or milliseconds; both are normalized to a date.
+    pub async fn activity_report(
+        &self,
+        filter: Document,
+        unit: &str,
+        timezone: &str,
+        from: Option<mongodb::bson::DateTime>,
+        to: Option<mongodb::bson::DateTime>,
+    ) -> Result<(Vec<Document>, Option<Document>), String> {
+        let mut range = Document::new();
+        if let Some(from) = from { range.insert("$gte", from); }
+        if let Some(to) = to { range.insert("$lt", to); }
+
+        let mut pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$addFields": { "ts": observed_at() } },
+        ];
+        if !range.is_empty() {
+            pipeline.push(doc! { "$match": { "ts": range } });
+        }
+
+        let hour = doc! { "$dateTrunc": { "date": "$ts", "unit": "hour", "timezone": timezone } };
+        pipeline.push(doc! { "$facet": {
+            "series": [
+                { "$group": {
+                    "_id": { "$dateTrunc": { "date": "$ts", "unit": unit, "timezone": timezone, "startOfWeek": "monday" } },
+                    "observations": { "$sum": 1 },
+                    "patients": { "$addToSet": "$id_pasien" },
+                    "hours": { "$addToSet": hour.clone() },
+                }},
+                { "$project": {
+                    "_id": 0,
+                    "period_start": "$_id",
+                    "observations": 1,
+                    "patients_served": { "$size": "$patients" },
+                    "active_hours": { "$size": "$hours" },
+                }},
+                { "$sort": { "period_start": 1 } },
+            ],
+            "totals": [
+                { "$group": {
+                    "_id": null,
+                    "observations": { "$sum": 1 },
+                    "patients": { "$addToSet": "$id_pasien" },
+                    "hours": { "$addToSet": hour },
+                }},
+                { "$project": {
+                    "_id": 0,
+                    "observations": 1,
+                    "patients_served": { "$size": "$patients" },
+                    "active_hours": { "$size": "$hours" },
+                }},
+            ],
+        }});
+
+        let mut cursor = self.collection
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(result) = cursor.try_next().await.map_err(|e| e.to_string())? else {
+            return Ok((Vec::new(), None));
+        };
+
+        let series = result.get_array("series").map_err(|e| e.to_string())?
+            .iter()
+            .filter_map(|b| b.as_document().cloned())
+            .collect();
+        let totals = result.get_array("totals").ok()
+            .and_then(|t| t.first())
+            .and_then(|t| t.as_document().cloned());
+
+        Ok((series, totals))
+    }
+
+    /// Monthly observations and the busiest operators on the kits with `kit_codes`, see `kit_activity_pipeline`
+    pub async fn kit_activity(
+        &self,
+        kit_codes: &[String],
+        from: mongodb::bson::DateTime,
+        timezone: &str,
+        top_operators: i64,
+    ) -> Result<(Vec<MonthlyActivityRow>, Vec<OperatorActivityRow>), String> {
+        let mut cursor = self.collection
+            .aggregate(kit_activity_pipeline(kit_codes, from, timezone, top_operators), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(result) = cursor.try_next().await.map_err(|e| e.to_string())? else {
+            return Ok((Vec::new(), Vec::new()));
+        };
+
+        let rows = |facet: &str| -> Result<Vec<Document>, String> {
+            Ok(result.get_array(facet).map_err(|e| e.to_string())?
+                .iter()
+                .filter_map(|row| row.as_document().cloned())
+                .collect())
+        };
+        let months = rows("months")?.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        let operators = rows("operators")?.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect::<Result<_, _>>()?;
+        Ok((months, operators))
+    }
+
+    /// Cursor over every matching observation, oldest first, read in batches so exports
+    /// never hold the whole result.
+    pub async fn export_cursor(
+        &self,
+        from: Option<i64>,
+        to: Option<i64>,
+        coding_codes: &[&str],
+        id_pasien: Option<&str>,
+    ) -> Result<Cursor<Observation>, String> {
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "time": 1, "_id": 1 })
+            .batch_size(500)
+            .build();
+
+        self.collection
+            .find(export_filter(from, to, coding_codes, id_pasien), options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
         let result = self
             .collection
@@ -87,3 +534,55 @@ impl ObservationRepository {
         Ok(result.deleted_count > 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_rows_read_the_projected_fields() {
+        let projection = summary_projection();
+        assert!(!projection.contains_key("pasien") && !projection.contains_key("atm_sehat"));
+
+        let id = ObjectId::new();
+        let patient = ObjectId::new();
+        let projected = doc! {
+            "_id": id, "id_pasien": patient.to_hex(), "value": 120.0, "time": 1_700_000_000_i64,
+            "unit": { "code": "mm[Hg]" }, "coding": { "code": "8480-6", "display": "Systolic" },
+            "interpretation": { "code": "N", "display": "Normal" },
+        };
+        let row: ObservationSummaryRow = mongodb::bson::from_document(projected).unwrap();
+        assert_eq!((row.id, row.id_pasien.id()), (id, patient));
+        assert_eq!(row.unit.display, "");
+        assert!(!row.derived);
+    }
+
+    #[test]
+    fn export_filter_only_includes_given_criteria() {
+        assert_eq!(export_filter(None, None, &[], None), Document::new());
+        assert_eq!(
+            export_filter(Some(10), Some(20), &["8480-6", "8462-4"], Some("abc")),
+            doc! {
+                "time": { "$gte": 10_i64, "$lte": 20_i64 },
+                "coding.code": { "$in": ["8480-6", "8462-4"] },
+                "id_pasien": "abc",
+            }
+        );
+        assert_eq!(export_filter(None, Some(20), &[], None), doc! { "time": { "$lte": 20_i64 } });
+    }
+
+    #[test]
+    fn kit_activity_buckets_months_and_ranks_operators() {
+        let codes = vec!["K1".to_string(), "K2".to_string()];
+        let from = mongodb::bson::DateTime::from_millis(1_767_225_600_000);
+        let pipeline = kit_activity_pipeline(&codes, from, "Asia/Jakarta", 10);
+        assert_eq!(pipeline[0], doc! { "$match": { "atm_sehat.code": { "$in": ["K1", "K2"] } } });
+        assert_eq!(pipeline[2], doc! { "$match": { "ts": { "$gte": from } } });
+
+        let facet = pipeline[3].get_document("$facet").unwrap();
+        let month = facet.get_array("months").unwrap()[0].as_document().unwrap().get_document("$group").unwrap().get_document("_id").unwrap();
+        assert_eq!(month.get_document("$dateToString").unwrap().get_str("timezone").unwrap(), "Asia/Jakarta");
+        let operators = facet.get_array("operators").unwrap();
+        assert_eq!(operators[2].as_document().unwrap(), &doc! { "$limit": 10_i64 });
+    }
+}
@@ -0,0 +1,77 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Organization;
+use futures_util::stream::TryStreamExt;
+
+pub struct OrganizationRepository {
+    collection: Collection<Organization>,
+}
+
+impl OrganizationRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Organization>("organizations");
+        Self { collection }
+    }
+
+    pub async fn create(&self, organization: Organization) -> Result<Organization, String> {
+        let result = self
+            .collection
+            .insert_one(organization.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = organization;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<Organization>, String> {
+        let options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Organization>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Distributor codes of the organizations among `ids` that represent one
+    pub async fn find_distributor_codes(&self, ids: &[String]) -> Result<Vec<String>, String> {
+        let ids: Vec<ObjectId> = ids.iter().filter_map(|id| ObjectId::parse_str(id).ok()).collect();
+        let codes = self.collection
+            .distinct("distributor_code", doc! { "_id": { "$in": ids }, "distributor_code": { "$type": "string" } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(codes.into_iter().filter_map(|code| code.as_str().map(str::to_string)).collect())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<Organization>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, session: &mut ClientSession, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one_with_session(doc! { "_id": id }, None, session)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
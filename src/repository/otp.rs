@@ -0,0 +1,70 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::{FindOneAndUpdateOptions, FindOneOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::OtpCode;
+
+pub struct OtpRepository {
+    collection: Collection<OtpCode>,
+}
+
+impl OtpRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<OtpCode>("otp_codes");
+        Self { collection }
+    }
+
+    pub async fn create(&self, code: OtpCode) -> Result<OtpCode, String> {
+        let result = self
+            .collection
+            .insert_one(code.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_code = code;
+        created_code.id = result.inserted_id.as_object_id();
+
+        Ok(created_code)
+    }
+
+    /// Most recently issued code; earlier codes for the subject are superseded by it
+    pub async fn find_latest(&self, purpose: &str, subject: &str) -> Result<Option<OtpCode>, String> {
+        let options = FindOneOptions::builder().sort(doc! { "created_at": -1 }).build();
+        self.collection
+            .find_one(doc! { "purpose": purpose, "subject": subject }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Codes issued for the subject at or after `since`, for the hourly limit
+    pub async fn count_since(&self, purpose: &str, subject: &str, since: DateTime) -> Result<u64, String> {
+        self.collection
+            .count_documents(doc! { "purpose": purpose, "subject": subject, "created_at": { "$gte": since } }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Count one guess against the code, unless `max_attempts` have already been made; the
+    /// check and the increment are one write so concurrent guesses cannot all pass the limit
+    pub async fn claim_attempt(&self, id: ObjectId, max_attempts: i32) -> Result<Option<OtpCode>, String> {
+        let options = FindOneAndUpdateOptions::builder().return_document(ReturnDocument::After).build();
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "attempts": { "$lt": max_attempts } },
+                doc! { "$inc": { "attempts": 1 } },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Mark the code used; `false` when another request consumed it first
+    pub async fn consume(&self, id: ObjectId, consumed_at: &str) -> Result<bool, String> {
+        self.collection
+            .update_one(doc! { "_id": id, "consumed_at": null }, doc! { "$set": { "consumed_at": consumed_at } }, None)
+            .await
+            .map(|result| result.modified_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::Duration;
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use serde::Serialize;
+use crate::models::OutboxEntry;
+use crate::pagination::PaginationParams;
+use crate::status::OutboxStatus;
+use futures_util::stream::TryStreamExt;
+
+fn later(delay: Duration) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + delay.as_millis() as i64)
+}
+
+pub struct OutboxRepository {
+    collection: Collection<OutboxEntry>,
+}
+
+impl OutboxRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<OutboxEntry>("outbox");
+        Self { collection }
+    }
+
+    /// A session with a transaction started, for writes that commit together with their
+    /// outbox entries. Needs a replica set.
+    pub async fn begin(&self) -> Result<ClientSession, String> {
+        let mut session = self.collection.client().start_session(None).await.map_err(|e| e.to_string())?;
+        session.start_transaction(None).await.map_err(|e| e.to_string())?;
+        Ok(session)
+    }
+
+    /// Insert `document` into `collection` and the entry `build` makes from its new id within
+    /// `session`'s transaction (see `begin`): both are stored or neither, along with any other
+    /// write the caller makes in it.
+    pub async fn insert_with<T: Serialize + Send + Sync>(
+        &self,
+        session: &mut ClientSession,
+        collection: &Collection<T>,
+        document: &T,
+        build: impl FnOnce(ObjectId) -> OutboxEntry,
+    ) -> Result<ObjectId, String> {
+        let id = collection.insert_one_with_session(document, None, session).await
+            .map_err(|e| e.to_string())?
+            .inserted_id
+            .as_object_id()
+            .ok_or("Inserted document has no ObjectId".to_string())?;
+        self.collection.insert_one_with_session(build(id), None, session).await.map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+
+    /// Insert an entry on its own, for messages about writes made elsewhere
+    pub async fn insert(&self, entry: OutboxEntry) -> Result<(), String> {
+        self.collection
+            .insert_one(entry, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<OutboxEntry>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<OutboxEntry>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .sort(doc! { "createdAt": -1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+
+    /// Take the oldest due pending entry, moving its next attempt `lease` ahead so other
+    /// relays skip it while it is delivered.
+    pub async fn claim_due(&self, lease: Duration) -> Result<Option<OutboxEntry>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! { "nextAttemptAt": 1 })
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(
+                doc! { "status": OutboxStatus::Pending, "nextAttemptAt": { "$lte": DateTime::now() } },
+                doc! { "$set": { "nextAttemptAt": later(lease) } },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn mark_published(&self, id: ObjectId, attempts: i32) -> Result<(), String> {
+        let set = doc! { "status": OutboxStatus::Published, "attempts": attempts, "publishedAt": DateTime::now() };
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$set": set }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Record a failed delivery; without a `retry_in` the entry is given up as `failed`.
+    pub async fn mark_attempt_failed(&self, id: ObjectId, attempts: i32, error: &str, retry_in: Option<Duration>) -> Result<(), String> {
+        let mut set = doc! { "attempts": attempts, "lastError": error };
+        match retry_in {
+            Some(delay) => set.insert("nextAttemptAt", later(delay)),
+            None => set.insert("status", OutboxStatus::Failed),
+        };
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$set": set }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Make an undelivered entry due now with a fresh attempt budget; `None` once published.
+    pub async fn retry(&self, id: ObjectId) -> Result<Option<OutboxEntry>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": { "$ne": OutboxStatus::Published } },
+                doc! { "$set": { "status": OutboxStatus::Pending, "attempts": 0, "nextAttemptAt": DateTime::now() } },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
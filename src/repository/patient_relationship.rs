@@ -0,0 +1,123 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::PatientRelationship;
+use futures_util::stream::TryStreamExt;
+
+pub struct PatientRelationshipRepository {
+    collection: Collection<PatientRelationship>,
+}
+
+impl PatientRelationshipRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<PatientRelationship>("patient_relationships");
+        Self { collection }
+    }
+
+    pub async fn create(&self, relationship: PatientRelationship) -> Result<PatientRelationship, String> {
+        let result = self
+            .collection
+            .insert_one(relationship.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = relationship;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    async fn find(&self, filter: Document) -> Result<Vec<PatientRelationship>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "createdAt": 1 })
+            .build();
+
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Links the patient is on either side of, oldest first
+    pub async fn find_by_patient(&self, patient_id: &str) -> Result<Vec<PatientRelationship>, String> {
+        self.find(doc! { "$or": [{ "patientId": patient_id }, { "relatedPatientId": patient_id }] }).await
+    }
+
+    /// Links recording guardians of each of `patient_ids`
+    pub async fn find_guardians(&self, patient_ids: &[String]) -> Result<Vec<PatientRelationship>, String> {
+        self.find(doc! { "patientId": { "$in": patient_ids }, "guardian": true }).await
+    }
+
+    /// The link between two patients, whichever side it was recorded from
+    pub async fn find_between(&self, a: &str, b: &str) -> Result<Option<PatientRelationship>, String> {
+        let filter = doc! {
+            "$or": [
+                { "patientId": a, "relatedPatientId": b },
+                { "patientId": b, "relatedPatientId": a },
+            ]
+        };
+        self.collection.find_one(filter, None).await.map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, patient_id: &str, id: ObjectId, set: Document) -> Result<Option<PatientRelationship>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "patientId": patient_id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, patient_id: &str, id: ObjectId) -> Result<bool, String> {
+        let result = self
+            .collection
+            .delete_one(doc! { "_id": id, "patientId": patient_id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Move a merged duplicate's links to the patient it was merged into, within `session`'s
+    /// transaction. A link between the two is dropped, as is the duplicate's link to anyone
+    /// the patient is already linked to, so each pair keeps one link.
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        let mut linked = Vec::new();
+        for (side, other) in [("patientId", "relatedPatientId"), ("relatedPatientId", "patientId")] {
+            let others = self.collection
+                .distinct_with_session(other, doc! { side: to }, None, session)
+                .await
+                .map_err(|e| e.to_string())?;
+            linked.extend(others);
+        }
+        linked.push(to.into());
+        self.collection
+            .delete_many_with_session(
+                doc! { "$or": [
+                    { "patientId": from, "relatedPatientId": { "$in": &linked } },
+                    { "relatedPatientId": from, "patientId": { "$in": &linked } },
+                ] },
+                None,
+                session,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut moved = 0;
+        for side in ["patientId", "relatedPatientId"] {
+            moved += self.collection
+                .update_many_with_session(doc! { side: from }, doc! { "$set": { side: to } }, None, session)
+                .await
+                .map(|result| result.modified_count)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(moved)
+    }
+}
@@ -0,0 +1,249 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Bson, Document},
+    options::FindOptions,
+    ClientSession, Collection, Database,
+};
+use serde::Deserialize;
+use crate::models::{OutboxEntry, Payment};
+use crate::repository::OutboxRepository;
+use crate::status::{PaymentMethod, RevenuePeriod};
+use futures_util::stream::TryStreamExt;
+
+/// Payments of one cashier by one method, see `PaymentRepository::settlement`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct SettlementRow {
+    pub cashier_id: String,
+    pub method: PaymentMethod,
+    pub count: i64,
+    pub amount: f64,
+}
+
+fn settlement_pipeline(date: &str, organization_id: Option<&str>) -> Vec<Document> {
+    let mut filter = doc! { "settlementDate": date };
+    if let Some(organization_id) = organization_id {
+        filter.insert("organizationId", organization_id);
+    }
+    vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": { "cashierId": "$cashierId", "method": "$method" },
+            "count": { "$sum": 1 },
+            "amount": { "$sum": "$amount" },
+        }},
+        doc! { "$project": { "_id": 0, "cashier_id": "$_id.cashierId", "method": "$_id.method", "count": 1, "amount": 1 } },
+        doc! { "$sort": { "cashier_id": 1, "method": 1 } },
+    ]
+}
+
+/// Payments settling on one day by one method, see `PaymentRepository::revenue`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct RevenueRow {
+    pub date: String,
+    pub method: PaymentMethod,
+    pub count: i64,
+    pub amount: f64,
+}
+
+fn revenue_pipeline(from: &str, to: &str, organization_id: Option<&str>) -> Vec<Document> {
+    let mut filter = doc! { "settlementDate": { "$gte": from, "$lte": to } };
+    if let Some(organization_id) = organization_id {
+        filter.insert("organizationId", organization_id);
+    }
+    vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": { "date": "$settlementDate", "method": "$method" },
+            "count": { "$sum": 1 },
+            "amount": { "$sum": "$amount" },
+        }},
+        doc! { "$project": { "_id": 0, "date": "$_id.date", "method": "$_id.method", "count": 1, "amount": 1 } },
+        doc! { "$sort": { "date": 1, "method": 1 } },
+    ]
+}
+
+/// Limits `filter` to `organizations`; `None` is every organization
+pub(crate) fn scope_to_organizations(filter: &mut Document, organizations: Option<&[String]>) {
+    if let Some(organizations) = organizations {
+        filter.insert("organizationId", doc! { "$in": organizations });
+    }
+}
+
+/// Payments settling in one period by one method, see `PaymentRepository::revenue_by_period`
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct RevenuePeriodRow {
+    /// The day, the Monday starting the week (`YYYY-MM-DD`) or the month (`YYYY-MM`)
+    pub period: String,
+    pub method: PaymentMethod,
+    pub count: i64,
+    pub amount: f64,
+}
+
+/// The period a payment's `settlementDate` falls in
+fn period_key(period: &RevenuePeriod) -> Bson {
+    match period {
+        RevenuePeriod::Month => Bson::Document(doc! { "$substrBytes": ["$settlementDate", 0, 7] }),
+        RevenuePeriod::Week => Bson::Document(doc! { "$dateToString": {
+            "format": "%Y-%m-%d",
+            "date": { "$dateTrunc": {
+                "date": { "$dateFromString": { "dateString": "$settlementDate", "format": "%Y-%m-%d" } },
+                "unit": "week",
+                "startOfWeek": "monday",
+            }},
+        }}),
+        _ => Bson::String("$settlementDate".to_string()),
+    }
+}
+
+fn revenue_by_period_pipeline(period: &RevenuePeriod, from: &str, to: &str, organizations: Option<&[String]>) -> Vec<Document> {
+    let mut filter = doc! { "settlementDate": { "$gte": from, "$lte": to } };
+    scope_to_organizations(&mut filter, organizations);
+    vec![
+        doc! { "$match": filter },
+        doc! { "$group": {
+            "_id": { "period": period_key(period), "method": "$method" },
+            "count": { "$sum": 1 },
+            "amount": { "$sum": "$amount" },
+        }},
+        doc! { "$project": { "_id": 0, "period": "$_id.period", "method": "$_id.method", "count": 1, "amount": 1 } },
+        doc! { "$sort": { "period": 1, "method": 1 } },
+    ]
+}
+
+pub struct PaymentRepository {
+    collection: Collection<Payment>,
+}
+
+impl PaymentRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Payment>("payments");
+        Self { collection }
+    }
+
+    pub async fn create(&self, payment: Payment) -> Result<Payment, String> {
+        let result = self
+            .collection
+            .insert_one(payment.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = payment;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    /// `create` within `session`'s transaction, with the outbox entry announcing the payment
+    pub async fn create_with_outbox(&self, session: &mut ClientSession, payment: Payment, outbox: &OutboxRepository, build: impl FnOnce(&Payment) -> OutboxEntry) -> Result<Payment, String> {
+        let mut created = payment;
+        let id = outbox.insert_with(session, &self.collection, &created, |id| {
+            let mut stored = created.clone();
+            stored.id = Some(id);
+            build(&stored)
+        }).await?;
+        created.id = Some(id);
+        Ok(created)
+    }
+
+    pub async fn find_by_invoice(&self, invoice_id: &str) -> Result<Vec<Payment>, String> {
+        self.find(doc! { "invoiceId": invoice_id }).await
+    }
+
+    pub async fn find_by_shift(&self, shift_id: &str) -> Result<Vec<Payment>, String> {
+        self.find(doc! { "shiftId": shift_id }).await
+    }
+
+    async fn find(&self, filter: Document) -> Result<Vec<Payment>, String> {
+        let options = FindOptions::builder().sort(doc! { "paidAt": 1 }).build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Count and sum of the payments settling on `date` per cashier and method
+    pub async fn settlement(&self, date: &str, organization_id: Option<&str>) -> Result<Vec<SettlementRow>, String> {
+        let cursor = self.collection
+            .aggregate(settlement_pipeline(date, organization_id), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Count and sum per settlement day and method, `from` to `to` inclusive
+    pub async fn revenue(&self, from: &str, to: &str, organization_id: Option<&str>) -> Result<Vec<RevenueRow>, String> {
+        let cursor = self.collection
+            .aggregate(revenue_pipeline(from, to, organization_id), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    /// Count and sum per period and method of the payments settling `from` to `to` inclusive
+    pub async fn revenue_by_period(&self, period: &RevenuePeriod, from: &str, to: &str, organizations: Option<&[String]>) -> Result<Vec<RevenuePeriodRow>, String> {
+        let cursor = self.collection
+            .aggregate(revenue_by_period_pipeline(period, from, to, organizations), None)
+            .await
+            .map_err(|e| e.to_string())?;
+        let docs: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        docs.into_iter()
+            .map(|d| mongodb::bson::from_document(d).map_err(|e| e.to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settlement_groups_by_cashier_and_method() {
+        let pipeline = settlement_pipeline("2026-03-10", Some("org1"));
+        assert_eq!(pipeline[0], doc! { "$match": { "settlementDate": "2026-03-10", "organizationId": "org1" } });
+        let group = pipeline[1].get_document("$group").unwrap();
+        assert_eq!(group.get_document("_id").unwrap(), &doc! { "cashierId": "$cashierId", "method": "$method" });
+
+        let row: SettlementRow = mongodb::bson::from_document(doc! { "cashier_id": "u1", "method": "qris", "count": 2_i64, "amount": 50_000.0 }).unwrap();
+        assert_eq!(row.method, PaymentMethod::Qris);
+    }
+
+    #[test]
+    fn revenue_groups_by_day_and_method() {
+        let pipeline = revenue_pipeline("2026-03-01", "2026-03-07", None);
+        assert_eq!(pipeline[0], doc! { "$match": { "settlementDate": { "$gte": "2026-03-01", "$lte": "2026-03-07" } } });
+        let group = pipeline[1].get_document("$group").unwrap();
+        assert_eq!(group.get_document("_id").unwrap(), &doc! { "date": "$settlementDate", "method": "$method" });
+    }
+
+    #[test]
+    fn revenue_periods_bucket_the_settlement_date() {
+        let organizations = vec!["org1".to_string()];
+        let pipeline = revenue_by_period_pipeline(&RevenuePeriod::Month, "2026-01-01", "2026-03-31", Some(&organizations));
+        assert_eq!(pipeline[0], doc! { "$match": { "settlementDate": { "$gte": "2026-01-01", "$lte": "2026-03-31" }, "organizationId": { "$in": ["org1"] } } });
+        let group = pipeline[1].get_document("$group").unwrap();
+        assert_eq!(group.get_document("_id").unwrap(), &doc! { "period": { "$substrBytes": ["$settlementDate", 0, 7] }, "method": "$method" });
+
+        let pipeline = revenue_by_period_pipeline(&RevenuePeriod::Week, "2026-03-01", "2026-03-31", None);
+        let key = pipeline[1].get_document("$group").unwrap().get_document("_id").unwrap().get_document("period").unwrap();
+        let truncated = key.get_document("$dateToString").unwrap().get_document("date").unwrap().get_document("$dateTrunc").unwrap();
+        assert_eq!(truncated.get_str("startOfWeek").unwrap(), "monday");
+    }
+}
@@ -0,0 +1,80 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::Permission;
+use futures_util::stream::TryStreamExt;
+
+pub struct PermissionRepository {
+    collection: Collection<Permission>,
+}
+
+impl PermissionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Permission>("permissions");
+        Self { collection }
+    }
+
+    pub async fn create(&self, permission: Permission) -> Result<Permission, String> {
+        let result = self
+            .collection
+            .insert_one(permission.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_permission = permission;
+        created_permission.id = result.inserted_id.as_object_id();
+
+        Ok(created_permission)
+    }
+
+    /// Every permission, ordered by resource then action
+    pub async fn find_all(&self) -> Result<Vec<Permission>, String> {
+        let options = FindOptions::builder().sort(doc! { "resource": 1, "action": 1 }).build();
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Permission>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_key(&self, resource: &str, action: &str) -> Result<Option<Permission>, String> {
+        self.collection
+            .find_one(doc! { "resource": resource, "action": action }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn count(&self) -> Result<u64, String> {
+        self.collection
+            .count_documents(None, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<Permission>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<Option<Permission>, String> {
+        self.collection
+            .find_one_and_delete(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
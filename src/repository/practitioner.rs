@@ -0,0 +1,116 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::FindOptions,
+    ClientSession, Collection, Database,
+};
+use futures_util::stream::TryStreamExt;
+use crate::models::Practitioner;
+use crate::pagination::PaginationParams;
+use crate::status::PractitionerType;
+
+/// Doctors and nurses in `practitioners`. Lookups take the type they are limited to, so
+/// the `/doctors` and `/nurses` views never see the other type.
+pub struct PractitionerRepository {
+    collection: Collection<Practitioner>,
+}
+
+/// `_id` filter, limited to one type when given
+fn by_id(id: ObjectId, practitioner_type: Option<&PractitionerType>) -> Document {
+    let mut filter = doc! { "_id": id };
+    if let Some(practitioner_type) = practitioner_type {
+        filter.insert("type", practitioner_type.as_str());
+    }
+    filter
+}
+
+impl PractitionerRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Practitioner>("practitioners");
+        Self { collection }
+    }
+
+    pub async fn find(&self, filter: Document) -> Result<Vec<Practitioner>, String> {
+        self.collection
+            .find(filter, None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))
+    }
+
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<Practitioner>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| format!("Failed to count documents: {}", e))?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+        let practitioners = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .try_collect()
+            .await
+            .map_err(|e| format!("Failed to collect results: {}", e))?;
+
+        Ok((practitioners, total))
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId, practitioner_type: Option<&PractitionerType>) -> Result<Option<Practitioner>, String> {
+        self.collection
+            .find_one(by_id(id, practitioner_type), None)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
+    }
+
+    pub async fn insert(&self, mut practitioner: Practitioner) -> Result<Practitioner, String> {
+        if practitioner.id.is_none() {
+            practitioner.id = Some(ObjectId::new());
+        }
+
+        self.collection
+            .insert_one(practitioner.clone(), None)
+            .await
+            .map_err(|e| format!("Insert failed: {}", e))?;
+
+        Ok(practitioner)
+    }
+
+    /// Replace the practitioner; its type is kept as stored
+    pub async fn replace(&self, id: ObjectId, practitioner: Practitioner) -> Result<Practitioner, String> {
+        self.collection
+            .replace_one(by_id(id, Some(&practitioner.practitioner_type)), practitioner.clone(), None)
+            .await
+            .map(|_| practitioner)
+            .map_err(|e| format!("Failed to update practitioner: {}", e))
+    }
+
+    pub async fn delete(&self, id: ObjectId, practitioner_type: Option<&PractitionerType>) -> Result<bool, String> {
+        self.collection
+            .delete_one(by_id(id, practitioner_type), None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| format!("Failed to delete practitioner: {}", e))
+    }
+
+    /// `delete` within `session`'s transaction, for deletes guarded by the delete policies
+    pub async fn delete_with_session(&self, session: &mut ClientSession, id: ObjectId, practitioner_type: Option<&PractitionerType>) -> Result<bool, String> {
+        self.collection
+            .delete_one_with_session(by_id(id, practitioner_type), None, session)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| format!("Failed to delete practitioner: {}", e))
+    }
+
+    pub async fn set_rating(&self, id: ObjectId, average: Option<f64>, count: i64) -> Result<(), String> {
+        self.collection
+            .update_one(doc! { "_id": id }, doc! { "$set": { "ratingAverage": average, "ratingCount": count } }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to update doctor rating: {}", e))
+    }
+}
@@ -0,0 +1,114 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::PriceList;
+use crate::pagination::PaginationParams;
+use crate::status::PriceListStatus;
+use futures_util::stream::TryStreamExt;
+
+pub struct PriceListRepository {
+    collection: Collection<PriceList>,
+}
+
+impl PriceListRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<PriceList>("price_lists");
+        Self { collection }
+    }
+
+    pub async fn create(&self, price_list: PriceList) -> Result<PriceList, String> {
+        let result = self
+            .collection
+            .insert_one(price_list.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = price_list;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<PriceList>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Highest version of the organization, 0 before its first list
+    pub async fn latest_version(&self, organization_id: &str) -> Result<i32, String> {
+        let options = FindOneOptions::builder().sort(doc! { "version": -1 }).build();
+        let latest = self.collection
+            .find_one(doc! { "organizationId": organization_id }, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(latest.map(|list| list.version).unwrap_or(0))
+    }
+
+    /// Newest version first
+    pub async fn find_paginated(&self, organization_id: Option<&str>, status: Option<&PriceListStatus>, pagination: &PaginationParams) -> Result<(Vec<PriceList>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(organization_id) = organization_id {
+            filter.insert("organizationId", organization_id);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "organizationId": 1, "version": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let lists: Vec<PriceList> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((lists, total))
+    }
+
+    /// Change a list that is still a draft; `None` when it is missing or published.
+    pub async fn update_draft(&self, id: ObjectId, set: Document) -> Result<Option<PriceList>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": PriceListStatus::Draft }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The published list of the organization in effect on `date` (`YYYY-MM-DD`): the
+    /// latest `effectiveFrom` not after it, and of those the highest version.
+    pub async fn find_active(&self, organization_id: &str, date: &str) -> Result<Option<PriceList>, String> {
+        let filter = doc! {
+            "organizationId": organization_id,
+            "status": PriceListStatus::Published,
+            "effectiveFrom": { "$lte": date },
+        };
+        let options = FindOneOptions::builder().sort(doc! { "effectiveFrom": -1, "version": -1 }).build();
+        self.collection
+            .find_one(filter, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Only drafts can be deleted
+    pub async fn delete_draft(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id, "status": PriceListStatus::Draft }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,182 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::{Program, ProgramEnrollment};
+use crate::pagination::PaginationParams;
+use crate::status::{ProgramEnrollmentStatus, ProgramStatus};
+use futures_util::stream::TryStreamExt;
+
+/// Chronic disease programs with their enrollments
+pub struct ProgramRepository {
+    programs: Collection<Program>,
+    enrollments: Collection<ProgramEnrollment>,
+}
+
+impl ProgramRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            programs: db.collection::<Program>("programs"),
+            enrollments: db.collection::<ProgramEnrollment>("program_enrollments"),
+        }
+    }
+
+    pub async fn create(&self, program: Program) -> Result<Program, String> {
+        let result = self.programs
+            .insert_one(program.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = program;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Program>, String> {
+        self.programs
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// By name
+    pub async fn find_paginated(&self, status: Option<&ProgramStatus>, pagination: &PaginationParams) -> Result<(Vec<Program>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.programs
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "name": 1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.programs
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let programs: Vec<Program> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((programs, total))
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<Program>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.programs
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        let result = self.programs
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count > 0)
+    }
+
+    /// Fails on a second active enrollment of the patient in the program
+    pub async fn create_enrollment(&self, enrollment: ProgramEnrollment) -> Result<ProgramEnrollment, String> {
+        let result = self.enrollments
+            .insert_one(enrollment.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = enrollment;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_enrollment(&self, program_id: &str, id: ObjectId) -> Result<Option<ProgramEnrollment>, String> {
+        self.enrollments
+            .find_one(doc! { "_id": id, "programId": program_id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_active_enrollment(&self, program_id: &str, patient_id: &str) -> Result<Option<ProgramEnrollment>, String> {
+        self.enrollments
+            .find_one(doc! { "programId": program_id, "patientId": patient_id, "status": ProgramEnrollmentStatus::Active }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The program's enrollments, newest first
+    pub async fn find_enrollments(&self, program_id: &str, status: Option<&ProgramEnrollmentStatus>) -> Result<Vec<ProgramEnrollment>, String> {
+        let mut filter = doc! { "programId": program_id };
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let options = FindOptions::builder().sort(doc! { "enrolledAt": -1 }).build();
+        self.enrollments
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn any_enrollment(&self, program_id: &str) -> Result<bool, String> {
+        self.enrollments
+            .find_one(doc! { "programId": program_id }, None)
+            .await
+            .map(|enrollment| enrollment.is_some())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Only while the enrollment is active, so a withdrawal is recorded once
+    pub async fn update_active_enrollment(&self, program_id: &str, id: ObjectId, set: Document) -> Result<Option<ProgramEnrollment>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.enrollments
+            .find_one_and_update(
+                doc! { "_id": id, "programId": program_id, "status": ProgramEnrollmentStatus::Active },
+                doc! { "$set": set },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Move a merged duplicate's enrollments to the patient it was merged into, within
+    /// `session`'s transaction. Where both were actively enrolled in a program, the
+    /// duplicate's enrollment is withdrawn first, as a patient is active in a program once.
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        let programs = self.enrollments
+            .distinct_with_session("programId", doc! { "patientId": to, "status": ProgramEnrollmentStatus::Active }, None, session)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.enrollments
+            .update_many_with_session(
+                doc! { "patientId": from, "programId": { "$in": programs }, "status": ProgramEnrollmentStatus::Active },
+                doc! { "$set": {
+                    "status": ProgramEnrollmentStatus::Withdrawn,
+                    "withdrawnAt": DateTime::now(),
+                    "withdrawalReason": format!("Merged into patient {}, who is enrolled", to),
+                } },
+                None,
+                session,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        self.enrollments
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
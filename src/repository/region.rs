@@ -3,16 +3,18 @@ use mongodb::{
     Collection, Database,
 };
 use crate::models::Region;
+use crate::repository::TombstoneRepository;
 use futures_util::stream::TryStreamExt;
 
 pub struct RegionRepository {
     collection: Collection<Region>,
+    tombstones: TombstoneRepository,
 }
 
 impl RegionRepository {
     pub fn new(db: Database) -> Self {
         let collection = db.collection::<Region>("regions");
-        Self { collection }
+        Self { collection, tombstones: TombstoneRepository::new(db) }
     }
 
     pub async fn create(&self, region: Region) -> Result<Region, String> {
@@ -93,6 +95,7 @@ impl RegionRepository {
                 "kecamatan": region.kecamatan.clone(),
                 "kelurahan": region.kelurahan.clone(),
                 "len": region.len.clone(),
+                "updated_at": region.updated_at,
             }
         };
 
@@ -111,6 +114,9 @@ impl RegionRepository {
             .await
             .map_err(|e| e.to_string())?;
 
+        if result.deleted_count > 0 {
+            self.tombstones.record("regions", id).await;
+        }
         Ok(result.deleted_count > 0)
     }
 }
@@ -0,0 +1,97 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::ReportSchedule;
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+pub struct ReportScheduleRepository {
+    collection: Collection<ReportSchedule>,
+}
+
+impl ReportScheduleRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<ReportSchedule>("report_schedules");
+        Self { collection }
+    }
+
+    pub async fn create(&self, schedule: ReportSchedule) -> Result<ReportSchedule, String> {
+        let result = self
+            .collection
+            .insert_one(schedule.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = schedule;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<ReportSchedule>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<ReportSchedule>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .sort(doc! { "name": 1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+
+    /// Active schedules whose next run is due at `now`
+    pub async fn find_due(&self, now: DateTime) -> Result<Vec<ReportSchedule>, String> {
+        let cursor = self.collection
+            .find(doc! { "active": true, "nextRunAt": { "$lte": now } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Move a due run to `next`; `false` when another instance already claimed it.
+    pub async fn claim_run(&self, id: ObjectId, due: DateTime, next: Option<DateTime>) -> Result<bool, String> {
+        let result = self.collection
+            .update_one(doc! { "_id": id, "nextRunAt": due }, doc! { "$set": { "nextRunAt": next } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.modified_count > 0)
+    }
+
+    pub async fn update(&self, id: ObjectId, set: Document) -> Result<Option<ReportSchedule>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let mut set = set;
+        set.insert("updatedAt", DateTime::now());
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,107 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{AggregateOptions, FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::ReportTemplate;
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+pub struct ReportTemplateRepository {
+    db: Database,
+    collection: Collection<ReportTemplate>,
+}
+
+impl ReportTemplateRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<ReportTemplate>("report_templates");
+        Self { db, collection }
+    }
+
+    pub async fn create(&self, template: ReportTemplate) -> Result<ReportTemplate, String> {
+        let result = self
+            .collection
+            .insert_one(template.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = template;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<ReportTemplate>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<ReportTemplate>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .sort(doc! { "name": 1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+
+    pub async fn update(&self, id: ObjectId, set: Document) -> Result<Option<ReportTemplate>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let mut set = set;
+        set.insert("updatedAt", DateTime::now());
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Run a paginated template pipeline, see `crate::report_templates::paginated`.
+    /// Returns the page of documents and the total before paging.
+    pub async fn run(&self, collection: &str, pipeline: Vec<Document>) -> Result<(Vec<Document>, u64), String> {
+        let options = AggregateOptions::builder()
+            .max_time(crate::report_templates::MAX_TIME)
+            .allow_disk_use(false)
+            .build();
+        let cursor = self.db.collection::<Document>(collection)
+            .aggregate(pipeline, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let facets: Vec<Document> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        let Some(facet) = facets.into_iter().next() else {
+            return Ok((Vec::new(), 0));
+        };
+        let rows = facet.get_array("rows")
+            .map(|rows| rows.iter().filter_map(|row| row.as_document().cloned()).collect())
+            .unwrap_or_default();
+        let total = facet.get_array("total").ok()
+            .and_then(|total| total.first())
+            .and_then(|count| count.as_document())
+            .and_then(|count| count.get("count"))
+            .and_then(|count| count.as_i64().or_else(|| count.as_i32().map(i64::from)))
+            .unwrap_or(0);
+        Ok((rows, total as u64))
+    }
+}
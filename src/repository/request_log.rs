@@ -0,0 +1,59 @@
+use mongodb::{
+    bson::doc,
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::RequestLog;
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+pub struct RequestLogRepository {
+    collection: Collection<RequestLog>,
+}
+
+impl RequestLogRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<RequestLog>(crate::request_log::COLLECTION);
+        Self { collection }
+    }
+
+    pub async fn create(&self, log: RequestLog) -> Result<(), String> {
+        self.collection
+            .insert_one(log, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Newest first, in the capped collection's insertion order
+    pub async fn find_paginated(&self, path_prefix: Option<&str>, status: Option<i32>, pagination: &PaginationParams) -> Result<(Vec<RequestLog>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(prefix) = path_prefix {
+            // Range on the prefix instead of a regex, so user input needs no escaping
+            filter.insert("path", doc! { "$gte": prefix, "$lt": format!("{}{}", prefix, char::MAX) });
+        }
+        if let Some(status) = status {
+            filter.insert("status", status);
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "$natural": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+        let logs = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((logs, total))
+    }
+}
@@ -0,0 +1,95 @@
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::{FindOneOptions, FindOptions},
+    Collection, Database,
+};
+use chrono::{DateTime, Utc};
+use futures_util::stream::TryStreamExt;
+use crate::models::RetentionRun;
+use crate::retention::{self, RUNS_COLLECTION};
+
+pub struct RetentionRepository {
+    db: Database,
+    runs: Collection<RetentionRun>,
+}
+
+impl RetentionRepository {
+    pub fn new(db: Database) -> Self {
+        let runs = db.collection::<RetentionRun>(RUNS_COLLECTION);
+        Self { db, runs }
+    }
+
+    /// Up to `limit` documents of `collection` whose `date_field` is before `cutoff`.
+    pub async fn find_expired(&self, collection: &str, date_field: &str, cutoff: DateTime<Utc>, limit: i64) -> Result<Vec<Document>, String> {
+        let options = FindOptions::builder()
+            .sort(doc! { "_id": 1 })
+            .limit(limit)
+            .build();
+
+        let cursor = self.db.collection::<Document>(collection)
+            .find(retention::expired_filter(date_field, cutoff), options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_by_ids(&self, collection: &str, ids: Vec<Bson>) -> Result<u64, String> {
+        self.db.collection::<Document>(collection)
+            .delete_many(doc! { "_id": { "$in": ids } }, None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_expired(&self, collection: &str, date_field: &str, cutoff: DateTime<Utc>) -> Result<u64, String> {
+        self.db.collection::<Document>(collection)
+            .delete_many(retention::expired_filter(date_field, cutoff), None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn insert_run(&self, run: RetentionRun) -> Result<RetentionRun, String> {
+        let result = self.runs
+            .insert_one(run.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_run = run;
+        created_run.id = result.inserted_id.as_object_id();
+
+        Ok(created_run)
+    }
+
+    pub async fn find_latest_run(&self) -> Result<Option<RetentionRun>, String> {
+        let options = FindOneOptions::builder()
+            .sort(doc! { "started_at": -1 })
+            .build();
+
+        self.runs
+            .find_one(None, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Documents moved per collection and action across all recorded runs.
+    pub async fn totals(&self) -> Result<Vec<Document>, String> {
+        let pipeline = vec![
+            doc! { "$unwind": "$results" },
+            doc! { "$group": {
+                "_id": { "collection": "$results.collection", "action": "$results.action" },
+                "documents": { "$sum": "$results.documents" },
+                "runs": { "$sum": 1 },
+            }},
+            doc! { "$sort": { "_id.collection": 1 } },
+        ];
+
+        let cursor = self.runs
+            .aggregate(pipeline, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+}
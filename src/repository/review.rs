@@ -0,0 +1,116 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Review;
+use crate::pagination::PaginationParams;
+use futures_util::stream::TryStreamExt;
+
+pub struct ReviewRepository {
+    collection: Collection<Review>,
+}
+
+impl ReviewRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Review>("reviews");
+        Self { collection }
+    }
+
+    pub async fn create(&self, review: Review) -> Result<Review, String> {
+        let result = self
+            .collection
+            .insert_one(review.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_review = review;
+        created_review.id = result.inserted_id.as_object_id();
+
+        Ok(created_review)
+    }
+
+    pub async fn find_by_appointment(&self, appointment_id: &str) -> Result<Option<Review>, String> {
+        self.collection
+            .find_one(doc! { "appointmentId": appointment_id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Newest first, optionally narrowed to a doctor and a status
+    pub async fn find_paginated(&self, doctor_id: Option<&str>, status: Option<&str>, pagination: &PaginationParams) -> Result<(Vec<Review>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(doctor_id) = doctor_id {
+            filter.insert("doctorId", doctor_id);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status);
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .build();
+        let reviews = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((reviews, total))
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<Review>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<Option<Review>, String> {
+        self.collection
+            .find_one_and_delete(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Average rating and count of a doctor's published reviews
+    pub async fn rating_summary(&self, doctor_id: &str) -> Result<(Option<f64>, i64), String> {
+        let pipeline = vec![
+            doc! { "$match": { "doctorId": doctor_id, "status": "published" } },
+            doc! { "$group": { "_id": null, "average": { "$avg": "$rating" }, "count": { "$sum": 1 } } },
+        ];
+
+        let mut cursor = self.collection.aggregate(pipeline, None).await.map_err(|e| e.to_string())?;
+        match cursor.try_next().await.map_err(|e| e.to_string())? {
+            Some(summary) => {
+                let average = summary.get_f64("average").ok().map(|avg| (avg * 100.0).round() / 100.0);
+                let count = summary.get_i32("count").map(i64::from).or_else(|_| summary.get_i64("count")).unwrap_or(0);
+                Ok((average, count))
+            }
+            None => Ok((None, 0)),
+        }
+    }
+
+    /// Credit a merged duplicate's reviews to the patient it was merged into, within
+    /// `session`'s transaction; ratings are unchanged
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -67,7 +67,7 @@ impl RoleRepository {
             None
         ).await?;
 
-        if let Some(_) = update_result {
+        if update_result.is_some() {
             // Return updated document (or just the local modified one)
             // find_one_and_update returns the OLD document by default unless ReturnDocument::After is used
             // Let's refetch or just modify the ID
@@ -0,0 +1,97 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId},
+    options::FindOptions,
+    Collection, Database,
+};
+use crate::models::RolePermission;
+use futures_util::stream::TryStreamExt;
+
+pub struct RolePermissionRepository {
+    collection: Collection<RolePermission>,
+}
+
+impl RolePermissionRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<RolePermission>("role_permissions");
+        Self { collection }
+    }
+
+    pub async fn create(&self, grant: RolePermission) -> Result<RolePermission, String> {
+        let result = self
+            .collection
+            .insert_one(grant.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_grant = grant;
+        created_grant.id = result.inserted_id.as_object_id();
+
+        Ok(created_grant)
+    }
+
+    pub async fn insert_many(&self, grants: Vec<RolePermission>) -> Result<(), String> {
+        if grants.is_empty() {
+            return Ok(());
+        }
+        self.collection
+            .insert_many(grants, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Grants of any of `role_codes`; all grants when `role_codes` is `None`
+    pub async fn find_for_roles(&self, role_codes: Option<&[String]>) -> Result<Vec<RolePermission>, String> {
+        let filter = role_codes.map(|codes| doc! { "role_code": { "$in": codes } });
+        let options = FindOptions::builder().sort(doc! { "role_code": 1, "resource": 1, "action": 1 }).build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_one(&self, role_code: &str, resource: &str, action: &str) -> Result<Option<RolePermission>, String> {
+        self.collection
+            .find_one(doc! { "role_code": role_code, "resource": resource, "action": action }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn count(&self) -> Result<u64, String> {
+        self.collection
+            .count_documents(None, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Point grants of a renamed permission at its new resource and action
+    pub async fn rename_permission(&self, from: (&str, &str), to: (&str, &str)) -> Result<u64, String> {
+        self.collection
+            .update_many(
+                doc! { "resource": from.0, "action": from.1 },
+                doc! { "$set": { "resource": to.0, "action": to.1 } },
+                None,
+            )
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete_for_permission(&self, resource: &str, action: &str) -> Result<u64, String> {
+        self.collection
+            .delete_many(doc! { "resource": resource, "action": action }, None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
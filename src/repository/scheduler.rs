@@ -0,0 +1,98 @@
+use futures_util::stream::TryStreamExt;
+use mongodb::{
+    bson::{doc, DateTime},
+    error::{ErrorKind, WriteFailure},
+    options::{FindOptions, IndexOptions, ReplaceOptions, UpdateOptions},
+    Collection, Database, IndexModel,
+};
+use std::time::Duration;
+use crate::models::{ScheduledJobRun, SchedulerLease};
+
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Leases of `crate::scheduler` and the last run of each job
+pub struct SchedulerRepository {
+    leases: Collection<SchedulerLease>,
+    runs: Collection<ScheduledJobRun>,
+}
+
+impl SchedulerRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            leases: db.collection::<SchedulerLease>("scheduler_leases"),
+            runs: db.collection::<ScheduledJobRun>("scheduled_job_runs"),
+        }
+    }
+
+    /// Remove lapsed leases once they expire
+    pub async fn ensure_ttl_index(&self) -> Result<(), String> {
+        let options = IndexOptions::builder()
+            .name("scheduler_leases_ttl".to_string())
+            .expire_after(Duration::ZERO)
+            .build();
+        let model = IndexModel::builder().keys(doc! { "expires_at": 1 }).options(options).build();
+        self.leases.create_index(model, None).await.map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    /// Renew the lease `name` for `holder`, or take it over when it is free or has lapsed.
+    /// `false` when another instance holds it.
+    pub async fn acquire(&self, name: &str, holder: &str, now: DateTime, expires_at: DateTime) -> Result<bool, String> {
+        let renewed = self.leases
+            .update_one(
+                doc! { "_id": name, "holder": holder },
+                doc! { "$set": { "renewed_at": now, "expires_at": expires_at } },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        if renewed.matched_count > 0 {
+            return Ok(true);
+        }
+
+        // A lapsed lease may linger until the TTL monitor removes it
+        let options = UpdateOptions::builder().upsert(true).build();
+        let taken = self.leases
+            .update_one(
+                doc! { "_id": name, "expires_at": { "$lte": now } },
+                doc! { "$set": { "holder": holder, "acquired_at": now, "renewed_at": now, "expires_at": expires_at } },
+                options,
+            )
+            .await;
+        match taken {
+            Ok(_) => Ok(true),
+            // The upsert collided with the lease of another instance
+            Err(e) => match *e.kind {
+                ErrorKind::Write(WriteFailure::WriteError(ref error)) if error.code == DUPLICATE_KEY => Ok(false),
+                _ => Err(e.to_string()),
+            },
+        }
+    }
+
+    pub async fn find_lease(&self, name: &str) -> Result<Option<SchedulerLease>, String> {
+        self.leases
+            .find_one(doc! { "_id": name }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn record_run(&self, run: &ScheduledJobRun) -> Result<(), String> {
+        let options = ReplaceOptions::builder().upsert(true).build();
+        self.runs
+            .replace_one(doc! { "_id": &run.job }, run, options)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// The last run of every job that ran, by job name
+    pub async fn find_runs(&self) -> Result<Vec<ScheduledJobRun>, String> {
+        let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+        self.runs
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,46 @@
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+    Database,
+};
+use serde::de::DeserializeOwned;
+use futures_util::stream::TryStreamExt;
+
+/// Full-text lookups over any collection that has a text index.
+pub struct SearchRepository {
+    db: Database,
+}
+
+impl SearchRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Run a `$text` query against the documents of `collection` matching `filter`, returning
+    /// them with their relevance score, best matches first.
+    pub async fn text_search<T: DeserializeOwned>(&self, collection: &str, mut filter: Document, query: &str, limit: i64) -> Result<Vec<(f64, T)>, String> {
+        let collection = self.db.collection::<Document>(collection);
+        let options = FindOptions::builder()
+            .projection(doc! { "score": { "$meta": "textScore" } })
+            .sort(doc! { "score": { "$meta": "textScore" } })
+            .limit(limit)
+            .build();
+
+        filter.insert("$text", doc! { "$search": query });
+        let mut cursor = collection
+            .find(filter, options)
+            .await
+            .map_err(|e| format!("Search failed: {}", e))?;
+
+        let mut hits = Vec::new();
+        while let Some(mut document) = cursor.try_next().await.map_err(|e| format!("Search failed: {}", e))? {
+            let score = document.get_f64("score").unwrap_or(0.0);
+            document.remove("score");
+            let item = mongodb::bson::from_document::<T>(document)
+                .map_err(|e| format!("Failed to decode search hit: {}", e))?;
+            hits.push((score, item));
+        }
+
+        Ok(hits)
+    }
+}
@@ -0,0 +1,73 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::ServiceAccount;
+use futures_util::stream::TryStreamExt;
+
+pub struct ServiceAccountRepository {
+    collection: Collection<ServiceAccount>,
+}
+
+impl ServiceAccountRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<ServiceAccount>("service_accounts");
+        Self { collection }
+    }
+
+    pub async fn create(&self, account: ServiceAccount) -> Result<ServiceAccount, String> {
+        let result = self
+            .collection
+            .insert_one(account.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_account = account;
+        created_account.id = result.inserted_id.as_object_id();
+
+        Ok(created_account)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<ServiceAccount>, String> {
+        let options = FindOptions::builder().sort(doc! { "name": 1 }).build();
+        let cursor = self.collection
+            .find(None, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<ServiceAccount>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_client_id(&self, client_id: &str) -> Result<Option<ServiceAccount>, String> {
+        self.collection
+            .find_one(doc! { "client_id": client_id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<ServiceAccount>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one(doc! { "_id": id }, None)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,202 @@
+//! Slow query log shared by every repository.
+//!
+//! Repositories talk to MongoDB through the same clients, so the timing lives in a command
+//! monitor installed on them rather than in each repository. A command that takes longer
+//! than `SLOW_QUERY_MS` (default 500, `0` disables the log) is logged with its collection,
+//! operation and filter shape (values replaced by `?`, see `telemetry::sanitize`) and counted
+//! per collection and operation; `GET /admin/system-info` lists the counts, which point at
+//! missing indexes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::sync::Mutex;
+use std::time::Duration;
+use mongodb::bson::{Bson, Document};
+use mongodb::event::command::{CommandEventHandler, CommandFailedEvent, CommandStartedEvent, CommandSucceededEvent};
+use crate::dto::system::SlowQueryCount;
+
+pub const DEFAULT_SLOW_QUERY_MS: u64 = 500;
+
+/// Slow commands seen since startup, by collection and operation
+static COUNTS: Mutex<BTreeMap<(String, String), SlowQueryCount>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlowQueryConfig {
+    /// `0` disables the log
+    pub threshold_ms: u64,
+}
+
+impl Default for SlowQueryConfig {
+    fn default() -> Self {
+        Self { threshold_ms: DEFAULT_SLOW_QUERY_MS }
+    }
+}
+
+impl SlowQueryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            threshold_ms: env::var("SLOW_QUERY_MS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_SLOW_QUERY_MS),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.threshold_ms > 0
+    }
+}
+
+/// The command fields that select documents, by operation
+fn filter_of(operation: &str, command: &Document) -> Option<Bson> {
+    let statements = |field: &str| command.get_array(field).ok().map(|statements| {
+        Bson::Array(statements.iter()
+            .filter_map(|s| s.as_document().and_then(|s| s.get("q")).cloned())
+            .collect())
+    });
+    match operation {
+        "find" | "count" | "distinct" => command.get("filter").or_else(|| command.get("query")).cloned(),
+        "findAndModify" => command.get("query").cloned(),
+        "update" => statements("updates"),
+        "delete" => statements("deletes"),
+        // Every stage is kept: `$lookup` and `$sort` are as likely to need an index as `$match`
+        "aggregate" => command.get("pipeline").cloned(),
+        _ => None,
+    }
+}
+
+/// Filter shape of a command, e.g. `{ "nik": "?" }`
+pub fn filter_shape(operation: &str, command: &Document) -> String {
+    let shape = filter_of(operation, command).map(|filter| {
+        let wrapped = crate::telemetry::sanitize(&mongodb::bson::doc! { "filter": filter });
+        wrapped.get("filter").cloned().unwrap_or(Bson::Null)
+    });
+    match shape {
+        Some(Bson::Document(document)) if document.is_empty() => "{}".to_string(),
+        Some(shape) => shape.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// Slow commands counted since startup
+pub fn counts() -> Vec<SlowQueryCount> {
+    COUNTS.lock().map(|counts| counts.values().cloned().collect()).unwrap_or_default()
+}
+
+fn record(collection: &str, operation: &str, duration: Duration) {
+    let Ok(mut counts) = COUNTS.lock() else {
+        return;
+    };
+    let millis = duration.as_millis() as u64;
+    let entry = counts.entry((collection.to_string(), operation.to_string())).or_insert_with(|| SlowQueryCount {
+        collection: collection.to_string(),
+        operation: operation.to_string(),
+        count: 0,
+        max_ms: 0,
+    });
+    entry.count += 1;
+    entry.max_ms = entry.max_ms.max(millis);
+}
+
+struct Started {
+    collection: String,
+    operation: String,
+    shape: String,
+}
+
+/// Command monitor that logs and counts commands slower than the threshold
+pub struct SlowQueryLog {
+    threshold: Duration,
+    started: Mutex<HashMap<i32, Started>>,
+}
+
+impl SlowQueryLog {
+    pub fn new(config: &SlowQueryConfig) -> Self {
+        Self { threshold: Duration::from_millis(config.threshold_ms), started: Mutex::default() }
+    }
+
+    fn take(&self, request_id: i32) -> Option<Started> {
+        self.started.lock().ok()?.remove(&request_id)
+    }
+
+    fn finish(&self, request_id: i32, duration: Duration, outcome: &str) {
+        let Some(started) = self.take(request_id) else {
+            return;
+        };
+        if duration < self.threshold {
+            return;
+        }
+        record(&started.collection, &started.operation, duration);
+        tracing::warn!(
+            operation = %started.operation,
+            collection = %started.collection,
+            duration_ms = duration.as_millis() as u64,
+            outcome,
+            filter = %started.shape,
+            "Slow query",
+        );
+    }
+}
+
+impl CommandEventHandler for SlowQueryLog {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        // Handshakes, heartbeats and cursor batches of a logged query are not queries
+        let Ok(collection) = event.command.get_str(&event.command_name) else {
+            return;
+        };
+        let started = Started {
+            collection: collection.to_string(),
+            shape: filter_shape(&event.command_name, &event.command),
+            operation: event.command_name,
+        };
+        if let Ok(mut pending) = self.started.lock() {
+            pending.insert(event.request_id, started);
+        }
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.finish(event.request_id, event.duration, "ok");
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        self.finish(event.request_id, event.duration, "failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn shapes_filters_by_operation() {
+        let find = doc! { "find": "medical_records", "filter": { "nik": "3201010101010001", "deletedAt": null }, "limit": 1 };
+        assert_eq!(filter_shape("find", &find), r#"{ "nik": "?", "deletedAt": "?" }"#);
+
+        let update = doc! { "update": "appointments", "updates": [{ "q": { "_id": 1 }, "u": { "$set": { "status": "done" } } }] };
+        assert_eq!(filter_shape("update", &update), r#"[{ "_id": "?" }]"#);
+
+        assert_eq!(filter_shape("find", &doc! { "find": "doctors", "filter": {} }), "{}");
+        assert_eq!(filter_shape("insert", &doc! { "insert": "notes", "documents": [] }), "-");
+    }
+
+    #[test]
+    fn counts_only_commands_over_the_threshold() {
+        let log = SlowQueryLog::new(&SlowQueryConfig { threshold_ms: 100 });
+        for (request_id, collection) in [(1, "slow_query_test_fast"), (2, "slow_query_test_slow")] {
+            log.started.lock().unwrap().insert(request_id, Started {
+                collection: collection.to_string(),
+                operation: "find".to_string(),
+                shape: "{}".to_string(),
+            });
+        }
+        log.finish(1, Duration::from_millis(20), "ok");
+        log.finish(2, Duration::from_millis(250), "ok");
+
+        let counts = counts();
+        assert!(!counts.iter().any(|c| c.collection == "slow_query_test_fast"));
+        let slow = counts.iter().find(|c| c.collection == "slow_query_test_slow").unwrap();
+        assert_eq!((slow.count, slow.max_ms), (1, 250));
+        assert!(log.started.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOptions, UpdateOptions},
+    Collection, Database,
+};
+use crate::models::{Medicine, StockMovement, StockOpname};
+use crate::pagination::PaginationParams;
+use crate::status::StockOpnameStatus;
+use futures_util::stream::TryStreamExt;
+
+/// A counted quantity for one item, see `StockOpnameRepository::record_counts`
+pub struct CountUpdate {
+    pub medicine_id: String,
+    pub counted_qty: f64,
+    pub note: Option<String>,
+}
+
+pub struct StockOpnameRepository {
+    collection: Collection<StockOpname>,
+    medicines: Collection<Medicine>,
+    movements: Collection<StockMovement>,
+}
+
+impl StockOpnameRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            collection: db.collection::<StockOpname>("stock_opnames"),
+            medicines: db.collection::<Medicine>("medicines"),
+            movements: db.collection::<StockMovement>("stock_movements"),
+        }
+    }
+
+    pub async fn create(&self, opname: StockOpname) -> Result<StockOpname, String> {
+        let result = self
+            .collection
+            .insert_one(opname.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = opname;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<StockOpname>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// The opname still being counted, if any
+    pub async fn find_counting(&self) -> Result<Option<StockOpname>, String> {
+        self.collection
+            .find_one(doc! { "status": StockOpnameStatus::Counting }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Newest first
+    pub async fn find_paginated(&self, status: Option<&StockOpnameStatus>, pagination: &PaginationParams) -> Result<(Vec<StockOpname>, u64), String> {
+        let mut filter = doc! {};
+        if let Some(status) = status {
+            filter.insert("status", status.clone());
+        }
+
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .sort(doc! { "startedAt": -1 })
+            .skip(pagination.skip())
+            .limit(pagination.limit as i64)
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        let opnames: Vec<StockOpname> = cursor.try_collect().await.map_err(|e| e.to_string())?;
+
+        Ok((opnames, total))
+    }
+
+    /// Set the counted quantity of each item in one update, so concurrent counters of other
+    /// items are not overwritten. `false` when the opname is missing or no longer counting.
+    pub async fn record_counts(&self, id: ObjectId, counts: &[CountUpdate], counted_by: &str) -> Result<bool, String> {
+        let now = DateTime::now();
+        let mut set = Document::new();
+        let mut filters = Vec::with_capacity(counts.len());
+        for (i, count) in counts.iter().enumerate() {
+            let item = format!("items.$[i{}]", i);
+            set.insert(format!("{}.countedQty", item), count.counted_qty);
+            set.insert(format!("{}.countedBy", item), counted_by);
+            set.insert(format!("{}.countedAt", item), now);
+            match &count.note {
+                Some(note) => set.insert(format!("{}.note", item), note.as_str()),
+                None => set.insert(format!("{}.note", item), mongodb::bson::Bson::Null),
+            };
+            filters.push(doc! { format!("i{}.medicineId", i): count.medicine_id.as_str() });
+        }
+
+        let options = UpdateOptions::builder().array_filters(filters).build();
+        let result = self.collection
+            .update_one(doc! { "_id": id, "status": StockOpnameStatus::Counting }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Cancel an opname that is still counting; `false` otherwise
+    pub async fn cancel(&self, id: ObjectId, cancelled_by: &str) -> Result<bool, String> {
+        let update = doc! { "$set": {
+            "status": StockOpnameStatus::Cancelled,
+            "closedBy": cancelled_by,
+            "closedAt": DateTime::now(),
+        } };
+        let result = self.collection
+            .update_one(doc! { "_id": id, "status": StockOpnameStatus::Counting }, update, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result.matched_count > 0)
+    }
+
+    /// Approve the opname, add each movement's `qty` to its batch and record the movements,
+    /// in one transaction: all of it happens or none. `false`, with nothing written, when the
+    /// opname is no longer counting. Needs a replica set.
+    pub async fn approve(&self, id: ObjectId, approved_by: &str, movements: Vec<StockMovement>) -> Result<bool, String> {
+        let mut session = self.collection.client().start_session(None).await.map_err(|e| e.to_string())?;
+        session.start_transaction(None).await.map_err(|e| e.to_string())?;
+
+        let written: Result<bool, String> = async {
+            let update = doc! { "$set": {
+                "status": StockOpnameStatus::Approved,
+                "closedBy": approved_by,
+                "closedAt": DateTime::now(),
+            } };
+            let approved = self.collection
+                .update_one_with_session(doc! { "_id": id, "status": StockOpnameStatus::Counting }, update, None, &mut session)
+                .await
+                .map_err(|e| e.to_string())?;
+            if approved.matched_count == 0 {
+                return Ok(false);
+            }
+
+            for movement in &movements {
+                let medicine_id = ObjectId::parse_str(&movement.medicine_id).map_err(|e| e.to_string())?;
+                self.medicines
+                    .update_one_with_session(doc! { "_id": medicine_id }, doc! { "$inc": { "qty": movement.qty } }, None, &mut session)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            if !movements.is_empty() {
+                self.movements
+                    .insert_many_with_session(movements.clone(), None, &mut session)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(true)
+        }.await;
+
+        match written {
+            Ok(true) => {
+                session.commit_transaction().await.map_err(|e| e.to_string())?;
+                Ok(true)
+            }
+            Ok(false) => {
+                let _ = session.abort_transaction().await;
+                Ok(false)
+            }
+            Err(e) => {
+                let _ = session.abort_transaction().await;
+                Err(e)
+            }
+        }
+    }
+}
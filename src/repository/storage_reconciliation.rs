@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::FindOneOptions,
+    Collection, Database,
+};
+use crate::models::StorageReconciliation;
+use crate::storage_gc::RUNS_COLLECTION;
+
+pub struct StorageReconciliationRepository {
+    db: Database,
+    runs: Collection<StorageReconciliation>,
+}
+
+impl StorageReconciliationRepository {
+    pub fn new(db: Database) -> Self {
+        let runs = db.collection::<StorageReconciliation>(RUNS_COLLECTION);
+        Self { db, runs }
+    }
+
+    /// Storage keys of every file, soft-deleted ones included, and of every kept version
+    pub async fn referenced_paths(&self) -> Result<HashSet<String>, String> {
+        let mut paths = HashSet::new();
+        for collection in ["files", "file_versions"] {
+            let values = self.db.collection::<Document>(collection)
+                .distinct("path", None, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            paths.extend(values.into_iter().filter_map(|value| match value {
+                Bson::String(path) => Some(path),
+                _ => None,
+            }));
+        }
+        Ok(paths)
+    }
+
+    pub async fn insert_run(&self, run: StorageReconciliation) -> Result<StorageReconciliation, String> {
+        let result = self.runs
+            .insert_one(run.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_run = run;
+        created_run.id = result.inserted_id.as_object_id();
+
+        Ok(created_run)
+    }
+
+    pub async fn find_latest_run(&self) -> Result<Option<StorageReconciliation>, String> {
+        let options = FindOneOptions::builder()
+            .sort(doc! { "started_at": -1 })
+            .build();
+
+        self.runs
+            .find_one(None, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
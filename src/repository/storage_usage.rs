@@ -0,0 +1,54 @@
+use mongodb::{
+    bson::{doc, DateTime, Document},
+    options::{FindOptions, UpdateOptions},
+    Collection, Database,
+};
+use crate::models::StorageUsage;
+use futures_util::stream::TryStreamExt;
+
+pub struct StorageUsageRepository {
+    collection: Collection<StorageUsage>,
+}
+
+impl StorageUsageRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<StorageUsage>("storage_usage");
+        Self { collection }
+    }
+
+    /// Bytes stored by the owner; `0` before their first upload
+    pub async fn bytes(&self, scope: &str, owner_id: &str) -> Result<u64, String> {
+        let usage = self.collection
+            .find_one(doc! { "scope": scope, "ownerId": owner_id }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(usage.map_or(0, |u| u.bytes.max(0) as u64))
+    }
+
+    /// Add `bytes` and `files` (negative to refund) to the owner's totals
+    pub async fn add(&self, scope: &str, owner_id: &str, bytes: i64, files: i64) -> Result<(), String> {
+        let options = UpdateOptions::builder().upsert(true).build();
+        self.collection
+            .update_one(
+                doc! { "scope": scope, "ownerId": owner_id },
+                doc! { "$inc": { "bytes": bytes, "files": files }, "$set": { "updated_at": DateTime::now() } },
+                options,
+            )
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Totals of every owner, largest first
+    pub async fn find_all(&self, scope: Option<&str>) -> Result<Vec<StorageUsage>, String> {
+        let filter = scope.map_or_else(Document::new, |scope| doc! { "scope": scope });
+        let options = FindOptions::builder().sort(doc! { "bytes": -1 }).build();
+        self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_collect()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
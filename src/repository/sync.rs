@@ -0,0 +1,32 @@
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+    Database,
+};
+use futures_util::stream::TryStreamExt;
+use crate::sync::Position;
+
+/// Change feeds of the reference collections, see `crate::sync`
+pub struct SyncRepository {
+    db: Database,
+}
+
+impl SyncRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Documents of `collection` written after `position`, by `updated_at` then `_id`
+    pub async fn changes(&self, collection: &str, position: Option<Position>, limit: i64) -> Result<Vec<Document>, String> {
+        let filter = position.map(|p| p.filter("updated_at")).unwrap_or_default();
+        let options = FindOptions::builder()
+            .sort(doc! { "updated_at": 1, "_id": 1 })
+            .limit(limit)
+            .build();
+        let cursor = self.db.collection::<Document>(collection)
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,78 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    Collection, Database,
+};
+use futures_util::stream::TryStreamExt;
+use crate::models::SyncConflict;
+use crate::pagination::PaginationParams;
+use crate::status::{ConflictResolution, SyncConflictStatus};
+
+pub struct SyncConflictRepository {
+    collection: Collection<SyncConflict>,
+}
+
+impl SyncConflictRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<SyncConflict>("sync_conflicts");
+        Self { collection }
+    }
+
+    pub async fn create(&self, conflict: SyncConflict) -> Result<SyncConflict, String> {
+        let result = self.collection
+            .insert_one(conflict.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = conflict;
+        created.id = result.inserted_id.as_object_id();
+        Ok(created)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<SyncConflict>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Newest first
+    pub async fn find_paginated(&self, filter: Document, pagination: &PaginationParams) -> Result<(Vec<SyncConflict>, u64), String> {
+        let total = self.collection
+            .count_documents(filter.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let options = FindOptions::builder()
+            .skip(pagination.skip())
+            .limit(pagination.limit() as i64)
+            .sort(doc! { "created_at": -1 })
+            .build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((cursor.try_collect().await.map_err(|e| e.to_string())?, total))
+    }
+
+    /// Marks an open conflict resolved; `None` when it was resolved meanwhile
+    pub async fn resolve(&self, id: ObjectId, resolution: &ConflictResolution, user_id: &str) -> Result<Option<SyncConflict>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(
+                doc! { "_id": id, "status": SyncConflictStatus::Open },
+                doc! { "$set": {
+                    "status": SyncConflictStatus::Resolved,
+                    "resolution": resolution.clone(),
+                    "resolved_by": user_id,
+                    "resolved_at": DateTime::now(),
+                } },
+                options,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
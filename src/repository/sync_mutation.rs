@@ -0,0 +1,28 @@
+use mongodb::{bson::doc, Collection, Database};
+use crate::models::SyncMutation;
+
+pub struct SyncMutationRepository {
+    collection: Collection<SyncMutation>,
+}
+
+impl SyncMutationRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<SyncMutation>("sync_mutations");
+        Self { collection }
+    }
+
+    pub async fn find_by_client_id(&self, client_id: &str) -> Result<Option<SyncMutation>, String> {
+        self.collection
+            .find_one(doc! { "client_id": client_id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn insert(&self, mutation: SyncMutation) -> Result<(), String> {
+        self.collection
+            .insert_one(mutation, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
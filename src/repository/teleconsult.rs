@@ -0,0 +1,51 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOneOptions, ReturnDocument},
+    Collection, Database,
+};
+use crate::models::TeleconsultSession;
+
+pub struct TeleconsultRepository {
+    collection: Collection<TeleconsultSession>,
+}
+
+impl TeleconsultRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<TeleconsultSession>("teleconsult_sessions");
+        Self { collection }
+    }
+
+    pub async fn create(&self, session: TeleconsultSession) -> Result<TeleconsultSession, String> {
+        let result = self
+            .collection
+            .insert_one(session.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_session = session;
+        created_session.id = result.inserted_id.as_object_id();
+
+        Ok(created_session)
+    }
+
+    /// Most recent session of an appointment
+    pub async fn find_latest_for_appointment(&self, appointment_id: &str) -> Result<Option<TeleconsultSession>, String> {
+        let options = FindOneOptions::builder().sort(doc! { "created_at": -1 }).build();
+        self.collection
+            .find_one(doc! { "appointmentId": appointment_id }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Update a session only while it is still in `expected_status`
+    pub async fn transition(&self, id: ObjectId, expected_status: &str, set: Document) -> Result<Option<TeleconsultSession>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": expected_status }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
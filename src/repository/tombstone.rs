@@ -0,0 +1,44 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, DateTime},
+    options::FindOptions,
+    Collection, Database,
+};
+use futures_util::stream::TryStreamExt;
+use crate::models::Tombstone;
+use crate::sync::Position;
+
+pub struct TombstoneRepository {
+    collection: Collection<Tombstone>,
+}
+
+impl TombstoneRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Tombstone>("tombstones");
+        Self { collection }
+    }
+
+    /// Remembers that `id` was deleted from `collection`; failures are logged, the delete stands
+    pub async fn record(&self, collection: &str, id: ObjectId) {
+        let tombstone = Tombstone {
+            id: None,
+            collection: collection.to_string(),
+            entity_id: id.to_hex(),
+            deleted_at: DateTime::now(),
+        };
+        if let Err(e) = self.collection.insert_one(tombstone, None).await {
+            tracing::error!(collection, id = %id.to_hex(), error = %e, "Recording a deletion failed");
+        }
+    }
+
+    /// Deletions from `collections` after `position`, oldest first
+    pub async fn find_after(&self, collections: &[&str], position: Option<Position>, limit: i64) -> Result<Vec<Tombstone>, String> {
+        let mut filter = position.map(|p| p.filter("deleted_at")).unwrap_or_default();
+        filter.insert("collection", doc! { "$in": collections });
+        let options = FindOptions::builder()
+            .sort(doc! { "deleted_at": 1, "_id": 1 })
+            .limit(limit)
+            .build();
+        let cursor = self.collection.find(filter, options).await.map_err(|e| e.to_string())?;
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+}
@@ -1,4 +1,4 @@
-use mongodb::{bson::doc, Database, options::FindOptions};
+use mongodb::{bson::{doc, DateTime}, Database, options::FindOptions};
 use futures_util::stream::TryStreamExt;
 use crate::models::User;
 use crate::pagination::PaginationParams;
@@ -47,6 +47,19 @@ impl UserRepository {
             .map_err(|e| format!("Database error: {}", e))
     }
 
+    pub async fn find_by_phone(&self, phone: &str) -> Result<Vec<User>, String> {
+        let collection = self.db.collection::<User>("users");
+        match collection.find(doc! { "phone": phone }, None).await {
+            Ok(cursor) => {
+                cursor
+                    .try_collect::<Vec<User>>()
+                    .await
+                    .map_err(|e| format!("Failed to collect results: {}", e))
+            }
+            Err(e) => Err(format!("Database error: {}", e)),
+        }
+    }
+
     pub async fn find_by_id(&self, id: mongodb::bson::oid::ObjectId) -> Result<Option<User>, String> {
         let collection = self.db.collection::<User>("users");
         collection
@@ -96,7 +109,7 @@ impl UserRepository {
             "$set": {
                 "resetToken": token,
                 "resetTokenExpiry": expiry,
-                "updatedAt": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+                "updatedAt": DateTime::now()
             }
         };
 
@@ -116,7 +129,7 @@ impl UserRepository {
                 "password": password_hash,
                 "resetToken": null,
                 "resetTokenExpiry": null,
-                "updatedAt": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+                "updatedAt": DateTime::now()
             }
         };
 
@@ -134,7 +147,25 @@ impl UserRepository {
         let update = doc! {
             "$set": {
                 "refreshToken": refresh_token,
-                "updatedAt": chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+                "updatedAt": DateTime::now()
+            }
+        };
+
+        let result = collection
+            .update_one(doc! { "_id": id }, update, None)
+            .await
+            .map_err(|e| format!("Update failed: {}", e))?;
+
+        Ok(result.modified_count > 0)
+    }
+
+    pub async fn set_email_verified(&self, id: mongodb::bson::oid::ObjectId, verified_at: &str) -> Result<bool, String> {
+        let collection = self.db.collection::<User>("users");
+
+        let update = doc! {
+            "$set": {
+                "emailVerifiedAt": verified_at,
+                "updatedAt": DateTime::now()
             }
         };
 
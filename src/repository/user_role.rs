@@ -1,10 +1,11 @@
 use mongodb::{
-    bson::{doc, oid::ObjectId},
+    bson::{doc, oid::ObjectId, DateTime},
     Collection, Database,
 };
 use futures_util::stream::TryStreamExt;
 use crate::models::UserRole;
 use crate::pagination::{PaginationParams, PaginatedResult};
+use crate::role_expiry;
 
 pub struct UserRoleRepository {
     collection: Collection<UserRole>,
@@ -49,6 +50,99 @@ impl UserRoleRepository {
         self.collection.find_one(doc! { "_id": id }, None).await
     }
 
+    /// Role codes of every active assignment held by the given user
+    pub async fn find_active_role_codes(&self, user_id: &str) -> Result<Vec<String>, mongodb::error::Error> {
+        let mut filter = role_expiry::current_filter(DateTime::now());
+        filter.insert("user._id", user_id);
+        let mut cursor = self.collection.find(filter, None).await?;
+        let mut codes = Vec::new();
+
+        while let Some(user_role) = cursor.try_next().await? {
+            if !codes.contains(&user_role.role.code) {
+                codes.push(user_role.role.code);
+            }
+        }
+
+        Ok(codes)
+    }
+
+    /// Organizations in which the user holds `role_code`, currently
+    pub async fn find_active_organization_ids_for_role(&self, user_id: &str, role_code: &str) -> Result<Vec<String>, mongodb::error::Error> {
+        let mut filter = role_expiry::current_filter(DateTime::now());
+        filter.insert("user._id", user_id);
+        filter.insert("role.code", role_code);
+        let ids = self.collection.distinct("organisasi._id", filter, None).await?;
+        Ok(ids.into_iter().filter_map(|id| id.as_str().map(str::to_string)).collect())
+    }
+
+    /// Organizations the user holds an active role in
+    pub async fn find_active_organization_ids(&self, user_id: &str) -> Result<Vec<String>, mongodb::error::Error> {
+        let mut filter = role_expiry::current_filter(DateTime::now());
+        filter.insert("user._id", user_id);
+        let mut cursor = self.collection.find(filter, None).await?;
+        let mut ids = Vec::new();
+
+        while let Some(user_role) = cursor.try_next().await? {
+            if !ids.contains(&user_role.organisasi.id) {
+                ids.push(user_role.organisasi.id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// The assignment of `role_code` to the user in the organization, if any
+    pub async fn find_assignment(&self, user_id: &str, role_code: &str, organization_id: &str) -> Result<Option<UserRole>, mongodb::error::Error> {
+        self.collection
+            .find_one(doc! { "user._id": user_id, "role.code": role_code, "organisasi._id": organization_id }, None)
+            .await
+    }
+
+    /// Assignments in the organization ordered by user name; only current ones unless `include_inactive`
+    pub async fn find_by_organization(&self, organization_id: &str, include_inactive: bool, now: DateTime) -> Result<Vec<UserRole>, mongodb::error::Error> {
+        let mut filter = if include_inactive { doc! {} } else { role_expiry::current_filter(now) };
+        filter.insert("organisasi._id", organization_id);
+        let options = mongodb::options::FindOptions::builder()
+            .sort(doc! { "user.nama.nama_depan": 1, "user.nama.nama_belakang": 1, "user._id": 1, "role.code": 1 })
+            .build();
+
+        self.collection.find(filter, options).await?.try_collect().await
+    }
+
+    /// Users currently holding the role `code`
+    pub async fn find_current_user_ids(&self, code: &str, now: DateTime) -> Result<Vec<String>, mongodb::error::Error> {
+        let mut filter = role_expiry::current_filter(now);
+        filter.insert("role.code", code);
+        let mut cursor = self.collection.find(filter, None).await?;
+        let mut ids = Vec::new();
+
+        while let Some(user_role) = cursor.try_next().await? {
+            if !ids.contains(&user_role.user.id) {
+                ids.push(user_role.user.id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Clear `is_active` on active assignments whose `valid_until` has passed, returning them
+    pub async fn deactivate_expired(&self, now: DateTime) -> Result<Vec<UserRole>, mongodb::error::Error> {
+        let filter = doc! { "is_active": true, "valid_until": { "$lte": now } };
+        let expired: Vec<UserRole> = self.collection.find(filter.clone(), None).await?.try_collect().await?;
+        if expired.is_empty() {
+            return Ok(expired);
+        }
+
+        let ids: Vec<ObjectId> = expired.iter().filter_map(|user_role| user_role.id).collect();
+        let mut matched = filter;
+        matched.insert("_id", doc! { "$in": ids });
+        self.collection
+            .update_many(matched, doc! { "$set": { "is_active": false, "updated_at": now } }, None)
+            .await?;
+
+        Ok(expired)
+    }
+
     pub async fn create(&self, user_role: UserRole) -> Result<UserRole, mongodb::error::Error> {
         let result = self.collection.insert_one(user_role.clone(), None).await?;
         let mut created_user_role = user_role;
@@ -57,7 +151,7 @@ impl UserRoleRepository {
     }
 
     pub async fn update(&self, id: ObjectId, user_role: UserRole) -> Result<Option<UserRole>, mongodb::error::Error> {
-        let mut doc = mongodb::bson::to_document(&user_role)?;
+        let mut doc = mongodb::bson::to_raw_document_buf(&user_role)?.to_document()?;
         doc.remove("_id");
 
         let update_result = self.collection.find_one_and_update(
@@ -66,7 +160,7 @@ impl UserRoleRepository {
             None
         ).await?;
 
-        if let Some(_) = update_result {
+        if update_result.is_some() {
             let mut updated_item = user_role;
             updated_item.id = Some(id);
             Ok(Some(updated_item))
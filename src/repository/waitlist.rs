@@ -0,0 +1,120 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::WaitlistEntry;
+use futures_util::stream::TryStreamExt;
+
+pub struct WaitlistRepository {
+    collection: Collection<WaitlistEntry>,
+}
+
+impl WaitlistRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<WaitlistEntry>("waitlist");
+        Self { collection }
+    }
+
+    pub async fn create(&self, entry: WaitlistEntry) -> Result<WaitlistEntry, String> {
+        let result = self
+            .collection
+            .insert_one(entry.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created_entry = entry;
+        created_entry.id = result.inserted_id.as_object_id();
+
+        Ok(created_entry)
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<WaitlistEntry>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Entries in arrival order, optionally narrowed to a doctor, a date and a status
+    pub async fn find(&self, doctor_id: Option<&str>, date: Option<&str>, status: Option<&str>) -> Result<Vec<WaitlistEntry>, String> {
+        let mut filter = doc! {};
+        if let Some(doctor_id) = doctor_id {
+            filter.insert("doctorId", doctor_id);
+        }
+        if let Some(date) = date {
+            filter.insert("date", date);
+        }
+        if let Some(status) = status {
+            filter.insert("status", status);
+        }
+
+        let options = FindOptions::builder().sort(doc! { "created_at": 1 }).build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_open_for_patient(&self, patient_id: &str, doctor_id: &str, date: &str) -> Result<Option<WaitlistEntry>, String> {
+        self.collection
+            .find_one(
+                doc! { "patientId": patient_id, "doctorId": doctor_id, "date": date, "status": { "$in": ["waiting", "held"] } },
+                None,
+            )
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Atomically move the longest-waiting entry that accepts `time` from `waiting` to `held`
+    pub async fn claim_next(&self, doctor_id: &str, date: &str, time: &str, set: Document) -> Result<Option<WaitlistEntry>, String> {
+        let filter = doc! {
+            "doctorId": doctor_id,
+            "date": date,
+            "status": "waiting",
+            "$or": [{ "time": null }, { "time": time }],
+        };
+        let options = FindOneAndUpdateOptions::builder()
+            .sort(doc! { "created_at": 1 })
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(filter, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Update an entry only while it is still in `expected_status`
+    pub async fn transition(&self, id: ObjectId, expected_status: &str, set: Document) -> Result<Option<WaitlistEntry>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        self.collection
+            .find_one_and_update(doc! { "_id": id, "status": expected_status }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn find_expired_holds(&self, now: &str) -> Result<Vec<WaitlistEntry>, String> {
+        let cursor = self.collection
+            .find(doc! { "status": "held", "holdExpiresAt": { "$lt": now } }, None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    /// Move a merged duplicate's waitlist entries to the patient it was merged into, within
+    /// `session`'s transaction, keeping their place in the queue
+    pub async fn reassign_patient(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<u64, String> {
+        self.collection
+            .update_many_with_session(doc! { "patientId": from }, doc! { "$set": { "patientId": to } }, None, session)
+            .await
+            .map(|result| result.modified_count)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,68 @@
+use mongodb::{
+    bson::{doc, oid::ObjectId, Document},
+    options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+    ClientSession, Collection, Database,
+};
+use crate::models::Ward;
+use futures_util::stream::TryStreamExt;
+
+pub struct WardRepository {
+    collection: Collection<Ward>,
+}
+
+impl WardRepository {
+    pub fn new(db: Database) -> Self {
+        let collection = db.collection::<Ward>("wards");
+        Self { collection }
+    }
+
+    pub async fn create(&self, ward: Ward) -> Result<Ward, String> {
+        let result = self
+            .collection
+            .insert_one(ward.clone(), None)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut created = ward;
+        created.id = result.inserted_id.as_object_id();
+
+        Ok(created)
+    }
+
+    /// By code, optionally of one organization
+    pub async fn find_all(&self, organization_id: Option<&str>) -> Result<Vec<Ward>, String> {
+        let filter = organization_id.map(|id| doc! { "organizationId": id });
+        let options = FindOptions::builder().sort(doc! { "code": 1 }).build();
+        let cursor = self.collection
+            .find(filter, options)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        cursor.try_collect().await.map_err(|e| e.to_string())
+    }
+
+    pub async fn find_by_id(&self, id: ObjectId) -> Result<Option<Ward>, String> {
+        self.collection
+            .find_one(doc! { "_id": id }, None)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn update_fields(&self, id: ObjectId, set: Document) -> Result<Option<Ward>, String> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        self.collection
+            .find_one_and_update(doc! { "_id": id }, doc! { "$set": set }, options)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn delete(&self, session: &mut ClientSession, id: ObjectId) -> Result<bool, String> {
+        self.collection
+            .delete_one_with_session(doc! { "_id": id }, None, session)
+            .await
+            .map(|result| result.deleted_count > 0)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,219 @@
+//! Opt-in capture of request and response bodies for troubleshooting client integrations.
+//!
+//! `REQUEST_LOG_ROUTES` lists the path prefixes to capture, comma separated; capture is off
+//! when it is empty. JSON and form bodies are stored in the capped `request_logs` collection
+//! after redaction: a field is masked when its name, lowercased without `_` and `-`, contains
+//! a rule, or equals it for rules written `=name`. `REQUEST_LOG_REDACT_FIELDS` adds rules to
+//! the defaults. Other body types and bodies that fail to parse are never stored verbatim.
+//! Stored bodies are cut at `REQUEST_LOG_MAX_BODY_BYTES` (default 16 KiB) and the collection
+//! holds `REQUEST_LOG_CAPACITY_MB` (default 64), the oldest entries rolling off.
+
+use std::env;
+use mongodb::{options::CreateCollectionOptions, Database};
+use serde_json::Value;
+use crate::config::path_has_prefix;
+use crate::mailer::parse_route_prefixes;
+
+pub const COLLECTION: &str = "request_logs";
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024;
+pub const DEFAULT_CAPACITY_MB: u64 = 64;
+pub const REDACTED: &str = "[REDACTED]";
+pub const DEFAULT_REDACT_RULES: &[&str] = &["password", "secret", "token", "nik", "otp", "authorization", "apikey", "=code"];
+/// Largest body buffered for capture, matching axum's default extractor limit
+pub const CAPTURE_BUFFER_LIMIT: usize = 2 * 1024 * 1024;
+const NAMESPACE_EXISTS: i32 = 48;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestLogConfig {
+    pub routes: Vec<String>,
+    pub redact_rules: Vec<String>,
+    pub max_body_bytes: usize,
+    pub capacity_mb: u64,
+}
+
+impl Default for RequestLogConfig {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            redact_rules: DEFAULT_REDACT_RULES.iter().map(|r| r.to_string()).collect(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            capacity_mb: DEFAULT_CAPACITY_MB,
+        }
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| *c != '_' && *c != '-').collect::<String>().to_lowercase()
+}
+
+/// Whether `field` matches one of the redaction `rules` described above
+pub fn matches_rules(field: &str, rules: &[String]) -> bool {
+    let field = normalize_name(field);
+    rules.iter().any(|rule| match rule.strip_prefix('=') {
+        Some(exact) => field == normalize_name(exact),
+        None => field.contains(&normalize_name(rule)),
+    })
+}
+
+impl RequestLogConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let mut redact_rules = defaults.redact_rules;
+        if let Ok(extra) = env::var("REQUEST_LOG_REDACT_FIELDS") {
+            redact_rules.extend(extra.split(',').map(str::trim).filter(|r| !r.is_empty()).map(str::to_string));
+        }
+
+        Self {
+            routes: env::var("REQUEST_LOG_ROUTES").map(|raw| parse_route_prefixes(&raw)).unwrap_or_default(),
+            redact_rules,
+            max_body_bytes: env::var("REQUEST_LOG_MAX_BODY_BYTES")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.max_body_bytes),
+            capacity_mb: env::var("REQUEST_LOG_CAPACITY_MB")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|mb| *mb > 0)
+                .unwrap_or(defaults.capacity_mb),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        !self.routes.is_empty()
+    }
+
+    pub fn captures(&self, path: &str) -> bool {
+        self.routes.iter().any(|prefix| path_has_prefix(path, prefix))
+    }
+
+    pub fn is_sensitive(&self, field: &str) -> bool {
+        matches_rules(field, &self.redact_rules)
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, field) in map.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *field = Value::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(field);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+
+    /// Redact `key=value` pairs of a query string or form body.
+    pub fn redact_form(&self, raw: &str) -> String {
+        let pairs = url::form_urlencoded::parse(raw.as_bytes()).map(|(key, value)| {
+            let value = if self.is_sensitive(&key) { REDACTED.into() } else { value };
+            (key, value)
+        });
+        url::form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish()
+    }
+
+    /// Sanitized, truncated form of a body, or a placeholder when it cannot be redacted.
+    pub fn sanitize_body(&self, content_type: Option<&str>, body: &[u8]) -> Option<String> {
+        if body.is_empty() {
+            return None;
+        }
+        let content_type = content_type.unwrap_or_default().to_lowercase();
+        let sanitized = if content_type.contains("json") {
+            match serde_json::from_slice::<Value>(body) {
+                Ok(mut value) => {
+                    self.redact_value(&mut value);
+                    value.to_string()
+                }
+                Err(_) => "[unparseable JSON omitted]".to_string(),
+            }
+        } else if content_type.starts_with("application/x-www-form-urlencoded") {
+            match std::str::from_utf8(body) {
+                Ok(raw) => self.redact_form(raw),
+                Err(_) => "[unparseable form omitted]".to_string(),
+            }
+        } else {
+            format!("[{} bytes of {} omitted]", body.len(), if content_type.is_empty() { "unknown content" } else { &content_type })
+        };
+        Some(truncate(sanitized, self.max_body_bytes))
+    }
+}
+
+/// Whether a body of this content type is buffered for capture. NDJSON exports are
+/// streamed and never buffered.
+pub fn is_capturable(content_type: Option<&str>) -> bool {
+    content_type.is_some_and(|ct| {
+        let ct = ct.to_lowercase();
+        (ct.contains("json") && !ct.contains("ndjson")) || ct.starts_with("application/x-www-form-urlencoded")
+    })
+}
+
+fn truncate(mut text: String, max: usize) -> String {
+    if text.len() <= max {
+        return text;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str("...[truncated]");
+    text
+}
+
+/// Create the capped collection when capture is enabled. An existing collection is kept as is.
+pub async fn ensure_collection(db: &Database, config: &RequestLogConfig) -> Result<(), String> {
+    if !config.enabled() {
+        return Ok(());
+    }
+    let options = CreateCollectionOptions::builder()
+        .capped(true)
+        .size(config.capacity_mb * 1024 * 1024)
+        .build();
+    match db.create_collection(COLLECTION, options).await {
+        Ok(()) => Ok(()),
+        Err(e) => match *e.kind {
+            mongodb::error::ErrorKind::Command(ref command) if command.code == NAMESPACE_EXISTS => Ok(()),
+            _ => Err(e.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_sensitive_fields_at_any_depth() {
+        let config = RequestLogConfig::default();
+        let body = br#"{"email":"a@b.c","password":"x","patient":{"NIK":"3201","name":"Ani"},"items":[{"access_token":"t","code":"123456"}],"codes":["A01"]}"#;
+        let sanitized = config.sanitize_body(Some("application/json"), body).unwrap();
+        let value: Value = serde_json::from_str(&sanitized).unwrap();
+        assert_eq!(value["password"], REDACTED);
+        assert_eq!(value["patient"]["NIK"], REDACTED);
+        assert_eq!(value["patient"]["name"], "Ani");
+        assert_eq!(value["items"][0]["access_token"], REDACTED);
+        assert_eq!(value["items"][0]["code"], REDACTED);
+        assert_eq!(value["codes"][0], "A01");
+    }
+
+    #[test]
+    fn redacts_form_bodies_and_truncates() {
+        let config = RequestLogConfig { max_body_bytes: 40, ..RequestLogConfig::default() };
+        assert_eq!(config.redact_form("grant_type=client_credentials&client_secret=s3"), "grant_type=client_credentials&client_secret=%5BREDACTED%5D");
+
+        let long = format!(r#"{{"note":"{}"}}"#, "é".repeat(40));
+        let sanitized = config.sanitize_body(Some("application/json"), long.as_bytes()).unwrap();
+        assert!(sanitized.ends_with("...[truncated]"));
+        assert_eq!(config.sanitize_body(Some("application/json"), b"{oops").as_deref(), Some("[unparseable JSON omitted]"));
+    }
+
+    #[test]
+    fn streamed_exports_are_not_captured() {
+        assert!(is_capturable(Some("application/json; charset=utf-8")));
+        assert!(!is_capturable(Some("application/x-ndjson")));
+        assert!(!is_capturable(None));
+    }
+}
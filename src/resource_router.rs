@@ -0,0 +1,165 @@
+//! Route registration for one resource.
+//!
+//! `crud("/doctors", "Doctors")` serves the collection at `/doctors` (`list`, `create`) and
+//! single items at `/doctors/:id` (`get`, `update`, `delete`); further routes hang off the
+//! same prefix with `get_at`, `post_at`, `put_at` and `delete_at`. Every operation is tagged
+//! for `/openapi.json`, and `admin()` puts the whole resource behind `require_admin`.
+
+use std::sync::Arc;
+use axum::{
+    handler::Handler,
+    middleware,
+    routing::{self, MethodRouter},
+    Router,
+};
+use crate::db::AppState;
+use crate::middleware::require_admin;
+
+type SharedState = Arc<AppState>;
+
+/// Path of a single item below the collection path
+pub const ITEM: &str = "/:id";
+
+/// What an operation does, which decides the response documented for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    List,
+    Create,
+    Get,
+    Update,
+    Delete,
+    /// Routes added with `get_at` and the like, whose responses vary
+    Other,
+}
+
+/// One route of a resource, as listed in `/openapi.json`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    /// OpenAPI form, e.g. `/doctors/{id}`
+    pub path: String,
+    /// Lowercase HTTP method
+    pub method: &'static str,
+    pub tag: &'static str,
+    pub kind: OperationKind,
+}
+
+pub struct ResourceRouter {
+    path: &'static str,
+    tag: &'static str,
+    admin: bool,
+    router: Router<SharedState>,
+    operations: Vec<Operation>,
+}
+
+/// Routes of the resource at `path`, tagged `tag` in the API docs
+pub fn crud(path: &'static str, tag: &'static str) -> ResourceRouter {
+    ResourceRouter { path, tag, admin: false, router: Router::new(), operations: Vec::new() }
+}
+
+/// `/doctors/:id/reviews` as `/doctors/{id}/reviews`
+pub fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+impl ResourceRouter {
+    pub fn list<H: Handler<T, SharedState>, T: 'static>(self, handler: H) -> Self {
+        self.add("", "get", OperationKind::List, routing::get(handler))
+    }
+
+    pub fn create<H: Handler<T, SharedState>, T: 'static>(self, handler: H) -> Self {
+        self.add("", "post", OperationKind::Create, routing::post(handler))
+    }
+
+    pub fn get<H: Handler<T, SharedState>, T: 'static>(self, handler: H) -> Self {
+        self.add(ITEM, "get", OperationKind::Get, routing::get(handler))
+    }
+
+    pub fn update<H: Handler<T, SharedState>, T: 'static>(self, handler: H) -> Self {
+        self.add(ITEM, "put", OperationKind::Update, routing::put(handler))
+    }
+
+    pub fn delete<H: Handler<T, SharedState>, T: 'static>(self, handler: H) -> Self {
+        self.add(ITEM, "delete", OperationKind::Delete, routing::delete(handler))
+    }
+
+    /// `GET` on `sub` below the collection path, e.g. `/by-nik/:nik`
+    pub fn get_at<H: Handler<T, SharedState>, T: 'static>(self, sub: &str, handler: H) -> Self {
+        self.add(sub, "get", OperationKind::Other, routing::get(handler))
+    }
+
+    pub fn post_at<H: Handler<T, SharedState>, T: 'static>(self, sub: &str, handler: H) -> Self {
+        self.add(sub, "post", OperationKind::Other, routing::post(handler))
+    }
+
+    pub fn put_at<H: Handler<T, SharedState>, T: 'static>(self, sub: &str, handler: H) -> Self {
+        self.add(sub, "put", OperationKind::Other, routing::put(handler))
+    }
+
+    pub fn delete_at<H: Handler<T, SharedState>, T: 'static>(self, sub: &str, handler: H) -> Self {
+        self.add(sub, "delete", OperationKind::Other, routing::delete(handler))
+    }
+
+    /// Only admins may call any route of the resource
+    pub fn admin(mut self) -> Self {
+        self.admin = true;
+        self
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn into_router(self, state: &SharedState) -> Router<SharedState> {
+        if self.admin {
+            self.router.route_layer(middleware::from_fn_with_state(state.clone(), require_admin))
+        } else {
+            self.router
+        }
+    }
+
+    fn add(mut self, sub: &str, method: &'static str, kind: OperationKind, route: MethodRouter<SharedState>) -> Self {
+        let path = format!("{}{}", self.path, sub);
+        self.operations.push(Operation { path: openapi_path(&path), method, tag: self.tag, kind });
+        // Methods registered on one path are merged into a single route
+        self.router = self.router.route(&path, route);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_path_parameters() {
+        assert_eq!(openapi_path("/doctors/:id/reviews"), "/doctors/{id}/reviews");
+        assert_eq!(openapi_path("/interpretations/find/:code/:coding_code"), "/interpretations/find/{code}/{coding_code}");
+        assert_eq!(openapi_path("/wards/occupancy"), "/wards/occupancy");
+    }
+
+    #[test]
+    fn registers_collection_item_and_extra_routes() {
+        let resource = crud("/things", "Things")
+            .list(|| async {})
+            .create(|| async {})
+            .get(|| async {})
+            .delete(|| async {})
+            .get_at("/:id/history", || async {});
+
+        let routes: Vec<(&str, &str)> = resource.operations().iter().map(|op| (op.path.as_str(), op.method)).collect();
+        assert_eq!(routes, [
+            ("/things", "get"),
+            ("/things", "post"),
+            ("/things/{id}", "get"),
+            ("/things/{id}", "delete"),
+            ("/things/{id}/history", "get"),
+        ]);
+        assert!(resource.operations().iter().all(|op| op.tag == "Things"));
+    }
+}
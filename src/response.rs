@@ -110,6 +110,9 @@ pub struct ErrorResponse {
     pub status: u16,
     pub message: String,
     pub error: ErrorDetails,
+    /// Structured context for the client, e.g. the conflicts behind a 409
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Box<serde_json::Value>>,
     pub timestamp: String,
 }
 
@@ -129,10 +132,17 @@ impl ErrorResponse {
                 code: error_code.into(),
                 details,
             },
+            data: None,
             timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
         }
     }
 
+    /// Attach structured `data` to the error
+    pub fn with_data(mut self, data: impl Serialize) -> Self {
+        self.data = serde_json::to_value(data).ok().map(Box::new);
+        self
+    }
+
     /// Bad Request (400)
     pub fn bad_request(message: impl Into<String>, details: Option<String>) -> Self {
         Self::new(StatusCode::BAD_REQUEST, message, "BAD_REQUEST", details)
@@ -183,7 +193,17 @@ impl IntoResponse for ErrorResponse {
     fn into_response(self) -> Response {
         let status = StatusCode::from_u16(self.status)
             .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
-        (status, Json(self)).into_response()
+        // Server errors are reported with what the handler said about them
+        let reported = status.is_server_error().then(|| crate::error_reporting::ReportedError {
+            code: self.error.code.clone(),
+            message: self.message.clone(),
+            details: self.error.details.clone(),
+        });
+        let mut response = (status, Json(self)).into_response();
+        if let Some(reported) = reported {
+            response.extensions_mut().insert(reported);
+        }
+        response
     }
 }
 
@@ -238,6 +258,10 @@ mod tests {
         assert!(!error.success);
         assert_eq!(error.status, 404);
         assert_eq!(error.error.code, "NOT_FOUND");
+        assert!(serde_json::to_value(&error).unwrap().get("data").is_none());
+
+        let conflict = ErrorResponse::conflict("Blocked", None).with_data(serde_json::json!([{ "code": "A" }]));
+        assert_eq!(serde_json::to_value(&conflict).unwrap()["data"][0]["code"], "A");
     }
 
     #[test]
@@ -0,0 +1,213 @@
+//! Data retention policies and the nightly job executing them.
+//!
+//! Policies come from `RETENTION_POLICIES`, a JSON array such as
+//! `[{"collection": "observations", "action": "archive", "older_than_days": 1825},
+//!   {"collection": "audit_logs", "action": "purge", "older_than_days": 2555}]`.
+//! `archive` writes expired documents to S3 as JSONL before deleting them; `purge` deletes
+//! them outright. Documents are selected by `date_field` (default `created_at`), compared
+//! natively when it holds a BSON date and as a timestamp string otherwise. Without policies
+//! the job does nothing.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use mongodb::bson::{doc, Document};
+use serde::{Deserialize, Serialize};
+use crate::db::AppState;
+use crate::repository::RetentionRepository;
+use crate::services::RetentionService;
+
+pub const DEFAULT_RUN_HOUR: u32 = 2;
+pub const RUNS_COLLECTION: &str = "retention_runs";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    Archive,
+    Purge,
+}
+
+impl RetentionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Archive => "archive",
+            Self::Purge => "purge",
+        }
+    }
+}
+
+fn default_date_field() -> String {
+    "created_at".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub collection: String,
+    pub action: RetentionAction,
+    pub older_than_days: i64,
+    #[serde(default = "default_date_field")]
+    pub date_field: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub policies: Vec<RetentionPolicy>,
+    /// Local hour (0-23) at which the nightly run starts
+    pub run_hour: u32,
+    pub archive_bucket: String,
+    pub archive_prefix: String,
+}
+
+impl RetentionConfig {
+    /// Read the configuration from the environment. Invalid policies are logged and ignored.
+    pub fn from_env() -> Self {
+        let policies = match env::var("RETENTION_POLICIES") {
+            Ok(raw) if !raw.trim().is_empty() => parse_policies(&raw).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Ignoring RETENTION_POLICIES");
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+
+        Self {
+            policies,
+            run_hour: env::var("RETENTION_RUN_HOUR")
+                .ok()
+                .and_then(|h| h.parse().ok())
+                .filter(|h| *h < 24)
+                .unwrap_or(DEFAULT_RUN_HOUR),
+            archive_bucket: env::var("RETENTION_ARCHIVE_BUCKET")
+                .or_else(|_| env::var("AWS_BUCKET"))
+                .unwrap_or_else(|_| "atm-sehat".to_string()),
+            archive_prefix: env::var("RETENTION_ARCHIVE_PREFIX").unwrap_or_else(|_| "archive".to_string()),
+        }
+    }
+}
+
+pub fn parse_policies(raw: &str) -> Result<Vec<RetentionPolicy>, String> {
+    let policies: Vec<RetentionPolicy> = serde_json::from_str(raw).map_err(|e| format!("Invalid JSON: {}", e))?;
+
+    for policy in &policies {
+        if policy.collection.trim().is_empty() {
+            return Err("Policy collection cannot be empty".to_string());
+        }
+        if policy.collection == RUNS_COLLECTION {
+            return Err(format!("'{}' cannot have a retention policy", RUNS_COLLECTION));
+        }
+        if policy.older_than_days <= 0 {
+            return Err(format!("older_than_days must be positive for '{}'", policy.collection));
+        }
+    }
+
+    Ok(policies)
+}
+
+/// Instant before which documents are expired.
+pub fn cutoff(now: DateTime<Utc>, older_than_days: i64) -> DateTime<Utc> {
+    now - chrono::Duration::days(older_than_days)
+}
+
+/// Filter for documents whose `date_field` is before `cutoff`. BSON dates compare natively;
+/// strings left by other fields or unmigrated documents compare against the `T`-separated
+/// form, which sorts correctly against both RFC 3339 and `%Y-%m-%d %H:%M:%S` values of earlier days.
+pub fn expired_filter(date_field: &str, cutoff: DateTime<Utc>) -> Document {
+    doc! {
+        "$or": [
+            { date_field: { "$lt": crate::datetime::from_chrono(cutoff) } },
+            { date_field: { "$lt": cutoff.format("%Y-%m-%dT%H:%M:%S").to_string() } },
+        ]
+    }
+}
+
+/// S3 key for one archived batch.
+pub fn archive_key(prefix: &str, collection: &str, started_at: DateTime<Utc>, part: usize) -> String {
+    format!(
+        "{}/{}/{}/{}-{:04}.jsonl",
+        prefix.trim_end_matches('/'),
+        collection,
+        started_at.format("%Y/%m/%d"),
+        started_at.format("%H%M%S"),
+        part,
+    )
+}
+
+/// Time from `now` until the next occurrence of `hour:00` local time.
+pub fn delay_until_next_run<Tz: TimeZone>(now: DateTime<Tz>, hour: u32) -> Duration {
+    let today = now.date_naive().and_hms_opt(hour, 0, 0).unwrap_or_default();
+    let mut next = today;
+    if next <= now.naive_local() {
+        next += chrono::Duration::days(1);
+    }
+    (next - now.naive_local()).to_std().unwrap_or(Duration::from_secs(3600))
+}
+
+/// Spawn the nightly retention job. Does nothing when no policies are configured.
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    let config = RetentionConfig::from_env();
+    if config.policies.is_empty() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(delay_until_next_run(Local::now(), config.run_hour)).await;
+
+            state.scheduler.run("retention", async {
+                let service = RetentionService::new(RetentionRepository::new(state.db.clone()), config.clone());
+                #[cfg(feature = "s3")]
+                let service = service.with_s3(state.storage.clone());
+                service.run("scheduled").await.map(|_| ())
+            }).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn parses_policies_with_defaults() {
+        let policies = parse_policies(r#"[{"collection": "audit_logs", "action": "purge", "older_than_days": 30}]"#).unwrap();
+        assert_eq!(policies[0].action, RetentionAction::Purge);
+        assert_eq!(policies[0].date_field, "created_at");
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        assert!(parse_policies(r#"[{"collection": "x", "action": "purge", "older_than_days": 0}]"#).is_err());
+        assert!(parse_policies(r#"[{"collection": "retention_runs", "action": "purge", "older_than_days": 1}]"#).is_err());
+        assert!(parse_policies(r#"[{"collection": "x", "action": "shred", "older_than_days": 1}]"#).is_err());
+    }
+
+    #[test]
+    fn expired_filter_matches_dates_and_strings() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let filter = expired_filter("created_at", cutoff(now, 365));
+        let clauses = filter.get_array("$or").unwrap();
+
+        let native = clauses[0].as_document().unwrap().get_document("created_at").unwrap();
+        assert_eq!(native.get_datetime("$lt").unwrap().timestamp_millis(), Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap().timestamp_millis());
+        let legacy = clauses[1].as_document().unwrap().get_document("created_at").unwrap().get_str("$lt").unwrap();
+        assert_eq!(legacy, "2025-03-10T12:00:00");
+        assert!("2025-03-09 23:59:59" < legacy);
+        assert!("2025-03-11T00:00:00+00:00" > legacy);
+    }
+
+    #[test]
+    fn next_run_is_today_or_tomorrow() {
+        let morning = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(1, 30, 0).unwrap().and_utc();
+        assert_eq!(delay_until_next_run(morning, 2), Duration::from_secs(30 * 60));
+
+        let evening = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap().and_hms_opt(3, 0, 0).unwrap().and_utc();
+        assert_eq!(delay_until_next_run(evening, 2), Duration::from_secs(23 * 3600));
+    }
+
+    #[test]
+    fn archive_keys_are_partitioned_by_date() {
+        let started = Utc.with_ymd_and_hms(2026, 3, 10, 2, 0, 5).unwrap();
+        assert_eq!(archive_key("archive/", "observations", started, 3), "archive/observations/2026/03/10/020005-0003.jsonl");
+    }
+}
@@ -0,0 +1,114 @@
+//! Validity periods of role assignments.
+//!
+//! A `UserRole` may carry `valid_from` and `valid_until`; outside that window it grants
+//! nothing, even while `is_active` is still set, because every lookup of a user's roles goes
+//! through `current_filter`. A sweep every `SWEEP_INTERVAL` then clears `is_active` on
+//! assignments past `valid_until`, so listings agree with what authorization enforces, and
+//! sends each current administrator an in-app notification listing what expired.
+
+use std::sync::Arc;
+use std::time::Duration;
+use mongodb::bson::{doc, Bson, DateTime, Document};
+use crate::db::AppState;
+use crate::models::UserRole;
+use crate::repository::{NotificationRepository, UserRoleRepository};
+use crate::services::UserRoleService;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Assignments that are active and within their validity period at `now`
+pub fn current_filter(now: DateTime) -> Document {
+    doc! {
+        "is_active": true,
+        "$and": [
+            { "$or": [{ "valid_from": Bson::Null }, { "valid_from": { "$lte": now } }] },
+            { "$or": [{ "valid_until": Bson::Null }, { "valid_until": { "$gt": now } }] },
+        ],
+    }
+}
+
+/// Whether the assignment grants its role at `now`, the same test as `current_filter`
+pub fn is_current(user_role: &UserRole, now: DateTime) -> bool {
+    user_role.is_active
+        && user_role.valid_from.is_none_or(|from| from <= now)
+        && user_role.valid_until.is_none_or(|until| until > now)
+}
+
+/// A validity window must end after it starts
+pub fn check_window(valid_from: Option<DateTime>, valid_until: Option<DateTime>) -> Result<(), String> {
+    match (valid_from, valid_until) {
+        (Some(from), Some(until)) if until <= from => Err("valid_until must be after valid_from".to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Spawn the sweep deactivating expired assignments.
+pub fn spawn_worker(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            sweep.tick().await;
+            state.scheduler.run("role_expiry", async {
+                let service = UserRoleService::new(UserRoleRepository::new(state.db.clone()));
+                let notifications = NotificationRepository::new(state.db.clone());
+                let expired = service.deactivate_expired(&notifications, DateTime::now()).await?;
+                if !expired.is_empty() {
+                    tracing::info!(count = expired.len(), "Deactivated expired role assignments");
+                }
+                Ok(())
+            }).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{OrganizationEmbed, RoleCategory, RoleEmbed, UserBirth, UserContact, UserEmbed, UserName};
+
+    fn assignment(valid_from: Option<i64>, valid_until: Option<i64>) -> UserRole {
+        let now = DateTime::from_millis(0);
+        UserRole {
+            id: None,
+            role: RoleEmbed {
+                code: "nurse".to_string(),
+                system: "roles".to_string(),
+                display: "Nurse".to_string(),
+                category: RoleCategory { code: "staff".to_string(), system: "roles".to_string(), display: "Staff".to_string(), id: "c1".to_string() },
+            },
+            user: UserEmbed {
+                nama: UserName { nama_depan: "Sari".to_string(), nama_belakang: "Dewi".to_string() },
+                nik: "3201010101010001".to_string(),
+                kontak: UserContact { email: "sari@example.com".to_string(), nomor_telepon: "+6281234567890".to_string() },
+                lahir: UserBirth { tempat: "Bandung".to_string(), tanggal: "1990-01-01".to_string() },
+                id: "u1".to_string(),
+            },
+            organisasi: OrganizationEmbed { name: "Klinik".to_string(), id: "o1".to_string() },
+            is_active: true,
+            valid_from: valid_from.map(DateTime::from_millis),
+            valid_until: valid_until.map(DateTime::from_millis),
+            updated_at: now,
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn grants_only_within_the_window() {
+        let now = DateTime::from_millis(1_000);
+        assert!(is_current(&assignment(None, None), now));
+        assert!(is_current(&assignment(Some(1_000), Some(1_001)), now));
+        assert!(!is_current(&assignment(Some(1_001), None), now));
+        assert!(!is_current(&assignment(None, Some(1_000)), now));
+
+        let mut inactive = assignment(None, None);
+        inactive.is_active = false;
+        assert!(!is_current(&inactive, now));
+    }
+
+    #[test]
+    fn windows_end_after_they_start() {
+        assert!(check_window(Some(DateTime::from_millis(5)), Some(DateTime::from_millis(5))).is_err());
+        assert!(check_window(Some(DateTime::from_millis(5)), Some(DateTime::from_millis(6))).is_ok());
+        assert!(check_window(None, Some(DateTime::from_millis(1))).is_ok());
+    }
+}
@@ -1,18 +1,23 @@
 use axum::{
-    routing::{get, post},
+    http::header,
+    routing::{delete, get, post, put},
     Router,
     middleware,
 };
-use tower_http::cors::{Any, CorsLayer};
-use crate::{handlers::*, db::AppState, middleware::auth_middleware};
+use tower_http::{cors::{Any, CorsLayer}, trace::TraceLayer};
+use crate::{handlers::*, db::AppState, middleware::{api_naming, auth_middleware, json_errors, patient_scope, report_errors, request_capture, kiosk_scope, require_admin, require_verified_email, security_headers, shed_load, service_scope, timeout_middleware}};
 use crate::docs;
+use crate::resource_router::{crud, ResourceRouter};
 use std::sync::Arc;
 
 pub fn create_router(state: Arc<AppState>) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        // Browsers hide other response headers from scripts; offline clients need the validators
+        // and support needs the request id
+        .expose_headers([header::ETAG, header::LAST_MODIFIED, header::HeaderName::from_static(crate::error_reporting::REQUEST_ID_HEADER)]);
 
     // Public routes (no authentication required)
     let public_routes = Router::new()
@@ -22,84 +27,135 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/auth/refresh", post(refresh_token))
         .route("/auth/forgot-password", post(forgot_password))
         .route("/auth/reset-password", post(reset_password))
+        .route("/auth/patient/otp", post(request_patient_otp))
+        .route("/auth/patient/login", post(patient_login))
+        .route("/auth/token", post(service_account_handlers::issue_token))
+        .route("/auth/kiosk/pair", post(kiosk_handlers::pair_kiosk))
+        .route("/auth/otp/request", post(request_otp))
+        .route("/auth/otp/verify", post(verify_otp))
+        .route("/auth/verify-email", get(verify_email))
+        .route("/auth/resend-verification", post(resend_verification))
+        // Kiosks onboarding walk-ins; OTP-verified and limited per client
+        .route("/public/patients/register", post(self_registration_handlers::register_patient))
+        // Booking without an account; captcha before a code is sent, staff confirm the result
+        .route("/public/doctors", get(public_booking_handlers::get_public_doctors))
+        .route("/public/doctors/:id/slots", get(public_booking_handlers::get_public_slots))
+        .route("/public/appointments", post(public_booking_handlers::create_public_appointment))
         // Documentation routes
-        .route("/docs", get(docs::docs_html))
-        .route("/openapi.json", get(docs::openapi_json));
+        .route("/openapi.json", get(docs::openapi_json))
+        // Scraped by Prometheus; never shed
+        .route("/metrics", get(crate::metrics::get_metrics));
+    #[cfg(feature = "docs-ui")]
+    let public_routes = public_routes.route("/docs", get(docs::docs_html));
+    // Share links carry their own signed token
+    #[cfg(feature = "s3")]
+    let public_routes = public_routes.route("/share/:token", get(file_handlers::get_shared_file));
+    #[cfg(not(feature = "s3"))]
+    let public_routes = public_routes.route("/share/:token", get(crate::system::storage_not_built));
+    #[cfg(feature = "billing")]
+    let public_routes = public_routes.route("/payments/callback", post(payment_handlers::payment_callback));
 
-    // Protected routes (authentication required)
+    // Admin routes that are not resources (authentication and the admin role required)
+    let admin_routes = Router::new()
+        .route("/admin/retention/status", get(admin_handlers::get_retention_status))
+        .route("/admin/scheduler", get(admin_handlers::get_scheduler_status))
+        .route("/admin/request-logs", get(admin_handlers::get_request_logs))
+        .route("/admin/jobs", get(job_handlers::get_jobs))
+        .route("/admin/jobs/retry", post(job_handlers::retry_jobs))
+        .route("/admin/jobs/:id", get(job_handlers::get_job))
+        .route("/admin/jobs/:id/retry", post(job_handlers::retry_job))
+        .route("/admin/outbox", get(outbox_handlers::get_outbox_entries))
+        .route("/admin/outbox/:id", get(outbox_handlers::get_outbox_entry))
+        .route("/admin/outbox/:id/retry", post(outbox_handlers::retry_outbox_entry))
+        .route("/reports/run", post(report_template_handlers::run_report))
+        .route("/admin/system-info", get(admin_handlers::get_system_info))
+        .route("/admin/indexes", get(admin_handlers::get_indexes))
+        .route("/admin/indexes/rebuild", post(admin_handlers::rebuild_indexes))
+        .route("/admin/denormalization/report", get(admin_handlers::get_denormalization_report))
+        .route("/admin/reviews", get(review_handlers::get_reviews_for_moderation))
+        .route("/admin/reviews/:id", put(review_handlers::moderate_review).delete(review_handlers::delete_review))
+        .route("/admin/role-permissions", get(permission_handlers::get_role_permissions).post(permission_handlers::grant_permission))
+        .route("/admin/role-permissions/:id", delete(permission_handlers::revoke_permission));
+    #[cfg(feature = "s3")]
+    let admin_routes = admin_routes
+        .route("/admin/storage-usage", get(file_handlers::get_storage_usage))
+        .route(
+            "/admin/storage/reconciliation",
+            get(file_handlers::get_storage_reconciliation).post(file_handlers::run_storage_reconciliation),
+        );
+    let admin_routes = admin_routes.route_layer(middleware::from_fn_with_state(state.clone(), require_admin));
+
+    // Protected routes that are not resources (authentication required)
     let protected_routes = Router::new()
         // Auth - Get current user
         .route("/auth/me", get(get_me))
-        // Users
-        .route("/users", get(get_users).post(create_user))
-        .route("/users/:id", get(get_user).put(update_user).delete(delete_user))
-        // Medical Records
-        .route("/medical-records", get(get_medical_records).post(create_medical_record))
-        .route("/medical-records/:id", get(get_medical_record).put(update_medical_record).delete(delete_medical_record))
-        // Doctors
-        .route("/doctors", get(get_doctors).post(create_doctor))
-        .route("/doctors/:id", get(get_doctor).put(update_doctor).delete(delete_doctor))
-        // Nurses
-        .route("/nurses", get(get_nurses).post(create_nurse))
-        .route("/nurses/:id", get(get_nurse).put(update_nurse).delete(delete_nurse))
-        // Medicines
-        .route("/medicines", get(get_medicines).post(create_medicine))
-        .route("/medicines/:id", get(get_medicine).put(update_medicine).delete(delete_medicine))
-        // Appointments
-        .route("/appointments", get(appointment_handlers::get_appointments).post(appointment_handlers::create_appointment))
-        .route("/appointments/:id", get(appointment_handlers::get_appointment).put(appointment_handlers::update_appointment).delete(appointment_handlers::delete_appointment))
-        // Services
-        .route("/services", get(service_handlers::get_services).post(service_handlers::create_service))
-        .route("/services/:id", get(service_handlers::get_service).put(service_handlers::update_service).delete(service_handlers::delete_service))
-        // Insurances
-        .route("/insurances", get(insurance_handlers::get_insurances).post(insurance_handlers::create_insurance))
-        .route("/insurances/:id", get(insurance_handlers::get_insurance).put(insurance_handlers::update_insurance).delete(insurance_handlers::delete_insurance))
-        // File
-        .nest("/files", Router::new()
-            .route("/", get(file_handlers::get_files).post(file_handlers::create_file))
-            .route("/:id", get(file_handlers::get_file).delete(file_handlers::delete_file))
-        )
-        // Create Child Codes
-        .nest("/child-codes", Router::new()
-            .route("/", get(child_code_handlers::get_child_codes).post(child_code_handlers::create_child_code))
-            .route("/:id", get(child_code_handlers::get_child_code).put(child_code_handlers::update_child_code).delete(child_code_handlers::delete_child_code))
-        )
-        // Regions
-        .nest("/regions", Router::new()
-            .route("/", get(region_handlers::get_regions).post(region_handlers::create_region))
-            .route("/provinsi/:provinsi", get(region_handlers::get_regions_by_provinsi))
-            .route("/kota/:kota", get(region_handlers::get_regions_by_kota))
-            .route("/kecamatan/:kecamatan", get(region_handlers::get_regions_by_kecamatan))
-            .route("/code/:code", get(region_handlers::get_region_by_code))
-            .route("/:id", get(region_handlers::get_region).put(region_handlers::update_region).delete(region_handlers::delete_region))
-        )
-        // Interpretations
-        .nest("/interpretations", Router::new()
-            .route("/", get(interpretation_handlers::get_interpretations).post(interpretation_handlers::create_interpretation))
-            .route("/code/:code", get(interpretation_handlers::get_interpretation_by_code))
-            .route("/coding/:coding_code", get(interpretation_handlers::get_interpretations_by_coding_code))
-            .route("/find/:code/:coding_code", get(interpretation_handlers::get_interpretation_by_code_and_coding_code))
-            .route("/:id", get(interpretation_handlers::get_interpretation).put(interpretation_handlers::update_interpretation).delete(interpretation_handlers::delete_interpretation))
-        )
-        // Kits
-        .nest("/kits", Router::new()
-            .route("/", get(kit_handlers::get_kits).post(kit_handlers::create_kit))
-            .route("/:id", get(kit_handlers::get_kit).put(kit_handlers::update_kit).delete(kit_handlers::delete_kit))
-        )
-        // Roles
-        .route("/roles", get(role_handlers::get_roles).post(role_handlers::create_role))
-        .route("/roles/:id", get(role_handlers::get_role).put(role_handlers::update_role).delete(role_handlers::delete_role))
-        // User Roles
-        .route("/user-roles", get(user_role_handlers::get_user_roles).post(user_role_handlers::create_user_role))
-        .route("/user-roles/:id", get(user_role_handlers::get_user_role).put(user_role_handlers::update_user_role).delete(user_role_handlers::delete_user_role))
-        // Codes
-        .route("/codes", get(code_handlers::get_codes).post(code_handlers::create_code))
-        .route("/codes/:id", get(code_handlers::get_code).put(code_handlers::update_code).delete(code_handlers::delete_code))
-        // Observations
-        .nest("/observations", Router::new()
-            .route("/", get(observation_handlers::get_observations).post(observation_handlers::create_observation))
-            .route("/:id", get(observation_handlers::get_observation).put(observation_handlers::update_observation).delete(observation_handlers::delete_observation))
+        .route("/auth/me/permissions", get(permission_handlers::get_my_permissions))
+        .route("/auth/me/flags", get(feature_flag_handlers::get_my_flags))
+        // Global search
+        .route("/search", get(search_handlers::global_search))
+        .route("/lookup/barcode/:value", get(search_handlers::lookup_barcode))
+        // Delta sync for offline clients, see `crate::sync`
+        .route("/sync/reference", get(sync_handlers::get_reference_changes))
+        .route("/sync/clinical/push", post(sync_handlers::push_clinical_changes))
+        .route("/sync/clinical/pull", get(sync_handlers::pull_clinical_changes))
+        .route("/sync/conflicts", get(sync_handlers::get_sync_conflicts))
+        .route("/sync/conflicts/:id/resolve", post(sync_handlers::resolve_sync_conflict))
+        .route("/stats/doctors/:id/appointments", get(appointment_handlers::get_doctor_appointment_stats))
+        // Patients (backed by medical records)
+        .route("/patients/duplicates", get(patient_handlers::get_duplicate_patients))
+        .route("/patients/registrations", get(self_registration_handlers::get_registrations))
+        .route("/patients/:id/registration/confirm", post(self_registration_handlers::confirm_registration))
+        .route("/patients/by-phone/:phone", get(patient_handlers::get_patients_by_phone))
+        .route("/patients/:id/merge", post(patient_handlers::merge_patients))
+        .route("/patients/:id/growth", get(patient_handlers::get_patient_growth))
+        .route("/patients/:id/timeline", get(patient_handlers::get_patient_timeline))
+        .route("/patients/:id/allergies", get(allergy_handlers::get_allergies).post(allergy_handlers::create_allergy))
+        .route("/patients/:id/allergies/check", post(allergy_handlers::check_allergies))
+        .route("/patients/:id/allergies/:allergy_id", put(allergy_handlers::update_allergy).delete(allergy_handlers::delete_allergy))
+        .route("/patients/:id/relationships", get(patient_relationship_handlers::get_relationships).post(patient_relationship_handlers::create_relationship))
+        .route(
+            "/patients/:id/relationships/:relationship_id",
+            put(patient_relationship_handlers::update_relationship).delete(patient_relationship_handlers::delete_relationship),
         )
+        // Waiting-room queues
+        .route("/queues/:doctor_id/next", post(queue_handlers::call_next_patient))
+        .route("/queues/:doctor_id/today", get(queue_handlers::get_today_queue))
+        .route("/queues/:doctor_id/stream", get(queue_handlers::stream_queue));
+    #[cfg(feature = "billing")]
+    let protected_routes = protected_routes
+        .route("/payments/settlement", get(payment_handlers::get_settlement))
+        // Management dashboard
+        .route("/stats/revenue", get(payment_handlers::get_revenue_stats))
+        .route("/stats/services/utilization", get(payment_handlers::get_service_utilization))
+        // BPJS VClaim bridging
+        .route("/integrations/bpjs/participants/:card_number", get(bpjs_handlers::get_participant))
+        .route("/integrations/bpjs/sep", post(bpjs_handlers::create_sep));
+    #[cfg(feature = "kits")]
+    let protected_routes = protected_routes
+        .route("/operators/:nik/activity", get(usage_handlers::get_operator_activity))
+        // Distributor portal; distributors are scoped to their own kits
+        .route("/distributors/:code/summary", get(usage_handlers::get_distributor_summary));
+    #[cfg(not(feature = "s3"))]
+    let protected_routes = protected_routes
+        .route("/files", axum::routing::any(crate::system::storage_not_built))
+        .route("/files/*rest", axum::routing::any(crate::system::storage_not_built));
+    #[cfg(feature = "fhir")]
+    let protected_routes = protected_routes
+        // Terminology imports and the bulk observation export
+        .route("/codes/import", post(code_handlers::import_codes))
+        .route("/codes/import/:job_id", get(code_handlers::get_import_job))
+        .route("/observations/export.ndjson", get(observation_handlers::export_observations));
+
+    let protected_routes = resources()
+        .into_iter()
+        .fold(protected_routes.merge(admin_routes), |routes, resource| routes.merge(resource.into_router(&state)))
+        // Patient portal tokens only reach the patient's own data
+        .layer(middleware::from_fn_with_state(state.clone(), patient_scope))
+        .layer(middleware::from_fn_with_state(state.clone(), service_scope))
+        // Kiosk tokens only reach check-in and queue endpoints
+        .layer(middleware::from_fn_with_state(state.clone(), kiosk_scope))
+        // Prefixes in EMAIL_VERIFIED_ROUTES need a verified email
+        .layer(middleware::from_fn_with_state(state.clone(), require_verified_email))
         // Apply auth middleware ONLY to these protected routes
         .layer(middleware::from_fn_with_state(state.clone(), auth_middleware));
 
@@ -107,6 +163,253 @@ pub fn create_router(state: Arc<AppState>) -> Router {
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), timeout_middleware))
+        // Extractor rejections and unknown routes answer in the documented error envelope
+        .layer(middleware::from_fn(json_errors))
+        .layer(middleware::from_fn(report_errors))
+        .layer(middleware::from_fn(api_naming))
+        .layer(middleware::from_fn_with_state(state.clone(), request_capture))
+        .layer(middleware::from_fn_with_state(state.clone(), security_headers))
+        // Outermost, so refused requests cost as little as possible
+        .layer(middleware::from_fn_with_state(state.clone(), shed_load))
         .with_state(state)
+        // Inside the router so spans are named by the matched route
+        .layer(TraceLayer::new_for_http()
+            .make_span_with(crate::telemetry::request_span)
+            .on_response(|response: &axum::response::Response, _latency, span: &tracing::Span| {
+                span.record("http.response.status_code", response.status().as_u16());
+            }))
         .layer(cors)
 }
+
+/// Authenticated resources; their operations are also tagged in `/openapi.json`
+pub fn resources() -> Vec<ResourceRouter> {
+    // Without the optional features nothing is added to the list
+    #[allow(unused_mut)]
+    let mut resources = vec![
+        // Administration
+        crud("/admin/permissions", "Permissions").admin()
+            .list(permission_handlers::get_permissions).create(permission_handlers::create_permission)
+            .get(permission_handlers::get_permission).update(permission_handlers::update_permission).delete(permission_handlers::delete_permission),
+        crud("/admin/feature-flags", "Feature flags").admin()
+            .list(feature_flag_handlers::get_feature_flags).create(feature_flag_handlers::create_feature_flag)
+            .get(feature_flag_handlers::get_feature_flag).update(feature_flag_handlers::update_feature_flag).delete(feature_flag_handlers::delete_feature_flag),
+        crud("/admin/service-accounts", "Service accounts").admin()
+            .list(service_account_handlers::get_service_accounts).create(service_account_handlers::create_service_account)
+            .get(service_account_handlers::get_service_account).update(service_account_handlers::update_service_account).delete(service_account_handlers::delete_service_account)
+            .post_at("/:id/rotate-secret", service_account_handlers::rotate_service_account_secret),
+        crud("/admin/kiosks", "Kiosk devices").admin()
+            .list(kiosk_handlers::get_kiosks).create(kiosk_handlers::create_kiosk).get(kiosk_handlers::get_kiosk)
+            .post_at("/:id/pairing-code", kiosk_handlers::renew_kiosk_pairing_code)
+            .post_at("/:id/revoke", kiosk_handlers::revoke_kiosk),
+        crud("/admin/organizations", "Organizations").admin()
+            .list(organization_handlers::get_organizations).create(organization_handlers::create_organization)
+            .get(organization_handlers::get_organization).update(organization_handlers::update_organization).delete(organization_handlers::delete_organization),
+        crud("/organizations", "Organizations")
+            .get_at("/:id/members", organization_handlers::get_organization_members),
+        crud("/admin/report-templates", "Report templates").admin()
+            .list(report_template_handlers::get_report_templates).create(report_template_handlers::create_report_template)
+            .get(report_template_handlers::get_report_template).update(report_template_handlers::update_report_template).delete(report_template_handlers::delete_report_template),
+        // Users and access
+        crud("/users", "Users")
+            .list(get_users).create(create_user).get(get_user).update(update_user).delete(delete_user)
+            .get_at("/by-phone/:phone", get_users_by_phone),
+        crud("/roles", "Roles")
+            .list(role_handlers::get_roles).create(role_handlers::create_role)
+            .get(role_handlers::get_role).update(role_handlers::update_role).delete(role_handlers::delete_role),
+        crud("/user-roles", "User roles")
+            .list(user_role_handlers::get_user_roles).create(user_role_handlers::create_user_role)
+            .get(user_role_handlers::get_user_role).update(user_role_handlers::update_user_role).delete(user_role_handlers::delete_user_role)
+            .post_at("/bulk", user_role_handlers::bulk_create_user_roles),
+        // Patients and clinical records
+        crud("/medical-records", "Medical records")
+            .list(get_medical_records).create(create_medical_record)
+            .get(get_medical_record).update(update_medical_record).delete(delete_medical_record)
+            .get_at("/by-nik/:nik", get_medical_record_by_nik)
+            .post_at("/:id/vitals", observation_handlers::record_vitals)
+            .get_at("/:id/changes", get_medical_record_changes),
+        crud("/notes", "Clinical notes")
+            .list(note_handlers::get_notes).create(note_handlers::create_note)
+            .get(note_handlers::get_note).update(note_handlers::update_note).delete(note_handlers::delete_note)
+            .get_at("/:id/versions", note_handlers::get_note_versions),
+        crud("/observations", "Observations")
+            .list(observation_handlers::get_observations).create(observation_handlers::create_observation)
+            .get(observation_handlers::get_observation).update(observation_handlers::update_observation).delete(observation_handlers::delete_observation)
+            .get_at("/pasien/:id/trends/:coding_code", observation_handlers::get_observation_trend),
+        // Inpatient wards, beds and admissions
+        crud("/wards", "Wards")
+            .list(ward_handlers::get_wards).create(ward_handlers::create_ward)
+            .get(ward_handlers::get_ward).update(ward_handlers::update_ward).delete(ward_handlers::delete_ward)
+            .get_at("/occupancy", ward_handlers::get_occupancy)
+            .get_at("/:id/beds", ward_handlers::get_beds)
+            .post_at("/:id/beds", ward_handlers::create_bed),
+        crud("/beds", "Wards")
+            .update(ward_handlers::update_bed).delete(ward_handlers::delete_bed),
+        crud("/admissions", "Admissions")
+            .list(admission_handlers::get_admissions).create(admission_handlers::admit_patient).get(admission_handlers::get_admission)
+            .post_at("/:id/transfer", admission_handlers::transfer_patient)
+            .post_at("/:id/discharge", admission_handlers::discharge_patient),
+        // Chronic disease programs such as Prolanis, with enrollments and cohort reports
+        crud("/programs", "Programs")
+            .list(program_handlers::get_programs).create(program_handlers::create_program)
+            .get(program_handlers::get_program).update(program_handlers::update_program).delete(program_handlers::delete_program)
+            .post_at("/:id/eligibility", program_handlers::check_program_eligibility)
+            .get_at("/:id/enrollments", program_handlers::get_program_enrollments)
+            .post_at("/:id/enrollments", program_handlers::enroll_patient)
+            .post_at("/:id/enrollments/:enrollment_id/withdraw", program_handlers::withdraw_enrollment)
+            .get_at("/:id/cohort", program_handlers::get_program_cohort),
+        // Doctors and nurses together; /doctors and /nurses show one type each
+        crud("/practitioners", "Practitioners")
+            .list(practitioner_handlers::get_practitioners).create(practitioner_handlers::create_practitioner)
+            .get(practitioner_handlers::get_practitioner).update(practitioner_handlers::update_practitioner).delete(practitioner_handlers::delete_practitioner),
+        crud("/doctors", "Doctors")
+            .list(get_doctors).create(create_doctor).get(get_doctor).update(update_doctor).delete(delete_doctor)
+            .get_at("/:id/reviews", review_handlers::get_doctor_reviews),
+        crud("/nurses", "Nurses")
+            .list(get_nurses).create(create_nurse).get(get_nurse).update(update_nurse).delete(delete_nurse),
+        crud("/medicines", "Medicines")
+            .list(get_medicines).create(create_medicine).get(get_medicine).update(update_medicine).delete(delete_medicine)
+            .get_at("/:id/label", get_medicine_label),
+        // Physical stock counts; approval adjusts medicine quantities
+        crud("/stock-opnames", "Stock opnames")
+            .list(stock_opname_handlers::get_stock_opnames).create(stock_opname_handlers::create_stock_opname)
+            .get(stock_opname_handlers::get_stock_opname)
+            .get_at("/:id/report", stock_opname_handlers::get_stock_opname_report)
+            .post_at("/:id/counts", stock_opname_handlers::submit_stock_counts)
+            .post_at("/:id/approve", stock_opname_handlers::approve_stock_opname)
+            .post_at("/:id/cancel", stock_opname_handlers::cancel_stock_opname),
+        // Appointments, series, teleconsults and the waitlist
+        crud("/appointments", "Appointments")
+            .list(appointment_handlers::get_appointments).create(appointment_handlers::create_appointment)
+            .get(appointment_handlers::get_appointment).update(appointment_handlers::update_appointment).delete(appointment_handlers::delete_appointment)
+            .get_at("/availability", appointment_handlers::get_availability)
+            .post_at("/:id/check-in", check_in_appointment)
+            .post_at("/:id/no-show", appointment_handlers::mark_no_show)
+            .get_at("/:id/teleconsult", teleconsult_handlers::get_session)
+            .post_at("/:id/teleconsult/start", teleconsult_handlers::start_session)
+            .post_at("/:id/teleconsult/end", teleconsult_handlers::end_session)
+            .post_at("/:id/review", review_handlers::create_review)
+            .post_at("/series", create_appointment_series)
+            .get_at("/series/:id", get_appointment_series)
+            .put_at("/series/:id", update_appointment_series)
+            .post_at("/series/:id/cancel", cancel_appointment_series)
+            .get_at("/waitlist", waitlist_handlers::get_waitlist)
+            .post_at("/waitlist", waitlist_handlers::join_waitlist)
+            .delete_at("/waitlist/:id", waitlist_handlers::cancel_waitlist_entry)
+            .post_at("/waitlist/:id/confirm", waitlist_handlers::confirm_waitlist_hold),
+        // Master data
+        crud("/services", "Services")
+            .list(service_handlers::get_services).create(service_handlers::create_service)
+            .get(service_handlers::get_service).update(service_handlers::update_service).delete(service_handlers::delete_service),
+        crud("/insurances", "Insurances")
+            .list(insurance_handlers::get_insurances).create(insurance_handlers::create_insurance)
+            .get(insurance_handlers::get_insurance).update(insurance_handlers::update_insurance).delete(insurance_handlers::delete_insurance)
+            .get_at("/:id/versions", insurance_handlers::get_insurance_versions),
+        crud("/child-codes", "Child codes")
+            .list(child_code_handlers::get_child_codes).create(child_code_handlers::create_child_code)
+            .get(child_code_handlers::get_child_code).update(child_code_handlers::update_child_code).delete(child_code_handlers::delete_child_code),
+        crud("/codes", "Codes")
+            .list(code_handlers::get_codes).create(code_handlers::create_code)
+            .get(code_handlers::get_code).update(code_handlers::update_code).delete(code_handlers::delete_code)
+            .post_at("/validate", code_handlers::validate_codes)
+            .post_at("/:id/children/bulk", child_code_handlers::bulk_link_child_codes),
+        crud("/regions", "Regions")
+            .list(region_handlers::Regions::list).create(region_handlers::Regions::create)
+            .get(region_handlers::Regions::get).update(region_handlers::Regions::update).delete(region_handlers::Regions::delete)
+            .get_at("/provinsi/:provinsi", region_handlers::get_regions_by_provinsi)
+            .get_at("/kota/:kota", region_handlers::get_regions_by_kota)
+            .get_at("/kecamatan/:kecamatan", region_handlers::get_regions_by_kecamatan)
+            .get_at("/code/:code", region_handlers::get_region_by_code),
+        crud("/interpretations", "Interpretations")
+            .list(interpretation_handlers::Interpretations::list).create(interpretation_handlers::Interpretations::create)
+            .get(interpretation_handlers::Interpretations::get).update(interpretation_handlers::Interpretations::update).delete(interpretation_handlers::Interpretations::delete)
+            .get_at("/code/:code", interpretation_handlers::get_interpretation_by_code)
+            .get_at("/coding/:coding_code", interpretation_handlers::get_interpretations_by_coding_code)
+            .get_at("/find/:code/:coding_code", interpretation_handlers::get_interpretation_by_code_and_coding_code),
+    ];
+
+    #[cfg(feature = "s3")]
+    resources.extend([
+        crud("/files", "Files")
+            .list(file_handlers::get_files).create(file_handlers::create_file)
+            .get(file_handlers::get_file).delete(file_handlers::delete_file)
+            .get_at("/:id/content", file_handlers::get_file_content)
+            .put_at("/:id/content", file_handlers::replace_file_content)
+            .get_at("/:id/versions", file_handlers::get_file_versions)
+            .get_at("/:id/versions/:version/content", file_handlers::get_file_version_content)
+            .post_at("/:id/versions/:version/restore", file_handlers::restore_file_version)
+            .post_at("/:id/share", file_handlers::create_file_share)
+            .delete_at("/:id/share/:share_id", file_handlers::revoke_file_share),
+        // Scheduled reports are delivered as stored files
+        crud("/admin/report-schedules", "Report schedules").admin()
+            .list(report_handlers::get_report_schedules).create(report_handlers::create_report_schedule)
+            .get(report_handlers::get_report_schedule).update(report_handlers::update_report_schedule).delete(report_handlers::delete_report_schedule)
+            .post_at("/:id/run", report_handlers::run_report_schedule),
+    ]);
+    #[cfg(feature = "kits")]
+    resources.extend([
+        crud("/kits", "Kits")
+            .list(kit_handlers::get_kits).create(kit_handlers::create_kit)
+            .get(kit_handlers::get_kit).update(kit_handlers::update_kit).delete(kit_handlers::delete_kit)
+            // Distributor operations map
+            .get_at("/nearby", kit_handlers::get_nearby_kits)
+            .get_at("/map-clusters", kit_handlers::get_kit_map_clusters)
+            // Kit-facing endpoints address kits by code
+            .post_at("/:id/heartbeat", kit_handlers::kit_heartbeat)
+            .get_at("/:id/firmware/latest", firmware_handlers::get_latest_firmware_for_kit)
+            .get_at("/:id/usage", usage_handlers::get_kit_usage),
+        crud("/admin/firmware", "Firmware").admin()
+            .list(firmware_handlers::get_firmware_releases).create(firmware_handlers::create_firmware)
+            .get(firmware_handlers::get_firmware).update(firmware_handlers::update_firmware).delete(firmware_handlers::delete_firmware),
+    ]);
+    #[cfg(feature = "billing")]
+    resources.extend([
+        // Versioned price lists; billing prices come from the active one
+        crud("/price-lists", "Price lists")
+            .list(price_list_handlers::get_price_lists).create(price_list_handlers::create_price_list)
+            .get(price_list_handlers::get_price_list).update(price_list_handlers::update_price_list).delete(price_list_handlers::delete_price_list)
+            .get_at("/active", price_list_handlers::get_active_price_list)
+            .post_at("/quote", price_list_handlers::quote_prices)
+            .post_at("/:id/publish", price_list_handlers::publish_price_list),
+        crud("/invoices", "Invoices")
+            .list(invoice_handlers::get_invoices).create(invoice_handlers::create_invoice).get(invoice_handlers::get_invoice)
+            .get_at("/:id/payments", payment_handlers::get_invoice_payments)
+            .post_at("/:id/payments", payment_handlers::record_payment)
+            .get_at("/:id/payment-links", payment_handlers::get_payment_links)
+            .post_at("/:id/payment-links", payment_handlers::create_payment_link),
+        // Employers billed for their employees' care
+        crud("/corporate-clients", "Corporate clients")
+            .list(corporate_client_handlers::get_corporate_clients).create(corporate_client_handlers::create_corporate_client)
+            .get(corporate_client_handlers::get_corporate_client).update(corporate_client_handlers::update_corporate_client)
+            .delete(corporate_client_handlers::delete_corporate_client)
+            .get_at("/:id/employees", corporate_client_handlers::get_corporate_employees)
+            .post_at("/:id/employees", corporate_client_handlers::add_corporate_employee)
+            .put_at("/:id/employees/:employee_id", corporate_client_handlers::update_corporate_employee)
+            .delete_at("/:id/employees/:employee_id", corporate_client_handlers::remove_corporate_employee)
+            .get_at("/:id/statements", corporate_client_handlers::get_corporate_statements)
+            .post_at("/:id/statements", corporate_client_handlers::generate_corporate_statement)
+            .get_at("/:id/statements/:month", corporate_client_handlers::get_corporate_statement),
+        crud("/cashier-shifts", "Cashier shifts")
+            .list(payment_handlers::get_shifts).create(payment_handlers::open_shift).get(payment_handlers::get_shift)
+            .post_at("/:id/close", payment_handlers::close_shift),
+    ]);
+
+    resources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn resources_register_each_operation_once() {
+        let mut seen = HashSet::new();
+        for resource in resources() {
+            for operation in resource.operations() {
+                assert!(seen.insert((operation.path.clone(), operation.method)), "{} {} registered twice", operation.method, operation.path);
+            }
+        }
+        assert!(seen.contains(&("/doctors/{id}/reviews".to_string(), "get")));
+    }
+}
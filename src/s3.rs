@@ -1,42 +1,117 @@
+//! S3 storage backend.
+//!
+//! Used when `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY` are set, with `AWS_BUCKET`,
+//! `AWS_DEFAULT_REGION`, `AWS_ENDPOINT` and `AWS_USE_PATH_STYLE_ENDPOINT` for the bucket and
+//! S3-compatible services. The client is built on first use, so a bad configuration shows
+//! up in the startup check of `crate::storage` rather than stopping the server.
+
+use std::env;
+use std::sync::OnceLock;
 use aws_sdk_s3::Client;
 use aws_sdk_s3::config::Builder;
-use std::env;
+use futures_util::future::BoxFuture;
+use crate::storage::{StorageBackend, StoredObject};
+
+pub const DEFAULT_BUCKET: &str = "atm-sehat";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct S3Config {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub bucket: String,
+    pub region: String,
+    pub endpoint: Option<String>,
+    pub path_style: bool,
+}
+
+impl S3Config {
+    /// `None` without credentials
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Some(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            bucket: var("AWS_BUCKET").unwrap_or_else(|| DEFAULT_BUCKET.to_string()),
+            region: var("AWS_DEFAULT_REGION").unwrap_or_else(|| "idn".to_string()),
+            endpoint: var("AWS_ENDPOINT"),
+            // Path-style addressing for S3-compatible services (NEO, etc.)
+            path_style: var("AWS_USE_PATH_STYLE_ENDPOINT").is_some_and(|v| v == "true"),
+        })
+    }
 
-pub async fn init_s3_client() -> Result<Client, String> {
-    // Load AWS credentials from environment
-    let credentials = aws_credential_types::Credentials::new(
-        env::var("AWS_ACCESS_KEY_ID").unwrap_or_default(),
-        env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default(),
-        None,
-        None,
-        "manual",
-    );
-
-    let region = aws_sdk_s3::config::Region::new(
-        env::var("AWS_DEFAULT_REGION").unwrap_or_else(|_| "idn".to_string()),
-    );
-
-    let mut builder = Builder::new()
-        .region(region)
-        .credentials_provider(credentials)
-        .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest());
-
-    // If custom endpoint is provided (for S3-compatible services)
-    if let Ok(endpoint) = env::var("AWS_ENDPOINT") {
-        builder = builder.endpoint_url(endpoint);
-        
-        // Use path style endpoint if specified (for NEO, etc.)
-        if env::var("AWS_USE_PATH_STYLE_ENDPOINT")
-            .map(|v| v == "true")
-            .unwrap_or(false)
-        {
-            builder = builder.force_path_style(true);
+    fn client(&self) -> Client {
+        let credentials = aws_credential_types::Credentials::new(
+            &self.access_key_id,
+            &self.secret_access_key,
+            None,
+            None,
+            "manual",
+        );
+
+        let mut builder = Builder::new()
+            .region(aws_sdk_s3::config::Region::new(self.region.clone()))
+            .credentials_provider(credentials)
+            .behavior_version(aws_sdk_s3::config::BehaviorVersion::latest());
+        if let Some(endpoint) = &self.endpoint {
+            builder = builder.endpoint_url(endpoint).force_path_style(self.path_style);
         }
+
+        Client::from_conf(builder.build())
     }
+}
 
-    Ok(Client::from_conf(builder.build()))
+/// S3 backend whose client is built on first use
+#[derive(Debug)]
+pub struct S3Backend {
+    config: S3Config,
+    client: OnceLock<Client>,
 }
 
+impl S3Backend {
+    pub fn new(config: S3Config) -> Self {
+        Self { config, client: OnceLock::new() }
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.config.bucket
+    }
+
+    pub fn client(&self) -> &Client {
+        self.client.get_or_init(|| self.config.client())
+    }
+}
+
+impl StorageBackend for S3Backend {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn put<'a>(&'a self, bucket: &'a str, key: &'a str, body: Vec<u8>) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move { upload_file_to_s3(self.client(), bucket, key, body).await.map(Some) })
+    }
+
+    fn delete<'a>(&'a self, bucket: &'a str, key: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(delete_file_from_s3(self.client(), bucket, key))
+    }
+
+    fn read<'a>(&'a self, bucket: &'a str, key: &'a str) -> Option<BoxFuture<'a, Result<Vec<u8>, String>>> {
+        Some(Box::pin(download_file_from_s3(self.client(), bucket, key)))
+    }
+
+    fn list<'a>(&'a self, bucket: &'a str, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, String>> {
+        Box::pin(list_objects_in_s3(self.client(), bucket, prefix))
+    }
+
+    fn check<'a>(&'a self, bucket: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            self.client().head_bucket().bucket(bucket).send().await
+                .map(|_| ())
+                .map_err(|e| aws_sdk_s3::error::DisplayErrorContext(e).to_string())
+        })
+    }
+}
+
+#[tracing::instrument(skip_all, err, fields(otel.kind = "client", rpc.system = "aws-api", rpc.service = "S3", rpc.method = "PutObject", aws.s3.bucket = bucket, aws.s3.key = key))]
 pub async fn upload_file_to_s3(
     client: &Client,
     bucket: &str,
@@ -60,18 +135,10 @@ pub async fn upload_file_to_s3(
         )
     });
 
-    let url = if env::var("AWS_USE_PATH_STYLE_ENDPOINT")
-        .map(|v| v == "true")
-        .unwrap_or(false)
-    {
-        format!("{}/{}/{}", endpoint, bucket, key)
-    } else {
-        format!("{}/{}/{}", endpoint, bucket, key)
-    };
-
-    Ok(url)
+    Ok(format!("{}/{}/{}", endpoint, bucket, key))
 }
 
+#[tracing::instrument(skip_all, err, fields(otel.kind = "client", rpc.system = "aws-api", rpc.service = "S3", rpc.method = "DeleteObject", aws.s3.bucket = bucket, aws.s3.key = key))]
 pub async fn delete_file_from_s3(
     client: &Client,
     bucket: &str,
@@ -88,7 +155,83 @@ pub async fn delete_file_from_s3(
     Ok(())
 }
 
+#[tracing::instrument(skip_all, err, fields(otel.kind = "client", rpc.system = "aws-api", rpc.service = "S3", rpc.method = "GetObject", aws.s3.bucket = bucket, aws.s3.key = key))]
+pub async fn download_file_from_s3(
+    client: &Client,
+    bucket: &str,
+    key: &str,
+) -> Result<Vec<u8>, String> {
+    let object = client
+        .get_object()
+        .bucket(bucket)
+        .key(key)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to download from S3: {}", e))?;
+
+    let body = object
+        .body
+        .collect()
+        .await
+        .map_err(|e| format!("Failed to read S3 object: {}", e))?;
+
+    Ok(body.into_bytes().to_vec())
+}
+
+#[tracing::instrument(skip_all, err, fields(otel.kind = "client", rpc.system = "aws-api", rpc.service = "S3", rpc.method = "ListObjectsV2", aws.s3.bucket = bucket, aws.s3.prefix = prefix))]
+pub async fn list_objects_in_s3(
+    client: &Client,
+    bucket: &str,
+    prefix: &str,
+) -> Result<Vec<StoredObject>, String> {
+    let mut objects = Vec::new();
+    let mut continuation: Option<String> = None;
+
+    loop {
+        let page = client
+            .list_objects_v2()
+            .bucket(bucket)
+            .prefix(prefix)
+            .set_continuation_token(continuation.take())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to list S3 objects: {}", e))?;
+
+        objects.extend(page.contents().iter().filter_map(|object| {
+            Some(StoredObject {
+                key: object.key()?.to_string(),
+                size: object.size().unwrap_or(0).max(0) as u64,
+                modified: object.last_modified().and_then(|t| chrono::DateTime::from_timestamp(t.secs(), 0)),
+            })
+        }));
+
+        match page.next_continuation_token() {
+            Some(token) if page.is_truncated().unwrap_or(false) => continuation = Some(token.to_string()),
+            _ => return Ok(objects),
+        }
+    }
+}
+
 pub fn generate_s3_key(filename: &str) -> String {
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
     format!("files/{}_{}", timestamp, filename)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_client_once() {
+        let backend = S3Backend::new(S3Config {
+            access_key_id: "key".to_string(),
+            secret_access_key: "secret".to_string(),
+            bucket: "rme".to_string(),
+            region: "idn".to_string(),
+            endpoint: Some("http://localhost:9000".to_string()),
+            path_style: true,
+        });
+        assert_eq!(backend.bucket(), "rme");
+        assert!(std::ptr::eq(backend.client(), backend.client()));
+    }
+}
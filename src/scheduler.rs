@@ -0,0 +1,212 @@
+//! Scheduled jobs on exactly one instance.
+//!
+//! Every replica spawns the same sweeps and nightly runs, so each instance also takes part in
+//! a lease-based election: a heartbeat every third of `SCHEDULER_LEASE_SECONDS` (default 30)
+//! renews the `scheduler` lease in `scheduler_leases` for its holder, or takes it over once it
+//! has lapsed. Jobs go through `Scheduler::run`, which skips them unless this instance holds
+//! the lease and records each run in `scheduled_job_runs`. An instance stops counting itself
+//! leader one lease period after its last successful heartbeat, which is when the others may
+//! take over; a TTL index removes lapsed leases. Instances are named by
+//! `SCHEDULER_INSTANCE_ID`, or the host name and process id. `GET /admin/scheduler` shows the
+//! lease, the answering instance and the last run of each job.
+//!
+//! Work triggered by events stays on the instance that published them; the outbox relay
+//! claims its entries one by one and needs no leader.
+
+use std::env;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use mongodb::{bson::{oid::ObjectId, DateTime}, Database};
+use crate::dto::scheduler::SchedulerStatusResponse;
+use crate::models::ScheduledJobRun;
+use crate::repository::SchedulerRepository;
+use crate::status::ScheduledRunStatus;
+
+pub const DEFAULT_LEASE_SECONDS: u64 = 30;
+/// Shorter leases would expire between heartbeats slowed by a busy database
+const MIN_LEASE_SECONDS: u64 = 3;
+const LEASE_NAME: &str = "scheduler";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulerConfig {
+    pub lease: Duration,
+    /// Name of this instance in the lease; generated when unset
+    pub instance_id: Option<String>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self { lease: Duration::from_secs(DEFAULT_LEASE_SECONDS), instance_id: None }
+    }
+}
+
+impl SchedulerConfig {
+    pub fn from_env() -> Self {
+        let seconds = env::var("SCHEDULER_LEASE_SECONDS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_LEASE_SECONDS)
+            .max(MIN_LEASE_SECONDS);
+        let instance_id = env::var("SCHEDULER_INSTANCE_ID").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Self { lease: Duration::from_secs(seconds), instance_id }
+    }
+}
+
+/// Host name and process id, which tell replicas apart in containers and on shared hosts alike
+fn default_instance_id() -> String {
+    match env::var("HOSTNAME").ok().filter(|host| !host.trim().is_empty()) {
+        Some(host) => format!("{}-{}", host.trim(), std::process::id()),
+        None => ObjectId::new().to_hex(),
+    }
+}
+
+/// Until when this instance may act as leader, given its heartbeats
+#[derive(Debug, Default)]
+struct Leadership {
+    valid_until: Option<Instant>,
+}
+
+impl Leadership {
+    /// Apply a heartbeat sent at `sent`; returns whether this instance was leader before
+    fn update(&mut self, acquired: bool, sent: Instant, lease: Duration) -> bool {
+        let was_leader = self.holds(sent);
+        // Counted from when the heartbeat was sent, so it never outlasts the stored lease
+        self.valid_until = acquired.then_some(sent + lease);
+        was_leader
+    }
+
+    fn holds(&self, now: Instant) -> bool {
+        self.valid_until.is_some_and(|until| now < until)
+    }
+}
+
+pub struct Scheduler {
+    repo: SchedulerRepository,
+    instance_id: String,
+    lease: Duration,
+    leadership: Mutex<Leadership>,
+}
+
+impl Scheduler {
+    pub fn new(db: Database, config: &SchedulerConfig) -> Self {
+        Self {
+            repo: SchedulerRepository::new(db),
+            instance_id: config.instance_id.clone().unwrap_or_else(default_instance_id),
+            lease: config.lease,
+            leadership: Mutex::new(Leadership::default()),
+        }
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leadership.lock().unwrap_or_else(|e| e.into_inner()).holds(Instant::now())
+    }
+
+    /// Create the TTL index and run for the lease, so jobs due at startup find a leader
+    pub async fn start(&self) {
+        if let Err(e) = self.repo.ensure_ttl_index().await {
+            tracing::error!(error = %e, "Creating the scheduler lease index failed");
+        }
+        self.heartbeat().await;
+    }
+
+    /// Renew the lease, or take it over when it lapsed; a failed heartbeat keeps the leadership
+    /// it already had until that runs out
+    pub async fn heartbeat(&self) {
+        let sent = Instant::now();
+        let now = DateTime::now();
+        let expires_at = DateTime::from_millis(now.timestamp_millis() + self.lease.as_millis() as i64);
+        let acquired = match self.repo.acquire(LEASE_NAME, &self.instance_id, now, expires_at).await {
+            Ok(acquired) => acquired,
+            Err(e) => {
+                tracing::error!(error = %e, "Scheduler heartbeat failed");
+                return;
+            }
+        };
+
+        let was_leader = self.leadership.lock().unwrap_or_else(|e| e.into_inner()).update(acquired, sent, self.lease);
+        match (was_leader, acquired) {
+            (false, true) => tracing::info!(instance_id = %self.instance_id, "This instance now runs scheduled jobs"),
+            (true, false) => tracing::info!(instance_id = %self.instance_id, "This instance no longer runs scheduled jobs"),
+            _ => {}
+        }
+    }
+
+    /// Run `work` as the scheduled job `job` when this instance is the leader, logging and
+    /// recording how it went; on other instances it is skipped
+    pub async fn run<F>(&self, job: &str, work: F)
+    where
+        F: Future<Output = Result<(), String>>,
+    {
+        if !self.is_leader() {
+            return;
+        }
+        let started_at = DateTime::now();
+        let result = work.await;
+        if let Err(e) = &result {
+            tracing::error!(job = %job, error = %e, "Scheduled job failed");
+        }
+
+        let run = ScheduledJobRun {
+            job: job.to_string(),
+            instance: self.instance_id.clone(),
+            started_at,
+            finished_at: DateTime::now(),
+            status: if result.is_ok() { ScheduledRunStatus::Succeeded } else { ScheduledRunStatus::Failed },
+            error: result.err(),
+        };
+        if let Err(e) = self.repo.record_run(&run).await {
+            tracing::error!(job = %job, error = %e, "Recording a scheduled job run failed");
+        }
+    }
+
+    /// The lease as stored, this instance and the last run of each job
+    pub async fn status(&self) -> Result<SchedulerStatusResponse, String> {
+        Ok(SchedulerStatusResponse {
+            instance_id: self.instance_id.clone(),
+            is_leader: self.is_leader(),
+            lease_seconds: self.lease.as_secs(),
+            lease: self.repo.find_lease(LEASE_NAME).await?,
+            jobs: self.repo.find_runs().await?,
+        })
+    }
+}
+
+/// Spawn the heartbeat keeping this instance in the election
+pub fn spawn_heartbeat(scheduler: Arc<Scheduler>) {
+    tokio::spawn(async move {
+        let mut beat = tokio::time::interval(scheduler.lease / 3);
+        beat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            beat.tick().await;
+            scheduler.heartbeat().await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leadership_lasts_one_lease_from_the_last_heartbeat() {
+        let lease = Duration::from_secs(30);
+        let start = Instant::now();
+        let mut leadership = Leadership::default();
+        assert!(!leadership.holds(start));
+
+        assert!(!leadership.update(true, start, lease));
+        assert!(leadership.holds(start + Duration::from_secs(29)));
+        assert!(!leadership.holds(start + lease));
+
+        assert!(leadership.update(true, start + Duration::from_secs(10), lease));
+        assert!(leadership.holds(start + Duration::from_secs(39)));
+
+        assert!(leadership.update(false, start + Duration::from_secs(20), lease));
+        assert!(!leadership.holds(start + Duration::from_secs(21)));
+    }
+}
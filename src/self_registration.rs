@@ -0,0 +1,149 @@
+//! Self-registration of walk-in patients at kiosks.
+//!
+//! `POST /public/patients/register` takes a NIK, name and phone. Without `otp` it sends a
+//! code to the phone; sent again with the code it creates a medical record marked
+//! `pending`, which staff list at `GET /patients/registrations` and confirm at
+//! `POST /patients/:id/registration/confirm` once they have seen the patient's ID card.
+//! The only identity check on the way in is the NIK itself: its date of birth and sex
+//! (day of birth plus 40 for women) must be a real date, and become the record's `dob` and
+//! `gender`. A NIK that is already registered is refused with 409 after the code is checked,
+//! so the endpoint cannot be used to look up NIKs without a phone to receive codes.
+//!
+//! Besides the per-phone limits of the codes themselves (see `crate::otp`), each client
+//! address may call the endpoint `SELF_REGISTRATION_MAX_PER_HOUR` (default 20, `0` for no
+//! limit) times per hour; more get 429. The count is shared between replicas through Redis when
+//! configured, see `crate::shared_store`. Behind a reverse proxy, set
+//! `SELF_REGISTRATION_TRUST_FORWARDED_FOR=true` to count the first `X-Forwarded-For` address
+//! instead of the proxy's.
+
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use axum::http::HeaderMap;
+use chrono::{Datelike, NaiveDate};
+use crate::shared_store::SharedStore;
+use crate::status::Gender;
+
+pub const DEFAULT_MAX_PER_HOUR: u32 = 20;
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelfRegistrationConfig {
+    /// Calls per client address per hour; `0` for no limit
+    pub max_per_hour: u32,
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for SelfRegistrationConfig {
+    fn default() -> Self {
+        Self { max_per_hour: DEFAULT_MAX_PER_HOUR, trust_forwarded_for: false }
+    }
+}
+
+impl SelfRegistrationConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            max_per_hour: env::var("SELF_REGISTRATION_MAX_PER_HOUR")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.max_per_hour),
+            trust_forwarded_for: env::var("SELF_REGISTRATION_TRUST_FORWARDED_FOR")
+                .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes"))
+                .unwrap_or(defaults.trust_forwarded_for),
+        }
+    }
+
+    /// The address a request is counted against
+    pub fn client_key(&self, peer: Option<SocketAddr>, headers: &HeaderMap) -> String {
+        let forwarded = self.trust_forwarded_for.then(|| {
+            headers
+                .get("x-forwarded-for")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(',').next())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        });
+        match (forwarded.flatten(), peer) {
+            (Some(address), _) => address.to_string(),
+            (None, Some(peer)) => peer.ip().to_string(),
+            (None, None) => "unknown".to_string(),
+        }
+    }
+}
+
+/// Date of birth and sex encoded in digits 7-12 of a NIK (`DDMMYY`, women's day plus 40).
+/// Two-digit years after `today`'s are taken as 1900s.
+pub fn birth_from_nik(nik: &str, today: NaiveDate) -> Option<(NaiveDate, Gender)> {
+    let digits = nik.get(6..12).filter(|d| d.chars().all(|c| c.is_ascii_digit()))?;
+    let number = |range: std::ops::Range<usize>| digits[range].parse::<u32>().ok();
+    let (day, month, year) = (number(0..2)?, number(2..4)?, number(4..6)? as i32);
+
+    let (day, gender) = if day > 40 { (day - 40, Gender::Female) } else { (day, Gender::Male) };
+    let century = if year > today.year() % 100 { 1900 } else { 2000 };
+    let dob = NaiveDate::from_ymd_opt(century + year, month, day)?;
+    (dob <= today).then_some((dob, gender))
+}
+
+/// Calls per client address in fixed hourly windows
+pub struct RegistrationLimiter {
+    max_per_hour: u32,
+    store: Arc<SharedStore>,
+}
+
+impl RegistrationLimiter {
+    pub fn new(config: &SelfRegistrationConfig, store: Arc<SharedStore>) -> Self {
+        Self { max_per_hour: config.max_per_hour, store }
+    }
+
+    /// Count a call from `key`; `Err` with the seconds until the window resets when over the limit
+    pub async fn check(&self, key: &str, now: Instant) -> Result<(), u64> {
+        if self.max_per_hour == 0 {
+            return Ok(());
+        }
+        let (calls, reset) = self.store.hit(&format!("registrations:{}", key), WINDOW, now).await;
+        if calls > u64::from(self.max_per_hour) {
+            return Err(reset.as_secs().max(1));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_birth_date_and_sex_from_nik() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        assert_eq!(birth_from_nik("3201011505900001", today), Some((NaiveDate::from_ymd_opt(1990, 5, 15).unwrap(), Gender::Male)));
+        assert_eq!(birth_from_nik("3201015501210002", today), Some((NaiveDate::from_ymd_opt(2021, 1, 15).unwrap(), Gender::Female)));
+        // 31 February, and a birth date after today
+        assert_eq!(birth_from_nik("3201013102900001", today), None);
+        assert_eq!(birth_from_nik("3201010104260001", today), None);
+    }
+
+    #[tokio::test]
+    async fn limits_calls_per_client_per_hour() {
+        let config = SelfRegistrationConfig { max_per_hour: 2, trust_forwarded_for: false };
+        let limiter = RegistrationLimiter::new(&config, Arc::new(SharedStore::in_memory()));
+        let start = Instant::now();
+        assert!(limiter.check("10.0.0.1", start).await.is_ok());
+        assert!(limiter.check("10.0.0.1", start).await.is_ok());
+        assert_eq!(limiter.check("10.0.0.1", start + Duration::from_secs(600)).await, Err(3000));
+        assert!(limiter.check("10.0.0.2", start).await.is_ok());
+        assert!(limiter.check("10.0.0.1", start + WINDOW).await.is_ok());
+    }
+
+    #[test]
+    fn counts_forwarded_addresses_only_when_trusted() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        let peer = Some("10.0.0.1:443".parse().unwrap());
+
+        assert_eq!(SelfRegistrationConfig::default().client_key(peer, &headers), "10.0.0.1");
+        let trusting = SelfRegistrationConfig { trust_forwarded_for: true, ..Default::default() };
+        assert_eq!(trusting.client_key(peer, &headers), "203.0.113.7");
+    }
+}
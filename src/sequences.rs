@@ -0,0 +1,77 @@
+//! Human-readable sequential numbers such as `RM-2026-000123`.
+//!
+//! Each number comes from a counter document in `counters`, taken with one
+//! `findOneAndUpdate` (`$inc` with upsert), so concurrent requests never receive the
+//! same value. A counter is kept per sequence and year, and per organization where the
+//! caller passes one, so numbering restarts at 1 every year. A number taken by a request
+//! that then fails is not reused: numbers are unique, not gapless.
+
+use mongodb::Database;
+use crate::repository::CounterRepository;
+
+/// A kind of number and how it is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sequence {
+    /// Part of the counter key
+    pub name: &'static str,
+    pub prefix: &'static str,
+    /// Digits the counter is zero-padded to
+    pub width: usize,
+}
+
+/// Patient medical record numbers (NRME)
+pub const MEDICAL_RECORD: Sequence = Sequence { name: "nrme", prefix: "RM", width: 6 };
+pub const INVOICE: Sequence = Sequence { name: "invoice", prefix: "INV", width: 6 };
+/// Monthly statements of corporate clients
+pub const CORPORATE_STATEMENT: Sequence = Sequence { name: "corporate_statement", prefix: "STM", width: 6 };
+
+impl Sequence {
+    /// Counter `_id`: `name:year`, or `name:organization:year` within an organization
+    pub fn counter_key(&self, organization: Option<&str>, year: i32) -> String {
+        match organization {
+            Some(organization) => format!("{}:{}:{}", self.name, organization, year),
+            None => format!("{}:{}", self.name, year),
+        }
+    }
+
+    /// `PREFIX-YEAR-000123`; values wider than `width` are written in full
+    pub fn format(&self, year: i32, value: i64) -> String {
+        format!("{}-{}-{:0width$}", self.prefix, year, value, width = self.width)
+    }
+}
+
+/// Allocates numbers of any `Sequence`; shared by the services that issue them.
+pub struct SequenceGenerator {
+    counters: CounterRepository,
+}
+
+impl SequenceGenerator {
+    pub fn new(db: Database) -> Self {
+        Self { counters: CounterRepository::new(db) }
+    }
+
+    /// Take the next number of `sequence` for `year`, optionally within one organization.
+    pub async fn next(&self, sequence: &Sequence, organization: Option<&str>, year: i32) -> Result<String, String> {
+        let value = self.counters.next(&sequence.counter_key(organization, year)).await?;
+        Ok(sequence.format(year, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_scoped_by_organization_and_year() {
+        assert_eq!(INVOICE.counter_key(Some("org1"), 2026), "invoice:org1:2026");
+        assert_eq!(MEDICAL_RECORD.counter_key(None, 2026), "nrme:2026");
+        assert_ne!(INVOICE.counter_key(None, 2026), INVOICE.counter_key(None, 2027));
+    }
+
+    #[test]
+    fn numbers_are_zero_padded() {
+        assert_eq!(MEDICAL_RECORD.format(2024, 123), "RM-2024-000123");
+        assert_eq!(INVOICE.format(2026, 1), "INV-2026-000001");
+        assert_eq!(INVOICE.format(2026, 1_234_567), "INV-2026-1234567");
+    }
+}
@@ -0,0 +1,184 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::admission::{AdmissionQuery, AdmissionResponse, AdmitRequest, BedTransferResponse, DischargeRequest, TransferRequest};
+use crate::models::{Admission, BedTransfer, MedicalRecord};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{AdmissionRepository, BedRepository};
+use crate::status::AdmissionStatus;
+
+pub struct AdmissionService {
+    admissions: AdmissionRepository,
+    beds: BedRepository,
+    references: ReferenceChecker,
+}
+
+fn parse_bed_id(value: &str) -> Result<ObjectId, (StatusCode, String)> {
+    ObjectId::parse_str(value.trim())
+        .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, format!("bed_id: '{}' is not a valid Bed ID", value)))
+}
+
+impl AdmissionService {
+    pub fn new(admissions: AdmissionRepository, beds: BedRepository, references: ReferenceChecker) -> Self {
+        Self { admissions, beds, references }
+    }
+
+    fn map_to_response(admission: Admission) -> AdmissionResponse {
+        AdmissionResponse {
+            id: admission.id.map(|id| id.to_hex()).unwrap_or_default(),
+            medical_record_id: admission.patient_id.to_hex(),
+            bed_id: admission.bed_id,
+            ward_id: admission.ward_id,
+            status: admission.status,
+            admitted_by: admission.admitted_by,
+            admitted_at: crate::datetime::to_rfc3339(admission.admitted_at),
+            transfers: admission.transfers.into_iter().map(|t| BedTransferResponse {
+                from_bed_id: t.from_bed_id,
+                to_bed_id: t.to_bed_id,
+                transferred_by: t.transferred_by,
+                transferred_at: crate::datetime::to_rfc3339(t.transferred_at),
+            }).collect(),
+            discharged_by: admission.discharged_by,
+            discharged_at: crate::datetime::to_rfc3339_opt(admission.discharged_at),
+            discharge_note: admission.discharge_note,
+        }
+    }
+
+    /// Occupy `bed_id` for the admission, 409 when the bed is taken or not in service
+    async fn claim_bed(&self, bed_id: ObjectId, patient_id: &str, admission_id: &str) -> Result<crate::models::Bed, (StatusCode, String)> {
+        if let Some(bed) = self.beds.claim(bed_id, patient_id, admission_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Ok(bed);
+        }
+        match self.beds.find_by_id(bed_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(bed) => Err((StatusCode::CONFLICT, format!("Bed {} is {}", bed.code, bed.status))),
+            None => Err((StatusCode::UNPROCESSABLE_ENTITY, format!("bed_id: Bed {} does not exist", bed_id.to_hex()))),
+        }
+    }
+
+    /// Admit a patient to an available bed. A patient holds one active admission at a time.
+    pub async fn admit(&self, request: AdmitRequest, admitted_by: &str) -> Result<AdmissionResponse, (StatusCode, String)> {
+        let patient = Ref::<MedicalRecord>::parse_field("medical_record_id", &request.medical_record_id)?;
+        let bed_id = parse_bed_id(&request.bed_id)?;
+        self.references.ensure_exist(&[patient.check("medical_record_id")]).await?;
+
+        let active = self.admissions.find_active_by_patient(&patient.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if active.is_some() {
+            return Err((StatusCode::CONFLICT, "Patient is already admitted".to_string()));
+        }
+
+        let id = ObjectId::new();
+        let bed = self.claim_bed(bed_id, &patient.to_hex(), &id.to_hex()).await?;
+
+        let admission = Admission {
+            id: Some(id),
+            patient_id: patient,
+            bed_id: bed_id.to_hex(),
+            ward_id: bed.ward_id,
+            status: AdmissionStatus::Admitted,
+            admitted_by: admitted_by.to_string(),
+            admitted_at: DateTime::now(),
+            transfers: Vec::new(),
+            discharged_by: None,
+            discharged_at: None,
+            discharge_note: None,
+        };
+        match self.admissions.insert(admission).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => {
+                let _ = self.beds.release(bed_id, &id.to_hex()).await;
+                Err((StatusCode::INTERNAL_SERVER_ERROR, e))
+            }
+        }
+    }
+
+    pub async fn list(&self, query: AdmissionQuery, pagination: PaginationParams) -> Result<(Vec<AdmissionResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.admissions.find_paginated(query.status.as_ref(), query.ward_id.as_deref(), &pagination).await {
+            Ok((admissions, total)) => {
+                let responses = admissions.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<AdmissionResponse>, (StatusCode, String)> {
+        match self.admissions.find_by_id(id).await {
+            Ok(admission) => Ok(admission.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    async fn find_active(&self, id: ObjectId) -> Result<Admission, (StatusCode, String)> {
+        let admission = self.admissions.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Admission not found".to_string()))?;
+        if admission.status != AdmissionStatus::Admitted {
+            return Err((StatusCode::CONFLICT, format!("Admission is already {}", admission.status)));
+        }
+        Ok(admission)
+    }
+
+    /// Move the patient to another available bed, freeing the current one.
+    pub async fn transfer(&self, id: ObjectId, request: TransferRequest, transferred_by: &str) -> Result<AdmissionResponse, (StatusCode, String)> {
+        let admission = self.find_active(id).await?;
+        let to_bed = parse_bed_id(&request.bed_id)?;
+        if admission.bed_id == to_bed.to_hex() {
+            return Err((StatusCode::CONFLICT, "Patient is already in this bed".to_string()));
+        }
+
+        let admission_id = id.to_hex();
+        let bed = self.claim_bed(to_bed, &admission.patient_id.to_hex(), &admission_id).await?;
+
+        let transfer = BedTransfer {
+            from_bed_id: admission.bed_id.clone(),
+            to_bed_id: to_bed.to_hex(),
+            transferred_by: transferred_by.to_string(),
+            transferred_at: DateTime::now(),
+        };
+        let transfer = mongodb::bson::to_bson(&transfer).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        let update = doc! {
+            "$set": { "bedId": to_bed.to_hex(), "wardId": &bed.ward_id },
+            "$push": { "transfers": transfer },
+        };
+
+        let updated = match self.admissions.update_active(id, &admission.bed_id, update).await {
+            Ok(Some(updated)) => updated,
+            outcome => {
+                let _ = self.beds.release(to_bed, &admission_id).await;
+                return Err(match outcome {
+                    Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+                    _ => (StatusCode::CONFLICT, "Admission changed concurrently".to_string()),
+                });
+            }
+        };
+
+        if let Ok(from_bed) = ObjectId::parse_str(&admission.bed_id) {
+            self.beds.release(from_bed, &admission_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        }
+        Ok(Self::map_to_response(updated))
+    }
+
+    /// Close the admission and free its bed.
+    pub async fn discharge(&self, id: ObjectId, request: DischargeRequest, discharged_by: &str) -> Result<AdmissionResponse, (StatusCode, String)> {
+        let admission = self.find_active(id).await?;
+
+        let mut set = doc! {
+            "status": AdmissionStatus::Discharged,
+            "dischargedBy": discharged_by,
+            "dischargedAt": DateTime::now(),
+        };
+        if let Some(note) = request.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+            set.insert("dischargeNote", note);
+        }
+
+        let updated = self.admissions.update_active(id, &admission.bed_id, doc! { "$set": set }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Admission changed concurrently".to_string()))?;
+
+        if let Ok(bed_id) = ObjectId::parse_str(&admission.bed_id) {
+            self.beds.release(bed_id, &id.to_hex()).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        }
+        Ok(Self::map_to_response(updated))
+    }
+}
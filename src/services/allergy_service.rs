@@ -0,0 +1,101 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::allergy::{self, AllergyCheckMode};
+use crate::dto::allergy::{AllergyCheckResponse, AllergyResponse, CreateAllergyRequest, UpdateAllergyRequest};
+use crate::middleware::AuthUser;
+use crate::models::{Allergy, MedicalRecord};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{AllergyRepository, MedicineRepository};
+
+pub struct AllergyService {
+    allergies: AllergyRepository,
+    medicines: MedicineRepository,
+    references: ReferenceChecker,
+    mode: AllergyCheckMode,
+}
+
+impl AllergyService {
+    pub fn new(allergies: AllergyRepository, medicines: MedicineRepository, references: ReferenceChecker, mode: AllergyCheckMode) -> Self {
+        Self { allergies, medicines, references, mode }
+    }
+
+    fn map_to_response(allergy: Allergy) -> AllergyResponse {
+        AllergyResponse {
+            id: allergy.id.map(|id| id.to_hex()).unwrap_or_default(),
+            patient_id: allergy.patient_id.to_hex(),
+            substance_code: allergy.substance_code,
+            substance_display: allergy.substance_display,
+            severity: allergy.severity,
+            reaction: allergy.reaction,
+            recorded_by: allergy.recorded_by,
+            created_at: crate::datetime::to_rfc3339(allergy.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(allergy.updated_at),
+        }
+    }
+
+    pub async fn create(&self, patient_id: ObjectId, user: &AuthUser, request: CreateAllergyRequest) -> Result<AllergyResponse, (StatusCode, String)> {
+        let patient: Ref<MedicalRecord> = Ref::new(patient_id);
+        self.references.ensure_exist(&[patient.check("patient_id")]).await?;
+
+        let allergy = Allergy {
+            id: None,
+            patient_id: patient,
+            substance_code: request.substance_code.trim().to_string(),
+            substance_display: request.substance_display.trim().to_string(),
+            severity: request.severity,
+            reaction: request.reaction.map(|r| r.trim().to_string()).filter(|r| !r.is_empty()),
+            recorded_by: user.id.clone(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.allergies.create(allergy).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, patient_id: ObjectId) -> Result<Vec<AllergyResponse>, (StatusCode, String)> {
+        match self.allergies.find_by_patient(&patient_id.to_hex()).await {
+            Ok(allergies) => Ok(allergies.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn update(&self, patient_id: ObjectId, id: ObjectId, request: UpdateAllergyRequest) -> Result<AllergyResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(code) = request.substance_code { set.insert("substanceCode", code.trim()); }
+        if let Some(display) = request.substance_display { set.insert("substanceDisplay", display.trim()); }
+        if let Some(severity) = request.severity { set.insert("severity", severity); }
+        if let Some(reaction) = request.reaction { set.insert("reaction", reaction.trim()); }
+
+        match self.allergies.update_fields(&patient_id.to_hex(), id, set).await {
+            Ok(Some(updated)) => Ok(Self::map_to_response(updated)),
+            Ok(None) => Err((StatusCode::NOT_FOUND, "Allergy not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn delete(&self, patient_id: ObjectId, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.allergies.delete(&patient_id.to_hex(), id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Conflicts between the patient's allergies and `medicine_ids`, with the configured mode.
+    /// Unknown medicines are a 422 so a typo cannot pass the check unnoticed.
+    pub async fn check(&self, patient_id: ObjectId, medicine_ids: &[String]) -> Result<AllergyCheckResponse, (StatusCode, String)> {
+        let ids = medicine_ids
+            .iter()
+            .map(|id| ObjectId::parse_str(id.trim()).map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, format!("medicine_ids: '{}' is not a valid Medicine ID", id))))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let medicines = self.medicines.find_by_ids(&ids).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if let Some(missing) = ids.iter().find(|id| !medicines.iter().any(|m| m.id == Some(**id))) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("medicine_ids: Medicine {} does not exist", missing.to_hex())));
+        }
+
+        let allergies = self.allergies.find_by_patient(&patient_id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(AllergyCheckResponse { mode: self.mode, conflicts: allergy::find_conflicts(&allergies, &medicines) })
+    }
+}
@@ -0,0 +1,301 @@
+use axum::http::StatusCode;
+use chrono::{Days, NaiveDate};
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::appointment::{
+    AppointmentSeriesResponse, CancelAppointmentSeriesRequest, CreateAppointmentSeriesRequest, SeriesScope,
+    UpdateAppointmentSeriesRequest,
+};
+use crate::models::{Appointment, AppointmentSeries, Doctor, MedicalRecord};
+use crate::recurrence::RecurrenceRule;
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{AppointmentRepository, AppointmentSeriesRepository};
+use crate::services::AppointmentService;
+use crate::status::AppointmentStatus;
+use crate::timezone::ClinicTimezone;
+
+pub const SERIES_ACTIVE: &str = "active";
+pub const SERIES_CANCELLED: &str = "cancelled";
+const DEFAULT_HORIZON_DAYS: u64 = 90;
+const MAX_HORIZON_DAYS: u64 = 365;
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub struct AppointmentSeriesService {
+    series: AppointmentSeriesRepository,
+    appointments: AppointmentRepository,
+    references: ReferenceChecker,
+    timezone: ClinicTimezone,
+}
+
+fn parse_day(value: &str, field: &str) -> Result<NaiveDate, (StatusCode, String)> {
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("{} must be a YYYY-MM-DD date", field)))
+}
+
+fn parse_rule(rrule: &str) -> Result<RecurrenceRule, (StatusCode, String)> {
+    RecurrenceRule::parse(rrule).map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid recurrence rule: {}", e)))
+}
+
+/// Date bounds of the occurrences a scope selects, as `(from, on)` for `find_by_series`.
+fn scope_bounds(scope: SeriesScope, occurrence_date: Option<&str>) -> Result<(Option<String>, Option<String>), (StatusCode, String)> {
+    if scope == SeriesScope::All {
+        return Ok((None, None));
+    }
+    let date = occurrence_date
+        .ok_or((StatusCode::BAD_REQUEST, "occurrence_date is required for the 'this' and 'future' scopes".to_string()))?;
+    let date = parse_day(date, "occurrence_date")?.format(DATE_FORMAT).to_string();
+
+    Ok(match scope {
+        SeriesScope::This => (None, Some(date)),
+        _ => (Some(date), None),
+    })
+}
+
+impl AppointmentSeriesService {
+    pub fn new(series: AppointmentSeriesRepository, appointments: AppointmentRepository, references: ReferenceChecker, timezone: ClinicTimezone) -> Self {
+        Self { series, appointments, references, timezone }
+    }
+
+    fn occurrences(&self, series: &AppointmentSeries, dates: &[NaiveDate]) -> Result<Vec<Appointment>, (StatusCode, String)> {
+        let series_id = series.id.map(|id| id.to_hex());
+        let patient_id = Ref::parse_field("patient_id", &series.patient_id)?;
+        let doctor_id = Ref::parse_field("doctor_id", &series.doctor_id)?;
+        Ok(dates
+            .iter()
+            .map(|date| {
+                let date = date.format(DATE_FORMAT).to_string();
+                let starts_at = self.timezone.to_utc(&date, &series.time).ok().map(crate::datetime::from_chrono);
+                Appointment {
+                    id: Some(ObjectId::new()),
+                    patient_id,
+                    doctor_id,
+                    date,
+                    time: series.time.clone(),
+                    status: AppointmentStatus::Scheduled,
+                    queue_number: None,
+                    checked_in_at: None,
+                    called_at: None,
+                    completed_at: None,
+                    series_id: series_id.clone(),
+                    mode: None,
+                    organization_id: None,
+                    timezone: Some(self.timezone.name().to_string()),
+                    starts_at,
+                    deleted_at: None,
+                    updated_at: None,
+                }
+            })
+            .collect())
+    }
+
+    /// Reject the change with 409 when any slot is already booked for the doctor.
+    async fn ensure_free(&self, doctor_id: &str, slots: Vec<(String, String)>, exclude: &[ObjectId]) -> Result<(), (StatusCode, String)> {
+        let conflicts = self.appointments.find_conflicts(doctor_id, &slots, exclude).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+
+        let taken: Vec<String> = conflicts.iter().map(|a| format!("{} {}", a.date, a.time)).collect();
+        Err((StatusCode::CONFLICT, format!("Slots already booked: {}", taken.join(", "))))
+    }
+
+    async fn build_response(&self, series: AppointmentSeries) -> Result<AppointmentSeriesResponse, (StatusCode, String)> {
+        let id = series.id.map(|id| id.to_hex()).unwrap_or_default();
+        let appointments = self.appointments.find_by_series(&id, None, None, false).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(AppointmentSeriesResponse {
+            id,
+            patient_id: series.patient_id,
+            doctor_id: series.doctor_id,
+            rrule: series.rrule,
+            start_date: series.start_date,
+            time: series.time,
+            horizon_date: series.horizon_date,
+            status: series.status,
+            created_at: crate::datetime::to_rfc3339(series.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(series.updated_at),
+            appointments: appointments.into_iter().map(AppointmentService::map_to_response).collect(),
+        })
+    }
+
+    async fn load_active(&self, id: ObjectId) -> Result<AppointmentSeries, (StatusCode, String)> {
+        let series = self.series.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Appointment series not found".to_string()))?;
+        if series.status == SERIES_CANCELLED {
+            return Err((StatusCode::CONFLICT, "Appointment series is cancelled".to_string()));
+        }
+        Ok(series)
+    }
+
+    /// Create a series and its appointments up to the horizon. Nothing is stored when any
+    /// generated slot is taken.
+    pub async fn create(&self, request: CreateAppointmentSeriesRequest) -> Result<AppointmentSeriesResponse, (StatusCode, String)> {
+        let patient_id: Ref<MedicalRecord> = Ref::parse_field("patient_id", &request.patient_id)?;
+        let doctor_id: Ref<Doctor> = Ref::parse_field("doctor_id", &request.doctor_id)?;
+        self.references.ensure_exist(&[patient_id.check("patient_id"), doctor_id.check("doctor_id")]).await?;
+
+        let rule = parse_rule(&request.rrule)?;
+        let start = parse_day(&request.start_date, "start_date")?;
+        let horizon = match &request.horizon_date {
+            Some(date) => parse_day(date, "horizon_date")?,
+            None => start + Days::new(DEFAULT_HORIZON_DAYS),
+        };
+        if horizon < start {
+            return Err((StatusCode::BAD_REQUEST, "horizon_date must not be before start_date".to_string()));
+        }
+        if horizon > start + Days::new(MAX_HORIZON_DAYS) {
+            return Err((StatusCode::BAD_REQUEST, format!("horizon_date can be at most {} days after start_date", MAX_HORIZON_DAYS)));
+        }
+
+        let dates = rule.expand(start, horizon);
+        if dates.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Recurrence rule produces no occurrences before the horizon".to_string()));
+        }
+
+        let slots = dates.iter().map(|d| (d.format(DATE_FORMAT).to_string(), request.time.clone())).collect();
+        self.ensure_free(&request.doctor_id, slots, &[]).await?;
+
+        let series = AppointmentSeries {
+            id: Some(ObjectId::new()),
+            patient_id: request.patient_id,
+            doctor_id: request.doctor_id,
+            rrule: request.rrule,
+            start_date: start.format(DATE_FORMAT).to_string(),
+            time: request.time,
+            horizon_date: horizon.format(DATE_FORMAT).to_string(),
+            status: SERIES_ACTIVE.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        let series = self.series.create(series).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.appointments.insert_many(self.occurrences(&series, &dates)?).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        self.build_response(series).await
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<AppointmentSeriesResponse>, (StatusCode, String)> {
+        match self.series.find_by_id(id).await {
+            Ok(Some(series)) => self.build_response(series).await.map(Some),
+            Ok(None) => Ok(None),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Change the doctor, time or recurrence of the occurrences selected by `scope`. Completed
+    /// and cancelled visits are never touched. A new `rrule` replaces the selected occurrences
+    /// with freshly generated ones.
+    pub async fn update(&self, id: ObjectId, request: UpdateAppointmentSeriesRequest) -> Result<AppointmentSeriesResponse, (StatusCode, String)> {
+        let mut series = self.load_active(id).await?;
+        let (from, on) = scope_bounds(request.scope, request.occurrence_date.as_deref())?;
+        if request.rrule.is_some() && request.scope == SeriesScope::This {
+            return Err((StatusCode::BAD_REQUEST, "A single occurrence cannot change the recurrence rule".to_string()));
+        }
+
+        let series_id = id.to_hex();
+        let affected = self.appointments.find_by_series(&series_id, from.as_deref(), on.as_deref(), true).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if affected.is_empty() && request.scope == SeriesScope::This {
+            return Err((StatusCode::NOT_FOUND, "No open occurrence on that date".to_string()));
+        }
+        let affected_ids: Vec<ObjectId> = affected.iter().filter_map(|a| a.id).collect();
+        if let Some(doctor_id) = &request.doctor_id {
+            let doctor: Ref<Doctor> = Ref::parse_field("doctor_id", doctor_id)?;
+            if doctor != series.doctor_id {
+                self.references.ensure_exist(&[doctor.check("doctor_id")]).await?;
+            }
+        }
+        let doctor_id = request.doctor_id.clone().unwrap_or_else(|| series.doctor_id.clone());
+
+        if request.scope != SeriesScope::This {
+            series.doctor_id = doctor_id.clone();
+            if let Some(time) = &request.time {
+                series.time = time.clone();
+            }
+        }
+
+        if let Some(rrule) = &request.rrule {
+            let rule = parse_rule(rrule)?;
+            let start = parse_day(from.as_deref().unwrap_or(&series.start_date), "start_date")?;
+            let horizon = parse_day(&series.horizon_date, "horizon_date")?;
+            let dates = rule.expand(start, horizon);
+
+            let slots = dates.iter().map(|d| (d.format(DATE_FORMAT).to_string(), series.time.clone())).collect();
+            self.ensure_free(&doctor_id, slots, &affected_ids).await?;
+
+            series.rrule = rrule.clone();
+            self.appointments.delete_by_ids(&affected_ids).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            self.appointments.insert_many(self.occurrences(&series, &dates)?).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        } else {
+            let slots = affected
+                .iter()
+                .map(|a| (a.date.clone(), request.time.clone().unwrap_or_else(|| a.time.clone())))
+                .collect();
+            self.ensure_free(&doctor_id, slots, &affected_ids).await?;
+
+            match &request.time {
+                // Each occurrence's instant depends on its own date
+                Some(time) => {
+                    for appointment in &affected {
+                        let Some(appointment_id) = appointment.id else { continue };
+                        let timezone = appointment.timezone.as_deref()
+                            .and_then(|tz| ClinicTimezone::parse(tz).ok())
+                            .unwrap_or_else(|| self.timezone.clone());
+                        let mut set = doc! { "doctorId": &doctor_id, "time": time };
+                        if let Ok(starts_at) = timezone.to_utc(&appointment.date, time) {
+                            set.insert("startsAt", crate::datetime::from_chrono(starts_at));
+                        }
+                        self.appointments.update_many_by_ids(&[appointment_id], set).await
+                            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                    }
+                }
+                None => {
+                    self.appointments.update_many_by_ids(&affected_ids, doc! { "doctorId": &doctor_id }).await
+                        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                }
+            }
+        }
+
+        series.updated_at = Some(DateTime::now());
+        let series = self.series.update(id, series).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.build_response(series).await
+    }
+
+    /// Cancel the occurrences selected by `scope`. Cancelling `future` ends the series the day
+    /// before `occurrence_date`; cancelling `all` cancels the series itself.
+    pub async fn cancel(&self, id: ObjectId, request: CancelAppointmentSeriesRequest) -> Result<(AppointmentSeriesResponse, Vec<Appointment>), (StatusCode, String)> {
+        let mut series = self.load_active(id).await?;
+        let (from, on) = scope_bounds(request.scope, request.occurrence_date.as_deref())?;
+
+        let affected = self.appointments.find_by_series(&id.to_hex(), from.as_deref(), on.as_deref(), true).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if affected.is_empty() && request.scope == SeriesScope::This {
+            return Err((StatusCode::NOT_FOUND, "No open occurrence on that date".to_string()));
+        }
+        let affected_ids: Vec<ObjectId> = affected.iter().filter_map(|a| a.id).collect();
+        self.appointments.update_many_by_ids(&affected_ids, doc! { "status": AppointmentStatus::Cancelled }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        match request.scope {
+            SeriesScope::All => series.status = SERIES_CANCELLED.to_string(),
+            SeriesScope::Future => {
+                let pivot = parse_day(from.as_deref().unwrap_or_default(), "occurrence_date")?;
+                let start = parse_day(&series.start_date, "start_date")?;
+                if pivot <= start {
+                    series.status = SERIES_CANCELLED.to_string();
+                } else {
+                    series.horizon_date = (pivot - Days::new(1)).format(DATE_FORMAT).to_string();
+                }
+            }
+            SeriesScope::This => {}
+        }
+
+        series.updated_at = Some(DateTime::now());
+        let series = self.series.update(id, series).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok((self.build_response(series).await?, affected))
+    }
+}
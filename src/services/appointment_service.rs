@@ -1,42 +1,127 @@
 use crate::models::Appointment;
+use crate::middleware::KioskIdentity;
 use crate::repository::AppointmentRepository;
+use crate::repository::appointment::{AppointmentOutcomeRow, Expansion};
 use crate::pagination::{PaginationParams, PaginationMeta};
-use crate::dto::appointment::{CreateAppointmentRequest, UpdateAppointmentRequest, AppointmentResponse};
-use mongodb::bson::oid::ObjectId;
+use crate::dto::appointment::{
+    AvailabilityQuery, AvailabilityResponse, AvailabilitySlot, CreateAppointmentRequest, UpdateAppointmentRequest,
+    AppointmentResponse, DoctorAppointmentStats, DoctorStatsQuery, DoctorSummary, PatientSummary, QueueBoard, QueueEntry,
+};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::services::OrganizationService;
+use crate::status::AppointmentStatus;
+use crate::timezone::{ClinicTimezone, SchedulingConfig};
+use chrono::NaiveDate;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use axum::http::StatusCode;
 
 pub struct AppointmentService {
     repository: AppointmentRepository,
+    organizations: OrganizationService,
+    references: ReferenceChecker,
+    scheduling: SchedulingConfig,
 }
 
 impl AppointmentService {
-    pub fn new(repository: AppointmentRepository) -> Self {
-        Self { repository }
+    pub fn new(
+        repository: AppointmentRepository,
+        organizations: OrganizationService,
+        references: ReferenceChecker,
+        scheduling: SchedulingConfig,
+    ) -> Self {
+        Self { repository, organizations, references, scheduling }
     }
 
-    /// Map Appointment model to AppointmentResponse DTO
-    fn map_to_response(appointment: Appointment) -> AppointmentResponse {
+    /// Map Appointment model to AppointmentResponse DTO. `starts_at` is given in the
+    /// appointment's own zone; `reminder_at` needs the scheduling config, see `respond`.
+    pub(crate) fn map_to_response(appointment: Appointment) -> AppointmentResponse {
+        let timezone = appointment.timezone.as_deref().and_then(|tz| ClinicTimezone::parse(tz).ok());
+        let starts_at = appointment.starts_at.map(|instant| {
+            let instant = crate::datetime::to_chrono(instant);
+            timezone.as_ref().map(|tz| tz.local(instant).to_rfc3339()).unwrap_or_else(|| instant.to_rfc3339())
+        });
         AppointmentResponse {
             id: appointment.id.map(|id| id.to_hex()).unwrap_or_default(),
-            patient_id: appointment.patient_id,
-            doctor_id: appointment.doctor_id,
+            patient_id: appointment.patient_id.to_hex(),
+            doctor_id: appointment.doctor_id.to_hex(),
             date: appointment.date,
             time: appointment.time,
             status: appointment.status,
+            queue_number: appointment.queue_number,
+            checked_in_at: appointment.checked_in_at,
+            called_at: appointment.called_at,
+            completed_at: appointment.completed_at,
+            series_id: appointment.series_id,
+            mode: appointment.mode,
+            organization_id: appointment.organization_id,
+            timezone: appointment.timezone,
+            starts_at,
+            reminder_at: None,
+            updated_at: crate::datetime::to_rfc3339_opt(appointment.updated_at),
+            doctor: None,
+            patient: None,
+            links: None,
+        }
+    }
+
+    fn respond(&self, appointment: Appointment) -> AppointmentResponse {
+        let reminder_at = appointment.starts_at.map(|instant| {
+            let timezone = appointment.timezone.as_deref()
+                .and_then(|tz| ClinicTimezone::parse(tz).ok())
+                .unwrap_or_else(|| self.scheduling.default_timezone.clone());
+            let due = self.scheduling.reminder_at(crate::datetime::to_chrono(instant), &timezone);
+            timezone.local(due).to_rfc3339()
+        });
+        AppointmentResponse { reminder_at, ..Self::map_to_response(appointment) }
+    }
+
+    fn zone_of(&self, appointment: &Appointment) -> ClinicTimezone {
+        appointment.timezone.as_deref()
+            .and_then(|tz| ClinicTimezone::parse(tz).ok())
+            .unwrap_or_else(|| self.scheduling.default_timezone.clone())
+    }
+
+    fn map_to_queue_entry(appointment: Appointment) -> QueueEntry {
+        QueueEntry {
+            appointment_id: appointment.id.map(|id| id.to_hex()).unwrap_or_default(),
+            patient_id: appointment.patient_id.to_hex(),
+            queue_number: appointment.queue_number.unwrap_or_default(),
+            status: appointment.status,
+            time: appointment.time,
+            checked_in_at: appointment.checked_in_at,
+            called_at: appointment.called_at,
         }
     }
 
     pub async fn get_all(&self) -> Result<Vec<AppointmentResponse>, (StatusCode, String)> {
         match self.repository.find_all().await {
-            Ok(appointments) => Ok(appointments.into_iter().map(Self::map_to_response).collect()),
+            Ok(appointments) => Ok(appointments.into_iter().map(|a| self.respond(a)).collect()),
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
     }
 
-    pub async fn get_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<AppointmentResponse>, PaginationMeta), (StatusCode, String)> {
-        match self.repository.find_all_paginated(pagination.clone()).await {
+    pub async fn get_all_paginated(
+        &self,
+        pagination: PaginationParams,
+        patient_id: Option<&str>,
+        status: Option<&AppointmentStatus>,
+        expansion: Expansion,
+    ) -> Result<(Vec<AppointmentResponse>, PaginationMeta), (StatusCode, String)> {
+        if !expansion.is_empty() {
+            let (rows, total) = self.repository.find_all_expanded(pagination.clone(), patient_id, status, expansion).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            let responses = rows.into_iter().map(|row| {
+                let mut response = self.respond(row.appointment);
+                response.doctor = row.doctor.map(|d| DoctorSummary { id: d.id.to_hex(), name: d.name, specialization: d.specialization });
+                response.patient = row.patient.map(|p| PatientSummary { id: p.id.to_hex(), name: p.name, nrme: p.nrme });
+                response
+            }).collect();
+            return Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)));
+        }
+
+        match self.repository.find_all_paginated(pagination.clone(), patient_id, status).await {
             Ok((appointments, total)) => {
-                let responses = appointments.into_iter().map(Self::map_to_response).collect();
+                let responses = appointments.into_iter().map(|a| self.respond(a)).collect();
                 let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
                 Ok((responses, meta))
             }
@@ -44,25 +129,62 @@ impl AppointmentService {
         }
     }
 
+    /// 409 when the doctor already has a non-cancelled appointment in the slot.
+    async fn ensure_slot_free(&self, appointment: &Appointment) -> Result<(), (StatusCode, String)> {
+        if !appointment.status.occupies_slot() {
+            return Ok(());
+        }
+        let slot = [(appointment.date.clone(), appointment.time.clone())];
+        let exclude: Vec<ObjectId> = appointment.id.into_iter().collect();
+        let conflicts = self.repository.find_conflicts(&appointment.doctor_id.to_hex(), &slot, &exclude).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err((StatusCode::CONFLICT, format!(
+                "Doctor is already booked on {} at {}; join the waitlist with POST /appointments/waitlist",
+                appointment.date, appointment.time
+            )))
+        }
+    }
+
     pub async fn create(&self, request: CreateAppointmentRequest) -> Result<(StatusCode, AppointmentResponse), (StatusCode, String)> {
+        let patient_id = Ref::parse_field("patient_id", &request.patient_id)?;
+        let doctor_id = Ref::parse_field("doctor_id", &request.doctor_id)?;
+        self.references.ensure_exist(&[patient_id.check("patient_id"), doctor_id.check("doctor_id")]).await?;
+
+        let timezone = self.organizations.timezone_for(request.organization_id.as_deref()).await?;
+        let starts_at = slot_instant(&timezone, &request.date, &request.time)?;
         let appointment = Appointment {
             id: Some(ObjectId::new()),
-            patient_id: request.patient_id,
-            doctor_id: request.doctor_id,
+            patient_id,
+            doctor_id,
             date: request.date,
             time: request.time,
             status: request.status,
+            queue_number: None,
+            checked_in_at: None,
+            called_at: None,
+            completed_at: None,
+            series_id: None,
+            mode: validate_mode(request.mode)?,
+            organization_id: request.organization_id,
+            timezone: Some(timezone.name().to_string()),
+            starts_at: Some(starts_at),
+            deleted_at: None,
+            updated_at: None,
         };
+        self.ensure_slot_free(&appointment).await?;
 
         match self.repository.insert(appointment).await {
-            Ok(created) => Ok((StatusCode::CREATED, Self::map_to_response(created))),
+            Ok(created) => Ok((StatusCode::CREATED, self.respond(created))),
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
     }
 
     pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<AppointmentResponse>, (StatusCode, String)> {
         match self.repository.find_by_id(id).await {
-            Ok(Some(appointment)) => Ok(Some(Self::map_to_response(appointment))),
+            Ok(Some(appointment)) => Ok(Some(self.respond(appointment))),
             Ok(None) => Ok(None),
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
@@ -73,14 +195,47 @@ impl AppointmentService {
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
             .ok_or((StatusCode::NOT_FOUND, "Appointment not found".to_string()))?;
 
-        if let Some(val) = request.patient_id { appointment.patient_id = val; }
-        if let Some(val) = request.doctor_id { appointment.doctor_id = val; }
+        // Only references that change are checked; an existing dangling one does not block edits
+        let mut checks = Vec::new();
+        if let Some(val) = request.patient_id {
+            let patient_id = Ref::parse_field("patient_id", &val)?;
+            if patient_id != appointment.patient_id {
+                checks.push(patient_id.check("patient_id"));
+            }
+            appointment.patient_id = patient_id;
+        }
+        if let Some(val) = request.doctor_id {
+            let doctor_id = Ref::parse_field("doctor_id", &val)?;
+            if doctor_id != appointment.doctor_id {
+                checks.push(doctor_id.check("doctor_id"));
+            }
+            appointment.doctor_id = doctor_id;
+        }
+        self.references.ensure_exist(&checks).await?;
         if let Some(val) = request.date { appointment.date = val; }
         if let Some(val) = request.time { appointment.time = val; }
-        if let Some(val) = request.status { appointment.status = val; }
+        if let Some(val) = request.status {
+            if val == AppointmentStatus::Completed && appointment.status != AppointmentStatus::Completed {
+                appointment.completed_at = Some(chrono::Utc::now().to_rfc3339());
+            }
+            appointment.status = val;
+        }
+        if request.mode.is_some() { appointment.mode = validate_mode(request.mode)?; }
+        // Moving to another organization re-reads the zone; otherwise the booked zone is kept
+        let timezone = match request.organization_id {
+            Some(organization_id) => {
+                let timezone = self.organizations.timezone_for(Some(&organization_id)).await?;
+                appointment.organization_id = Some(organization_id);
+                timezone
+            }
+            None => self.zone_of(&appointment),
+        };
+        appointment.starts_at = Some(slot_instant(&timezone, &appointment.date, &appointment.time)?);
+        appointment.timezone = Some(timezone.name().to_string());
+        self.ensure_slot_free(&appointment).await?;
 
         match self.repository.update(id, appointment).await {
-            Ok(updated) => Ok(Self::map_to_response(updated)),
+            Ok(updated) => Ok(self.respond(updated)),
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
     }
@@ -91,4 +246,244 @@ impl AppointmentService {
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
     }
+
+    /// Assign the next queue number of the doctor's day. Only appointments scheduled for
+    /// today that are not cancelled, completed, unconfirmed or already checked in can check in.
+    /// A kiosk only checks in appointments of the organization it is paired to.
+    pub async fn check_in(&self, id: ObjectId, kiosk: Option<&KioskIdentity>) -> Result<AppointmentResponse, (StatusCode, String)> {
+        let appointment = self.repository.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Appointment not found".to_string()))?;
+
+        if !kiosk.is_none_or(|kiosk| kiosk_may_check_in(kiosk.organization_id.as_deref(), appointment.organization_id.as_deref())) {
+            return Err((StatusCode::FORBIDDEN, "Appointment belongs to another organization than this kiosk".to_string()));
+        }
+
+        if appointment.queue_number.is_some() {
+            return Err((StatusCode::CONFLICT, "Appointment is already checked in".to_string()));
+        }
+        if matches!(appointment.status, AppointmentStatus::Cancelled | AppointmentStatus::Completed | AppointmentStatus::Held | AppointmentStatus::PendingConfirmation) {
+            return Err((StatusCode::CONFLICT, format!("A {} appointment cannot check in", appointment.status)));
+        }
+        let today = self.zone_of(&appointment).today(chrono::Utc::now());
+        if appointment.date != today {
+            return Err((StatusCode::CONFLICT, format!("Appointment is scheduled for {}, not today ({})", appointment.date, today)));
+        }
+
+        let number = self.repository.next_queue_number(&appointment.doctor_id.to_hex(), &appointment.date).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        // The filter on a missing queue number makes a concurrent second check-in lose here
+        match self.repository.check_in(id, number, &chrono::Utc::now().to_rfc3339()).await {
+            Ok(Some(updated)) => Ok(self.respond(updated)),
+            Ok(None) => Err((StatusCode::CONFLICT, "Appointment is already checked in".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Call the waiting patient with the lowest queue number for a doctor today
+    pub async fn call_next(&self, doctor_id: &str) -> Result<QueueEntry, (StatusCode, String)> {
+        match self.repository.call_next(doctor_id, &self.queue_date(), &chrono::Utc::now().to_rfc3339()).await {
+            Ok(Some(appointment)) => Ok(Self::map_to_queue_entry(appointment)),
+            Ok(None) => Err((StatusCode::NOT_FOUND, "No patients waiting".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn queue_board(&self, doctor_id: &str) -> Result<QueueBoard, (StatusCode, String)> {
+        let date = self.queue_date();
+        let entries: Vec<QueueEntry> = self.repository.find_queue(doctor_id, &date).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .into_iter()
+            .map(Self::map_to_queue_entry)
+            .collect();
+
+        let total = entries.len();
+        let current = entries.iter()
+            .filter(|entry| entry.called_at.is_some())
+            .max_by(|a, b| a.called_at.cmp(&b.called_at))
+            .cloned();
+        let waiting: Vec<QueueEntry> = entries.into_iter().filter(|entry| entry.status == AppointmentStatus::CheckedIn).collect();
+
+        Ok(QueueBoard {
+            doctor_id: doctor_id.to_string(),
+            date,
+            current,
+            served: total - waiting.len(),
+            waiting,
+            total,
+        })
+    }
+
+    /// Mark a scheduled appointment the patient did not come to. Only appointments that have
+    /// started and were never checked in qualify.
+    pub async fn mark_no_show(&self, id: ObjectId) -> Result<AppointmentResponse, (StatusCode, String)> {
+        let appointment = self.repository.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Appointment not found".to_string()))?;
+        if appointment.status != AppointmentStatus::Scheduled || appointment.queue_number.is_some() {
+            return Err((StatusCode::CONFLICT, format!("A {} appointment cannot become a no-show", appointment.status)));
+        }
+        if appointment.starts_at.is_some_and(|starts_at| starts_at > DateTime::now()) {
+            return Err((StatusCode::CONFLICT, "The appointment has not started yet".to_string()));
+        }
+
+        // The filter on status and queue number makes a concurrent check-in win
+        match self.repository.mark_no_show(id).await {
+            Ok(Some(updated)) => Ok(self.respond(updated)),
+            Ok(None) => Err((StatusCode::CONFLICT, "The appointment was checked in or changed meanwhile".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Mark scheduled appointments that started more than `NO_SHOW_GRACE_MINUTES` ago without
+    /// a check-in, returning their ids
+    pub async fn mark_no_shows(&self, now: chrono::DateTime<chrono::Utc>) -> Result<Vec<String>, String> {
+        let cutoff = now - chrono::Duration::minutes(self.scheduling.no_show_grace_minutes);
+        let ids = self.repository.mark_no_shows(crate::datetime::from_chrono(cutoff)).await?;
+        Ok(ids.into_iter().map(|id| id.to_hex()).collect())
+    }
+
+    /// Outcomes of a doctor's appointments dated in the period, by default the 30 days up to
+    /// today in the clinic's zone
+    pub async fn doctor_stats(&self, doctor_id: ObjectId, query: DoctorStatsQuery) -> Result<DoctorAppointmentStats, (StatusCode, String)> {
+        let today = self.queue_date();
+        let to = query.to.unwrap_or(today);
+        let from = match query.from {
+            Some(from) => from,
+            None => NaiveDate::parse_from_str(&to, "%Y-%m-%d")
+                .map(|to| (to - chrono::Duration::days(29)).format("%Y-%m-%d").to_string())
+                .map_err(|_| (StatusCode::BAD_REQUEST, "to: Date must be YYYY-MM-DD".to_string()))?,
+        };
+        if from > to {
+            return Err((StatusCode::BAD_REQUEST, "from must not be after to".to_string()));
+        }
+
+        let rows = self.repository.find_outcomes(&doctor_id.to_hex(), &from, &to).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(doctor_stats(doctor_id.to_hex(), from, to, &rows))
+    }
+
+    /// Queue day in the default clinic zone, in the `YYYY-MM-DD` form appointments store
+    fn queue_date(&self) -> String {
+        self.scheduling.default_timezone.today(chrono::Utc::now())
+    }
+
+    /// Bookable slots of a doctor's day in the clinic's zone. Slots taken by another
+    /// appointment or already in the past are unavailable.
+    pub async fn availability(&self, query: AvailabilityQuery) -> Result<AvailabilityResponse, (StatusCode, String)> {
+        let timezone = self.organizations.timezone_for(query.organization_id.as_deref()).await?;
+        let now = chrono::Utc::now();
+
+        let mut slots = Vec::new();
+        for time in self.scheduling.slots() {
+            let starts_at = timezone.to_utc(&query.date, &time).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            slots.push((time, starts_at));
+        }
+        let wanted: Vec<(String, String)> = slots.iter().map(|(time, _)| (query.date.clone(), time.clone())).collect();
+        let booked = self.repository.find_conflicts(&query.doctor_id, &wanted, &[]).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let slots = slots
+            .into_iter()
+            .map(|(time, starts_at)| AvailabilitySlot {
+                available: starts_at > now && !booked.iter().any(|a| a.time == time),
+                starts_at: timezone.local(starts_at).to_rfc3339(),
+                time,
+            })
+            .collect();
+
+        Ok(AvailabilityResponse {
+            doctor_id: query.doctor_id,
+            date: query.date,
+            timezone: timezone.name().to_string(),
+            utc_offset: timezone.utc_offset(),
+            slots,
+        })
+    }
+}
+
+/// Counts per outcome and the average consult of `rows`
+fn doctor_stats(doctor_id: String, from: String, to: String, rows: &[AppointmentOutcomeRow]) -> DoctorAppointmentStats {
+    let count = |status: AppointmentStatus| rows.iter().filter(|row| row.status == status).count() as i64;
+    let (completed, cancelled, no_show) = (count(AppointmentStatus::Completed), count(AppointmentStatus::Cancelled), count(AppointmentStatus::NoShow));
+
+    let parse = |value: &Option<String>| value.as_deref().and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok());
+    let minutes: Vec<f64> = rows.iter()
+        .filter(|row| row.status == AppointmentStatus::Completed)
+        .filter_map(|row| Some((parse(&row.completed_at)? - parse(&row.called_at)?).num_seconds() as f64 / 60.0))
+        .filter(|minutes| *minutes >= 0.0)
+        .collect();
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+
+    DoctorAppointmentStats {
+        doctor_id,
+        from,
+        to,
+        total: rows.len() as i64,
+        completed,
+        cancelled,
+        no_show,
+        no_show_rate: (completed + no_show > 0).then(|| (no_show as f64 / (completed + no_show) as f64 * 1000.0).round() / 1000.0),
+        average_consult_minutes: crate::stats::mean(&minutes).map(round),
+        timed_consults: minutes.len() as i64,
+    }
+}
+
+/// The instant of a clinic-local slot, as stored in `startsAt`
+pub(crate) fn slot_instant(timezone: &ClinicTimezone, date: &str, time: &str) -> Result<DateTime, (StatusCode, String)> {
+    timezone.to_utc(date, time)
+        .map(crate::datetime::from_chrono)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+pub const MODE_IN_PERSON: &str = "in_person";
+pub const MODE_VIRTUAL: &str = "virtual";
+
+fn validate_mode(mode: Option<String>) -> Result<Option<String>, (StatusCode, String)> {
+    match mode.as_deref() {
+        None | Some(MODE_IN_PERSON) | Some(MODE_VIRTUAL) => Ok(mode),
+        Some(other) => Err((StatusCode::BAD_REQUEST, format!("Unknown appointment mode '{}'; use in_person or virtual", other))),
+    }
+}
+
+/// A kiosk paired without an organization serves a single-clinic deployment and reaches every
+/// appointment; one paired to an organization only reaches that organization's appointments.
+fn kiosk_may_check_in(kiosk_organization: Option<&str>, appointment_organization: Option<&str>) -> bool {
+    kiosk_organization.is_none_or(|kiosk| appointment_organization == Some(kiosk))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(status: AppointmentStatus, called_at: Option<&str>, completed_at: Option<&str>) -> AppointmentOutcomeRow {
+        AppointmentOutcomeRow { status, called_at: called_at.map(String::from), completed_at: completed_at.map(String::from) }
+    }
+
+    #[test]
+    fn counts_outcomes_and_consult_minutes() {
+        let rows = [
+            row(AppointmentStatus::Completed, Some("2026-03-10T09:00:00+07:00"), Some("2026-03-10T09:12:00+07:00")),
+            row(AppointmentStatus::Completed, Some("2026-03-10T02:20:00+00:00"), Some("2026-03-10T09:38:00+07:00")),
+            row(AppointmentStatus::Completed, None, Some("2026-03-10T10:00:00+07:00")),
+            row(AppointmentStatus::NoShow, None, None),
+            row(AppointmentStatus::Cancelled, None, None),
+            row(AppointmentStatus::Scheduled, None, None),
+        ];
+        let stats = doctor_stats("d1".into(), "2026-03-01".into(), "2026-03-31".into(), &rows);
+        assert_eq!((stats.total, stats.completed, stats.cancelled, stats.no_show), (6, 3, 1, 1));
+        assert_eq!(stats.no_show_rate, Some(0.25));
+        assert_eq!(stats.timed_consults, 2);
+        assert_eq!(stats.average_consult_minutes, Some(15.0));
+    }
+
+    #[test]
+    fn kiosks_check_in_only_their_own_organizations_appointments() {
+        assert!(kiosk_may_check_in(Some("clinic-a"), Some("clinic-a")));
+        assert!(!kiosk_may_check_in(Some("clinic-a"), Some("clinic-b")));
+        assert!(!kiosk_may_check_in(Some("clinic-a"), None));
+        assert!(kiosk_may_check_in(None, Some("clinic-b")));
+        assert!(kiosk_may_check_in(None, None));
+    }
 }
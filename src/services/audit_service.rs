@@ -0,0 +1,32 @@
+use mongodb::bson::{DateTime, Document};
+use crate::models::AuditLog;
+use crate::repository::AuditLogRepository;
+
+pub struct AuditService {
+    repo: AuditLogRepository,
+}
+
+impl AuditService {
+    pub fn new(repo: AuditLogRepository) -> Self {
+        Self { repo }
+    }
+
+    /// Record an action performed by `actor` against a single entity.
+    ///
+    /// Audit writes never fail the calling operation; errors are logged and dropped.
+    pub async fn record(&self, action: &str, entity: &str, entity_id: &str, actor: &str, details: Document) {
+        let log = AuditLog {
+            id: None,
+            action: action.to_string(),
+            entity: entity.to_string(),
+            entity_id: entity_id.to_string(),
+            actor: actor.to_string(),
+            details,
+            created_at: DateTime::now(),
+        };
+
+        if let Err(e) = self.repo.insert(log).await {
+            tracing::error!(entity, entity_id = %entity_id, error = %e, "Failed to write audit log");
+        }
+    }
+}
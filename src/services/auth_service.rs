@@ -1,7 +1,7 @@
 use axum::http::StatusCode;
 use bcrypt::{hash, verify, DEFAULT_COST};
 use jsonwebtoken::{encode, decode, Header, Algorithm, Validation, EncodingKey, DecodingKey};
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -11,7 +11,8 @@ use crate::dto::auth::{
     AuthResponse, LoginResponse, ForgotPasswordResponse, ResetPasswordResponse,
     RefreshTokenRequest, RefreshTokenResponse,
 };
-use crate::models::User;
+use crate::models::{KioskDevice, MedicalRecord, ServiceAccount, User};
+use crate::phone;
 use crate::repository::UserRepository;
 
 /// JWT Claims structure for access token
@@ -20,9 +21,15 @@ pub struct Claims {
     pub sub: String,      // Subject (user id)
     pub email: String,    // User email
     pub name: String,     // User name
-    pub token_type: String, // "access" or "refresh"
+    pub token_type: String, // "access", "refresh", "patient", "service", "kiosk", "email_verification" or "file_share"
     pub exp: usize,       // Expiration time
     pub iat: usize,       // Issued at
+    /// Patient (medical record id) a `patient` token is scoped to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patient_id: Option<String>,
+    /// Scopes a `service` token is restricted to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
 }
 
 pub struct AuthService {
@@ -63,6 +70,30 @@ impl AuthService {
             .unwrap_or(7) // Default to 7 days for refresh token
     }
 
+    /// Get patient portal token expiration time in hours from environment variable
+    fn get_patient_expiration_hours() -> i64 {
+        env::var("PATIENT_TOKEN_EXPIRATION_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(12) // Default to 12 hours; patient tokens have no refresh token
+    }
+
+    /// Get service account token expiration time in minutes from environment variable
+    fn get_service_expiration_minutes() -> i64 {
+        env::var("SERVICE_TOKEN_EXPIRATION_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60) // Default to 1 hour; clients request a new token with their credentials
+    }
+
+    /// Get kiosk token expiration time in days from environment variable
+    fn get_kiosk_expiration_days() -> i64 {
+        env::var("KIOSK_TOKEN_EXPIRATION_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(180) // Default to 180 days; kiosks are re-paired rather than logged in again
+    }
+
     /// Hash password using bcrypt
     fn hash_password(password: &str) -> Result<String, String> {
         hash(password, DEFAULT_COST).map_err(|e| format!("Failed to hash password: {}", e))
@@ -94,6 +125,8 @@ impl AuthService {
             token_type: "access".to_string(),
             exp,
             iat,
+            patient_id: None,
+            scopes: None,
         };
 
         let token = encode(
@@ -126,6 +159,8 @@ impl AuthService {
             token_type: "refresh".to_string(),
             exp,
             iat,
+            patient_id: None,
+            scopes: None,
         };
 
         encode(
@@ -136,7 +171,110 @@ impl AuthService {
         .map_err(|e| format!("Failed to generate refresh token: {}", e))
     }
 
+    /// Generate a patient portal token scoped to one medical record
+    pub fn generate_patient_token(record: &MedicalRecord) -> Result<(String, i64), String> {
+        let secret = Self::get_jwt_secret();
+        let expiration_hours = Self::get_patient_expiration_hours();
+
+        let now = chrono::Utc::now();
+        let exp = (now + chrono::Duration::hours(expiration_hours)).timestamp() as usize;
+        let iat = now.timestamp() as usize;
+        let expires_in = expiration_hours * 3600;
+
+        let patient_id = record.id.as_ref()
+            .map(|id| id.to_hex())
+            .ok_or_else(|| "Medical record ID not found".to_string())?;
+
+        let claims = Claims {
+            sub: patient_id.clone(),
+            email: record.email.clone(),
+            name: record.name.clone(),
+            token_type: "patient".to_string(),
+            exp,
+            iat,
+            patient_id: Some(patient_id),
+            scopes: None,
+        };
+
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| format!("Failed to generate token: {}", e))?;
+
+        Ok((token, expires_in))
+    }
+
     /// Validate access token and return claims
+    /// Generate a client-credentials token for a service account, limited to `scopes`
+    pub fn generate_service_token(account: &ServiceAccount, scopes: Vec<String>) -> Result<(String, i64), String> {
+        let secret = Self::get_jwt_secret();
+        let expiration_minutes = Self::get_service_expiration_minutes();
+
+        let now = chrono::Utc::now();
+        let exp = (now + chrono::Duration::minutes(expiration_minutes)).timestamp() as usize;
+        let iat = now.timestamp() as usize;
+
+        let account_id = account.id.as_ref()
+            .map(|id| id.to_hex())
+            .ok_or_else(|| "Service account ID not found".to_string())?;
+
+        let claims = Claims {
+            sub: account_id,
+            email: account.client_id.clone(),
+            name: account.name.clone(),
+            token_type: "service".to_string(),
+            exp,
+            iat,
+            patient_id: None,
+            scopes: Some(scopes),
+        };
+
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| format!("Failed to generate token: {}", e))?;
+
+        Ok((token, expiration_minutes * 60))
+    }
+
+    /// Generate a long-lived token for a paired kiosk; `crate::middleware::kiosk_scope` limits where it goes
+    pub fn generate_kiosk_token(device: &KioskDevice) -> Result<(String, i64), String> {
+        let secret = Self::get_jwt_secret();
+        let expiration_days = Self::get_kiosk_expiration_days();
+
+        let now = chrono::Utc::now();
+        let exp = (now + chrono::Duration::days(expiration_days)).timestamp() as usize;
+        let iat = now.timestamp() as usize;
+
+        let device_id = device.id.as_ref()
+            .map(|id| id.to_hex())
+            .ok_or_else(|| "Kiosk device ID not found".to_string())?;
+
+        let claims = Claims {
+            sub: device_id,
+            email: String::new(),
+            name: device.name.clone(),
+            token_type: "kiosk".to_string(),
+            exp,
+            iat,
+            patient_id: None,
+            scopes: None,
+        };
+
+        let token = encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| format!("Failed to generate token: {}", e))?;
+
+        Ok((token, expiration_days * 86_400))
+    }
+
     pub fn validate_token(token: &str) -> Result<Claims, String> {
         let secret = Self::get_jwt_secret();
         
@@ -150,8 +288,97 @@ impl AuthService {
         .map(|data| data.claims)
         .map_err(|e| format!("Invalid token: {}", e))?;
 
-        // Verify it's an access token
-        if claims.token_type != "access" {
+        // Verify it's an access token, a patient token bound to a patient, a scoped service token
+        // or a kiosk token
+        match claims.token_type.as_str() {
+            "access" | "kiosk" => {}
+            "patient" if claims.patient_id.is_some() => {}
+            "service" if claims.scopes.is_some() => {}
+            _ => return Err("Invalid token type".to_string()),
+        }
+
+        Ok(claims)
+    }
+
+    /// Generate a signed email verification token bound to the user's current address
+    pub fn generate_verification_token(user_id: &str, email: &str, ttl_hours: i64) -> Result<String, String> {
+        let secret = Self::get_jwt_secret();
+
+        let now = chrono::Utc::now();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            email: email.to_string(),
+            name: String::new(),
+            token_type: "email_verification".to_string(),
+            exp: (now + chrono::Duration::hours(ttl_hours)).timestamp() as usize,
+            iat: now.timestamp() as usize,
+            patient_id: None,
+            scopes: None,
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| format!("Failed to generate verification token: {}", e))
+    }
+
+    /// Validate email verification token and return claims
+    pub fn validate_verification_token(token: &str) -> Result<Claims, String> {
+        let secret = Self::get_jwt_secret();
+
+        let claims = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &Validation::new(Algorithm::HS256),
+        )
+        .map(|data| data.claims)
+        .map_err(|e| format!("Invalid verification token: {}", e))?;
+
+        if claims.token_type != "email_verification" {
+            return Err("Invalid token type".to_string());
+        }
+
+        Ok(claims)
+    }
+
+    /// Generate the token of a public file share link, valid until the share expires
+    pub fn generate_share_token(share_id: &str, expires_at: chrono::DateTime<chrono::Utc>) -> Result<String, String> {
+        let secret = Self::get_jwt_secret();
+
+        let claims = Claims {
+            sub: share_id.to_string(),
+            email: String::new(),
+            name: String::new(),
+            token_type: "file_share".to_string(),
+            exp: expires_at.timestamp() as usize,
+            iat: chrono::Utc::now().timestamp() as usize,
+            patient_id: None,
+            scopes: None,
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .map_err(|e| format!("Failed to generate share token: {}", e))
+    }
+
+    /// Validate a file share token and return claims; `sub` is the share id
+    pub fn validate_share_token(token: &str) -> Result<Claims, String> {
+        let secret = Self::get_jwt_secret();
+
+        let claims = decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(secret.as_bytes()),
+            &Validation::new(Algorithm::HS256),
+        )
+        .map(|data| data.claims)
+        .map_err(|e| format!("Invalid share token: {}", e))?;
+
+        if claims.token_type != "file_share" {
             return Err("Invalid token type".to_string());
         }
 
@@ -222,16 +449,21 @@ impl AuthService {
         let password_hash = Self::hash_password(&request.password)
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
+        let phone = request.phone.as_deref().map(phone::normalize).transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
         // Create user
         let user = User {
             id: Some(ObjectId::new()),
             email: request.email.to_lowercase(),
             password: password_hash,
             name: request.name,
+            phone,
             refresh_token: None,
             reset_token: None,
             reset_token_expiry: None,
-            created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            email_verified_at: None,
+            created_at: DateTime::now(),
             updated_at: None,
         };
 
@@ -260,7 +492,7 @@ impl AuthService {
             refresh_token,
             token_type: "Bearer".to_string(),
             expires_in,
-            created_at: created_user.created_at,
+            created_at: crate::datetime::to_rfc3339(created_user.created_at),
         };
 
         Ok((StatusCode::CREATED, response))
@@ -458,10 +690,12 @@ mod tests {
             email: "user@example.com".to_string(),
             password: "hashed".to_string(),
             name: "User".to_string(),
+            phone: None,
             refresh_token: None,
             reset_token: None,
             reset_token_expiry: None,
-            created_at: "2026-01-01 00:00:00".to_string(),
+            email_verified_at: None,
+            created_at: DateTime::now(),
             updated_at: None,
         }
     }
@@ -508,4 +742,58 @@ mod tests {
         // Keep the assertion tolerant to formatting changes.
         assert!(err.to_lowercase().contains("token type"));
     }
+
+    #[test]
+    fn verification_tokens_are_not_access_tokens() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("JWT_SECRET", "test_jwt_secret");
+
+        let token = AuthService::generate_verification_token("abc", "user@example.com", 24).expect("verification token");
+        let claims = AuthService::validate_verification_token(&token).expect("claims");
+        assert_eq!(claims.email, "user@example.com");
+        assert!(AuthService::validate_token(&token).is_err());
+    }
+
+    #[test]
+    fn share_tokens_only_open_shares() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("JWT_SECRET", "test_jwt_secret");
+
+        let expires_at = chrono::Utc::now() + chrono::Duration::hours(1);
+        let token = AuthService::generate_share_token("65a1b2c3d4e5f60718293a4b", expires_at).expect("share token");
+        assert_eq!(AuthService::validate_share_token(&token).expect("claims").sub, "65a1b2c3d4e5f60718293a4b");
+        assert!(AuthService::validate_token(&token).is_err());
+
+        let expired = AuthService::generate_share_token("65a1b2c3d4e5f60718293a4b", chrono::Utc::now() - chrono::Duration::hours(1)).expect("share token");
+        assert!(AuthService::validate_share_token(&expired).is_err());
+
+        let verification = AuthService::generate_verification_token("abc", "user@example.com", 24).expect("verification token");
+        assert!(AuthService::validate_share_token(&verification).is_err());
+    }
+
+    #[test]
+    fn patient_token_carries_patient_scope() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        std::env::set_var("JWT_SECRET", "test_jwt_secret");
+        let record = MedicalRecord {
+            id: Some(ObjectId::new()),
+            nrme: "RM-0001".to_string(),
+            nik: "3201010101010001".to_string(),
+            name: "Patient".to_string(),
+            dob: "1990-01-01".to_string(),
+            gender: crate::status::Gender::Female,
+            hp: "081234567890".to_string(),
+            email: "patient@example.com".to_string(),
+            last_visit_date: "2026-01-01".to_string(),
+            phone_verified_at: None,
+            updated_at: None,
+            self_registration: None,
+        };
+
+        let (token, _expires_in) = AuthService::generate_patient_token(&record).expect("patient token");
+        let claims = AuthService::validate_token(&token).expect("claims");
+
+        assert_eq!(claims.token_type, "patient");
+        assert_eq!(claims.patient_id, record.id.map(|id| id.to_hex()));
+    }
 }
@@ -0,0 +1,50 @@
+use crate::bpjs::{BpjsClient, BpjsError, SepRequest};
+use crate::dto::bpjs::{CreateSepRequest, EligibilityResponse, SepResponse};
+use crate::timezone::ClinicTimezone;
+
+pub struct BpjsService<'a> {
+    client: &'a BpjsClient,
+    timezone: ClinicTimezone,
+}
+
+impl<'a> BpjsService<'a> {
+    pub fn new(client: &'a BpjsClient, timezone: ClinicTimezone) -> Self {
+        Self { client, timezone }
+    }
+
+    fn date_or_today(&self, date: Option<String>) -> String {
+        date.map(|d| d.trim().to_string()).unwrap_or_else(|| self.timezone.today(chrono::Utc::now()))
+    }
+
+    pub async fn eligibility(&self, card_number: &str, date: Option<String>) -> Result<EligibilityResponse, BpjsError> {
+        let date = self.date_or_today(date);
+        let (participant, cached) = self.client.participant(card_number, &date).await?;
+        Ok(EligibilityResponse { participant, date, cached, source: self.client.mode().to_string() })
+    }
+
+    /// Issue a SEP for an active participant; the care class defaults to the entitled one.
+    pub async fn create_sep(&self, request: CreateSepRequest, user: &str) -> Result<SepResponse, BpjsError> {
+        let service_date = self.date_or_today(request.service_date);
+        let (participant, _) = self.client.participant(&request.card_number, &service_date).await?;
+        if !participant.active {
+            return Err(BpjsError::Ineligible(format!("Participant is not active ({})", participant.status)));
+        }
+        let class = request.class.or_else(|| participant.class.clone())
+            .ok_or_else(|| BpjsError::Ineligible("Participant has no entitled care class".to_string()))?;
+
+        let sep = self.client.create_sep(&SepRequest {
+            card_number: request.card_number,
+            service_date,
+            service_type: if request.care_type == "inpatient" { "1" } else { "2" }.to_string(),
+            class,
+            medical_record_number: request.medical_record_number,
+            diagnosis_code: request.diagnosis_code.to_uppercase(),
+            poli_code: request.poli_code.to_uppercase(),
+            referral_number: request.referral_number,
+            doctor_code: request.doctor_code,
+            phone: request.phone,
+            user: user.to_string(),
+        }).await?;
+        Ok(SepResponse { sep, participant, source: self.client.mode().to_string() })
+    }
+}
@@ -1,9 +1,59 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use mongodb::bson::oid::ObjectId;
-use chrono::Local;
+use axum::http::StatusCode;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use crate::repository::{ChildCodeRepository, CodeRepository};
-use crate::models::{ChildCode, ParentCodeEmbed};
-use crate::dto::child_code::{CreateChildCodeRequest, UpdateChildCodeRequest};
+use crate::models::{ChildCode, Code, ParentCodeEmbed};
+use crate::dto::child_code::{
+    BulkChildCodeItem, BulkChildCodeResponse, BulkChildCodeResult, BulkLinkChildCodesRequest, CreateChildCodeRequest, UpdateChildCodeRequest,
+};
+
+/// A planned link, or the result status and error of an item that cannot be linked
+type PlannedLink = Result<ChildCode, (&'static str, String)>;
+
+/// Checks every item against the parent, the fetched `codes` (by id) and the codes already
+/// `linked` under the parent, before anything is written
+fn plan_links(parent: &Code, children: &[BulkChildCodeItem], codes: &HashMap<String, Code>, linked: &[String], now: DateTime) -> Vec<PlannedLink> {
+    let parent_id = parent.id.map(|id| id.to_hex()).unwrap_or_default();
+    let mut seen: Vec<&str> = Vec::new();
+
+    children.iter().map(|item| {
+        crate::validation::validate_item(item).map_err(|e| ("invalid", e))?;
+        if item.child_code_id == parent_id {
+            return Err(("invalid", "A code cannot be its own child".to_string()));
+        }
+        let child = codes.get(&item.child_code_id)
+            .ok_or_else(|| ("not_found", format!("Code {} not found", item.child_code_id)))?;
+        if linked.contains(&item.child_code_id) {
+            return Err(("duplicate", format!("Code {} is already a child of {}", child.code, parent.code)));
+        }
+        if seen.contains(&item.child_code_id.as_str()) {
+            return Err(("duplicate", "Repeats an earlier child in this request".to_string()));
+        }
+        seen.push(&item.child_code_id);
+
+        Ok(ChildCode {
+            id: None,
+            parent: ParentCodeEmbed {
+                code_id: parent_id.clone(),
+                code: parent.code.clone(),
+                system: parent.system.clone(),
+                display: parent.display.clone(),
+            },
+            code_id: item.child_code_id.clone(),
+            code: child.code.clone(),
+            system: child.system.clone(),
+            display: child.display.clone(),
+            norut: item.norut,
+            created_at: now,
+            updated_at: Some(now),
+        })
+    }).collect()
+}
+
+fn bulk_result(index: usize, status: &str, child_code: Option<ChildCode>, error: Option<String>) -> BulkChildCodeResult {
+    BulkChildCodeResult { index, status: status.to_string(), child_code, error }
+}
 
 pub struct ChildCodeService {
     repo: Arc<ChildCodeRepository>,
@@ -39,13 +89,52 @@ impl ChildCodeService {
             system: child_code_ref.system,
             display: child_code_ref.display,
             norut: dto.norut,
-            created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            updated_at: Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+            created_at: DateTime::now(),
+            updated_at: Some(DateTime::now()),
         };
 
         self.repo.create(child_code).await
     }
 
+    /// Links many codes under `parent_id`. Every item is checked first; the links that pass are
+    /// inserted in one transaction, or none at all with `all_or_nothing` when any item fails.
+    pub async fn bulk_link(&self, parent_id: ObjectId, dto: BulkLinkChildCodesRequest) -> Result<BulkChildCodeResponse, (StatusCode, String)> {
+        let parent = self.code_repo.find_by_id(parent_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "Parent Code not found".to_string()))?;
+
+        let ids: Vec<ObjectId> = dto.children.iter().filter_map(|item| ObjectId::parse_str(&item.child_code_id).ok()).collect();
+        let codes: HashMap<String, Code> = self.code_repo.find_by_ids(&ids).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .into_iter()
+            .filter_map(|code| code.id.map(|id| (id.to_hex(), code)))
+            .collect();
+        let linked = self.repo.find_linked_code_ids(&parent_id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let planned = plan_links(&parent, &dto.children, &codes, &linked, DateTime::now());
+        let rejected = planned.iter().filter(|link| link.is_err()).count();
+
+        if dto.all_or_nothing && rejected > 0 {
+            let results = planned.into_iter().enumerate().map(|(index, link)| match link {
+                Ok(_) => bulk_result(index, "skipped", None, Some("Not linked because another child failed its checks".to_string())),
+                Err((status, e)) => bulk_result(index, status, None, Some(e)),
+            }).collect::<Vec<_>>();
+            return Ok(BulkChildCodeResponse { created: 0, failed: results.len(), results });
+        }
+
+        let links: Vec<ChildCode> = planned.iter().filter_map(|link| link.as_ref().ok().cloned()).collect();
+        let mut created = self.repo.create_all(links).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .into_iter();
+        let results = planned.into_iter().enumerate().map(|(index, link)| match link {
+            Ok(_) => bulk_result(index, "created", created.next(), None),
+            Err((status, e)) => bulk_result(index, status, None, Some(e)),
+        }).collect::<Vec<_>>();
+
+        Ok(BulkChildCodeResponse { created: results.len() - rejected, failed: rejected, results })
+    }
+
     pub async fn get_all(&self) -> Result<Vec<ChildCode>, String> {
         self.repo.find_all().await
     }
@@ -86,7 +175,7 @@ impl ChildCodeService {
             existing.norut = norut;
         }
 
-        existing.updated_at = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        existing.updated_at = Some(DateTime::now());
 
         self.repo.update(id, existing).await
     }
@@ -95,3 +184,56 @@ impl ChildCodeService {
         self.repo.delete(id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CodeCategoryEmbed;
+
+    fn code(code: &str) -> Code {
+        Code {
+            id: Some(ObjectId::new()),
+            code: code.to_string(),
+            display: code.to_string(),
+            display_i18n: Default::default(),
+            system: "http://loinc.org".to_string(),
+            category: CodeCategoryEmbed { code: "panel".to_string(), system: "local".to_string(), display: "Panel".to_string() },
+            active: true,
+            created_at: DateTime::now(),
+            updated_at: None,
+        }
+    }
+
+    fn item(code: &Code, norut: i32) -> BulkChildCodeItem {
+        BulkChildCodeItem { child_code_id: code.id.unwrap().to_hex(), norut }
+    }
+
+    #[test]
+    fn every_child_is_checked_before_linking() {
+        let parent = code("85354-9");
+        let (systolic, diastolic, pulse) = (code("8480-6"), code("8462-4"), code("8867-4"));
+        let codes: HashMap<String, Code> = [&systolic, &diastolic]
+            .into_iter()
+            .map(|c| (c.id.unwrap().to_hex(), c.clone()))
+            .collect();
+        let linked = vec![diastolic.id.unwrap().to_hex()];
+        let children = vec![
+            item(&systolic, 1),
+            item(&diastolic, 2),
+            item(&pulse, 3),
+            item(&systolic, 4),
+            item(&parent, 5),
+            BulkChildCodeItem { child_code_id: "short".to_string(), norut: 0 },
+        ];
+
+        let statuses: Vec<&str> = plan_links(&parent, &children, &codes, &linked, DateTime::now())
+            .iter()
+            .map(|link| link.as_ref().map_or_else(|(status, _)| *status, |_| "ok"))
+            .collect();
+        assert_eq!(statuses, ["ok", "duplicate", "not_found", "duplicate", "invalid", "invalid"]);
+
+        let link = plan_links(&parent, &children[..1], &codes, &linked, DateTime::now()).remove(0).unwrap();
+        assert_eq!(link.parent.code, "85354-9");
+        assert_eq!((link.code.as_str(), link.norut), ("8480-6", 1));
+    }
+}
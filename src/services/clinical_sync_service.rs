@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Uuid};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+use crate::dto::note::{CreateNoteRequest, DeleteNoteRequest, NoteResponse, UpdateNoteRequest};
+use crate::dto::sync::{
+    ClientMutation, ClinicalPullQuery, CollectionChanges, MutationResult, PushRequest, PushResponse, ResolveConflictRequest,
+    SyncChanges, SyncConflictQuery,
+};
+use crate::middleware::AuthUser;
+use crate::models::{SyncConflict, SyncMutation};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::{NoteRepository, SyncConflictRepository, SyncMutationRepository};
+use crate::services::NoteService;
+use crate::status::{ConflictResolution, SyncConflictStatus, SyncEntity, SyncOperation, SyncOutcome, SyncStrategy};
+use crate::sync::{decide, Decision, Position, SyncCursor, NOTE_FEEDS};
+
+const DEFAULT_LIMIT: i64 = 500;
+const MAX_MEDICAL_RECORDS: usize = 200;
+const NOTES: &str = "notes";
+const NOTES_DELETED: &str = "notes_deleted";
+
+/// Push and pull of clinical notes for offline devices, see `crate::sync`
+pub struct ClinicalSyncService {
+    notes: NoteService,
+    note_repo: NoteRepository,
+    mutations: SyncMutationRepository,
+    conflicts: SyncConflictRepository,
+}
+
+/// What became of a mutation, before it is recorded
+struct Outcome {
+    status: SyncOutcome,
+    entity_id: Option<String>,
+    version: Option<i32>,
+    conflict_id: Option<String>,
+    error: Option<String>,
+}
+
+impl Outcome {
+    fn new(status: SyncOutcome) -> Self {
+        Self { status, entity_id: None, version: None, conflict_id: None, error: None }
+    }
+
+    fn rejected(error: impl Into<String>) -> Self {
+        Self { error: Some(error.into()), ..Self::new(SyncOutcome::Rejected) }
+    }
+
+    fn applied(id: ObjectId, note: Option<NoteResponse>) -> Self {
+        Self {
+            entity_id: Some(id.to_hex()),
+            version: note.map(|note| note.version),
+            ..Self::new(SyncOutcome::Applied)
+        }
+    }
+
+    /// Client errors reject the mutation for good; server errors and lost races leave it to be
+    /// pushed again
+    fn from_error((status, error): (StatusCode, String)) -> Self {
+        let outcome = if status.is_server_error() || status == StatusCode::CONFLICT { SyncOutcome::Failed } else { SyncOutcome::Rejected };
+        Self { error: Some(error), ..Self::new(outcome) }
+    }
+
+    fn into_result(self, client_id: String, replayed: bool) -> MutationResult {
+        MutationResult {
+            client_id,
+            status: self.status,
+            entity_id: self.entity_id,
+            version: self.version,
+            conflict_id: self.conflict_id,
+            error: self.error,
+            replayed,
+        }
+    }
+}
+
+/// The mutation's `data` as the request body `T`
+fn body<T: DeserializeOwned + Validate>(data: &serde_json::Value) -> Result<T, String> {
+    let body: T = serde_json::from_value(data.clone()).map_err(|e| format!("Invalid data: {}", e))?;
+    crate::validation::validate_item(&body)?;
+    Ok(body)
+}
+
+/// Distinct ObjectIds of a comma separated list
+fn medical_record_ids(raw: &str) -> Result<Vec<String>, String> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in raw.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let id = ObjectId::parse_str(id).map_err(|_| format!("'{}' is not a valid medical record ID", id))?.to_hex();
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    if ids.is_empty() || ids.len() > MAX_MEDICAL_RECORDS {
+        return Err(format!("Pass between 1 and {} medical record IDs", MAX_MEDICAL_RECORDS));
+    }
+    Ok(ids)
+}
+
+impl ClinicalSyncService {
+    pub fn new(notes: NoteService, note_repo: NoteRepository, mutations: SyncMutationRepository, conflicts: SyncConflictRepository) -> Self {
+        Self { notes, note_repo, mutations, conflicts }
+    }
+
+    /// Applies the mutations in order, each with its own result
+    pub async fn push(&self, user: &AuthUser, request: PushRequest) -> PushResponse {
+        let strategy = request.strategy.unwrap_or(SyncStrategy::LastWriterWins);
+        let mut results = Vec::with_capacity(request.mutations.len());
+        for mutation in request.mutations {
+            results.push(self.push_one(user, &request.device_id, &strategy, mutation).await);
+        }
+
+        let count = |status: SyncOutcome| results.iter().filter(|r| r.status == status).count();
+        let (applied, conflicts) = (count(SyncOutcome::Applied), count(SyncOutcome::Conflict));
+        PushResponse { applied, conflicts, not_applied: results.len() - applied - conflicts, results }
+    }
+
+    async fn push_one(&self, user: &AuthUser, device_id: &str, strategy: &SyncStrategy, mutation: ClientMutation) -> MutationResult {
+        if let Err(e) = crate::validation::validate_item(&mutation) {
+            return Outcome::rejected(e).into_result(mutation.client_id, false);
+        }
+        let client_id = mutation.client_id.trim().to_lowercase();
+
+        match self.mutations.find_by_client_id(&client_id).await {
+            Ok(Some(previous)) if previous.user_id != user.id => {
+                return Outcome::rejected("client_id was already used by another user").into_result(client_id, false);
+            }
+            Ok(Some(previous)) => {
+                let outcome = Outcome {
+                    status: previous.status,
+                    entity_id: previous.entity_id,
+                    version: previous.version,
+                    conflict_id: previous.conflict_id,
+                    error: previous.error,
+                };
+                return outcome.into_result(client_id, true);
+            }
+            Ok(None) => {}
+            Err(e) => return Outcome::from_error((StatusCode::INTERNAL_SERVER_ERROR, e)).into_result(client_id, false),
+        }
+
+        let outcome = self.apply(user, device_id, strategy, &client_id, &mutation).await;
+        if outcome.status != SyncOutcome::Failed {
+            let record = SyncMutation {
+                id: None,
+                client_id: client_id.clone(),
+                user_id: user.id.clone(),
+                device_id: device_id.to_string(),
+                entity: mutation.entity,
+                operation: mutation.operation,
+                entity_id: outcome.entity_id.clone(),
+                status: outcome.status.clone(),
+                version: outcome.version,
+                conflict_id: outcome.conflict_id.clone(),
+                error: outcome.error.clone(),
+                created_at: DateTime::now(),
+            };
+            if let Err(e) = self.mutations.insert(record).await {
+                tracing::error!(client_id = %client_id, error = %e, "Recording a sync mutation failed");
+            }
+        }
+        outcome.into_result(client_id, false)
+    }
+
+    async fn apply(&self, user: &AuthUser, device_id: &str, strategy: &SyncStrategy, client_id: &str, mutation: &ClientMutation) -> Outcome {
+        if mutation.entity != SyncEntity::Notes {
+            return Outcome::rejected(format!("Cannot sync {}", mutation.entity));
+        }
+        if mutation.operation == SyncOperation::Create {
+            let request = match body::<CreateNoteRequest>(&mutation.data) {
+                Ok(request) => request,
+                Err(e) => return Outcome::rejected(e),
+            };
+            return match self.notes.create(user, request).await {
+                Ok(note) => Outcome {
+                    entity_id: Some(note.id.clone()),
+                    version: Some(note.version),
+                    ..Outcome::new(SyncOutcome::Applied)
+                },
+                Err(e) => Outcome::from_error(e),
+            };
+        }
+
+        let id = match self.entity_id(mutation.entity_id.as_deref()).await {
+            Ok(id) => id,
+            Err(outcome) => return outcome,
+        };
+        let Some(base_version) = mutation.base_version else {
+            return Outcome::rejected("base_version is required for updates and deletes");
+        };
+        let valid = match mutation.operation {
+            SyncOperation::Update => body::<UpdateNoteRequest>(&mutation.data).map(|_| ()),
+            _ => body::<DeleteNoteRequest>(&mutation.data).map(|_| ()),
+        };
+        if let Err(e) = valid {
+            return Outcome::rejected(e);
+        }
+        let current = match self.note_repo.find_by_id(id).await {
+            Ok(Some(note)) => note,
+            Ok(None) => return Outcome { entity_id: Some(id.to_hex()), ..Outcome::rejected("Note not found") },
+            Err(e) => return Outcome::from_error((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        };
+        let changed_at = crate::datetime::parse(&mutation.changed_at).unwrap_or_else(DateTime::now);
+
+        match decide(base_version, current.version, strategy, changed_at, current.updated_at) {
+            Decision::Apply => match self.perform(&mutation.operation, id, current.version, user, &mutation.data).await {
+                Ok(note) => Outcome::applied(id, note),
+                Err(e) => Outcome { entity_id: Some(id.to_hex()), ..Outcome::from_error(e) },
+            },
+            Decision::Superseded => Outcome {
+                entity_id: Some(id.to_hex()),
+                version: Some(current.version),
+                error: Some(format!("The note was changed on the server after this edit; it is at version {}", current.version)),
+                ..Outcome::new(SyncOutcome::Superseded)
+            },
+            Decision::Conflict => {
+                let conflict = SyncConflict {
+                    id: None,
+                    client_id: client_id.to_string(),
+                    user_id: user.id.clone(),
+                    device_id: device_id.to_string(),
+                    entity: mutation.entity.clone(),
+                    operation: mutation.operation.clone(),
+                    entity_id: id.to_hex(),
+                    base_version,
+                    server_version: current.version,
+                    data: mutation.data.clone(),
+                    changed_at,
+                    status: SyncConflictStatus::Open,
+                    resolution: None,
+                    resolved_by: None,
+                    resolved_at: None,
+                    created_at: DateTime::now(),
+                };
+                match self.conflicts.create(conflict).await {
+                    Ok(conflict) => Outcome {
+                        entity_id: Some(id.to_hex()),
+                        version: Some(current.version),
+                        conflict_id: conflict.id.map(|id| id.to_hex()),
+                        ..Outcome::new(SyncOutcome::Conflict)
+                    },
+                    Err(e) => Outcome::from_error((StatusCode::INTERNAL_SERVER_ERROR, e)),
+                }
+            }
+        }
+    }
+
+    /// The note an update or delete targets: an ObjectId, or the UUID of the create that made it
+    async fn entity_id(&self, entity_id: Option<&str>) -> Result<ObjectId, Outcome> {
+        let Some(entity_id) = entity_id.map(str::trim).filter(|id| !id.is_empty()) else {
+            return Err(Outcome::rejected("entity_id is required for updates and deletes"));
+        };
+        if let Ok(id) = ObjectId::parse_str(entity_id) {
+            return Ok(id);
+        }
+        if Uuid::parse_str(entity_id).is_err() {
+            return Err(Outcome::rejected(format!("'{}' is neither a note ID nor a mutation UUID", entity_id)));
+        }
+        match self.mutations.find_by_client_id(&entity_id.to_lowercase()).await {
+            Ok(Some(create)) if create.operation == SyncOperation::Create => create.entity_id
+                .and_then(|id| ObjectId::parse_str(id).ok())
+                .ok_or_else(|| Outcome::rejected(format!("The create {} was not applied", entity_id))),
+            Ok(_) => Err(Outcome::rejected(format!("No create was pushed with client_id {}", entity_id))),
+            Err(e) => Err(Outcome::from_error((StatusCode::INTERNAL_SERVER_ERROR, e))),
+        }
+    }
+
+    /// Writes an update or delete over `version` of the note
+    async fn perform(
+        &self,
+        operation: &SyncOperation,
+        id: ObjectId,
+        version: i32,
+        user: &AuthUser,
+        data: &serde_json::Value,
+    ) -> Result<Option<NoteResponse>, (StatusCode, String)> {
+        let invalid = |e: String| (StatusCode::UNPROCESSABLE_ENTITY, e);
+        match operation {
+            SyncOperation::Update => {
+                let request = UpdateNoteRequest { version: Some(version), ..body::<UpdateNoteRequest>(data).map_err(invalid)? };
+                self.notes.update(id, user, request).await.map(Some)
+            }
+            SyncOperation::Delete => match self.notes.delete(id, user, body(data).map_err(invalid)?).await? {
+                true => Ok(None),
+                false => Err((StatusCode::NOT_FOUND, "Note not found".to_string())),
+            },
+            other => Err((StatusCode::UNPROCESSABLE_ENTITY, format!("Cannot resolve a {} conflict", other))),
+        }
+    }
+
+    /// Notes of the medical records changed and deleted since `since`
+    pub async fn pull(&self, query: ClinicalPullQuery) -> Result<SyncChanges, (StatusCode, String)> {
+        let ids = medical_record_ids(&query.medical_record_ids).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let since = query.since.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let full = since.is_none();
+        let mut cursor = match since {
+            Some(since) => SyncCursor::parse(since, &NOTE_FEEDS).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+            None => SyncCursor::default(),
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+        // As for reference data, a full sync starts the deletion feed now
+        let started = DateTime::now();
+        let mut has_more = false;
+        let mut changes = CollectionChanges::default();
+
+        let position = cursor.position(NOTES);
+        let mut notes = self.note_repo.find_changed(&ids, position, limit + 1).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if notes.len() as i64 > limit {
+            has_more = true;
+            notes.truncate(limit as usize);
+        }
+        for note in notes {
+            cursor.advance(NOTES, Position { at: note.updated_at, id: note.id });
+            let created = position.is_none_or(|p| note.created_at >= p.at);
+            match serde_json::to_value(NoteService::map_to_response(note)) {
+                Ok(value) if created => changes.created.push(value),
+                Ok(value) => changes.updated.push(value),
+                Err(e) => tracing::warn!(error = %e, "Skipping unreadable note in sync"),
+            }
+        }
+
+        let position = cursor.position(NOTES_DELETED)
+            .or(full.then_some(Position { at: started, id: None }));
+        let mut deleted = self.note_repo.find_deleted(&ids, position, limit + 1).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if deleted.len() as i64 > limit {
+            has_more = true;
+            deleted.truncate(limit as usize);
+        }
+        if let Some(position) = position {
+            cursor.advance(NOTES_DELETED, position);
+        }
+        for note in deleted {
+            if let (Some(at), Some(id)) = (note.deleted_at, note.id) {
+                cursor.advance(NOTES_DELETED, Position { at, id: Some(id) });
+                changes.deleted.push(id.to_hex());
+            }
+        }
+
+        Ok(SyncChanges { cursor: cursor.encode(), has_more, collections: BTreeMap::from([(NOTES.to_string(), changes)]) })
+    }
+
+    /// Conflicts of `owner`, or of everyone when `None`, newest first
+    pub async fn conflicts(
+        &self,
+        owner: Option<&str>,
+        query: SyncConflictQuery,
+        pagination: PaginationParams,
+    ) -> Result<(Vec<SyncConflict>, PaginationMeta), (StatusCode, String)> {
+        let mut filter = doc! {};
+        if let Some(owner) = owner {
+            filter.insert("user_id", owner);
+        }
+        if let Some(status) = query.status {
+            filter.insert("status", status);
+        }
+        match self.conflicts.find_paginated(filter, &pagination).await {
+            Ok((conflicts, total)) => Ok((conflicts, PaginationMeta::new(pagination.page, pagination.limit, total))),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Settles an open conflict; `use_client` writes the queued edit over the current version.
+    /// `owner` limits it to that user's conflicts.
+    pub async fn resolve(&self, id: ObjectId, user: &AuthUser, owner: Option<&str>, request: ResolveConflictRequest) -> Result<SyncConflict, (StatusCode, String)> {
+        let conflict = self.conflicts.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .filter(|conflict| owner.is_none_or(|owner| conflict.user_id == owner))
+            .ok_or((StatusCode::NOT_FOUND, "Sync conflict not found".to_string()))?;
+        if conflict.status != SyncConflictStatus::Open {
+            return Err((StatusCode::CONFLICT, "Sync conflict is already resolved".to_string()));
+        }
+
+        if request.resolution == ConflictResolution::UseClient {
+            let note_id = ObjectId::parse_str(&conflict.entity_id).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            let current = self.note_repo.find_by_id(note_id).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+                .ok_or((StatusCode::CONFLICT, "The note was deleted; only keep_server applies".to_string()))?;
+            self.perform(&conflict.operation, note_id, current.version, user, &conflict.data).await?;
+        }
+
+        self.conflicts.resolve(id, &request.resolution, &user.id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Sync conflict was resolved concurrently".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn medical_record_ids_are_deduplicated_and_bounded() {
+        let id = "65a1b2c3d4e5f60718293a4b";
+        assert_eq!(medical_record_ids(&format!("{id}, {id},")).unwrap(), vec![id.to_string()]);
+        assert!(medical_record_ids("patient-1").is_err());
+        assert!(medical_record_ids(" , ").is_err());
+        let too_many = (0..=MAX_MEDICAL_RECORDS).map(|_| ObjectId::new().to_hex()).collect::<Vec<_>>().join(",");
+        assert!(medical_record_ids(&too_many).is_err());
+
+        assert_eq!(Outcome::from_error((StatusCode::CONFLICT, String::new())).status, SyncOutcome::Failed);
+        assert_eq!(Outcome::from_error((StatusCode::UNPROCESSABLE_ENTITY, String::new())).status, SyncOutcome::Rejected);
+    }
+}
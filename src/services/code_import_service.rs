@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use axum::http::StatusCode;
+use mongodb::bson::{self, doc, oid::ObjectId, DateTime, Document};
+use serde::{Deserialize, Serialize};
+use crate::dto::code::ImportCodesDto;
+use crate::models::{Code, CodeCategoryEmbed, Job, JobProgress};
+use crate::repository::CodeRepository;
+use crate::services::JobService;
+use crate::terminology::{self, ImportFormat, Terminology, TerminologyEntry};
+
+pub const CODE_IMPORT_JOB: &str = "code_import";
+
+const MAX_IMPORT_ENTRIES: usize = 50_000;
+const BATCH_SIZE: usize = 500;
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// Job payload for a code import, stored on the job so it can be re-run.
+#[derive(Debug, Serialize, Deserialize)]
+struct ImportPayload {
+    terminology: String,
+    system: String,
+    category: CodeCategoryEmbed,
+    update_existing: bool,
+    entries: Vec<ImportEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ImportEntry {
+    code: String,
+    display: String,
+}
+
+impl From<TerminologyEntry> for ImportEntry {
+    fn from(entry: TerminologyEntry) -> Self {
+        Self { code: entry.code, display: entry.display }
+    }
+}
+
+/// Bulk import of LOINC / SNOMED CT subsets into the `codes` collection.
+pub struct CodeImportService {
+    codes: CodeRepository,
+    jobs: JobService,
+}
+
+impl CodeImportService {
+    pub fn new(codes: CodeRepository, jobs: JobService) -> Self {
+        Self { codes, jobs }
+    }
+
+    /// Parse and validate an export, then persist it as a pending import job.
+    pub async fn enqueue(&self, dto: ImportCodesDto, actor: &str) -> Result<Job, (StatusCode, String)> {
+        let terminology = Terminology::parse(&dto.terminology)
+            .ok_or((StatusCode::BAD_REQUEST, format!("Unsupported terminology '{}' (expected loinc or snomed)", dto.terminology)))?;
+        let format = ImportFormat::parse(&dto.format)
+            .ok_or((StatusCode::BAD_REQUEST, format!("Unsupported format '{}' (expected csv or json)", dto.format)))?;
+
+        let entries = terminology::parse_entries(terminology, format, &dto.content)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        if entries.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Import contains no active entries".to_string()));
+        }
+        if entries.len() > MAX_IMPORT_ENTRIES {
+            return Err((StatusCode::BAD_REQUEST, format!("Import exceeds {} entries; split it into smaller files", MAX_IMPORT_ENTRIES)));
+        }
+
+        let category_oid = ObjectId::parse_str(&dto.category_id)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid Category ID format".to_string()))?;
+        let category = self.codes.find_by_id(category_oid).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Category Code not found".to_string()))?;
+
+        let total = entries.len() as u64;
+        let payload = ImportPayload {
+            terminology: dto.terminology.trim().to_lowercase(),
+            system: terminology.system().to_string(),
+            category: CodeCategoryEmbed {
+                code: category.code,
+                system: category.system,
+                display: category.display,
+            },
+            update_existing: dto.update_existing,
+            entries: entries.into_iter().map(ImportEntry::from).collect(),
+        };
+        let payload = bson::to_document(&payload).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        self.jobs.enqueue(CODE_IMPORT_JOB, payload, total, actor).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Run an import job, deduping by system + code and reporting progress after every batch.
+    pub async fn execute(&self, job: &Job) -> Result<Document, String> {
+        let job_id = job.id.ok_or("Job has no ID")?;
+        let payload: ImportPayload = bson::from_document(job.payload.clone())
+            .map_err(|e| format!("Invalid import payload: {}", e))?;
+
+        let mut progress = JobProgress { total: payload.entries.len() as u64, ..Default::default() };
+        let mut created = 0u64;
+        let mut updated = 0u64;
+        let mut errors: Vec<String> = Vec::new();
+
+        // Later rows repeating a code already seen in this file are skipped
+        let mut seen = HashSet::new();
+        let mut unique = Vec::new();
+        for entry in payload.entries {
+            if seen.insert(entry.code.clone()) {
+                unique.push(entry);
+            } else {
+                progress.skipped += 1;
+                progress.processed += 1;
+            }
+        }
+
+        for batch in unique.chunks(BATCH_SIZE) {
+            let codes: Vec<String> = batch.iter().map(|e| e.code.clone()).collect();
+            let existing: HashMap<String, Code> = self.codes.find_by_system_and_codes(&payload.system, &codes).await?
+                .into_iter()
+                .map(|c| (c.code.clone(), c))
+                .collect();
+
+            let now = DateTime::now();
+            let mut inserts = Vec::new();
+
+            for entry in batch {
+                match existing.get(&entry.code) {
+                    Some(current) if payload.update_existing && Self::differs(current, entry, &payload.category) => {
+                        let Some(id) = current.id else { continue };
+                        match self.codes.update_display_and_category(id, &entry.display, &payload.category, now).await {
+                            Ok(()) => {
+                                updated += 1;
+                                progress.succeeded += 1;
+                            }
+                            Err(e) => {
+                                progress.failed += 1;
+                                errors.push(format!("{}: {}", entry.code, e));
+                            }
+                        }
+                    }
+                    Some(_) => progress.skipped += 1,
+                    None => inserts.push(Code {
+                        id: None,
+                        code: entry.code.clone(),
+                        display: entry.display.clone(),
+                        display_i18n: Default::default(),
+                        system: payload.system.clone(),
+                        category: payload.category.clone(),
+                        active: true,
+                        created_at: now,
+                        updated_at: Some(now),
+                    }),
+                }
+            }
+
+            let batch_inserts = inserts.len() as u64;
+            match self.codes.insert_many(inserts).await {
+                Ok(count) => {
+                    created += count as u64;
+                    progress.succeeded += count as u64;
+                }
+                Err(e) => {
+                    progress.failed += batch_inserts;
+                    errors.push(format!("Batch insert failed: {}", e));
+                }
+            }
+
+            progress.processed += batch.len() as u64;
+            if let Err(e) = self.jobs.report_progress(job_id, &progress).await {
+                tracing::warn!(job_id = %job_id, error = %e, "Failed to report job progress");
+            }
+        }
+
+        let error_count = errors.len() as i64;
+        errors.truncate(MAX_REPORTED_ERRORS);
+
+        Ok(doc! {
+            "system": payload.system,
+            "created": created as i64,
+            "updated": updated as i64,
+            "skipped": progress.skipped as i64,
+            "failed": progress.failed as i64,
+            "error_count": error_count,
+            "errors": errors,
+        })
+    }
+
+    fn differs(current: &Code, entry: &ImportEntry, category: &CodeCategoryEmbed) -> bool {
+        current.display != entry.display
+            || current.category.code != category.code
+            || current.category.system != category.system
+    }
+}
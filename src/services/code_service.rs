@@ -1,9 +1,10 @@
 use std::sync::Arc;
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use crate::repository::CodeRepository;
 use crate::models::{Code, CodeCategoryEmbed};
-use crate::dto::code::{CreateCodeDto, UpdateCodeDto};
-use chrono::Local;
+use crate::code_cache::CodeCache;
+use crate::dto::code::{CodeRefDto, CodeValidation, CreateCodeDto, UpdateCodeDto};
+use crate::i18n::Locale;
 
 pub struct CodeService {
     repo: Arc<CodeRepository>,
@@ -30,14 +31,16 @@ impl CodeService {
             id: None,
             code: dto.code,
             display: dto.display,
+            display_i18n: dto.display_i18n.unwrap_or_default(),
             system: dto.system,
             category: CodeCategoryEmbed {
                 code: category_code.code,
                 system: category_code.system,
                 display: category_code.display,
             },
-            created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
-            updated_at: Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+            active: true,
+            created_at: DateTime::now(),
+            updated_at: Some(DateTime::now()),
         };
 
         self.repo.create(code).await
@@ -72,9 +75,15 @@ impl CodeService {
         if let Some(display) = dto.display {
             existing.display = display;
         }
+        if let Some(display_i18n) = dto.display_i18n {
+            existing.display_i18n = display_i18n;
+        }
         if let Some(system) = dto.system {
             existing.system = system;
         }
+        if let Some(active) = dto.active {
+            existing.active = active;
+        }
         if let Some(category) = dto.category {
             existing.category = CodeCategoryEmbed {
                 code: category.code,
@@ -83,11 +92,32 @@ impl CodeService {
             };
         }
 
-        existing.updated_at = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        existing.updated_at = Some(DateTime::now());
 
         self.repo.update(oid, existing).await
     }
 
+    /// Existence, status and display of each pair, in request order
+    pub async fn validate_codes(&self, cache: &CodeCache, refs: Vec<CodeRefDto>, locale: Locale) -> Result<Vec<CodeValidation>, String> {
+        let keys: Vec<(String, String)> = refs.into_iter().map(|r| (r.system, r.code)).collect();
+        let codes = cache.lookup(&self.repo, &keys).await?;
+
+        Ok(keys.into_iter().map(|key| {
+            let code = codes.get(&key).cloned().flatten().map(|mut code| {
+                code.localize(locale);
+                code
+            });
+            CodeValidation {
+                exists: code.is_some(),
+                active: code.as_ref().is_some_and(|code| code.active),
+                id: code.as_ref().and_then(|code| code.id).map(|id| id.to_hex()),
+                display: code.map(|code| code.display),
+                system: key.0,
+                code: key.1,
+            }
+        }).collect())
+    }
+
     pub async fn delete_code(&self, id: &str) -> Result<bool, String> {
         let oid = ObjectId::parse_str(id).map_err(|_| "Invalid ID format")?;
         self.repo.delete(oid).await
@@ -0,0 +1,378 @@
+use axum::http::StatusCode;
+use chrono::{Days, Months, NaiveDate, Utc};
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::corporate_client::{
+    AddCorporateEmployeeRequest, CorporateClientQuery, CorporateClientResponse, CorporateContractDto, CorporateContractResponse,
+    CorporateEmployeeResponse, CorporateStatementLineResponse, CorporateStatementResponse, CreateCorporateClientRequest,
+    UpdateCorporateClientRequest, UpdateCorporateEmployeeRequest,
+};
+use crate::models::{
+    CorporateClient, CorporateContract, CorporateEmployee, CorporateStatement, CorporateStatementLine, Invoice, MedicalRecord, Organization,
+};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{CorporateClientRepository, InvoiceRepository};
+use crate::sequences::{self, SequenceGenerator};
+use crate::services::price_list_service::round_money;
+use crate::status::CorporateClientStatus;
+use crate::timezone::ClinicTimezone;
+
+pub struct CorporateClientService {
+    clients: CorporateClientRepository,
+    invoices: InvoiceRepository,
+    references: ReferenceChecker,
+    sequences: SequenceGenerator,
+    timezone: ClinicTimezone,
+}
+
+/// First and last day of a `YYYY-MM` month
+fn month_range(month: &str) -> Result<(String, String), (StatusCode, String)> {
+    let first = NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| (StatusCode::BAD_REQUEST, "month must be YYYY-MM".to_string()))?;
+    let last = first.checked_add_months(Months::new(1)).and_then(|next| next.pred_opt()).unwrap_or(first);
+    Ok((first.format("%Y-%m-%d").to_string(), last.format("%Y-%m-%d").to_string()))
+}
+
+/// The client's share of each invoice billed to it, and their sum
+fn statement_lines(invoices: Vec<Invoice>) -> (Vec<CorporateStatementLine>, f64) {
+    let lines: Vec<CorporateStatementLine> = invoices.into_iter()
+        .filter_map(|invoice| {
+            let corporate = invoice.corporate?;
+            Some(CorporateStatementLine {
+                invoice_id: invoice.id.map(|id| id.to_hex()).unwrap_or_default(),
+                invoice_number: invoice.number,
+                patient_id: invoice.patient_id.to_hex(),
+                employee_number: corporate.employee_number,
+                service_date: invoice.service_date,
+                total: invoice.total,
+                amount: corporate.covered_amount,
+            })
+        })
+        .collect();
+    let total = round_money(lines.iter().map(|line| line.amount).sum());
+    (lines, total)
+}
+
+fn contract_from(dto: CorporateContractDto) -> CorporateContract {
+    CorporateContract {
+        number: dto.number.trim().to_string(),
+        start_date: dto.start_date.trim().to_string(),
+        end_date: dto.end_date.map(|end| end.trim().to_string()),
+        terms: dto.terms.into(),
+        payment_term_days: dto.payment_term_days,
+    }
+}
+
+fn internal(e: String) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e)
+}
+
+impl CorporateClientService {
+    pub fn new(clients: CorporateClientRepository, invoices: InvoiceRepository, references: ReferenceChecker, sequences: SequenceGenerator, timezone: ClinicTimezone) -> Self {
+        Self { clients, invoices, references, sequences, timezone }
+    }
+
+    fn map_to_response(client: CorporateClient) -> CorporateClientResponse {
+        CorporateClientResponse {
+            id: client.id.map(|id| id.to_hex()).unwrap_or_default(),
+            organization_id: client.organization_id.to_hex(),
+            code: client.code,
+            name: client.name,
+            billing_email: client.billing_email,
+            contract: CorporateContractResponse {
+                number: client.contract.number,
+                start_date: client.contract.start_date,
+                end_date: client.contract.end_date,
+                terms: client.contract.terms.into(),
+                payment_term_days: client.contract.payment_term_days,
+            },
+            status: client.status,
+            created_by: client.created_by,
+            created_at: crate::datetime::to_rfc3339(client.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(client.updated_at),
+        }
+    }
+
+    fn map_employee(employee: CorporateEmployee) -> CorporateEmployeeResponse {
+        CorporateEmployeeResponse {
+            id: employee.id.map(|id| id.to_hex()).unwrap_or_default(),
+            client_id: employee.client_id,
+            medical_record_id: employee.patient_id.to_hex(),
+            employee_number: employee.employee_number,
+            start_date: employee.start_date,
+            end_date: employee.end_date,
+            created_by: employee.created_by,
+            created_at: crate::datetime::to_rfc3339(employee.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(employee.updated_at),
+        }
+    }
+
+    fn map_statement(statement: CorporateStatement) -> CorporateStatementResponse {
+        CorporateStatementResponse {
+            id: statement.id.map(|id| id.to_hex()).unwrap_or_default(),
+            number: statement.number,
+            client_id: statement.client_id,
+            organization_id: statement.organization_id,
+            month: statement.month,
+            lines: statement.lines.into_iter().map(|line| CorporateStatementLineResponse {
+                invoice_id: line.invoice_id,
+                invoice_number: line.invoice_number,
+                medical_record_id: line.patient_id,
+                employee_number: line.employee_number,
+                service_date: line.service_date,
+                total: line.total,
+                amount: line.amount,
+            }).collect(),
+            total: statement.total,
+            due_date: statement.due_date,
+            generated_by: statement.generated_by,
+            generated_at: crate::datetime::to_rfc3339(statement.generated_at),
+        }
+    }
+
+    async fn client(&self, id: ObjectId) -> Result<CorporateClient, (StatusCode, String)> {
+        self.clients.find_by_id(id).await
+            .map_err(internal)?
+            .ok_or((StatusCode::NOT_FOUND, "Corporate client not found".to_string()))
+    }
+
+    pub async fn create(&self, request: CreateCorporateClientRequest, created_by: &str) -> Result<CorporateClientResponse, (StatusCode, String)> {
+        let organization = Ref::<Organization>::parse_field("organization_id", &request.organization_id)?;
+        self.references.ensure_exist(&[organization.check("organization_id")]).await?;
+
+        let client = CorporateClient {
+            id: None,
+            organization_id: organization,
+            code: request.code.trim().to_string(),
+            name: request.name.trim().to_string(),
+            billing_email: request.billing_email.map(|email| email.trim().to_string()),
+            contract: contract_from(request.contract),
+            status: request.status.unwrap_or(CorporateClientStatus::Active),
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.clients.create(client).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            // `corporate_clients_code` is unique per organization
+            Err(e) if e.contains("E11000") => Err((StatusCode::CONFLICT, "Another corporate client of this organization has this code".to_string())),
+            Err(e) => Err(internal(e)),
+        }
+    }
+
+    pub async fn list(&self, query: CorporateClientQuery, pagination: PaginationParams) -> Result<(Vec<CorporateClientResponse>, PaginationMeta), (StatusCode, String)> {
+        let (clients, total) = self.clients.find_paginated(query.organization_id.as_deref(), query.status.as_ref(), &pagination).await
+            .map_err(internal)?;
+        let responses = clients.into_iter().map(Self::map_to_response).collect();
+        Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<CorporateClientResponse>, (StatusCode, String)> {
+        self.clients.find_by_id(id).await
+            .map(|client| client.map(Self::map_to_response))
+            .map_err(internal)
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdateCorporateClientRequest) -> Result<CorporateClientResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(name) = request.name { set.insert("name", name.trim()); }
+        if let Some(email) = request.billing_email { set.insert("billingEmail", email.trim()); }
+        if let Some(status) = request.status { set.insert("status", status); }
+        if let Some(contract) = request.contract {
+            set.insert("contract", mongodb::bson::to_bson(&contract_from(contract)).map_err(|e| internal(e.to_string()))?);
+        }
+
+        self.clients.update_fields(id, set).await
+            .map_err(internal)?
+            .map(Self::map_to_response)
+            .ok_or((StatusCode::NOT_FOUND, "Corporate client not found".to_string()))
+    }
+
+    /// Clients already billed stay for their statements; deactivate them instead
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        if self.invoices.any_billed_to_client(&id.to_hex()).await.map_err(internal)? {
+            return Err((StatusCode::CONFLICT, "Invoices were billed to this corporate client; set its status to inactive instead".to_string()));
+        }
+        self.clients.delete(id).await.map_err(internal)
+    }
+
+    pub async fn employees(&self, client_id: ObjectId) -> Result<Vec<CorporateEmployeeResponse>, (StatusCode, String)> {
+        self.client(client_id).await?;
+        let employees = self.clients.find_employees(&client_id.to_hex()).await.map_err(internal)?;
+        Ok(employees.into_iter().map(Self::map_employee).collect())
+    }
+
+    pub async fn add_employee(&self, client_id: ObjectId, request: AddCorporateEmployeeRequest, created_by: &str) -> Result<CorporateEmployeeResponse, (StatusCode, String)> {
+        self.client(client_id).await?;
+        let patient = Ref::<MedicalRecord>::parse_field("medical_record_id", &request.medical_record_id)?;
+        self.references.ensure_exist(&[patient.check("medical_record_id")]).await?;
+
+        let start_date = request.start_date.map(|d| d.trim().to_string()).unwrap_or_else(|| self.timezone.today(Utc::now()));
+        let end_date = request.end_date.map(|d| d.trim().to_string());
+        if end_date.as_ref().is_some_and(|end| *end < start_date) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "end_date cannot be before start_date".to_string()));
+        }
+
+        let employee = CorporateEmployee {
+            id: None,
+            client_id: client_id.to_hex(),
+            patient_id: patient,
+            employee_number: request.employee_number.trim().to_string(),
+            start_date,
+            end_date,
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.clients.create_employee(employee).await {
+            Ok(created) => Ok(Self::map_employee(created)),
+            // `corporate_employees_patient` is unique per client
+            Err(e) if e.contains("E11000") => Err((StatusCode::CONFLICT, "The patient is already on this client's roster".to_string())),
+            Err(e) => Err(internal(e)),
+        }
+    }
+
+    pub async fn update_employee(&self, client_id: ObjectId, id: ObjectId, request: UpdateCorporateEmployeeRequest) -> Result<Option<CorporateEmployeeResponse>, (StatusCode, String)> {
+        let client_id = client_id.to_hex();
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(number) = request.employee_number { set.insert("employeeNumber", number.trim()); }
+        if let Some(end_date) = request.end_date {
+            let employee = self.clients.find_employee(&client_id, id).await.map_err(internal)?;
+            if employee.is_some_and(|employee| end_date.trim() < employee.start_date.as_str()) {
+                return Err((StatusCode::UNPROCESSABLE_ENTITY, "end_date cannot be before start_date".to_string()));
+            }
+            set.insert("endDate", end_date.trim());
+        }
+
+        self.clients.update_employee(&client_id, id, set).await
+            .map(|employee| employee.map(Self::map_employee))
+            .map_err(internal)
+    }
+
+    pub async fn remove_employee(&self, client_id: ObjectId, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.clients.delete_employee(&client_id.to_hex(), id).await.map_err(internal)
+    }
+
+    /// The active client of `organization_id` employing the patient on `date` under a contract
+    /// in effect then, with the patient's roster entry; the most recent employment wins
+    pub async fn coverage(&self, patient_id: &str, organization_id: &str, date: &str) -> Result<Option<(CorporateClient, CorporateEmployee)>, (StatusCode, String)> {
+        for employee in self.clients.find_employments(patient_id, date).await.map_err(internal)? {
+            let Ok(client_id) = ObjectId::parse_str(&employee.client_id) else {
+                continue;
+            };
+            let Some(client) = self.clients.find_by_id(client_id).await.map_err(internal)? else {
+                continue;
+            };
+            if client.status == CorporateClientStatus::Active && client.organization_id.to_hex() == organization_id && client.contract.covers(date) {
+                return Ok(Some((client, employee)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Bill the client for what it covered of the invoices served in `month` (`YYYY-MM`).
+    /// Generating a month again recomputes it under the same number.
+    pub async fn generate_statement(&self, client_id: ObjectId, month: &str, generated_by: &str) -> Result<CorporateStatementResponse, (StatusCode, String)> {
+        let client = self.client(client_id).await?;
+        let month = month.trim();
+        let (from, to) = month_range(month)?;
+        let client_hex = client_id.to_hex();
+        let organization = client.organization_id.to_hex();
+
+        let invoices = self.invoices.find_billed_to_client(&client_hex, &from, &to).await.map_err(internal)?;
+        let (lines, total) = statement_lines(invoices);
+
+        let number = match self.clients.find_statement(&client_hex, month).await.map_err(internal)? {
+            Some(existing) => existing.number,
+            None => {
+                let year = month.get(..4).and_then(|year| year.parse().ok()).unwrap_or_default();
+                self.sequences.next(&sequences::CORPORATE_STATEMENT, Some(&organization), year).await.map_err(internal)?
+            }
+        };
+        let today = self.timezone.today(Utc::now());
+        let due_date = NaiveDate::parse_from_str(&today, "%Y-%m-%d").ok()
+            .and_then(|today| today.checked_add_days(Days::new(client.contract.payment_term_days.into())))
+            .map(|due| due.format("%Y-%m-%d").to_string())
+            .unwrap_or(today);
+
+        let statement = CorporateStatement {
+            id: None,
+            number,
+            client_id: client_hex,
+            organization_id: organization,
+            month: month.to_string(),
+            lines,
+            total,
+            due_date,
+            generated_by: generated_by.to_string(),
+            generated_at: DateTime::now(),
+        };
+        self.clients.save_statement(statement).await
+            .map(Self::map_statement)
+            .map_err(internal)
+    }
+
+    pub async fn statements(&self, client_id: ObjectId) -> Result<Vec<CorporateStatementResponse>, (StatusCode, String)> {
+        self.client(client_id).await?;
+        let statements = self.clients.find_statements(&client_id.to_hex()).await.map_err(internal)?;
+        Ok(statements.into_iter().map(Self::map_statement).collect())
+    }
+
+    pub async fn statement(&self, client_id: ObjectId, month: &str) -> Result<Option<CorporateStatementResponse>, (StatusCode, String)> {
+        self.clients.find_statement(&client_id.to_hex(), month.trim()).await
+            .map(|statement| statement.map(Self::map_statement))
+            .map_err(internal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::CorporateBilling;
+    use crate::status::InvoiceStatus;
+
+    fn invoice(service_date: &str, total: f64, covered: Option<f64>) -> Invoice {
+        Invoice {
+            id: Some(ObjectId::new()),
+            organization_id: Ref::new(ObjectId::new()),
+            patient_id: Ref::new(ObjectId::new()),
+            number: Some("INV-2026-000001".to_string()),
+            service_date: service_date.to_string(),
+            price_list_id: "p1".to_string(),
+            price_list_version: 1,
+            lines: Vec::new(),
+            total,
+            paid_amount: 0.0,
+            claim: None,
+            corporate: covered.map(|covered_amount| CorporateBilling {
+                client_id: "c1".to_string(),
+                employee_number: "E-001".to_string(),
+                covered_amount,
+                patient_amount: round_money(total - covered_amount),
+            }),
+            status: InvoiceStatus::Unpaid,
+            created_by: "u1".to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn statements_bill_the_covered_share_of_the_month() {
+        assert_eq!(month_range("2026-02").unwrap(), ("2026-02-01".to_string(), "2026-02-28".to_string()));
+        assert_eq!(month_range("2024-12").unwrap(), ("2024-12-01".to_string(), "2024-12-31".to_string()));
+        assert!(month_range("2026-13").is_err());
+
+        let (lines, total) = statement_lines(vec![
+            invoice("2026-02-03", 200_000.0, Some(150_000.0)),
+            invoice("2026-02-10", 80_000.0, None),
+            invoice("2026-02-21", 100_000.5, Some(100_000.5)),
+        ]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].employee_number, "E-001");
+        assert_eq!(lines[1].total, 100_000.5);
+        assert_eq!(total, 250_000.5);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use chrono::Utc;
+use mongodb::bson::oid::ObjectId;
+use crate::denormalize::{self, Refresh, OBSERVATIONS, USER_ROLES};
+use crate::models::MedicalRecord;
+use crate::dto::denormalization::{DenormalizationReport, DenormalizationTarget, StaleSource};
+use crate::repository::{DenormalizationRepository, MedicalRecordRepository, UserRepository};
+
+/// Stale sources listed per target in a report
+const MAX_SAMPLES: usize = 50;
+/// Medical records loaded per query in a full pass
+const RECORD_BATCH: usize = 500;
+
+pub struct DenormalizationService {
+    repo: DenormalizationRepository,
+    users: UserRepository,
+    records: MedicalRecordRepository,
+}
+
+impl DenormalizationService {
+    pub fn new(repo: DenormalizationRepository, users: UserRepository, records: MedicalRecordRepository) -> Self {
+        Self { repo, users, records }
+    }
+
+    /// Refresh the user's copies in role assignments, returning the documents changed
+    pub async fn refresh_user(&self, id: &str) -> Result<u64, String> {
+        let Ok(oid) = ObjectId::parse_str(id) else { return Ok(0) };
+        match self.users.find_by_id(oid).await?.as_ref().and_then(denormalize::user_roles_refresh) {
+            Some(refresh) => self.repo.apply(&refresh).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Refresh the patient's copies in observations, returning the documents changed
+    pub async fn refresh_patient(&self, id: &str) -> Result<u64, String> {
+        let Ok(oid) = ObjectId::parse_str(id) else { return Ok(0) };
+        match self.records.find_by_id(oid).await?.as_ref().and_then(denormalize::observations_refresh) {
+            Some(refresh) => self.repo.apply(&refresh).await,
+            None => Ok(0),
+        }
+    }
+
+    /// Check every source copied anywhere; only counts stale documents when `dry_run`
+    pub async fn full_pass(&self, dry_run: bool) -> Result<DenormalizationReport, String> {
+        let started_at = Utc::now().to_rfc3339();
+
+        let mut user_roles = target(USER_ROLES, "users");
+        for id in self.repo.distinct_ids(USER_ROLES, "user._id").await? {
+            let user = match ObjectId::parse_str(&id) {
+                Ok(oid) => self.users.find_by_id(oid).await?,
+                Err(_) => None,
+            };
+            let refresh = user.as_ref().and_then(denormalize::user_roles_refresh);
+            self.check(&mut user_roles, id, refresh, dry_run).await?;
+        }
+
+        let mut observations = target(OBSERVATIONS, "medical_records");
+        let patient_ids = self.repo.distinct_ids(OBSERVATIONS, "id_pasien").await?;
+        for batch in patient_ids.chunks(RECORD_BATCH) {
+            let oids: Vec<ObjectId> = batch.iter().filter_map(|id| ObjectId::parse_str(id).ok()).collect();
+            let records: HashMap<String, MedicalRecord> = self.records.find_by_ids(&oids).await?
+                .into_iter()
+                .filter_map(|record| Some((record.id?.to_hex(), record)))
+                .collect();
+            for id in batch {
+                let refresh = records.get(id).and_then(denormalize::observations_refresh);
+                self.check(&mut observations, id.clone(), refresh, dry_run).await?;
+            }
+        }
+
+        Ok(DenormalizationReport {
+            dry_run,
+            started_at,
+            finished_at: Utc::now().to_rfc3339(),
+            targets: vec![user_roles, observations],
+        })
+    }
+
+    async fn check(&self, target: &mut DenormalizationTarget, source_id: String, refresh: Option<Refresh>, dry_run: bool) -> Result<(), String> {
+        target.sources_checked += 1;
+        let Some(refresh) = refresh else {
+            target.sources_missing += 1;
+            return Ok(());
+        };
+
+        let documents = if dry_run { self.repo.count_stale(&refresh).await? } else { self.repo.apply(&refresh).await? };
+        if documents > 0 {
+            target.sources_stale += 1;
+            target.stale_documents += documents;
+            if target.samples.len() < MAX_SAMPLES {
+                target.samples.push(StaleSource { source_id, documents });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn target(collection: &str, source: &str) -> DenormalizationTarget {
+    DenormalizationTarget {
+        collection: collection.to_string(),
+        source: source.to_string(),
+        sources_checked: 0,
+        sources_missing: 0,
+        sources_stale: 0,
+        stale_documents: 0,
+        samples: Vec::new(),
+    }
+}
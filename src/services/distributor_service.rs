@@ -0,0 +1,91 @@
+use axum::http::StatusCode;
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use mongodb::bson::DateTime;
+use crate::dto::kit::{DistributorSummary, DistributorSummaryQuery, MonthlyObservations, TopOperator};
+use crate::models::Kit;
+use crate::repository::observation::OperatorActivityRow;
+use crate::repository::{KitRepository, ObservationRepository};
+
+const DEFAULT_MONTHS: u32 = 12;
+const DEFAULT_TIMEZONE: &str = "UTC";
+const TOP_OPERATORS: i64 = 10;
+
+/// The distributor portal: a distributor's kits and the observations recorded on them.
+/// `kits` carries the caller's scope, see `crate::distributor`.
+pub struct DistributorService {
+    kits: KitRepository,
+    observations: ObservationRepository,
+}
+
+/// First day of the month `months - 1` months before `today`'s
+fn window_start(today: NaiveDate, months: u32) -> NaiveDate {
+    let first = today.with_day(1).unwrap_or(today);
+    first.checked_sub_months(Months::new(months.saturating_sub(1))).unwrap_or(first)
+}
+
+/// The NIK recorded with the operator's id on any of the kits
+fn operator(row: OperatorActivityRow, kits: &[Kit]) -> TopOperator {
+    TopOperator {
+        nik: kits.iter().find(|kit| kit.operator.id == row.operator_id).map(|kit| kit.operator.nik.clone()),
+        operator_id: row.operator_id,
+        observations: row.observations,
+        patients_served: row.patients_served,
+    }
+}
+
+impl DistributorService {
+    pub fn new(kits: KitRepository, observations: ObservationRepository) -> Self {
+        Self { kits, observations }
+    }
+
+    /// Kit counts, observations per month and the busiest operators; 403 outside the caller's
+    /// scope, 404 when no kit belongs to the distributor
+    pub async fn summary(&self, code: &str, query: DistributorSummaryQuery) -> Result<DistributorSummary, (StatusCode, String)> {
+        if !self.kits.scope().allows(code) {
+            return Err((StatusCode::FORBIDDEN, format!("You cannot view distributor {}", code)));
+        }
+        let kits = self.kits.find_by_distributor(code).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let Some(first) = kits.first() else {
+            return Err((StatusCode::NOT_FOUND, "Distributor not found".to_string()));
+        };
+
+        let timezone = query.tz
+            .map(|tz| tz.trim().to_string())
+            .filter(|tz| !tz.is_empty())
+            .unwrap_or_else(|| DEFAULT_TIMEZONE.to_string());
+        let from = window_start(Utc::now().date_naive(), query.months.unwrap_or(DEFAULT_MONTHS));
+        let start = DateTime::from_millis(from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp_millis());
+
+        let codes: Vec<String> = kits.iter().map(|kit| kit.code.clone()).collect();
+        let (months, operators) = self.observations.kit_activity(&codes, start, &timezone, TOP_OPERATORS).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(DistributorSummary {
+            code: code.to_string(),
+            name: first.distributor.name.clone(),
+            kits: kits.len() as i64,
+            active_kits: kits.iter().filter(|kit| kit.is_active).count() as i64,
+            timezone,
+            from: from.format("%Y-%m-%d").to_string(),
+            observations: months.iter().map(|m| m.observations).sum(),
+            observations_per_month: months.into_iter().map(|m| MonthlyObservations {
+                month: m.month,
+                observations: m.observations,
+                patients_served: m.patients_served,
+            }).collect(),
+            top_operators: operators.into_iter().map(|row| operator(row, &kits)).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_starts_on_the_first_of_the_earliest_month() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 18).unwrap();
+        assert_eq!(window_start(today, 1), NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        assert_eq!(window_start(today, 12), NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+    }
+}
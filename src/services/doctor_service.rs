@@ -1,21 +1,24 @@
+use crate::delete_policy::DeleteGuard;
 use crate::models::Doctor;
 use crate::repository::DoctorRepository;
 use crate::pagination::{PaginationParams, PaginationMeta};
 use crate::dto::doctor::{CreateDoctorRequest, UpdateDoctorRequest, DoctorResponse};
+use crate::status::DoctorStatus;
 use mongodb::bson::oid::ObjectId;
 use axum::http::StatusCode;
 
 pub struct DoctorService {
     repository: DoctorRepository,
+    deletes: DeleteGuard,
 }
 
 impl DoctorService {
-    pub fn new(repository: DoctorRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: DoctorRepository, deletes: DeleteGuard) -> Self {
+        Self { repository, deletes }
     }
 
     /// Map Doctor model to DoctorResponse DTO
-    fn map_to_response(doctor: Doctor) -> DoctorResponse {
+    pub(crate) fn map_to_response(doctor: Doctor) -> DoctorResponse {
         DoctorResponse {
             id: doctor.id.map(|id| id.to_hex()).unwrap_or_default(),
             name: doctor.name,
@@ -23,6 +26,8 @@ impl DoctorService {
             sip: doctor.sip,
             specialization: doctor.specialization,
             status: doctor.status,
+            rating_average: doctor.rating_average,
+            rating_count: doctor.rating_count,
         }
     }
 
@@ -33,8 +38,8 @@ impl DoctorService {
         }
     }
 
-    pub async fn get_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<DoctorResponse>, PaginationMeta), (StatusCode, String)> {
-        match self.repository.find_all_paginated(pagination.clone()).await {
+    pub async fn get_all_paginated(&self, pagination: PaginationParams, status: Option<&DoctorStatus>) -> Result<(Vec<DoctorResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.repository.find_all_paginated(pagination.clone(), status).await {
             Ok((doctors, total)) => {
                 let responses = doctors.into_iter().map(Self::map_to_response).collect();
                 let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
@@ -44,15 +49,41 @@ impl DoctorService {
         }
     }
 
-    pub async fn create(&self, request: CreateDoctorRequest) -> Result<(StatusCode, DoctorResponse), (StatusCode, String)> {
-        let doctor = Doctor {
+    /// The doctor a create request describes, not yet stored
+    pub(crate) fn build(request: CreateDoctorRequest) -> Doctor {
+        Doctor {
             id: Some(ObjectId::new()),
             name: request.name,
             nip: request.nip,
             sip: request.sip,
             specialization: request.specialization,
             status: request.status,
-        };
+            rating_average: None,
+            rating_count: 0,
+        }
+    }
+
+    /// Set the fields an update request carries
+    pub(crate) fn apply(doctor: &mut Doctor, request: UpdateDoctorRequest) {
+        if let Some(name) = request.name {
+            doctor.name = name;
+        }
+        if let Some(nip) = request.nip {
+            doctor.nip = nip;
+        }
+        if let Some(sip) = request.sip {
+            doctor.sip = sip;
+        }
+        if let Some(specialization) = request.specialization {
+            doctor.specialization = specialization;
+        }
+        if let Some(status) = request.status {
+            doctor.status = status;
+        }
+    }
+
+    pub async fn create(&self, request: CreateDoctorRequest) -> Result<(StatusCode, DoctorResponse), (StatusCode, String)> {
+        let doctor = Self::build(request);
 
         match self.repository.insert(doctor).await {
             Ok(created) => Ok((StatusCode::CREATED, Self::map_to_response(created))),
@@ -72,22 +103,7 @@ impl DoctorService {
         let mut doctor = self.repository.find_by_id(id).await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
             .ok_or((StatusCode::NOT_FOUND, "Doctor not found".to_string()))?;
-
-        if let Some(name) = request.name {
-            doctor.name = name;
-        }
-        if let Some(nip) = request.nip {
-            doctor.nip = nip;
-        }
-        if let Some(sip) = request.sip {
-            doctor.sip = sip;
-        }
-        if let Some(specialization) = request.specialization {
-            doctor.specialization = specialization;
-        }
-        if let Some(status) = request.status {
-            doctor.status = status;
-        }
+        Self::apply(&mut doctor, request);
 
         match self.repository.update(id, doctor).await {
             Ok(updated) => Ok(Self::map_to_response(updated)),
@@ -95,10 +111,13 @@ impl DoctorService {
         }
     }
 
+    /// 409 while the doctor has upcoming appointments, unless policies cascade to them.
     pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
-        match self.repository.delete(id).await {
-            Ok(deleted) => Ok(deleted),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
-        }
+        let mut session = self.deletes.begin().await?;
+        let deleted = async {
+            self.deletes.prepare(&mut session, "doctors", id).await?;
+            self.repository.delete(&mut session, id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        }.await;
+        crate::services::finish(session, deleted).await
     }
 }
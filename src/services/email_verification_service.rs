@@ -0,0 +1,82 @@
+use axum::http::StatusCode;
+use mongodb::bson::oid::ObjectId;
+
+use crate::dto::auth::{EmailVerificationResponse, ResendVerificationRequest, ResendVerificationResponse};
+use crate::mailer::{Email, EmailConfig, Mailer};
+use crate::repository::UserRepository;
+use crate::services::AuthService;
+
+/// Sends signed verification links to new accounts and records when they are opened.
+pub struct EmailVerificationService {
+    repo: UserRepository,
+    config: EmailConfig,
+    mailer: Box<dyn Mailer>,
+}
+
+impl EmailVerificationService {
+    pub fn new(repo: UserRepository, config: &EmailConfig) -> Self {
+        Self { repo, config: config.clone(), mailer: config.mailer() }
+    }
+
+    /// Email a verification link for `email` to the account `user_id`.
+    pub async fn send_link(&self, user_id: &str, email: &str) -> Result<(), String> {
+        let token = AuthService::generate_verification_token(user_id, email, self.config.verification_ttl_hours)?;
+        let link = self.config.verification_link(&token);
+        let message = Email {
+            to: email.to_string(),
+            subject: "Verify your email address".to_string(),
+            body: format!(
+                "Open this link to verify your email address:\n\n{}\n\nThe link expires in {} hours.",
+                link, self.config.verification_ttl_hours
+            ),
+        };
+        self.mailer
+            .send(&message)
+            .await
+            .map_err(|e| format!("Failed to send verification email via {}: {}", self.mailer.name(), e))
+    }
+
+    pub async fn verify(&self, token: &str) -> Result<EmailVerificationResponse, (StatusCode, String)> {
+        let claims = AuthService::validate_verification_token(token)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let user_id = ObjectId::parse_str(&claims.sub)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid verification token".to_string()))?;
+
+        let user = self.repo.find_by_id(user_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "User not found".to_string()))?;
+
+        // Links sent before the address changed must not verify the new one
+        if user.email != claims.email {
+            return Err((StatusCode::BAD_REQUEST, "Verification link does not match the account's current email".to_string()));
+        }
+
+        if let Some(verified_at) = user.email_verified_at {
+            return Ok(EmailVerificationResponse { email: user.email, email_verified_at: verified_at });
+        }
+
+        let verified_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        self.repo.set_email_verified(user_id, &verified_at).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(EmailVerificationResponse { email: user.email, email_verified_at: verified_at })
+    }
+
+    /// Send a fresh link to an unverified account. Like forgot-password, the answer does not
+    /// reveal whether the email is registered.
+    pub async fn resend(&self, request: ResendVerificationRequest) -> Result<ResendVerificationResponse, (StatusCode, String)> {
+        let user = self.repo.find_by_email(&request.email.to_lowercase()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        if let Some(user) = user.filter(|u| u.email_verified_at.is_none()) {
+            let user_id = user.id.map(|id| id.to_hex()).unwrap_or_default();
+            self.send_link(&user_id, &user.email).await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, e))?;
+        }
+
+        Ok(ResendVerificationResponse {
+            success: true,
+            message: "If the email belongs to an unverified account, a verification link has been sent".to_string(),
+        })
+    }
+}
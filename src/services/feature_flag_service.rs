@@ -0,0 +1,105 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::feature_flag::{CreateFeatureFlagRequest, FeatureFlagResponse, UpdateFeatureFlagRequest};
+use crate::models::FeatureFlag;
+use crate::repository::FeatureFlagRepository;
+
+pub struct FeatureFlagService {
+    repo: FeatureFlagRepository,
+}
+
+fn valid_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'))
+}
+
+fn clean_organizations(organizations: Vec<String>) -> Vec<String> {
+    let mut organizations: Vec<String> = organizations.into_iter().map(|o| o.trim().to_string()).filter(|o| !o.is_empty()).collect();
+    organizations.sort();
+    organizations.dedup();
+    organizations
+}
+
+impl FeatureFlagService {
+    pub fn new(repo: FeatureFlagRepository) -> Self {
+        Self { repo }
+    }
+
+    fn map_to_response(flag: FeatureFlag) -> FeatureFlagResponse {
+        FeatureFlagResponse {
+            id: flag.id.map(|id| id.to_hex()).unwrap_or_default(),
+            key: flag.key,
+            description: flag.description,
+            enabled: flag.enabled,
+            organizations: flag.organizations,
+            rollout_percentage: flag.rollout_percentage,
+            created_at: crate::datetime::to_rfc3339(flag.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(flag.updated_at),
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<FeatureFlagResponse>, (StatusCode, String)> {
+        match self.repo.find_all().await {
+            Ok(flags) => Ok(flags.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<FeatureFlagResponse>, (StatusCode, String)> {
+        match self.repo.find_by_id(id).await {
+            Ok(flag) => Ok(flag.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreateFeatureFlagRequest) -> Result<FeatureFlagResponse, (StatusCode, String)> {
+        let key = request.key.trim().to_string();
+        if !valid_key(&key) {
+            return Err((StatusCode::BAD_REQUEST, "Key may only contain lowercase letters, digits, '_', '-' and '.'".to_string()));
+        }
+        let existing = self.repo.find_by_key(&key).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if existing.is_some() {
+            return Err((StatusCode::CONFLICT, format!("Feature flag '{}' already exists", key)));
+        }
+
+        let flag = FeatureFlag {
+            id: None,
+            key,
+            description: request.description,
+            enabled: request.enabled,
+            organizations: clean_organizations(request.organizations),
+            rollout_percentage: request.rollout_percentage.unwrap_or(100),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.repo.create(flag).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdateFeatureFlagRequest) -> Result<Option<FeatureFlagResponse>, (StatusCode, String)> {
+        let mut set = doc! { "updated_at": DateTime::now() };
+        if let Some(description) = request.description {
+            set.insert("description", description);
+        }
+        if let Some(enabled) = request.enabled {
+            set.insert("enabled", enabled);
+        }
+        if let Some(organizations) = request.organizations {
+            set.insert("organizations", clean_organizations(organizations));
+        }
+        if let Some(percentage) = request.rollout_percentage {
+            set.insert("rollout_percentage", percentage);
+        }
+
+        match self.repo.update_fields(id, set).await {
+            Ok(flag) => Ok(flag.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.repo.delete(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+}
@@ -1,23 +1,74 @@
-use crate::models::File;
-use crate::repository::FileRepository;
+use crate::models::{File, FileVersion, MedicalRecord};
+use crate::repository::{FileRepository, FileVersionRepository, StorageUsageRepository};
 use crate::validation;
 use crate::pagination::{PaginationParams, PaginationMeta};
-use crate::dto::file::FileResponse;
-use mongodb::bson::oid::ObjectId;
+use crate::dto::file::{FileResponse, FileVersionResponse, QuotaExceeded, StorageUsageResponse};
+use crate::quota::{StorageQuotaConfig, UploadOwner};
+use crate::refs::{Ref, ReferenceChecker};
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use std::collections::BTreeMap;
 use axum::http::StatusCode;
 use std::sync::Arc;
-use aws_sdk_s3::Client as S3Client;
+use crate::storage::Storage;
+
+/// Content of one version, ready to send
+pub struct FileContent {
+    pub name: String,
+    pub file_type: String,
+    pub content: Vec<u8>,
+}
+
+/// The current version of `file`, for `file_versions`
+fn snapshot(file: &File, restored_from: Option<i32>) -> FileVersion {
+    FileVersion {
+        id: None,
+        file_id: file.id.map(|id| id.to_hex()).unwrap_or_default(),
+        version: file.version,
+        name: file.name.clone(),
+        file_type: file.file_type.clone(),
+        extension: file.extension.clone(),
+        size: file.size,
+        path: file.path.clone(),
+        url: file.url.clone(),
+        uploader: file.uploader.clone(),
+        restored_from,
+        created_at: file.updated_at.unwrap_or(file.created_at),
+    }
+}
+
+fn owner(file: &File) -> UploadOwner {
+    UploadOwner { user_id: file.owner_id.clone(), organization_id: file.organization_id.clone() }
+}
+
+fn extension(file_name: &str) -> String {
+    file_name.split('.').next_back().unwrap_or("").to_string()
+}
 
 pub struct FileService {
     repository: FileRepository,
-    s3_client: Arc<S3Client>,
+    versions: FileVersionRepository,
+    usage: StorageUsageRepository,
+    storage: Arc<Storage>,
+    references: ReferenceChecker,
+    quotas: StorageQuotaConfig,
 }
 
 impl FileService {
-    pub fn new(repository: FileRepository, s3_client: Arc<S3Client>) -> Self {
+    pub fn new(
+        repository: FileRepository,
+        versions: FileVersionRepository,
+        usage: StorageUsageRepository,
+        storage: Arc<Storage>,
+        references: ReferenceChecker,
+        quotas: &StorageQuotaConfig,
+    ) -> Self {
         Self {
             repository,
-            s3_client,
+            versions,
+            usage,
+            storage,
+            references,
+            quotas: quotas.clone(),
         }
     }
 
@@ -32,8 +83,114 @@ impl FileService {
             path: file.path,
             url: file.url,
             uploader: file.uploader,
-            created_at: file.created_at,
+            medical_record_id: file.medical_record_id.map(|id| id.to_hex()),
+            version: file.version,
+            created_at: crate::datetime::to_rfc3339(file.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(file.updated_at),
+        }
+    }
+
+    fn map_version(&self, version: FileVersion, current: i32) -> FileVersionResponse {
+        FileVersionResponse {
+            content_url: self.storage.version_content_url(&version.file_id, version.version),
+            current: version.version == current,
+            version: version.version,
+            name: version.name,
+            file_type: version.file_type,
+            extension: version.extension,
+            size: version.size,
+            uploader: version.uploader,
+            restored_from: version.restored_from,
+            created_at: crate::datetime::to_rfc3339(version.created_at),
+        }
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<File, (StatusCode, String)> {
+        self.repository.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "File not found".to_string()))
+    }
+
+    /// Add to the owner's storage totals. Like audit writes, usage writes never fail the
+    /// upload or delete; errors are logged and the totals drift.
+    async fn charge(&self, owner: &UploadOwner, bytes: i64, files: i64) {
+        for (scope, owner_id) in owner.scopes() {
+            if let Err(e) = self.usage.add(scope, owner_id, bytes, files).await {
+                tracing::error!(scope = %scope, owner_id = %owner_id, error = %e, "Failed to update storage usage");
+            }
+        }
+    }
+
+    /// The quota `requested` more bytes would exceed for `owner`, if any
+    pub async fn quota_exceeded(&self, owner: &UploadOwner, requested: u64) -> Result<Option<QuotaExceeded>, (StatusCode, String)> {
+        for (scope, owner_id) in owner.scopes() {
+            if self.quotas.limit(scope).is_none() {
+                continue;
+            }
+            let used = self.usage.bytes(scope, owner_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            if let Some(exceeded) = self.quotas.exceeded(scope, owner_id, used, requested) {
+                return Ok(Some(exceeded));
+            }
         }
+        Ok(None)
+    }
+
+    /// Who the storage of a live file is charged to
+    pub async fn owner_of(&self, id: ObjectId) -> Result<UploadOwner, (StatusCode, String)> {
+        self.find(id).await.map(|file| owner(&file))
+    }
+
+    /// Storage totals per uploader and organization, largest first, against their quotas
+    pub async fn usage(&self, scope: Option<&str>) -> Result<Vec<StorageUsageResponse>, (StatusCode, String)> {
+        let usage = self.usage.find_all(scope).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(usage.into_iter().map(|usage| {
+            let bytes = usage.bytes.max(0) as u64;
+            let limit_bytes = self.quotas.limit(&usage.scope);
+            StorageUsageResponse {
+                remaining_bytes: limit_bytes.map(|limit| limit.saturating_sub(bytes)),
+                limit_bytes,
+                scope: usage.scope,
+                owner_id: usage.owner_id,
+                bytes,
+                files: usage.files.max(0) as u64,
+                updated_at: crate::datetime::to_rfc3339(usage.updated_at),
+            }
+        }).collect())
+    }
+
+    async fn record_version(&self, version: FileVersion) -> Result<(), (StatusCode, String)> {
+        self.versions.insert(version).await
+            .map(|_| ())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Version `version` of `file`; files stored before versioning only have their current
+    /// content, which is not in `file_versions` until it is first replaced
+    async fn find_version(&self, file: &File, version: i32) -> Result<Option<FileVersion>, (StatusCode, String)> {
+        let id = file.id.map(|id| id.to_hex()).unwrap_or_default();
+        match self.versions.find_version(&id, version).await {
+            Ok(None) if version == file.version => Ok(Some(snapshot(file, None))),
+            Ok(found) => Ok(found),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Move `file` to a new version with `set`, keeping the current one listed
+    async fn advance(&self, file: &File, mut set: mongodb::bson::Document, restored_from: Option<i32>) -> Result<File, (StatusCode, String)> {
+        let id = file.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "File has no ID".to_string()))?;
+        let recorded = self.versions.find_version(&id.to_hex(), file.version).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if recorded.is_none() {
+            self.record_version(snapshot(file, None)).await?;
+        }
+
+        set.insert("version", file.version + 1);
+        set.insert("updatedAt", DateTime::now());
+        let updated = self.repository.update_at_version(id, file.version, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "File was changed or deleted concurrently".to_string()))?;
+        self.record_version(snapshot(&updated, restored_from)).await?;
+        Ok(updated)
     }
 
     pub async fn get_all(&self) -> Result<Vec<FileResponse>, String> {
@@ -58,43 +215,159 @@ impl FileService {
         file_name: String,
         file_bytes: Vec<u8>,
         uploader: String,
+        medical_record_id: Option<String>,
+        owner: &UploadOwner,
     ) -> Result<(StatusCode, FileResponse), (StatusCode, String)> {
         // ... (validation and upload logic same) ...
+        let backend = self.storage.backend()?;
         let file_size = file_bytes.len() as u64;
-        if let Err(_) = validation::validate_file_upload(&file_name, file_size) {
+        if validation::validate_file_upload(&file_name, file_size).is_err() {
             return Err((StatusCode::BAD_REQUEST, "Invalid file".to_string()));
         }
+        let file_type = validation::sniff_file_type(&file_name, &file_bytes).map_err(|e| (e.status, e.message))?;
 
-        let s3_key = crate::s3::generate_s3_key(&file_name);
-        let bucket = std::env::var("AWS_BUCKET").unwrap_or_else(|_| "atm-sehat".to_string());
+        let medical_record_id = match medical_record_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => {
+                let record: Ref<MedicalRecord> = Ref::parse_field("medical_record_id", id)?;
+                self.references.ensure_exist(&[record.check("medical_record_id")]).await?;
+                Some(record)
+            }
+            None => None,
+        };
 
-        let s3_url = match crate::s3::upload_file_to_s3(&self.s3_client, &bucket, &s3_key, file_bytes).await {
-            Ok(url) => url,
+        let id = ObjectId::new();
+        let s3_key = crate::s3::generate_s3_key(&file_name);
+        let s3_url = match backend.put(self.storage.bucket(), &s3_key, file_bytes).await {
+            Ok(url) => url.unwrap_or_else(|| self.storage.content_url(&id.to_hex())),
             Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         };
 
         let file_record = File {
-            id: Some(ObjectId::new()),
+            id: Some(id),
             name: file_name.clone(),
-            file_type: mime_guess::from_path(&file_name)
-                .first_raw()
-                .unwrap_or("application/octet-stream")
-                .to_string(),
-            extension: file_name.split('.').last().unwrap_or("").to_string(),
+            file_type,
+            extension: extension(&file_name),
             size: file_size,
             path: s3_key,
             url: s3_url,
             uploader,
-            created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            medical_record_id,
+            owner_id: owner.user_id.clone(),
+            organization_id: owner.organization_id.clone(),
+            version: 1,
+            created_at: DateTime::now(),
+            updated_at: None,
+            deleted_at: None,
         };
 
-        match self.repository.insert(file_record).await {
-            Ok(created) => Ok((StatusCode::CREATED, Self::map_to_response(created))),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        let created = self.repository.insert(file_record).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.record_version(snapshot(&created, None)).await?;
+        self.charge(owner, file_size as i64, 1).await;
+        Ok((StatusCode::CREATED, Self::map_to_response(created)))
+    }
+
+    /// Store new content as the next version under a new key; earlier versions keep theirs.
+    /// `expected_version`, when given, must be the current one.
+    pub async fn replace(
+        &self,
+        id: ObjectId,
+        file_name: String,
+        file_bytes: Vec<u8>,
+        uploader: String,
+        expected_version: Option<i32>,
+    ) -> Result<FileResponse, (StatusCode, String)> {
+        let backend = self.storage.backend()?;
+        let file_size = file_bytes.len() as u64;
+        if validation::validate_file_upload(&file_name, file_size).is_err() {
+            return Err((StatusCode::BAD_REQUEST, "Invalid file".to_string()));
+        }
+        let file_type = validation::sniff_file_type(&file_name, &file_bytes).map_err(|e| (e.status, e.message))?;
+        let current = self.find(id).await?;
+        if expected_version.is_some_and(|version| version != current.version) {
+            return Err((StatusCode::CONFLICT, format!("File is at version {}; reload it before replacing", current.version)));
+        }
+
+        let key = crate::s3::generate_s3_key(&file_name);
+        let url = backend.put(self.storage.bucket(), &key, file_bytes).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .unwrap_or_else(|| self.storage.content_url(&id.to_hex()));
+        let set = doc! {
+            "name": &file_name,
+            "type": file_type,
+            "extension": extension(&file_name),
+            "size": file_size as i64,
+            "path": &key,
+            "url": url,
+            "uploader": uploader,
+        };
+
+        match self.advance(&current, set, None).await {
+            Ok(updated) => {
+                self.charge(&owner(&updated), file_size as i64, 0).await;
+                Ok(Self::map_to_response(updated))
+            }
+            Err(e) => {
+                // Nothing refers to the new object when the version was not written
+                if let Err(cleanup) = backend.delete(self.storage.bucket(), &key).await {
+                    tracing::error!(key = %key, error = %cleanup, "Failed to remove an unused upload");
+                }
+                Err(e)
+            }
         }
     }
 
+    /// Every version of a live file, oldest first; `None` when the file does not exist.
+    pub async fn versions(&self, id: ObjectId) -> Result<Option<Vec<FileVersionResponse>>, (StatusCode, String)> {
+        let Some(file) = self.repository.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? else {
+            return Ok(None);
+        };
+        let mut versions = self.versions.find_by_file(&id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if !versions.iter().any(|v| v.version == file.version) {
+            versions.push(snapshot(&file, None));
+        }
+        Ok(Some(versions.into_iter().map(|v| self.map_version(v, file.version)).collect()))
+    }
+
+    /// Make an earlier version's content current again, as a new version
+    pub async fn restore(&self, id: ObjectId, version: i32, user: &str) -> Result<FileResponse, (StatusCode, String)> {
+        let current = self.find(id).await?;
+        if version == current.version {
+            return Ok(Self::map_to_response(current));
+        }
+        let restored = self.find_version(&current, version).await?
+            .ok_or((StatusCode::NOT_FOUND, format!("File has no version {}", version)))?;
+
+        let set = doc! {
+            "name": restored.name,
+            "type": restored.file_type,
+            "extension": restored.extension,
+            "size": restored.size as i64,
+            "path": restored.path,
+            "url": restored.url,
+            "uploader": user,
+        };
+        self.advance(&current, set, Some(version)).await.map(Self::map_to_response)
+    }
+
+    /// Content of one version of a live file
+    pub async fn version_content(&self, id: ObjectId, version: i32) -> Result<FileContent, (StatusCode, String)> {
+        let backend = self.storage.backend()?;
+        let file = self.find(id).await?;
+        let stored = self.find_version(&file, version).await?
+            .ok_or((StatusCode::NOT_FOUND, format!("File has no version {}", version)))?;
+        let Some(read) = backend.read(self.storage.bucket(), &stored.path) else {
+            return Err((StatusCode::NOT_IMPLEMENTED, format!("The {} storage backend cannot serve file versions", backend.name())));
+        };
+
+        let content = read.await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(FileContent { name: stored.name, file_type: stored.file_type, content })
+    }
+
     pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        let backend = self.storage.backend()?;
+
         // Get file record to retrieve S3 path
         let file = match self.repository.find_by_id(id).await {
             Ok(Some(f)) => f,
@@ -102,16 +375,61 @@ impl FileService {
             Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         };
 
-        // Delete from S3
-        let bucket = std::env::var("AWS_BUCKET").unwrap_or_else(|_| "atm-sehat".to_string());
-        if let Err(e) = crate::s3::delete_file_from_s3(&self.s3_client, &bucket, &file.path).await {
-            return Err((StatusCode::INTERNAL_SERVER_ERROR, e));
+        // Delete the content of every version; restores share keys with earlier versions
+        let versions = self.versions.find_by_file(&id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let sizes: BTreeMap<&str, u64> = versions.iter().map(|v| (v.path.as_str(), v.size)).chain([(file.path.as_str(), file.size)]).collect();
+        for path in sizes.keys() {
+            if let Err(e) = backend.delete(self.storage.bucket(), path).await {
+                return Err((StatusCode::INTERNAL_SERVER_ERROR, e));
+            }
         }
 
         // Delete from database
-        match self.repository.delete(id).await {
-            Ok(deleted) => Ok(deleted),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        let deleted = self.repository.delete(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.versions.delete_by_file(&id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if deleted {
+            self.charge(&owner(&file), -(sizes.values().sum::<u64>() as i64), -1).await;
         }
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_the_current_content() {
+        let created_at = DateTime::from_millis(1_767_225_600_000);
+        let mut file = File {
+            id: Some(ObjectId::new()),
+            name: "lab.pdf".to_string(),
+            file_type: "application/pdf".to_string(),
+            extension: extension("lab.pdf"),
+            size: 1024,
+            path: "files/20260101_000000_lab.pdf".to_string(),
+            url: "/api/v1/files/x/content".to_string(),
+            uploader: "clinician".to_string(),
+            medical_record_id: None,
+            owner_id: None,
+            organization_id: None,
+            version: 1,
+            created_at,
+            updated_at: None,
+            deleted_at: None,
+        };
+
+        let first = snapshot(&file, None);
+        assert_eq!((first.version, first.file_type.as_str(), first.extension.as_str()), (1, "application/pdf", "pdf"));
+        assert_eq!(first.created_at, created_at);
+
+        file.version = 3;
+        file.updated_at = Some(DateTime::from_millis(1_767_312_000_000));
+        let restored = snapshot(&file, Some(1));
+        assert_eq!((restored.version, restored.restored_from), (3, Some(1)));
+        assert_eq!(restored.created_at, file.updated_at.unwrap());
     }
 }
@@ -0,0 +1,181 @@
+//! Public, expiring links to stored files.
+//!
+//! `POST /files/:id/share` records a share and returns a signed token for it; anyone holding
+//! the token downloads the file at `GET /share/:token` until the share expires (one day by
+//! default, seven at most) or is revoked. Creation, revocation and every download attempt,
+//! refused ones included, are written to the audit log. Files are not scanned here: shares
+//! only reach files already accepted by `validation::validate_file_upload`, and are sent as
+//! attachments so browsers do not render them in the API's origin.
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use std::sync::Arc;
+use crate::dto::file::{CreateFileShareRequest, FileShareResponse};
+use crate::links::LinkConfig;
+use crate::middleware::AuthUser;
+use crate::models::FileShare;
+use crate::repository::{FileRepository, FileShareRepository};
+use crate::services::{AuditService, AuthService};
+use crate::storage::Storage;
+
+pub const DEFAULT_SHARE_MINUTES: i64 = 24 * 60;
+pub const MAX_SHARE_MINUTES: i64 = 7 * 24 * 60;
+const ENTITY: &str = "file_share";
+/// Audit actor of anonymous downloads
+const PUBLIC_ACTOR: &str = "public";
+
+/// A shared file's content, ready to send
+pub struct SharedFile {
+    pub name: String,
+    pub file_type: String,
+    pub content: Vec<u8>,
+}
+
+/// Why a share no longer opens
+pub fn refusal(share: &FileShare, now: DateTime) -> Option<&'static str> {
+    if share.revoked_at.is_some() {
+        Some("revoked")
+    } else if share.expires_at <= now {
+        Some("expired")
+    } else {
+        None
+    }
+}
+
+pub struct FileShareService {
+    shares: FileShareRepository,
+    files: FileRepository,
+    storage: Arc<Storage>,
+    audit: AuditService,
+    links: LinkConfig,
+}
+
+impl FileShareService {
+    pub fn new(shares: FileShareRepository, files: FileRepository, storage: Arc<Storage>, audit: AuditService, links: &LinkConfig) -> Self {
+        Self { shares, files, storage, audit, links: links.clone() }
+    }
+
+    pub async fn create(&self, file_id: ObjectId, user: &AuthUser, request: CreateFileShareRequest) -> Result<FileShareResponse, (StatusCode, String)> {
+        let file = self.files.find_by_id(file_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "File not found".to_string()))?;
+
+        let now = Utc::now();
+        let minutes = request.expires_in_minutes.unwrap_or(DEFAULT_SHARE_MINUTES).clamp(1, MAX_SHARE_MINUTES);
+        let expires_at = now + Duration::minutes(minutes);
+        let share = self.shares.create(FileShare {
+            id: Some(ObjectId::new()),
+            file_id: file_id.to_hex(),
+            created_by: user.id.clone(),
+            expires_at: crate::datetime::from_chrono(expires_at),
+            revoked_at: None,
+            access_count: 0,
+            last_accessed_at: None,
+            created_at: crate::datetime::from_chrono(now),
+        }).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let id = share.id.map(|id| id.to_hex()).unwrap_or_default();
+        let token = AuthService::generate_share_token(&id, expires_at)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.audit.record("share.create", ENTITY, &id, &user.id, doc! {
+            "file_id": &share.file_id,
+            "file_name": &file.name,
+            "expires_at": share.expires_at,
+        }).await;
+
+        Ok(FileShareResponse {
+            url: self.links.url(&format!("/share/{}", token)),
+            token,
+            id,
+            file_id: share.file_id,
+            expires_at: crate::datetime::to_rfc3339(share.expires_at),
+            created_by: share.created_by,
+            created_at: crate::datetime::to_rfc3339(share.created_at),
+        })
+    }
+
+    /// `false` when the file has no such share, or it was already revoked
+    pub async fn revoke(&self, file_id: ObjectId, share_id: ObjectId, user: &AuthUser) -> Result<bool, (StatusCode, String)> {
+        let revoked = self.shares.revoke(share_id, &file_id.to_hex(), DateTime::now()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if revoked.is_some() {
+            self.audit.record("share.revoke", ENTITY, &share_id.to_hex(), &user.id, doc! { "file_id": file_id.to_hex() }).await;
+        }
+        Ok(revoked.is_some())
+    }
+
+    /// Content of the file behind `token`; every attempt is audited with `client`
+    pub async fn open(&self, token: &str, client: Document) -> Result<SharedFile, (StatusCode, String)> {
+        let share_id = AuthService::validate_share_token(token).ok().and_then(|claims| ObjectId::parse_str(&claims.sub).ok());
+        let Some(share_id) = share_id else {
+            return self.deny("unknown", "invalid_token", client, StatusCode::NOT_FOUND, "Share link is invalid or has expired").await;
+        };
+        let entity_id = share_id.to_hex();
+
+        let share = match self.shares.find_by_id(share_id).await {
+            Ok(Some(share)) => share,
+            Ok(None) => return self.deny(&entity_id, "not_found", client, StatusCode::NOT_FOUND, "Share link is invalid or has expired").await,
+            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        };
+        if let Some(reason) = refusal(&share, DateTime::now()) {
+            return self.deny(&entity_id, reason, client, StatusCode::GONE, &format!("Share link has {}", reason)).await;
+        }
+
+        let file = match ObjectId::parse_str(&share.file_id) {
+            Ok(file_id) => self.files.find_by_id(file_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?,
+            Err(_) => None,
+        };
+        let Some(file) = file else {
+            return self.deny(&entity_id, "file_deleted", client, StatusCode::GONE, "Shared file has been deleted").await;
+        };
+
+        let backend = self.storage.backend()?;
+        let Some(read) = backend.read(self.storage.bucket(), &file.path) else {
+            return Err((StatusCode::NOT_IMPLEMENTED, format!("The {} storage backend cannot serve shared files", backend.name())));
+        };
+        let content = read.await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        if let Err(e) = self.shares.record_access(share_id, DateTime::now()).await {
+            tracing::warn!(file_share_id = %entity_id, error = %e, "Failed to count access to a file share");
+        }
+        let mut details = client;
+        details.insert("file_id", &share.file_id);
+        self.audit.record("share.access", ENTITY, &entity_id, PUBLIC_ACTOR, details).await;
+
+        Ok(SharedFile { name: file.name, file_type: file.file_type, content })
+    }
+
+    async fn deny<T>(&self, entity_id: &str, reason: &str, mut client: Document, status: StatusCode, message: &str) -> Result<T, (StatusCode, String)> {
+        client.insert("reason", reason);
+        self.audit.record("share.denied", ENTITY, entity_id, PUBLIC_ACTOR, client).await;
+        Err((status, message.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(expires_in: Duration, revoked: bool) -> FileShare {
+        let now = Utc::now();
+        FileShare {
+            id: Some(ObjectId::new()),
+            file_id: ObjectId::new().to_hex(),
+            created_by: "clinician".to_string(),
+            expires_at: crate::datetime::from_chrono(now + expires_in),
+            revoked_at: revoked.then(DateTime::now),
+            access_count: 0,
+            last_accessed_at: None,
+            created_at: crate::datetime::from_chrono(now),
+        }
+    }
+
+    #[test]
+    fn refuses_revoked_and_expired_shares() {
+        let now = DateTime::now();
+        assert_eq!(refusal(&share(Duration::hours(1), false), now), None);
+        assert_eq!(refusal(&share(Duration::hours(1), true), now), Some("revoked"));
+        assert_eq!(refusal(&share(Duration::minutes(-1), false), now), Some("expired"));
+    }
+}
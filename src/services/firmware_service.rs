@@ -0,0 +1,165 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::firmware::{CreateFirmwareRequest, UpdateFirmwareRequest, FirmwareResponse, LatestFirmwareResponse};
+use crate::models::Firmware;
+use crate::repository::{FirmwareRepository, KitRepository};
+
+pub struct FirmwareService {
+    repo: Arc<FirmwareRepository>,
+    kits: Arc<KitRepository>,
+}
+
+impl FirmwareService {
+    pub fn new(repo: Arc<FirmwareRepository>, kits: Arc<KitRepository>) -> Self {
+        Self { repo, kits }
+    }
+
+    pub async fn create(&self, dto: CreateFirmwareRequest) -> Result<FirmwareResponse, String> {
+        let version = dto.version.trim().to_string();
+        if self.repo.find_by_version(&version).await?.is_some() {
+            return Err(format!("Firmware version '{}' already exists", version));
+        }
+
+        let firmware = Firmware {
+            id: None,
+            version,
+            checksum: dto.checksum.to_lowercase(),
+            artifact_key: dto.artifact_key,
+            artifact_url: dto.artifact_url,
+            kit_models: dto.kit_models,
+            release_notes: dto.release_notes,
+            is_active: dto.is_active,
+            created_at: DateTime::now(),
+            updated_at: Some(DateTime::now()),
+        };
+
+        let created = self.repo.create(firmware).await?;
+        Ok(Self::map_to_response(created))
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<FirmwareResponse>, String> {
+        let releases = self.repo.find_all().await?;
+        Ok(releases.into_iter().map(Self::map_to_response).collect())
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<FirmwareResponse>, String> {
+        let firmware = self.repo.find_by_id(id).await?;
+        Ok(firmware.map(Self::map_to_response))
+    }
+
+    pub async fn update(&self, id: ObjectId, dto: UpdateFirmwareRequest) -> Result<FirmwareResponse, String> {
+        let mut existing = self.repo.find_by_id(id).await?
+            .ok_or_else(|| "Firmware not found".to_string())?;
+
+        if let Some(version) = dto.version {
+            let version = version.trim().to_string();
+            if version != existing.version {
+                if self.repo.find_by_version(&version).await?.is_some() {
+                    return Err(format!("Firmware version '{}' already exists", version));
+                }
+                existing.version = version;
+            }
+        }
+        if let Some(checksum) = dto.checksum {
+            existing.checksum = checksum.to_lowercase();
+        }
+        if let Some(artifact_key) = dto.artifact_key {
+            existing.artifact_key = artifact_key;
+        }
+        if let Some(artifact_url) = dto.artifact_url {
+            existing.artifact_url = artifact_url;
+        }
+        if let Some(kit_models) = dto.kit_models {
+            existing.kit_models = kit_models;
+        }
+        if dto.release_notes.is_some() {
+            existing.release_notes = dto.release_notes;
+        }
+        if let Some(is_active) = dto.is_active {
+            existing.is_active = is_active;
+        }
+
+        existing.updated_at = Some(DateTime::now());
+
+        let updated = self.repo.update(id, existing).await?;
+        Ok(Self::map_to_response(updated))
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, String> {
+        self.repo.delete(id).await
+    }
+
+    /// Newest active release for a kit's model. `None` when the kit code is unknown.
+    pub async fn latest_for_kit(&self, kit_code: &str) -> Result<Option<LatestFirmwareResponse>, String> {
+        let Some(kit) = self.kits.find_by_code(kit_code).await? else { return Ok(None) };
+
+        let latest = self.repo.find_active_for_model(kit.model.as_deref()).await?
+            .into_iter()
+            .max_by(|a, b| compare_versions(&a.version, &b.version));
+
+        let update_available = match (&latest, &kit.firmware_version) {
+            (Some(latest), Some(current)) => compare_versions(&latest.version, current) == Ordering::Greater,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        Ok(Some(LatestFirmwareResponse {
+            kit_code: kit.code,
+            kit_model: kit.model,
+            current_version: kit.firmware_version,
+            latest: latest.map(Self::map_to_response),
+            update_available,
+        }))
+    }
+
+    fn map_to_response(firmware: Firmware) -> FirmwareResponse {
+        FirmwareResponse {
+            id: firmware.id.map(|oid| oid.to_hex()).unwrap_or_default(),
+            version: firmware.version,
+            checksum: firmware.checksum,
+            artifact_key: firmware.artifact_key,
+            artifact_url: firmware.artifact_url,
+            kit_models: firmware.kit_models,
+            release_notes: firmware.release_notes,
+            is_active: firmware.is_active,
+            created_at: crate::datetime::to_rfc3339(firmware.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(firmware.updated_at),
+        }
+    }
+}
+
+/// Compare dotted versions numerically (`1.10.0 > 1.9.3`), ignoring a leading `v` and any
+/// `-suffix`. Missing components count as zero; non-numeric components compare as text.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parts(version: &str) -> Vec<&str> {
+        let version = version.trim().trim_start_matches(['v', 'V']);
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        core.split('.').collect()
+    }
+
+    let (a, b) = (parts(a), parts(b));
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (a.get(i).copied().unwrap_or("0"), b.get(i).copied().unwrap_or("0"));
+        let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.3-beta", "1.2.4"), Ordering::Less);
+    }
+}
@@ -0,0 +1,136 @@
+use axum::http::{HeaderMap, StatusCode};
+use mongodb::{bson::{doc, oid::ObjectId, DateTime}, ClientSession};
+use crate::dto::payment::{CallbackResponse, PaymentLinkResponse};
+use crate::models::GatewayTransaction;
+use crate::payment_gateway::{payment_method_for, CallbackError, GatewayEvent, LinkRequest, PaymentGateway};
+use crate::repository::GatewayTransactionRepository;
+use crate::services::price_list_service::round_money;
+use crate::services::{finish, PaymentService};
+use crate::status::GatewayStatus;
+
+pub struct GatewayService {
+    gateway: Option<Box<dyn PaymentGateway>>,
+    transactions: GatewayTransactionRepository,
+    payments: PaymentService,
+}
+
+impl GatewayService {
+    pub fn new(gateway: Option<Box<dyn PaymentGateway>>, transactions: GatewayTransactionRepository, payments: PaymentService) -> Self {
+        Self { gateway, transactions, payments }
+    }
+
+    fn gateway(&self) -> Result<&dyn PaymentGateway, (StatusCode, String)> {
+        self.gateway.as_deref().ok_or((StatusCode::SERVICE_UNAVAILABLE, "No payment gateway is configured (PAYMENT_GATEWAY)".to_string()))
+    }
+
+    fn map_to_response(transaction: GatewayTransaction) -> PaymentLinkResponse {
+        PaymentLinkResponse {
+            id: transaction.id.map(|id| id.to_hex()).unwrap_or_default(),
+            invoice_id: transaction.invoice_id,
+            provider: transaction.provider,
+            amount: transaction.amount,
+            url: transaction.url,
+            status: transaction.status,
+            expires_at: transaction.expires_at,
+            payment_id: transaction.payment_id,
+            created_at: crate::datetime::to_rfc3339(transaction.created_at),
+        }
+    }
+
+    /// A link paying the invoice's outstanding balance
+    pub async fn create_link(&self, invoice_id: ObjectId) -> Result<PaymentLinkResponse, (StatusCode, String)> {
+        let gateway = self.gateway()?;
+        let invoice = self.payments.payable_invoice(invoice_id).await?;
+        let amount = round_money(invoice.total - invoice.paid_amount);
+        if amount <= 0.0 {
+            return Err((StatusCode::CONFLICT, "Invoice has no outstanding balance".to_string()));
+        }
+
+        let id = ObjectId::new();
+        let request = LinkRequest { order_id: id.to_hex(), amount, description: format!("Invoice {}", invoice.number.clone().unwrap_or_else(|| invoice_id.to_hex())) };
+        let link = gateway.create_link(&request).await.map_err(|e| (StatusCode::BAD_GATEWAY, e))?;
+
+        let transaction = GatewayTransaction {
+            id: Some(id),
+            invoice_id: invoice_id.to_hex(),
+            provider: gateway.name().to_string(),
+            amount,
+            url: link.url,
+            provider_reference: link.provider_reference,
+            status: GatewayStatus::Pending,
+            expires_at: link.expires_at,
+            payment_id: None,
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+        match self.transactions.insert(transaction).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn links(&self, invoice_id: ObjectId) -> Result<Vec<PaymentLinkResponse>, (StatusCode, String)> {
+        match self.transactions.find_by_invoice(&invoice_id.to_hex()).await {
+            Ok(transactions) => Ok(transactions.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Settle the pending transaction with the event's status and, when paid, record its
+    /// payment, all within `session`: `None` when it was no longer pending.
+    async fn settle(&self, session: &mut ClientSession, id: ObjectId, event: &GatewayEvent) -> Result<Option<(GatewayTransaction, Option<String>)>, (StatusCode, String)> {
+        let set = match &event.provider_reference {
+            Some(reference) => doc! { "providerReference": reference },
+            None => doc! {},
+        };
+        let Some(settled) = self.transactions.settle(session, id, event.status.clone(), set).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? else {
+            return Ok(None);
+        };
+        if settled.status != GatewayStatus::Paid {
+            return Ok(Some((settled, None)));
+        }
+
+        let invoice_id = ObjectId::parse_str(&settled.invoice_id).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        let method = payment_method_for(event.payment_type.as_deref());
+        let payment = self.payments
+            .record_gateway(session, invoice_id, method, event.amount, event.provider_reference.clone(), &settled.provider)
+            .await?;
+        self.transactions.set_payment(session, id, &payment.id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(Some((settled, Some(payment.id))))
+    }
+
+    /// Verify a provider callback and apply it once: a paid link records a payment, which
+    /// moves the invoice to partially paid or paid. Settling and recording commit together,
+    /// so when the payment cannot be recorded the link stays pending and a retry applies it.
+    pub async fn handle_callback(&self, headers: &HeaderMap, body: &[u8]) -> Result<CallbackResponse, (StatusCode, String)> {
+        let gateway = self.gateway()?;
+        let event = gateway.verify_callback(headers, body).map_err(|e| match e {
+            CallbackError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            CallbackError::Invalid(msg) => (StatusCode::BAD_REQUEST, msg),
+        })?;
+
+        let not_found = || (StatusCode::NOT_FOUND, format!("Unknown order {}", event.order_id));
+        let id = ObjectId::parse_str(&event.order_id).map_err(|_| not_found())?;
+        let transaction = self.transactions.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or_else(not_found)?;
+
+        let unchanged = |transaction: GatewayTransaction| CallbackResponse {
+            order_id: event.order_id.clone(),
+            status: transaction.status,
+            applied: false,
+            payment_id: transaction.payment_id,
+        };
+        if event.status == GatewayStatus::Pending {
+            return Ok(unchanged(transaction));
+        }
+
+        let mut session = self.payments.begin().await?;
+        let settled = self.settle(&mut session, id, &event).await;
+        let Some((settled, payment_id)) = finish(session, settled).await? else {
+            return Ok(unchanged(transaction));
+        };
+
+        Ok(CallbackResponse { order_id: event.order_id, status: settled.status, applied: true, payment_id })
+    }
+}
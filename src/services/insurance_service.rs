@@ -1,17 +1,49 @@
-use crate::models::Insurance;
+use crate::models::{Insurance, InsuranceVersion};
 use crate::repository::InsuranceRepository;
 use crate::pagination::{PaginationParams, PaginationMeta};
-use crate::dto::insurance::{CreateInsuranceRequest, UpdateInsuranceRequest, InsuranceResponse};
+use crate::dto::insurance::{CreateInsuranceRequest, UpdateInsuranceRequest, InsuranceResponse, InsuranceVersionResponse};
+use crate::status::InsuranceStatus;
+use crate::timezone::ClinicTimezone;
+use chrono::Utc;
 use mongodb::bson::oid::ObjectId;
 use axum::http::StatusCode;
 
 pub struct InsuranceService {
     repository: InsuranceRepository,
+    timezone: ClinicTimezone,
 }
 
 impl InsuranceService {
-    pub fn new(repository: InsuranceRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: InsuranceRepository, timezone: ClinicTimezone) -> Self {
+        Self { repository, timezone }
+    }
+
+    fn date_or_today(&self, date: Option<&str>) -> String {
+        date.map(|d| d.trim().to_string()).unwrap_or_else(|| self.timezone.today(Utc::now()))
+    }
+
+    fn map_version(version: InsuranceVersion) -> InsuranceVersionResponse {
+        InsuranceVersionResponse {
+            insurance_id: version.insurance_id,
+            version: version.version,
+            name: version.name,
+            insurance_type: version.insurance_type,
+            code: version.code,
+            status: version.status,
+            terms: version.terms.into(),
+            effective_from: version.effective_from,
+            effective_until: version.effective_until,
+            created_by: version.created_by,
+            created_at: crate::datetime::to_rfc3339(version.created_at),
+        }
+    }
+
+    fn version_conflict(e: String) -> (StatusCode, String) {
+        if e.contains("E11000") {
+            (StatusCode::CONFLICT, "Another version was created at the same time; retry".to_string())
+        } else {
+            (StatusCode::INTERNAL_SERVER_ERROR, e)
+        }
     }
 
     /// Map Insurance model to InsuranceResponse DTO
@@ -22,6 +54,9 @@ impl InsuranceService {
             insurance_type: insurance.insurance_type,
             code: insurance.code,
             status: insurance.status,
+            terms: insurance.terms.into(),
+            version: insurance.version,
+            effective_from: insurance.effective_from,
         }
     }
 
@@ -32,8 +67,8 @@ impl InsuranceService {
         }
     }
 
-    pub async fn get_all_paginated(&self, pagination: PaginationParams) -> Result<(Vec<InsuranceResponse>, PaginationMeta), (StatusCode, String)> {
-        match self.repository.find_all_paginated(pagination.clone()).await {
+    pub async fn get_all_paginated(&self, pagination: PaginationParams, status: Option<&InsuranceStatus>) -> Result<(Vec<InsuranceResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.repository.find_all_paginated(pagination.clone(), status).await {
             Ok((insurances, total)) => {
                 let responses = insurances.into_iter().map(Self::map_to_response).collect();
                 let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
@@ -43,19 +78,24 @@ impl InsuranceService {
         }
     }
 
-    pub async fn create(&self, request: CreateInsuranceRequest) -> Result<(StatusCode, InsuranceResponse), (StatusCode, String)> {
+    pub async fn create(&self, request: CreateInsuranceRequest, created_by: &str) -> Result<(StatusCode, InsuranceResponse), (StatusCode, String)> {
         let insurance = Insurance {
-            id: Some(ObjectId::new()),
+            id: None,
             name: request.name,
             insurance_type: request.insurance_type,
             code: request.code,
             status: request.status,
+            terms: request.terms.map(Into::into).unwrap_or_default(),
+            version: 1,
+            effective_from: self.date_or_today(request.effective_from.as_deref()),
         };
 
-        match self.repository.insert(insurance).await {
-            Ok(created) => Ok((StatusCode::CREATED, Self::map_to_response(created))),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
-        }
+        let created = self.repository.insert(insurance).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let id = created.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Insurance ID not found".to_string()))?;
+        self.repository.insert_version(&InsuranceVersion::of(&created, id, created_by)).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok((StatusCode::CREATED, Self::map_to_response(created)))
     }
 
     pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<InsuranceResponse>, (StatusCode, String)> {
@@ -66,11 +106,19 @@ impl InsuranceService {
         }
     }
 
-    pub async fn update(&self, id: ObjectId, request: UpdateInsuranceRequest) -> Result<InsuranceResponse, (StatusCode, String)> {
+    /// Apply the changes as a new version, closing the current one where it takes effect
+    pub async fn update(&self, id: ObjectId, request: UpdateInsuranceRequest, updated_by: &str) -> Result<InsuranceResponse, (StatusCode, String)> {
         let mut insurance = self.repository.find_by_id(id).await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
             .ok_or((StatusCode::NOT_FOUND, "Insurance not found".to_string()))?;
 
+        // Invoices already claimed under the current version keep their terms
+        let effective_from = self.date_or_today(request.effective_from.as_deref());
+        if effective_from < insurance.effective_from {
+            return Err((StatusCode::BAD_REQUEST, format!("effective_from must not be before {}, when version {} took effect", insurance.effective_from, insurance.version)));
+        }
+        let previous = insurance.version;
+
         if let Some(name) = request.name {
             insurance.name = name;
         }
@@ -83,13 +131,42 @@ impl InsuranceService {
         if let Some(status) = request.status {
             insurance.status = status;
         }
+        if let Some(terms) = request.terms {
+            insurance.terms = terms.into();
+        }
+        insurance.version = previous + 1;
+        insurance.effective_from = effective_from;
 
+        // The unique (insuranceId, version) index stops concurrent changes from both applying
+        self.repository.insert_version(&InsuranceVersion::of(&insurance, id, updated_by)).await
+            .map_err(Self::version_conflict)?;
+        self.repository.close_version(id, previous, &insurance.effective_from).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
         match self.repository.update(id, insurance).await {
             Ok(updated) => Ok(Self::map_to_response(updated)),
             Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
     }
 
+    /// Every version, newest first; `None` when the insurance never existed. Versions outlive
+    /// a deleted insurance, as invoices still refer to them.
+    pub async fn versions(&self, id: ObjectId) -> Result<Option<Vec<InsuranceVersionResponse>>, (StatusCode, String)> {
+        let versions = self.repository.find_versions(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok((!versions.is_empty()).then(|| versions.into_iter().map(Self::map_version).collect()))
+    }
+
+    /// The version to claim an invoice from on `date`; 422 unless one is in effect and active
+    pub async fn version_on(&self, id: ObjectId, date: &str) -> Result<InsuranceVersion, (StatusCode, String)> {
+        let version = self.repository.find_version_on(id, date).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::UNPROCESSABLE_ENTITY, format!("Insurance {} is not in effect on {}", id.to_hex(), date)))?;
+        if version.status != InsuranceStatus::Active {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("Insurance {} is {} on {}", id.to_hex(), version.status, date)));
+        }
+        Ok(version)
+    }
+
     pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
         match self.repository.delete(id).await {
             Ok(deleted) => Ok(deleted),
@@ -97,3 +174,18 @@ impl InsuranceService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::InsuranceTerms;
+
+    #[test]
+    fn claims_leave_the_copay_to_the_patient_up_to_the_maximum() {
+        let terms = InsuranceTerms { coverage_percent: 80.0, copay: 50_000.0, max_claim: Some(1_000_000.0) };
+        assert_eq!(terms.covered_amount(200_000.0), 150_000.0);
+        assert_eq!(terms.covered_amount(1_000_000.0), 800_000.0);
+        assert_eq!(terms.covered_amount(2_000_000.0), 1_000_000.0);
+        assert_eq!(terms.covered_amount(30_000.0), 0.0);
+        assert_eq!(InsuranceTerms::default().covered_amount(100_000.0), 0.0);
+    }
+}
@@ -1,6 +1,5 @@
 use std::sync::Arc;
-use mongodb::bson::{oid::ObjectId, doc};
-use chrono::Local;
+use mongodb::bson::{oid::ObjectId, doc, DateTime};
 use crate::repository::InterpretationRepository;
 use crate::models::{Interpretation, InterpretationCoding};
 use crate::dto::interpretation::{CreateInterpretationRequest, UpdateInterpretationRequest};
@@ -20,6 +19,11 @@ impl InterpretationService {
             return Err("Interpretation with this code already exists".to_string());
         }
 
+        let created_at = match dto.created_at {
+            Some(raw) => crate::datetime::parse(&raw).ok_or_else(|| format!("Invalid created_at '{}'", raw))?,
+            None => DateTime::now(),
+        };
+
         let interpretation = Interpretation {
             id: None,
             code: dto.code,
@@ -31,8 +35,9 @@ impl InterpretationService {
                 display: dto.coding.display,
             },
             text: dto.text.unwrap_or_default(),
-            created_at: Some(dto.created_at.unwrap_or_else(|| Local::now().format("%Y-%m-%d %H:%M:%S").to_string())),
-            updated_at: Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+            display_i18n: dto.display_i18n.unwrap_or_default(),
+            created_at: Some(created_at),
+            updated_at: Some(DateTime::now()),
         };
 
         self.repo.create(interpretation).await
@@ -104,11 +109,15 @@ impl InterpretationService {
             existing.text = text;
         }
 
-        if let Some(created_at) = dto.created_at {
-            existing.created_at = Some(created_at);
+        if let Some(display_i18n) = dto.display_i18n {
+            existing.display_i18n = display_i18n;
+        }
+
+        if let Some(raw) = dto.created_at {
+            existing.created_at = Some(crate::datetime::parse(&raw).ok_or_else(|| format!("Invalid created_at '{}'", raw))?);
         }
 
-        existing.updated_at = Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        existing.updated_at = Some(DateTime::now());
 
         self.repo.update(id, existing).await
     }
@@ -0,0 +1,163 @@
+use axum::http::StatusCode;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::invoice::{CreateInvoiceRequest, InvoiceClaimResponse, InvoiceCorporateResponse, InvoiceLineResponse, InvoiceQuery, InvoiceResponse};
+use crate::dto::price_list::QuoteRequest;
+use crate::models::{CorporateBilling, InsuranceClaim, Invoice, InvoiceLine, MedicalRecord, Organization};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::InvoiceRepository;
+use crate::sequences::{self, SequenceGenerator};
+use crate::services::price_list_service::round_money;
+use crate::services::{CorporateClientService, InsuranceService, PriceListService};
+use crate::status::InvoiceStatus;
+
+pub struct InvoiceService {
+    invoices: InvoiceRepository,
+    price_lists: PriceListService,
+    insurances: InsuranceService,
+    corporate: CorporateClientService,
+    references: ReferenceChecker,
+    sequences: SequenceGenerator,
+}
+
+impl InvoiceService {
+    pub fn new(invoices: InvoiceRepository, price_lists: PriceListService, insurances: InsuranceService, corporate: CorporateClientService, references: ReferenceChecker, sequences: SequenceGenerator) -> Self {
+        Self { invoices, price_lists, insurances, corporate, references, sequences }
+    }
+
+    pub(crate) fn map_to_response(invoice: Invoice) -> InvoiceResponse {
+        InvoiceResponse {
+            id: invoice.id.map(|id| id.to_hex()).unwrap_or_default(),
+            number: invoice.number,
+            organization_id: invoice.organization_id.to_hex(),
+            medical_record_id: invoice.patient_id.to_hex(),
+            service_date: invoice.service_date,
+            price_list_id: invoice.price_list_id,
+            price_list_version: invoice.price_list_version,
+            lines: invoice.lines.into_iter().map(|line| InvoiceLineResponse {
+                item_type: line.item_type,
+                item_id: line.item_id,
+                quantity: line.quantity,
+                unit_price: line.unit_price,
+                amount: line.amount,
+            }).collect(),
+            total: invoice.total,
+            paid_amount: invoice.paid_amount,
+            outstanding: round_money(invoice.total - invoice.paid_amount),
+            claim: invoice.claim.map(|claim| InvoiceClaimResponse {
+                insurance_id: claim.insurance_id,
+                insurance_version: claim.insurance_version,
+                covered_amount: claim.covered_amount,
+                patient_amount: claim.patient_amount,
+            }),
+            corporate: invoice.corporate.map(|corporate| InvoiceCorporateResponse {
+                client_id: corporate.client_id,
+                employee_number: corporate.employee_number,
+                covered_amount: corporate.covered_amount,
+                patient_amount: corporate.patient_amount,
+            }),
+            status: invoice.status,
+            created_by: invoice.created_by,
+            created_at: crate::datetime::to_rfc3339(invoice.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(invoice.updated_at),
+        }
+    }
+
+    /// Bill the items at the prices in effect on the day of service
+    pub async fn create(&self, request: CreateInvoiceRequest, created_by: &str) -> Result<InvoiceResponse, (StatusCode, String)> {
+        let organization = Ref::<Organization>::parse_field("organization_id", &request.organization_id)?;
+        let patient = Ref::<MedicalRecord>::parse_field("medical_record_id", &request.medical_record_id)?;
+        let insurance = request.insurance_id.as_deref()
+            .map(|id| ObjectId::parse_str(id).map_err(|_| (StatusCode::BAD_REQUEST, "insurance_id must be a valid ObjectId".to_string())))
+            .transpose()?;
+        self.references.ensure_exist(&[organization.check("organization_id"), patient.check("medical_record_id")]).await?;
+
+        let quote = self.price_lists.quote(QuoteRequest {
+            organization_id: organization.to_hex(),
+            date: request.service_date,
+            items: request.items,
+        }).await?;
+
+        // Numbered in the year of service, which the quote has already checked is a valid date
+        let year = quote.date.get(..4).and_then(|year| year.parse().ok()).unwrap_or_default();
+        let number = self.sequences.next(&sequences::INVOICE, Some(&organization.to_hex()), year).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        // Claimed under the terms in effect on the day of service, not today's
+        let claim = match insurance {
+            Some(id) => {
+                let version = self.insurances.version_on(id, &quote.date).await?;
+                let covered = round_money(version.terms.covered_amount(quote.total));
+                Some(InsuranceClaim {
+                    insurance_id: version.insurance_id,
+                    insurance_version: version.version,
+                    covered_amount: covered,
+                    patient_amount: round_money(quote.total - covered),
+                })
+            }
+            None => None,
+        };
+        // The patient's employer covers part of what the insurer leaves, per its contract
+        let remaining = claim.as_ref().map_or(quote.total, |claim| claim.patient_amount);
+        let corporate = match self.corporate.coverage(&patient.to_hex(), &organization.to_hex(), &quote.date).await? {
+            Some((client, employee)) => {
+                let covered = round_money(client.contract.terms.covered_amount(remaining));
+                Some(CorporateBilling {
+                    client_id: client.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    employee_number: employee.employee_number,
+                    covered_amount: covered,
+                    patient_amount: round_money(remaining - covered),
+                })
+            }
+            None => None,
+        };
+
+        let status = if quote.total > 0.0 { InvoiceStatus::Unpaid } else { InvoiceStatus::Paid };
+        let invoice = Invoice {
+            id: None,
+            organization_id: organization,
+            patient_id: patient,
+            number: Some(number),
+            service_date: quote.date,
+            price_list_id: quote.price_list_id,
+            price_list_version: quote.price_list_version,
+            lines: quote.lines.into_iter().map(|line| InvoiceLine {
+                item_type: line.item_type,
+                item_id: line.item_id,
+                quantity: line.quantity,
+                unit_price: line.unit_price,
+                amount: line.amount,
+            }).collect(),
+            total: quote.total,
+            paid_amount: 0.0,
+            claim,
+            corporate,
+            status,
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.invoices.create(invoice).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, query: InvoiceQuery, pagination: PaginationParams) -> Result<(Vec<InvoiceResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.invoices.find_paginated(query.medical_record_id.as_deref(), query.status.as_ref(), &pagination).await {
+            Ok((invoices, total)) => {
+                let responses = invoices.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<InvoiceResponse>, (StatusCode, String)> {
+        match self.invoices.find_by_id(id).await {
+            Ok(invoice) => Ok(invoice.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, Document, DateTime};
+use crate::dto::job::JobQuery;
+use crate::models::{Job, JobFailure, JobProgress};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::JobRepository;
+
+pub const JOB_PENDING: &str = "pending";
+pub const JOB_RUNNING: &str = "running";
+pub const JOB_COMPLETED: &str = "completed";
+pub const JOB_FAILED: &str = "failed";
+pub const JOB_STATUSES: &[&str] = &[JOB_PENDING, JOB_RUNNING, JOB_COMPLETED, JOB_FAILED];
+
+fn job_filter(query: &JobQuery) -> Document {
+    let mut filter = Document::new();
+    if let Some(status) = query.status.as_deref() {
+        filter.insert("status", status);
+    }
+    if let Some(job_type) = query.job_type.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        filter.insert("job_type", job_type);
+    }
+    filter
+}
+
+/// Lifecycle of persisted background jobs. Execution itself lives in `crate::jobs`.
+pub struct JobService {
+    repo: JobRepository,
+}
+
+impl JobService {
+    pub fn new(repo: JobRepository) -> Self {
+        Self { repo }
+    }
+
+    /// Persist a new pending job; `total` seeds the progress counter.
+    pub async fn enqueue(&self, job_type: &str, payload: Document, total: u64, actor: &str) -> Result<Job, String> {
+        let job = Job {
+            id: None,
+            job_type: job_type.to_string(),
+            status: JOB_PENDING.to_string(),
+            payload,
+            progress: JobProgress { total, ..Default::default() },
+            result: None,
+            error: None,
+            errors: Vec::new(),
+            attempts: 0,
+            created_by: actor.to_string(),
+            started_at: None,
+            finished_at: None,
+            updated_at: None,
+            created_at: DateTime::now(),
+        };
+
+        self.repo.create(job).await
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<Job>, String> {
+        self.repo.find_by_id(id).await
+    }
+
+    pub async fn start(&self, id: ObjectId) -> Result<(), String> {
+        self.repo.mark_running(id, DateTime::now()).await
+    }
+
+    pub async fn report_progress(&self, id: ObjectId, progress: &JobProgress) -> Result<(), String> {
+        self.repo.update_progress(id, progress, DateTime::now()).await
+    }
+
+    pub async fn complete(&self, id: ObjectId, result: Document) -> Result<(), String> {
+        let now = DateTime::now();
+        self.repo.set_fields(id, doc! { "status": JOB_COMPLETED, "result": result, "finished_at": crate::datetime::to_rfc3339(now) }, now).await
+    }
+
+    /// Record the failure of run number `attempt`
+    pub async fn fail(&self, id: ObjectId, attempt: i32, error: &str) -> Result<(), String> {
+        let now = DateTime::now();
+        let failure = JobFailure { attempt, error: error.to_string(), failed_at: crate::datetime::to_rfc3339(now) };
+        self.repo.record_failure(id, &failure, now).await
+    }
+
+    pub async fn list(&self, query: JobQuery, pagination: PaginationParams) -> Result<(Vec<Job>, PaginationMeta), (StatusCode, String)> {
+        let (jobs, total) = self.repo.find_paginated(job_filter(&query), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok((jobs, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    /// Requeue a failed job. The caller runs it, see `crate::jobs::spawn`.
+    pub async fn retry(&self, id: ObjectId) -> Result<Job, (StatusCode, String)> {
+        let job = self.repo.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Job not found".to_string()))?;
+        if job.status != JOB_FAILED {
+            return Err((StatusCode::CONFLICT, format!("Job is {}; only failed jobs can be retried", job.status)));
+        }
+        self.repo.requeue_failed(id, DateTime::now()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Job was retried meanwhile".to_string()))
+    }
+
+    /// Requeue up to `limit` failed jobs of `job_type`, oldest first
+    pub async fn retry_failed(&self, job_type: &str, limit: i64) -> Result<Vec<Job>, (StatusCode, String)> {
+        let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+        let ids = self.repo.find_ids(doc! { "job_type": job_type, "status": JOB_FAILED }, limit).await.map_err(internal)?;
+        let mut requeued = Vec::new();
+        for id in ids {
+            // Skips jobs another request requeued in the meantime
+            if let Some(job) = self.repo.requeue_failed(id, DateTime::now()).await.map_err(internal)? {
+                requeued.push(job);
+            }
+        }
+        Ok(requeued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_status_and_type() {
+        let query = JobQuery { status: Some(JOB_FAILED.to_string()), job_type: Some(" code_import ".to_string()) };
+        assert_eq!(job_filter(&query), doc! { "status": "failed", "job_type": "code_import" });
+        assert_eq!(job_filter(&JobQuery::default()), Document::new());
+    }
+}
@@ -0,0 +1,152 @@
+use axum::http::StatusCode;
+use chrono::Duration;
+use mongodb::bson::{doc, oid::ObjectId, Bson, DateTime};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use crate::dto::kiosk::{CreateKioskDeviceRequest, KioskDeviceResponse, KioskPairingCodeResponse, KioskTokenResponse};
+use crate::models::KioskDevice;
+use crate::repository::KioskDeviceRepository;
+use crate::services::AuthService;
+use crate::status::KioskStatus;
+
+/// Letters and digits that cannot be mistaken for each other on a kiosk keyboard
+const PAIRING_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const PAIRING_CODE_LENGTH: usize = 8;
+const PAIRING_TTL_MINUTES: i64 = 15;
+
+/// Check-in kiosks: an admin registers a device and gets a pairing code, which the kiosk
+/// exchanges once for a long-lived token limited to check-in and queue endpoints.
+pub struct KioskService {
+    repo: KioskDeviceRepository,
+}
+
+fn generate_pairing_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..PAIRING_CODE_LENGTH)
+        .map(|_| PAIRING_ALPHABET[rng.gen_range(0..PAIRING_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Codes are typed by hand, so case, spaces and dashes are ignored
+fn hash_pairing_code(code: &str) -> String {
+    let normalized: String = code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_uppercase();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+fn status_of(device: &KioskDevice) -> KioskStatus {
+    if device.revoked_at.is_some() {
+        KioskStatus::Revoked
+    } else if device.paired_at.is_some() {
+        KioskStatus::Paired
+    } else {
+        KioskStatus::Pending
+    }
+}
+
+impl KioskService {
+    pub fn new(repo: KioskDeviceRepository) -> Self {
+        Self { repo }
+    }
+
+    fn map_to_response(device: KioskDevice) -> KioskDeviceResponse {
+        KioskDeviceResponse {
+            id: device.id.map(|id| id.to_hex()).unwrap_or_default(),
+            status: status_of(&device),
+            name: device.name,
+            organization_id: device.organization_id,
+            pairing_expires_at: crate::datetime::to_rfc3339_opt(device.pairing_expires_at),
+            paired_at: crate::datetime::to_rfc3339_opt(device.paired_at),
+            revoked_at: crate::datetime::to_rfc3339_opt(device.revoked_at),
+            last_seen_at: crate::datetime::to_rfc3339_opt(device.last_seen_at),
+            created_by: device.created_by,
+            created_at: crate::datetime::to_rfc3339(device.created_at),
+        }
+    }
+
+    /// A fresh code and its expiry
+    fn pairing_code() -> (String, DateTime) {
+        let expires_at = chrono::Utc::now() + Duration::minutes(PAIRING_TTL_MINUTES);
+        (generate_pairing_code(), crate::datetime::from_chrono(expires_at))
+    }
+
+    pub async fn list(&self) -> Result<Vec<KioskDeviceResponse>, (StatusCode, String)> {
+        match self.repo.find_all().await {
+            Ok(devices) => Ok(devices.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<KioskDeviceResponse>, (StatusCode, String)> {
+        match self.repo.find_by_id(id).await {
+            Ok(device) => Ok(device.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreateKioskDeviceRequest, created_by: &str) -> Result<KioskPairingCodeResponse, (StatusCode, String)> {
+        let (pairing_code, expires_at) = Self::pairing_code();
+        let device = KioskDevice {
+            id: None,
+            name: request.name.trim().to_string(),
+            organization_id: request.organization_id,
+            pairing_code_hash: Some(hash_pairing_code(&pairing_code)),
+            pairing_expires_at: Some(expires_at),
+            paired_at: None,
+            revoked_at: None,
+            last_seen_at: None,
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+        };
+
+        match self.repo.create(device).await {
+            Ok(created) => Ok(KioskPairingCodeResponse { device: Self::map_to_response(created), pairing_code }),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// A new pairing code for a device, e.g. after its hardware was replaced. Pairing with it
+    /// revokes the tokens issued before.
+    pub async fn renew_pairing_code(&self, id: ObjectId) -> Result<Option<KioskPairingCodeResponse>, (StatusCode, String)> {
+        let (pairing_code, expires_at) = Self::pairing_code();
+        let set = doc! { "pairingCodeHash": hash_pairing_code(&pairing_code), "pairingExpiresAt": expires_at };
+
+        match self.repo.update_fields(id, set).await {
+            Ok(device) => Ok(device.map(|d| KioskPairingCodeResponse { device: Self::map_to_response(d), pairing_code })),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Stop the device's tokens and any outstanding pairing code
+    pub async fn revoke(&self, id: ObjectId) -> Result<Option<KioskDeviceResponse>, (StatusCode, String)> {
+        let set = doc! { "revokedAt": DateTime::now(), "pairingCodeHash": Bson::Null, "pairingExpiresAt": Bson::Null };
+        match self.repo.update_fields(id, set).await {
+            Ok(device) => Ok(device.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Exchange a pairing code for the kiosk's token
+    pub async fn pair(&self, pairing_code: &str) -> Result<KioskTokenResponse, (StatusCode, String)> {
+        let device = self.repo.pair(&hash_pairing_code(pairing_code), DateTime::now()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::UNAUTHORIZED, "Invalid or expired pairing code".to_string()))?;
+
+        let (access_token, expires_in) = AuthService::generate_kiosk_token(&device)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(KioskTokenResponse { access_token, token_type: "Bearer".to_string(), expires_in, device: Self::map_to_response(device) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairing_codes_are_typed_loosely() {
+        let code = generate_pairing_code();
+        assert_eq!(code.len(), PAIRING_CODE_LENGTH);
+        assert!(code.bytes().all(|c| PAIRING_ALPHABET.contains(&c)));
+        assert_eq!(hash_pairing_code("abcd-2345"), hash_pairing_code("ABCD 2345"));
+        assert_ne!(hash_pairing_code("ABCD2345"), hash_pairing_code("ABCD2346"));
+    }
+}
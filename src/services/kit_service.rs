@@ -1,9 +1,10 @@
 use std::sync::Arc;
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use chrono::Local;
 use crate::repository::KitRepository;
 use crate::models::{Kit, KitOwner, KitDistributor, KitOperator, KitPasien};
-use crate::dto::kit::{CreateKitRequest, UpdateKitRequest, KitResponse, KitOwnerDto, KitDistributorDto, KitOperatorDto, KitPasienDto};
+use crate::dto::kit::{CreateKitRequest, UpdateKitRequest, KitHeartbeatRequest, KitResponse, KitSummary, KitOwnerDto, KitDistributorDto, KitOperatorDto, KitPasienDto, MapCluster, MapClustersQuery, NearbyKit, NearbyKitsQuery};
+use crate::geo::{self, GeoPoint};
 
 pub struct KitService {
     repo: Arc<KitRepository>,
@@ -16,10 +17,14 @@ impl KitService {
 
     pub async fn create(&self, dto: CreateKitRequest) -> Result<Kit, String> {
         // Unique Code Check
-        if self.repo.find_by_code(&dto.code).await?.is_some() {
+        if self.repo.code_exists(&dto.code).await? {
             return Err("Kit with this code already exists".to_string());
         }
+        if !self.repo.scope().allows(&dto.distributor.code) {
+            return Err(format!("You cannot manage kits of distributor {}", dto.distributor.code));
+        }
 
+        let location = geo::point(dto.latitude, dto.longitude)?;
         let kit = Kit {
             id: None,
             code: dto.code,
@@ -44,8 +49,12 @@ impl KitService {
                 id_pasien: dto.pasien.id_pasien,
                 time: dto.pasien.time,
             },
-            created_at: Local::now().to_rfc3339(),
-            updated_at: Some(Local::now().to_rfc3339()),
+            model: dto.model,
+            firmware_version: None,
+            last_heartbeat_at: None,
+            location,
+            created_at: DateTime::now(),
+            updated_at: Some(DateTime::now()),
         };
 
         self.repo.create(kit).await
@@ -56,6 +65,19 @@ impl KitService {
         Ok(kits.into_iter().map(Self::map_to_response).collect())
     }
 
+    pub async fn get_summaries(&self) -> Result<Vec<KitSummary>, String> {
+        let rows = self.repo.find_all_summaries().await?;
+        Ok(rows.into_iter().map(|row| KitSummary {
+            id: row.id.to_hex(),
+            code: row.code,
+            name: row.name,
+            is_active: row.is_active,
+            model: row.model,
+            firmware_version: row.firmware_version,
+            last_heartbeat_at: row.last_heartbeat_at,
+        }).collect())
+    }
+
     pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<KitResponse>, String> {
         let kit = self.repo.find_by_id(id).await?;
         Ok(kit.map(Self::map_to_response))
@@ -67,7 +89,7 @@ impl KitService {
 
         if let Some(code) = dto.code {
             if code != existing.code {
-                if self.repo.find_by_code(&code).await?.is_some() {
+                if self.repo.code_exists(&code).await? {
                     return Err("Kit with this code already exists".to_string());
                 }
                 existing.code = code;
@@ -86,6 +108,9 @@ impl KitService {
         }
 
         if let Some(distributor) = dto.distributor {
+            if !self.repo.scope().allows(&distributor.code) {
+                return Err(format!("You cannot manage kits of distributor {}", distributor.code));
+            }
             existing.distributor = KitDistributor {
                 code: distributor.code,
                 name: distributor.name,
@@ -119,7 +144,15 @@ impl KitService {
             };
         }
 
-        existing.updated_at = Some(Local::now().to_rfc3339());
+        if let Some(model) = dto.model {
+            existing.model = Some(model);
+        }
+
+        if let Some(location) = geo::point(dto.latitude, dto.longitude)? {
+            existing.location = Some(location);
+        }
+
+        existing.updated_at = Some(DateTime::now());
 
         let updated = self.repo.update(id, existing).await?;
         Ok(Self::map_to_response(updated))
@@ -129,7 +162,47 @@ impl KitService {
         self.repo.delete(id).await
     }
 
-    fn map_to_response(kit: Kit) -> KitResponse {
+    /// Kits within `radius` meters (10 km by default) of the point, nearest first
+    pub async fn nearby(&self, query: NearbyKitsQuery) -> Result<Vec<NearbyKit>, String> {
+        let center = GeoPoint::new(query.lat, query.lng);
+        let rows = self.repo
+            .find_nearby(&center, query.radius.unwrap_or(10_000.0), query.limit.unwrap_or(100), query.distributor.as_deref())
+            .await?;
+        Ok(rows.into_iter().map(|row| NearbyKit {
+            id: row.id.to_hex(),
+            code: row.code,
+            name: row.name,
+            is_active: row.is_active,
+            distributor: KitDistributorDto { code: row.distributor.code, name: row.distributor.name },
+            latitude: row.location.latitude(),
+            longitude: row.location.longitude(),
+            distance: row.distance,
+        }).collect())
+    }
+
+    /// Kits grouped into a grid that gets finer with `zoom` (5 by default)
+    pub async fn map_clusters(&self, query: MapClustersQuery, bounds: Option<[f64; 4]>) -> Result<Vec<MapCluster>, String> {
+        let cell = geo::cell_degrees(query.zoom.unwrap_or(5));
+        let rows = self.repo.map_clusters(cell, bounds, query.distributor.as_deref()).await?;
+        Ok(rows.into_iter().map(|row| MapCluster {
+            latitude: row.latitude,
+            longitude: row.longitude,
+            count: row.count,
+            active: row.active,
+            kit_id: (row.count == 1).then(|| row.first_id.to_hex()),
+        }).collect())
+    }
+
+    /// Record a heartbeat from a kit, storing the firmware version (and model, if sent) it reports.
+    pub async fn heartbeat(&self, code: &str, dto: KitHeartbeatRequest) -> Result<Option<KitResponse>, String> {
+        let now = Local::now().to_rfc3339();
+        let kit = self.repo
+            .record_heartbeat(code, dto.firmware_version.trim(), dto.model.as_deref().map(str::trim), &now)
+            .await?;
+        Ok(kit.map(Self::map_to_response))
+    }
+
+    pub(crate) fn map_to_response(kit: Kit) -> KitResponse {
         KitResponse {
             id: kit.id.map(|oid| oid.to_hex()).unwrap_or_default(),
             code: kit.code,
@@ -154,8 +227,13 @@ impl KitService {
                 id_pasien: kit.pasien.id_pasien,
                 time: kit.pasien.time,
             },
-            created_at: kit.created_at,
-            updated_at: kit.updated_at,
+            model: kit.model,
+            firmware_version: kit.firmware_version,
+            last_heartbeat_at: kit.last_heartbeat_at,
+            latitude: kit.location.as_ref().map(GeoPoint::latitude),
+            longitude: kit.location.as_ref().map(GeoPoint::longitude),
+            created_at: crate::datetime::to_rfc3339(kit.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(kit.updated_at),
         }
     }
 }
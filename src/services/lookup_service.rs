@@ -0,0 +1,113 @@
+use crate::dto::appointment::PatientSummary;
+#[cfg(feature = "kits")]
+use crate::dto::kit::KitSummary;
+use crate::dto::search::{LookupMatch, LookupResponse};
+use crate::rbac::{PermissionSet, Resource};
+#[cfg(feature = "kits")]
+use crate::repository::KitRepository;
+use crate::repository::{MedicalRecordRepository, MedicineRepository};
+use crate::services::MedicineService;
+
+/// Prefix of the QR code printed on patient cards, followed by the medical record number
+pub const CARD_PREFIX: &str = "RME:";
+/// Batches returned for one scanned batch number
+const MAX_BATCHES: i64 = 10;
+
+/// The medical record number in a patient card payload
+pub fn card_nrme(value: &str) -> Option<&str> {
+    let prefix = value.get(..CARD_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(CARD_PREFIX) {
+        return None;
+    }
+    Some(value[CARD_PREFIX.len()..].trim()).filter(|nrme| !nrme.is_empty())
+}
+
+/// Resolves scanned barcodes and QR codes with exact, indexed matches: medicine batch
+/// labels (see `crate::labels`), kit codes and patient cards.
+pub struct LookupService {
+    medicines: MedicineRepository,
+    records: MedicalRecordRepository,
+    #[cfg(feature = "kits")]
+    kits: KitRepository,
+}
+
+impl LookupService {
+    pub fn new(
+        medicines: MedicineRepository,
+        records: MedicalRecordRepository,
+        #[cfg(feature = "kits")] kits: KitRepository,
+    ) -> Self {
+        Self {
+            medicines,
+            records,
+            #[cfg(feature = "kits")]
+            kits,
+        }
+    }
+
+    /// Card payloads only match patients; other values are tried as a batch number, a kit
+    /// code and a bare medical record number, skipping resources the caller may not read.
+    pub async fn lookup(&self, value: &str, permissions: &PermissionSet) -> Result<LookupResponse, String> {
+        let mut matches = Vec::new();
+        if let Some(nrme) = card_nrme(value) {
+            if permissions.can_read(Resource::Patients) {
+                matches.extend(self.patient(nrme).await?);
+            }
+            return Ok(LookupResponse { value: value.to_string(), matches });
+        }
+
+        let medicines = async {
+            if !permissions.can_read(Resource::Medicines) {
+                return Ok(Vec::new());
+            }
+            self.medicines.find_by_batch_number(value, MAX_BATCHES).await
+        };
+        let patient = async {
+            if !permissions.can_read(Resource::Patients) {
+                return Ok(None);
+            }
+            self.patient(value).await
+        };
+        #[cfg(feature = "kits")]
+        let (medicines, kit, patient) = tokio::join!(medicines, self.kits.find_by_code(value), patient);
+        #[cfg(not(feature = "kits"))]
+        let (medicines, patient) = tokio::join!(medicines, patient);
+
+        matches.extend(medicines?.into_iter().map(|m| LookupMatch::Medicine(MedicineService::map_to_response(m))));
+        #[cfg(feature = "kits")]
+        matches.extend(kit?.map(|kit| LookupMatch::Kit(KitSummary {
+            id: kit.id.map(|id| id.to_hex()).unwrap_or_default(),
+            code: kit.code,
+            name: kit.name,
+            is_active: kit.is_active,
+            model: kit.model,
+            firmware_version: kit.firmware_version,
+            last_heartbeat_at: kit.last_heartbeat_at,
+        })));
+        matches.extend(patient?);
+        Ok(LookupResponse { value: value.to_string(), matches })
+    }
+
+    async fn patient(&self, nrme: &str) -> Result<Option<LookupMatch>, String> {
+        let record = self.records.find_by_nrme(nrme).await?;
+        Ok(record.map(|record| LookupMatch::Patient(PatientSummary {
+            id: record.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: record.name,
+            nrme: record.nrme,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_card_payloads() {
+        assert_eq!(card_nrme("RME:2026.000123"), Some("2026.000123"));
+        assert_eq!(card_nrme("rme: 2026.000123 "), Some("2026.000123"));
+        assert_eq!(card_nrme("RME:"), None);
+        assert_eq!(card_nrme("BN2024"), None);
+        assert_eq!(card_nrme("Ré"), None);
+    }
+}
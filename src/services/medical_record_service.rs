@@ -1,22 +1,52 @@
-use crate::models::MedicalRecord;
-use crate::repository::MedicalRecordRepository;
+use crate::delete_policy::DeleteGuard;
+use crate::middleware::AuthUser;
+use crate::models::{FieldChange, MedicalRecord, MedicalRecordChange};
+use crate::repository::{MedicalRecordChangeRepository, MedicalRecordRepository};
+use crate::phone;
+use crate::sequences::{self, SequenceGenerator};
 use crate::validation;
 use crate::pagination::{PaginationParams, PaginationMeta};
-use crate::dto::medical_record::{CreateMedicalRecordRequest, UpdateMedicalRecordRequest, MedicalRecordResponse};
-use mongodb::bson::oid::ObjectId;
+use crate::dto::medical_record::{
+    CreateMedicalRecordRequest, FieldChangeResponse, MedicalRecordChangeResponse, MedicalRecordResponse, UpdateMedicalRecordRequest,
+};
+use mongodb::bson::{oid::ObjectId, Bson, DateTime};
 use axum::http::StatusCode;
+use chrono::Datelike;
+
+/// Left out of a change: the id, and `lastVisitDate`, which every update stamps
+const UNTRACKED: &[&str] = &["id", "_id", "lastVisitDate"];
 
 pub struct MedicalRecordService {
     repository: MedicalRecordRepository,
+    changes: MedicalRecordChangeRepository,
+    sequences: SequenceGenerator,
+    deletes: DeleteGuard,
+}
+
+/// The stored fields that differ between two versions of a record, in field order
+fn diff(before: &MedicalRecord, after: &MedicalRecord) -> Vec<FieldChange> {
+    let (Ok(before), Ok(after)) = (mongodb::bson::to_document(before), mongodb::bson::to_document(after)) else {
+        return Vec::new();
+    };
+    let mut fields: Vec<&String> = before.keys().collect();
+    fields.extend(after.keys().filter(|key| !before.contains_key(key.as_str())));
+
+    fields.into_iter()
+        .filter(|field| !UNTRACKED.contains(&field.as_str()))
+        .filter_map(|field| {
+            let (old, new) = (before.get(field).cloned(), after.get(field).cloned());
+            (old != new).then(|| FieldChange { field: field.clone(), old, new })
+        })
+        .collect()
 }
 
 impl MedicalRecordService {
-    pub fn new(repository: MedicalRecordRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: MedicalRecordRepository, changes: MedicalRecordChangeRepository, sequences: SequenceGenerator, deletes: DeleteGuard) -> Self {
+        Self { repository, changes, sequences, deletes }
     }
 
     /// Map MedicalRecord model to MedicalRecordResponse DTO
-    fn map_to_response(record: MedicalRecord) -> MedicalRecordResponse {
+    pub(crate) fn map_to_response(record: MedicalRecord) -> MedicalRecordResponse {
         MedicalRecordResponse {
             id: record.id.map(|id| id.to_hex()).unwrap_or_default(),
             nik: record.nik,
@@ -27,6 +57,11 @@ impl MedicalRecordService {
             hp: record.hp,
             email: record.email,
             last_visit_date: record.last_visit_date,
+            phone_verified_at: record.phone_verified_at,
+            updated_at: crate::datetime::to_rfc3339_opt(record.updated_at),
+            registration_status: record.self_registration.map(|r| r.status),
+            guardians: None,
+            links: None,
         }
     }
 
@@ -47,33 +82,66 @@ impl MedicalRecordService {
         Ok(record.map(Self::map_to_response))
     }
 
-    pub async fn create(&self, request: CreateMedicalRecordRequest) -> Result<(StatusCode, MedicalRecordResponse), (StatusCode, String)> {
-        // Validate NIK format
-        if let Err(_) = validation::validate_nik(&request.nik) {
+    pub async fn get_by_nik(&self, nik: &str) -> Result<Option<MedicalRecordResponse>, (StatusCode, String)> {
+        if validation::validate_nik(nik).is_err() {
             return Err((StatusCode::BAD_REQUEST, "Invalid NIK format".to_string()));
         }
 
-        // Check NIK uniqueness
-        match self.repository.find_by_nik(&request.nik).await {
-            Ok(Some(_)) => {
-                return Err((StatusCode::CONFLICT, "NIK already exists".to_string()));
-            }
-            Ok(None) => {}
-            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        match self.repository.find_by_nik(nik).await {
+            Ok(record) => Ok(record.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
+    }
 
-        // Create record model
-        let record = MedicalRecord {
+    /// The record a create request describes under `nrme`, not yet stored
+    pub(crate) fn build(request: CreateMedicalRecordRequest, nrme: String) -> Result<MedicalRecord, (StatusCode, String)> {
+        let hp = phone::normalize(&request.hp).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        Ok(MedicalRecord {
             id: Some(ObjectId::new()),
             nik: request.nik,
-            nrme: request.nrme,
+            nrme,
             name: request.name,
             dob: request.dob,
             gender: request.gender,
-            hp: request.hp,
+            hp,
             email: request.email,
             last_visit_date: chrono::Local::now().format("%Y-%m-%d").to_string(),
-        };
+            phone_verified_at: None,
+            updated_at: None,
+            self_registration: None,
+        })
+    }
+
+    /// Set the fields an update request carries; a new phone number needs verifying again
+    pub(crate) fn apply(record: &mut MedicalRecord, request: UpdateMedicalRecordRequest) -> Result<(), (StatusCode, String)> {
+        if let Some(nrme) = request.nrme { record.nrme = nrme; }
+        if let Some(name) = request.name { record.name = name; }
+        if let Some(dob) = request.dob { record.dob = dob; }
+        if let Some(gender) = request.gender { record.gender = gender; }
+        if let Some(hp) = request.hp {
+            let hp = phone::normalize(&hp).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            if hp != record.hp {
+                record.phone_verified_at = None;
+            }
+            record.hp = hp;
+        }
+        if let Some(email) = request.email { record.email = email; }
+
+        record.last_visit_date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        Ok(())
+    }
+
+    /// Create a record with a generated NRME. When the NIK is already registered, the existing
+    /// record is returned with 200 instead.
+    pub async fn create(&self, request: CreateMedicalRecordRequest) -> Result<(StatusCode, MedicalRecordResponse), (StatusCode, String)> {
+        // Validate NIK format, and return the patient's record if there is one
+        if let Some(existing) = self.get_by_nik(&request.nik).await? {
+            return Ok((StatusCode::OK, existing));
+        }
+
+        let nrme = self.sequences.next(&sequences::MEDICAL_RECORD, None, chrono::Local::now().year()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let record = Self::build(request, nrme)?;
 
         // Insert record
         match self.repository.insert(record).await {
@@ -82,32 +150,108 @@ impl MedicalRecordService {
         }
     }
 
-    pub async fn update(&self, id: ObjectId, request: UpdateMedicalRecordRequest) -> Result<MedicalRecordResponse, (StatusCode, String)> {
+    fn map_change(change: MedicalRecordChange) -> MedicalRecordChangeResponse {
+        MedicalRecordChangeResponse {
+            id: change.id.map(|id| id.to_hex()).unwrap_or_default(),
+            changed_by: change.changed_by,
+            changed_by_name: change.changed_by_name,
+            fields: change.fields.into_iter().map(|f| FieldChangeResponse {
+                field: f.field,
+                old: f.old.map(Bson::into_relaxed_extjson),
+                new: f.new.map(Bson::into_relaxed_extjson),
+            }).collect(),
+            created_at: crate::datetime::to_rfc3339(change.created_at),
+        }
+    }
+
+    /// Update the record and store the fields that changed, with `editor` as the author.
+    pub async fn update(&self, id: ObjectId, editor: &AuthUser, request: UpdateMedicalRecordRequest) -> Result<MedicalRecordResponse, (StatusCode, String)> {
         // Find existing record
-        let mut record = self.repository.find_by_id(id).await
+        let before = self.repository.find_by_id(id).await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
             .ok_or((StatusCode::NOT_FOUND, "Medical record not found".to_string()))?;
+        let mut record = before.clone();
+        Self::apply(&mut record, request)?;
 
-        // Update fields if provided
-        if let Some(nrme) = request.nrme { record.nrme = nrme; }
-        if let Some(name) = request.name { record.name = name; }
-        if let Some(dob) = request.dob { record.dob = dob; }
-        if let Some(gender) = request.gender { record.gender = gender; }
-        if let Some(hp) = request.hp { record.hp = hp; }
-        if let Some(email) = request.email { record.email = email; }
+        let fields = diff(&before, &record);
+        let updated = self.repository.update(id, record).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
 
-        record.last_visit_date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        if !fields.is_empty() {
+            let change = MedicalRecordChange {
+                id: None,
+                record_id: id.to_hex(),
+                changed_by: editor.id.clone(),
+                changed_by_name: editor.name.clone(),
+                fields,
+                created_at: DateTime::now(),
+            };
+            self.changes.insert(change).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        }
 
-        match self.repository.update(id, record).await {
-            Ok(updated) => Ok(Self::map_to_response(updated)),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        Ok(Self::map_to_response(updated))
+    }
+
+    /// Field changes of a record, newest first; `None` when the record does not exist.
+    pub async fn changes(&self, id: ObjectId, pagination: PaginationParams) -> Result<Option<(Vec<MedicalRecordChangeResponse>, PaginationMeta)>, (StatusCode, String)> {
+        match self.repository.find_by_id(id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Ok(None),
+            Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
         }
+
+        let (changes, total) = self.changes.find_by_record(&id.to_hex(), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
+        Ok(Some((changes.into_iter().map(Self::map_change).collect(), meta)))
     }
 
+    /// Purge a record. By default its upcoming appointments are cancelled and its files
+    /// soft-deleted first, and a record with observations is kept (409).
     pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
-        match self.repository.delete(id).await {
-            Ok(deleted) => Ok(deleted),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        let mut session = self.deletes.begin().await?;
+        let deleted = async {
+            self.deletes.prepare(&mut session, "medical_records", id).await?;
+            self.repository.delete(&mut session, id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        }.await;
+        crate::services::finish(session, deleted).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::status::Gender;
+
+    fn record() -> MedicalRecord {
+        MedicalRecord {
+            id: Some(ObjectId::new()),
+            nik: "3171234567890001".to_string(),
+            nrme: "RM-0001".to_string(),
+            name: "Siti".to_string(),
+            dob: "1990-01-01".to_string(),
+            gender: Gender::Female,
+            hp: "081234567890".to_string(),
+            email: "siti@example.com".to_string(),
+            last_visit_date: "2026-01-01".to_string(),
+            phone_verified_at: Some("2026-01-01T00:00:00Z".to_string()),
+            updated_at: None,
+            self_registration: None,
         }
     }
+
+    #[test]
+    fn diff_lists_changed_fields_only() {
+        let before = record();
+        let mut after = before.clone();
+        after.last_visit_date = "2026-02-01".to_string();
+        assert!(diff(&before, &after).is_empty());
+
+        after.hp = "081111111111".to_string();
+        after.phone_verified_at = None;
+        assert_eq!(diff(&before, &after), vec![
+            FieldChange { field: "hp".to_string(), old: Some(Bson::from("081234567890")), new: Some(Bson::from("081111111111")) },
+            FieldChange { field: "phoneVerifiedAt".to_string(), old: Some(Bson::from("2026-01-01T00:00:00Z")), new: None },
+        ]);
+    }
 }
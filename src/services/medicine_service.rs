@@ -15,7 +15,7 @@ impl MedicineService {
     }
 
     /// Map Medicine model to MedicineResponse DTO
-    fn map_to_response(medicine: Medicine) -> MedicineResponse {
+    pub(crate) fn map_to_response(medicine: Medicine) -> MedicineResponse {
         MedicineResponse {
             id: medicine.id.map(|id| id.to_hex()).unwrap_or_default(),
             master_medicine_id: medicine.master_medicine_id,
@@ -48,8 +48,9 @@ impl MedicineService {
         }
     }
 
-    pub async fn create(&self, request: CreateMedicineRequest) -> Result<(StatusCode, MedicineResponse), (StatusCode, String)> {
-        let medicine = Medicine {
+    /// The medicine a create request describes, not yet stored
+    pub(crate) fn build(request: CreateMedicineRequest) -> Medicine {
+        Medicine {
             id: Some(ObjectId::new()),
             master_medicine_id: request.master_medicine_id,
             batch_number: request.batch_number,
@@ -60,7 +61,24 @@ impl MedicineService {
             selling_price: request.selling_price,
             qty: request.qty,
             manufacturer: request.manufacturer,
-        };
+        }
+    }
+
+    /// Set the fields an update request carries
+    pub(crate) fn apply(medicine: &mut Medicine, request: UpdateMedicineRequest) {
+        if let Some(val) = request.master_medicine_id { medicine.master_medicine_id = val; }
+        if let Some(val) = request.batch_number { medicine.batch_number = val; }
+        if let Some(val) = request.trade_name { medicine.trade_name = val; }
+        if let Some(val) = request.production_date { medicine.production_date = val; }
+        if let Some(val) = request.expired_date { medicine.expired_date = val; }
+        if let Some(val) = request.purchase_price { medicine.purchase_price = val; }
+        if let Some(val) = request.selling_price { medicine.selling_price = val; }
+        if let Some(val) = request.qty { medicine.qty = val; }
+        if let Some(val) = request.manufacturer { medicine.manufacturer = val; }
+    }
+
+    pub async fn create(&self, request: CreateMedicineRequest) -> Result<(StatusCode, MedicineResponse), (StatusCode, String)> {
+        let medicine = Self::build(request);
 
         match self.repository.insert(medicine).await {
             Ok(created) => Ok((StatusCode::CREATED, Self::map_to_response(created))),
@@ -80,16 +98,7 @@ impl MedicineService {
         let mut medicine = self.repository.find_by_id(id).await
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
             .ok_or((StatusCode::NOT_FOUND, "Medicine not found".to_string()))?;
-
-        if let Some(val) = request.master_medicine_id { medicine.master_medicine_id = val; }
-        if let Some(val) = request.batch_number { medicine.batch_number = val; }
-        if let Some(val) = request.trade_name { medicine.trade_name = val; }
-        if let Some(val) = request.production_date { medicine.production_date = val; }
-        if let Some(val) = request.expired_date { medicine.expired_date = val; }
-        if let Some(val) = request.purchase_price { medicine.purchase_price = val; }
-        if let Some(val) = request.selling_price { medicine.selling_price = val; }
-        if let Some(val) = request.qty { medicine.qty = val; }
-        if let Some(val) = request.manufacturer { medicine.manufacturer = val; }
+        Self::apply(&mut medicine, request);
 
         match self.repository.update(id, medicine).await {
             Ok(updated) => Ok(Self::map_to_response(updated)),
@@ -104,3 +113,41 @@ impl MedicineService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[test]
+    fn create_requests_ignore_client_ids_and_are_validated() {
+        let request: CreateMedicineRequest = serde_json::from_value(serde_json::json!({
+            "_id": "65a1b2c3d4e5f60718293a4b",
+            "master_medicine_id": "MM-1",
+            "batch_number": "B-1",
+            "trade_name": "Paracetamol",
+            "production_date": "2024-01-01",
+            "expired_date": "2026-01-01",
+            "purchase_price": 1000.0,
+            "selling_price": -1.0,
+            "qty": 10.0,
+            "manufacturer": "Kimia Farma"
+        })).unwrap();
+        assert!(request.validate().is_err());
+
+        let response = MedicineService::map_to_response(Medicine {
+            id: None,
+            master_medicine_id: request.master_medicine_id,
+            batch_number: request.batch_number,
+            trade_name: request.trade_name,
+            production_date: request.production_date,
+            expired_date: request.expired_date,
+            purchase_price: request.purchase_price,
+            selling_price: 1500.0,
+            qty: request.qty,
+            manufacturer: request.manufacturer,
+        });
+        assert_eq!(response.id, "");
+        assert_eq!(response.trade_name, "Paracetamol");
+    }
+}
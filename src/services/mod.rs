@@ -1,5 +1,10 @@
 pub mod medical_record_service;
+#[cfg(feature = "s3")]
 pub mod file_service;
+#[cfg(feature = "s3")]
+pub mod file_share_service;
+#[cfg(feature = "s3")]
+pub mod storage_reconciliation_service;
 pub mod doctor_service;
 pub mod nurse_service;
 pub mod medicine_service;
@@ -10,7 +15,12 @@ pub mod auth_service;
 pub mod user_service;
 
 pub use medical_record_service::MedicalRecordService;
+#[cfg(feature = "s3")]
 pub use file_service::FileService;
+#[cfg(feature = "s3")]
+pub use file_share_service::FileShareService;
+#[cfg(feature = "s3")]
+pub use storage_reconciliation_service::StorageReconciliationService;
 pub use doctor_service::DoctorService;
 pub use nurse_service::NurseService;
 pub use medicine_service::MedicineService;
@@ -33,7 +43,134 @@ pub mod region_service;
 pub use region_service::RegionService;
 pub mod interpretation;
 pub use interpretation::InterpretationService;
+#[cfg(feature = "kits")]
 pub mod kit_service;
+#[cfg(feature = "kits")]
 pub use kit_service::KitService;
 pub mod observation_service;
 pub use observation_service::ObservationService;
+pub mod audit_service;
+pub use audit_service::AuditService;
+pub mod patient_service;
+pub use patient_service::{PatientReferences, PatientService};
+pub mod search_service;
+pub use search_service::SearchService;
+pub mod timeline_service;
+pub use timeline_service::TimelineService;
+pub mod job_service;
+pub use job_service::JobService;
+#[cfg(feature = "fhir")]
+pub mod code_import_service;
+#[cfg(feature = "fhir")]
+pub use code_import_service::CodeImportService;
+pub mod retention_service;
+pub mod denormalization_service;
+pub use retention_service::RetentionService;
+pub use denormalization_service::DenormalizationService;
+#[cfg(feature = "kits")]
+pub mod firmware_service;
+#[cfg(feature = "kits")]
+pub use firmware_service::FirmwareService;
+#[cfg(feature = "kits")]
+pub mod usage_service;
+#[cfg(feature = "kits")]
+pub use usage_service::UsageService;
+#[cfg(feature = "kits")]
+pub mod distributor_service;
+#[cfg(feature = "kits")]
+pub use distributor_service::DistributorService;
+pub mod appointment_series_service;
+pub use appointment_series_service::AppointmentSeriesService;
+pub mod waitlist_service;
+pub use waitlist_service::WaitlistService;
+pub mod teleconsult_service;
+pub use teleconsult_service::TeleconsultService;
+pub mod review_service;
+pub use review_service::ReviewService;
+pub mod patient_auth_service;
+pub use patient_auth_service::PatientAuthService;
+pub mod otp_service;
+pub use otp_service::OtpService;
+pub mod email_verification_service;
+pub use email_verification_service::EmailVerificationService;
+pub mod permission_service;
+pub use permission_service::PermissionService;
+pub mod service_account_service;
+pub use service_account_service::ServiceAccountService;
+pub mod kiosk_service;
+pub use kiosk_service::KioskService;
+pub mod feature_flag_service;
+pub use feature_flag_service::FeatureFlagService;
+pub mod organization_service;
+pub use organization_service::OrganizationService;
+pub mod note_service;
+pub use note_service::NoteService;
+pub mod allergy_service;
+pub use allergy_service::AllergyService;
+pub mod ward_service;
+pub use ward_service::WardService;
+pub mod admission_service;
+pub use admission_service::AdmissionService;
+pub mod program_service;
+pub use program_service::ProgramService;
+#[cfg(feature = "billing")]
+pub mod price_list_service;
+#[cfg(feature = "billing")]
+pub use price_list_service::PriceListService;
+#[cfg(feature = "billing")]
+pub mod invoice_service;
+#[cfg(feature = "billing")]
+pub use invoice_service::InvoiceService;
+#[cfg(feature = "billing")]
+pub mod corporate_client_service;
+#[cfg(feature = "billing")]
+pub use corporate_client_service::CorporateClientService;
+#[cfg(feature = "billing")]
+pub mod payment_service;
+#[cfg(feature = "billing")]
+pub use payment_service::PaymentService;
+#[cfg(feature = "billing")]
+pub mod gateway_service;
+#[cfg(feature = "billing")]
+pub use gateway_service::GatewayService;
+#[cfg(feature = "billing")]
+pub mod bpjs_service;
+#[cfg(feature = "billing")]
+pub use bpjs_service::BpjsService;
+pub mod outbox_service;
+pub use outbox_service::OutboxService;
+#[cfg(feature = "s3")]
+pub mod report_service;
+#[cfg(feature = "s3")]
+pub use report_service::ReportService;
+pub mod report_template_service;
+pub use report_template_service::ReportTemplateService;
+pub mod practitioner_service;
+pub use practitioner_service::PractitionerService;
+pub mod patient_relationship_service;
+pub use patient_relationship_service::PatientRelationshipService;
+pub mod self_registration_service;
+pub use self_registration_service::SelfRegistrationService;
+pub mod public_booking_service;
+pub use public_booking_service::PublicBookingService;
+pub mod lookup_service;
+pub use lookup_service::LookupService;
+pub mod stock_opname_service;
+pub use stock_opname_service::StockOpnameService;
+pub mod sync_service;
+pub use sync_service::SyncService;
+pub mod clinical_sync_service;
+pub use clinical_sync_service::ClinicalSyncService;
+
+/// Commit `session` if `result` is Ok, abort it otherwise
+pub(crate) async fn finish<T>(mut session: mongodb::ClientSession, result: Result<T, (axum::http::StatusCode, String)>) -> Result<T, (axum::http::StatusCode, String)> {
+    match result {
+        Ok(value) => session.commit_transaction().await
+            .map(|_| value)
+            .map_err(|e| (axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Err(e) => {
+            let _ = session.abort_transaction().await;
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, to_bson, DateTime};
+use crate::delete_policy::DELETED_AT;
+use crate::dto::note::{
+    CreateNoteRequest, DeleteNoteRequest, NoteQuery, NoteResponse, NoteVersionResponse, SoapSectionsDto, UpdateNoteRequest,
+};
+use crate::middleware::AuthUser;
+use crate::models::{MedicalRecord, Note, NoteVersion, SoapSections};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{NoteRepository, NoteVersionRepository};
+use crate::services::AuditService;
+
+pub struct NoteService {
+    notes: NoteRepository,
+    versions: NoteVersionRepository,
+    references: ReferenceChecker,
+    audit: AuditService,
+}
+
+/// Trimmed text and SOAP sections of a note; at least one of them must be present.
+fn note_content(text: Option<String>, soap: Option<SoapSectionsDto>) -> Result<(Option<String>, Option<SoapSections>), (StatusCode, String)> {
+    let text = text.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    let soap = soap.and_then(SoapSectionsDto::into_sections);
+    if text.is_none() && soap.is_none() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "A note needs text or at least one SOAP section".to_string()));
+    }
+    Ok((text, soap))
+}
+
+impl NoteService {
+    pub fn new(notes: NoteRepository, versions: NoteVersionRepository, references: ReferenceChecker, audit: AuditService) -> Self {
+        Self { notes, versions, references, audit }
+    }
+
+    pub(crate) fn map_to_response(note: Note) -> NoteResponse {
+        NoteResponse {
+            id: note.id.map(|id| id.to_hex()).unwrap_or_default(),
+            medical_record_id: note.medical_record_id.to_hex(),
+            author_id: note.author_id,
+            text: note.text,
+            soap: note.soap.map(SoapSectionsDto::from),
+            version: note.version,
+            updated_by: note.updated_by,
+            created_at: crate::datetime::to_rfc3339(note.created_at),
+            updated_at: crate::datetime::to_rfc3339(note.updated_at),
+        }
+    }
+
+    fn map_version(version: NoteVersion) -> NoteVersionResponse {
+        NoteVersionResponse {
+            version: version.version,
+            edited_by: version.edited_by,
+            text: version.text,
+            soap: version.soap.map(SoapSectionsDto::from),
+            created_at: crate::datetime::to_rfc3339(version.created_at),
+        }
+    }
+
+    /// Snapshot the note as it is now into `note_versions`
+    async fn record_version(&self, note: &Note) -> Result<(), (StatusCode, String)> {
+        let version = NoteVersion {
+            id: None,
+            note_id: note.id.map(|id| id.to_hex()).unwrap_or_default(),
+            version: note.version,
+            edited_by: note.updated_by.clone(),
+            text: note.text.clone(),
+            soap: note.soap.clone(),
+            created_at: note.updated_at,
+        };
+        self.versions.insert(version).await
+            .map(|_| ())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    pub async fn create(&self, author: &AuthUser, request: CreateNoteRequest) -> Result<NoteResponse, (StatusCode, String)> {
+        let medical_record_id: Ref<MedicalRecord> = Ref::parse_field("medical_record_id", &request.medical_record_id)?;
+        self.references.ensure_exist(&[medical_record_id.check("medical_record_id")]).await?;
+        let (text, soap) = note_content(request.text, request.soap)?;
+
+        let now = DateTime::now();
+        let note = Note {
+            id: None,
+            medical_record_id,
+            author_id: author.id.clone(),
+            text,
+            soap,
+            version: 1,
+            updated_by: author.id.clone(),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            deleted_by: None,
+            delete_reason: None,
+        };
+
+        let created = self.notes.create(note).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.record_version(&created).await?;
+        Ok(Self::map_to_response(created))
+    }
+
+    pub async fn list(&self, query: NoteQuery, pagination: PaginationParams) -> Result<(Vec<NoteResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.notes.find_paginated(query.medical_record_id.as_deref(), &pagination).await {
+            Ok((notes, total)) => {
+                let responses = notes.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<NoteResponse>, (StatusCode, String)> {
+        match self.notes.find_by_id(id).await {
+            Ok(note) => Ok(note.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Write a new version of the note. Earlier versions stay untouched in `note_versions`.
+    pub async fn update(&self, id: ObjectId, editor: &AuthUser, request: UpdateNoteRequest) -> Result<NoteResponse, (StatusCode, String)> {
+        let current = self.notes.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Note not found".to_string()))?;
+        if request.version.is_some_and(|version| version != current.version) {
+            return Err((StatusCode::CONFLICT, format!("Note is at version {}; reload it before editing", current.version)));
+        }
+        let (text, soap) = note_content(request.text, request.soap)?;
+        if text == current.text && soap == current.soap {
+            return Ok(Self::map_to_response(current));
+        }
+
+        let set = doc! {
+            "text": text,
+            "soap": to_bson(&soap).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+            "version": current.version + 1,
+            "updatedBy": &editor.id,
+            "updatedAt": DateTime::now(),
+        };
+        let updated = self.notes.update_at_version(id, current.version, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Note was edited or deleted concurrently".to_string()))?;
+        self.record_version(&updated).await?;
+        Ok(Self::map_to_response(updated))
+    }
+
+    /// All versions of a live note, oldest first; `None` when the note does not exist.
+    pub async fn versions(&self, id: ObjectId) -> Result<Option<Vec<NoteVersionResponse>>, (StatusCode, String)> {
+        let note = self.notes.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if note.is_none() {
+            return Ok(None);
+        }
+        match self.versions.find_by_note(&id.to_hex()).await {
+            Ok(versions) => Ok(Some(versions.into_iter().map(Self::map_version).collect())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Soft-delete with the reason, which is also written to the audit log.
+    pub async fn delete(&self, id: ObjectId, user: &AuthUser, request: DeleteNoteRequest) -> Result<bool, (StatusCode, String)> {
+        let reason = request.reason.trim().to_string();
+        let set = doc! { DELETED_AT: DateTime::now(), "deletedBy": &user.id, "deleteReason": &reason };
+        match self.notes.soft_delete(id, set).await {
+            Ok(Some(note)) => {
+                self.audit
+                    .record("delete", "notes", &id.to_hex(), &user.id, doc! { "reason": reason, "version": note.version })
+                    .await;
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_need_text_or_a_soap_section() {
+        let blank = SoapSectionsDto { subjective: Some("  ".into()), ..SoapSectionsDto::default() };
+        let (status, _) = note_content(Some(" ".into()), Some(blank)).unwrap_err();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let soap = SoapSectionsDto { assessment: Some(" Hypertension ".into()), plan: Some(String::new()), ..SoapSectionsDto::default() };
+        let (text, sections) = note_content(None, Some(soap)).unwrap();
+        assert_eq!(text, None);
+        let sections = sections.unwrap();
+        assert_eq!(sections.assessment.as_deref(), Some("Hypertension"));
+        assert_eq!(sections.plan, None);
+    }
+}
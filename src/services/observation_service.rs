@@ -1,26 +1,145 @@
-use mongodb::bson::oid::ObjectId;
-use chrono::Utc;
+use mongodb::bson::{oid::ObjectId, DateTime};
 use crate::models::{
-    Observation, ObservationUnit, ObservationPasien, ObservationPasienNama,
+    MedicalRecord, Observation, ObservationUnit, ObservationPasien, ObservationPasienNama,
     ObservationPasienLahir, ObservationPasienUsia, ObservationAtmSehat,
     ObservationAtmSehatOwner, ObservationCoding, ObservationCategory,
     ObservationBaseLine, ObservationInterpretation
 };
-use crate::repository::ObservationRepository;
-use crate::dto::observation::{CreateObservationRequest, UpdateObservationRequest, ObservationResponse};
+use crate::repository::{MedicalRecordRepository, ObservationRepository};
+use crate::repository::observation::ObservationTrendRow;
+use crate::dto::observation::{
+    CreateObservationRequest, UpdateObservationRequest, ObservationResponse, ObservationSummary,
+    ObservationBaseLineDto, ObservationCodingDto, ObservationUnitDto,
+    TrendQuery, TrendPoint, TrendFlags, TrendResponse, ObservationExportQuery, VitalsBundleRequest,
+};
 use crate::pagination::PaginationParams;
+use crate::stats;
+use crate::derived::{self, DerivedRule, INTERPRETATION_SYSTEM};
+use crate::vitals::{self, VitalSign, CATEGORY_SYSTEM, CATEGORY_VITAL_SIGNS, LOINC_SYSTEM, UCUM_SYSTEM};
+use crate::refs::{Ref, ReferenceChecker};
+use axum::http::StatusCode;
+use futures_util::stream::{Stream, StreamExt};
+use std::collections::HashMap;
+
+const DEFAULT_TREND_WINDOW: i64 = 20;
+const MAX_TREND_WINDOW: i64 = 500;
+const DEFAULT_ROLLING_WINDOW: i64 = 5;
 
 pub struct ObservationService {
     repository: ObservationRepository,
+    patients: MedicalRecordRepository,
+    references: ReferenceChecker,
+}
+
+/// The patient as embedded in observations, aged on `today`
+fn embedded_patient(record: &MedicalRecord, today: chrono::NaiveDate) -> ObservationPasien {
+    let (nama_depan, nama_belakang) = crate::denormalize::split_name(&record.name);
+    let (tahun, bulan, hari) = chrono::NaiveDate::parse_from_str(&record.dob, "%Y-%m-%d")
+        .ok()
+        .and_then(|dob| vitals::age_on(dob, today))
+        .unwrap_or_default();
+    ObservationPasien {
+        id: record.id.map(|id| id.to_hex()).unwrap_or_default(),
+        nama: ObservationPasienNama { nama_depan, nama_belakang },
+        gender: record.gender.clone(),
+        nik: record.nik.clone(),
+        lahir: ObservationPasienLahir { tempat: String::new(), tanggal: record.dob.clone() },
+        usia: ObservationPasienUsia { tahun, bulan, hari },
+        parent: None,
+    }
 }
 
 impl ObservationService {
-    pub fn new(repository: ObservationRepository) -> Self {
-        Self { repository }
+    pub fn new(repository: ObservationRepository, patients: MedicalRecordRepository, references: ReferenceChecker) -> Self {
+        Self { repository, patients, references }
     }
 
-    pub async fn create_observation(&self, req: CreateObservationRequest) -> Result<ObservationResponse, String> {
-        let now = Utc::now().to_rfc3339();
+    /// Store each sign of a vitals bundle as its own observation, all in one transaction,
+    /// then derive from them (e.g. BMI from weight and height).
+    pub async fn record_vitals(&self, medical_record_id: ObjectId, recorded_by: &str, bundle: VitalsBundleRequest) -> Result<Vec<ObservationResponse>, (StatusCode, String)> {
+        let record = self.patients.find_by_id(medical_record_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Medical record not found".to_string()))?;
+
+        let now = chrono::Utc::now();
+        let time = bundle.time.unwrap_or_else(|| now.timestamp());
+        let pasien = embedded_patient(&record, now.date_naive());
+        let atm_sehat = bundle.atm_sehat.map(|kit| ObservationAtmSehat {
+            code: kit.code,
+            name: kit.name,
+            owner: ObservationAtmSehatOwner { code: kit.owner.code, name: kit.owner.name },
+        }).unwrap_or(ObservationAtmSehat {
+            code: String::new(),
+            name: String::new(),
+            owner: ObservationAtmSehatOwner { code: String::new(), name: String::new() },
+        });
+
+        let measured = [
+            (VitalSign::Temperature, bundle.temperature),
+            (VitalSign::Systolic, bundle.systolic),
+            (VitalSign::Diastolic, bundle.diastolic),
+            (VitalSign::Pulse, bundle.pulse),
+            (VitalSign::Spo2, bundle.spo2),
+            (VitalSign::Weight, bundle.weight),
+            (VitalSign::Height, bundle.height),
+        ];
+        let created_at = DateTime::now();
+        let observations: Vec<Observation> = measured
+            .into_iter()
+            .filter_map(|(sign, value)| value.map(|value| (vitals::definition(sign), value)))
+            .map(|(definition, value)| {
+                let (code, display) = derived::interpret(value, definition.base_line);
+                Observation {
+                    id: None,
+                    value,
+                    unit: ObservationUnit {
+                        code: definition.unit_code.to_string(),
+                        display: definition.unit_display.to_string(),
+                        system: UCUM_SYSTEM.to_string(),
+                    },
+                    id_pasien: Ref::new(medical_record_id),
+                    pasien: pasien.clone(),
+                    id_petugas: recorded_by.to_string(),
+                    atm_sehat: atm_sehat.clone(),
+                    time,
+                    coding: ObservationCoding {
+                        code: definition.code.to_string(),
+                        display: definition.display.to_string(),
+                        system: LOINC_SYSTEM.to_string(),
+                    },
+                    category: ObservationCategory {
+                        code: CATEGORY_VITAL_SIGNS.0.to_string(),
+                        display: CATEGORY_VITAL_SIGNS.1.to_string(),
+                        system: CATEGORY_SYSTEM.to_string(),
+                    },
+                    base_line: ObservationBaseLine { min: definition.base_line.0, max: definition.base_line.1 },
+                    interpretation: ObservationInterpretation {
+                        code: code.to_string(),
+                        display: display.to_string(),
+                        system: INTERPRETATION_SYSTEM.to_string(),
+                        text: format!("{} {}", definition.display, display.to_lowercase()),
+                    },
+                    log_user_kit_id: None,
+                    derived: false,
+                    derived_from: Vec::new(),
+                    created_at: Some(created_at),
+                    updated_at: Some(created_at),
+                }
+            })
+            .collect();
+
+        let created = self.repository.create_all(observations).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        for observation in &created {
+            self.derive_from(observation).await;
+        }
+        Ok(created.into_iter().map(ObservationResponse::from).collect())
+    }
+
+    pub async fn create_observation(&self, req: CreateObservationRequest) -> Result<ObservationResponse, (StatusCode, String)> {
+        let now = DateTime::now();
+        let id_pasien = Ref::parse_field("id_pasien", &req.id_pasien)?;
+        self.references.ensure_exist(&[id_pasien.check("id_pasien")]).await?;
         
         let observation = Observation {
             id: None,
@@ -30,7 +149,7 @@ impl ObservationService {
                 display: req.unit.display,
                 system: req.unit.system,
             },
-            id_pasien: req.id_pasien,
+            id_pasien,
             pasien: ObservationPasien {
                 id: req.pasien.id,
                 nama: ObservationPasienNama {
@@ -81,31 +200,41 @@ impl ObservationService {
                 text: req.interpretation.text,
             },
             log_user_kit_id: req.log_user_kit_id,
-            created_at: Some(now.clone()),
+            derived: false,
+            derived_from: Vec::new(),
+            created_at: Some(now),
             updated_at: Some(now),
         };
 
-        let created = self.repository.create(observation).await?;
+        let created = self.repository.create(observation).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.derive_from(&created).await;
         Ok(ObservationResponse::from(created))
     }
 
-    pub async fn get_observations(&self, pagination: PaginationParams) -> Result<(Vec<ObservationResponse>, u64), String> {
-        let (observations, total) = self.repository.find_all_paginated(pagination).await?;
+    pub async fn get_observations(&self, pagination: PaginationParams, id_pasien: Option<&str>) -> Result<(Vec<ObservationResponse>, u64), String> {
+        let (observations, total) = self.repository.find_all_paginated(pagination, id_pasien).await?;
         let responses = observations.into_iter().map(ObservationResponse::from).collect();
         Ok((responses, total))
     }
 
+    pub async fn get_summaries(&self, pagination: PaginationParams, id_pasien: Option<&str>) -> Result<(Vec<ObservationSummary>, u64), String> {
+        let (rows, total) = self.repository.find_summaries_paginated(pagination, id_pasien).await?;
+        Ok((rows.into_iter().map(ObservationSummary::from).collect(), total))
+    }
+
     pub async fn get_observation_by_id(&self, id: &str) -> Result<Option<ObservationResponse>, String> {
         let obj_id = ObjectId::parse_str(id).map_err(|_| "Invalid ID format".to_string())?;
         let observation = self.repository.find_by_id(obj_id).await?;
         Ok(observation.map(ObservationResponse::from))
     }
 
-    pub async fn update_observation(&self, id: &str, req: UpdateObservationRequest) -> Result<ObservationResponse, String> {
-        let obj_id = ObjectId::parse_str(id).map_err(|_| "Invalid ID format".to_string())?;
+    pub async fn update_observation(&self, id: &str, req: UpdateObservationRequest) -> Result<ObservationResponse, (StatusCode, String)> {
+        let obj_id = ObjectId::parse_str(id).map_err(|_| (StatusCode::BAD_REQUEST, "Invalid ID format".to_string()))?;
         
-        let mut observation = self.repository.find_by_id(obj_id).await?
-            .ok_or_else(|| "Observation not found".to_string())?;
+        let mut observation = self.repository.find_by_id(obj_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "Observation not found".to_string()))?;
 
         if let Some(v) = req.value { observation.value = v; }
         if let Some(u) = req.unit {
@@ -113,7 +242,13 @@ impl ObservationService {
             observation.unit.display = u.display;
             observation.unit.system = u.system;
         }
-        if let Some(ip) = req.id_pasien { observation.id_pasien = ip; }
+        if let Some(ip) = req.id_pasien {
+            let id_pasien = Ref::parse_field("id_pasien", &ip)?;
+            if id_pasien != observation.id_pasien {
+                self.references.ensure_exist(&[id_pasien.check("id_pasien")]).await?;
+            }
+            observation.id_pasien = id_pasien;
+        }
         if let Some(p) = req.pasien {
             observation.pasien.id = p.id;
             observation.pasien.nama.nama_depan = p.nama.nama_depan;
@@ -159,14 +294,182 @@ impl ObservationService {
             observation.log_user_kit_id = req.log_user_kit_id;
         }
 
-        observation.updated_at = Some(Utc::now().to_rfc3339());
+        observation.updated_at = Some(DateTime::now());
 
-        let updated = self.repository.update(obj_id, observation).await?;
+        let updated = self.repository.update(obj_id, observation).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.derive_from(&updated).await;
         Ok(ObservationResponse::from(updated))
     }
 
+    /// Observations matching an export query, converted one by one as the cursor advances.
+    pub async fn export(&self, query: &ObservationExportQuery) -> Result<impl Stream<Item = Result<ObservationResponse, mongodb::error::Error>>, String> {
+        let cursor = self.repository
+            .export_cursor(query.from, query.to, &query.coding_codes(), query.id_pasien.as_deref())
+            .await?;
+        Ok(cursor.map(|observation| observation.map(ObservationResponse::from)))
+    }
+
     pub async fn delete_observation(&self, id: &str) -> Result<bool, String> {
         let obj_id = ObjectId::parse_str(id).map_err(|_| "Invalid ID format".to_string())?;
         self.repository.delete(obj_id).await
     }
+
+    /// Rolling statistics, regression slope and base-line breach flags for one vital sign of a patient.
+    /// Returns `None` when the patient has no observations of that coding in range.
+    pub async fn get_trend(&self, patient_id: &str, coding_code: &str, query: TrendQuery) -> Result<Option<TrendResponse>, String> {
+        let window = query.window.unwrap_or(DEFAULT_TREND_WINDOW).clamp(1, MAX_TREND_WINDOW);
+        let rolling = query.rolling.unwrap_or(DEFAULT_ROLLING_WINDOW).clamp(1, window);
+
+        let rows = self.repository
+            .find_trend(patient_id, coding_code, query.from, query.to, window, rolling)
+            .await?;
+        let Some(latest) = rows.last() else { return Ok(None) };
+
+        let values: Vec<f64> = rows.iter().map(|r| r.value).collect();
+        let mean = stats::mean(&values).unwrap_or_default();
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let regression: Vec<(f64, f64)> = rows.iter().map(|r| (stats::epoch_to_days(r.time), r.value)).collect();
+        let slope_per_day = stats::linear_regression_slope(&regression);
+
+        let latest_breach = Self::breach(latest);
+        let consecutive_breaches = rows.iter().rev().take_while(|r| Self::breach(r).is_some()).count();
+        let worsening = match (latest_breach, slope_per_day) {
+            (Some("above_max"), Some(slope)) => slope > 0.0,
+            (Some("below_min"), Some(slope)) => slope < 0.0,
+            _ => false,
+        };
+
+        Ok(Some(TrendResponse {
+            id_pasien: patient_id.to_string(),
+            coding: ObservationCodingDto {
+                code: latest.coding.code.clone(),
+                display: latest.coding.display.clone(),
+                system: latest.coding.system.clone(),
+            },
+            unit: ObservationUnitDto {
+                code: latest.unit.code.clone(),
+                display: latest.unit.display.clone(),
+                system: latest.unit.system.clone(),
+            },
+            base_line: ObservationBaseLineDto {
+                min: latest.base_line.min,
+                max: latest.base_line.max,
+            },
+            count: rows.len(),
+            mean,
+            min,
+            max,
+            slope_per_day,
+            flags: TrendFlags {
+                latest_breach: latest_breach.map(str::to_string),
+                consecutive_breaches,
+                worsening,
+            },
+            points: rows.iter().map(|r| TrendPoint {
+                time: r.time,
+                value: r.value,
+                rolling_mean: r.rolling_mean,
+                rolling_min: r.rolling_min,
+                rolling_max: r.rolling_max,
+                breach: Self::breach(r).map(str::to_string),
+            }).collect(),
+        }))
+    }
+
+    /// Recompute every derived observation that takes `trigger` as an input.
+    ///
+    /// Derivation never fails the triggering write; errors are logged.
+    async fn derive_from(&self, trigger: &Observation) {
+        if trigger.derived {
+            return;
+        }
+        for rule in derived::rules_for(&trigger.coding.code) {
+            if let Err(e) = self.apply_rule(rule, trigger).await {
+                tracing::error!(code = %rule.output_code, patient_id = %trigger.id_pasien, error = %e, "Failed to derive an observation");
+            }
+        }
+    }
+
+    async fn apply_rule(&self, rule: &DerivedRule, trigger: &Observation) -> Result<(), String> {
+        let window = stats::epoch_delta_hours(trigger.time, rule.window_hours);
+        let mut values = HashMap::new();
+        let mut sources = Vec::new();
+        let mut time = trigger.time;
+
+        for input in rule.inputs {
+            let observation = if input.coding_codes.contains(&trigger.coding.code.as_str()) {
+                trigger.clone()
+            } else {
+                let found = self.repository
+                    .find_latest_measured(&trigger.id_pasien.to_hex(), input.coding_codes, trigger.time - window, trigger.time + window)
+                    .await?;
+                let Some(found) = found else { return Ok(()) };
+                found
+            };
+
+            let Some(value) = (input.normalize)(observation.value, &observation.unit.code)
+                .or_else(|| (input.normalize)(observation.value, &observation.unit.display)) else {
+                return Err(format!("Unsupported unit '{}' for {}", observation.unit.code, input.name));
+            };
+
+            values.insert(input.name, value);
+            sources.extend(observation.id.map(|id| id.to_hex()));
+            time = time.max(observation.time);
+        }
+
+        let Some(value) = rule.compute(&values) else { return Ok(()) };
+        let (interpretation_code, interpretation_display) = rule.interpret(value);
+        let now = DateTime::now();
+
+        let mut derived = trigger.clone();
+        derived.id = None;
+        derived.value = value;
+        derived.time = time;
+        derived.unit = ObservationUnit {
+            code: rule.unit_code.to_string(),
+            display: rule.unit_display.to_string(),
+            system: rule.unit_system.to_string(),
+        };
+        derived.coding = ObservationCoding {
+            code: rule.output_code.to_string(),
+            display: rule.output_display.to_string(),
+            system: rule.output_system.to_string(),
+        };
+        derived.base_line = ObservationBaseLine { min: rule.base_line.0, max: rule.base_line.1 };
+        derived.interpretation = ObservationInterpretation {
+            code: interpretation_code.to_string(),
+            display: interpretation_display.to_string(),
+            system: INTERPRETATION_SYSTEM.to_string(),
+            text: format!("{} {}", rule.output_display, interpretation_display.to_lowercase()),
+        };
+        derived.derived = true;
+        derived.derived_from = sources;
+        derived.updated_at = Some(now);
+
+        match self.repository.find_derived(&trigger.id_pasien.to_hex(), rule.output_code, time).await? {
+            Some(existing) => {
+                derived.created_at = existing.created_at;
+                let id = existing.id.ok_or("Derived observation has no ID")?;
+                self.repository.update(id, derived).await?;
+            }
+            None => {
+                derived.created_at = Some(now);
+                self.repository.create(derived).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn breach(row: &ObservationTrendRow) -> Option<&'static str> {
+        if row.value > row.base_line.max {
+            Some("above_max")
+        } else if row.value < row.base_line.min {
+            Some("below_min")
+        } else {
+            None
+        }
+    }
 }
@@ -0,0 +1,120 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::delete_policy::DeleteGuard;
+use crate::dto::organization::{CreateOrganizationRequest, OrganizationResponse, UpdateOrganizationRequest};
+use crate::geo::{self, GeoPoint};
+use crate::models::Organization;
+use crate::repository::OrganizationRepository;
+use crate::timezone::ClinicTimezone;
+
+pub struct OrganizationService {
+    repo: OrganizationRepository,
+    deletes: DeleteGuard,
+    default_timezone: ClinicTimezone,
+}
+
+fn parse_timezone(raw: &str) -> Result<ClinicTimezone, (StatusCode, String)> {
+    ClinicTimezone::parse(raw).map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+impl OrganizationService {
+    pub fn new(repo: OrganizationRepository, deletes: DeleteGuard, default_timezone: ClinicTimezone) -> Self {
+        Self { repo, deletes, default_timezone }
+    }
+
+    fn map_to_response(&self, organization: Organization) -> OrganizationResponse {
+        let timezone = ClinicTimezone::parse(&organization.timezone).unwrap_or_else(|_| self.default_timezone.clone());
+        OrganizationResponse {
+            id: organization.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: organization.name,
+            timezone: timezone.name().to_string(),
+            utc_offset: timezone.utc_offset(),
+            latitude: organization.location.as_ref().map(GeoPoint::latitude),
+            longitude: organization.location.as_ref().map(GeoPoint::longitude),
+            distributor_code: organization.distributor_code,
+            created_at: crate::datetime::to_rfc3339(organization.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(organization.updated_at),
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<OrganizationResponse>, (StatusCode, String)> {
+        match self.repo.find_all().await {
+            Ok(organizations) => Ok(organizations.into_iter().map(|o| self.map_to_response(o)).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<OrganizationResponse>, (StatusCode, String)> {
+        match self.repo.find_by_id(id).await {
+            Ok(organization) => Ok(organization.map(|o| self.map_to_response(o))),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreateOrganizationRequest) -> Result<OrganizationResponse, (StatusCode, String)> {
+        let timezone = match request.timezone.as_deref() {
+            Some(raw) => parse_timezone(raw)?,
+            None => self.default_timezone.clone(),
+        };
+        let organization = Organization {
+            id: None,
+            name: request.name.trim().to_string(),
+            timezone: timezone.name().to_string(),
+            location: geo::point(request.latitude, request.longitude).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+            distributor_code: request.distributor_code.map(|code| code.trim().to_string()),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.repo.create(organization).await {
+            Ok(created) => Ok(self.map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Changing the zone affects appointments booked afterwards; existing ones keep the zone
+    /// and instant they were booked with.
+    pub async fn update(&self, id: ObjectId, request: UpdateOrganizationRequest) -> Result<Option<OrganizationResponse>, (StatusCode, String)> {
+        let mut set = doc! { "updated_at": DateTime::now() };
+        if let Some(name) = request.name {
+            set.insert("name", name.trim());
+        }
+        if let Some(raw) = request.timezone {
+            set.insert("timezone", parse_timezone(&raw)?.name());
+        }
+        if let Some(location) = geo::point(request.latitude, request.longitude).map_err(|e| (StatusCode::BAD_REQUEST, e))? {
+            set.insert("location", &location);
+        }
+        if let Some(code) = request.distributor_code {
+            set.insert("distributor_code", code.trim());
+        }
+
+        match self.repo.update_fields(id, set).await {
+            Ok(organization) => Ok(organization.map(|o| self.map_to_response(o))),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        let mut session = self.deletes.begin().await?;
+        let deleted = async {
+            self.deletes.prepare(&mut session, "organizations", id).await?;
+            self.repo.delete(&mut session, id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        }.await;
+        crate::services::finish(session, deleted).await
+    }
+
+    /// Zone of an organization, or the default one without an organization. Unknown
+    /// organizations are a 400 so bookings are not silently made in the wrong zone.
+    pub async fn timezone_for(&self, organization_id: Option<&str>) -> Result<ClinicTimezone, (StatusCode, String)> {
+        let Some(raw_id) = organization_id else {
+            return Ok(self.default_timezone.clone());
+        };
+        let oid = ObjectId::parse_str(raw_id)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "Organization ID must be a valid MongoDB ObjectId".to_string()))?;
+        let organization = self.repo.find_by_id(oid).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Organization {} does not exist", raw_id)))?;
+        ClinicTimezone::parse(&organization.timezone).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+}
@@ -0,0 +1,108 @@
+use axum::http::StatusCode;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::Utc;
+
+use crate::models::OtpCode;
+use crate::otp::{self, OtpConfig, OtpProvider};
+use crate::repository::OtpRepository;
+
+/// Issues and checks one-time codes, enforcing the resend cooldown, the hourly limit and the
+/// per-code attempt counter kept in `otp_codes`.
+pub struct OtpService {
+    repo: OtpRepository,
+    config: OtpConfig,
+    provider: Box<dyn OtpProvider>,
+}
+
+fn message_for(purpose: &str, code: &str, ttl_seconds: i64) -> String {
+    let minutes = (ttl_seconds + 59) / 60;
+    match purpose {
+        otp::PURPOSE_PATIENT_LOGIN => format!("Your patient portal login code is {}. It expires in {} minutes.", code, minutes),
+        otp::PURPOSE_PATIENT_REGISTRATION => format!("Your patient registration code is {}. It expires in {} minutes.", code, minutes),
+        otp::PURPOSE_APPOINTMENT_BOOKING => format!("Your appointment booking code is {}. It expires in {} minutes.", code, minutes),
+        _ => format!("Your verification code is {}. It expires in {} minutes.", code, minutes),
+    }
+}
+
+impl OtpService {
+    pub fn new(repo: OtpRepository, config: &OtpConfig) -> Self {
+        // init_db refuses to start with a provider that cannot be built
+        let provider = config.provider().unwrap_or_else(|_| Box::new(otp::DisabledProvider));
+        Self { repo, config: config.clone(), provider }
+    }
+
+    /// Send a new code for `purpose` and `subject` to `phone`. Returns its lifetime in seconds.
+    pub async fn issue(&self, purpose: &str, subject: &str, phone: &str) -> Result<i64, (StatusCode, String)> {
+        let now = Utc::now();
+
+        if let Some(latest) = self.repo.find_latest(purpose, subject).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            let resend_at = crate::datetime::to_chrono(latest.created_at) + chrono::Duration::seconds(self.config.resend_cooldown_seconds);
+            if resend_at > now {
+                let wait = (resend_at - now).num_seconds().max(1);
+                return Err((StatusCode::TOO_MANY_REQUESTS, format!("Wait {} seconds before requesting another code", wait)));
+            }
+        }
+
+        let hour_ago = crate::datetime::from_chrono(now - chrono::Duration::hours(1));
+        let issued = self.repo.count_since(purpose, subject, hour_ago).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if issued >= self.config.max_per_hour {
+            return Err((StatusCode::TOO_MANY_REQUESTS, "Too many codes requested; try again later".to_string()));
+        }
+
+        let code = otp::generate_code();
+        let code_hash = hash(&code, DEFAULT_COST)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to hash code: {}", e)))?;
+        self.repo.create(OtpCode {
+            id: None,
+            purpose: purpose.to_string(),
+            subject: subject.to_string(),
+            code_hash,
+            attempts: 0,
+            expires_at: (now + chrono::Duration::seconds(self.config.ttl_seconds)).to_rfc3339(),
+            consumed_at: None,
+            created_at: crate::datetime::from_chrono(now),
+        }).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        self.provider
+            .send(phone, &message_for(purpose, &code, self.config.ttl_seconds))
+            .await
+            .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Failed to deliver code via {}: {}", self.provider.name(), e)))?;
+
+        Ok(self.config.ttl_seconds)
+    }
+
+    /// Accept `code` if it is the subject's latest, unused and unexpired code. Every guess is
+    /// counted against the code before it is compared; once `max_attempts` are used a new code
+    /// must be requested.
+    pub async fn verify(&self, purpose: &str, subject: &str, code: &str) -> Result<(), (StatusCode, String)> {
+        let invalid = || (StatusCode::UNAUTHORIZED, "Invalid or expired code".to_string());
+        let now = Utc::now().to_rfc3339();
+
+        let otp = self.repo.find_latest(purpose, subject).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or_else(invalid)?;
+        let otp_id = otp.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "OTP ID not found".to_string()))?;
+
+        if otp.consumed_at.is_some() || otp.expires_at < now {
+            return Err(invalid());
+        }
+        let Some(otp) = self.repo.claim_attempt(otp_id, self.config.max_attempts).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+        else {
+            return Err((StatusCode::TOO_MANY_REQUESTS, "Too many attempts; request a new code".to_string()));
+        };
+
+        let matches = verify(code, &otp.code_hash)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to verify code: {}", e)))?;
+        if !matches {
+            return Err(invalid());
+        }
+
+        if self.repo.consume(otp_id, &now).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Ok(())
+        } else {
+            Err(invalid())
+        }
+    }
+}
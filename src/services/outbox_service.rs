@@ -0,0 +1,75 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use crate::dto::outbox::{OutboxEntryResponse, OutboxQuery};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::OutboxRepository;
+use crate::status::OutboxStatus;
+
+pub struct OutboxService {
+    outbox: OutboxRepository,
+}
+
+fn outbox_filter(query: &OutboxQuery) -> Document {
+    let mut filter = Document::new();
+    if let Some(status) = &query.status {
+        filter.insert("status", status.clone());
+    }
+    if let Some(topic) = query.topic.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        filter.insert("topic", topic);
+    }
+    if query.stuck {
+        filter.insert("$or", vec![
+            doc! { "status": OutboxStatus::Failed },
+            doc! { "status": OutboxStatus::Pending, "attempts": { "$gt": 0 } },
+        ]);
+    }
+    filter
+}
+
+impl OutboxService {
+    pub fn new(outbox: OutboxRepository) -> Self {
+        Self { outbox }
+    }
+
+    pub async fn list(&self, query: OutboxQuery, pagination: PaginationParams) -> Result<(Vec<OutboxEntryResponse>, PaginationMeta), (StatusCode, String)> {
+        let (entries, total) = self.outbox.find_paginated(outbox_filter(&query), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let entries = entries.into_iter().map(OutboxEntryResponse::from).collect();
+        Ok((entries, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<OutboxEntryResponse, (StatusCode, String)> {
+        self.outbox.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .map(OutboxEntryResponse::from)
+            .ok_or((StatusCode::NOT_FOUND, "Outbox entry not found".to_string()))
+    }
+
+    /// Queue an undelivered entry for the relay's next round
+    pub async fn retry(&self, id: ObjectId) -> Result<OutboxEntryResponse, (StatusCode, String)> {
+        let existing = self.get(id).await?;
+        if existing.status == OutboxStatus::Published {
+            return Err((StatusCode::CONFLICT, "Outbox entry is already published".to_string()));
+        }
+        self.outbox.retry(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .map(OutboxEntryResponse::from)
+            .ok_or((StatusCode::CONFLICT, "Outbox entry was published meanwhile".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stuck_entries_are_failed_or_retried_pending_ones() {
+        let query = OutboxQuery { status: None, topic: Some(" payment.recorded ".to_string()), stuck: true };
+        let filter = outbox_filter(&query);
+        assert_eq!(filter.get_str("topic").unwrap(), "payment.recorded");
+        assert_eq!(filter.get_array("$or").unwrap().len(), 2);
+
+        let all = outbox_filter(&OutboxQuery { status: Some(OutboxStatus::Pending), ..Default::default() });
+        assert_eq!(all, doc! { "status": "pending" });
+    }
+}
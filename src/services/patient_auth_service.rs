@@ -0,0 +1,116 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+
+use crate::dto::auth::{
+    OtpRequest, OtpRequestResponse, OtpVerification, OtpVerifyRequest, PatientLoginRequest, PatientLoginResponse,
+    PatientOtpRequest, PhoneVerificationResponse,
+};
+use crate::otp::{mask_phone, PURPOSE_PATIENT_LOGIN, PURPOSE_PHONE_VERIFICATION};
+use crate::phone;
+use crate::repository::MedicalRecordRepository;
+use crate::services::{AuthService, OtpService};
+
+/// Patient portal sign-in and phone-number verification over one-time codes. Login codes go
+/// to the phone on the patient's medical record and are exchanged for a `patient` token
+/// scoped to that record.
+pub struct PatientAuthService {
+    records: MedicalRecordRepository,
+    otp: OtpService,
+}
+
+fn required<'a>(value: &'a Option<String>, name: &str, purpose: &str) -> Result<&'a str, (StatusCode, String)> {
+    value
+        .as_deref()
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("{} is required for {}", name, purpose)))
+}
+
+fn unknown_purpose(purpose: &str) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, format!(
+        "Unknown purpose '{}'; expected {} or {}",
+        purpose, PURPOSE_PATIENT_LOGIN, PURPOSE_PHONE_VERIFICATION
+    ))
+}
+
+impl PatientAuthService {
+    pub fn new(records: MedicalRecordRepository, otp: OtpService) -> Self {
+        Self { records, otp }
+    }
+
+    /// Send a login code to the patient with this NIK. Unknown NIKs get the same answer so
+    /// the endpoint cannot be used to probe for patients.
+    pub async fn request_login_code(&self, request: PatientOtpRequest) -> Result<OtpRequestResponse, (StatusCode, String)> {
+        let record = self.records.find_by_nik(&request.nik).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let expires_in = match record {
+            Some(record) => self.otp.issue(PURPOSE_PATIENT_LOGIN, &record.nik, &record.hp).await?,
+            None => 0,
+        };
+
+        Ok(OtpRequestResponse {
+            success: true,
+            message: "If the NIK is registered, a login code has been sent to its phone number".to_string(),
+            expires_in,
+        })
+    }
+
+    pub async fn login(&self, request: PatientLoginRequest) -> Result<PatientLoginResponse, (StatusCode, String)> {
+        self.otp.verify(PURPOSE_PATIENT_LOGIN, &request.nik, &request.otp).await?;
+
+        let record = self.records.find_by_nik(&request.nik).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::UNAUTHORIZED, "Invalid or expired code".to_string()))?;
+
+        let (access_token, expires_in) = AuthService::generate_patient_token(&record)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(PatientLoginResponse {
+            patient_id: record.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: record.name,
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+        })
+    }
+
+    pub async fn request_code(&self, request: OtpRequest) -> Result<OtpRequestResponse, (StatusCode, String)> {
+        match request.purpose.as_str() {
+            PURPOSE_PATIENT_LOGIN => {
+                let nik = required(&request.nik, "nik", PURPOSE_PATIENT_LOGIN)?;
+                self.request_login_code(PatientOtpRequest { nik: nik.to_string() }).await
+            }
+            PURPOSE_PHONE_VERIFICATION => {
+                let phone = phone::normalize(required(&request.phone, "phone", PURPOSE_PHONE_VERIFICATION)?)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+                let expires_in = self.otp.issue(PURPOSE_PHONE_VERIFICATION, &phone, &phone).await?;
+                Ok(OtpRequestResponse {
+                    success: true,
+                    message: format!("A verification code has been sent to {}", mask_phone(&phone)),
+                    expires_in,
+                })
+            }
+            other => Err(unknown_purpose(other)),
+        }
+    }
+
+    pub async fn verify_code(&self, request: OtpVerifyRequest) -> Result<OtpVerification, (StatusCode, String)> {
+        match request.purpose.as_str() {
+            PURPOSE_PATIENT_LOGIN => {
+                let nik = required(&request.nik, "nik", PURPOSE_PATIENT_LOGIN)?;
+                let login = PatientLoginRequest { nik: nik.to_string(), otp: request.code };
+                Ok(OtpVerification::PatientLogin(self.login(login).await?))
+            }
+            PURPOSE_PHONE_VERIFICATION => {
+                let phone = phone::normalize(required(&request.phone, "phone", PURPOSE_PHONE_VERIFICATION)?)
+                    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+                self.otp.verify(PURPOSE_PHONE_VERIFICATION, &phone, &request.code).await?;
+
+                let verified_at = Utc::now().to_rfc3339();
+                let records_updated = self.records.mark_phone_verified(&phone, &verified_at).await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                Ok(OtpVerification::Phone(PhoneVerificationResponse { phone, verified_at, records_updated }))
+            }
+            other => Err(unknown_purpose(other)),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::medical_record::{GuardianResponse, MedicalRecordResponse};
+use crate::dto::patient::{CreatePatientRelationshipRequest, PatientRelationshipResponse, UpdatePatientRelationshipRequest};
+use crate::middleware::AuthUser;
+use crate::models::{MedicalRecord, PatientRelationship};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{MedicalRecordRepository, PatientRelationshipRepository};
+use crate::status::RelationshipType;
+
+/// A link seen from one of its patients
+#[derive(Debug, PartialEq)]
+struct View {
+    related: Ref<MedicalRecord>,
+    relationship: RelationshipType,
+    /// The related patient acts for the viewing one
+    guardian: bool,
+    /// The viewing patient acts for the related one
+    guardian_of_related: bool,
+    inverse: bool,
+}
+
+fn view(relationship: &PatientRelationship, patient_id: &str) -> View {
+    if relationship.patient_id == *patient_id {
+        View {
+            related: relationship.related_patient_id,
+            relationship: relationship.relationship.clone(),
+            guardian: relationship.guardian,
+            guardian_of_related: false,
+            inverse: false,
+        }
+    } else {
+        View {
+            related: relationship.patient_id,
+            relationship: relationship.relationship.inverse(),
+            guardian: false,
+            guardian_of_related: relationship.guardian,
+            inverse: true,
+        }
+    }
+}
+
+pub struct PatientRelationshipService {
+    relationships: PatientRelationshipRepository,
+    records: MedicalRecordRepository,
+    references: ReferenceChecker,
+}
+
+impl PatientRelationshipService {
+    pub fn new(relationships: PatientRelationshipRepository, records: MedicalRecordRepository, references: ReferenceChecker) -> Self {
+        Self { relationships, records, references }
+    }
+
+    fn map_to_response(relationship: PatientRelationship, patient_id: &str, names: &HashMap<String, MedicalRecord>) -> PatientRelationshipResponse {
+        let seen = view(&relationship, patient_id);
+        let related_patient_id = seen.related.to_hex();
+        PatientRelationshipResponse {
+            id: relationship.id.map(|id| id.to_hex()).unwrap_or_default(),
+            patient_id: patient_id.to_string(),
+            related_patient_name: names.get(&related_patient_id).map(|r| r.name.clone()).unwrap_or_default(),
+            related_patient_id,
+            relationship: seen.relationship,
+            guardian: seen.guardian,
+            guardian_of_related: seen.guardian_of_related,
+            inverse: seen.inverse,
+            notes: relationship.notes,
+            recorded_by: relationship.recorded_by,
+            created_at: crate::datetime::to_rfc3339(relationship.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(relationship.updated_at),
+        }
+    }
+
+    /// The records of `ids`, keyed by hex ID
+    async fn records_by_id(&self, ids: Vec<ObjectId>) -> Result<HashMap<String, MedicalRecord>, (StatusCode, String)> {
+        let records = self.records.find_by_ids(&ids).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(records.into_iter().filter_map(|record| Some((record.id?.to_hex(), record))).collect())
+    }
+
+    pub async fn create(&self, patient_id: ObjectId, user: &AuthUser, request: CreatePatientRelationshipRequest) -> Result<PatientRelationshipResponse, (StatusCode, String)> {
+        let patient: Ref<MedicalRecord> = Ref::new(patient_id);
+        let related: Ref<MedicalRecord> = Ref::parse_field("related_patient_id", &request.related_patient_id)?;
+        if related == patient {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "related_patient_id: A patient cannot be related to themselves".to_string()));
+        }
+        self.references.ensure_exist(&[patient.check("patient_id"), related.check("related_patient_id")]).await?;
+
+        let existing = self.relationships.find_between(&patient.to_hex(), &related.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if let Some(existing) = existing {
+            let id = existing.id.map(|id| id.to_hex()).unwrap_or_default();
+            return Err((StatusCode::CONFLICT, format!("These patients are already linked by relationship {}", id)));
+        }
+
+        let relationship = PatientRelationship {
+            id: None,
+            patient_id: patient,
+            related_patient_id: related,
+            guardian: request.guardian.unwrap_or_else(|| request.relationship.guardian_by_default()),
+            relationship: request.relationship,
+            notes: request.notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            recorded_by: user.id.clone(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        let created = self.relationships.create(relationship).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let names = self.records_by_id(vec![related.id()]).await?;
+        Ok(Self::map_to_response(created, &patient.to_hex(), &names))
+    }
+
+    /// The patient's links in both directions, each seen from the patient
+    pub async fn list(&self, patient_id: ObjectId) -> Result<Vec<PatientRelationshipResponse>, (StatusCode, String)> {
+        let patient = patient_id.to_hex();
+        let relationships = self.relationships.find_by_patient(&patient).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let names = self.records_by_id(relationships.iter().map(|r| view(r, &patient).related.id()).collect()).await?;
+        Ok(relationships.into_iter().map(|r| Self::map_to_response(r, &patient, &names)).collect())
+    }
+
+    /// Change a link recorded for the patient; inverse links are changed from the other side
+    pub async fn update(&self, patient_id: ObjectId, id: ObjectId, request: UpdatePatientRelationshipRequest) -> Result<PatientRelationshipResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(relationship) = request.relationship { set.insert("relationship", relationship); }
+        if let Some(guardian) = request.guardian { set.insert("guardian", guardian); }
+        if let Some(notes) = request.notes { set.insert("notes", notes.trim()); }
+
+        let patient = patient_id.to_hex();
+        match self.relationships.update_fields(&patient, id, set).await {
+            Ok(Some(updated)) => {
+                let names = self.records_by_id(vec![updated.related_patient_id.id()]).await?;
+                Ok(Self::map_to_response(updated, &patient, &names))
+            }
+            Ok(None) => Err((StatusCode::NOT_FOUND, "Relationship not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn delete(&self, patient_id: ObjectId, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.relationships.delete(&patient_id.to_hex(), id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Fill `guardians` of each record, oldest link first
+    pub async fn expand_guardians(&self, records: &mut [MedicalRecordResponse]) -> Result<(), (StatusCode, String)> {
+        let ids: Vec<String> = records.iter().map(|r| r.id.clone()).collect();
+        let relationships = self.relationships.find_guardians(&ids).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let guardians = self.records_by_id(relationships.iter().map(|r| r.related_patient_id.id()).collect()).await?;
+
+        for record in records.iter_mut() {
+            let found = relationships
+                .iter()
+                .filter(|r| r.patient_id == record.id)
+                .filter_map(|r| {
+                    let guardian = guardians.get(&r.related_patient_id.to_hex())?;
+                    Some(GuardianResponse {
+                        relationship_id: r.id.map(|id| id.to_hex()).unwrap_or_default(),
+                        patient_id: r.related_patient_id.to_hex(),
+                        name: guardian.name.clone(),
+                        hp: guardian.hp.clone(),
+                        email: guardian.email.clone(),
+                        relationship: r.relationship.clone(),
+                    })
+                })
+                .collect();
+            record.guardians = Some(found);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_read_from_either_side() {
+        let (child, mother) = (ObjectId::new(), ObjectId::new());
+        let link = PatientRelationship {
+            id: Some(ObjectId::new()),
+            patient_id: Ref::new(child),
+            related_patient_id: Ref::new(mother),
+            relationship: RelationshipType::Parent,
+            guardian: true,
+            notes: None,
+            recorded_by: "u1".to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        let from_child = view(&link, &child.to_hex());
+        assert_eq!(from_child.related, Ref::new(mother));
+        assert_eq!(from_child.relationship, RelationshipType::Parent);
+        assert!(from_child.guardian && !from_child.guardian_of_related && !from_child.inverse);
+
+        let from_mother = view(&link, &mother.to_hex());
+        assert_eq!(from_mother.related, Ref::new(child));
+        assert_eq!(from_mother.relationship, RelationshipType::Child);
+        assert!(!from_mother.guardian && from_mother.guardian_of_related && from_mother.inverse);
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, HashSet};
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId};
+use mongodb::{ClientSession, Database};
+use crate::delete_policy::DeleteGuard;
+use crate::growth::{self, GrowthMetric, Sex};
+use crate::matching;
+use crate::phone;
+use crate::models::MedicalRecord;
+use crate::repository::{MedicalRecordRepository, AppointmentRepository, ObservationRepository, AllergyRepository, KitRepository, AppointmentSeriesRepository, WaitlistRepository, ReviewRepository, NoteRepository, AdmissionRepository, BedRepository, InvoiceRepository, PatientRelationshipRepository, CorporateClientRepository, FileRepository, ProgramRepository};
+use crate::services::{AuditService, MedicalRecordService};
+use crate::dto::medical_record::MedicalRecordResponse;
+use crate::dto::patient::{DuplicateGroupResponse, MergePatientResponse, GrowthPoint, GrowthReferencePoint, GrowthResponse};
+
+const DEFAULT_SIMILARITY_THRESHOLD: f64 = 0.85;
+const DEFAULT_GROUP_LIMIT: usize = 50;
+
+/// The collections holding a patient's ID, all repointed when the patient is merged into
+/// another. A collection keyed by patient belongs here, or merges orphan its documents.
+pub struct PatientReferences {
+    appointments: AppointmentRepository,
+    observations: ObservationRepository,
+    allergies: AllergyRepository,
+    kits: KitRepository,
+    series: AppointmentSeriesRepository,
+    waitlist: WaitlistRepository,
+    reviews: ReviewRepository,
+    notes: NoteRepository,
+    admissions: AdmissionRepository,
+    beds: BedRepository,
+    invoices: InvoiceRepository,
+    relationships: PatientRelationshipRepository,
+    rosters: CorporateClientRepository,
+    files: FileRepository,
+    enrollments: ProgramRepository,
+}
+
+impl PatientReferences {
+    pub fn new(db: Database) -> Self {
+        Self {
+            appointments: AppointmentRepository::new(db.clone()),
+            observations: ObservationRepository::new(db.clone()),
+            allergies: AllergyRepository::new(db.clone()),
+            kits: KitRepository::new(db.clone()),
+            series: AppointmentSeriesRepository::new(db.clone()),
+            waitlist: WaitlistRepository::new(db.clone()),
+            reviews: ReviewRepository::new(db.clone()),
+            notes: NoteRepository::new(db.clone()),
+            admissions: AdmissionRepository::new(db.clone()),
+            beds: BedRepository::new(db.clone()),
+            invoices: InvoiceRepository::new(db.clone()),
+            relationships: PatientRelationshipRepository::new(db.clone()),
+            rosters: CorporateClientRepository::new(db.clone()),
+            files: FileRepository::new(db.clone()),
+            enrollments: ProgramRepository::new(db),
+        }
+    }
+
+    /// Repoint everything referencing `from` to `to` within `session`'s transaction,
+    /// returning the number of documents moved per collection
+    async fn reassign(&self, session: &mut ClientSession, from: &str, to: &str) -> Result<Vec<(&'static str, u64)>, String> {
+        Ok(vec![
+            ("appointments", self.appointments.reassign_patient(session, from, to).await?),
+            ("observations", self.observations.reassign_patient(session, from, to).await?),
+            ("allergies", self.allergies.reassign_patient(session, from, to).await?),
+            ("kits", self.kits.reassign_patient(session, from, to).await?),
+            ("appointment_series", self.series.reassign_patient(session, from, to).await?),
+            ("waitlist", self.waitlist.reassign_patient(session, from, to).await?),
+            ("reviews", self.reviews.reassign_patient(session, from, to).await?),
+            ("notes", self.notes.reassign_patient(session, from, to).await?),
+            ("admissions", self.admissions.reassign_patient(session, from, to).await?),
+            ("beds", self.beds.reassign_patient(session, from, to).await?),
+            ("invoices", self.invoices.reassign_patient(session, from, to).await?),
+            ("patient_relationships", self.relationships.reassign_patient(session, from, to).await?),
+            ("corporate_employees", self.rosters.reassign_patient(session, from, to).await?),
+            ("files", self.files.reassign_patient(session, from, to).await?),
+            ("program_enrollments", self.enrollments.reassign_patient(session, from, to).await?),
+        ])
+    }
+}
+
+/// Patient-level operations spanning several collections.
+///
+/// Patients are stored in the `medical_records` collection.
+pub struct PatientService {
+    records: MedicalRecordRepository,
+    observations: ObservationRepository,
+    references: PatientReferences,
+    deletes: DeleteGuard,
+    audit: AuditService,
+}
+
+impl PatientService {
+    pub fn new(
+        records: MedicalRecordRepository,
+        observations: ObservationRepository,
+        references: PatientReferences,
+        deletes: DeleteGuard,
+        audit: AuditService,
+    ) -> Self {
+        Self { records, observations, references, deletes, audit }
+    }
+
+    /// Patients whose stored phone matches `raw` once normalized; families often share one.
+    pub async fn find_by_phone(&self, raw: &str) -> Result<Vec<MedicalRecordResponse>, (StatusCode, String)> {
+        let phone = phone::normalize(raw).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        match self.records.find_by_phone(&phone).await {
+            Ok(records) => Ok(records.into_iter().map(MedicalRecordService::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Find candidate duplicate groups: identical NIK, or same date of birth with similar normalized names.
+    pub async fn find_duplicates(&self, threshold: Option<f64>, limit: Option<usize>) -> Result<Vec<DuplicateGroupResponse>, (StatusCode, String)> {
+        let threshold = threshold.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD).clamp(0.0, 1.0);
+        let limit = limit.unwrap_or(DEFAULT_GROUP_LIMIT);
+
+        let mut groups = Vec::new();
+
+        let nik_groups = self.records.find_groups_by_field("nik").await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        for records in nik_groups {
+            groups.push(DuplicateGroupResponse {
+                reason: "nik".to_string(),
+                score: 1.0,
+                records: records.into_iter().map(MedicalRecordService::map_to_response).collect(),
+            });
+        }
+
+        let dob_buckets = self.records.find_groups_by_field("dob").await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        for bucket in dob_buckets {
+            for (score, records) in Self::cluster_by_name(bucket, threshold) {
+                // Groups made only of one NIK are already reported above
+                let niks: HashSet<&str> = records.iter().map(|r| r.nik.as_str()).collect();
+                if niks.len() < 2 {
+                    continue;
+                }
+                groups.push(DuplicateGroupResponse {
+                    reason: "name_dob".to_string(),
+                    score,
+                    records: records.into_iter().map(MedicalRecordService::map_to_response).collect(),
+                });
+            }
+        }
+
+        groups.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        groups.truncate(limit);
+
+        Ok(groups)
+    }
+
+    /// Greedily cluster records whose normalized names are at least `threshold` similar.
+    /// Returns each cluster of two or more records with its lowest pairwise score.
+    fn cluster_by_name(records: Vec<MedicalRecord>, threshold: f64) -> Vec<(f64, Vec<MedicalRecord>)> {
+        let mut remaining: Vec<Option<MedicalRecord>> = records.into_iter().map(Some).collect();
+        let mut clusters = Vec::new();
+
+        for i in 0..remaining.len() {
+            let Some(anchor) = remaining[i].take() else { continue };
+            let mut score = 1.0_f64;
+            let mut cluster = Vec::new();
+
+            for candidate in remaining.iter_mut().skip(i + 1) {
+                let matched = candidate
+                    .as_ref()
+                    .map(|c| matching::name_similarity(&anchor.name, &c.name))
+                    .filter(|s| *s >= threshold);
+                if let Some(s) = matched {
+                    score = score.min(s);
+                    cluster.extend(candidate.take());
+                }
+            }
+
+            if !cluster.is_empty() {
+                cluster.insert(0, anchor);
+                clusters.push((score, cluster));
+            }
+        }
+
+        clusters
+    }
+
+    /// Merge duplicate records into `primary_id`, repointing everything in `PatientReferences`,
+    /// filling blank contact fields from the duplicates, and deleting the duplicates under the
+    /// delete policies, in one transaction.
+    pub async fn merge(&self, primary_id: ObjectId, duplicate_ids: Vec<String>, actor: &str) -> Result<MergePatientResponse, (StatusCode, String)> {
+        let mut dup_oids = Vec::new();
+        for id in &duplicate_ids {
+            let oid = ObjectId::parse_str(id)
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid duplicate ID: {}", id)))?;
+            if oid == primary_id {
+                return Err((StatusCode::BAD_REQUEST, "A patient cannot be merged into itself".to_string()));
+            }
+            if !dup_oids.contains(&oid) {
+                dup_oids.push(oid);
+            }
+        }
+
+        let mut primary = self.records.find_by_id(primary_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Patient not found".to_string()))?;
+
+        let duplicates = self.records.find_by_ids(&dup_oids).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if duplicates.len() != dup_oids.len() {
+            return Err((StatusCode::NOT_FOUND, "One or more duplicate patients not found".to_string()));
+        }
+
+        // A patient is admitted to one bed at a time, and a merge must not leave two
+        let mut admitted = Vec::new();
+        for oid in std::iter::once(&primary_id).chain(&dup_oids) {
+            if self.references.admissions.find_active_by_patient(&oid.to_hex()).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+                .is_some()
+            {
+                admitted.push(oid.to_hex());
+            }
+        }
+        if admitted.len() > 1 {
+            return Err((StatusCode::CONFLICT, format!("Patients {} are all admitted; discharge all but one before merging", admitted.join(", "))));
+        }
+
+        let primary_hex = primary_id.to_hex();
+        let mut merged_ids = Vec::new();
+        let mut snapshots = Vec::new();
+        for duplicate in &duplicates {
+            if primary.hp.trim().is_empty() { primary.hp = duplicate.hp.clone(); }
+            if primary.email.trim().is_empty() { primary.email = duplicate.email.clone(); }
+            if duplicate.last_visit_date > primary.last_visit_date {
+                primary.last_visit_date = duplicate.last_visit_date.clone();
+            }
+
+            snapshots.push(mongodb::bson::to_bson(duplicate).unwrap_or(mongodb::bson::Bson::Null));
+            merged_ids.push(duplicate.id.map(|id| id.to_hex()).unwrap_or_default());
+        }
+
+        // Repointing, deleting and updating commit together, so a failure leaves no half merge.
+        // Whatever is left referencing a duplicate once repointed meets its delete policy.
+        let mut session = self.deletes.begin().await?;
+        let merged = async {
+            let mut repointed: BTreeMap<&'static str, u64> = BTreeMap::new();
+            for (dup_oid, dup_hex) in dup_oids.iter().zip(&merged_ids) {
+                let moved = self.references.reassign(&mut session, dup_hex, &primary_hex).await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                for (collection, count) in moved {
+                    *repointed.entry(collection).or_default() += count;
+                }
+                self.deletes.prepare(&mut session, "medical_records", *dup_oid).await?;
+                self.records.delete(&mut session, *dup_oid).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            }
+            let primary = self.records.merge_into(&mut session, primary_id, primary).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            Ok((primary, repointed))
+        }.await;
+        let (primary, repointed) = crate::services::finish(session, merged).await?;
+        let count = |collection: &str| repointed.get(collection).copied().unwrap_or(0);
+
+        self.audit.record(
+            "patient.merge",
+            "medical_record",
+            &primary_hex,
+            actor,
+            doc! {
+                "merged_ids": merged_ids.clone(),
+                "merged_records": snapshots,
+                "repointed": repointed.iter().map(|(collection, count)| (collection.to_string(), mongodb::bson::Bson::Int64(*count as i64))).collect::<mongodb::bson::Document>(),
+            },
+        ).await;
+
+        Ok(MergePatientResponse {
+            patient: MedicalRecordService::map_to_response(primary),
+            merged_ids,
+            appointments_repointed: count("appointments"),
+            observations_repointed: count("observations"),
+        })
+    }
+
+    /// WHO growth z-scores and percentiles for a patient's weight or height observations.
+    pub async fn growth(&self, patient_id: ObjectId, metric: GrowthMetric) -> Result<GrowthResponse, (StatusCode, String)> {
+        let patient = self.records.find_by_id(patient_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Patient not found".to_string()))?;
+
+        let patient_hex = patient_id.to_hex();
+        let observations = self.observations.find_by_patient_and_codings(&patient_hex, metric.coding_codes()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let sex = Sex::parse(patient.gender.as_str())
+            .or_else(|| observations.iter().rev().find_map(|o| Sex::parse(o.pasien.gender.as_str())))
+            .ok_or((StatusCode::UNPROCESSABLE_ENTITY, format!("Unrecognised patient gender '{}'", patient.gender)))?;
+
+        let series = observations
+            .into_iter()
+            .filter_map(|o| {
+                // Observations in units we cannot convert are left out of the chart
+                let value = metric.normalize(o.value, &o.unit.code)
+                    .or_else(|| metric.normalize(o.value, &o.unit.display))?;
+                let age_months = growth::age_in_months(o.pasien.usia.tahun, o.pasien.usia.bulan, o.pasien.usia.hari);
+                let z_score = growth::lms_at(metric, sex, age_months).map(|lms| growth::z_score(metric, &lms, value));
+                Some(GrowthPoint {
+                    time: o.time,
+                    age_months,
+                    value,
+                    z_score,
+                    percentile: z_score.map(growth::percentile),
+                })
+            })
+            .collect();
+
+        let reference = growth::reference_table(metric, sex)
+            .iter()
+            .map(|lms| GrowthReferencePoint {
+                age_months: lms.month,
+                sd_neg3: growth::value_at(lms, -3.0),
+                sd_neg2: growth::value_at(lms, -2.0),
+                median: lms.m,
+                sd2: growth::value_at(lms, 2.0),
+                sd3: growth::value_at(lms, 3.0),
+            })
+            .collect();
+
+        Ok(GrowthResponse {
+            patient_id: patient_hex,
+            metric: metric.as_str().to_string(),
+            sex: sex.as_str().to_string(),
+            unit: metric.unit().to_string(),
+            series,
+            reference,
+        })
+    }
+}
@@ -0,0 +1,446 @@
+use axum::http::StatusCode;
+use chrono::{NaiveDate, Utc};
+use mongodb::{bson::{doc, oid::ObjectId, DateTime}, ClientSession};
+use crate::dto::payment::{
+    CashierSettlement, CategoryRevenue, CloseShiftRequest, MethodTotal, OpenShiftRequest, PaymentResponse, PeriodRevenue,
+    RecordPaymentRequest, RevenueStats, RevenueStatsQuery, ServiceUtilization, ServiceUtilizationStats, SettlementQuery,
+    SettlementReport, ShiftQuery, ShiftResponse, UtilizationStatsQuery,
+};
+use crate::models::{CashierShift, Invoice, Payment};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::invoice::{CategoryRevenueRow, ServiceUtilizationRow};
+use crate::repository::payment::{RevenuePeriodRow, SettlementRow};
+use crate::repository::{CashierShiftRepository, InvoiceRepository, OutboxRepository, PaymentRepository};
+use crate::services::finish;
+use crate::services::price_list_service::round_money;
+use crate::status::{InvoiceStatus, PaymentMethod, RevenuePeriod, ShiftStatus};
+use crate::timezone::ClinicTimezone;
+
+pub struct PaymentService {
+    invoices: InvoiceRepository,
+    payments: PaymentRepository,
+    shifts: CashierShiftRepository,
+    outbox: OutboxRepository,
+    timezone: ClinicTimezone,
+}
+
+/// Status of an invoice of `total` once `paid` has been received
+fn invoice_status(total: f64, paid: f64) -> InvoiceStatus {
+    if paid >= total {
+        InvoiceStatus::Paid
+    } else if paid > 0.0 {
+        InvoiceStatus::PartiallyPaid
+    } else {
+        InvoiceStatus::Unpaid
+    }
+}
+
+/// Count and sum per method, in the order methods first appear
+fn method_totals<'a>(payments: impl IntoIterator<Item = (&'a PaymentMethod, i64, f64)>) -> Vec<MethodTotal> {
+    let mut totals: Vec<MethodTotal> = Vec::new();
+    for (method, count, amount) in payments {
+        match totals.iter_mut().find(|total| total.method == *method) {
+            Some(total) => {
+                total.count += count;
+                total.amount = round_money(total.amount + amount);
+            }
+            None => totals.push(MethodTotal { method: method.clone(), count, amount: round_money(amount) }),
+        }
+    }
+    totals
+}
+
+/// Cash the drawer should hold: the opening float plus cash payments
+fn expected_cash(opening_cash: f64, payments: &[Payment]) -> f64 {
+    let cash: f64 = payments.iter().filter(|p| p.method == PaymentMethod::Cash).map(|p| p.amount).sum();
+    round_money(opening_cash + cash)
+}
+
+fn settlement_report(date: String, organization_id: Option<String>, rows: &[SettlementRow]) -> SettlementReport {
+    let mut by_cashier: Vec<CashierSettlement> = Vec::new();
+    for row in rows {
+        if !by_cashier.iter().any(|c| c.cashier_id == row.cashier_id) {
+            let cashier_rows = rows.iter().filter(|r| r.cashier_id == row.cashier_id);
+            let by_method = method_totals(cashier_rows.map(|r| (&r.method, r.count, r.amount)));
+            by_cashier.push(CashierSettlement {
+                cashier_id: row.cashier_id.clone(),
+                count: by_method.iter().map(|m| m.count).sum(),
+                amount: round_money(by_method.iter().map(|m| m.amount).sum()),
+                by_method,
+            });
+        }
+    }
+    let by_method = method_totals(rows.iter().map(|r| (&r.method, r.count, r.amount)));
+
+    SettlementReport {
+        date,
+        organization_id,
+        count: by_method.iter().map(|m| m.count).sum(),
+        total: round_money(by_method.iter().map(|m| m.amount).sum()),
+        by_method,
+        by_cashier,
+    }
+}
+
+fn revenue_stats(from: String, to: String, group_by: RevenuePeriod, organization_id: Option<String>, periods: &[RevenuePeriodRow], categories: Vec<CategoryRevenueRow>) -> RevenueStats {
+    let mut by_period: Vec<PeriodRevenue> = Vec::new();
+    for row in periods {
+        if !by_period.iter().any(|p| p.period == row.period) {
+            let by_method = method_totals(periods.iter().filter(|r| r.period == row.period).map(|r| (&r.method, r.count, r.amount)));
+            by_period.push(PeriodRevenue {
+                period: row.period.clone(),
+                count: by_method.iter().map(|m| m.count).sum(),
+                amount: round_money(by_method.iter().map(|m| m.amount).sum()),
+                by_method,
+            });
+        }
+    }
+    let by_method = method_totals(periods.iter().map(|r| (&r.method, r.count, r.amount)));
+
+    RevenueStats {
+        from,
+        to,
+        group_by,
+        organization_id,
+        count: by_method.iter().map(|m| m.count).sum(),
+        total: round_money(by_method.iter().map(|m| m.amount).sum()),
+        by_period,
+        by_method,
+        by_category: categories.into_iter().map(|row| CategoryRevenue {
+            item_type: row.item_type,
+            category: row.category,
+            quantity: row.quantity,
+            amount: round_money(row.amount),
+        }).collect(),
+    }
+}
+
+fn service_utilization(row: ServiceUtilizationRow) -> ServiceUtilization {
+    ServiceUtilization {
+        service_id: row.service_id,
+        name: row.name,
+        category: row.category,
+        invoices: row.invoices,
+        quantity: row.quantity,
+        amount: round_money(row.amount),
+    }
+}
+
+impl PaymentService {
+    pub fn new(invoices: InvoiceRepository, payments: PaymentRepository, shifts: CashierShiftRepository, outbox: OutboxRepository, timezone: ClinicTimezone) -> Self {
+        Self { invoices, payments, shifts, outbox, timezone }
+    }
+
+    fn map_payment(payment: Payment) -> PaymentResponse {
+        PaymentResponse {
+            id: payment.id.map(|id| id.to_hex()).unwrap_or_default(),
+            invoice_id: payment.invoice_id,
+            organization_id: payment.organization_id,
+            method: payment.method,
+            amount: payment.amount,
+            reference: payment.reference,
+            cashier_id: payment.cashier_id,
+            shift_id: payment.shift_id,
+            paid_at: crate::datetime::to_rfc3339(payment.paid_at),
+            settlement_date: payment.settlement_date,
+        }
+    }
+
+    fn map_shift(shift: CashierShift, payments: &[Payment]) -> ShiftResponse {
+        ShiftResponse {
+            id: shift.id.map(|id| id.to_hex()).unwrap_or_default(),
+            cashier_id: shift.cashier_id,
+            status: shift.status,
+            opening_cash: shift.opening_cash,
+            opened_at: crate::datetime::to_rfc3339(shift.opened_at),
+            closed_at: crate::datetime::to_rfc3339_opt(shift.closed_at),
+            expected_cash: shift.expected_cash.unwrap_or_else(|| expected_cash(shift.opening_cash, payments)),
+            counted_cash: shift.counted_cash,
+            difference: shift.difference,
+            note: shift.note,
+            payments: method_totals(payments.iter().map(|p| (&p.method, 1, p.amount))),
+        }
+    }
+
+    async fn shift_response(&self, shift: CashierShift) -> Result<ShiftResponse, (StatusCode, String)> {
+        let shift_id = shift.id.map(|id| id.to_hex()).unwrap_or_default();
+        let payments = self.payments.find_by_shift(&shift_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(Self::map_shift(shift, &payments))
+    }
+
+    /// Record a payment in the cashier's open shift and move the invoice to partially paid
+    /// or paid. Amounts above the outstanding balance are refused.
+    pub async fn record(&self, invoice_id: ObjectId, request: RecordPaymentRequest, cashier_id: &str) -> Result<PaymentResponse, (StatusCode, String)> {
+        if request.method == PaymentMethod::Gateway {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "method: gateway payments are recorded by the gateway callback".to_string()));
+        }
+        let shift = self.shifts.find_open(cashier_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Open a cashier shift before recording payments".to_string()))?;
+        let invoice = self.payable_invoice(invoice_id).await?;
+
+        let amount = round_money(request.amount);
+        let outstanding = round_money(invoice.total - invoice.paid_amount);
+        if amount > outstanding {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("amount: exceeds the outstanding balance of {:.2}", outstanding)));
+        }
+
+        let reference = request.reference.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+        let mut session = self.begin().await?;
+        let recorded = self.apply(&mut session, invoice, request.method, amount, reference, cashier_id, shift.id.map(|id| id.to_hex())).await;
+        finish(session, recorded).await
+    }
+
+    /// Record what a gateway callback reports as paid, up to the outstanding balance. The
+    /// cashier is `gateway:<provider>`, outside any shift. Runs in `session`'s transaction
+    /// (see `begin`), which the caller commits.
+    pub async fn record_gateway(&self, session: &mut ClientSession, invoice_id: ObjectId, method: PaymentMethod, amount: f64, reference: Option<String>, provider: &str) -> Result<PaymentResponse, (StatusCode, String)> {
+        let invoice = self.payable_invoice(invoice_id).await?;
+        let outstanding = round_money(invoice.total - invoice.paid_amount);
+        let amount = round_money(amount).min(outstanding);
+        if amount <= 0.0 {
+            return Err((StatusCode::CONFLICT, "Invoice has no outstanding balance".to_string()));
+        }
+        self.apply(session, invoice, method, amount, reference, &format!("gateway:{}", provider), None).await
+    }
+
+    /// A transaction for `apply` and the writes that must commit with it
+    pub(crate) async fn begin(&self) -> Result<ClientSession, (StatusCode, String)> {
+        self.outbox.begin().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    pub(crate) async fn payable_invoice(&self, invoice_id: ObjectId) -> Result<Invoice, (StatusCode, String)> {
+        let invoice = self.invoices.find_by_id(invoice_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Invoice not found".to_string()))?;
+        if !invoice.status.is_payable() {
+            return Err((StatusCode::CONFLICT, format!("Invoice is {}", invoice.status)));
+        }
+        Ok(invoice)
+    }
+
+    /// Count `amount` against the invoice and store the payment with its outbox entry, all in
+    /// `session`'s transaction: aborting it undoes every write.
+    #[allow(clippy::too_many_arguments)]
+    async fn apply(&self, session: &mut ClientSession, invoice: Invoice, method: PaymentMethod, amount: f64, reference: Option<String>, cashier_id: &str, shift_id: Option<String>) -> Result<PaymentResponse, (StatusCode, String)> {
+        let invoice_id = invoice.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Invoice without an ID".to_string()))?;
+        let paid = round_money(invoice.paid_amount + amount);
+        self.invoices.apply_payment(session, invoice_id, invoice.paid_amount, paid, invoice_status(invoice.total, paid)).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Invoice changed concurrently; retry".to_string()))?;
+
+        let payment = Payment {
+            id: None,
+            invoice_id: invoice_id.to_hex(),
+            organization_id: invoice.organization_id.to_hex(),
+            method,
+            amount,
+            reference,
+            cashier_id: cashier_id.to_string(),
+            shift_id,
+            paid_at: DateTime::now(),
+            settlement_date: self.timezone.today(Utc::now()),
+        };
+        let announce = |stored: &Payment| {
+            let payload = mongodb::bson::to_document(&Self::map_payment(stored.clone())).unwrap_or_default();
+            crate::outbox::entry(crate::outbox::PAYMENT_RECORDED, "payments", stored.id.unwrap_or_default(), payload)
+        };
+        match self.payments.create_with_outbox(session, payment, &self.outbox, announce).await {
+            Ok(created) => Ok(Self::map_payment(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list_for_invoice(&self, invoice_id: ObjectId) -> Result<Vec<PaymentResponse>, (StatusCode, String)> {
+        match self.payments.find_by_invoice(&invoice_id.to_hex()).await {
+            Ok(payments) => Ok(payments.into_iter().map(Self::map_payment).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// A cashier works one shift at a time
+    pub async fn open_shift(&self, cashier_id: &str, request: OpenShiftRequest) -> Result<ShiftResponse, (StatusCode, String)> {
+        let open = self.shifts.find_open(cashier_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if open.is_some() {
+            return Err((StatusCode::CONFLICT, "A shift is already open for this cashier".to_string()));
+        }
+
+        let shift = CashierShift {
+            id: None,
+            cashier_id: cashier_id.to_string(),
+            status: ShiftStatus::Open,
+            opening_cash: round_money(request.opening_cash),
+            opened_at: DateTime::now(),
+            closed_at: None,
+            expected_cash: None,
+            counted_cash: None,
+            difference: None,
+            note: None,
+        };
+        match self.shifts.create(shift).await {
+            Ok(created) => Ok(Self::map_shift(created, &[])),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Close the shift, storing the expected and counted cash and their difference
+    pub async fn close_shift(&self, id: ObjectId, request: CloseShiftRequest) -> Result<ShiftResponse, (StatusCode, String)> {
+        let shift = self.shifts.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Shift not found".to_string()))?;
+        if shift.status != ShiftStatus::Open {
+            return Err((StatusCode::CONFLICT, "Shift is already closed".to_string()));
+        }
+
+        let payments = self.payments.find_by_shift(&id.to_hex()).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let expected = expected_cash(shift.opening_cash, &payments);
+        let counted = round_money(request.counted_cash);
+        let mut set = doc! {
+            "closedAt": DateTime::now(),
+            "expectedCash": expected,
+            "countedCash": counted,
+            "difference": round_money(counted - expected),
+        };
+        if let Some(note) = request.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+            set.insert("note", note);
+        }
+
+        let closed = self.shifts.close(id, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Shift is already closed".to_string()))?;
+        Ok(Self::map_shift(closed, &payments))
+    }
+
+    pub async fn get_shift(&self, id: ObjectId) -> Result<Option<ShiftResponse>, (StatusCode, String)> {
+        match self.shifts.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(shift) => self.shift_response(shift).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn list_shifts(&self, query: ShiftQuery, pagination: PaginationParams) -> Result<(Vec<ShiftResponse>, PaginationMeta), (StatusCode, String)> {
+        let (shifts, total) = self.shifts.find_paginated(query.cashier_id.as_deref(), query.status.as_ref(), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let mut responses = Vec::with_capacity(shifts.len());
+        for shift in shifts {
+            responses.push(self.shift_response(shift).await?);
+        }
+        Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    /// Payments settling on a day, per method and per cashier
+    pub async fn settlement(&self, query: SettlementQuery) -> Result<SettlementReport, (StatusCode, String)> {
+        let date = query.date.map(|d| d.trim().to_string()).unwrap_or_else(|| self.timezone.today(Utc::now()));
+        let rows = self.payments.settlement(&date, query.organization_id.as_deref()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(settlement_report(date, query.organization_id, &rows))
+    }
+
+    /// `from` and `to` of a statistics query; the 30 days up to today in the clinic timezone by default
+    fn stats_range(&self, from: Option<String>, to: Option<String>) -> Result<(String, String), (StatusCode, String)> {
+        let to = to.unwrap_or_else(|| self.timezone.today(Utc::now()));
+        let from = match from {
+            Some(from) => from,
+            None => NaiveDate::parse_from_str(&to, "%Y-%m-%d")
+                .map(|to| (to - chrono::Duration::days(29)).format("%Y-%m-%d").to_string())
+                .map_err(|_| (StatusCode::BAD_REQUEST, "to: Date must be YYYY-MM-DD".to_string()))?,
+        };
+        if from > to {
+            return Err((StatusCode::BAD_REQUEST, "from must not be after to".to_string()));
+        }
+        Ok((from, to))
+    }
+
+    /// Payments per period and method, and billed lines per service category, of
+    /// `organizations` (`None` is all of them)
+    pub async fn revenue_stats(&self, query: RevenueStatsQuery, organizations: Option<Vec<String>>) -> Result<RevenueStats, (StatusCode, String)> {
+        let (from, to) = self.stats_range(query.from, query.to)?;
+        let group_by = query.group_by.unwrap_or(RevenuePeriod::Day);
+        let (periods, categories) = tokio::join!(
+            self.payments.revenue_by_period(&group_by, &from, &to, organizations.as_deref()),
+            self.invoices.revenue_by_category(&from, &to, organizations.as_deref()),
+        );
+        let periods = periods.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let categories = categories.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(revenue_stats(from, to, group_by, query.organization_id, &periods, categories))
+    }
+
+    /// Services billed on invoices served in the period, most used first
+    pub async fn service_utilization(&self, query: UtilizationStatsQuery, organizations: Option<Vec<String>>) -> Result<ServiceUtilizationStats, (StatusCode, String)> {
+        let (from, to) = self.stats_range(query.from, query.to)?;
+        let rows = self.invoices.service_utilization(&from, &to, organizations.as_deref()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(ServiceUtilizationStats {
+            from,
+            to,
+            organization_id: query.organization_id,
+            services: rows.into_iter().map(service_utilization).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payment(method: PaymentMethod, amount: f64) -> Payment {
+        Payment {
+            id: None,
+            invoice_id: "i1".to_string(),
+            organization_id: "o1".to_string(),
+            method,
+            amount,
+            reference: None,
+            cashier_id: "u1".to_string(),
+            shift_id: Some("s1".to_string()),
+            paid_at: DateTime::now(),
+            settlement_date: "2026-03-10".to_string(),
+        }
+    }
+
+    #[test]
+    fn invoice_status_follows_the_paid_amount() {
+        assert_eq!(invoice_status(100.0, 0.0), InvoiceStatus::Unpaid);
+        assert_eq!(invoice_status(100.0, 40.0), InvoiceStatus::PartiallyPaid);
+        assert_eq!(invoice_status(100.0, 100.0), InvoiceStatus::Paid);
+    }
+
+    #[test]
+    fn only_cash_counts_towards_the_drawer() {
+        let payments = [payment(PaymentMethod::Cash, 50_000.0), payment(PaymentMethod::Qris, 20_000.0), payment(PaymentMethod::Cash, 12_500.0)];
+        assert_eq!(expected_cash(100_000.0, &payments), 162_500.0);
+    }
+
+    #[test]
+    fn settlement_totals_by_method_and_cashier() {
+        let row = |cashier: &str, method: PaymentMethod, count: i64, amount: f64| SettlementRow { cashier_id: cashier.to_string(), method, count, amount };
+        let rows = [
+            row("u1", PaymentMethod::Cash, 2, 75_000.0),
+            row("u1", PaymentMethod::Qris, 1, 20_000.0),
+            row("u2", PaymentMethod::Cash, 1, 10_000.0),
+        ];
+        let report = settlement_report("2026-03-10".to_string(), None, &rows);
+        assert_eq!((report.count, report.total), (4, 105_000.0));
+        assert_eq!(report.by_method[0], MethodTotal { method: PaymentMethod::Cash, count: 3, amount: 85_000.0 });
+        assert_eq!(report.by_cashier.len(), 2);
+        assert_eq!((report.by_cashier[0].count, report.by_cashier[0].amount), (3, 95_000.0));
+    }
+
+    #[test]
+    fn revenue_stats_roll_methods_up_per_period() {
+        let row = |period: &str, method: PaymentMethod, count: i64, amount: f64| RevenuePeriodRow { period: period.to_string(), method, count, amount };
+        let periods = [
+            row("2026-03", PaymentMethod::Cash, 3, 150_000.0),
+            row("2026-03", PaymentMethod::Qris, 1, 25_000.0),
+            row("2026-04", PaymentMethod::Cash, 2, 40_000.0),
+        ];
+        let stats = revenue_stats("2026-03-01".to_string(), "2026-04-30".to_string(), RevenuePeriod::Month, None, &periods, Vec::new());
+
+        assert_eq!(stats.count, 6);
+        assert_eq!(stats.total, 215_000.0);
+        assert_eq!(stats.by_period.len(), 2);
+        assert_eq!(stats.by_period[0].amount, 175_000.0);
+        assert_eq!(stats.by_period[0].by_method.len(), 2);
+        assert_eq!(stats.by_method[0], MethodTotal { method: PaymentMethod::Cash, count: 5, amount: 190_000.0 });
+    }
+}
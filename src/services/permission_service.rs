@@ -0,0 +1,182 @@
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::permission::{
+    CreatePermissionRequest, GrantPermissionRequest, PermissionResponse, RolePermissionResponse, UpdatePermissionRequest,
+};
+use crate::models::{Permission, RolePermission};
+use crate::rbac::ANY;
+use crate::repository::{PermissionRepository, RolePermissionRepository};
+
+pub struct PermissionService {
+    permissions: PermissionRepository,
+    grants: RolePermissionRepository,
+}
+
+fn normalize(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
+impl PermissionService {
+    pub fn new(permissions: PermissionRepository, grants: RolePermissionRepository) -> Self {
+        Self { permissions, grants }
+    }
+
+    fn map_to_response(permission: Permission) -> PermissionResponse {
+        PermissionResponse {
+            id: permission.id.map(|id| id.to_hex()).unwrap_or_default(),
+            resource: permission.resource,
+            action: permission.action,
+            description: permission.description,
+            created_at: crate::datetime::to_rfc3339(permission.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(permission.updated_at),
+        }
+    }
+
+    fn map_grant_to_response(grant: RolePermission) -> RolePermissionResponse {
+        RolePermissionResponse {
+            id: grant.id.map(|id| id.to_hex()).unwrap_or_default(),
+            role_code: grant.role_code,
+            resource: grant.resource,
+            action: grant.action,
+            created_at: crate::datetime::to_rfc3339(grant.created_at),
+        }
+    }
+
+    async fn ensure_unique(&self, resource: &str, action: &str, except: Option<ObjectId>) -> Result<(), (StatusCode, String)> {
+        let existing = self.permissions.find_by_key(resource, action).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        match existing {
+            Some(p) if except.is_none() || p.id != except => {
+                Err((StatusCode::CONFLICT, format!("Permission {}:{} already exists", resource, action)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<PermissionResponse>, (StatusCode, String)> {
+        match self.permissions.find_all().await {
+            Ok(permissions) => Ok(permissions.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<PermissionResponse>, (StatusCode, String)> {
+        match self.permissions.find_by_id(id).await {
+            Ok(permission) => Ok(permission.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreatePermissionRequest) -> Result<PermissionResponse, (StatusCode, String)> {
+        let resource = normalize(&request.resource);
+        let action = normalize(&request.action);
+        if resource == ANY || action == ANY {
+            return Err((StatusCode::BAD_REQUEST, "'*' is a wildcard and cannot name a permission".to_string()));
+        }
+        self.ensure_unique(&resource, &action, None).await?;
+
+        let permission = Permission {
+            id: None,
+            resource,
+            action,
+            description: request.description,
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.permissions.create(permission).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Update a permission. Renaming it moves its role grants along.
+    pub async fn update(&self, id: ObjectId, request: UpdatePermissionRequest) -> Result<Option<PermissionResponse>, (StatusCode, String)> {
+        let Some(current) = self.permissions.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? else {
+            return Ok(None);
+        };
+
+        let resource = request.resource.as_deref().map(normalize).unwrap_or_else(|| current.resource.clone());
+        let action = request.action.as_deref().map(normalize).unwrap_or_else(|| current.action.clone());
+        if resource == ANY || action == ANY {
+            return Err((StatusCode::BAD_REQUEST, "'*' is a wildcard and cannot name a permission".to_string()));
+        }
+        let renamed = resource != current.resource || action != current.action;
+        if renamed {
+            self.ensure_unique(&resource, &action, Some(id)).await?;
+        }
+
+        let mut set = doc! { "resource": &resource, "action": &action, "updated_at": DateTime::now() };
+        if let Some(description) = request.description {
+            set.insert("description", description);
+        }
+        let updated = self.permissions.update_fields(id, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        if renamed {
+            self.grants.rename_permission((&current.resource, &current.action), (&resource, &action)).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        }
+        Ok(updated.map(Self::map_to_response))
+    }
+
+    /// Delete a permission and revoke it from every role.
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        match self.permissions.delete(id).await {
+            Ok(Some(permission)) => {
+                self.grants.delete_for_permission(&permission.resource, &permission.action).await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list_grants(&self, role_code: Option<&str>) -> Result<Vec<RolePermissionResponse>, (StatusCode, String)> {
+        let codes = role_code.map(|code| vec![code.to_string()]);
+        match self.grants.find_for_roles(codes.as_deref()).await {
+            Ok(grants) => Ok(grants.into_iter().map(Self::map_grant_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Grant a permission to a role. Wildcard grants need no permission document; any other
+    /// resource and action must name an existing permission.
+    pub async fn grant(&self, request: GrantPermissionRequest) -> Result<RolePermissionResponse, (StatusCode, String)> {
+        let role_code = request.role_code.trim().to_string();
+        let resource = normalize(&request.resource);
+        let action = normalize(&request.action);
+
+        if resource != ANY && action != ANY {
+            let exists = self.permissions.find_by_key(&resource, &action).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            if exists.is_none() {
+                return Err((StatusCode::NOT_FOUND, format!("Permission {}:{} not found", resource, action)));
+            }
+        }
+
+        let existing = self.grants.find_one(&role_code, &resource, &action).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if existing.is_some() {
+            return Err((StatusCode::CONFLICT, format!("Role {} already has {}:{}", role_code, resource, action)));
+        }
+
+        let grant = RolePermission {
+            id: None,
+            role_code,
+            resource,
+            action,
+            created_at: DateTime::now(),
+        };
+
+        match self.grants.create(grant).await {
+            Ok(created) => Ok(Self::map_grant_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn revoke(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.grants.delete(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+}
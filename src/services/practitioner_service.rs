@@ -0,0 +1,216 @@
+use crate::delete_policy::DeleteGuard;
+use crate::models::{Practitioner, Qualification, SIP};
+use crate::repository::PractitionerRepository;
+use crate::pagination::{PaginationParams, PaginationMeta};
+use crate::dto::practitioner::{CreatePractitionerRequest, PractitionerResponse, QualificationDto, UpdatePractitionerRequest};
+use crate::status::{DoctorStatus, PractitionerType};
+use mongodb::bson::{doc, oid::ObjectId};
+use axum::http::StatusCode;
+
+pub struct PractitionerService {
+    repository: PractitionerRepository,
+    deletes: DeleteGuard,
+}
+
+/// Doctors must carry what the `/doctors` view requires
+fn check_complete(practitioner: &Practitioner) -> Result<(), (StatusCode, String)> {
+    if practitioner.practitioner_type != PractitionerType::Doctor {
+        return Ok(());
+    }
+    if practitioner.specialization.as_deref().is_none_or(str::is_empty) {
+        return Err((StatusCode::BAD_REQUEST, "Doctors need a specialization".to_string()));
+    }
+    if practitioner.qualification(SIP).is_none_or(str::is_empty) {
+        return Err((StatusCode::BAD_REQUEST, format!("Doctors need a {} qualification", SIP)));
+    }
+    Ok(())
+}
+
+fn to_qualification(dto: QualificationDto) -> Qualification {
+    Qualification { code: dto.code, number: dto.number, issuer: dto.issuer, valid_until: dto.valid_until }
+}
+
+impl PractitionerService {
+    pub fn new(repository: PractitionerRepository, deletes: DeleteGuard) -> Self {
+        Self { repository, deletes }
+    }
+
+    pub(crate) fn map_to_response(practitioner: Practitioner) -> PractitionerResponse {
+        PractitionerResponse {
+            id: practitioner.id.map(|id| id.to_hex()).unwrap_or_default(),
+            practitioner_type: practitioner.practitioner_type,
+            name: practitioner.name,
+            nip: practitioner.nip,
+            status: practitioner.status,
+            specialization: practitioner.specialization,
+            qualifications: practitioner.qualifications.into_iter()
+                .map(|q| QualificationDto { code: q.code, number: q.number, issuer: q.issuer, valid_until: q.valid_until })
+                .collect(),
+            rating_average: practitioner.rating_average,
+            rating_count: practitioner.rating_count,
+        }
+    }
+
+    pub async fn get_all_paginated(
+        &self,
+        pagination: PaginationParams,
+        practitioner_type: Option<&PractitionerType>,
+        status: Option<&DoctorStatus>,
+    ) -> Result<(Vec<PractitionerResponse>, PaginationMeta), (StatusCode, String)> {
+        let mut filter = doc! {};
+        if let Some(practitioner_type) = practitioner_type {
+            filter.insert("type", practitioner_type.as_str());
+        }
+        if let Some(status) = status {
+            filter.insert("status", status.as_str());
+        }
+
+        match self.repository.find_paginated(filter, &pagination).await {
+            Ok((practitioners, total)) => {
+                let responses = practitioners.into_iter().map(Self::map_to_response).collect();
+                let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
+                Ok((responses, meta))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreatePractitionerRequest) -> Result<PractitionerResponse, (StatusCode, String)> {
+        let practitioner = Practitioner {
+            id: Some(ObjectId::new()),
+            practitioner_type: request.practitioner_type,
+            name: request.name,
+            nip: request.nip,
+            status: request.status,
+            specialization: request.specialization,
+            qualifications: request.qualifications.into_iter().map(to_qualification).collect(),
+            rating_average: None,
+            rating_count: 0,
+        };
+        check_complete(&practitioner)?;
+
+        match self.repository.insert(practitioner).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get_by_id(&self, id: ObjectId) -> Result<Option<PractitionerResponse>, (StatusCode, String)> {
+        match self.repository.find_by_id(id, None).await {
+            Ok(practitioner) => Ok(practitioner.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdatePractitionerRequest) -> Result<PractitionerResponse, (StatusCode, String)> {
+        let mut practitioner = self.repository.find_by_id(id, None).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Practitioner not found".to_string()))?;
+
+        if let Some(name) = request.name {
+            practitioner.name = name;
+        }
+        if let Some(nip) = request.nip {
+            practitioner.nip = nip;
+        }
+        if let Some(status) = request.status {
+            practitioner.status = status;
+        }
+        if let Some(specialization) = request.specialization {
+            practitioner.specialization = Some(specialization);
+        }
+        if let Some(qualifications) = request.qualifications {
+            practitioner.qualifications = qualifications.into_iter().map(to_qualification).collect();
+        }
+        check_complete(&practitioner)?;
+
+        match self.repository.replace(id, practitioner).await {
+            Ok(updated) => Ok(Self::map_to_response(updated)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Type of the deleted practitioner, `None` when there was none. Doctors follow the
+    /// delete policies of `/doctors`.
+    pub async fn delete(&self, id: ObjectId) -> Result<Option<PractitionerType>, (StatusCode, String)> {
+        let Some(practitioner) = self.repository.find_by_id(id, None).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? else {
+            return Ok(None);
+        };
+
+        let mut session = self.deletes.begin().await?;
+        let deleted = async {
+            if practitioner.practitioner_type == PractitionerType::Doctor {
+                self.deletes.prepare(&mut session, "doctors", id).await?;
+            }
+            self.repository.delete_with_session(&mut session, id, Some(&practitioner.practitioner_type)).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        }.await;
+        match crate::services::finish(session, deleted).await? {
+            true => Ok(Some(practitioner.practitioner_type)),
+            false => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Doctor, Nurse};
+
+    fn doctor() -> Doctor {
+        Doctor {
+            id: Some(ObjectId::new()),
+            name: "dr. Sari".to_string(),
+            nip: "1987".to_string(),
+            sip: "SIP-001".to_string(),
+            specialization: "Cardiology".to_string(),
+            status: DoctorStatus::Active,
+            rating_average: Some(4.5),
+            rating_count: 2,
+        }
+    }
+
+    #[test]
+    fn doctor_view_round_trips_and_keeps_other_qualifications() {
+        let original = doctor();
+        let mut practitioner = Practitioner::from(original.clone());
+        assert_eq!(practitioner.practitioner_type, PractitionerType::Doctor);
+        assert_eq!(practitioner.qualification(SIP), Some("SIP-001"));
+
+        practitioner.qualifications.push(Qualification { code: "STR".to_string(), number: "STR-9".to_string(), issuer: None, valid_until: None });
+        practitioner.apply_doctor(Doctor { sip: "SIP-002".to_string(), ..original.clone() });
+        assert_eq!(practitioner.qualification(SIP), Some("SIP-002"));
+        assert_eq!(practitioner.qualification("STR"), Some("STR-9"));
+
+        let view = Doctor::from(practitioner);
+        assert_eq!(view.sip, "SIP-002");
+        assert_eq!(view.specialization, original.specialization);
+        assert_eq!(view.rating_count, 2);
+    }
+
+    #[test]
+    fn nurse_view_round_trips() {
+        let nurse = Nurse { id: Some(ObjectId::new()), name: "Rina".to_string(), nip: "2001".to_string(), status: "on_leave".to_string() };
+        let practitioner = Practitioner::from(nurse.clone());
+        assert_eq!(practitioner.practitioner_type, PractitionerType::Nurse);
+        assert_eq!(practitioner.status, DoctorStatus::OnLeave);
+        assert!(check_complete(&practitioner).is_ok());
+
+        let view = Nurse::from(practitioner);
+        assert_eq!((view.id, view.name, view.status), (nurse.id, nurse.name, nurse.status));
+    }
+
+    #[test]
+    fn doctors_need_specialization_and_sip() {
+        let mut practitioner = Practitioner::from(doctor());
+        assert!(check_complete(&practitioner).is_ok());
+
+        practitioner.qualifications.clear();
+        assert_eq!(check_complete(&practitioner).unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        practitioner.set_qualification(SIP, "SIP-003".to_string());
+        practitioner.specialization = None;
+        assert!(check_complete(&practitioner).is_err());
+    }
+}
@@ -0,0 +1,281 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::price_list::{
+    CreatePriceListRequest, PriceListItemDto, PriceListItemResponse, PriceListQuery, PriceListResponse, QuoteItemDto, QuoteLine,
+    QuoteRequest, QuoteResponse, UpdatePriceListRequest,
+};
+use crate::models::{Medicine, Organization, PriceList, PriceListItem, Service};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, RefCheck, ReferenceChecker};
+use crate::repository::PriceListRepository;
+use crate::status::{PriceItemType, PriceListStatus};
+use crate::timezone::ClinicTimezone;
+
+pub struct PriceListService {
+    price_lists: PriceListRepository,
+    references: ReferenceChecker,
+    timezone: ClinicTimezone,
+}
+
+/// Price each item from `list`; 422 naming the first item the list has no price for.
+fn quote_lines(list: &PriceList, items: &[QuoteItemDto]) -> Result<(Vec<QuoteLine>, f64), (StatusCode, String)> {
+    let mut lines = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let unit_price = list.price_of(&item.item_type, &item.item_id).ok_or_else(|| (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("items[{}]: {} {} has no price in price list version {}", i, item.item_type, item.item_id, list.version),
+        ))?;
+        lines.push(QuoteLine {
+            item_type: item.item_type.clone(),
+            item_id: item.item_id.clone(),
+            quantity: item.quantity,
+            unit_price,
+            amount: round_money(unit_price * item.quantity),
+        });
+    }
+    let total = round_money(lines.iter().map(|line| line.amount).sum());
+    Ok((lines, total))
+}
+
+pub(crate) fn round_money(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+impl PriceListService {
+    pub fn new(price_lists: PriceListRepository, references: ReferenceChecker, timezone: ClinicTimezone) -> Self {
+        Self { price_lists, references, timezone }
+    }
+
+    fn map_to_response(list: PriceList) -> PriceListResponse {
+        PriceListResponse {
+            id: list.id.map(|id| id.to_hex()).unwrap_or_default(),
+            organization_id: list.organization_id.to_hex(),
+            version: list.version,
+            name: list.name,
+            effective_from: list.effective_from,
+            status: list.status,
+            items: list.items.into_iter().map(|item| PriceListItemResponse {
+                item_type: item.item_type,
+                item_id: item.item_id,
+                price: item.price,
+            }).collect(),
+            created_by: list.created_by,
+            created_at: crate::datetime::to_rfc3339(list.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(list.updated_at),
+            published_by: list.published_by,
+            published_at: crate::datetime::to_rfc3339_opt(list.published_at),
+        }
+    }
+
+    /// The items as stored, after checking every priced service and medicine exists
+    async fn checked_items(&self, items: Vec<PriceListItemDto>) -> Result<Vec<PriceListItem>, (StatusCode, String)> {
+        let mut checks: Vec<RefCheck> = Vec::with_capacity(items.len());
+        for item in &items {
+            let check = match item.item_type {
+                PriceItemType::Service => Ref::<Service>::parse_field("items.item_id", &item.item_id)?.check("items.item_id"),
+                PriceItemType::Medicine => Ref::<Medicine>::parse_field("items.item_id", &item.item_id)?.check("items.item_id"),
+                PriceItemType::Other(_) => continue,
+            };
+            checks.push(check);
+        }
+        self.references.ensure_exist(&checks).await?;
+
+        Ok(items.into_iter().map(|item| PriceListItem {
+            item_type: item.item_type,
+            item_id: item.item_id.trim().to_string(),
+            price: item.price,
+        }).collect())
+    }
+
+    /// Create the organization's next version as a draft
+    pub async fn create(&self, request: CreatePriceListRequest, created_by: &str) -> Result<PriceListResponse, (StatusCode, String)> {
+        let organization = Ref::<Organization>::parse_field("organization_id", &request.organization_id)?;
+        self.references.ensure_exist(&[organization.check("organization_id")]).await?;
+
+        let items = match (request.items, request.based_on) {
+            (Some(items), _) => self.checked_items(items).await?,
+            (None, Some(based_on)) => {
+                let base = ObjectId::parse_str(based_on.trim()).ok();
+                let base = match base {
+                    Some(id) => self.price_lists.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?,
+                    None => None,
+                };
+                match base {
+                    Some(base) if base.organization_id == organization => base.items,
+                    _ => return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("based_on: '{}' is not a price list of this organization", based_on))),
+                }
+            }
+            (None, None) => Vec::new(),
+        };
+
+        let version = self.price_lists.latest_version(&organization.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? + 1;
+        let list = PriceList {
+            id: None,
+            organization_id: organization,
+            version,
+            name: request.name.trim().to_string(),
+            effective_from: request.effective_from.trim().to_string(),
+            status: PriceListStatus::Draft,
+            items,
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+            published_by: None,
+            published_at: None,
+        };
+
+        match self.price_lists.create(list).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            // `price_lists_version` is unique per organization
+            Err(e) if e.contains("E11000") => Err((StatusCode::CONFLICT, "Another version was created at the same time; retry".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, query: PriceListQuery, pagination: PaginationParams) -> Result<(Vec<PriceListResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.price_lists.find_paginated(query.organization_id.as_deref(), query.status.as_ref(), &pagination).await {
+            Ok((lists, total)) => {
+                let responses = lists.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<PriceListResponse>, (StatusCode, String)> {
+        match self.price_lists.find_by_id(id).await {
+            Ok(list) => Ok(list.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// 404 for a missing list, 409 for a published one
+    async fn draft_missing(&self, id: ObjectId) -> (StatusCode, String) {
+        match self.price_lists.find_by_id(id).await {
+            Ok(Some(_)) => (StatusCode::CONFLICT, "Published price lists cannot be changed; create a new version".to_string()),
+            Ok(None) => (StatusCode::NOT_FOUND, "Price list not found".to_string()),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdatePriceListRequest) -> Result<PriceListResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(name) = request.name { set.insert("name", name.trim()); }
+        if let Some(effective_from) = request.effective_from { set.insert("effectiveFrom", effective_from.trim()); }
+        if let Some(items) = request.items {
+            let items = self.checked_items(items).await?;
+            set.insert("items", mongodb::bson::to_bson(&items).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?);
+        }
+
+        match self.price_lists.update_draft(id, set).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(list) => Ok(Self::map_to_response(list)),
+            None => Err(self.draft_missing(id).await),
+        }
+    }
+
+    /// Freeze a draft. From its `effectiveFrom` on it replaces earlier versions.
+    pub async fn publish(&self, id: ObjectId, published_by: &str) -> Result<PriceListResponse, (StatusCode, String)> {
+        let list = self.price_lists.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Price list not found".to_string()))?;
+        if list.items.is_empty() {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "items: a price list needs at least one item to be published".to_string()));
+        }
+
+        let set = doc! {
+            "status": PriceListStatus::Published,
+            "publishedBy": published_by,
+            "publishedAt": DateTime::now(),
+        };
+        match self.price_lists.update_draft(id, set).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(list) => Ok(Self::map_to_response(list)),
+            None => Err(self.draft_missing(id).await),
+        }
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        if self.price_lists.delete_draft(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Ok(true);
+        }
+        match self.draft_missing(id).await {
+            (StatusCode::NOT_FOUND, _) => Ok(false),
+            error => Err(error),
+        }
+    }
+
+    fn date_or_today(&self, date: Option<&str>) -> String {
+        date.map(|d| d.trim().to_string()).unwrap_or_else(|| self.timezone.today(Utc::now()))
+    }
+
+    /// The published list in effect for the organization on `date`
+    pub async fn active(&self, organization_id: &str, date: Option<&str>) -> Result<PriceList, (StatusCode, String)> {
+        let date = self.date_or_today(date);
+        self.price_lists.find_active(organization_id.trim(), &date).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, format!("No published price list is in effect on {}", date)))
+    }
+
+    pub async fn active_response(&self, organization_id: &str, date: Option<&str>) -> Result<PriceListResponse, (StatusCode, String)> {
+        self.active(organization_id, date).await.map(Self::map_to_response)
+    }
+
+    /// Prices as billing charges them: from the list in effect on the day of service, so
+    /// later price changes leave earlier services untouched.
+    pub async fn quote(&self, request: QuoteRequest) -> Result<QuoteResponse, (StatusCode, String)> {
+        let date = self.date_or_today(request.date.as_deref());
+        let list = self.active(&request.organization_id, Some(&date)).await?;
+        let (lines, total) = quote_lines(&list, &request.items)?;
+        Ok(QuoteResponse {
+            price_list_id: list.id.map(|id| id.to_hex()).unwrap_or_default(),
+            price_list_version: list.version,
+            date,
+            lines,
+            total,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(item_type: PriceItemType, item_id: &str, price: f64) -> PriceListItem {
+        PriceListItem { item_type, item_id: item_id.to_string(), price }
+    }
+
+    fn quote_item(item_type: PriceItemType, item_id: &str, quantity: f64) -> QuoteItemDto {
+        QuoteItemDto { item_type, item_id: item_id.to_string(), quantity }
+    }
+
+    #[test]
+    fn quotes_use_the_list_prices() {
+        let list = PriceList {
+            id: None,
+            organization_id: Ref::new(ObjectId::new()),
+            version: 3,
+            name: "2026".to_string(),
+            effective_from: "2026-01-01".to_string(),
+            status: PriceListStatus::Published,
+            items: vec![item(PriceItemType::Service, "a", 150_000.0), item(PriceItemType::Medicine, "a", 2_500.5)],
+            created_by: "u1".to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+            published_by: None,
+            published_at: None,
+        };
+
+        let (lines, total) = quote_lines(&list, &[
+            quote_item(PriceItemType::Service, "a", 1.0),
+            quote_item(PriceItemType::Medicine, "a", 3.0),
+        ]).unwrap();
+        assert_eq!(lines[1].unit_price, 2_500.5);
+        assert_eq!(lines[1].amount, 7_501.5);
+        assert_eq!(total, 157_501.5);
+
+        let (status, message) = quote_lines(&list, &[quote_item(PriceItemType::Medicine, "b", 1.0)]).unwrap_err();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(message.starts_with("items[0]:"));
+    }
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use axum::http::StatusCode;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::program::{
+    CohortPatient, CohortReading, CohortReportResponse, CreateProgramRequest, EnrollPatientRequest, EnrollmentQuery, EnrollmentResponse,
+    ProgramQuery, ProgramResponse, UpdateProgramRequest, WithdrawEnrollmentRequest,
+};
+use crate::models::{MedicalRecord, Observation, ObservationThreshold, Program, ProgramEligibility, ProgramEnrollment};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::programs::{self, ControlStatus, Eligibility};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::repository::{ObservationRepository, ProgramRepository};
+use crate::status::{ProgramEnrollmentStatus, ProgramStatus};
+
+pub struct ProgramService {
+    programs: ProgramRepository,
+    observations: ObservationRepository,
+    references: ReferenceChecker,
+}
+
+fn internal(e: String) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e)
+}
+
+fn latest_values<'a>(observations: &[&'a Observation]) -> HashMap<&'a str, f64> {
+    observations.iter().map(|observation| (observation.coding.code.as_str(), observation.value)).collect()
+}
+
+fn readings(targets: &[ObservationThreshold], observations: &[&Observation]) -> Vec<CohortReading> {
+    targets.iter()
+        .filter_map(|target| {
+            let observation = observations.iter().find(|observation| observation.coding.code == target.code)?;
+            Some(CohortReading {
+                code: target.code.clone(),
+                value: observation.value,
+                unit: observation.unit.display.clone(),
+                observed_at: crate::datetime::to_rfc3339(DateTime::from_millis(observation.time.saturating_mul(1000))),
+                within_target: target.contains(observation.value),
+            })
+        })
+        .collect()
+}
+
+fn diagnosis_codes(codes: &[String]) -> Vec<String> {
+    codes.iter().map(|code| code.trim().to_uppercase()).filter(|code| !code.is_empty()).collect()
+}
+
+impl ProgramService {
+    pub fn new(programs: ProgramRepository, observations: ObservationRepository, references: ReferenceChecker) -> Self {
+        Self { programs, observations, references }
+    }
+
+    fn map_to_response(program: Program) -> ProgramResponse {
+        ProgramResponse {
+            id: program.id.map(|id| id.to_hex()).unwrap_or_default(),
+            code: program.code,
+            name: program.name,
+            description: program.description,
+            eligibility: program.eligibility.into(),
+            control_targets: program.control_targets.into_iter().map(Into::into).collect(),
+            status: program.status,
+            created_by: program.created_by,
+            created_at: crate::datetime::to_rfc3339(program.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(program.updated_at),
+        }
+    }
+
+    fn map_enrollment(enrollment: ProgramEnrollment) -> EnrollmentResponse {
+        EnrollmentResponse {
+            id: enrollment.id.map(|id| id.to_hex()).unwrap_or_default(),
+            program_id: enrollment.program_id,
+            medical_record_id: enrollment.patient_id.to_hex(),
+            diagnosis_codes: enrollment.diagnosis_codes,
+            eligible_by: enrollment.eligible_by,
+            status: enrollment.status,
+            enrolled_by: enrollment.enrolled_by,
+            enrolled_at: crate::datetime::to_rfc3339(enrollment.enrolled_at),
+            withdrawn_by: enrollment.withdrawn_by,
+            withdrawn_at: crate::datetime::to_rfc3339_opt(enrollment.withdrawn_at),
+            withdrawal_reason: enrollment.withdrawal_reason,
+        }
+    }
+
+    async fn program(&self, id: ObjectId) -> Result<Program, (StatusCode, String)> {
+        self.programs.find_by_id(id).await
+            .map_err(internal)?
+            .ok_or((StatusCode::NOT_FOUND, "Program not found".to_string()))
+    }
+
+    pub async fn create(&self, request: CreateProgramRequest, created_by: &str) -> Result<ProgramResponse, (StatusCode, String)> {
+        let program = Program {
+            id: None,
+            code: request.code.trim().to_string(),
+            name: request.name.trim().to_string(),
+            description: request.description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty()),
+            eligibility: request.eligibility.into(),
+            control_targets: request.control_targets.into_iter().map(Into::into).collect(),
+            status: request.status.unwrap_or(ProgramStatus::Active),
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.programs.create(program).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            // `programs_code` is unique
+            Err(e) if e.contains("E11000") => Err((StatusCode::CONFLICT, "Another program has this code".to_string())),
+            Err(e) => Err(internal(e)),
+        }
+    }
+
+    pub async fn list(&self, query: ProgramQuery, pagination: PaginationParams) -> Result<(Vec<ProgramResponse>, PaginationMeta), (StatusCode, String)> {
+        let (programs, total) = self.programs.find_paginated(query.status.as_ref(), &pagination).await.map_err(internal)?;
+        let responses = programs.into_iter().map(Self::map_to_response).collect();
+        Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<ProgramResponse>, (StatusCode, String)> {
+        self.programs.find_by_id(id).await
+            .map(|program| program.map(Self::map_to_response))
+            .map_err(internal)
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdateProgramRequest) -> Result<ProgramResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(name) = request.name { set.insert("name", name.trim()); }
+        if let Some(description) = request.description { set.insert("description", description.trim()); }
+        if let Some(status) = request.status { set.insert("status", status); }
+        if let Some(eligibility) = request.eligibility {
+            let eligibility: ProgramEligibility = eligibility.into();
+            set.insert("eligibility", mongodb::bson::to_bson(&eligibility).map_err(|e| internal(e.to_string()))?);
+        }
+        if let Some(targets) = request.control_targets {
+            let targets: Vec<ObservationThreshold> = targets.into_iter().map(Into::into).collect();
+            set.insert("controlTargets", mongodb::bson::to_bson(&targets).map_err(|e| internal(e.to_string()))?);
+        }
+
+        self.programs.update_fields(id, set).await
+            .map_err(internal)?
+            .map(Self::map_to_response)
+            .ok_or((StatusCode::NOT_FOUND, "Program not found".to_string()))
+    }
+
+    /// Programs anyone was enrolled in stay for the record; deactivate them instead
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        if self.programs.any_enrollment(&id.to_hex()).await.map_err(internal)? {
+            return Err((StatusCode::CONFLICT, "Patients were enrolled in this program; set its status to inactive instead".to_string()));
+        }
+        self.programs.delete(id).await.map_err(internal)
+    }
+
+    /// Whether the patient may enroll, by the given diagnoses and the latest observations
+    pub async fn eligibility(&self, program_id: ObjectId, request: &EnrollPatientRequest) -> Result<Eligibility, (StatusCode, String)> {
+        let program = self.program(program_id).await?;
+        let patient = Ref::<MedicalRecord>::parse_field("medical_record_id", &request.medical_record_id)?;
+        self.references.ensure_exist(&[patient.check("medical_record_id")]).await?;
+        self.check(&program, patient, &diagnosis_codes(&request.diagnosis_codes)).await
+    }
+
+    async fn check(&self, program: &Program, patient: Ref<MedicalRecord>, diagnoses: &[String]) -> Result<Eligibility, (StatusCode, String)> {
+        let codes: Vec<&str> = program.eligibility.observations.iter().map(|threshold| threshold.code.as_str()).collect();
+        let observations = self.observations.find_latest_per_coding(&[patient.to_hex()], &codes).await.map_err(internal)?;
+        let latest = latest_values(&observations.iter().collect::<Vec<_>>());
+        Ok(programs::eligibility(&program.eligibility, diagnoses, &latest))
+    }
+
+    pub async fn enroll(&self, program_id: ObjectId, request: EnrollPatientRequest, enrolled_by: &str) -> Result<EnrollmentResponse, (StatusCode, String)> {
+        let program = self.program(program_id).await?;
+        if program.status != ProgramStatus::Active {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "The program is inactive".to_string()));
+        }
+        let patient = Ref::<MedicalRecord>::parse_field("medical_record_id", &request.medical_record_id)?;
+        self.references.ensure_exist(&[patient.check("medical_record_id")]).await?;
+
+        let program_hex = program_id.to_hex();
+        if self.programs.find_active_enrollment(&program_hex, &patient.to_hex()).await.map_err(internal)?.is_some() {
+            return Err((StatusCode::CONFLICT, "The patient is already enrolled in this program".to_string()));
+        }
+
+        let diagnoses = diagnosis_codes(&request.diagnosis_codes);
+        let eligibility = self.check(&program, patient, &diagnoses).await?;
+        if !eligibility.eligible {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "The patient meets none of the program's eligibility rules".to_string()));
+        }
+
+        let enrollment = ProgramEnrollment {
+            id: None,
+            program_id: program_hex,
+            patient_id: patient,
+            diagnosis_codes: diagnoses,
+            eligible_by: eligibility.met,
+            status: ProgramEnrollmentStatus::Active,
+            enrolled_by: enrolled_by.to_string(),
+            enrolled_at: DateTime::now(),
+            withdrawn_by: None,
+            withdrawn_at: None,
+            withdrawal_reason: None,
+        };
+
+        match self.programs.create_enrollment(enrollment).await {
+            Ok(created) => Ok(Self::map_enrollment(created)),
+            // `program_enrollments_active` is unique per program and patient
+            Err(e) if e.contains("E11000") => Err((StatusCode::CONFLICT, "The patient is already enrolled in this program".to_string())),
+            Err(e) => Err(internal(e)),
+        }
+    }
+
+    pub async fn enrollments(&self, program_id: ObjectId, query: EnrollmentQuery) -> Result<Vec<EnrollmentResponse>, (StatusCode, String)> {
+        self.program(program_id).await?;
+        let enrollments = self.programs.find_enrollments(&program_id.to_hex(), query.status.as_ref()).await.map_err(internal)?;
+        Ok(enrollments.into_iter().map(Self::map_enrollment).collect())
+    }
+
+    pub async fn withdraw(&self, program_id: ObjectId, id: ObjectId, request: WithdrawEnrollmentRequest, withdrawn_by: &str) -> Result<EnrollmentResponse, (StatusCode, String)> {
+        let program_hex = program_id.to_hex();
+        let enrollment = self.programs.find_enrollment(&program_hex, id).await
+            .map_err(internal)?
+            .ok_or((StatusCode::NOT_FOUND, "Enrollment not found".to_string()))?;
+        if enrollment.status != ProgramEnrollmentStatus::Active {
+            return Err((StatusCode::CONFLICT, "The enrollment was already withdrawn".to_string()));
+        }
+
+        let mut set = doc! {
+            "status": ProgramEnrollmentStatus::Withdrawn,
+            "withdrawnBy": withdrawn_by,
+            "withdrawnAt": DateTime::now(),
+        };
+        if let Some(reason) = request.reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty()) {
+            set.insert("withdrawalReason", reason);
+        }
+
+        self.programs.update_active_enrollment(&program_hex, id, set).await
+            .map_err(internal)?
+            .map(Self::map_enrollment)
+            .ok_or((StatusCode::CONFLICT, "Enrollment changed concurrently".to_string()))
+    }
+
+    /// Active enrollments by whether the latest observations meet the control targets
+    pub async fn cohort(&self, program_id: ObjectId) -> Result<CohortReportResponse, (StatusCode, String)> {
+        let program = self.program(program_id).await?;
+        let program_hex = program_id.to_hex();
+        let enrollments = self.programs.find_enrollments(&program_hex, Some(&ProgramEnrollmentStatus::Active)).await.map_err(internal)?;
+
+        let patient_ids: Vec<String> = enrollments.iter().map(|enrollment| enrollment.patient_id.to_hex()).collect();
+        let codes: Vec<&str> = program.control_targets.iter().map(|target| target.code.as_str()).collect();
+        let observations = self.observations.find_latest_per_coding(&patient_ids, &codes).await.map_err(internal)?;
+
+        let patients: Vec<CohortPatient> = enrollments.into_iter()
+            .map(|enrollment| {
+                let patient = enrollment.patient_id.to_hex();
+                let own: Vec<&Observation> = observations.iter().filter(|observation| observation.id_pasien == *patient).collect();
+                CohortPatient {
+                    enrollment_id: enrollment.id.map(|id| id.to_hex()).unwrap_or_default(),
+                    medical_record_id: patient,
+                    control: programs::control(&program.control_targets, &latest_values(&own)),
+                    readings: readings(&program.control_targets, &own),
+                }
+            })
+            .collect();
+
+        let count = |status: ControlStatus| patients.iter().filter(|patient| patient.control == status).count();
+        let (controlled, uncontrolled, no_data) = (count(ControlStatus::Controlled), count(ControlStatus::Uncontrolled), count(ControlStatus::NoData));
+        let with_data = controlled + uncontrolled;
+        let controlled_percent = (with_data > 0).then(|| (controlled as f64 * 1000.0 / with_data as f64).round() / 10.0);
+
+        Ok(CohortReportResponse {
+            program_id: program_hex,
+            code: program.code,
+            name: program.name,
+            enrolled: patients.len(),
+            controlled,
+            uncontrolled,
+            no_data,
+            controlled_percent,
+            patients,
+        })
+    }
+}
@@ -0,0 +1,148 @@
+use axum::http::StatusCode;
+use mongodb::bson::oid::ObjectId;
+use crate::captcha::CaptchaVerifier;
+use crate::dto::appointment::{
+    AvailabilityQuery, AvailabilityResponse, CreateAppointmentRequest, DoctorSummary, PublicBookingRequest,
+    PublicBookingResponse, PublicSlotsQuery,
+};
+use crate::dto::auth::OtpRequestResponse;
+use crate::models::Doctor;
+use crate::otp::{mask_phone, PURPOSE_APPOINTMENT_BOOKING};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::phone;
+use crate::repository::{DoctorRepository, MedicalRecordRepository};
+use crate::services::{AppointmentService, OtpService};
+use crate::status::{AppointmentStatus, DoctorStatus};
+use crate::validation;
+
+/// Booking by patients without an account: active doctors, their free slots, and
+/// appointments that wait in `pending_confirmation` until staff confirm them.
+///
+/// Requesting a code costs an SMS, so it needs a captcha token; booking then needs the code,
+/// which proves the caller holds the phone on the patient's record.
+pub struct PublicBookingService {
+    doctors: DoctorRepository,
+    records: MedicalRecordRepository,
+    appointments: AppointmentService,
+    otp: OtpService,
+    captcha: Box<dyn CaptchaVerifier>,
+}
+
+/// Bookings either send a code or create an appointment
+pub enum PublicBookingOutcome {
+    CodeSent(OtpRequestResponse),
+    Booked(PublicBookingResponse),
+}
+
+fn summary(doctor: Doctor) -> DoctorSummary {
+    DoctorSummary {
+        id: doctor.id.map(|id| id.to_hex()).unwrap_or_default(),
+        name: doctor.name,
+        specialization: doctor.specialization,
+    }
+}
+
+impl PublicBookingService {
+    pub fn new(
+        doctors: DoctorRepository,
+        records: MedicalRecordRepository,
+        appointments: AppointmentService,
+        otp: OtpService,
+        captcha: Box<dyn CaptchaVerifier>,
+    ) -> Self {
+        Self { doctors, records, appointments, otp, captcha }
+    }
+
+    pub async fn doctors(&self, pagination: PaginationParams) -> Result<(Vec<DoctorSummary>, PaginationMeta), (StatusCode, String)> {
+        let (doctors, total) = self.doctors.find_all_paginated(pagination.clone(), Some(&DoctorStatus::Active)).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
+        Ok((doctors.into_iter().map(summary).collect(), meta))
+    }
+
+    /// 404 for doctors that are missing or not active, so inactive ones cannot be booked
+    async fn active_doctor(&self, id: ObjectId) -> Result<Doctor, (StatusCode, String)> {
+        self.doctors.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .filter(|doctor| doctor.status == DoctorStatus::Active)
+            .ok_or((StatusCode::NOT_FOUND, "Doctor not found".to_string()))
+    }
+
+    pub async fn slots(&self, doctor_id: ObjectId, query: PublicSlotsQuery) -> Result<AvailabilityResponse, (StatusCode, String)> {
+        self.active_doctor(doctor_id).await?;
+        self.appointments.availability(AvailabilityQuery {
+            doctor_id: doctor_id.to_hex(),
+            date: query.date,
+            organization_id: query.organization_id,
+        }).await
+    }
+
+    /// 422 when `time` is not one of the day's slots, 409 when it is taken or past
+    async fn ensure_bookable(&self, request: &PublicBookingRequest) -> Result<(), (StatusCode, String)> {
+        let availability = self.appointments.availability(AvailabilityQuery {
+            doctor_id: request.doctor_id.clone(),
+            date: request.date.clone(),
+            organization_id: request.organization_id.clone(),
+        }).await?;
+        match availability.slots.iter().find(|slot| slot.time == request.time) {
+            Some(slot) if slot.available => Ok(()),
+            Some(_) => Err((StatusCode::CONFLICT, format!("The slot on {} at {} is no longer available", request.date, request.time))),
+            None => Err((StatusCode::UNPROCESSABLE_ENTITY, format!("time: {} is not one of the doctor's slots", request.time))),
+        }
+    }
+
+    pub async fn book(&self, request: PublicBookingRequest, remote_ip: Option<&str>) -> Result<PublicBookingOutcome, (StatusCode, String)> {
+        validation::validate_nik(&request.nik).map_err(|e| (StatusCode::BAD_REQUEST, e.message))?;
+        let hp = phone::normalize(&request.phone).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let doctor_id = ObjectId::parse_str(&request.doctor_id)
+            .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, "doctor_id: Not a valid ID".to_string()))?;
+        let doctor = self.active_doctor(doctor_id).await?;
+        self.ensure_bookable(&request).await?;
+
+        let Some(code) = request.otp.as_deref() else {
+            let token = request.captcha_token.as_deref().map(str::trim).filter(|t| !t.is_empty())
+                .ok_or((StatusCode::BAD_REQUEST, "captcha_token is required to request a booking code".to_string()))?;
+            let passed = self.captcha.verify(token, remote_ip).await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Captcha verification failed: {}", e)))?;
+            if !passed {
+                return Err((StatusCode::FORBIDDEN, "The captcha was not solved; please try again".to_string()));
+            }
+
+            let expires_in = self.otp.issue(PURPOSE_APPOINTMENT_BOOKING, &hp, &hp).await?;
+            return Ok(PublicBookingOutcome::CodeSent(OtpRequestResponse {
+                success: true,
+                message: format!("A booking code has been sent to {}", mask_phone(&hp)),
+                expires_in,
+            }));
+        };
+        self.otp.verify(PURPOSE_APPOINTMENT_BOOKING, &hp, code.trim()).await?;
+
+        // Checked only after the code so the endpoint cannot be used to look up NIKs
+        let patient = self.records.find_by_nik(&request.nik).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .filter(|record| phone::normalize(&record.hp).is_ok_and(|record_hp| record_hp == hp))
+            .ok_or((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "No patient is registered with this NIK and phone; register first at POST /public/patients/register".to_string(),
+            ))?;
+
+        let (_, appointment) = self.appointments.create(CreateAppointmentRequest {
+            patient_id: patient.id.map(|id| id.to_hex()).unwrap_or_default(),
+            doctor_id: request.doctor_id,
+            date: request.date,
+            time: request.time,
+            status: AppointmentStatus::PendingConfirmation,
+            mode: None,
+            organization_id: request.organization_id,
+        }).await?;
+
+        Ok(PublicBookingOutcome::Booked(PublicBookingResponse {
+            appointment_id: appointment.id,
+            doctor: summary(doctor),
+            date: appointment.date,
+            time: appointment.time,
+            starts_at: appointment.starts_at,
+            status: appointment.status,
+        }))
+    }
+}
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use mongodb::bson::{oid::ObjectId, doc};
+use mongodb::bson::{oid::ObjectId, doc, DateTime};
 use crate::repository::RegionRepository;
 use crate::models::Region;
 use crate::dto::region::{CreateRegionRequest, UpdateRegionRequest};
@@ -19,6 +19,7 @@ impl RegionService {
             return Err("Region with this code already exists".to_string());
         }
 
+        let now = DateTime::now();
         let region = Region {
             id_mongo: None,
             code: dto.code,
@@ -29,6 +30,8 @@ impl RegionService {
             kecamatan: dto.kecamatan,
             kelurahan: dto.kelurahan,
             len: dto.len,
+            updated_at: Some(now),
+            created_at: Some(now),
         };
 
         self.repo.create(region).await
@@ -115,6 +118,8 @@ impl RegionService {
             existing.len = len;
         }
 
+        existing.updated_at = Some(DateTime::now());
+
         self.repo.update(id, existing).await
     }
 
@@ -0,0 +1,283 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use crate::cron::CronSchedule;
+use crate::dto::report::{
+    CreateReportScheduleRequest, ReportRunResponse, ReportScheduleQuery, ReportScheduleResponse, UpdateReportScheduleRequest,
+};
+use crate::mailer::{Email, Mailer};
+use crate::models::{Organization, ReportSchedule};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::refs::{Ref, ReferenceChecker};
+use crate::reports::{self, ReportTable};
+use crate::repository::{AppointmentRepository, MedicineRepository, PaymentRepository, ReportScheduleRepository};
+use crate::quota::UploadOwner;
+use crate::services::FileService;
+use crate::status::{ReportFormat, ReportType};
+use crate::timezone::ClinicTimezone;
+
+pub struct ReportService {
+    schedules: ReportScheduleRepository,
+    appointments: AppointmentRepository,
+    payments: PaymentRepository,
+    medicines: MedicineRepository,
+    files: FileService,
+    references: ReferenceChecker,
+    mailer: Box<dyn Mailer>,
+    timezone: ClinicTimezone,
+}
+
+fn schedule_filter(query: &ReportScheduleQuery) -> Document {
+    let mut filter = Document::new();
+    if let Some(report_type) = &query.report_type {
+        filter.insert("reportType", report_type.clone());
+    }
+    if let Some(active) = query.active {
+        filter.insert("active", active);
+    }
+    filter
+}
+
+impl ReportService {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        schedules: ReportScheduleRepository,
+        appointments: AppointmentRepository,
+        payments: PaymentRepository,
+        medicines: MedicineRepository,
+        files: FileService,
+        references: ReferenceChecker,
+        mailer: Box<dyn Mailer>,
+        timezone: ClinicTimezone,
+    ) -> Self {
+        Self { schedules, appointments, payments, medicines, files, references, mailer, timezone }
+    }
+
+    fn map_to_response(schedule: ReportSchedule) -> ReportScheduleResponse {
+        ReportScheduleResponse {
+            id: schedule.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: schedule.name,
+            report_type: schedule.report_type,
+            cron: schedule.cron,
+            recipients: schedule.recipients,
+            format: schedule.format,
+            period_days: schedule.period_days,
+            organization_id: schedule.organization_id,
+            active: schedule.active,
+            next_run_at: crate::datetime::to_rfc3339_opt(schedule.next_run_at),
+            last_run_at: crate::datetime::to_rfc3339_opt(schedule.last_run_at),
+            last_file_id: schedule.last_file_id,
+            last_error: schedule.last_error,
+            created_by: schedule.created_by,
+            created_at: crate::datetime::to_rfc3339(schedule.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(schedule.updated_at),
+        }
+    }
+
+    /// Next run of `cron` from now; cron was validated with the request
+    fn next_run_at(&self, cron: &str) -> Option<DateTime> {
+        let cron = CronSchedule::parse(cron).ok()?;
+        reports::next_run(&cron, &self.timezone, Utc::now()).map(crate::datetime::from_chrono)
+    }
+
+    pub async fn create(&self, request: CreateReportScheduleRequest, user_id: &str) -> Result<ReportScheduleResponse, (StatusCode, String)> {
+        let organization_id = match request.organization_id.as_deref().map(str::trim).filter(|id| !id.is_empty()) {
+            Some(id) => {
+                let organization: Ref<Organization> = Ref::parse_field("organization_id", id)?;
+                self.references.ensure_exist(&[organization.check("organization_id")]).await?;
+                Some(organization.to_hex())
+            }
+            None => None,
+        };
+
+        let schedule = ReportSchedule {
+            id: None,
+            name: request.name.trim().to_string(),
+            report_type: request.report_type,
+            next_run_at: self.next_run_at(&request.cron),
+            cron: request.cron.trim().to_string(),
+            recipients: request.recipients.iter().map(|r| r.trim().to_lowercase()).collect(),
+            format: request.format.unwrap_or(ReportFormat::Csv),
+            period_days: request.period_days.unwrap_or(1),
+            organization_id,
+            active: request.active.unwrap_or(true),
+            last_run_at: None,
+            last_file_id: None,
+            last_error: None,
+            created_by: user_id.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.schedules.create(schedule).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, query: ReportScheduleQuery, pagination: PaginationParams) -> Result<(Vec<ReportScheduleResponse>, PaginationMeta), (StatusCode, String)> {
+        let (schedules, total) = self.schedules.find_paginated(schedule_filter(&query), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let schedules = schedules.into_iter().map(Self::map_to_response).collect();
+        Ok((schedules, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<ReportSchedule, (StatusCode, String)> {
+        self.schedules.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Report schedule not found".to_string()))
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<ReportScheduleResponse, (StatusCode, String)> {
+        self.find(id).await.map(Self::map_to_response)
+    }
+
+    /// A new cron expression or reactivation recomputes the next run
+    pub async fn update(&self, id: ObjectId, request: UpdateReportScheduleRequest) -> Result<ReportScheduleResponse, (StatusCode, String)> {
+        let existing = self.find(id).await?;
+        let mut set = Document::new();
+        if let Some(name) = request.name {
+            set.insert("name", name.trim());
+        }
+        if let Some(recipients) = request.recipients {
+            set.insert("recipients", recipients.iter().map(|r| r.trim().to_lowercase()).collect::<Vec<_>>());
+        }
+        if let Some(format) = request.format {
+            set.insert("format", format);
+        }
+        if let Some(period_days) = request.period_days {
+            set.insert("periodDays", period_days);
+        }
+        if let Some(active) = request.active {
+            set.insert("active", active);
+        }
+        let reactivated = request.active == Some(true) && !existing.active;
+        if request.cron.is_some() || reactivated {
+            let cron = request.cron.as_deref().unwrap_or(&existing.cron).trim().to_string();
+            set.insert("nextRunAt", self.next_run_at(&cron));
+            set.insert("cron", cron);
+        }
+
+        self.schedules.update(id, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .map(Self::map_to_response)
+            .ok_or((StatusCode::NOT_FOUND, "Report schedule not found".to_string()))
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<(), (StatusCode, String)> {
+        match self.schedules.delete(id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err((StatusCode::NOT_FOUND, "Report schedule not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// The report of `schedule` as of today, with the period it covers
+    async fn render(&self, schedule: &ReportSchedule) -> Result<(ReportTable, String, String), String> {
+        let today = self.timezone.local(Utc::now()).date_naive();
+        let (from, to) = reports::report_period(today, schedule.period_days);
+        let organization_id = schedule.organization_id.as_deref();
+        let table = match schedule.report_type {
+            ReportType::AppointmentsPerDoctor => reports::appointments_table(&from, &to, &self.appointments.count_per_doctor(&from, &to, organization_id).await?),
+            ReportType::Revenue => reports::revenue_table(&from, &to, &self.payments.revenue(&from, &to, organization_id).await?),
+            ReportType::Stock => {
+                let date = today.format("%Y-%m-%d").to_string();
+                return Ok((reports::stock_table(today, &self.medicines.stock_summary().await?), date.clone(), date));
+            }
+            ReportType::Other(ref other) => return Err(format!("Unknown report type '{}'", other)),
+        };
+        Ok((table, from, to))
+    }
+
+    /// Render, store and email the report of a schedule now
+    pub async fn run(&self, schedule: ReportSchedule) -> Result<ReportRunResponse, (StatusCode, String)> {
+        let id = schedule.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Schedule without an ID".to_string()))?;
+        let outcome = self.deliver(&schedule).await;
+        let mut set = doc! { "lastRunAt": DateTime::now() };
+        match &outcome {
+            Ok(run) => {
+                set.insert("lastFileId", &run.file.id);
+                set.insert("lastError", mongodb::bson::Bson::Null);
+            }
+            Err((_, e)) => {
+                set.insert("lastError", e);
+            }
+        }
+        if let Err(e) = self.schedules.update(id, set).await {
+            tracing::error!(schedule_id = %id, error = %e, "Recording a report run failed");
+        }
+        outcome
+    }
+
+    async fn deliver(&self, schedule: &ReportSchedule) -> Result<ReportRunResponse, (StatusCode, String)> {
+        let (table, from, to) = self.render(schedule).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let (bytes, extension) = match schedule.format {
+            ReportFormat::Pdf => (reports::text_pdf(&table.text_lines(usize::MAX)), "pdf"),
+            _ => (table.to_csv().into_bytes(), "csv"),
+        };
+        let file_name = format!("{}_{}_{}.{}", schedule.report_type, from, to, extension);
+        let (_, file) = self.files.create(file_name, bytes, format!("report-schedule:{}", schedule.id.map(|id| id.to_hex()).unwrap_or_default()), None, &UploadOwner::default()).await?;
+
+        let mut body = table.text_lines(reports::EMAIL_PREVIEW_ROWS).join("\n");
+        body.push_str(&format!("\n\nFull report ({}): {}", schedule.format, file.url));
+        let subject = format!("{}: {}", schedule.name, table.title);
+
+        let mut delivered_to = Vec::new();
+        let mut failed_recipients = Vec::new();
+        for recipient in &schedule.recipients {
+            let email = Email { to: recipient.clone(), subject: subject.clone(), body: body.clone() };
+            match self.mailer.send(&email).await {
+                Ok(()) => delivered_to.push(recipient.clone()),
+                Err(e) => {
+                    tracing::error!(report = %schedule.name, recipient = %recipient, error = %e, "Emailing a report failed");
+                    failed_recipients.push(recipient.clone());
+                }
+            }
+        }
+
+        Ok(ReportRunResponse {
+            schedule_id: schedule.id.map(|id| id.to_hex()).unwrap_or_default(),
+            report_type: schedule.report_type.clone(),
+            from,
+            to,
+            rows: table.rows.len(),
+            file,
+            delivered_to,
+            failed_recipients,
+        })
+    }
+
+    pub async fn run_now(&self, id: ObjectId) -> Result<ReportRunResponse, (StatusCode, String)> {
+        let schedule = self.find(id).await?;
+        self.run(schedule).await
+    }
+
+    /// Run every due schedule once, claiming each so only one instance sends it
+    pub async fn run_due(&self, now: chrono::DateTime<Utc>) -> Result<usize, String> {
+        let mut runs = 0;
+        for schedule in self.schedules.find_due(crate::datetime::from_chrono(now)).await? {
+            let (Some(id), Some(due)) = (schedule.id, schedule.next_run_at) else { continue };
+            let next = self.next_run_at(&schedule.cron);
+            if !self.schedules.claim_run(id, due, next).await? {
+                continue;
+            }
+            if let Err((_, e)) = self.run(schedule).await {
+                tracing::error!(schedule_id = %id, error = %e, "Scheduled report failed");
+            }
+            runs += 1;
+        }
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_by_type_and_activity() {
+        let query = ReportScheduleQuery { report_type: Some(ReportType::Revenue), active: Some(true) };
+        assert_eq!(schedule_filter(&query), doc! { "reportType": "revenue", "active": true });
+        assert_eq!(schedule_filter(&ReportScheduleQuery::default()), Document::new());
+    }
+}
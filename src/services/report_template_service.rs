@@ -0,0 +1,186 @@
+use axum::http::StatusCode;
+use mongodb::bson::{oid::ObjectId, Bson, DateTime, Document};
+use serde_json::Value;
+use crate::dto::report_template::{
+    CreateReportTemplateRequest, ReportParameterRequest, ReportResultResponse, ReportTemplateResponse, RunReportRequest,
+    UpdateReportTemplateRequest,
+};
+use crate::models::{ReportParameter, ReportTemplate};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::report_templates;
+use crate::repository::ReportTemplateRepository;
+
+pub struct ReportTemplateService {
+    templates: ReportTemplateRepository,
+}
+
+fn unprocessable(message: String) -> (StatusCode, String) {
+    (StatusCode::UNPROCESSABLE_ENTITY, message)
+}
+
+/// Request stages as BSON documents
+fn to_pipeline(stages: &[Value]) -> Result<Vec<Document>, (StatusCode, String)> {
+    stages.iter().enumerate().map(|(i, stage)| match Bson::try_from(stage.clone()) {
+        Ok(Bson::Document(stage)) => Ok(stage),
+        _ => Err(unprocessable(format!("Stage {} must be a JSON object", i + 1))),
+    }).collect()
+}
+
+/// Request parameters with their defaults coerced to the declared type
+fn to_parameters(parameters: &[ReportParameterRequest]) -> Result<Vec<ReportParameter>, (StatusCode, String)> {
+    parameters.iter().map(|p| {
+        let mut parameter = ReportParameter { name: p.name.trim().to_string(), param_type: p.param_type.clone(), required: p.required, default: None };
+        if let Some(default) = p.default.as_ref().filter(|d| !d.is_null()) {
+            parameter.default = Some(report_templates::coerce(&parameter, default).map_err(unprocessable)?);
+        }
+        Ok(parameter)
+    }).collect()
+}
+
+impl ReportTemplateService {
+    pub fn new(templates: ReportTemplateRepository) -> Self {
+        Self { templates }
+    }
+
+    fn map_to_response(template: ReportTemplate) -> ReportTemplateResponse {
+        ReportTemplateResponse {
+            id: template.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: template.name,
+            description: template.description,
+            collection: template.collection,
+            pipeline: template.pipeline.into_iter().map(|stage| Bson::Document(stage).into_relaxed_extjson()).collect(),
+            parameters: template.parameters.into_iter().map(|p| ReportParameterRequest {
+                name: p.name,
+                param_type: p.param_type,
+                required: p.required,
+                default: p.default.map(Bson::into_relaxed_extjson),
+            }).collect(),
+            columns: template.columns,
+            created_by: template.created_by,
+            created_at: crate::datetime::to_rfc3339(template.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(template.updated_at),
+        }
+    }
+
+    pub async fn create(&self, request: CreateReportTemplateRequest, user_id: &str) -> Result<ReportTemplateResponse, (StatusCode, String)> {
+        let collection = request.collection.trim().to_string();
+        let pipeline = to_pipeline(&request.pipeline)?;
+        let parameters = to_parameters(&request.parameters)?;
+        report_templates::validate_template(&collection, &pipeline, &parameters).map_err(unprocessable)?;
+
+        let template = ReportTemplate {
+            id: None,
+            name: request.name.trim().to_string(),
+            description: request.description,
+            collection,
+            pipeline,
+            parameters,
+            columns: request.columns,
+            created_by: user_id.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.templates.create(template).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, pagination: PaginationParams) -> Result<(Vec<ReportTemplateResponse>, PaginationMeta), (StatusCode, String)> {
+        let (templates, total) = self.templates.find_paginated(Document::new(), &pagination).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let templates = templates.into_iter().map(Self::map_to_response).collect();
+        Ok((templates, PaginationMeta::new(pagination.page, pagination.limit, total)))
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<ReportTemplate, (StatusCode, String)> {
+        self.templates.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Report template not found".to_string()))
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<ReportTemplateResponse, (StatusCode, String)> {
+        self.find(id).await.map(Self::map_to_response)
+    }
+
+    /// The changed template is checked as a whole before it is saved
+    pub async fn update(&self, id: ObjectId, request: UpdateReportTemplateRequest) -> Result<ReportTemplateResponse, (StatusCode, String)> {
+        let existing = self.find(id).await?;
+        let collection = request.collection.map(|c| c.trim().to_string()).unwrap_or(existing.collection);
+        let pipeline = match &request.pipeline {
+            Some(stages) => to_pipeline(stages)?,
+            None => existing.pipeline,
+        };
+        let parameters = match &request.parameters {
+            Some(parameters) => to_parameters(parameters)?,
+            None => existing.parameters,
+        };
+        report_templates::validate_template(&collection, &pipeline, &parameters).map_err(unprocessable)?;
+
+        let mut set = mongodb::bson::doc! {
+            "collection": collection,
+            "pipeline": pipeline,
+            "parameters": mongodb::bson::to_bson(&parameters).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        };
+        if let Some(name) = request.name {
+            set.insert("name", name.trim());
+        }
+        if let Some(description) = request.description {
+            set.insert("description", description);
+        }
+        if let Some(columns) = request.columns {
+            set.insert("columns", columns);
+        }
+
+        self.templates.update(id, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .map(Self::map_to_response)
+            .ok_or((StatusCode::NOT_FOUND, "Report template not found".to_string()))
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<(), (StatusCode, String)> {
+        match self.templates.delete(id).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err((StatusCode::NOT_FOUND, "Report template not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Bind the parameters and return one page of the result as a table.
+    pub async fn run(&self, request: RunReportRequest, pagination: PaginationParams) -> Result<ReportResultResponse, (StatusCode, String)> {
+        let id = ObjectId::parse_str(&request.template_id)
+            .map_err(|_| unprocessable("template_id must be a valid report template ID".to_string()))?;
+        let template = self.find(id).await?;
+        // A $limit of 0 is rejected by the server, so the bounds are applied here
+        let pagination = PaginationParams::new(pagination.page, pagination.limit);
+
+        let pipeline = report_templates::bind(&template.pipeline, &template.parameters, &request.parameters).map_err(unprocessable)?;
+        let (documents, total) = self.templates.run(&template.collection, report_templates::paginated(pipeline, &pagination)).await
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("The report failed: {}", e)))?;
+        let (columns, rows) = report_templates::tabulate(documents, &template.columns);
+
+        Ok(ReportResultResponse {
+            template_id: request.template_id,
+            name: template.name,
+            columns,
+            rows,
+            pagination: PaginationMeta::new(pagination.page, pagination.limit, total),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_must_be_objects_and_defaults_typed() {
+        assert!(to_pipeline(&[serde_json::json!({ "$match": { "status": "completed" } })]).is_ok());
+        assert_eq!(to_pipeline(&[serde_json::json!("$match")]).unwrap_err().0, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let parameter = |default: Value| ReportParameterRequest { name: "days".to_string(), param_type: "number".into(), required: false, default: Some(default) };
+        assert_eq!(to_parameters(&[parameter(serde_json::json!(7))]).unwrap()[0].default, Some(Bson::Int64(7)));
+        assert!(to_parameters(&[parameter(serde_json::json!("seven"))]).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+#[cfg(feature = "s3")]
+use std::sync::Arc;
+#[cfg(feature = "s3")]
+use crate::storage::Storage;
+use chrono::{DateTime, Utc};
+use mongodb::bson::{Bson, Document};
+use crate::dto::retention::{RetentionStatusResponse, RetentionTotal};
+use crate::models::{RetentionResult, RetentionRun};
+use crate::repository::RetentionRepository;
+use crate::retention::{self, RetentionAction, RetentionConfig, RetentionPolicy};
+
+const ARCHIVE_BATCH_SIZE: i64 = 1000;
+/// Upper bound on batches archived per policy per run, so one run cannot stall indefinitely
+const MAX_ARCHIVE_BATCHES: usize = 200;
+
+pub struct RetentionService {
+    repo: RetentionRepository,
+    #[cfg(feature = "s3")]
+    storage: Option<Arc<Storage>>,
+    config: RetentionConfig,
+}
+
+impl RetentionService {
+    pub fn new(repo: RetentionRepository, config: RetentionConfig) -> Self {
+        Self {
+            repo,
+            #[cfg(feature = "s3")]
+            storage: None,
+            config,
+        }
+    }
+
+    /// Storage for `archive` policies; without it they fail and their documents stay.
+    #[cfg(feature = "s3")]
+    pub fn with_s3(mut self, storage: Arc<Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Execute every configured policy and record the run. A failing policy does not stop the others.
+    pub async fn run(&self, trigger: &str) -> Result<RetentionRun, String> {
+        let started = Utc::now();
+        let mut results = Vec::new();
+
+        for policy in &self.config.policies {
+            let cutoff = retention::cutoff(started, policy.older_than_days);
+            let mut result = RetentionResult {
+                collection: policy.collection.clone(),
+                action: policy.action.as_str().to_string(),
+                cutoff: cutoff.to_rfc3339(),
+                documents: 0,
+                archive_keys: Vec::new(),
+                error: None,
+            };
+
+            let outcome = match policy.action {
+                RetentionAction::Archive => self.archive(policy, cutoff, started, &mut result).await,
+                RetentionAction::Purge => self.repo
+                    .delete_expired(&policy.collection, &policy.date_field, cutoff)
+                    .await
+                    .map(|deleted| result.documents = deleted),
+            };
+            if let Err(e) = outcome {
+                tracing::error!(collection = %policy.collection, error = %e, "Retention policy failed");
+                result.error = Some(e);
+            }
+
+            results.push(result);
+        }
+
+        self.repo.insert_run(RetentionRun {
+            id: None,
+            trigger: trigger.to_string(),
+            started_at: started.to_rfc3339(),
+            finished_at: Utc::now().to_rfc3339(),
+            results,
+        }).await
+    }
+
+    /// Upload expired documents to S3 as JSONL in batches, deleting each batch once stored.
+    async fn archive(&self, policy: &RetentionPolicy, cutoff: DateTime<Utc>, started: DateTime<Utc>, result: &mut RetentionResult) -> Result<(), String> {
+        for part in 0..MAX_ARCHIVE_BATCHES {
+            let batch = self.repo.find_expired(&policy.collection, &policy.date_field, cutoff, ARCHIVE_BATCH_SIZE).await?;
+            if batch.is_empty() {
+                break;
+            }
+
+            let (body, ids) = Self::to_jsonl(batch)?;
+            let key = retention::archive_key(&self.config.archive_prefix, &policy.collection, started, part);
+            self.upload(&key, body).await?;
+            result.archive_keys.push(key);
+
+            result.documents += self.repo.delete_by_ids(&policy.collection, ids).await?;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "s3")]
+    async fn upload(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        let storage = self.storage.as_ref().ok_or("Archiving needs file storage")?;
+        let backend = storage.backend().map_err(|(_, e)| e)?;
+        backend.put(&self.config.archive_bucket, key, body).await.map(|_| ())
+    }
+
+    #[cfg(not(feature = "s3"))]
+    async fn upload(&self, _key: &str, _body: Vec<u8>) -> Result<(), String> {
+        Err("Archiving needs the s3 feature; this build has no file storage".to_string())
+    }
+
+    fn to_jsonl(batch: Vec<Document>) -> Result<(Vec<u8>, Vec<Bson>), String> {
+        let mut body = Vec::new();
+        let mut ids = Vec::with_capacity(batch.len());
+
+        for document in batch {
+            if let Some(id) = document.get("_id") {
+                ids.push(id.clone());
+            }
+            let line = serde_json::to_string(&Bson::Document(document).into_relaxed_extjson())
+                .map_err(|e| e.to_string())?;
+            body.extend_from_slice(line.as_bytes());
+            body.push(b'\n');
+        }
+
+        Ok((body, ids))
+    }
+
+    pub async fn status(&self) -> Result<RetentionStatusResponse, String> {
+        let last_run = self.repo.find_latest_run().await?;
+        let totals = self.repo.totals().await?
+            .into_iter()
+            .map(|d| {
+                let key = d.get_document("_id").cloned().unwrap_or_default();
+                RetentionTotal {
+                    collection: key.get_str("collection").unwrap_or_default().to_string(),
+                    action: key.get_str("action").unwrap_or_default().to_string(),
+                    documents: d.get_i64("documents").or_else(|_| d.get_i32("documents").map(i64::from)).unwrap_or_default(),
+                    runs: d.get_i64("runs").or_else(|_| d.get_i32("runs").map(i64::from)).unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        Ok(RetentionStatusResponse {
+            enabled: !self.config.policies.is_empty(),
+            run_hour: self.config.run_hour,
+            policies: self.config.policies.clone(),
+            last_run,
+            totals,
+        })
+    }
+}
@@ -0,0 +1,134 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::review::{CreateReviewRequest, ModerateReviewRequest, ReviewQuery, ReviewResponse};
+use crate::middleware::AuthUser;
+use crate::models::Review;
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::{AppointmentRepository, DoctorRepository, MedicalRecordRepository, ReviewRepository};
+use crate::status::AppointmentStatus;
+
+pub const REVIEW_PUBLISHED: &str = "published";
+pub const REVIEW_HIDDEN: &str = "hidden";
+
+pub struct ReviewService {
+    reviews: ReviewRepository,
+    appointments: AppointmentRepository,
+    patients: MedicalRecordRepository,
+    doctors: DoctorRepository,
+}
+
+impl ReviewService {
+    pub fn new(reviews: ReviewRepository, appointments: AppointmentRepository, patients: MedicalRecordRepository, doctors: DoctorRepository) -> Self {
+        Self { reviews, appointments, patients, doctors }
+    }
+
+    fn map_to_response(review: Review) -> ReviewResponse {
+        ReviewResponse {
+            id: review.id.map(|id| id.to_hex()).unwrap_or_default(),
+            patient_id: review.patient_id,
+            doctor_id: review.doctor_id,
+            appointment_id: review.appointment_id,
+            rating: review.rating,
+            comment: review.comment,
+            status: review.status,
+            moderation_note: review.moderation_note,
+            moderated_at: review.moderated_at,
+            created_at: crate::datetime::to_rfc3339(review.created_at),
+        }
+    }
+
+    /// Recompute the doctor's denormalized rating from published reviews.
+    async fn refresh_rating(&self, doctor_id: &str) -> Result<(), (StatusCode, String)> {
+        let Ok(oid) = ObjectId::parse_str(doctor_id) else { return Ok(()) };
+        let (average, count) = self.reviews.rating_summary(doctor_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.doctors.set_rating(oid, average, count).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Review a completed appointment. Only the patient, identified by the email on their
+    /// medical record matching the caller's account, may review it, and only once.
+    pub async fn create(&self, appointment_id: ObjectId, user: &AuthUser, request: CreateReviewRequest) -> Result<ReviewResponse, (StatusCode, String)> {
+        let appointment = self.appointments.find_by_id(appointment_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Appointment not found".to_string()))?;
+        if appointment.status != AppointmentStatus::Completed {
+            return Err((StatusCode::CONFLICT, "Only completed appointments can be reviewed".to_string()));
+        }
+
+        let patient = self.patients.find_by_id(appointment.patient_id.id()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let is_patient = patient.is_some_and(|p| !p.email.is_empty() && p.email.eq_ignore_ascii_case(&user.email));
+        if !is_patient {
+            return Err((StatusCode::FORBIDDEN, "Only the patient of this appointment can review it".to_string()));
+        }
+
+        let appointment_hex = appointment_id.to_hex();
+        let existing = self.reviews.find_by_appointment(&appointment_hex).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if existing.is_some() {
+            return Err((StatusCode::CONFLICT, "This appointment has already been reviewed".to_string()));
+        }
+
+        let review = Review {
+            id: None,
+            patient_id: appointment.patient_id.to_hex(),
+            doctor_id: appointment.doctor_id.to_hex(),
+            appointment_id: appointment_hex,
+            rating: request.rating,
+            comment: request.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+            status: REVIEW_PUBLISHED.to_string(),
+            moderation_note: None,
+            moderated_by: None,
+            moderated_at: None,
+            created_by: user.id.clone(),
+            created_at: DateTime::now(),
+        };
+
+        let created = self.reviews.create(review).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        self.refresh_rating(&created.doctor_id).await?;
+        Ok(Self::map_to_response(created))
+    }
+
+    pub async fn list(&self, query: ReviewQuery, pagination: PaginationParams) -> Result<(Vec<ReviewResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.reviews.find_paginated(query.doctor_id.as_deref(), query.status.as_deref(), &pagination).await {
+            Ok((reviews, total)) => {
+                let responses = reviews.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Publish or hide a review and update the doctor's rating accordingly.
+    pub async fn moderate(&self, id: ObjectId, moderator: &AuthUser, request: ModerateReviewRequest) -> Result<ReviewResponse, (StatusCode, String)> {
+        if request.status != REVIEW_PUBLISHED && request.status != REVIEW_HIDDEN {
+            return Err((StatusCode::BAD_REQUEST, format!("Status must be '{}' or '{}'", REVIEW_PUBLISHED, REVIEW_HIDDEN)));
+        }
+
+        let set = doc! {
+            "status": &request.status,
+            "moderation_note": request.note,
+            "moderated_by": &moderator.id,
+            "moderated_at": Utc::now().to_rfc3339(),
+        };
+        let review = self.reviews.update_fields(id, set).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Review not found".to_string()))?;
+
+        self.refresh_rating(&review.doctor_id).await?;
+        Ok(Self::map_to_response(review))
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        match self.reviews.delete(id).await {
+            Ok(Some(review)) => {
+                self.refresh_rating(&review.doctor_id).await?;
+                Ok(true)
+            }
+            Ok(None) => Ok(false),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use mongodb::bson::{doc, Document};
+use serde::de::DeserializeOwned;
+use crate::models::{Doctor, Practitioner};
+use crate::rbac::{PermissionSet, Resource};
+use crate::repository::SearchRepository;
+use crate::services::{AppointmentService, DoctorService, MedicalRecordService, MedicineService};
+use crate::dto::search::{GlobalSearchResponse, SearchHit};
+use crate::status::PractitionerType;
+
+const DEFAULT_LIMIT: i64 = 5;
+const MAX_LIMIT: i64 = 50;
+
+/// Collection and filter holding a search group; doctors live in `practitioners`
+fn source(group: &str) -> (&str, Document) {
+    match group {
+        "doctors" => ("practitioners", doc! { "type": PractitionerType::Doctor.as_str() }),
+        other => (other, Document::new()),
+    }
+}
+
+pub struct SearchService {
+    repo: SearchRepository,
+    #[cfg(feature = "meilisearch")]
+    meili: Option<std::sync::Arc<crate::meilisearch::MeiliClient>>,
+}
+
+impl SearchService {
+    pub fn new(repo: SearchRepository) -> Self {
+        Self {
+            repo,
+            #[cfg(feature = "meilisearch")]
+            meili: None,
+        }
+    }
+
+    /// Serve indexed resource types from Meilisearch, falling back to MongoDB on failure.
+    #[cfg(feature = "meilisearch")]
+    pub fn with_meilisearch(mut self, client: Option<std::sync::Arc<crate::meilisearch::MeiliClient>>) -> Self {
+        self.meili = client;
+        self
+    }
+
+    /// Parse the `types` query parameter; `None` selects every resource type.
+    pub fn parse_types(types: Option<&str>) -> Result<Vec<Resource>, String> {
+        let Some(types) = types else {
+            return Ok(vec![Resource::Patients, Resource::Doctors, Resource::Medicines, Resource::Appointments]);
+        };
+
+        types
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| match t {
+                "patients" => Ok(Resource::Patients),
+                "doctors" => Ok(Resource::Doctors),
+                "medicines" => Ok(Resource::Medicines),
+                "appointments" => Ok(Resource::Appointments),
+                other => Err(format!("Unknown search type '{}'", other)),
+            })
+            .collect()
+    }
+
+    /// Query every requested collection the caller may read, in parallel.
+    pub async fn search(&self, query: &str, types: &[Resource], permissions: &PermissionSet, limit: Option<i64>) -> Result<GlobalSearchResponse, String> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let enabled = |resource: Resource| types.contains(&resource) && permissions.can_read(resource);
+
+        let (patients, doctors, medicines, appointments) = tokio::join!(
+            self.group(enabled(Resource::Patients), "medical_records", query, limit, MedicalRecordService::map_to_response),
+            self.group(enabled(Resource::Doctors), "doctors", query, limit, |p: Practitioner| DoctorService::map_to_response(Doctor::from(p))),
+            self.group(enabled(Resource::Medicines), "medicines", query, limit, MedicineService::map_to_response),
+            self.group(enabled(Resource::Appointments), "appointments", query, limit, AppointmentService::map_to_response),
+        );
+
+        Ok(GlobalSearchResponse {
+            query: query.to_string(),
+            patients: patients?,
+            doctors: doctors?,
+            medicines: medicines?,
+            appointments: appointments?,
+        })
+    }
+
+    async fn group<M: DeserializeOwned, R: DeserializeOwned>(
+        &self,
+        enabled: bool,
+        group: &str,
+        query: &str,
+        limit: i64,
+        map: fn(M) -> R,
+    ) -> Result<Option<Vec<SearchHit<R>>>, String> {
+        if !enabled {
+            return Ok(None);
+        }
+
+        #[cfg(feature = "meilisearch")]
+        if let (Some(client), Some(index)) = (&self.meili, crate::meilisearch::index_for(group)) {
+            match client.search::<R>(index, query, limit).await {
+                Ok(hits) => return Ok(Some(hits.into_iter().map(|(score, item)| SearchHit { score, item }).collect())),
+                Err(e) => tracing::warn!(index, error = %e, "Meilisearch query failed, falling back to MongoDB"),
+            }
+        }
+
+        let (collection, filter) = source(group);
+        let hits = self.repo.text_search::<M>(collection, filter, query, limit).await?;
+        Ok(Some(hits.into_iter().map(|(score, item)| SearchHit { score, item: map(item) }).collect()))
+    }
+}
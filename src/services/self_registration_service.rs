@@ -0,0 +1,110 @@
+use axum::http::StatusCode;
+use chrono::{Datelike, Local};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::auth::OtpRequestResponse;
+use crate::dto::medical_record::MedicalRecordResponse;
+use crate::dto::patient::{SelfRegistrationRequest, SelfRegistrationResponse};
+use crate::middleware::AuthUser;
+use crate::models::{MedicalRecord, SelfRegistration};
+use crate::otp::{mask_phone, PURPOSE_PATIENT_REGISTRATION};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::phone;
+use crate::repository::MedicalRecordRepository;
+use crate::self_registration::birth_from_nik;
+use crate::sequences::{self, SequenceGenerator};
+use crate::services::{MedicalRecordService, OtpService};
+use crate::status::RegistrationStatus;
+use crate::validation;
+
+/// Walk-in patients registering themselves, and staff confirming them; see `crate::self_registration`
+pub struct SelfRegistrationService {
+    records: MedicalRecordRepository,
+    sequences: SequenceGenerator,
+    otp: OtpService,
+}
+
+/// Registrations either send a code or create a record
+pub enum SelfRegistrationOutcome {
+    CodeSent(OtpRequestResponse),
+    Registered(SelfRegistrationResponse),
+}
+
+impl SelfRegistrationService {
+    pub fn new(records: MedicalRecordRepository, sequences: SequenceGenerator, otp: OtpService) -> Self {
+        Self { records, sequences, otp }
+    }
+
+    pub async fn register(&self, request: SelfRegistrationRequest) -> Result<SelfRegistrationOutcome, (StatusCode, String)> {
+        validation::validate_nik(&request.nik).map_err(|e| (StatusCode::BAD_REQUEST, e.message))?;
+        let today = Local::now().date_naive();
+        let (dob, gender) = birth_from_nik(&request.nik, today)
+            .ok_or((StatusCode::UNPROCESSABLE_ENTITY, "nik: The NIK does not hold a valid date of birth".to_string()))?;
+        let hp = phone::normalize(&request.phone).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+        let Some(code) = request.otp else {
+            let expires_in = self.otp.issue(PURPOSE_PATIENT_REGISTRATION, &hp, &hp).await?;
+            return Ok(SelfRegistrationOutcome::CodeSent(OtpRequestResponse {
+                success: true,
+                message: format!("A registration code has been sent to {}", mask_phone(&hp)),
+                expires_in,
+            }));
+        };
+        self.otp.verify(PURPOSE_PATIENT_REGISTRATION, &hp, code.trim()).await?;
+
+        let existing = self.records.find_by_nik(&request.nik).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if existing.is_some() {
+            return Err((StatusCode::CONFLICT, "This NIK is already registered; please ask at the front desk".to_string()));
+        }
+
+        let nrme = self.sequences.next(&sequences::MEDICAL_RECORD, None, Local::now().year()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let now = DateTime::now();
+        let record = MedicalRecord {
+            id: Some(ObjectId::new()),
+            nrme,
+            nik: request.nik,
+            name: request.name.trim().to_string(),
+            dob: dob.format("%Y-%m-%d").to_string(),
+            gender,
+            hp,
+            email: String::new(),
+            last_visit_date: today.format("%Y-%m-%d").to_string(),
+            // The code just went to this phone
+            phone_verified_at: Some(crate::datetime::to_rfc3339(now)),
+            updated_at: None,
+            self_registration: Some(SelfRegistration {
+                status: RegistrationStatus::Pending,
+                registered_at: now,
+                confirmed_by: None,
+                confirmed_at: None,
+            }),
+        };
+
+        let created = self.records.insert(record).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(SelfRegistrationOutcome::Registered(SelfRegistrationResponse {
+            patient_id: created.id.map(|id| id.to_hex()).unwrap_or_default(),
+            nrme: created.nrme,
+            name: created.name,
+            registration_status: RegistrationStatus::Pending,
+        }))
+    }
+
+    pub async fn list(&self, status: &RegistrationStatus, pagination: PaginationParams) -> Result<(Vec<MedicalRecordResponse>, PaginationMeta), (StatusCode, String)> {
+        let (records, total) = self.records.find_registrations(status, pagination.clone()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let meta = PaginationMeta::new(pagination.page, pagination.limit, total);
+        Ok((records.into_iter().map(MedicalRecordService::map_to_response).collect(), meta))
+    }
+
+    /// Confirm a pending registration once staff have checked the patient's identity
+    pub async fn confirm(&self, id: ObjectId, user: &AuthUser) -> Result<MedicalRecordResponse, (StatusCode, String)> {
+        if let Some(confirmed) = self.records.confirm_registration(id, &user.id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Ok(MedicalRecordService::map_to_response(confirmed));
+        }
+
+        match self.records.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(_) => Err((StatusCode::CONFLICT, "The patient has no pending registration".to_string())),
+            None => Err((StatusCode::NOT_FOUND, "Patient not found".to_string())),
+        }
+    }
+}
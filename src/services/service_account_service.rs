@@ -0,0 +1,203 @@
+use axum::http::StatusCode;
+use bcrypt::{hash, verify, DEFAULT_COST};
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use rand::{distributions::Alphanumeric, Rng};
+use crate::dto::service_account::{
+    CreateServiceAccountRequest, ServiceAccountCredentialsResponse, ServiceAccountResponse, TokenResponse, UpdateServiceAccountRequest,
+};
+use crate::models::ServiceAccount;
+use crate::rbac::{self, ACTION_READ, ACTION_WRITE, ANY};
+use crate::repository::ServiceAccountRepository;
+use crate::services::AuthService;
+
+const CLIENT_ID_PREFIX: &str = "sa_";
+const CLIENT_SECRET_LENGTH: usize = 40;
+
+pub struct ServiceAccountService {
+    repo: ServiceAccountRepository,
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng().sample_iter(&Alphanumeric).take(len).map(char::from).collect()
+}
+
+/// Normalize scopes to `resource:action`, rejecting unknown actions.
+fn normalize_scopes(scopes: &[String]) -> Result<Vec<String>, (StatusCode, String)> {
+    let mut normalized = Vec::new();
+    for scope in scopes {
+        let (resource, action) = rbac::parse_scope(scope)
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Scope '{}' must be resource:action", scope)))?;
+        if action != ACTION_READ && action != ACTION_WRITE && action != ANY {
+            return Err((StatusCode::BAD_REQUEST, format!("Scope '{}' must use read, write or *", scope)));
+        }
+        normalized.push(format!("{}:{}", resource, action));
+    }
+    normalized.sort();
+    normalized.dedup();
+    Ok(normalized)
+}
+
+/// The first requested scope the granted ones do not cover. Matched as `service_scope` matches
+/// requests, so `observations:*` covers `observations:read`.
+fn ungranted<'a>(granted: &[String], requested: &'a [String]) -> Option<&'a String> {
+    let granted = rbac::PermissionSet::from_scopes(granted);
+    requested.iter().find(|scope| !rbac::parse_scope(scope).is_some_and(|(resource, action)| granted.allows(&resource, &action)))
+}
+
+fn hash_secret(secret: &str) -> Result<String, (StatusCode, String)> {
+    hash(secret, DEFAULT_COST).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to hash client secret: {}", e)))
+}
+
+impl ServiceAccountService {
+    pub fn new(repo: ServiceAccountRepository) -> Self {
+        Self { repo }
+    }
+
+    fn map_to_response(account: ServiceAccount) -> ServiceAccountResponse {
+        ServiceAccountResponse {
+            id: account.id.map(|id| id.to_hex()).unwrap_or_default(),
+            name: account.name,
+            client_id: account.client_id,
+            scopes: account.scopes,
+            active: account.active,
+            created_by: account.created_by,
+            created_at: crate::datetime::to_rfc3339(account.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(account.updated_at),
+            rotated_at: account.rotated_at,
+            last_used_at: account.last_used_at,
+        }
+    }
+
+    pub async fn list(&self) -> Result<Vec<ServiceAccountResponse>, (StatusCode, String)> {
+        match self.repo.find_all().await {
+            Ok(accounts) => Ok(accounts.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<Option<ServiceAccountResponse>, (StatusCode, String)> {
+        match self.repo.find_by_id(id).await {
+            Ok(account) => Ok(account.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn create(&self, request: CreateServiceAccountRequest, created_by: &str) -> Result<ServiceAccountCredentialsResponse, (StatusCode, String)> {
+        let scopes = normalize_scopes(&request.scopes)?;
+        let client_secret = random_string(CLIENT_SECRET_LENGTH);
+
+        let account = ServiceAccount {
+            id: None,
+            name: request.name.trim().to_string(),
+            client_id: format!("{}{}", CLIENT_ID_PREFIX, random_string(24).to_lowercase()),
+            client_secret_hash: hash_secret(&client_secret)?,
+            scopes,
+            active: true,
+            created_by: created_by.to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+            rotated_at: None,
+            last_used_at: None,
+        };
+
+        match self.repo.create(account).await {
+            Ok(created) => Ok(ServiceAccountCredentialsResponse { account: Self::map_to_response(created), client_secret }),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn update(&self, id: ObjectId, request: UpdateServiceAccountRequest) -> Result<Option<ServiceAccountResponse>, (StatusCode, String)> {
+        let mut set = doc! { "updated_at": DateTime::now() };
+        if let Some(name) = request.name {
+            set.insert("name", name.trim());
+        }
+        if let Some(scopes) = request.scopes {
+            set.insert("scopes", normalize_scopes(&scopes)?);
+        }
+        if let Some(active) = request.active {
+            set.insert("active", active);
+        }
+
+        match self.repo.update_fields(id, set).await {
+            Ok(account) => Ok(account.map(Self::map_to_response)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        self.repo.delete(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+    }
+
+    /// Replace the client secret. Tokens issued with the old one stop working.
+    pub async fn rotate_secret(&self, id: ObjectId) -> Result<Option<ServiceAccountCredentialsResponse>, (StatusCode, String)> {
+        let client_secret = random_string(CLIENT_SECRET_LENGTH);
+        let now = DateTime::now();
+        let set = doc! { "client_secret_hash": hash_secret(&client_secret)?, "rotated_at": crate::datetime::to_rfc3339(now), "updated_at": now };
+
+        match self.repo.update_fields(id, set).await {
+            Ok(account) => Ok(account.map(|a| ServiceAccountCredentialsResponse { account: Self::map_to_response(a), client_secret })),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Client-credentials grant. `scope` narrows the token to a subset of the account's scopes.
+    pub async fn issue_token(&self, client_id: &str, client_secret: &str, scope: Option<&str>) -> Result<TokenResponse, (StatusCode, String)> {
+        let invalid_client = || (StatusCode::UNAUTHORIZED, "Invalid client credentials".to_string());
+
+        let account = self.repo.find_by_client_id(client_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .filter(|a| a.active)
+            .ok_or_else(invalid_client)?;
+        if !verify(client_secret, &account.client_secret_hash).unwrap_or(false) {
+            return Err(invalid_client());
+        }
+
+        let scopes = match scope.map(str::split_whitespace) {
+            Some(requested) => {
+                let requested: Vec<String> = requested.map(str::to_string).collect();
+                let requested = normalize_scopes(&requested)?;
+                if let Some(extra) = ungranted(&account.scopes, &requested) {
+                    return Err((StatusCode::BAD_REQUEST, format!("Scope '{}' is not granted to this client", extra)));
+                }
+                requested
+            }
+            None => account.scopes.clone(),
+        };
+
+        let (access_token, expires_in) = AuthService::generate_service_token(&account, scopes.clone())
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        if let Some(id) = account.id {
+            // Best effort; a failed bookkeeping write should not refuse the token
+            if let Err(e) = self.repo.update_fields(id, doc! { "last_used_at": Utc::now().to_rfc3339() }).await {
+                tracing::warn!(error = %e, "Failed to record service account use");
+            }
+        }
+
+        Ok(TokenResponse { access_token, token_type: "Bearer".to_string(), expires_in, scope: scopes.join(" ") })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_are_normalized_and_deduplicated() {
+        let scopes = normalize_scopes(&["Observations:WRITE".to_string(), "observations:write".to_string(), "kits:read".to_string()]).unwrap();
+        assert_eq!(scopes, vec!["kits:read".to_string(), "observations:write".to_string()]);
+        assert!(normalize_scopes(&["patients:delete".to_string()]).is_err());
+        assert!(normalize_scopes(&["patients".to_string()]).is_err());
+    }
+
+    #[test]
+    fn wildcard_grants_cover_narrower_scopes() {
+        let scopes = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let granted = scopes(&["observations:*", "kits:read"]);
+        assert_eq!(ungranted(&granted, &scopes(&["observations:read", "observations:write", "kits:read"])), None);
+        assert_eq!(ungranted(&granted, &scopes(&["kits:write"])).map(String::as_str), Some("kits:write"));
+        assert_eq!(ungranted(&granted, &scopes(&["kits:*"])).map(String::as_str), Some("kits:*"));
+        assert_eq!(ungranted(&scopes(&["*:read"]), &scopes(&["patients:read"])), None);
+    }
+}
@@ -92,3 +92,37 @@ impl ServiceService {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[test]
+    fn requests_reject_blank_fields() {
+        let create: CreateServiceRequest = serde_json::from_value(serde_json::json!({
+            "_id": "65a1b2c3d4e5f60718293a4b",
+            "name": "",
+            "category": "Laboratorium",
+            "sub_category": "Hematologi"
+        })).unwrap();
+        assert!(create.validate().is_err());
+
+        let update = UpdateServiceRequest { name: None, category: Some(String::new()), sub_category: None };
+        assert!(update.validate().is_err());
+        let update = UpdateServiceRequest { name: Some("Darah lengkap".into()), category: None, sub_category: None };
+        assert!(update.validate().is_ok());
+    }
+
+    #[test]
+    fn maps_the_stored_id() {
+        let id = ObjectId::new();
+        let response = ServiceService::map_to_response(Service {
+            id: Some(id),
+            name: "Darah lengkap".into(),
+            category: "Laboratorium".into(),
+            sub_category: "Hematologi".into(),
+        });
+        assert_eq!(response.id, id.to_hex());
+    }
+}
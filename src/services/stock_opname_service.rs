@@ -0,0 +1,274 @@
+use axum::http::StatusCode;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::stock_opname::{
+    CreateStockOpnameRequest, StockOpnameItemResponse, StockOpnameQuery, StockOpnameReport, StockOpnameResponse,
+    StockVarianceLine, SubmitStockCountsRequest,
+};
+use crate::models::{StockMovement, StockOpname, StockOpnameItem};
+use crate::pagination::{PaginationMeta, PaginationParams};
+use crate::repository::stock_opname::CountUpdate;
+use crate::repository::{MedicineRepository, StockOpnameRepository};
+use crate::status::{StockMovementType, StockOpnameStatus};
+
+/// Stock opname: physical counts of medicine batches against a snapshot of their `qty`.
+///
+/// Approval adds each batch's variance to its current `qty` rather than overwriting it, so
+/// stock dispensed between the snapshot and the approval is not lost.
+pub struct StockOpnameService {
+    opnames: StockOpnameRepository,
+    medicines: MedicineRepository,
+}
+
+fn round_money(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// Totals and the variance lines of `opname`
+pub fn report(opname: &StockOpname) -> StockOpnameReport {
+    let mut lines = Vec::new();
+    let (mut shortage_qty, mut surplus_qty, mut variance_value) = (0.0, 0.0, 0.0);
+    for item in &opname.items {
+        let (Some(counted_qty), Some(variance)) = (item.counted_qty, item.variance()) else { continue };
+        if variance == 0.0 {
+            continue;
+        }
+        if variance < 0.0 {
+            shortage_qty -= variance;
+        } else {
+            surplus_qty += variance;
+        }
+        let value = round_money(variance * item.purchase_price);
+        variance_value += value;
+        lines.push(StockVarianceLine {
+            medicine_id: item.medicine_id.clone(),
+            batch_number: item.batch_number.clone(),
+            trade_name: item.trade_name.clone(),
+            expected_qty: item.expected_qty,
+            counted_qty,
+            variance,
+            variance_value: value,
+        });
+    }
+
+    let counted = opname.items.iter().filter(|item| item.counted_qty.is_some()).count();
+    StockOpnameReport {
+        id: opname.id.map(|id| id.to_hex()).unwrap_or_default(),
+        status: opname.status.clone(),
+        started_at: crate::datetime::to_rfc3339(opname.started_at),
+        closed_at: crate::datetime::to_rfc3339_opt(opname.closed_at),
+        items: opname.items.len(),
+        counted,
+        uncounted: opname.items.len() - counted,
+        shortage_qty,
+        surplus_qty,
+        variance_value: round_money(variance_value),
+        lines,
+    }
+}
+
+impl StockOpnameService {
+    pub fn new(opnames: StockOpnameRepository, medicines: MedicineRepository) -> Self {
+        Self { opnames, medicines }
+    }
+
+    fn map_to_response(opname: StockOpname) -> StockOpnameResponse {
+        StockOpnameResponse {
+            id: opname.id.map(|id| id.to_hex()).unwrap_or_default(),
+            notes: opname.notes,
+            status: opname.status,
+            items: opname.items.into_iter().map(|item| StockOpnameItemResponse {
+                variance: item.variance(),
+                medicine_id: item.medicine_id,
+                master_medicine_id: item.master_medicine_id,
+                batch_number: item.batch_number,
+                trade_name: item.trade_name,
+                expired_date: item.expired_date,
+                expected_qty: item.expected_qty,
+                counted_qty: item.counted_qty,
+                note: item.note,
+                counted_by: item.counted_by,
+                counted_at: crate::datetime::to_rfc3339_opt(item.counted_at),
+            }).collect(),
+            started_by: opname.started_by,
+            started_at: crate::datetime::to_rfc3339(opname.started_at),
+            closed_by: opname.closed_by,
+            closed_at: crate::datetime::to_rfc3339_opt(opname.closed_at),
+        }
+    }
+
+    async fn find(&self, id: ObjectId) -> Result<StockOpname, (StatusCode, String)> {
+        self.opnames.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Stock opname not found".to_string()))
+    }
+
+    fn not_counting(opname: &StockOpname) -> (StatusCode, String) {
+        (StatusCode::CONFLICT, format!("The stock opname is already {}", opname.status))
+    }
+
+    /// Snapshot the batches' quantities; one opname is counted at a time
+    pub async fn start(&self, request: CreateStockOpnameRequest, started_by: &str) -> Result<StockOpnameResponse, (StatusCode, String)> {
+        if let Some(open) = self.opnames.find_counting().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("Stock opname {} is still being counted; approve or cancel it first", open.id.map(|id| id.to_hex()).unwrap_or_default()),
+            ));
+        }
+
+        let medicines = match &request.master_medicine_ids {
+            Some(ids) => self.medicines.find_by_master_ids(ids).await,
+            None => self.medicines.find_all().await,
+        }.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if medicines.is_empty() {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "There are no medicine batches to count".to_string()));
+        }
+
+        let mut items: Vec<StockOpnameItem> = medicines.into_iter().map(|medicine| StockOpnameItem {
+            medicine_id: medicine.id.map(|id| id.to_hex()).unwrap_or_default(),
+            master_medicine_id: medicine.master_medicine_id,
+            batch_number: medicine.batch_number,
+            trade_name: medicine.trade_name,
+            expired_date: medicine.expired_date,
+            purchase_price: medicine.purchase_price,
+            expected_qty: medicine.qty,
+            counted_qty: None,
+            note: None,
+            counted_by: None,
+            counted_at: None,
+        }).collect();
+        items.sort_by(|a, b| a.trade_name.cmp(&b.trade_name).then_with(|| a.expired_date.cmp(&b.expired_date)));
+
+        let opname = StockOpname {
+            id: None,
+            notes: request.notes.map(|notes| notes.trim().to_string()).filter(|notes| !notes.is_empty()),
+            status: StockOpnameStatus::Counting,
+            items,
+            started_by: started_by.to_string(),
+            started_at: DateTime::now(),
+            closed_by: None,
+            closed_at: None,
+        };
+        let created = self.opnames.create(opname).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        Ok(Self::map_to_response(created))
+    }
+
+    pub async fn list(&self, query: StockOpnameQuery, pagination: PaginationParams) -> Result<(Vec<StockOpnameResponse>, PaginationMeta), (StatusCode, String)> {
+        match self.opnames.find_paginated(query.status.as_ref(), &pagination).await {
+            Ok((opnames, total)) => {
+                let responses = opnames.into_iter().map(Self::map_to_response).collect();
+                Ok((responses, PaginationMeta::new(pagination.page, pagination.limit, total)))
+            }
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get(&self, id: ObjectId) -> Result<StockOpnameResponse, (StatusCode, String)> {
+        Ok(Self::map_to_response(self.find(id).await?))
+    }
+
+    /// 422 naming the first batch that is not part of the opname
+    pub async fn submit_counts(&self, id: ObjectId, request: SubmitStockCountsRequest, counted_by: &str) -> Result<StockOpnameResponse, (StatusCode, String)> {
+        let opname = self.find(id).await?;
+        if opname.status != StockOpnameStatus::Counting {
+            return Err(Self::not_counting(&opname));
+        }
+        if let Some((i, item)) = request.items.iter().enumerate()
+            .find(|(_, item)| !opname.items.iter().any(|batch| batch.medicine_id == item.medicine_id))
+        {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("items[{}]: medicine {} is not part of this stock opname", i, item.medicine_id)));
+        }
+
+        let counts: Vec<CountUpdate> = request.items.into_iter().map(|item| CountUpdate {
+            medicine_id: item.medicine_id,
+            counted_qty: item.counted_qty,
+            note: item.note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty()),
+        }).collect();
+        if !self.opnames.record_counts(id, &counts, counted_by).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Err(Self::not_counting(&self.find(id).await?));
+        }
+        self.get(id).await
+    }
+
+    /// Adjust every batch with a variance and record the adjustments; 422 while batches are uncounted
+    pub async fn approve(&self, id: ObjectId, approved_by: &str) -> Result<StockOpnameResponse, (StatusCode, String)> {
+        let opname = self.find(id).await?;
+        if opname.status != StockOpnameStatus::Counting {
+            return Err(Self::not_counting(&opname));
+        }
+        let uncounted = opname.items.iter().filter(|item| item.counted_qty.is_none()).count();
+        if uncounted > 0 {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("{} of {} batches have not been counted", uncounted, opname.items.len())));
+        }
+
+        let now = DateTime::now();
+        let opname_id = id.to_hex();
+        let movements = opname.items.iter()
+            .filter_map(|item| item.variance().filter(|variance| *variance != 0.0).map(|variance| StockMovement {
+                id: None,
+                medicine_id: item.medicine_id.clone(),
+                batch_number: item.batch_number.clone(),
+                movement_type: StockMovementType::Adjustment,
+                qty: variance,
+                stock_opname_id: Some(opname_id.clone()),
+                reason: item.note.clone(),
+                created_by: approved_by.to_string(),
+                created_at: now,
+            }))
+            .collect();
+        if !self.opnames.approve(id, approved_by, movements).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Err(Self::not_counting(&self.find(id).await?));
+        }
+        self.get(id).await
+    }
+
+    pub async fn cancel(&self, id: ObjectId, cancelled_by: &str) -> Result<StockOpnameResponse, (StatusCode, String)> {
+        if !self.opnames.cancel(id, cancelled_by).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Err(Self::not_counting(&self.find(id).await?));
+        }
+        self.get(id).await
+    }
+
+    pub async fn report(&self, id: ObjectId) -> Result<StockOpnameReport, (StatusCode, String)> {
+        Ok(report(&self.find(id).await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(medicine_id: &str, expected_qty: f64, counted_qty: Option<f64>) -> StockOpnameItem {
+        StockOpnameItem {
+            medicine_id: medicine_id.to_string(),
+            master_medicine_id: "m1".to_string(),
+            batch_number: format!("B-{}", medicine_id),
+            trade_name: "Paracetamol 500mg".to_string(),
+            expired_date: "2027-01-31".to_string(),
+            purchase_price: 1_250.5,
+            expected_qty,
+            counted_qty,
+            note: None,
+            counted_by: None,
+            counted_at: None,
+        }
+    }
+
+    #[test]
+    fn reports_variances() {
+        let opname = StockOpname {
+            id: None,
+            notes: None,
+            status: StockOpnameStatus::Counting,
+            items: vec![item("a", 100.0, Some(97.0)), item("b", 10.0, Some(12.0)), item("c", 5.0, Some(5.0)), item("d", 8.0, None)],
+            started_by: "u1".to_string(),
+            started_at: DateTime::from_millis(0),
+            closed_by: None,
+            closed_at: None,
+        };
+        let report = report(&opname);
+        assert_eq!((report.items, report.counted, report.uncounted), (4, 3, 1));
+        assert_eq!((report.shortage_qty, report.surplus_qty), (3.0, 2.0));
+        assert_eq!(report.variance_value, -1_250.5);
+        assert_eq!(report.lines.iter().map(|line| line.variance).collect::<Vec<_>>(), vec![-3.0, 2.0]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use axum::http::StatusCode;
+use chrono::Utc;
+use crate::dto::file::StorageReconciliationStatus;
+use crate::models::{OrphanedObject, StorageReconciliation};
+use crate::repository::StorageReconciliationRepository;
+use crate::storage::{Storage, StoredObject};
+use crate::storage_gc::{self, Findings, GcMode, StorageGcConfig};
+
+/// Orphans and missing keys kept in a run's report; the counts cover all of them
+const MAX_LISTED: usize = 1000;
+
+pub struct StorageReconciliationService {
+    repo: StorageReconciliationRepository,
+    storage: Arc<Storage>,
+    config: StorageGcConfig,
+}
+
+impl StorageReconciliationService {
+    pub fn new(repo: StorageReconciliationRepository, storage: Arc<Storage>, config: StorageGcConfig) -> Self {
+        Self { repo, storage, config }
+    }
+
+    /// Reconcile the configured prefixes and record the run. A run that cannot list storage or
+    /// read the file records is recorded with its error and deletes nothing.
+    pub async fn run(&self, trigger: &str) -> Result<StorageReconciliation, (StatusCode, String)> {
+        let backend = self.storage.backend()?;
+        let started = Utc::now();
+        let deleting = self.config.mode == GcMode::Delete;
+        let mut run = StorageReconciliation {
+            id: None,
+            trigger: trigger.to_string(),
+            mode: if deleting { GcMode::Delete } else { GcMode::Report }.as_str().to_string(),
+            started_at: started.to_rfc3339(),
+            finished_at: String::new(),
+            bucket: self.storage.bucket().to_string(),
+            prefixes: self.config.prefixes.clone(),
+            grace_hours: self.config.grace_hours,
+            objects_scanned: 0,
+            orphans_found: 0,
+            orphan_bytes: 0,
+            orphans_pending: 0,
+            orphans_deleted: 0,
+            missing_found: 0,
+            orphans: Vec::new(),
+            missing: Vec::new(),
+            error: None,
+        };
+
+        match self.find(started).await {
+            Ok((Findings { orphans, missing }, scanned)) => {
+                run.objects_scanned = scanned;
+                run.missing_found = missing.len() as u64;
+                run.missing = missing.into_iter().take(MAX_LISTED).collect();
+
+                for orphan in orphans {
+                    let StoredObject { key, size, modified } = orphan.object;
+                    let mut entry = OrphanedObject {
+                        last_modified: modified.map(|m| m.to_rfc3339()),
+                        key,
+                        size,
+                        pending: orphan.pending,
+                        deleted: false,
+                        error: None,
+                    };
+                    if deleting && !orphan.pending {
+                        match backend.delete(self.storage.bucket(), &entry.key).await {
+                            Ok(()) => entry.deleted = true,
+                            Err(e) => {
+                                tracing::error!(key = %entry.key, error = %e, "Failed to delete orphaned object");
+                                entry.error = Some(e);
+                            }
+                        }
+                    }
+
+                    run.orphans_found += 1;
+                    run.orphan_bytes += entry.size;
+                    run.orphans_pending += u64::from(entry.pending);
+                    run.orphans_deleted += u64::from(entry.deleted);
+                    if run.orphans.len() < MAX_LISTED {
+                        run.orphans.push(entry);
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!(error = %e, "Storage reconciliation failed");
+                run.error = Some(e);
+            }
+        }
+
+        run.finished_at = Utc::now().to_rfc3339();
+        let failure = run.error.clone();
+        let run = self.repo.insert_run(run).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        match failure {
+            Some(e) => Err((StatusCode::BAD_GATEWAY, e)),
+            None => Ok(run),
+        }
+    }
+
+    /// Findings and the number of objects listed. Storage is listed before the file records
+    /// are read, so an object uploaded in between is never taken for an orphan.
+    async fn find(&self, now: chrono::DateTime<Utc>) -> Result<(Findings, u64), String> {
+        let backend = self.storage.backend().map_err(|(_, e)| e)?;
+        let mut objects = Vec::new();
+        for prefix in &self.config.prefixes {
+            objects.extend(backend.list(self.storage.bucket(), prefix).await?);
+        }
+        // Overlapping prefixes list the same object twice
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        objects.dedup_by(|a, b| a.key == b.key);
+        let scanned = objects.len() as u64;
+
+        let referenced = self.repo.referenced_paths().await?;
+        Ok((storage_gc::reconcile(objects, &referenced, &self.config.prefixes, now, self.config.grace_hours), scanned))
+    }
+
+    pub async fn status(&self) -> Result<StorageReconciliationStatus, String> {
+        Ok(StorageReconciliationStatus {
+            mode: self.config.mode.as_str().to_string(),
+            run_hour: self.config.run_hour,
+            grace_hours: self.config.grace_hours,
+            prefixes: self.config.prefixes.clone(),
+            last_run: self.repo.find_latest_run().await?,
+        })
+    }
+}
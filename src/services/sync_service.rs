@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use axum::http::StatusCode;
+use mongodb::bson::{DateTime, Document};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::dto::sync::{CollectionChanges, ReferenceSyncQuery, SyncChanges};
+use crate::models::{Code, Interpretation, Region};
+use crate::repository::{SyncRepository, TombstoneRepository};
+use crate::sync::{Position, SyncCursor, REFERENCE_COLLECTIONS, REFERENCE_FEEDS, TOMBSTONES};
+
+const DEFAULT_LIMIT: i64 = 500;
+
+/// Delta sync of codes, interpretations and regions, see `crate::sync`
+pub struct SyncService {
+    repo: SyncRepository,
+    tombstones: TombstoneRepository,
+}
+
+/// The collections named in `collections`, in feed order; all of them when absent
+fn requested_collections(collections: Option<&str>) -> Result<Vec<&'static str>, String> {
+    let Some(collections) = collections.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(REFERENCE_COLLECTIONS.to_vec());
+    };
+    let names: Vec<&str> = collections.split(',').map(str::trim).filter(|c| !c.is_empty()).collect();
+    if let Some(unknown) = names.iter().find(|name| !REFERENCE_COLLECTIONS.contains(name)) {
+        return Err(format!("Unknown collection '{}', expected some of {}", unknown, REFERENCE_COLLECTIONS.join(", ")));
+    }
+    Ok(REFERENCE_COLLECTIONS.into_iter().filter(|c| names.contains(c)).collect())
+}
+
+/// Created after the client's position, rather than changed; everything is new to a full sync
+fn is_created(document: &Document, position: Option<Position>) -> bool {
+    match (position, document.get_datetime("created_at")) {
+        (None, _) => true,
+        (Some(position), Ok(created_at)) => *created_at >= position.at,
+        (Some(_), Err(_)) => false,
+    }
+}
+
+/// A stored document in the shape the collection's API returns
+fn reference_json(collection: &str, document: Document) -> Result<serde_json::Value, String> {
+    fn convert<T: DeserializeOwned + Serialize>(document: Document) -> Result<serde_json::Value, String> {
+        let item: T = mongodb::bson::from_document(document).map_err(|e| e.to_string())?;
+        serde_json::to_value(item).map_err(|e| e.to_string())
+    }
+    match collection {
+        "codes" => convert::<Code>(document),
+        "interpretations" => convert::<Interpretation>(document),
+        _ => convert::<Region>(document),
+    }
+}
+
+impl SyncService {
+    pub fn new(repo: SyncRepository, tombstones: TombstoneRepository) -> Self {
+        Self { repo, tombstones }
+    }
+
+    /// Up to `limit` changed documents per collection and `limit` deletions after `since`
+    pub async fn reference(&self, query: ReferenceSyncQuery) -> Result<SyncChanges, (StatusCode, String)> {
+        let collections = requested_collections(query.collections.as_deref()).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let since = query.since.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let full = since.is_none();
+        let mut cursor = match since {
+            Some(since) => SyncCursor::parse(since, &REFERENCE_FEEDS).map_err(|e| (StatusCode::BAD_REQUEST, e))?,
+            None => SyncCursor::default(),
+        };
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+        // A full sync starts the deletion feed now: nothing deleted before it is on the client
+        let started = DateTime::now();
+        let mut has_more = false;
+        let mut changes: BTreeMap<String, CollectionChanges> = BTreeMap::new();
+
+        for collection in &collections {
+            let position = cursor.position(collection);
+            let mut documents = self.repo.changes(collection, position, limit + 1).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+            if documents.len() as i64 > limit {
+                has_more = true;
+                documents.truncate(limit as usize);
+            }
+
+            let entry = changes.entry(collection.to_string()).or_default();
+            for document in documents {
+                if let (Ok(at), Ok(id)) = (document.get_datetime("updated_at"), document.get_object_id("_id")) {
+                    cursor.advance(collection, Position { at: *at, id: Some(id) });
+                }
+                let created = is_created(&document, position);
+                match reference_json(collection, document) {
+                    Ok(value) if created => entry.created.push(value),
+                    Ok(value) => entry.updated.push(value),
+                    Err(e) => tracing::warn!(collection, error = %e, "Skipping unreadable document in sync"),
+                }
+            }
+        }
+
+        let position = cursor.position(TOMBSTONES)
+            .or(full.then_some(Position { at: started, id: None }));
+        let mut tombstones = self.tombstones.find_after(&collections, position, limit + 1).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if tombstones.len() as i64 > limit {
+            has_more = true;
+            tombstones.truncate(limit as usize);
+        }
+        if let Some(position) = position {
+            cursor.advance(TOMBSTONES, position);
+        }
+        for tombstone in tombstones {
+            cursor.advance(TOMBSTONES, Position { at: tombstone.deleted_at, id: tombstone.id });
+            changes.entry(tombstone.collection).or_default().deleted.push(tombstone.entity_id);
+        }
+
+        Ok(SyncChanges { cursor: cursor.encode(), has_more, collections: changes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn collections_are_checked_and_documents_classified() {
+        assert_eq!(requested_collections(None).unwrap(), REFERENCE_COLLECTIONS.to_vec());
+        assert_eq!(requested_collections(Some("regions, codes")).unwrap(), vec!["codes", "regions"]);
+        assert!(requested_collections(Some("codes,patients")).is_err());
+
+        let position = Position { at: DateTime::from_millis(1_000), id: None };
+        assert!(is_created(&doc! { "created_at": DateTime::from_millis(1_500) }, Some(position)));
+        assert!(!is_created(&doc! { "created_at": DateTime::from_millis(500) }, Some(position)));
+        assert!(!is_created(&doc! {}, Some(position)));
+        assert!(is_created(&doc! {}, None));
+    }
+}
@@ -0,0 +1,125 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId};
+use crate::dto::appointment::TeleconsultSessionResponse;
+use crate::models::{Appointment, TeleconsultSession};
+use crate::repository::{AppointmentRepository, TeleconsultRepository};
+use crate::services::appointment_service::MODE_VIRTUAL;
+use crate::status::AppointmentStatus;
+use crate::teleconsult::TeleconsultProvider;
+
+pub const SESSION_SCHEDULED: &str = "scheduled";
+pub const SESSION_ACTIVE: &str = "active";
+pub const SESSION_ENDED: &str = "ended";
+
+pub struct TeleconsultService {
+    sessions: TeleconsultRepository,
+    appointments: AppointmentRepository,
+    provider: Box<dyn TeleconsultProvider>,
+}
+
+impl TeleconsultService {
+    pub fn new(sessions: TeleconsultRepository, appointments: AppointmentRepository, provider: Box<dyn TeleconsultProvider>) -> Self {
+        Self { sessions, appointments, provider }
+    }
+
+    fn map_to_response(session: TeleconsultSession) -> TeleconsultSessionResponse {
+        TeleconsultSessionResponse {
+            id: session.id.map(|id| id.to_hex()).unwrap_or_default(),
+            appointment_id: session.appointment_id,
+            provider: session.provider,
+            room: session.room,
+            meeting_url: session.meeting_url,
+            token: session.token,
+            token_expires_at: session.token_expires_at,
+            status: session.status,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+            created_at: crate::datetime::to_rfc3339(session.created_at),
+        }
+    }
+
+    async fn load_virtual(&self, appointment_id: ObjectId) -> Result<Appointment, (StatusCode, String)> {
+        let appointment = self.appointments.find_by_id(appointment_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Appointment not found".to_string()))?;
+        if appointment.mode.as_deref() != Some(MODE_VIRTUAL) {
+            return Err((StatusCode::CONFLICT, "Appointment is not a virtual appointment".to_string()));
+        }
+        Ok(appointment)
+    }
+
+    /// Current session of a virtual appointment, creating one with the provider when there
+    /// is none or the previous one has ended.
+    pub async fn ensure_session(&self, appointment_id: ObjectId) -> Result<TeleconsultSessionResponse, (StatusCode, String)> {
+        self.load_virtual(appointment_id).await?;
+        let id = appointment_id.to_hex();
+
+        let existing = self.sessions.find_latest_for_appointment(&id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if let Some(session) = existing.filter(|s| s.status != SESSION_ENDED) {
+            return Ok(Self::map_to_response(session));
+        }
+
+        let now = Utc::now();
+        let meeting = self.provider.create_meeting(&id, now)
+            .map_err(|e| (StatusCode::BAD_GATEWAY, e))?;
+        let session = TeleconsultSession {
+            id: None,
+            appointment_id: id,
+            provider: self.provider.name().to_string(),
+            room: meeting.room,
+            meeting_url: meeting.url,
+            token: meeting.token,
+            token_expires_at: meeting.expires_at,
+            status: SESSION_SCHEDULED.to_string(),
+            started_at: None,
+            ended_at: None,
+            created_at: crate::datetime::from_chrono(now),
+        };
+
+        match self.sessions.create(session).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Start the session and mark the appointment `in_progress`.
+    pub async fn start(&self, appointment_id: ObjectId) -> Result<TeleconsultSessionResponse, (StatusCode, String)> {
+        let session = self.ensure_session(appointment_id).await?;
+        if session.status != SESSION_SCHEDULED {
+            return Err((StatusCode::CONFLICT, format!("Session is already {}", session.status)));
+        }
+        let session_id = ObjectId::parse_str(&session.id).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let now = Utc::now().to_rfc3339();
+        let started = self.sessions.transition(session_id, SESSION_SCHEDULED, doc! { "status": SESSION_ACTIVE, "started_at": &now }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Session was started concurrently".to_string()))?;
+
+        self.appointments.update_many_by_ids(&[appointment_id], doc! { "status": AppointmentStatus::InProgress, "calledAt": &now }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(Self::map_to_response(started))
+    }
+
+    /// End the active session and mark the appointment `completed`.
+    pub async fn end(&self, appointment_id: ObjectId) -> Result<TeleconsultSessionResponse, (StatusCode, String)> {
+        self.load_virtual(appointment_id).await?;
+        let session = self.sessions.find_latest_for_appointment(&appointment_id.to_hex()).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .filter(|s| s.status == SESSION_ACTIVE)
+            .ok_or((StatusCode::CONFLICT, "Appointment has no active session".to_string()))?;
+        let session_id = session.id.ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Session has no id".to_string()))?;
+
+        let now = Utc::now().to_rfc3339();
+        let ended = self.sessions.transition(session_id, SESSION_ACTIVE, doc! { "status": SESSION_ENDED, "ended_at": &now }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Session was ended concurrently".to_string()))?;
+
+        self.appointments.update_many_by_ids(&[appointment_id], doc! { "status": AppointmentStatus::Completed, "completedAt": &now }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(Self::map_to_response(ended))
+    }
+}
@@ -0,0 +1,130 @@
+use axum::http::StatusCode;
+use mongodb::bson::{oid::ObjectId, DateTime};
+use crate::dto::patient::{AppointmentEntry, EncounterEntry, FileEntry, ObservationEntry, TimelineEntry, TimelineQuery, TimelineResponse};
+use crate::models::{Admission, Appointment, File, Observation};
+use crate::repository::{AdmissionRepository, AppointmentRepository, FileRepository, MedicalRecordRepository, ObservationRepository};
+use crate::status::TimelineEntryType;
+use crate::timeline::{self, Position};
+
+const DEFAULT_LIMIT: usize = 20;
+
+pub struct TimelineService {
+    records: MedicalRecordRepository,
+    admissions: AdmissionRepository,
+    observations: ObservationRepository,
+    files: FileRepository,
+    appointments: AppointmentRepository,
+}
+
+/// Entry types of a `types` parameter; every type when absent
+fn parse_types(raw: Option<&str>) -> Result<Vec<TimelineEntryType>, String> {
+    let Some(raw) = raw.filter(|raw| !raw.trim().is_empty()) else {
+        return Ok(TimelineEntryType::KNOWN.iter().map(|&known| TimelineEntryType::from(known)).collect());
+    };
+    raw.split(',')
+        .map(|value| TimelineEntryType::parse(value).ok_or_else(|| {
+            format!("Unknown timeline entry type '{}'; expected {}", value.trim(), TimelineEntryType::KNOWN.join(", "))
+        }))
+        .collect()
+}
+
+fn position(at: i64, entry_type: TimelineEntryType, id: Option<ObjectId>) -> Position {
+    Position { at, entry_type, id: id.unwrap_or_else(|| ObjectId::from_bytes([0; 12])) }
+}
+
+fn encounter(admission: Admission) -> (Position, TimelineEntry) {
+    let at = position(admission.admitted_at.timestamp_millis(), TimelineEntryType::Encounter, admission.id);
+    (at, TimelineEntry::Encounter(EncounterEntry {
+        id: admission.id.map(|id| id.to_hex()).unwrap_or_default(),
+        occurred_at: crate::datetime::to_rfc3339(admission.admitted_at),
+        status: admission.status,
+        ward_id: admission.ward_id,
+        bed_id: admission.bed_id,
+        discharged_at: crate::datetime::to_rfc3339_opt(admission.discharged_at),
+        discharge_note: admission.discharge_note,
+    }))
+}
+
+fn observation(observation: Observation) -> (Position, TimelineEntry) {
+    let at = position(observation.time.saturating_mul(1000), TimelineEntryType::Observation, observation.id);
+    (at, TimelineEntry::Observation(ObservationEntry {
+        id: observation.id.map(|id| id.to_hex()).unwrap_or_default(),
+        occurred_at: crate::datetime::to_rfc3339(DateTime::from_millis(observation.time.saturating_mul(1000))),
+        code: observation.coding.code,
+        display: observation.coding.display,
+        value: observation.value,
+        unit: observation.unit.display,
+        interpretation: observation.interpretation.code,
+        derived: observation.derived,
+    }))
+}
+
+fn file(file: File) -> (Position, TimelineEntry) {
+    let at = position(file.created_at.timestamp_millis(), TimelineEntryType::File, file.id);
+    (at, TimelineEntry::File(FileEntry {
+        id: file.id.map(|id| id.to_hex()).unwrap_or_default(),
+        occurred_at: crate::datetime::to_rfc3339(file.created_at),
+        name: file.name,
+        file_type: file.file_type,
+        extension: file.extension,
+        size: file.size,
+    }))
+}
+
+/// Only appointments with `startsAt` are read for the timeline
+fn appointment(appointment: Appointment) -> (Position, TimelineEntry) {
+    let starts_at = appointment.starts_at.unwrap_or(DateTime::MIN);
+    let at = position(starts_at.timestamp_millis(), TimelineEntryType::Appointment, appointment.id);
+    (at, TimelineEntry::Appointment(AppointmentEntry {
+        id: appointment.id.map(|id| id.to_hex()).unwrap_or_default(),
+        occurred_at: crate::datetime::to_rfc3339(starts_at),
+        doctor_id: appointment.doctor_id.to_hex(),
+        date: appointment.date,
+        time: appointment.time,
+        status: appointment.status,
+        mode: appointment.mode,
+    }))
+}
+
+impl TimelineService {
+    pub fn new(
+        records: MedicalRecordRepository,
+        admissions: AdmissionRepository,
+        observations: ObservationRepository,
+        files: FileRepository,
+        appointments: AppointmentRepository,
+    ) -> Self {
+        Self { records, admissions, observations, files, appointments }
+    }
+
+    /// One page of the patient's timeline, reading every requested source in parallel
+    pub async fn timeline(&self, patient_id: ObjectId, query: TimelineQuery) -> Result<TimelineResponse, (StatusCode, String)> {
+        let types = parse_types(query.types.as_deref()).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let after = query.cursor.as_deref().map(Position::parse).transpose().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        self.records.find_by_id(patient_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Patient not found".to_string()))?;
+
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+        // One more than a page from each source tells whether another page follows
+        let fetch = limit as i64 + 1;
+        let patient = patient_id.to_hex();
+        let (patient, after) = (patient.as_str(), after.as_ref());
+
+        let wanted = |entry_type: TimelineEntryType| types.contains(&entry_type);
+        let (admissions, observations, files, appointments) = tokio::join!(
+            async { if wanted(TimelineEntryType::Encounter) { self.admissions.find_timeline(patient, after, fetch).await } else { Ok(Vec::new()) } },
+            async { if wanted(TimelineEntryType::Observation) { self.observations.find_timeline(patient, after, fetch).await } else { Ok(Vec::new()) } },
+            async { if wanted(TimelineEntryType::File) { self.files.find_timeline(patient, after, fetch).await } else { Ok(Vec::new()) } },
+            async { if wanted(TimelineEntryType::Appointment) { self.appointments.find_timeline(patient, after, fetch).await } else { Ok(Vec::new()) } },
+        );
+        let internal = |e: String| (StatusCode::INTERNAL_SERVER_ERROR, e);
+        let entries: Vec<_> = admissions.map_err(internal)?.into_iter().map(encounter)
+            .chain(observations.map_err(internal)?.into_iter().map(observation))
+            .chain(files.map_err(internal)?.into_iter().map(file))
+            .chain(appointments.map_err(internal)?.into_iter().map(appointment))
+            .collect();
+        let (entries, next_cursor) = timeline::page(entries, limit);
+        Ok(TimelineResponse { entries, next_cursor })
+    }
+}
@@ -0,0 +1,97 @@
+use axum::http::StatusCode;
+use chrono::NaiveDate;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use crate::dto::usage::{UsageBucket, UsageQuery, UsageReport, UsageTotals};
+use crate::repository::{KitRepository, ObservationRepository};
+
+const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// Kit and operator activity aggregated from the observations they recorded.
+pub struct UsageService {
+    observations: ObservationRepository,
+    kits: KitRepository,
+}
+
+impl UsageService {
+    pub fn new(observations: ObservationRepository, kits: KitRepository) -> Self {
+        Self { observations, kits }
+    }
+
+    /// Usage of one kit, matched on the kit code stored in `atm_sehat.code`.
+    pub async fn kit_usage(&self, kit_id: ObjectId, query: UsageQuery) -> Result<UsageReport, (StatusCode, String)> {
+        let kit = self.kits.find_by_id(kit_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Kit not found".to_string()))?;
+
+        self.report(kit.code.clone(), doc! { "atm_sehat.code": kit.code }, query).await
+    }
+
+    /// Activity of one operator. Observations reference operators by `id_petugas`, so the NIK is
+    /// resolved to the operator IDs recorded on kits (the NIK itself is matched as well).
+    /// Distributors only count observations made on their kits.
+    pub async fn operator_activity(&self, nik: &str, query: UsageQuery) -> Result<UsageReport, (StatusCode, String)> {
+        let mut ids = self.kits.find_operator_ids_by_nik(nik).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        ids.push(nik.to_string());
+
+        let mut filter = doc! { "id_petugas": { "$in": ids } };
+        if let Some(codes) = self.kits.find_scoped_codes().await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            filter.insert("atm_sehat.code", doc! { "$in": codes });
+        }
+        self.report(nik.to_string(), filter, query).await
+    }
+
+    async fn report(&self, subject: String, filter: Document, query: UsageQuery) -> Result<UsageReport, (StatusCode, String)> {
+        let period = match query.period.as_deref().map(str::trim) {
+            None | Some("") | Some("day") => "day",
+            Some("week") => "week",
+            Some(other) => return Err((StatusCode::BAD_REQUEST, format!("Invalid period '{}' (expected day or week)", other))),
+        };
+        let timezone = query.tz
+            .map(|tz| tz.trim().to_string())
+            .filter(|tz| !tz.is_empty())
+            .unwrap_or_else(|| DEFAULT_TIMEZONE.to_string());
+        let from = Self::parse_date(query.from.as_deref(), 0)?;
+        // `to` is inclusive, so the range ends at the start of the following day
+        let to = Self::parse_date(query.to.as_deref(), 1)?;
+
+        let (series, totals) = self.observations
+            .activity_report(filter, period, &timezone, from, to)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        Ok(UsageReport {
+            subject,
+            period: period.to_string(),
+            timezone,
+            totals: totals.map(|t| UsageTotals {
+                observations: Self::count(&t, "observations"),
+                patients_served: Self::count(&t, "patients_served"),
+                active_hours: Self::count(&t, "active_hours"),
+            }).unwrap_or_default(),
+            series: series.iter().map(|b| UsageBucket {
+                period_start: b.get_datetime("period_start")
+                    .ok()
+                    .and_then(|d| d.try_to_rfc3339_string().ok())
+                    .unwrap_or_default(),
+                observations: Self::count(b, "observations"),
+                patients_served: Self::count(b, "patients_served"),
+                active_hours: Self::count(b, "active_hours"),
+            }).collect(),
+        })
+    }
+
+    fn parse_date(value: Option<&str>, offset_days: i64) -> Result<Option<DateTime>, (StatusCode, String)> {
+        let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else { return Ok(None) };
+        let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid date '{}' (expected YYYY-MM-DD)", value)))?;
+        let start = (date + chrono::Duration::days(offset_days)).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        Ok(Some(DateTime::from_millis(start.timestamp_millis())))
+    }
+
+    fn count(document: &Document, key: &str) -> i64 {
+        document.get_i64(key)
+            .or_else(|_| document.get_i32(key).map(i64::from))
+            .unwrap_or_default()
+    }
+}
@@ -1,15 +1,80 @@
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
 use axum::http::StatusCode;
-use chrono::Utc;
 use crate::{
-    models::{UserRole, RoleEmbed, UserEmbed, OrganizationEmbed, UserName, UserContact, UserBirth},
-    repository::UserRoleRepository,
+    models::{Notification, UserRole, RoleEmbed, UserEmbed, OrganizationEmbed, UserName, UserContact, UserBirth},
+    repository::{NotificationRepository, UserRoleRepository},
+    role_expiry,
     dto::user_role::{CreateUserRoleRequest, UserRoleResponse, UpdateUserRoleRequest, RoleEmbedDto, UserEmbedDto, OrganizationEmbedDto},
     pagination::{PaginationParams, PaginationMeta},
     dto::role::RoleCategoryDto,
-    dto::user_role::{UserNameDto, UserContactDto, UserBirthDto}
+    dto::user_role::{UserNameDto, UserContactDto, UserBirthDto},
+    dto::user_role::{BulkUserRoleResponse, BulkUserRoleResult, MemberRoleResponse, OrganizationMemberResponse},
 };
 
+/// `None` for an absent or empty value
+fn parse_validity(field: &str, value: Option<&str>) -> Result<Option<DateTime>, (StatusCode, String)> {
+    match value.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => crate::datetime::parse(value)
+            .map(Some)
+            .ok_or((StatusCode::BAD_REQUEST, format!("{} must be an RFC 3339 timestamp", field))),
+        None => Ok(None),
+    }
+}
+
+/// Refuse windows ending before they start, and active assignments that have already expired
+fn check_validity(is_active: bool, valid_from: Option<DateTime>, valid_until: Option<DateTime>, now: DateTime) -> Result<(), (StatusCode, String)> {
+    role_expiry::check_window(valid_from, valid_until).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if is_active && valid_until.is_some_and(|until| until <= now) {
+        return Err((StatusCode::BAD_REQUEST, "valid_until is in the past; extend it to reactivate the role".to_string()));
+    }
+    Ok(())
+}
+
+/// Assignments grouped per user, in the order each user first appears
+pub fn group_by_user(assignments: Vec<UserRole>) -> Vec<(UserEmbed, Vec<UserRole>)> {
+    let mut members: Vec<(UserEmbed, Vec<UserRole>)> = Vec::new();
+    for assignment in assignments {
+        match members.iter_mut().find(|(user, _)| user.id == assignment.user.id) {
+            Some((_, roles)) => roles.push(assignment),
+            None => members.push((assignment.user.clone(), vec![assignment])),
+        }
+    }
+    members
+}
+
+fn role_dto(role: RoleEmbed) -> RoleEmbedDto {
+    RoleEmbedDto {
+        code: role.code,
+        system: role.system,
+        display: role.display,
+        category: RoleCategoryDto {
+            code: role.category.code,
+            system: role.category.system,
+            display: role.category.display,
+            id: role.category.id,
+        },
+    }
+}
+
+fn user_dto(user: UserEmbed) -> UserEmbedDto {
+    UserEmbedDto {
+        nama: UserNameDto {
+             nama_depan: user.nama.nama_depan,
+             nama_belakang: user.nama.nama_belakang,
+        },
+        nik: user.nik,
+        kontak: UserContactDto {
+             email: user.kontak.email,
+             nomor_telepon: user.kontak.nomor_telepon,
+        },
+        lahir: UserBirthDto {
+             tempat: user.lahir.tempat,
+             tanggal: user.lahir.tanggal,
+        },
+        id: user.id,
+    }
+}
+
 pub struct UserRoleService {
     repo: UserRoleRepository,
 }
@@ -43,7 +108,13 @@ impl UserRoleService {
     }
 
     pub async fn create(&self, req: CreateUserRoleRequest) -> Result<(StatusCode, UserRoleResponse), (StatusCode, String)> {
-        let now = Utc::now().to_rfc3339();
+        let now = DateTime::now();
+        let nomor_telepon = crate::phone::normalize(&req.user.kontak.nomor_telepon)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        let valid_from = parse_validity("valid_from", req.valid_from.as_deref())?;
+        let valid_until = parse_validity("valid_until", req.valid_until.as_deref())?;
+        check_validity(req.is_active, valid_from, valid_until, now)?;
+        self.ensure_unassigned(&req.user.id, &req.role.code, &req.organisasi.id, None).await?;
 
         let new_user_role = UserRole {
             id: None,
@@ -66,7 +137,7 @@ impl UserRoleService {
                 nik: req.user.nik,
                 kontak: UserContact {
                     email: req.user.kontak.email,
-                    nomor_telepon: req.user.kontak.nomor_telepon,
+                    nomor_telepon,
                 },
                 lahir: UserBirth {
                     tempat: req.user.lahir.tempat,
@@ -79,7 +150,9 @@ impl UserRoleService {
                 id: req.organisasi.id,
             },
             is_active: req.is_active,
-            updated_at: now.clone(),
+            valid_from,
+            valid_until,
+            updated_at: now,
             created_at: now,
         };
 
@@ -91,7 +164,7 @@ impl UserRoleService {
         let existing = self.repo.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
         let match_item = existing.ok_or((StatusCode::NOT_FOUND, "User Role not found".to_string()))?;
 
-        let now = Utc::now().to_rfc3339();
+        let now = DateTime::now();
 
         let updated_role = if let Some(r) = req.role {
             RoleEmbed {
@@ -110,6 +183,8 @@ impl UserRoleService {
         };
 
         let updated_user = if let Some(u) = req.user {
+             let nomor_telepon = crate::phone::normalize(&u.kontak.nomor_telepon)
+                 .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
              UserEmbed {
                 nama: UserName {
                     nama_depan: u.nama.nama_depan,
@@ -118,7 +193,7 @@ impl UserRoleService {
                 nik: u.nik,
                 kontak: UserContact {
                     email: u.kontak.email,
-                    nomor_telepon: u.kontak.nomor_telepon,
+                    nomor_telepon,
                 },
                 lahir: UserBirth {
                     tempat: u.lahir.tempat,
@@ -139,12 +214,26 @@ impl UserRoleService {
             match_item.organisasi
         };
 
+        let valid_from = match req.valid_from.as_deref() {
+            Some(value) => parse_validity("valid_from", Some(value))?,
+            None => match_item.valid_from,
+        };
+        let valid_until = match req.valid_until.as_deref() {
+            Some(value) => parse_validity("valid_until", Some(value))?,
+            None => match_item.valid_until,
+        };
+        let is_active = req.is_active.unwrap_or(match_item.is_active);
+        check_validity(is_active, valid_from, valid_until, now)?;
+        self.ensure_unassigned(&updated_user.id, &updated_role.code, &updated_org.id, Some(id)).await?;
+
         let updated_item = UserRole {
             id: Some(id),
             role: updated_role,
             user: updated_user,
             organisasi: updated_org,
-            is_active: req.is_active.unwrap_or(match_item.is_active),
+            is_active,
+            valid_from,
+            valid_until,
             created_at: match_item.created_at,
             updated_at: now,
         };
@@ -156,47 +245,181 @@ impl UserRoleService {
         }
     }
 
+    /// 409 when the user already holds the role in the organization, in an assignment other than `except`
+    async fn ensure_unassigned(&self, user_id: &str, role_code: &str, organization_id: &str, except: Option<ObjectId>) -> Result<(), (StatusCode, String)> {
+        let existing = self.repo.find_assignment(user_id, role_code, organization_id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        match existing {
+            Some(existing) if existing.id != except => Err((
+                StatusCode::CONFLICT,
+                format!(
+                    "User {} already has role {} in organization {} (assignment {}); update that assignment instead",
+                    user_id, role_code, organization_id, existing.id.map(|id| id.to_hex()).unwrap_or_default(),
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Create each assignment on its own, reporting per item; one failing does not stop the rest
+    pub async fn bulk_create(&self, assignments: Vec<CreateUserRoleRequest>) -> BulkUserRoleResponse {
+        let mut seen: Vec<(String, String, String)> = Vec::new();
+        let mut results = Vec::with_capacity(assignments.len());
+
+        for (index, assignment) in assignments.into_iter().enumerate() {
+            let key = (assignment.user.id.clone(), assignment.role.code.clone(), assignment.organisasi.id.clone());
+            let outcome = if let Err(e) = crate::validation::validate_item(&assignment) {
+                Err((StatusCode::BAD_REQUEST, e))
+            } else if seen.contains(&key) {
+                Err((StatusCode::CONFLICT, "Repeats an earlier assignment in this request".to_string()))
+            } else {
+                seen.push(key);
+                self.create(assignment).await.map(|(_, created)| created)
+            };
+
+            results.push(match outcome {
+                Ok(created) => BulkUserRoleResult { index, status: "created".to_string(), user_role: Some(created), error: None },
+                Err((status, e)) => BulkUserRoleResult {
+                    index,
+                    status: match status {
+                        StatusCode::BAD_REQUEST => "invalid",
+                        StatusCode::CONFLICT => "duplicate",
+                        _ => "failed",
+                    }.to_string(),
+                    user_role: None,
+                    error: Some(e),
+                },
+            });
+        }
+
+        let created = results.iter().filter(|r| r.user_role.is_some()).count();
+        BulkUserRoleResponse { created, failed: results.len() - created, results }
+    }
+
+    /// Users with roles in the organization, each with their assignments there
+    pub async fn members(&self, organization_id: &str, include_inactive: bool, params: PaginationParams) -> Result<(Vec<OrganizationMemberResponse>, PaginationMeta), String> {
+        let assignments = self.repo.find_by_organization(organization_id, include_inactive, DateTime::now()).await
+            .map_err(|e| e.to_string())?;
+        let members = group_by_user(assignments);
+        let meta = PaginationMeta::new(params.page, params.limit, members.len() as u64);
+
+        let page = members
+            .into_iter()
+            .skip(params.skip() as usize)
+            .take(params.limit as usize)
+            .map(|(user, roles)| OrganizationMemberResponse {
+                user: user_dto(user),
+                roles: roles.into_iter().map(|item| MemberRoleResponse {
+                    user_role_id: item.id.map(|oid| oid.to_hex()).unwrap_or_default(),
+                    role: role_dto(item.role),
+                    is_active: item.is_active,
+                    valid_from: crate::datetime::to_rfc3339_opt(item.valid_from),
+                    valid_until: crate::datetime::to_rfc3339_opt(item.valid_until),
+                }).collect(),
+            })
+            .collect();
+
+        Ok((page, meta))
+    }
+
     pub async fn delete(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
         self.repo.delete(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
     }
 
+    /// Deactivate assignments past `valid_until` and tell every current administrator which
+    pub async fn deactivate_expired(&self, notifications: &NotificationRepository, now: DateTime) -> Result<Vec<UserRoleResponse>, String> {
+        let expired = self.repo.deactivate_expired(now).await.map_err(|e| e.to_string())?;
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let details: Vec<Document> = expired.iter().map(|item| doc! {
+            "userRoleId": item.id.map(|id| id.to_hex()).unwrap_or_default(),
+            "userId": &item.user.id,
+            "userName": format!("{} {}", item.user.nama.nama_depan, item.user.nama.nama_belakang).trim(),
+            "role": &item.role.code,
+            "organizationId": &item.organisasi.id,
+            "organizationName": &item.organisasi.name,
+            "validUntil": crate::datetime::to_rfc3339_opt(item.valid_until),
+        }).collect();
+        let admins = self.repo.find_current_user_ids(crate::rbac::ROLE_ADMIN, now).await.map_err(|e| e.to_string())?;
+        for admin in admins {
+            notifications.create(Notification {
+                id: None,
+                recipient_type: "user".to_string(),
+                recipient_id: admin,
+                kind: "user_roles_expired".to_string(),
+                message: format!("{} role assignment(s) reached their end date and were deactivated.", expired.len()),
+                data: doc! { "userRoles": details.clone() },
+                read_at: None,
+                created_at: now,
+            }).await?;
+        }
+
+        Ok(expired.into_iter().map(|item| self.map_to_response(item)).collect())
+    }
+
     fn map_to_response(&self, item: UserRole) -> UserRoleResponse {
         UserRoleResponse {
             id: item.id.map(|oid| oid.to_hex()).unwrap_or_default(),
-            role: RoleEmbedDto {
-                code: item.role.code,
-                system: item.role.system,
-                display: item.role.display,
-                category: RoleCategoryDto {
-                    code: item.role.category.code,
-                    system: item.role.category.system,
-                    display: item.role.category.display,
-                    id: item.role.category.id,
-                },
-            },
-            user: UserEmbedDto {
-                nama: UserNameDto {
-                     nama_depan: item.user.nama.nama_depan,
-                     nama_belakang: item.user.nama.nama_belakang,
-                },
-                nik: item.user.nik,
-                kontak: UserContactDto {
-                     email: item.user.kontak.email,
-                     nomor_telepon: item.user.kontak.nomor_telepon,
-                },
-                lahir: UserBirthDto {
-                     tempat: item.user.lahir.tempat,
-                     tanggal: item.user.lahir.tanggal,
-                },
-                id: item.user.id,
-            },
+            role: role_dto(item.role),
+            user: user_dto(item.user),
             organisasi: OrganizationEmbedDto {
                 name: item.organisasi.name,
                 id: item.organisasi.id,
             },
             is_active: item.is_active,
-            updated_at: item.updated_at,
-            created_at: item.created_at,
+            valid_from: crate::datetime::to_rfc3339_opt(item.valid_from),
+            valid_until: crate::datetime::to_rfc3339_opt(item.valid_until),
+            updated_at: crate::datetime::to_rfc3339(item.updated_at),
+            created_at: crate::datetime::to_rfc3339(item.created_at),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignment(user_id: &str, nama_depan: &str, role: &str) -> UserRole {
+        let now = DateTime::now();
+        UserRole {
+            id: Some(ObjectId::new()),
+            role: RoleEmbed {
+                code: role.to_string(),
+                system: "roles".to_string(),
+                display: role.to_string(),
+                category: crate::models::RoleCategory { code: "staff".to_string(), system: "roles".to_string(), display: "Staff".to_string(), id: "c1".to_string() },
+            },
+            user: UserEmbed {
+                nama: UserName { nama_depan: nama_depan.to_string(), nama_belakang: String::new() },
+                nik: "3201010101010001".to_string(),
+                kontak: UserContact { email: format!("{}@example.com", user_id), nomor_telepon: "+6281234567890".to_string() },
+                lahir: UserBirth { tempat: "Bandung".to_string(), tanggal: "1990-01-01".to_string() },
+                id: user_id.to_string(),
+            },
+            organisasi: OrganizationEmbed { name: "Klinik".to_string(), id: "o1".to_string() },
+            is_active: true,
+            valid_from: None,
+            valid_until: None,
+            updated_at: now,
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn groups_roles_under_each_member() {
+        // A stale name on one copy must not split the member in two
+        let members = group_by_user(vec![
+            assignment("u1", "Andi", "doctor"),
+            assignment("u2", "Budi", "nurse"),
+            assignment("u1", "Andy", "admin"),
+        ]);
+
+        let grouped: Vec<(&str, Vec<&str>)> = members
+            .iter()
+            .map(|(user, roles)| (user.id.as_str(), roles.iter().map(|r| r.role.code.as_str()).collect()))
+            .collect();
+        assert_eq!(grouped, vec![("u1", vec!["doctor", "admin"]), ("u2", vec!["nurse"])]);
+    }
+}
@@ -1,10 +1,11 @@
 use axum::http::StatusCode;
 use bcrypt::{hash, DEFAULT_COST};
-use mongodb::bson::oid::ObjectId;
+use mongodb::bson::{oid::ObjectId, DateTime};
 
 use crate::dto::auth::RegisterRequest;
 use crate::dto::user::{UserResponse, UpdateUserRequest};
 use crate::models::User;
+use crate::phone;
 use crate::repository::UserRepository;
 use crate::pagination::{PaginationParams, PaginationMeta};
 
@@ -28,8 +29,10 @@ impl UserService {
             id: user.id.map(|id| id.to_hex()).unwrap_or_default(),
             email: user.email,
             name: user.name,
-            created_at: user.created_at,
-            updated_at: user.updated_at,
+            phone: user.phone,
+            email_verified_at: user.email_verified_at,
+            created_at: crate::datetime::to_rfc3339(user.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(user.updated_at),
         }
     }
 
@@ -52,12 +55,24 @@ impl UserService {
         }
     }
 
+    /// Users whose stored phone matches `raw` once normalized
+    pub async fn find_by_phone(&self, raw: &str) -> Result<Vec<UserResponse>, (StatusCode, String)> {
+        let phone = phone::normalize(raw).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        match self.repo.find_by_phone(&phone).await {
+            Ok(users) => Ok(users.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
     pub async fn create(&self, request: RegisterRequest) -> Result<(StatusCode, UserResponse), (StatusCode, String)> {
         // Check if email already exists
         if let Ok(Some(_)) = self.repo.find_by_email(&request.email).await {
             return Err((StatusCode::CONFLICT, "Email already registered".to_string()));
         }
 
+        let phone = request.phone.as_deref().map(phone::normalize).transpose()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
         // Hash password
         let password_hash = Self::hash_password(&request.password)
             .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
@@ -68,10 +83,12 @@ impl UserService {
             email: request.email.to_lowercase(),
             password: password_hash,
             name: request.name,
+            phone,
             refresh_token: None,
             reset_token: None,
             reset_token_expiry: None,
-            created_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            email_verified_at: None,
+            created_at: DateTime::now(),
             updated_at: None,
         };
 
@@ -93,6 +110,10 @@ impl UserService {
             user.name = name;
         }
 
+        if let Some(raw) = request.phone {
+            user.phone = Some(phone::normalize(&raw).map_err(|e| (StatusCode::BAD_REQUEST, e))?);
+        }
+
         if let Some(email) = request.email {
             // Check if new email is already taken by another user
             if email != user.email {
@@ -100,6 +121,8 @@ impl UserService {
                     return Err((StatusCode::CONFLICT, "Email already in use".to_string()));
                 }
                 user.email = email.to_lowercase();
+                // A new address has to be verified again
+                user.email_verified_at = None;
             }
         }
 
@@ -108,7 +131,7 @@ impl UserService {
                 .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
         }
 
-        user.updated_at = Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string());
+        user.updated_at = Some(DateTime::now());
 
         // Update in database
         let updated_user = self.repo.update(id, user).await
@@ -0,0 +1,208 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::dto::waitlist::{JoinWaitlistRequest, WaitlistEntryResponse, WaitlistQuery};
+use crate::models::{Appointment, Notification, WaitlistEntry};
+use crate::repository::{AppointmentRepository, NotificationRepository, WaitlistRepository};
+use crate::refs::Ref;
+use crate::status::AppointmentStatus;
+use crate::timezone::ClinicTimezone;
+
+pub const WAITLIST_WAITING: &str = "waiting";
+pub const WAITLIST_HELD: &str = "held";
+pub const WAITLIST_BOOKED: &str = "booked";
+pub const WAITLIST_EXPIRED: &str = "expired";
+pub const WAITLIST_CANCELLED: &str = "cancelled";
+
+pub struct WaitlistService {
+    waitlist: WaitlistRepository,
+    appointments: AppointmentRepository,
+    notifications: NotificationRepository,
+    timezone: ClinicTimezone,
+}
+
+impl WaitlistService {
+    pub fn new(waitlist: WaitlistRepository, appointments: AppointmentRepository, notifications: NotificationRepository, timezone: ClinicTimezone) -> Self {
+        Self { waitlist, appointments, notifications, timezone }
+    }
+
+    fn map_to_response(entry: WaitlistEntry) -> WaitlistEntryResponse {
+        WaitlistEntryResponse {
+            id: entry.id.map(|id| id.to_hex()).unwrap_or_default(),
+            patient_id: entry.patient_id,
+            doctor_id: entry.doctor_id,
+            date: entry.date,
+            time: entry.time,
+            status: entry.status,
+            hold_appointment_id: entry.hold_appointment_id,
+            hold_expires_at: entry.hold_expires_at,
+            notified_at: entry.notified_at,
+            created_at: crate::datetime::to_rfc3339(entry.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(entry.updated_at),
+        }
+    }
+
+    pub async fn join(&self, request: JoinWaitlistRequest) -> Result<WaitlistEntryResponse, (StatusCode, String)> {
+        let existing = self.waitlist.find_open_for_patient(&request.patient_id, &request.doctor_id, &request.date).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        if existing.is_some() {
+            return Err((StatusCode::CONFLICT, "Patient is already on this waitlist".to_string()));
+        }
+
+        let entry = WaitlistEntry {
+            id: None,
+            patient_id: request.patient_id,
+            doctor_id: request.doctor_id,
+            date: request.date,
+            time: request.time,
+            status: WAITLIST_WAITING.to_string(),
+            hold_appointment_id: None,
+            hold_expires_at: None,
+            notified_at: None,
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+
+        match self.waitlist.create(entry).await {
+            Ok(created) => Ok(Self::map_to_response(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list(&self, query: WaitlistQuery) -> Result<Vec<WaitlistEntryResponse>, (StatusCode, String)> {
+        match self.waitlist.find(query.doctor_id.as_deref(), query.date.as_deref(), query.status.as_deref()).await {
+            Ok(entries) => Ok(entries.into_iter().map(Self::map_to_response).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Accept an offered slot, turning the held appointment into a scheduled one.
+    pub async fn confirm(&self, id: ObjectId) -> Result<WaitlistEntryResponse, (StatusCode, String)> {
+        let entry = self.waitlist.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Waitlist entry not found".to_string()))?;
+
+        let now = Utc::now().to_rfc3339();
+        if entry.status != WAITLIST_HELD {
+            return Err((StatusCode::CONFLICT, format!("Waitlist entry is {}, not held", entry.status)));
+        }
+        if entry.hold_expires_at.as_deref().is_some_and(|expires| expires < now.as_str()) {
+            return Err((StatusCode::CONFLICT, "The hold on this slot has expired".to_string()));
+        }
+
+        let confirmed = self.waitlist.transition(id, WAITLIST_HELD, doc! { "status": WAITLIST_BOOKED, "updated_at": DateTime::now() }).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Waitlist entry is no longer held".to_string()))?;
+
+        if let Some(appointment_id) = confirmed.hold_appointment_id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) {
+            self.appointments.update_many_by_ids(&[appointment_id], doc! { "status": AppointmentStatus::Scheduled }).await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        }
+
+        Ok(Self::map_to_response(confirmed))
+    }
+
+    /// Leave the waitlist. Returns the entry and the id of a held appointment that was released.
+    pub async fn cancel(&self, id: ObjectId) -> Result<(WaitlistEntryResponse, Option<String>), (StatusCode, String)> {
+        let entry = self.waitlist.find_by_id(id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::NOT_FOUND, "Waitlist entry not found".to_string()))?;
+        if entry.status != WAITLIST_WAITING && entry.status != WAITLIST_HELD {
+            return Err((StatusCode::CONFLICT, format!("Waitlist entry is already {}", entry.status)));
+        }
+
+        let cancelled = self.waitlist
+            .transition(id, &entry.status, doc! { "status": WAITLIST_CANCELLED, "updated_at": DateTime::now() })
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+            .ok_or((StatusCode::CONFLICT, "Waitlist entry changed concurrently".to_string()))?;
+
+        let released = if entry.status == WAITLIST_HELD {
+            self.release_hold(&entry).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?
+        } else {
+            None
+        };
+
+        Ok((Self::map_to_response(cancelled), released))
+    }
+
+    async fn release_hold(&self, entry: &WaitlistEntry) -> Result<Option<String>, String> {
+        let Some(appointment_id) = entry.hold_appointment_id.as_deref().and_then(|id| ObjectId::parse_str(id).ok()) else {
+            return Ok(None);
+        };
+        self.appointments.update_many_by_ids(&[appointment_id], doc! { "status": AppointmentStatus::Cancelled }).await?;
+        Ok(Some(appointment_id.to_hex()))
+    }
+
+    /// Offer a released slot to the longest-waiting patient: hold it with an appointment until
+    /// `hold_expires_at` and notify the patient. Nothing happens when the slot was rebooked.
+    pub async fn promote(&self, doctor_id: &str, date: &str, time: &str, hold_expires_at: &str) -> Result<Option<WaitlistEntry>, String> {
+        let slot = [(date.to_string(), time.to_string())];
+        if !self.appointments.find_conflicts(doctor_id, &slot, &[]).await?.is_empty() {
+            return Ok(None);
+        }
+
+        let now = Utc::now().to_rfc3339();
+        let appointment_id = ObjectId::new();
+        let set = doc! {
+            "status": WAITLIST_HELD,
+            "holdAppointmentId": appointment_id.to_hex(),
+            "holdExpiresAt": hold_expires_at,
+            "notified_at": &now,
+            "updated_at": DateTime::now(),
+        };
+        let Some(entry) = self.waitlist.claim_next(doctor_id, date, time, set).await? else {
+            return Ok(None);
+        };
+
+        self.appointments.insert(Appointment {
+            id: Some(appointment_id),
+            patient_id: Ref::parse_field("patient_id", &entry.patient_id).map_err(|(_, e)| e)?,
+            doctor_id: Ref::parse_field("doctor_id", doctor_id).map_err(|(_, e)| e)?,
+            date: date.to_string(),
+            time: time.to_string(),
+            status: AppointmentStatus::Held,
+            queue_number: None,
+            checked_in_at: None,
+            called_at: None,
+            completed_at: None,
+            series_id: None,
+            mode: None,
+            organization_id: None,
+            timezone: Some(self.timezone.name().to_string()),
+            starts_at: self.timezone.to_utc(date, time).ok().map(crate::datetime::from_chrono),
+            deleted_at: None,
+            updated_at: None,
+        }).await?;
+
+        self.notifications.create(Notification {
+            id: None,
+            recipient_type: "patient".to_string(),
+            recipient_id: entry.patient_id.clone(),
+            kind: "waitlist_offer".to_string(),
+            message: format!("A slot on {} at {} is held for you until {}. Confirm to keep it.", date, time, hold_expires_at),
+            data: doc! {
+                "waitlistId": entry.id.map(|id| id.to_hex()).unwrap_or_default(),
+                "appointmentId": appointment_id.to_hex(),
+                "holdExpiresAt": hold_expires_at,
+            },
+            read_at: None,
+            created_at: DateTime::now(),
+        }).await?;
+
+        Ok(Some(entry))
+    }
+
+    /// Expire holds past their deadline, returning the released appointment ids.
+    pub async fn expire_holds(&self, now: &str) -> Result<Vec<String>, String> {
+        let mut released = Vec::new();
+        for entry in self.waitlist.find_expired_holds(now).await? {
+            let Some(id) = entry.id else { continue };
+            let expired = self.waitlist.transition(id, WAITLIST_HELD, doc! { "status": WAITLIST_EXPIRED, "updated_at": DateTime::now() }).await?;
+            if expired.is_some() {
+                released.extend(self.release_hold(&entry).await?);
+            }
+        }
+        Ok(released)
+    }
+}
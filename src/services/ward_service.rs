@@ -0,0 +1,236 @@
+use axum::http::StatusCode;
+use chrono::Utc;
+use mongodb::bson::{doc, oid::ObjectId, DateTime};
+use crate::delete_policy::DeleteGuard;
+use crate::dto::ward::{
+    BedResponse, CreateBedRequest, CreateWardRequest, OccupancyCounts, OccupancyResponse, UpdateBedRequest, UpdateWardRequest,
+    WardOccupancy, WardResponse,
+};
+use crate::models::{Bed, Ward};
+use crate::repository::bed::BedOccupancyRow;
+use crate::repository::{BedRepository, WardRepository};
+use crate::status::BedStatus;
+
+pub struct WardService {
+    wards: WardRepository,
+    beds: BedRepository,
+    deletes: DeleteGuard,
+}
+
+impl From<&BedOccupancyRow> for OccupancyCounts {
+    fn from(row: &BedOccupancyRow) -> Self {
+        let mut counts = OccupancyCounts {
+            total: row.total,
+            available: row.available,
+            occupied: row.occupied,
+            cleaning: row.cleaning,
+            maintenance: row.maintenance,
+            occupancy_rate: 0.0,
+        };
+        counts.occupancy_rate = occupancy_rate(&counts);
+        counts
+    }
+}
+
+/// Occupied share of beds, rounded to four decimals
+fn occupancy_rate(counts: &OccupancyCounts) -> f64 {
+    if counts.total == 0 {
+        return 0.0;
+    }
+    (counts.occupied as f64 / counts.total as f64 * 10_000.0).round() / 10_000.0
+}
+
+/// Sum of the counts of every ward
+fn totals<'a>(counts: impl IntoIterator<Item = &'a OccupancyCounts>) -> OccupancyCounts {
+    let mut totals = counts.into_iter().fold(OccupancyCounts::default(), |mut totals, c| {
+        totals.total += c.total;
+        totals.available += c.available;
+        totals.occupied += c.occupied;
+        totals.cleaning += c.cleaning;
+        totals.maintenance += c.maintenance;
+        totals
+    });
+    totals.occupancy_rate = occupancy_rate(&totals);
+    totals
+}
+
+impl WardService {
+    pub fn new(wards: WardRepository, beds: BedRepository, deletes: DeleteGuard) -> Self {
+        Self { wards, beds, deletes }
+    }
+
+    fn map_ward(ward: Ward) -> WardResponse {
+        WardResponse {
+            id: ward.id.map(|id| id.to_hex()).unwrap_or_default(),
+            code: ward.code,
+            name: ward.name,
+            class: ward.class,
+            organization_id: ward.organization_id,
+            created_at: crate::datetime::to_rfc3339(ward.created_at),
+            updated_at: crate::datetime::to_rfc3339_opt(ward.updated_at),
+        }
+    }
+
+    pub(crate) fn map_bed(bed: Bed) -> BedResponse {
+        BedResponse {
+            id: bed.id.map(|id| id.to_hex()).unwrap_or_default(),
+            ward_id: bed.ward_id,
+            code: bed.code,
+            status: bed.status,
+            patient_id: bed.patient_id,
+            admission_id: bed.admission_id,
+            updated_at: crate::datetime::to_rfc3339_opt(bed.updated_at),
+        }
+    }
+
+    pub async fn create_ward(&self, request: CreateWardRequest) -> Result<WardResponse, (StatusCode, String)> {
+        let ward = Ward {
+            id: None,
+            code: request.code.trim().to_string(),
+            name: request.name.trim().to_string(),
+            class: request.class.map(|c| c.trim().to_string()).filter(|c| !c.is_empty()),
+            organization_id: request.organization_id,
+            created_at: DateTime::now(),
+            updated_at: None,
+        };
+        match self.wards.create(ward).await {
+            Ok(created) => Ok(Self::map_ward(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list_wards(&self, organization_id: Option<&str>) -> Result<Vec<WardResponse>, (StatusCode, String)> {
+        match self.wards.find_all(organization_id).await {
+            Ok(wards) => Ok(wards.into_iter().map(Self::map_ward).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn get_ward(&self, id: ObjectId) -> Result<Option<WardResponse>, (StatusCode, String)> {
+        match self.wards.find_by_id(id).await {
+            Ok(ward) => Ok(ward.map(Self::map_ward)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn update_ward(&self, id: ObjectId, request: UpdateWardRequest) -> Result<WardResponse, (StatusCode, String)> {
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(code) = request.code { set.insert("code", code.trim()); }
+        if let Some(name) = request.name { set.insert("name", name.trim()); }
+        if let Some(class) = request.class { set.insert("class", class.trim()); }
+
+        match self.wards.update_fields(id, set).await {
+            Ok(Some(ward)) => Ok(Self::map_ward(ward)),
+            Ok(None) => Err((StatusCode::NOT_FOUND, "Ward not found".to_string())),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Refused with 409 while the ward has beds (`wards.beds` in `DELETE_POLICIES`)
+    pub async fn delete_ward(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        if self.wards.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?.is_none() {
+            return Ok(false);
+        }
+        let mut session = self.deletes.begin().await?;
+        let deleted = async {
+            self.deletes.prepare(&mut session, "wards", id).await?;
+            self.wards.delete(&mut session, id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+        }.await;
+        crate::services::finish(session, deleted).await
+    }
+
+    pub async fn create_bed(&self, ward_id: ObjectId, request: CreateBedRequest) -> Result<BedResponse, (StatusCode, String)> {
+        if self.wards.find_by_id(ward_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?.is_none() {
+            return Err((StatusCode::NOT_FOUND, "Ward not found".to_string()));
+        }
+        let bed = Bed {
+            id: None,
+            ward_id: ward_id.to_hex(),
+            code: request.code.trim().to_string(),
+            status: BedStatus::Available,
+            patient_id: None,
+            admission_id: None,
+            updated_at: None,
+        };
+        match self.beds.create(bed).await {
+            Ok(created) => Ok(Self::map_bed(created)),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    pub async fn list_beds(&self, ward_id: ObjectId) -> Result<Vec<BedResponse>, (StatusCode, String)> {
+        match self.beds.find_by_ward(&ward_id.to_hex()).await {
+            Ok(beds) => Ok(beds.into_iter().map(Self::map_bed).collect()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+        }
+    }
+
+    /// Rename a bed or mark it available, cleaning or under maintenance. Occupied beds are
+    /// left to admissions.
+    pub async fn update_bed(&self, id: ObjectId, request: UpdateBedRequest) -> Result<BedResponse, (StatusCode, String)> {
+        if request.status == Some(BedStatus::Occupied) {
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, "status: beds become occupied by admitting a patient".to_string()));
+        }
+        let mut set = doc! { "updatedAt": DateTime::now() };
+        if let Some(code) = request.code { set.insert("code", code.trim()); }
+        if let Some(status) = request.status { set.insert("status", status); }
+
+        match self.beds.update_unoccupied(id, set).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(bed) => Ok(Self::map_bed(bed)),
+            None => match self.beds.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+                Some(_) => Err((StatusCode::CONFLICT, "Bed is occupied; discharge or transfer the patient first".to_string())),
+                None => Err((StatusCode::NOT_FOUND, "Bed not found".to_string())),
+            },
+        }
+    }
+
+    pub async fn delete_bed(&self, id: ObjectId) -> Result<bool, (StatusCode, String)> {
+        if self.beds.delete(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            return Ok(true);
+        }
+        match self.beds.find_by_id(id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))? {
+            Some(_) => Err((StatusCode::CONFLICT, "Bed is occupied; discharge or transfer the patient first".to_string())),
+            None => Ok(false),
+        }
+    }
+
+    /// Bed counts per ward, computed from the beds at request time
+    pub async fn occupancy(&self, organization_id: Option<&str>) -> Result<OccupancyResponse, (StatusCode, String)> {
+        let wards = self.wards.find_all(organization_id).await.map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+        let ward_ids: Vec<String> = wards.iter().filter_map(|w| w.id.map(|id| id.to_hex())).collect();
+        let rows = self.beds.occupancy(organization_id.map(|_| ward_ids.as_slice())).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))?;
+
+        let wards: Vec<WardOccupancy> = wards
+            .into_iter()
+            .map(|ward| {
+                let ward_id = ward.id.map(|id| id.to_hex()).unwrap_or_default();
+                let counts = rows.iter().find(|row| row.ward_id == ward_id).map(OccupancyCounts::from).unwrap_or_default();
+                WardOccupancy { ward_id, code: ward.code, name: ward.name, counts }
+            })
+            .collect();
+
+        Ok(OccupancyResponse {
+            totals: totals(wards.iter().map(|w| &w.counts)),
+            wards,
+            generated_at: Utc::now().to_rfc3339(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occupancy_rates_and_totals() {
+        let a = OccupancyCounts::from(&BedOccupancyRow { ward_id: "a".into(), total: 3, available: 1, occupied: 2, cleaning: 0, maintenance: 0 });
+        let b = OccupancyCounts::from(&BedOccupancyRow { ward_id: "b".into(), total: 1, available: 0, occupied: 0, cleaning: 0, maintenance: 1 });
+        assert_eq!(a.occupancy_rate, 0.6667);
+        assert_eq!(b.occupancy_rate, 0.0);
+
+        let sum = totals([&a, &b, &OccupancyCounts::default()]);
+        assert_eq!((sum.total, sum.occupied, sum.maintenance), (4, 2, 1));
+        assert_eq!(sum.occupancy_rate, 0.5);
+    }
+}
@@ -0,0 +1,220 @@
+//! Counters and cached values shared between replicas.
+//!
+//! Rate-limit windows, cache generations and cached values live in this process by default,
+//! which is right for a single instance; with several replicas each would count, invalidate
+//! and cache on its own. Built with the `redis` feature and given `REDIS_URL`, `SharedStore`
+//! keeps them in Redis under `REDIS_KEY_PREFIX` (default `rme:`): a window is a key `INCR`ed
+//! until it expires with the window, a generation a plain counter, a value a string key with
+//! its time to live. Used by the self-registration limiter (see `crate::self_registration`),
+//! to invalidate the code cache on every replica (see `crate::code_cache`) and for the BPJS
+//! eligibility cache (see `crate::bpjs`). A Redis error is logged and the call is answered
+//! in process, so an outage degrades to per-instance limits and caches rather than failing
+//! requests.
+//!
+//! The service keeps no idempotency keys and no token denylist (revoking a kiosk or service
+//! account is checked against MongoDB on every request), so neither goes through here.
+
+use std::collections::HashMap;
+use std::env;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_KEY_PREFIX: &str = "rme:";
+
+#[derive(Default)]
+pub struct SharedStore {
+    /// Count and, for windows, when it resets
+    counters: Mutex<HashMap<String, (u64, Option<Instant>)>>,
+    /// Cached values and when they expire
+    values: Mutex<HashMap<String, (String, Instant)>>,
+    #[cfg(feature = "redis")]
+    redis: Option<(redis::aio::ConnectionManager, String)>,
+}
+
+impl SharedStore {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Redis when built with the `redis` feature and `REDIS_URL` is set and reachable
+    pub async fn from_env() -> Self {
+        let url = env::var("REDIS_URL").ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let Some(url) = url else {
+            return Self::in_memory();
+        };
+        Self::connect(&url).await
+    }
+
+    #[cfg(feature = "redis")]
+    async fn connect(url: &str) -> Self {
+        let prefix = env::var("REDIS_KEY_PREFIX").unwrap_or_else(|_| DEFAULT_KEY_PREFIX.to_string());
+        let client = match redis::Client::open(url) {
+            Ok(client) => client,
+            Err(e) => {
+                tracing::warn!(error = %e, "Invalid REDIS_URL, keeping rate limits and caches in process");
+                return Self::in_memory();
+            }
+        };
+        match redis::aio::ConnectionManager::new(client).await {
+            Ok(connection) => {
+                tracing::info!("Sharing rate limits and caches through Redis");
+                Self { redis: Some((connection, prefix)), ..Self::default() }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Connecting to Redis failed, keeping rate limits and caches in process");
+                Self::in_memory()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "redis"))]
+    async fn connect(_url: &str) -> Self {
+        tracing::warn!("REDIS_URL is set but this build has no redis feature; keeping rate limits and caches in process");
+        Self::in_memory()
+    }
+
+    /// Whether other replicas see the same counters
+    pub fn is_shared(&self) -> bool {
+        #[cfg(feature = "redis")]
+        let shared = self.redis.is_some();
+        #[cfg(not(feature = "redis"))]
+        let shared = false;
+        shared
+    }
+
+    /// Counts a hit in the fixed window `key`, which starts with its first hit; returns the
+    /// hits so far, this one included, and the time until the window resets
+    pub async fn hit(&self, key: &str, window: Duration, now: Instant) -> (u64, Duration) {
+        #[cfg(feature = "redis")]
+        if let Some((connection, prefix)) = &self.redis {
+            let key = format!("{}{}", prefix, key);
+            let result: redis::RedisResult<(u64, i64)> = redis::pipe()
+                .atomic()
+                .cmd("SET").arg(&key).arg(0).arg("PX").arg(window.as_millis() as u64).arg("NX").ignore()
+                .cmd("INCR").arg(&key)
+                .cmd("PTTL").arg(&key)
+                .query_async(&mut connection.clone())
+                .await;
+            match result {
+                Ok((hits, ttl)) => return (hits, Duration::from_millis(ttl.max(0) as u64)),
+                Err(e) => tracing::warn!(key = %key, error = %e, "Redis rate limit failed, counting in process"),
+            }
+        }
+
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop finished windows so the map only holds recent keys
+        counters.retain(|_, (_, resets)| resets.is_none_or(|at| at > now));
+        let (hits, resets) = counters.entry(key.to_string()).or_insert((0, Some(now + window)));
+        *hits += 1;
+        (*hits, resets.map_or(window, |at| at.saturating_duration_since(now)))
+    }
+
+    /// Current value of the counter `key`, 0 when it was never incremented
+    pub async fn counter(&self, key: &str) -> u64 {
+        #[cfg(feature = "redis")]
+        if let Some((connection, prefix)) = &self.redis {
+            let result: redis::RedisResult<Option<u64>> = redis::cmd("GET")
+                .arg(format!("{}{}", prefix, key))
+                .query_async(&mut connection.clone())
+                .await;
+            match result {
+                Ok(value) => return value.unwrap_or_default(),
+                Err(e) => tracing::warn!(key, error = %e, "Reading a counter from Redis failed"),
+            }
+        }
+
+        let counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        counters.get(key).map(|(value, _)| *value).unwrap_or_default()
+    }
+
+    /// Increments the counter `key` and returns its new value
+    pub async fn increment(&self, key: &str) -> u64 {
+        #[cfg(feature = "redis")]
+        if let Some((connection, prefix)) = &self.redis {
+            let result: redis::RedisResult<u64> = redis::cmd("INCR")
+                .arg(format!("{}{}", prefix, key))
+                .query_async(&mut connection.clone())
+                .await;
+            match result {
+                Ok(value) => return value,
+                Err(e) => tracing::warn!(key, error = %e, "Incrementing a counter in Redis failed"),
+            }
+        }
+
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        let (value, _) = counters.entry(key.to_string()).or_insert((0, None));
+        *value += 1;
+        *value
+    }
+
+    /// The value cached under `key`, unless it expired
+    pub async fn get(&self, key: &str, now: Instant) -> Option<String> {
+        #[cfg(feature = "redis")]
+        if let Some((connection, prefix)) = &self.redis {
+            let result: redis::RedisResult<Option<String>> = redis::cmd("GET")
+                .arg(format!("{}{}", prefix, key))
+                .query_async(&mut connection.clone())
+                .await;
+            match result {
+                Ok(value) => return value,
+                Err(e) => tracing::warn!(key, error = %e, "Reading a value from Redis failed, using the in-process cache"),
+            }
+        }
+
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(key).filter(|(_, expires)| *expires > now).map(|(value, _)| value.clone())
+    }
+
+    /// Caches `value` under `key` for `ttl`
+    pub async fn set(&self, key: &str, value: &str, ttl: Duration, now: Instant) {
+        #[cfg(feature = "redis")]
+        if let Some((connection, prefix)) = &self.redis {
+            let result: redis::RedisResult<()> = redis::cmd("SET")
+                .arg(format!("{}{}", prefix, key))
+                .arg(value)
+                .arg("PX")
+                .arg(ttl.as_millis().max(1) as u64)
+                .query_async(&mut connection.clone())
+                .await;
+            match result {
+                Ok(()) => return,
+                Err(e) => tracing::warn!(key, error = %e, "Writing a value to Redis failed, caching in process"),
+            }
+        }
+
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        // Drop expired values so the map only holds live entries
+        values.retain(|_, (_, expires)| *expires > now);
+        values.insert(key.to_string(), (value.to_string(), now + ttl));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn windows_reset_and_counters_persist_in_process() {
+        let store = SharedStore::in_memory();
+        let window = Duration::from_secs(60);
+        let start = Instant::now();
+        assert_eq!(store.hit("a", window, start).await, (1, window));
+        assert_eq!(store.hit("a", window, start + Duration::from_secs(45)).await, (2, Duration::from_secs(15)));
+        assert_eq!(store.hit("a", window, start + window).await, (1, window));
+
+        assert_eq!(store.counter("generation").await, 0);
+        assert_eq!(store.increment("generation").await, 1);
+        assert_eq!(store.counter("generation").await, 1);
+        assert!(!store.is_shared());
+    }
+
+    #[tokio::test]
+    async fn values_expire_with_their_ttl() {
+        let store = SharedStore::in_memory();
+        let start = Instant::now();
+        assert_eq!(store.get("v", start).await, None);
+        store.set("v", "cached", Duration::from_secs(60), start).await;
+        assert_eq!(store.get("v", start + Duration::from_secs(59)).await.as_deref(), Some("cached"));
+        assert_eq!(store.get("v", start + Duration::from_secs(60)).await, None);
+    }
+}
@@ -0,0 +1,82 @@
+//! Small numeric helpers for clinical trend calculations.
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+/// `time` values above this are treated as epoch milliseconds rather than seconds.
+const MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Convert an observation `time` (Unix epoch seconds or milliseconds) into fractional days.
+pub fn epoch_to_days(time: i64) -> f64 {
+    if time.abs() > MILLIS_THRESHOLD {
+        time as f64 / (SECONDS_PER_DAY * 1000.0)
+    } else {
+        time as f64 / SECONDS_PER_DAY
+    }
+}
+
+/// Width of `hours` in the same epoch unit (seconds or milliseconds) as `reference_time`.
+pub fn epoch_delta_hours(reference_time: i64, hours: i64) -> i64 {
+    if reference_time.abs() > MILLIS_THRESHOLD {
+        hours * 3_600_000
+    } else {
+        hours * 3_600
+    }
+}
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Ordinary least-squares slope of `y` over `x`; `None` with fewer than two distinct `x` values.
+pub fn linear_regression_slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+        let dx = x - mean_x;
+        (cov + dx * (y - mean_y), var + dx * dx)
+    });
+
+    if variance == 0.0 {
+        return None;
+    }
+    Some(covariance / variance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slope_of_a_line() {
+        let points = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+        assert!((linear_regression_slope(&points).unwrap() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slope_needs_spread_in_x() {
+        assert_eq!(linear_regression_slope(&[(1.0, 2.0)]), None);
+        assert_eq!(linear_regression_slope(&[(1.0, 2.0), (1.0, 4.0)]), None);
+    }
+
+    #[test]
+    fn epoch_units_are_detected() {
+        assert_eq!(epoch_to_days(86_400), 1.0);
+        assert_eq!(epoch_to_days(1_700_000_000_000), 1_700_000_000_000.0 / 86_400_000.0);
+        assert_eq!(epoch_delta_hours(1_700_000_000, 2), 7_200);
+        assert_eq!(epoch_delta_hours(1_700_000_000_000, 2), 7_200_000);
+    }
+
+    #[test]
+    fn mean_of_values() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(mean(&[1.0, 2.0, 6.0]), Some(3.0));
+    }
+}
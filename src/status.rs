@@ -0,0 +1,487 @@
+//! Typed status and gender fields.
+//!
+//! Each type stores and serializes as a lowercase string. Values written before these types
+//! existed that match none of the variants deserialize into `Other` and keep their text, so
+//! old documents still load and are returned unchanged; DTO validation rejects them on
+//! input with the list of accepted values.
+
+use std::fmt;
+use mongodb::bson::Bson;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use validator::ValidationError;
+
+macro_rules! string_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident { $($variant:ident => $text:literal $(| $alias:literal)*),+ $(,)? }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)+
+            /// A stored value matching no variant
+            Other(String),
+        }
+
+        impl $name {
+            /// Canonical values, as stored
+            pub const KNOWN: &'static [&'static str] = &[$($text),+];
+
+            /// A known value or alias, ignoring case and surrounding whitespace.
+            pub fn parse(value: &str) -> Option<Self> {
+                match value.trim().to_lowercase().as_str() {
+                    $($text $(| $alias)* => Some(Self::$variant),)+
+                    _ => None,
+                }
+            }
+
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $text,)+
+                    Self::Other(value) => value,
+                }
+            }
+
+            pub fn is_known(&self) -> bool {
+                !matches!(self, Self::Other(_))
+            }
+
+            /// `#[validate(custom = ..)]` check rejecting values outside `KNOWN`
+            pub fn validate(value: &Self) -> Result<(), ValidationError> {
+                if value.is_known() {
+                    return Ok(());
+                }
+                let mut error = ValidationError::new("unknown_variant");
+                error.message = Some(format!("'{}' is not one of {}", value.as_str(), Self::KNOWN.join(", ")).into());
+                Err(error)
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                Self::parse(&value).unwrap_or(Self::Other(value))
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(value: &str) -> Self {
+                Self::from(value.to_string())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.as_str() == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.as_str() == *other
+            }
+        }
+
+        impl From<$name> for Bson {
+            fn from(value: $name) -> Self {
+                Bson::String(value.as_str().to_string())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer).map(Self::from)
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// Lifecycle of an appointment. `held` reserves a slot offered from the waitlist;
+    /// `pending_confirmation` holds one booked through the public API until staff confirm it.
+    AppointmentStatus {
+        Scheduled => "scheduled",
+        Held => "held",
+        CheckedIn => "checked_in",
+        Called => "called",
+        InProgress => "in_progress",
+        Completed => "completed",
+        Cancelled => "cancelled",
+        NoShow => "no_show",
+        PendingConfirmation => "pending_confirmation",
+    }
+}
+
+impl AppointmentStatus {
+    /// Whether the appointment still occupies its slot
+    pub fn occupies_slot(&self) -> bool {
+        *self != AppointmentStatus::Cancelled
+    }
+}
+
+string_enum! {
+    DoctorStatus {
+        Active => "active",
+        Inactive => "inactive",
+        OnLeave => "on_leave",
+    }
+}
+
+string_enum! {
+    InsuranceStatus {
+        Active => "active",
+        Inactive => "inactive",
+        Suspended => "suspended",
+    }
+}
+
+string_enum! {
+    /// Kinds of entry in a patient's timeline, see `crate::timeline`
+    TimelineEntryType {
+        Encounter => "encounter",
+        Observation => "observation",
+        File => "file",
+        Appointment => "appointment",
+    }
+}
+
+string_enum! {
+    /// Only active clients are billed, and only within their contract period
+    CorporateClientStatus {
+        Active => "active",
+        Inactive => "inactive",
+    }
+}
+
+string_enum! {
+    /// Accepts the Indonesian forms (`L`/`P`, `laki-laki`/`perempuan`) on input and stores
+    /// the English one.
+    Gender {
+        Male => "male" | "l" | "laki-laki" | "laki laki" | "m" | "pria",
+        Female => "female" | "p" | "perempuan" | "f" | "wanita",
+    }
+}
+
+string_enum! {
+    /// Occupancy of an inpatient bed. Only `available` beds can be assigned.
+    BedStatus {
+        Available => "available",
+        Occupied => "occupied",
+        Cleaning => "cleaning",
+        Maintenance => "maintenance",
+    }
+}
+
+string_enum! {
+    /// A price list is edited as a draft and frozen once published.
+    PriceListStatus {
+        Draft => "draft",
+        Published => "published",
+    }
+}
+
+string_enum! {
+    /// What a price list item prices: a `services` or a `medicines` document
+    PriceItemType {
+        Service => "service",
+        Medicine => "medicine",
+    }
+}
+
+string_enum! {
+    /// Follows the payments recorded against the invoice
+    InvoiceStatus {
+        Unpaid => "unpaid",
+        PartiallyPaid => "partially_paid",
+        Paid => "paid",
+        Cancelled => "cancelled",
+    }
+}
+
+impl InvoiceStatus {
+    /// Whether payments can still be recorded
+    pub fn is_payable(&self) -> bool {
+        matches!(self, Self::Unpaid | Self::PartiallyPaid)
+    }
+}
+
+string_enum! {
+    /// `gateway` is paid online, see `crate::payment_gateway`
+    PaymentMethod {
+        Cash => "cash" | "tunai",
+        Transfer => "transfer",
+        Qris => "qris",
+        Gateway => "gateway",
+    }
+}
+
+string_enum! {
+    /// State of an online payment link
+    GatewayStatus {
+        Pending => "pending",
+        Paid => "paid",
+        Failed => "failed",
+        Expired => "expired",
+    }
+}
+
+string_enum! {
+    /// Delivery state of an outbox entry; `failed` ones wait for an admin retry.
+    OutboxStatus {
+        Pending => "pending",
+        Published => "published",
+        Failed => "failed",
+    }
+}
+
+string_enum! {
+    /// Reports a `ReportSchedule` can send, see `crate::reports`
+    ReportType {
+        AppointmentsPerDoctor => "appointments_per_doctor",
+        Revenue => "revenue",
+        Stock => "stock",
+    }
+}
+
+string_enum! {
+    ReportFormat {
+        Csv => "csv",
+        Pdf => "pdf",
+    }
+}
+
+string_enum! {
+    /// Buckets of the revenue statistics; weeks start on Monday
+    RevenuePeriod {
+        Day => "day",
+        Week => "week",
+        Month => "month",
+    }
+}
+
+string_enum! {
+    /// Payload of a printable label, see `crate::labels`
+    LabelFormat {
+        Zpl => "zpl",
+        Pdf => "pdf",
+    }
+}
+
+string_enum! {
+    /// Type a `ReportTemplate` parameter is bound as; `date` is a `YYYY-MM-DD` string.
+    ReportParameterType {
+        String => "string",
+        Number => "number",
+        Boolean => "boolean",
+        Date => "date",
+    }
+}
+
+string_enum! {
+    /// Discriminator of a `Practitioner`; `/doctors` and `/nurses` each show one type.
+    PractitionerType {
+        Doctor => "doctor",
+        Nurse => "nurse",
+    }
+}
+
+string_enum! {
+    ShiftStatus {
+        Open => "open",
+        Closed => "closed",
+    }
+}
+
+string_enum! {
+    AdmissionStatus {
+        Admitted => "admitted",
+        Discharged => "discharged",
+    }
+}
+
+string_enum! {
+    /// Severity of a recorded allergic or adverse reaction
+    AllergySeverity {
+        Mild => "mild" | "ringan",
+        Moderate => "moderate" | "sedang",
+        Severe => "severe" | "berat",
+    }
+}
+
+string_enum! {
+    /// Staff review of a patient who registered themselves, see `crate::self_registration`
+    RegistrationStatus {
+        Pending => "pending",
+        Confirmed => "confirmed",
+    }
+}
+
+string_enum! {
+    /// Pairing state of a check-in kiosk; `pending` devices have an unused pairing code
+    KioskStatus {
+        Pending => "pending",
+        Paired => "paired",
+        Revoked => "revoked",
+    }
+}
+
+string_enum! {
+    /// What the related patient is to the patient in a `PatientRelationship`
+    RelationshipType {
+        Parent => "parent" | "orang_tua" | "mother" | "father" | "ibu" | "ayah",
+        Child => "child" | "anak",
+        Spouse => "spouse" | "pasangan" | "suami" | "istri",
+        Sibling => "sibling" | "saudara",
+        Caregiver => "caregiver" | "pengasuh" | "wali",
+        Dependent => "dependent" | "tanggungan",
+    }
+}
+
+impl RelationshipType {
+    /// The same link seen from the related patient
+    pub fn inverse(&self) -> Self {
+        match self {
+            Self::Parent => Self::Child,
+            Self::Child => Self::Parent,
+            Self::Caregiver => Self::Dependent,
+            Self::Dependent => Self::Caregiver,
+            other => other.clone(),
+        }
+    }
+
+    /// Whether the related patient acts for the patient unless told otherwise
+    pub fn guardian_by_default(&self) -> bool {
+        matches!(self, Self::Parent | Self::Caregiver)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_stored_values_round_trip() {
+        let status: AppointmentStatus = serde_json::from_str("\"confirmed\"").unwrap();
+        assert_eq!(status, AppointmentStatus::Other("confirmed".to_string()));
+        assert_eq!(serde_json::to_string(&status).unwrap(), "\"confirmed\"");
+        assert!(AppointmentStatus::validate(&status).is_err());
+
+        let status: AppointmentStatus = serde_json::from_str("\"Checked_In\"").unwrap();
+        assert_eq!(status, AppointmentStatus::CheckedIn);
+        assert!(AppointmentStatus::validate(&status).is_ok());
+    }
+
+    #[test]
+    fn gender_normalizes_indonesian_forms() {
+        assert_eq!(Gender::from("Perempuan"), Gender::Female);
+        assert_eq!(Gender::from("L").as_str(), "male");
+        assert_eq!(Bson::from(Gender::from("x")), Bson::String("x".to_string()));
+    }
+
+    #[test]
+    fn relationships_invert() {
+        assert_eq!(RelationshipType::from("Ibu").inverse(), RelationshipType::Child);
+        assert_eq!(RelationshipType::Dependent.inverse(), RelationshipType::Caregiver);
+        assert_eq!(RelationshipType::Spouse.inverse(), RelationshipType::Spouse);
+        assert_eq!(RelationshipType::from("cousin").inverse().as_str(), "cousin");
+    }
+}
+
+string_enum! {
+    /// A stock opname is counted while `counting`, then approved, which adjusts stock, or cancelled
+    StockOpnameStatus {
+        Counting => "counting",
+        Approved => "approved",
+        Cancelled => "cancelled",
+    }
+}
+
+string_enum! {
+    /// Why a medicine batch's quantity changed
+    StockMovementType {
+        Adjustment => "adjustment",
+    }
+}
+
+string_enum! {
+    /// How a push treats an offline edit of a note that changed on the server since, see `crate::sync`
+    SyncStrategy {
+        LastWriterWins => "last_writer_wins",
+        Manual => "manual",
+    }
+}
+
+string_enum! {
+    SyncOperation {
+        Create => "create",
+        Update => "update",
+        Delete => "delete",
+    }
+}
+
+string_enum! {
+    /// Collections clients can push mutations of
+    SyncEntity {
+        Notes => "notes",
+    }
+}
+
+string_enum! {
+    /// Outcome of one pushed mutation. `superseded` edits lost to a later server write, `conflict`
+    /// ones wait in `sync_conflicts`; `failed` ones were not recorded and can be pushed again.
+    SyncOutcome {
+        Applied => "applied",
+        Superseded => "superseded",
+        Conflict => "conflict",
+        Rejected => "rejected",
+        Failed => "failed",
+    }
+}
+
+string_enum! {
+    SyncConflictStatus {
+        Open => "open",
+        Resolved => "resolved",
+    }
+}
+
+string_enum! {
+    /// `keep_server` drops the offline edit, `use_client` applies it over the current version
+    ConflictResolution {
+        KeepServer => "keep_server",
+        UseClient => "use_client",
+    }
+}
+
+string_enum! {
+    ScheduledRunStatus {
+        Succeeded => "succeeded",
+        Failed => "failed",
+    }
+}
+
+string_enum! {
+    /// Only active programs enroll patients; an inactive one keeps its cohort for reporting
+    ProgramStatus {
+        Active => "active",
+        Inactive => "inactive",
+    }
+}
+
+string_enum! {
+    ProgramEnrollmentStatus {
+        Active => "active",
+        Withdrawn => "withdrawn",
+    }
+}
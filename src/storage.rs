@@ -0,0 +1,285 @@
+//! Where uploaded files, report deliveries and retention archives are stored.
+//!
+//! `STORAGE_BACKEND` picks the backend: `s3` (default, see `crate::s3`) or `local`, which
+//! keeps files under `STORAGE_LOCAL_DIR` (default `./storage`) so development needs no MinIO.
+//! Local files are linked to `GET /files/:id/content`, S3 files to their object URL; both
+//! can be read back for share links. A bucket is a directory of the local backend. Without a usable backend the server
+//! still boots, and writes answer 503.
+
+use std::env;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use crate::links::LinkConfig;
+use crate::s3::{S3Backend, S3Config, DEFAULT_BUCKET};
+
+pub const DEFAULT_LOCAL_DIR: &str = "storage";
+pub const NOT_CONFIGURED: &str = "File storage is not configured; set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY, or STORAGE_BACKEND=local";
+
+/// An object found by `StorageBackend::list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredObject {
+    pub key: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+pub trait StorageBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Store `body` at `key`; the URL clients download it from, or `None` when the API
+    /// serves it at `/files/:id/content`
+    fn put<'a>(&'a self, bucket: &'a str, key: &'a str, body: Vec<u8>) -> BoxFuture<'a, Result<Option<String>, String>>;
+
+    fn delete<'a>(&'a self, bucket: &'a str, key: &'a str) -> BoxFuture<'a, Result<(), String>>;
+
+    /// Content at `key`, for `/files/:id/content` and share links; `None` for backends that
+    /// cannot read files back
+    fn read<'a>(&'a self, _bucket: &'a str, _key: &'a str) -> Option<BoxFuture<'a, Result<Vec<u8>, String>>> {
+        None
+    }
+
+    /// Every object in `bucket` whose key starts with `prefix`
+    fn list<'a>(&'a self, bucket: &'a str, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, String>>;
+
+    /// Whether `bucket` can be reached, for the startup check
+    fn check<'a>(&'a self, bucket: &'a str) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// Files under a directory on this machine
+#[derive(Debug, Clone)]
+pub struct LocalDisk {
+    root: PathBuf,
+}
+
+impl LocalDisk {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Path of `key` in `bucket`; keys and buckets may only name entries below the root
+    pub fn path(&self, bucket: &str, key: &str) -> Result<PathBuf, String> {
+        let mut path = self.root.clone();
+        for part in [bucket, key] {
+            let relative = Path::new(part);
+            let safe = !part.is_empty()
+                && !part.contains('\\')
+                && relative.components().all(|c| matches!(c, Component::Normal(_)));
+            if !safe {
+                return Err(format!("Invalid storage path: {}", part));
+            }
+            path.push(relative);
+        }
+        Ok(path)
+    }
+}
+
+impl StorageBackend for LocalDisk {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn put<'a>(&'a self, bucket: &'a str, key: &'a str, body: Vec<u8>) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move {
+            let path = self.path(bucket, key)?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await.map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            tokio::fs::write(&path, body).await.map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            Ok(None)
+        })
+    }
+
+    fn delete<'a>(&'a self, bucket: &'a str, key: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let path = self.path(bucket, key)?;
+            match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to delete {}: {}", path.display(), e)),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn read<'a>(&'a self, bucket: &'a str, key: &'a str) -> Option<BoxFuture<'a, Result<Vec<u8>, String>>> {
+        Some(Box::pin(async move {
+            let path = self.path(bucket, key)?;
+            tokio::fs::read(&path).await.map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        }))
+    }
+
+    fn list<'a>(&'a self, bucket: &'a str, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, String>> {
+        Box::pin(async move {
+            let base = self.path(bucket, "probe")?.with_file_name("");
+            // Only the directory the prefix names is walked
+            let start = match prefix.rsplit_once('/') {
+                Some((dir, _)) if !dir.is_empty() => self.path(bucket, dir)?,
+                _ => base.clone(),
+            };
+
+            let mut objects = Vec::new();
+            let mut pending = vec![start];
+            while let Some(dir) = pending.pop() {
+                let mut entries = match tokio::fs::read_dir(&dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(format!("Failed to list {}: {}", dir.display(), e)),
+                };
+                while let Some(entry) = entries.next_entry().await.map_err(|e| format!("Failed to list {}: {}", dir.display(), e))? {
+                    let metadata = entry.metadata().await.map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?;
+                    if metadata.is_dir() {
+                        pending.push(entry.path());
+                        continue;
+                    }
+                    let Ok(relative) = entry.path().strip_prefix(&base).map(Path::to_path_buf) else {
+                        continue;
+                    };
+                    let key = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                    if key.starts_with(prefix) {
+                        objects.push(StoredObject { key, size: metadata.len(), modified: metadata.modified().ok().map(DateTime::<Utc>::from) });
+                    }
+                }
+            }
+            Ok(objects)
+        })
+    }
+
+    fn check<'a>(&'a self, bucket: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let probe = self.path(bucket, "probe")?;
+            let dir = probe.parent().unwrap_or(&self.root);
+            tokio::fs::create_dir_all(dir).await.map_err(|e| format!("{} is not writable: {}", dir.display(), e))
+        })
+    }
+}
+
+/// The configured backend, if any, and the bucket files go to
+pub struct Storage {
+    backend: Option<Box<dyn StorageBackend>>,
+    bucket: String,
+    /// Prefix of `/files/:id/content` links
+    content_base: String,
+}
+
+impl Storage {
+    pub fn new(backend: Option<Box<dyn StorageBackend>>, bucket: &str, links: &LinkConfig) -> Self {
+        Self { backend, bucket: bucket.to_string(), content_base: links.url("/files") }
+    }
+
+    /// Local disk under `STORAGE_LOCAL_DIR`, whatever `STORAGE_BACKEND` says; used by mock mode
+    pub fn local_from_env(links: &LinkConfig) -> Self {
+        let dir = env::var("STORAGE_LOCAL_DIR").ok().filter(|v| !v.trim().is_empty()).unwrap_or_else(|| DEFAULT_LOCAL_DIR.to_string());
+        let bucket = env::var("AWS_BUCKET").ok().filter(|v| !v.trim().is_empty()).unwrap_or_else(|| DEFAULT_BUCKET.to_string());
+        Self::new(Some(Box::new(LocalDisk::new(dir))), &bucket, links)
+    }
+
+    pub fn from_env(links: &LinkConfig) -> Self {
+        match env::var("STORAGE_BACKEND").map(|v| v.trim().to_lowercase()).as_deref() {
+            Ok("local") => Self::local_from_env(links),
+            other => {
+                if let Ok(other) = other {
+                    if !other.is_empty() && other != "s3" {
+                        tracing::warn!(backend = other, "Unknown STORAGE_BACKEND, using s3");
+                    }
+                }
+                match S3Config::from_env() {
+                    Some(config) => {
+                        let backend = S3Backend::new(config);
+                        let bucket = backend.bucket().to_string();
+                        Self::new(Some(Box::new(backend)), &bucket, links)
+                    }
+                    None => Self::new(None, DEFAULT_BUCKET, links),
+                }
+            }
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    /// The backend, or 503 when none is configured
+    pub fn backend(&self) -> Result<&dyn StorageBackend, (StatusCode, String)> {
+        self.backend.as_deref().ok_or((StatusCode::SERVICE_UNAVAILABLE, NOT_CONFIGURED.to_string()))
+    }
+
+    /// Where the API serves the content of file `id`
+    pub fn content_url(&self, id: &str) -> String {
+        format!("{}/{}/content", self.content_base, id)
+    }
+
+    /// Where the API serves one version of file `id`
+    pub fn version_content_url(&self, id: &str, version: i32) -> String {
+        format!("{}/{}/versions/{}/content", self.content_base, id, version)
+    }
+}
+
+/// Check the backend in the background, so the first upload is not the one to find out the
+/// credentials or the directory are wrong
+pub fn spawn_warm_up(storage: Arc<Storage>) {
+    let Ok(backend) = storage.backend() else {
+        tracing::warn!("File storage is not configured; file uploads answer 503");
+        return;
+    };
+    tracing::info!(backend = backend.name(), bucket = %storage.bucket(), "File storage ready");
+    tokio::spawn(async move {
+        if let Ok(backend) = storage.backend() {
+            if let Err(e) = backend.check(storage.bucket()).await {
+                tracing::error!(bucket = %storage.bucket(), error = %e, "File storage check failed");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_paths_below_the_root() {
+        let disk = LocalDisk::new("/srv/rme");
+        assert_eq!(disk.path("rme", "files/20260101_scan.pdf").unwrap(), PathBuf::from("/srv/rme/rme/files/20260101_scan.pdf"));
+        assert!(disk.path("rme", "files/../../etc/passwd").is_err());
+        assert!(disk.path("rme", "/etc/passwd").is_err());
+        assert!(disk.path("..", "files/scan.pdf").is_err());
+        assert!(disk.path("rme", "files\\..\\scan.pdf").is_err());
+        assert!(disk.path("rme", "").is_err());
+    }
+
+    #[tokio::test]
+    async fn stores_reads_and_deletes_files() {
+        let root = std::env::temp_dir().join(format!("rme-storage-{}", mongodb::bson::oid::ObjectId::new().to_hex()));
+        let disk = LocalDisk::new(&root);
+
+        assert_eq!(disk.put("rme", "files/scan.txt", b"hello".to_vec()).await.unwrap(), None);
+        assert_eq!(disk.read("rme", "files/scan.txt").unwrap().await.unwrap(), b"hello");
+        disk.put("rme", "files/2026/report.pdf", b"report".to_vec()).await.unwrap();
+        disk.put("rme", "archive/runs.jsonl", b"{}".to_vec()).await.unwrap();
+        let mut keys: Vec<String> = disk.list("rme", "files/").await.unwrap().into_iter().map(|o| o.key).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["files/2026/report.pdf", "files/scan.txt"]);
+        assert_eq!(disk.list("rme", "files/scan").await.unwrap().len(), 1);
+        assert!(disk.list("other", "files/").await.unwrap().is_empty());
+
+        disk.delete("rme", "files/scan.txt").await.unwrap();
+        assert!(disk.read("rme", "files/scan.txt").unwrap().await.is_err());
+        // Deleting twice is not an error
+        disk.delete("rme", "files/scan.txt").await.unwrap();
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn unconfigured_storage_answers_503() {
+        let storage = Storage::new(None, DEFAULT_BUCKET, &LinkConfig::new("/api/v1"));
+        assert!(!storage.is_configured());
+        assert_eq!(storage.backend().err().unwrap().0, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(storage.content_url("65a1b2c3d4e5f60718293a4b"), "/api/v1/files/65a1b2c3d4e5f60718293a4b/content");
+        assert_eq!(storage.version_content_url("65a1b2c3d4e5f60718293a4b", 2), "/api/v1/files/65a1b2c3d4e5f60718293a4b/versions/2/content");
+    }
+}
@@ -0,0 +1,197 @@
+//! Reconciliation of stored objects against the file records pointing at them.
+//!
+//! An upload whose database insert fails, or a delete interrupted halfway, leaves objects no
+//! file references. The nightly job lists every prefix in `STORAGE_GC_PREFIXES` (default
+//! `files/`), compares the keys with the paths of `files` (soft-deleted ones included) and
+//! `file_versions`, and deletes orphans last modified more than `STORAGE_GC_GRACE_HOURS`
+//! (default 24) ago, so uploads still being recorded are left alone. Paths recorded in the
+//! database but absent from storage are reported, never changed. `STORAGE_GC_MODE` is
+//! `delete` (default), `report` to only record findings, or `off` to skip the nightly run;
+//! runs started from `POST /admin/storage/reconciliation` then only report as well. Each
+//! run is kept in `storage_reconciliations` and the latest one is served at
+//! `GET /admin/storage/reconciliation`.
+
+use std::collections::HashSet;
+use std::env;
+use std::sync::Arc;
+use chrono::{DateTime, Duration, Local, Utc};
+use serde::Serialize;
+use crate::db::AppState;
+use crate::repository::StorageReconciliationRepository;
+use crate::retention::delay_until_next_run;
+use crate::services::StorageReconciliationService;
+use crate::storage::StoredObject;
+
+pub const DEFAULT_RUN_HOUR: u32 = 3;
+pub const DEFAULT_GRACE_HOURS: i64 = 24;
+pub const RUNS_COLLECTION: &str = "storage_reconciliations";
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GcMode {
+    Delete,
+    Report,
+    Off,
+}
+
+impl GcMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Report => "report",
+            Self::Off => "off",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageGcConfig {
+    pub mode: GcMode,
+    /// Key prefixes owned by file records
+    pub prefixes: Vec<String>,
+    /// Orphans younger than this are kept
+    pub grace_hours: i64,
+    /// Local hour (0-23) at which the nightly run starts
+    pub run_hour: u32,
+}
+
+impl Default for StorageGcConfig {
+    fn default() -> Self {
+        Self {
+            mode: GcMode::Delete,
+            prefixes: vec!["files/".to_string()],
+            grace_hours: DEFAULT_GRACE_HOURS,
+            run_hour: DEFAULT_RUN_HOUR,
+        }
+    }
+}
+
+impl StorageGcConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let defaults = Self::default();
+
+        Self {
+            mode: match var("STORAGE_GC_MODE").map(|m| m.to_lowercase()).as_deref() {
+                Some("report") => GcMode::Report,
+                Some("off") | Some("false") => GcMode::Off,
+                _ => GcMode::Delete,
+            },
+            prefixes: var("STORAGE_GC_PREFIXES")
+                .map(|raw| parse_prefixes(&raw))
+                .filter(|prefixes| !prefixes.is_empty())
+                .unwrap_or(defaults.prefixes),
+            grace_hours: var("STORAGE_GC_GRACE_HOURS")
+                .and_then(|h| h.parse().ok())
+                .filter(|h| *h >= 1)
+                .unwrap_or(defaults.grace_hours),
+            run_hour: var("STORAGE_GC_RUN_HOUR")
+                .and_then(|h| h.parse().ok())
+                .filter(|h| *h < 24)
+                .unwrap_or(defaults.run_hour),
+        }
+    }
+}
+
+/// Comma-separated prefixes; an empty one would cover the whole bucket and is dropped
+pub fn parse_prefixes(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(|p| p.trim().trim_start_matches('/'))
+        .filter(|p| !p.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// An object no file references
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub object: StoredObject,
+    /// Still within the grace period, or of unknown age
+    pub pending: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Findings {
+    pub orphans: Vec<Orphan>,
+    /// Referenced keys under the scanned prefixes that storage does not have
+    pub missing: Vec<String>,
+}
+
+/// Compare `objects` listed under `prefixes` with the `referenced` keys
+pub fn reconcile(objects: Vec<StoredObject>, referenced: &HashSet<String>, prefixes: &[String], now: DateTime<Utc>, grace_hours: i64) -> Findings {
+    let grace_start = now - Duration::hours(grace_hours);
+    let stored: HashSet<&str> = objects.iter().map(|o| o.key.as_str()).collect();
+
+    let mut missing: Vec<String> = referenced
+        .iter()
+        .filter(|key| prefixes.iter().any(|p| key.starts_with(p.as_str())) && !stored.contains(key.as_str()))
+        .cloned()
+        .collect();
+    missing.sort();
+
+    let mut orphans: Vec<Orphan> = objects
+        .into_iter()
+        .filter(|o| !referenced.contains(&o.key))
+        .map(|object| Orphan { pending: object.modified.is_none_or(|m| m > grace_start), object })
+        .collect();
+    orphans.sort_by(|a, b| a.object.key.cmp(&b.object.key));
+
+    Findings { orphans, missing }
+}
+
+/// Spawn the nightly reconciliation. Does nothing when it is off or storage is not configured.
+pub fn spawn_scheduler(state: Arc<AppState>) {
+    let config = StorageGcConfig::from_env();
+    if config.mode == GcMode::Off || !state.storage.is_configured() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(delay_until_next_run(Local::now(), config.run_hour)).await;
+
+            state.scheduler.run("storage_reconciliation", async {
+                let service = StorageReconciliationService::new(
+                    StorageReconciliationRepository::new(state.db.clone()),
+                    state.storage.clone(),
+                    config.clone(),
+                );
+                service.run("scheduled").await.map(|_| ()).map_err(|(_, e)| e)
+            }).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn object(key: &str, modified: Option<DateTime<Utc>>) -> StoredObject {
+        StoredObject { key: key.to_string(), size: 10, modified }
+    }
+
+    #[test]
+    fn parses_prefixes() {
+        assert_eq!(parse_prefixes(" files/ , /reports/,,"), vec!["files/", "reports/"]);
+        assert!(parse_prefixes(" , /").is_empty());
+    }
+
+    #[test]
+    fn deletes_only_orphans_past_the_grace_period() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 3, 0, 0).unwrap();
+        let referenced: HashSet<String> = ["files/kept.pdf", "files/lost.pdf", "archive/other.jsonl"].iter().map(|k| k.to_string()).collect();
+        let objects = vec![
+            object("files/kept.pdf", Some(now - Duration::days(30))),
+            object("files/old.pdf", Some(now - Duration::hours(25))),
+            object("files/fresh.pdf", Some(now - Duration::hours(2))),
+            object("files/undated.pdf", None),
+        ];
+
+        let findings = reconcile(objects, &referenced, &["files/".to_string()], now, 24);
+        let orphans: Vec<(&str, bool)> = findings.orphans.iter().map(|o| (o.object.key.as_str(), o.pending)).collect();
+        assert_eq!(orphans, vec![("files/fresh.pdf", true), ("files/old.pdf", false), ("files/undated.pdf", true)]);
+        // Paths outside the scanned prefixes are not reported missing
+        assert_eq!(findings.missing, vec!["files/lost.pdf"]);
+    }
+}
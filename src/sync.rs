@@ -0,0 +1,182 @@
+//! Delta sync for offline clients.
+//!
+//! `GET /sync/reference` returns the codes, interpretations and regions written since a cursor,
+//! by `updated_at`, and the ones deleted since, from `tombstones` (every API delete of those
+//! collections records one). Each feed is read in `(timestamp, _id)` order so a page never
+//! splits documents that share a timestamp, as imported codes do; the cursor keeps the last
+//! position of every feed, e.g. `codes.1767225600000.65a1…~tombstones.1767225600000.65a1…`.
+//! `since` also takes an RFC 3339 timestamp, for clients without a cursor; without `since`
+//! everything is returned, and deletions from before the first page are left out. Documents
+//! stored before `updated_at` was kept get one at startup, see
+//! `crate::migrations::migrate_sync_timestamps`.
+//!
+//! Clinical notes sync both ways. `POST /sync/clinical/push` takes mutations made offline,
+//! each with a client-generated UUID (a replayed UUID returns its first outcome) and the
+//! version it was based on. An edit based on an older version conflicts: `last_writer_wins`
+//! applies it when the device changed the note after the server copy was last written and
+//! reports it `superseded` otherwise, while `manual` queues it in `sync_conflicts` for
+//! `POST /sync/conflicts/:id/resolve`. `GET /sync/clinical/pull` returns the notes of the
+//! given medical records changed since a cursor, like the reference feeds.
+
+use std::collections::BTreeMap;
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use crate::status::SyncStrategy;
+
+pub const REFERENCE_COLLECTIONS: [&str; 3] = ["codes", "interpretations", "regions"];
+/// Feed of deletions, named after its collection
+pub const TOMBSTONES: &str = "tombstones";
+/// Feeds of `GET /sync/reference`
+pub const REFERENCE_FEEDS: [&str; 4] = ["codes", "interpretations", "regions", TOMBSTONES];
+/// Feeds of `GET /sync/clinical/pull`: live notes by `updatedAt`, deleted ones by `deletedAt`
+pub const NOTE_FEEDS: [&str; 2] = ["notes", "notes_deleted"];
+
+const FEED_SEPARATOR: char = '~';
+
+/// The last document a client has of one feed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub at: DateTime,
+    /// `None` when the position is only a timestamp
+    pub id: Option<ObjectId>,
+}
+
+impl Position {
+    /// Documents after this position, in a feed ordered by `field` then `_id`
+    pub fn filter(&self, field: &str) -> Document {
+        match self.id {
+            None => doc! { field: { "$gte": self.at } },
+            Some(id) => doc! { "$or": [
+                { field: { "$gt": self.at } },
+                { field: self.at, "_id": { "$gt": id } },
+            ] },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncCursor {
+    positions: BTreeMap<String, Position>,
+}
+
+impl SyncCursor {
+    /// Every feed from `at`
+    pub fn from_time(at: DateTime, feeds: &[&str]) -> Self {
+        let positions = feeds.iter()
+            .map(|feed| (feed.to_string(), Position { at, id: None }))
+            .collect();
+        Self { positions }
+    }
+
+    /// A cursor over `feeds` from a previous response, or an RFC 3339 timestamp
+    pub fn parse(since: &str, feeds: &[&str]) -> Result<Self, String> {
+        if let Some(at) = crate::datetime::parse(since) {
+            return Ok(Self::from_time(at, feeds));
+        }
+
+        let mut positions = BTreeMap::new();
+        for entry in since.trim().split(FEED_SEPARATOR).filter(|entry| !entry.is_empty()) {
+            let invalid = || format!("Invalid sync cursor entry '{}'", entry);
+            let mut parts = entry.splitn(3, '.');
+            let feed = parts.next().filter(|feed| feeds.contains(feed)).ok_or_else(invalid)?;
+            let at = parts.next().and_then(|ms| ms.parse::<i64>().ok()).ok_or_else(invalid)?;
+            let id = match parts.next().filter(|id| !id.is_empty()) {
+                Some(id) => Some(ObjectId::parse_str(id).map_err(|_| invalid())?),
+                None => None,
+            };
+            positions.insert(feed.to_string(), Position { at: DateTime::from_millis(at), id });
+        }
+        if positions.is_empty() {
+            return Err("since must be a sync cursor or an RFC 3339 timestamp".to_string());
+        }
+        Ok(Self { positions })
+    }
+
+    pub fn position(&self, feed: &str) -> Option<Position> {
+        self.positions.get(feed).copied()
+    }
+
+    pub fn advance(&mut self, feed: &str, position: Position) {
+        self.positions.insert(feed.to_string(), position);
+    }
+
+    pub fn encode(&self) -> String {
+        self.positions.iter()
+            .map(|(feed, position)| format!(
+                "{}.{}.{}",
+                feed,
+                position.at.timestamp_millis(),
+                position.id.map(|id| id.to_hex()).unwrap_or_default(),
+            ))
+            .collect::<Vec<_>>()
+            .join(&FEED_SEPARATOR.to_string())
+    }
+}
+
+/// What becomes of an offline edit of a document
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Apply,
+    /// The server copy was written after the device's change and wins
+    Superseded,
+    Conflict,
+}
+
+/// An edit based on the current version applies; otherwise `strategy` decides, comparing when
+/// the device made the change with when the server copy was last written
+pub fn decide(base_version: i32, current_version: i32, strategy: &SyncStrategy, changed_at: DateTime, server_written_at: DateTime) -> Decision {
+    if base_version == current_version {
+        return Decision::Apply;
+    }
+    match strategy {
+        SyncStrategy::Manual => Decision::Conflict,
+        _ if changed_at > server_written_at => Decision::Apply,
+        _ => Decision::Superseded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursors_round_trip_and_timestamps_start_every_feed() {
+        let id = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        let mut cursor = SyncCursor::default();
+        cursor.advance("codes", Position { at: DateTime::from_millis(1_767_225_600_000), id: Some(id) });
+        cursor.advance(TOMBSTONES, Position { at: DateTime::from_millis(1_767_225_600_500), id: None });
+
+        let encoded = cursor.encode();
+        assert_eq!(encoded, "codes.1767225600000.65a1b2c3d4e5f60718293a4b~tombstones.1767225600500.");
+        assert_eq!(SyncCursor::parse(&encoded, &REFERENCE_FEEDS).unwrap(), cursor);
+        assert!(SyncCursor::parse(&encoded, &NOTE_FEEDS).is_err());
+
+        let from_time = SyncCursor::parse("2026-01-01T00:00:00Z", &REFERENCE_FEEDS).unwrap();
+        assert_eq!(from_time.position("regions"), Some(Position { at: DateTime::from_millis(1_767_225_600_000), id: None }));
+
+        assert!(SyncCursor::parse("patients.1767225600000.", &REFERENCE_FEEDS).is_err());
+        assert!(SyncCursor::parse("codes.yesterday.", &REFERENCE_FEEDS).is_err());
+        assert!(SyncCursor::parse("", &REFERENCE_FEEDS).is_err());
+    }
+
+    #[test]
+    fn positions_continue_after_the_last_document() {
+        let at = DateTime::from_millis(1_767_225_600_000);
+        let id = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        assert_eq!(Position { at, id: None }.filter("updated_at"), doc! { "updated_at": { "$gte": at } });
+        assert_eq!(
+            Position { at, id: Some(id) }.filter("deleted_at"),
+            doc! { "$or": [{ "deleted_at": { "$gt": at } }, { "deleted_at": at, "_id": { "$gt": id } }] },
+        );
+    }
+
+    #[test]
+    fn stale_edits_follow_the_strategy() {
+        let (earlier, later) = (DateTime::from_millis(1_000), DateTime::from_millis(2_000));
+        let lww = SyncStrategy::LastWriterWins;
+        assert_eq!(decide(3, 3, &SyncStrategy::Manual, earlier, later), Decision::Apply);
+        assert_eq!(decide(2, 3, &lww, later, earlier), Decision::Apply);
+        assert_eq!(decide(2, 3, &lww, earlier, later), Decision::Superseded);
+        assert_eq!(decide(2, 3, &lww, earlier, earlier), Decision::Superseded);
+        assert_eq!(decide(2, 3, &SyncStrategy::Manual, later, earlier), Decision::Conflict);
+    }
+}
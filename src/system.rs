@@ -0,0 +1,434 @@
+//! Build information, configuration checks and the startup self-check.
+//!
+//! `init_db` runs `startup_check` once migrations have run. `STARTUP_CHECKS` controls what
+//! happens to problems it finds: `strict` (default) refuses to start on errors such as a
+//! missing JWT secret in production or indexes the migration runner could not create, `warn`
+//! only logs them and `off` skips the check. `GET /admin/system-info` reports the same
+//! findings on a running instance.
+
+use std::collections::BTreeSet;
+use std::env;
+use futures_util::stream::TryStreamExt;
+use mongodb::{bson::{doc, Bson, Document}, Database, IndexModel};
+use crate::config::AppConfig;
+use crate::dto::system::{CollectionIndexes, ConfigIssue, IndexHealth, IndexReport, IndexSpec, IndexState, IndexStatus, IssueSeverity, MongoTopology, StorageInfo};
+use crate::migrations::{index_definitions, IndexDefinition};
+use crate::models::{Job, JobProgress};
+use crate::services::JobService;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+const MIN_SECRET_LENGTH: usize = 32;
+const NAMESPACE_NOT_FOUND: i32 = 26;
+pub const INDEX_REBUILD_JOB: &str = "index_rebuild";
+
+/// Commit the binary was built from, embedded by `build.rs`
+pub fn git_sha() -> &'static str {
+    option_env!("GIT_SHA").unwrap_or("unknown")
+}
+
+pub fn compiled_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "meilisearch") {
+        features.push("meilisearch".to_string());
+    }
+    features
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupMode {
+    Strict,
+    Warn,
+    Off,
+}
+
+impl StartupMode {
+    pub fn from_env() -> Self {
+        match env::var("STARTUP_CHECKS").map(|v| v.trim().to_lowercase()).as_deref() {
+            Ok("warn") => Self::Warn,
+            Ok("off") | Ok("false") => Self::Off,
+            _ => Self::Strict,
+        }
+    }
+}
+
+fn issue(severity: IssueSeverity, key: &str, message: &str) -> ConfigIssue {
+    ConfigIssue { severity, key: key.to_string(), message: message.to_string() }
+}
+
+/// Problems with the loaded configuration; `lookup` reads raw environment variables.
+pub fn check_config(config: &AppConfig, lookup: impl Fn(&str) -> Option<String>) -> Vec<ConfigIssue> {
+    let set = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+    let production = set("APP_ENV").is_some_and(|e| e.trim().eq_ignore_ascii_case("production"));
+    let mut issues = Vec::new();
+
+    match set("JWT_SECRET") {
+        None => issues.push(issue(
+            if production { IssueSeverity::Error } else { IssueSeverity::Warning },
+            "JWT_SECRET",
+            "Not set; tokens are signed with the built-in default secret",
+        )),
+        Some(secret) if secret.len() < MIN_SECRET_LENGTH => {
+            issues.push(issue(IssueSeverity::Warning, "JWT_SECRET", "Shorter than 32 characters"))
+        }
+        Some(_) => {}
+    }
+    let local_storage = set("STORAGE_BACKEND").is_some_and(|b| b.trim().eq_ignore_ascii_case("local"));
+    if local_storage && production {
+        issues.push(issue(IssueSeverity::Warning, "STORAGE_BACKEND", "local keeps files on this machine's disk; use s3 in production"));
+    }
+    if cfg!(feature = "s3") && !local_storage && (set("AWS_ACCESS_KEY_ID").is_none() || set("AWS_SECRET_ACCESS_KEY").is_none()) {
+        issues.push(issue(IssueSeverity::Warning, "AWS_ACCESS_KEY_ID", "S3 credentials not set; file uploads, deletes and archiving answer 503"));
+    }
+
+    let otp = &config.otp;
+    if let Err(e) = otp.provider() {
+        issues.push(issue(IssueSeverity::Error, "OTP_PROVIDER", &e));
+    }
+    if config.email.provider == "sendgrid" && config.email.sendgrid_api_key.is_none() {
+        issues.push(issue(IssueSeverity::Error, "MAIL_PROVIDER", "sendgrid needs SENDGRID_API_KEY; emails would only be logged"));
+    }
+    if let Err(e) = config.captcha.verifier() {
+        issues.push(issue(IssueSeverity::Error, "CAPTCHA_PROVIDER", &e));
+    }
+
+    issues
+}
+
+pub fn storage_info() -> StorageInfo {
+    let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+    StorageInfo {
+        backend: if !cfg!(feature = "s3") {
+            "none"
+        } else if var("STORAGE_BACKEND").is_some_and(|b| b.trim().eq_ignore_ascii_case("local")) {
+            "local"
+        } else {
+            "s3"
+        }.to_string(),
+        bucket: var("AWS_BUCKET"),
+        region: var("AWS_DEFAULT_REGION"),
+        endpoint: var("AWS_ENDPOINT"),
+    }
+}
+
+/// Answers the file routes of builds without the `s3` feature
+#[cfg(not(feature = "s3"))]
+pub async fn storage_not_built() -> axum::response::Response {
+    use axum::response::IntoResponse;
+    crate::response::ErrorResponse::new(
+        axum::http::StatusCode::NOT_IMPLEMENTED,
+        "File storage is not available",
+        "STORAGE_NOT_BUILT",
+        Some("This build has no file storage; it was compiled without the s3 feature".to_string()),
+    ).into_response()
+}
+
+/// Deployment shape from the `hello` command
+pub async fn mongo_topology(db: &Database) -> Result<MongoTopology, String> {
+    let hello = db.run_command(doc! { "hello": 1 }, None).await.map_err(|e| e.to_string())?;
+    let set_name = hello.get_str("setName").ok().map(str::to_string);
+    let kind = if hello.get_str("msg").ok() == Some("isdbgrid") {
+        "sharded"
+    } else if set_name.is_some() {
+        "replica_set"
+    } else {
+        "standalone"
+    };
+
+    Ok(MongoTopology {
+        kind: kind.to_string(),
+        set_name,
+        hosts: hello
+            .get_array("hosts")
+            .map(|hosts| hosts.iter().filter_map(|h| h.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+        writable_primary: hello.get_bool("isWritablePrimary").unwrap_or(false),
+    })
+}
+
+/// Indexes from `migrations::index_definitions` missing in the database
+pub async fn index_health(db: &Database) -> Result<IndexHealth, String> {
+    let definitions = index_definitions();
+    let collections: BTreeSet<&str> = definitions.iter().map(|d| d.collection).collect();
+    let mut missing = Vec::new();
+
+    for collection in collections {
+        let existing = match db.collection::<Document>(collection).list_index_names().await {
+            Ok(names) => names,
+            Err(e) => match *e.kind {
+                mongodb::error::ErrorKind::Command(ref command) if command.code == NAMESPACE_NOT_FOUND => Vec::new(),
+                _ => return Err(e.to_string()),
+            },
+        };
+        missing.extend(
+            definitions
+                .iter()
+                .filter(|d| d.collection == collection && !existing.iter().any(|name| name == d.name))
+                .map(|d| format!("{}.{}", d.collection, d.name)),
+        );
+    }
+
+    Ok(IndexHealth { expected: definitions.len(), missing })
+}
+
+/// Indexes of `collection`, none when it does not exist yet
+async fn list_indexes(db: &Database, collection: &str) -> Result<Vec<IndexModel>, String> {
+    match db.collection::<Document>(collection).list_indexes(None).await {
+        Ok(cursor) => cursor.try_collect().await.map_err(|e| e.to_string()),
+        Err(e) => match *e.kind {
+            mongodb::error::ErrorKind::Command(ref command) if command.code == NAMESPACE_NOT_FOUND => Ok(Vec::new()),
+            _ => Err(e.to_string()),
+        },
+    }
+}
+
+/// A key's direction or type; numbers compare by value whether stored as int or double
+fn key_type(value: &Bson) -> Option<String> {
+    match value {
+        Bson::Int32(n) => Some(f64::from(*n).to_string()),
+        Bson::Int64(n) => Some((*n as f64).to_string()),
+        Bson::Double(n) => Some(n.to_string()),
+        Bson::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
+
+/// Whether `existing` indexes what `definition` describes. Text indexes are stored as
+/// `{ _fts: "text", _ftsx: 1 }` with their fields in the weights, so those are compared instead.
+fn index_matches(definition: &IndexDefinition, existing: &IndexModel) -> bool {
+    let options = existing.options.as_ref();
+    if options.and_then(|o| o.unique).unwrap_or(false) != definition.unique {
+        return false;
+    }
+    if options.and_then(|o| o.partial_filter_expression.as_ref()) != definition.partial.as_ref() {
+        return false;
+    }
+
+    let text_fields: BTreeSet<&str> = definition.keys.iter()
+        .filter(|(_, value)| value.as_str() == Some("text"))
+        .map(|(field, _)| field.as_str())
+        .collect();
+    if !text_fields.is_empty() {
+        let weighted: BTreeSet<&str> = options
+            .and_then(|o| o.weights.as_ref())
+            .map(|weights| weights.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        return existing.keys.get_str("_fts") == Ok("text") && weighted == text_fields;
+    }
+
+    definition.keys.len() == existing.keys.len()
+        && definition.keys.iter().zip(existing.keys.iter()).all(|((field, value), (other_field, other_value))| {
+            field == other_field && key_type(value).is_some() && key_type(value) == key_type(other_value)
+        })
+}
+
+fn spec_of(model: &IndexModel) -> IndexSpec {
+    let options = model.options.as_ref();
+    IndexSpec {
+        keys: model.keys.clone(),
+        unique: options.and_then(|o| o.unique).unwrap_or(false),
+        partial: options.and_then(|o| o.partial_filter_expression.clone()),
+    }
+}
+
+/// Each definition against the index of the same name, then the indexes nothing defines
+fn compare_indexes(definitions: &[&IndexDefinition], existing: &[IndexModel]) -> Vec<IndexStatus> {
+    let name_of = |model: &IndexModel| model.options.as_ref().and_then(|o| o.name.clone()).unwrap_or_default();
+    let mut statuses: Vec<IndexStatus> = definitions.iter()
+        .map(|definition| {
+            let actual = existing.iter().find(|model| name_of(model) == definition.name);
+            let state = match actual {
+                None => IndexState::Missing,
+                Some(model) if index_matches(definition, model) => IndexState::Present,
+                Some(_) => IndexState::Differs,
+            };
+            IndexStatus {
+                name: definition.name.to_string(),
+                state,
+                expected: Some(IndexSpec { keys: definition.keys.clone(), unique: definition.unique, partial: definition.partial.clone() }),
+                actual: actual.map(spec_of),
+            }
+        })
+        .collect();
+
+    statuses.extend(
+        existing.iter()
+            .filter(|model| name_of(model) != "_id_" && !definitions.iter().any(|d| d.name == name_of(model)))
+            .map(|model| IndexStatus { name: name_of(model), state: IndexState::Unmanaged, expected: None, actual: Some(spec_of(model)) }),
+    );
+    statuses
+}
+
+/// Expected indexes against those in the database, for `GET /admin/indexes`
+pub async fn index_report(db: &Database) -> Result<IndexReport, String> {
+    let definitions = index_definitions();
+    let names: BTreeSet<&str> = definitions.iter().map(|d| d.collection).collect();
+    let mut collections = Vec::new();
+
+    for collection in names {
+        let expected: Vec<&IndexDefinition> = definitions.iter().filter(|d| d.collection == collection).collect();
+        let existing = list_indexes(db, collection).await?;
+        collections.push(CollectionIndexes { collection: collection.to_string(), indexes: compare_indexes(&expected, &existing) });
+    }
+
+    let count = |state: IndexState| collections.iter().flat_map(|c| &c.indexes).filter(|i| i.state == state).count();
+    Ok(IndexReport {
+        expected: definitions.len(),
+        missing: count(IndexState::Missing),
+        differing: count(IndexState::Differs),
+        unmanaged: count(IndexState::Unmanaged),
+        collections,
+    })
+}
+
+/// Queue an `index_rebuild` job for the indexes missing now; see `rebuild_indexes`
+pub async fn enqueue_index_rebuild(db: &Database, jobs: &JobService, actor: &str) -> Result<Job, String> {
+    let report = index_report(db).await?;
+    let missing: Vec<String> = report.collections.iter()
+        .flat_map(|c| c.indexes.iter().filter(|i| i.state == IndexState::Missing).map(move |i| format!("{}.{}", c.collection, i.name)))
+        .collect();
+    jobs.enqueue(INDEX_REBUILD_JOB, doc! { "indexes": &missing }, missing.len() as u64, actor).await
+}
+
+/// Create the indexes an `index_rebuild` job lists, reporting progress after each. Indexes
+/// that differ from their definition are left for an operator to drop first.
+pub async fn rebuild_indexes(db: &Database, jobs: &JobService, job: &Job) -> Result<Document, String> {
+    let job_id = job.id.ok_or("Job has no id")?;
+    let names: Vec<String> = job.payload.get_array("indexes")
+        .map_err(|e| format!("Invalid index rebuild payload: {}", e))?
+        .iter()
+        .filter_map(|name| name.as_str().map(str::to_string))
+        .collect();
+    let definitions = index_definitions();
+    let mut progress = JobProgress { total: names.len() as u64, ..Default::default() };
+    let (mut created, mut errors) = (Vec::new(), Vec::new());
+
+    for name in &names {
+        // Definitions removed since the job was queued are skipped
+        match definitions.iter().find(|d| format!("{}.{}", d.collection, d.name) == *name) {
+            None => progress.skipped += 1,
+            Some(definition) => match crate::migrations::create_index(db, definition).await {
+                Ok(()) => {
+                    progress.succeeded += 1;
+                    created.push(name.clone());
+                }
+                Err(e) => {
+                    progress.failed += 1;
+                    errors.push(e);
+                }
+            },
+        }
+        progress.processed += 1;
+        if let Err(e) = jobs.report_progress(job_id, &progress).await {
+            tracing::warn!(job_id = %job_id, error = %e, "Failed to report job progress");
+        }
+    }
+
+    Ok(doc! { "created": created, "errors": errors })
+}
+
+/// Validate configuration and indexes before serving; see the module docs for `STARTUP_CHECKS`.
+pub async fn startup_check(db: &Database, config: &AppConfig) -> Result<(), String> {
+    let mode = StartupMode::from_env();
+    if mode == StartupMode::Off {
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    for found in check_config(config, |name| env::var(name).ok()) {
+        match found.severity {
+            IssueSeverity::Error => errors.push(format!("{}: {}", found.key, found.message)),
+            IssueSeverity::Warning => tracing::warn!(key = %found.key, message = %found.message, "Startup check warning"),
+        }
+    }
+    match index_health(db).await {
+        Ok(health) if !health.missing.is_empty() => errors.push(format!("missing indexes: {}", health.missing.join(", "))),
+        Ok(_) => {}
+        Err(e) => errors.push(format!("index check failed: {}", e)),
+    }
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let report = errors.join("; ");
+    match mode {
+        StartupMode::Strict => Err(format!("Startup check failed (set STARTUP_CHECKS=warn to start anyway): {}", report)),
+        _ => {
+            tracing::error!(problems = %report, "Startup check found problems");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn default_secret_is_an_error_only_in_production() {
+        let config = AppConfig::default();
+        let dev = check_config(&config, lookup(&[("AWS_ACCESS_KEY_ID", "key"), ("AWS_SECRET_ACCESS_KEY", "secret")]));
+        assert_eq!(dev.len(), 1);
+        assert_eq!(dev[0].severity, IssueSeverity::Warning);
+
+        let prod = check_config(&config, lookup(&[("APP_ENV", "production"), ("AWS_ACCESS_KEY_ID", "key"), ("AWS_SECRET_ACCESS_KEY", "secret")]));
+        assert_eq!(prod[0].severity, IssueSeverity::Error);
+
+        let secret = "s".repeat(40);
+        assert!(check_config(&config, lookup(&[("JWT_SECRET", &secret), ("AWS_ACCESS_KEY_ID", "key"), ("AWS_SECRET_ACCESS_KEY", "secret")])).is_empty());
+    }
+
+    #[test]
+    fn incomplete_providers_are_errors() {
+        let mut config = AppConfig::default();
+        config.otp.provider = "twilio".to_string();
+        config.email.provider = "sendgrid".to_string();
+        config.captcha.provider = "turnstile".to_string();
+        let secret = "s".repeat(40);
+        let issues = check_config(&config, lookup(&[("JWT_SECRET", &secret), ("AWS_ACCESS_KEY_ID", "key"), ("AWS_SECRET_ACCESS_KEY", "secret")]));
+        let keys: Vec<&str> = issues.iter().filter(|i| i.severity == IssueSeverity::Error).map(|i| i.key.as_str()).collect();
+        assert_eq!(keys, vec!["OTP_PROVIDER", "MAIL_PROVIDER", "CAPTCHA_PROVIDER"]);
+    }
+
+    #[test]
+    fn indexes_are_compared_by_keys_uniqueness_partial_filters_and_text_weights() {
+        use mongodb::options::IndexOptions;
+        let model = |name: &str, keys: Document, unique: Option<bool>, weights: Option<Document>| IndexModel::builder()
+            .keys(keys)
+            .options(IndexOptions::builder().name(name.to_string()).unique(unique).weights(weights).build())
+            .build();
+        let by_date = IndexDefinition { collection: "visits", name: "visits_date", keys: doc! { "date": 1, "status": 1 }, unique: false, partial: None };
+        let by_code = IndexDefinition { collection: "visits", name: "visits_code", keys: doc! { "code": 1 }, unique: true, partial: None };
+        let by_text = IndexDefinition { collection: "visits", name: "visits_text", keys: doc! { "name": "text", "code": "text" }, unique: false, partial: None };
+        let by_doctor = IndexDefinition { collection: "visits", name: "visits_doctor", keys: doc! { "doctor_id": 1 }, unique: false, partial: None };
+        let open = IndexDefinition {
+            collection: "visits", name: "visits_open", keys: doc! { "patient_id": 1 }, unique: true, partial: Some(doc! { "status": "open" }),
+        };
+        let existing = [
+            model("_id_", doc! { "_id": 1 }, None, None),
+            model("visits_date", doc! { "date": 1.0, "status": 1 }, None, None),
+            model("visits_code", doc! { "code": 1 }, Some(false), None),
+            model("visits_text", doc! { "_fts": "text", "_ftsx": 1 }, None, Some(doc! { "code": 1, "name": 1 })),
+            model("legacy_status", doc! { "status": -1 }, None, None),
+            model("visits_open", doc! { "patient_id": 1 }, Some(true), None),
+        ];
+
+        let states: Vec<(String, IndexState)> = compare_indexes(&[&by_date, &by_code, &by_text, &by_doctor, &open], &existing)
+            .into_iter()
+            .map(|status| (status.name, status.state))
+            .collect();
+        assert_eq!(states, vec![
+            ("visits_date".to_string(), IndexState::Present),
+            ("visits_code".to_string(), IndexState::Differs),
+            ("visits_text".to_string(), IndexState::Present),
+            ("visits_doctor".to_string(), IndexState::Missing),
+            ("visits_open".to_string(), IndexState::Differs),
+            ("legacy_status".to_string(), IndexState::Unmanaged),
+        ]);
+    }
+}
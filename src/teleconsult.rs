@@ -0,0 +1,200 @@
+//! Meeting providers for teleconsultation sessions.
+//!
+//! `TELECONSULT_PROVIDER` selects the provider: `jitsi` (default) builds rooms on
+//! `JITSI_BASE_URL` and, when `JITSI_APP_ID`/`JITSI_APP_SECRET` are set, signs a room JWT
+//! valid for `TELECONSULT_TOKEN_TTL_MINUTES`; `zoom` is a stub generating Zoom-style links
+//! until an API integration exists.
+
+use std::env;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use rand::Rng;
+use serde::Serialize;
+
+pub const DEFAULT_JITSI_BASE_URL: &str = "https://meet.jit.si";
+pub const DEFAULT_TOKEN_TTL_MINUTES: i64 = 120;
+
+/// A meeting created by a provider for one appointment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meeting {
+    pub room: String,
+    pub url: String,
+    pub token: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+pub trait TeleconsultProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn create_meeting(&self, appointment_id: &str, now: DateTime<Utc>) -> Result<Meeting, String>;
+}
+
+#[derive(Debug, Clone)]
+pub struct TeleconsultConfig {
+    pub provider: String,
+    pub jitsi_base_url: String,
+    pub jitsi_app_id: Option<String>,
+    pub jitsi_app_secret: Option<String>,
+    pub token_ttl_minutes: i64,
+}
+
+impl Default for TeleconsultConfig {
+    fn default() -> Self {
+        Self {
+            provider: "jitsi".to_string(),
+            jitsi_base_url: DEFAULT_JITSI_BASE_URL.to_string(),
+            jitsi_app_id: None,
+            jitsi_app_secret: None,
+            token_ttl_minutes: DEFAULT_TOKEN_TTL_MINUTES,
+        }
+    }
+}
+
+impl TeleconsultConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            provider: env::var("TELECONSULT_PROVIDER").map(|p| p.trim().to_lowercase()).unwrap_or(defaults.provider),
+            jitsi_base_url: env::var("JITSI_BASE_URL").unwrap_or(defaults.jitsi_base_url),
+            jitsi_app_id: env::var("JITSI_APP_ID").ok().filter(|v| !v.is_empty()),
+            jitsi_app_secret: env::var("JITSI_APP_SECRET").ok().filter(|v| !v.is_empty()),
+            token_ttl_minutes: env::var("TELECONSULT_TOKEN_TTL_MINUTES")
+                .ok()
+                .and_then(|m| m.parse().ok())
+                .filter(|m| *m > 0)
+                .unwrap_or(defaults.token_ttl_minutes),
+        }
+    }
+
+    /// The configured provider; unknown names fall back to Jitsi.
+    pub fn provider(&self) -> Box<dyn TeleconsultProvider> {
+        match self.provider.as_str() {
+            "zoom" => Box::new(ZoomStubProvider),
+            other => {
+                if other != "jitsi" {
+                    tracing::warn!(provider = other, "Unknown TELECONSULT_PROVIDER, using jitsi");
+                }
+                Box::new(JitsiProvider::from_config(self))
+            }
+        }
+    }
+}
+
+fn random_suffix(len: usize) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijkmnpqrstuvwxyz23456789";
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| ALPHABET[rng.gen_range(0..ALPHABET.len())] as char).collect()
+}
+
+/// Unguessable room name tied to an appointment.
+pub fn room_name(appointment_id: &str) -> String {
+    format!("rme-{}-{}", appointment_id, random_suffix(10))
+}
+
+#[derive(Serialize)]
+struct JitsiClaims<'a> {
+    aud: &'a str,
+    iss: &'a str,
+    sub: &'a str,
+    room: &'a str,
+    exp: i64,
+}
+
+pub struct JitsiProvider {
+    base_url: String,
+    app: Option<(String, String)>,
+    ttl_minutes: i64,
+}
+
+impl JitsiProvider {
+    pub fn from_config(config: &TeleconsultConfig) -> Self {
+        Self {
+            base_url: config.jitsi_base_url.trim_end_matches('/').to_string(),
+            app: config.jitsi_app_id.clone().zip(config.jitsi_app_secret.clone()),
+            ttl_minutes: config.token_ttl_minutes,
+        }
+    }
+}
+
+impl TeleconsultProvider for JitsiProvider {
+    fn name(&self) -> &'static str {
+        "jitsi"
+    }
+
+    fn create_meeting(&self, appointment_id: &str, now: DateTime<Utc>) -> Result<Meeting, String> {
+        let room = room_name(appointment_id);
+        let mut url = format!("{}/{}", self.base_url, room);
+
+        let Some((app_id, secret)) = &self.app else {
+            return Ok(Meeting { room, url, token: None, expires_at: None });
+        };
+
+        let expires = now + chrono::Duration::minutes(self.ttl_minutes);
+        let domain = self.base_url.split("://").nth(1).unwrap_or(&self.base_url);
+        let claims = JitsiClaims { aud: "jitsi", iss: app_id, sub: domain, room: &room, exp: expires.timestamp() };
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes()))
+            .map_err(|e| format!("Failed to sign meeting token: {}", e))?;
+        url = format!("{}?jwt={}", url, token);
+
+        Ok(Meeting { room, url, token: Some(token), expires_at: Some(expires.to_rfc3339()) })
+    }
+}
+
+/// Produces Zoom-shaped meeting links without calling the Zoom API.
+pub struct ZoomStubProvider;
+
+impl TeleconsultProvider for ZoomStubProvider {
+    fn name(&self) -> &'static str {
+        "zoom"
+    }
+
+    fn create_meeting(&self, _appointment_id: &str, _now: DateTime<Utc>) -> Result<Meeting, String> {
+        let meeting_id: u64 = rand::thread_rng().gen_range(10_000_000_000..100_000_000_000);
+        let passcode = random_suffix(8);
+        Ok(Meeting {
+            room: meeting_id.to_string(),
+            url: format!("https://zoom.us/j/{}?pwd={}", meeting_id, passcode),
+            token: Some(passcode),
+            expires_at: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn jitsi_rooms_without_credentials_have_no_token() {
+        let provider = TeleconsultConfig::default().provider();
+        let meeting = provider.create_meeting("abc", Utc::now()).unwrap();
+        assert_eq!(provider.name(), "jitsi");
+        assert!(meeting.room.starts_with("rme-abc-"));
+        assert_eq!(meeting.url, format!("https://meet.jit.si/{}", meeting.room));
+        assert_eq!(meeting.token, None);
+    }
+
+    #[test]
+    fn jitsi_signs_room_tokens_when_configured() {
+        let config = TeleconsultConfig {
+            jitsi_base_url: "https://meet.example.org/".to_string(),
+            jitsi_app_id: Some("rme".to_string()),
+            jitsi_app_secret: Some("secret".to_string()),
+            ..TeleconsultConfig::default()
+        };
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 8, 0, 0).unwrap();
+        let meeting = JitsiProvider::from_config(&config).create_meeting("abc", now).unwrap();
+
+        let token = meeting.token.unwrap();
+        assert!(meeting.url.starts_with("https://meet.example.org/rme-abc-"));
+        assert!(meeting.url.ends_with(&format!("?jwt={}", token)));
+        assert_eq!(meeting.expires_at.as_deref(), Some("2026-03-10T10:00:00+00:00"));
+    }
+
+    #[test]
+    fn zoom_stub_builds_join_links() {
+        let config = TeleconsultConfig { provider: "zoom".to_string(), ..TeleconsultConfig::default() };
+        let meeting = config.provider().create_meeting("abc", Utc::now()).unwrap();
+        assert!(meeting.url.starts_with(&format!("https://zoom.us/j/{}?pwd=", meeting.room)));
+    }
+}
@@ -0,0 +1,288 @@
+//! Tracing setup and W3C trace context propagation.
+//!
+//! `init` installs the log output and, built with the `otel` feature and with
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` set, an OTLP/HTTP exporter for Jaeger, Tempo or a collector
+//! (`OTEL_SERVICE_NAME` names the service, default `rme-api`). Each request gets a server span
+//! whose parent is the caller's `traceparent`; `HttpClient` passes the current context on to
+//! webhooks and other integrations, and MongoDB commands and S3 calls get client spans.
+//!
+//! `db.statement` carries the shape of a command with every value replaced by `?`, so patient
+//! data never leaves for the trace backend.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use axum::{
+    extract::MatchedPath,
+    http::{HeaderMap, Request},
+};
+use mongodb::bson::{Bson, Document};
+use mongodb::event::command::{CommandEventHandler, CommandFailedEvent, CommandStartedEvent, CommandSucceededEvent};
+use tracing::{field, Span};
+use tracing_subscriber::{filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+pub const DEFAULT_SERVICE_NAME: &str = "rme-api";
+/// Longest `db.statement` exported; longer ones are cut
+const MAX_STATEMENT_LEN: usize = 2048;
+/// Command fields the driver adds to every command
+const DRIVER_FIELDS: &[&str] = &["lsid", "$clusterTime", "$db", "$readPreference", "txnNumber", "autocommit", "startTransaction"];
+
+static EXPORTING: AtomicBool = AtomicBool::new(false);
+
+/// Whether spans are exported, i.e. worth creating for every database command
+pub fn is_exporting() -> bool {
+    EXPORTING.load(Ordering::Relaxed)
+}
+
+/// Flushes the spans still buffered when dropped at shutdown
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::TracerProvider>,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                tracing::error!(error = %e, "Failed to flush traces");
+            }
+        }
+    }
+}
+
+/// Install the global subscriber; keep the guard alive for the life of the server.
+pub fn init() -> TelemetryGuard {
+    let registry = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otel")]
+    if let Some(provider) = otel::provider() {
+        use opentelemetry::trace::TracerProvider as _;
+        let tracer = provider.tracer("rme-api-rust");
+        registry.with(tracing_opentelemetry::layer().with_tracer(tracer)).init();
+        EXPORTING.store(true, Ordering::Relaxed);
+        return TelemetryGuard { provider: Some(provider) };
+    }
+
+    registry.init();
+    TelemetryGuard {
+        #[cfg(feature = "otel")]
+        provider: None,
+    }
+}
+
+/// Server span of a request, named by its route so ids and NIKs stay out of span names.
+/// Meant for `TraceLayer::make_span_with` inside the router, where the route is known.
+pub fn request_span<B>(request: &Request<B>) -> Span {
+    let route = request.extensions().get::<MatchedPath>().map_or("unmatched", MatchedPath::as_str);
+    let span = tracing::info_span!(
+        "request",
+        otel.name = %format!("{} {}", request.method(), route),
+        otel.kind = "server",
+        http.request.method = %request.method(),
+        http.route = route,
+        http.response.status_code = field::Empty,
+    );
+    set_remote_parent(&span, request.headers());
+    span
+}
+
+/// Continue the caller's trace when the request carries `traceparent`
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+    #[cfg(feature = "otel")]
+    otel::set_remote_parent(span, headers);
+}
+
+/// `traceparent` (and `tracestate`) of the current span, for outbound requests; empty
+/// unless traces are exported
+pub fn trace_headers() -> Vec<(String, String)> {
+    #[cfg(feature = "otel")]
+    if is_exporting() {
+        return otel::current_headers();
+    }
+    Vec::new()
+}
+
+/// A command with every value replaced by `?`, keeping keys, operators and array lengths
+pub fn sanitize(command: &Document) -> Document {
+    command.iter()
+        .filter(|(key, _)| !DRIVER_FIELDS.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), sanitize_value(key, value)))
+        .collect()
+}
+
+fn sanitize_value(key: &str, value: &Bson) -> Bson {
+    match value {
+        Bson::Document(document) => Bson::Document(sanitize(document)),
+        Bson::Array(items) => Bson::Array(items.iter().map(|item| sanitize_value(key, item)).collect()),
+        // The first key names the command and its value the collection, which is not data
+        Bson::String(_) if is_command_key(key) => value.clone(),
+        _ => Bson::String("?".to_string()),
+    }
+}
+
+fn is_command_key(key: &str) -> bool {
+    matches!(key, "find" | "insert" | "update" | "delete" | "aggregate" | "count" | "distinct" | "findAndModify" | "createIndexes" | "getMore")
+}
+
+fn statement(command: &Document) -> String {
+    let mut statement = sanitize(command).to_string();
+    if statement.len() > MAX_STATEMENT_LEN {
+        let cut = (0..=MAX_STATEMENT_LEN).rev().find(|i| statement.is_char_boundary(*i)).unwrap_or(0);
+        statement.truncate(cut);
+        statement.push('…');
+    }
+    statement
+}
+
+/// Client spans for MongoDB commands, opened when a command starts and closed by its reply
+#[derive(Debug, Default)]
+pub struct CommandTracer {
+    spans: Mutex<HashMap<i32, Span>>,
+}
+
+impl CommandTracer {
+    fn finish(&self, request_id: i32) -> Option<Span> {
+        self.spans.lock().ok()?.remove(&request_id)
+    }
+}
+
+impl CommandEventHandler for CommandTracer {
+    fn handle_command_started_event(&self, event: CommandStartedEvent) {
+        let collection = event.command.get_str(&event.command_name).unwrap_or_default();
+        let span = tracing::info_span!(
+            "mongodb",
+            otel.name = %format!("{} {}", event.command_name, collection),
+            otel.kind = "client",
+            otel.status_code = field::Empty,
+            db.system = "mongodb",
+            db.name = %event.db,
+            db.operation = %event.command_name,
+            db.mongodb.collection = collection,
+            db.statement = %statement(&event.command),
+            error.message = field::Empty,
+        );
+        if let Ok(mut spans) = self.spans.lock() {
+            spans.insert(event.request_id, span);
+        }
+    }
+
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        self.finish(event.request_id);
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        if let Some(span) = self.finish(event.request_id) {
+            span.record("otel.status_code", "ERROR");
+            span.record("error.message", field::display(&event.failure));
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use std::collections::HashMap;
+    use axum::http::HeaderMap;
+    use opentelemetry::{global, propagation::{Extractor, Injector}, KeyValue};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::TracerProvider, Resource};
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    /// The exporter, when `OTEL_EXPORTER_OTLP_ENDPOINT` (or the traces-only variant) is set
+    pub fn provider() -> Option<TracerProvider> {
+        let configured = ["OTEL_EXPORTER_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"]
+            .iter()
+            .any(|name| std::env::var(name).is_ok_and(|v| !v.trim().is_empty()));
+        if !configured {
+            return None;
+        }
+
+        // Reads the endpoint, headers and timeout from the standard OTEL_EXPORTER_OTLP_* variables
+        let exporter = match opentelemetry_otlp::SpanExporter::builder().with_http().build() {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                // Runs before the subscriber is installed, so tracing would drop this
+                eprintln!("Trace export disabled: {}", e);
+                return None;
+            }
+        };
+        let service_name = std::env::var("OTEL_SERVICE_NAME")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| super::DEFAULT_SERVICE_NAME.to_string());
+
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        Some(TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(Resource::new([KeyValue::new("service.name", service_name)]))
+            .build())
+    }
+
+    struct HeaderExtractor<'a>(&'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    struct HeaderInjector(HashMap<String, String>);
+
+    impl Injector for HeaderInjector {
+        fn set(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+    }
+
+    pub fn set_remote_parent(span: &Span, headers: &HeaderMap) {
+        let context = global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+        span.set_parent(context);
+    }
+
+    pub fn current_headers() -> Vec<(String, String)> {
+        let context = Span::current().context();
+        let mut injector = HeaderInjector(HashMap::new());
+        global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut injector));
+        injector.0.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::doc;
+
+    #[test]
+    fn statements_keep_the_shape_without_values() {
+        let command = doc! {
+            "find": "medical_records",
+            "filter": { "nik": "3201010101010001", "age": { "$gte": 40 } },
+            "limit": 1,
+            "lsid": { "id": "session" },
+            "$db": "rme",
+        };
+        assert_eq!(sanitize(&command), doc! {
+            "find": "medical_records",
+            "filter": { "nik": "?", "age": { "$gte": "?" } },
+            "limit": "?",
+        });
+
+        let insert = doc! { "insert": "notes", "documents": [{ "text": "Hipertensi" }, { "text": "Kontrol" }] };
+        assert_eq!(sanitize(&insert), doc! { "insert": "notes", "documents": [{ "text": "?" }, { "text": "?" }] });
+    }
+
+    #[test]
+    fn long_statements_are_cut() {
+        let command = doc! { "insert": "notes", "documents": vec![Bson::Document(doc! { "text": "x" }); 500] };
+        let statement = statement(&command);
+        assert!(statement.len() <= MAX_STATEMENT_LEN + '…'.len_utf8());
+        assert!(statement.ends_with('…'));
+    }
+}
@@ -0,0 +1,242 @@
+//! Parsing of LOINC / SNOMED CT subset exports for `POST /codes/import`.
+//!
+//! CSV (comma or tab separated, e.g. `Loinc.csv` or an RF2 description file) and JSON
+//! (an array of objects using the same column names) are both accepted. Columns are
+//! matched case-insensitively.
+
+use serde_json::Value;
+
+pub const LOINC_SYSTEM: &str = "http://loinc.org";
+pub const SNOMED_SYSTEM: &str = "http://snomed.info/sct";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terminology {
+    Loinc,
+    Snomed,
+}
+
+impl Terminology {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "loinc" | LOINC_SYSTEM => Some(Self::Loinc),
+            "snomed" | "snomed-ct" | "sct" | SNOMED_SYSTEM => Some(Self::Snomed),
+            _ => None,
+        }
+    }
+
+    pub fn system(&self) -> &'static str {
+        match self {
+            Self::Loinc => LOINC_SYSTEM,
+            Self::Snomed => SNOMED_SYSTEM,
+        }
+    }
+
+    fn code_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::Loinc => &["loinc_num", "loinc", "code"],
+            Self::Snomed => &["conceptid", "concept_id", "id", "code"],
+        }
+    }
+
+    fn display_columns(&self) -> &'static [&'static str] {
+        match self {
+            Self::Loinc => &["long_common_name", "shortname", "component", "display"],
+            Self::Snomed => &["term", "fsn", "preferred_term", "display"],
+        }
+    }
+
+    /// Whether a row is retired: RF2 `active = 0`, or LOINC `STATUS = DEPRECATED`.
+    fn is_inactive(&self, field: &dyn Fn(&str) -> Option<String>) -> bool {
+        match self {
+            Self::Loinc => field("status").is_some_and(|s| s.eq_ignore_ascii_case("deprecated")),
+            Self::Snomed => field("active").is_some_and(|s| s == "0" || s.eq_ignore_ascii_case("false")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl ImportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "csv" | "tsv" | "text/csv" => Some(Self::Csv),
+            "json" | "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminologyEntry {
+    pub code: String,
+    pub display: String,
+}
+
+/// Parse an export into entries. Inactive rows are dropped; rows missing a code or display are errors.
+pub fn parse_entries(terminology: Terminology, format: ImportFormat, content: &str) -> Result<Vec<TerminologyEntry>, String> {
+    match format {
+        ImportFormat::Csv => parse_csv(terminology, content),
+        ImportFormat::Json => parse_json(terminology, content),
+    }
+}
+
+fn parse_csv(terminology: Terminology, content: &str) -> Result<Vec<TerminologyEntry>, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = if first_line.contains('\t') { '\t' } else { ',' };
+
+    let mut records = split_records(content, delimiter).into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or("Import file is empty")?
+        .into_iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+
+    let column = |names: &[&str]| names.iter().find_map(|n| header.iter().position(|h| h == n));
+    let code_idx = column(terminology.code_columns())
+        .ok_or_else(|| format!("Missing code column (expected one of: {})", terminology.code_columns().join(", ")))?;
+    let display_idx = column(terminology.display_columns())
+        .ok_or_else(|| format!("Missing display column (expected one of: {})", terminology.display_columns().join(", ")))?;
+
+    let mut entries = Vec::new();
+    for (row, record) in records.enumerate() {
+        if record.iter().all(|f| f.trim().is_empty()) {
+            continue;
+        }
+        let field = |name: &str| header.iter().position(|h| h == name).and_then(|i| record.get(i)).map(|v| v.trim().to_string());
+        if terminology.is_inactive(&field) {
+            continue;
+        }
+        let get = |idx: usize| record.get(idx).map(|v| v.trim().to_string()).unwrap_or_default();
+        // Row numbers are 1-based and count the header line
+        entries.push(build_entry(get(code_idx), get(display_idx), row + 2)?);
+    }
+
+    Ok(entries)
+}
+
+fn parse_json(terminology: Terminology, content: &str) -> Result<Vec<TerminologyEntry>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let items = value.as_array().ok_or("JSON import must be an array of objects")?;
+
+    let mut entries = Vec::new();
+    for (idx, item) in items.iter().enumerate() {
+        let object = item.as_object().ok_or_else(|| format!("Item {} is not an object", idx + 1))?;
+        let field = |name: &str| {
+            object.iter()
+                .find(|(k, _)| k.to_lowercase() == name)
+                .and_then(|(_, v)| match v {
+                    Value::String(s) => Some(s.trim().to_string()),
+                    Value::Number(n) => Some(n.to_string()),
+                    Value::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+                    _ => None,
+                })
+        };
+        if terminology.is_inactive(&field) {
+            continue;
+        }
+        let first = |names: &[&str]| names.iter().find_map(|n| field(n)).unwrap_or_default();
+        entries.push(build_entry(first(terminology.code_columns()), first(terminology.display_columns()), idx + 1)?);
+    }
+
+    Ok(entries)
+}
+
+fn build_entry(code: String, display: String, row: usize) -> Result<TerminologyEntry, String> {
+    if code.is_empty() {
+        return Err(format!("Row {}: code is empty", row));
+    }
+    if display.is_empty() {
+        return Err(format!("Row {}: display is empty", row));
+    }
+    Ok(TerminologyEntry { code, display })
+}
+
+/// Split delimited text into records, honouring double-quoted fields (with `""` escapes and embedded newlines).
+fn split_records(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_loinc_csv_with_quotes() {
+        let csv = "\"LOINC_NUM\",\"COMPONENT\",\"STATUS\",\"LONG_COMMON_NAME\"\n\
+                   \"2345-7\",\"Glucose\",\"ACTIVE\",\"Glucose [Mass/volume] in Serum or Plasma\"\n\
+                   \"1234-5\",\"Old\",\"DEPRECATED\",\"Old test\"\n\
+                   \"718-7\",\"Hemoglobin\",\"ACTIVE\",\"Hemoglobin, \"\"total\"\" [Mass/volume] in Blood\"\n";
+        let entries = parse_entries(Terminology::Loinc, ImportFormat::Csv, csv).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].code, "2345-7");
+        assert_eq!(entries[0].display, "Glucose [Mass/volume] in Serum or Plasma");
+        assert_eq!(entries[1].display, "Hemoglobin, \"total\" [Mass/volume] in Blood");
+    }
+
+    #[test]
+    fn parses_snomed_rf2_descriptions() {
+        let tsv = "id\teffectiveTime\tactive\tmoduleId\tconceptId\tlanguageCode\ttypeId\tterm\n\
+                   1\t20240101\t1\t900\t38341003\ten\t900\tHypertensive disorder\n\
+                   2\t20240101\t0\t900\t22298006\ten\t900\tRetired term\n";
+        let entries = parse_entries(Terminology::Snomed, ImportFormat::Csv, tsv).unwrap();
+
+        assert_eq!(entries, vec![TerminologyEntry { code: "38341003".into(), display: "Hypertensive disorder".into() }]);
+    }
+
+    #[test]
+    fn parses_json_array() {
+        let json = r#"[{"conceptId": 73211009, "term": "Diabetes mellitus"}, {"conceptId": "1", "term": "x", "active": false}]"#;
+        let entries = parse_entries(Terminology::Snomed, ImportFormat::Json, json).unwrap();
+
+        assert_eq!(entries, vec![TerminologyEntry { code: "73211009".into(), display: "Diabetes mellitus".into() }]);
+    }
+
+    #[test]
+    fn reports_missing_columns_and_values() {
+        assert!(parse_entries(Terminology::Loinc, ImportFormat::Csv, "foo,bar\n1,2\n").unwrap_err().contains("code column"));
+        let err = parse_entries(Terminology::Loinc, ImportFormat::Csv, "code,display\n1,\n").unwrap_err();
+        assert_eq!(err, "Row 2: display is empty");
+    }
+
+    #[test]
+    fn parses_terminology_names() {
+        assert_eq!(Terminology::parse("LOINC"), Some(Terminology::Loinc));
+        assert_eq!(Terminology::parse("http://snomed.info/sct"), Some(Terminology::Snomed));
+        assert_eq!(Terminology::parse("icd10"), None);
+    }
+}
@@ -0,0 +1,173 @@
+//! A patient's timeline: admissions (the encounters this API records), observations, files and
+//! appointments merged into one feed, newest first. Prescriptions are not recorded yet, so
+//! none appear.
+//!
+//! Each source is read by its own time field: `admittedAt`, `time` (Unix seconds), `createdAt`
+//! and `startsAt`. The feed is ordered by `(time, type, _id)` descending, so entries sharing a
+//! timestamp, as the observations of one vitals bundle do, have a fixed order and a page
+//! never splits them ambiguously. The cursor is the position of the last entry of a page,
+//! e.g. `1767225600000.observation.65a1…`; every source is then read from just after it and
+//! the next page is the newest `limit` of what they return.
+
+use std::cmp::Ordering;
+use mongodb::bson::{doc, oid::ObjectId, Bson, DateTime, Document};
+use crate::status::TimelineEntryType;
+
+/// Where an entry sits in the feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    /// Unix milliseconds
+    pub at: i64,
+    pub entry_type: TimelineEntryType,
+    pub id: ObjectId,
+}
+
+/// The field a source is ordered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeField {
+    Date(&'static str),
+    UnixSeconds(&'static str),
+}
+
+impl TimeField {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Date(name) | Self::UnixSeconds(name) => name,
+        }
+    }
+
+    /// The stored value of `at`, when the field can hold it exactly
+    fn exact(&self, at: i64) -> Option<Bson> {
+        match self {
+            Self::Date(_) => Some(Bson::DateTime(DateTime::from_millis(at))),
+            Self::UnixSeconds(_) if at.rem_euclid(1000) == 0 => Some(Bson::Int64(at / 1000)),
+            Self::UnixSeconds(_) => None,
+        }
+    }
+
+    /// Stored values before `at`
+    fn before(&self, at: i64) -> Document {
+        match self {
+            Self::Date(_) => doc! { "$lt": DateTime::from_millis(at) },
+            Self::UnixSeconds(_) => doc! { "$lt": (at + 999).div_euclid(1000) },
+        }
+    }
+
+    /// Stored values at or before `at`
+    fn until(&self, at: i64) -> Document {
+        match self {
+            Self::Date(_) => doc! { "$lte": DateTime::from_millis(at) },
+            Self::UnixSeconds(_) => doc! { "$lte": at.div_euclid(1000) },
+        }
+    }
+}
+
+impl Position {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid timeline cursor '{}'", raw);
+        let mut parts = raw.trim().splitn(3, '.');
+        let at = parts.next().and_then(|ms| ms.parse::<i64>().ok()).ok_or_else(invalid)?;
+        let entry_type = parts.next().and_then(TimelineEntryType::parse).ok_or_else(invalid)?;
+        let id = parts.next().and_then(|id| ObjectId::parse_str(id).ok()).ok_or_else(invalid)?;
+        Ok(Self { at, entry_type, id })
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}.{}.{}", self.at, self.entry_type, self.id.to_hex())
+    }
+
+    /// Newest first
+    fn cmp_desc(&self, other: &Self) -> Ordering {
+        other.at.cmp(&self.at)
+            .then_with(|| other.entry_type.as_str().cmp(self.entry_type.as_str()))
+            .then_with(|| other.id.cmp(&self.id))
+    }
+
+    /// Entries of a source of `entry_type`, ordered by `field`, that come after this position
+    pub fn after(&self, entry_type: &TimelineEntryType, field: TimeField) -> Document {
+        let name = field.name();
+        match entry_type.as_str().cmp(self.entry_type.as_str()) {
+            Ordering::Less => doc! { name: field.until(self.at) },
+            Ordering::Greater => doc! { name: field.before(self.at) },
+            Ordering::Equal => match field.exact(self.at) {
+                Some(at) => doc! { "$or": [
+                    { name: field.before(self.at) },
+                    { name: at, "_id": { "$lt": self.id } },
+                ] },
+                None => doc! { name: field.before(self.at) },
+            },
+        }
+    }
+}
+
+/// `base` narrowed to what comes after `after`, for a source of `entry_type` ordered by `field`
+pub fn source_filter(base: Document, entry_type: &TimelineEntryType, field: TimeField, after: Option<&Position>) -> Document {
+    match after {
+        Some(position) => doc! { "$and": [base, position.after(entry_type, field)] },
+        None => base,
+    }
+}
+
+/// Sort of a source, matching the feed's order
+pub fn newest_first(field: TimeField) -> Document {
+    doc! { field.name(): -1, "_id": -1 }
+}
+
+/// The newest `limit` entries of the sources, each read newest first up to `limit + 1`, and
+/// the cursor of the next page when there is one
+pub fn page<T>(mut entries: Vec<(Position, T)>, limit: usize) -> (Vec<T>, Option<String>) {
+    entries.sort_by(|(a, _), (b, _)| a.cmp_desc(b));
+    let next = match entries.len() > limit {
+        true => {
+            entries.truncate(limit);
+            entries.last().map(|(position, _)| position.encode())
+        }
+        false => None,
+    };
+    (entries.into_iter().map(|(_, entry)| entry).collect(), next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_continue_right_after_the_cursor() {
+        let id = ObjectId::parse_str("65a1b2c3d4e5f60718293a4b").unwrap();
+        let cursor = Position { at: 1_767_225_600_000, entry_type: TimelineEntryType::Observation, id };
+        assert_eq!(Position::parse(&cursor.encode()).unwrap(), cursor);
+        assert!(Position::parse("1767225600000.prescription.65a1b2c3d4e5f60718293a4b").is_err());
+
+        // At one instant types run in descending order: `observation`, `file`, `encounter`
+        assert_eq!(
+            cursor.after(&TimelineEntryType::File, TimeField::Date("createdAt")),
+            doc! { "createdAt": { "$lte": DateTime::from_millis(1_767_225_600_000) } },
+        );
+        let file = Position { entry_type: TimelineEntryType::File, ..cursor.clone() };
+        assert_eq!(
+            file.after(&TimelineEntryType::Observation, TimeField::UnixSeconds("time")),
+            doc! { "time": { "$lt": 1_767_225_600_i64 } },
+        );
+        assert_eq!(
+            cursor.after(&TimelineEntryType::Observation, TimeField::UnixSeconds("time")),
+            doc! { "$or": [{ "time": { "$lt": 1_767_225_600_i64 } }, { "time": 1_767_225_600_i64, "_id": { "$lt": id } }] },
+        );
+        let between = Position { at: 1_767_225_600_500, ..cursor.clone() };
+        assert_eq!(
+            between.after(&TimelineEntryType::Observation, TimeField::UnixSeconds("time")),
+            doc! { "time": { "$lt": 1_767_225_601_i64 } },
+        );
+
+        let at = |at: i64, entry_type: TimelineEntryType| Position { at, entry_type, id };
+        let entries = vec![
+            (at(1_000, TimelineEntryType::File), "file"),
+            (at(3_000, TimelineEntryType::Appointment), "appointment"),
+            (at(2_000, TimelineEntryType::Observation), "observation"),
+            (at(2_000, TimelineEntryType::Encounter), "encounter"),
+        ];
+        let (first, next) = page(entries.clone(), 3);
+        assert_eq!(first, vec!["appointment", "observation", "encounter"]);
+        assert_eq!(next.as_deref(), Some("2000.encounter.65a1b2c3d4e5f60718293a4b"));
+        assert_eq!(page(entries, 4).1, None);
+    }
+}
@@ -0,0 +1,248 @@
+//! Clinic time zones and the scheduling times derived from them.
+//!
+//! Appointments keep their wall-clock `date` and `time` in the clinic's zone, which slot
+//! matching and the queue use, and `startsAt`, the same instant as a UTC BSON date. A clinic's
+//! zone is its organization's `timezone`, falling back to `CLINIC_TIMEZONE` (default
+//! `Asia/Jakarta`). Zones are fixed UTC offsets: the Indonesian zone names, `UTC`, or an
+//! offset such as `+07:00`; none of them observe daylight saving time.
+//!
+//! `CLINIC_HOURS` (default `08:00-16:00`) and `APPOINTMENT_SLOT_MINUTES` (default 30) define
+//! the bookable slots of a day. Reminders are due `APPOINTMENT_REMINDER_HOURS` (default 24)
+//! before the visit; one falling in the clinic's night is moved to the evening before.
+//! Scheduled visits nobody checked in for are marked `no_show` once `NO_SHOW_GRACE_MINUTES`
+//! (default 120) have passed since their start, see `crate::no_show`.
+
+use std::env;
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
+
+pub const DEFAULT_TIMEZONE: &str = "Asia/Jakarta";
+pub const DEFAULT_SLOT_MINUTES: i64 = 30;
+pub const DEFAULT_REMINDER_HOURS: i64 = 24;
+pub const DEFAULT_NO_SHOW_GRACE_MINUTES: i64 = 120;
+/// Reminders are not sent between 21:00 and 07:00 clinic time
+const QUIET_START_HOUR: u32 = 21;
+const QUIET_END_HOUR: u32 = 7;
+const EVENING_REMINDER_HOUR: u32 = 20;
+
+const NAMED_ZONES: &[(&str, i32)] = &[
+    ("UTC", 0),
+    ("Asia/Jakarta", 7),
+    ("Asia/Pontianak", 7),
+    ("Asia/Makassar", 8),
+    ("Asia/Jayapura", 9),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClinicTimezone {
+    name: String,
+    offset: FixedOffset,
+}
+
+impl Default for ClinicTimezone {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TIMEZONE).expect("default time zone is valid")
+    }
+}
+
+impl ClinicTimezone {
+    /// A zone name from `NAMED_ZONES` or a `±HH:MM` offset.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        if let Some((name, hours)) = NAMED_ZONES.iter().find(|(name, _)| name.eq_ignore_ascii_case(raw)) {
+            let offset = FixedOffset::east_opt(hours * 3600).ok_or_else(|| format!("Invalid offset for {}", name))?;
+            return Ok(Self { name: name.to_string(), offset });
+        }
+
+        let invalid = || format!("Unknown time zone '{}'; use an Indonesian zone name, UTC or an offset like +07:00", raw);
+        let (sign, rest) = match raw.chars().next() {
+            Some('+') => (1, &raw[1..]),
+            Some('-') => (-1, &raw[1..]),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = rest.split_once(':').ok_or_else(invalid)?;
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+        let offset = FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)?;
+        Ok(Self { name: offset.to_string(), offset })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The offset as `+07:00`
+    pub fn utc_offset(&self) -> String {
+        self.offset.to_string()
+    }
+
+    /// The instant of a clinic-local `YYYY-MM-DD` date and `HH:MM[:SS]` time.
+    pub fn to_utc(&self, date: &str, time: &str) -> Result<DateTime<Utc>, String> {
+        let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").map_err(|_| format!("Date '{}' must be YYYY-MM-DD", date))?;
+        let time = parse_time(time)?;
+        self.offset
+            .from_local_datetime(&NaiveDateTime::new(date, time))
+            .single()
+            .map(|local| local.with_timezone(&Utc))
+            .ok_or_else(|| "Ambiguous local time".to_string())
+    }
+
+    pub fn local(&self, instant: DateTime<Utc>) -> DateTime<FixedOffset> {
+        instant.with_timezone(&self.offset)
+    }
+
+    /// Today's date at the clinic, as appointments store it
+    pub fn today(&self, now: DateTime<Utc>) -> String {
+        self.local(now).format("%Y-%m-%d").to_string()
+    }
+}
+
+/// `HH:MM` or `HH:MM:SS`
+pub fn parse_time(raw: &str) -> Result<NaiveTime, String> {
+    let raw = raw.trim();
+    NaiveTime::parse_from_str(raw, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(raw, "%H:%M:%S"))
+        .map_err(|_| format!("Time '{}' must be HH:MM", raw))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchedulingConfig {
+    pub default_timezone: ClinicTimezone,
+    pub day_start: NaiveTime,
+    pub day_end: NaiveTime,
+    pub slot_minutes: i64,
+    pub reminder_hours: i64,
+    pub no_show_grace_minutes: i64,
+}
+
+impl Default for SchedulingConfig {
+    fn default() -> Self {
+        Self {
+            default_timezone: ClinicTimezone::default(),
+            day_start: NaiveTime::from_hms_opt(8, 0, 0).expect("valid time"),
+            day_end: NaiveTime::from_hms_opt(16, 0, 0).expect("valid time"),
+            slot_minutes: DEFAULT_SLOT_MINUTES,
+            reminder_hours: DEFAULT_REMINDER_HOURS,
+            no_show_grace_minutes: DEFAULT_NO_SHOW_GRACE_MINUTES,
+        }
+    }
+}
+
+impl SchedulingConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let default_timezone = match env::var("CLINIC_TIMEZONE") {
+            Ok(raw) if !raw.trim().is_empty() => ClinicTimezone::parse(&raw).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Ignoring CLINIC_TIMEZONE");
+                defaults.default_timezone.clone()
+            }),
+            _ => defaults.default_timezone.clone(),
+        };
+        let (day_start, day_end) = match env::var("CLINIC_HOURS") {
+            Ok(raw) if !raw.trim().is_empty() => parse_hours(&raw).unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Ignoring CLINIC_HOURS");
+                (defaults.day_start, defaults.day_end)
+            }),
+            _ => (defaults.day_start, defaults.day_end),
+        };
+        let positive = |name: &str, default: i64| {
+            env::var(name).ok().and_then(|v| v.trim().parse().ok()).filter(|v| *v > 0).unwrap_or(default)
+        };
+
+        Self {
+            default_timezone,
+            day_start,
+            day_end,
+            slot_minutes: positive("APPOINTMENT_SLOT_MINUTES", defaults.slot_minutes),
+            reminder_hours: positive("APPOINTMENT_REMINDER_HOURS", defaults.reminder_hours),
+            no_show_grace_minutes: positive("NO_SHOW_GRACE_MINUTES", defaults.no_show_grace_minutes),
+        }
+    }
+
+    /// Slot start times of a clinic day, `HH:MM`
+    pub fn slots(&self) -> Vec<String> {
+        let mut slots = Vec::new();
+        let mut time = self.day_start;
+        while time < self.day_end {
+            slots.push(time.format("%H:%M").to_string());
+            let (next, wrapped) = time.overflowing_add_signed(Duration::minutes(self.slot_minutes));
+            if wrapped != 0 {
+                break;
+            }
+            time = next;
+        }
+        slots
+    }
+
+    /// When the reminder for a visit starting at `starts_at` is due.
+    pub fn reminder_at(&self, starts_at: DateTime<Utc>, timezone: &ClinicTimezone) -> DateTime<Utc> {
+        let due = timezone.local(starts_at - Duration::hours(self.reminder_hours));
+        let evening = |date: NaiveDate| {
+            let local = NaiveDateTime::new(date, NaiveTime::from_hms_opt(EVENING_REMINDER_HOUR, 0, 0).expect("valid time"));
+            timezone.offset.from_local_datetime(&local).single().map(|t| t.with_timezone(&Utc))
+        };
+
+        let moved = match due.hour() {
+            hour if hour >= QUIET_START_HOUR => evening(due.date_naive()),
+            hour if hour < QUIET_END_HOUR => due.date_naive().pred_opt().and_then(evening),
+            _ => None,
+        };
+        moved.unwrap_or_else(|| due.with_timezone(&Utc))
+    }
+}
+
+/// `HH:MM-HH:MM`
+fn parse_hours(raw: &str) -> Result<(NaiveTime, NaiveTime), String> {
+    let (start, end) = raw.split_once('-').ok_or_else(|| format!("Expected HH:MM-HH:MM, got '{}'", raw))?;
+    let (start, end) = (parse_time(start)?, parse_time(end)?);
+    if start >= end {
+        return Err(format!("Clinic hours '{}' end before they start", raw));
+    }
+    Ok((start, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_zones_and_offsets() {
+        assert_eq!(ClinicTimezone::parse("asia/makassar").unwrap().utc_offset(), "+08:00");
+        assert_eq!(ClinicTimezone::parse("-03:30").unwrap().name(), "-03:30");
+        assert!(ClinicTimezone::parse("Europe/Berlin").is_err());
+        assert!(ClinicTimezone::parse("+15:00").is_err());
+    }
+
+    #[test]
+    fn converts_clinic_times_to_utc() {
+        let jayapura = ClinicTimezone::parse("Asia/Jayapura").unwrap();
+        let instant = jayapura.to_utc("2026-03-10", "08:30").unwrap();
+        assert_eq!(instant, Utc.with_ymd_and_hms(2026, 3, 9, 23, 30, 0).unwrap());
+        assert_eq!(jayapura.today(instant), "2026-03-10");
+        assert!(jayapura.to_utc("10/03/2026", "08:30").is_err());
+    }
+
+    #[test]
+    fn builds_slots_within_clinic_hours() {
+        let config = SchedulingConfig { slot_minutes: 90, ..SchedulingConfig::default() };
+        assert_eq!(config.slots(), vec!["08:00", "09:30", "11:00", "12:30", "14:00", "15:30"]);
+        assert!(parse_hours("16:00-08:00").is_err());
+    }
+
+    #[test]
+    fn reminders_avoid_the_clinic_night() {
+        let config = SchedulingConfig::default();
+        let jakarta = ClinicTimezone::default();
+
+        let afternoon = jakarta.to_utc("2026-03-10", "14:00").unwrap();
+        assert_eq!(config.reminder_at(afternoon, &jakarta), jakarta.to_utc("2026-03-09", "14:00").unwrap());
+
+        let early = jakarta.to_utc("2026-03-10", "06:00").unwrap();
+        assert_eq!(config.reminder_at(early, &jakarta), jakarta.to_utc("2026-03-08", "20:00").unwrap());
+
+        let late = jakarta.to_utc("2026-03-10", "22:00").unwrap();
+        assert_eq!(config.reminder_at(late, &jakarta), jakarta.to_utc("2026-03-09", "20:00").unwrap());
+    }
+}
@@ -121,7 +121,7 @@ pub fn validate_file_upload(filename: &str, file_size: u64) -> Result<(), Valida
     // Extract file extension
     let extension = filename
         .split('.')
-        .last()
+        .next_back()
         .unwrap_or("")
         .to_lowercase();
 
@@ -143,6 +143,31 @@ pub fn validate_file_upload(filename: &str, file_size: u64) -> Result<(), Valida
     Ok(())
 }
 
+/// Content type of an upload sniffed from its leading bytes, which must be the format its
+/// extension claims. CSV has no signature, so it only has to be free of NUL bytes and of any
+/// recognised binary format.
+#[cfg(feature = "s3")]
+pub fn sniff_file_type(filename: &str, content: &[u8]) -> Result<String, ValidationError> {
+    let normalise = |extension: &str| match extension {
+        "jpeg" => "jpg".to_string(),
+        other => other.to_lowercase(),
+    };
+    let claimed = normalise(filename.rsplit_once('.').map_or("", |(_, extension)| extension));
+    let sniffed = infer::get(content);
+
+    match sniffed {
+        Some(kind) if normalise(kind.extension()) == claimed => return Ok(kind.mime_type().to_string()),
+        None if claimed == "csv" && !content.contains(&0) => return Ok("text/csv".to_string()),
+        _ => {}
+    }
+
+    let found = sniffed.map_or("unrecognised binary data".to_string(), |kind| kind.mime_type().to_string());
+    Err(ValidationError {
+        status: StatusCode::BAD_REQUEST,
+        message: format!("File content does not match its .{} extension (found {})", claimed, found),
+    })
+}
+
 use validator::{Validate, ValidationErrors};
 use crate::response::ErrorResponse;
 
@@ -160,6 +185,11 @@ pub fn validate_payload<T: Validate>(payload: &T) -> Result<(), ErrorResponse> {
     }
 }
 
+/// The same check as `validate_payload`, for one item of a batch
+pub fn validate_item<T: Validate>(payload: &T) -> Result<(), String> {
+    payload.validate().map_err(|e| format_validation_errors(&e))
+}
+
 fn format_validation_errors(errors: &ValidationErrors) -> String {
     errors
         .field_errors()
@@ -174,3 +204,27 @@ fn format_validation_errors(errors: &ValidationErrors) -> String {
         .collect::<Vec<String>>()
         .join("; ")
 }
+
+#[cfg(all(test, feature = "s3"))]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D, b'I', b'H', b'D', b'R'];
+
+    #[test]
+    fn stores_the_sniffed_type_of_matching_content() {
+        assert_eq!(sniff_file_type("scan.PNG", PNG).unwrap(), "image/png");
+        assert_eq!(sniff_file_type("lab.pdf", b"%PDF-1.7\n").unwrap(), "application/pdf");
+        assert_eq!(sniff_file_type("photo.jpeg", &[0xFF, 0xD8, 0xFF, 0xE0]).unwrap(), "image/jpeg");
+        assert_eq!(sniff_file_type("vitals.csv", b"date,systolic\n2026-01-01,120\n").unwrap(), "text/csv");
+    }
+
+    #[test]
+    fn rejects_content_of_another_type() {
+        let renamed = sniff_file_type("lab.pdf", PNG).unwrap_err();
+        assert_eq!(renamed.message, "File content does not match its .pdf extension (found image/png)");
+        assert!(sniff_file_type("scan.png", b"MZ\x90\x00").is_err());
+        assert!(sniff_file_type("vitals.csv", PNG).is_err());
+        assert!(sniff_file_type("vitals.csv", b"a,b\x00c").is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Vital signs captured together as a bundle.
+//!
+//! Each sign of a bundle becomes one observation with the LOINC coding, UCUM unit and
+//! adult base line listed in `VITAL_SIGNS`, interpreted as low, normal or high against it.
+//! Weight and height have no normal range; their base line is the plausible range, so
+//! they only come out abnormal when implausible.
+
+use chrono::{Datelike, NaiveDate};
+
+pub const LOINC_SYSTEM: &str = "http://loinc.org";
+pub const UCUM_SYSTEM: &str = "http://unitsofmeasure.org";
+pub const CATEGORY_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/observation-category";
+pub const CATEGORY_VITAL_SIGNS: (&str, &str) = ("vital-signs", "Vital Signs");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VitalSign {
+    Temperature,
+    Systolic,
+    Diastolic,
+    Pulse,
+    Spo2,
+    Weight,
+    Height,
+}
+
+/// Coding, unit and base line of one vital sign
+#[derive(Debug)]
+pub struct VitalSignDefinition {
+    pub sign: VitalSign,
+    pub code: &'static str,
+    pub display: &'static str,
+    pub unit_code: &'static str,
+    pub unit_display: &'static str,
+    pub base_line: (f64, f64),
+}
+
+pub const VITAL_SIGNS: &[VitalSignDefinition] = &[
+    VitalSignDefinition { sign: VitalSign::Temperature, code: "8310-5", display: "Body temperature", unit_code: "Cel", unit_display: "°C", base_line: (36.1, 37.5) },
+    VitalSignDefinition { sign: VitalSign::Systolic, code: "8480-6", display: "Systolic blood pressure", unit_code: "mm[Hg]", unit_display: "mmHg", base_line: (90.0, 120.0) },
+    VitalSignDefinition { sign: VitalSign::Diastolic, code: "8462-4", display: "Diastolic blood pressure", unit_code: "mm[Hg]", unit_display: "mmHg", base_line: (60.0, 80.0) },
+    VitalSignDefinition { sign: VitalSign::Pulse, code: "8867-4", display: "Heart rate", unit_code: "/min", unit_display: "beats/minute", base_line: (60.0, 100.0) },
+    VitalSignDefinition { sign: VitalSign::Spo2, code: "59408-5", display: "Oxygen saturation in Arterial blood by Pulse oximetry", unit_code: "%", unit_display: "%", base_line: (95.0, 100.0) },
+    VitalSignDefinition { sign: VitalSign::Weight, code: "29463-7", display: "Body weight", unit_code: "kg", unit_display: "kg", base_line: (0.5, 350.0) },
+    VitalSignDefinition { sign: VitalSign::Height, code: "8302-2", display: "Body height", unit_code: "cm", unit_display: "cm", base_line: (30.0, 250.0) },
+];
+
+pub fn definition(sign: VitalSign) -> &'static VitalSignDefinition {
+    VITAL_SIGNS.iter().find(|d| d.sign == sign).expect("every vital sign has a definition")
+}
+
+/// Age in whole (years, months, days) on `on`; `None` for a birth date after it.
+pub fn age_on(dob: NaiveDate, on: NaiveDate) -> Option<(i32, i32, i32)> {
+    if dob > on {
+        return None;
+    }
+    let mut months = (on.year() - dob.year()) * 12 + on.month() as i32 - dob.month() as i32;
+    if on.day() < dob.day() {
+        months -= 1;
+    }
+    let anniversary = dob.checked_add_months(chrono::Months::new(months as u32))?;
+    let days = (on - anniversary).num_days() as i32;
+    Some((months / 12, months % 12, days))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_sign_is_defined_once() {
+        for sign in [VitalSign::Temperature, VitalSign::Systolic, VitalSign::Diastolic, VitalSign::Pulse, VitalSign::Spo2, VitalSign::Weight, VitalSign::Height] {
+            assert_eq!(VITAL_SIGNS.iter().filter(|d| d.sign == sign).count(), 1);
+        }
+        assert_eq!(definition(VitalSign::Spo2).unit_code, "%");
+    }
+
+    #[test]
+    fn age_counts_whole_months_and_remaining_days() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert_eq!(age_on(date("1990-05-20"), date("2026-10-14")), Some((36, 4, 24)));
+        assert_eq!(age_on(date("2026-01-31"), date("2026-03-01")), Some((0, 1, 1)));
+        assert_eq!(age_on(date("2026-10-15"), date("2026-10-14")), None);
+    }
+}
@@ -0,0 +1,144 @@
+//! Waitlist promotion worker.
+//!
+//! Subscribes to appointment events on the domain event bus. When an appointment is
+//! cancelled or deleted, its slot is offered to the longest-waiting patient for that
+//! doctor and date: an appointment with status `held` reserves the slot for
+//! `WAITLIST_HOLD_MINUTES` (default 30) and the patient gets a notification. Holds that
+//! are not confirmed in time are released, which in turn offers the slot to the next one.
+
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use mongodb::bson::oid::ObjectId;
+use tokio::sync::broadcast::error::RecvError;
+use crate::db::AppState;
+use crate::events::{DomainEvent, EventKind};
+use crate::repository::{AppointmentRepository, NotificationRepository, WaitlistRepository};
+use crate::services::WaitlistService;
+use crate::status::AppointmentStatus;
+
+pub const DEFAULT_HOLD_MINUTES: i64 = 30;
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    pub doctor_id: String,
+    pub date: String,
+    pub time: String,
+}
+
+fn hold_minutes() -> i64 {
+    env::var("WAITLIST_HOLD_MINUTES")
+        .ok()
+        .and_then(|m| m.parse().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(DEFAULT_HOLD_MINUTES)
+}
+
+/// Deadline of a hold created at `now`, in the RFC 3339 form holds are compared in.
+pub fn hold_expiry(now: DateTime<Utc>, minutes: i64) -> String {
+    (now + chrono::Duration::minutes(minutes)).to_rfc3339()
+}
+
+/// Slot freed by a deleted appointment, from the snapshot carried by its event. Deleting an
+/// already cancelled appointment frees nothing.
+pub fn slot_from_snapshot(data: &serde_json::Value) -> Option<Slot> {
+    if data.get("status").and_then(|s| s.as_str()) == Some("cancelled") {
+        return None;
+    }
+    let field = |name: &str| data.get(name).and_then(|v| v.as_str()).map(str::to_string);
+    Some(Slot { doctor_id: field("doctor_id")?, date: field("date")?, time: field("time")? })
+}
+
+fn build_service(state: &AppState) -> WaitlistService {
+    WaitlistService::new(
+        WaitlistRepository::new(state.db.clone()),
+        AppointmentRepository::new(state.db.clone()),
+        NotificationRepository::new(state.db.clone()),
+        state.config.scheduling.default_timezone.clone(),
+    )
+}
+
+async fn released_slot(state: &AppState, event: &DomainEvent) -> Result<Option<Slot>, String> {
+    match event.kind {
+        EventKind::Deleted => Ok(event.data.as_ref().and_then(slot_from_snapshot)),
+        EventKind::Updated => {
+            let oid = ObjectId::parse_str(&event.id).map_err(|e| e.to_string())?;
+            let appointment = AppointmentRepository::new(state.db.clone()).find_by_id(oid).await?;
+            Ok(appointment
+                .filter(|a| a.status == AppointmentStatus::Cancelled)
+                .map(|a| Slot { doctor_id: a.doctor_id.to_hex(), date: a.date, time: a.time }))
+        }
+        EventKind::Created => Ok(None),
+    }
+}
+
+async fn handle_event(state: &AppState, event: &DomainEvent, minutes: i64) -> Result<(), String> {
+    if event.collection != "appointments" {
+        return Ok(());
+    }
+    let Some(slot) = released_slot(state, event).await? else { return Ok(()) };
+
+    let expires = hold_expiry(Utc::now(), minutes);
+    if let Some(entry) = build_service(state).promote(&slot.doctor_id, &slot.date, &slot.time, &expires).await? {
+        tracing::info!(date = %slot.date, time = %slot.time, doctor_id = %slot.doctor_id, patient_id = %entry.patient_id, "Offered a freed slot from the waitlist");
+        state.events.publish(DomainEvent::updated("waitlist", &entry.id.map(|id| id.to_hex()).unwrap_or_default()));
+    }
+    Ok(())
+}
+
+/// Spawn the background task promoting waitlisted patients and expiring stale holds.
+pub fn spawn_worker(state: Arc<AppState>) {
+    let minutes = hold_minutes();
+    let mut events = state.events.subscribe();
+
+    tokio::spawn(async move {
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                received = events.recv() => match received {
+                    Ok(event) => {
+                        if let Err(e) = handle_event(&state, &event, minutes).await {
+                            tracing::error!(collection = %event.collection, id = %event.id, error = %e, "Waitlist promotion failed");
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => tracing::warn!(skipped, "Waitlist worker lagged, events skipped"),
+                    Err(RecvError::Closed) => break,
+                },
+                _ = sweep.tick() => {
+                    state.scheduler.run("waitlist_holds", async {
+                        let released = build_service(&state).expire_holds(&Utc::now().to_rfc3339()).await?;
+                        // Each released hold is a cancelled appointment, promoted like any other
+                        released.iter().for_each(|id| state.events.publish(DomainEvent::updated("appointments", id)));
+                        Ok(())
+                    }).await;
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn snapshots_of_active_appointments_free_their_slot() {
+        let data = serde_json::json!({ "doctor_id": "d", "date": "2026-03-10", "time": "09:00", "status": "scheduled" });
+        assert_eq!(slot_from_snapshot(&data), Some(Slot { doctor_id: "d".into(), date: "2026-03-10".into(), time: "09:00".into() }));
+
+        let cancelled = serde_json::json!({ "doctor_id": "d", "date": "2026-03-10", "time": "09:00", "status": "cancelled" });
+        assert_eq!(slot_from_snapshot(&cancelled), None);
+        assert_eq!(slot_from_snapshot(&serde_json::json!({ "doctor_id": "d" })), None);
+    }
+
+    #[test]
+    fn hold_expiry_compares_as_a_string() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 9, 45, 0).unwrap();
+        let expiry = hold_expiry(now, 30);
+        assert_eq!(expiry, "2026-03-10T10:15:00+00:00");
+        assert!(now.to_rfc3339() < expiry);
+    }
+}
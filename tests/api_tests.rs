@@ -36,3 +36,212 @@ async fn get_medical_records_returns_ok() {
     let resp = app.oneshot(req).await.expect("request failed");
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
+
+#[tokio::test]
+async fn medicine_and_service_crud_routes_require_auth() {
+    dotenvy::dotenv().ok();
+    let state = rme_api_rust::db::init_db().await.expect("db init");
+    let app = rme_api_rust::routes::create_router(state);
+
+    // Every CRUD route is registered and served by the authenticated, validating handlers.
+    let id = "65a1b2c3d4e5f60718293a4b";
+    for resource in ["/medicines", "/services"] {
+        let item = format!("{}/{}", resource, id);
+        let requests = [
+            ("GET", resource.to_string()),
+            ("POST", resource.to_string()),
+            ("GET", item.clone()),
+            ("PUT", item.clone()),
+            ("DELETE", item),
+        ];
+        for (method, uri) in requests {
+            let req = Request::builder()
+                .method(method)
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+
+            let resp = app.clone().oneshot(req).await.expect("request failed");
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{} {}", method, uri);
+        }
+    }
+}
+
+#[cfg(feature = "billing")]
+mod gateway_callback {
+    use axum::http::{HeaderMap, StatusCode};
+    use futures_util::future::BoxFuture;
+    use mongodb::bson::{oid::ObjectId, DateTime};
+    use rme_api_rust::models::GatewayTransaction;
+    use rme_api_rust::payment_gateway::{CallbackError, GatewayEvent, LinkRequest, PaymentGateway, PaymentLink};
+    use rme_api_rust::repository::{CashierShiftRepository, GatewayTransactionRepository, InvoiceRepository, OutboxRepository, PaymentRepository};
+    use rme_api_rust::services::{GatewayService, PaymentService};
+    use rme_api_rust::status::GatewayStatus;
+
+    /// Reports every callback as paying `order_id`
+    struct PaidGateway {
+        order_id: String,
+    }
+
+    impl PaymentGateway for PaidGateway {
+        fn name(&self) -> &'static str {
+            "test"
+        }
+
+        fn create_link<'a>(&'a self, _request: &'a LinkRequest) -> BoxFuture<'a, Result<PaymentLink, String>> {
+            Box::pin(async { Err("not used".to_string()) })
+        }
+
+        fn verify_callback(&self, _headers: &HeaderMap, _body: &[u8]) -> Result<GatewayEvent, CallbackError> {
+            Ok(GatewayEvent {
+                order_id: self.order_id.clone(),
+                status: GatewayStatus::Paid,
+                amount: 150_000.0,
+                payment_type: Some("qris".to_string()),
+                provider_reference: Some("ref-1".to_string()),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn a_payment_that_cannot_be_recorded_leaves_the_link_pending() {
+        dotenvy::dotenv().ok();
+        let state = rme_api_rust::db::init_db().await.expect("db init");
+        let db = state.db.clone();
+
+        // The invoice does not exist, so recording the payment fails after settling
+        let id = ObjectId::new();
+        let transactions = GatewayTransactionRepository::new(db.clone());
+        transactions.insert(GatewayTransaction {
+            id: Some(id),
+            invoice_id: ObjectId::new().to_hex(),
+            provider: "test".to_string(),
+            amount: 150_000.0,
+            url: "https://pay.example/1".to_string(),
+            provider_reference: None,
+            status: GatewayStatus::Pending,
+            expires_at: None,
+            payment_id: None,
+            created_at: DateTime::now(),
+            updated_at: None,
+        }).await.expect("insert transaction");
+
+        let service = || GatewayService::new(
+            Some(Box::new(PaidGateway { order_id: id.to_hex() })),
+            GatewayTransactionRepository::new(db.clone()),
+            PaymentService::new(
+                InvoiceRepository::new(db.clone()),
+                PaymentRepository::new(db.clone()),
+                CashierShiftRepository::new(db.clone()),
+                OutboxRepository::new(db.clone()),
+                state.config.scheduling.default_timezone.clone(),
+            ),
+        );
+        for _ in 0..2 {
+            let (status, _) = service().handle_callback(&HeaderMap::new(), b"{}").await.expect_err("payment must fail");
+            assert_eq!(status, StatusCode::NOT_FOUND);
+
+            let stored = transactions.find_by_id(id).await.expect("find").expect("transaction");
+            assert_eq!(stored.status, GatewayStatus::Pending);
+            assert_eq!(stored.payment_id, None);
+        }
+    }
+}
+
+mod otp_verify {
+    use axum::http::StatusCode;
+    use mongodb::bson::DateTime;
+    use rme_api_rust::models::OtpCode;
+    use rme_api_rust::otp::OtpConfig;
+    use rme_api_rust::repository::OtpRepository;
+    use rme_api_rust::services::OtpService;
+
+    #[tokio::test]
+    async fn concurrent_guesses_cannot_exceed_the_attempt_limit() {
+        dotenvy::dotenv().ok();
+        let state = rme_api_rust::db::init_db().await.expect("db init");
+        let subject = mongodb::bson::oid::ObjectId::new().to_hex();
+        let config = OtpConfig { max_attempts: 3, ..OtpConfig::default() };
+
+        OtpRepository::new(state.db.clone()).create(OtpCode {
+            id: None,
+            purpose: "test".to_string(),
+            subject: subject.clone(),
+            code_hash: bcrypt::hash("123456", 4).expect("hash"),
+            attempts: 0,
+            expires_at: (chrono::Utc::now() + chrono::Duration::minutes(5)).to_rfc3339(),
+            consumed_at: None,
+            created_at: DateTime::now(),
+        }).await.expect("create code");
+
+        let service = OtpService::new(OtpRepository::new(state.db.clone()), &config);
+        let guesses = (0..10).map(|n| service.verify("test", &subject, if n % 2 == 0 { "000000" } else { "999999" }));
+        let results = futures_util::future::join_all(guesses).await;
+
+        let compared = results.iter().filter(|r| matches!(r, Err((StatusCode::UNAUTHORIZED, _)))).count();
+        let refused = results.iter().filter(|r| matches!(r, Err((StatusCode::TOO_MANY_REQUESTS, _)))).count();
+        assert_eq!((compared, refused), (3, 7));
+
+        // The right code no longer helps once the attempts are used up
+        let (status, _) = service.verify("test", &subject, "123456").await.expect_err("attempts used up");
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    }
+}
+
+mod patient_merge {
+    use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+    use rme_api_rust::delete_policy::DeleteGuard;
+    use rme_api_rust::models::Allergy;
+    use rme_api_rust::refs::Ref;
+    use rme_api_rust::repository::{AllergyRepository, AuditLogRepository, MedicalRecordRepository, ObservationRepository};
+    use rme_api_rust::services::{AuditService, PatientReferences, PatientService};
+    use rme_api_rust::status::AllergySeverity;
+
+    #[tokio::test]
+    async fn a_duplicates_allergies_move_to_the_primary() {
+        dotenvy::dotenv().ok();
+        let state = rme_api_rust::db::init_db().await.expect("db init");
+        let db = state.db.clone();
+
+        let (primary, duplicate) = (ObjectId::new(), ObjectId::new());
+        let records = db.collection::<Document>("medical_records");
+        for (id, nik) in [(primary, "3201010101900091"), (duplicate, "3201010101900092")] {
+            records.insert_one(doc! {
+                "_id": id, "nrme": format!("RM-{}", id.to_hex()), "nik": nik, "name": "Sari Dewi", "dob": "1990-04-12",
+                "gender": "female", "hp": "+6281311110001", "email": "", "lastVisitDate": "2026-01-01",
+            }, None).await.expect("insert record");
+        }
+
+        let allergies = AllergyRepository::new(db.clone());
+        allergies.create(Allergy {
+            id: None,
+            patient_id: Ref::new(duplicate),
+            substance_code: "372687004".to_string(),
+            substance_display: "Amoxicillin".to_string(),
+            severity: AllergySeverity::Severe,
+            reaction: Some("anaphylaxis".to_string()),
+            recorded_by: "test".to_string(),
+            created_at: DateTime::now(),
+            updated_at: None,
+        }).await.expect("create allergy");
+
+        let service = PatientService::new(
+            MedicalRecordRepository::new(db.clone()),
+            ObservationRepository::new(db.clone()),
+            PatientReferences::new(db.clone()),
+            DeleteGuard::from_state(&state),
+            AuditService::new(AuditLogRepository::new(db.clone())),
+        );
+        let merged = service.merge(primary, vec![duplicate.to_hex()], "test").await.expect("merge");
+        assert_eq!(merged.merged_ids, vec![duplicate.to_hex()]);
+
+        let moved = allergies.find_by_patient(&primary.to_hex()).await.expect("find allergies");
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].substance_display, "Amoxicillin");
+        assert!(allergies.find_by_patient(&duplicate.to_hex()).await.expect("find allergies").is_empty());
+        assert!(records.find_one(doc! { "_id": duplicate }, None).await.expect("find record").is_none());
+
+        records.delete_one(doc! { "_id": primary }, None).await.ok();
+        db.collection::<Document>("allergies").delete_many(doc! { "patientId": primary.to_hex() }, None).await.ok();
+    }
+}
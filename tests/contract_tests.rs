@@ -0,0 +1,425 @@
+//! Contract tests against `/openapi.json`.
+//!
+//! Every documented operation is called through the router with requests derived from the
+//! spec: path parameters valid for their schema (an ObjectId for ids) and invalid ones; no
+//! token, the token of a user holding no roles, and the token of a user whose role is granted
+//! `*:*`; and, where the operation takes a body, one generated to satisfy its `requestBody`
+//! schema, ones that break it (a required field missing, every field mistyped) and malformed
+//! JSON. Each response must have a documented status, never a 5xx, and a JSON body that
+//! conforms to the schema declared for it, 2xx answers included, so handlers and docs cannot
+//! drift apart unnoticed. All failures are reported together.
+//!
+//! Like `api_tests.rs` these need MongoDB; point `MONGODB_URI` at a disposable database, as
+//! the permitted caller creates, updates and deletes through every documented endpoint.
+
+use std::time::Duration;
+use axum::{body::Body, http::{header, Method, Request, StatusCode}, Router};
+use mongodb::bson::{doc, oid::ObjectId, DateTime, Document};
+use mongodb::Database;
+use serde_json::{json, Map, Value};
+use tower::util::ServiceExt;
+use rme_api_rust::models::{OrganizationEmbed, RoleCategory, RoleEmbed, RolePermission, User, UserBirth, UserContact, UserEmbed, UserName, UserRole};
+use rme_api_rust::repository::{RolePermissionRepository, UserRoleRepository};
+use rme_api_rust::services::AuthService;
+
+const VALID_ID: &str = "65a1b2c3d4e5f60718293a4b";
+/// Role granted every permission for the permitted caller
+const ROLE: &str = "contract-test";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Follows a local `$ref` such as `#/components/schemas/ErrorResponse`
+fn resolve<'a>(spec: &'a Value, schema: &'a Value) -> &'a Value {
+    match schema["$ref"].as_str().and_then(|r| r.strip_prefix('#')) {
+        Some(pointer) => spec.pointer(pointer).map(|target| resolve(spec, target)).unwrap_or(&Value::Null),
+        None => schema,
+    }
+}
+
+/// Where `value` breaks `schema`, for the subset of OpenAPI 3.0 schemas the spec uses
+fn violations(spec: &Value, schema: &Value, value: &Value, at: &str) -> Vec<String> {
+    let schema = resolve(spec, schema);
+    if let Some(options) = schema["oneOf"].as_array() {
+        return match options.iter().any(|option| violations(spec, option, value, at).is_empty()) {
+            true => Vec::new(),
+            false => vec![format!("{}: matches none of the oneOf schemas", at)],
+        };
+    }
+    if value.is_null() {
+        return match schema["nullable"].as_bool() == Some(true) || schema.get("type").is_none() {
+            true => Vec::new(),
+            false => vec![format!("{}: null is not nullable", at)],
+        };
+    }
+
+    let type_matches = match schema["type"].as_str() {
+        None => true,
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("integer") => value.is_i64() || value.is_u64(),
+        Some("number") => value.is_number(),
+        Some("boolean") => value.is_boolean(),
+        Some(_) => true,
+    };
+    if !type_matches {
+        return vec![format!("{}: expected {}, got {}", at, schema["type"], value)];
+    }
+
+    let mut found = Vec::new();
+    if let Some(allowed) = schema["enum"].as_array() {
+        if !allowed.contains(value) {
+            found.push(format!("{}: {} is not one of {:?}", at, value, allowed));
+        }
+    }
+    if let Some(object) = value.as_object() {
+        for name in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                found.push(format!("{}: missing required `{}`", at, name));
+            }
+        }
+        for (name, property) in schema["properties"].as_object().into_iter().flatten() {
+            if let Some(field) = object.get(name) {
+                found.extend(violations(spec, property, field, &format!("{}.{}", at, name)));
+            }
+        }
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in array.iter().enumerate() {
+            found.extend(violations(spec, items, item, &format!("{}[{}]", at, i)));
+        }
+    }
+    found
+}
+
+/// The response documented for `status`: the exact code, then its range such as `4XX`, then
+/// `default`
+fn documented_response(operation: &Value, status: StatusCode) -> Option<&Value> {
+    let responses = operation["responses"].as_object()?;
+    let range = format!("{}XX", status.as_u16() / 100);
+    responses.get(status.as_str())
+        .or_else(|| responses.get(&range))
+        .or_else(|| responses.get("default"))
+}
+
+/// A value of the parameter's schema, or one that breaks it; `None` when nothing breaks it
+fn parameter_value(schema: &Value, valid: bool) -> Option<String> {
+    match (schema["type"].as_str(), schema.get("pattern").is_some(), valid) {
+        (_, true, true) => Some(VALID_ID.to_string()),
+        (_, true, false) => Some("not-an-id".to_string()),
+        (Some("integer"), _, true) => Some("1".to_string()),
+        (Some("integer"), _, false) => Some("one".to_string()),
+        (_, _, true) => Some("contract-test".to_string()),
+        (_, _, false) => None,
+    }
+}
+
+/// `path` with its parameters filled in, or `None` when no parameter can be made invalid
+fn fill_path(path: &str, operation: &Value, valid: bool) -> Option<String> {
+    let mut filled = path.to_string();
+    let mut any_invalid = false;
+    for parameter in operation["parameters"].as_array().into_iter().flatten() {
+        let name = parameter["name"].as_str().unwrap_or_default();
+        let value = match parameter_value(&parameter["schema"], valid) {
+            Some(value) => {
+                any_invalid |= !valid;
+                value
+            }
+            None => parameter_value(&parameter["schema"], true)?,
+        };
+        filled = filled.replace(&format!("{{{}}}", name), &value);
+    }
+    (valid || any_invalid).then_some(filled)
+}
+
+/// A value that satisfies `schema`: its `example` when it has one, otherwise the first enum
+/// value or the smallest value of its type. Objects get their required fields and the ones
+/// that may not be null.
+fn example(spec: &Value, schema: &Value) -> Value {
+    let schema = resolve(spec, schema);
+    if let Some(first) = schema["oneOf"].as_array().and_then(|options| options.first()) {
+        return example(spec, first);
+    }
+    if let Some(example) = schema.get("example") {
+        return example.clone();
+    }
+    if let Some(first) = schema["enum"].as_array().and_then(|values| values.first()) {
+        return first.clone();
+    }
+    match schema["type"].as_str() {
+        Some("object") => {
+            let required: Vec<&str> = schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+            let fields = schema["properties"].as_object().into_iter().flatten()
+                .filter(|(name, property)| required.contains(&name.as_str()) || resolve(spec, property)["nullable"].as_bool() != Some(true))
+                .map(|(name, property)| (name.clone(), example(spec, property)))
+                .collect::<Map<String, Value>>();
+            Value::Object(fields)
+        }
+        Some("array") => {
+            let count = schema["minItems"].as_u64().unwrap_or(0) as usize;
+            Value::Array(vec![example(spec, &schema["items"]); count])
+        }
+        Some("string") => match schema["format"].as_str() {
+            Some("date") => json!("2000-01-31"),
+            Some("date-time") => json!("2000-01-31T00:00:00Z"),
+            Some("email") => json!("contract-test@example.com"),
+            _ => json!("x".repeat(schema["minLength"].as_u64().unwrap_or(1).max(1) as usize)),
+        },
+        Some("integer") => json!(schema["minimum"].as_i64().unwrap_or(1)),
+        Some("number") => json!(schema["minimum"].as_f64().unwrap_or(1.0)),
+        Some("boolean") => json!(true),
+        _ => json!({}),
+    }
+}
+
+/// A value of another type than `schema` allows
+fn mistyped(spec: &Value, schema: &Value) -> Value {
+    match resolve(spec, schema)["type"].as_str() {
+        Some("string") => json!(12345),
+        _ => json!("text"),
+    }
+}
+
+/// Bodies that break an object schema: one without its first required field, and one with
+/// every field of the valid body mistyped
+fn invalid_bodies(spec: &Value, schema: &Value) -> Vec<Value> {
+    let schema = resolve(spec, schema);
+    let Value::Object(valid) = example(spec, schema) else {
+        return vec![mistyped(spec, schema)];
+    };
+    let mut bodies = Vec::new();
+    if let Some(first) = schema["required"].as_array().and_then(|required| required.first()).and_then(Value::as_str) {
+        let mut missing = valid.clone();
+        missing.remove(first);
+        bodies.push(Value::Object(missing));
+    }
+    let mistyped_fields = valid.keys().map(|name| (name.clone(), mistyped(spec, &schema["properties"][name]))).collect::<Map<String, Value>>();
+    if !mistyped_fields.is_empty() {
+        bodies.push(Value::Object(mistyped_fields));
+    }
+    bodies
+}
+
+/// Bodies to send: one generated from the `requestBody` schema, ones breaking it, and
+/// malformed JSON no handler accepts
+fn bodies(spec: &Value, method: &Method, operation: &Value) -> Vec<Option<String>> {
+    if *method != Method::POST && *method != Method::PUT && *method != Method::PATCH {
+        return vec![None];
+    }
+    let mut bodies: Vec<Option<String>> = ["{", "[]", "\"text\""].iter().map(|body| Some(body.to_string())).collect();
+    if let Some(schema) = operation.pointer("/requestBody/content/application~1json/schema") {
+        bodies.push(Some(example(spec, schema).to_string()));
+        bodies.extend(invalid_bodies(spec, schema).iter().map(|body| Some(body.to_string())));
+    }
+    bodies
+}
+
+fn access_token(id: ObjectId) -> String {
+    let user = User {
+        id: Some(id),
+        email: format!("contract-test-{}@example.com", id.to_hex()),
+        password: String::new(),
+        name: "Contract Test".to_string(),
+        phone: None,
+        refresh_token: None,
+        reset_token: None,
+        reset_token_expiry: None,
+        email_verified_at: None,
+        created_at: DateTime::now(),
+        updated_at: None,
+    };
+    AuthService::generate_access_token(&user).expect("token").0
+}
+
+/// What the permitted caller added, removed again after the run
+struct Permitted {
+    user: ObjectId,
+    grant: ObjectId,
+    assignment: ObjectId,
+}
+
+/// A user holding `ROLE`, which is granted every permission
+async fn permitted_caller(db: &Database) -> (Permitted, String) {
+    let user = ObjectId::new();
+    let now = DateTime::now();
+    db.collection::<Document>("users").insert_one(doc! {
+        "_id": user,
+        "email": format!("contract-test-{}@example.com", user.to_hex()),
+        "password": "",
+        "name": "Contract Test",
+        "createdAt": now,
+    }, None).await.expect("insert user");
+
+    let grant = RolePermissionRepository::new(db.clone()).create(RolePermission {
+        id: None,
+        role_code: ROLE.to_string(),
+        resource: "*".to_string(),
+        action: "*".to_string(),
+        created_at: now,
+    }).await.expect("grant").id.expect("grant id");
+
+    let assignment = UserRoleRepository::new(db.clone()).create(UserRole {
+        id: None,
+        role: RoleEmbed {
+            code: ROLE.to_string(),
+            system: "contract-test".to_string(),
+            display: "Contract test".to_string(),
+            category: RoleCategory { code: ROLE.to_string(), system: "contract-test".to_string(), display: "Contract test".to_string(), id: ObjectId::new().to_hex() },
+        },
+        user: UserEmbed {
+            nama: UserName { nama_depan: "Contract".to_string(), nama_belakang: "Test".to_string() },
+            nik: "0000000000000000".to_string(),
+            kontak: UserContact { email: format!("contract-test-{}@example.com", user.to_hex()), nomor_telepon: String::new() },
+            lahir: UserBirth { tempat: String::new(), tanggal: String::new() },
+            id: user.to_hex(),
+        },
+        organisasi: OrganizationEmbed { name: "Contract test".to_string(), id: ObjectId::new().to_hex() },
+        is_active: true,
+        valid_from: None,
+        valid_until: None,
+        updated_at: now,
+        created_at: now,
+    }).await.expect("assign role").id.expect("assignment id");
+
+    (Permitted { user, grant, assignment }, access_token(user))
+}
+
+async fn remove_permitted_caller(db: &Database, permitted: Permitted) {
+    db.collection::<Document>("users").delete_one(doc! { "_id": permitted.user }, None).await.expect("remove user");
+    RolePermissionRepository::new(db.clone()).delete(permitted.grant).await.expect("remove grant");
+    UserRoleRepository::new(db.clone()).delete(permitted.assignment).await.expect("remove assignment");
+}
+
+async fn fetch_spec(app: &Router) -> Value {
+    let request = Request::builder().uri("/openapi.json").body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.expect("openapi.json");
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    serde_json::from_slice(&bytes).expect("spec is JSON")
+}
+
+/// Send one request and check its response against the spec
+async fn check(app: &Router, spec: &Value, operation: &Value, method: &Method, uri: &str, (caller, token): (&str, Option<&str>), body: Option<&str>) -> Vec<String> {
+    let label = format!("{} {} (caller: {}, body: {})", method, uri, caller, body.unwrap_or("none"));
+    let mut request = Request::builder().method(method.clone()).uri(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    if body.is_some() {
+        request = request.header(header::CONTENT_TYPE, "application/json");
+    }
+    let request = request.body(body.map(|body| Body::from(body.to_string())).unwrap_or_else(Body::empty)).unwrap();
+
+    let response = match tokio::time::timeout(REQUEST_TIMEOUT, app.clone().oneshot(request)).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(e)) => return vec![format!("{}: request failed: {}", label, e)],
+        Err(_) => return vec![format!("{}: no response within {:?}", label, REQUEST_TIMEOUT)],
+    };
+    let status = response.status();
+    if status.is_server_error() {
+        return vec![format!("{}: answered {}", label, status)];
+    }
+    let Some(documented) = documented_response(operation, status) else {
+        // Successes of routes without a documented body, e.g. streams and downloads
+        return match status.is_success() {
+            true => Vec::new(),
+            false => vec![format!("{}: {} is not documented", label, status)],
+        };
+    };
+    let Some(schema) = documented.pointer("/content/application~1json/schema") else {
+        return Vec::new();
+    };
+    if status == StatusCode::NO_CONTENT {
+        return Vec::new();
+    }
+
+    let is_json = response.headers().get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return vec![format!("{}: {} is not JSON", label, status)];
+    }
+    let bytes = match tokio::time::timeout(REQUEST_TIMEOUT, axum::body::to_bytes(response.into_body(), usize::MAX)).await {
+        Ok(Ok(bytes)) => bytes,
+        _ => return vec![format!("{}: body could not be read", label)],
+    };
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(value) => violations(spec, schema, &value, "body").into_iter().map(|v| format!("{} -> {}: {}", label, status, v)).collect(),
+        Err(e) => vec![format!("{}: {} body is not JSON: {}", label, status, e)],
+    }
+}
+
+#[tokio::test]
+async fn responses_conform_to_the_openapi_document() {
+    dotenvy::dotenv().ok();
+    let state = rme_api_rust::db::init_db().await.expect("db init");
+    let db = state.db.clone();
+    let app = rme_api_rust::routes::create_router(state);
+    let spec = fetch_spec(&app).await;
+    let unprivileged = access_token(ObjectId::new());
+    let (permitted, privileged) = permitted_caller(&db).await;
+    let callers = [("anonymous", None), ("no roles", Some(unprivileged.as_str())), ("all permissions", Some(privileged.as_str()))];
+
+    let mut failures = Vec::new();
+    let mut requests = 0;
+    for (path, methods) in spec["paths"].as_object().expect("paths") {
+        for (method, operation) in methods.as_object().expect("operations") {
+            let method: Method = method.to_uppercase().parse().expect("method");
+            let uris = [fill_path(path, operation, true), fill_path(path, operation, false)];
+            for uri in uris.iter().flatten() {
+                for body in bodies(&spec, &method, operation) {
+                    for caller in callers {
+                        requests += 1;
+                        failures.extend(check(&app, &spec, operation, &method, uri, caller, body.as_deref()).await);
+                    }
+                }
+            }
+        }
+    }
+
+    remove_permitted_caller(&db, permitted).await;
+
+    assert!(requests > 0, "the spec documents operations");
+    assert!(failures.is_empty(), "{} of {} requests broke the contract:\n{}", failures.len(), requests, failures.join("\n"));
+}
+
+#[test]
+fn schemas_are_checked_through_references() {
+    let spec = serde_json::json!({ "components": { "schemas": {
+        "Error": { "type": "object", "required": ["success", "error"], "properties": {
+            "success": { "type": "boolean", "enum": [false] },
+            "error": { "type": "object", "required": ["code"], "properties": { "code": { "type": "string" } } },
+            "data": { "nullable": true }
+        } }
+    } } });
+    let schema = serde_json::json!({ "$ref": "#/components/schemas/Error" });
+
+    let valid = serde_json::json!({ "success": false, "error": { "code": "NOT_FOUND" }, "data": null });
+    assert!(violations(&spec, &schema, &valid, "body").is_empty());
+
+    let invalid = serde_json::json!({ "success": true, "error": { "code": 404 } });
+    assert_eq!(violations(&spec, &schema, &invalid, "body"), vec![
+        "body.success: true is not one of [Bool(false)]".to_string(),
+        "body.error.code: expected \"string\", got 404".to_string(),
+    ]);
+}
+
+#[test]
+fn bodies_are_generated_from_request_schemas() {
+    let spec = serde_json::json!({});
+    let schema = serde_json::json!({ "type": "object", "required": ["name", "status"], "properties": {
+        "name": { "type": "string", "minLength": 3 },
+        "status": { "type": "string", "enum": ["active", "inactive"] },
+        "dob": { "type": "string", "format": "date" },
+        "nik": { "type": "string", "pattern": "^[0-9]{16}$", "example": "3201010101900001" },
+        "note": { "type": "string", "nullable": true }
+    } });
+
+    let valid = example(&spec, &schema);
+    assert_eq!(valid, serde_json::json!({ "name": "xxx", "status": "active", "dob": "2000-01-31", "nik": "3201010101900001" }));
+    assert!(violations(&spec, &schema, &valid, "body").is_empty());
+
+    let invalid = invalid_bodies(&spec, &schema);
+    assert_eq!(invalid.len(), 2);
+    assert!(invalid.iter().all(|body| !violations(&spec, &schema, body, "body").is_empty()));
+    assert!(invalid[0].get("name").is_none());
+}
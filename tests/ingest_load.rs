@@ -0,0 +1,124 @@
+//! Load scenario for observation ingest: vitals bundles recorded concurrently for one patient,
+//! each parsed, validated and stored as seven observations in one transaction, with BMI
+//! derived afterwards, as kits and nurse stations submit them.
+//!
+//! Ignored by default. Against a disposable database (a replica set, as transactions need),
+//! run `cargo test --release --test ingest_load -- --ignored --nocapture`. `LOAD_BUNDLES`
+//! (default 2000) bundles are sent by `LOAD_CONCURRENCY` (default 32) workers. The target on a
+//! single-node replica set on the same host is 1000 observations per second with a p95 of
+//! 250 ms per bundle; override with `LOAD_TARGET_OBSERVATIONS_PER_SEC` and `LOAD_TARGET_P95_MS`
+//! for other hardware. The patient and its observations are removed afterwards.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use rme_api_rust::dto::observation::VitalsBundleRequest;
+use rme_api_rust::models::MedicalRecord;
+use rme_api_rust::refs::ReferenceChecker;
+use rme_api_rust::repository::{MedicalRecordRepository, ObservationRepository};
+use rme_api_rust::services::ObservationService;
+use rme_api_rust::status::Gender;
+use validator::Validate;
+
+const BUNDLE: &str = r#"{
+    "temperature": 36.8, "systolic": 128, "diastolic": 84, "pulse": 76, "spo2": 98,
+    "weight": 64.5, "height": 158,
+    "atm_sehat": { "code": "LOAD-KIT", "name": "Load test kit", "owner": { "code": "LOAD", "name": "Load test" } }
+}"#;
+/// Observations stored per bundle, one per sign
+const SIGNS_PER_BUNDLE: usize = 7;
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let index = ((sorted.len() as f64 * p).ceil() as usize).clamp(1, sorted.len()) - 1;
+    sorted[index]
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+#[ignore = "load test; needs a disposable MongoDB replica set"]
+async fn vitals_ingest_meets_target_throughput() {
+    dotenvy::dotenv().ok();
+    let bundles: usize = env_or("LOAD_BUNDLES", 2000);
+    let concurrency: usize = env_or("LOAD_CONCURRENCY", 32);
+    let target_rate: f64 = env_or("LOAD_TARGET_OBSERVATIONS_PER_SEC", 1000.0);
+    let target_p95 = Duration::from_millis(env_or("LOAD_TARGET_P95_MS", 250));
+
+    let state = rme_api_rust::db::init_db().await.expect("db init");
+    let db = state.db.clone();
+    let patient_id = ObjectId::new();
+    let suffix = format!("{:08}", patient_id.timestamp().timestamp_millis() % 100_000_000);
+    MedicalRecordRepository::new(db.clone())
+        .insert(MedicalRecord {
+            id: Some(patient_id),
+            nik: format!("32010199{}", suffix),
+            nrme: format!("LOAD-{}", patient_id.to_hex()),
+            name: "Load Test".to_string(),
+            dob: "1990-04-12".to_string(),
+            gender: Gender::Female,
+            hp: format!("+62812{}", suffix),
+            email: format!("load-{}@example.com", patient_id.to_hex()),
+            last_visit_date: "2026-01-01".to_string(),
+            phone_verified_at: None,
+            updated_at: None,
+            self_registration: None,
+        })
+        .await
+        .expect("create patient");
+
+    let service = Arc::new(ObservationService::new(
+        ObservationRepository::new(db.clone()),
+        MedicalRecordRepository::new(db.clone()),
+        ReferenceChecker::new(db.clone()),
+    ));
+    let next = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let started = Instant::now();
+    let workers: Vec<_> = (0..concurrency)
+        .map(|_| {
+            let (service, next) = (service.clone(), next.clone());
+            tokio::spawn(async move {
+                let mut latencies = Vec::new();
+                let mut failures = 0usize;
+                while next.fetch_add(1, std::sync::atomic::Ordering::Relaxed) < bundles {
+                    let sent = Instant::now();
+                    let bundle: VitalsBundleRequest = serde_json::from_str(BUNDLE).expect("bundle parses");
+                    bundle.validate().expect("bundle is valid");
+                    match service.record_vitals(patient_id, "load-test", bundle).await {
+                        Ok(_) => latencies.push(sent.elapsed()),
+                        Err((status, e)) => {
+                            eprintln!("Bundle failed with {}: {}", status, e);
+                            failures += 1;
+                        }
+                    }
+                }
+                (latencies, failures)
+            })
+        })
+        .collect();
+
+    let mut latencies = Vec::new();
+    let mut failures = 0;
+    for worker in workers {
+        let (worker_latencies, worker_failures) = worker.await.expect("worker panicked");
+        latencies.extend(worker_latencies);
+        failures += worker_failures;
+    }
+    let elapsed = started.elapsed();
+
+    db.collection::<Document>("observations").delete_many(doc! { "id_pasien": patient_id.to_hex() }, None).await.ok();
+    db.collection::<Document>("medical_records").delete_one(doc! { "_id": patient_id }, None).await.ok();
+
+    latencies.sort();
+    let rate = (latencies.len() * SIGNS_PER_BUNDLE) as f64 / elapsed.as_secs_f64();
+    let (p50, p95) = (percentile(&latencies, 0.50), percentile(&latencies, 0.95));
+    println!(
+        "{} bundles ({} failed) by {} workers in {:.2?}: {:.0} observations/s, p50 {:.2?}, p95 {:.2?}",
+        bundles, failures, concurrency, elapsed, rate, p50, p95,
+    );
+
+    assert_eq!(failures, 0, "every bundle is stored");
+    assert!(rate >= target_rate, "{:.0} observations/s is below the target of {:.0}", rate, target_rate);
+    assert!(p95 <= target_p95, "p95 of {:.2?} is above the target of {:.2?}", p95, target_p95);
+}